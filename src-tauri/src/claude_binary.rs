@@ -1,17 +1,23 @@
 use anyhow::Result;
 use log::{debug, error, info, warn};
+use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
 use std::cmp::Ordering;
 /// Shared module for detecting Claude Code binary installations
 /// Supports NVM installations, aliased paths, version-based selection, and bundled sidecars
 /// Cross-platform support for Windows and macOS
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use std::io::Read;
 use std::path::PathBuf;
-use std::process::Command;
+use std::process::{Command, Stdio};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
 #[cfg(target_os = "windows")]
 use std::time::{SystemTime, UNIX_EPOCH};
 use tauri::Manager;
 
+use crate::utils::config_utils::ConfigPathBuilder;
+
 /// 运行时环境信息（替换单纯的 #[cfg] 检测，支持容器/WSL/架构）
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RuntimeEnvironment {
@@ -37,8 +43,66 @@ pub struct BinarySearchSection {
     /// 额外搜索路径（目录或完整文件路径）
     #[serde(default)]
     pub search_paths: Vec<String>,
+    /// 来源优先顺序（path/nvm/volta/bun/homebrew/custom），未列出的来源排在后面；为空时按默认规则
+    #[serde(default)]
+    pub source_priority: Vec<String>,
+    /// 黑名单路径（按前缀匹配，不区分大小写），命中的候选不参与选择
+    #[serde(default)]
+    pub blocked_paths: Vec<String>,
 }
 
+/// 候选来源标签，可用于 `source_priority`
+pub const BINARY_ORIGINS: [&str; 6] = ["path", "nvm", "volta", "bun", "homebrew", "custom"];
+
+/// 检测结果缓存有效期
+const BINARY_CACHE_TTL: Duration = Duration::from_secs(10 * 60);
+
+/// 单个候选 `--version` 探测的超时时间
+const VERSION_PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// 检测到的候选二进制
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BinaryCandidate {
+    pub path: String,
+    pub version: Option<String>,
+    /// 发现方式（env:XXX / PATH / registry / common-path / user-config 等）
+    pub source: String,
+    /// 来源标签（见 BINARY_ORIGINS）
+    pub origin: String,
+}
+
+/// 某个工具的检测结果：全部候选（按选择顺序排列，首个即最终使用的二进制）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BinaryDetection {
+    pub tool: String,
+    pub candidates: Vec<BinaryCandidate>,
+    /// 检测时间（Unix 秒）
+    pub detected_at: i64,
+    /// 检测时的配置指纹（环境变量与 binaries.json 对应段），配置变化时缓存失效
+    pub fingerprint: String,
+}
+
+impl BinaryDetection {
+    pub fn selected(&self) -> Option<&BinaryCandidate> {
+        self.candidates.first()
+    }
+
+    fn is_fresh(&self, fingerprint: &str) -> bool {
+        let age = chrono::Utc::now().timestamp() - self.detected_at;
+        self.fingerprint == fingerprint
+            && (0..BINARY_CACHE_TTL.as_secs() as i64).contains(&age)
+            && self
+                .selected()
+                .is_none_or(|c| !looks_like_path(&c.path) || PathBuf::from(&c.path).exists())
+    }
+}
+
+/// 内存中的检测结果缓存：tool → 检测结果（磁盘副本在 ~/.anycode/binary-cache.json）
+static BINARY_CACHE: Lazy<Mutex<HashMap<String, BinaryDetection>>> =
+    Lazy::new(|| Mutex::new(load_disk_cache()));
+
 /// Get user home directory (cross-platform)
 fn get_home_dir() -> Result<String, String> {
     #[cfg(target_os = "windows")]
@@ -128,6 +192,99 @@ fn pick_section(cfg: &BinarySearchConfig, key: &str) -> Option<BinarySearchSecti
     }
 }
 
+fn binary_cache_path() -> Option<PathBuf> {
+    ConfigPathBuilder::from_home_subdir(".anycode")
+        .ok()
+        .map(|dir| dir.build("binary-cache.json"))
+}
+
+fn load_disk_cache() -> HashMap<String, BinaryDetection> {
+    binary_cache_path()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save_disk_cache(cache: &HashMap<String, BinaryDetection>) {
+    let Some(path) = binary_cache_path() else {
+        return;
+    };
+    if let Some(parent) = path.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+    match serde_json::to_string_pretty(cache) {
+        Ok(content) => {
+            if let Err(e) = std::fs::write(&path, content) {
+                warn!("Failed to write binary cache {:?}: {}", path, e);
+            }
+        }
+        Err(e) => warn!("Failed to serialize binary cache: {}", e),
+    }
+}
+
+/// 使检测结果缓存失效（内存与磁盘）；`tool` 为 None 时清空全部
+pub fn invalidate_binary_cache(tool: Option<&str>) {
+    let mut cache = BINARY_CACHE.lock().unwrap();
+    match tool {
+        Some(tool) => {
+            cache.remove(tool);
+        }
+        None => cache.clear(),
+    }
+    save_disk_cache(&cache);
+    info!(
+        "Binary detection cache invalidated: {}",
+        tool.unwrap_or("all")
+    );
+}
+
+/// 候选的来源标签：环境变量/用户配置为 custom，其余按（解析符号链接后的）路径判断
+fn classify_origin(path: &str, source: &str) -> &'static str {
+    if source.starts_with("env:") || source == "user-config" {
+        return "custom";
+    }
+
+    let resolved = std::fs::canonicalize(path)
+        .map(|p| p.to_string_lossy().to_string())
+        .unwrap_or_else(|_| path.to_string());
+    let [raw, resolved] = [path, resolved.as_str()].map(|p| p.to_lowercase().replace('\\', "/"));
+    let contains = |needle: &str| raw.contains(needle) || resolved.contains(needle);
+
+    if contains("/.nvm/") || contains("/nvm/") {
+        "nvm"
+    } else if contains("/.volta/") || contains("/volta/") {
+        "volta"
+    } else if contains("/.bun/") {
+        "bun"
+    } else if contains("/homebrew/") || contains("/linuxbrew/") || contains("/cellar/") {
+        "homebrew"
+    } else {
+        "path"
+    }
+}
+
+/// 路径是否命中黑名单（前缀匹配，不区分大小写，统一分隔符）
+fn is_blocked_path(path: &str, blocked: &[String]) -> bool {
+    let normalize = |p: &str| p.trim().to_lowercase().replace('\\', "/");
+    let path = normalize(path);
+    blocked
+        .iter()
+        .map(|b| normalize(b))
+        .any(|b| !b.is_empty() && path.starts_with(&b))
+}
+
+/// 来源标签在用户优先顺序中的位置（未列出的排在最后）
+fn origin_rank(origin: &str, source_priority: &[String]) -> usize {
+    source_priority
+        .iter()
+        .position(|o| o.eq_ignore_ascii_case(origin))
+        .unwrap_or(source_priority.len())
+}
+
+fn looks_like_path(path: &str) -> bool {
+    path.contains('\\') || path.contains('/')
+}
+
 /// Initialize shell environment for Unix GUI applications (macOS and Linux)
 /// This function should be called at application startup to ensure
 /// CLI tools installed via package managers, npm, nvm, etc. can be found
@@ -511,34 +668,53 @@ fn push_candidate(
         return;
     }
 
-    let path_obj = PathBuf::from(&path);
-    let looks_like_path = path.contains('\\') || path.contains('/');
-    if looks_like_path && !path_obj.exists() {
+    if looks_like_path(&path) && !PathBuf::from(&path).exists() {
         debug!("Skip non-existing candidate: {}", path);
         return;
     }
 
-    // 执行一次版本探测，失败也允许继续，只是 version 为 None
-    let version = get_binary_version_generic(&path);
-    if !looks_like_path && version.is_none() {
-        debug!(
-            "Skip candidate {} because version probe failed and no concrete path",
-            path
-        );
-        return;
-    }
-
+    // 版本在收集完成后由 probe_candidate_versions 并行探测
     list.push(PrioritizedInstallation {
         priority,
         installation: ClaudeInstallation {
             path,
-            version,
+            version: None,
             source: source.to_string(),
             installation_type: InstallationType::System,
         },
     });
 }
 
+/// 并行探测全部候选的版本（每个带超时）
+///
+/// 探测失败也保留候选（version 为 None），但不是具体路径的裸命令探测失败时丢弃
+fn probe_candidate_versions(candidates: &mut Vec<PrioritizedInstallation>) {
+    let versions: Vec<Option<String>> = std::thread::scope(|scope| {
+        let handles: Vec<_> = candidates
+            .iter()
+            .map(|c| scope.spawn(|| get_binary_version_generic(&c.installation.path)))
+            .collect();
+        handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap_or(None))
+            .collect()
+    });
+    for (candidate, version) in candidates.iter_mut().zip(versions) {
+        candidate.installation.version = version;
+    }
+
+    candidates.retain(|c| {
+        let keep = looks_like_path(&c.installation.path) || c.installation.version.is_some();
+        if !keep {
+            debug!(
+                "Skip candidate {} because version probe failed and no concrete path",
+                c.installation.path
+            );
+        }
+        keep
+    });
+}
+
 /// 组合多来源的候选路径，使用运行时环境信息
 fn collect_runtime_candidates(
     tool: &str,
@@ -549,6 +725,10 @@ fn collect_runtime_candidates(
     let mut candidates: Vec<PrioritizedInstallation> = Vec::new();
     let mut seen: HashSet<String> = HashSet::new();
     let aliases = get_tool_aliases(tool, env);
+    let blocked_paths = user_section
+        .as_ref()
+        .map(|section| section.blocked_paths.clone())
+        .unwrap_or_default();
 
     // 1. 环境变量覆盖
     if let Ok(val) = std::env::var(env_var) {
@@ -758,6 +938,20 @@ fn collect_runtime_candidates(
         }
     }
 
+    // 版本管理器/包管理器的全局目录：同一工具可能装在多处，全部列为候选，由优先顺序决定使用哪个
+    for root in package_manager_roots(env) {
+        for alias in &aliases {
+            let candidate = PathBuf::from(&root).join(alias);
+            push_candidate(
+                &mut candidates,
+                &mut seen,
+                candidate.to_string_lossy().to_string(),
+                "common-path",
+                3,
+            );
+        }
+    }
+
     // 5. 用户配置文件中的额外搜索路径（优先级最低但可覆盖奇异环境）
     if let Some(section) = user_section {
         if let Some(custom) = section.override_path {
@@ -779,9 +973,40 @@ fn collect_runtime_candidates(
         }
     }
 
+    candidates.retain(|c| {
+        let blocked = is_blocked_path(&c.installation.path, &blocked_paths);
+        if blocked {
+            info!("Skip blocked {} candidate: {}", tool, c.installation.path);
+        }
+        !blocked
+    });
+    probe_candidate_versions(&mut candidates);
     candidates
 }
 
+/// bun / nvm / Homebrew 等全局安装目录（PATH 中通常只有其中一个生效）
+fn package_manager_roots(env: &RuntimeEnvironment) -> Vec<String> {
+    let mut roots = Vec::new();
+    let Ok(home) = get_home_dir() else {
+        return roots;
+    };
+
+    roots.push(
+        PathBuf::from(&home)
+            .join(".bun")
+            .join("bin")
+            .to_string_lossy()
+            .to_string(),
+    );
+    if env.os != "windows" {
+        #[cfg(unix)]
+        roots.extend(get_nvm_paths(&home));
+        roots.push("/home/linuxbrew/.linuxbrew/bin".to_string());
+        roots.push(format!("{}/.linuxbrew/bin", home));
+    }
+    roots
+}
+
 #[cfg(target_os = "windows")]
 fn find_fnm_multishell_candidates(local_appdata: &str, aliases: &[String]) -> Vec<String> {
     let base = PathBuf::from(local_appdata).join("fnm_multishells");
@@ -855,22 +1080,92 @@ mod fnm_multishell_tests {
     }
 }
 
-/// 按优先级 -> 版本降序选择最佳安装
-fn select_best_with_priority(
-    mut installations: Vec<PrioritizedInstallation>,
-) -> Option<ClaudeInstallation> {
-    installations.sort_by(|a, b| {
-        a.priority.cmp(&b.priority).then_with(|| {
-            match (&a.installation.version, &b.installation.version) {
-                (Some(v1), Some(v2)) => compare_versions(v2, v1),
-                (Some(_), None) => Ordering::Less,
-                (None, Some(_)) => Ordering::Greater,
-                _ => Ordering::Equal,
-            }
+/// 按 用户来源优先顺序 -> 优先级 -> 版本降序 排列候选
+fn rank_installations(
+    installations: Vec<PrioritizedInstallation>,
+    source_priority: &[String],
+) -> Vec<PrioritizedInstallation> {
+    let mut ranked: Vec<(usize, PrioritizedInstallation)> = installations
+        .into_iter()
+        .map(|p| {
+            let origin = classify_origin(&p.installation.path, &p.installation.source);
+            (origin_rank(origin, source_priority), p)
         })
+        .collect();
+    ranked.sort_by(|(rank_a, a), (rank_b, b)| {
+        rank_a
+            .cmp(rank_b)
+            .then_with(|| a.priority.cmp(&b.priority))
+            .then_with(|| {
+                let (va, vb) = (&a.installation.version, &b.installation.version);
+                match (va, vb) {
+                    (Some(v1), Some(v2)) => compare_versions(v2, v1),
+                    (Some(_), None) => Ordering::Less,
+                    (None, Some(_)) => Ordering::Greater,
+                    _ => Ordering::Equal,
+                }
+            })
     });
+    ranked.into_iter().map(|(_, p)| p).collect()
+}
 
-    installations.into_iter().map(|p| p.installation).next()
+/// 按优先顺序选择最佳安装
+fn select_best_with_priority(
+    installations: Vec<PrioritizedInstallation>,
+    source_priority: &[String],
+) -> Option<ClaudeInstallation> {
+    rank_installations(installations, source_priority)
+        .into_iter()
+        .map(|p| p.installation)
+        .next()
+}
+
+/// 检测某个工具的全部候选（优先使用 10 分钟内的缓存），按选择顺序排列
+pub fn detect_binary_candidates(tool: &str, env_var: &str, config_key: &str) -> BinaryDetection {
+    let user_cfg = load_binary_search_config();
+    let user_section = pick_section(&user_cfg, config_key);
+    let fingerprint = format!(
+        "{}|{}",
+        std::env::var(env_var).unwrap_or_default(),
+        serde_json::to_string(&user_section).unwrap_or_default()
+    );
+
+    if let Some(cached) = BINARY_CACHE.lock().unwrap().get(tool) {
+        if cached.is_fresh(&fingerprint) {
+            debug!("Using cached {} detection", tool);
+            return cached.clone();
+        }
+    }
+
+    let runtime_env = detect_runtime_environment();
+    let source_priority = user_section
+        .as_ref()
+        .map(|section| section.source_priority.clone())
+        .unwrap_or_default();
+    let prioritized = rank_installations(
+        collect_runtime_candidates(tool, env_var, &runtime_env, user_section),
+        &source_priority,
+    );
+
+    let detection = BinaryDetection {
+        tool: tool.to_string(),
+        candidates: prioritized
+            .into_iter()
+            .map(|p| BinaryCandidate {
+                origin: classify_origin(&p.installation.path, &p.installation.source).to_string(),
+                path: p.installation.path,
+                version: p.installation.version,
+                source: p.installation.source,
+            })
+            .collect(),
+        detected_at: chrono::Utc::now().timestamp(),
+        fingerprint,
+    };
+
+    let mut cache = BINARY_CACHE.lock().unwrap();
+    cache.insert(tool.to_string(), detection.clone());
+    save_disk_cache(&cache);
+    detection
 }
 
 /// 通用检测入口，可供 Codex/其他二进制共享
@@ -880,11 +1175,13 @@ pub fn detect_binary_for_tool(
     config_key: &str,
 ) -> (RuntimeEnvironment, Option<ClaudeInstallation>) {
     let runtime_env = detect_runtime_environment();
-    let user_cfg = load_binary_search_config();
-    let user_section = pick_section(&user_cfg, config_key);
-
-    let prioritized = collect_runtime_candidates(tool, env_var, &runtime_env, user_section);
-    let best = select_best_with_priority(prioritized);
+    let detection = detect_binary_candidates(tool, env_var, config_key);
+    let best = detection.selected().map(|c| ClaudeInstallation {
+        path: c.path.clone(),
+        version: c.version.clone(),
+        source: c.source.clone(),
+        installation_type: InstallationType::System,
+    });
     (runtime_env, best)
 }
 
@@ -1130,6 +1427,15 @@ pub fn find_claude_binary(app_handle: &tauri::AppHandle) -> Result<String, Strin
     let runtime_env = detect_runtime_environment();
     let user_cfg = load_binary_search_config();
     let user_section = pick_section(&user_cfg, "claude");
+    let (source_priority, blocked_paths) = user_section
+        .as_ref()
+        .map(|section| {
+            (
+                section.source_priority.clone(),
+                section.blocked_paths.clone(),
+            )
+        })
+        .unwrap_or_default();
 
     // 新的运行时候选收集（支持 env/注册表/常见路径/用户路径）
     let mut prioritized =
@@ -1138,6 +1444,7 @@ pub fn find_claude_binary(app_handle: &tauri::AppHandle) -> Result<String, Strin
     // 兼容旧逻辑：补充 discover_system_installations 结果，优先级稍低
     let legacy = discover_system_installations()
         .into_iter()
+        .filter(|inst| !is_blocked_path(&inst.path, &blocked_paths))
         .map(|inst| PrioritizedInstallation {
             priority: 5,
             installation: inst,
@@ -1154,7 +1461,7 @@ pub fn find_claude_binary(app_handle: &tauri::AppHandle) -> Result<String, Strin
         prioritized.len()
     );
 
-    if let Some(best) = select_best_with_priority(prioritized) {
+    if let Some(best) = select_best_with_priority(prioritized, &source_priority) {
         info!("========================================");
         info!("✅ Selected Claude CLI: {}", best.path);
         info!(
@@ -1956,10 +2263,13 @@ fn find_macos_installations() -> Vec<ClaudeInstallation> {
     vec![]
 }
 
-/// 通用的版本获取（用于 Claude/Codex 等 CLI）
+/// 通用的版本获取（用于 Claude/Codex 等 CLI），超过 VERSION_PROBE_TIMEOUT 未退出时终止进程
 fn get_binary_version_generic(path: &str) -> Option<String> {
     let mut cmd = Command::new(path);
-    cmd.arg("--version");
+    cmd.arg("--version")
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null());
 
     #[cfg(target_os = "windows")]
     {
@@ -1967,9 +2277,28 @@ fn get_binary_version_generic(path: &str) -> Option<String> {
         cmd.creation_flags(0x08000000);
     }
 
-    match cmd.output() {
-        Ok(output) if output.status.success() => extract_version_from_output(&output.stdout),
-        _ => None,
+    let mut child = cmd.spawn().ok()?;
+    let deadline = Instant::now() + VERSION_PROBE_TIMEOUT;
+    loop {
+        match child.try_wait() {
+            Ok(Some(status)) => {
+                if !status.success() {
+                    return None;
+                }
+                let mut stdout = Vec::new();
+                child.stdout.take()?.read_to_end(&mut stdout).ok()?;
+                return extract_version_from_output(&stdout);
+            }
+            Ok(None) if Instant::now() < deadline => {
+                std::thread::sleep(Duration::from_millis(20));
+            }
+            _ => {
+                warn!("Version probe timed out for {}", path);
+                let _ = child.kill();
+                let _ = child.wait();
+                return None;
+            }
+        }
     }
 }
 
@@ -2343,3 +2672,84 @@ pub fn create_command_with_env(program: &str) -> Command {
 
     cmd
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn installation(
+        path: &str,
+        source: &str,
+        priority: u8,
+        version: &str,
+    ) -> PrioritizedInstallation {
+        PrioritizedInstallation {
+            priority,
+            installation: ClaudeInstallation {
+                path: path.to_string(),
+                version: Some(version.to_string()),
+                source: source.to_string(),
+                installation_type: InstallationType::System,
+            },
+        }
+    }
+
+    #[test]
+    fn classifies_origin_by_path_and_source() {
+        assert_eq!(
+            classify_origin("/home/u/.nvm/versions/node/v20/bin/claude", "PATH"),
+            "nvm"
+        );
+        assert_eq!(
+            classify_origin("/home/u/.volta/bin/claude", "common-path"),
+            "volta"
+        );
+        assert_eq!(
+            classify_origin("/home/u/.bun/bin/claude", "common-path"),
+            "bun"
+        );
+        assert_eq!(
+            classify_origin("/opt/homebrew/bin/claude", "PATH"),
+            "homebrew"
+        );
+        assert_eq!(classify_origin("/usr/local/bin/claude", "PATH"), "path");
+        assert_eq!(
+            classify_origin("/usr/local/bin/claude", "env:CLAUDE_PATH"),
+            "custom"
+        );
+        assert_eq!(
+            classify_origin("/home/u/.bun/bin/claude", "user-config"),
+            "custom"
+        );
+    }
+
+    #[test]
+    fn blocked_paths_match_prefix_case_insensitively() {
+        let blocked = vec!["C:\\Users\\u\\AppData\\Roaming\\npm".to_string()];
+        assert!(is_blocked_path(
+            "c:/users/u/appdata/roaming/npm/claude.cmd",
+            &blocked
+        ));
+        assert!(!is_blocked_path(
+            "C:\\Program Files\\claude\\claude.exe",
+            &blocked
+        ));
+        assert!(!is_blocked_path("/usr/bin/claude", &["  ".to_string()]));
+    }
+
+    #[test]
+    fn source_priority_overrides_default_ranking() {
+        let candidates = || {
+            vec![
+                installation("/usr/local/bin/claude", "PATH", 1, "1.0.0"),
+                installation("/home/u/.volta/bin/claude", "common-path", 3, "2.0.0"),
+            ]
+        };
+
+        let best = select_best_with_priority(candidates(), &[]).unwrap();
+        assert_eq!(best.path, "/usr/local/bin/claude");
+
+        let best = select_best_with_priority(candidates(), &["volta".to_string()]).unwrap();
+        assert_eq!(best.path, "/home/u/.volta/bin/claude");
+    }
+}
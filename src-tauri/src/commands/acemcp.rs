@@ -14,12 +14,25 @@ use log::{debug, error, info, warn};
 use regex::Regex;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
-use std::collections::HashSet;
+use futures::StreamExt;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::hash::{Hash, Hasher};
+use std::ops::ControlFlow;
 use std::path::PathBuf;
 use std::process::Stdio;
-use tauri::AppHandle;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex as StdMutex};
+use std::time::{Duration, Instant, SystemTime};
+use tauri::{AppHandle, Emitter, Manager, State};
 use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
 use tokio::process::Command;
+use tokio::sync::{oneshot, Mutex as AsyncMutex};
+
+use super::claude::normalize_path_for_comparison;
+use super::input_validation::resolve_export_target;
+use super::session_utils::find_session_path;
+use crate::utils::jsonl_reader::for_each_line;
 
 // Windows: 导入 CommandExt trait 以使用 creation_flags
 #[cfg(target_os = "windows")]
@@ -54,11 +67,36 @@ struct JsonRpcRequest {
 #[allow(dead_code)]
 struct JsonRpcResponse {
     jsonrpc: String,
-    id: u64,
+    /// 对应请求的 id（服务端可能回显为数字或字符串，无法解析请求时为 null）
+    #[serde(default)]
+    id: Value,
     result: Option<Value>,
     error: Option<JsonRpcError>,
 }
 
+impl JsonRpcResponse {
+    /// 解析一行 stdout：只有带 result/error 且不带 method 的消息才是对本端请求的响应，
+    /// 通知与服务端发起的请求（带 method）不能占用任何等待中的请求
+    fn parse(line: &str) -> Option<Self> {
+        let value: Value = serde_json::from_str(line).ok()?;
+        if value.get("method").is_some()
+            || (value.get("result").is_none() && value.get("error").is_none())
+        {
+            return None;
+        }
+        serde_json::from_value(value).ok()
+    }
+
+    /// 响应对应的本端请求 id
+    fn request_id(&self) -> Option<u64> {
+        match &self.id {
+            Value::Number(n) => n.as_u64(),
+            Value::String(s) => s.parse().ok(),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Debug, Deserialize)]
 struct JsonRpcError {
     code: i32,
@@ -77,10 +115,34 @@ pub struct EnhancementResult {
     pub context_count: usize,
     /// 是否成功调用 acemcp
     pub acemcp_used: bool,
+    /// 多轮搜索是否因超时只返回了部分结果
+    #[serde(default)]
+    pub partial: bool,
+    /// 因路径已在提示词中引用（或在未提交的 diff 中）而剔除的片段数
+    #[serde(default)]
+    pub excluded_count: usize,
+    /// 上下文中的各个片段（来源文件与行号），供前端跳转
+    #[serde(default)]
+    pub snippets: Vec<ContextSnippet>,
     /// 错误信息（如果有）
     pub error: Option<String>,
 }
 
+/// 搜索结果中的一个代码片段
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContextSnippet {
+    /// 片段来源文件（sidecar 返回的原始路径）
+    pub path: String,
+    /// 起止行号（1-based，无法解析时为 None）
+    pub start_line: Option<u32>,
+    pub end_line: Option<u32>,
+    /// 片段正文（不含 "Path:" 标题行）
+    pub text: String,
+    /// 相关度分数（sidecar 未提供时为 None）
+    pub score: Option<f64>,
+}
+
 // ============================================================================
 // 对话历史分析
 // ============================================================================
@@ -111,26 +173,31 @@ async fn load_recent_history(
     project_id: &str,
     limit: usize,
 ) -> Result<Vec<HistoryMessage>> {
-    let history_file = dirs::home_dir()
-        .ok_or_else(|| anyhow::anyhow!("Cannot find home directory"))?
-        .join(".claude")
-        .join("projects")
-        .join(project_id)
-        .join(format!("{}.jsonl", session_id));
-
-    if !history_file.exists() {
-        debug!("History file not found: {:?}", history_file);
+    let Some(history_file) =
+        find_session_path("claude", session_id, project_id).map_err(|e| anyhow::anyhow!(e))?
+    else {
+        debug!("History file not found for session: {}", session_id);
         return Ok(Vec::new());
-    }
+    };
 
-    let content = std::fs::read_to_string(&history_file)
-        .map_err(|e| anyhow::anyhow!("Failed to read history file: {}", e))?;
+    // 流式读取，只保留最后 limit * 2 行，避免把超大会话整体读入内存
+    let window = limit * 2;
+    let mut tail: VecDeque<String> = VecDeque::with_capacity(window);
+    for_each_line(&history_file, |_, line| {
+        if window > 0 {
+            if tail.len() == window {
+                tail.pop_front();
+            }
+            tail.push_back(line.to_string());
+        }
+        ControlFlow::Continue(())
+    })
+    .map_err(|e| anyhow::anyhow!("Failed to read history file: {}", e))?;
 
     let mut messages = Vec::new();
-    let lines: Vec<&str> = content.lines().collect();
 
     // 读取最后 N 条消息（倒序取）
-    for line in lines.iter().rev().take(limit * 2) {
+    for line in tail.iter().rev() {
         if let Ok(msg) = serde_json::from_str::<HistoryMessage>(line) {
             // 只保留用户和助手的消息
             if msg.role == "user" || msg.role == "assistant" {
@@ -285,10 +352,211 @@ fn generate_smart_query(current_prompt: &str, history_info: &HistoryContextInfo)
 // Acemcp Client
 // ============================================================================
 
+/// 等待响应的请求表：request id -> 响应发送端
+type PendingRequests = Arc<StdMutex<HashMap<u64, oneshot::Sender<JsonRpcResponse>>>>;
+
+/// 请求结束时从等待表中移除自身的 id
+///
+/// 请求 future 被丢弃（如预索引任务被中止）时同样生效，已取消请求的等待者不会残留在共享连接上
+struct PendingGuard<'a> {
+    client: &'a AcemcpClient,
+    id: u64,
+}
+
+impl Drop for PendingGuard<'_> {
+    fn drop(&mut self) {
+        self.client.remove_pending(self.id);
+    }
+}
+
+/// 轻量请求（initialize、tools/list）的超时时间
+const LIGHT_REQUEST_TIMEOUT_SECS: u64 = 30;
+
+/// tools/call 请求的默认超时时间（可由 config.toml 的 REQUEST_TIMEOUT_SECS 覆盖）
+const DEFAULT_TOOL_CALL_TIMEOUT_SECS: u64 = 60;
+
+/// 空闲多少分钟后关闭 sidecar 的默认值（可由 config.toml 的 IDLE_TIMEOUT_MINS 覆盖）
+const DEFAULT_IDLE_TIMEOUT_MINS: u64 = 10;
+
+/// 检查 sidecar 是否空闲超时的间隔
+const IDLE_CHECK_INTERVAL_SECS: u64 = 30;
+
+/// 复用前已空闲超过该时长的连接先 ping 一次，确认 sidecar 仍能响应
+const PING_AFTER_IDLE_SECS: u64 = 30;
+
+/// ping 请求的超时时间
+const PING_TIMEOUT_SECS: u64 = 5;
+
+/// 多轮搜索的并发上限
+const MULTI_ROUND_CONCURRENCY: usize = 3;
+
+/// 多轮搜索的默认整体截止时间
+const DEFAULT_SEARCH_DEADLINE_SECS: u64 = 20;
+
+/// 支持增量索引的 sidecar 提供的工具名（未提供时退回完整重新索引）
+const INCREMENTAL_INDEX_TOOL: &str = "index_files";
+
 /// Acemcp MCP 客户端
+///
+/// stdout 由后台任务统一读取，并按 JSON-RPC id 分发给对应的请求，
+/// 因此同一连接上可以同时存在多个进行中的请求。
 struct AcemcpClient {
-    child: tokio::process::Child,
-    request_id: u64,
+    child: AsyncMutex<tokio::process::Child>,
+    stdin: AsyncMutex<tokio::process::ChildStdin>,
+    pending: PendingRequests,
+    request_id: AtomicU64,
+    reader_task: tokio::task::JoinHandle<()>,
+    /// 写入 stdin 失败后置位，客户端不再复用
+    broken: AtomicBool,
+    /// tools/call 请求的超时秒数，启动时从配置读取，多轮搜索的每一轮共用
+    tool_call_timeout_secs: u64,
+    /// 最近一次发送请求或收到响应的时间，用于空闲关闭与复用前的 ping
+    last_activity: StdMutex<Instant>,
+}
+
+/// 多轮搜索结果
+#[derive(Debug, Default)]
+struct MultiRoundSearchResult {
+    /// 合并去重后的上下文
+    context: String,
+    /// 已完成（成功返回）的轮次数
+    completed_rounds: usize,
+    /// 计划执行的轮次数
+    total_rounds: usize,
+    /// 是否因整体截止时间到达而只返回了部分结果
+    partial: bool,
+}
+
+/// 片段去重用的哈希（进程内稳定即可，不需要密码学性质）
+fn snippet_hash(snippet: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    snippet.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// 代码片段收集器：按 "Path:" 切分搜索结果并去重
+#[derive(Debug, Default)]
+struct SnippetCollector {
+    snippets: Vec<String>,
+    seen: HashSet<u64>,
+    total_len: usize,
+}
+
+impl SnippetCollector {
+    /// 添加一轮搜索结果，返回本轮新增（去重后）的片段
+    ///
+    /// 切分时去掉的 "Path:" 前缀先补回，保证同一片段无论是否为某轮的首个片段都得到相同的哈希
+    fn add(&mut self, result: &str) -> &[String] {
+        let start = self.snippets.len();
+        for (i, piece) in result.split("\n\nPath:").enumerate() {
+            if piece.trim().is_empty() {
+                continue;
+            }
+            let snippet = if i == 0 {
+                piece.to_string()
+            } else {
+                format!("Path:{}", piece)
+            };
+            if self.seen.insert(snippet_hash(&snippet)) {
+                self.total_len += snippet.len();
+                self.snippets.push(snippet);
+            }
+        }
+        &self.snippets[start..]
+    }
+
+    fn total_len(&self) -> usize {
+        self.total_len
+    }
+
+    fn len(&self) -> usize {
+        self.snippets.len()
+    }
+
+    /// 合并为最终上下文
+    fn into_combined(self) -> String {
+        self.snippets.join("\n\n")
+    }
+}
+
+/// 把 sidecar 返回的文本解析为结构化片段
+///
+/// 每个 "Path: xxx" 行开始一个片段，第一个 "Path:" 之前的说明文字被忽略。行号依次取自：
+/// 标题中的 `#L10-L20` / `:10-20` / `(lines 10-20)` 后缀、紧随标题的 "Lines: 10-20" 行、
+/// 正文中 `cat -n` 风格的行号前缀（"    12\t..."）；分数取自紧随标题的 "Score: 0.87" 行。
+fn parse_context_snippets(context: &str) -> Vec<ContextSnippet> {
+    lazy_static::lazy_static! {
+        static ref HEADER_RANGE_RE: Regex = Regex::new(
+            r"^(.+?)(?:#L(\d+)(?:-L?(\d+))?|:(\d+)(?:-(\d+))?|\s+\(lines?\s+(\d+)(?:\s*-\s*(\d+))?\))$"
+        ).unwrap();
+        static ref META_RE: Regex =
+            Regex::new(r"^(?i)(lines?|score)\s*:\s*(\d+(?:\.\d+)?)(?:\s*-\s*(\d+))?\s*$").unwrap();
+        static ref NUMBERED_LINE_RE: Regex = Regex::new(r"^\s*(\d+)\t").unwrap();
+    }
+
+    let number = |m: Option<regex::Match>| m.and_then(|m| m.as_str().parse::<u32>().ok());
+
+    let mut snippets: Vec<ContextSnippet> = Vec::new();
+    // 当前片段是否仍在标题之后的元数据行中
+    let mut in_meta = false;
+    for line in context.lines() {
+        if let Some(header) = line.trim_start().strip_prefix("Path:") {
+            let header = header.trim();
+            let (path, start_line, end_line) = match HEADER_RANGE_RE.captures(header) {
+                Some(caps) => {
+                    let start = number(caps.get(2).or(caps.get(4)).or(caps.get(6)));
+                    let end = number(caps.get(3).or(caps.get(5)).or(caps.get(7))).or(start);
+                    (caps[1].trim().to_string(), start, end)
+                }
+                None => (header.to_string(), None, None),
+            };
+            snippets.push(ContextSnippet {
+                path,
+                start_line,
+                end_line,
+                text: String::new(),
+                score: None,
+            });
+            in_meta = true;
+            continue;
+        }
+        let Some(snippet) = snippets.last_mut() else {
+            continue;
+        };
+
+        if in_meta {
+            if let Some(caps) = META_RE.captures(line.trim()) {
+                if caps[1].eq_ignore_ascii_case("score") {
+                    snippet.score = caps[2].parse().ok();
+                } else if snippet.start_line.is_none() {
+                    snippet.start_line = number(caps.get(2));
+                    snippet.end_line = number(caps.get(3)).or(snippet.start_line);
+                }
+                continue;
+            }
+            in_meta = false;
+        }
+
+        if !snippet.text.is_empty() {
+            snippet.text.push('\n');
+        }
+        snippet.text.push_str(line);
+    }
+
+    for snippet in &mut snippets {
+        snippet.text = snippet.text.trim_matches('\n').trim_end().to_string();
+        if snippet.start_line.is_none() {
+            let numbers: Vec<u32> = snippet
+                .text
+                .lines()
+                .filter_map(|line| number(NUMBERED_LINE_RE.captures(line)?.get(1)))
+                .collect();
+            snippet.start_line = numbers.iter().min().copied();
+            snippet.end_line = numbers.iter().max().copied();
+        }
+    }
+    snippets.retain(|snippet| !snippet.path.is_empty());
+    snippets
 }
 
 impl AcemcpClient {
@@ -391,7 +659,8 @@ impl AcemcpClient {
         cmd.arg(&sidecar_path)
             .stdin(Stdio::piped())
             .stdout(Stdio::piped())
-            .stderr(Stdio::null());
+            .stderr(Stdio::null())
+            .kill_on_drop(true);
 
         // Windows: 隐藏控制台窗口
         #[cfg(target_os = "windows")]
@@ -404,70 +673,220 @@ impl AcemcpClient {
             anyhow::anyhow!("Failed to spawn sidecar: {}. Path: {:?}", e, sidecar_path)
         })?;
 
-        info!("Acemcp sidecar started successfully");
+        let tool_call_timeout_secs = read_acemcp_config()
+            .ok()
+            .and_then(|config| config.request_timeout_secs)
+            .filter(|secs| *secs > 0)
+            .unwrap_or(DEFAULT_TOOL_CALL_TIMEOUT_SECS);
+
+        info!(
+            "Acemcp sidecar started successfully (tools/call timeout: {}s)",
+            tool_call_timeout_secs
+        );
+
+        Self::from_child(child, tool_call_timeout_secs)
+    }
+
+    /// 接管已启动进程的 stdin/stdout，并开始后台读取响应
+    fn from_child(mut child: tokio::process::Child, tool_call_timeout_secs: u64) -> Result<Self> {
+        let stdin = child
+            .stdin
+            .take()
+            .ok_or_else(|| anyhow::anyhow!("stdin not available"))?;
+        let stdout = child
+            .stdout
+            .take()
+            .ok_or_else(|| anyhow::anyhow!("stdout not available"))?;
+
+        let pending: PendingRequests = Arc::new(StdMutex::new(HashMap::new()));
+        let reader_task = tokio::spawn(Self::read_responses(stdout, pending.clone()));
 
         Ok(Self {
-            child,
-            request_id: 0,
+            child: AsyncMutex::new(child),
+            stdin: AsyncMutex::new(stdin),
+            pending,
+            request_id: AtomicU64::new(0),
+            reader_task,
+            broken: AtomicBool::new(false),
+            tool_call_timeout_secs,
+            last_activity: StdMutex::new(Instant::now()),
         })
     }
 
-    /// 发送 JSON-RPC 请求
-    async fn send_request(&mut self, method: &str, params: Option<Value>) -> Result<Value> {
-        self.request_id += 1;
+    /// 进程仍在运行且管道可用（stdout 读取任务结束即表示进程退出或读取失败）
+    fn is_healthy(&self) -> bool {
+        !self.broken.load(Ordering::Relaxed) && !self.reader_task.is_finished()
+    }
+
+    /// 记录一次活动
+    fn touch(&self) {
+        if let Ok(mut last) = self.last_activity.lock() {
+            *last = Instant::now();
+        }
+    }
+
+    /// 距最近一次活动的时长
+    fn idle_for(&self) -> Duration {
+        self.last_activity
+            .lock()
+            .map(|last| last.elapsed())
+            .unwrap_or_default()
+    }
+
+    /// 是否有进行中的请求（长时间的索引请求期间不算空闲）
+    fn has_pending_requests(&self) -> bool {
+        self.pending
+            .lock()
+            .map(|map| !map.is_empty())
+            .unwrap_or(false)
+    }
+
+    /// 空闲一段时间的连接先 ping 确认 sidecar 仍能响应；ping 失败后不再复用
+    async fn is_responsive(&self) -> bool {
+        if self.idle_for() < Duration::from_secs(PING_AFTER_IDLE_SECS) {
+            return true;
+        }
+        match self.send_request("ping", None, PING_TIMEOUT_SECS).await {
+            Ok(_) => true,
+            Err(e) => {
+                warn!("Acemcp sidecar did not answer ping: {}", e);
+                self.broken.store(true, Ordering::Relaxed);
+                false
+            }
+        }
+    }
+
+    /// 后台读取 stdout，按 id 把响应分发给等待中的请求
+    async fn read_responses(stdout: tokio::process::ChildStdout, pending: PendingRequests) {
+        let mut lines = BufReader::new(stdout).lines();
+
+        loop {
+            match lines.next_line().await {
+                Ok(Some(line)) => {
+                    if line.trim().is_empty() {
+                        continue;
+                    }
+                    debug!("Received MCP message: {}", line.trim());
+
+                    let Some(response) = JsonRpcResponse::parse(&line) else {
+                        // 通知、服务端请求或日志输出，没有可关联的请求
+                        debug!("Ignoring non-response MCP message");
+                        continue;
+                    };
+
+                    let Some(id) = response.request_id() else {
+                        if let Some(error) = &response.error {
+                            warn!(
+                                "MCP error without request id {}: {}",
+                                error.code, error.message
+                            );
+                        }
+                        continue;
+                    };
+
+                    let sender = pending.lock().ok().and_then(|mut map| map.remove(&id));
+                    match sender {
+                        Some(tx) => {
+                            let _ = tx.send(response);
+                        }
+                        // 已超时请求的迟到响应：直接丢弃，不会交给后续请求
+                        None => debug!("Dropping response for unknown or timed-out id {}", id),
+                    }
+                }
+                Ok(None) => break,
+                Err(e) => {
+                    warn!("Failed to read acemcp stdout: {}", e);
+                    break;
+                }
+            }
+        }
+
+        // 连接已关闭：丢弃所有等待者，使其立即收到错误而不是等到超时
+        if let Ok(mut map) = pending.lock() {
+            map.clear();
+        }
+        debug!("Acemcp stdout reader stopped");
+    }
+
+    /// 写入一行 JSON 消息（MCP 使用换行符分隔的 JSON）
+    async fn write_message(&self, message: &str) -> Result<()> {
+        let mut stdin = self.stdin.lock().await;
+        let written = async {
+            stdin.write_all(message.as_bytes()).await?;
+            stdin.write_all(b"\n").await?;
+            stdin.flush().await
+        }
+        .await;
+        if written.is_err() {
+            self.broken.store(true, Ordering::Relaxed);
+        }
+        Ok(written?)
+    }
+
+    /// 发送 JSON-RPC 请求，超过 `timeout_secs` 未收到响应则报错
+    async fn send_request(
+        &self,
+        method: &str,
+        params: Option<Value>,
+        timeout_secs: u64,
+    ) -> Result<Value> {
+        let id = self.request_id.fetch_add(1, Ordering::Relaxed) + 1;
         let request = JsonRpcRequest {
             jsonrpc: "2.0".to_string(),
-            id: self.request_id,
+            id,
             method: method.to_string(),
             params,
         };
 
         let request_json = serde_json::to_string(&request)?;
         debug!("Sending MCP request: {}", request_json);
-
-        // 发送请求（MCP 使用换行符分隔的 JSON）
-        if let Some(stdin) = self.child.stdin.as_mut() {
-            stdin.write_all(request_json.as_bytes()).await?;
-            stdin.write_all(b"\n").await?;
-            stdin.flush().await?;
-        } else {
-            return Err(anyhow::anyhow!("stdin not available"));
-        }
-
-        // 读取响应
-        if let Some(stdout) = self.child.stdout.as_mut() {
-            let mut reader = BufReader::new(stdout);
-            let mut line = String::new();
-
-            // 设置超时（30秒）
-            let timeout = tokio::time::Duration::from_secs(30);
-            match tokio::time::timeout(timeout, reader.read_line(&mut line)).await {
-                Ok(Ok(_)) => {
-                    debug!("Received MCP response: {}", line.trim());
-                    let response: JsonRpcResponse = serde_json::from_str(&line)?;
-
-                    if let Some(error) = response.error {
-                        return Err(anyhow::anyhow!(
-                            "MCP error {}: {}",
-                            error.code,
-                            error.message
-                        ));
-                    }
-
-                    response
-                        .result
-                        .ok_or_else(|| anyhow::anyhow!("No result in response"))
+        self.touch();
+
+        // 先登记再发送，避免响应先于登记到达
+        let (tx, rx) = oneshot::channel();
+        self.pending
+            .lock()
+            .map_err(|_| anyhow::anyhow!("Pending request map poisoned"))?
+            .insert(id, tx);
+        let _guard = PendingGuard { client: self, id };
+
+        self.write_message(&request_json).await?;
+
+        let timeout = tokio::time::Duration::from_secs(timeout_secs);
+        let received = tokio::time::timeout(timeout, rx).await;
+        self.touch();
+        match received {
+            Ok(Ok(response)) => {
+                // 读取任务按 id 分发，收到的响应必然属于本请求
+                if let Some(error) = response.error {
+                    return Err(anyhow::anyhow!(
+                        "MCP error {}: {}",
+                        error.code,
+                        error.message
+                    ));
                 }
-                Ok(Err(e)) => Err(anyhow::anyhow!("Failed to read response: {}", e)),
-                Err(_) => Err(anyhow::anyhow!("Request timeout (30s)")),
+
+                response
+                    .result
+                    .ok_or_else(|| anyhow::anyhow!("No result in response"))
             }
-        } else {
-            Err(anyhow::anyhow!("stdout not available"))
+            Ok(Err(_)) => Err(anyhow::anyhow!("Connection closed before response")),
+            Err(_) => Err(anyhow::anyhow!(
+                "Request timeout ({}s): {} received no response",
+                timeout_secs,
+                method
+            )),
+        }
+    }
+
+    fn remove_pending(&self, id: u64) {
+        if let Ok(mut map) = self.pending.lock() {
+            map.remove(&id);
         }
     }
 
     /// 发送通知（notification，无需响应）
-    async fn send_notification(&mut self, method: &str, params: Option<Value>) -> Result<()> {
+    async fn send_notification(&self, method: &str, params: Option<Value>) -> Result<()> {
         let notification = json!({
             "jsonrpc": "2.0",
             "method": method,
@@ -478,19 +897,11 @@ impl AcemcpClient {
         debug!("Sending MCP notification: {}", notification_json);
 
         // 发送通知（不等待响应）
-        if let Some(stdin) = self.child.stdin.as_mut() {
-            stdin.write_all(notification_json.as_bytes()).await?;
-            stdin.write_all(b"\n").await?;
-            stdin.flush().await?;
-        } else {
-            return Err(anyhow::anyhow!("stdin not available"));
-        }
-
-        Ok(())
+        self.write_message(&notification_json).await
     }
 
     /// 初始化 MCP 会话
-    async fn initialize(&mut self) -> Result<()> {
+    async fn initialize(&self) -> Result<()> {
         info!("Initializing MCP session...");
         let params = json!({
             "protocolVersion": "2024-11-05",
@@ -502,7 +913,8 @@ impl AcemcpClient {
         });
 
         // 发送 initialize 请求并等待响应
-        self.send_request("initialize", Some(params)).await?;
+        self.send_request("initialize", Some(params), LIGHT_REQUEST_TIMEOUT_SECS)
+            .await?;
 
         // 发送 initialized 通知（不等待响应）
         self.send_notification("notifications/initialized", None)
@@ -513,7 +925,7 @@ impl AcemcpClient {
     }
 
     /// 调用 search_context 工具
-    async fn search_context(&mut self, project_path: &str, query: &str) -> Result<String> {
+    async fn search_context(&self, project_path: &str, query: &str) -> Result<String> {
         info!(
             "Calling search_context: project={}, query={}",
             project_path, query
@@ -527,7 +939,9 @@ impl AcemcpClient {
             }
         });
 
-        let result = self.send_request("tools/call", Some(params)).await?;
+        let result = self
+            .send_request("tools/call", Some(params), self.tool_call_timeout_secs)
+            .await?;
 
         // 解析结果
         if let Some(content) = result.get("content").and_then(|c| c.as_array()) {
@@ -541,85 +955,267 @@ impl AcemcpClient {
         Err(anyhow::anyhow!("Invalid search_context response format"))
     }
 
+    /// 列出服务端提供的工具名称
+    async fn list_tools(&self) -> Result<Vec<String>> {
+        let result = self
+            .send_request("tools/list", None, LIGHT_REQUEST_TIMEOUT_SECS)
+            .await?;
+        Ok(result
+            .get("tools")
+            .and_then(|t| t.as_array())
+            .map(|tools| {
+                tools
+                    .iter()
+                    .filter_map(|t| t.get("name").and_then(|n| n.as_str()))
+                    .map(String::from)
+                    .collect()
+            })
+            .unwrap_or_default())
+    }
+
+    /// 调用增量索引工具，只重新索引指定文件（相对项目根目录的路径）
+    async fn index_files(&self, project_path: &str, file_paths: &[String]) -> Result<()> {
+        info!(
+            "Calling {}: project={}, files={}",
+            INCREMENTAL_INDEX_TOOL,
+            project_path,
+            file_paths.len()
+        );
+
+        let params = json!({
+            "name": INCREMENTAL_INDEX_TOOL,
+            "arguments": {
+                "project_root_path": project_path.replace('\\', "/"),
+                "file_paths": file_paths
+            }
+        });
+
+        let result = self
+            .send_request("tools/call", Some(params), self.tool_call_timeout_secs)
+            .await?;
+        if result.get("isError").and_then(|v| v.as_bool()) == Some(true) {
+            return Err(anyhow::anyhow!(
+                "{} reported an error",
+                INCREMENTAL_INDEX_TOOL
+            ));
+        }
+        Ok(())
+    }
+
+    /// 执行单轮搜索，返回轮次编号与结果
+    async fn search_round(
+        &self,
+        round: usize,
+        project_path: &str,
+        query: &str,
+    ) -> (usize, Result<String>) {
+        info!("Round {}: searching with query: {}", round + 1, query);
+        (round, self.search_context(project_path, query).await)
+    }
+
     /// 多轮搜索：使用不同的查询策略获取更全面的上下文
-    async fn multi_round_search(
-        &mut self,
+    ///
+    /// 各轮查询并发执行（上限 3），按完成顺序收集并去重；单轮失败不影响其他轮。
+    /// 每轮请求共用客户端的 tools/call 超时（REQUEST_TIMEOUT_SECS）。
+    /// 到达整体截止时间后，返回已完成轮次的结果并标记 partial。
+    /// 每个新增的去重片段会立即交给 `on_snippet(轮次, 片段)`，便于 UI 实时展示。
+    async fn multi_round_search<F>(
+        &self,
         project_path: &str,
         queries: &[String],
         max_total_length: usize,
-    ) -> Result<String> {
-        info!("Starting multi-round search with {} queries", queries.len());
-
-        let mut all_results = Vec::new();
-        let mut seen_snippets = HashSet::new(); // 用于去重
+        deadline: tokio::time::Duration,
+        mut on_snippet: F,
+    ) -> MultiRoundSearchResult
+    where
+        F: FnMut(usize, &str) + Send,
+    {
+        let rounds: Vec<(usize, &String)> = queries
+            .iter()
+            .enumerate()
+            .filter(|(_, q)| !q.trim().is_empty())
+            .collect();
+        info!(
+            "Starting multi-round search with {} queries (concurrency={}, deadline={:?})",
+            rounds.len(),
+            MULTI_ROUND_CONCURRENCY,
+            deadline
+        );
 
-        for (round, query) in queries.iter().enumerate() {
-            if query.trim().is_empty() {
-                continue;
-            }
+        let total_rounds = rounds.len();
+        let mut collector = SnippetCollector::default();
+        let mut completed_rounds = 0;
+        let mut partial = false;
+
+        // 先构造好每轮的 future，再交给 buffer_unordered 控制并发
+        let round_futures: Vec<_> = rounds
+            .into_iter()
+            .map(|(round, query)| self.search_round(round, project_path, query))
+            .collect();
+        let mut searches =
+            futures::stream::iter(round_futures).buffer_unordered(MULTI_ROUND_CONCURRENCY);
+
+        let deadline_at = tokio::time::Instant::now() + deadline;
+        loop {
+            match tokio::time::timeout_at(deadline_at, searches.next()).await {
+                Ok(Some((round, Ok(result)))) => {
+                    completed_rounds += 1;
+                    for snippet in collector.add(&result) {
+                        on_snippet(round, snippet);
+                    }
 
-            info!("Round {}: searching with query: {}", round + 1, query);
-
-            match self.search_context(project_path, query).await {
-                Ok(result) => {
-                    // 简单去重：按代码片段切分
-                    for snippet in result.split("\n\nPath:") {
-                        if !snippet.trim().is_empty() {
-                            // 生成简单的哈希来去重
-                            let snippet_hash = format!("{:x}", md5::compute(snippet));
-                            if !seen_snippets.contains(&snippet_hash) {
-                                seen_snippets.insert(snippet_hash);
-
-                                // 恢复 "Path:" 前缀（除了第一个）
-                                if !all_results.is_empty() {
-                                    all_results.push(format!("\n\nPath:{}", snippet));
-                                } else {
-                                    all_results.push(snippet.to_string());
-                                }
-                            }
-                        }
+                    // 检查是否已经收集够了
+                    if collector.total_len() >= max_total_length {
+                        info!("Reached max length limit after round {}", round + 1);
+                        break;
                     }
                 }
-                Err(e) => {
+                Ok(Some((round, Err(e)))) => {
                     warn!("Round {} search failed: {}", round + 1, e);
-                    // 继续下一轮
+                }
+                Ok(None) => break,
+                Err(_) => {
+                    warn!(
+                        "Multi-round search deadline reached, returning {}/{} completed rounds",
+                        completed_rounds, total_rounds
+                    );
+                    partial = true;
+                    break;
                 }
             }
-
-            // 检查是否已经收集够了
-            let current_length: usize = all_results.iter().map(|s| s.len()).sum();
-            if current_length >= max_total_length {
-                info!("Reached max length limit, stopping at round {}", round + 1);
-                break;
-            }
-
-            // 轻微延迟，避免请求过快
-            tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
         }
 
-        let combined = all_results.join("");
+        let unique_snippets = collector.len();
+        let context = collector.into_combined();
         info!(
-            "Multi-round search completed: {} unique snippets, {} total chars",
-            seen_snippets.len(),
-            combined.len()
+            "Multi-round search completed: {} unique snippets, {} total chars, partial={}",
+            unique_snippets,
+            context.len(),
+            partial
         );
 
-        Ok(combined)
+        MultiRoundSearchResult {
+            context,
+            completed_rounds,
+            total_rounds,
+            partial,
+        }
     }
 
     /// 关闭客户端
-    async fn shutdown(mut self) -> Result<()> {
+    async fn shutdown(&self) -> Result<()> {
         info!("Shutting down acemcp client...");
 
         // 尝试优雅关闭
-        if let Err(e) = self.child.kill().await {
+        if let Err(e) = self.child.lock().await.kill().await {
             warn!("Failed to kill acemcp process: {}", e);
         }
+        self.reader_task.abort();
 
         Ok(())
     }
 }
 
+/// acemcp 客户端池（Tauri managed state）
+///
+/// 首次使用时启动并初始化 sidecar，之后各命令复用同一连接；请求按 JSON-RPC id
+/// 分发，多个命令可以并发使用。进程退出、管道读写失败或空闲后 ping 不通时，
+/// 下次获取时丢弃并重建；空闲超过 IDLE_TIMEOUT_MINS（默认 10 分钟）后关闭进程。
+#[derive(Default)]
+pub struct AcemcpClientPool {
+    slot: Arc<AsyncMutex<Option<Arc<AcemcpClient>>>>,
+}
+
+impl AcemcpClientPool {
+    /// 获取可用的客户端，不存在或不可用时重新启动
+    ///
+    /// 持有锁完成启动，并发调用只会启动一个 sidecar。
+    async fn get_or_spawn(&self, app: &AppHandle) -> Result<Arc<AcemcpClient>> {
+        let mut slot = self.slot.lock().await;
+        if let Some(client) = slot.as_ref() {
+            if client.is_healthy() && client.is_responsive().await {
+                client.touch();
+                return Ok(client.clone());
+            }
+        }
+        if let Some(stale) = slot.take() {
+            warn!("Acemcp client is unhealthy, restarting sidecar");
+            let _ = stale.shutdown().await;
+        }
+
+        let client = AcemcpClient::start(app).await?;
+        if let Err(e) = client.initialize().await {
+            let _ = client.shutdown().await;
+            return Err(anyhow::anyhow!("Failed to initialize MCP: {}", e));
+        }
+        let client = Arc::new(client);
+        *slot = Some(client.clone());
+        self.watch_idle(&client, idle_timeout_from_config());
+        Ok(client)
+    }
+
+    /// 后台检查空闲：没有进行中的请求且空闲超过 `idle_timeout` 时关闭 sidecar
+    ///
+    /// 客户端被替换或关闭后检查任务随之结束
+    fn watch_idle(&self, client: &Arc<AcemcpClient>, idle_timeout: Duration) {
+        let slot = self.slot.clone();
+        let client = Arc::downgrade(client);
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(Duration::from_secs(IDLE_CHECK_INTERVAL_SECS)).await;
+                let Some(current) = client.upgrade() else {
+                    break;
+                };
+                let mut slot = slot.lock().await;
+                if !slot.as_ref().is_some_and(|c| Arc::ptr_eq(c, &current)) {
+                    break;
+                }
+                if current.has_pending_requests() || current.idle_for() < idle_timeout {
+                    continue;
+                }
+
+                info!(
+                    "Acemcp sidecar idle for {}s, shutting it down",
+                    current.idle_for().as_secs()
+                );
+                slot.take();
+                drop(slot);
+                let _ = current.shutdown().await;
+                break;
+            }
+        });
+    }
+
+    /// 关闭当前客户端，下次使用时重新启动
+    pub async fn shutdown(&self) {
+        if let Some(client) = self.slot.lock().await.take() {
+            let _ = client.shutdown().await;
+        }
+    }
+}
+
+/// 从客户端池获取可用的客户端
+async fn shared_client(app: &AppHandle) -> Result<Arc<AcemcpClient>> {
+    app.state::<AcemcpClientPool>().get_or_spawn(app).await
+}
+
+/// 关闭共享客户端（应用退出、配置变更时调用），下次使用时重新启动
+pub async fn shutdown_shared_client(app: &AppHandle) {
+    if let Some(pool) = app.try_state::<AcemcpClientPool>() {
+        pool.shutdown().await;
+    }
+}
+
+/// 空闲关闭时长，配置为 0 或未配置时使用默认值
+fn idle_timeout_from_config() -> Duration {
+    let mins = read_acemcp_config()
+        .ok()
+        .and_then(|config| config.idle_timeout_mins)
+        .filter(|mins| *mins > 0)
+        .unwrap_or(DEFAULT_IDLE_TIMEOUT_MINS);
+    Duration::from_secs(mins * 60)
+}
+
 // ============================================================================
 // 关键词提取
 // ============================================================================
@@ -753,8 +1349,153 @@ const CHINESE_TECH_WORDS: &[&str] = &[
     "聚合",
 ];
 
+/// 内置英文停用词（小写）
+const DEFAULT_STOPWORDS: &[&str] = &[
+    "the", "a", "an", "is", "are", "was", "were", "be", "been", "please", "help", "me", "i",
+    "want", "how", "can", "could", "would", "should", "will", "shall", "may", "might", "must",
+    "have", "has", "had", "do", "does", "did", "this", "that", "these", "those", "and", "or",
+    "but", "not", "with", "for", "from", "into", "about", "after", "before", "between", "get",
+    "set", "new", "add", "use", "let", "var", "const",
+];
+
+/// 用户自定义词库文件名（位于 ~/.acemcp/）
+const KEYWORD_CONFIG_FILE: &str = "keywords.toml";
+
+/// 用户自定义词库（~/.acemcp/keywords.toml）
+///
+/// ```toml
+/// abbreviations = ["rtos", "isr", "dma"]
+/// stopwords = ["todo"]
+/// chinese_words = ["寄存器", "中断"]
+/// ```
+///
+/// 与内置词库合并；`stopwords` 同时会屏蔽内置的缩写词和中文词。
+/// 也接受 `extra_abbreviations` / `extra_chinese_words` 作为键名。
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct KeywordConfig {
+    #[serde(alias = "extra_abbreviations")]
+    pub abbreviations: Vec<String>,
+    pub stopwords: Vec<String>,
+    #[serde(alias = "extra_chinese_words")]
+    pub chinese_words: Vec<String>,
+}
+
+impl KeywordConfig {
+    /// 去除空白与空项、英文统一小写、去重（保持原有顺序）
+    fn normalized(&self) -> Self {
+        fn clean(words: &[String], lowercase: bool) -> Vec<String> {
+            let mut seen = HashSet::new();
+            words
+                .iter()
+                .map(|word| word.trim())
+                .filter(|word| !word.is_empty())
+                .map(|word| {
+                    if lowercase {
+                        word.to_lowercase()
+                    } else {
+                        word.to_string()
+                    }
+                })
+                .filter(|word| seen.insert(word.clone()))
+                .collect()
+        }
+        Self {
+            abbreviations: clean(&self.abbreviations, true),
+            stopwords: clean(&self.stopwords, true),
+            chinese_words: clean(&self.chinese_words, false),
+        }
+    }
+}
+
+/// 内置词库与用户词库合并后的结果
+#[derive(Debug)]
+struct KeywordLexicon {
+    abbreviations: Vec<String>,
+    stopwords: HashSet<String>,
+    chinese_words: Vec<String>,
+}
+
+impl KeywordLexicon {
+    /// 用户词追加在内置词之后；停用词优先，命中停用词的缩写词和中文词被剔除
+    fn merge(user: &KeywordConfig) -> Self {
+        let user = user.normalized();
+        let stopwords: HashSet<String> = DEFAULT_STOPWORDS
+            .iter()
+            .map(|word| word.to_string())
+            .chain(user.stopwords)
+            .collect();
+
+        let merge_words = |defaults: &[&str], extra: Vec<String>| -> Vec<String> {
+            let mut seen = HashSet::new();
+            defaults
+                .iter()
+                .map(|word| word.to_string())
+                .chain(extra)
+                .filter(|word| !stopwords.contains(&word.to_lowercase()))
+                .filter(|word| seen.insert(word.clone()))
+                .collect()
+        };
+        let abbreviations = merge_words(TECH_ABBREVIATIONS, user.abbreviations);
+        let chinese_words = merge_words(CHINESE_TECH_WORDS, user.chinese_words);
+
+        Self {
+            abbreviations,
+            stopwords,
+            chinese_words,
+        }
+    }
+}
+
+lazy_static::lazy_static! {
+    /// 合并后的词库缓存，按 keywords.toml 的修改时间失效（None 表示文件不存在）
+    static ref KEYWORD_LEXICON_CACHE: StdMutex<Option<(Option<SystemTime>, Arc<KeywordLexicon>)>> =
+        StdMutex::new(None);
+}
+
+fn keyword_config_path() -> Option<PathBuf> {
+    dirs::home_dir().map(|home| home.join(".acemcp").join(KEYWORD_CONFIG_FILE))
+}
+
+/// 解析用户词库；TOML 无效时记录警告并回退为空（即只使用内置词库）
+fn parse_keyword_config(content: &str) -> KeywordConfig {
+    toml::from_str(content).unwrap_or_else(|e| {
+        warn!(
+            "Invalid {}, using built-in keywords: {}",
+            KEYWORD_CONFIG_FILE, e
+        );
+        KeywordConfig::default()
+    })
+}
+
+/// 获取合并后的词库，文件未变化时直接使用缓存
+fn keyword_lexicon() -> Arc<KeywordLexicon> {
+    let path = keyword_config_path();
+    let modified = path
+        .as_ref()
+        .and_then(|path| std::fs::metadata(path).ok())
+        .and_then(|meta| meta.modified().ok());
+
+    let mut cache = KEYWORD_LEXICON_CACHE.lock().unwrap();
+    if let Some((cached_modified, lexicon)) = cache.as_ref() {
+        if *cached_modified == modified {
+            return lexicon.clone();
+        }
+    }
+
+    let user = match (&path, modified) {
+        (Some(path), Some(_)) => std::fs::read_to_string(path)
+            .map(|content| parse_keyword_config(&content))
+            .unwrap_or_default(),
+        _ => KeywordConfig::default(),
+    };
+    let lexicon = Arc::new(KeywordLexicon::merge(&user));
+    *cache = Some((modified, lexicon.clone()));
+    lexicon
+}
+
 /// 关键词提取结果
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct ExtractedKeywords {
     /// 所有关键词组合成的查询字符串
     pub combined: String,
@@ -773,7 +1514,13 @@ pub struct ExtractedKeywords {
 /// - 驼峰命名（如 getUserInfo → get, User, Info）
 /// - 下划线命名（如 get_user_info → get, user, info）
 /// - 中文技术词汇（基于词库匹配）
+///
+/// 词库为内置词库与 ~/.acemcp/keywords.toml 的合并结果
 fn extract_keywords_v2(prompt: &str) -> ExtractedKeywords {
+    extract_keywords_with(prompt, &keyword_lexicon())
+}
+
+fn extract_keywords_with(prompt: &str, lexicon: &KeywordLexicon) -> ExtractedKeywords {
     lazy_static::lazy_static! {
         // 匹配英文单词（至少3个字符）
         static ref ENGLISH_WORD_RE: Regex = Regex::new(
@@ -791,16 +1538,7 @@ fn extract_keywords_v2(prompt: &str) -> ExtractedKeywords {
         ).unwrap();
     }
 
-    // 英文停用词（小写）
-    let stopwords: HashSet<&str> = [
-        "the", "a", "an", "is", "are", "was", "were", "be", "been", "please", "help", "me", "i",
-        "want", "how", "can", "could", "would", "should", "will", "shall", "may", "might", "must",
-        "have", "has", "had", "do", "does", "did", "this", "that", "these", "those", "and", "or",
-        "but", "not", "with", "for", "from", "into", "about", "after", "before", "between", "get",
-        "set", "new", "add", "use", "let", "var", "const",
-    ]
-    .into_iter()
-    .collect();
+    let stopwords = &lexicon.stopwords;
 
     let mut english_keywords: Vec<String> = Vec::new();
     let mut chinese_keywords: Vec<String> = Vec::new();
@@ -812,7 +1550,7 @@ fn extract_keywords_v2(prompt: &str) -> ExtractedKeywords {
         // 拆分下划线命名
         for part in snake_word.split('_') {
             let lower = part.to_lowercase();
-            if lower.len() >= 3 && !stopwords.contains(lower.as_str()) && !seen.contains(&lower) {
+            if lower.len() >= 3 && !stopwords.contains(&lower) && !seen.contains(&lower) {
                 seen.insert(lower.clone());
                 english_keywords.push(lower);
             }
@@ -831,7 +1569,7 @@ fn extract_keywords_v2(prompt: &str) -> ExtractedKeywords {
             // 拆分驼峰命名
             for part_cap in CAMEL_CASE_RE.captures_iter(word) {
                 let part = part_cap[0].to_lowercase();
-                if part.len() >= 3 && !stopwords.contains(part.as_str()) && !seen.contains(&part) {
+                if part.len() >= 3 && !stopwords.contains(&part) && !seen.contains(&part) {
                     seen.insert(part.clone());
                     english_keywords.push(part);
                 }
@@ -839,7 +1577,7 @@ fn extract_keywords_v2(prompt: &str) -> ExtractedKeywords {
         } else {
             // 普通英文单词
             let lower = word.to_lowercase();
-            if lower.len() >= 3 && !stopwords.contains(lower.as_str()) && !seen.contains(&lower) {
+            if lower.len() >= 3 && !stopwords.contains(&lower) && !seen.contains(&lower) {
                 seen.insert(lower.clone());
                 english_keywords.push(lower);
             }
@@ -848,22 +1586,20 @@ fn extract_keywords_v2(prompt: &str) -> ExtractedKeywords {
 
     // 3️⃣ 提取英文技术缩写词（如 ui, ux, api 等短词）
     let prompt_lower = prompt.to_lowercase();
-    for &abbr in TECH_ABBREVIATIONS {
+    for abbr in &lexicon.abbreviations {
+        let abbr = abbr.as_str();
         // 使用单词边界匹配，避免误匹配（如 "paid" 中的 "ai"）
         // 检查缩写词前后是否为非字母数字字符
         if let Some(pos) = prompt_lower.find(abbr) {
-            let before_ok = pos == 0
-                || !prompt_lower
-                    .chars()
-                    .nth(pos - 1)
-                    .map(|c| c.is_alphanumeric())
-                    .unwrap_or(false);
-            let after_ok = pos + abbr.len() >= prompt_lower.len()
-                || !prompt_lower
-                    .chars()
-                    .nth(pos + abbr.len())
-                    .map(|c| c.is_alphanumeric())
-                    .unwrap_or(false);
+            // pos 是字节偏移，按字节切片取相邻字符（提示词可能含中文）
+            let before_ok = !prompt_lower[..pos]
+                .chars()
+                .next_back()
+                .is_some_and(|c| c.is_alphanumeric());
+            let after_ok = !prompt_lower[pos + abbr.len()..]
+                .chars()
+                .next()
+                .is_some_and(|c| c.is_alphanumeric());
 
             if before_ok && after_ok && !seen.contains(abbr) {
                 seen.insert(abbr.to_string());
@@ -873,7 +1609,8 @@ fn extract_keywords_v2(prompt: &str) -> ExtractedKeywords {
     }
 
     // 4️⃣ 提取中文技术词汇（基于词库匹配）
-    for &tech_word in CHINESE_TECH_WORDS {
+    for tech_word in &lexicon.chinese_words {
+        let tech_word = tech_word.as_str();
         if prompt.contains(tech_word) && !seen.contains(tech_word) {
             seen.insert(tech_word.to_string());
             chinese_keywords.push(tech_word.to_string());
@@ -981,6 +1718,12 @@ fn generate_multi_round_queries(
 // Tauri Command
 // ============================================================================
 
+/// 最大提示词长度
+const MAX_PROMPT_LENGTH: usize = 80_000;
+
+/// 增强后提示词的最大输出长度
+const MAX_TOTAL_OUTPUT_LENGTH: usize = 150_000;
+
 /// 使用 acemcp 增强提示词，添加项目上下文
 /// UTF-8 安全的字符串截断函数
 /// 如果 max_bytes 不在字符边界上，会向前寻找最近的边界，防止 panic
@@ -1007,7 +1750,111 @@ fn truncate_utf8_safe(s: &str, max_bytes: usize) -> &str {
     }
 }
 
+/// 汇总需要从上下文中剔除的路径（规范化为相对项目根目录的路径）
+fn collect_excluded_paths(
+    project_path: &str,
+    already_included_paths: Vec<String>,
+    exclude_recent_diff: bool,
+) -> Vec<String> {
+    let mut paths = already_included_paths;
+    if exclude_recent_diff {
+        match super::simple_git::git_uncommitted_files(project_path) {
+            Ok(files) => paths.extend(files),
+            Err(e) => warn!("Failed to list uncommitted files for exclusion: {}", e),
+        }
+    }
+
+    let mut normalized: Vec<String> = paths
+        .iter()
+        .map(|path| normalize_snippet_path(project_path, path))
+        .filter(|path| !path.is_empty())
+        .collect();
+    normalized.sort();
+    normalized.dedup();
+    normalized
+}
+
+/// 规范化片段路径：统一 "/" 分隔，去掉 @ 引用前缀、项目根目录与 "./"
+fn normalize_snippet_path(project_path: &str, path: &str) -> String {
+    let path = path
+        .trim()
+        .trim_start_matches('@')
+        .trim_matches('"')
+        .replace('\\', "/");
+    let root = project_path.replace('\\', "/");
+    let root = root.trim_end_matches('/');
+
+    let relative = match path.strip_prefix(root) {
+        Some(rest) if !root.is_empty() && (rest.is_empty() || rest.starts_with('/')) => rest,
+        _ => path.as_str(),
+    };
+    let mut relative = relative.trim_start_matches('/');
+    while let Some(rest) = relative.strip_prefix("./") {
+        relative = rest;
+    }
+    relative.trim_end_matches('/').to_string()
+}
+
+/// 片段路径与排除路径精确相同，或位于排除的目录下
+fn is_excluded_path(snippet_path: &str, excluded: &[String]) -> bool {
+    excluded.iter().any(|path| {
+        snippet_path == path
+            || snippet_path
+                .strip_prefix(path.as_str())
+                .is_some_and(|rest| rest.starts_with('/'))
+    })
+}
+
+/// 剔除路径命中 `excluded` 的片段，返回剩余上下文与剔除数量
+///
+/// `excluded` 需已规范化；片段首行 "Path: xxx" 按同样规则规范化后比较
+fn exclude_snippets(context: &str, excluded: &[String]) -> (String, usize) {
+    if excluded.is_empty() {
+        return (context.to_string(), 0);
+    }
+
+    let mut kept = Vec::new();
+    let mut excluded_count = 0;
+    for (i, snippet) in context.split("\n\nPath:").enumerate() {
+        // 第一段保留原有前缀（可能是搜索结果的说明文字，而不是片段）
+        let header = if i == 0 {
+            snippet.trim_start().strip_prefix("Path:")
+        } else {
+            Some(snippet)
+        };
+        let path = header
+            .and_then(|rest| rest.lines().next())
+            .map(|line| normalize_snippet_path("", line));
+        if path.is_some_and(|path| is_excluded_path(&path, excluded)) {
+            excluded_count += 1;
+            continue;
+        }
+        kept.push(if i == 0 {
+            snippet.to_string()
+        } else {
+            format!("Path:{}", snippet)
+        });
+    }
+
+    (kept.join("\n\n"), excluded_count)
+}
+
+/// 多轮搜索新增片段事件（acemcp-context-snippet）
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ContextSnippetEvent<'a> {
+    /// 调用方传入的 stream_id，用于区分并发的增强请求
+    stream_id: Option<&'a str>,
+    project_path: &'a str,
+    /// 片段来自第几轮搜索（从 1 开始）
+    round: usize,
+    /// 片段在本次增强中的序号（从 0 开始）
+    index: usize,
+    snippet: String,
+}
+
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn enhance_prompt_with_context(
     app: AppHandle,
     prompt: String,
@@ -1015,7 +1862,11 @@ pub async fn enhance_prompt_with_context(
     session_id: Option<String>, // 新增：会话 ID
     project_id: Option<String>, // 新增：项目 ID
     max_context_length: Option<usize>,
-    enable_multi_round: Option<bool>, // 新增：是否启用多轮搜索
+    enable_multi_round: Option<bool>,  // 新增：是否启用多轮搜索
+    search_deadline_secs: Option<u64>, // 多轮搜索整体截止时间（秒），默认 20
+    stream_id: Option<String>,         // 片段实时推送的标识，随 acemcp-context-snippet 事件返回
+    already_included_paths: Option<Vec<String>>, // 提示词中已 @ 引用或粘贴的文件路径，命中的片段会被剔除
+    exclude_recent_diff: Option<bool>,           // 是否同时剔除未提交 diff 涉及的文件
 ) -> Result<EnhancementResult, String> {
     info!(
         "enhance_prompt_with_context: prompt_len={}, project={}, has_history={}, multi_round={}",
@@ -1025,10 +1876,6 @@ pub async fn enhance_prompt_with_context(
         enable_multi_round.unwrap_or(true)
     );
 
-    // ⚡ 添加长度限制配置
-    const MAX_PROMPT_LENGTH: usize = 80_000; // 最大提示词长度
-    const MAX_TOTAL_OUTPUT_LENGTH: usize = 150_000; // 最大输出长度
-
     let max_length = max_context_length.unwrap_or(3000);
 
     // ⚡ 检查提示词长度
@@ -1043,6 +1890,9 @@ pub async fn enhance_prompt_with_context(
             enhanced_prompt: prompt.clone(),
             context_count: 0,
             acemcp_used: false,
+            partial: false,
+            excluded_count: 0,
+            snippets: Vec::new(),
             error: Some(format!(
                 "提示词过长（{} 字符），超过最大限制（{} 字符）。请缩短提示词或分批处理。",
                 prompt.len(),
@@ -1058,6 +1908,9 @@ pub async fn enhance_prompt_with_context(
             enhanced_prompt: prompt,
             context_count: 0,
             acemcp_used: false,
+            partial: false,
+            excluded_count: 0,
+            snippets: Vec::new(),
             error: Some("Project path does not exist".to_string()),
         });
     }
@@ -1133,6 +1986,9 @@ pub async fn enhance_prompt_with_context(
             enhanced_prompt: prompt,
             context_count: 0,
             acemcp_used: false,
+            partial: false,
+            excluded_count: 0,
+            snippets: Vec::new(),
             error: Some("No keywords could be extracted from prompt".to_string()),
         });
     }
@@ -1146,8 +2002,8 @@ pub async fn enhance_prompt_with_context(
         debug!("  Query {}: {}", i + 1, q);
     }
 
-    // 启动 acemcp 客户端
-    let mut client = match AcemcpClient::start(&app).await {
+    // 获取共享的 acemcp 客户端（首次使用时启动并初始化）
+    let client = match shared_client(&app).await {
         Ok(c) => c,
         Err(e) => {
             error!("Failed to start acemcp: {}", e);
@@ -1156,47 +2012,67 @@ pub async fn enhance_prompt_with_context(
                 enhanced_prompt: prompt,
                 context_count: 0,
                 acemcp_used: false,
+                partial: false,
+                excluded_count: 0,
+                snippets: Vec::new(),
                 error: Some(format!("Failed to start acemcp: {}", e)),
             });
         }
     };
 
-    // 初始化 MCP 会话
-    if let Err(e) = client.initialize().await {
-        error!("Failed to initialize MCP session: {}", e);
-        let _ = client.shutdown().await;
-        return Ok(EnhancementResult {
-            original_prompt: prompt.clone(),
-            enhanced_prompt: prompt,
-            context_count: 0,
-            acemcp_used: false,
-            error: Some(format!("Failed to initialize MCP: {}", e)),
-        });
-    }
-
     // 🚀 执行搜索（单轮或多轮）
+    let mut partial = false;
     let context_result = if valid_queries.len() > 1 && enable_multi_round.unwrap_or(true) {
         info!(
             "🔄 Using multi-round search with {} queries",
             valid_queries.len()
         );
-        match client
-            .multi_round_search(&project_path, &valid_queries, max_length * 2)
-            .await
-        {
-            Ok(ctx) => ctx,
-            Err(e) => {
-                error!("Failed to perform multi-round search: {}", e);
-                let _ = client.shutdown().await;
-                return Ok(EnhancementResult {
-                    original_prompt: prompt.clone(),
-                    enhanced_prompt: prompt,
-                    context_count: 0,
-                    acemcp_used: false,
-                    error: Some(format!("Failed to search context: {}", e)),
-                });
-            }
+        let deadline = tokio::time::Duration::from_secs(
+            search_deadline_secs.unwrap_or(DEFAULT_SEARCH_DEADLINE_SECS),
+        );
+        // 多轮搜索中每找到一个新片段就推送给前端，便于在结果偏题时提前中止
+        let mut snippet_index = 0;
+        let search = client
+            .multi_round_search(
+                &project_path,
+                &valid_queries,
+                max_length * 2,
+                deadline,
+                |round, snippet| {
+                    let _ = app.emit(
+                        "acemcp-context-snippet",
+                        ContextSnippetEvent {
+                            stream_id: stream_id.as_deref(),
+                            project_path: &project_path,
+                            round: round + 1,
+                            index: snippet_index,
+                            snippet: snippet.to_string(),
+                        },
+                    );
+                    snippet_index += 1;
+                },
+            )
+            .await;
+
+        if search.completed_rounds == 0 && !search.partial {
+            error!(
+                "All {} search rounds failed",
+                search.total_rounds
+            );
+            return Ok(EnhancementResult {
+                original_prompt: prompt.clone(),
+                enhanced_prompt: prompt,
+                context_count: 0,
+                acemcp_used: false,
+                partial: false,
+                excluded_count: 0,
+                snippets: Vec::new(),
+                error: Some("Failed to search context: all search rounds failed".to_string()),
+            });
         }
+
+        partial = search.partial;
+        search.context
     } else {
         info!("🔍 Using single-round search");
         match client
@@ -1206,20 +2082,33 @@ pub async fn enhance_prompt_with_context(
             Ok(ctx) => ctx,
             Err(e) => {
                 error!("Failed to search context: {}", e);
-                let _ = client.shutdown().await;
                 return Ok(EnhancementResult {
                     original_prompt: prompt.clone(),
                     enhanced_prompt: prompt,
                     context_count: 0,
                     acemcp_used: false,
+                    partial: false,
+                    excluded_count: 0,
+                    snippets: Vec::new(),
                     error: Some(format!("Failed to search context: {}", e)),
                 });
             }
         }
     };
 
-    // 关闭客户端
-    let _ = client.shutdown().await;
+    // 剔除用户已经提供的文件的片段，避免重复占用上下文
+    let excluded_paths = collect_excluded_paths(
+        &project_path,
+        already_included_paths.unwrap_or_default(),
+        exclude_recent_diff.unwrap_or(false),
+    );
+    let (context_result, excluded_count) = exclude_snippets(&context_result, &excluded_paths);
+    if excluded_count > 0 {
+        info!(
+            "Excluded {} snippets already included by the user",
+            excluded_count
+        );
+    }
 
     // ⚡ 改进：智能处理上下文结果
     let trimmed_context = if context_result.len() > max_length {
@@ -1236,8 +2125,9 @@ pub async fn enhance_prompt_with_context(
         context_result.clone()
     };
 
-    // 统计上下文条目数（简单计数 "Path:" 出现次数）
-    let context_count = trimmed_context.matches("Path:").count();
+    // 解析为结构化片段（不含截断提示），条目数即片段数
+    let snippets = parse_context_snippets(truncate_utf8_safe(&context_result, max_length));
+    let context_count = snippets.len();
 
     // ⚡ 改进：格式化增强后的提示词，并验证总长度
     let enhanced_prompt = if !trimmed_context.trim().is_empty() {
@@ -1278,6 +2168,9 @@ pub async fn enhance_prompt_with_context(
                     enhanced_prompt: prompt.clone(),
                     context_count: 0,
                     acemcp_used: false,
+                    partial: false,
+                    excluded_count,
+                    snippets: Vec::new(),
                     error: Some(format!(
                         "提示词太长（{} 字符），无法添加项目上下文。\n\
                         建议：\n\
@@ -1309,23 +2202,336 @@ pub async fn enhance_prompt_with_context(
         enhanced_prompt,
         context_count,
         acemcp_used: true,
+        partial,
+        excluded_count,
+        snippets,
         error: None,
     })
 }
 
+// ============================================================================
+// 多项目上下文
+// ============================================================================
+
+/// 单个项目的上下文统计
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectContextStat {
+    /// 项目路径
+    pub project_path: String,
+    /// 最终写入提示词的代码片段数
+    pub snippet_count: usize,
+    /// 该项目的搜索是否因超时只返回了部分结果
+    pub partial: bool,
+    /// 错误信息（如果有）
+    pub error: Option<String>,
+}
+
+/// 多项目增强结果
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MultiProjectEnhancementResult {
+    /// 原始提示词
+    pub original_prompt: String,
+    /// 增强后的提示词（包含各项目上下文）
+    pub enhanced_prompt: String,
+    /// 所有项目合计的上下文条目数
+    pub context_count: usize,
+    /// 是否成功调用 acemcp
+    pub acemcp_used: bool,
+    /// 是否有项目只返回了部分结果
+    pub partial: bool,
+    /// 各项目的片段统计（与去重后的 project_paths 顺序一致）
+    pub projects: Vec<ProjectContextStat>,
+    /// 错误信息（如果有）
+    pub error: Option<String>,
+}
+
+impl MultiProjectEnhancementResult {
+    fn unchanged(prompt: String, projects: Vec<ProjectContextStat>, error: String) -> Self {
+        Self {
+            original_prompt: prompt.clone(),
+            enhanced_prompt: prompt,
+            context_count: 0,
+            acemcp_used: false,
+            partial: false,
+            projects,
+            error: Some(error),
+        }
+    }
+}
+
+/// 合并多个项目的搜索结果
+///
+/// 跨项目按片段内容去重（先出现的项目保留），每个项目最多占用 `per_project_budget` 字节，
+/// 并在每段前加上项目标题。返回合并后的上下文和各项目实际保留的片段数。
+fn merge_project_contexts(
+    contexts: &[(String, String)],
+    per_project_budget: usize,
+) -> (String, Vec<usize>) {
+    let mut seen = HashSet::new();
+    let mut sections = Vec::new();
+    let mut counts = Vec::with_capacity(contexts.len());
+
+    for (project_path, context) in contexts {
+        let mut kept: Vec<String> = Vec::new();
+        let mut used = 0;
+
+        for snippet in context.split("\n\nPath:") {
+            let body = snippet.trim().trim_start_matches("Path:").trim();
+            if body.is_empty() {
+                continue;
+            }
+            if !seen.insert(snippet_hash(body)) {
+                continue;
+            }
+
+            let entry = format!("Path: {}", body);
+            if used + entry.len() > per_project_budget {
+                // 预算内一个完整片段都放不下时，截断第一个片段
+                if kept.is_empty() {
+                    kept.push(format!(
+                        "{}...",
+                        truncate_utf8_safe(&entry, per_project_budget)
+                    ));
+                }
+                break;
+            }
+            used += entry.len();
+            kept.push(entry);
+        }
+
+        counts.push(kept.len());
+        if kept.is_empty() {
+            continue;
+        }
+
+        let name = std::path::Path::new(project_path)
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| project_path.clone());
+        sections.push(format!(
+            "### 项目: {} ({})\n{}",
+            name,
+            project_path,
+            kept.join("\n\n")
+        ));
+    }
+
+    (sections.join("\n\n"), counts)
+}
+
+/// 使用多个项目的 acemcp 上下文增强提示词（跨仓库提示词）
+///
+/// 所有项目共用同一个 sidecar 连接，各项目的多轮搜索并发执行；
+/// 上下文预算在项目之间平分，单个项目失败不影响其他项目。
+#[tauri::command]
+pub async fn enhance_prompt_with_multi_project_context(
+    app: AppHandle,
+    prompt: String,
+    project_paths: Vec<String>,
+    max_context_length: Option<usize>,
+    enable_multi_round: Option<bool>,
+    search_deadline_secs: Option<u64>,
+) -> Result<MultiProjectEnhancementResult, String> {
+    // 去重（保持顺序）
+    let mut seen_paths = HashSet::new();
+    let project_paths: Vec<String> = project_paths
+        .into_iter()
+        .filter(|p| !p.trim().is_empty())
+        .filter(|p| seen_paths.insert(normalize_path_for_comparison(p)))
+        .collect();
+
+    info!(
+        "enhance_prompt_with_multi_project_context: prompt_len={}, projects={}",
+        prompt.len(),
+        project_paths.len()
+    );
+
+    if project_paths.is_empty() {
+        return Err("No project paths provided".to_string());
+    }
+
+    if prompt.len() > MAX_PROMPT_LENGTH {
+        return Ok(MultiProjectEnhancementResult::unchanged(
+            prompt.clone(),
+            Vec::new(),
+            format!(
+                "提示词过长（{} 字符），超过最大限制（{} 字符）。请缩短提示词或分批处理。",
+                prompt.len(),
+                MAX_PROMPT_LENGTH
+            ),
+        ));
+    }
+
+    let max_length = max_context_length.unwrap_or(3000);
+    let per_project_budget = (max_length / project_paths.len()).max(1);
+
+    let extracted = extract_keywords_v2(&prompt);
+    let queries = generate_multi_round_queries(&extracted, enable_multi_round.unwrap_or(true));
+    if queries.is_empty() {
+        return Ok(MultiProjectEnhancementResult::unchanged(
+            prompt,
+            Vec::new(),
+            "No keywords could be extracted from prompt".to_string(),
+        ));
+    }
+
+    let client = match shared_client(&app).await {
+        Ok(c) => c,
+        Err(e) => {
+            error!("Failed to start acemcp: {}", e);
+            return Ok(MultiProjectEnhancementResult::unchanged(
+                prompt,
+                Vec::new(),
+                format!("Failed to start acemcp: {}", e),
+            ));
+        }
+    };
+
+    let deadline = tokio::time::Duration::from_secs(
+        search_deadline_secs.unwrap_or(DEFAULT_SEARCH_DEADLINE_SECS),
+    );
+    let searches = project_paths.iter().map(|project_path| {
+        let client = &client;
+        let queries = &queries;
+        async move {
+            if !std::path::Path::new(project_path).exists() {
+                return Err("Project path does not exist".to_string());
+            }
+            let search = client
+                .multi_round_search(
+                    project_path,
+                    queries,
+                    per_project_budget * 2,
+                    deadline,
+                    |_, _| {},
+                )
+                .await;
+            if search.completed_rounds == 0 && !search.partial {
+                return Err("All search rounds failed".to_string());
+            }
+            Ok(search)
+        }
+    });
+    let results = futures::future::join_all(searches).await;
+
+    let mut contexts = Vec::new();
+    let mut stats = Vec::with_capacity(project_paths.len());
+    for (project_path, result) in project_paths.iter().zip(results) {
+        match result {
+            Ok(search) => {
+                stats.push(ProjectContextStat {
+                    project_path: project_path.clone(),
+                    snippet_count: 0,
+                    partial: search.partial,
+                    error: None,
+                });
+                contexts.push((project_path.clone(), search.context));
+            }
+            Err(e) => {
+                warn!("Context search failed for {}: {}", project_path, e);
+                stats.push(ProjectContextStat {
+                    project_path: project_path.clone(),
+                    snippet_count: 0,
+                    partial: false,
+                    error: Some(e),
+                });
+            }
+        }
+    }
+
+    if contexts.is_empty() {
+        return Ok(MultiProjectEnhancementResult::unchanged(
+            prompt,
+            stats,
+            "Failed to search context in all projects".to_string(),
+        ));
+    }
+
+    let (merged, counts) = merge_project_contexts(&contexts, per_project_budget);
+    for ((project_path, _), count) in contexts.iter().zip(counts) {
+        if let Some(stat) = stats.iter_mut().find(|s| &s.project_path == project_path) {
+            stat.snippet_count = count;
+        }
+    }
+    let context_count = stats.iter().map(|s| s.snippet_count).sum();
+    let partial = stats.iter().any(|s| s.partial);
+
+    let enhanced_prompt = if merged.trim().is_empty() {
+        info!("No relevant context found in any project");
+        prompt.clone()
+    } else {
+        let available_space = MAX_TOTAL_OUTPUT_LENGTH.saturating_sub(prompt.len() + 100);
+        let context = if merged.len() > available_space {
+            format!(
+                "{}...\n\n[上下文已自动调整以适应长度限制]",
+                truncate_utf8_safe(&merged, available_space)
+            )
+        } else {
+            merged
+        };
+        format!(
+            "{}\n\n--- 多项目上下文 (来自 acemcp 语义搜索) ---\n{}",
+            prompt.trim(),
+            context
+        )
+    };
+
+    info!(
+        "Multi-project enhancement: projects={}, context_count={}, enhanced_len={}, partial={}",
+        stats.len(),
+        context_count,
+        enhanced_prompt.len(),
+        partial
+    );
+
+    Ok(MultiProjectEnhancementResult {
+        original_prompt: prompt,
+        enhanced_prompt,
+        context_count,
+        acemcp_used: true,
+        partial,
+        projects: stats,
+        error: None,
+    })
+}
+
+/// 关键词预览结果
+#[derive(Debug, Clone, Serialize)]
+pub struct PromptKeywordsPreview {
+    pub keywords: ExtractedKeywords,
+    /// 按顺序执行的各轮搜索查询
+    pub queries: Vec<String>,
+}
+
+fn preview_keywords(prompt: &str, enable_multi_round: bool) -> PromptKeywordsPreview {
+    let keywords = extract_keywords_v2(prompt);
+    let queries = generate_multi_round_queries(&keywords, enable_multi_round);
+    PromptKeywordsPreview { keywords, queries }
+}
+
+/// 预览从提示词中提取的关键词与多轮搜索查询（不启动 sidecar、不需要索引）
+///
+/// 与 `enhance_prompt_with_context` 在没有历史上下文时使用的查询一致，便于调试增强效果
+#[tauri::command]
+pub async fn preview_prompt_keywords(
+    prompt: String,
+    enable_multi_round: Option<bool>,
+) -> Result<PromptKeywordsPreview, String> {
+    Ok(preview_keywords(
+        &prompt,
+        enable_multi_round.unwrap_or(true),
+    ))
+}
+
 /// 测试 acemcp 是否可用
 #[tauri::command]
 pub async fn test_acemcp_availability(app: AppHandle) -> Result<bool, String> {
     info!("Testing acemcp availability...");
 
-    match AcemcpClient::start(&app).await {
-        Ok(mut client) => {
-            if let Err(e) = client.initialize().await {
-                error!("Failed to initialize acemcp: {}", e);
-                let _ = client.shutdown().await;
-                return Ok(false);
-            }
-            let _ = client.shutdown().await;
+    match shared_client(&app).await {
+        Ok(_) => {
             info!("Acemcp is available");
             Ok(true)
         }
@@ -1347,6 +2553,10 @@ pub struct AcemcpConfigData {
     pub token: String,
     pub batch_size: Option<u32>,
     pub max_lines_per_blob: Option<u32>,
+    /// tools/call 请求（搜索、索引）的超时秒数
+    pub request_timeout_secs: Option<u64>,
+    /// sidecar 空闲多少分钟后关闭
+    pub idle_timeout_mins: Option<u64>,
 }
 
 impl Default for AcemcpConfigData {
@@ -1356,18 +2566,33 @@ impl Default for AcemcpConfigData {
             token: String::new(),
             batch_size: Some(10),
             max_lines_per_blob: Some(800),
+            request_timeout_secs: Some(DEFAULT_TOOL_CALL_TIMEOUT_SECS),
+            idle_timeout_mins: Some(DEFAULT_IDLE_TIMEOUT_MINS),
         }
     }
 }
 
+/// 由设置界面管理的配置键，保存时整体重写，其余键原样保留
+const UI_MANAGED_KEYS: &[&str] = &[
+    "BASE_URL",
+    "TOKEN",
+    "BATCH_SIZE",
+    "MAX_LINES_PER_BLOB",
+    "REQUEST_TIMEOUT_SECS",
+    "IDLE_TIMEOUT_MINS",
+];
+
 /// 保存 acemcp 配置到 ~/.acemcp/config.toml
 /// 只更新指定的字段，保留其他现有配置（如 TEXT_EXTENSIONS, EXCLUDE_PATTERNS 等）
 #[tauri::command]
 pub async fn save_acemcp_config(
+    app: AppHandle,
     base_url: String,
     token: String,
     batch_size: Option<u32>,
     max_lines_per_blob: Option<u32>,
+    request_timeout_secs: Option<u64>,
+    idle_timeout_mins: Option<u64>,
 ) -> Result<(), String> {
     use std::collections::HashMap;
     use std::fs;
@@ -1438,20 +2663,12 @@ pub async fn save_acemcp_config(
                     }
 
                     // 保留非 UI 管理的字段
-                    if key != "BASE_URL"
-                        && key != "TOKEN"
-                        && key != "BATCH_SIZE"
-                        && key != "MAX_LINES_PER_BLOB"
-                    {
+                    if !UI_MANAGED_KEYS.contains(&key) {
                         existing_entries.insert(key.to_string(), multiline_content);
                     }
                 } else {
                     // 单行配置
-                    if key != "BASE_URL"
-                        && key != "TOKEN"
-                        && key != "BATCH_SIZE"
-                        && key != "MAX_LINES_PER_BLOB"
-                    {
+                    if !UI_MANAGED_KEYS.contains(&key) {
                         existing_entries.insert(key.to_string(), line.to_string());
                     }
                 }
@@ -1475,6 +2692,14 @@ pub async fn save_acemcp_config(
         toml_content.push_str(&format!("MAX_LINES_PER_BLOB = {}\n", max_lines));
     }
 
+    if let Some(timeout_secs) = request_timeout_secs {
+        toml_content.push_str(&format!("REQUEST_TIMEOUT_SECS = {}\n", timeout_secs));
+    }
+
+    if let Some(idle_mins) = idle_timeout_mins {
+        toml_content.push_str(&format!("IDLE_TIMEOUT_MINS = {}\n", idle_mins));
+    }
+
     // 保留的其他配置（包括多行数组）
     for entry in existing_entries.values() {
         toml_content.push_str(entry);
@@ -1491,14 +2716,54 @@ pub async fn save_acemcp_config(
 
     fs::write(&config_file, toml_content).map_err(|e| format!("Failed to write config: {}", e))?;
 
+    // 共享的 sidecar 使用启动时的配置，关闭后下次使用时按新配置重启
+    shutdown_shared_client(&app).await;
+
     info!("Acemcp config saved to: {:?}", config_file);
     Ok(())
 }
 
 /// 加载 acemcp 配置从 ~/.acemcp/config.toml
-/// 自动迁移旧的 settings.toml 配置文件
+/// 旧版 settings.toml 由启动迁移重命名（见 migrations）
 #[tauri::command]
 pub async fn load_acemcp_config() -> Result<AcemcpConfigData, String> {
+    read_acemcp_config()
+}
+
+/// 读取用户自定义词库 ~/.acemcp/keywords.toml（不含内置词库），文件不存在时返回空词库
+#[tauri::command]
+pub async fn get_acemcp_keyword_config() -> Result<KeywordConfig, String> {
+    let path = keyword_config_path().ok_or("Cannot find home directory")?;
+    if !path.exists() {
+        return Ok(KeywordConfig::default());
+    }
+    let content = std::fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read {}: {}", KEYWORD_CONFIG_FILE, e))?;
+    toml::from_str(&content).map_err(|e| format!("Invalid {}: {}", KEYWORD_CONFIG_FILE, e))
+}
+
+/// 保存用户自定义词库并使缓存失效，下一次提取关键词即生效
+#[tauri::command]
+pub async fn save_acemcp_keyword_config(config: KeywordConfig) -> Result<KeywordConfig, String> {
+    let path = keyword_config_path().ok_or("Cannot find home directory")?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create .acemcp directory: {}", e))?;
+    }
+
+    let config = config.normalized();
+    let content = toml::to_string(&config)
+        .map_err(|e| format!("Failed to serialize keyword config: {}", e))?;
+    std::fs::write(&path, content)
+        .map_err(|e| format!("Failed to write {}: {}", KEYWORD_CONFIG_FILE, e))?;
+
+    *KEYWORD_LEXICON_CACHE.lock().unwrap() = None;
+    info!("Saved acemcp keyword config to {:?}", path);
+    Ok(config)
+}
+
+/// 读取 ~/.acemcp/config.toml，文件不存在时返回默认配置
+fn read_acemcp_config() -> Result<AcemcpConfigData, String> {
     use std::fs;
 
     let acemcp_dir = dirs::home_dir()
@@ -1506,28 +2771,6 @@ pub async fn load_acemcp_config() -> Result<AcemcpConfigData, String> {
         .join(".acemcp");
 
     let config_file = acemcp_dir.join("config.toml");
-    let old_config_file = acemcp_dir.join("settings.toml");
-
-    // 迁移逻辑：如果 settings.toml 存在而 config.toml 不存在，自动迁移
-    if !config_file.exists() && old_config_file.exists() {
-        info!("Migrating configuration from settings.toml to config.toml");
-        match fs::rename(&old_config_file, &config_file) {
-            Ok(_) => info!("✅ Configuration migrated successfully"),
-            Err(e) => {
-                warn!(
-                    "Failed to migrate config file: {}. Will try to copy instead.",
-                    e
-                );
-                // 如果重命名失败（可能是跨设备），尝试复制
-                if let Ok(content) = fs::read_to_string(&old_config_file) {
-                    if let Err(copy_err) = fs::write(&config_file, content) {
-                        return Err(format!("Failed to migrate config: {}", copy_err));
-                    }
-                    info!("✅ Configuration copied successfully");
-                }
-            }
-        }
-    }
 
     if !config_file.exists() {
         info!("Acemcp config file not found, returning defaults");
@@ -1537,11 +2780,18 @@ pub async fn load_acemcp_config() -> Result<AcemcpConfigData, String> {
     let content =
         fs::read_to_string(&config_file).map_err(|e| format!("Failed to read config: {}", e))?;
 
-    // 简单的 TOML 解析（只解析我们需要的字段）
+    info!("Loaded acemcp config from: {:?}", config_file);
+    Ok(parse_acemcp_config(&content))
+}
+
+/// 简单的 TOML 解析（只解析我们需要的字段）
+fn parse_acemcp_config(content: &str) -> AcemcpConfigData {
     let mut base_url = String::new();
     let mut token = String::new();
     let mut batch_size = None;
     let mut max_lines_per_blob = None;
+    let mut request_timeout_secs = None;
+    let mut idle_timeout_mins = None;
 
     for line in content.lines() {
         let line = line.trim();
@@ -1561,16 +2811,25 @@ pub async fn load_acemcp_config() -> Result<AcemcpConfigData, String> {
             if let Some(value) = extract_toml_number_value(line) {
                 max_lines_per_blob = Some(value);
             }
+        } else if line.starts_with("REQUEST_TIMEOUT_SECS") {
+            if let Some(value) = extract_toml_number_value(line) {
+                request_timeout_secs = Some(u64::from(value));
+            }
+        } else if line.starts_with("IDLE_TIMEOUT_MINS") {
+            if let Some(value) = extract_toml_number_value(line) {
+                idle_timeout_mins = Some(u64::from(value));
+            }
         }
     }
 
-    info!("Loaded acemcp config from: {:?}", config_file);
-    Ok(AcemcpConfigData {
+    AcemcpConfigData {
         base_url,
         token,
         batch_size,
         max_lines_per_blob,
-    })
+        request_timeout_secs,
+        idle_timeout_mins,
+    }
 }
 
 /// 提取 TOML 字符串值
@@ -1599,10 +2858,150 @@ fn extract_toml_number_value(line: &str) -> Option<u32> {
 // 后台预索引
 // ============================================================================
 
+/// 预索引状态
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum PreindexStatus {
+    Indexing,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+/// 项目最近一次预索引的状态
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct PreindexStatusInfo {
+    pub status: PreindexStatus,
+    /// 失败原因（仅 Failed 时有值）
+    pub error: Option<String>,
+}
+
+/// 预索引进度阶段
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum PreindexStage {
+    /// 已登记任务，正在启动 sidecar
+    Started,
+    /// sidecar 已启动，正在建立索引
+    Indexing,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+/// 进行中的预索引任务
+struct PreindexTask {
+    handle: tauri::async_runtime::JoinHandle<()>,
+    started_at: Instant,
+}
+
+/// 后台预索引状态（按规范化的项目路径索引）
+#[derive(Default)]
+pub struct PreindexState {
+    tasks: Arc<StdMutex<HashMap<String, PreindexTask>>>,
+    statuses: Arc<StdMutex<HashMap<String, PreindexStatusInfo>>>,
+    /// 当前项目的文件变更监听任务（同一时间只监听一个项目）
+    watcher: Arc<StdMutex<Option<ProjectWatcher>>>,
+}
+
+/// 预索引状态变更事件
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PreindexStatusEvent<'a> {
+    project_path: &'a str,
+    status: PreindexStatus,
+    error: Option<&'a str>,
+}
+
+/// 预索引进度事件（`acemcp-preindex-progress`）
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PreindexProgressEvent<'a> {
+    project_path: &'a str,
+    stage: PreindexStage,
+    /// 参与索引的项目文件数（sidecar 不报告处理进度，按文件监听的忽略规则统计；
+    /// 完成时给出，超过监听上限时为 None）
+    file_count: Option<usize>,
+    /// 自预索引开始以来的耗时（毫秒）
+    elapsed_ms: u64,
+    /// 失败原因
+    error: Option<String>,
+}
+
+fn emit_preindex_progress(
+    app: &AppHandle,
+    project_path: &str,
+    stage: PreindexStage,
+    started_at: Instant,
+    file_count: Option<usize>,
+    error: Option<String>,
+) {
+    let _ = app.emit(
+        "acemcp-preindex-progress",
+        PreindexProgressEvent {
+            project_path,
+            stage,
+            file_count,
+            elapsed_ms: started_at.elapsed().as_millis() as u64,
+            error,
+        },
+    );
+}
+
+/// 记录项目最近一次预索引的状态，覆盖上一次的状态与失败原因
+fn record_preindex_status(
+    statuses: &StdMutex<HashMap<String, PreindexStatusInfo>>,
+    project_path: &str,
+    status: PreindexStatus,
+    error: Option<&str>,
+) {
+    if let Ok(mut statuses) = statuses.lock() {
+        statuses.insert(
+            normalize_path_for_comparison(project_path),
+            PreindexStatusInfo {
+                status,
+                error: error.map(String::from),
+            },
+        );
+    }
+}
+
+fn lookup_preindex_status(
+    statuses: &StdMutex<HashMap<String, PreindexStatusInfo>>,
+    project_path: &str,
+) -> Result<Option<PreindexStatusInfo>, String> {
+    let statuses = statuses.lock().map_err(|e| e.to_string())?;
+    Ok(statuses
+        .get(&normalize_path_for_comparison(project_path))
+        .cloned())
+}
+
+fn set_preindex_status(
+    app: &AppHandle,
+    statuses: &StdMutex<HashMap<String, PreindexStatusInfo>>,
+    project_path: &str,
+    status: PreindexStatus,
+    error: Option<&str>,
+) {
+    record_preindex_status(statuses, project_path, status, error);
+    let _ = app.emit(
+        "acemcp-preindex-status",
+        PreindexStatusEvent {
+            project_path,
+            status,
+            error,
+        },
+    );
+}
+
 /// 后台预索引项目（不阻塞 UI）
 /// 在用户选择项目后自动调用，提前完成索引以加快后续搜索
 #[tauri::command]
-pub async fn preindex_project(app: AppHandle, project_path: String) -> Result<(), String> {
+pub async fn preindex_project(
+    app: AppHandle,
+    state: State<'_, PreindexState>,
+    project_path: String,
+) -> Result<(), String> {
     info!(
         "Starting background pre-indexing for project: {}",
         project_path
@@ -1617,47 +3016,475 @@ pub async fn preindex_project(app: AppHandle, project_path: String) -> Result<()
         return Ok(());
     }
 
+    let key = normalize_path_for_comparison(&project_path);
+    let tasks = state.tasks.clone();
+    let statuses = state.statuses.clone();
+
+    // 持有锁完成登记，保证任务结束时的清理一定发生在登记之后
+    let mut running = tasks.lock().map_err(|e| e.to_string())?;
+    if running.contains_key(&key) {
+        info!("Pre-indexing already running for: {}", project_path);
+        return Ok(());
+    }
+
+    let started_at = Instant::now();
+    set_preindex_status(
+        &app,
+        &statuses,
+        &project_path,
+        PreindexStatus::Indexing,
+        None,
+    );
+    emit_preindex_progress(
+        &app,
+        &project_path,
+        PreindexStage::Started,
+        started_at,
+        None,
+        None,
+    );
+
     // 启动后台任务进行索引
-    tauri::async_runtime::spawn(async move {
-        match preindex_project_internal(&app, &project_path).await {
-            Ok(_) => {
-                info!("✅ Background pre-indexing completed for: {}", project_path);
+    let task_key = key.clone();
+    let task_tasks = tasks.clone();
+    let handle = tauri::async_runtime::spawn(async move {
+        let result = preindex_project_internal(&app, &project_path, || {
+            emit_preindex_progress(
+                &app,
+                &project_path,
+                PreindexStage::Indexing,
+                started_at,
+                None,
+                None,
+            );
+        })
+        .await;
+
+        if let Ok(mut running) = task_tasks.lock() {
+            running.remove(&task_key);
+        }
+
+        match result {
+            Ok(file_count) => {
+                info!(
+                    "✅ Background pre-indexing completed for: {} ({} ms)",
+                    project_path,
+                    started_at.elapsed().as_millis()
+                );
+                set_preindex_status(
+                    &app,
+                    &statuses,
+                    &project_path,
+                    PreindexStatus::Completed,
+                    None,
+                );
+                emit_preindex_progress(
+                    &app,
+                    &project_path,
+                    PreindexStage::Completed,
+                    started_at,
+                    file_count,
+                    None,
+                );
             }
             Err(e) => {
                 warn!(
                     "⚠️ Background pre-indexing failed for {}: {}",
                     project_path, e
                 );
+                let error = e.to_string();
+                set_preindex_status(
+                    &app,
+                    &statuses,
+                    &project_path,
+                    PreindexStatus::Failed,
+                    Some(&error),
+                );
+                emit_preindex_progress(
+                    &app,
+                    &project_path,
+                    PreindexStage::Failed,
+                    started_at,
+                    None,
+                    Some(error),
+                );
             }
         }
     });
+    running.insert(key, PreindexTask { handle, started_at });
 
     // 立即返回，不等待索引完成
     Ok(())
 }
 
+/// 取消项目的后台预索引
+///
+/// 只中止后台任务：进行中的索引请求随之被丢弃并从等待表中移除，共享的 sidecar 进程不受影响，
+/// 其他命令在同一连接上的请求照常完成；没有进行中的预索引时返回 false。
+#[tauri::command]
+pub async fn cancel_preindex(
+    app: AppHandle,
+    state: State<'_, PreindexState>,
+    project_path: String,
+) -> Result<bool, String> {
+    let key = normalize_path_for_comparison(&project_path);
+    let task = state
+        .tasks
+        .lock()
+        .map_err(|e| e.to_string())?
+        .remove(&key);
+
+    let Some(task) = task else {
+        info!("No running pre-indexing to cancel for: {}", project_path);
+        return Ok(false);
+    };
+
+    task.handle.abort();
+
+    info!("Cancelled pre-indexing for: {}", project_path);
+    set_preindex_status(
+        &app,
+        &state.statuses,
+        &project_path,
+        PreindexStatus::Cancelled,
+        None,
+    );
+    emit_preindex_progress(
+        &app,
+        &project_path,
+        PreindexStage::Cancelled,
+        task.started_at,
+        None,
+        None,
+    );
+    Ok(true)
+}
+
+/// 获取项目最近一次预索引的状态与失败原因（从未预索引过时返回 None）
+#[tauri::command]
+pub async fn get_preindex_status(
+    state: State<'_, PreindexState>,
+    project_path: String,
+) -> Result<Option<PreindexStatusInfo>, String> {
+    lookup_preindex_status(&state.statuses, &project_path)
+}
+
 /// 内部预索引实现
-async fn preindex_project_internal(app: &AppHandle, project_path: &str) -> Result<()> {
+///
+/// `on_started` 在获取到共享客户端后被调用一次，用于报告进入索引阶段。
+/// 返回参与索引的项目文件数（超过统计上限时为 None）。
+async fn preindex_project_internal(
+    app: &AppHandle,
+    project_path: &str,
+    on_started: impl FnOnce(),
+) -> Result<Option<usize>> {
     info!("🔄 Pre-indexing project: {}", project_path);
 
-    // 启动 acemcp 客户端
-    let mut client = AcemcpClient::start(app).await?;
+    // 获取共享的 acemcp 客户端
+    let client = shared_client(app).await?;
+    on_started();
+    index_project(&client, project_path).await
+}
 
-    // 初始化 MCP 会话
-    client.initialize().await?;
+/// 在给定客户端上建立项目索引，返回参与索引的项目文件数
+///
+/// 中止调用方任务即可取消：进行中的请求被丢弃并从等待表移除，客户端仍可供其他命令使用。
+async fn index_project(client: &AcemcpClient, project_path: &str) -> Result<Option<usize>> {
+    // 调用 search_context，触发自动索引；使用一个通用的查询，不关心搜索结果。
+    // 索引是 sidecar 内部的黑盒，同时按监听规则统计文件数供进度展示
+    let (search, files) = tokio::join!(
+        client.search_context(project_path, "preindex initialization"),
+        scan_project_files(PathBuf::from(project_path)),
+    );
+    search?;
 
-    // 调用 search_context，触发自动索引
-    // 使用一个通用的查询来触发索引，不关心搜索结果
-    let _ = client
-        .search_context(project_path, "preindex initialization")
-        .await;
+    Ok(files)
+}
 
-    // 关闭客户端
-    client.shutdown().await?;
+// ============================================================================
+// 增量重新索引
+// ============================================================================
+
+/// 文件变更的重新索引方式
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+pub enum ReindexMode {
+    /// sidecar 支持增量索引，只重新索引了变更文件
+    Incremental,
+    /// sidecar 不支持增量索引，重新同步了整个项目
+    Full,
+    /// 没有需要索引的文件，或预索引正在进行
+    Skipped,
+}
+
+/// 文件变更的合并窗口：保存时的一连串事件合并为一次重新索引
+const WATCH_DEBOUNCE_MS: u64 = 1000;
+
+/// 统计项目文件数的上限，超过后不再统计
+const WATCH_MAX_FILES: usize = 50_000;
+
+/// 不参与监听的目录（以 "." 开头的目录也会被跳过）
+const WATCH_IGNORED_DIRS: &[&str] = &[
+    "node_modules",
+    "target",
+    "dist",
+    "build",
+    "__pycache__",
+    "venv",
+];
+
+/// 进行中的项目文件监听
+///
+/// 丢弃 `_fs_watcher` 即停止接收文件系统事件。
+struct ProjectWatcher {
+    project_key: String,
+    _fs_watcher: notify::RecommendedWatcher,
+    handle: tauri::async_runtime::JoinHandle<()>,
+}
+
+/// 将变更路径转换为相对项目根目录的路径（统一使用 "/"），丢弃项目外的路径
+fn relative_changed_paths(project_path: &str, changed_paths: &[String]) -> Vec<String> {
+    let root = std::path::Path::new(project_path);
+    let mut files: Vec<String> = changed_paths
+        .iter()
+        .filter_map(|changed| {
+            let path = std::path::Path::new(changed);
+            let relative = if path.is_absolute() {
+                path.strip_prefix(root).ok()?
+            } else {
+                path
+            };
+            let relative = relative.to_string_lossy().replace('\\', "/");
+            (!relative.is_empty()).then_some(relative)
+        })
+        .collect();
+    files.sort();
+    files.dedup();
+    files
+}
+
+async fn reindex_changed_files_internal(
+    app: &AppHandle,
+    project_path: &str,
+    files: &[String],
+) -> Result<ReindexMode> {
+    let client = shared_client(app).await?;
+
+    let tools = client.list_tools().await.unwrap_or_default();
+    if tools.iter().any(|name| name == INCREMENTAL_INDEX_TOOL) {
+        match client.index_files(project_path, files).await {
+            Ok(()) => return Ok(ReindexMode::Incremental),
+            Err(e) => warn!("Incremental reindex failed, falling back to full: {}", e),
+        }
+    }
+
+    // search_context 会在搜索前同步整个项目的索引
+    client
+        .search_context(project_path, "reindex changed files")
+        .await?;
+    Ok(ReindexMode::Full)
+}
+
+async fn reindex_project_files(
+    app: &AppHandle,
+    state: &PreindexState,
+    project_path: &str,
+    changed_paths: &[String],
+) -> Result<ReindexMode, String> {
+    let files = relative_changed_paths(project_path, changed_paths);
+    if files.is_empty() {
+        return Ok(ReindexMode::Skipped);
+    }
+
+    // 进行中的预索引会包含这些变更
+    let key = normalize_path_for_comparison(project_path);
+    let preindexing = state
+        .tasks
+        .lock()
+        .map(|running| running.contains_key(&key))
+        .unwrap_or(false);
+    if preindexing {
+        info!(
+            "Pre-indexing running, skipping reindex for: {}",
+            project_path
+        );
+        return Ok(ReindexMode::Skipped);
+    }
+
+    let mode = reindex_changed_files_internal(app, project_path, &files)
+        .await
+        .map_err(|e| e.to_string())?;
+    info!(
+        "Reindexed {} changed file(s) in {} ({:?})",
+        files.len(),
+        project_path,
+        mode
+    );
+    Ok(mode)
+}
+
+/// 重新索引项目中发生变更的文件
+///
+/// sidecar 支持增量索引时只索引这些文件，否则退回完整重新索引。
+#[tauri::command]
+pub async fn reindex_changed_files(
+    app: AppHandle,
+    state: State<'_, PreindexState>,
+    project_path: String,
+    changed_paths: Vec<String>,
+) -> Result<ReindexMode, String> {
+    reindex_project_files(&app, &state, &project_path, &changed_paths).await
+}
+
+fn is_ignored_dir(entry: &walkdir::DirEntry) -> bool {
+    entry.file_type().is_dir()
+        && entry
+            .file_name()
+            .to_str()
+            .is_some_and(|name| name.starts_with('.') || WATCH_IGNORED_DIRS.contains(&name))
+}
+
+/// 统计项目内的文件数；文件数超过上限时返回 None
+fn count_project_files(root: &std::path::Path) -> Option<usize> {
+    let walker = walkdir::WalkDir::new(root)
+        .into_iter()
+        .filter_entry(|entry| entry.depth() == 0 || !is_ignored_dir(entry));
+
+    let mut count = 0;
+    for entry in walker.filter_map(|e| e.ok()) {
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        count += 1;
+        if count > WATCH_MAX_FILES {
+            return None;
+        }
+    }
+    Some(count)
+}
+
+async fn scan_project_files(root: PathBuf) -> Option<usize> {
+    tokio::task::spawn_blocking(move || count_project_files(&root))
+        .await
+        .ok()
+        .flatten()
+}
+
+/// 相对路径是否位于被忽略的目录中（以 "." 开头的目录或 `WATCH_IGNORED_DIRS`）
+fn is_ignored_path(relative: &std::path::Path) -> bool {
+    let mut components = relative.components().peekable();
+    while let Some(component) = components.next() {
+        // 最后一个组件是文件本身，只检查其所在的目录
+        if components.peek().is_none() {
+            break;
+        }
+        let std::path::Component::Normal(name) = component else {
+            continue;
+        };
+        if name
+            .to_str()
+            .is_some_and(|name| name.starts_with('.') || WATCH_IGNORED_DIRS.contains(&name))
+        {
+            return true;
+        }
+    }
+    false
+}
+
+/// 文件系统事件中需要重新索引的文件，返回相对 `root` 的路径
+///
+/// 跳过只读访问、目录本身、`root` 之外与被忽略目录中的文件。
+fn changed_event_paths(root: &std::path::Path, event: &notify::Event) -> Vec<String> {
+    if matches!(event.kind, notify::EventKind::Access(_)) {
+        return Vec::new();
+    }
+    event
+        .paths
+        .iter()
+        .filter(|path| !path.is_dir())
+        .filter_map(|path| path.strip_prefix(root).ok())
+        .filter(|relative| !is_ignored_path(relative))
+        .map(|relative| relative.to_string_lossy().replace('\\', "/"))
+        .collect()
+}
+
+/// 监听项目文件变更，保存后自动重新索引变更的文件
+///
+/// 同一时间只监听一个项目：监听新项目会停止之前的监听。
+#[tauri::command]
+pub async fn watch_project_changes(
+    app: AppHandle,
+    state: State<'_, PreindexState>,
+    project_path: String,
+) -> Result<(), String> {
+    if !std::path::Path::new(&project_path).is_dir() {
+        warn!(
+            "Project path does not exist, skipping watch: {}",
+            project_path
+        );
+        return Ok(());
+    }
+
+    let key = normalize_path_for_comparison(&project_path);
+    let mut watcher = state.watcher.lock().map_err(|e| e.to_string())?;
+    if let Some(current) = watcher.as_ref() {
+        if current.project_key == key {
+            return Ok(());
+        }
+    }
+    if let Some(previous) = watcher.take() {
+        previous.handle.abort();
+    }
+
+    // 监听规范化后的根目录，事件路径才能与之比较（如 macOS 的 /private 前缀）
+    let root = std::path::Path::new(&project_path)
+        .canonicalize()
+        .unwrap_or_else(|_| PathBuf::from(&project_path));
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<Vec<String>>();
+    let event_root = root.clone();
+    let mut fs_watcher =
+        notify::recommended_watcher(move |result: notify::Result<notify::Event>| match result {
+            Ok(event) => {
+                let paths = changed_event_paths(&event_root, &event);
+                if !paths.is_empty() {
+                    let _ = tx.send(paths);
+                }
+            }
+            Err(e) => warn!("Project watcher error: {}", e),
+        })
+        .map_err(|e| format!("Failed to create project watcher: {}", e))?;
+    notify::Watcher::watch(&mut fs_watcher, &root, notify::RecursiveMode::Recursive)
+        .map_err(|e| format!("Failed to watch {}: {}", project_path, e))?;
+
+    info!("Watching project for changes: {}", project_path);
+    let handle = tauri::async_runtime::spawn(async move {
+        while let Some(paths) = rx.recv().await {
+            // 合并窗口内的后续事件
+            tokio::time::sleep(Duration::from_millis(WATCH_DEBOUNCE_MS)).await;
+            let mut changed = paths;
+            while let Ok(more) = rx.try_recv() {
+                changed.extend(more);
+            }
+
+            let state = app.state::<PreindexState>();
+            if let Err(e) = reindex_project_files(&app, &state, &project_path, &changed).await {
+                warn!("Failed to reindex changed files in {}: {}", project_path, e);
+            }
+        }
+    });
+    *watcher = Some(ProjectWatcher {
+        project_key: key,
+        _fs_watcher: fs_watcher,
+        handle,
+    });
 
     Ok(())
 }
 
+/// 获取（必要时提取）sidecar 路径，供诊断使用
+pub(crate) fn sidecar_path() -> Result<PathBuf, String> {
+    AcemcpClient::get_or_extract_sidecar().map_err(|e| e.to_string())
+}
+
 // ============================================================================
 // Sidecar 导出（用于 CLI 配置）
 // ============================================================================
@@ -1679,6 +3506,8 @@ pub async fn export_acemcp_sidecar(target_path: String) -> Result<String, String
     } else {
         std::path::PathBuf::from(&target_path)
     };
+    // 绝对化并解析符号链接
+    let expanded_path = resolve_export_target("target_path", &expanded_path.to_string_lossy())?;
 
     info!("Expanded path: {:?}", expanded_path);
 
@@ -1741,3 +3570,497 @@ pub async fn get_extracted_sidecar_path() -> Result<Option<String>, String> {
         Ok(None)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keyword_preview_matches_multi_round_queries() {
+        let prompt = "修复 getUserInfo 接口的缓存问题";
+        let multi = preview_keywords(prompt, true);
+        assert!(multi.keywords.english.iter().any(|k| k.contains("user")));
+        assert_eq!(
+            multi.queries,
+            generate_multi_round_queries(&multi.keywords, true)
+        );
+        assert_eq!(multi.queries.first(), Some(&multi.keywords.combined));
+
+        let single = preview_keywords(prompt, false);
+        assert_eq!(single.queries, vec![single.keywords.combined.clone()]);
+    }
+
+    #[test]
+    fn only_responses_are_matched_to_requests() {
+        let response =
+            JsonRpcResponse::parse(r#"{"jsonrpc":"2.0","id":3,"result":{"ok":true}}"#).unwrap();
+        assert_eq!(response.request_id(), Some(3));
+
+        let string_id = JsonRpcResponse::parse(
+            r#"{"jsonrpc":"2.0","id":"4","error":{"code":-1,"message":"x"}}"#,
+        )
+        .unwrap();
+        assert_eq!(string_id.request_id(), Some(4));
+
+        // 通知与服务端请求不能消费等待中的请求
+        assert!(JsonRpcResponse::parse(
+            r#"{"jsonrpc":"2.0","method":"notifications/progress","params":{}}"#
+        )
+        .is_none());
+        assert!(
+            JsonRpcResponse::parse(r#"{"jsonrpc":"2.0","id":3,"method":"roots/list"}"#).is_none()
+        );
+        assert!(JsonRpcResponse::parse("server log line").is_none());
+    }
+
+    fn collect(results: &[&str]) -> SnippetCollector {
+        let mut collector = SnippetCollector::default();
+        for result in results {
+            collector.add(result);
+        }
+        collector
+    }
+
+    #[test]
+    fn snippet_collector_deduplicates_across_rounds() {
+        let collector = collect(&[
+            "Path: a.rs\nfn a() {}\n\nPath: b.rs\nfn b() {}",
+            "Path: b.rs\nfn b() {}",
+        ]);
+        // "b.rs" 在第二轮作为首个片段出现时前面没有 "\n\nPath:" 分隔符，仍按同一片段去重
+        assert_eq!(collector.len(), 2);
+    }
+
+    #[test]
+    fn identical_rounds_yield_each_snippet_once() {
+        let round = "Path: a.rs\nfn a() {}\n\nPath: b.rs\nfn b() {}";
+        let mut collector = SnippetCollector::default();
+        assert_eq!(collector.add(round).len(), 2);
+        assert!(collector.add(round).is_empty());
+        assert_eq!(collector.total_len(), round.len() - "\n\n".len());
+        assert_eq!(collector.into_combined(), round);
+    }
+
+    #[test]
+    fn concurrent_completion_order_matches_serial_result_set() {
+        let rounds = [
+            "Path: a.rs\nfn a() {}\n\nPath: shared.rs\nfn shared() {}",
+            "Path: b.rs\nfn b() {}\n\nPath: shared.rs\nfn shared() {}",
+            "Path: c.rs\nfn c() {}",
+        ];
+        let serial = collect(&rounds);
+        let concurrent = collect(&[rounds[2], rounds[0], rounds[1]]);
+
+        let serial_set: HashSet<String> = serial.snippets.iter().cloned().collect();
+        let concurrent_set: HashSet<String> = concurrent.snippets.iter().cloned().collect();
+        assert_eq!(serial_set, concurrent_set);
+        assert_eq!(serial.total_len(), concurrent.total_len());
+    }
+
+    #[test]
+    fn combined_output_restores_path_separators() {
+        let combined = collect(&["Path: a.rs\nfn a() {}\n\nPath: b.rs\nfn b() {}"]).into_combined();
+        assert_eq!(combined, "Path: a.rs\nfn a() {}\n\nPath: b.rs\nfn b() {}");
+    }
+
+    #[test]
+    fn add_reports_only_new_snippets_for_streaming() {
+        let mut collector = SnippetCollector::default();
+        let first: Vec<String> = collector
+            .add("Path: a.rs\nfn a() {}\n\nPath: b.rs\nfn b() {}")
+            .to_vec();
+        assert_eq!(
+            first,
+            vec!["Path: a.rs\nfn a() {}", "Path: b.rs\nfn b() {}"]
+        );
+
+        let second = collector.add("Path: a.rs\nfn a() {}\n\nPath: b.rs\nfn b() {}");
+        assert!(second.is_empty());
+    }
+
+    #[test]
+    fn multi_project_merge_dedups_across_projects_and_splits_budget() {
+        let contexts = vec![
+            (
+                "/work/api".to_string(),
+                "Path: shared.rs\nfn shared() {}\n\nPath: api.rs\nfn api() {}".to_string(),
+            ),
+            (
+                "/work/web".to_string(),
+                "Path: shared.rs\nfn shared() {}\n\nPath: web.rs\nfn web() {}".to_string(),
+            ),
+        ];
+
+        let (merged, counts) = merge_project_contexts(&contexts, 1000);
+        assert_eq!(counts, vec![2, 1]);
+        assert!(merged.starts_with("### 项目: api (/work/api)\nPath: shared.rs"));
+        assert!(merged.contains("### 项目: web (/work/web)\nPath: web.rs"));
+        assert_eq!(merged.matches("fn shared()").count(), 1);
+
+        let (_, counts) = merge_project_contexts(&contexts, 30);
+        assert_eq!(counts, vec![1, 1]);
+    }
+
+    #[test]
+    fn request_timeout_is_read_from_config() {
+        let config = parse_acemcp_config(
+            "BASE_URL = \"https://example.com\"\nTOKEN = \"t\"\nREQUEST_TIMEOUT_SECS = 180\n\
+             EXCLUDE_PATTERNS = [\"node_modules\"]\n",
+        );
+        assert_eq!(config.base_url, "https://example.com");
+        assert_eq!(config.request_timeout_secs, Some(180));
+        assert_eq!(
+            parse_acemcp_config("TOKEN = \"t\"\n").request_timeout_secs,
+            None
+        );
+        assert_eq!(
+            AcemcpConfigData::default().request_timeout_secs,
+            Some(DEFAULT_TOOL_CALL_TIMEOUT_SECS)
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn client_is_unhealthy_once_the_process_exits() {
+        let child = Command::new("cat")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .unwrap();
+        let client = AcemcpClient::from_child(child, DEFAULT_TOOL_CALL_TIMEOUT_SECS).unwrap();
+        assert!(client.is_healthy());
+
+        client.shutdown().await.unwrap();
+        tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+        assert!(!client.is_healthy());
+        assert!(client.write_message("{}").await.is_err());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn idle_client_is_pinged_before_reuse() {
+        // 对每个请求回复空结果的最小 JSON-RPC 服务端
+        let child = Command::new("sed")
+            .args([
+                "-u",
+                r#"s/.*"id":\([0-9]*\).*/{"jsonrpc":"2.0","id":\1,"result":{}}/"#,
+            ])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .unwrap();
+        let client = AcemcpClient::from_child(child, DEFAULT_TOOL_CALL_TIMEOUT_SECS).unwrap();
+        assert!(client.is_responsive().await);
+
+        *client.last_activity.lock().unwrap() = Instant::now()
+            .checked_sub(Duration::from_secs(PING_AFTER_IDLE_SECS + 1))
+            .unwrap();
+        assert!(client.idle_for() >= Duration::from_secs(PING_AFTER_IDLE_SECS));
+        assert!(client.is_responsive().await);
+        assert!(client.idle_for() < Duration::from_secs(PING_AFTER_IDLE_SECS));
+        assert!(!client.has_pending_requests());
+        assert!(client.is_healthy());
+
+        client.shutdown().await.unwrap();
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn cancelling_preindex_keeps_concurrent_requests_on_the_pooled_client() {
+        // 不回复预索引查询、其他请求延迟回复的 JSON-RPC 服务端
+        let script = r#"while IFS= read -r line; do
+  id=$(printf '%s' "$line" | sed -n 's/.*"id":\([0-9]*\).*/\1/p')
+  case "$line" in
+    *"preindex initialization"*) ;;
+    *) [ -n "$id" ] && (sleep 0.2; printf '{"jsonrpc":"2.0","id":%s,"result":{"content":[{"type":"text","text":"ok"}]}}\n' "$id") & ;;
+  esac
+done"#;
+        let child = Command::new("sh")
+            .args(["-c", script])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .unwrap();
+        let client =
+            Arc::new(AcemcpClient::from_child(child, DEFAULT_TOOL_CALL_TIMEOUT_SECS).unwrap());
+        let project = tempfile::tempdir().unwrap();
+        let project_path = project.path().to_string_lossy().into_owned();
+
+        let preindex = {
+            let (client, path) = (client.clone(), project_path.clone());
+            tokio::spawn(async move { index_project(&client, &path).await })
+        };
+        let search = {
+            let (client, path) = (client.clone(), project_path.clone());
+            tokio::spawn(async move { client.search_context(&path, "other query").await })
+        };
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(client.pending.lock().unwrap().len(), 2);
+
+        preindex.abort();
+        assert!(preindex.await.unwrap_err().is_cancelled());
+        assert_eq!(client.pending.lock().unwrap().len(), 1);
+
+        assert_eq!(search.await.unwrap().unwrap(), "ok");
+        assert!(!client.has_pending_requests());
+        assert!(client.is_healthy());
+
+        client.shutdown().await.unwrap();
+    }
+
+    #[test]
+    fn failed_preindex_error_is_kept_until_a_later_success() {
+        let statuses = StdMutex::new(HashMap::new());
+        let project = "/work/app";
+        assert_eq!(lookup_preindex_status(&statuses, project).unwrap(), None);
+
+        record_preindex_status(&statuses, project, PreindexStatus::Indexing, None);
+        record_preindex_status(
+            &statuses,
+            project,
+            PreindexStatus::Failed,
+            Some("sidecar exited"),
+        );
+        assert_eq!(
+            lookup_preindex_status(&statuses, project).unwrap(),
+            Some(PreindexStatusInfo {
+                status: PreindexStatus::Failed,
+                error: Some("sidecar exited".to_string()),
+            })
+        );
+
+        record_preindex_status(&statuses, project, PreindexStatus::Indexing, None);
+        record_preindex_status(&statuses, project, PreindexStatus::Completed, None);
+        assert_eq!(
+            lookup_preindex_status(&statuses, project).unwrap(),
+            Some(PreindexStatusInfo {
+                status: PreindexStatus::Completed,
+                error: None,
+            })
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn multi_round_search_drops_snippets_seen_in_earlier_rounds() {
+        // 第二轮延迟回复，结果与第一轮有两个片段重叠
+        let script = r#"while IFS= read -r line; do
+  id=$(printf '%s' "$line" | sed -n 's/.*"id":\([0-9]*\).*/\1/p')
+  [ -z "$id" ] && continue
+  case "$line" in
+    *'"query":"beta"'*) (sleep 0.2; printf '%s\n' "{\"jsonrpc\":\"2.0\",\"id\":$id,\"result\":{\"content\":[{\"type\":\"text\",\"text\":\"Path: a.rs\\nfn a()\\n\\nPath: shared.rs\\nfn shared()\\n\\nPath: b.rs\\nfn b()\"}]}}") & ;;
+    *) printf '%s\n' "{\"jsonrpc\":\"2.0\",\"id\":$id,\"result\":{\"content\":[{\"type\":\"text\",\"text\":\"Path: a.rs\\nfn a()\\n\\nPath: shared.rs\\nfn shared()\"}]}}" ;;
+  esac
+done"#;
+        let child = Command::new("sh")
+            .args(["-c", script])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .unwrap();
+        let client = AcemcpClient::from_child(child, DEFAULT_TOOL_CALL_TIMEOUT_SECS).unwrap();
+
+        let mut reported = Vec::new();
+        let result = client
+            .multi_round_search(
+                "/work/app",
+                &["alpha".to_string(), "beta".to_string()],
+                usize::MAX,
+                Duration::from_secs(5),
+                |round, snippet: &str| reported.push((round, snippet.to_string())),
+            )
+            .await;
+
+        assert_eq!(result.completed_rounds, 2);
+        assert!(!result.partial);
+        assert_eq!(
+            result.context,
+            "Path: a.rs\nfn a()\n\nPath: shared.rs\nfn shared()\n\nPath: b.rs\nfn b()"
+        );
+        // 第二轮只上报新出现的片段
+        assert_eq!(
+            reported,
+            vec![
+                (0, "Path: a.rs\nfn a()".to_string()),
+                (0, "Path: shared.rs\nfn shared()".to_string()),
+                (1, "Path: b.rs\nfn b()".to_string()),
+            ]
+        );
+
+        client.shutdown().await.unwrap();
+    }
+
+    #[test]
+    fn idle_timeout_is_read_from_config() {
+        let config = parse_acemcp_config("TOKEN = \"t\"\nIDLE_TIMEOUT_MINS = 3\n");
+        assert_eq!(config.idle_timeout_mins, Some(3));
+        assert_eq!(parse_acemcp_config("").idle_timeout_mins, None);
+        assert_eq!(
+            AcemcpConfigData::default().idle_timeout_mins,
+            Some(DEFAULT_IDLE_TIMEOUT_MINS)
+        );
+    }
+
+    #[test]
+    fn changed_paths_are_made_relative_to_project() {
+        let changed = vec![
+            "/work/api/src/main.rs".to_string(),
+            "src/lib.rs".to_string(),
+            "/work/web/index.ts".to_string(),
+            "/work/api/src/main.rs".to_string(),
+        ];
+        assert_eq!(
+            relative_changed_paths("/work/api", &changed),
+            vec!["src/lib.rs", "src/main.rs"]
+        );
+    }
+
+    #[test]
+    fn watch_events_keep_project_files_outside_ignored_dirs() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::create_dir_all(root.join("node_modules")).unwrap();
+        std::fs::write(root.join("src").join("main.rs"), "fn main() {}").unwrap();
+        std::fs::write(root.join("node_modules").join("dep.js"), "").unwrap();
+        assert_eq!(count_project_files(root), Some(1));
+
+        let event = notify::Event::new(notify::EventKind::Modify(notify::event::ModifyKind::Any))
+            .add_path(root.join("src").join("main.rs"))
+            .add_path(root.join("src").join("removed.rs"))
+            .add_path(root.join("src"))
+            .add_path(root.join("node_modules").join("dep.js"))
+            .add_path(root.join(".git").join("index"))
+            .add_path(std::path::PathBuf::from("/elsewhere/file.rs"));
+        assert_eq!(
+            changed_event_paths(root, &event),
+            vec!["src/main.rs", "src/removed.rs"]
+        );
+
+        let access = notify::Event::new(notify::EventKind::Access(notify::event::AccessKind::Any))
+            .add_path(root.join("src").join("main.rs"));
+        assert!(changed_event_paths(root, &access).is_empty());
+    }
+
+    #[test]
+    fn snippets_of_already_included_paths_are_excluded() {
+        let excluded = collect_excluded_paths(
+            "/work/app",
+            vec![
+                "@src/foo.rs".to_string(),
+                "/work/app/docs/".to_string(),
+                "./README.md".to_string(),
+            ],
+            false,
+        );
+        assert_eq!(excluded, vec!["README.md", "docs", "src/foo.rs"]);
+
+        let context = "Path: src/foo.rs\nfn foo() {}\n\nPath: src/foo.rs.bak\nold\n\n\
+                       Path: docs/guide.md\n# Guide\n\nPath: src/bar.rs\nfn bar() {}";
+        let (kept, count) = exclude_snippets(context, &excluded);
+        assert_eq!(count, 2);
+        assert_eq!(
+            kept,
+            "Path: src/foo.rs.bak\nold\n\nPath: src/bar.rs\nfn bar() {}"
+        );
+        assert_eq!(exclude_snippets(context, &[]), (context.to_string(), 0));
+    }
+
+    #[test]
+    fn user_keywords_merge_with_built_in_lists() {
+        let user = parse_keyword_config(
+            "abbreviations = [\"ISR\", \"dma\", \" \"]\n\
+             stopwords = [\"api\", \"Fix\"]\n\
+             chinese_words = [\"寄存器\", \"缓存\"]\n",
+        );
+        let lexicon = KeywordLexicon::merge(&user);
+
+        // User stopwords suppress built-in abbreviations and plain words
+        assert!(!lexicon.abbreviations.iter().any(|a| a == "api"));
+        assert!(lexicon.stopwords.contains("fix"));
+        assert!(lexicon.stopwords.contains("the"));
+        // User entries are appended after the built-in ones without duplicates
+        assert_eq!(lexicon.abbreviations.last().unwrap(), "dma");
+        assert!(lexicon.abbreviations.iter().any(|a| a == "isr"));
+        assert_eq!(
+            lexicon
+                .chinese_words
+                .iter()
+                .filter(|w| *w == "缓存")
+                .count(),
+            1
+        );
+
+        let keywords = extract_keywords_with("fix the api 中断 isr，dma 寄存器 io", &lexicon);
+        assert!(keywords.english.contains(&"isr".to_string()));
+        assert!(keywords.english.contains(&"dma".to_string()));
+        assert!(keywords.english.contains(&"io".to_string()));
+        assert!(!keywords.english.contains(&"api".to_string()));
+        assert!(!keywords.english.contains(&"fix".to_string()));
+        assert_eq!(keywords.chinese, vec!["寄存器"]);
+    }
+
+    #[test]
+    fn extra_keyword_keys_are_accepted() {
+        let user = parse_keyword_config(
+            "extra_abbreviations = [\"RTOS\"]\nextra_chinese_words = [\"固件\", \"中断\"]\n",
+        );
+        assert_eq!(user.abbreviations, vec!["RTOS"]);
+        assert_eq!(user.chinese_words, vec!["固件", "中断"]);
+
+        let keywords =
+            extract_keywords_with("升级 rtos 固件后中断丢失", &KeywordLexicon::merge(&user));
+        assert!(keywords.english.contains(&"rtos".to_string()));
+        assert!(keywords.chinese.contains(&"固件".to_string()));
+        assert!(keywords.chinese.contains(&"中断".to_string()));
+    }
+
+    #[test]
+    fn invalid_keyword_config_falls_back_to_built_in_lists() {
+        let user = parse_keyword_config("abbreviations = \"not a list\"\n[[");
+        assert_eq!(user, KeywordConfig::default());
+
+        let lexicon = KeywordLexicon::merge(&user);
+        assert_eq!(lexicon.abbreviations.len(), TECH_ABBREVIATIONS.len());
+        let keywords = extract_keywords_with("修复 api 缓存", &lexicon);
+        assert_eq!(keywords.english, vec!["api"]);
+        assert_eq!(keywords.chinese, vec!["修复", "缓存"]);
+    }
+
+    #[test]
+    fn context_is_parsed_into_structured_snippets() {
+        let context = "The following code sections were retrieved:\n\
+                       Path: src/foo.rs\n    10\tfn foo() {\n    11\t}\n...\n    20\tfn baz() {}\n\n\
+                       Path: src/bar.rs#L3-L5\nScore: 0.87\nfn bar() {}\n\n\
+                       Path: README.md\n# Title";
+        let snippets = parse_context_snippets(context);
+        assert_eq!(snippets.len(), 3);
+
+        assert_eq!(snippets[0].path, "src/foo.rs");
+        assert_eq!(
+            (snippets[0].start_line, snippets[0].end_line),
+            (Some(10), Some(20))
+        );
+        assert!(snippets[0].text.starts_with("    10\tfn foo() {"));
+        assert_eq!(snippets[0].score, None);
+
+        assert_eq!(snippets[1].path, "src/bar.rs");
+        assert_eq!(
+            (snippets[1].start_line, snippets[1].end_line),
+            (Some(3), Some(5))
+        );
+        assert_eq!(snippets[1].score, Some(0.87));
+        assert_eq!(snippets[1].text, "fn bar() {}");
+
+        // Unparseable fields stay empty instead of failing the whole result
+        assert_eq!(snippets[2].path, "README.md");
+        assert_eq!((snippets[2].start_line, snippets[2].end_line), (None, None));
+        assert_eq!(snippets[2].text, "# Title");
+
+        assert!(parse_context_snippets("no paths here").is_empty());
+    }
+}
@@ -0,0 +1,490 @@
+//! 命令错误码体系
+//!
+//! 执行与 provider 相关的 Tauri 命令返回 `AppError` 而不是拼接的字符串，序列化为：
+//! `{ code, message, details, retryable, suggestedAction }`
+//! - code：稳定的错误码（snake_case），前端据此做差异化处理（例如 401 显示"检查 API Key"）
+//! - message：按后端文案语言（中/英，见 messages）给出的说明；无法识别的错误保留原始文本，旧前端直接展示即可
+//! - details：原始错误文本（CLI stderr、HTTP 响应等），`SESSION_IN_USE`/`CONTEXT_OVERFLOW`
+//!   这类带协议前缀的错误在这里保持原样
+//!
+//! 内部仍以 `Result<_, String>` 传递错误，在命令边界通过 `From<String>` 或各引擎的分类函数
+//! 转换；内部调用方需要字符串时用 `String::from(AppError)` 取回原始文本。
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use super::codex::context::CONTEXT_OVERFLOW_ERROR_PREFIX;
+use super::execution_retry::contains_status_code;
+use super::input_validation::INVALID_ARGUMENT_ERROR_PREFIX;
+use super::messages::{current_locale, t_in, Locale, MessageKey};
+use crate::utils::session_lock::SESSION_IN_USE_ERROR_PREFIX;
+
+/// 错误码
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    /// API Key / Token 无效或未授权（401/403）
+    AuthFailed,
+    /// 请求过于频繁（429）
+    RateLimited,
+    /// 额度或余额不足
+    QuotaExceeded,
+    /// 模型不存在或不可用
+    ModelNotFound,
+    /// 请求超时
+    NetworkTimeout,
+    /// 无法连接到服务（连接被拒绝/重置、DNS 失败等）
+    NetworkError,
+    /// 服务端错误或过载（5xx、529）
+    ServiceUnavailable,
+    /// 找不到 CLI 可执行文件
+    BinaryNotFound,
+    /// 会话正被另一个进程写入
+    SessionBusy,
+    /// 会话为只读
+    SessionReadOnly,
+    /// 续接会话预计超出模型上下文窗口
+    ContextOverflow,
+    /// 工作区存在未提交的修改
+    GitDirty,
+    /// 命令参数不合法（路径越界、空值、超长）
+    InvalidArgument,
+    /// 无法归类的错误
+    Unknown,
+}
+
+/// 建议前端提供的操作
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SuggestedAction {
+    CheckApiKey,
+    WaitAndRetry,
+    CheckBilling,
+    ChooseModel,
+    CheckNetwork,
+    Retry,
+    InstallCli,
+    WaitForSession,
+    CompactSession,
+    CommitChanges,
+}
+
+impl ErrorCode {
+    /// 是否值得原样重试
+    pub fn retryable(self) -> bool {
+        matches!(
+            self,
+            ErrorCode::RateLimited
+                | ErrorCode::NetworkTimeout
+                | ErrorCode::NetworkError
+                | ErrorCode::ServiceUnavailable
+                | ErrorCode::SessionBusy
+        )
+    }
+
+    pub fn suggested_action(self) -> Option<SuggestedAction> {
+        match self {
+            ErrorCode::AuthFailed => Some(SuggestedAction::CheckApiKey),
+            ErrorCode::RateLimited => Some(SuggestedAction::WaitAndRetry),
+            ErrorCode::QuotaExceeded => Some(SuggestedAction::CheckBilling),
+            ErrorCode::ModelNotFound => Some(SuggestedAction::ChooseModel),
+            ErrorCode::NetworkTimeout | ErrorCode::NetworkError => {
+                Some(SuggestedAction::CheckNetwork)
+            }
+            ErrorCode::ServiceUnavailable => Some(SuggestedAction::Retry),
+            ErrorCode::BinaryNotFound => Some(SuggestedAction::InstallCli),
+            ErrorCode::SessionBusy => Some(SuggestedAction::WaitForSession),
+            ErrorCode::ContextOverflow => Some(SuggestedAction::CompactSession),
+            ErrorCode::GitDirty => Some(SuggestedAction::CommitChanges),
+            ErrorCode::SessionReadOnly | ErrorCode::InvalidArgument | ErrorCode::Unknown => None,
+        }
+    }
+
+    /// 错误码对应的说明文案
+    fn message_key(self) -> MessageKey {
+        match self {
+            ErrorCode::AuthFailed => MessageKey::ErrorAuthFailed,
+            ErrorCode::RateLimited => MessageKey::ErrorRateLimited,
+            ErrorCode::QuotaExceeded => MessageKey::ErrorQuotaExceeded,
+            ErrorCode::ModelNotFound => MessageKey::ErrorModelNotFound,
+            ErrorCode::NetworkTimeout => MessageKey::ErrorNetworkTimeout,
+            ErrorCode::NetworkError => MessageKey::ErrorNetworkError,
+            ErrorCode::ServiceUnavailable => MessageKey::ErrorServiceUnavailable,
+            ErrorCode::BinaryNotFound => MessageKey::ErrorBinaryNotFound,
+            ErrorCode::SessionBusy => MessageKey::ErrorSessionBusy,
+            ErrorCode::SessionReadOnly => MessageKey::ErrorSessionReadOnly,
+            ErrorCode::ContextOverflow => MessageKey::ErrorContextOverflow,
+            ErrorCode::GitDirty => MessageKey::ErrorGitDirty,
+            ErrorCode::InvalidArgument => MessageKey::ErrorInvalidArgument,
+            ErrorCode::Unknown => MessageKey::ErrorUnknown,
+        }
+    }
+}
+
+/// 命令返回的结构化错误
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AppError {
+    pub code: ErrorCode,
+    pub message: String,
+    pub details: Option<String>,
+    pub retryable: bool,
+    pub suggested_action: Option<SuggestedAction>,
+}
+
+impl AppError {
+    /// 使用当前语言的文案构造错误，原始文本放在 details
+    pub fn new(code: ErrorCode, details: impl Into<String>) -> Self {
+        Self::localized(code, details.into(), current_locale())
+    }
+
+    fn localized(code: ErrorCode, details: String, locale: Locale) -> Self {
+        // 无法识别的错误没有更好的说明，保留原始文本
+        let (message, details) = if code == ErrorCode::Unknown {
+            (details, None)
+        } else {
+            (t_in(code.message_key(), locale).to_string(), Some(details))
+        };
+        Self {
+            code,
+            message,
+            details,
+            retryable: code.retryable(),
+            suggested_action: code.suggested_action(),
+        }
+    }
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for AppError {}
+
+impl From<String> for AppError {
+    fn from(raw: String) -> Self {
+        AppError::new(classify(&raw, &[]), raw)
+    }
+}
+
+impl From<&str> for AppError {
+    fn from(raw: &str) -> Self {
+        AppError::from(raw.to_string())
+    }
+}
+
+/// 内部调用方取回原始错误文本，保留 `SESSION_IN_USE` 等协议前缀
+impl From<AppError> for String {
+    fn from(error: AppError) -> Self {
+        error.details.unwrap_or(error.message)
+    }
+}
+
+/// 各错误码的通用特征（小写匹配，按顺序取第一个命中的）
+const SHARED_PATTERNS: &[(ErrorCode, &[&str])] = &[
+    (
+        ErrorCode::BinaryNotFound,
+        &[
+            "cli not found",
+            "no working claude cli",
+            "binary not found",
+            "command not found",
+            "is not recognized as an internal or external command",
+            "program not found",
+        ],
+    ),
+    (
+        ErrorCode::QuotaExceeded,
+        &[
+            "insufficient_quota",
+            "exceeded your current quota",
+            "credit balance is too low",
+            "余额不足",
+        ],
+    ),
+    (
+        ErrorCode::AuthFailed,
+        &[
+            "unauthorized",
+            "invalid api key",
+            "invalid_api_key",
+            "incorrect api key",
+            "api key not valid",
+            "invalid x-api-key",
+            "authentication",
+            "forbidden",
+            "令牌无效",
+        ],
+    ),
+    (
+        ErrorCode::RateLimited,
+        &["rate limit", "rate_limit", "too many requests"],
+    ),
+    (
+        ErrorCode::ModelNotFound,
+        &[
+            "model not found",
+            "model_not_found",
+            "invalid model",
+            "unsupported model",
+            "model is not supported",
+        ],
+    ),
+    (
+        ErrorCode::NetworkTimeout,
+        &["timed out", "timeout", "etimedout", "超时"],
+    ),
+    (
+        ErrorCode::NetworkError,
+        &[
+            "connection refused",
+            "connection reset",
+            "connection closed",
+            "connection aborted",
+            "econnrefused",
+            "econnreset",
+            "enotfound",
+            "dns error",
+            "error sending request",
+            "socket hang up",
+            "stream disconnected",
+            "network error",
+        ],
+    ),
+    (
+        ErrorCode::ServiceUnavailable,
+        &[
+            "overloaded",
+            "bad gateway",
+            "service unavailable",
+            "internal server error",
+            "temporarily unavailable",
+        ],
+    ),
+    (
+        ErrorCode::GitDirty,
+        &[
+            "uncommitted changes",
+            "working tree is dirty",
+            "your local changes would be overwritten",
+            "未提交的修改",
+        ],
+    ),
+    (
+        ErrorCode::SessionReadOnly,
+        &["session is read-only", "read-only session", "会话为只读"],
+    ),
+];
+
+/// 特征都未命中时按 HTTP 状态码归类
+const STATUS_CODES: &[(&str, ErrorCode)] = &[
+    ("401", ErrorCode::AuthFailed),
+    ("403", ErrorCode::AuthFailed),
+    ("429", ErrorCode::RateLimited),
+    ("408", ErrorCode::NetworkTimeout),
+    ("504", ErrorCode::NetworkTimeout),
+    ("500", ErrorCode::ServiceUnavailable),
+    ("502", ErrorCode::ServiceUnavailable),
+    ("503", ErrorCode::ServiceUnavailable),
+    ("529", ErrorCode::ServiceUnavailable),
+];
+
+/// Claude CLI 特有的错误特征
+const CLAUDE_PATTERNS: &[(ErrorCode, &[&str])] = &[
+    (ErrorCode::AuthFailed, &["please run /login", "oauth token"]),
+    (
+        ErrorCode::ServiceUnavailable,
+        &["overloaded_error", "api_error"],
+    ),
+];
+
+/// Codex CLI 特有的错误特征
+const CODEX_PATTERNS: &[(ErrorCode, &[&str])] = &[
+    (
+        ErrorCode::RateLimited,
+        &["usage limit", "usage_limit_reached"],
+    ),
+    (
+        ErrorCode::ModelNotFound,
+        &["does not exist or you do not have access to it"],
+    ),
+    (ErrorCode::AuthFailed, &["codex login", "not logged in"]),
+];
+
+/// Gemini CLI（Google API 状态）特有的错误特征
+const GEMINI_PATTERNS: &[(ErrorCode, &[&str])] = &[
+    (
+        ErrorCode::AuthFailed,
+        &[
+            "api_key_invalid",
+            "unauthenticated",
+            "permission_denied",
+            "please set an auth method",
+        ],
+    ),
+    (ErrorCode::RateLimited, &["resource_exhausted"]),
+    (ErrorCode::NetworkTimeout, &["deadline_exceeded"]),
+    (ErrorCode::ModelNotFound, &["is not found for api version"]),
+];
+
+/// 按引擎特征、通用特征、HTTP 状态码的顺序归类错误文本
+fn classify(raw: &str, engine_patterns: &[(ErrorCode, &[&str])]) -> ErrorCode {
+    let trimmed = raw.trim_start();
+    if trimmed.starts_with(SESSION_IN_USE_ERROR_PREFIX) {
+        return ErrorCode::SessionBusy;
+    }
+    if trimmed.starts_with(CONTEXT_OVERFLOW_ERROR_PREFIX) {
+        return ErrorCode::ContextOverflow;
+    }
+    if trimmed.starts_with(INVALID_ARGUMENT_ERROR_PREFIX) {
+        return ErrorCode::InvalidArgument;
+    }
+
+    let text = raw.to_lowercase();
+    let matched = engine_patterns
+        .iter()
+        .chain(SHARED_PATTERNS)
+        .find(|(_, patterns)| patterns.iter().any(|p| text.contains(p)))
+        .map(|(code, _)| *code);
+    if let Some(code) = matched {
+        return code;
+    }
+
+    STATUS_CODES
+        .iter()
+        .find(|(status, _)| contains_status_code(&text, status))
+        .map(|(_, code)| *code)
+        .unwrap_or(ErrorCode::Unknown)
+}
+
+/// 归类 Claude CLI / Anthropic API 的错误
+pub fn classify_claude_error(raw: String) -> AppError {
+    AppError::new(classify(&raw, CLAUDE_PATTERNS), raw)
+}
+
+/// 归类 Codex CLI / OpenAI 兼容 API 的错误
+pub fn classify_codex_error(raw: String) -> AppError {
+    AppError::new(classify(&raw, CODEX_PATTERNS), raw)
+}
+
+/// 归类 Gemini CLI / Google API 的错误
+pub fn classify_gemini_error(raw: String) -> AppError {
+    AppError::new(classify(&raw, GEMINI_PATTERNS), raw)
+}
+
+/// 按引擎归类错误，未知引擎只使用通用特征
+pub fn classify_engine_error(engine: &str, raw: String) -> AppError {
+    match engine {
+        "claude" => classify_claude_error(raw),
+        "codex" => classify_codex_error(raw),
+        "gemini" => classify_gemini_error(raw),
+        _ => AppError::from(raw),
+    }
+}
+
+/// 归类执行过程中通过事件上报的错误文本（stderr、错误事件），供前端展示本地化说明
+#[tauri::command]
+pub fn classify_execution_error(engine: String, message: String) -> AppError {
+    classify_engine_error(&engine, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_shared_and_engine_specific_errors() {
+        let cases = [
+            (
+                "",
+                "API Error: 401 {\"type\":\"error\"}",
+                ErrorCode::AuthFailed,
+            ),
+            (
+                "claude",
+                "Invalid API key · Please run /login",
+                ErrorCode::AuthFailed,
+            ),
+            (
+                "claude",
+                "API Error: 529 overloaded_error",
+                ErrorCode::ServiceUnavailable,
+            ),
+            (
+                "codex",
+                "You've hit your usage limit",
+                ErrorCode::RateLimited,
+            ),
+            (
+                "codex",
+                "stream error: error sending request for url",
+                ErrorCode::NetworkError,
+            ),
+            (
+                "gemini",
+                "[API Error: RESOURCE_EXHAUSTED]",
+                ErrorCode::RateLimited,
+            ),
+            (
+                "gemini",
+                "models/gemini-x is not found for API version v1beta",
+                ErrorCode::ModelNotFound,
+            ),
+            (
+                "",
+                "Claude CLI not found. 请安装",
+                ErrorCode::BinaryNotFound,
+            ),
+            (
+                "",
+                "订阅信息查询失败: 504 Gateway Timeout - ",
+                ErrorCode::NetworkTimeout,
+            ),
+            ("", "Provider with ID 'x' not found", ErrorCode::Unknown),
+        ];
+        for (engine, raw, expected) in cases {
+            assert_eq!(
+                classify_engine_error(engine, raw.to_string()).code,
+                expected,
+                "{}",
+                raw
+            );
+        }
+    }
+
+    #[test]
+    fn protocol_errors_keep_raw_text_for_internal_callers() {
+        let raw = format!("{}: {{\"pid\":1}}", SESSION_IN_USE_ERROR_PREFIX);
+        let error = AppError::from(raw.clone());
+        assert_eq!(error.code, ErrorCode::SessionBusy);
+        assert!(error.retryable);
+        assert_eq!(String::from(error), raw);
+
+        let invalid = AppError::from(super::super::input_validation::invalid_argument(
+            "path",
+            "is outside of /tmp",
+        ));
+        assert_eq!(invalid.code, ErrorCode::InvalidArgument);
+        assert!(!invalid.retryable);
+
+        let unknown = AppError::from("something odd");
+        assert_eq!(unknown.message, "something odd");
+        assert_eq!(unknown.details, None);
+        assert_eq!(String::from(unknown), "something odd");
+    }
+
+    #[test]
+    fn messages_follow_language() {
+        let zh = AppError::localized(ErrorCode::RateLimited, "429".into(), Locale::ZhCn);
+        let en = AppError::localized(ErrorCode::RateLimited, "429".into(), Locale::EnUs);
+        assert_ne!(zh.message, en.message);
+        assert_eq!(zh.message, "请求过于频繁，已被限流，请稍后重试");
+
+        let json = serde_json::to_value(&en).unwrap();
+        assert_eq!(json["code"], "rate_limited");
+        assert_eq!(json["suggestedAction"], "wait_and_retry");
+        assert_eq!(json["details"], "429");
+    }
+}
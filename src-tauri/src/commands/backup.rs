@@ -0,0 +1,1164 @@
+//! 会话与项目数据的定时备份
+//!
+//! 备份写到用户指定的目标目录（可以是 NAS 的挂载路径），按日期组织：
+//! `<目标目录>/anycode-backups/<YYYY-MM-DD_HHMMSS>/<分类>/<数据源>/<相对路径>`，
+//! 每份备份根目录下的 manifest.json 记录文件清单（大小与 mtime）。
+//! - 分类：会话、git-records、应用数据（~/.anycode 与应用数据目录）、配置文件
+//! - 基于 mtime 的文件级增量：与上一份备份相比未变化的文件以硬链接复用，
+//!   目标文件系统不支持硬链接时退回复制；每份备份都是完整的目录镜像，可以单独删除
+//! - 备份先写入 `.partial` 目录，完成后再改名，中断的备份不会被当作可用备份
+//! - 完成后只保留最近 N 份，更早的自动清理
+//! - 定时任务每 10 分钟检查一次是否到期，有任务运行时顺延到下一次检查
+//! - 还原按分类选择，默认只补回缺失的文件；选择覆盖时才替换与备份不同的现有文件，
+//!   被覆盖的文件先移到 ~/.anycode/backup-safety/<时间>/ 下（开启加密存储时加密保存）
+//!
+//! 数据源按本机 home 目录定位（不跟随 WSL 模式）。
+//! 持久化：~/.anycode/backup.json（默认关闭）
+
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::commands::codex::CodexProcessState;
+use crate::commands::encrypted_storage;
+use crate::commands::gemini::GeminiProcessState;
+use crate::commands::messages::{t, t_with, MessageKey};
+use crate::process::ProcessRegistryState;
+use crate::utils::config_utils::{load_json_config, save_json_config, ConfigPathBuilder};
+
+/// 目标目录下存放备份的子目录
+const BACKUP_DIR_NAME: &str = "anycode-backups";
+const MANIFEST_FILE: &str = "manifest.json";
+const PARTIAL_SUFFIX: &str = ".partial";
+const BACKUP_ID_FORMAT: &str = "%Y-%m-%d_%H%M%S";
+
+/// 定时检查间隔；启动后先等待一段时间，避开启动时的读写高峰
+const SCHEDULE_CHECK_INTERVAL: Duration = Duration::from_secs(10 * 60);
+const SCHEDULE_INITIAL_DELAY: Duration = Duration::from_secs(2 * 60);
+
+/// 每处理多少个文件上报一次进度
+const PROGRESS_EVENT_EVERY: usize = 200;
+
+/// 同一时间只允许一个备份或还原
+static BACKUP_RUNNING: AtomicBool = AtomicBool::new(false);
+
+/// 备份分类
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum BackupCategory {
+    Sessions,
+    GitRecords,
+    AppData,
+    Config,
+}
+
+impl BackupCategory {
+    const ALL: [BackupCategory; 4] = [
+        BackupCategory::Sessions,
+        BackupCategory::GitRecords,
+        BackupCategory::AppData,
+        BackupCategory::Config,
+    ];
+
+    /// 备份目录中的子目录名
+    fn dir_name(self) -> &'static str {
+        match self {
+            BackupCategory::Sessions => "sessions",
+            BackupCategory::GitRecords => "git-records",
+            BackupCategory::AppData => "app-data",
+            BackupCategory::Config => "config",
+        }
+    }
+}
+
+/// 备份频率
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum BackupFrequency {
+    Daily,
+    Weekly,
+}
+
+impl BackupFrequency {
+    fn interval_secs(self) -> i64 {
+        match self {
+            BackupFrequency::Daily => 24 * 60 * 60,
+            BackupFrequency::Weekly => 7 * 24 * 60 * 60,
+        }
+    }
+}
+
+/// 备份配置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct BackupConfig {
+    /// 是否按计划自动备份（手动备份不受影响）
+    pub enabled: bool,
+    pub target_dir: Option<String>,
+    pub frequency: BackupFrequency,
+    pub categories: Vec<BackupCategory>,
+    /// 保留最近几份备份
+    pub keep_count: u32,
+}
+
+impl Default for BackupConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            target_dir: None,
+            frequency: BackupFrequency::Daily,
+            categories: BackupCategory::ALL.to_vec(),
+            keep_count: 7,
+        }
+    }
+}
+
+/// manifest 中的一个文件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ManifestFile {
+    category: BackupCategory,
+    /// 数据源名称（见 `backup_sources`）
+    source: String,
+    /// 相对数据源根目录的路径，统一使用 `/`
+    path: String,
+    size: u64,
+    /// 备份时源文件的 mtime（Unix 毫秒）
+    mtime_ms: i64,
+}
+
+/// 每份备份根目录下的 manifest.json
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BackupManifest {
+    id: String,
+    /// 完成时间（UTC Unix 秒）
+    created_at: i64,
+    /// "manual" | "scheduled"
+    trigger: String,
+    categories: Vec<BackupCategory>,
+    files: Vec<ManifestFile>,
+    total_bytes: u64,
+}
+
+/// 备份列表中的一项
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupInfo {
+    pub id: String,
+    pub created_at: i64,
+    pub trigger: String,
+    pub categories: Vec<BackupCategory>,
+    pub file_count: usize,
+    pub total_bytes: u64,
+    pub path: String,
+}
+
+/// 一次备份的结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupResult {
+    pub backup: BackupInfo,
+    /// 新复制的文件数（其余文件复用上一份备份）
+    pub copied_files: usize,
+    pub linked_files: usize,
+    pub copied_bytes: u64,
+    /// 按保留份数清理掉的旧备份
+    pub removed_backups: Vec<String>,
+    /// 读取失败而跳过的文件
+    pub errors: Vec<String>,
+}
+
+/// 备份进度（`backup-progress` 事件）
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupProgress {
+    pub backup_id: String,
+    /// "scanning" | "copying" | "cleanup"
+    pub phase: &'static str,
+    pub processed: usize,
+    pub total: usize,
+}
+
+/// 备份结束（`backup-finished` 事件），成功时带结果，失败时带错误
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupFinished {
+    pub trigger: String,
+    pub result: Option<BackupResult>,
+    pub error: Option<String>,
+}
+
+/// 还原结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RestoreResult {
+    pub backup_id: String,
+    pub restored_files: usize,
+    /// 与备份一致、无需还原的文件
+    pub unchanged_files: usize,
+    /// 与备份不同、但未选择覆盖而保留的现有文件
+    pub skipped_existing: usize,
+    /// 被覆盖前移到 safety 目录的现有文件
+    pub moved_to_safety: usize,
+    pub safety_dir: Option<String>,
+    pub errors: Vec<String>,
+}
+
+// ============================================================================
+// Sources
+// ============================================================================
+
+/// 备份数据源：某个分类下的一个目录，按相对路径过滤
+struct BackupSource {
+    category: BackupCategory,
+    name: &'static str,
+    root: PathBuf,
+    /// 只遍历到这一层（配置文件只取根目录下的文件）
+    max_depth: usize,
+    include: fn(&Path) -> bool,
+}
+
+/// 数据源定位所需的目录
+#[derive(Debug, Clone)]
+struct BackupPaths {
+    home: PathBuf,
+    app_data_dir: Option<PathBuf>,
+}
+
+impl BackupPaths {
+    fn from_app(app: &AppHandle) -> Result<Self, String> {
+        Ok(Self {
+            home: dirs::home_dir().ok_or("Failed to get home directory")?,
+            app_data_dir: app.path().app_data_dir().ok(),
+        })
+    }
+
+    fn safety_root(&self) -> PathBuf {
+        self.home.join(".anycode").join("backup-safety")
+    }
+}
+
+fn is_git_records(path: &Path) -> bool {
+    path.to_string_lossy().ends_with(".git-records.json")
+}
+
+fn is_session_data(path: &Path) -> bool {
+    !is_git_records(path)
+}
+
+fn any_file(_: &Path) -> bool {
+    true
+}
+
+/// ~/.anycode 下不备份日志与还原产生的 safety 目录
+fn is_app_data(path: &Path) -> bool {
+    !matches!(
+        path.components()
+            .next()
+            .and_then(|c| c.as_os_str().to_str()),
+        Some("logs") | Some("backup-safety")
+    )
+}
+
+/// 配置文件（凭据类文件如 auth.json 不在其中）
+fn is_config_file(path: &Path) -> bool {
+    matches!(
+        path.to_str(),
+        Some(
+            ".claude.json"
+                | "settings.json"
+                | "CLAUDE.md"
+                | "AGENTS.md"
+                | "GEMINI.md"
+                | "config.toml"
+        )
+    )
+}
+
+fn backup_sources(paths: &BackupPaths) -> Vec<BackupSource> {
+    let home = &paths.home;
+    let dir = |category, name, root: PathBuf, include: fn(&Path) -> bool| BackupSource {
+        category,
+        name,
+        root,
+        max_depth: usize::MAX,
+        include,
+    };
+    let config = |name, root: PathBuf| BackupSource {
+        category: BackupCategory::Config,
+        name,
+        root,
+        max_depth: 1,
+        include: is_config_file,
+    };
+
+    let mut sources = vec![
+        dir(
+            BackupCategory::Sessions,
+            "claude-projects",
+            home.join(".claude").join("projects"),
+            is_session_data,
+        ),
+        dir(
+            BackupCategory::Sessions,
+            "codex-sessions",
+            home.join(".codex").join("sessions"),
+            any_file,
+        ),
+        dir(
+            BackupCategory::Sessions,
+            "gemini-tmp",
+            home.join(".gemini").join("tmp"),
+            any_file,
+        ),
+        dir(
+            BackupCategory::GitRecords,
+            "claude-projects",
+            home.join(".claude").join("projects"),
+            is_git_records,
+        ),
+        dir(
+            BackupCategory::GitRecords,
+            "codex-git-records",
+            home.join(".codex").join("git-records"),
+            any_file,
+        ),
+        dir(
+            BackupCategory::GitRecords,
+            "gemini-git-records",
+            home.join(".gemini").join("git-records"),
+            any_file,
+        ),
+        dir(
+            BackupCategory::AppData,
+            "anycode",
+            home.join(".anycode"),
+            is_app_data,
+        ),
+        config("home", home.clone()),
+        config("claude", home.join(".claude")),
+        config("codex", home.join(".codex")),
+        config("gemini", home.join(".gemini")),
+        config("acemcp", home.join(".acemcp")),
+    ];
+    if let Some(app_data_dir) = &paths.app_data_dir {
+        sources.push(dir(
+            BackupCategory::AppData,
+            "app-data",
+            app_data_dir.clone(),
+            any_file,
+        ));
+    }
+    sources
+}
+
+// ============================================================================
+// Backup
+// ============================================================================
+
+fn mtime_ms(metadata: &fs::Metadata) -> i64 {
+    metadata
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or(0)
+}
+
+fn relative_to_path(relative: &str) -> PathBuf {
+    relative.split('/').collect()
+}
+
+/// 是否为单个普通路径段（非空，不含分隔符、`.`、`..` 或盘符）
+fn is_plain_name(name: &str) -> bool {
+    let mut components = Path::new(name).components();
+    matches!(
+        (components.next(), components.next()),
+        (Some(Component::Normal(normal)), None) if normal == name
+    )
+}
+
+/// manifest 中的相对路径只允许普通路径段，拒绝 `..`、绝对路径与盘符
+fn safe_relative_path(relative: &str) -> Option<PathBuf> {
+    relative
+        .split('/')
+        .all(is_plain_name)
+        .then(|| relative_to_path(relative))
+}
+
+/// 文件在某份备份中的位置
+fn file_in_backup(backup_dir: &Path, file: &ManifestFile) -> PathBuf {
+    backup_dir
+        .join(file.category.dir_name())
+        .join(&file.source)
+        .join(relative_to_path(&file.path))
+}
+
+/// 复制文件并保留 mtime
+fn copy_preserving_mtime(src: &Path, dest: &Path, mtime_ms: i64) -> std::io::Result<u64> {
+    if let Some(parent) = dest.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let bytes = fs::copy(src, dest)?;
+    if mtime_ms > 0 {
+        let modified = UNIX_EPOCH + Duration::from_millis(mtime_ms as u64);
+        fs::File::options()
+            .write(true)
+            .open(dest)?
+            .set_modified(modified)?;
+    }
+    Ok(bytes)
+}
+
+/// 扫描数据源，跳过位于 `excluded` 之下的路径（目标目录可能在数据源内）
+fn scan_source(source: &BackupSource, excluded: &[PathBuf]) -> Vec<(ManifestFile, PathBuf)> {
+    if !source.root.is_dir() {
+        return Vec::new();
+    }
+    walkdir::WalkDir::new(&source.root)
+        .max_depth(source.max_depth)
+        .into_iter()
+        .filter_entry(|entry| !excluded.iter().any(|ex| entry.path().starts_with(ex)))
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_file())
+        .filter_map(|entry| {
+            let relative = entry.path().strip_prefix(&source.root).ok()?;
+            if !(source.include)(relative) {
+                return None;
+            }
+            let metadata = entry.metadata().ok()?;
+            let path = relative
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            Some((
+                ManifestFile {
+                    category: source.category,
+                    source: source.name.to_string(),
+                    path,
+                    size: metadata.len(),
+                    mtime_ms: mtime_ms(&metadata),
+                },
+                entry.path().to_path_buf(),
+            ))
+        })
+        .collect()
+}
+
+fn read_manifest(backup_dir: &Path) -> Option<BackupManifest> {
+    let content = fs::read_to_string(backup_dir.join(MANIFEST_FILE)).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+/// 列出完整的备份（按时间从新到旧）
+fn list_complete_backups(backup_root: &Path) -> Vec<(PathBuf, BackupManifest)> {
+    let Ok(entries) = fs::read_dir(backup_root) else {
+        return Vec::new();
+    };
+    let mut backups: Vec<(PathBuf, BackupManifest)> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir() && !path.to_string_lossy().ends_with(PARTIAL_SUFFIX))
+        .filter_map(|path| read_manifest(&path).map(|manifest| (path, manifest)))
+        .collect();
+    backups.sort_by(|a, b| b.1.id.cmp(&a.1.id));
+    backups
+}
+
+fn to_info(dir: &Path, manifest: &BackupManifest) -> BackupInfo {
+    BackupInfo {
+        id: manifest.id.clone(),
+        created_at: manifest.created_at,
+        trigger: manifest.trigger.clone(),
+        categories: manifest.categories.clone(),
+        file_count: manifest.files.len(),
+        total_bytes: manifest.total_bytes,
+        path: dir.to_string_lossy().to_string(),
+    }
+}
+
+/// 生成不与已有目录冲突的备份 ID
+fn new_backup_id(backup_root: &Path, now: chrono::DateTime<Local>) -> String {
+    let base = now.format(BACKUP_ID_FORMAT).to_string();
+    let mut id = base.clone();
+    let mut n = 1;
+    while backup_root.join(&id).exists()
+        || backup_root
+            .join(format!("{}{}", id, PARTIAL_SUFFIX))
+            .exists()
+    {
+        id = format!("{}-{}", base, n);
+        n += 1;
+    }
+    id
+}
+
+/// 删除超出保留份数的旧备份与中断遗留的 `.partial` 目录
+fn prune_backups(backup_root: &Path, keep_count: u32, current_partial: &Path) -> Vec<String> {
+    let mut removed = Vec::new();
+    for (dir, manifest) in list_complete_backups(backup_root)
+        .into_iter()
+        .skip(keep_count.max(1) as usize)
+    {
+        match fs::remove_dir_all(&dir) {
+            Ok(()) => removed.push(manifest.id),
+            Err(e) => log::warn!("[Backup] Failed to remove old backup {:?}: {}", dir, e),
+        }
+    }
+
+    if let Ok(entries) = fs::read_dir(backup_root) {
+        for path in entries.flatten().map(|entry| entry.path()) {
+            if path != current_partial && path.to_string_lossy().ends_with(PARTIAL_SUFFIX) {
+                if let Err(e) = fs::remove_dir_all(&path) {
+                    log::warn!("[Backup] Failed to remove partial backup {:?}: {}", path, e);
+                }
+            }
+        }
+    }
+    removed
+}
+
+fn run_backup_blocking(
+    paths: &BackupPaths,
+    backup_root: &Path,
+    categories: &[BackupCategory],
+    keep_count: u32,
+    trigger: &str,
+    on_progress: &mut dyn FnMut(BackupProgress),
+) -> Result<BackupResult, String> {
+    fs::create_dir_all(backup_root)
+        .map_err(|e| format!("Failed to create backup directory {:?}: {}", backup_root, e))?;
+
+    let id = new_backup_id(backup_root, Local::now());
+    let partial_dir = backup_root.join(format!("{}{}", id, PARTIAL_SUFFIX));
+    let mut progress = |phase, processed, total| {
+        on_progress(BackupProgress {
+            backup_id: id.clone(),
+            phase,
+            processed,
+            total,
+        })
+    };
+
+    progress("scanning", 0, 0);
+    let excluded = [backup_root.to_path_buf(), paths.safety_root()];
+    let files: Vec<(ManifestFile, PathBuf)> = backup_sources(paths)
+        .iter()
+        .filter(|source| categories.contains(&source.category))
+        .flat_map(|source| scan_source(source, &excluded))
+        .collect();
+
+    // 上一份备份中大小与 mtime 都相同的文件直接复用
+    let previous = list_complete_backups(backup_root).into_iter().next();
+    let previous_files: HashMap<(BackupCategory, &str, &str), &ManifestFile> = previous
+        .as_ref()
+        .map(|(_, manifest)| {
+            manifest
+                .files
+                .iter()
+                .map(|f| ((f.category, f.source.as_str(), f.path.as_str()), f))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let total = files.len();
+    let mut manifest_files = Vec::with_capacity(total);
+    let mut copied_files = 0;
+    let mut linked_files = 0;
+    let mut copied_bytes = 0;
+    let mut errors = Vec::new();
+
+    for (index, (file, src)) in files.into_iter().enumerate() {
+        if index % PROGRESS_EVENT_EVERY == 0 {
+            progress("copying", index, total);
+        }
+        let dest = file_in_backup(&partial_dir, &file);
+
+        let unchanged = previous_files
+            .get(&(file.category, file.source.as_str(), file.path.as_str()))
+            .filter(|prev| prev.size == file.size && prev.mtime_ms == file.mtime_ms);
+        if let (Some(prev), Some((previous_dir, _))) = (unchanged, &previous) {
+            let previous_path = file_in_backup(previous_dir, prev);
+            if let Some(parent) = dest.parent() {
+                let _ = fs::create_dir_all(parent);
+            }
+            if fs::hard_link(&previous_path, &dest).is_ok() {
+                linked_files += 1;
+                manifest_files.push(file);
+                continue;
+            }
+        }
+
+        match copy_preserving_mtime(&src, &dest, file.mtime_ms) {
+            Ok(bytes) => {
+                copied_files += 1;
+                copied_bytes += bytes;
+                manifest_files.push(file);
+            }
+            // 正在写入或已被删除的文件跳过，不影响整份备份
+            Err(e) => errors.push(format!("{}: {}", src.display(), e)),
+        }
+    }
+
+    let manifest = BackupManifest {
+        id: id.clone(),
+        created_at: chrono::Utc::now().timestamp(),
+        trigger: trigger.to_string(),
+        categories: categories.to_vec(),
+        total_bytes: manifest_files.iter().map(|f| f.size).sum(),
+        files: manifest_files,
+    };
+    fs::create_dir_all(&partial_dir)
+        .map_err(|e| format!("Failed to create backup directory: {}", e))?;
+    let manifest_json = serde_json::to_string(&manifest)
+        .map_err(|e| format!("Failed to serialize backup manifest: {}", e))?;
+    fs::write(partial_dir.join(MANIFEST_FILE), manifest_json)
+        .map_err(|e| format!("Failed to write backup manifest: {}", e))?;
+
+    let backup_dir = backup_root.join(&id);
+    fs::rename(&partial_dir, &backup_dir)
+        .map_err(|e| format!("Failed to finalize backup {}: {}", id, e))?;
+
+    progress("cleanup", total, total);
+    let removed_backups = prune_backups(backup_root, keep_count, &partial_dir);
+
+    log::info!(
+        "[Backup] {} backup {} done: {} files ({} copied, {} reused), {} removed, {} errors",
+        trigger,
+        id,
+        manifest.files.len(),
+        copied_files,
+        linked_files,
+        removed_backups.len(),
+        errors.len()
+    );
+
+    Ok(BackupResult {
+        backup: to_info(&backup_dir, &manifest),
+        copied_files,
+        linked_files,
+        copied_bytes,
+        removed_backups,
+        errors,
+    })
+}
+
+// ============================================================================
+// Restore
+// ============================================================================
+
+/// 移动文件，跨文件系统时退回复制 + 删除
+fn move_file(src: &Path, dest: &Path) -> std::io::Result<()> {
+    if let Some(parent) = dest.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    if fs::rename(src, dest).is_ok() {
+        return Ok(());
+    }
+    fs::copy(src, dest)?;
+    fs::remove_file(src)
+}
+
+/// 把将被覆盖的文件移到 safety 目录，开启加密存储时与回收站一样加密写入
+fn move_to_safety(src: &Path, dest: &Path) -> Result<(), String> {
+    if !encrypted_storage::encryption_enabled() {
+        return move_file(src, dest).map_err(|e| e.to_string());
+    }
+    let content = fs::read(src).map_err(|e| format!("Failed to read {:?}: {}", src, e))?;
+    encrypted_storage::write_protected(dest, &content)?;
+    fs::remove_file(src).map_err(|e| format!("Failed to remove {:?}: {}", src, e))
+}
+
+/// 备份 ID 必须是备份根目录下的单个目录名
+fn validate_backup_id(backup_id: &str) -> Result<(), String> {
+    if is_plain_name(backup_id) && !backup_id.ends_with(PARTIAL_SUFFIX) {
+        Ok(())
+    } else {
+        Err(format!("Invalid backup id: {}", backup_id))
+    }
+}
+
+/// 从备份还原所选分类；`overwrite` 为 false 时只补回缺失的文件
+fn restore_blocking(
+    paths: &BackupPaths,
+    backup_root: &Path,
+    backup_id: &str,
+    categories: &[BackupCategory],
+    overwrite: bool,
+) -> Result<RestoreResult, String> {
+    validate_backup_id(backup_id)?;
+    if overwrite {
+        // 开启加密时 safety 文件需要密钥，先确认已解锁，避免还原到一半才失败
+        encrypted_storage::ensure_writable()?;
+    }
+    let backup_dir = backup_root.join(backup_id);
+    let manifest = read_manifest(&backup_dir)
+        .ok_or_else(|| t_with(MessageKey::BackupNotFound, &[("id", &backup_id)]))?;
+
+    let roots: HashMap<(BackupCategory, &str), PathBuf> = backup_sources(paths)
+        .into_iter()
+        .map(|source| ((source.category, source.name), source.root))
+        .collect();
+    let safety_dir = paths
+        .safety_root()
+        .join(Local::now().format(BACKUP_ID_FORMAT).to_string());
+
+    let mut result = RestoreResult {
+        backup_id: backup_id.to_string(),
+        restored_files: 0,
+        unchanged_files: 0,
+        skipped_existing: 0,
+        moved_to_safety: 0,
+        safety_dir: None,
+        errors: Vec::new(),
+    };
+
+    for file in manifest
+        .files
+        .iter()
+        .filter(|f| categories.contains(&f.category))
+    {
+        let Some(root) = roots.get(&(file.category, file.source.as_str())) else {
+            result.errors.push(format!(
+                "{}/{}: unknown data source",
+                file.source, file.path
+            ));
+            continue;
+        };
+        let Some(relative) = safe_relative_path(&file.path) else {
+            result
+                .errors
+                .push(format!("{}/{}: invalid path", file.source, file.path));
+            continue;
+        };
+        let dest = root.join(relative);
+
+        if let Ok(metadata) = fs::metadata(&dest) {
+            if metadata.len() == file.size && mtime_ms(&metadata) == file.mtime_ms {
+                result.unchanged_files += 1;
+                continue;
+            }
+            if !overwrite {
+                result.skipped_existing += 1;
+                continue;
+            }
+            let safety_path = file_in_backup(&safety_dir, file);
+            if let Err(e) = move_to_safety(&dest, &safety_path) {
+                result
+                    .errors
+                    .push(format!("{}: failed to move aside: {}", dest.display(), e));
+                continue;
+            }
+            result.moved_to_safety += 1;
+        }
+
+        match copy_preserving_mtime(&file_in_backup(&backup_dir, file), &dest, file.mtime_ms) {
+            Ok(_) => result.restored_files += 1,
+            Err(e) => result.errors.push(format!("{}: {}", dest.display(), e)),
+        }
+    }
+
+    if result.moved_to_safety > 0 {
+        result.safety_dir = Some(safety_dir.to_string_lossy().to_string());
+    }
+    log::info!(
+        "[Backup] Restored {} files from {} ({} unchanged, {} kept, {} moved to safety, {} errors)",
+        result.restored_files,
+        backup_id,
+        result.unchanged_files,
+        result.skipped_existing,
+        result.moved_to_safety,
+        result.errors.len()
+    );
+    Ok(result)
+}
+
+// ============================================================================
+// Scheduling
+// ============================================================================
+
+fn get_backup_config_path() -> Result<PathBuf, String> {
+    Ok(ConfigPathBuilder::from_home_subdir(".anycode")?.build("backup.json"))
+}
+
+fn load_backup_config() -> Result<BackupConfig, String> {
+    load_json_config(get_backup_config_path()?)
+}
+
+fn backup_root_for(config: &BackupConfig) -> Result<PathBuf, String> {
+    config
+        .target_dir
+        .as_deref()
+        .map(str::trim)
+        .filter(|dir| !dir.is_empty())
+        .map(|dir| PathBuf::from(dir).join(BACKUP_DIR_NAME))
+        .ok_or_else(|| t(MessageKey::BackupTargetNotSet))
+}
+
+/// 距上一份备份是否已满一个周期
+fn backup_due(last_backup_at: Option<i64>, frequency: BackupFrequency, now: i64) -> bool {
+    last_backup_at.is_none_or(|last| now - last >= frequency.interval_secs())
+}
+
+/// 是否有 Claude / Codex / Gemini 任务正在运行
+async fn has_running_tasks(app: &AppHandle) -> bool {
+    if let Some(registry) = app.try_state::<ProcessRegistryState>() {
+        if registry
+            .0
+            .get_running_claude_sessions()
+            .is_ok_and(|sessions| !sessions.is_empty())
+        {
+            return true;
+        }
+    }
+    if let Some(state) = app.try_state::<CodexProcessState>() {
+        if !state.processes.lock().await.is_empty() {
+            return true;
+        }
+    }
+    if let Some(state) = app.try_state::<GeminiProcessState>() {
+        if !state.processes.lock().await.is_empty() {
+            return true;
+        }
+    }
+    false
+}
+
+/// 持有期间标记备份/还原进行中
+struct RunningGuard;
+
+impl RunningGuard {
+    fn acquire() -> Result<Self, String> {
+        BACKUP_RUNNING
+            .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+            .map(|_| RunningGuard)
+            .map_err(|_| t(MessageKey::BackupAlreadyRunning))
+    }
+}
+
+impl Drop for RunningGuard {
+    fn drop(&mut self) {
+        BACKUP_RUNNING.store(false, Ordering::SeqCst);
+    }
+}
+
+/// 执行一次备份，通过 `backup-progress` / `backup-finished` 事件报告进度与结果
+async fn run_backup(app: &AppHandle, trigger: &'static str) -> Result<BackupResult, String> {
+    let result = async {
+        let _guard = RunningGuard::acquire()?;
+        let config = load_backup_config()?;
+        let backup_root = backup_root_for(&config)?;
+        let paths = BackupPaths::from_app(app)?;
+        let progress_app = app.clone();
+
+        tokio::task::spawn_blocking(move || {
+            run_backup_blocking(
+                &paths,
+                &backup_root,
+                &config.categories,
+                config.keep_count,
+                trigger,
+                &mut |progress| {
+                    let _ = progress_app.emit("backup-progress", progress);
+                },
+            )
+        })
+        .await
+        .map_err(|e| format!("Backup task failed: {}", e))?
+    }
+    .await;
+
+    if let Err(e) = &result {
+        log::warn!("[Backup] {} backup failed: {}", trigger, e);
+    }
+    let _ = app.emit(
+        "backup-finished",
+        BackupFinished {
+            trigger: trigger.to_string(),
+            result: result.as_ref().ok().cloned(),
+            error: result.as_ref().err().cloned(),
+        },
+    );
+    result
+}
+
+/// 定时备份：到期且没有任务运行时执行，有任务运行时顺延到下一次检查
+pub async fn run_backup_scheduler(app: AppHandle) {
+    tokio::time::sleep(SCHEDULE_INITIAL_DELAY).await;
+    loop {
+        if let Ok(config) = load_backup_config() {
+            if config.enabled {
+                check_scheduled_backup(&app, &config).await;
+            }
+        }
+        tokio::time::sleep(SCHEDULE_CHECK_INTERVAL).await;
+    }
+}
+
+async fn check_scheduled_backup(app: &AppHandle, config: &BackupConfig) {
+    let Ok(backup_root) = backup_root_for(config) else {
+        return;
+    };
+    let last_backup_at = tokio::task::spawn_blocking(move || {
+        list_complete_backups(&backup_root)
+            .first()
+            .map(|(_, manifest)| manifest.created_at)
+    })
+    .await
+    .ok()
+    .flatten();
+    if !backup_due(
+        last_backup_at,
+        config.frequency,
+        chrono::Utc::now().timestamp(),
+    ) {
+        return;
+    }
+    if has_running_tasks(app).await {
+        log::info!("[Backup] Scheduled backup postponed: tasks are running");
+        return;
+    }
+    let _ = run_backup(app, "scheduled").await;
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/// 获取备份配置
+#[tauri::command]
+pub async fn get_backup_config() -> Result<BackupConfig, String> {
+    load_backup_config()
+}
+
+/// 保存备份配置
+#[tauri::command]
+pub async fn update_backup_config(config: BackupConfig) -> Result<(), String> {
+    save_json_config(&config, &get_backup_config_path()?)
+}
+
+/// 立即备份（不等待计划、不检查任务运行）
+#[tauri::command]
+pub async fn backup_now(app: AppHandle) -> Result<BackupResult, String> {
+    run_backup(&app, "manual").await
+}
+
+/// 列出目标目录中的备份（从新到旧）
+#[tauri::command]
+pub async fn list_backups() -> Result<Vec<BackupInfo>, String> {
+    let backup_root = backup_root_for(&load_backup_config()?)?;
+    tokio::task::spawn_blocking(move || {
+        list_complete_backups(&backup_root)
+            .iter()
+            .map(|(dir, manifest)| to_info(dir, manifest))
+            .collect()
+    })
+    .await
+    .map_err(|e| format!("Failed to list backups: {}", e))
+}
+
+/// 从指定备份还原所选分类，默认只补回缺失的文件，`overwrite` 为 true 时覆盖有改动的文件
+#[tauri::command]
+pub async fn restore_from_backup(
+    app: AppHandle,
+    backup_id: String,
+    categories: Vec<BackupCategory>,
+    overwrite: Option<bool>,
+) -> Result<RestoreResult, String> {
+    let _guard = RunningGuard::acquire()?;
+    let backup_root = backup_root_for(&load_backup_config()?)?;
+    let paths = BackupPaths::from_app(&app)?;
+    let overwrite = overwrite.unwrap_or(false);
+    tokio::task::spawn_blocking(move || {
+        restore_blocking(&paths, &backup_root, &backup_id, &categories, overwrite)
+    })
+    .await
+    .map_err(|e| format!("Restore task failed: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(path: &Path, content: &str) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    #[test]
+    fn deleted_session_is_restored_from_incremental_backup() {
+        let dir = tempfile::tempdir().unwrap();
+        let paths = BackupPaths {
+            home: dir.path().join("home"),
+            app_data_dir: None,
+        };
+        let project = paths
+            .home
+            .join(".claude")
+            .join("projects")
+            .join("-work-app");
+        write(&project.join("s1.jsonl"), "{\"type\":\"user\"}\n");
+        write(&project.join("s2.jsonl"), "{\"type\":\"user\"}\n");
+        write(&project.join("sessions").join("s1.git-records.json"), "{}");
+        write(&paths.home.join(".claude").join("settings.json"), "{}");
+        write(&paths.home.join(".codex").join("auth.json"), "secret");
+        let backup_root = dir.path().join("nas").join(BACKUP_DIR_NAME);
+
+        let first = run_backup_blocking(
+            &paths,
+            &backup_root,
+            &BackupCategory::ALL,
+            7,
+            "manual",
+            &mut |_| {},
+        )
+        .unwrap();
+        assert_eq!(first.copied_files, 4);
+        assert!(first.errors.is_empty());
+
+        let second = run_backup_blocking(
+            &paths,
+            &backup_root,
+            &BackupCategory::ALL,
+            1,
+            "scheduled",
+            &mut |_| {},
+        )
+        .unwrap();
+        assert_eq!(second.linked_files + second.copied_files, 4);
+        assert_eq!(second.removed_backups, vec![first.backup.id.clone()]);
+        assert_eq!(list_complete_backups(&backup_root).len(), 1);
+
+        fs::remove_file(project.join("s1.jsonl")).unwrap();
+        write(&project.join("s2.jsonl"), "edited after backup\n");
+        let restored = restore_blocking(
+            &paths,
+            &backup_root,
+            &second.backup.id,
+            &[BackupCategory::Sessions],
+            false,
+        )
+        .unwrap();
+
+        // 默认只补回缺失的文件，改动过的会话保持不变
+        assert_eq!(restored.restored_files, 1);
+        assert_eq!(restored.skipped_existing, 1);
+        assert_eq!(restored.moved_to_safety, 0);
+        assert_eq!(
+            fs::read_to_string(project.join("s1.jsonl")).unwrap(),
+            "{\"type\":\"user\"}\n"
+        );
+        assert_eq!(
+            fs::read_to_string(project.join("s2.jsonl")).unwrap(),
+            "edited after backup\n"
+        );
+
+        let restored = restore_blocking(
+            &paths,
+            &backup_root,
+            &second.backup.id,
+            &[BackupCategory::Sessions],
+            true,
+        )
+        .unwrap();
+        assert_eq!(restored.restored_files, 1);
+        assert_eq!(restored.moved_to_safety, 1);
+        assert_eq!(
+            fs::read_to_string(project.join("s2.jsonl")).unwrap(),
+            "{\"type\":\"user\"}\n"
+        );
+        let safety = PathBuf::from(restored.safety_dir.unwrap())
+            .join("sessions")
+            .join("claude-projects")
+            .join("-work-app")
+            .join("s2.jsonl");
+        assert_eq!(fs::read_to_string(safety).unwrap(), "edited after backup\n");
+        assert!(restore_blocking(&paths, &backup_root, "missing", &[], false).is_err());
+    }
+
+    #[test]
+    fn restore_rejects_paths_outside_the_backup_and_data_roots() {
+        let dir = tempfile::tempdir().unwrap();
+        let paths = BackupPaths {
+            home: dir.path().join("home"),
+            app_data_dir: None,
+        };
+        let backup_root = dir.path().join("nas").join(BACKUP_DIR_NAME);
+        write(
+            &dir.path().join("nas").join("other").join(MANIFEST_FILE),
+            "{}",
+        );
+        for id in ["../other", "..", "", "a/b", "2025-01-01_000000.partial"] {
+            assert!(validate_backup_id(id).is_err(), "{}", id);
+        }
+        assert!(restore_blocking(&paths, &backup_root, "../other", &[], false).is_err());
+
+        let backup_dir = backup_root.join("2025-01-01_000000");
+        let file = |path: &str| ManifestFile {
+            category: BackupCategory::Sessions,
+            source: "codex-sessions".to_string(),
+            path: path.to_string(),
+            size: 4,
+            mtime_ms: 0,
+        };
+        let manifest = BackupManifest {
+            id: "2025-01-01_000000".to_string(),
+            created_at: 0,
+            trigger: "manual".to_string(),
+            categories: vec![BackupCategory::Sessions],
+            files: vec![
+                file("../../../escaped.txt"),
+                file("/abs.txt"),
+                file("ok.jsonl"),
+            ],
+            total_bytes: 12,
+        };
+        write(
+            &backup_dir.join(MANIFEST_FILE),
+            &serde_json::to_string(&manifest).unwrap(),
+        );
+        for name in ["escaped.txt", "abs.txt", "ok.jsonl"] {
+            write(
+                &backup_dir
+                    .join("sessions")
+                    .join("codex-sessions")
+                    .join(name),
+                "data",
+            );
+        }
+
+        let restored = restore_blocking(
+            &paths,
+            &backup_root,
+            "2025-01-01_000000",
+            &[BackupCategory::Sessions],
+            false,
+        )
+        .unwrap();
+        assert_eq!(restored.restored_files, 1);
+        assert_eq!(restored.errors.len(), 2);
+        assert!(paths
+            .home
+            .join(".codex")
+            .join("sessions")
+            .join("ok.jsonl")
+            .exists());
+        assert!(!paths.home.join("escaped.txt").exists());
+    }
+
+    #[test]
+    fn scheduled_backup_is_due_after_one_period() {
+        let day = BackupFrequency::Daily.interval_secs();
+        assert!(backup_due(None, BackupFrequency::Daily, 0));
+        assert!(!backup_due(Some(0), BackupFrequency::Daily, day - 1));
+        assert!(backup_due(Some(0), BackupFrequency::Daily, day));
+        assert!(!backup_due(Some(0), BackupFrequency::Weekly, day));
+    }
+}
@@ -0,0 +1,219 @@
+//! 二进制检测设置与缓存
+//!
+//! Claude / Codex / Gemini 的 CLI 可能同时装在 PATH、nvm、volta、bun、Homebrew 等多处。
+//! 这里暴露检测到的全部候选及其来源，并允许在 ~/.claude/binaries.json 中为每个工具
+//! 设置来源优先顺序与黑名单路径。检测结果缓存 10 分钟，修改设置或手动刷新时失效。
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::claude_binary::{self, BinaryDetection, BinarySearchSection, BINARY_ORIGINS};
+use crate::commands::codex::config::get_binary_override;
+
+/// 单个工具的检测设置（对应 binaries.json 中的同名段）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct BinaryDetectionSettings {
+    /// 来源优先顺序（path/nvm/volta/bun/homebrew/custom）
+    pub source_priority: Vec<String>,
+    /// 黑名单路径（前缀匹配）
+    pub blocked_paths: Vec<String>,
+}
+
+/// 工具名 → (可执行文件名, 环境变量)
+fn tool_spec(tool: &str) -> Result<(&'static str, &'static str), String> {
+    match tool {
+        "claude" => Ok(("claude", "CLAUDE_PATH")),
+        "codex" => Ok(("codex", "CODEX_PATH")),
+        "gemini" => Ok(("gemini", "GEMINI_CLI_PATH")),
+        _ => Err(format!("Unsupported tool: {}", tool)),
+    }
+}
+
+fn binaries_config_path() -> Result<std::path::PathBuf, String> {
+    let home = dirs::home_dir().ok_or("Cannot find home directory".to_string())?;
+    Ok(home.join(".claude").join("binaries.json"))
+}
+
+fn read_binaries_json() -> Result<serde_json::Value, String> {
+    let config_path = binaries_config_path()?;
+    if !config_path.exists() {
+        return Ok(serde_json::json!({}));
+    }
+    let content = std::fs::read_to_string(&config_path)
+        .map_err(|e| format!("Failed to read binaries.json: {}", e))?;
+    Ok(serde_json::from_str(&content).unwrap_or_else(|_| serde_json::json!({})))
+}
+
+/// 去除空白项与重复项，并校验来源标签
+fn normalize_settings(
+    settings: BinaryDetectionSettings,
+) -> Result<BinaryDetectionSettings, String> {
+    let mut source_priority: Vec<String> = Vec::new();
+    for origin in settings.source_priority {
+        let origin = origin.trim().to_lowercase();
+        if !BINARY_ORIGINS.contains(&origin.as_str()) {
+            return Err(format!(
+                "Unknown binary source '{}', expected one of: {}",
+                origin,
+                BINARY_ORIGINS.join(", ")
+            ));
+        }
+        if !source_priority.contains(&origin) {
+            source_priority.push(origin);
+        }
+    }
+
+    let mut blocked_paths: Vec<String> = Vec::new();
+    for path in settings.blocked_paths {
+        let path = path.trim().to_string();
+        if !path.is_empty() && !blocked_paths.contains(&path) {
+            blocked_paths.push(path);
+        }
+    }
+
+    Ok(BinaryDetectionSettings {
+        source_priority,
+        blocked_paths,
+    })
+}
+
+/// 清除检测缓存；Claude 还会清除 agents.db 中自动检测记下的路径（用户自定义路径除外）
+fn invalidate_tool_cache(app: &AppHandle, tool: Option<&str>) {
+    claude_binary::invalidate_binary_cache(tool);
+
+    if tool.is_some_and(|t| t != "claude") || get_binary_override("claude").is_some() {
+        return;
+    }
+    if let Ok(app_data_dir) = app.path().app_data_dir() {
+        let db_path = app_data_dir.join("agents.db");
+        if db_path.exists() {
+            if let Ok(conn) = rusqlite::Connection::open(&db_path) {
+                let _ = conn.execute(
+                    "DELETE FROM app_settings WHERE key = 'claude_binary_path'",
+                    [],
+                );
+            }
+        }
+    }
+}
+
+/// 读取某个工具的检测设置
+#[tauri::command]
+pub async fn get_binary_detection_settings(
+    tool: String,
+) -> Result<BinaryDetectionSettings, String> {
+    tool_spec(&tool)?;
+    let json = read_binaries_json()?;
+    let section: BinarySearchSection = json
+        .get(&tool)
+        .cloned()
+        .and_then(|section| serde_json::from_value(section).ok())
+        .unwrap_or_default();
+    Ok(BinaryDetectionSettings {
+        source_priority: section.source_priority,
+        blocked_paths: section.blocked_paths,
+    })
+}
+
+/// 保存某个工具的检测设置（保留 binaries.json 中的其他字段），并使缓存失效
+#[tauri::command]
+pub async fn save_binary_detection_settings(
+    app: AppHandle,
+    tool: String,
+    settings: BinaryDetectionSettings,
+) -> Result<BinaryDetectionSettings, String> {
+    tool_spec(&tool)?;
+    let settings = normalize_settings(settings)?;
+
+    let config_path = binaries_config_path()?;
+    if let Some(parent) = config_path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create config directory: {}", e))?;
+    }
+
+    let mut json = read_binaries_json()?;
+    let section = json
+        .as_object_mut()
+        .ok_or("Invalid binaries.json format (not an object)".to_string())?;
+    let entry = section
+        .entry(tool.clone())
+        .or_insert_with(|| serde_json::json!({}));
+    if let Some(obj) = entry.as_object_mut() {
+        obj.insert(
+            "source_priority".to_string(),
+            serde_json::json!(settings.source_priority),
+        );
+        obj.insert(
+            "blocked_paths".to_string(),
+            serde_json::json!(settings.blocked_paths),
+        );
+    }
+
+    let serialized = serde_json::to_string_pretty(&json)
+        .map_err(|e| format!("Failed to serialize binaries.json: {}", e))?;
+    std::fs::write(&config_path, serialized)
+        .map_err(|e| format!("Failed to write binaries.json: {}", e))?;
+
+    invalidate_tool_cache(&app, Some(&tool));
+    log::info!("Saved binary detection settings for {}", tool);
+    Ok(settings)
+}
+
+/// 列出某个工具检测到的全部候选（首个为最终使用的二进制）
+///
+/// `refresh` 为 true 时忽略缓存重新检测
+#[tauri::command]
+pub async fn list_binary_candidates(
+    app: AppHandle,
+    tool: String,
+    refresh: Option<bool>,
+) -> Result<BinaryDetection, String> {
+    let (binary, env_var) = tool_spec(&tool)?;
+    if refresh.unwrap_or(false) {
+        invalidate_tool_cache(&app, Some(&tool));
+    }
+    tauri::async_runtime::spawn_blocking(move || {
+        claude_binary::detect_binary_candidates(binary, env_var, &tool)
+    })
+    .await
+    .map_err(|e| format!("Binary detection task failed: {}", e))
+}
+
+/// 使检测缓存失效，下次使用时重新检测；`tool` 为空时清空全部工具
+#[tauri::command]
+pub async fn invalidate_binary_cache(app: AppHandle, tool: Option<String>) -> Result<(), String> {
+    if let Some(tool) = &tool {
+        tool_spec(tool)?;
+    }
+    invalidate_tool_cache(&app, tool.as_deref());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_settings_dedupes_and_lowercases() {
+        let settings = normalize_settings(BinaryDetectionSettings {
+            source_priority: vec!["Volta".into(), "nvm".into(), "volta".into()],
+            blocked_paths: vec![" /usr/local/bin/claude ".into(), "".into()],
+        })
+        .unwrap();
+
+        assert_eq!(settings.source_priority, vec!["volta", "nvm"]);
+        assert_eq!(settings.blocked_paths, vec!["/usr/local/bin/claude"]);
+    }
+
+    #[test]
+    fn normalize_settings_rejects_unknown_source() {
+        let err = normalize_settings(BinaryDetectionSettings {
+            source_priority: vec!["scoop".into()],
+            blocked_paths: Vec::new(),
+        })
+        .unwrap_err();
+
+        assert!(err.contains("scoop"));
+    }
+}
@@ -6,11 +6,29 @@ use tauri::{AppHandle, Emitter, Manager};
 use tokio::process::{Child, Command};
 use tokio::sync::Mutex;
 
+use crate::commands::app_error::{classify_claude_error, AppError};
+use crate::commands::execution_trace::{
+    record_trace_event, resolve_trace_id, TraceFields, STAGE_EXECUTION_STARTED,
+    STAGE_PROCESS_SPAWNED, STAGE_SESSION_BOUND,
+};
 use crate::commands::permission_config::{
     build_execution_args, ClaudeExecutionConfig, ClaudePermissionConfig,
 };
+use crate::commands::process_cancel::{
+    check_session_file, request_exit_and_wait, CancelMode, CancelOutcome, SessionFileCheck,
+    GRACEFUL_EXIT_TIMEOUT,
+};
+use crate::commands::project_memory::compiled_memory_for_execution;
+use crate::commands::prompt_enhancement::{enhance_for_execution, EnhancementRequest};
+use crate::commands::protected_paths::protected_paths_instruction;
+use crate::commands::rate_limiter;
+use crate::commands::session_alias::{handle_session_id_drift, resolve_session_alias};
+use crate::commands::session_invalidation::invalidate_session;
+use crate::commands::session_utils::resolve_session_file;
+use crate::commands::tool_timing::LiveToolTimer;
 #[cfg(windows)]
 use crate::process::JobObject;
+use crate::utils::session_lock::{lock_for_execution, SessionLockHeartbeat};
 
 use super::config::get_claude_execution_config;
 use super::paths::{encode_project_path, get_claude_dir};
@@ -255,18 +273,62 @@ fn create_windows_command(
     cmd.stdout(Stdio::piped());
     cmd.stderr(Stdio::piped());
 
-    // Apply platform-specific no-window configuration
-    platform::apply_no_window_async(&mut cmd);
+    // Hide the console window and start a new process group
+    // This allows cancellation to interrupt the whole process tree with a single signal
+    platform::apply_cancellable_process_async(&mut cmd);
 
-    // On Unix-like systems, create a new process group
-    // This allows us to kill the entire process tree with a single signal
-    #[cfg(unix)]
-    {
-        use std::os::unix::process::CommandExt;
-        cmd.process_group(0); // Create new process group, process becomes group leader
+    Ok(cmd)
+}
+
+/// 通过 --append-system-prompt 注入编译后的项目记忆（开启时）与禁改区约束（不改动用户提示词）
+fn append_system_prompt_args(
+    args: &mut Vec<String>,
+    project_path: &str,
+    use_project_memory: Option<bool>,
+) {
+    let mut sections = Vec::new();
+    if use_project_memory.unwrap_or(false) {
+        if let Some(memory) = compiled_memory_for_execution(project_path) {
+            log::info!(
+                "Appending project memory to system prompt ({} chars)",
+                memory.chars().count()
+            );
+            sections.push(memory);
+        }
+    }
+    if let Some(instruction) = protected_paths_instruction(project_path) {
+        log::info!("Appending protected paths to system prompt");
+        sections.push(instruction);
     }
+    if !sections.is_empty() {
+        args.push("--append-system-prompt".to_string());
+        args.push(sections.join("\n\n"));
+    }
+}
 
-    Ok(cmd)
+/// 带有 enhancement 时，启动 CLI 前先用 acemcp 项目上下文增强提示词
+///
+/// 续接会话时（`session_id` 已知）结合该会话的对话历史生成检索查询
+async fn enhance_claude_prompt(
+    app: &AppHandle,
+    project_path: &str,
+    session_id: Option<&str>,
+    prompt: String,
+    enhancement: Option<EnhancementRequest>,
+    trace_id: &str,
+) -> Result<String, String> {
+    let Some(mut request) = enhancement else {
+        return Ok(prompt);
+    };
+    if let Some(session_id) = session_id {
+        request
+            .session_id
+            .get_or_insert_with(|| session_id.to_string());
+        request
+            .project_id
+            .get_or_insert_with(|| encode_project_path(project_path));
+    }
+    enhance_for_execution(app, project_path, prompt, request, trace_id).await
 }
 
 /// Execute Claude Code session with project context resume and streaming output
@@ -281,7 +343,10 @@ pub async fn execute_claude_code(
     plan_mode: Option<bool>,
     max_thinking_tokens: Option<u32>,
     tab_id: Option<String>,
-) -> Result<(), String> {
+    use_project_memory: Option<bool>,
+    trace_id: Option<String>,
+    enhancement: Option<EnhancementRequest>,
+) -> Result<(), AppError> {
     let plan_mode = plan_mode.unwrap_or(false);
     log::info!(
         "Starting Claude Code session with project context resume in: {} with model: {}, plan_mode: {}",
@@ -289,6 +354,9 @@ pub async fn execute_claude_code(
         model,
         plan_mode
     );
+    let trace_id = start_claude_trace(trace_id, "execute", &project_path, &model, plan_mode);
+    let prompt =
+        enhance_claude_prompt(&app, &project_path, None, prompt, enhancement, &trace_id).await?;
 
     let claude_path = crate::claude_binary::find_claude_binary(&app)?;
 
@@ -321,7 +389,8 @@ pub async fn execute_claude_code(
     // 使用新的参数构建函数（先映射模型名称）
     // 🔥 修复：prompt 不再通过命令行参数传递，改为 stdin 管道传递
     let mapped_model = map_model_to_claude_alias(&model);
-    let args = build_execution_args(&execution_config, &mapped_model);
+    let mut args = build_execution_args(&execution_config, &mapped_model);
+    append_system_prompt_args(&mut args, &project_path, use_project_memory);
 
     // Create command
     let cmd = create_system_command(
@@ -331,7 +400,20 @@ pub async fn execute_claude_code(
         Some(&mapped_model),
         max_thinking_tokens,
     )?;
-    spawn_claude_process(app, cmd, prompt, model, project_path, tab_id).await
+    spawn_claude_process(
+        app,
+        cmd,
+        prompt,
+        model,
+        project_path,
+        plan_mode,
+        tab_id,
+        trace_id,
+        None,
+        None,
+    )
+    .await
+    .map_err(classify_claude_error)
 }
 
 /// Continue an existing Claude Code conversation with streaming output
@@ -345,7 +427,10 @@ pub async fn continue_claude_code(
     plan_mode: Option<bool>,
     max_thinking_tokens: Option<u32>,
     tab_id: Option<String>,
-) -> Result<(), String> {
+    use_project_memory: Option<bool>,
+    trace_id: Option<String>,
+    enhancement: Option<EnhancementRequest>,
+) -> Result<(), AppError> {
     let plan_mode = plan_mode.unwrap_or(false);
     log::info!(
         "Continuing Claude Code conversation in: {} with model: {}, plan_mode: {}",
@@ -353,6 +438,9 @@ pub async fn continue_claude_code(
         model,
         plan_mode
     );
+    let trace_id = start_claude_trace(trace_id, "continue", &project_path, &model, plan_mode);
+    let prompt =
+        enhance_claude_prompt(&app, &project_path, None, prompt, enhancement, &trace_id).await?;
 
     let claude_path = crate::claude_binary::find_claude_binary(&app)?;
 
@@ -386,6 +474,7 @@ pub async fn continue_claude_code(
     // 🔥 修复：prompt 不再通过命令行参数传递，改为 stdin 管道传递
     let mapped_model = map_model_to_claude_alias(&model);
     let mut args = build_execution_args(&execution_config, &mapped_model);
+    append_system_prompt_args(&mut args, &project_path, use_project_memory);
 
     // 在开头插入 -c 标志
     args.insert(0, "-c".to_string());
@@ -398,7 +487,20 @@ pub async fn continue_claude_code(
         Some(&mapped_model),
         max_thinking_tokens,
     )?;
-    spawn_claude_process(app, cmd, prompt, model, project_path, tab_id).await
+    spawn_claude_process(
+        app,
+        cmd,
+        prompt,
+        model,
+        project_path,
+        plan_mode,
+        tab_id,
+        trace_id,
+        None,
+        None,
+    )
+    .await
+    .map_err(classify_claude_error)
 }
 
 /// Resume an existing Claude Code session by ID with streaming output
@@ -413,8 +515,13 @@ pub async fn resume_claude_code(
     plan_mode: Option<bool>,
     max_thinking_tokens: Option<u32>,
     tab_id: Option<String>,
-) -> Result<(), String> {
+    use_project_memory: Option<bool>,
+    trace_id: Option<String>,
+    enhancement: Option<EnhancementRequest>,
+) -> Result<(), AppError> {
     let plan_mode = plan_mode.unwrap_or(false);
+    // 之前 resume 时 CLI 换过 id 的会话，继续从最新的 id 续接
+    let session_id = resolve_session_alias("claude", &session_id);
     log::info!(
         "Resuming Claude Code session: {} in: {} with model: {}, plan_mode: {}",
         session_id,
@@ -422,6 +529,22 @@ pub async fn resume_claude_code(
         model,
         plan_mode
     );
+    let trace_id = start_claude_trace(trace_id, "resume", &project_path, &model, plan_mode);
+
+    // 持有会话锁直到进程退出，另一个实例同时续接该会话时返回占用错误
+    let session_lock = lock_for_execution(
+        resolve_session_file("claude", &session_id, &encode_project_path(&project_path)).ok(),
+        "claude resume",
+    )?;
+    let prompt = enhance_claude_prompt(
+        &app,
+        &project_path,
+        Some(&session_id),
+        prompt,
+        enhancement,
+        &trace_id,
+    )
+    .await?;
 
     // Log the session file path for debugging
     let session_dir = format!(
@@ -466,6 +589,7 @@ pub async fn resume_claude_code(
     // 🔥 修复：prompt 不再通过命令行参数传递，改为 stdin 管道传递
     let mapped_model = map_model_to_claude_alias(&model);
     let mut args = build_execution_args(&execution_config, &mapped_model);
+    append_system_prompt_args(&mut args, &project_path, use_project_memory);
 
     // 为resume模式重新组织参数：--resume session_id 应该在最前面
     args.insert(0, "--resume".to_string());
@@ -489,7 +613,11 @@ pub async fn resume_claude_code(
         prompt.clone(),
         model.clone(),
         project_path.clone(),
+        plan_mode,
         tab_id.clone(),
+        trace_id.clone(),
+        session_lock,
+        Some(session_id.clone()),
     )
     .await
     {
@@ -508,13 +636,43 @@ pub async fn resume_claude_code(
                 Some(plan_mode),
                 max_thinking_tokens,
                 tab_id,
+                use_project_memory,
+                Some(trace_id),
+                // The prompt has already been enhanced
+                None,
             )
             .await
         }
     }
 }
 
+/// Assigns the run its trace ID and records the start of the execution
+fn start_claude_trace(
+    trace_id: Option<String>,
+    kind: &str,
+    project_path: &str,
+    model: &str,
+    plan_mode: bool,
+) -> String {
+    let trace_id = resolve_trace_id(trace_id);
+    log::info!("Claude {} run started with trace_id={}", kind, trace_id);
+    record_trace_event(
+        &trace_id,
+        STAGE_EXECUTION_STARTED,
+        TraceFields::engine("claude").with_detail(serde_json::json!({
+            "kind": kind,
+            "projectPath": project_path,
+            "model": model,
+            "mode": if plan_mode { "plan" } else { "default" },
+        })),
+    );
+    trace_id
+}
+
 /// Cancel the currently running Claude Code execution
+///
+/// The process group is interrupted first so Claude can finish writing the session
+/// file, and only force killed if it does not exit within the grace period
 #[tauri::command]
 pub async fn cancel_claude_execution(
     app: AppHandle,
@@ -527,6 +685,7 @@ pub async fn cancel_claude_execution(
 
     let mut killed = false;
     let mut attempted_methods = Vec::new();
+    let mut outcome: Option<CancelOutcome> = None;
 
     // Method 1: Try to find and kill via ProcessRegistry using session ID
     if let Some(sid) = &session_id {
@@ -539,19 +698,39 @@ pub async fn cancel_claude_execution(
                     process_info.run_id,
                     process_info.pid
                 );
-                match registry.0.kill_process(process_info.run_id).await {
-                    Ok(success) => {
-                        if success {
-                            log::info!("Successfully killed process via registry");
-                            killed = true;
-                        } else {
-                            log::warn!("Registry kill returned false");
+                // The registry entry is removed once the process has exited
+                // and its output has been fully read
+                let run_id = process_info.run_id;
+                let exited = request_exit_and_wait(process_info.pid, GRACEFUL_EXIT_TIMEOUT, || {
+                    !matches!(registry.0.get_process(run_id), Ok(Some(_)))
+                })
+                .await;
+                let mode = if exited {
+                    killed = true;
+                    CancelMode::Graceful
+                } else {
+                    match registry.0.kill_process(run_id).await {
+                        Ok(success) => {
+                            if success {
+                                log::info!("Successfully killed process via registry");
+                                killed = true;
+                            } else {
+                                log::warn!("Registry kill returned false");
+                            }
+                        }
+                        Err(e) => {
+                            log::warn!("Failed to kill via registry: {}", e);
                         }
                     }
-                    Err(e) => {
-                        log::warn!("Failed to kill via registry: {}", e);
-                    }
-                }
+                    CancelMode::Forced
+                };
+                outcome = Some(CancelOutcome {
+                    engine: "claude".to_string(),
+                    session_id: sid.clone(),
+                    pid: Some(process_info.pid),
+                    mode,
+                    session_file: check_claude_session_file(sid, &process_info.project_path),
+                });
                 attempted_methods.push("registry");
             }
             Ok(None) => {
@@ -614,20 +793,44 @@ pub async fn cancel_claude_execution(
         let claude_state = app.state::<ClaudeProcessState>();
         let last_pid = { *claude_state.last_spawned_pid.lock().await };
         if let Some(pid) = last_pid {
-            log::info!("Attempting to kill Claude process via last spawned PID: {}", pid);
-            match platform::kill_process_tree(pid) {
-                Ok(_) => {
-                    log::info!("Successfully killed process tree via last spawned PID");
-                    let mut last_pid_guard = claude_state.last_spawned_pid.lock().await;
-                    if last_pid_guard.as_ref() == Some(&pid) {
-                        *last_pid_guard = None;
+            log::info!(
+                "Attempting to cancel Claude process via last spawned PID: {}",
+                pid
+            );
+            // The wait task clears last_spawned_pid once the process has exited
+            let exited = request_exit_and_wait(pid, GRACEFUL_EXIT_TIMEOUT, || {
+                claude_state
+                    .last_spawned_pid
+                    .try_lock()
+                    .is_ok_and(|guard| guard.as_ref() != Some(&pid))
+            })
+            .await;
+            let mode = if exited {
+                killed = true;
+                CancelMode::Graceful
+            } else {
+                match platform::kill_process_tree(pid) {
+                    Ok(_) => {
+                        log::info!("Successfully killed process tree via last spawned PID");
+                        let mut last_pid_guard = claude_state.last_spawned_pid.lock().await;
+                        if last_pid_guard.as_ref() == Some(&pid) {
+                            *last_pid_guard = None;
+                        }
+                        killed = true;
+                    }
+                    Err(e) => {
+                        log::error!("Failed to kill process tree via last spawned PID: {}", e);
                     }
-                    killed = true;
-                }
-                Err(e) => {
-                    log::error!("Failed to kill process tree via last spawned PID: {}", e);
                 }
-            }
+                CancelMode::Forced
+            };
+            outcome = Some(CancelOutcome {
+                engine: "claude".to_string(),
+                session_id: session_id.clone().unwrap_or_default(),
+                pid: Some(pid),
+                mode,
+                session_file: None,
+            });
             attempted_methods.push("last_spawned_pid");
         }
     }
@@ -637,14 +840,15 @@ pub async fn cancel_claude_execution(
     }
 
     // Always emit cancellation events for UI consistency
+    // (payload is the cancel outcome, or null when there was no process to cancel)
     if let Some(sid) = session_id {
-        let _ = app.emit(&format!("claude-cancelled:{}", sid), true);
+        let _ = app.emit(&format!("claude-cancelled:{}", sid), &outcome);
         tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
         let _ = app.emit(&format!("claude-complete:{}", sid), false);
     }
 
     // Also emit generic events for backward compatibility
-    let _ = app.emit("claude-cancelled", true);
+    let _ = app.emit("claude-cancelled", &outcome);
     tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
     let _ = app.emit("claude-complete", false);
 
@@ -657,6 +861,15 @@ pub async fn cancel_claude_execution(
     Ok(())
 }
 
+/// Check the session file of a cancelled Claude run (truncating a half-written last event)
+fn check_claude_session_file(session_id: &str, project_path: &str) -> Option<SessionFileCheck> {
+    let path =
+        resolve_session_file("claude", session_id, &encode_project_path(project_path)).ok()?;
+    check_session_file(&path)
+        .map_err(|e| log::warn!("Failed to check Claude session file: {}", e))
+        .ok()
+}
+
 /// Get all running Claude sessions
 #[tauri::command]
 pub async fn list_running_claude_sessions(
@@ -690,13 +903,20 @@ fn is_slash_command(prompt: &str) -> bool {
 /// 🔥 修复：斜杠命令通过 -p 参数传递（触发命令解析），普通 prompt 通过 stdin 管道传递
 /// 这样既支持斜杠命令，又避免操作系统命令行长度限制（Windows ~8KB, Linux/macOS ~128KB-2MB）
 /// 🔒 CRITICAL FIX: 添加 tab_id 参数，用于全局事件中标识消息来源，解决新建会话并发时的消息串扰
+/// trace_id 随全局事件信封一并发送，关联同一次用户动作产生的日志与产物
+/// session_lock 为续接会话时持有的会话锁，进程退出后释放
+#[allow(clippy::too_many_arguments)]
 async fn spawn_claude_process(
     app: AppHandle,
     mut cmd: Command,
     prompt: String,
     model: String,
     project_path: String,
+    plan_mode: bool,
     tab_id: Option<String>,
+    trace_id: String,
+    session_lock: Option<SessionLockHeartbeat>,
+    requested_session_id: Option<String>,
 ) -> Result<(), String> {
     use std::sync::Mutex;
     use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
@@ -710,7 +930,11 @@ async fn spawn_claude_process(
         cmd.arg(&prompt);
     }
 
+    // 按当前 provider 的限流配置排队启动
+    rate_limiter::acquire_cli_launch("claude").await?;
+
     // Spawn the process
+    let started_at = std::time::Instant::now();
     let mut child = cmd
         .spawn()
         .map_err(|e| format!("Failed to spawn Claude: {}", e))?;
@@ -756,7 +980,16 @@ async fn spawn_claude_process(
 
     // Get the child PID for logging
     let pid = child.id().unwrap_or(0);
-    log::info!("Spawned Claude process with PID: {:?}", pid);
+    log::info!(
+        "Spawned Claude process with PID: {:?} (trace_id={})",
+        pid,
+        trace_id
+    );
+    record_trace_event(
+        &trace_id,
+        STAGE_PROCESS_SPAWNED,
+        TraceFields::engine("claude").with_detail(serde_json::json!({ "pid": pid })),
+    );
 
     // 🔧 FIX: Create Job Object IMMEDIATELY after spawn, before Claude starts MCP servers
     // This ensures all child processes (including MCP node processes) are automatically
@@ -836,23 +1069,47 @@ async fn spawn_claude_process(
     let model_clone = model.clone();
     // 🔒 CRITICAL FIX: 克隆 tab_id 用于事件发送
     let tab_id_for_stdout = tab_id.clone();
+    let trace_id_for_stdout = trace_id.clone();
     // 🔧 FIX: Clone job_object_holder for passing to register_claude_session
     #[cfg(windows)]
     let job_object_holder_clone = job_object_holder.clone();
     let stdout_task = tokio::spawn(async move {
         let mut lines = stdout_reader.lines();
-        while let Ok(Some(line)) = lines.next_line().await {
+        let mut tool_timer = LiveToolTimer::default();
+        while let Ok(Some(mut line)) = lines.next_line().await {
             // Use trace level to avoid flooding logs in debug mode
             log::trace!("Claude stdout: {}", line);
 
             // Parse the line to check for init message with session ID
-            if let Ok(msg) = serde_json::from_str::<serde_json::Value>(&line) {
+            if let Ok(mut msg) = serde_json::from_str::<serde_json::Value>(&line) {
+                // tool_result 附上工具耗时
+                if tool_timer.observe_claude_message(&mut msg) {
+                    line = msg.to_string();
+                }
                 if msg["type"] == "system" && msg["subtype"] == "init" {
                     if let Some(claude_session_id) = msg["session_id"].as_str() {
                         let mut session_id_guard = session_id_holder_clone.lock().unwrap();
                         if session_id_guard.is_none() {
                             *session_id_guard = Some(claude_session_id.to_string());
                             log::info!("Extracted Claude session ID: {}", claude_session_id);
+                            // resume 时 CLI 可能以新 id 写出后续消息，记录别名并迁移附属数据
+                            if let Some(requested) = requested_session_id.as_deref() {
+                                if requested != claude_session_id {
+                                    handle_session_id_drift(
+                                        &app_handle,
+                                        "claude",
+                                        &project_path_clone,
+                                        requested,
+                                        claude_session_id,
+                                        tab_id_for_stdout.as_deref(),
+                                    );
+                                }
+                            }
+                            record_trace_event(
+                                &trace_id_for_stdout,
+                                STAGE_SESSION_BOUND,
+                                TraceFields::engine("claude").with_session(Some(claude_session_id)),
+                            );
 
                             // Register with auto-compact manager
                             if auto_compact_available {
@@ -898,6 +1155,7 @@ async fn spawn_claude_process(
                                         "status": "started",
                                         "pid": pid,
                                         "run_id": run_id,
+                                        "trace_id": trace_id_for_stdout,
                                     });
                                     if let Err(e) =
                                         app_handle.emit("claude-session-state", &event_payload)
@@ -988,6 +1246,7 @@ async fn spawn_claude_process(
             // 🔒 CRITICAL FIX: 全局事件包含 tab_id，用于前端过滤新建会话的消息
             let global_payload = serde_json::json!({
                 "tab_id": tab_id_for_stdout,
+                "trace_id": trace_id_for_stdout,
                 "payload": &line
             });
             let _ = app_handle.emit("claude-output", &global_payload);
@@ -998,6 +1257,7 @@ async fn spawn_claude_process(
     let session_id_holder_clone2 = session_id_holder.clone();
     // 🔒 CRITICAL FIX: 克隆 tab_id 用于 stderr 事件
     let tab_id_for_stderr = tab_id.clone();
+    let trace_id_for_stderr = trace_id.clone();
     let stderr_task = tokio::spawn(async move {
         let mut lines = stderr_reader.lines();
         while let Ok(Some(line)) = lines.next_line().await {
@@ -1009,6 +1269,7 @@ async fn spawn_claude_process(
             // 🔒 CRITICAL FIX: 全局事件包含 tab_id
             let global_payload = serde_json::json!({
                 "tab_id": tab_id_for_stderr,
+                "trace_id": trace_id_for_stderr,
                 "payload": &line
             });
             let _ = app_handle_stderr.emit("claude-error", &global_payload);
@@ -1025,13 +1286,25 @@ async fn spawn_claude_process(
     let last_spawned_pid = claude_state.last_spawned_pid.clone();
     // 🔒 CRITICAL FIX: 克隆 tab_id 用于 complete 事件
     let tab_id_for_complete = tab_id;
+    let project_path_for_complete = project_path;
     tokio::spawn(async move {
         let _ = stdout_task.await;
         let _ = stderr_task.await;
 
         // 🔒 CRITICAL FIX: 直接等待 child，不再从全局 state 取出
         // child 已经被移动到这个 async block 中
-        match child.wait().await {
+        let wait_result = child.wait().await;
+        // 进程已退出，先释放会话锁再发送完成事件，避免紧接着的续接被误判为占用
+        drop(session_lock);
+        let task_success = matches!(&wait_result, Ok(status) if status.success());
+        let completed_session_id = session_id_holder_clone3.lock().unwrap().clone();
+        invalidate_session(
+            &app_handle_wait,
+            "claude",
+            completed_session_id.as_deref(),
+            Some(&project_path_for_complete),
+        );
+        match wait_result {
             Ok(status) => {
                 log::info!("Claude process exited with status: {}", status);
                 // Add a small delay to ensure all messages are processed
@@ -1051,6 +1324,7 @@ async fn spawn_claude_process(
                 // 🔒 CRITICAL FIX: 全局事件包含 tab_id
                 let global_payload = serde_json::json!({
                     "tab_id": tab_id_for_complete,
+                    "trace_id": trace_id,
                     "payload": status.success()
                 });
                 let _ = app_handle_wait.emit("claude-complete", &global_payload);
@@ -1075,12 +1349,29 @@ async fn spawn_claude_process(
                 // 🔒 CRITICAL FIX: 全局事件包含 tab_id
                 let global_payload = serde_json::json!({
                     "tab_id": tab_id_for_complete,
+                    "trace_id": trace_id,
                     "payload": false
                 });
                 let _ = app_handle_wait.emit("claude-complete", &global_payload);
             }
         }
 
+        // Claude 的会话 ID 本身就是 CLI 会话 ID
+        let cli_session_id = session_id_holder_clone3.lock().unwrap().clone();
+        crate::commands::task_actions::dispatch_task_completion(
+            &app_handle_wait,
+            crate::commands::task_actions::TaskCompletionEvent {
+                engine: "claude".to_string(),
+                project_path: project_path_for_complete,
+                session_id: cli_session_id.clone(),
+                cli_session_id,
+                mode: Some(if plan_mode { "plan" } else { "default" }.to_string()),
+                success: task_success,
+                duration_ms: started_at.elapsed().as_millis() as u64,
+                trace_id: Some(trace_id),
+            },
+        );
+
         // Unregister from ProcessRegistry if we have a run_id
         if let Some(run_id) = *run_id_holder_clone2.lock().unwrap() {
             let _ = registry_clone2.unregister_process(run_id);
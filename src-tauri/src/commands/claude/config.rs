@@ -1,5 +1,5 @@
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::time::SystemTime;
 
 use dirs;
@@ -14,7 +14,12 @@ use tokio::sync::OnceCell;
 use super::super::wsl_utils;
 use super::paths::{get_claude_dir, get_codex_dir};
 use super::platform;
+use super::project_store::ProjectStore;
 use super::{ClaudeMdFile, ClaudeSettings, ClaudeVersionStatus};
+use crate::commands::input_validation::{
+    invalid_argument, resolve_path, validate_length, validate_path_arg, validate_path_within,
+    MAX_CONTENT_LEN,
+};
 use crate::commands::permission_config::{
     ClaudeExecutionConfig, ClaudePermissionConfig, PermissionMode, ALL_TOOLS, DEVELOPMENT_TOOLS,
     SAFE_TOOLS,
@@ -518,12 +523,57 @@ fn find_claude_md_recursive(
     Ok(())
 }
 
+/// Whether a path names a CLAUDE.md file (case insensitive, like the project scan)
+fn is_claude_md(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|n| n.to_str())
+        .is_some_and(|name| name.eq_ignore_ascii_case("CLAUDE.md"))
+}
+
+/// Validates a CLAUDE.md path for reading or saving
+///
+/// The file must be named CLAUDE.md and live inside ~/.claude or a known project,
+/// so these commands cannot be used to read or write arbitrary files
+fn validate_claude_md_path(file_path: &str) -> Result<PathBuf, String> {
+    validate_path_arg("file_path", file_path)?;
+    if !is_claude_md(Path::new(file_path)) {
+        return Err(invalid_argument(
+            "file_path",
+            format!("must be a CLAUDE.md file: {}", file_path),
+        ));
+    }
+
+    let claude_dir = get_claude_dir().map_err(|e| e.to_string())?;
+    let path = match validate_path_within(&claude_dir, file_path) {
+        Ok(path) => path,
+        Err(_) => ProjectStore::new()?
+            .list_projects()?
+            .iter()
+            .find_map(|project| validate_path_within(Path::new(&project.path), file_path).ok())
+            .ok_or_else(|| {
+                invalid_argument(
+                    "file_path",
+                    format!("must be inside ~/.claude or a project: {}", file_path),
+                )
+            })?,
+    };
+
+    // A symlink named CLAUDE.md must not lead to another file
+    if !is_claude_md(&path) {
+        return Err(invalid_argument(
+            "file_path",
+            format!("must be a CLAUDE.md file: {}", file_path),
+        ));
+    }
+    Ok(path)
+}
+
 /// Reads a specific CLAUDE.md file by its absolute path
 #[tauri::command]
 pub async fn read_claude_md_file(file_path: String) -> Result<String, String> {
+    let path = validate_claude_md_path(&file_path)?;
     log::info!("Reading CLAUDE.md file: {}", file_path);
 
-    let path = PathBuf::from(&file_path);
     if !path.exists() {
         return Err(format!("File does not exist: {}", file_path));
     }
@@ -534,10 +584,10 @@ pub async fn read_claude_md_file(file_path: String) -> Result<String, String> {
 /// Saves a specific CLAUDE.md file by its absolute path
 #[tauri::command]
 pub async fn save_claude_md_file(file_path: String, content: String) -> Result<String, String> {
+    validate_length("content", &content, MAX_CONTENT_LEN)?;
+    let path = validate_claude_md_path(&file_path)?;
     log::info!("Saving CLAUDE.md file: {}", file_path);
 
-    let path = PathBuf::from(&file_path);
-
     // Ensure the parent directory exists
     if let Some(parent) = path.parent() {
         fs::create_dir_all(parent)
@@ -548,6 +598,35 @@ pub async fn save_claude_md_file(file_path: String, content: String) -> Result<S
 
     Ok("File saved successfully".to_string())
 }
+
+/// Resolves the .gitignore at the root of a project directory
+fn project_gitignore_path(project_path: &str) -> Result<PathBuf, String> {
+    validate_path_arg("project_path", project_path)?;
+    let root = resolve_path(Path::new(project_path))?;
+    if !root.is_dir() {
+        return Err(format!("Project path does not exist: {}", project_path));
+    }
+    Ok(root.join(".gitignore"))
+}
+
+/// Reads the project's .gitignore (empty when the file does not exist)
+#[tauri::command]
+pub async fn read_project_gitignore(project_path: String) -> Result<String, String> {
+    let path = project_gitignore_path(&project_path)?;
+    if !path.exists() {
+        return Ok(String::new());
+    }
+    fs::read_to_string(&path).map_err(|e| format!("Failed to read .gitignore: {}", e))
+}
+
+/// Saves the project's .gitignore
+#[tauri::command]
+pub async fn save_project_gitignore(project_path: String, content: String) -> Result<(), String> {
+    validate_length("content", &content, MAX_CONTENT_LEN)?;
+    let path = project_gitignore_path(&project_path)?;
+    log::info!("Saving .gitignore: {}", path.display());
+    fs::write(&path, content).map_err(|e| format!("Failed to write .gitignore: {}", e))
+}
 #[tauri::command]
 pub async fn set_custom_claude_path(app: AppHandle, custom_path: String) -> Result<(), String> {
     log::info!("Setting custom Claude CLI path: {}", custom_path);
@@ -8,6 +8,9 @@ mod platform;
 mod project_store;
 mod session_history;
 
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
 pub use models::*;
 pub use paths::*;
 // Export platform utilities for process window hiding
@@ -21,15 +24,21 @@ pub use self::config::{
     get_claude_settings, get_codex_system_prompt, get_permission_presets, get_system_prompt,
     // Claude WSL mode configuration
     get_claude_wsl_mode_config, set_claude_wsl_mode_config,
-    open_new_session, read_claude_md_file, reset_claude_execution_config, save_claude_md_file,
-    save_claude_settings, save_codex_system_prompt, save_system_prompt, set_custom_claude_path,
+    open_new_session, read_claude_md_file, read_project_gitignore, reset_claude_execution_config,
+    save_claude_md_file, save_claude_settings, save_codex_system_prompt, save_project_gitignore,
+    save_system_prompt, set_custom_claude_path,
     update_claude_execution_config, update_claude_permission_config, update_thinking_mode,
     validate_permission_config,
 };
 pub use self::hooks::{get_hooks_config, update_hooks_config, validate_hook_command};
 use self::project_store::ProjectStore;
+use super::session_alias::resolve_session_alias;
+use super::session_invalidation::invalidate_session;
+use super::undo::{self, Undoable};
 pub use file_ops::{list_directory_contents, search_files};
-pub use platform::{apply_no_window_async, kill_process_tree};
+pub use platform::{
+    apply_cancellable_process_async, apply_no_window_async, kill_process_tree, request_process_exit,
+};
 // Agent functionality removed
 
 #[tauri::command]
@@ -47,9 +56,14 @@ pub async fn get_project_sessions(project_id: String) -> Result<Vec<Session>, St
 
 /// Deletes a session and all its associated data
 #[tauri::command]
-pub async fn delete_session(session_id: String, project_id: String) -> Result<String, String> {
+pub async fn delete_session(
+    app: AppHandle,
+    session_id: String,
+    project_id: String,
+) -> Result<String, String> {
     let store = ProjectStore::new()?;
     let session_deleted = store.delete_session(&project_id, &session_id)?;
+    invalidate_session(&app, "claude", Some(&session_id), None);
 
     if session_deleted {
         Ok(format!("Successfully deleted session: {}", session_id))
@@ -64,11 +78,15 @@ pub async fn delete_session(session_id: String, project_id: String) -> Result<St
 /// Deletes multiple sessions in batch
 #[tauri::command]
 pub async fn delete_sessions_batch(
+    app: AppHandle,
     session_ids: Vec<String>,
     project_id: String,
 ) -> Result<String, String> {
     let store = ProjectStore::new()?;
     let outcome = store.delete_sessions_batch(&project_id, &session_ids);
+    for session_id in &session_ids {
+        invalidate_session(&app, "claude", Some(session_id), None);
+    }
 
     if outcome.failed_count > 0 {
         Err(format!(
@@ -92,6 +110,9 @@ pub async fn delete_project(project_id: String) -> Result<String, String> {
     let newly_hidden = store.hide_project(&project_id)?;
 
     let result_msg = if newly_hidden {
+        undo::record(&ProjectRemoval {
+            project_id: project_id.clone(),
+        });
         format!(
             "Project '{}' has been removed from the list (files are preserved)",
             project_id
@@ -118,6 +139,25 @@ pub async fn restore_project(project_id: String) -> Result<String, String> {
     Ok(result_msg)
 }
 
+/// Undo record for removing a project from the list
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectRemoval {
+    project_id: String,
+}
+
+impl Undoable for ProjectRemoval {
+    const KIND: &'static str = "project_removal";
+
+    fn description(&self) -> String {
+        format!("移除项目 {}", self.project_id)
+    }
+
+    fn undo(self) -> Result<(), String> {
+        ProjectStore::new()?.restore_project(&self.project_id)
+    }
+}
+
 /// Permanently delete a project from the file system with intelligent directory detection
 #[tauri::command]
 pub async fn delete_project_permanently(project_id: String) -> Result<String, String> {
@@ -151,6 +191,31 @@ pub async fn list_hidden_projects() -> Result<Vec<String>, String> {
 pub async fn load_session_history(
     session_id: String,
     project_id: String,
-) -> Result<Vec<serde_json::Value>, String> {
+) -> Result<SessionHistory, String> {
+    let session_id = resolve_session_alias("claude", &session_id);
     session_history::load_session_history(&session_id, &project_id)
 }
+
+/// Repairs a session file by dropping corrupted lines (the original is backed up)
+#[tauri::command]
+pub async fn repair_claude_session(
+    session_id: String,
+    project_id: String,
+) -> Result<SessionRepairReport, String> {
+    session_history::repair_session_file(&session_id, &project_id)
+}
+
+/// Lists the subagent (sidechain) sessions spawned by a session
+#[tauri::command]
+pub async fn list_session_agents(
+    session_id: String,
+    project_id: String,
+) -> Result<Vec<SessionAgent>, String> {
+    session_history::list_session_agents(&session_id, &project_id)
+}
+
+/// Loads the messages of a subagent session file (agent-*.jsonl)
+#[tauri::command]
+pub async fn get_agent_messages(agent_file: String) -> Result<Vec<serde_json::Value>, String> {
+    session_history::load_agent_messages(&agent_file)
+}
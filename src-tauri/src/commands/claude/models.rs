@@ -1,6 +1,8 @@
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
+use crate::utils::jsonl_reader::CorruptedLine;
+
 /// Represents a project in the ~/.claude/projects directory
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Project {
@@ -107,3 +109,40 @@ pub struct FileEntry {
     /// File extension (if applicable)
     pub extension: Option<String>,
 }
+
+/// The loaded history of a Claude session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionHistory {
+    /// Session messages (including subagent messages), sorted by timestamp
+    pub messages: Vec<Value>,
+    /// Lines of the main session file that could not be parsed cleanly
+    pub corrupted_lines: Vec<CorruptedLine>,
+}
+
+/// Result of repairing a Claude session file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionRepairReport {
+    /// Corrupted lines that were dropped (or kept after lossy decoding)
+    pub corrupted_lines: Vec<CorruptedLine>,
+    /// Backup of the original file (None when nothing needed repairing)
+    pub backup_path: Option<String>,
+}
+
+/// Represents a subagent (sidechain) session stored in an agent-*.jsonl file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionAgent {
+    /// The agent ID (from the file name, e.g. "agent-aa740fde.jsonl" -> "aa740fde")
+    pub agent_id: String,
+    /// Absolute path of the agent-*.jsonl file
+    pub agent_file: String,
+    /// The Task tool_use ID in the main session that spawned this agent (if it could be matched)
+    pub parent_tool_use_id: Option<String>,
+    /// Number of message entries in the agent file
+    pub message_count: usize,
+    /// Timestamp of the first message (ISO string)
+    pub started_at: Option<String>,
+    /// Timestamp of the last message (ISO string)
+    pub ended_at: Option<String>,
+    /// The first user message of the agent, i.e. the task it was given
+    pub task_description: Option<String>,
+}
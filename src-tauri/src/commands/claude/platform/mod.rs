@@ -23,6 +23,10 @@ pub use unix::*;
 #[cfg(target_os = "windows")]
 pub const CREATE_NO_WINDOW: u32 = 0x08000000;
 
+/// Starts the process in its own process group so CTRL_BREAK can target it alone
+#[cfg(target_os = "windows")]
+pub const CREATE_NEW_PROCESS_GROUP: u32 = 0x00000200;
+
 /// Apply platform-specific command configuration to hide console windows
 ///
 /// On Windows, this sets the CREATE_NO_WINDOW flag to prevent console window popups.
@@ -51,6 +55,38 @@ pub fn apply_no_window_async(_cmd: &mut tokio::process::Command) {
     // No-op on non-Windows platforms
 }
 
+/// Apply configuration for CLI processes that may be cancelled gracefully
+///
+/// On Windows, hides the console window and starts a new process group so that
+/// CTRL_BREAK can be delivered to the process without affecting the app.
+/// On Unix, makes the process a process group leader so an interrupt signal
+/// reaches the whole tree.
+#[cfg(target_os = "windows")]
+pub fn apply_cancellable_process_async(cmd: &mut tokio::process::Command) {
+    cmd.creation_flags(CREATE_NO_WINDOW | CREATE_NEW_PROCESS_GROUP);
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn apply_cancellable_process_async(cmd: &mut tokio::process::Command) {
+    cmd.process_group(0);
+}
+
+/// Ask a process to exit on its own, without force killing it
+///
+/// On Windows, sends CTRL_BREAK to the process group, falling back to taskkill without /F.
+/// On Unix, sends SIGINT to the process group, falling back to the process itself.
+pub fn request_process_exit(pid: u32) -> Result<(), String> {
+    #[cfg(target_os = "windows")]
+    {
+        windows::request_process_exit_impl(pid)
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        unix::request_process_exit_impl(pid)
+    }
+}
+
 /// Kill a process tree (parent and all children)
 ///
 /// On Windows, uses taskkill with /T flag.
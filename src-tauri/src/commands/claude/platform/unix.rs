@@ -46,6 +46,25 @@ pub fn kill_process_tree_impl(pid: u32) -> Result<(), String> {
     }
 }
 
+/// Send SIGINT so the process can flush its session file and exit
+///
+/// Processes started with `apply_cancellable_process_async` lead their own process
+/// group (PGID == PID), so the signal goes to the whole group; otherwise only the
+/// process itself is signalled.
+pub fn request_process_exit_impl(pid: u32) -> Result<(), String> {
+    log::info!("Sending SIGINT to process {} on Unix", pid);
+
+    let mut last_error = String::new();
+    for target in [format!("-{}", pid), pid.to_string()] {
+        match Command::new("kill").args(["-INT", &target]).output() {
+            Ok(output) if output.status.success() => return Ok(()),
+            Ok(output) => last_error = String::from_utf8_lossy(&output.stderr).to_string(),
+            Err(e) => last_error = format!("Failed to execute kill command: {}", e),
+        }
+    }
+    Err(format!("Failed to send SIGINT: {}", last_error.trim()))
+}
+
 /// Setup Unix-specific environment variables for a command
 ///
 /// On Unix, this adds NVM paths if detected.
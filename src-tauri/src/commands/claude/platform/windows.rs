@@ -102,6 +102,60 @@ pub fn kill_process_tree_impl(pid: u32) -> Result<(), String> {
     }
 }
 
+/// Ask a process tree to exit without force killing it
+///
+/// Sends CTRL_BREAK to the process group first (requires the process to be started with
+/// CREATE_NEW_PROCESS_GROUP). If that fails, falls back to taskkill without /F.
+pub fn request_process_exit_impl(pid: u32) -> Result<(), String> {
+    match send_ctrl_break(pid) {
+        Ok(()) => {
+            log::info!("Sent CTRL_BREAK to process group {}", pid);
+            return Ok(());
+        }
+        Err(e) => log::warn!("CTRL_BREAK failed for PID {}: {}, trying taskkill", pid, e),
+    }
+
+    let mut cmd = Command::new("taskkill");
+    cmd.args(["/T", "/PID", &pid.to_string()]);
+
+    // Hide the console window
+    cmd.creation_flags(super::CREATE_NO_WINDOW);
+
+    match cmd.output() {
+        Ok(output) if output.status.success() => {
+            log::info!("Requested process tree {} to exit via taskkill", pid);
+            Ok(())
+        }
+        Ok(output) => Err(format!(
+            "taskkill without /F failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )),
+        Err(e) => Err(format!("Failed to execute taskkill: {}", e)),
+    }
+}
+
+/// Attach to the process's (hidden) console and send CTRL_BREAK to its process group
+///
+/// The app itself has no console, so it has to attach temporarily. Console attachment
+/// is process-wide state, hence the lock.
+fn send_ctrl_break(pid: u32) -> Result<(), String> {
+    use windows::Win32::System::Console::{
+        AttachConsole, FreeConsole, GenerateConsoleCtrlEvent, CTRL_BREAK_EVENT,
+    };
+
+    static CONSOLE_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+    let _guard = CONSOLE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+    unsafe {
+        let _ = FreeConsole();
+        AttachConsole(pid).map_err(|e| format!("AttachConsole failed: {}", e))?;
+        let result = GenerateConsoleCtrlEvent(CTRL_BREAK_EVENT, pid)
+            .map_err(|e| format!("GenerateConsoleCtrlEvent failed: {}", e));
+        let _ = FreeConsole();
+        result
+    }
+}
+
 /// Setup Windows-specific environment variables for a command
 ///
 /// Configures PATH and other necessary environment variables to ensure
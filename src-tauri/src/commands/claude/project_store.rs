@@ -11,6 +11,8 @@ use super::paths::{decode_project_path, get_claude_dir, normalize_path_for_compa
 use super::session_history::{
     extract_first_user_message, extract_last_message_timestamp, extract_session_model,
 };
+use crate::commands::session_alias::merge_aliased_sessions;
+use crate::utils::session_lock::SessionLock;
 
 pub struct ProjectStore {
     claude_dir: PathBuf,
@@ -249,6 +251,9 @@ impl ProjectStore {
             }
         }
 
+        // resume 时换了 id 的会话只显示最新的那一个
+        merge_aliased_sessions("claude", &mut sessions, |s| s.id.as_str());
+
         sessions.sort_by(|a, b| b.created_at.cmp(&a.created_at));
         Ok(sessions)
     }
@@ -268,6 +273,7 @@ impl ProjectStore {
             .join(format!("{}.jsonl", session_id));
 
         if session_file.exists() {
+            let _lock = SessionLock::acquire(&session_file, "claude delete")?;
             fs::remove_file(&session_file)
                 .map_err(|e| format!("Failed to delete session file: {}", e))?;
             log::info!("Deleted session file: {:?}", session_file);
@@ -1,13 +1,21 @@
+use std::collections::HashMap;
 use std::fs;
 use std::io::{BufRead, BufReader};
+use std::ops::ControlFlow;
 use std::path::Path;
 use std::time::SystemTime;
 
 use chrono::{DateTime, Utc};
 use serde_json::Value;
 
-use super::models::JsonlEntry;
+use super::models::{JsonlEntry, SessionAgent, SessionHistory, SessionRepairReport};
 use super::paths::get_claude_dir;
+use crate::commands::session_utils::resolve_session_file;
+use crate::commands::tool_timing::annotate_claude_history;
+use crate::utils::jsonl_reader::{
+    ensure_loadable_in_memory, for_each_json_line, repair_jsonl, CorruptedLine,
+};
+use crate::utils::session_lock::{write_session_file, FileSnapshot, SessionLock};
 
 /// Extracts the first valid user message from a JSONL file
 pub fn extract_first_user_message<P: AsRef<Path>>(
@@ -146,22 +154,294 @@ pub fn extract_session_model<P: AsRef<Path>>(jsonl_path: P) -> Option<String> {
     last_model
 }
 
+/// Task 工具 -> 子代理文件的关联线索（从主会话中收集）
+#[derive(Debug, Default)]
+struct TaskLinks {
+    /// agentId（来自 toolUseResult）-> Task tool_use_id
+    by_agent_id: HashMap<String, String>,
+    /// Task 输入的 prompt -> Task tool_use_id（旧版本没有 agentId 时的回退匹配）
+    by_prompt: HashMap<String, String>,
+}
+
+impl TaskLinks {
+    fn collect(messages: &[Value]) -> Self {
+        let mut links = TaskLinks::default();
+        for json in messages {
+            let Some(content) = json
+                .get("message")
+                .and_then(|m| m.get("content"))
+                .and_then(|c| c.as_array())
+            else {
+                continue;
+            };
+            for item in content {
+                match item.get("type").and_then(|t| t.as_str()) {
+                    Some("tool_result") => {
+                        // Get tool_use_id and agentId from toolUseResult
+                        if let (Some(tool_use_id), Some(agent_id)) = (
+                            item.get("tool_use_id").and_then(|t| t.as_str()),
+                            json.get("toolUseResult")
+                                .and_then(|r| r.get("agentId"))
+                                .and_then(|a| a.as_str()),
+                        ) {
+                            log::debug!("Found agentId mapping: {} -> {}", agent_id, tool_use_id);
+                            links
+                                .by_agent_id
+                                .insert(agent_id.to_string(), tool_use_id.to_string());
+                        }
+                    }
+                    Some("tool_use") if is_task_tool_use(item) => {
+                        if let (Some(tool_use_id), Some(prompt)) = (
+                            item.get("id").and_then(|t| t.as_str()),
+                            item.get("input")
+                                .and_then(|i| i.get("prompt"))
+                                .and_then(|p| p.as_str()),
+                        ) {
+                            links
+                                .by_prompt
+                                .insert(prompt.trim().to_string(), tool_use_id.to_string());
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+        links
+    }
+
+    fn resolve(&self, agent_id: &str, task_description: Option<&str>) -> Option<String> {
+        self.by_agent_id.get(agent_id).cloned().or_else(|| {
+            task_description.and_then(|prompt| self.by_prompt.get(prompt.trim()).cloned())
+        })
+    }
+}
+
+/// Whether a content block is a Task (subagent) tool_use
+fn is_task_tool_use(item: &Value) -> bool {
+    item.get("type").and_then(|t| t.as_str()) == Some("tool_use")
+        && matches!(
+            item.get("name").and_then(|n| n.as_str()),
+            Some("Task") | Some("Agent")
+        )
+}
+
+/// Extracts the agent ID from an agent-*.jsonl file name
+fn agent_id_from_file_name(file_name: &str) -> Option<&str> {
+    file_name
+        .strip_prefix("agent-")
+        .and_then(|s| s.strip_suffix(".jsonl"))
+        .filter(|id| !id.is_empty())
+}
+
+/// Extracts the plain text of a message content (string or array of text blocks)
+fn message_text(content: &Value) -> Option<String> {
+    if let Some(text) = content.as_str() {
+        return Some(text.to_string());
+    }
+    let text: Vec<&str> = content
+        .as_array()?
+        .iter()
+        .filter(|item| item.get("type").and_then(|t| t.as_str()) == Some("text"))
+        .filter_map(|item| item.get("text").and_then(|t| t.as_str()))
+        .collect();
+    if text.is_empty() {
+        None
+    } else {
+        Some(text.join("\n"))
+    }
+}
+
+/// Reads all JSON lines of a JSONL file, reporting the corrupted lines that were skipped
+fn read_jsonl_with_report(path: &Path) -> Result<(Vec<Value>, Vec<CorruptedLine>), String> {
+    let mut values = Vec::new();
+    let corrupted_lines = for_each_json_line(path, |_, value| {
+        values.push(value);
+        ControlFlow::Continue(())
+    })
+    .map_err(|e| format!("Failed to read session file: {}", e))?;
+
+    if !corrupted_lines.is_empty() {
+        log::warn!(
+            "Found {} corrupted lines in {:?}: {:?}",
+            corrupted_lines.len(),
+            path,
+            corrupted_lines
+        );
+    }
+    Ok((values, corrupted_lines))
+}
+
+/// Reads all JSON lines of a JSONL file (corrupted lines are skipped)
+fn read_jsonl_values(path: &Path) -> Result<Vec<Value>, String> {
+    read_jsonl_with_report(path).map(|(values, _)| values)
+}
+
+/// Summarizes an agent-*.jsonl file if it belongs to the given session
+fn summarize_agent_file(path: &Path, session_id: &str, links: &TaskLinks) -> Option<SessionAgent> {
+    let file_name = path.file_name()?.to_str()?;
+    let agent_id = agent_id_from_file_name(file_name)?;
+
+    let mut belongs_to_session = false;
+    let mut message_count = 0;
+    let mut started_at = None;
+    let mut ended_at = None;
+    let mut task_description = None;
+
+    for json in read_jsonl_values(path).ok()? {
+        if json.get("sessionId").and_then(|s| s.as_str()) != Some(session_id) {
+            continue;
+        }
+        belongs_to_session = true;
+
+        let Some(message) = json.get("message") else {
+            continue;
+        };
+        message_count += 1;
+
+        if let Some(ts) = json.get("timestamp").and_then(|t| t.as_str()) {
+            if started_at.is_none() {
+                started_at = Some(ts.to_string());
+            }
+            ended_at = Some(ts.to_string());
+        }
+
+        if task_description.is_none()
+            && message.get("role").and_then(|r| r.as_str()) == Some("user")
+        {
+            task_description = message
+                .get("content")
+                .and_then(message_text)
+                .filter(|t| !t.trim().is_empty());
+        }
+    }
+
+    if !belongs_to_session {
+        return None;
+    }
+
+    Some(SessionAgent {
+        agent_id: agent_id.to_string(),
+        agent_file: path.to_string_lossy().to_string(),
+        parent_tool_use_id: links.resolve(agent_id, task_description.as_deref()),
+        message_count,
+        started_at,
+        ended_at,
+        task_description,
+    })
+}
+
+/// Scans the project directory for agent-*.jsonl files belonging to the session
+fn scan_session_agents(
+    project_dir: &Path,
+    session_id: &str,
+    links: &TaskLinks,
+) -> Vec<SessionAgent> {
+    let Ok(entries) = fs::read_dir(project_dir) else {
+        return Vec::new();
+    };
+
+    let mut agents: Vec<SessionAgent> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter_map(|path| summarize_agent_file(&path, session_id, links))
+        .collect();
+    agents.sort_by(|a, b| a.started_at.cmp(&b.started_at));
+    agents
+}
+
+/// Lists the subagent (sidechain) sessions spawned by a session
+pub fn list_session_agents(
+    session_id: &str,
+    project_id: &str,
+) -> Result<Vec<SessionAgent>, String> {
+    let session_path = resolve_session_file("claude", session_id, project_id)?;
+    let project_dir = session_path.parent().unwrap_or(Path::new(""));
+
+    let links = TaskLinks::collect(&read_jsonl_values(&session_path)?);
+    let agents = scan_session_agents(project_dir, session_id, &links);
+    log::info!(
+        "Found {} subagent sessions for {}",
+        agents.len(),
+        session_id
+    );
+    Ok(agents)
+}
+
+/// Loads the messages of a single agent-*.jsonl file
+///
+/// Only files named agent-*.jsonl under ~/.claude/projects are accepted.
+pub fn load_agent_messages(agent_file: &str) -> Result<Vec<Value>, String> {
+    let projects_dir = get_claude_dir()
+        .map_err(|e| e.to_string())?
+        .join("projects");
+    let projects_dir = projects_dir
+        .canonicalize()
+        .map_err(|e| format!("Failed to resolve projects directory: {}", e))?;
+    let path = Path::new(agent_file)
+        .canonicalize()
+        .map_err(|e| format!("Agent file not found: {}", e))?;
+
+    let is_agent_file = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .and_then(agent_id_from_file_name)
+        .is_some();
+    if !is_agent_file || !path.starts_with(&projects_dir) {
+        return Err(format!(
+            "Not a Claude subagent session file: {}",
+            agent_file
+        ));
+    }
+
+    read_jsonl_values(&path)
+}
+
+/// Attaches agent file references to the Task tool_use blocks that spawned them
+fn attach_agent_refs(messages: &mut [Value], agents: &[SessionAgent]) {
+    let by_tool_use_id: HashMap<&str, &SessionAgent> = agents
+        .iter()
+        .filter_map(|a| a.parent_tool_use_id.as_deref().map(|id| (id, a)))
+        .collect();
+    if by_tool_use_id.is_empty() {
+        return;
+    }
+
+    for message in messages.iter_mut() {
+        let Some(content) = message
+            .get_mut("message")
+            .and_then(|m| m.get_mut("content"))
+            .and_then(|c| c.as_array_mut())
+        else {
+            continue;
+        };
+        for item in content.iter_mut() {
+            if !is_task_tool_use(item) {
+                continue;
+            }
+            let agent = item
+                .get("id")
+                .and_then(|id| id.as_str())
+                .and_then(|id| by_tool_use_id.get(id));
+            if let Some(agent) = agent {
+                item["agent_id"] = Value::String(agent.agent_id.clone());
+                item["agent_file"] = Value::String(agent.agent_file.clone());
+            }
+        }
+    }
+}
+
 /// Loads the JSONL history for a specific session
 /// Also loads subagent messages from agent-*.jsonl files and merges them
-pub fn load_session_history(session_id: &str, project_id: &str) -> Result<Vec<Value>, String> {
+/// Corrupted lines of the main session file are skipped and reported
+pub fn load_session_history(session_id: &str, project_id: &str) -> Result<SessionHistory, String> {
     log::info!(
         "Loading session history for session: {} in project: {}",
         session_id,
         project_id
     );
 
-    let claude_dir = get_claude_dir().map_err(|e| e.to_string())?;
-    let project_dir = claude_dir.join("projects").join(project_id);
-    let session_path = project_dir.join(format!("{}.jsonl", session_id));
-
-    if !session_path.exists() {
-        return Err(format!("Session file not found: {}", session_id));
-    }
+    let session_path = resolve_session_file("claude", session_id, project_id)?;
+    let project_dir = session_path.parent().unwrap_or(Path::new(""));
 
     // Get file modification time as base timestamp
     let file_metadata =
@@ -170,99 +450,36 @@ pub fn load_session_history(session_id: &str, project_id: &str) -> Result<Vec<Va
         .modified()
         .unwrap_or_else(|_| SystemTime::now());
 
-    let file =
-        fs::File::open(&session_path).map_err(|e| format!("Failed to open session file: {}", e))?;
-
-    let reader = BufReader::new(file);
-    let mut messages = Vec::new();
-
-    // Step 1: Load main session messages and build agentId -> tool_use_id mapping
-    let mut agent_to_tool_use_id: std::collections::HashMap<String, String> =
-        std::collections::HashMap::new();
-
-    for line in reader.lines() {
-        if let Ok(line) = line {
-            if let Ok(json) = serde_json::from_str::<Value>(&line) {
-                // Check for tool_result with agentId to build mapping
-                if let Some(content) = json
-                    .get("message")
-                    .and_then(|m| m.get("content"))
-                    .and_then(|c| c.as_array())
-                {
-                    for item in content {
-                        if item.get("type").and_then(|t| t.as_str()) == Some("tool_result") {
-                            // Get tool_use_id and agentId from toolUseResult
-                            if let (Some(tool_use_id), Some(agent_id)) = (
-                                item.get("tool_use_id").and_then(|t| t.as_str()),
-                                json.get("toolUseResult")
-                                    .and_then(|r| r.get("agentId"))
-                                    .and_then(|a| a.as_str()),
-                            ) {
-                                log::debug!(
-                                    "Found agentId mapping: {} -> {}",
-                                    agent_id,
-                                    tool_use_id
-                                );
-                                agent_to_tool_use_id
-                                    .insert(agent_id.to_string(), tool_use_id.to_string());
-                            }
-                        }
-                    }
-                }
-                messages.push(json);
-            }
-        }
-    }
+    // Step 1: Load main session messages and collect Task -> agent links
+    let (mut messages, corrupted_lines) = read_jsonl_with_report(&session_path)?;
+    let links = TaskLinks::collect(&messages);
 
     log::info!(
         "Found {} agent-to-tool_use_id mappings",
-        agent_to_tool_use_id.len()
+        links.by_agent_id.len()
     );
 
     // Step 2: Load subagent messages from agent-*.jsonl files
-    if !agent_to_tool_use_id.is_empty() {
-        if let Ok(entries) = fs::read_dir(&project_dir) {
-            for entry in entries.flatten() {
-                let path = entry.path();
-                if let Some(file_name) = path.file_name().and_then(|n| n.to_str()) {
-                    // Match agent-*.jsonl files
-                    if file_name.starts_with("agent-") && file_name.ends_with(".jsonl") {
-                        // Extract agentId from filename (e.g., "agent-aa740fde.jsonl" -> "aa740fde")
-                        let agent_id = file_name
-                            .strip_prefix("agent-")
-                            .and_then(|s| s.strip_suffix(".jsonl"))
-                            .unwrap_or("");
-
-                        // Check if this agent belongs to our session
-                        if let Some(tool_use_id) = agent_to_tool_use_id.get(agent_id) {
-                            log::info!(
-                                "Loading subagent file: {} for tool_use_id: {}",
-                                file_name,
-                                tool_use_id
-                            );
-
-                            // Load subagent messages
-                            if let Ok(file) = fs::File::open(&path) {
-                                let reader = BufReader::new(file);
-                                for line in reader.lines() {
-                                    if let Ok(line) = line {
-                                        if let Ok(mut json) = serde_json::from_str::<Value>(&line) {
-                                            // Verify this subagent belongs to our session
-                                            let subagent_session_id =
-                                                json.get("sessionId").and_then(|s| s.as_str());
-                                            if subagent_session_id == Some(session_id) {
-                                                // Add parent_tool_use_id to link subagent messages to Task
-                                                json["parent_tool_use_id"] =
-                                                    Value::String(tool_use_id.clone());
-                                                messages.push(json);
-                                            }
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
+    let agents = scan_session_agents(project_dir, session_id, &links);
+    attach_agent_refs(&mut messages, &agents);
+
+    for agent in &agents {
+        let Some(tool_use_id) = &agent.parent_tool_use_id else {
+            continue;
+        };
+        log::info!(
+            "Loading subagent file: {} for tool_use_id: {}",
+            agent.agent_file,
+            tool_use_id
+        );
+
+        let agent_messages = read_jsonl_values(Path::new(&agent.agent_file)).unwrap_or_default();
+        for mut json in agent_messages {
+            // Verify this subagent belongs to our session
+            if json.get("sessionId").and_then(|s| s.as_str()) == Some(session_id) {
+                // Add parent_tool_use_id to link subagent messages to Task
+                json["parent_tool_use_id"] = Value::String(tool_use_id.clone());
+                messages.push(json);
             }
         }
     }
@@ -274,6 +491,9 @@ pub fn load_session_history(session_id: &str, project_id: &str) -> Result<Vec<Va
         ts_a.cmp(ts_b)
     });
 
+    // 用 tool_use / tool_result 的时间戳计算工具耗时（须在补充合成时间前）
+    annotate_claude_history(&mut messages);
+
     // Add timestamps to historical messages that don't have them
     let messages_count = messages.len();
     for (i, message) in messages.iter_mut().enumerate() {
@@ -309,5 +529,188 @@ pub fn load_session_history(session_id: &str, project_id: &str) -> Result<Vec<Va
         "Loaded {} total messages (including subagent messages)",
         messages.len()
     );
-    Ok(messages)
+    Ok(SessionHistory {
+        messages,
+        corrupted_lines,
+    })
+}
+
+/// Repairs a session file by dropping the lines that cannot be parsed
+///
+/// The original file is kept as `<session>.jsonl.bak-<timestamp>`; lines that only had
+/// invalid UTF-8 but still parse are kept in their lossy-decoded form.
+pub fn repair_session_file(
+    session_id: &str,
+    project_id: &str,
+) -> Result<SessionRepairReport, String> {
+    let session_path = resolve_session_file("claude", session_id, project_id)?;
+    let _lock = SessionLock::acquire(&session_path, "claude repair")?;
+
+    ensure_loadable_in_memory(&session_path)?;
+    let snapshot = FileSnapshot::capture(&session_path)?;
+    let content =
+        fs::read(&session_path).map_err(|e| format!("Failed to read session file: {}", e))?;
+    let (repaired, corrupted_lines) = repair_jsonl(&content);
+    if corrupted_lines.is_empty() {
+        return Ok(SessionRepairReport {
+            corrupted_lines,
+            backup_path: None,
+        });
+    }
+
+    let mut backup_name = session_path.file_name().unwrap_or_default().to_os_string();
+    backup_name.push(format!(".bak-{}", Utc::now().format("%Y%m%d%H%M%S")));
+    let backup_path = session_path.with_file_name(backup_name);
+    fs::write(&backup_path, &content)
+        .map_err(|e| format!("Failed to back up session file: {}", e))?;
+    write_session_file(&session_path, &snapshot, repaired)?;
+
+    log::info!(
+        "Repaired session {}: {} corrupted lines handled, backup at {:?}",
+        session_id,
+        corrupted_lines.len(),
+        backup_path
+    );
+    Ok(SessionRepairReport {
+        corrupted_lines,
+        backup_path: Some(backup_path.to_string_lossy().to_string()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn write_jsonl(path: &Path, lines: &[Value]) {
+        let body: Vec<String> = lines.iter().map(|l| l.to_string()).collect();
+        fs::write(path, body.join("\n")).unwrap();
+    }
+
+    fn task_tool_use(id: &str, prompt: &str) -> Value {
+        json!({
+            "type": "assistant",
+            "message": {
+                "role": "assistant",
+                "content": [{
+                    "type": "tool_use",
+                    "id": id,
+                    "name": "Task",
+                    "input": { "prompt": prompt }
+                }]
+            }
+        })
+    }
+
+    fn agent_line(session_id: &str, role: &str, text: &str, timestamp: &str) -> Value {
+        json!({
+            "sessionId": session_id,
+            "timestamp": timestamp,
+            "message": { "role": role, "content": text }
+        })
+    }
+
+    #[test]
+    fn lists_agents_of_the_session_and_links_them_to_tasks() {
+        let dir = tempfile::tempdir().unwrap();
+        let main = vec![
+            task_tool_use("toolu_new", "review the diff"),
+            json!({
+                "type": "user",
+                "toolUseResult": { "agentId": "a1" },
+                "message": {
+                    "role": "user",
+                    "content": [{ "type": "tool_result", "tool_use_id": "toolu_new" }]
+                }
+            }),
+            task_tool_use("toolu_old", "write the tests"),
+        ];
+        let links = TaskLinks::collect(&main);
+
+        write_jsonl(
+            &dir.path().join("agent-a1.jsonl"),
+            &[
+                agent_line("s1", "user", "review the diff", "2024-01-01T00:00:02Z"),
+                agent_line("s1", "assistant", "done", "2024-01-01T00:00:03Z"),
+            ],
+        );
+        // 旧版本没有 agentId，按 Task prompt 匹配
+        write_jsonl(
+            &dir.path().join("agent-b2.jsonl"),
+            &[agent_line(
+                "s1",
+                "user",
+                " write the tests ",
+                "2024-01-01T00:00:01Z",
+            )],
+        );
+        write_jsonl(
+            &dir.path().join("agent-c3.jsonl"),
+            &[agent_line(
+                "other",
+                "user",
+                "review the diff",
+                "2024-01-01T00:00:00Z",
+            )],
+        );
+        write_jsonl(
+            &dir.path().join("s1.jsonl"),
+            &[agent_line("s1", "user", "hi", "2024-01-01T00:00:00Z")],
+        );
+
+        let agents = scan_session_agents(dir.path(), "s1", &links);
+
+        let summary: Vec<_> = agents
+            .iter()
+            .map(|a| (a.agent_id.as_str(), a.parent_tool_use_id.as_deref()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![("b2", Some("toolu_old")), ("a1", Some("toolu_new"))]
+        );
+        assert_eq!(agents[1].message_count, 2);
+        assert_eq!(
+            agents[1].started_at.as_deref(),
+            Some("2024-01-01T00:00:02Z")
+        );
+        assert_eq!(agents[1].ended_at.as_deref(), Some("2024-01-01T00:00:03Z"));
+        assert_eq!(
+            agents[1].task_description.as_deref(),
+            Some("review the diff")
+        );
+    }
+
+    #[test]
+    fn attaches_agent_refs_to_the_spawning_task() {
+        let agent = SessionAgent {
+            agent_id: "a1".to_string(),
+            agent_file: "/p/agent-a1.jsonl".to_string(),
+            parent_tool_use_id: Some("toolu_1".to_string()),
+            message_count: 1,
+            started_at: None,
+            ended_at: None,
+            task_description: None,
+        };
+        let mut messages = vec![
+            task_tool_use("toolu_1", "review"),
+            task_tool_use("toolu_2", "other"),
+            json!({
+                "message": {
+                    "content": [{ "type": "tool_use", "id": "toolu_1", "name": "Bash" }]
+                }
+            }),
+        ];
+
+        attach_agent_refs(&mut messages, &[agent]);
+
+        let spawned = &messages[0]["message"]["content"][0];
+        assert_eq!(spawned["agent_id"], "a1");
+        assert_eq!(spawned["agent_file"], "/p/agent-a1.jsonl");
+        assert!(messages[1]["message"]["content"][0]
+            .get("agent_id")
+            .is_none());
+        assert!(messages[2]["message"]["content"][0]
+            .get("agent_id")
+            .is_none());
+    }
 }
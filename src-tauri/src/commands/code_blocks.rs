@@ -0,0 +1,665 @@
+//! 会话代码块提取
+//!
+//! 从会话的 assistant 回复中收集全部围栏代码块（``` / ~~~）：
+//! - 用 `MarkdownSegmenter` 把回复切分为文字与代码片段
+//! - 按代码块前后的文字推测模型建议的文件名（"保存为 xxx"、`src/main.rs` 这类路径样式的字符串）
+//! - 支持按语言过滤；相同内容只保留最新的一段
+//! - 选中的代码块可批量写入指定目录，没有文件名建议的写成 snippet_N.ext
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use super::cross_engine_review::read_assistant_messages;
+use super::session_utils::resolve_session_file;
+
+/// 推测文件名时查看代码块之前的非空行数
+const FILENAME_LOOKBEHIND_LINES: usize = 2;
+
+/// 明确指明文件名的说法："保存为 xxx" / "save it as xxx" / "文件：xxx"
+static EXPLICIT_FILENAME: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r#"(?i)(?:保存为|另存为|保存到|保存至|存为|写入|命名为|文件名?\s*[:：]|save(?:\s+it)?\s+(?:as|to)|file(?:\s*name)?\s*[:：])\s*[`"'*“]*([\w\-./\\]+\.[A-Za-z][A-Za-z0-9]{0,9})"#,
+    )
+    .unwrap()
+});
+
+/// 路径样式的字符串：`config.toml`、**src/main.rs**、src/lib/api.ts
+static PATH_LIKE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"`([\w\-./\\]+\.[A-Za-z][A-Za-z0-9]{0,9})`|\*\*([\w\-./\\]+\.[A-Za-z][A-Za-z0-9]{0,9})\*\*|(?:^|\s)((?:[\w\-.]+/)+[\w\-.]+\.[A-Za-z][A-Za-z0-9]{0,9})",
+    )
+    .unwrap()
+});
+
+/// 信息串中的文件名属性：```ts title="src/a.ts"、```rust file=src/main.rs
+static INFO_FILENAME: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"(?i)\b(?:title|file|filename|path)\s*=\s*["']?([^"'\s]+)"#).unwrap()
+});
+
+// ============================================================================
+// Markdown Segmentation
+// ============================================================================
+
+/// Markdown 片段
+#[derive(Debug, Clone, PartialEq)]
+pub enum MarkdownSegment {
+    Text(String),
+    /// 围栏代码块；`info` 为开头围栏后的信息串（语言等）
+    Code {
+        info: String,
+        content: String,
+    },
+}
+
+/// 按围栏代码块切分 Markdown（CommonMark 规则：最多缩进 3 个空格，
+/// 结束围栏须与开头同字符且不短于开头；未闭合的代码块延续到文末）
+pub struct MarkdownSegmenter;
+
+impl MarkdownSegmenter {
+    pub fn segment(markdown: &str) -> Vec<MarkdownSegment> {
+        let mut segments = Vec::new();
+        let mut text = String::new();
+        let mut lines = markdown.lines();
+
+        while let Some(line) = lines.next() {
+            let Some((fence, info)) = opening_fence(line) else {
+                text.push_str(line);
+                text.push('\n');
+                continue;
+            };
+
+            if !text.is_empty() {
+                segments.push(MarkdownSegment::Text(std::mem::take(&mut text)));
+            }
+            let mut content = String::new();
+            for line in lines.by_ref() {
+                if is_closing_fence(line, fence) {
+                    break;
+                }
+                content.push_str(line);
+                content.push('\n');
+            }
+            segments.push(MarkdownSegment::Code {
+                info: info.to_string(),
+                content,
+            });
+        }
+
+        if !text.is_empty() {
+            segments.push(MarkdownSegment::Text(text));
+        }
+        segments
+    }
+}
+
+fn fence_indent(line: &str) -> usize {
+    line.len() - line.trim_start_matches(' ').len()
+}
+
+/// 开头围栏，返回 (围栏, 信息串)
+fn opening_fence(line: &str) -> Option<(&str, &str)> {
+    if fence_indent(line) > 3 {
+        return None;
+    }
+    let rest = line.trim_start_matches(' ');
+    let marker = rest.chars().next().filter(|c| *c == '`' || *c == '~')?;
+    let len = rest.chars().take_while(|c| *c == marker).count();
+    if len < 3 {
+        return None;
+    }
+    let info = rest[len..].trim();
+    if marker == '`' && info.contains('`') {
+        return None;
+    }
+    Some((&rest[..len], info))
+}
+
+fn is_closing_fence(line: &str, fence: &str) -> bool {
+    let trimmed = line.trim();
+    let marker = fence.chars().next().unwrap_or('`');
+    fence_indent(line) <= 3 && trimmed.len() >= fence.len() && trimmed.chars().all(|c| c == marker)
+}
+
+// ============================================================================
+// Extraction
+// ============================================================================
+
+/// 会话中的一个代码块
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CodeBlock {
+    /// "{message_index}-{块序号}"
+    pub id: String,
+    pub language: Option<String>,
+    pub content: String,
+    /// 所在 assistant 回复的序号（从 0 开始）
+    pub message_index: usize,
+    /// 从上下文推测的目标文件名（相对路径或文件名）
+    pub suggested_filename: Option<String>,
+}
+
+/// 代码块过滤条件
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CodeBlockFilters {
+    /// 只保留这些语言（别名按同一语言处理，如 rs/rust），为空时不过滤
+    pub languages: Vec<String>,
+    /// 相同内容只保留最新的一段
+    pub dedupe: bool,
+}
+
+impl Default for CodeBlockFilters {
+    fn default() -> Self {
+        Self {
+            languages: Vec::new(),
+            dedupe: true,
+        }
+    }
+}
+
+/// 统一语言名（小写，常见别名合并）
+fn canonical_language(language: &str) -> String {
+    let language = language.trim().to_lowercase();
+    match language.as_str() {
+        "rs" => "rust",
+        "py" | "python3" => "python",
+        "ts" => "typescript",
+        "js" | "node" => "javascript",
+        "sh" | "shell" | "zsh" | "console" => "bash",
+        "yml" => "yaml",
+        "c++" | "cc" => "cpp",
+        "cs" | "c#" => "csharp",
+        "rb" => "ruby",
+        "md" => "markdown",
+        "ps1" | "pwsh" => "powershell",
+        "kt" => "kotlin",
+        _ => return language,
+    }
+    .to_string()
+}
+
+/// 语言对应的文件扩展名（未知语言用 txt）
+fn language_extension(language: Option<&str>) -> &'static str {
+    match language.map(canonical_language).as_deref() {
+        Some("rust") => "rs",
+        Some("python") => "py",
+        Some("typescript") => "ts",
+        Some("tsx") => "tsx",
+        Some("javascript") => "js",
+        Some("jsx") => "jsx",
+        Some("json") => "json",
+        Some("bash") => "sh",
+        Some("powershell") => "ps1",
+        Some("yaml") => "yaml",
+        Some("toml") => "toml",
+        Some("html") => "html",
+        Some("css") => "css",
+        Some("scss") => "scss",
+        Some("sql") => "sql",
+        Some("go") => "go",
+        Some("java") => "java",
+        Some("kotlin") => "kt",
+        Some("swift") => "swift",
+        Some("c") => "c",
+        Some("cpp") => "cpp",
+        Some("csharp") => "cs",
+        Some("ruby") => "rb",
+        Some("php") => "php",
+        Some("markdown") => "md",
+        Some("xml") => "xml",
+        _ => "txt",
+    }
+}
+
+/// 解析信息串，返回 (语言, 信息串中指定的文件名)
+///
+/// 支持 ```rust、```rust:src/main.rs、```ts title="src/a.ts"
+fn parse_info(info: &str) -> (Option<String>, Option<String>) {
+    let first = info.split_whitespace().next().unwrap_or_default();
+    let (language, inline_file) = match first.split_once(':') {
+        Some((language, file)) => (language, Some(file.to_string())),
+        None => (first, None),
+    };
+    let language =
+        (!language.is_empty() && !language.contains('=')).then(|| canonical_language(language));
+    let filename = inline_file
+        .filter(|f| !f.is_empty())
+        .or_else(|| INFO_FILENAME.captures(info).map(|caps| caps[1].to_string()));
+    (language, filename)
+}
+
+fn plausible_filename(candidate: &str) -> Option<String> {
+    let candidate = candidate
+        .trim_start_matches("./")
+        .trim_end_matches(['.', '/', '\\']);
+    if candidate.is_empty() || candidate.contains("://") || candidate.starts_with("www.") {
+        return None;
+    }
+    Some(candidate.to_string())
+}
+
+/// 从代码块前后的文字推测文件名：前文优先（明确说法 > 路径样式），后文只认明确说法
+fn suggest_filename(before: &str, after: &str) -> Option<String> {
+    let recent: Vec<&str> = before
+        .lines()
+        .rev()
+        .filter(|line| !line.trim().is_empty())
+        .take(FILENAME_LOOKBEHIND_LINES)
+        .collect();
+
+    for line in &recent {
+        if let Some(name) = EXPLICIT_FILENAME
+            .captures_iter(line)
+            .last()
+            .and_then(|caps| plausible_filename(&caps[1]))
+        {
+            return Some(name);
+        }
+    }
+    for line in &recent {
+        if let Some(name) = PATH_LIKE.captures_iter(line).last().and_then(|caps| {
+            caps.iter()
+                .skip(1)
+                .flatten()
+                .next()
+                .and_then(|m| plausible_filename(m.as_str()))
+        }) {
+            return Some(name);
+        }
+    }
+
+    after
+        .lines()
+        .find(|line| !line.trim().is_empty())
+        .and_then(|line| EXPLICIT_FILENAME.captures(line))
+        .and_then(|caps| plausible_filename(&caps[1]))
+}
+
+/// 提取一条回复中的代码块
+fn blocks_in_message(message_index: usize, text: &str) -> Vec<CodeBlock> {
+    let segments = MarkdownSegmenter::segment(text);
+    let text_at = |index: Option<usize>| match index.and_then(|i| segments.get(i)) {
+        Some(MarkdownSegment::Text(text)) => text.as_str(),
+        _ => "",
+    };
+
+    let mut blocks = Vec::new();
+    for (index, segment) in segments.iter().enumerate() {
+        let MarkdownSegment::Code { info, content } = segment else {
+            continue;
+        };
+        if content.trim().is_empty() {
+            continue;
+        }
+        let (language, info_filename) = parse_info(info);
+        let suggested_filename = info_filename
+            .and_then(|f| plausible_filename(&f))
+            .or_else(|| suggest_filename(text_at(index.checked_sub(1)), text_at(Some(index + 1))));
+        blocks.push(CodeBlock {
+            id: format!("{}-{}", message_index, blocks.len()),
+            language,
+            content: content.clone(),
+            message_index,
+            suggested_filename,
+        });
+    }
+    blocks
+}
+
+/// 按过滤条件筛选；去重时从后往前保留每种内容第一次出现（即最新）的代码块
+fn filter_blocks(blocks: Vec<CodeBlock>, filters: &CodeBlockFilters) -> Vec<CodeBlock> {
+    let languages: HashSet<String> = filters
+        .languages
+        .iter()
+        .map(|l| canonical_language(l))
+        .filter(|l| !l.is_empty())
+        .collect();
+
+    let mut seen: HashSet<String> = HashSet::new();
+    let mut kept: Vec<CodeBlock> = blocks
+        .into_iter()
+        .rev()
+        .filter(|block| {
+            languages.is_empty()
+                || block
+                    .language
+                    .as_ref()
+                    .is_some_and(|l| languages.contains(l))
+        })
+        .filter(|block| !filters.dedupe || seen.insert(block.content.trim().to_string()))
+        .collect();
+    kept.reverse();
+    kept
+}
+
+/// 提取会话中的全部代码块
+///
+/// `project_id_or_path` 的含义与 `resolve_session_file` 相同
+#[tauri::command]
+pub async fn extract_code_blocks(
+    engine: String,
+    session_id: String,
+    project_id_or_path: String,
+    filters: Option<CodeBlockFilters>,
+) -> Result<Vec<CodeBlock>, String> {
+    let filters = filters.unwrap_or_default();
+    tokio::task::spawn_blocking(move || {
+        let path = resolve_session_file(&engine, &session_id, &project_id_or_path)?;
+        let blocks = read_assistant_messages(&engine, &path)?
+            .iter()
+            .enumerate()
+            .flat_map(|(index, message)| blocks_in_message(index, &message.text))
+            .collect();
+        Ok(filter_blocks(blocks, &filters))
+    })
+    .await
+    .map_err(|e| format!("Code block extraction task failed: {}", e))?
+}
+
+// ============================================================================
+// Saving
+// ============================================================================
+
+/// 目标文件已存在时的处理方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OverwritePolicy {
+    /// 跳过该代码块
+    Skip,
+    /// 覆盖已有文件
+    Overwrite,
+    /// 改名为 name_1.ext、name_2.ext ...
+    #[default]
+    Rename,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SaveStatus {
+    Written,
+    Overwritten,
+    Renamed,
+    Skipped,
+    Failed,
+}
+
+/// 单个代码块的写入结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SavedCodeBlock {
+    pub id: String,
+    pub path: Option<String>,
+    pub status: SaveStatus,
+    pub error: Option<String>,
+}
+
+/// 批量写入报告
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SaveCodeBlocksReport {
+    pub target_dir: String,
+    pub items: Vec<SavedCodeBlock>,
+    pub written: usize,
+    pub skipped: usize,
+    pub failed: usize,
+}
+
+/// 把建议的文件名转为目标目录内的相对路径；绝对路径或含 ".." 时只取文件名
+fn safe_relative_path(name: &str) -> Option<PathBuf> {
+    let normalized = name.trim().replace('\\', "/");
+    let path = Path::new(&normalized);
+    let escapes = normalized.contains(':')
+        || path
+            .components()
+            .any(|c| !matches!(c, Component::Normal(_) | Component::CurDir));
+    let relative: PathBuf = if escapes {
+        PathBuf::from(path.file_name()?)
+    } else {
+        path.components().collect()
+    };
+    (!relative.as_os_str().is_empty()).then_some(relative)
+}
+
+/// 在文件名后追加序号，直到找到未被占用的路径
+fn next_free_path(path: &Path, claimed: &HashSet<PathBuf>) -> PathBuf {
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let extension = path
+        .extension()
+        .map(|e| format!(".{}", e.to_string_lossy()))
+        .unwrap_or_default();
+    (1..)
+        .map(|n| path.with_file_name(format!("{}_{}{}", stem, n, extension)))
+        .find(|candidate| !candidate.exists() && !claimed.contains(candidate))
+        .expect("unbounded range always yields a free path")
+}
+
+fn save_blocks(
+    blocks: &[CodeBlock],
+    target_dir: &Path,
+    policy: OverwritePolicy,
+) -> SaveCodeBlocksReport {
+    let mut items = Vec::new();
+    // 本批次已写入的路径：同名代码块与已有文件同样按策略处理
+    let mut claimed: HashSet<PathBuf> = HashSet::new();
+    let mut snippet_count = 0;
+
+    for block in blocks {
+        let relative = block
+            .suggested_filename
+            .as_deref()
+            .and_then(safe_relative_path)
+            .unwrap_or_else(|| {
+                snippet_count += 1;
+                PathBuf::from(format!(
+                    "snippet_{}.{}",
+                    snippet_count,
+                    language_extension(block.language.as_deref())
+                ))
+            });
+        let mut path = target_dir.join(relative);
+
+        let status = if path.exists() || claimed.contains(&path) {
+            match policy {
+                OverwritePolicy::Skip => {
+                    items.push(SavedCodeBlock {
+                        id: block.id.clone(),
+                        path: Some(path.to_string_lossy().to_string()),
+                        status: SaveStatus::Skipped,
+                        error: None,
+                    });
+                    continue;
+                }
+                OverwritePolicy::Overwrite => SaveStatus::Overwritten,
+                OverwritePolicy::Rename => {
+                    path = next_free_path(&path, &claimed);
+                    SaveStatus::Renamed
+                }
+            }
+        } else {
+            SaveStatus::Written
+        };
+
+        let result = path
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| fs::write(&path, &block.content));
+        let (status, error) = match result {
+            Ok(()) => {
+                claimed.insert(path.clone());
+                (status, None)
+            }
+            Err(e) => (
+                SaveStatus::Failed,
+                Some(format!("Failed to write file: {}", e)),
+            ),
+        };
+        items.push(SavedCodeBlock {
+            id: block.id.clone(),
+            path: Some(path.to_string_lossy().to_string()),
+            status,
+            error,
+        });
+    }
+
+    let count = |status: &[SaveStatus]| {
+        items
+            .iter()
+            .filter(|item| status.contains(&item.status))
+            .count()
+    };
+    SaveCodeBlocksReport {
+        target_dir: target_dir.to_string_lossy().to_string(),
+        written: count(&[
+            SaveStatus::Written,
+            SaveStatus::Overwritten,
+            SaveStatus::Renamed,
+        ]),
+        skipped: count(&[SaveStatus::Skipped]),
+        failed: count(&[SaveStatus::Failed]),
+        items,
+    }
+}
+
+/// 把选中的代码块写入目标目录（不存在时创建）
+#[tauri::command]
+pub async fn save_code_blocks(
+    blocks: Vec<CodeBlock>,
+    target_dir: String,
+    overwrite_policy: Option<OverwritePolicy>,
+) -> Result<SaveCodeBlocksReport, String> {
+    let target = PathBuf::from(target_dir.trim());
+    if !target.is_absolute() {
+        return Err(format!(
+            "Target directory must be an absolute path: {}",
+            target_dir
+        ));
+    }
+    fs::create_dir_all(&target).map_err(|e| format!("Failed to create target directory: {}", e))?;
+
+    let policy = overwrite_policy.unwrap_or_default();
+    let report = tokio::task::spawn_blocking(move || save_blocks(&blocks, &target, policy))
+        .await
+        .map_err(|e| format!("Code block save task failed: {}", e))?;
+    log::info!(
+        "Saved code blocks to {}: {} written, {} skipped, {} failed",
+        report.target_dir,
+        report.written,
+        report.skipped,
+        report.failed
+    );
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn segments_fenced_blocks() {
+        let markdown =
+            "intro\n```rust\nfn main() {}\n```\nmiddle\n~~~~\n```\nnested\n~~~~\n```py\nunclosed\n";
+        let segments = MarkdownSegmenter::segment(markdown);
+
+        assert_eq!(
+            segments,
+            vec![
+                MarkdownSegment::Text("intro\n".into()),
+                MarkdownSegment::Code {
+                    info: "rust".into(),
+                    content: "fn main() {}\n".into()
+                },
+                MarkdownSegment::Text("middle\n".into()),
+                MarkdownSegment::Code {
+                    info: "".into(),
+                    content: "```\nnested\n".into()
+                },
+                MarkdownSegment::Code {
+                    info: "py".into(),
+                    content: "unclosed\n".into()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn suggests_filenames_from_context() {
+        let text = "把下面的代码保存为 `src/utils/math.ts`：\n```ts\nexport const one = 1;\n```\n\
+            Then update **Cargo.toml**:\n```toml\n[package]\n```\n\
+            ```rust title=\"src/main.rs\"\nfn main() {}\n```\n\
+            See https://example.com/a.html\n```js\nconsole.log(1)\n```\n";
+        let blocks = blocks_in_message(3, text);
+
+        let names: Vec<Option<&str>> = blocks
+            .iter()
+            .map(|b| b.suggested_filename.as_deref())
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                Some("src/utils/math.ts"),
+                Some("Cargo.toml"),
+                Some("src/main.rs"),
+                None
+            ]
+        );
+        assert_eq!(blocks[0].language.as_deref(), Some("typescript"));
+        assert_eq!(blocks[2].id, "3-2");
+    }
+
+    #[test]
+    fn dedupe_keeps_newest_and_filters_language_aliases() {
+        let mut blocks = blocks_in_message(0, "```rs\nfn a() {}\n```\n```py\nprint(1)\n```\n");
+        blocks.extend(blocks_in_message(1, "```rust\nfn a() {}\n```\n"));
+
+        let kept = filter_blocks(
+            blocks,
+            &CodeBlockFilters {
+                languages: vec!["Rust".into()],
+                dedupe: true,
+            },
+        );
+        assert_eq!(kept.len(), 1);
+        assert_eq!(kept[0].message_index, 1);
+    }
+
+    #[test]
+    fn saves_with_rename_policy_inside_target_dir() {
+        let temp = tempfile::tempdir().unwrap();
+        fs::write(temp.path().join("main.rs"), "old").unwrap();
+        let block = |id: &str, name: Option<&str>| CodeBlock {
+            id: id.into(),
+            language: Some("python".into()),
+            content: format!("# {}\n", id),
+            message_index: 0,
+            suggested_filename: name.map(String::from),
+        };
+        let blocks = vec![
+            block("a", Some("main.rs")),
+            block("b", Some("../../etc/evil.py")),
+            block("c", None),
+            block("d", None),
+        ];
+
+        let report = save_blocks(&blocks, temp.path(), OverwritePolicy::Rename);
+
+        assert_eq!(report.written, 4);
+        assert_eq!(report.items[0].status, SaveStatus::Renamed);
+        assert!(temp.path().join("main_1.rs").is_file());
+        assert!(temp.path().join("evil.py").is_file());
+        assert!(temp.path().join("snippet_2.py").is_file());
+        assert_eq!(
+            fs::read_to_string(temp.path().join("main.rs")).unwrap(),
+            "old"
+        );
+
+        let report = save_blocks(&blocks[..1], temp.path(), OverwritePolicy::Skip);
+        assert_eq!(report.skipped, 1);
+    }
+}
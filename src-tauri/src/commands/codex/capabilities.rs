@@ -0,0 +1,279 @@
+//! Codex 配置项透传与 CLI 版本能力
+//!
+//! 执行选项中的 reasoning_effort / verbosity 以 `-c key=value` 透传给 `codex exec`
+//! （续接会话同样生效）。不同 CLI 版本认识的配置键不同，按已知版本映射表判断：
+//! - CLI 版本复用可用性检测的缓存结果（WSL 模式下为 WSL 内的版本）
+//! - 版本无法识别时视为支持，只记日志
+//! - 不支持的键默认剔除，并在启动前随 session_init 事件提示；strict 模式下直接拒绝执行
+//! - 所选 profile 自己设置了同一配置项时以 profile 为准，与模型选择的处理一致
+//!
+//! 实际透传的配置项写入执行开始的 trace 事件，并随 session_init 事件回显给前端。
+
+use serde::Serialize;
+
+use super::config::{check_codex_availability, parse_codex_version};
+use super::profiles::CodexProfile;
+use crate::commands::messages::{t_with, MessageKey};
+
+/// 推理强度（model_reasoning_effort）
+pub const KEY_REASONING_EFFORT: &str = "model_reasoning_effort";
+/// 输出详略（model_verbosity）
+pub const KEY_VERBOSITY: &str = "model_verbosity";
+
+const REASONING_EFFORTS: &[&str] = &["minimal", "low", "medium", "high", "xhigh"];
+const VERBOSITIES: &[&str] = &["low", "medium", "high"];
+
+/// 配置键 -> 开始支持该键的 CLI 版本
+const CONFIG_KEY_MIN_VERSIONS: &[(&str, (u64, u64, u64))] = &[
+    (KEY_REASONING_EFFORT, (0, 2, 0)),
+    (KEY_VERBOSITY, (0, 24, 0)),
+];
+
+/// 当前 CLI 对可透传配置键的支持情况
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CodexCapabilities {
+    /// 解析出的 CLI 版本（x.y.z），无法识别时为 None
+    pub version: Option<String>,
+    pub supported_keys: Vec<String>,
+    pub unsupported_keys: Vec<String>,
+}
+
+/// 一个 `-c key=value` 配置项
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CodexConfigOverride {
+    pub key: String,
+    pub value: String,
+}
+
+impl CodexConfigOverride {
+    /// `-c` 的参数值
+    pub fn as_arg(&self) -> String {
+        format!("{}={}", self.key, self.value)
+    }
+}
+
+/// 解析后的透传结果：实际透传的配置项与启动前的提示
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ResolvedConfigOverrides {
+    pub overrides: Vec<CodexConfigOverride>,
+    pub warnings: Vec<String>,
+}
+
+fn format_version((major, minor, patch): (u64, u64, u64)) -> String {
+    format!("{}.{}.{}", major, minor, patch)
+}
+
+fn min_version(key: &str) -> Option<(u64, u64, u64)> {
+    CONFIG_KEY_MIN_VERSIONS
+        .iter()
+        .find(|(k, _)| *k == key)
+        .map(|(_, v)| *v)
+}
+
+/// 按版本映射表计算能力；版本未知时视为全部支持
+pub fn capabilities_for_version(version: Option<&str>) -> CodexCapabilities {
+    let parsed = version.and_then(parse_codex_version);
+    let (supported_keys, unsupported_keys) = CONFIG_KEY_MIN_VERSIONS
+        .iter()
+        .map(|(key, _)| key.to_string())
+        .partition(|key| match (parsed, min_version(key)) {
+            (Some(current), Some(min)) => current >= min,
+            _ => true,
+        });
+    CodexCapabilities {
+        version: parsed.map(format_version),
+        supported_keys,
+        unsupported_keys,
+    }
+}
+
+/// 当前 CLI 的能力（版本来自可用性检测的缓存）
+pub async fn current_capabilities() -> CodexCapabilities {
+    let version = check_codex_availability()
+        .await
+        .ok()
+        .filter(|a| a.available)
+        .and_then(|a| a.version);
+    if version.as_deref().and_then(parse_codex_version).is_none() {
+        log::warn!(
+            "[Codex] Unrecognized CLI version {:?}, assuming all config keys are supported",
+            version
+        );
+    }
+    capabilities_for_version(version.as_deref())
+}
+
+fn validate_value(key: &str, value: &str, allowed: &[&str]) -> Result<String, String> {
+    let value = value.trim().to_lowercase();
+    if allowed.contains(&value.as_str()) {
+        Ok(value)
+    } else {
+        Err(format!(
+            "Invalid {} '{}': expected one of {}",
+            key,
+            value,
+            allowed.join(", ")
+        ))
+    }
+}
+
+/// 校验推理强度取值
+pub fn validate_reasoning_effort(value: &str) -> Result<String, String> {
+    validate_value(KEY_REASONING_EFFORT, value, REASONING_EFFORTS)
+}
+
+/// 校验输出详略取值
+pub fn validate_verbosity(value: &str) -> Result<String, String> {
+    validate_value(KEY_VERBOSITY, value, VERBOSITIES)
+}
+
+/// 计算实际透传的配置项
+///
+/// 取值非法时报错；CLI 不支持的键在 strict 模式下报错，否则剔除并给出提示
+pub fn resolve_config_overrides(
+    reasoning_effort: Option<&str>,
+    verbosity: Option<&str>,
+    profile: Option<&CodexProfile>,
+    strict: bool,
+    capabilities: &CodexCapabilities,
+) -> Result<ResolvedConfigOverrides, String> {
+    let requested = [
+        (
+            KEY_REASONING_EFFORT,
+            reasoning_effort,
+            profile.and_then(|p| p.model_reasoning_effort.as_ref()),
+        ),
+        (
+            KEY_VERBOSITY,
+            verbosity,
+            profile.and_then(|p| p.model_verbosity.as_ref()),
+        ),
+    ];
+
+    let mut resolved = ResolvedConfigOverrides::default();
+    for (key, value, profile_value) in requested {
+        let Some(value) = value.map(str::trim).filter(|v| !v.is_empty()) else {
+            continue;
+        };
+        let value = if key == KEY_REASONING_EFFORT {
+            validate_reasoning_effort(value)?
+        } else {
+            validate_verbosity(value)?
+        };
+
+        if profile_value.is_some() {
+            log::info!(
+                "[Codex] Profile defines {}, not overriding it with {}",
+                key,
+                value
+            );
+            continue;
+        }
+
+        if capabilities.unsupported_keys.iter().any(|k| k == key) {
+            let message_key = if strict {
+                MessageKey::CodexConfigUnsupportedStrict
+            } else {
+                MessageKey::CodexConfigUnsupported
+            };
+            let message = t_with(
+                message_key,
+                &[
+                    ("key", &key),
+                    (
+                        "version",
+                        &capabilities.version.as_deref().unwrap_or("unknown"),
+                    ),
+                    (
+                        "minVersion",
+                        &min_version(key).map(format_version).unwrap_or_default(),
+                    ),
+                ],
+            );
+            if strict {
+                return Err(message);
+            }
+            log::warn!(
+                "[Codex] Dropping unsupported config override {}={}",
+                key,
+                value
+            );
+            resolved.warnings.push(message);
+            continue;
+        }
+
+        resolved.overrides.push(CodexConfigOverride {
+            key: key.to_string(),
+            value,
+        });
+    }
+
+    Ok(resolved)
+}
+
+/// 获取当前 Codex CLI 支持透传的配置键
+#[tauri::command]
+pub async fn get_codex_capabilities() -> Result<CodexCapabilities, String> {
+    Ok(current_capabilities().await)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn version_table_decides_supported_keys() {
+        let old = capabilities_for_version(Some("codex-cli 0.20.1"));
+        assert_eq!(old.version.as_deref(), Some("0.20.1"));
+        assert_eq!(old.supported_keys, vec![KEY_REASONING_EFFORT]);
+        assert_eq!(old.unsupported_keys, vec![KEY_VERBOSITY]);
+
+        let new = capabilities_for_version(Some("codex-cli 0.46.0"));
+        assert!(new.unsupported_keys.is_empty());
+
+        let unknown = capabilities_for_version(Some("Unknown version"));
+        assert_eq!(unknown.version, None);
+        assert_eq!(unknown.supported_keys.len(), CONFIG_KEY_MIN_VERSIONS.len());
+    }
+
+    #[test]
+    fn unsupported_keys_are_dropped_or_rejected_in_strict_mode() {
+        let old = capabilities_for_version(Some("0.20.0"));
+
+        let resolved =
+            resolve_config_overrides(Some("High"), Some("low"), None, false, &old).unwrap();
+        assert_eq!(
+            resolved.overrides,
+            vec![CodexConfigOverride {
+                key: KEY_REASONING_EFFORT.to_string(),
+                value: "high".to_string(),
+            }]
+        );
+        assert_eq!(
+            resolved.overrides[0].as_arg(),
+            "model_reasoning_effort=high"
+        );
+        assert_eq!(resolved.warnings.len(), 1);
+        assert!(resolved.warnings[0].contains(KEY_VERBOSITY));
+
+        assert!(resolve_config_overrides(None, Some("low"), None, true, &old).is_err());
+        assert!(resolve_config_overrides(Some("extreme"), None, None, false, &old).is_err());
+    }
+
+    #[test]
+    fn profile_settings_take_precedence() {
+        let profile = CodexProfile {
+            name: "deep".to_string(),
+            model_reasoning_effort: Some("high".to_string()),
+            ..Default::default()
+        };
+        let caps = capabilities_for_version(Some("0.46.0"));
+        let resolved =
+            resolve_config_overrides(Some("low"), Some("high"), Some(&profile), false, &caps)
+                .unwrap();
+        assert_eq!(resolved.overrides.len(), 1);
+        assert_eq!(resolved.overrides[0].key, KEY_VERBOSITY);
+    }
+}
@@ -10,6 +10,7 @@ use rusqlite;
  * - Provider management (presets, switching, CRUD)
  */
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
 use tauri::{AppHandle, Manager};
@@ -18,7 +19,12 @@ use tokio::sync::OnceCell;
 
 // Import platform-specific utilities for window hiding
 use crate::claude_binary::detect_binary_for_tool;
+use crate::commands::app_error::AppError;
 use crate::commands::claude::apply_no_window_async;
+use crate::commands::messages::{t, t_with, MessageKey};
+use crate::commands::provider_presets;
+use crate::commands::rate_limiter;
+use crate::utils::http_headers::{apply_custom_headers, masked_headers, validate_custom_headers};
 // Import WSL utilities
 use super::super::wsl_utils;
 
@@ -32,8 +38,14 @@ pub struct CodexAvailability {
     pub available: bool,
     pub version: Option<String>,
     pub error: Option<String>,
+    /// 非致命提示（如当前配置需要更高版本的 CLI）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub warning: Option<String>,
 }
 
+/// 支持 `model_providers.<id>.http_headers` 的最低 Codex CLI 版本
+const MIN_CODEX_VERSION_FOR_HTTP_HEADERS: (u64, u64, u64) = (0, 10, 0);
+
 /// 全局 Codex 可用性结果缓存
 /// 避免重复创建 WSL 进程检测可用性
 static CODEX_AVAILABILITY_CACHE: OnceCell<CodexAvailability> = OnceCell::const_new();
@@ -76,6 +88,12 @@ pub struct CodexProviderConfig {
     pub is_official: Option<bool>,
     pub is_partner: Option<bool>,
     pub created_at: Option<i64>,
+    /// Extra HTTP headers required by self-hosted gateways (e.g. X-Org-Id)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub custom_headers: Option<HashMap<String, String>>,
+    /// Preset origin; "remote" for presets from the remote manifest
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
 }
 
 /// Current Codex configuration (from ~/.codex directory)
@@ -309,7 +327,85 @@ pub async fn check_codex_availability() -> Result<CodexAvailability, String> {
         .await;
 
     log::debug!("[Codex] Returning cached availability: {:?}", result);
-    Ok(result.clone())
+    let mut result = result.clone();
+    if result.available && current_config_uses_http_headers() {
+        result.warning = http_headers_version_warning(result.version.as_deref());
+    }
+    Ok(result)
+}
+
+/// 从版本输出中解析语义化版本号（如 "codex-cli 0.46.0" -> (0, 46, 0)）
+pub(crate) fn parse_codex_version(version: &str) -> Option<(u64, u64, u64)> {
+    let re = regex::Regex::new(r"(\d+)\.(\d+)\.(\d+)").ok()?;
+    let caps = re.captures(version)?;
+    Some((
+        caps[1].parse().ok()?,
+        caps[2].parse().ok()?,
+        caps[3].parse().ok()?,
+    ))
+}
+
+/// 当 CLI 版本过低、不支持自定义请求头时返回提示
+fn http_headers_version_warning(version: Option<&str>) -> Option<String> {
+    let (major, minor, patch) = MIN_CODEX_VERSION_FOR_HTTP_HEADERS;
+    match version.and_then(parse_codex_version) {
+        Some(v) if v >= MIN_CODEX_VERSION_FOR_HTTP_HEADERS => None,
+        _ => Some(format!(
+            "Custom HTTP headers are configured but require Codex CLI >= {}.{}.{} (model_providers.*.http_headers)",
+            major, minor, patch
+        )),
+    }
+}
+
+/// 当前 config.toml 中是否有供应商配置了 http_headers
+fn current_config_uses_http_headers() -> bool {
+    let Ok(config_path) = get_codex_config_path() else {
+        return false;
+    };
+    let Ok(content) = fs::read_to_string(config_path) else {
+        return false;
+    };
+    let Ok(table) = toml::from_str::<toml::Table>(&content) else {
+        return false;
+    };
+    table
+        .get("model_providers")
+        .and_then(|p| p.as_table())
+        .is_some_and(|providers| {
+            providers
+                .values()
+                .any(|provider| provider.get("http_headers").is_some())
+        })
+}
+
+/// 将自定义请求头写入当前 model_provider 的 `http_headers` 段
+fn inject_http_headers(
+    provider_config: &str,
+    headers: &HashMap<String, String>,
+) -> Result<String, String> {
+    let mut table: toml::Table = toml::from_str(provider_config)
+        .map_err(|e| format!("Invalid TOML configuration: {}", e))?;
+
+    let provider_id = table
+        .get("model_provider")
+        .and_then(|v| v.as_str())
+        .map(|s| s.to_string())
+        .ok_or("Custom headers require a custom model_provider in config.toml")?;
+
+    let provider = table
+        .get_mut("model_providers")
+        .and_then(|p| p.as_table_mut())
+        .and_then(|p| p.get_mut(&provider_id))
+        .and_then(|p| p.as_table_mut())
+        .ok_or_else(|| format!("[model_providers.{}] not found in config.toml", provider_id))?;
+
+    let http_headers: toml::Table = headers
+        .iter()
+        .map(|(name, value)| (name.clone(), toml::Value::String(value.clone())))
+        .collect();
+    provider.insert("http_headers".to_string(), toml::Value::Table(http_headers));
+
+    toml::to_string_pretty(&table).map_err(|e| format!("Failed to serialize config: {}", e))
 }
 
 /// 实际执行 Codex 可用性检测（内部函数）
@@ -334,6 +430,7 @@ async fn do_check_codex_availability() -> CodexAvailability {
                     available: true,
                     version: Some(format!("WSL: {}", version)),
                     error: None,
+                    warning: None,
                 };
             }
         }
@@ -372,6 +469,7 @@ async fn do_check_codex_availability() -> CodexAvailability {
                         available: true,
                         version: Some(version),
                         error: None,
+                        warning: None,
                     };
                 } else {
                     log::warn!(
@@ -420,6 +518,7 @@ async fn do_check_codex_availability() -> CodexAvailability {
                         available: true,
                         version: Some(version),
                         error: None,
+                        warning: None,
                     };
                 }
             }
@@ -435,6 +534,7 @@ async fn do_check_codex_availability() -> CodexAvailability {
         available: false,
         version: None,
         error: Some("Codex CLI not found. Please set CODEX_PATH or install codex CLI".to_string()),
+        warning: None,
     }
 }
 
@@ -1001,7 +1101,7 @@ fn get_codex_auth_path() -> Result<PathBuf, String> {
 }
 
 /// Get Codex config.toml path
-fn get_codex_config_path() -> Result<PathBuf, String> {
+pub(crate) fn get_codex_config_path() -> Result<PathBuf, String> {
     Ok(get_codex_config_dir()?.join("config.toml"))
 }
 
@@ -1051,20 +1151,27 @@ fn extract_model_from_config(config: &str) -> Option<String> {
 
 /// Get Codex provider presets (custom user-defined presets)
 #[tauri::command]
-pub async fn get_codex_provider_presets() -> Result<Vec<CodexProviderConfig>, String> {
+pub async fn get_codex_provider_presets() -> Result<Vec<CodexProviderConfig>, AppError> {
     log::info!("[Codex Provider] Getting provider presets");
 
     let providers_path = get_codex_providers_path()?;
 
-    if !providers_path.exists() {
-        return Ok(vec![]);
-    }
-
-    let content = fs::read_to_string(&providers_path)
-        .map_err(|e| format!("Failed to read providers.json: {}", e))?;
+    let mut providers: Vec<CodexProviderConfig> = if providers_path.exists() {
+        let content = fs::read_to_string(&providers_path)
+            .map_err(|e| format!("Failed to read providers.json: {}", e))?;
+        serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to parse providers.json: {}", e))?
+    } else {
+        vec![]
+    };
 
-    let providers: Vec<CodexProviderConfig> = serde_json::from_str(&content)
-        .map_err(|e| format!("Failed to parse providers.json: {}", e))?;
+    // Append presets from the remote manifest; user presets keep their ids
+    let remote = provider_presets::remote_presets().await;
+    for preset in &remote.codex {
+        if !providers.iter().any(|p| p.id == preset.id) {
+            providers.push(provider_presets::to_codex_provider(preset));
+        }
+    }
 
     Ok(providers)
 }
@@ -1072,7 +1179,7 @@ pub async fn get_codex_provider_presets() -> Result<Vec<CodexProviderConfig>, St
 /// Get current Codex configuration
 /// Supports both Native Windows and WSL modes
 #[tauri::command]
-pub async fn get_current_codex_config() -> Result<CurrentCodexConfig, String> {
+pub async fn get_current_codex_config() -> Result<CurrentCodexConfig, AppError> {
     let is_wsl_mode = should_use_wsl_config();
     log::info!("[Codex Provider] Getting current config (WSL mode: {})", is_wsl_mode);
 
@@ -1117,7 +1224,7 @@ pub async fn get_current_codex_config() -> Result<CurrentCodexConfig, String> {
 /// Preserves user's custom settings and OAuth tokens
 /// Supports both Native Windows and WSL modes
 #[tauri::command]
-pub async fn switch_codex_provider(config: CodexProviderConfig) -> Result<String, String> {
+pub async fn switch_codex_provider(config: CodexProviderConfig) -> Result<String, AppError> {
     log::info!("[Codex Provider] Switching to provider: {}", config.name);
 
     let is_wsl_mode = should_use_wsl_config();
@@ -1138,10 +1245,23 @@ pub async fn switch_codex_provider(config: CodexProviderConfig) -> Result<String
             .map_err(|e| format!("Failed to create .codex directory at {:?}: {}", config_dir, e))?;
     }
 
+    // Apply custom headers to the provider's config.toml section
+    let provider_config = match config.custom_headers.as_ref().filter(|h| !h.is_empty()) {
+        Some(headers) => {
+            validate_custom_headers(headers)?;
+            log::info!(
+                "[Codex Provider] Applying custom headers: {:?}",
+                masked_headers(headers)
+            );
+            inject_http_headers(&config.config, headers)?
+        }
+        None => config.config.clone(),
+    };
+
     // Validate new TOML if not empty
-    let new_config_table: Option<toml::Table> = if !config.config.trim().is_empty() {
+    let new_config_table: Option<toml::Table> = if !provider_config.trim().is_empty() {
         Some(
-            toml::from_str(&config.config)
+            toml::from_str(&provider_config)
                 .map_err(|e| format!("Invalid TOML configuration: {}", e))?,
         )
     } else {
@@ -1235,11 +1355,11 @@ pub async fn switch_codex_provider(config: CodexProviderConfig) -> Result<String
             }
         } else {
             // Existing config is invalid, use new config directly
-            config.config.clone()
+            provider_config.clone()
         }
     } else {
         // No existing config, use new config directly
-        config.config.clone()
+        provider_config.clone()
     };
 
     // Write merged config.toml
@@ -1250,18 +1370,25 @@ pub async fn switch_codex_provider(config: CodexProviderConfig) -> Result<String
 
     // Return success message with mode info
     let mode_info = if is_wsl_mode { " (WSL)" } else { "" };
-    Ok(format!(
-        "Successfully switched to Codex provider: {}{}",
-        config.name,
-        mode_info
+    Ok(t_with(
+        MessageKey::ProviderEngineSwitched,
+        &[
+            ("engine", &"Codex"),
+            ("name", &config.name),
+            ("mode", &mode_info),
+        ],
     ))
 }
 
 /// Add a new Codex provider configuration
 #[tauri::command]
-pub async fn add_codex_provider_config(config: CodexProviderConfig) -> Result<String, String> {
+pub async fn add_codex_provider_config(config: CodexProviderConfig) -> Result<String, AppError> {
     log::info!("[Codex Provider] Adding provider: {}", config.name);
 
+    if let Some(headers) = &config.custom_headers {
+        validate_custom_headers(headers)?;
+    }
+
     let providers_path = get_codex_providers_path()?;
 
     // Ensure parent directory exists
@@ -1282,7 +1409,7 @@ pub async fn add_codex_provider_config(config: CodexProviderConfig) -> Result<St
 
     // Check for duplicate ID
     if providers.iter().any(|p| p.id == config.id) {
-        return Err(format!("Provider with ID '{}' already exists", config.id));
+        return Err(t_with(MessageKey::ProviderIdExists, &[("id", &config.id)]).into());
     }
 
     providers.push(config.clone());
@@ -1297,21 +1424,25 @@ pub async fn add_codex_provider_config(config: CodexProviderConfig) -> Result<St
         "[Codex Provider] Successfully added provider: {}",
         config.name
     );
-    Ok(format!(
-        "Successfully added Codex provider: {}",
-        config.name
+    Ok(t_with(
+        MessageKey::ProviderEngineAdded,
+        &[("engine", &"Codex"), ("name", &config.name)],
     ))
 }
 
 /// Update an existing Codex provider configuration
 #[tauri::command]
-pub async fn update_codex_provider_config(config: CodexProviderConfig) -> Result<String, String> {
+pub async fn update_codex_provider_config(config: CodexProviderConfig) -> Result<String, AppError> {
     log::info!("[Codex Provider] Updating provider: {}", config.name);
 
+    if let Some(headers) = &config.custom_headers {
+        validate_custom_headers(headers)?;
+    }
+
     let providers_path = get_codex_providers_path()?;
 
     if !providers_path.exists() {
-        return Err(format!("Provider with ID '{}' not found", config.id));
+        return Err(t_with(MessageKey::ProviderNotFound, &[("id", &config.id)]).into());
     }
 
     let content = fs::read_to_string(&providers_path)
@@ -1323,7 +1454,7 @@ pub async fn update_codex_provider_config(config: CodexProviderConfig) -> Result
     let index = providers
         .iter()
         .position(|p| p.id == config.id)
-        .ok_or_else(|| format!("Provider with ID '{}' not found", config.id))?;
+        .ok_or_else(|| t_with(MessageKey::ProviderNotFound, &[("id", &config.id)]))?;
 
     providers[index] = config.clone();
 
@@ -1337,21 +1468,21 @@ pub async fn update_codex_provider_config(config: CodexProviderConfig) -> Result
         "[Codex Provider] Successfully updated provider: {}",
         config.name
     );
-    Ok(format!(
-        "Successfully updated Codex provider: {}",
-        config.name
+    Ok(t_with(
+        MessageKey::ProviderEngineUpdated,
+        &[("engine", &"Codex"), ("name", &config.name)],
     ))
 }
 
 /// Delete a Codex provider configuration
 #[tauri::command]
-pub async fn delete_codex_provider_config(id: String) -> Result<String, String> {
+pub async fn delete_codex_provider_config(id: String) -> Result<String, AppError> {
     log::info!("[Codex Provider] Deleting provider: {}", id);
 
     let providers_path = get_codex_providers_path()?;
 
     if !providers_path.exists() {
-        return Err(format!("Provider with ID '{}' not found", id));
+        return Err(t_with(MessageKey::ProviderNotFound, &[("id", &id)]).into());
     }
 
     let content = fs::read_to_string(&providers_path)
@@ -1364,7 +1495,7 @@ pub async fn delete_codex_provider_config(id: String) -> Result<String, String>
     providers.retain(|p| p.id != id);
 
     if providers.len() == initial_len {
-        return Err(format!("Provider with ID '{}' not found", id));
+        return Err(t_with(MessageKey::ProviderNotFound, &[("id", &id)]).into());
     }
 
     // Save providers
@@ -1374,12 +1505,15 @@ pub async fn delete_codex_provider_config(id: String) -> Result<String, String>
         .map_err(|e| format!("Failed to write providers.json: {}", e))?;
 
     log::info!("[Codex Provider] Successfully deleted provider: {}", id);
-    Ok(format!("Successfully deleted Codex provider: {}", id))
+    Ok(t_with(
+        MessageKey::ProviderEngineDeleted,
+        &[("engine", &"Codex"), ("id", &id)],
+    ))
 }
 
 /// Reorder Codex provider configurations
 #[tauri::command]
-pub async fn reorder_codex_provider_configs(ids: Vec<String>) -> Result<String, String> {
+pub async fn reorder_codex_provider_configs(ids: Vec<String>) -> Result<String, AppError> {
     log::info!("[Codex Provider] Reordering providers");
 
     let providers_path = get_codex_providers_path()?;
@@ -1415,12 +1549,15 @@ pub async fn reorder_codex_provider_configs(ids: Vec<String>) -> Result<String,
         .map_err(|e| format!("Failed to write providers.json: {}", e))?;
 
     log::info!("[Codex Provider] Successfully reordered providers");
-    Ok("Successfully reordered Codex providers".to_string())
+    Ok(t_with(
+        MessageKey::ProviderEngineReordered,
+        &[("engine", &"Codex")],
+    ))
 }
 
 /// Clear Codex provider configuration (reset to official)
 #[tauri::command]
-pub async fn clear_codex_provider_config() -> Result<String, String> {
+pub async fn clear_codex_provider_config() -> Result<String, AppError> {
     log::info!("[Codex Provider] Clearing config");
 
     let auth_path = get_codex_auth_path()?;
@@ -1438,7 +1575,7 @@ pub async fn clear_codex_provider_config() -> Result<String, String> {
     }
 
     log::info!("[Codex Provider] Successfully cleared config");
-    Ok("Successfully cleared Codex configuration. Now using official OpenAI.".to_string())
+    Ok(t(MessageKey::ProviderCodexCleared))
 }
 
 /// Test Codex provider connection
@@ -1446,7 +1583,8 @@ pub async fn clear_codex_provider_config() -> Result<String, String> {
 pub async fn test_codex_provider_connection(
     base_url: String,
     api_key: Option<String>,
-) -> Result<String, String> {
+    custom_headers: Option<HashMap<String, String>>,
+) -> Result<String, AppError> {
     log::info!("[Codex Provider] Testing connection to: {}", base_url);
 
     // Simple connectivity test - just try to reach the endpoint
@@ -1462,21 +1600,25 @@ pub async fn test_codex_provider_connection(
     if let Some(key) = api_key {
         request = request.header("Authorization", format!("Bearer {}", key));
     }
+    let request = apply_custom_headers(request, custom_headers.as_ref())?;
 
-    match request.send().await {
+    match rate_limiter::send(&base_url, request).await {
         Ok(response) => {
             let status = response.status();
             if status.is_success() || status.as_u16() == 401 {
                 // 401 means the endpoint exists but auth is required
-                Ok(format!(
-                    "Connection test successful: endpoint is reachable (status: {})",
-                    status
+                Ok(t_with(
+                    MessageKey::ProviderConnectionReachable,
+                    &[("status", &status)],
                 ))
             } else {
-                Ok(format!("Connection test completed with status: {}", status))
+                Ok(t_with(
+                    MessageKey::ProviderConnectionStatus,
+                    &[("status", &status)],
+                ))
             }
         }
-        Err(e) => Err(format!("Connection test failed: {}", e)),
+        Err(e) => Err(t_with(MessageKey::ProviderConnectionFailed, &[("error", &e)]).into()),
     }
 }
 
@@ -0,0 +1,246 @@
+/**
+ * Codex Context Estimation Module
+ *
+ * Estimates how many tokens a Codex session will occupy when resumed, so an
+ * oversized session can be reported before spawning a process that would fail
+ * with a context window error.
+ */
+use serde::Serialize;
+use serde_json::Value;
+use std::path::Path;
+
+use super::super::session_utils::resolve_session_file;
+use crate::utils::jsonl_reader::for_each_line;
+
+/// Prefix of the error returned by `resume_codex` when the context would overflow;
+/// the frontend matches on it to offer compaction instead of retrying
+pub const CONTEXT_OVERFLOW_ERROR_PREFIX: &str = "CONTEXT_OVERFLOW";
+
+/// Rough characters-per-token ratio used when the session has no usage events
+const CHARS_PER_TOKEN: u64 = 4;
+
+/// Token footprint of a Codex session against a model's context window
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CodexContextEstimate {
+    /// Tokens already in the session context
+    pub session_tokens: u64,
+    /// Tokens of the prompt about to be sent
+    pub prompt_tokens: u64,
+    pub total_tokens: u64,
+    /// Model the estimate is checked against
+    pub model: Option<String>,
+    /// Context window of that model, if known
+    pub context_window: Option<u64>,
+    /// "usage" when taken from the CLI's token_count events, "heuristic" otherwise
+    pub source: String,
+    pub overflow: bool,
+}
+
+/// Known context windows for models whose sessions do not report one
+pub(crate) fn model_context_window(model: &str) -> Option<u64> {
+    let model = model.to_lowercase();
+    if model.starts_with("gpt-5") || model.contains("codex") {
+        Some(272_000)
+    } else if model.starts_with("gpt-4.1") {
+        Some(1_047_576)
+    } else if model.starts_with("o3") || model.starts_with("o4") {
+        Some(200_000)
+    } else if model.starts_with("gpt-4o") {
+        Some(128_000)
+    } else {
+        None
+    }
+}
+
+fn estimate_text_tokens(text: &str) -> u64 {
+    (text.chars().count() as u64).div_ceil(CHARS_PER_TOKEN)
+}
+
+/// Text length of a response_item payload (messages, reasoning summaries, tool calls)
+fn response_item_chars(payload: &Value) -> u64 {
+    let mut chars = 0;
+    for key in ["content", "summary"] {
+        if let Some(blocks) = payload.get(key).and_then(|v| v.as_array()) {
+            for block in blocks {
+                if let Some(text) = block.get("text").and_then(|v| v.as_str()) {
+                    chars += text.chars().count() as u64;
+                }
+            }
+        }
+    }
+    for key in ["arguments", "output", "input"] {
+        if let Some(text) = payload.get(key).and_then(|v| v.as_str()) {
+            chars += text.chars().count() as u64;
+        }
+    }
+    chars
+}
+
+/// Estimate the context footprint of a session file before sending `prompt`
+///
+/// Uses the last `token_count` event (input + output of the latest turn) when
+/// present; otherwise counts the text of the conversation since the last
+/// compaction. `model` overrides the session's own model for the window lookup.
+pub fn estimate_session_context(
+    path: &Path,
+    model: Option<&str>,
+    prompt: &str,
+) -> Result<CodexContextEstimate, String> {
+    let mut session_model: Option<String> = None;
+    let mut reported_window: Option<u64> = None;
+    let mut usage_tokens: Option<u64> = None;
+    let mut heuristic_chars: u64 = 0;
+
+    for_each_line(path, |_, line| {
+        let Ok(event) = serde_json::from_str::<Value>(line) else {
+            return std::ops::ControlFlow::Continue(());
+        };
+        let payload = &event["payload"];
+
+        match event["type"].as_str().unwrap_or("") {
+            "session_meta" | "turn_context" => {
+                if let Some(m) = payload["model"].as_str() {
+                    session_model = Some(m.to_string());
+                }
+            }
+            "compacted" => {
+                // History before a compaction is no longer sent to the model
+                heuristic_chars = payload["message"]
+                    .as_str()
+                    .map(|s| s.chars().count() as u64)
+                    .unwrap_or(0);
+                usage_tokens = None;
+            }
+            "response_item" => heuristic_chars += response_item_chars(payload),
+            "event_msg" if payload["type"].as_str() == Some("token_count") => {
+                let info = &payload["info"];
+                if let Some(window) = info["model_context_window"].as_u64() {
+                    reported_window = Some(window);
+                }
+                let last = &info["last_token_usage"];
+                if let Some(input) = last["input_tokens"].as_u64() {
+                    usage_tokens = Some(input + last["output_tokens"].as_u64().unwrap_or(0));
+                }
+            }
+            _ => {}
+        }
+        std::ops::ControlFlow::Continue(())
+    })
+    .map_err(|e| format!("Failed to read session file: {}", e))?;
+
+    let (session_tokens, source) = match usage_tokens {
+        Some(tokens) => (tokens, "usage"),
+        None => (heuristic_chars.div_ceil(CHARS_PER_TOKEN), "heuristic"),
+    };
+
+    // The window reported by the CLI only applies if the model is unchanged
+    let model_changed = match (model, session_model.as_deref()) {
+        (Some(requested), Some(recorded)) => requested != recorded,
+        _ => false,
+    };
+    let effective_model = model.map(String::from).or(session_model);
+    let context_window = if model_changed { None } else { reported_window }
+        .or_else(|| effective_model.as_deref().and_then(model_context_window));
+
+    let prompt_tokens = estimate_text_tokens(prompt);
+    let total_tokens = session_tokens + prompt_tokens;
+
+    Ok(CodexContextEstimate {
+        session_tokens,
+        prompt_tokens,
+        total_tokens,
+        model: effective_model,
+        context_window,
+        source: source.to_string(),
+        overflow: context_window.is_some_and(|window| total_tokens >= window),
+    })
+}
+
+/// Build the error returned instead of spawning a resume that would overflow
+pub fn context_overflow_error(estimate: &CodexContextEstimate) -> String {
+    format!(
+        "{}: 预计上下文约 {} tokens，超出模型 {} 的上下文窗口 {} tokens。建议先压缩会话（/compact）或开启新会话，也可强制继续。",
+        CONTEXT_OVERFLOW_ERROR_PREFIX,
+        estimate.total_tokens,
+        estimate.model.as_deref().unwrap_or("unknown"),
+        estimate.context_window.unwrap_or(0)
+    )
+}
+
+/// Estimate the context footprint of resuming a Codex session
+#[tauri::command]
+pub async fn estimate_codex_context(
+    session_id: String,
+    model: Option<String>,
+    prompt: Option<String>,
+) -> Result<CodexContextEstimate, String> {
+    tokio::task::spawn_blocking(move || {
+        let path = resolve_session_file("codex", &session_id, "")?;
+        estimate_session_context(&path, model.as_deref(), prompt.as_deref().unwrap_or(""))
+    })
+    .await
+    .map_err(|e| format!("Context estimation task failed: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::io::Write;
+
+    fn write_session(events: &[Value]) -> tempfile::NamedTempFile {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        for event in events {
+            writeln!(file, "{}", event).unwrap();
+        }
+        file
+    }
+
+    fn token_count(input: u64, output: u64, window: u64) -> Value {
+        json!({"type": "event_msg", "payload": {"type": "token_count", "info": {
+            "last_token_usage": {"input_tokens": input, "output_tokens": output},
+            "model_context_window": window,
+        }}})
+    }
+
+    #[test]
+    fn uses_last_reported_usage_and_window() {
+        let file = write_session(&[
+            json!({"type": "session_meta", "payload": {"id": "s1", "model": "gpt-5-codex"}}),
+            token_count(10_000, 500, 272_000),
+            token_count(250_000, 30_000, 272_000),
+        ]);
+
+        let estimate = estimate_session_context(file.path(), None, "continue").unwrap();
+        assert_eq!(estimate.source, "usage");
+        assert_eq!(estimate.session_tokens, 280_000);
+        assert_eq!(estimate.context_window, Some(272_000));
+        assert!(estimate.overflow);
+
+        // A different model falls back to the known window table
+        let estimate = estimate_session_context(file.path(), Some("gpt-4.1"), "").unwrap();
+        assert_eq!(estimate.context_window, Some(1_047_576));
+        assert!(!estimate.overflow);
+    }
+
+    #[test]
+    fn falls_back_to_text_heuristic_after_compaction() {
+        let message = |text: &str| {
+            json!({"type": "response_item", "payload": {"type": "message", "role": "user",
+                "content": [{"type": "input_text", "text": text}]}})
+        };
+        let file = write_session(&[
+            json!({"type": "session_meta", "payload": {"id": "s1"}}),
+            message(&"x".repeat(4_000)),
+            json!({"type": "compacted", "payload": {"message": "y".repeat(400)}}),
+            message(&"z".repeat(400)),
+        ]);
+
+        let estimate = estimate_session_context(file.path(), Some("unknown-model"), "").unwrap();
+        assert_eq!(estimate.source, "heuristic");
+        assert_eq!(estimate.session_tokens, 200);
+        assert_eq!(estimate.context_window, None);
+        assert!(!estimate.overflow);
+    }
+}
@@ -10,18 +10,34 @@ use chrono::Utc;
  */
 use serde::{Deserialize, Serialize};
 use std::fs;
-use std::path::PathBuf;
+use std::ops::ControlFlow;
+use std::path::{Path, PathBuf};
+use tauri::AppHandle;
 
+use super::super::messages::{t, t_with, MessageKey};
 // Import simple_git for rewind operations
 use super::super::simple_git;
+// Protected paths snapshot and post-task check
+use super::super::protected_paths::{
+    attach_restore_records, capture_task_snapshot, check_protected_changes,
+};
 // Import rewind helpers/types shared with Claude
 use super::super::prompt_tracker::{
-    load_execution_config, PromptRecord as ClaudePromptRecord, RewindCapabilities, RewindMode,
+    load_execution_config, report_snapshot_failure, revert_single_prompt_range,
+    PromptCompletionResult, PromptRecord as ClaudePromptRecord, RewindCapabilities, RewindMode,
+};
+// Import execution tracing
+use super::super::execution_trace::{
+    record_trace_event, with_trace_trailer, TraceFields, STAGE_GIT_SNAPSHOT, STAGE_PROMPT_RECORDED,
 };
-// Import WSL utilities
-use super::super::wsl_utils;
 // Import session helpers
-use super::session::find_session_file;
+use super::super::session_annotations::bind_prompt_trace;
+use super::super::session_invalidation::invalidate_session;
+use super::super::session_utils::resolve_session_file;
+use super::session::is_codex_context_message;
+use crate::utils::jsonl_reader::for_each_line;
+use crate::utils::session_lock::{write_session_file, FileSnapshot, SessionLock};
+use crate::utils::timestamp::{deserialize_unix_seconds, parse_timestamp_value};
 
 // Align Codex prompt record type with Claude prompt tracker representation
 pub type PromptRecord = ClaudePromptRecord;
@@ -48,7 +64,15 @@ pub struct CodexPromptGitRecord {
     pub prompt_index: usize,
     pub commit_before: String,
     pub commit_after: Option<String>,
-    pub timestamp: String,
+    /// UTC Unix seconds (older records stored an RFC3339 string)
+    #[serde(default, deserialize_with = "deserialize_unix_seconds")]
+    pub timestamp: i64,
+    /// Trace ID of the execution that produced this prompt
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trace_id: Option<String>,
+    /// When this prompt's code changes were reverted on their own (UTC Unix seconds)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reverted_at: Option<i64>,
 }
 
 /// Collection of Git records for a Codex session
@@ -79,27 +103,6 @@ pub fn get_codex_git_records_dir() -> Result<PathBuf, String> {
     Ok(records_dir)
 }
 
-/// Get the Codex sessions directory
-/// On Windows with WSL mode enabled, returns the WSL UNC path
-pub fn get_codex_sessions_dir() -> Result<PathBuf, String> {
-    // Check for WSL mode on Windows
-    #[cfg(target_os = "windows")]
-    {
-        let wsl_config = wsl_utils::get_wsl_config();
-        if wsl_config.enabled {
-            if let Some(sessions_dir) = wsl_utils::get_wsl_codex_sessions_dir() {
-                log::debug!("[Codex] Using WSL sessions directory: {:?}", sessions_dir);
-                return Ok(sessions_dir);
-            }
-        }
-    }
-
-    // Native mode: use local home directory
-    let home_dir = dirs::home_dir().ok_or_else(|| "Failed to get home directory".to_string())?;
-
-    Ok(home_dir.join(".codex").join("sessions"))
-}
-
 // ============================================================================
 // Git Records CRUD Operations
 // ============================================================================
@@ -162,19 +165,42 @@ pub fn truncate_codex_git_records(session_id: &str, prompt_index: usize) -> Resu
 /// Extract all user prompts from a Codex session JSONL
 /// This mirrors Claude prompt extraction so indices stay consistent
 pub fn extract_codex_prompts(session_id: &str) -> Result<Vec<PromptRecord>, String> {
-    let sessions_dir = get_codex_sessions_dir()?;
-    let session_file = find_session_file(&sessions_dir, session_id)
-        .ok_or_else(|| format!("Session file not found for: {}", session_id))?;
+    let session_file = resolve_session_file("codex", session_id, "")?;
 
-    let content = fs::read_to_string(&session_file)
-        .map_err(|e| format!("Failed to read session file: {}", e))?;
+    let mut prompts = extract_codex_prompts_from_file(&session_file)?;
+
+    // Enrich with git records (if present)
+    let git_records = load_codex_git_records(session_id)?;
+    for prompt in prompts.iter_mut() {
+        if let Some(record) = git_records
+            .records
+            .iter()
+            .find(|r| r.prompt_index == prompt.index)
+        {
+            prompt.git_commit_before = record.commit_before.clone();
+            prompt.git_commit_after = record.commit_after.clone();
+            prompt.reverted_at = record.reverted_at;
+            prompt.source = "project".to_string();
 
+            prompt.fill_missing_timestamp(record.timestamp);
+        }
+    }
+    attach_restore_records("codex", session_id, &mut prompts);
+
+    Ok(prompts)
+}
+
+/// Extract user prompts from a Codex rollout file (without git record enrichment)
+pub(crate) fn extract_codex_prompts_from_file(
+    session_file: &Path,
+) -> Result<Vec<PromptRecord>, String> {
     let mut prompts: Vec<PromptRecord> = Vec::new();
     let mut prompt_index = 0;
 
-    for (line_idx, line) in content.lines().enumerate() {
+    // Stream line by line so huge sessions never live in memory as a whole
+    for_each_line(session_file, |line_idx, line| {
         if line.trim().is_empty() {
-            continue;
+            return ControlFlow::Continue(());
         }
 
         if let Ok(event) = serde_json::from_str::<serde_json::Value>(line) {
@@ -187,10 +213,7 @@ pub fn extract_codex_prompts(session_id: &str) -> Result<Vec<PromptRecord>, Stri
                     for item in content {
                         if item["type"].as_str() == Some("input_text") {
                             if let Some(text) = item["text"].as_str() {
-                                if !text.contains("<environment_context>")
-                                    && !text.contains("# AGENTS.md instructions")
-                                    && !text.trim().is_empty()
-                                {
+                                if !is_codex_context_message(text) && !text.trim().is_empty() {
                                     prompt_text = Some(text.to_string());
                                     break;
                                 }
@@ -200,46 +223,27 @@ pub fn extract_codex_prompts(session_id: &str) -> Result<Vec<PromptRecord>, Stri
                 }
 
                 if let Some(text) = prompt_text {
-                    let timestamp = event["timestamp"]
-                        .as_str()
-                        .and_then(|ts| chrono::DateTime::parse_from_rfc3339(ts).ok())
-                        .map(|dt| dt.timestamp())
-                        .unwrap_or_else(|| chrono::Utc::now().timestamp());
-
-                    prompts.push(PromptRecord {
+                    let mut prompt = PromptRecord {
                         index: prompt_index,
                         text,
                         git_commit_before: String::new(),
                         git_commit_after: None,
-                        timestamp,
+                        timestamp: 0,
+                        timestamp_ms: 0,
                         source: "cli".to_string(), // default to CLI; update below if git record exists
                         line_number: line_idx,
-                    });
+                        protected_restore: None,
+                        reverted_at: None,
+                    };
+                    prompt.set_sent_at(parse_timestamp_value(&event["timestamp"]));
+                    prompts.push(prompt);
                     prompt_index += 1;
                 }
             }
         }
-    }
-
-    // Enrich with git records (if present)
-    let git_records = load_codex_git_records(session_id)?;
-    for prompt in prompts.iter_mut() {
-        if let Some(record) = git_records
-            .records
-            .iter()
-            .find(|r| r.prompt_index == prompt.index)
-        {
-            prompt.git_commit_before = record.commit_before.clone();
-            prompt.git_commit_after = record.commit_after.clone();
-            prompt.source = "project".to_string();
-
-            if prompt.timestamp == 0 {
-                if let Ok(ts) = chrono::DateTime::parse_from_rfc3339(&record.timestamp) {
-                    prompt.timestamp = ts.timestamp();
-                }
-            }
-        }
-    }
+        ControlFlow::Continue(())
+    })
+    .map_err(|e| format!("Failed to read session file: {}", e))?;
 
     Ok(prompts)
 }
@@ -299,9 +303,7 @@ pub async fn check_codex_rewind_capabilities(
             conversation: true,
             code: false,
             both: false,
-            warning: Some(
-                "Git 操作已在配置中禁用。只能撤回对话历史，无法回滚代码变更。".to_string(),
-            ),
+            warning: Some(t(MessageKey::RewindGitDisabled)),
             source: prompt.source.clone(),
         });
     }
@@ -322,7 +324,7 @@ pub async fn check_codex_rewind_capabilities(
             warning: if has_valid_commit {
                 None
             } else {
-                Some("此提示词没有关联的 Git 记录，只能删除对话历史。".to_string())
+                Some(t(MessageKey::RewindNoValidCommit))
             },
             source: "project".to_string(),
         })
@@ -331,9 +333,7 @@ pub async fn check_codex_rewind_capabilities(
             conversation: true,
             code: false,
             both: false,
-            warning: Some(
-                "此提示词没有关联的 Git 记录（可能来自 CLI），只能删除对话历史。".to_string(),
-            ),
+            warning: Some(t(MessageKey::RewindNoGitRecord)),
             source: prompt.source.clone(),
         })
     }
@@ -346,9 +346,7 @@ pub async fn check_codex_rewind_capabilities(
 /// Get prompt text from Codex session file
 #[allow(dead_code)]
 pub fn get_codex_prompt_text(session_id: &str, prompt_index: usize) -> Result<String, String> {
-    let sessions_dir = get_codex_sessions_dir()?;
-    let session_file = find_session_file(&sessions_dir, session_id)
-        .ok_or_else(|| format!("Session file not found for: {}", session_id))?;
+    let session_file = resolve_session_file("codex", session_id, "")?;
 
     use std::io::{BufRead, BufReader};
     let file =
@@ -373,10 +371,7 @@ pub fn get_codex_prompt_text(session_id: &str, prompt_index: usize) -> Result<St
                                 if item["type"].as_str() == Some("input_text") {
                                     if let Some(text) = item["text"].as_str() {
                                         // Skip system messages
-                                        if !text.contains("<environment_context>")
-                                            && !text.contains("# AGENTS.md instructions")
-                                            && !text.is_empty()
-                                        {
+                                        if !is_codex_context_message(text) && !text.is_empty() {
                                             return Ok(text.to_string());
                                         }
                                     }
@@ -393,15 +388,56 @@ pub fn get_codex_prompt_text(session_id: &str, prompt_index: usize) -> Result<St
     Err(format!("Prompt #{} not found in session", prompt_index))
 }
 
+/// Find the line index of every real user prompt in a Codex session JSONL
+///
+/// Skips injected context messages (AGENTS.md, environment context), matching
+/// the prompt numbering used for rewind.
+pub(crate) fn find_codex_prompt_lines(lines: &[&str]) -> Vec<usize> {
+    let mut prompt_lines = Vec::new();
+
+    for (idx, line) in lines.iter().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let Ok(event) = serde_json::from_str::<serde_json::Value>(line) else {
+            continue;
+        };
+        if event["type"].as_str() != Some("response_item")
+            || event["payload"]["role"].as_str() != Some("user")
+        {
+            continue;
+        }
+
+        // Skip non-user prompts (e.g., AGENTS/system context)
+        let is_prompt = event["payload"]["content"]
+            .as_array()
+            .map(|content| {
+                content.iter().any(|item| {
+                    item["type"].as_str() == Some("input_text")
+                        && item["text"].as_str().is_some_and(|text| {
+                            !is_codex_context_message(text) && !text.trim().is_empty()
+                        })
+                })
+            })
+            .unwrap_or(false);
+        if is_prompt {
+            prompt_lines.push(idx);
+        }
+    }
+
+    prompt_lines
+}
+
 /// Truncate Codex session file to before a specific prompt
 pub fn truncate_codex_session_to_prompt(
     session_id: &str,
     prompt_index: usize,
 ) -> Result<(), String> {
-    let sessions_dir = get_codex_sessions_dir()?;
-    let session_file = find_session_file(&sessions_dir, session_id)
-        .ok_or_else(|| format!("Session file not found for: {}", session_id))?;
+    let session_file = resolve_session_file("codex", session_id, "")?;
+    let _lock = SessionLock::acquire(&session_file, "codex rewind")?;
 
+    let snapshot = FileSnapshot::capture(&session_file)?;
     let content = fs::read_to_string(&session_file)
         .map_err(|e| format!("Failed to read session file: {}", e))?;
 
@@ -409,55 +445,9 @@ pub fn truncate_codex_session_to_prompt(
     let total_lines = lines.len();
 
     // Find the line index to truncate at
-    let mut user_message_count = 0;
-    let mut truncate_at_line = 0;
-    let mut found_target = false;
-
-    for (idx, line) in lines.iter().enumerate() {
-        if line.trim().is_empty() {
-            continue;
-        }
-
-        if let Ok(event) = serde_json::from_str::<serde_json::Value>(line) {
-            if event["type"].as_str() == Some("response_item") {
-                if event["payload"]["role"].as_str() == Some("user") {
-                    // Extract user text and skip system injections
-                    let mut prompt_text: Option<String> = None;
-                    if let Some(content) = event["payload"]["content"].as_array() {
-                        for item in content {
-                            if item["type"].as_str() == Some("input_text") {
-                                if let Some(text) = item["text"].as_str() {
-                                    if !text.contains("<environment_context>")
-                                        && !text.contains("# AGENTS.md instructions")
-                                        && !text.trim().is_empty()
-                                    {
-                                        prompt_text = Some(text.to_string());
-                                        break;
-                                    }
-                                }
-                            }
-                        }
-                    }
-
-                    // Skip non-user prompts (e.g., AGENTS/system context)
-                    if prompt_text.is_none() {
-                        continue;
-                    }
-
-                    if user_message_count == prompt_index {
-                        truncate_at_line = idx;
-                        found_target = true;
-                        break;
-                    }
-                    user_message_count += 1;
-                }
-            }
-        }
-    }
-
-    if !found_target {
+    let Some(&truncate_at_line) = find_codex_prompt_lines(&lines).get(prompt_index) else {
         return Err(format!("Prompt #{} not found in session", prompt_index));
-    }
+    };
 
     log::info!(
         "[Codex Rewind] Total lines: {}, truncating at line {} (prompt #{})",
@@ -475,8 +465,7 @@ pub fn truncate_codex_session_to_prompt(
         truncated_lines.join("\n") + "\n"
     };
 
-    fs::write(&session_file, new_content)
-        .map_err(|e| format!("Failed to write truncated session: {}", e))?;
+    write_session_file(&session_file, &snapshot, new_content)?;
 
     log::info!(
         "[Codex Rewind] Truncated session: kept {} lines, deleted {} lines",
@@ -497,10 +486,12 @@ pub async fn record_codex_prompt_sent(
     session_id: String,
     project_path: String,
     _prompt_text: String,
+    trace_id: Option<String>,
 ) -> Result<usize, String> {
     log::info!(
-        "[Codex Record] Recording prompt sent for session: {}",
-        session_id
+        "[Codex Record] Recording prompt sent for session: {} (trace_id={})",
+        session_id,
+        trace_id.as_deref().unwrap_or("-")
     );
 
     // Check if Git operations are disabled in config
@@ -516,6 +507,8 @@ pub async fn record_codex_prompt_sent(
             "[Codex Record] Returning prompt index #{} (no git record)",
             prompt_index
         );
+        capture_task_snapshot("codex", &session_id, None, prompt_index, &project_path).await;
+        bind_prompt_trace(trace_id.as_deref(), "codex", &session_id, prompt_index);
         return Ok(prompt_index);
     }
 
@@ -543,7 +536,9 @@ pub async fn record_codex_prompt_sent(
         prompt_index,
         commit_before: commit_before.clone(),
         commit_after: None,
-        timestamp: Utc::now().to_rfc3339(),
+        timestamp: Utc::now().timestamp(),
+        trace_id: trace_id.clone(),
+        reverted_at: None,
     };
 
     git_records.records.push(record);
@@ -554,6 +549,20 @@ pub async fn record_codex_prompt_sent(
         prompt_index,
         &commit_before[..8.min(commit_before.len())]
     );
+    if let Some(trace_id) = trace_id.as_deref() {
+        record_trace_event(
+            trace_id,
+            STAGE_PROMPT_RECORDED,
+            TraceFields::engine("codex")
+                .with_session(Some(&session_id))
+                .with_detail(serde_json::json!({
+                    "promptIndex": prompt_index,
+                    "commitBefore": commit_before,
+                })),
+        );
+    }
+    capture_task_snapshot("codex", &session_id, None, prompt_index, &project_path).await;
+    bind_prompt_trace(trace_id.as_deref(), "codex", &session_id, prompt_index);
 
     Ok(prompt_index)
 }
@@ -561,46 +570,72 @@ pub async fn record_codex_prompt_sent(
 /// Record a Codex prompt completion (called after AI response)
 #[tauri::command]
 pub async fn record_codex_prompt_completed(
+    app: AppHandle,
     session_id: String,
     project_path: String,
     prompt_index: usize,
     prompt_text: Option<String>,
-) -> Result<(), String> {
+    trace_id: Option<String>,
+) -> Result<PromptCompletionResult, String> {
     log::info!(
-        "[Codex Record] Recording prompt #{} completed for session: {}",
+        "[Codex Record] Recording prompt #{} completed for session: {} (trace_id={})",
         prompt_index,
-        session_id
+        session_id,
+        trace_id.as_deref().unwrap_or("-")
     );
 
+    // Compare protected paths with the snapshot taken when the prompt was sent
+    check_protected_changes(
+        &app,
+        "codex",
+        &session_id,
+        prompt_index,
+        trace_id.as_deref(),
+    )
+    .await;
+
     // Check if Git operations are disabled in config
     let execution_config =
         load_execution_config().map_err(|e| format!("Failed to load execution config: {}", e))?;
 
     if execution_config.disable_rewind_git_operations {
         log::info!("[Codex Record] Git operations disabled, skipping git commit and record update");
-        return Ok(());
+        return Ok(PromptCompletionResult::default());
     }
 
     // Auto-commit any changes made by AI
-    let commit_message = build_prompt_commit_message("[Codex]", prompt_text.as_deref(), prompt_index);
-    match simple_git::git_commit_changes(&project_path, &commit_message) {
-        Ok(true) => {
-            log::info!(
-                "[Codex Record] Auto-committed changes after prompt #{}",
-                prompt_index
-            );
-        }
-        Ok(false) => {
-            log::debug!(
-                "[Codex Record] No changes to commit after prompt #{}",
-                prompt_index
-            );
+    let commit_message = with_trace_trailer(
+        build_prompt_commit_message("[Codex]", prompt_text.as_deref(), prompt_index),
+        trace_id.as_deref(),
+    );
+    let mut result = PromptCompletionResult::default();
+    let committed = match simple_git::git_commit_changes(
+        &project_path,
+        &commit_message,
+        execution_config.git_identity_fallback,
+    ) {
+        Ok(committed) => {
+            if committed {
+                log::info!(
+                    "[Codex Record] Auto-committed changes after prompt #{}",
+                    prompt_index
+                );
+            } else {
+                log::debug!(
+                    "[Codex Record] No changes to commit after prompt #{}",
+                    prompt_index
+                );
+            }
+            result.snapshot_created = true;
+            committed
         }
         Err(e) => {
-            log::warn!("[Codex Record] Failed to auto-commit: {}", e);
-            // Continue anyway
+            // Continue anyway, but tell the user
+            report_snapshot_failure(&app, "codex", &session_id, prompt_index, &e);
+            result.snapshot_error = Some(e);
+            false
         }
-    }
+    };
 
     // Get current commit (state after AI completion)
     let commit_after = simple_git::git_current_commit(&project_path)
@@ -615,6 +650,9 @@ pub async fn record_codex_prompt_completed(
         .find(|r| r.prompt_index == prompt_index)
     {
         record.commit_after = Some(commit_after.clone());
+        if record.trace_id.is_none() {
+            record.trace_id = trace_id.clone();
+        }
         save_codex_git_records(&session_id, &git_records)?;
 
         log::info!(
@@ -628,8 +666,22 @@ pub async fn record_codex_prompt_completed(
             prompt_index
         );
     }
+    if let Some(trace_id) = trace_id.as_deref() {
+        record_trace_event(
+            trace_id,
+            STAGE_GIT_SNAPSHOT,
+            TraceFields::engine("codex")
+                .with_session(Some(&session_id))
+                .with_detail(serde_json::json!({
+                    "promptIndex": prompt_index,
+                    "commit": commit_after,
+                    "committed": committed,
+                })),
+        );
+    }
 
-    Ok(())
+    result.commit_after = Some(commit_after);
+    Ok(result)
 }
 
 // ============================================================================
@@ -639,6 +691,7 @@ pub async fn record_codex_prompt_completed(
 /// Revert Codex session to a specific prompt
 #[tauri::command]
 pub async fn revert_codex_to_prompt(
+    app: AppHandle,
     session_id: String,
     project_path: String,
     prompt_index: usize,
@@ -678,18 +731,16 @@ pub async fn revert_codex_to_prompt(
     match mode {
         RewindMode::CodeOnly | RewindMode::Both => {
             if git_operations_disabled {
-                return Err(
-                    "无法回滚代码：Git 操作已在配置中禁用。只能撤回对话历史，无法回滚代码变更。"
-                        .into(),
-                );
+                return Err(t(MessageKey::RewindCodeBlockedGitDisabled));
             }
             if git_record.is_none() {
-                return Err(format!(
-                    "无法回滚代码：提示词 #{} 没有关联的 Git 记录",
-                    prompt_index
+                return Err(t_with(
+                    MessageKey::RewindCodeBlockedNoRecord,
+                    &[("index", &prompt_index)],
                 ));
             }
         }
+        RewindMode::Selective { .. } => return Err(t(MessageKey::RewindSelectiveUnsupported)),
         RewindMode::ConversationOnly => {}
     }
 
@@ -722,6 +773,7 @@ pub async fn revert_codex_to_prompt(
                     "Auto-stash before Codex code revert to prompt #{}",
                     prompt_index
                 ),
+                execution_config.git_identity_fallback,
             )
             .map_err(|e| format!("Failed to stash changes: {}", e))?;
 
@@ -759,6 +811,15 @@ pub async fn revert_codex_to_prompt(
             let mut failure_message = String::new();
 
             for record in &records_to_revert {
+                // Already reverted on its own, reverting again would re-apply nothing useful
+                if record.reverted_at.is_some() {
+                    log::debug!(
+                        "[Codex Precise Revert] Skipping prompt #{} - already reverted",
+                        record.prompt_index
+                    );
+                    continue;
+                }
+
                 // Skip if no commit_after (AI didn't make any changes)
                 let commit_after = match &record.commit_after {
                     Some(c) if c != &record.commit_before => c.clone(),
@@ -807,6 +868,7 @@ pub async fn revert_codex_to_prompt(
                     &commit_after,
                     &format!("[Codex Revert] 撤回提示词 #{} 的代码更改", record.prompt_index),
                     3, // Max 3 retries for Git lock conflicts
+                    execution_config.git_identity_fallback,
                 );
 
                 match revert_result {
@@ -846,9 +908,9 @@ pub async fn revert_codex_to_prompt(
                 simple_git::git_reset_hard(&project_path, &original_head)
                     .map_err(|e| format!("Failed to rollback: {}", e))?;
 
-                return Err(format!(
-                    "撤回失败，已回滚到操作前状态。原因: {}",
-                    failure_message
+                return Err(t_with(
+                    MessageKey::RewindRevertFailedRolledBack,
+                    &[("reason", &failure_message)],
                 ));
             }
 
@@ -870,6 +932,7 @@ pub async fn revert_codex_to_prompt(
                     "Auto-stash before Codex full revert to prompt #{}",
                     prompt_index
                 ),
+                execution_config.git_identity_fallback,
             )
             .map_err(|e| format!("Failed to stash changes: {}", e))?;
 
@@ -907,6 +970,15 @@ pub async fn revert_codex_to_prompt(
             let mut failure_message = String::new();
 
             for record in &records_to_revert {
+                // Already reverted on its own, reverting again would re-apply nothing useful
+                if record.reverted_at.is_some() {
+                    log::debug!(
+                        "[Codex Precise Revert] Skipping prompt #{} - already reverted",
+                        record.prompt_index
+                    );
+                    continue;
+                }
+
                 // Skip if no commit_after (AI didn't make any changes)
                 let commit_after = match &record.commit_after {
                     Some(c) if c != &record.commit_before => c.clone(),
@@ -955,6 +1027,7 @@ pub async fn revert_codex_to_prompt(
                     &commit_after,
                     &format!("[Codex Revert] 撤回提示词 #{} 的代码更改", record.prompt_index),
                     3, // Max 3 retries for Git lock conflicts
+                    execution_config.git_identity_fallback,
                 );
 
                 match revert_result {
@@ -994,9 +1067,9 @@ pub async fn revert_codex_to_prompt(
                 simple_git::git_reset_hard(&project_path, &original_head)
                     .map_err(|e| format!("Failed to rollback: {}", e))?;
 
-                return Err(format!(
-                    "撤回失败，已回滚到操作前状态。原因: {}",
-                    failure_message
+                return Err(t_with(
+                    MessageKey::RewindRevertFailedRolledBack,
+                    &[("reason", &failure_message)],
                 ));
             }
 
@@ -1017,17 +1090,15 @@ pub async fn revert_codex_to_prompt(
 
                 if let Err(rollback_err) = simple_git::git_reset_hard(&project_path, &original_head) {
                     log::error!("[CRITICAL] Git rollback failed: {}", rollback_err);
-                    return Err(format!(
-                        "会话截断失败且 Git 回滚失败。\n\
-                         会话错误: {}\n\
-                         Git 回滚错误: {}",
-                        e, rollback_err
+                    return Err(t_with(
+                        MessageKey::RewindTruncateFailedRollbackFailed,
+                        &[("reason", &e), ("rollbackError", &rollback_err)],
                     ));
                 }
 
-                return Err(format!(
-                    "会话截断失败，已原子性回滚 Git 更改。原因: {}",
-                    e
+                return Err(t_with(
+                    MessageKey::RewindTruncateFailedRolledBack,
+                    &[("reason", &e)],
                 ));
             }
 
@@ -1042,19 +1113,15 @@ pub async fn revert_codex_to_prompt(
 
                     if let Err(rollback_err) = simple_git::git_reset_hard(&project_path, &original_head) {
                         log::error!("[CRITICAL] Git rollback failed: {}", rollback_err);
-                        return Err(format!(
-                            "Git 记录截断失败且回滚失败。\n\
-                             记录错误: {}\n\
-                             回滚错误: {}\n\
-                             注意：会话已截断。",
-                            e, rollback_err
+                        return Err(t_with(
+                            MessageKey::RewindRecordsTruncateFailedRollbackFailed,
+                            &[("reason", &e), ("rollbackError", &rollback_err)],
                         ));
                     }
 
-                    return Err(format!(
-                        "Git 记录截断失败，已回滚 Git 更改。\n\
-                         注意：会话已截断但无法回滚。原因: {}",
-                        e
+                    return Err(t_with(
+                        MessageKey::RewindRecordsTruncateFailedRolledBack,
+                        &[("reason", &e)],
                     ));
                 }
             }
@@ -1064,8 +1131,72 @@ pub async fn revert_codex_to_prompt(
                 prompt_index
             );
         }
+
+        RewindMode::Selective { .. } => return Err(t(MessageKey::RewindSelectiveUnsupported)),
     }
 
+    invalidate_session(&app, "codex", Some(&session_id), Some(&project_path));
+
     // Return the prompt text for restoring to input
     Ok(prompt.text.clone())
 }
+
+/// Revert only the code changes of Codex prompt #`prompt_index`, keeping later prompts and the conversation
+///
+/// Refuses when the changes conflict with later prompts (the repository is left as it was).
+/// The git record is kept and marked as reverted. Returns the number of reverted commits.
+#[tauri::command]
+pub async fn revert_codex_single_prompt_code(
+    session_id: String,
+    project_path: String,
+    prompt_index: usize,
+) -> Result<usize, String> {
+    log::info!(
+        "[Codex Rewind] Reverting code of prompt #{} alone in session: {}",
+        prompt_index,
+        session_id
+    );
+
+    let execution_config =
+        load_execution_config().map_err(|e| format!("Failed to load execution config: {}", e))?;
+    if execution_config.disable_rewind_git_operations {
+        return Err(t(MessageKey::RewindCodeBlockedGitDisabled));
+    }
+
+    let mut git_records = load_codex_git_records(&session_id)?;
+    let record = git_records
+        .records
+        .iter_mut()
+        .find(|r| r.prompt_index == prompt_index)
+        .ok_or_else(|| {
+            t_with(
+                MessageKey::RewindCodeBlockedNoRecord,
+                &[("index", &prompt_index)],
+            )
+        })?;
+    if record.reverted_at.is_some() {
+        return Err(t_with(
+            MessageKey::RewindSingleAlreadyReverted,
+            &[("index", &prompt_index)],
+        ));
+    }
+
+    let reverted = revert_single_prompt_range(
+        &project_path,
+        prompt_index,
+        &record.commit_before,
+        record.commit_after.as_deref(),
+        &format!("[Codex Revert] 单独撤回提示词 #{} 的代码更改", prompt_index),
+        execution_config.git_identity_fallback,
+    )?;
+
+    record.reverted_at = Some(Utc::now().timestamp());
+    save_codex_git_records(&session_id, &git_records)?;
+
+    log::info!(
+        "[Codex Rewind] Reverted code of prompt #{} alone ({} commits)",
+        prompt_index,
+        reverted
+    );
+    Ok(reverted)
+}
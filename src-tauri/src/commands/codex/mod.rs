@@ -8,9 +8,15 @@
  * - session.rs: Session lifecycle management (execute, resume, cancel, list, delete)
  * - git_ops.rs: Git operations for rewind functionality (records, truncate, revert)
  * - config.rs: Configuration management (availability, paths, mode, providers)
+ * - context.rs: Context footprint estimation before resuming a session
+ * - profiles.rs: Named profiles in config.toml (`[profiles.*]`, `--profile`)
+ * - capabilities.rs: `-c key=value` overrides and CLI version capability checks
  */
+pub mod capabilities;
 pub mod config;
+pub mod context;
 pub mod git_ops;
+pub mod profiles;
 pub mod session;
 pub mod session_converter;
 pub mod usage;
@@ -31,6 +37,18 @@ pub use git_ops::{CodexGitRecords, CodexPromptGitRecord, CodexPromptRecord, Prom
 #[allow(unused_imports)]
 pub use config::{CodexAvailability, CodexModeInfo, CodexProviderConfig, CurrentCodexConfig};
 
+// Profile types
+#[allow(unused_imports)]
+pub use profiles::{CodexProfile, CodexProfileInput};
+
+// Config override types
+#[allow(unused_imports)]
+pub use capabilities::{CodexCapabilities, CodexConfigOverride};
+
+// Context estimation types
+#[allow(unused_imports)]
+pub use context::CodexContextEstimate;
+
 // Session converter types
 #[allow(unused_imports)]
 pub use session_converter::{ConversionResult, ConversionSource};
@@ -44,13 +62,15 @@ pub use session::{
     load_codex_session_history, resume_codex, resume_last_codex,
 };
 
+pub use context::estimate_codex_context;
+
 // ============================================================================
 // Re-export Tauri Commands - Git Operations / Rewind
 // ============================================================================
 
 pub use git_ops::{
     check_codex_rewind_capabilities, get_codex_prompt_list, record_codex_prompt_completed,
-    record_codex_prompt_sent, revert_codex_to_prompt,
+    record_codex_prompt_sent, revert_codex_single_prompt_code, revert_codex_to_prompt,
 };
 
 // ============================================================================
@@ -73,11 +93,25 @@ pub use config::{
     update_codex_reasoning_level,
 };
 
+pub use capabilities::get_codex_capabilities;
+
+// ============================================================================
+// Re-export Tauri Commands - Profiles
+// ============================================================================
+
+pub use profiles::{
+    create_codex_profile, delete_codex_profile, get_codex_profiles, update_codex_profile,
+};
+
 // ============================================================================
 // Re-export Tauri Commands - Session Conversion
 // ============================================================================
 
-pub use session_converter::{convert_claude_to_codex, convert_codex_to_claude, convert_session};
+pub use session_converter::{
+    cancel_conversion, convert_claude_to_codex, convert_codex_to_claude, convert_session,
+    convert_sessions_batch, export_reasoning, export_session_markdown, export_session_to_markdown,
+    import_session_file,
+};
 
 // ============================================================================
 // Re-export Helper Functions (for internal use by submodules)
@@ -87,7 +121,7 @@ pub use session_converter::{convert_claude_to_codex, convert_codex_to_claude, co
 pub use config::{get_codex_command_candidates, get_codex_sessions_dir};
 
 #[allow(unused_imports)]
-pub use session::{find_session_file, parse_codex_session_file};
+pub use session::{find_session_file, is_codex_context_message, parse_codex_session_file};
 
 #[allow(unused_imports)]
 pub use git_ops::{
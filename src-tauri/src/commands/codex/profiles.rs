@@ -0,0 +1,469 @@
+//! Codex 命名 profile 管理
+//!
+//! Codex CLI 支持在 config.toml 中用 `[profiles.<name>]` 定义多组模型 / 审批策略组合，
+//! 终端中通过 `--profile <name>` 切换。这里负责解析与编辑这些段落：
+//! - 编辑使用 toml_edit，保留 config.toml 中的其他内容、注释与格式
+//! - 配置文件路径与供应商管理一致，WSL 模式下读写 WSL 侧的 config.toml
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use toml_edit::{DocumentMut, Item, Table, TableLike};
+
+use super::config::get_codex_config_path;
+use crate::commands::undo::{self, Undoable};
+
+/// profile 中可在应用内编辑的字段
+const PROFILE_FIELDS: [&str; 6] = [
+    "model",
+    "model_provider",
+    "approval_policy",
+    "sandbox_mode",
+    "model_reasoning_effort",
+    "model_verbosity",
+];
+
+/// 一个 Codex profile 的关键字段摘要
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CodexProfile {
+    pub name: String,
+    pub model: Option<String>,
+    pub model_provider: Option<String>,
+    pub approval_policy: Option<String>,
+    pub sandbox_mode: Option<String>,
+    pub model_reasoning_effort: Option<String>,
+    pub model_verbosity: Option<String>,
+    /// 是否为 config.toml 顶层 `profile` 指定的默认 profile
+    pub is_default: bool,
+}
+
+impl CodexProfile {
+    /// profile 是否自行决定审批与沙箱行为（此时不再追加执行模式参数）
+    pub fn defines_permissions(&self) -> bool {
+        self.approval_policy.is_some() || self.sandbox_mode.is_some()
+    }
+}
+
+/// 创建 / 更新 profile 时提交的字段，为空的字段会从该段中移除
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CodexProfileInput {
+    pub model: Option<String>,
+    pub model_provider: Option<String>,
+    pub approval_policy: Option<String>,
+    pub sandbox_mode: Option<String>,
+    pub model_reasoning_effort: Option<String>,
+    pub model_verbosity: Option<String>,
+}
+
+impl CodexProfileInput {
+    fn field(&self, key: &str) -> Option<&str> {
+        let value = match key {
+            "model" => &self.model,
+            "model_provider" => &self.model_provider,
+            "approval_policy" => &self.approval_policy,
+            "sandbox_mode" => &self.sandbox_mode,
+            "model_reasoning_effort" => &self.model_reasoning_effort,
+            "model_verbosity" => &self.model_verbosity,
+            _ => &None,
+        };
+        value.as_deref().map(str::trim).filter(|v| !v.is_empty())
+    }
+}
+
+/// profile 名称会作为 `--profile` 参数与 TOML 键使用，只允许安全字符
+fn validate_profile_name(name: &str) -> Result<(), String> {
+    if name.is_empty() {
+        return Err("Profile name is empty".to_string());
+    }
+    if !name
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+    {
+        return Err(format!(
+            "Invalid profile name '{}': only letters, digits, '-', '_' and '.' are allowed",
+            name
+        ));
+    }
+    Ok(())
+}
+
+fn read_config_document() -> Result<DocumentMut, String> {
+    let path = get_codex_config_path()?;
+    if !path.exists() {
+        return Ok(DocumentMut::new());
+    }
+    let content =
+        fs::read_to_string(&path).map_err(|e| format!("Failed to read config.toml: {}", e))?;
+    content
+        .parse::<DocumentMut>()
+        .map_err(|e| format!("Failed to parse config.toml: {}", e))
+}
+
+fn write_config_document(doc: &DocumentMut) -> Result<(), String> {
+    let path = get_codex_config_path()?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create .codex directory: {}", e))?;
+    }
+    fs::write(&path, doc.to_string()).map_err(|e| format!("Failed to write config.toml: {}", e))
+}
+
+/// 解析文档中的全部 `[profiles.*]` 段
+fn parse_profiles(doc: &DocumentMut) -> Vec<CodexProfile> {
+    let default_profile = doc.get("profile").and_then(|v| v.as_str());
+    let Some(profiles) = doc.get("profiles").and_then(|p| p.as_table_like()) else {
+        return Vec::new();
+    };
+
+    profiles
+        .iter()
+        .filter_map(|(name, item)| {
+            let table = item.as_table_like()?;
+            let field = |key: &str| table.get(key).and_then(|v| v.as_str()).map(String::from);
+            Some(CodexProfile {
+                name: name.to_string(),
+                model: field("model"),
+                model_provider: field("model_provider"),
+                approval_policy: field("approval_policy"),
+                sandbox_mode: field("sandbox_mode"),
+                model_reasoning_effort: field("model_reasoning_effort"),
+                model_verbosity: field("model_verbosity"),
+                is_default: default_profile == Some(name),
+            })
+        })
+        .collect()
+}
+
+/// `[profiles]` 表，不存在时创建（隐式表，不单独输出表头）
+fn profiles_table_mut(doc: &mut DocumentMut) -> Result<&mut dyn TableLike, String> {
+    if !doc.contains_key("profiles") {
+        let mut profiles = Table::new();
+        profiles.set_implicit(true);
+        doc["profiles"] = Item::Table(profiles);
+    }
+    doc["profiles"]
+        .as_table_like_mut()
+        .ok_or_else(|| "Invalid config.toml: 'profiles' is not a table".to_string())
+}
+
+/// 写入 profile 的可编辑字段，段内其他键保持不变
+fn upsert_profile(
+    doc: &mut DocumentMut,
+    name: &str,
+    input: &CodexProfileInput,
+    must_exist: bool,
+) -> Result<(), String> {
+    let exists = parse_profiles(doc).iter().any(|p| p.name == name);
+    if must_exist && !exists {
+        return Err(format!("Codex profile '{}' not found in config.toml", name));
+    }
+    if !must_exist && exists {
+        return Err(format!("Codex profile '{}' already exists", name));
+    }
+
+    let profiles = profiles_table_mut(doc)?;
+    if !profiles.contains_key(name) {
+        profiles.insert(name, Item::Table(Table::new()));
+    }
+    let profile = profiles
+        .get_mut(name)
+        .and_then(|item| item.as_table_like_mut())
+        .ok_or_else(|| format!("Invalid config.toml: profile '{}' is not a table", name))?;
+
+    for key in PROFILE_FIELDS {
+        match input.field(key) {
+            Some(value) => {
+                profile.insert(key, toml_edit::value(value));
+            }
+            None => {
+                profile.remove(key);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// 删除 profile；若它是顶层默认 profile，一并移除 `profile` 键
+fn remove_profile(doc: &mut DocumentMut, name: &str) -> Result<(), String> {
+    let removed = doc
+        .get_mut("profiles")
+        .and_then(|p| p.as_table_like_mut())
+        .and_then(|profiles| profiles.remove(name));
+    if removed.is_none() {
+        return Err(format!("Codex profile '{}' not found in config.toml", name));
+    }
+    if doc.get("profile").and_then(|v| v.as_str()) == Some(name) {
+        doc.remove("profile");
+    }
+    if doc
+        .get("profiles")
+        .and_then(|p| p.as_table_like())
+        .is_some_and(|profiles| profiles.is_empty())
+    {
+        doc.remove("profiles");
+    }
+    Ok(())
+}
+
+/// 把 profile 段完整序列化为 TOML 文本（含应用内不编辑的键与注释），用于撤销删除
+fn snapshot_profile(doc: &DocumentMut, name: &str) -> Option<String> {
+    let item = doc.get("profiles")?.as_table_like()?.get(name)?;
+    let mut snapshot = DocumentMut::new();
+    snapshot.insert(name, item.clone());
+    Some(snapshot.to_string())
+}
+
+/// 文档中最大的表位置（决定表头的输出顺序）
+fn max_table_position(table: &Table) -> usize {
+    table
+        .iter()
+        .map(|(_, item)| match item {
+            Item::Table(t) => t.position().unwrap_or(0).max(max_table_position(t)),
+            Item::ArrayOfTables(array) => array
+                .iter()
+                .map(|t| t.position().unwrap_or(0).max(max_table_position(t)))
+                .max()
+                .unwrap_or(0),
+            _ => 0,
+        })
+        .max()
+        .unwrap_or(0)
+}
+
+fn shift_table_positions(item: &mut Item, offset: usize) {
+    fn shift(table: &mut Table, offset: usize) {
+        if let Some(position) = table.position() {
+            table.set_position(position + offset);
+        }
+        for (_, item) in table.iter_mut() {
+            shift_table_positions(item, offset);
+        }
+    }
+    match item {
+        Item::Table(table) => shift(table, offset),
+        Item::ArrayOfTables(array) => array.iter_mut().for_each(|t| shift(t, offset)),
+        _ => {}
+    }
+}
+
+/// 按 `snapshot_profile` 的快照原样恢复 profile 段，追加到 config.toml 末尾
+fn restore_profile(doc: &mut DocumentMut, name: &str, snapshot: &str) -> Result<(), String> {
+    let mut parsed = snapshot
+        .parse::<DocumentMut>()
+        .map_err(|e| format!("Invalid profile snapshot: {}", e))?;
+    let mut item = parsed
+        .remove(name)
+        .ok_or_else(|| format!("Profile snapshot does not contain '{}'", name))?;
+    // 快照中各表的位置整体后移，恢复的段及其子表按原顺序输出在文件末尾
+    let offset = max_table_position(doc.as_table()) + 1;
+    shift_table_positions(&mut item, offset);
+
+    let profiles = profiles_table_mut(doc)?;
+    if profiles.contains_key(name) {
+        return Err(format!("Codex profile '{}' already exists", name));
+    }
+    profiles.insert(name, item);
+    Ok(())
+}
+
+/// 按名称读取 profile，用于执行前校验
+pub(crate) fn load_codex_profile(name: &str) -> Result<CodexProfile, String> {
+    validate_profile_name(name)?;
+    parse_profiles(&read_config_document()?)
+        .into_iter()
+        .find(|p| p.name == name)
+        .ok_or_else(|| format!("Codex profile '{}' not found in config.toml", name))
+}
+
+/// 列出 config.toml 中定义的 profile
+#[tauri::command]
+pub async fn get_codex_profiles() -> Result<Vec<CodexProfile>, String> {
+    Ok(parse_profiles(&read_config_document()?))
+}
+
+/// 新建 profile
+#[tauri::command]
+pub async fn create_codex_profile(
+    name: String,
+    profile: CodexProfileInput,
+) -> Result<CodexProfile, String> {
+    let name = name.trim().to_string();
+    validate_profile_name(&name)?;
+    let mut doc = read_config_document()?;
+    upsert_profile(&mut doc, &name, &profile, false)?;
+    write_config_document(&doc)?;
+    log::info!("[Codex] Created profile: {}", name);
+    load_codex_profile(&name)
+}
+
+/// 更新已有 profile 的可编辑字段
+#[tauri::command]
+pub async fn update_codex_profile(
+    name: String,
+    profile: CodexProfileInput,
+) -> Result<CodexProfile, String> {
+    validate_profile_name(&name)?;
+    let mut doc = read_config_document()?;
+    upsert_profile(&mut doc, &name, &profile, true)?;
+    write_config_document(&doc)?;
+    log::info!("[Codex] Updated profile: {}", name);
+    load_codex_profile(&name)
+}
+
+/// 删除 profile
+#[tauri::command]
+pub async fn delete_codex_profile(name: String) -> Result<(), String> {
+    validate_profile_name(&name)?;
+    let mut doc = read_config_document()?;
+    let profile = parse_profiles(&doc).into_iter().find(|p| p.name == name);
+    let snapshot = snapshot_profile(&doc, &name);
+    remove_profile(&mut doc, &name)?;
+    write_config_document(&doc)?;
+    log::info!("[Codex] Deleted profile: {}", name);
+    if let Some(profile) = profile {
+        undo::record(&CodexProfileDeletion { profile, snapshot });
+    }
+    Ok(())
+}
+
+/// 撤销删除 profile：按快照原样恢复整个段，原为默认 profile 时恢复顶层 `profile` 键
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CodexProfileDeletion {
+    profile: CodexProfile,
+    /// 删除前整个段的 TOML 文本；早期记录没有快照，只能重建可编辑字段
+    #[serde(default)]
+    snapshot: Option<String>,
+}
+
+impl CodexProfileDeletion {
+    /// 在文档上执行撤销
+    fn restore_into(self, doc: &mut DocumentMut) -> Result<(), String> {
+        let profile = self.profile;
+        match self.snapshot {
+            Some(snapshot) => restore_profile(doc, &profile.name, &snapshot)?,
+            None => {
+                let input = CodexProfileInput {
+                    model: profile.model,
+                    model_provider: profile.model_provider,
+                    approval_policy: profile.approval_policy,
+                    sandbox_mode: profile.sandbox_mode,
+                    model_reasoning_effort: profile.model_reasoning_effort,
+                    model_verbosity: profile.model_verbosity,
+                };
+                upsert_profile(doc, &profile.name, &input, false)?;
+            }
+        }
+        if profile.is_default && !doc.contains_key("profile") {
+            doc["profile"] = toml_edit::value(profile.name.as_str());
+        }
+        Ok(())
+    }
+}
+
+impl Undoable for CodexProfileDeletion {
+    const KIND: &'static str = "codex_profile_deletion";
+
+    fn description(&self) -> String {
+        format!("删除 Codex profile {}", self.profile.name)
+    }
+
+    fn undo(self) -> Result<(), String> {
+        let mut doc = read_config_document()?;
+        self.restore_into(&mut doc)?;
+        write_config_document(&doc)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"# user settings
+model = "gpt-5.2"
+profile = "fast"
+
+[profiles.fast]
+model = "gpt-5.2-codex"
+approval_policy = "never" # keep quiet
+
+[profiles.review]
+model = "o3"
+sandbox_mode = "read-only"
+
+[mcp_servers.docs]
+command = "docs-mcp"
+"#;
+
+    #[test]
+    fn parses_profiles_and_default() {
+        let doc = CONFIG.parse::<DocumentMut>().unwrap();
+        let profiles = parse_profiles(&doc);
+
+        assert_eq!(profiles.len(), 2);
+        assert_eq!(profiles[0].name, "fast");
+        assert!(profiles[0].is_default);
+        assert_eq!(profiles[0].approval_policy.as_deref(), Some("never"));
+        assert_eq!(profiles[1].sandbox_mode.as_deref(), Some("read-only"));
+        assert!(!profiles[1].is_default);
+    }
+
+    #[test]
+    fn editing_profiles_preserves_other_content() {
+        let mut doc = CONFIG.parse::<DocumentMut>().unwrap();
+        let input = CodexProfileInput {
+            model: Some("gpt-5.2".into()),
+            approval_policy: Some("on-request".into()),
+            ..Default::default()
+        };
+        upsert_profile(&mut doc, "deep", &input, false).unwrap();
+        assert!(upsert_profile(&mut doc, "deep", &input, false).is_err());
+        remove_profile(&mut doc, "fast").unwrap();
+
+        let text = doc.to_string();
+        assert!(text.starts_with("# user settings\nmodel = \"gpt-5.2\"\n"));
+        assert!(!text.contains("profile = \"fast\""));
+        assert!(text.contains("[profiles.deep]"));
+        assert!(text.contains("[mcp_servers.docs]\ncommand = \"docs-mcp\""));
+        let names: Vec<_> = parse_profiles(&doc).into_iter().map(|p| p.name).collect();
+        assert_eq!(names, vec!["review", "deep"]);
+    }
+
+    #[test]
+    fn rejects_unsafe_profile_names() {
+        assert!(validate_profile_name("team.fast-1").is_ok());
+        assert!(validate_profile_name("").is_err());
+        assert!(validate_profile_name("a b").is_err());
+    }
+
+    #[test]
+    fn undoing_a_deletion_restores_the_whole_profile() {
+        let config = format!("{}\n[profiles.fast.features]\nweb_search = true\n", CONFIG);
+        let mut doc = config.parse::<DocumentMut>().unwrap();
+        let profile = parse_profiles(&doc).into_iter().find(|p| p.name == "fast");
+        let deletion = CodexProfileDeletion {
+            profile: profile.unwrap(),
+            snapshot: snapshot_profile(&doc, "fast"),
+        };
+        remove_profile(&mut doc, "fast").unwrap();
+        assert!(!doc.to_string().contains("fast"));
+
+        // The undo payload goes through the persisted undo stack as JSON
+        let payload = serde_json::to_value(&deletion).unwrap();
+        let deletion: CodexProfileDeletion = serde_json::from_value(payload).unwrap();
+        deletion.restore_into(&mut doc).unwrap();
+
+        let text = doc.to_string();
+        assert!(text.contains("profile = \"fast\""));
+        assert!(text.contains("approval_policy = \"never\" # keep quiet"));
+        assert!(text.contains("[profiles.fast.features]\nweb_search = true"));
+        let restored = parse_profiles(&doc);
+        assert_eq!(restored.len(), 2);
+        assert!(restored.iter().any(|p| p.name == "fast" && p.is_default));
+        assert_eq!(
+            doc["profiles"]["fast"]["features"]["web_search"].as_bool(),
+            Some(true)
+        );
+    }
+}
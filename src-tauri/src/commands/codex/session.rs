@@ -8,6 +8,8 @@
  */
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
 use std::process::Stdio;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering};
@@ -18,12 +20,43 @@ use tokio::sync::Mutex;
 
 // Import platform-specific utilities for window hiding
 use crate::claude_binary::detect_binary_for_tool;
-use crate::commands::claude::apply_no_window_async;
+use crate::commands::app_error::{classify_codex_error, AppError};
+use crate::commands::claude::{apply_cancellable_process_async, normalize_path_for_comparison};
+use crate::commands::execution_retry::{
+    cancel_pending_retries, load_retry_config, plan_retry, wait_before_retry, FailedAttempt,
+};
+use crate::commands::execution_trace::{
+    record_trace_event, resolve_trace_id, TraceFields, STAGE_EXECUTION_STARTED,
+    STAGE_PROCESS_SPAWNED,
+};
+use crate::commands::power_inhibit::{self, PowerInhibitHold};
+use crate::commands::process_cancel::{
+    check_session_file, request_exit_and_wait, CancelMode, CancelOutcome, GRACEFUL_EXIT_TIMEOUT,
+};
+use crate::commands::project_memory::{append_memory_to_prompt, compiled_memory_for_execution};
+use crate::commands::prompt_enhancement::{enhance_for_execution, EnhancementRequest};
+use crate::commands::protected_paths::append_protected_paths_to_prompt;
+use crate::commands::rate_limiter;
+use crate::commands::session_invalidation::invalidate_session;
+use crate::commands::stream_stall::{
+    announce_reconnect, classify_codex_event, emit_stalled, interrupt_stalled_process,
+    new_shared_monitor, record_activity, watch_stream, StallRecovery, StreamActivity,
+    CONTINUE_PROMPT,
+};
+use crate::commands::tool_timing::{annotate_codex_history, LiveToolTimer};
 use crate::process::JobObject;
+use crate::utils::config_utils::{load_json_config, save_json_config};
+use crate::utils::session_lock::{lock_for_execution, SessionLock, SessionLockHeartbeat};
+use crate::utils::text::truncate_chars;
+// Import shared session path resolution
+use super::super::session_utils::{find_session_path, resolve_session_file};
 // Import WSL utilities for Windows + WSL Codex support
 use super::super::wsl_utils;
+use super::capabilities::{current_capabilities, resolve_config_overrides, CodexConfigOverride};
 // Import config module for sessions directory
 use super::config::get_codex_sessions_dir;
+use super::context::{context_overflow_error, estimate_session_context};
+use super::profiles::{load_codex_profile, CodexProfile};
 
 // ============================================================================
 // Type Definitions
@@ -47,6 +80,17 @@ impl Default for CodexExecutionMode {
     }
 }
 
+impl CodexExecutionMode {
+    /// 与序列化值一致的模式名称
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::ReadOnly => "read-only",
+            Self::FullAuto => "full-auto",
+            Self::DangerFullAccess => "danger-full-access",
+        }
+    }
+}
+
 /// Codex execution options
 #[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -64,6 +108,31 @@ pub struct CodexExecutionOptions {
     /// Model to use (e.g., "gpt-5.1-codex-max")
     pub model: Option<String>,
 
+    /// Named profile from config.toml (`--profile`); its model and approval settings take precedence
+    #[serde(default)]
+    pub profile: Option<String>,
+
+    /// Reasoning effort passed as `-c model_reasoning_effort=<value>`
+    #[serde(default)]
+    pub reasoning_effort: Option<String>,
+
+    /// Output verbosity passed as `-c model_verbosity=<value>`
+    #[serde(default)]
+    pub verbosity: Option<String>,
+
+    /// Refuse to run when the CLI does not support a requested config override
+    /// (otherwise the override is dropped with a warning)
+    #[serde(default)]
+    pub strict_config: bool,
+
+    /// `-c` overrides resolved against the CLI capabilities before launch
+    #[serde(skip)]
+    pub config_overrides: Vec<CodexConfigOverride>,
+
+    /// Warnings about overrides dropped before launch
+    #[serde(skip)]
+    pub config_warnings: Vec<String>,
+
     /// Enable JSON output mode
     #[serde(default = "default_json_mode")]
     pub json: bool,
@@ -87,6 +156,26 @@ pub struct CodexExecutionOptions {
     /// Resume last session
     #[serde(default)]
     pub resume_last: bool,
+
+    /// Check the session's estimated context against the model window before resuming
+    #[serde(default)]
+    pub check_context_overflow: bool,
+
+    /// Resume even if the context check predicts an overflow
+    #[serde(default)]
+    pub force: bool,
+
+    /// Append the compiled project memory to the prompt (new sessions only)
+    #[serde(default)]
+    pub use_project_memory: bool,
+
+    /// Trace ID of the user action that started this run (generated when absent)
+    #[serde(default)]
+    pub trace_id: Option<String>,
+
+    /// Enhance the prompt with acemcp project context before running
+    #[serde(default)]
+    pub enhancement: Option<EnhancementRequest>,
 }
 
 fn default_json_mode() -> bool {
@@ -118,19 +207,40 @@ pub struct CodexSession {
     /// Session status
     pub status: String,
 
-    /// First user message
+    /// First user message (or a fallback summary, see `first_message_source`)
     pub first_message: Option<String>,
 
+    /// Where `first_message` came from
+    #[serde(default)]
+    pub first_message_source: FirstMessageSource,
+
     /// Last message timestamp (ISO string)
     pub last_message_timestamp: Option<String>,
 }
 
+/// first_message 的来源（回退层级），供前端区分展示
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FirstMessageSource {
+    /// 用户输入
+    #[default]
+    User,
+    /// 首个 assistant agent_message 文本（带 "[AI] " 前缀）
+    Assistant,
+    /// session_meta 中的 instructions / originator
+    SessionMeta,
+    /// 无可用文本：“（无用户消息）”+ 文件名日期
+    Placeholder,
+}
+
 /// Codex process handle with PID for proper cleanup
 pub struct CodexProcessHandle {
     pub child: Child,
     pub pid: u32,
     /// Windows Job Object (kills all child processes when dropped); no-op on non-Windows.
     pub job_object: Option<JobObject>,
+    /// Codex thread ID (rollout session ID), known once thread.started arrives
+    pub cli_session_id: Option<String>,
 }
 
 /// Global state to track Codex processes
@@ -148,6 +258,20 @@ impl Default for CodexProcessState {
     }
 }
 
+/// Everything needed to (re)start a Codex run, kept so transient failures can be retried
+#[derive(Debug, Clone)]
+struct CodexLaunch {
+    options: CodexExecutionOptions,
+    /// Session ID (or "--last") for `codex exec resume`, None for a new session
+    resume_target: Option<String>,
+    /// Lock on the resumed session file, held until the run (including retries) finishes
+    session_lock: Option<Arc<SessionLockHeartbeat>>,
+    /// Sleep inhibition, held until the run (including retries) finishes
+    power_hold: Option<Arc<PowerInhibitHold>>,
+    /// How many times a stalled stream has already been resumed during this run
+    stall_reconnects: u32,
+}
+
 // ============================================================================
 // Core Execution Methods
 // ============================================================================
@@ -155,9 +279,9 @@ impl Default for CodexProcessState {
 /// Executes a Codex task in non-interactive mode with streaming output
 #[tauri::command]
 pub async fn execute_codex(
-    options: CodexExecutionOptions,
+    mut options: CodexExecutionOptions,
     app_handle: AppHandle,
-) -> Result<(), String> {
+) -> Result<(), AppError> {
     // Avoid logging sensitive fields (prompt/api_key). Log only non-sensitive metadata.
     log::info!(
         "execute_codex called: project_path={}, mode={:?}, model={:?}, json={}, output_schema_present={}, output_file_present={}, skip_git_repo_check={}, session_id_present={}, resume_last={}, api_key_present={}, prompt_len={}",
@@ -174,114 +298,363 @@ pub async fn execute_codex(
         options.prompt.len()
     );
 
-    // Build codex exec command
-    let (cmd, prompt) = build_codex_command(&options, false, None)?;
+    apply_prompt_enhancement(&mut options, &app_handle).await?;
+    apply_project_context(&mut options, true);
+    apply_config_overrides(&mut options).await?;
+    start_codex_trace(&mut options, "execute");
 
-    // Execute and stream output
+    // Execute codex exec and stream output
     let session_id = format!("codex-{}", uuid::Uuid::new_v4());
-    execute_codex_process(session_id, cmd, prompt, options.project_path.clone(), app_handle).await
+    let launch = CodexLaunch {
+        power_hold: codex_power_hold(&session_id, &options),
+        options,
+        resume_target: None,
+        session_lock: None,
+        stall_reconnects: 0,
+    };
+    execute_codex_process(session_id, launch, 1, app_handle)
+        .await
+        .map_err(classify_codex_error)
 }
 
 /// Resumes a previous Codex session
 #[tauri::command]
 pub async fn resume_codex(
     session_id: String,
-    options: CodexExecutionOptions,
+    mut options: CodexExecutionOptions,
     app_handle: AppHandle,
-) -> Result<(), String> {
+) -> Result<(), AppError> {
     log::info!("resume_codex called for session: {}", session_id);
 
-    // Build codex exec resume command (session_id added inside build function)
-    let (cmd, prompt) = build_codex_command(&options, true, Some(&session_id))?;
+    // Refuse to resume while another instance is writing the same session file
+    let session_lock = lock_for_execution(
+        resolve_session_file("codex", &session_id, "").ok(),
+        "codex resume",
+    )?;
 
-    // Execute and stream output
+    apply_prompt_enhancement(&mut options, &app_handle).await?;
+    apply_project_context(&mut options, false);
+
+    if options.check_context_overflow && !options.force {
+        check_resume_context(&session_id, &options).await?;
+    }
+    apply_config_overrides(&mut options).await?;
+    start_codex_trace(&mut options, "resume");
+
+    // Execute codex exec resume and stream output (session_id added inside build function)
     let channel_session_id = format!("codex-{}", uuid::Uuid::new_v4());
-    execute_codex_process(
-        channel_session_id,
-        cmd,
-        prompt,
-        options.project_path.clone(),
+    let launch = CodexLaunch {
+        power_hold: codex_power_hold(&channel_session_id, &options),
+        options,
+        resume_target: Some(session_id),
+        session_lock: session_lock.map(Arc::new),
+        stall_reconnects: 0,
+    };
+    execute_codex_process(channel_session_id, launch, 1, app_handle)
+        .await
+        .map_err(classify_codex_error)
+}
+
+/// Enhances the prompt with acemcp context when `enhancement` is set
+///
+/// The trace ID is assigned here so the enhancement metadata can be matched to the prompt record
+async fn apply_prompt_enhancement(
+    options: &mut CodexExecutionOptions,
+    app_handle: &AppHandle,
+) -> Result<(), String> {
+    let Some(request) = options.enhancement.take() else {
+        return Ok(());
+    };
+    let trace_id = resolve_trace_id(options.trace_id.take());
+    let prompt = std::mem::take(&mut options.prompt);
+    options.prompt = enhance_for_execution(
         app_handle,
+        &options.project_path,
+        prompt,
+        request,
+        &trace_id,
+    )
+    .await?;
+    options.trace_id = Some(trace_id);
+    Ok(())
+}
+
+/// Appends the compiled project memory (when `use_project_memory` is set)
+/// and the protected paths constraint to the prompt
+///
+/// The memory goes only into the first prompt of a new session; a resumed
+/// session already carries it in its history
+fn apply_project_context(options: &mut CodexExecutionOptions, new_session: bool) {
+    if options.use_project_memory && new_session {
+        if let Some(memory) = compiled_memory_for_execution(&options.project_path) {
+            options.prompt = append_memory_to_prompt(&options.prompt, &memory);
+        }
+    }
+    options.prompt = append_protected_paths_to_prompt(&options.prompt, &options.project_path);
+}
+
+/// Resolves the requested `-c` overrides against the installed CLI version
+///
+/// Unsupported keys are dropped with a warning, or rejected when `strict_config` is set
+async fn apply_config_overrides(options: &mut CodexExecutionOptions) -> Result<(), String> {
+    if options.reasoning_effort.is_none() && options.verbosity.is_none() {
+        return Ok(());
+    }
+    let profile = resolve_profile(options)?;
+    let capabilities = current_capabilities().await;
+    let resolved = resolve_config_overrides(
+        options.reasoning_effort.as_deref(),
+        options.verbosity.as_deref(),
+        profile.as_ref(),
+        options.strict_config,
+        &capabilities,
+    )?;
+    options.config_overrides = resolved.overrides;
+    options.config_warnings = resolved.warnings;
+    Ok(())
+}
+
+/// Assigns the run its trace ID and records the start of the execution
+fn start_codex_trace(options: &mut CodexExecutionOptions, kind: &str) {
+    let trace_id = resolve_trace_id(options.trace_id.take());
+    log::info!("[Codex] {} run started with trace_id={}", kind, trace_id);
+    record_trace_event(
+        &trace_id,
+        STAGE_EXECUTION_STARTED,
+        TraceFields::engine("codex").with_detail(serde_json::json!({
+            "kind": kind,
+            "projectPath": options.project_path,
+            "model": options.model,
+            "mode": options.mode.as_str(),
+            "configOverrides": options.config_overrides,
+        })),
+    );
+    options.trace_id = Some(trace_id);
+}
+
+/// Prevents system sleep for the duration of a run
+fn codex_power_hold(
+    session_id: &str,
+    options: &CodexExecutionOptions,
+) -> Option<Arc<PowerInhibitHold>> {
+    power_inhibit::acquire(
+        "codex",
+        session_id,
+        options.trace_id.as_deref(),
+        &options.project_path,
     )
+    .map(Arc::new)
+}
+
+/// Refuses to resume a session whose estimated context exceeds the model window
+async fn check_resume_context(
+    session_id: &str,
+    options: &CodexExecutionOptions,
+) -> Result<(), String> {
+    let session_id = session_id.to_string();
+    let model = options.model.clone();
+    let prompt = options.prompt.clone();
+
+    let estimate = tokio::task::spawn_blocking(move || {
+        let session_file = find_session_path("codex", &session_id, "")?;
+        match session_file {
+            Some(path) => estimate_session_context(&path, model.as_deref(), &prompt).map(Some),
+            None => Ok(None),
+        }
+    })
     .await
+    .map_err(|e| format!("Context estimation task failed: {}", e))?;
+
+    match estimate {
+        Ok(Some(estimate)) if estimate.overflow => {
+            log::warn!(
+                "[Codex] Refusing to resume: estimated {} tokens exceeds window {:?}",
+                estimate.total_tokens,
+                estimate.context_window
+            );
+            Err(context_overflow_error(&estimate))
+        }
+        Ok(_) => Ok(()),
+        Err(e) => {
+            // The estimate is advisory; never block a resume because it failed
+            log::warn!("[Codex] Context estimation failed, resuming anyway: {}", e);
+            Ok(())
+        }
+    }
 }
 
 /// Resumes the last Codex session
 #[tauri::command]
 pub async fn resume_last_codex(
-    options: CodexExecutionOptions,
+    mut options: CodexExecutionOptions,
     app_handle: AppHandle,
-) -> Result<(), String> {
+) -> Result<(), AppError> {
     log::info!("resume_last_codex called");
 
-    // Build codex exec resume --last command
-    let (cmd, prompt) = build_codex_command(&options, true, Some("--last"))?;
+    apply_prompt_enhancement(&mut options, &app_handle).await?;
+    apply_project_context(&mut options, false);
+    apply_config_overrides(&mut options).await?;
+    start_codex_trace(&mut options, "resume_last");
 
-    // Execute and stream output
+    // Execute codex exec resume --last and stream output
     let session_id = format!("codex-{}", uuid::Uuid::new_v4());
-    execute_codex_process(session_id, cmd, prompt, options.project_path.clone(), app_handle).await
+    let launch = CodexLaunch {
+        power_hold: codex_power_hold(&session_id, &options),
+        options,
+        resume_target: Some("--last".to_string()),
+        session_lock: None,
+        stall_reconnects: 0,
+    };
+    execute_codex_process(session_id, launch, 1, app_handle)
+        .await
+        .map_err(classify_codex_error)
 }
 
 /// Cancels a running Codex execution
+///
+/// The process is interrupted first so Codex can finish writing its rollout file,
+/// and only force killed if it does not exit within the grace period
 #[tauri::command]
 pub async fn cancel_codex(session_id: Option<String>, app_handle: AppHandle) -> Result<(), String> {
-    use crate::commands::claude::kill_process_tree;
-
     log::info!("cancel_codex called for session: {:?}", session_id);
 
-    let state: tauri::State<'_, CodexProcessState> = app_handle.state();
-    let mut processes = state.processes.lock().await;
+    // A run waiting to retry has no process; cancelling it ends the run instead
+    if cancel_pending_retries(session_id.as_deref(), "codex-") {
+        log::info!(
+            "Cancelled pending Codex retry for session: {:?}",
+            session_id
+        );
+    }
 
-    if let Some(sid) = session_id {
-        // Cancel specific session
-        if let Some(handle) = processes.remove(&sid) {
-            let pid = handle.pid;
-            log::info!("Killing Codex process tree for session: {} (PID: {})", sid, pid);
-
-            // Kill the entire process tree (parent + all children)
-            if let Err(e) = kill_process_tree(pid) {
-                log::error!("Failed to kill process tree for session {}: {}", sid, e);
-                // Fallback: try to kill main process directly
-                let mut child = handle.child;
-                if let Err(e2) = child.kill().await {
-                    log::error!("Fallback kill also failed: {}", e2);
+    // Take the handles out first so the state lock is not held while waiting for exit
+    let state: tauri::State<'_, CodexProcessState> = app_handle.state();
+    let handles: Vec<(String, CodexProcessHandle)> = {
+        let mut processes = state.processes.lock().await;
+        match session_id.as_deref() {
+            // Cancel specific session (backend session ID or Codex thread ID)
+            Some(sid) => {
+                let key = processes
+                    .iter()
+                    .find(|(key, handle)| {
+                        key.as_str() == sid || handle.cli_session_id.as_deref() == Some(sid)
+                    })
+                    .map(|(key, _)| key.clone());
+                match key.and_then(|key| processes.remove_entry(&key)) {
+                    Some(entry) => vec![entry],
+                    None => {
+                        log::warn!("No running process found for session: {}", sid);
+                        Vec::new()
+                    }
                 }
-            } else {
-                log::info!("Successfully killed Codex process tree for session: {}", sid);
             }
-        } else {
-            log::warn!("No running process found for session: {}", sid);
+            // Cancel all processes
+            None => processes.drain().collect(),
         }
+    };
+
+    let outcomes = futures::future::join_all(
+        handles
+            .into_iter()
+            .map(|(sid, handle)| cancel_codex_process(sid, handle)),
+    )
+    .await;
+    for outcome in outcomes {
+        let _ = app_handle.emit(&format!("codex-cancelled:{}", outcome.session_id), &outcome);
+        let _ = app_handle.emit("codex-cancelled", &outcome);
+    }
+
+    Ok(())
+}
+
+/// Interrupts one Codex process, force kills it after the grace period,
+/// then checks that its rollout file ends with a complete event line
+async fn cancel_codex_process(session_id: String, mut handle: CodexProcessHandle) -> CancelOutcome {
+    use crate::commands::claude::kill_process_tree;
+
+    let pid = handle.pid;
+    log::info!(
+        "Cancelling Codex process for session: {} (PID: {})",
+        session_id,
+        pid
+    );
+
+    let exited = request_exit_and_wait(pid, GRACEFUL_EXIT_TIMEOUT, || {
+        !matches!(handle.child.try_wait(), Ok(None))
+    })
+    .await;
+    let mode = if exited {
+        CancelMode::Graceful
     } else {
-        // Cancel all processes
-        for (sid, handle) in processes.drain() {
-            let pid = handle.pid;
-            log::info!("Killing Codex process tree for session: {} (PID: {})", sid, pid);
-
-            if let Err(e) = kill_process_tree(pid) {
-                log::error!("Failed to kill process tree for session {}: {}", sid, e);
-                let mut child = handle.child;
-                if let Err(e2) = child.kill().await {
-                    log::error!("Fallback kill also failed: {}", e2);
-                }
-            } else {
-                log::info!("Successfully killed Codex process tree for session: {}", sid);
+        // Kill the entire process tree (parent + all children)
+        if let Err(e) = kill_process_tree(pid) {
+            log::error!(
+                "Failed to kill process tree for session {}: {}",
+                session_id,
+                e
+            );
+            // Fallback: try to kill main process directly
+            if let Err(e2) = handle.child.kill().await {
+                log::error!("Fallback kill also failed: {}", e2);
             }
+        } else {
+            log::info!(
+                "Successfully killed Codex process tree for session: {}",
+                session_id
+            );
         }
-    }
+        CancelMode::Forced
+    };
+    // Job Object is dropped here, killing remaining descendants (MCP servers, node.exe, etc.)
+    drop(handle.job_object);
 
-    Ok(())
+    let session_file = handle
+        .cli_session_id
+        .as_deref()
+        .and_then(|thread_id| resolve_session_file("codex", thread_id, "").ok())
+        .and_then(|path| {
+            check_session_file(&path)
+                .map_err(|e| log::warn!("Failed to check Codex session file: {}", e))
+                .ok()
+        });
+    log::info!(
+        "Codex session {} cancelled ({:?}), session file: {:?}",
+        session_id,
+        mode,
+        session_file
+    );
+
+    CancelOutcome {
+        engine: "codex".to_string(),
+        session_id,
+        pid: Some(pid),
+        mode,
+        session_file,
+    }
 }
 
 // ============================================================================
 // Session Management
 // ============================================================================
 
-/// Lists all Codex sessions by reading ~/.codex/sessions directory
+/// Lists Codex sessions by reading ~/.codex/sessions directory
 /// On Windows with WSL mode, reads from WSL filesystem via UNC path
+///
+/// `include_context_messages`: 为 true 时，Codex 注入的 environment_context / AGENTS.md
+/// 消息也可作为会话预览（first_message），默认跳过
+///
+/// 分页与过滤见 [`CodexSessionQuery`]：按 rollout 文件名（即创建时间）从新到旧排序后
+/// 只完整解析当前页的文件，日期过滤只看日期目录，项目过滤只读各文件首行的 session_meta。
+/// 解析结果按文件 mtime 缓存在 sessions 目录下的 `.index.json`
 #[tauri::command]
-pub async fn list_codex_sessions() -> Result<Vec<CodexSession>, String> {
+pub async fn list_codex_sessions(
+    include_context_messages: Option<bool>,
+    query: Option<CodexSessionQuery>,
+) -> Result<Vec<CodexSession>, String> {
     log::info!("list_codex_sessions called");
+    let query = query.unwrap_or_default();
+    let include_context_messages = include_context_messages.unwrap_or(false);
+    // 在进入阻塞任务前校验日期格式
+    query.date_range()?;
 
     // Use unified sessions directory function (supports WSL)
     let sessions_dir = get_codex_sessions_dir()?;
@@ -295,56 +668,394 @@ pub async fn list_codex_sessions() -> Result<Vec<CodexSession>, String> {
         return Ok(Vec::new());
     }
 
-    let mut sessions = Vec::new();
+    let sessions = tokio::task::spawn_blocking(move || {
+        list_sessions_in_dir(&sessions_dir, &query, include_context_messages)
+    })
+    .await
+    .map_err(|e| format!("Failed to list Codex sessions: {}", e))??;
 
-    // Walk through date-organized directories (2025/11/23/rollout-xxx.jsonl)
-    if let Ok(entries) = std::fs::read_dir(&sessions_dir) {
-        for year_entry in entries.flatten() {
-            if let Ok(month_entries) = std::fs::read_dir(year_entry.path()) {
-                for month_entry in month_entries.flatten() {
-                    if let Ok(day_entries) = std::fs::read_dir(month_entry.path()) {
-                        for day_entry in day_entries.flatten() {
-                            // day_entry is a day directory (e.g., "23"), go into it
-                            if day_entry.path().is_dir() {
-                                if let Ok(file_entries) = std::fs::read_dir(day_entry.path()) {
-                                    for file_entry in file_entries.flatten() {
-                                        let path = file_entry.path();
-                                        if path.extension().and_then(|s| s.to_str())
-                                            == Some("jsonl")
-                                        {
-                                            match parse_codex_session_file(&path) {
-                                                Some(session) => {
-                                                    log::debug!(
-                                                        "Found session: {} ({})",
-                                                        session.id,
-                                                        session.project_path
-                                                    );
-                                                    sessions.push(session);
-                                                }
-                                                None => {
-                                                    log::debug!("Failed to parse: {:?}", path);
-                                                }
-                                            }
-                                        }
-                                    }
-                                }
-                            }
-                        }
+    log::info!("Found {} Codex sessions", sessions.len());
+    Ok(sessions)
+}
+
+/// `list_codex_sessions` 的分页与过滤参数
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CodexSessionQuery {
+    /// 页码（从 0 开始）
+    pub page: usize,
+    /// 每页会话数，0 表示不分页
+    pub page_size: usize,
+    /// 只保留工作目录与该路径一致的会话
+    pub project_path_filter: Option<String>,
+    /// 起始日期（YYYY-MM-DD，含）
+    pub date_from: Option<String>,
+    /// 结束日期（YYYY-MM-DD，含）
+    pub date_to: Option<String>,
+}
+
+impl CodexSessionQuery {
+    /// 解析 `date_from` / `date_to`
+    fn date_range(&self) -> Result<(Option<chrono::NaiveDate>, Option<chrono::NaiveDate>), String> {
+        let parse = |date: &Option<String>| {
+            date.as_deref()
+                .map(|d| {
+                    chrono::NaiveDate::parse_from_str(d.trim(), "%Y-%m-%d")
+                        .map_err(|e| format!("Invalid date '{}': {}", d, e))
+                })
+                .transpose()
+        };
+        Ok((parse(&self.date_from)?, parse(&self.date_to)?))
+    }
+}
+
+/// 会话元数据索引文件名（位于 sessions 目录下）
+const SESSION_INDEX_FILE: &str = ".index.json";
+
+/// 会话元数据索引：相对 sessions 目录的文件路径 -> 解析结果
+#[derive(Debug, Default, Deserialize, Serialize)]
+struct CodexSessionIndex {
+    #[serde(default)]
+    entries: HashMap<String, CodexSessionIndexEntry>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CodexSessionIndexEntry {
+    /// 文件 mtime（毫秒），不一致即视为失效
+    mtime_ms: u64,
+    size: u64,
+    /// session_meta 中的会话 ID 与工作目录（项目过滤只需要这两项）
+    #[serde(default)]
+    id: String,
+    #[serde(default)]
+    cwd: String,
+    #[serde(default)]
+    include_context_messages: bool,
+    /// 完整解析结果，只有文件出现在某一页时才填充
+    #[serde(default)]
+    session: Option<CodexSession>,
+}
+
+impl CodexSessionIndexEntry {
+    fn is_fresh(&self, stamp: Option<(u64, u64)>) -> bool {
+        stamp == Some((self.mtime_ms, self.size))
+    }
+
+    /// 工作目录；旧版索引项只有完整解析结果
+    fn cwd(&self) -> Option<&str> {
+        if !self.cwd.is_empty() {
+            return Some(&self.cwd);
+        }
+        self.session.as_ref().map(|s| s.project_path.as_str())
+    }
+}
+
+/// 文件的 (mtime 毫秒, 大小)
+fn file_stamp(path: &std::path::Path) -> Option<(u64, u64)> {
+    let metadata = std::fs::metadata(path).ok()?;
+    let mtime = metadata
+        .modified()
+        .ok()?
+        .duration_since(std::time::UNIX_EPOCH)
+        .ok()?;
+    Some((mtime.as_millis() as u64, metadata.len()))
+}
+
+/// 遍历按日期组织的目录（2025/11/23/rollout-xxx.jsonl），返回 (日期目录, 文件路径)
+fn collect_session_files(
+    sessions_dir: &std::path::Path,
+) -> Vec<(Option<chrono::NaiveDate>, std::path::PathBuf)> {
+    let read_dirs = |dir: &std::path::Path| -> Vec<std::path::PathBuf> {
+        std::fs::read_dir(dir)
+            .map(|entries| {
+                entries
+                    .flatten()
+                    .map(|entry| entry.path())
+                    .filter(|path| path.is_dir())
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+    let name = |path: &std::path::Path| {
+        path.file_name()
+            .and_then(|s| s.to_str())
+            .unwrap_or("")
+            .to_string()
+    };
+
+    let mut files = Vec::new();
+    for year_dir in read_dirs(sessions_dir) {
+        for month_dir in read_dirs(&year_dir) {
+            for day_dir in read_dirs(&month_dir) {
+                let date = format!(
+                    "{}-{}-{}",
+                    name(&year_dir),
+                    name(&month_dir),
+                    name(&day_dir)
+                );
+                let date = chrono::NaiveDate::parse_from_str(&date, "%Y-%m-%d").ok();
+                let Ok(file_entries) = std::fs::read_dir(&day_dir) else {
+                    continue;
+                };
+                for file_entry in file_entries.flatten() {
+                    let path = file_entry.path();
+                    if path.extension().and_then(|s| s.to_str()) == Some("jsonl") {
+                        files.push((date, path));
                     }
                 }
             }
         }
     }
+    files
+}
+
+/// 列出 sessions 目录中符合条件的会话，未变化的文件直接取自索引
+fn list_sessions_in_dir(
+    sessions_dir: &std::path::Path,
+    query: &CodexSessionQuery,
+    include_context_messages: bool,
+) -> Result<Vec<CodexSession>, String> {
+    let (date_from, date_to) = query.date_range()?;
+    let index_path = sessions_dir.join(SESSION_INDEX_FILE);
+    let mut index: CodexSessionIndex = load_json_config(&index_path).unwrap_or_else(|e| {
+        log::warn!("Ignoring unreadable Codex session index: {}", e);
+        CodexSessionIndex::default()
+    });
+    let relative_key = |path: &std::path::Path| {
+        path.strip_prefix(sessions_dir)
+            .unwrap_or(path)
+            .to_string_lossy()
+            .replace('\\', "/")
+    };
+
+    let files = collect_session_files(sessions_dir);
+
+    // 清理已删除文件的索引项
+    let existing: std::collections::HashSet<String> =
+        files.iter().map(|(_, path)| relative_key(path)).collect();
+    let before = index.entries.len();
+    index.entries.retain(|key, _| existing.contains(key));
+    let mut dirty = index.entries.len() != before;
+
+    let mut candidates: Vec<std::path::PathBuf> = files
+        .into_iter()
+        .filter(|(date, _)| match date {
+            Some(date) => {
+                date_from.is_none_or(|start| *date >= start)
+                    && date_to.is_none_or(|end| *date <= end)
+            }
+            None => date_from.is_none() && date_to.is_none(),
+        })
+        .map(|(_, path)| path)
+        .collect();
+    // rollout-YYYY-MM-DDTHH-MM-SS-<uuid>.jsonl：文件名倒序即创建时间从新到旧
+    candidates.sort_by(|a, b| b.file_name().cmp(&a.file_name()));
+
+    // 项目过滤：工作目录取自索引，缺失或过期时只读取文件首行
+    if let Some(filter) = query
+        .project_path_filter
+        .as_deref()
+        .filter(|p| !p.trim().is_empty())
+    {
+        let target = normalize_path_for_comparison(filter);
+        candidates.retain(|path| {
+            let key = relative_key(path);
+            let stamp = file_stamp(path);
+            let cached = index
+                .entries
+                .get(&key)
+                .filter(|entry| entry.is_fresh(stamp))
+                .and_then(|entry| entry.cwd().map(String::from));
+            let cwd = match cached {
+                Some(cwd) => cwd,
+                None => {
+                    let Some((id, cwd)) = read_session_header(path) else {
+                        return false;
+                    };
+                    if let Some((mtime_ms, size)) = stamp {
+                        index.entries.insert(
+                            key,
+                            CodexSessionIndexEntry {
+                                mtime_ms,
+                                size,
+                                id,
+                                cwd: cwd.clone(),
+                                include_context_messages,
+                                session: None,
+                            },
+                        );
+                        dirty = true;
+                    }
+                    cwd
+                }
+            };
+            normalize_path_for_comparison(&cwd) == target
+        });
+    }
+
+    let page_size = if query.page_size == 0 {
+        usize::MAX
+    } else {
+        query.page_size
+    };
+    let mut sessions = Vec::new();
+    for path in candidates
+        .into_iter()
+        .skip(query.page.saturating_mul(query.page_size))
+        .take(page_size)
+    {
+        let key = relative_key(&path);
+        let stamp = file_stamp(&path);
+        let cached = index
+            .entries
+            .get(&key)
+            .filter(|entry| {
+                entry.is_fresh(stamp) && entry.include_context_messages == include_context_messages
+            })
+            .and_then(|entry| entry.session.as_ref());
+        if let Some(session) = cached {
+            sessions.push(session.clone());
+            continue;
+        }
+
+        match parse_codex_session_file_with_options(&path, include_context_messages) {
+            Some(session) => {
+                log::debug!("Found session: {} ({})", session.id, session.project_path);
+                if let Some((mtime_ms, size)) = stamp {
+                    index.entries.insert(
+                        key,
+                        CodexSessionIndexEntry {
+                            mtime_ms,
+                            size,
+                            id: session.id.clone(),
+                            cwd: session.project_path.clone(),
+                            include_context_messages,
+                            session: Some(session.clone()),
+                        },
+                    );
+                    dirty = true;
+                }
+                sessions.push(session);
+            }
+            None => {
+                log::debug!("Failed to parse: {:?}", path);
+            }
+        }
+    }
+
+    if dirty {
+        if let Err(e) = save_json_config(&index, &index_path) {
+            log::warn!("Failed to save Codex session index: {}", e);
+        }
+    }
 
     // Sort by creation time (newest first)
     sessions.sort_by(|a, b| b.created_at.cmp(&a.created_at));
-
-    log::info!("Found {} Codex sessions", sessions.len());
     Ok(sessions)
 }
 
+/// 从会话索引中移除某个会话的缓存项（会话被回滚、截断或删除后调用）
+pub(crate) fn invalidate_codex_session_index(session_id: &str) {
+    let Ok(sessions_dir) = get_codex_sessions_dir() else {
+        return;
+    };
+    if let Err(e) = remove_index_entries(&sessions_dir, session_id) {
+        log::warn!("Failed to update Codex session index: {}", e);
+    }
+}
+
+/// 删除索引中属于该会话的条目，返回是否有改动
+fn remove_index_entries(sessions_dir: &std::path::Path, session_id: &str) -> Result<bool, String> {
+    let index_path = sessions_dir.join(SESSION_INDEX_FILE);
+    if !index_path.exists() {
+        return Ok(false);
+    }
+    let mut index: CodexSessionIndex = load_json_config(&index_path)?;
+    let before = index.entries.len();
+    index.entries.retain(|_, entry| {
+        entry.id != session_id && entry.session.as_ref().is_none_or(|s| s.id != session_id)
+    });
+    if index.entries.len() == before {
+        return Ok(false);
+    }
+    save_json_config(&index, &index_path)?;
+    Ok(true)
+}
+
+/// 只读取首行 session_meta，返回 (会话 ID, 工作目录)
+fn read_session_header(path: &std::path::Path) -> Option<(String, String)> {
+    use std::io::{BufRead, BufReader};
+
+    let file = std::fs::File::open(path).ok()?;
+    let first_line = BufReader::new(file).lines().next()?.ok()?;
+    let meta: serde_json::Value = serde_json::from_str(&first_line).ok()?;
+    if meta["type"].as_str()? != "session_meta" {
+        return None;
+    }
+    let payload = &meta["payload"];
+    let id = payload["id"].as_str()?.to_string();
+    Some((id, session_cwd(payload["cwd"].as_str().unwrap_or(""))))
+}
+
+/// session_meta 中的 cwd；Windows 上把 WSL 路径转换为 Windows 路径
+pub(crate) fn session_cwd(cwd_raw: &str) -> String {
+    // Convert WSL path (/mnt/c/... or /home/...) to Windows path (C:\... or \\wsl.localhost\...)
+    // This ensures the UI displays Windows-friendly paths that match the opened project
+    #[cfg(target_os = "windows")]
+    if cwd_raw.starts_with('/') {
+        return wsl_utils::wsl_to_windows_path(
+            cwd_raw,
+            wsl_utils::get_wsl_config().distro.as_deref(),
+        )
+        .unwrap_or_else(|_| cwd_raw.to_string());
+    }
+    cwd_raw.to_string()
+}
+
+/// 判断 user 消息是否为 Codex 自动注入的上下文消息（而非用户输入）
+///
+/// 这类合成消息结构固定，整条消息即一个完整的块：
+/// - `<environment_context>...</environment_context>`
+/// - `# AGENTS.md instructions for <dir>` 标题行 + `<INSTRUCTIONS>...</INSTRUCTIONS>`
+/// - 旧版本的 `<user_instructions>...</user_instructions>`
+///
+/// 只看结构而非子串，避免正常提到 AGENTS.md 的用户消息被误判
+pub fn is_codex_context_message(text: &str) -> bool {
+    let trimmed = text.trim();
+
+    let is_wrapped = |tag: &str| {
+        trimmed.starts_with(&format!("<{}>", tag)) && trimmed.ends_with(&format!("</{}>", tag))
+    };
+    if is_wrapped("environment_context") || is_wrapped("user_instructions") {
+        return true;
+    }
+
+    let mut parts = trimmed.splitn(2, '\n');
+    let header = parts.next().unwrap_or("").trim_end();
+    let body = parts.next().unwrap_or("").trim();
+    header.starts_with("# AGENTS.md instructions for ")
+        && body.starts_with("<INSTRUCTIONS>")
+        && body.ends_with("</INSTRUCTIONS>")
+}
+
+/// 解析 first_message 时在 session_meta 之后最多读取的行数（不做全文件扫描）
+const FIRST_MESSAGE_SCAN_LINES: usize = 200;
+/// 回退摘要（assistant 消息 / instructions）截取的字符数
+const FIRST_MESSAGE_FALLBACK_CHARS: usize = 80;
+/// 读取最后一条 timestamp 时从文件末尾读取的字节数
+const LAST_TIMESTAMP_TAIL_BYTES: u64 = 64 * 1024;
+
 /// Parses a Codex session JSONL file to extract metadata
 pub fn parse_codex_session_file(path: &std::path::Path) -> Option<CodexSession> {
+    parse_codex_session_file_with_options(path, false)
+}
+
+/// Parses a Codex session JSONL file, optionally using injected context messages as first_message
+pub fn parse_codex_session_file_with_options(
+    path: &std::path::Path,
+    include_context_messages: bool,
+) -> Option<CodexSession> {
     use std::io::{BufRead, BufReader};
 
     let file = std::fs::File::open(path).ok()?;
@@ -367,84 +1078,99 @@ pub fn parse_codex_session_file(path: &std::path::Path) -> Option<CodexSession>
         .timestamp() as u64;
 
     // Get cwd and convert from WSL path format if needed
-    let cwd_raw = payload["cwd"].as_str().unwrap_or("");
-    #[cfg(target_os = "windows")]
-    let cwd = {
-        // Convert WSL path (/mnt/c/...) to Windows path (C:\...)
-        // This ensures the UI displays Windows-friendly paths
-        if cwd_raw.starts_with("/mnt/") {
-            wsl_utils::wsl_to_windows_path(cwd_raw)
-        } else {
-            cwd_raw.to_string()
-        }
-    };
-    #[cfg(not(target_os = "windows"))]
-    let cwd = cwd_raw.to_string();
+    let cwd = session_cwd(payload["cwd"].as_str().unwrap_or(""));
 
-    // Extract first user message and other metadata from subsequent lines
-    let mut first_message: Option<String> = None;
+    // Extract first user message and other metadata from the first lines only
+    let mut user_message: Option<String> = None;
+    let mut assistant_message: Option<String> = None;
     let mut last_timestamp: Option<String> = None;
-    let mut model: Option<String> = None;
-
-    // Parse remaining lines to find first user message
-    for line_result in lines {
-        if let Ok(line) = line_result {
-            if let Ok(event) = serde_json::from_str::<serde_json::Value>(&line) {
-                // Update last timestamp
-                if let Some(ts) = event["timestamp"].as_str() {
-                    last_timestamp = Some(ts.to_string());
-                }
+    let mut model: Option<String> = payload["model"].as_str().map(String::from);
 
-                // Extract model from session_meta or other events
-                if event["type"].as_str() == Some("session_meta") {
-                    if let Some(m) = event["payload"]["model"].as_str() {
-                        model = Some(m.to_string());
-                    }
-                }
+    for line in lines.take(FIRST_MESSAGE_SCAN_LINES).map_while(Result::ok) {
+        let Ok(event) = serde_json::from_str::<serde_json::Value>(&line) else {
+            continue;
+        };
 
-                // Find first user message
-                if first_message.is_none() && event["type"].as_str() == Some("response_item") {
-                    if let Some(payload_obj) = event["payload"].as_object() {
-                        if payload_obj.get("role").and_then(|r| r.as_str()) == Some("user") {
-                            if let Some(content) =
-                                payload_obj.get("content").and_then(|c| c.as_array())
-                            {
-                                // Extract text from content array
-                                for item in content {
-                                    // Check if this is a text content block (input_text type)
-                                    if item["type"].as_str() == Some("input_text") {
-                                        if let Some(text) = item["text"].as_str() {
-                                            // Skip system messages (environment_context and AGENTS.md)
-                                            if !text.contains("<environment_context>")
-                                                && !text.contains("# AGENTS.md instructions")
-                                                && !text.is_empty()
-                                                && text.trim().len() > 0
-                                            {
-                                                first_message = Some(text.to_string());
-                                                break;
-                                            }
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
+        if let Some(ts) = event["timestamp"].as_str() {
+            last_timestamp = Some(ts.to_string());
+        }
+
+        let event_type = event["type"].as_str();
+        let event_payload = &event["payload"];
 
-                // Early exit if we have all info
-                if first_message.is_some() && model.is_some() {
-                    break;
+        // Extract model from session_meta or other events
+        if event_type == Some("session_meta") {
+            if let Some(m) = event_payload["model"].as_str() {
+                model = Some(m.to_string());
+            }
+        }
+
+        if user_message.is_none() && event_type == Some("response_item") {
+            if let Some(content) = event_payload["content"].as_array() {
+                match event_payload["role"].as_str() {
+                    Some("user") => {
+                        // Skip injected context messages unless requested
+                        user_message = content
+                            .iter()
+                            .filter(|item| item["type"].as_str() == Some("input_text"))
+                            .filter_map(|item| item["text"].as_str())
+                            .find(|text| {
+                                (include_context_messages || !is_codex_context_message(text))
+                                    && !text.trim().is_empty()
+                            })
+                            .map(String::from);
+                    }
+                    Some("assistant") if assistant_message.is_none() => {
+                        assistant_message = content
+                            .iter()
+                            .filter_map(|item| item["text"].as_str())
+                            .find(|text| !text.trim().is_empty())
+                            .map(String::from);
+                    }
+                    _ => {}
                 }
             }
         }
+
+        if assistant_message.is_none()
+            && event_type == Some("event_msg")
+            && event_payload["type"].as_str() == Some("agent_message")
+        {
+            assistant_message = event_payload["message"]
+                .as_str()
+                .filter(|text| !text.trim().is_empty())
+                .map(String::from);
+        }
+
+        // Stop at the first user message; updated_at comes from the file tail below
+        if user_message.is_some() {
+            break;
+        }
     }
 
+    // updated_at comes from the file tail so the whole file is never scanned
+    let last_timestamp = read_last_timestamp(path).or(last_timestamp);
     let updated_at = last_timestamp
         .as_ref()
         .and_then(|ts| chrono::DateTime::parse_from_rfc3339(ts).ok())
         .map(|dt| dt.timestamp() as u64)
         .unwrap_or(created_at);
 
+    let (first_message, first_message_source) = match (user_message, assistant_message) {
+        (Some(text), _) => (text, FirstMessageSource::User),
+        (None, Some(text)) => (
+            format!("[AI] {}", truncate_preview(&text)),
+            FirstMessageSource::Assistant,
+        ),
+        (None, None) => match session_meta_summary(payload) {
+            Some(summary) => (summary, FirstMessageSource::SessionMeta),
+            None => (
+                placeholder_first_message(path),
+                FirstMessageSource::Placeholder,
+            ),
+        },
+    };
+
     Some(CodexSession {
         id: session_id,
         project_path: cwd,
@@ -453,11 +1179,74 @@ pub fn parse_codex_session_file(path: &std::path::Path) -> Option<CodexSession>
         mode: CodexExecutionMode::ReadOnly,
         model,
         status: "completed".to_string(),
-        first_message,
+        first_message: Some(first_message),
+        first_message_source,
         last_message_timestamp: last_timestamp,
     })
 }
 
+/// First `FIRST_MESSAGE_FALLBACK_CHARS` characters of a text, on one line
+fn truncate_preview(text: &str) -> String {
+    let line = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    truncate_chars(&line, FIRST_MESSAGE_FALLBACK_CHARS)
+}
+
+/// 由 session_meta 的 instructions / originator 生成会话摘要
+fn session_meta_summary(payload: &serde_json::Value) -> Option<String> {
+    let originator = payload["originator"]
+        .as_str()
+        .map(str::trim)
+        .filter(|s| !s.is_empty());
+    let instructions = payload["instructions"]
+        .as_str()
+        .and_then(|s| s.lines().map(str::trim).find(|line| !line.is_empty()))
+        .map(truncate_preview);
+
+    match (originator, instructions) {
+        (Some(originator), Some(instructions)) => {
+            Some(format!("[{}] {}", originator, instructions))
+        }
+        (None, Some(instructions)) => Some(instructions),
+        (Some(originator), None) => Some(format!("来自 {} 的会话", originator)),
+        (None, None) => None,
+    }
+}
+
+/// “（无用户消息）”+ 文件名中的日期（rollout-YYYY-MM-DDTHH-MM-SS-<uuid>.jsonl）
+fn placeholder_first_message(path: &std::path::Path) -> String {
+    let date = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .and_then(|name| name.strip_prefix("rollout-"))
+        .and_then(|rest| rest.get(..10))
+        .filter(|date| chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d").is_ok());
+
+    match date {
+        Some(date) => format!("（无用户消息）{}", date),
+        None => "（无用户消息）".to_string(),
+    }
+}
+
+/// 从文件末尾读取最后一条事件的 timestamp
+fn read_last_timestamp(path: &std::path::Path) -> Option<String> {
+    use std::io::{Read, Seek, SeekFrom};
+
+    let mut file = std::fs::File::open(path).ok()?;
+    let len = file.metadata().ok()?.len();
+    let tail_start = len.saturating_sub(LAST_TIMESTAMP_TAIL_BYTES);
+    file.seek(SeekFrom::Start(tail_start)).ok()?;
+    let mut buf = Vec::new();
+    file.read_to_end(&mut buf).ok()?;
+
+    String::from_utf8_lossy(&buf)
+        .lines()
+        .rev()
+        .find_map(|line| {
+            let event = serde_json::from_str::<serde_json::Value>(line).ok()?;
+            event["timestamp"].as_str().map(String::from)
+        })
+}
+
 /// Loads Codex session history from JSONL file
 /// On Windows with WSL mode, reads from WSL filesystem via UNC path
 #[tauri::command]
@@ -466,12 +1255,8 @@ pub async fn load_codex_session_history(
 ) -> Result<Vec<serde_json::Value>, String> {
     log::info!("load_codex_session_history called for: {}", session_id);
 
-    // Use unified sessions directory function (supports WSL)
-    let sessions_dir = get_codex_sessions_dir()?;
-
-    // Search for file containing this session_id
-    let session_file = find_session_file(&sessions_dir, &session_id)
-        .ok_or_else(|| format!("Session file not found for ID: {}", session_id))?;
+    // Search for file containing this session_id (supports WSL)
+    let session_file = resolve_session_file("codex", &session_id, "")?;
 
     // Read and parse JSONL file
     use std::io::{BufRead, BufReader};
@@ -517,6 +1302,8 @@ pub async fn load_codex_session_history(
         }
     }
 
+    annotate_codex_history(&mut events);
+
     log::info!(
         "Loaded {} events from Codex session {} (total lines: {}, parse errors: {})",
         events.len(),
@@ -568,15 +1355,12 @@ pub fn find_session_file(
 /// Deletes a Codex session
 /// On Windows with WSL mode, deletes from WSL filesystem via UNC path
 #[tauri::command]
-pub async fn delete_codex_session(session_id: String) -> Result<String, String> {
+pub async fn delete_codex_session(app: AppHandle, session_id: String) -> Result<String, String> {
     log::info!("delete_codex_session called for: {}", session_id);
 
-    // Use unified sessions directory function (supports WSL)
-    let sessions_dir = get_codex_sessions_dir()?;
-
-    // Find the session file
-    let session_file = find_session_file(&sessions_dir, &session_id)
-        .ok_or_else(|| format!("Session file not found for ID: {}", session_id))?;
+    // Find the session file (supports WSL)
+    let session_file = resolve_session_file("codex", &session_id, "")?;
+    let _lock = SessionLock::acquire(&session_file, "codex delete")?;
 
     // Delete the file
     std::fs::remove_file(&session_file)
@@ -586,6 +1370,7 @@ pub async fn delete_codex_session(session_id: String) -> Result<String, String>
         "Successfully deleted Codex session file: {:?}",
         session_file
     );
+    invalidate_session(&app, "codex", Some(&session_id), None);
     Ok(format!("Session {} deleted", session_id))
 }
 
@@ -596,6 +1381,14 @@ pub async fn delete_codex_session(session_id: String) -> Result<String, String>
 /// Builds a Codex command with the given options
 /// Returns (Command, Option<String>) where the String is the prompt to be passed via stdin
 /// Supports both native execution and WSL mode on Windows
+/// Validates the requested profile against config.toml (WSL side in WSL mode)
+fn resolve_profile(options: &CodexExecutionOptions) -> Result<Option<CodexProfile>, String> {
+    match options.profile.as_deref().map(str::trim) {
+        Some(name) if !name.is_empty() => load_codex_profile(name).map(Some),
+        _ => Ok(None),
+    }
+}
+
 fn build_codex_command(
     options: &CodexExecutionOptions,
     is_resume: bool,
@@ -638,6 +1431,18 @@ fn build_codex_command(
         cmd.arg("--json");
     }
 
+    let profile = resolve_profile(options)?;
+    if let Some(ref profile) = profile {
+        cmd.arg("--profile");
+        cmd.arg(&profile.name);
+    }
+
+    // Config overrides apply to resumed sessions too, so they go before 'resume'
+    for config in &options.config_overrides {
+        cmd.arg("-c");
+        cmd.arg(config.as_arg());
+    }
+
     if is_resume {
         // Add 'resume' after --json
         cmd.arg("resume");
@@ -653,22 +1458,28 @@ fn build_codex_command(
         // For new sessions: add other options
         // (--json already added above)
 
-        match options.mode {
-            CodexExecutionMode::FullAuto => {
-                cmd.arg("--full-auto");
-            }
-            CodexExecutionMode::DangerFullAccess => {
-                cmd.arg("--sandbox");
-                cmd.arg("danger-full-access");
-            }
-            CodexExecutionMode::ReadOnly => {
-                // Read-only is default
+        // A profile that defines approval/sandbox or model settings decides them itself,
+        // matching `codex --profile` in a terminal
+        if !profile.as_ref().is_some_and(|p| p.defines_permissions()) {
+            match options.mode {
+                CodexExecutionMode::FullAuto => {
+                    cmd.arg("--full-auto");
+                }
+                CodexExecutionMode::DangerFullAccess => {
+                    cmd.arg("--sandbox");
+                    cmd.arg("danger-full-access");
+                }
+                CodexExecutionMode::ReadOnly => {
+                    // Read-only is default
+                }
             }
         }
 
         if let Some(ref model) = options.model {
-            cmd.arg("--model");
-            cmd.arg(model);
+            if !profile.as_ref().is_some_and(|p| p.model.is_some()) {
+                cmd.arg("--model");
+                cmd.arg(model);
+            }
         }
 
         if let Some(ref schema) = options.output_schema {
@@ -731,26 +1542,41 @@ fn build_wsl_codex_command(
         args.push("--json".to_string());
     }
 
+    let profile = resolve_profile(options)?;
+    if let Some(ref profile) = profile {
+        args.push("--profile".to_string());
+        args.push(profile.name.clone());
+    }
+
+    for config in &options.config_overrides {
+        args.push("-c".to_string());
+        args.push(config.as_arg());
+    }
+
     if is_resume {
         args.push("resume".to_string());
         if let Some(sid) = session_id {
             args.push(sid.to_string());
         }
     } else {
-        match options.mode {
-            CodexExecutionMode::FullAuto => {
-                args.push("--full-auto".to_string());
-            }
-            CodexExecutionMode::DangerFullAccess => {
-                args.push("--sandbox".to_string());
-                args.push("danger-full-access".to_string());
+        if !profile.as_ref().is_some_and(|p| p.defines_permissions()) {
+            match options.mode {
+                CodexExecutionMode::FullAuto => {
+                    args.push("--full-auto".to_string());
+                }
+                CodexExecutionMode::DangerFullAccess => {
+                    args.push("--sandbox".to_string());
+                    args.push("danger-full-access".to_string());
+                }
+                CodexExecutionMode::ReadOnly => {}
             }
-            CodexExecutionMode::ReadOnly => {}
         }
 
         if let Some(ref model) = options.model {
-            args.push("--model".to_string());
-            args.push(model.clone());
+            if !profile.as_ref().is_some_and(|p| p.model.is_some()) {
+                args.push("--model".to_string());
+                args.push(model.clone());
+            }
         }
 
         if let Some(ref schema) = options.output_schema {
@@ -764,7 +1590,7 @@ fn build_wsl_codex_command(
             args.push(wsl_utils::windows_to_wsl_path_with_distro(
                 file,
                 wsl_config.distro.as_deref(),
-            ));
+            )?);
         }
 
         if options.skip_git_repo_check {
@@ -805,7 +1631,7 @@ fn build_wsl_codex_command(
         &args_for_wsl,
         Some(&options.project_path),
         wsl_config.distro.as_deref(),
-    );
+    )?;
 
     // Set API key environment variable if provided
     // Note: This will be passed to WSL environment
@@ -819,7 +1645,8 @@ fn build_wsl_codex_command(
         wsl_utils::windows_to_wsl_path_with_distro(
             &options.project_path,
             wsl_config.distro.as_deref(),
-        ),
+        )
+        .unwrap_or_default(),
         program_for_wsl,
         args_for_wsl
     );
@@ -827,23 +1654,55 @@ fn build_wsl_codex_command(
     Ok((cmd, Some(options.prompt.clone())))
 }
 
+/// Record the Codex thread ID on the running process so cancellation can locate
+/// (and verify) its rollout file
+async fn remember_cli_session_id(
+    app_handle: &AppHandle,
+    backend_session_id: &str,
+    thread_id: &str,
+) {
+    let state = app_handle.state::<CodexProcessState>();
+    let mut processes = state.processes.lock().await;
+    if let Some(handle) = processes.get_mut(backend_session_id) {
+        handle.cli_session_id = Some(thread_id.to_string());
+    }
+}
+
 /// Executes a Codex process and streams output to frontend
+///
+/// `attempt` starts at 1; retries of a transient failure reuse the same channel `session_id`
 async fn execute_codex_process(
     session_id: String,
-    mut cmd: Command,
-    prompt: Option<String>,
-    _project_path: String,
+    mut launch: CodexLaunch,
+    attempt: u32,
     app_handle: AppHandle,
 ) -> Result<(), String> {
+    let (mut cmd, prompt) = build_codex_command(
+        &launch.options,
+        launch.resume_target.is_some(),
+        launch.resume_target.as_deref(),
+    )?;
+    let project_path = launch.options.project_path.clone();
+    let mode = launch.options.mode.clone();
+    let trace_id = launch.options.trace_id.clone();
+
     // 启动流程一开始就发送 session_init，确保即使启动失败也能让前端拿到 session_id 做隔离与错误反馈
-    let init_payload = serde_json::json!({
-        "type": "session_init",
-        "session_id": session_id
-    });
-    if let Err(e) = app_handle.emit("codex-session-init", init_payload) {
-        log::error!("Failed to emit codex-session-init: {}", e);
+    // 重试时前端已绑定该 session_id，无需重复发送
+    if attempt == 1 {
+        let init_payload = serde_json::json!({
+            "type": "session_init",
+            "session_id": session_id,
+            "trace_id": trace_id,
+            "config_overrides": launch.options.config_overrides,
+            "config_warnings": launch.options.config_warnings
+        });
+        if let Err(e) = app_handle.emit("codex-session-init", init_payload) {
+            log::error!("Failed to emit codex-session-init: {}", e);
+        }
+        log::info!("Codex session initialized with ID: {}", session_id);
+    } else {
+        log::info!("Codex session {} starting attempt {}", session_id, attempt);
     }
-    log::info!("Codex session initialized with ID: {}", session_id);
 
     // Setup stdio
     cmd.stdin(Stdio::piped()); // Enable stdin to pass prompt
@@ -851,10 +1710,18 @@ async fn execute_codex_process(
     cmd.stderr(Stdio::piped());
 
     // Fix: Apply platform-specific no-window configuration to hide console
-    // This prevents the terminal window from flashing when starting Codex sessions
-    apply_no_window_async(&mut cmd);
+    // This prevents the terminal window from flashing when starting Codex sessions;
+    // the new process group lets cancellation interrupt Codex before force killing it
+    apply_cancellable_process_async(&mut cmd);
+
+    // 按当前 provider 的限流配置排队启动
+    if let Err(e) = rate_limiter::acquire_cli_launch("codex").await {
+        emit_codex_error(&app_handle, &session_id, "启动 Codex 失败", Some(&e));
+        return Ok(());
+    }
 
     // Spawn process
+    let started_at = std::time::Instant::now();
     let mut child = match cmd.spawn() {
         Ok(child) => child,
         Err(e) => {
@@ -878,7 +1745,20 @@ async fn execute_codex_process(
             return Ok(());
         }
     };
-    log::info!("[Codex] Spawned process with PID: {}", pid);
+    log::info!(
+        "[Codex] Spawned process with PID: {} (trace_id={})",
+        pid,
+        trace_id.as_deref().unwrap_or("-")
+    );
+    if let Some(trace_id) = trace_id.as_deref() {
+        record_trace_event(
+            trace_id,
+            STAGE_PROCESS_SPAWNED,
+            TraceFields::engine("codex")
+                .with_session(Some(&session_id))
+                .with_detail(serde_json::json!({ "pid": pid, "attempt": attempt })),
+        );
+    }
 
     // Windows robustness: assign the process to a Job Object so *all* descendants are cleaned up
     // even if Codex/MCP spawns detached node.exe processes.
@@ -965,6 +1845,7 @@ async fn execute_codex_process(
             child,
             pid,
             job_object,
+            cli_session_id: None,
         };
         processes.insert(session_id.clone(), handle);
 
@@ -985,6 +1866,21 @@ async fn execute_codex_process(
     let stderr_buffer: Arc<Mutex<Vec<String>>> = Arc::new(Mutex::new(Vec::new()));
     let stderr_buffer_for_stderr = stderr_buffer.clone();
     let stderr_buffer_for_complete = stderr_buffer.clone();
+    // 记录是否出现 turn.failed / error 事件，用于任务完成动作判断成败
+    let saw_failure = Arc::new(AtomicBool::new(false));
+    let saw_failure_for_stdout = saw_failure.clone();
+    // 自动重试判断：是否已有 item.* 事件，以及错误事件中的消息
+    let saw_progress = Arc::new(AtomicBool::new(false));
+    let saw_progress_for_stdout = saw_progress.clone();
+    let failure_messages: Arc<Mutex<Vec<String>>> = Arc::new(Mutex::new(Vec::new()));
+    let failure_messages_for_stdout = failure_messages.clone();
+    // 输出卡死检测：stdout 任务记录活动，看门狗决定唤醒或续接
+    let stall_monitor = new_shared_monitor(launch.stall_reconnects);
+    let stall_monitor_for_stdout = stall_monitor.clone();
+    let stream_closed = Arc::new(AtomicBool::new(false));
+    let stream_closed_for_stdout = stream_closed.clone();
+    let stall_recovery: Arc<Mutex<Option<(StallRecovery, String)>>> = Arc::new(Mutex::new(None));
+    let stall_recovery_for_complete = stall_recovery.clone();
 
     // 🔧 FIX: Use channels to track stdout/stderr closure for timeout detection
     let (done_tx, done_rx) = tokio::sync::oneshot::channel();
@@ -995,11 +1891,28 @@ async fn execute_codex_process(
     tokio::spawn(async move {
         let mut reader = BufReader::new(stdout).lines();
         let mut done_tx = Some(done_tx);
-        while let Ok(Some(line)) = reader.next_line().await {
+        let mut tool_timer = LiveToolTimer::default();
+        while let Ok(Some(mut line)) = reader.next_line().await {
             if !line.trim().is_empty() {
                 saw_stdout.store(true, Ordering::Relaxed);
                 // Use trace level to avoid flooding logs in debug mode
                 log::trace!("Codex output: {}", line);
+
+                let mut event = serde_json::from_str::<serde_json::Value>(&line).ok();
+                record_activity(
+                    &stall_monitor_for_stdout,
+                    event
+                        .as_ref()
+                        .map(classify_codex_event)
+                        .unwrap_or(StreamActivity::Output),
+                );
+                // item.completed 事件附上工具耗时
+                if let Some(event) = event.as_mut() {
+                    if tool_timer.observe_codex_event(event) {
+                        line = event.to_string();
+                    }
+                }
+
                 // Emit to session-specific channel first (for multi-tab isolation)
                 if let Err(e) =
                     app_handle_stdout.emit(&format!("codex-output:{}", session_id_stdout), &line)
@@ -1011,15 +1924,32 @@ async fn execute_codex_process(
                     log::error!("Failed to emit codex-output (global): {}", e);
                 }
 
+                let event_type = event.as_ref().and_then(|v| {
+                    v.get("type")
+                        .and_then(|t| t.as_str())
+                        .map(|s| s.to_string())
+                });
+                if event_type.as_deref() == Some("thread.started") {
+                    if let Some(thread_id) = event.as_ref().and_then(|v| v["thread_id"].as_str()) {
+                        remember_cli_session_id(&app_handle_stdout, &session_id_stdout, thread_id)
+                            .await;
+                    }
+                }
+                if matches!(event_type.as_deref(), Some("turn.failed" | "error")) {
+                    saw_failure_for_stdout.store(true, Ordering::Relaxed);
+                    if let Some(message) = event.as_ref().and_then(codex_event_error_message) {
+                        failure_messages_for_stdout.lock().await.push(message);
+                    }
+                } else if event_type
+                    .as_deref()
+                    .is_some_and(|t| t.starts_with("item."))
+                {
+                    saw_progress_for_stdout.store(true, Ordering::Relaxed);
+                }
+
                 // Detect turn completion to trigger backend cleanup even if stdout never closes.
                 if done_tx.is_some() {
-                    let is_done_event = serde_json::from_str::<serde_json::Value>(&line)
-                        .ok()
-                        .and_then(|v| {
-                            v.get("type")
-                                .and_then(|t| t.as_str())
-                                .map(|s| s.to_string())
-                        })
+                    let is_done_event = event_type
                         .map(|t| matches!(t.as_str(), "turn.completed" | "turn.failed" | "error"))
                         .unwrap_or(false);
 
@@ -1036,6 +1966,7 @@ async fn execute_codex_process(
             }
         }
         log::info!("[Codex] Stdout closed for session: {}", session_id_stdout);
+        stream_closed_for_stdout.store(true, Ordering::Relaxed);
         // Fallback: stdout closed, treat as completion if not already signaled.
         if let Some(tx) = done_tx.take() {
             let _ = tx.send(());
@@ -1061,6 +1992,57 @@ async fn execute_codex_process(
         let _ = stderr_done_tx.send(());
     });
 
+    // Watchdog: detect a stalled stream while the process is still alive, then interrupt it
+    // so the completion task can resume the thread
+    let app_handle_watchdog = app_handle.clone();
+    let session_id_watchdog = session_id.clone();
+    let trace_id_watchdog = trace_id.clone();
+    tokio::spawn(async move {
+        let Some(recovery) = watch_stream(
+            &app_handle_watchdog,
+            "codex",
+            &session_id_watchdog,
+            trace_id_watchdog.as_deref(),
+            &stall_monitor,
+            &stream_closed,
+        )
+        .await
+        else {
+            return;
+        };
+
+        // Resuming needs the thread ID; a process that already exited will close stdout by itself
+        let resume_target = {
+            let state = app_handle_watchdog.state::<CodexProcessState>();
+            let mut processes = state.processes.lock().await;
+            let Some(handle) = processes.get_mut(&session_id_watchdog) else {
+                return;
+            };
+            if !matches!(handle.child.try_wait(), Ok(None)) {
+                return;
+            }
+            handle.cli_session_id.clone()
+        };
+        let Some(resume_target) = resume_target else {
+            log::warn!(
+                "[Codex] Session {} stalled before reporting a thread ID, cannot resume",
+                session_id_watchdog
+            );
+            emit_stalled(
+                &app_handle_watchdog,
+                "codex",
+                &session_id_watchdog,
+                std::time::Duration::from_secs(recovery.idle_secs),
+                None,
+                trace_id_watchdog.as_deref(),
+            );
+            return;
+        };
+
+        *stall_recovery.lock().await = Some((recovery, resume_target));
+        interrupt_stalled_process(pid, &stream_closed).await;
+    });
+
     // Spawn task to wait for process completion
     // 🔧 FIX: Only wait for stdout to close, then send completion event immediately
     // stderr may continue outputting logs (MCP servers, etc.) for a long time
@@ -1074,8 +2056,33 @@ async fn execute_codex_process(
         let _ = done_rx.await;
         log::info!("[Codex] Completion signaled for session: {}", session_id_complete);
 
+        let success =
+            saw_stdout_for_complete.load(Ordering::Relaxed) && !saw_failure.load(Ordering::Relaxed);
+        // 看门狗因输出卡死中断了进程：不发送完成事件，清理进程后续接
+        let stall_recovery = stall_recovery_for_complete.lock().await.take();
+
+        // 瞬时错误导致的快速失败：不发送完成事件，清理进程后按配置重试
+        let retry_plan = if success || stall_recovery.is_some() {
+            None
+        } else {
+            let mut messages = failure_messages.lock().await.clone();
+            messages.extend(stderr_buffer_for_complete.lock().await.iter().cloned());
+            plan_retry(
+                &load_retry_config(),
+                &FailedAttempt {
+                    attempt,
+                    elapsed: started_at.elapsed(),
+                    made_progress: saw_progress.load(Ordering::Relaxed),
+                    messages,
+                },
+            )
+        };
+
         // 若 stdout 完全无输出但 stderr 有内容，补发一次可诊断错误事件，避免前端表现为“无反应”
-        if !saw_stdout_for_complete.load(Ordering::Relaxed) {
+        if retry_plan.is_none()
+            && stall_recovery.is_none()
+            && !saw_stdout_for_complete.load(Ordering::Relaxed)
+        {
             let buf = stderr_buffer_for_complete.lock().await;
             if !buf.is_empty() {
                 let detail = buf.join("\n");
@@ -1091,17 +2098,20 @@ async fn execute_codex_process(
         // 🔧 CRITICAL FIX: Emit completion event immediately after stdout closes
         // Don't wait for process exit or stderr - those can take a long time
         // stdout closing means all JSONL events have been sent, session is effectively complete
-        log::info!(
-            "[Codex] Sending completion event for session: {}",
-            session_id_complete
-        );
-        if let Err(e) =
-            app_handle_complete.emit(&format!("codex-complete:{}", session_id_complete), true)
-        {
-            log::error!("Failed to emit codex-complete (session-specific): {}", e);
-        }
-        if let Err(e) = app_handle_complete.emit("codex-complete", true) {
-            log::error!("Failed to emit codex-complete (global): {}", e);
+        if retry_plan.is_none() && stall_recovery.is_none() {
+            // Release the session lock and sleep inhibition before announcing completion so a
+            // follow-up resume is not refused
+            launch.session_lock = None;
+            launch.power_hold = None;
+            finish_codex_run(
+                &app_handle_complete,
+                &session_id_complete,
+                project_path.clone(),
+                &mode,
+                success,
+                started_at,
+                trace_id.as_deref(),
+            );
         }
 
         // Continue waiting for process exit in background (with timeout protection)
@@ -1110,6 +2120,7 @@ async fn execute_codex_process(
         // let it flush session files, then force-kill to prevent orphan node.exe accumulation.
         let timeout_duration = tokio::time::Duration::from_secs(3);
         let start_time = tokio::time::Instant::now();
+        let mut cancelled = false;
 
         loop {
             let mut processes = state.processes.lock().await;
@@ -1185,14 +2196,197 @@ async fn execute_codex_process(
                     "[Codex] Process {} was removed (cancelled)",
                     session_id_complete
                 );
+                cancelled = true;
                 break;
             }
         }
+
+        if let Some((recovery, resume_target)) = stall_recovery {
+            if cancelled {
+                log::info!(
+                    "[Codex] Stall recovery cancelled for session: {}",
+                    session_id_complete
+                );
+                drop(launch);
+                finish_codex_run(
+                    &app_handle_complete,
+                    &session_id_complete,
+                    project_path,
+                    &mode,
+                    false,
+                    started_at,
+                    trace_id.as_deref(),
+                );
+                return;
+            }
+            announce_reconnect(
+                &app_handle_complete,
+                "codex",
+                &session_id_complete,
+                &recovery,
+                &resume_target,
+                trace_id.as_deref(),
+            );
+            launch.resume_target = Some(resume_target);
+            launch.options.prompt = CONTINUE_PROMPT.to_string();
+            launch.stall_reconnects = recovery.reconnect;
+            if let Err(e) = respawn_codex_process(
+                session_id_complete.clone(),
+                launch,
+                attempt + 1,
+                app_handle_complete.clone(),
+            )
+            .await
+            {
+                emit_codex_error(
+                    &app_handle_complete,
+                    &session_id_complete,
+                    "Codex 续接启动失败",
+                    Some(&e),
+                );
+                finish_codex_run(
+                    &app_handle_complete,
+                    &session_id_complete,
+                    project_path,
+                    &mode,
+                    false,
+                    started_at,
+                    trace_id.as_deref(),
+                );
+            }
+            return;
+        }
+
+        let Some(plan) = retry_plan else {
+            return;
+        };
+        if cancelled
+            || !wait_before_retry(
+                &app_handle_complete,
+                "codex",
+                &session_id_complete,
+                &plan,
+                trace_id.as_deref(),
+            )
+            .await
+        {
+            log::info!(
+                "[Codex] Retry cancelled for session: {}",
+                session_id_complete
+            );
+            drop(launch);
+            finish_codex_run(
+                &app_handle_complete,
+                &session_id_complete,
+                project_path,
+                &mode,
+                false,
+                started_at,
+                trace_id.as_deref(),
+            );
+            return;
+        }
+        if let Err(e) = respawn_codex_process(
+            session_id_complete.clone(),
+            launch,
+            plan.next_attempt,
+            app_handle_complete.clone(),
+        )
+        .await
+        {
+            emit_codex_error(
+                &app_handle_complete,
+                &session_id_complete,
+                "Codex 重试启动失败",
+                Some(&e),
+            );
+            finish_codex_run(
+                &app_handle_complete,
+                &session_id_complete,
+                project_path,
+                &mode,
+                false,
+                started_at,
+                trace_id.as_deref(),
+            );
+        }
     });
 
     Ok(())
 }
 
+/// Starts the next attempt of a run from inside the previous attempt's completion task
+fn respawn_codex_process(
+    session_id: String,
+    launch: CodexLaunch,
+    attempt: u32,
+    app_handle: AppHandle,
+) -> Pin<Box<dyn Future<Output = Result<(), String>> + Send>> {
+    Box::pin(execute_codex_process(
+        session_id, launch, attempt, app_handle,
+    ))
+}
+
+/// Emits the completion events of a run and dispatches the task completion actions
+fn finish_codex_run(
+    app_handle: &AppHandle,
+    session_id: &str,
+    project_path: String,
+    mode: &CodexExecutionMode,
+    success: bool,
+    started_at: std::time::Instant,
+    trace_id: Option<&str>,
+) {
+    log::info!(
+        "[Codex] Sending completion event for session: {}",
+        session_id
+    );
+    // 进程句柄已被移除（如取消后）时拿不到 thread ID，前端按引擎刷新整个列表
+    let thread_id = app_handle
+        .state::<CodexProcessState>()
+        .processes
+        .try_lock()
+        .ok()
+        .and_then(|processes| processes.get(session_id)?.cli_session_id.clone());
+    invalidate_session(
+        app_handle,
+        "codex",
+        thread_id.as_deref(),
+        Some(&project_path),
+    );
+
+    if let Err(e) = app_handle.emit(&format!("codex-complete:{}", session_id), true) {
+        log::error!("Failed to emit codex-complete (session-specific): {}", e);
+    }
+    if let Err(e) = app_handle.emit("codex-complete", true) {
+        log::error!("Failed to emit codex-complete (global): {}", e);
+    }
+
+    crate::commands::task_actions::dispatch_task_completion(
+        app_handle,
+        crate::commands::task_actions::TaskCompletionEvent {
+            engine: "codex".to_string(),
+            project_path,
+            session_id: Some(session_id.to_string()),
+            cli_session_id: thread_id,
+            mode: Some(mode.as_str().to_string()),
+            success,
+            duration_ms: started_at.elapsed().as_millis() as u64,
+            trace_id: trace_id.map(|t| t.to_string()),
+        },
+    );
+}
+
+/// Extracts the message of a `turn.failed` / `error` event
+fn codex_event_error_message(event: &serde_json::Value) -> Option<String> {
+    event
+        .get("error")
+        .and_then(|e| e.get("message"))
+        .or_else(|| event.get("message"))
+        .and_then(|m| m.as_str())
+        .map(|m| m.to_string())
+}
+
 fn emit_codex_error(app_handle: &AppHandle, session_id: &str, message: &str, detail: Option<&str>) {
     let payload = serde_json::json!({
         "session_id": session_id,
@@ -1207,3 +2401,328 @@ fn emit_codex_error(app_handle: &AppHandle, session_id: &str, message: &str, det
     let _ = app_handle.emit(&format!("codex-error:{}", session_id), &payload_str);
     let _ = app_handle.emit("codex-error", &payload_str);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn user_line(text: &str) -> String {
+        serde_json::json!({
+            "timestamp": "2025-11-23T10:00:01Z",
+            "type": "response_item",
+            "payload": {
+                "type": "message",
+                "role": "user",
+                "content": [{ "type": "input_text", "text": text }]
+            }
+        })
+        .to_string()
+    }
+
+    fn write_session(messages: &[&str]) -> tempfile::NamedTempFile {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        let meta = serde_json::json!({
+            "timestamp": "2025-11-23T10:00:00Z",
+            "type": "session_meta",
+            "payload": {
+                "id": "test-session",
+                "timestamp": "2025-11-23T10:00:00Z",
+                "cwd": "/tmp/project"
+            }
+        });
+        writeln!(file, "{}", meta).unwrap();
+        for message in messages {
+            writeln!(file, "{}", user_line(message)).unwrap();
+        }
+        file
+    }
+
+    const AGENTS_MESSAGE: &str =
+        "# AGENTS.md instructions for /tmp/project\n\n<INSTRUCTIONS>\nUse pnpm.\n</INSTRUCTIONS>";
+    const ENV_MESSAGE: &str =
+        "<environment_context>\n  <cwd>/tmp/project</cwd>\n</environment_context>";
+
+    #[test]
+    fn detects_injected_context_messages_by_structure() {
+        assert!(is_codex_context_message(AGENTS_MESSAGE));
+        assert!(is_codex_context_message(ENV_MESSAGE));
+        assert!(is_codex_context_message(
+            "<user_instructions>\nBe brief.\n</user_instructions>"
+        ));
+        assert!(!is_codex_context_message(
+            "Please update AGENTS.md to mention the new test command"
+        ));
+        assert!(!is_codex_context_message(
+            "# AGENTS.md instructions for the team\nshould be shorter"
+        ));
+    }
+
+    #[test]
+    fn user_message_mentioning_agents_md_is_first_message() {
+        let text = "Please update AGENTS.md to mention the new test command";
+        let file = write_session(&[AGENTS_MESSAGE, ENV_MESSAGE, text]);
+
+        let session = parse_codex_session_file(file.path()).unwrap();
+        assert_eq!(session.first_message.as_deref(), Some(text));
+    }
+
+    #[test]
+    fn context_messages_can_be_included_as_preview() {
+        let file = write_session(&[ENV_MESSAGE, "hello"]);
+
+        let session = parse_codex_session_file_with_options(file.path(), true).unwrap();
+        assert_eq!(session.first_message.as_deref(), Some(ENV_MESSAGE));
+        assert_eq!(session.first_message_source, FirstMessageSource::User);
+    }
+
+    fn event_line(event_type: &str, payload: serde_json::Value) -> String {
+        serde_json::json!({
+            "timestamp": "2025-11-23T10:00:02Z",
+            "type": event_type,
+            "payload": payload
+        })
+        .to_string()
+    }
+
+    /// Writes a session named like the CLI's rollout files, with extra session_meta fields
+    fn write_rollout(
+        dir: &std::path::Path,
+        meta_extra: serde_json::Value,
+        lines: &[String],
+    ) -> std::path::PathBuf {
+        let mut meta = serde_json::json!({
+            "id": "test-session",
+            "timestamp": "2025-11-23T10:00:00Z",
+            "cwd": "/tmp/project"
+        });
+        if let (Some(meta), Some(extra)) = (meta.as_object_mut(), meta_extra.as_object()) {
+            meta.extend(extra.clone());
+        }
+
+        let path = dir.join("rollout-2025-11-23T10-00-00-test-session.jsonl");
+        let mut file = std::fs::File::create(&path).unwrap();
+        writeln!(file, "{}", event_line("session_meta", meta)).unwrap();
+        for line in lines {
+            writeln!(file, "{}", line).unwrap();
+        }
+        path
+    }
+
+    #[test]
+    fn falls_back_to_first_agent_message() {
+        let dir = tempfile::tempdir().unwrap();
+        let reply = format!("  I'll apply the patch.\n{}", "x".repeat(100));
+        let path = write_rollout(
+            dir.path(),
+            serde_json::json!({}),
+            &[
+                user_line(ENV_MESSAGE),
+                event_line(
+                    "response_item",
+                    serde_json::json!({"type": "function_call", "name": "apply_patch"}),
+                ),
+                event_line(
+                    "event_msg",
+                    serde_json::json!({"type": "agent_message", "message": reply}),
+                ),
+            ],
+        );
+
+        let session = parse_codex_session_file(&path).unwrap();
+        let first_message = session.first_message.unwrap();
+        assert_eq!(session.first_message_source, FirstMessageSource::Assistant);
+        assert!(first_message.starts_with("[AI] I'll apply the patch. xxx"));
+        assert!(first_message.ends_with('…'));
+        assert_eq!(first_message.chars().count(), "[AI] ".len() + 80 + 1);
+    }
+
+    #[test]
+    fn falls_back_to_session_meta_then_placeholder() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = write_rollout(
+            dir.path(),
+            serde_json::json!({"originator": "codex_exec", "instructions": "\nReview the diff\nonly"}),
+            &[user_line(ENV_MESSAGE)],
+        );
+        let session = parse_codex_session_file(&path).unwrap();
+        assert_eq!(
+            session.first_message_source,
+            FirstMessageSource::SessionMeta
+        );
+        assert_eq!(
+            session.first_message.as_deref(),
+            Some("[codex_exec] Review the diff")
+        );
+
+        let path = write_rollout(dir.path(), serde_json::json!({}), &[user_line(ENV_MESSAGE)]);
+        let session = parse_codex_session_file(&path).unwrap();
+        assert_eq!(
+            session.first_message_source,
+            FirstMessageSource::Placeholder
+        );
+        assert_eq!(
+            session.first_message.as_deref(),
+            Some("（无用户消息）2025-11-23")
+        );
+    }
+
+    #[test]
+    fn user_message_beyond_scan_limit_is_not_read() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut lines = vec![user_line(ENV_MESSAGE); FIRST_MESSAGE_SCAN_LINES];
+        lines.push(user_line("too late"));
+        lines.push(
+            event_line("event_msg", serde_json::json!({"type": "task_complete"}))
+                .replace("2025-11-23T10:00:02Z", "2025-11-23T12:00:00Z"),
+        );
+        let path = write_rollout(dir.path(), serde_json::json!({}), &lines);
+
+        let session = parse_codex_session_file(&path).unwrap();
+        assert_eq!(
+            session.first_message_source,
+            FirstMessageSource::Placeholder
+        );
+        assert_eq!(
+            session.last_message_timestamp.as_deref(),
+            Some("2025-11-23T12:00:00Z")
+        );
+    }
+
+    #[test]
+    fn listing_is_paginated_and_served_from_index() {
+        let dir = tempfile::tempdir().unwrap();
+        for (day, id) in [("21", "a"), ("22", "b"), ("23", "c")] {
+            let day_dir = dir.path().join("2025").join("11").join(day);
+            std::fs::create_dir_all(&day_dir).unwrap();
+            let path = day_dir.join(format!("rollout-2025-11-{}T10-00-00-{}.jsonl", day, id));
+            let meta = serde_json::json!({
+                "id": id,
+                "timestamp": format!("2025-11-{}T10:00:00Z", day),
+                "cwd": "/tmp/project"
+            });
+            let content = format!(
+                "{}\n{}\n",
+                event_line("session_meta", meta),
+                user_line(&format!("prompt {}", id))
+            );
+            std::fs::write(&path, content).unwrap();
+        }
+        let ids = |sessions: Vec<CodexSession>| -> Vec<String> {
+            sessions.into_iter().map(|s| s.id).collect()
+        };
+
+        let list =
+            |query: &CodexSessionQuery| list_sessions_in_dir(dir.path(), query, false).unwrap();
+
+        let query = CodexSessionQuery {
+            page_size: 2,
+            ..Default::default()
+        };
+        assert_eq!(ids(list(&query)), ["c", "b"]);
+        let query = CodexSessionQuery {
+            page: 1,
+            page_size: 2,
+            ..Default::default()
+        };
+        assert_eq!(ids(list(&query)), ["a"]);
+        let query = CodexSessionQuery {
+            date_from: Some("2025-11-22".to_string()),
+            date_to: Some("2025-11-22".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(ids(list(&query)), ["b"]);
+        let query = CodexSessionQuery {
+            date_from: Some("22/11/2025".to_string()),
+            ..Default::default()
+        };
+        assert!(list_sessions_in_dir(dir.path(), &query, false).is_err());
+
+        // Unchanged files are answered from the index without re-reading them
+        let index_path = dir.path().join(SESSION_INDEX_FILE);
+        let mut index: CodexSessionIndex = load_json_config(&index_path).unwrap();
+        assert_eq!(index.entries.len(), 3);
+        index
+            .entries
+            .get_mut("2025/11/21/rollout-2025-11-21T10-00-00-a.jsonl")
+            .unwrap()
+            .session
+            .as_mut()
+            .unwrap()
+            .first_message = Some("cached".to_string());
+        save_json_config(&index, &index_path).unwrap();
+
+        let sessions = list(&CodexSessionQuery::default());
+        assert_eq!(sessions[2].first_message.as_deref(), Some("cached"));
+
+        // Invalidating a session drops its entry so the next listing re-reads the file
+        assert!(remove_index_entries(dir.path(), "a").unwrap());
+        assert!(!remove_index_entries(dir.path(), "a").unwrap());
+        let sessions = list(&CodexSessionQuery::default());
+        assert_ne!(sessions[2].first_message.as_deref(), Some("cached"));
+
+        // A deleted file drops out of the index
+        std::fs::remove_file(
+            dir.path()
+                .join("2025/11/23/rollout-2025-11-23T10-00-00-c.jsonl"),
+        )
+        .unwrap();
+        let sessions = list(&CodexSessionQuery::default());
+        assert_eq!(ids(sessions), ["b", "a"]);
+        let index: CodexSessionIndex = load_json_config(&index_path).unwrap();
+        assert_eq!(index.entries.len(), 2);
+    }
+
+    #[test]
+    fn project_filter_only_reads_session_headers() {
+        let dir = tempfile::tempdir().unwrap();
+        let day_dir = dir.path().join("2025").join("11").join("23");
+        std::fs::create_dir_all(&day_dir).unwrap();
+        for (minute, id, cwd) in [
+            ("01", "a", "/tmp/alpha"),
+            ("02", "b", "/tmp/beta"),
+            ("03", "c", "/tmp/alpha/"),
+        ] {
+            let path = day_dir.join(format!("rollout-2025-11-23T10-{}-00-{}.jsonl", minute, id));
+            let meta = serde_json::json!({
+                "id": id,
+                "timestamp": format!("2025-11-23T10:{}:00Z", minute),
+                "cwd": cwd
+            });
+            let content = format!(
+                "{}\n{}\n",
+                event_line("session_meta", meta),
+                user_line(&format!("prompt {}", id))
+            );
+            std::fs::write(&path, content).unwrap();
+        }
+
+        let query = CodexSessionQuery {
+            page_size: 1,
+            project_path_filter: Some("/TMP/Alpha".to_string()),
+            ..Default::default()
+        };
+        let sessions = list_sessions_in_dir(dir.path(), &query, false).unwrap();
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].id, "c");
+
+        // Files outside the page only get a header entry, not a full parse
+        let index: CodexSessionIndex =
+            load_json_config(dir.path().join(SESSION_INDEX_FILE)).unwrap();
+        assert_eq!(index.entries.len(), 3);
+        let entry = |id: &str| index.entries.values().find(|e| e.id == id).unwrap();
+        assert!(entry("c").session.is_some());
+        assert!(entry("a").session.is_none());
+        assert_eq!(entry("b").cwd, "/tmp/beta");
+
+        let query = CodexSessionQuery {
+            page: 1,
+            page_size: 1,
+            project_path_filter: Some("/tmp/alpha".to_string()),
+            ..Default::default()
+        };
+        let sessions = list_sessions_in_dir(dir.path(), &query, false).unwrap();
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].id, "a");
+    }
+}
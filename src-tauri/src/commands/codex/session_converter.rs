@@ -1,23 +1,50 @@
 use once_cell::sync::Lazy;
 /**
- * Claude ↔ Codex Session 转换模块
+ * Claude ↔ Codex / Gemini Session 转换模块
  *
- * 实现 Claude 与 Codex 引擎之间的 Session 双向转换功能。
+ * 实现 Claude 与 Codex、Gemini 引擎之间的 Session 双向转换功能。
  * 支持：
  * - Claude → Codex：将 Claude session 转换为 Codex 可执行的 session
  * - Codex → Claude：将 Codex session 转换为 Claude 可加载的历史记录
+ * - Gemini ↔ Claude：chats/session-*.json 与 Claude JSONL 互转（工具调用转为 tool_use / tool_result）
  *
  * 核心特性：
- * - 自动识别引擎类型（UUID vs rollout-前缀）
+ * - 自动识别引擎类型（按 Codex sessions、Claude projects、Gemini chats 目录依次查找）
  * - 生成新的 Session ID（避免冲突）
  * - 元数据中记录转换来源（可追溯）
  * - 工具调用名称映射（bash ↔ shell_command 等）
  * - 仅支持已完成的 Session 转换
+ * - 流式转换（边读边写），支持进度事件与取消
+ * - 试运行（完整转换但不写出文件）与按项目批量转换（跳过已转换的会话）
+ * - 仅导出推理块（Claude thinking / Codex reasoning），便于单独研究模型的思考过程
+ * - Claude / Codex 会话按时间顺序导出为 Markdown（GFM），便于分享和归档对话记录
  */
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use std::collections::HashMap;
-use std::io::{BufRead, BufReader, Write};
+use std::collections::{HashMap, HashSet};
+use std::io::{BufWriter, Write};
+use std::ops::ControlFlow;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Emitter};
+
+use super::git_ops::extract_codex_prompts_from_file;
+use crate::commands::gemini::config::hash_project_path;
+use crate::commands::gemini::git_ops::get_gemini_sessions_dir;
+use crate::commands::input_validation::resolve_export_target;
+use crate::commands::messages::{t, t_with, MessageKey};
+use crate::commands::prompt_tracker::{extract_prompts_from_jsonl, PromptRecord};
+use crate::commands::session_blobs::{
+    attachments_dir, externalize_session_file, inline_blobs, BLOB_SCHEME, DEFAULT_BLOB_THRESHOLD,
+};
+use crate::commands::session_utils::{
+    claude_project_dir, claude_session_path, find_session_path, resolve_session_file,
+};
+use crate::utils::jsonl_reader::{
+    ensure_loadable_in_memory, for_each_line, for_each_raw_line, parse_json_line, CorruptedLine,
+};
 
 // ================================
 // 数据结构定义
@@ -27,7 +54,7 @@ use std::io::{BufRead, BufReader, Write};
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ConversionSource {
-    /// 源引擎类型: "claude" | "codex"
+    /// 源引擎类型: "claude" | "codex" | "gemini"
     pub engine: String,
     /// 源 Session ID
     pub session_id: String,
@@ -55,6 +82,12 @@ pub struct ConversionResult {
     pub target_path: String,
     /// 错误信息 (如果失败)
     pub error: Option<String>,
+    /// 是否为试运行（目标文件未写出）
+    #[serde(default)]
+    pub dry_run: bool,
+    /// 源会话中跳过（或有损解码后保留）的损坏行
+    #[serde(default)]
+    pub corrupted_lines: Vec<CorruptedLine>,
 }
 
 // ================================
@@ -269,6 +302,40 @@ pub static CLAUDE_TO_CODEX_TOOL_MAP: Lazy<HashMap<&'static str, &'static str>> =
     m
 });
 
+/// Gemini → Claude 工具名称映射
+pub static GEMINI_TO_CLAUDE_TOOL_MAP: Lazy<HashMap<&'static str, &'static str>> = Lazy::new(|| {
+    let mut m = HashMap::new();
+    m.insert("run_shell_command", "bash");
+    m.insert("read_file", "read");
+    m.insert("read_many_files", "read");
+    m.insert("write_file", "write");
+    m.insert("replace", "edit");
+    m.insert("glob", "glob");
+    m.insert("search_file_content", "grep");
+    m.insert("list_directory", "ls");
+    m.insert("web_fetch", "webfetch");
+    m.insert("google_web_search", "websearch");
+    m.insert("write_todos", "todowrite");
+    m
+});
+
+/// Claude → Gemini 工具名称映射
+pub static CLAUDE_TO_GEMINI_TOOL_MAP: Lazy<HashMap<&'static str, &'static str>> = Lazy::new(|| {
+    let mut m = HashMap::new();
+    m.insert("bash", "run_shell_command");
+    m.insert("read", "read_file");
+    m.insert("write", "write_file");
+    m.insert("edit", "replace");
+    m.insert("multiedit", "replace");
+    m.insert("glob", "glob");
+    m.insert("grep", "search_file_content");
+    m.insert("ls", "list_directory");
+    m.insert("webfetch", "web_fetch");
+    m.insert("websearch", "google_web_search");
+    m.insert("todowrite", "write_todos");
+    m
+});
+
 /// 映射 Codex 工具名到 Claude 工具名
 /// MCP 工具 (mcp__ 前缀) 不进行映射
 pub fn map_codex_to_claude_tool(codex_name: &str) -> String {
@@ -295,10 +362,100 @@ pub fn map_claude_to_codex_tool(claude_name: &str) -> String {
         .unwrap_or_else(|| claude_name.to_string())
 }
 
+/// 映射 Gemini 工具名到 Claude 工具名
+/// MCP 工具 (mcp__ 前缀) 不进行映射
+pub fn map_gemini_to_claude_tool(gemini_name: &str) -> String {
+    if gemini_name.starts_with("mcp__") {
+        return gemini_name.to_string();
+    }
+    let lower = gemini_name.to_lowercase();
+    GEMINI_TO_CLAUDE_TOOL_MAP
+        .get(lower.as_str())
+        .map(|s| s.to_string())
+        .unwrap_or_else(|| gemini_name.to_string())
+}
+
+/// 映射 Claude 工具名到 Gemini 工具名
+/// MCP 工具 (mcp__ 前缀) 不进行映射
+pub fn map_claude_to_gemini_tool(claude_name: &str) -> String {
+    if claude_name.starts_with("mcp__") {
+        return claude_name.to_string();
+    }
+    let lower = claude_name.to_lowercase();
+    CLAUDE_TO_GEMINI_TOOL_MAP
+        .get(lower.as_str())
+        .map(|s| s.to_string())
+        .unwrap_or_else(|| claude_name.to_string())
+}
+
 // ================================
 // Claude → Codex 转换器
 // ================================
 
+/// 解析 content（支持字符串或数组格式）为 ClaudeContentBlock 数组
+pub(crate) fn parse_claude_content_blocks(content: &Option<Value>) -> Vec<ClaudeContentBlock> {
+    let mut blocks = Vec::new();
+
+    if let Some(content_value) = content {
+        if let Some(text) = content_value.as_str() {
+            // 字符串格式 - 直接转为文本块
+            blocks.push(ClaudeContentBlock::Text {
+                text: text.to_string(),
+            });
+        } else if let Some(array) = content_value.as_array() {
+            // 数组格式 - 解析每个块
+            for item in array {
+                if let Some(block_type) = item.get("type").and_then(|t| t.as_str()) {
+                    match block_type {
+                        "text" => {
+                            if let Some(text) = item.get("text").and_then(|t| t.as_str()) {
+                                blocks.push(ClaudeContentBlock::Text {
+                                    text: text.to_string(),
+                                });
+                            }
+                        }
+                        "tool_use" => {
+                            if let (Some(id), Some(name), Some(input)) = (
+                                item.get("id").and_then(|i| i.as_str()),
+                                item.get("name").and_then(|n| n.as_str()),
+                                item.get("input"),
+                            ) {
+                                blocks.push(ClaudeContentBlock::ToolUse {
+                                    id: id.to_string(),
+                                    name: name.to_string(),
+                                    input: input.clone(),
+                                });
+                            }
+                        }
+                        "tool_result" => {
+                            if let (Some(tool_use_id), Some(content)) = (
+                                item.get("tool_use_id").and_then(|t| t.as_str()),
+                                item.get("content"),
+                            ) {
+                                blocks.push(ClaudeContentBlock::ToolResult {
+                                    tool_use_id: tool_use_id.to_string(),
+                                    content: content.clone(),
+                                    is_error: item.get("is_error").and_then(|e| e.as_bool()),
+                                });
+                            }
+                        }
+                        "thinking" => {
+                            if let Some(thinking) = item.get("thinking").and_then(|t| t.as_str()) {
+                                blocks.push(ClaudeContentBlock::Thinking {
+                                    thinking: thinking.to_string(),
+                                });
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+    }
+
+    blocks
+}
+
 /// Claude Session → Codex Session 转换器
 pub struct ClaudeToCodexConverter {
     source_session_id: String,
@@ -310,13 +467,8 @@ pub struct ClaudeToCodexConverter {
 
 impl ClaudeToCodexConverter {
     pub fn new(source_session_id: String, project_id: String, project_path: String) -> Self {
-        let uuid = uuid::Uuid::new_v4().to_string();
-        let new_session_uuid = uuid.clone();
-
-        // 生成带时间戳的文件名：rollout-2025-12-01T09-26-15-{uuid}
-        let now = chrono::Utc::now();
-        let timestamp = now.format("%Y-%m-%dT%H-%M-%S").to_string();
-        let new_session_filename = format!("rollout-{}-{}", timestamp, uuid);
+        let new_session_uuid = uuid::Uuid::new_v4().to_string();
+        let new_session_filename = codex_session_filename(&new_session_uuid);
 
         Self {
             source_session_id,
@@ -327,184 +479,170 @@ impl ClaudeToCodexConverter {
         }
     }
 
-    /// 解析 content（支持字符串或数组格式）为 ClaudeContentBlock 数组
-    fn parse_content_blocks(&self, content: &Option<Value>) -> Vec<ClaudeContentBlock> {
-        let mut blocks = Vec::new();
-
-        if let Some(content_value) = content {
-            if let Some(text) = content_value.as_str() {
-                // 字符串格式 - 直接转为文本块
-                blocks.push(ClaudeContentBlock::Text {
-                    text: text.to_string(),
-                });
-            } else if let Some(array) = content_value.as_array() {
-                // 数组格式 - 解析每个块
-                for item in array {
-                    if let Some(block_type) = item.get("type").and_then(|t| t.as_str()) {
-                        match block_type {
-                            "text" => {
-                                if let Some(text) = item.get("text").and_then(|t| t.as_str()) {
-                                    blocks.push(ClaudeContentBlock::Text {
-                                        text: text.to_string(),
-                                    });
-                                }
-                            }
-                            "tool_use" => {
-                                if let (Some(id), Some(name), Some(input)) = (
-                                    item.get("id").and_then(|i| i.as_str()),
-                                    item.get("name").and_then(|n| n.as_str()),
-                                    item.get("input"),
-                                ) {
-                                    blocks.push(ClaudeContentBlock::ToolUse {
-                                        id: id.to_string(),
-                                        name: name.to_string(),
-                                        input: input.clone(),
-                                    });
-                                }
-                            }
-                            "tool_result" => {
-                                if let (Some(tool_use_id), Some(content)) = (
-                                    item.get("tool_use_id").and_then(|t| t.as_str()),
-                                    item.get("content"),
-                                ) {
-                                    blocks.push(ClaudeContentBlock::ToolResult {
-                                        tool_use_id: tool_use_id.to_string(),
-                                        content: content.clone(),
-                                        is_error: item.get("is_error").and_then(|e| e.as_bool()),
-                                    });
-                                }
-                            }
-                            "thinking" => {
-                                if let Some(thinking) =
-                                    item.get("thinking").and_then(|t| t.as_str())
-                                {
-                                    blocks.push(ClaudeContentBlock::Thinking {
-                                        thinking: thinking.to_string(),
-                                    });
-                                }
-                            }
-                            _ => {}
-                        }
-                    }
-                }
-            }
-        }
-
-        blocks
-    }
-
-    pub fn convert(&self) -> Result<ConversionResult, String> {
+    pub fn convert(&self, ctx: &ConversionContext) -> Result<ConversionResult, String> {
         log::info!(
             "Converting Claude session {} to Codex",
             self.source_session_id
         );
 
-        // 1. 读取源 Claude session
-        let claude_messages = self.read_claude_session()?;
-
-        // 2. 验证 session 已完成
-        self.validate_session_completed(&claude_messages)?;
-
-        // 3. 转换消息为 Codex 事件
-        let mut codex_events = Vec::new();
-
-        // 3a. 创建 session_meta 事件 (首行)
-        let first_timestamp = claude_messages
-            .first()
-            .and_then(|m| {
-                m.timestamp
-                    .clone()
-                    .or_else(|| m.sent_at.clone())
-                    .or_else(|| m.received_at.clone())
-            })
-            .unwrap_or_else(|| chrono::Utc::now().to_rfc3339());
-        let model = claude_messages.iter().find_map(|m| m.model.clone());
-        codex_events.push(self.create_session_meta(&first_timestamp, model.as_deref()));
-
-        // 3b. 转换每条消息（拆分多内容块为多个事件）
-        for msg in &claude_messages {
-            codex_events.extend(self.convert_claude_message(msg));
-        }
+        let session_path = self.claude_session_path()?;
+        let target_path = self.codex_target_path()?;
 
-        // 4. 写入目标文件
-        let target_path = self.write_codex_session(&codex_events)?;
+        let (event_count, corrupted_lines) = self.convert_file(&session_path, &target_path, ctx)?;
 
         log::info!(
             "Successfully converted {} messages to Codex session {}",
-            codex_events.len(),
+            event_count,
             self.new_session_filename
         );
 
-        Ok(ConversionResult {
+        Ok(self.conversion_result(event_count, &target_path, false, corrupted_lines))
+    }
+
+    /// 试运行：完整执行扫描、校验与转换，但不写出目标文件
+    pub fn dry_run(&self, ctx: &ConversionContext) -> Result<ConversionResult, String> {
+        let session_path = self.claude_session_path()?;
+        let target_path = self.codex_target_path()?;
+
+        let summary = self.scan_claude_session(&session_path, ctx)?;
+        self.validate_session_completed(&summary)?;
+        let event_count = self.write_events(&session_path, &summary, &mut std::io::sink(), ctx)?;
+
+        Ok(self.conversion_result(event_count, &target_path, true, summary.corrupted_lines))
+    }
+
+    fn conversion_result(
+        &self,
+        event_count: usize,
+        target_path: &Path,
+        dry_run: bool,
+        corrupted_lines: Vec<CorruptedLine>,
+    ) -> ConversionResult {
+        ConversionResult {
             success: true,
             new_session_id: self.new_session_filename.clone(), // 返回文件名（带 rollout- 前缀）
             target_engine: "codex".to_string(),
-            message_count: codex_events.len(),
+            message_count: event_count,
             source: ConversionSource {
                 engine: "claude".to_string(),
                 session_id: self.source_session_id.clone(),
                 converted_at: chrono::Utc::now().to_rfc3339(),
                 source_project_path: self.project_path.clone(),
             },
-            target_path,
+            target_path: target_path.to_string_lossy().to_string(),
             error: None,
-        })
+            dry_run,
+            corrupted_lines,
+        }
     }
 
-    /// 读取 Claude session 文件
-    fn read_claude_session(&self) -> Result<Vec<ClaudeMessage>, String> {
-        let claude_dir = super::super::claude::get_claude_dir()
-            .map_err(|e| format!("Failed to get Claude directory: {}", e))?;
+    /// 流式转换，返回写出的 Codex 事件数与源文件中的损坏行
+    ///
+    /// session_meta 位于首行但依赖全文信息（模型），因此先扫描一遍源文件收集摘要并校验完整性，
+    /// 再逐条转换、逐行写出；两遍都只持有当前一条消息
+    fn convert_file(
+        &self,
+        source: &Path,
+        target: &Path,
+        ctx: &ConversionContext,
+    ) -> Result<(usize, Vec<CorruptedLine>), String> {
+        // 1. 扫描源 Claude session
+        let summary = self.scan_claude_session(source, ctx)?;
 
-        // 直接使用 project_id（实际的目录名）
-        let session_path = claude_dir
-            .join("projects")
-            .join(&self.project_id)
-            .join(format!("{}.jsonl", self.source_session_id));
+        // 2. 验证 session 已完成
+        self.validate_session_completed(&summary)?;
 
-        if !session_path.exists() {
-            return Err(format!(
-                "Claude session file not found: {}",
-                session_path.display()
-            ));
-        }
+        // 3. 逐条转换并写入目标文件
+        let event_count = write_streaming(target, |writer| {
+            self.write_events(source, &summary, writer, ctx)
+        })?;
+        Ok((event_count, summary.corrupted_lines))
+    }
+
+    /// 逐条转换源消息并写出 Codex 事件，返回写出的事件数
+    fn write_events<W: Write>(
+        &self,
+        source: &Path,
+        summary: &ClaudeSessionSummary,
+        writer: &mut W,
+        ctx: &ConversionContext,
+    ) -> Result<usize, String> {
+        // 创建 session_meta 事件 (首行)
+        let first_timestamp = summary
+            .first_timestamp
+            .clone()
+            .unwrap_or_else(|| chrono::Utc::now().to_rfc3339());
+        write_json_line(
+            writer,
+            &self.create_session_meta(&first_timestamp, summary.model.as_deref()),
+        )?;
+        let mut event_count = 1;
+
+        // 转换每条消息（拆分多内容块为多个事件）
+        let mut progress = ProgressTracker::new(ctx, &self.source_session_id, source);
+        for_each_message::<ClaudeMessage, _>(source, ctx, |msg, bytes_read| {
+            for event in self.convert_claude_message(&msg) {
+                write_json_line(writer, &event)?;
+                event_count += 1;
+            }
+            progress.tick(bytes_read);
+            Ok(())
+        })?;
+        progress.finish();
 
-        let file = std::fs::File::open(&session_path)
-            .map_err(|e| format!("Failed to open session file: {}", e))?;
+        Ok(event_count)
+    }
 
-        let reader = BufReader::new(file);
-        let mut messages = Vec::new();
+    /// Claude session 文件路径
+    fn claude_session_path(&self) -> Result<PathBuf, String> {
+        claude_session_source_path(&self.project_id, &self.source_session_id)
+    }
 
-        for line in reader.lines() {
-            let line = line.map_err(|e| format!("Failed to read line: {}", e))?;
-            if line.trim().is_empty() {
-                continue;
+    /// 扫描 Claude session 文件，只保留生成 session_meta 与校验所需的信息
+    fn scan_claude_session(
+        &self,
+        source: &Path,
+        ctx: &ConversionContext,
+    ) -> Result<ClaudeSessionSummary, String> {
+        let mut summary = ClaudeSessionSummary::default();
+
+        summary.corrupted_lines = for_each_message::<ClaudeMessage, _>(source, ctx, |msg, _| {
+            if summary.message_count == 0 {
+                summary.first_timestamp = msg.timestamp.or(msg.sent_at).or(msg.received_at);
             }
-
-            match serde_json::from_str::<ClaudeMessage>(&line) {
-                Ok(msg) => messages.push(msg),
-                Err(e) => log::warn!("Failed to parse Claude message: {}", e),
+            if summary.model.is_none() {
+                summary.model = msg.model;
             }
+            summary.last_message_type = Some(msg.message_type);
+            summary.message_count += 1;
+            Ok(())
+        })?;
+
+        if summary.message_count == 0 {
+            return Err(t_with(
+                MessageKey::ConversionSessionEmpty,
+                &[("engine", &"Claude")],
+            ));
         }
 
-        if messages.is_empty() {
-            return Err("Claude session is empty".to_string());
-        }
-
-        log::info!("Read {} messages from Claude session", messages.len());
-        Ok(messages)
+        log::info!(
+            "Scanned {} messages from Claude session",
+            summary.message_count
+        );
+        Ok(summary)
     }
 
     /// 验证 session 已完成（最后一条消息不应该是 user）
-    fn validate_session_completed(&self, messages: &[ClaudeMessage]) -> Result<(), String> {
-        if messages.is_empty() {
-            return Err("Session is empty".to_string());
+    fn validate_session_completed(&self, summary: &ClaudeSessionSummary) -> Result<(), String> {
+        if summary.message_count == 0 {
+            return Err(t_with(
+                MessageKey::ConversionSessionEmpty,
+                &[("engine", &"Claude")],
+            ));
         }
 
-        if let Some(last) = messages.last() {
-            if last.message_type == "user" {
-                return Err("Session appears incomplete (ends with user message)".to_string());
-            }
+        if summary.last_message_type.as_deref() == Some("user") {
+            return Err(t(MessageKey::ConversionSessionIncomplete));
         }
 
         Ok(())
@@ -548,13 +686,13 @@ impl ClaudeToCodexConverter {
         match msg.message_type.as_str() {
             "user" => {
                 if let Some(ref message) = msg.message {
-                    let blocks = self.parse_content_blocks(&message.content);
+                    let blocks = parse_claude_content_blocks(&message.content);
                     events.push(self.create_user_response_item(&blocks, &timestamp));
                 }
             }
             "assistant" => {
                 if let Some(ref message) = msg.message {
-                    let blocks = self.parse_content_blocks(&message.content);
+                    let blocks = parse_claude_content_blocks(&message.content);
                     // 拆分多内容块为多个事件
                     events.extend(self.convert_assistant_content(&blocks, &timestamp));
                 }
@@ -687,34 +825,9 @@ impl ClaudeToCodexConverter {
         events
     }
 
-    /// 写入 Codex session 文件
-    fn write_codex_session(&self, events: &[CodexEvent]) -> Result<String, String> {
-        let sessions_dir = super::config::get_codex_sessions_dir()
-            .map_err(|e| format!("Failed to get Codex sessions directory: {}", e))?;
-
-        // 创建日期目录结构 YYYY/MM/DD
-        let now = chrono::Utc::now();
-        let date_dir = sessions_dir
-            .join(now.format("%Y").to_string())
-            .join(now.format("%m").to_string())
-            .join(now.format("%d").to_string());
-
-        std::fs::create_dir_all(&date_dir)
-            .map_err(|e| format!("Failed to create date directory: {}", e))?;
-
-        let file_path = date_dir.join(format!("{}.jsonl", self.new_session_filename));
-
-        let mut file = std::fs::File::create(&file_path)
-            .map_err(|e| format!("Failed to create session file: {}", e))?;
-
-        // 逐行写入 JSONL
-        for event in events {
-            let line = serde_json::to_string(event)
-                .map_err(|e| format!("Failed to serialize event: {}", e))?;
-            writeln!(file, "{}", line).map_err(|e| format!("Failed to write event: {}", e))?;
-        }
-
-        Ok(file_path.to_string_lossy().to_string())
+    /// Codex session 目标文件路径（YYYY/MM/DD 日期目录）
+    fn codex_target_path(&self) -> Result<PathBuf, String> {
+        codex_dated_session_path(&self.new_session_filename)
     }
 }
 
@@ -741,44 +854,6 @@ impl CodexToClaudeConverter {
         }
     }
 
-    /// 转换 content 为标准数组格式
-    fn simplify_content(&self, content: Vec<ClaudeContentBlock>) -> Option<Value> {
-        if content.is_empty() {
-            return None;
-        }
-
-        // 统一使用数组格式（与原生 Claude 一致）
-        let array: Vec<Value> = content
-            .iter()
-            .filter_map(|block| match block {
-                ClaudeContentBlock::Text { text } => {
-                    Some(serde_json::json!({"type": "text", "text": text}))
-                }
-                ClaudeContentBlock::ToolUse { id, name, input } => Some(serde_json::json!({
-                    "type": "tool_use",
-                    "id": id,
-                    "name": name,
-                    "input": input
-                })),
-                ClaudeContentBlock::ToolResult {
-                    tool_use_id,
-                    content,
-                    is_error,
-                } => Some(serde_json::json!({
-                    "type": "tool_result",
-                    "tool_use_id": tool_use_id,
-                    "content": content,
-                    "is_error": is_error
-                })),
-                ClaudeContentBlock::Thinking { thinking } => {
-                    Some(serde_json::json!({"type": "thinking", "thinking": thinking}))
-                }
-            })
-            .collect();
-
-        Some(Value::Array(array))
-    }
-
     /// 创建标准 Claude 消息的辅助函数
     fn create_claude_message(
         &self,
@@ -788,175 +863,133 @@ impl CodexToClaudeConverter {
         timestamp: &str,
         model: Option<String>,
     ) -> ClaudeMessage {
-        // 将 content 数组转换为简化格式
-        let simplified_content = self.simplify_content(content);
-
-        ClaudeMessage {
-            message_type: message_type.to_string(),
-            message: Some(ClaudeMessageContent {
-                role: role.to_string(),
-                content: simplified_content,
-                usage: None,
-            }),
-            timestamp: Some(timestamp.to_string()),
-            uuid: Some(uuid::Uuid::new_v4().to_string()),
-            parent_uuid: None,
-            session_id: Some(self.new_session_id.clone()),
-            cwd: Some(self.project_path.clone()),
-            version: Some("2.0.55".to_string()), // 使用真实版本号，避免被识别为特殊模式
-            git_branch: None,
-            user_type: if role == "user" {
-                Some("external".to_string())
-            } else {
-                None
-            },
-            is_sidechain: Some(false),
-            subtype: None,
-            received_at: if role != "user" {
-                Some(timestamp.to_string())
-            } else {
-                None
-            },
-            sent_at: if role == "user" {
-                Some(timestamp.to_string())
-            } else {
-                None
-            },
+        new_claude_message(
+            &self.new_session_id,
+            &self.project_path,
+            message_type,
+            role,
+            content,
+            timestamp,
             model,
-            conversion_source: None,
-            extra: HashMap::new(),
-        }
+        )
     }
 
-    pub fn convert(&self) -> Result<ConversionResult, String> {
+    pub fn convert(&self, ctx: &ConversionContext) -> Result<ConversionResult, String> {
         log::info!(
             "Converting Codex session {} to Claude",
             self.source_session_id
         );
 
-        // 1. 读取源 Codex session
-        let codex_events = self.read_codex_session()?;
-
-        // 2. 验证 session 已完成
-        self.validate_session_completed(&codex_events)?;
-
-        // 3. 转换事件为 Claude 消息
-        let mut claude_messages: Vec<ClaudeMessage> = Vec::new();
-
-        // 3a. 添加 file-history-snapshot 作为第一条消息（必需！）
-        let first_timestamp = codex_events
-            .first()
-            .and_then(|e| e.timestamp.clone())
-            .unwrap_or_else(|| chrono::Utc::now().to_rfc3339());
-        let snapshot_uuid = uuid::Uuid::new_v4().to_string();
-
-        claude_messages.push(ClaudeMessage {
-            message_type: "file-history-snapshot".to_string(),
-            message: None,
-            timestamp: Some(first_timestamp.clone()),
-            uuid: Some(snapshot_uuid.clone()),
-            parent_uuid: None,
-            session_id: None,
-            cwd: None,
-            version: None,
-            git_branch: None,
-            user_type: None,
-            is_sidechain: None,
-            subtype: None,
-            received_at: None,
-            sent_at: None,
-            model: None,
-            conversion_source: None,
-            extra: {
-                let mut map = HashMap::new();
-                map.insert(
-                    "messageId".to_string(),
-                    Value::String(snapshot_uuid.clone()),
-                );
-                map.insert(
-                    "snapshot".to_string(),
-                    serde_json::json!({
-                        "messageId": snapshot_uuid,
-                        "trackedFileBackups": {},
-                        "timestamp": first_timestamp
-                    }),
-                );
-                map.insert("isSnapshotUpdate".to_string(), Value::Bool(false));
-                map
-            },
-        });
-
-        // 3b. 转换 Codex 事件
-        for event in &codex_events {
-            if let Some(msg) = self.convert_codex_event(event) {
-                claude_messages.push(msg);
-            }
-        }
+        let session_path = self.codex_session_path()?;
+        let target_path = self.claude_target_path()?;
 
-        // 4. 写入目标文件
-        let target_path = self.write_claude_session(&claude_messages)?;
+        let message_count = self.convert_file(&session_path, &target_path, ctx)?;
 
         log::info!(
             "Successfully converted {} events to Claude session {}",
-            claude_messages.len(),
+            message_count,
             self.new_session_id
         );
 
-        Ok(ConversionResult {
+        Ok(self.conversion_result(message_count, &target_path, false))
+    }
+
+    /// 试运行：完整执行转换与校验，但不写出目标文件
+    pub fn dry_run(&self, ctx: &ConversionContext) -> Result<ConversionResult, String> {
+        let session_path = self.codex_session_path()?;
+        let target_path = self.claude_target_path()?;
+
+        let message_count = self.write_messages(&session_path, &mut std::io::sink(), ctx)?;
+
+        Ok(self.conversion_result(message_count, &target_path, true))
+    }
+
+    fn conversion_result(
+        &self,
+        message_count: usize,
+        target_path: &Path,
+        dry_run: bool,
+    ) -> ConversionResult {
+        ConversionResult {
             success: true,
             new_session_id: self.new_session_id.clone(),
             target_engine: "claude".to_string(),
-            message_count: claude_messages.len(),
+            message_count,
             source: ConversionSource {
                 engine: "codex".to_string(),
                 session_id: self.source_session_id.clone(),
                 converted_at: chrono::Utc::now().to_rfc3339(),
                 source_project_path: self.project_path.clone(),
             },
-            target_path,
+            target_path: target_path.to_string_lossy().to_string(),
             error: None,
-        })
+            dry_run,
+            corrupted_lines: Vec::new(),
+        }
     }
 
-    /// 读取 Codex session 文件
-    fn read_codex_session(&self) -> Result<Vec<CodexEvent>, String> {
-        let sessions_dir = super::config::get_codex_sessions_dir()
-            .map_err(|e| format!("Failed to get Codex sessions directory: {}", e))?;
-
-        // 使用 codex/session.rs 中的 find_session_file 函数
-        let session_path =
-            super::session::find_session_file(&sessions_dir, &self.source_session_id).ok_or_else(
-                || format!("Codex session file not found: {}", self.source_session_id),
-            )?;
-
-        let file = std::fs::File::open(&session_path)
-            .map_err(|e| format!("Failed to open session file: {}", e))?;
+    /// 流式转换：逐条读取 Codex 事件、转换并写出，同时建立 parentUuid 消息链；返回写出的消息数
+    fn convert_file(
+        &self,
+        source: &Path,
+        target: &Path,
+        ctx: &ConversionContext,
+    ) -> Result<usize, String> {
+        write_streaming(target, |writer| self.write_messages(source, writer, ctx))
+    }
 
-        let reader = BufReader::new(file);
-        let mut events = Vec::new();
+    fn write_messages<W: Write>(
+        &self,
+        source: &Path,
+        writer: &mut W,
+        ctx: &ConversionContext,
+    ) -> Result<usize, String> {
+        let mut message_count = 0;
+        let mut last_event_type: Option<String> = None;
+        let mut prev_uuid: Option<String> = None;
 
-        for line in reader.lines() {
-            let line = line.map_err(|e| format!("Failed to read line: {}", e))?;
-            if line.trim().is_empty() {
-                continue;
+        let mut progress = ProgressTracker::new(ctx, &self.source_session_id, source);
+        for_each_message::<CodexEvent, _>(source, ctx, |event, bytes_read| {
+            // 添加 file-history-snapshot 作为第一条消息（必需！）
+            // 它不参与 parentUuid 链：与原生 Claude 一致，其后第一条消息的 parentUuid 也为空
+            if last_event_type.is_none() {
+                let first_timestamp = event
+                    .timestamp
+                    .clone()
+                    .unwrap_or_else(|| chrono::Utc::now().to_rfc3339());
+                write_json_line(writer, &file_history_snapshot(&first_timestamp))?;
+                message_count += 1;
             }
 
-            match serde_json::from_str::<CodexEvent>(&line) {
-                Ok(event) => events.push(event),
-                Err(e) => log::warn!("Failed to parse Codex event: {}", e),
+            if let Some(msg) = self.convert_codex_event(&event) {
+                write_linked(writer, &mut prev_uuid, msg)?;
+                message_count += 1;
             }
-        }
 
-        if events.is_empty() {
-            return Err("Codex session is empty".to_string());
+            last_event_type = Some(event.event_type);
+            progress.tick(bytes_read);
+            Ok(())
+        })?;
+
+        if last_event_type.is_none() {
+            return Err(t_with(
+                MessageKey::ConversionSessionEmpty,
+                &[("engine", &"Codex")],
+            ));
         }
+        self.validate_session_completed(last_event_type.as_deref())?;
+        progress.finish();
 
-        log::info!("Read {} events from Codex session", events.len());
-        Ok(events)
+        Ok(message_count)
+    }
+
+    /// Codex session 文件路径
+    fn codex_session_path(&self) -> Result<PathBuf, String> {
+        resolve_session_file("codex", &self.source_session_id, &self.project_id)
     }
 
     /// 验证 session 已完成
-    fn validate_session_completed(&self, _events: &[CodexEvent]) -> Result<(), String> {
+    fn validate_session_completed(&self, _last_event_type: Option<&str>) -> Result<(), String> {
         // Codex session 的完成性检查可以更灵活
         // 暂时只检查是否为空
         Ok(())
@@ -1174,129 +1207,3229 @@ impl CodexToClaudeConverter {
         }
     }
 
-    /// 写入 Claude session 文件
-    fn write_claude_session(&self, messages: &[ClaudeMessage]) -> Result<String, String> {
-        let claude_dir = super::super::claude::get_claude_dir()
-            .map_err(|e| format!("Failed to get Claude directory: {}", e))?;
+    /// Claude session 目标文件路径
+    fn claude_target_path(&self) -> Result<PathBuf, String> {
+        claude_session_target_path(&self.project_id, &self.new_session_id)
+    }
+}
 
-        // 直接使用 project_id（实际的目录名）
-        let project_dir = claude_dir.join("projects").join(&self.project_id);
+// ================================
+// Gemini → Claude 转换器
+// ================================
 
-        std::fs::create_dir_all(&project_dir)
-            .map_err(|e| format!("Failed to create project directory: {}", e))?;
+/// Gemini Session → Claude Session 转换器
+///
+/// Gemini 会话是单个 JSON 文件（`chats/session-*.json`），`messages` 中 `type` 为 "user" / "gemini"；
+/// gemini 消息的 toolCalls 拆为 tool_use（assistant）与 tool_result（user）消息对
+pub struct GeminiToClaudeConverter {
+    source_session_id: String,
+    project_id: String,     // Claude 项目目录名
+    project_path: String,   // 原始项目路径（同时用于定位 Gemini 会话目录）
+    new_session_id: String, // UUID 格式
+}
 
-        let file_path = project_dir.join(format!("{}.jsonl", self.new_session_id));
+impl GeminiToClaudeConverter {
+    pub fn new(source_session_id: String, project_id: String, project_path: String) -> Self {
+        Self {
+            source_session_id,
+            project_id,
+            project_path,
+            new_session_id: uuid::Uuid::new_v4().to_string(),
+        }
+    }
 
-        let mut file = std::fs::File::create(&file_path)
-            .map_err(|e| format!("Failed to create session file: {}", e))?;
+    pub fn convert(&self, ctx: &ConversionContext) -> Result<ConversionResult, String> {
+        log::info!(
+            "Converting Gemini session {} to Claude",
+            self.source_session_id
+        );
 
-        // 建立 parentUuid 消息链
-        let mut prev_uuid: Option<String> = None;
-        let mut linked_messages = messages.to_vec();
+        let session_path = self.gemini_session_path()?;
+        let target_path = claude_session_target_path(&self.project_id, &self.new_session_id)?;
 
-        for msg in &mut linked_messages {
-            // 设置 parent_uuid 指向前一条消息
-            msg.parent_uuid = prev_uuid.clone();
-            // 更新 prev_uuid 为当前消息的 uuid
-            prev_uuid = msg.uuid.clone();
-        }
+        let message_count = write_streaming(&target_path, |writer| {
+            self.write_messages(&session_path, writer, ctx)
+        })?;
 
-        // 写入文件
-        for msg in &linked_messages {
-            let line = serde_json::to_string(msg)
-                .map_err(|e| format!("Failed to serialize message: {}", e))?;
-            writeln!(file, "{}", line).map_err(|e| format!("Failed to write message: {}", e))?;
-        }
+        log::info!(
+            "Successfully converted Gemini session into {} Claude messages ({})",
+            message_count,
+            self.new_session_id
+        );
 
-        Ok(file_path.to_string_lossy().to_string())
+        Ok(self.conversion_result(message_count, &target_path, false))
     }
-}
 
-// ================================
-// Tauri Commands
-// ================================
+    /// 试运行：完整执行转换，但不写出目标文件
+    pub fn dry_run(&self, ctx: &ConversionContext) -> Result<ConversionResult, String> {
+        let session_path = self.gemini_session_path()?;
+        let target_path = claude_session_target_path(&self.project_id, &self.new_session_id)?;
 
-/// 根据文件存在性判断 session 的源引擎类型
-fn detect_session_engine(session_id: &str, project_id: &str) -> Result<String, String> {
-    // 1. 检查是否为 Codex session（查找 sessions 目录）
-    if let Ok(sessions_dir) = super::config::get_codex_sessions_dir() {
-        if super::session::find_session_file(&sessions_dir, session_id).is_some() {
-            return Ok("codex".to_string());
+        let message_count = self.write_messages(&session_path, &mut std::io::sink(), ctx)?;
+
+        Ok(self.conversion_result(message_count, &target_path, true))
+    }
+
+    fn conversion_result(
+        &self,
+        message_count: usize,
+        target_path: &Path,
+        dry_run: bool,
+    ) -> ConversionResult {
+        ConversionResult {
+            success: true,
+            new_session_id: self.new_session_id.clone(),
+            target_engine: "claude".to_string(),
+            message_count,
+            source: self.conversion_source(),
+            target_path: target_path.to_string_lossy().to_string(),
+            error: None,
+            dry_run,
+            corrupted_lines: Vec::new(),
         }
     }
 
-    // 2. 检查是否为 Claude session（查找 projects 目录）
-    if let Ok(claude_dir) = super::super::claude::get_claude_dir() {
-        let session_path = claude_dir
-            .join("projects")
-            .join(project_id)
-            .join(format!("{}.jsonl", session_id));
-        if session_path.exists() {
-            return Ok("claude".to_string());
+    fn conversion_source(&self) -> ConversionSource {
+        ConversionSource {
+            engine: "gemini".to_string(),
+            session_id: self.source_session_id.clone(),
+            converted_at: chrono::Utc::now().to_rfc3339(),
+            source_project_path: self.project_path.clone(),
         }
     }
 
-    Err(format!(
-        "Session {} not found in either Claude or Codex directories",
-        session_id
-    ))
-}
+    /// Gemini session 文件路径（~/.gemini/tmp/<项目哈希>/chats）
+    fn gemini_session_path(&self) -> Result<PathBuf, String> {
+        resolve_session_file("gemini", &self.source_session_id, &self.project_path)
+    }
 
-/// 统一转换接口
-#[tauri::command]
-pub async fn convert_session(
-    session_id: String,
-    target_engine: String,
-    project_id: String,
-    project_path: String,
-) -> Result<ConversionResult, String> {
-    log::info!(
-        "Converting session {} to engine: {}, project_id: {}, project_path: {}",
-        session_id,
-        target_engine,
-        project_id,
-        project_path
-    );
+    /// 逐条转换 Gemini 消息并写出，同时建立 parentUuid 消息链；返回写出的消息数
+    fn write_messages<W: Write>(
+        &self,
+        source: &Path,
+        writer: &mut W,
+        ctx: &ConversionContext,
+    ) -> Result<usize, String> {
+        let session = read_gemini_session(source)?;
+        let messages = session
+            .get("messages")
+            .and_then(|m| m.as_array())
+            .filter(|m| !m.is_empty())
+            .ok_or_else(|| t_with(MessageKey::ConversionSessionEmpty, &[("engine", &"Gemini")]))?;
+
+        let first_timestamp = session
+            .get("startTime")
+            .or_else(|| messages[0].get("timestamp"))
+            .and_then(|v| v.as_str())
+            .map(String::from)
+            .unwrap_or_else(|| chrono::Utc::now().to_rfc3339());
+        let model = messages
+            .iter()
+            .find_map(|m| m.get("model").and_then(|v| v.as_str()))
+            .map(String::from);
 
-    // 根据文件存在性检测源引擎
-    let source_engine = detect_session_engine(&session_id, &project_id)?;
+        // file-history-snapshot 不参与 parentUuid 链
+        let mut prev_uuid: Option<String> = None;
+        write_json_line(writer, &file_history_snapshot(&first_timestamp))?;
+        write_linked(
+            writer,
+            &mut prev_uuid,
+            self.create_init_message(&first_timestamp, model),
+        )?;
+        let mut message_count = 2;
+
+        let mut progress = ProgressTracker::new(ctx, &self.source_session_id, source);
+        for (index, message) in messages.iter().enumerate() {
+            if ctx.is_cancelled() {
+                return Err(CANCELLED_MESSAGE.to_string());
+            }
+            for msg in self.convert_gemini_message(message) {
+                write_linked(writer, &mut prev_uuid, msg)?;
+                message_count += 1;
+            }
+            // 整个文件一次读入，按已处理的消息比例估算读取进度
+            let bytes_read = progress.total_bytes * (index as u64 + 1) / messages.len() as u64;
+            progress.tick(bytes_read);
+        }
+        progress.finish();
 
-    if source_engine == target_engine {
-        return Err(format!(
-            "Session {} is already a {} session",
-            session_id, target_engine
-        ));
+        Ok(message_count)
     }
 
-    match target_engine.as_str() {
-        "codex" => {
-            let converter = ClaudeToCodexConverter::new(session_id, project_id, project_path);
-            converter.convert()
-        }
-        "claude" => {
+    /// 会话开头的 system 消息，记录转换来源（批量转换据此跳过已转换的会话）
+    fn create_init_message(&self, timestamp: &str, model: Option<String>) -> ClaudeMessage {
+        ClaudeMessage {
+            subtype: Some("init".to_string()),
+            conversion_source: Some(self.conversion_source()),
+            message: None,
+            ..new_claude_message(
+                &self.new_session_id,
+                &self.project_path,
+                "system",
+                "system",
+                Vec::new(),
+                timestamp,
+                model,
+            )
+        }
+    }
+
+    /// 转换单条 Gemini 消息；info / error 等提示消息与空的 assistant 消息不输出
+    fn convert_gemini_message(&self, message: &Value) -> Vec<ClaudeMessage> {
+        let timestamp = message
+            .get("timestamp")
+            .and_then(|v| v.as_str())
+            .map(String::from)
+            .unwrap_or_else(|| chrono::Utc::now().to_rfc3339());
+        let text = gemini_message_text(message);
+
+        match message.get("type").and_then(|v| v.as_str()) {
+            Some("user") if !text.trim().is_empty() => vec![self.create_message(
+                "user",
+                vec![ClaudeContentBlock::Text { text }],
+                &timestamp,
+                None,
+            )],
+            Some("gemini") => {
+                let model = message
+                    .get("model")
+                    .and_then(|v| v.as_str())
+                    .map(String::from);
+                let mut converted = Vec::new();
+
+                if !text.trim().is_empty() {
+                    converted.push(self.create_message(
+                        "assistant",
+                        vec![ClaudeContentBlock::Text { text }],
+                        &timestamp,
+                        model.clone(),
+                    ));
+                }
+
+                let tool_calls = message.get("toolCalls").and_then(|v| v.as_array());
+                for call in tool_calls.into_iter().flatten() {
+                    let id = call
+                        .get("id")
+                        .and_then(|v| v.as_str())
+                        .map(String::from)
+                        .unwrap_or_else(|| format!("gemini_{}", uuid::Uuid::new_v4()));
+                    let name = call
+                        .get("name")
+                        .and_then(|v| v.as_str())
+                        .unwrap_or("unknown");
+                    let status = call.get("status").and_then(|v| v.as_str());
+                    let result_timestamp = call
+                        .get("timestamp")
+                        .and_then(|v| v.as_str())
+                        .unwrap_or(&timestamp);
+
+                    converted.push(self.create_message(
+                        "assistant",
+                        vec![ClaudeContentBlock::ToolUse {
+                            id: id.clone(),
+                            name: map_gemini_to_claude_tool(name),
+                            input: call
+                                .get("args")
+                                .cloned()
+                                .unwrap_or_else(|| serde_json::json!({})),
+                        }],
+                        &timestamp,
+                        model.clone(),
+                    ));
+                    // tool_result 必须在 user 消息中
+                    converted.push(self.create_message(
+                        "user",
+                        vec![ClaudeContentBlock::ToolResult {
+                            tool_use_id: id,
+                            content: Value::String(gemini_tool_result_text(call)),
+                            is_error: Some(matches!(status, Some("error" | "cancelled"))),
+                        }],
+                        result_timestamp,
+                        None,
+                    ));
+                }
+
+                converted
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    fn create_message(
+        &self,
+        role: &str,
+        content: Vec<ClaudeContentBlock>,
+        timestamp: &str,
+        model: Option<String>,
+    ) -> ClaudeMessage {
+        new_claude_message(
+            &self.new_session_id,
+            &self.project_path,
+            role,
+            role,
+            content,
+            timestamp,
+            model,
+        )
+    }
+}
+
+// ================================
+// Claude → Gemini 转换器
+// ================================
+
+/// Claude Session → Gemini Session 转换器
+///
+/// 连续的 assistant 消息合并为一条 gemini 消息（文本拼接、tool_use 进入 toolCalls），
+/// user 消息中的 tool_result 回填到对应 toolCall 的 result；thinking 块不转换
+pub struct ClaudeToGeminiConverter {
+    source_session_id: String,
+    project_id: String,     // Claude 项目目录名
+    project_path: String,   // 原始项目路径（决定 Gemini 会话目录）
+    new_session_id: String, // UUID 格式
+}
+
+/// Claude → Gemini 转换生成的会话
+struct GeminiSessionBuild {
+    session: Value,
+    message_count: usize,
+    corrupted_lines: Vec<CorruptedLine>,
+}
+
+impl ClaudeToGeminiConverter {
+    pub fn new(source_session_id: String, project_id: String, project_path: String) -> Self {
+        Self {
+            source_session_id,
+            project_id,
+            project_path,
+            new_session_id: uuid::Uuid::new_v4().to_string(),
+        }
+    }
+
+    pub fn convert(&self, ctx: &ConversionContext) -> Result<ConversionResult, String> {
+        log::info!(
+            "Converting Claude session {} to Gemini",
+            self.source_session_id
+        );
+
+        let session_path = claude_session_source_path(&self.project_id, &self.source_session_id)?;
+        let target_path = self.gemini_target_path()?;
+
+        let build = self.build_session(&session_path, ctx)?;
+        write_streaming(&target_path, |writer| {
+            serde_json::to_writer_pretty(&mut *writer, &build.session)
+                .map_err(|e| format!("Failed to write session file: {}", e))?;
+            Ok(build.message_count)
+        })?;
+
+        log::info!(
+            "Successfully converted {} messages to Gemini session {}",
+            build.message_count,
+            self.new_session_id
+        );
+
+        Ok(self.conversion_result(build, &target_path, false))
+    }
+
+    /// 试运行：完整执行转换与校验，但不写出目标文件
+    pub fn dry_run(&self, ctx: &ConversionContext) -> Result<ConversionResult, String> {
+        let session_path = claude_session_source_path(&self.project_id, &self.source_session_id)?;
+        let target_path = self.gemini_target_path()?;
+
+        let build = self.build_session(&session_path, ctx)?;
+
+        Ok(self.conversion_result(build, &target_path, true))
+    }
+
+    fn conversion_result(
+        &self,
+        build: GeminiSessionBuild,
+        target_path: &Path,
+        dry_run: bool,
+    ) -> ConversionResult {
+        ConversionResult {
+            success: true,
+            new_session_id: self.new_session_id.clone(),
+            target_engine: "gemini".to_string(),
+            message_count: build.message_count,
+            source: self.conversion_source(),
+            target_path: target_path.to_string_lossy().to_string(),
+            error: None,
+            dry_run,
+            corrupted_lines: build.corrupted_lines,
+        }
+    }
+
+    fn conversion_source(&self) -> ConversionSource {
+        ConversionSource {
+            engine: "claude".to_string(),
+            session_id: self.source_session_id.clone(),
+            converted_at: chrono::Utc::now().to_rfc3339(),
+            source_project_path: self.project_path.clone(),
+        }
+    }
+
+    /// 逐条读取 Claude 消息并组装 Gemini 会话（Gemini 会话是单个 JSON，需整体写出）
+    fn build_session(
+        &self,
+        source: &Path,
+        ctx: &ConversionContext,
+    ) -> Result<GeminiSessionBuild, String> {
+        let mut messages: Vec<Value> = Vec::new();
+        let mut turn_open = false;
+        let mut source_count = 0;
+        let mut first_timestamp: Option<String> = None;
+        let mut last_timestamp: Option<String> = None;
+        let mut last_message_type: Option<String> = None;
+
+        let mut progress = ProgressTracker::new(ctx, &self.source_session_id, source);
+        let corrupted_lines =
+            for_each_message::<ClaudeMessage, _>(source, ctx, |msg, bytes_read| {
+                let timestamp = msg
+                    .timestamp
+                    .clone()
+                    .or_else(|| msg.received_at.clone())
+                    .or_else(|| msg.sent_at.clone())
+                    .unwrap_or_else(|| chrono::Utc::now().to_rfc3339());
+                first_timestamp.get_or_insert_with(|| timestamp.clone());
+                last_timestamp = Some(timestamp.clone());
+
+                // 子代理的侧链消息不属于主对话
+                if msg.is_sidechain != Some(true) {
+                    append_claude_message(&mut messages, &mut turn_open, &msg, &timestamp);
+                }
+
+                last_message_type = Some(msg.message_type);
+                source_count += 1;
+                progress.tick(bytes_read);
+                Ok(())
+            })?;
+
+        if source_count == 0 {
+            return Err(t_with(
+                MessageKey::ConversionSessionEmpty,
+                &[("engine", &"Claude")],
+            ));
+        }
+        // 最后一条是 user 消息说明会话还在进行中
+        if last_message_type.as_deref() == Some("user") {
+            return Err(t(MessageKey::ConversionSessionIncomplete));
+        }
+        progress.finish();
+
+        // 只有工具调用被拆走后什么也不剩的 gemini 消息不输出
+        messages.retain_mut(|message| {
+            if message["type"] != "gemini" {
+                return true;
+            }
+            let has_tool_calls = message["toolCalls"]
+                .as_array()
+                .is_some_and(|calls| !calls.is_empty());
+            if !has_tool_calls {
+                if let Some(message) = message.as_object_mut() {
+                    message.remove("toolCalls");
+                }
+            }
+            has_tool_calls || !message["content"].as_str().unwrap_or_default().is_empty()
+        });
+
+        let message_count = messages.len();
+        let session = serde_json::json!({
+            "sessionId": self.new_session_id,
+            "projectHash": hash_project_path(&self.project_path),
+            "startTime": first_timestamp,
+            "lastUpdated": last_timestamp,
+            "messages": messages,
+            "conversionSource": self.conversion_source(),
+        });
+
+        Ok(GeminiSessionBuild {
+            session,
+            message_count,
+            corrupted_lines,
+        })
+    }
+
+    /// 新 Gemini session 文件：chats/session-<YYYY-MM-DDTHH-MM>-<session id 前 8 位>.json
+    fn gemini_target_path(&self) -> Result<PathBuf, String> {
+        let sessions_dir = get_gemini_sessions_dir(&self.project_path)?;
+        let timestamp = chrono::Utc::now().format("%Y-%m-%dT%H-%M").to_string();
+        Ok(sessions_dir.join(format!(
+            "session-{}-{}.json",
+            timestamp,
+            &self.new_session_id[..8]
+        )))
+    }
+}
+
+/// 把一条 Claude 消息并入 Gemini 消息列表
+///
+/// `turn_open` 表示最后一条 gemini 消息仍可追加 assistant 内容；遇到 user 消息后关闭
+fn append_claude_message(
+    messages: &mut Vec<Value>,
+    turn_open: &mut bool,
+    msg: &ClaudeMessage,
+    timestamp: &str,
+) {
+    let Some(content) = msg.message.as_ref() else {
+        return;
+    };
+    let blocks = parse_claude_content_blocks(&content.content);
+
+    match msg.message_type.as_str() {
+        "user" => {
+            *turn_open = false;
+            let mut texts = Vec::new();
+            for block in blocks {
+                match block {
+                    ClaudeContentBlock::Text { text } => texts.push(text),
+                    ClaudeContentBlock::ToolResult {
+                        tool_use_id,
+                        content,
+                        is_error,
+                    } => attach_tool_result(
+                        messages,
+                        &tool_use_id,
+                        &content,
+                        is_error.unwrap_or(false),
+                    ),
+                    _ => {}
+                }
+            }
+
+            let text = texts.join("\n\n");
+            if !text.trim().is_empty() {
+                messages.push(serde_json::json!({
+                    "id": uuid::Uuid::new_v4().to_string(),
+                    "timestamp": timestamp,
+                    "type": "user",
+                    "content": text,
+                }));
+            }
+        }
+        "assistant" => {
+            for block in blocks {
+                match block {
+                    ClaudeContentBlock::Text { text } if !text.trim().is_empty() => {
+                        let turn = open_gemini_turn(messages, turn_open, timestamp);
+                        let merged = match turn["content"].as_str() {
+                            Some(existing) if !existing.is_empty() => {
+                                format!("{}\n\n{}", existing, text)
+                            }
+                            _ => text,
+                        };
+                        turn["content"] = Value::String(merged);
+                    }
+                    ClaudeContentBlock::ToolUse { id, name, input } => {
+                        let turn = open_gemini_turn(messages, turn_open, timestamp);
+                        if let Some(calls) = turn["toolCalls"].as_array_mut() {
+                            calls.push(serde_json::json!({
+                                "id": id,
+                                "name": map_claude_to_gemini_tool(&name),
+                                "args": input,
+                                "status": "success",
+                                "timestamp": timestamp,
+                            }));
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// 当前可追加内容的 gemini 消息，没有时新建一条
+fn open_gemini_turn<'a>(
+    messages: &'a mut Vec<Value>,
+    turn_open: &mut bool,
+    timestamp: &str,
+) -> &'a mut Value {
+    let is_gemini = messages.last().is_some_and(|m| m["type"] == "gemini");
+    if !*turn_open || !is_gemini {
+        messages.push(serde_json::json!({
+            "id": uuid::Uuid::new_v4().to_string(),
+            "timestamp": timestamp,
+            "type": "gemini",
+            "content": "",
+            "toolCalls": [],
+        }));
+        *turn_open = true;
+    }
+    messages.last_mut().expect("gemini turn was just pushed")
+}
+
+/// 把 tool_result 回填到之前 gemini 消息中 id 相同的 toolCall
+fn attach_tool_result(messages: &mut [Value], tool_use_id: &str, content: &Value, is_error: bool) {
+    let output = match content {
+        Value::String(s) => s.clone(),
+        Value::Array(items) => items
+            .iter()
+            .filter_map(|item| item.get("text").and_then(|t| t.as_str()))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        other => other.to_string(),
+    };
+
+    let call = messages
+        .iter_mut()
+        .rev()
+        .filter_map(|m| m["toolCalls"].as_array_mut())
+        .flatten()
+        .find(|call| call["id"] == tool_use_id);
+    let Some(call) = call else {
+        log::debug!("No Gemini tool call found for tool_result {}", tool_use_id);
+        return;
+    };
+
+    call["result"] = serde_json::json!([{
+        "functionResponse": {
+            "id": tool_use_id,
+            "name": call["name"].clone(),
+            "response": { "output": output },
+        }
+    }]);
+    call["resultDisplay"] = Value::String(output);
+    call["status"] = Value::String(if is_error { "error" } else { "success" }.to_string());
+}
+
+/// 读取整个 Gemini 会话 JSON（单个文件无法逐行处理，超过内存加载上限时直接拒绝）
+fn read_gemini_session(path: &Path) -> Result<Value, String> {
+    ensure_loadable_in_memory(path)?;
+    let content =
+        std::fs::read_to_string(path).map_err(|e| format!("Failed to read session file: {}", e))?;
+    serde_json::from_str(&content).map_err(|e| format!("Failed to parse session JSON: {}", e))
+}
+
+/// Gemini 消息的文本：content 为字符串，或 `[{ text }]` 形式的 parts 数组
+fn gemini_message_text(message: &Value) -> String {
+    match message.get("content") {
+        Some(Value::String(text)) => text.clone(),
+        Some(Value::Array(parts)) => parts
+            .iter()
+            .filter_map(|part| part.get("text").and_then(|t| t.as_str()))
+            .collect::<Vec<_>>()
+            .join(""),
+        _ => String::new(),
+    }
+}
+
+/// Gemini toolCall 的结果文本：取 functionResponse 的 output / error，没有时退回 resultDisplay
+fn gemini_tool_result_text(call: &Value) -> String {
+    if let Some(text) = call.get("result").and_then(|r| r.as_str()) {
+        return text.to_string();
+    }
+
+    let parts = call.get("result").and_then(|r| r.as_array());
+    let texts: Vec<&str> = parts
+        .into_iter()
+        .flatten()
+        .filter_map(|part| {
+            let response = part.get("functionResponse").and_then(|f| f.get("response"));
+            response
+                .and_then(|r| r.get("output").or_else(|| r.get("error")))
+                .and_then(|v| v.as_str())
+                .or_else(|| part.get("text").and_then(|t| t.as_str()))
+        })
+        .collect();
+    if !texts.is_empty() {
+        return texts.join("\n");
+    }
+
+    call.get("resultDisplay")
+        .and_then(|d| d.as_str())
+        .unwrap_or_default()
+        .to_string()
+}
+
+/// 新 Codex session 文件名：rollout-2025-12-01T09-26-15-{uuid}
+fn codex_session_filename(uuid: &str) -> String {
+    let timestamp = chrono::Utc::now().format("%Y-%m-%dT%H-%M-%S").to_string();
+    format!("rollout-{}-{}", timestamp, uuid)
+}
+
+/// Codex sessions 目录下按当天日期（YYYY/MM/DD）存放的 session 文件路径
+fn codex_dated_session_path(filename: &str) -> Result<PathBuf, String> {
+    let sessions_dir = super::config::get_codex_sessions_dir()
+        .map_err(|e| format!("Failed to get Codex sessions directory: {}", e))?;
+
+    let now = chrono::Utc::now();
+    let date_dir = sessions_dir
+        .join(now.format("%Y").to_string())
+        .join(now.format("%m").to_string())
+        .join(now.format("%d").to_string());
+
+    Ok(date_dir.join(format!("{}.jsonl", filename)))
+}
+
+/// Claude projects 目录下的 session 文件路径
+fn claude_session_target_path(project_id: &str, session_id: &str) -> Result<PathBuf, String> {
+    // 直接使用 project_id（实际的目录名）
+    claude_session_path(project_id, session_id)
+}
+
+/// 已有 Claude session 文件的路径（不存在时报错）
+fn claude_session_source_path(project_id: &str, session_id: &str) -> Result<PathBuf, String> {
+    resolve_session_file("claude", session_id, project_id)
+}
+
+/// Claude 内容块转为标准数组格式（与原生 Claude 一致），无内容时返回 None
+fn claude_content_value(content: Vec<ClaudeContentBlock>) -> Option<Value> {
+    if content.is_empty() {
+        return None;
+    }
+
+    let array: Vec<Value> = content
+        .iter()
+        .map(|block| match block {
+            ClaudeContentBlock::Text { text } => serde_json::json!({"type": "text", "text": text}),
+            ClaudeContentBlock::ToolUse { id, name, input } => serde_json::json!({
+                "type": "tool_use",
+                "id": id,
+                "name": name,
+                "input": input
+            }),
+            ClaudeContentBlock::ToolResult {
+                tool_use_id,
+                content,
+                is_error,
+            } => serde_json::json!({
+                "type": "tool_result",
+                "tool_use_id": tool_use_id,
+                "content": content,
+                "is_error": is_error
+            }),
+            ClaudeContentBlock::Thinking { thinking } => {
+                serde_json::json!({"type": "thinking", "thinking": thinking})
+            }
+        })
+        .collect();
+
+    Some(Value::Array(array))
+}
+
+/// 创建标准 Claude 消息（Codex / Gemini → Claude 共用）
+fn new_claude_message(
+    session_id: &str,
+    cwd: &str,
+    message_type: &str,
+    role: &str,
+    content: Vec<ClaudeContentBlock>,
+    timestamp: &str,
+    model: Option<String>,
+) -> ClaudeMessage {
+    ClaudeMessage {
+        message_type: message_type.to_string(),
+        message: Some(ClaudeMessageContent {
+            role: role.to_string(),
+            content: claude_content_value(content),
+            usage: None,
+        }),
+        timestamp: Some(timestamp.to_string()),
+        uuid: Some(uuid::Uuid::new_v4().to_string()),
+        parent_uuid: None,
+        session_id: Some(session_id.to_string()),
+        cwd: Some(cwd.to_string()),
+        version: Some("2.0.55".to_string()), // 使用真实版本号，避免被识别为特殊模式
+        git_branch: None,
+        user_type: if role == "user" {
+            Some("external".to_string())
+        } else {
+            None
+        },
+        is_sidechain: Some(false),
+        subtype: None,
+        received_at: if role != "user" {
+            Some(timestamp.to_string())
+        } else {
+            None
+        },
+        sent_at: if role == "user" {
+            Some(timestamp.to_string())
+        } else {
+            None
+        },
+        model,
+        conversion_source: None,
+        extra: HashMap::new(),
+    }
+}
+
+/// file-history-snapshot 消息（Claude session 的首条消息）
+fn file_history_snapshot(timestamp: &str) -> ClaudeMessage {
+    let snapshot_uuid = uuid::Uuid::new_v4().to_string();
+
+    ClaudeMessage {
+        message_type: "file-history-snapshot".to_string(),
+        message: None,
+        timestamp: Some(timestamp.to_string()),
+        uuid: Some(snapshot_uuid.clone()),
+        parent_uuid: None,
+        session_id: None,
+        cwd: None,
+        version: None,
+        git_branch: None,
+        user_type: None,
+        is_sidechain: None,
+        subtype: None,
+        received_at: None,
+        sent_at: None,
+        model: None,
+        conversion_source: None,
+        extra: {
+            let mut map = HashMap::new();
+            map.insert(
+                "messageId".to_string(),
+                Value::String(snapshot_uuid.clone()),
+            );
+            map.insert(
+                "snapshot".to_string(),
+                serde_json::json!({
+                    "messageId": snapshot_uuid,
+                    "trackedFileBackups": {},
+                    "timestamp": timestamp
+                }),
+            );
+            map.insert("isSnapshotUpdate".to_string(), Value::Bool(false));
+            map
+        },
+    }
+}
+
+// ================================
+// 流式转换基础设施
+// ================================
+
+/// 每处理多少条源消息上报一次进度
+const PROGRESS_INTERVAL: usize = 500;
+
+/// 取消时返回的错误信息
+const CANCELLED_MESSAGE: &str = "转换已取消";
+
+/// 进行中的转换：conversion_id → 取消标记
+static ACTIVE_CONVERSIONS: Lazy<Mutex<HashMap<String, Arc<AtomicBool>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// 转换进度 - conversion:progress 事件载荷
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConversionProgress {
+    /// 转换 ID（取消时使用）
+    pub conversion_id: String,
+    /// 源 Session ID
+    pub session_id: String,
+    /// 已处理的源消息数
+    pub processed: usize,
+    /// 预估源消息总数（按已读字节占文件大小的比例估算，完成时为实际值）
+    pub estimated_total: usize,
+    /// 是否已处理完全部消息
+    pub done: bool,
+}
+
+/// 转换上下文 - 负责进度上报与取消检查
+pub struct ConversionContext {
+    conversion_id: String,
+    cancelled: Arc<AtomicBool>,
+    on_progress: Box<dyn Fn(ConversionProgress) + Send + Sync>,
+    /// 目标会话保留完整工具结果，不外置为 blob
+    inline_blobs: bool,
+}
+
+impl ConversionContext {
+    pub fn new(
+        conversion_id: String,
+        cancelled: Arc<AtomicBool>,
+        on_progress: impl Fn(ConversionProgress) + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            conversion_id,
+            cancelled,
+            on_progress: Box::new(on_progress),
+            inline_blobs: false,
+        }
+    }
+
+    /// 设置目标会话是否内联完整工具结果（默认外置超过阈值的工具结果）
+    pub fn with_inline_blobs(mut self, inline_blobs: bool) -> Self {
+        self.inline_blobs = inline_blobs;
+        self
+    }
+
+    /// 不上报进度、不可取消的上下文（导入等内部流程使用）
+    fn detached() -> Self {
+        Self::new(String::new(), Arc::new(AtomicBool::new(false)), |_| {})
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+/// Claude session 扫描摘要（生成 session_meta 与完整性校验所需的信息）
+#[derive(Debug, Default)]
+struct ClaudeSessionSummary {
+    message_count: usize,
+    first_timestamp: Option<String>,
+    model: Option<String>,
+    last_message_type: Option<String>,
+    corrupted_lines: Vec<CorruptedLine>,
+}
+
+/// 进度跟踪：每 PROGRESS_INTERVAL 条消息上报一次
+struct ProgressTracker<'a> {
+    ctx: &'a ConversionContext,
+    session_id: &'a str,
+    total_bytes: u64,
+    bytes_read: u64,
+    processed: usize,
+}
+
+impl<'a> ProgressTracker<'a> {
+    fn new(ctx: &'a ConversionContext, session_id: &'a str, source: &Path) -> Self {
+        Self {
+            ctx,
+            session_id,
+            total_bytes: std::fs::metadata(source).map(|m| m.len()).unwrap_or(0),
+            bytes_read: 0,
+            processed: 0,
+        }
+    }
+
+    /// 记录处理完一条消息，`bytes_read` 为截至该消息已读取的字节数
+    fn tick(&mut self, bytes_read: u64) {
+        self.bytes_read = bytes_read;
+        self.processed += 1;
+        if self.processed.is_multiple_of(PROGRESS_INTERVAL) {
+            self.report(false);
+        }
+    }
+
+    fn finish(&self) {
+        self.report(true);
+    }
+
+    fn report(&self, done: bool) {
+        let estimated_total = if done || self.bytes_read == 0 || self.bytes_read >= self.total_bytes
+        {
+            self.processed
+        } else {
+            (self.processed as f64 * self.total_bytes as f64 / self.bytes_read as f64).round()
+                as usize
+        };
+
+        (self.ctx.on_progress)(ConversionProgress {
+            conversion_id: self.ctx.conversion_id.clone(),
+            session_id: self.session_id.to_string(),
+            processed: self.processed,
+            estimated_total,
+            done,
+        });
+    }
+}
+
+/// 逐行解析源 JSONL 文件，每次只持有一条消息
+///
+/// - 空行跳过，解析失败的行记录警告后跳过，非法 UTF-8 先按有损解码再尝试
+/// - 每行检查取消标记，已取消时返回 CANCELLED_MESSAGE
+/// - 回调参数为 (消息, 截至该行已读取的字节数)
+/// - 返回跳过（或有损解码后保留）的损坏行
+fn for_each_message<T, F>(
+    source: &Path,
+    ctx: &ConversionContext,
+    mut f: F,
+) -> Result<Vec<CorruptedLine>, String>
+where
+    T: DeserializeOwned,
+    F: FnMut(T, u64) -> Result<(), String>,
+{
+    let mut bytes_read: u64 = 0;
+    let mut corrupted_lines = Vec::new();
+    let mut result = Ok(());
+    let blob_dir = attachments_dir(source);
+
+    for_each_raw_line(source, |line_idx, line| {
+        bytes_read += line.len() as u64 + 1;
+
+        if ctx.is_cancelled() {
+            result = Err(CANCELLED_MESSAGE.to_string());
+            return ControlFlow::Break(());
+        }
+
+        let (item, corrupted) = parse_message_line::<T>(line_idx, line, &blob_dir);
+        if let Some(corrupted) = corrupted {
+            log::warn!(
+                "Corrupted session line {} ({:?}, recovered: {})",
+                corrupted.line,
+                corrupted.kind,
+                corrupted.recovered
+            );
+            corrupted_lines.push(corrupted);
+        }
+        if let Some(item) = item {
+            if let Err(e) = f(item, bytes_read) {
+                result = Err(e);
+                return ControlFlow::Break(());
+            }
+        }
+        ControlFlow::Continue(())
+    })
+    .map_err(|e| format!("Failed to read session file: {}", e))?;
+
+    result.map(|()| corrupted_lines)
+}
+
+/// 解析一行源消息；含外置占位的行先内联还原工具结果，再按消息结构解析
+fn parse_message_line<T: DeserializeOwned>(
+    line_idx: usize,
+    line: &[u8],
+    blob_dir: &Path,
+) -> (Option<T>, Option<CorruptedLine>) {
+    let marker = BLOB_SCHEME.as_bytes();
+    if !line.windows(marker.len()).any(|w| w == marker) {
+        return parse_json_line::<T>(line_idx, line);
+    }
+    let (value, corrupted) = parse_json_line::<Value>(line_idx, line);
+    let inlined = value.and_then(|mut value| {
+        inline_blobs(&mut value, blob_dir);
+        serde_json::from_value::<T>(value).ok()
+    });
+    match inlined {
+        Some(item) => (Some(item), corrupted),
+        None => parse_json_line::<T>(line_idx, line),
+    }
+}
+
+/// 流式写出目标文件；写入失败或被取消时删除写了一半的文件
+fn write_streaming<F>(target: &Path, write_body: F) -> Result<usize, String>
+where
+    F: FnOnce(&mut BufWriter<std::fs::File>) -> Result<usize, String>,
+{
+    if let Some(parent) = target.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create target directory: {}", e))?;
+    }
+
+    let file = std::fs::File::create(target)
+        .map_err(|e| format!("Failed to create session file: {}", e))?;
+    let mut writer = BufWriter::new(file);
+
+    let result = write_body(&mut writer).and_then(|count| {
+        writer
+            .flush()
+            .map(|_| count)
+            .map_err(|e| format!("Failed to flush session file: {}", e))
+    });
+
+    if let Err(ref e) = result {
+        drop(writer);
+        if let Err(remove_err) = std::fs::remove_file(target) {
+            log::warn!(
+                "Failed to remove partial session file {:?}: {}",
+                target,
+                remove_err
+            );
+        }
+        log::info!(
+            "Conversion aborted, removed partial file {:?}: {}",
+            target,
+            e
+        );
+    }
+
+    result
+}
+
+/// 写入一行 JSONL
+fn write_json_line<T: Serialize>(writer: &mut impl Write, value: &T) -> Result<(), String> {
+    let line =
+        serde_json::to_string(value).map_err(|e| format!("Failed to serialize line: {}", e))?;
+    writeln!(writer, "{}", line).map_err(|e| format!("Failed to write line: {}", e))
+}
+
+/// 转换到 Claude 时建立 parentUuid 消息链并写出（file-history-snapshot 不经过这里）
+fn write_linked(
+    writer: &mut impl Write,
+    prev_uuid: &mut Option<String>,
+    mut msg: ClaudeMessage,
+) -> Result<(), String> {
+    // 设置 parent_uuid 指向前一条消息
+    msg.parent_uuid = prev_uuid.take();
+    // 更新 prev_uuid 为当前消息的 uuid
+    *prev_uuid = msg.uuid.clone();
+    write_json_line(writer, &msg)
+}
+
+// ================================
+// Tauri Commands
+// ================================
+
+/// 根据文件存在性判断 session 的源引擎类型
+fn detect_session_engine(
+    session_id: &str,
+    project_id: &str,
+    project_path: &str,
+) -> Result<String, String> {
+    // 依次查找 Codex sessions 目录、Claude projects 目录、Gemini tmp/<项目哈希>/chats 目录
+    for (engine, project_id_or_path) in [
+        ("codex", project_id),
+        ("claude", project_id),
+        ("gemini", project_path),
+    ] {
+        if let Ok(Some(_)) = find_session_path(engine, session_id, project_id_or_path) {
+            return Ok(engine.to_string());
+        }
+    }
+
+    Err(t_with(
+        MessageKey::ConversionSessionNotFound,
+        &[("sessionId", &session_id)],
+    ))
+}
+
+/// 校验外部 session 文件：每个非空行都必须能被对应引擎的消息结构解析
+///
+/// 返回有效条目数与首个条目
+fn validate_import_file<T: DeserializeOwned>(
+    source: &Path,
+    engine: &str,
+) -> Result<(usize, Option<T>), String> {
+    let mut count = 0;
+    let mut first = None;
+    let mut result = Ok(());
+
+    for_each_line(source, |line_idx, line| {
+        if line.trim().is_empty() {
+            return ControlFlow::Continue(());
+        }
+        match serde_json::from_str::<T>(line) {
+            Ok(item) => {
+                if first.is_none() {
+                    first = Some(item);
+                }
+                count += 1;
+                ControlFlow::Continue(())
+            }
+            Err(e) => {
+                result = Err(format!(
+                    "Line {} is not a valid {} session entry: {}",
+                    line_idx + 1,
+                    engine,
+                    e
+                ));
+                ControlFlow::Break(())
+            }
+        }
+    })
+    .map_err(|e| format!("Failed to read session file: {}", e))?;
+
+    result?;
+    if count == 0 {
+        return Err(t_with(
+            MessageKey::ConversionSessionEmpty,
+            &[("engine", &engine)],
+        ));
+    }
+    Ok((count, first))
+}
+
+/// 复制 session 文件，逐行交给 `rewrite` 修改（替换 session id）后写出，超过阈值的工具结果外置；
+/// 返回写出的行数
+fn copy_session_file<F>(source: &Path, target: &Path, mut rewrite: F) -> Result<usize, String>
+where
+    F: FnMut(usize, &mut Value),
+{
+    let ctx = ConversionContext::detached();
+    let count = write_streaming(target, |writer| {
+        let mut count = 0;
+        for_each_message::<Value, _>(source, &ctx, |mut entry, _| {
+            rewrite(count, &mut entry);
+            write_json_line(writer, &entry)?;
+            count += 1;
+            Ok(())
+        })?;
+        Ok(count)
+    })?;
+    externalize_session_file(target, DEFAULT_BLOB_THRESHOLD)?;
+    Ok(count)
+}
+
+/// 导入 Codex session：首行必须是 session_meta，写入当天的日期目录并换用新的 session id
+fn import_codex_file(source: &Path, target: &Path, new_session_id: &str) -> Result<usize, String> {
+    let (_, first) = validate_import_file::<CodexEvent>(source, "Codex")?;
+    let has_meta = first.is_some_and(|event| {
+        event.event_type == "session_meta" && event.payload.as_ref().is_some_and(|p| p.is_object())
+    });
+    if !has_meta {
+        return Err("Codex session file must start with a session_meta event".to_string());
+    }
+
+    copy_session_file(source, target, |index, event| {
+        if index == 0 {
+            if let Some(payload) = event.get_mut("payload").and_then(|p| p.as_object_mut()) {
+                payload.insert("id".to_string(), Value::String(new_session_id.to_string()));
+            }
+        }
+    })
+}
+
+/// 导入 Claude session：写入项目目录，并把每条消息的 sessionId 换成新的 session id
+fn import_claude_file(source: &Path, target: &Path, new_session_id: &str) -> Result<usize, String> {
+    validate_import_file::<ClaudeMessage>(source, "Claude")?;
+
+    copy_session_file(source, target, |_, message| {
+        if let Some(message) = message.as_object_mut() {
+            if message.contains_key("sessionId") {
+                message.insert(
+                    "sessionId".to_string(),
+                    Value::String(new_session_id.to_string()),
+                );
+            }
+        }
+    })
+}
+
+/// 导入外部 session 文件（如同事分享的会话）到应用管理的目录
+///
+/// 文件先按引擎的消息结构完整校验，再以新生成的 session id 写入（Codex 按日期目录，
+/// Claude 写入 `projects/<project_id>/`），不会与已有会话冲突；返回新的 session id
+#[tauri::command]
+pub async fn import_session_file(
+    file_path: String,
+    engine: String,
+    project_id: Option<String>,
+) -> Result<String, String> {
+    log::info!("Importing {} session file: {}", engine, file_path);
+
+    let source = PathBuf::from(&file_path);
+    if !source.is_file() {
+        return Err(format!("Session file not found: {}", file_path));
+    }
+
+    tokio::task::spawn_blocking(move || {
+        let new_session_id = uuid::Uuid::new_v4().to_string();
+        let (target, count) = match engine.as_str() {
+            "codex" => {
+                let target = codex_dated_session_path(&codex_session_filename(&new_session_id))?;
+                let count = import_codex_file(&source, &target, &new_session_id)?;
+                (target, count)
+            }
+            "claude" => {
+                let project_id = project_id
+                    .filter(|id| !id.trim().is_empty())
+                    .ok_or("Project ID is required for Claude sessions")?;
+                if project_id.contains(['/', '\\']) || project_id.contains("..") {
+                    return Err(format!("Invalid project ID: {}", project_id));
+                }
+                let target = claude_session_target_path(&project_id, &new_session_id)?;
+                let count = import_claude_file(&source, &target, &new_session_id)?;
+                (target, count)
+            }
+            _ => return Err(format!("Unknown engine: {}", engine)),
+        };
+
+        log::info!(
+            "Imported {} lines as session {} at {:?}",
+            count,
+            new_session_id,
+            target
+        );
+        Ok(new_session_id)
+    })
+    .await
+    .map_err(|e| format!("Import task failed: {}", e))?
+}
+
+/// 转换单个会话（阻塞执行）：检测源引擎后按目标引擎转换，`dry_run` 时不写出文件
+fn convert_one(
+    session_id: &str,
+    target_engine: &str,
+    project_id: &str,
+    project_path: &str,
+    dry_run: bool,
+    ctx: &ConversionContext,
+) -> Result<ConversionResult, String> {
+    // 根据文件存在性检测源引擎
+    let source_engine = detect_session_engine(session_id, project_id, project_path)?;
+
+    if source_engine == target_engine {
+        return Err(t_with(
+            MessageKey::ConversionAlreadyTargetEngine,
+            &[("sessionId", &session_id), ("engine", &target_engine)],
+        ));
+    }
+
+    let (session_id, project_id, project_path) = (
+        session_id.to_string(),
+        project_id.to_string(),
+        project_path.to_string(),
+    );
+    let result = match (source_engine.as_str(), target_engine) {
+        ("claude", "codex") => {
+            let converter = ClaudeToCodexConverter::new(session_id, project_id, project_path);
+            if dry_run {
+                converter.dry_run(ctx)
+            } else {
+                converter.convert(ctx)
+            }
+        }
+        ("codex", "claude") => {
             let converter = CodexToClaudeConverter::new(session_id, project_id, project_path);
-            converter.convert()
+            if dry_run {
+                converter.dry_run(ctx)
+            } else {
+                converter.convert(ctx)
+            }
+        }
+        ("gemini", "claude") => {
+            let converter = GeminiToClaudeConverter::new(session_id, project_id, project_path);
+            if dry_run {
+                converter.dry_run(ctx)
+            } else {
+                converter.convert(ctx)
+            }
+        }
+        ("claude", "gemini") => {
+            let converter = ClaudeToGeminiConverter::new(session_id, project_id, project_path);
+            if dry_run {
+                converter.dry_run(ctx)
+            } else {
+                converter.convert(ctx)
+            }
+        }
+        (_, "claude" | "codex" | "gemini") => Err(format!(
+            "Unsupported conversion: {} → {}",
+            source_engine, target_engine
+        )),
+        _ => Err(format!("Unknown target engine: {}", target_engine)),
+    }?;
+
+    // Gemini 会话为单个 JSON 文件，不做外置
+    if !dry_run && !ctx.inline_blobs && target_engine != "gemini" {
+        externalize_session_file(Path::new(&result.target_path), DEFAULT_BLOB_THRESHOLD)?;
+    }
+    Ok(result)
+}
+
+/// 统一转换接口
+///
+/// 转换在阻塞线程池中流式执行，期间每处理 500 条消息发送 `conversion:progress` 事件；
+/// 传入 `conversion_id` 后可通过 `cancel_conversion` 取消。
+/// `dry_run` 为 true 时完整执行转换与校验但不写出文件，返回预计的转换结果。
+/// 源会话中外置的工具结果读取时内联还原；目标会话默认重新外置超过阈值的工具结果，
+/// `inline_blobs` 为 true 时保留全文
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn convert_session(
+    app: AppHandle,
+    session_id: String,
+    target_engine: String,
+    project_id: String,
+    project_path: String,
+    conversion_id: Option<String>,
+    dry_run: Option<bool>,
+    inline_blobs: Option<bool>,
+) -> Result<ConversionResult, String> {
+    log::info!(
+        "Converting session {} to engine: {}, project_id: {}, project_path: {}",
+        session_id,
+        target_engine,
+        project_id,
+        project_path
+    );
+
+    let conversion_id = conversion_id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+    let cancelled = Arc::new(AtomicBool::new(false));
+    ACTIVE_CONVERSIONS
+        .lock()
+        .unwrap()
+        .insert(conversion_id.clone(), cancelled.clone());
+
+    let ctx = ConversionContext::new(conversion_id.clone(), cancelled, move |progress| {
+        let _ = app.emit("conversion:progress", &progress);
+    })
+    .with_inline_blobs(inline_blobs.unwrap_or(false));
+
+    let result = tokio::task::spawn_blocking(move || {
+        convert_one(
+            &session_id,
+            &target_engine,
+            &project_id,
+            &project_path,
+            dry_run.unwrap_or(false),
+            &ctx,
+        )
+    })
+    .await
+    .map_err(|e| format!("Conversion task failed: {}", e));
+
+    ACTIVE_CONVERSIONS.lock().unwrap().remove(&conversion_id);
+    result?
+}
+
+// ================================
+// 批量转换
+// ================================
+
+/// 批量转换的最大并发数
+const MAX_BATCH_CONCURRENCY: usize = 2;
+
+/// 查找转换来源标记时在 Claude 会话开头读取的行数
+/// （标记位于 session_meta 转换出的 system 消息，紧跟 file-history-snapshot）
+const CONVERSION_MARKER_SCAN_LINES: usize = 5;
+
+/// 批量转换选项
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct BatchConversionOptions {
+    /// 只试运行并汇总预览，不写出文件
+    pub dry_run: bool,
+    /// 目标目录已有来自同一会话的转换产物时仍然转换
+    pub force: bool,
+    /// 并发数（1 或 2，默认 1）
+    pub concurrency: Option<usize>,
+    /// 批量 ID（进度事件与 cancel_conversion 使用），不传时自动生成
+    pub batch_id: Option<String>,
+}
+
+/// 批量转换中单个会话的结果状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BatchItemStatus {
+    /// 已转换（试运行时为可转换）
+    Converted,
+    /// 已有转换产物，跳过
+    Skipped,
+    /// 转换失败
+    Failed,
+    /// 批量被取消，未转换
+    Cancelled,
+}
+
+/// 批量转换中单个会话的结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchConversionItem {
+    /// 源 Session ID
+    pub session_id: String,
+    pub status: BatchItemStatus,
+    /// 转换结果（仅 Converted）
+    pub result: Option<ConversionResult>,
+    /// 失败或跳过的原因
+    pub error: Option<String>,
+}
+
+impl BatchConversionItem {
+    fn new(session_id: &str, status: BatchItemStatus, error: Option<String>) -> Self {
+        Self {
+            session_id: session_id.to_string(),
+            status,
+            result: None,
+            error,
+        }
+    }
+}
+
+/// 批量转换汇总报告
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchConversionReport {
+    pub batch_id: String,
+    pub target_engine: String,
+    pub dry_run: bool,
+    pub total: usize,
+    pub converted: usize,
+    pub skipped: usize,
+    pub failed: usize,
+    /// 批量是否被取消
+    pub cancelled: bool,
+    /// 按输入顺序排列的每个会话结果
+    pub items: Vec<BatchConversionItem>,
+}
+
+/// 批量转换进度 - conversion:batch-progress 事件载荷（每个会话处理完发送一次）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchConversionProgress {
+    pub batch_id: String,
+    /// 刚处理完的源 Session ID
+    pub session_id: String,
+    pub status: BatchItemStatus,
+    pub error: Option<String>,
+    /// 已处理的会话数（含本次）
+    pub completed: usize,
+    pub total: usize,
+}
+
+/// 按顺序（或最多 MAX_BATCH_CONCURRENCY 个并发）处理会话
+///
+/// 单个会话失败不影响其余会话；取消后不再开始新的会话，未开始的会话标记为 Cancelled。
+/// `on_done` 参数为 (结果, 已处理数)；返回值与 `session_ids` 顺序一致
+fn run_batch<F, P>(
+    session_ids: &[String],
+    concurrency: usize,
+    cancelled: &AtomicBool,
+    convert: F,
+    on_done: P,
+) -> Vec<BatchConversionItem>
+where
+    F: Fn(&str) -> BatchConversionItem + Sync,
+    P: Fn(&BatchConversionItem, usize) + Sync,
+{
+    let next = AtomicUsize::new(0);
+    let completed = AtomicUsize::new(0);
+    let slots: Mutex<Vec<Option<BatchConversionItem>>> =
+        Mutex::new(session_ids.iter().map(|_| None).collect());
+
+    std::thread::scope(|scope| {
+        for _ in 0..concurrency.clamp(1, MAX_BATCH_CONCURRENCY) {
+            scope.spawn(|| loop {
+                if cancelled.load(Ordering::Relaxed) {
+                    break;
+                }
+                let index = next.fetch_add(1, Ordering::SeqCst);
+                let Some(session_id) = session_ids.get(index) else {
+                    break;
+                };
+
+                let item = convert(session_id);
+                on_done(&item, completed.fetch_add(1, Ordering::SeqCst) + 1);
+                slots.lock().unwrap()[index] = Some(item);
+            });
+        }
+    });
+
+    slots
+        .into_inner()
+        .unwrap()
+        .into_iter()
+        .zip(session_ids)
+        .map(|(slot, session_id)| {
+            slot.unwrap_or_else(|| {
+                BatchConversionItem::new(session_id, BatchItemStatus::Cancelled, None)
+            })
+        })
+        .collect()
+}
+
+/// 读取转换产物中记录的源 Session ID（Codex 的 session_meta 或 Claude 的 conversionSource）
+fn conversion_marker(path: &Path, max_lines: usize) -> Option<String> {
+    use std::io::{BufRead, BufReader};
+
+    let file = std::fs::File::open(path).ok()?;
+    BufReader::new(file)
+        .lines()
+        .take(max_lines)
+        .map_while(Result::ok)
+        .find_map(|line| {
+            let value: Value = serde_json::from_str(&line).ok()?;
+            let source = value
+                .get("conversionSource")
+                .or_else(|| value.get("payload")?.get("conversion_source"))?;
+            source
+                .get("sessionId")
+                .or_else(|| source.get("session_id"))?
+                .as_str()
+                .map(String::from)
+        })
+}
+
+/// 目标目录中已有转换产物的源 Session ID 集合
+fn converted_source_ids(target_dir: &Path, max_lines: usize) -> HashSet<String> {
+    walkdir::WalkDir::new(target_dir)
+        .into_iter()
+        .flatten()
+        .filter(|entry| entry.path().extension().and_then(|s| s.to_str()) == Some("jsonl"))
+        .filter_map(|entry| conversion_marker(entry.path(), max_lines))
+        .collect()
+}
+
+/// 列出项目下指定引擎的全部会话 ID
+async fn list_project_sessions(
+    engine: &str,
+    project_id: &str,
+    project_path: &str,
+) -> Result<Vec<String>, String> {
+    match engine {
+        "claude" => {
+            let project_dir = claude_project_dir(project_id)?;
+            let entries = std::fs::read_dir(&project_dir)
+                .map_err(|e| format!("Failed to read project directory: {}", e))?;
+
+            let mut ids: Vec<String> = entries
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|path| path.extension().and_then(|s| s.to_str()) == Some("jsonl"))
+                .filter_map(|path| Some(path.file_stem()?.to_str()?.to_string()))
+                // 子代理会话（agent-*.jsonl）不是独立会话
+                .filter(|id| !id.starts_with("agent-"))
+                .collect();
+            ids.sort();
+            Ok(ids)
+        }
+        "codex" => {
+            let query = super::session::CodexSessionQuery {
+                project_path_filter: Some(project_path.to_string()),
+                ..Default::default()
+            };
+            Ok(
+                super::session::list_codex_sessions(Some(false), Some(query))
+                    .await?
+                    .into_iter()
+                    .map(|session| session.id)
+                    .collect(),
+            )
+        }
+        _ => Err(format!("Unknown source engine: {}", engine)),
+    }
+}
+
+/// 批量转换会话
+///
+/// 转换 `session_ids` 指定的会话；未指定时转换项目下 `source_engine`（默认为目标引擎的另一方）的全部会话。
+/// 每个会话处理完发送 `conversion:batch-progress` 事件，单个失败不中断整体；
+/// 目标目录已有来自同一会话的转换产物时默认跳过（`force` 时仍然转换）。
+/// 以 `batch_id` 调用 `cancel_conversion` 可取消：进行中的会话被中止，未开始的会话标记为已取消
+#[tauri::command]
+pub async fn convert_sessions_batch(
+    app: AppHandle,
+    session_ids: Option<Vec<String>>,
+    project_id: String,
+    project_path: String,
+    source_engine: Option<String>,
+    target_engine: String,
+    options: Option<BatchConversionOptions>,
+) -> Result<BatchConversionReport, String> {
+    let options = options.unwrap_or_default();
+    let target_dir = match target_engine.as_str() {
+        "codex" => super::config::get_codex_sessions_dir()
+            .map_err(|e| format!("Failed to get Codex sessions directory: {}", e))?,
+        "claude" => claude_project_dir(&project_id)?,
+        _ => return Err(format!("Unknown target engine: {}", target_engine)),
+    };
+
+    let mut session_ids = match session_ids {
+        Some(ids) => ids,
+        None => {
+            let default_source = if target_engine == "codex" {
+                "claude"
+            } else {
+                "codex"
+            };
+            let source_engine = source_engine.unwrap_or_else(|| default_source.to_string());
+            list_project_sessions(&source_engine, &project_id, &project_path).await?
+        }
+    };
+    let mut seen = HashSet::new();
+    session_ids.retain(|id| seen.insert(id.clone()));
+
+    let batch_id = options
+        .batch_id
+        .clone()
+        .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+    log::info!(
+        "Batch converting {} sessions to {} (batch {}, dry_run: {}, force: {})",
+        session_ids.len(),
+        target_engine,
+        batch_id,
+        options.dry_run,
+        options.force
+    );
+
+    let cancelled = Arc::new(AtomicBool::new(false));
+    ACTIVE_CONVERSIONS
+        .lock()
+        .unwrap()
+        .insert(batch_id.clone(), cancelled.clone());
+
+    let report = {
+        let batch_id = batch_id.clone();
+        tokio::task::spawn_blocking(move || {
+            let max_lines = if target_engine == "codex" {
+                1
+            } else {
+                CONVERSION_MARKER_SCAN_LINES
+            };
+            let converted = if options.force {
+                HashSet::new()
+            } else {
+                converted_source_ids(&target_dir, max_lines)
+            };
+
+            let convert = |session_id: &str| {
+                if converted.contains(session_id) {
+                    return BatchConversionItem::new(
+                        session_id,
+                        BatchItemStatus::Skipped,
+                        Some(format!("已存在转换到 {} 的会话", target_engine)),
+                    );
+                }
+
+                let app = app.clone();
+                let ctx = ConversionContext::new(batch_id.clone(), cancelled.clone(), move |p| {
+                    let _ = app.emit("conversion:progress", &p);
+                });
+                match convert_one(
+                    session_id,
+                    &target_engine,
+                    &project_id,
+                    &project_path,
+                    options.dry_run,
+                    &ctx,
+                ) {
+                    Ok(result) => BatchConversionItem {
+                        result: Some(result),
+                        ..BatchConversionItem::new(session_id, BatchItemStatus::Converted, None)
+                    },
+                    Err(e) if e == CANCELLED_MESSAGE => {
+                        BatchConversionItem::new(session_id, BatchItemStatus::Cancelled, None)
+                    }
+                    Err(e) => {
+                        log::warn!("Batch conversion of {} failed: {}", session_id, e);
+                        BatchConversionItem::new(session_id, BatchItemStatus::Failed, Some(e))
+                    }
+                }
+            };
+
+            let total = session_ids.len();
+            let items = run_batch(
+                &session_ids,
+                options.concurrency.unwrap_or(1),
+                &cancelled,
+                convert,
+                |item, completed| {
+                    let _ = app.emit(
+                        "conversion:batch-progress",
+                        &BatchConversionProgress {
+                            batch_id: batch_id.clone(),
+                            session_id: item.session_id.clone(),
+                            status: item.status,
+                            error: item.error.clone(),
+                            completed,
+                            total,
+                        },
+                    );
+                },
+            );
+
+            let count = |status| items.iter().filter(|item| item.status == status).count();
+            BatchConversionReport {
+                batch_id,
+                target_engine,
+                dry_run: options.dry_run,
+                total,
+                converted: count(BatchItemStatus::Converted),
+                skipped: count(BatchItemStatus::Skipped),
+                failed: count(BatchItemStatus::Failed),
+                cancelled: cancelled.load(Ordering::Relaxed),
+                items,
+            }
+        })
+        .await
+        .map_err(|e| format!("Batch conversion task failed: {}", e))
+    };
+
+    ACTIVE_CONVERSIONS.lock().unwrap().remove(&batch_id);
+    let report = report?;
+    log::info!(
+        "Batch {} finished: {} converted, {} skipped, {} failed",
+        report.batch_id,
+        report.converted,
+        report.skipped,
+        report.failed
+    );
+    Ok(report)
+}
+
+/// 取消进行中的转换（写了一半的目标文件会被删除）
+///
+/// 返回是否找到对应的转换
+#[tauri::command]
+pub async fn cancel_conversion(conversion_id: String) -> Result<bool, String> {
+    match ACTIVE_CONVERSIONS.lock().unwrap().get(&conversion_id) {
+        Some(cancelled) => {
+            log::info!("Cancelling conversion: {}", conversion_id);
+            cancelled.store(true, Ordering::Relaxed);
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+/// 便捷接口：Claude → Codex
+#[tauri::command]
+pub async fn convert_claude_to_codex(
+    app: AppHandle,
+    session_id: String,
+    project_id: String,
+    project_path: String,
+) -> Result<ConversionResult, String> {
+    convert_session(
+        app,
+        session_id,
+        "codex".to_string(),
+        project_id,
+        project_path,
+        None,
+        None,
+        None,
+    )
+    .await
+}
+
+/// 便捷接口：Codex → Claude
+#[tauri::command]
+pub async fn convert_codex_to_claude(
+    app: AppHandle,
+    session_id: String,
+    project_id: String,
+    project_path: String,
+) -> Result<ConversionResult, String> {
+    convert_session(
+        app,
+        session_id,
+        "claude".to_string(),
+        project_id,
+        project_path,
+        None,
+        None,
+        None,
+    )
+    .await
+}
+
+// ================================
+// 推理块导出
+// ================================
+
+/// 会话中的一段推理内容（Claude thinking / Codex reasoning）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReasoningBlock {
+    /// 推理文本
+    pub text: String,
+    /// 所在消息的时间戳 (ISO 8601)
+    pub timestamp: Option<String>,
+    /// 之前最近一条用户提示词的序号（与提示词列表/撤回一致），出现在首条提示词之前时为 None
+    pub prompt_index: Option<usize>,
+    /// 在会话文件中的行号（从 0 开始）
+    pub line_number: usize,
+}
+
+/// 导出会话中的全部推理块
+///
+/// `project_id` 的含义与 `resolve_session_path` 相同（Codex 忽略）；Gemini 会话不记录推理内容
+#[tauri::command]
+pub async fn export_reasoning(
+    engine: String,
+    session_id: String,
+    project_id: String,
+) -> Result<Vec<ReasoningBlock>, String> {
+    tokio::task::spawn_blocking(move || collect_reasoning_blocks(&engine, &session_id, &project_id))
+        .await
+        .map_err(|e| format!("Reasoning export task failed: {}", e))?
+}
+
+fn collect_reasoning_blocks(
+    engine: &str,
+    session_id: &str,
+    project_id: &str,
+) -> Result<Vec<ReasoningBlock>, String> {
+    if engine != "claude" && engine != "codex" {
+        return Err(format!(
+            "Reasoning export is not supported for engine: {}",
+            engine
+        ));
+    }
+
+    let session_path = resolve_session_file(engine, session_id, project_id)?;
+    let (mut blocks, prompts) = if engine == "claude" {
+        let prompts =
+            extract_prompts_from_jsonl(session_id, project_id).map_err(|e| e.to_string())?;
+        (read_claude_reasoning(&session_path)?, prompts)
+    } else {
+        let prompts = extract_codex_prompts_from_file(&session_path)?;
+        (read_codex_reasoning(&session_path)?, prompts)
+    };
+
+    assign_prompt_indices(&mut blocks, &prompts);
+    Ok(blocks)
+}
+
+/// 读取 Claude 会话中的 thinking 块（跳过子代理的侧链消息）
+fn read_claude_reasoning(session_path: &Path) -> Result<Vec<ReasoningBlock>, String> {
+    let mut blocks = Vec::new();
+
+    for_each_line(session_path, |line_idx, line| {
+        let Ok(msg) = serde_json::from_str::<ClaudeMessage>(line) else {
+            return ControlFlow::Continue(());
+        };
+        if msg.message_type != "assistant" || msg.is_sidechain == Some(true) {
+            return ControlFlow::Continue(());
+        }
+
+        if let Some(message) = &msg.message {
+            for block in parse_claude_content_blocks(&message.content) {
+                if let ClaudeContentBlock::Thinking { thinking } = block {
+                    if !thinking.trim().is_empty() {
+                        blocks.push(ReasoningBlock {
+                            text: thinking,
+                            timestamp: msg.timestamp.clone(),
+                            prompt_index: None,
+                            line_number: line_idx,
+                        });
+                    }
+                }
+            }
+        }
+        ControlFlow::Continue(())
+    })
+    .map_err(|e| format!("Failed to read session file: {}", e))?;
+
+    Ok(blocks)
+}
+
+/// 读取 Codex 会话 event_msg 中的 reasoning 块
+fn read_codex_reasoning(session_path: &Path) -> Result<Vec<ReasoningBlock>, String> {
+    let mut blocks = Vec::new();
+
+    for_each_line(session_path, |line_idx, line| {
+        let Ok(event) = serde_json::from_str::<CodexEvent>(line) else {
+            return ControlFlow::Continue(());
+        };
+        if event.event_type != "event_msg" {
+            return ControlFlow::Continue(());
+        }
+
+        if let Some(text) = event.payload.as_ref().and_then(codex_reasoning_text) {
+            if !text.trim().is_empty() {
+                blocks.push(ReasoningBlock {
+                    text: text.to_string(),
+                    timestamp: event.timestamp.clone(),
+                    prompt_index: None,
+                    line_number: line_idx,
+                });
+            }
+        }
+        ControlFlow::Continue(())
+    })
+    .map_err(|e| format!("Failed to read session file: {}", e))?;
+
+    Ok(blocks)
+}
+
+/// event_msg 中的推理文本：rollout 文件的 agent_reasoning，或 exec --json 的 reasoning item
+fn codex_reasoning_text(payload: &Value) -> Option<&str> {
+    if payload.get("type").and_then(|t| t.as_str()) == Some("agent_reasoning") {
+        return payload.get("text").and_then(|t| t.as_str());
+    }
+
+    let item = payload.get("item")?;
+    if item.get("type").and_then(|t| t.as_str()) == Some("reasoning") {
+        return item.get("text").and_then(|t| t.as_str());
+    }
+    None
+}
+
+/// 为每个推理块标注之前最近一条用户提示词的序号
+fn assign_prompt_indices(blocks: &mut [ReasoningBlock], prompts: &[PromptRecord]) {
+    for block in blocks.iter_mut() {
+        let preceding = prompts.partition_point(|p| p.line_number < block.line_number);
+        block.prompt_index = preceding.checked_sub(1).map(|i| prompts[i].index);
+    }
+}
+
+// ================================
+// Markdown 导出
+// ================================
+
+/// 渲染为 bash 代码块的 shell 类工具（按小写名称匹配）
+const SHELL_TOOL_NAMES: &[&str] = &[
+    "bash",
+    "shell",
+    "shell_command",
+    "exec_command",
+    "local_shell",
+];
+
+/// 将 Claude 会话导出为 Markdown 文件，返回写入的路径
+///
+/// 用户消息渲染为 `**User:**` 段落，助手文本渲染为 `**Claude:**` 段落；
+/// `include_tool_calls` 为 true 时工具调用渲染为代码块、工具结果渲染为可折叠的 `<details>`，
+/// 为 false 时两者都省略。thinking 块与子代理的侧链消息不导出。
+#[tauri::command]
+pub async fn export_session_to_markdown(
+    session_id: String,
+    project_id: String,
+    output_path: String,
+    include_tool_calls: bool,
+) -> Result<String, String> {
+    let output_path = resolve_export_target("output_path", &output_path)?;
+    tokio::task::spawn_blocking(move || {
+        let source = claude_session_source_path(&project_id, &session_id)?;
+        let markdown = render_transcript_markdown(
+            &format!("Claude Session {}", session_id),
+            &read_claude_transcript(&source)?,
+            include_tool_calls,
+            MarkdownStyle::Dialogue,
+        );
+        write_streaming(&output_path, |writer| {
+            writer
+                .write_all(markdown.as_bytes())
+                .map(|_| markdown.len())
+                .map_err(|e| format!("Failed to write markdown file: {}", e))
+        })?;
+        log::info!(
+            "Exported Claude session {} to markdown: {:?}",
+            session_id,
+            output_path
+        );
+        Ok(output_path.to_string_lossy().into_owned())
+    })
+    .await
+    .map_err(|e| format!("Markdown export task failed: {}", e))?
+}
+
+/// 将 Claude 或 Codex 会话按时间顺序导出为 Markdown
+///
+/// 用户消息渲染为引用块，助手文本原样输出，工具调用渲染为带语言标注的代码块（命令 + 输出），
+/// thinking / reasoning 折叠在 `<details>` 中。未指定 `output_path` 时返回 Markdown 字符串，
+/// 否则写入该文件并返回写入的路径。
+#[tauri::command]
+pub async fn export_session_markdown(
+    session_id: String,
+    project_id: String,
+    engine: String,
+    output_path: Option<String>,
+) -> Result<String, String> {
+    let output_path = output_path
+        .filter(|path| !path.trim().is_empty())
+        .map(|path| resolve_export_target("output_path", &path))
+        .transpose()?;
+
+    tokio::task::spawn_blocking(move || {
+        let (title, transcript) = match engine.as_str() {
+            "claude" => {
+                let source = claude_session_source_path(&project_id, &session_id)?;
+                ("Claude", read_claude_transcript(&source)?)
+            }
+            "codex" => {
+                let source = resolve_session_file("codex", &session_id, &project_id)?;
+                ("Codex", read_codex_transcript(&source)?)
+            }
+            other => {
+                return Err(format!(
+                    "Markdown export is not supported for engine: {}",
+                    other
+                ))
+            }
+        };
+        let markdown = render_transcript_markdown(
+            &format!("{} Session {}", title, session_id),
+            &transcript,
+            true,
+            MarkdownStyle::Transcript,
+        );
+
+        let Some(output_path) = output_path else {
+            return Ok(markdown);
+        };
+        write_streaming(&output_path, |writer| {
+            writer
+                .write_all(markdown.as_bytes())
+                .map(|_| markdown.len())
+                .map_err(|e| format!("Failed to write markdown file: {}", e))
+        })?;
+        log::info!(
+            "Exported {} session {} to markdown: {:?}",
+            title,
+            session_id,
+            output_path
+        );
+        Ok(output_path.to_string_lossy().into_owned())
+    })
+    .await
+    .map_err(|e| format!("Markdown export task failed: {}", e))?
+}
+
+/// 导出用的对话条目，Claude 与 Codex 会话都先读成这一中间表示再渲染
+#[derive(Debug, Clone, PartialEq)]
+enum TranscriptEntry {
+    User(String),
+    Assistant(String),
+    Reasoning(String),
+    Tool(TranscriptToolCall),
+}
+
+/// 工具调用及其结果；找不到对应调用的结果 `name` 为空、`input` 为 null
+#[derive(Debug, Clone, PartialEq)]
+struct TranscriptToolCall {
+    name: String,
+    input: Value,
+    output: Option<String>,
+    is_error: bool,
+}
+
+/// Markdown 导出的版式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MarkdownStyle {
+    /// `**User:**` / `**Claude:**` 段落，工具参数为 JSON 代码块、结果折叠在 `<details>` 中，不含 thinking
+    Dialogue,
+    /// 按时间顺序的记录：用户消息为引用块，推理折叠，工具调用为带语言标注的命令 + 输出
+    Transcript,
+}
+
+#[derive(Default)]
+struct Transcript {
+    entries: Vec<TranscriptEntry>,
+    /// 工具调用 id → `entries` 中的下标，用于把结果配对回调用
+    calls: HashMap<String, usize>,
+}
+
+impl Transcript {
+    fn push_text(&mut self, entry: fn(String) -> TranscriptEntry, text: &str) {
+        if !text.trim().is_empty() {
+            self.entries.push(entry(text.trim().to_string()));
         }
-        _ => Err(format!("Unknown target engine: {}", target_engine)),
+    }
+
+    fn push_call(&mut self, id: &str, name: &str, input: &Value) {
+        self.calls.insert(id.to_string(), self.entries.len());
+        self.entries.push(TranscriptEntry::Tool(TranscriptToolCall {
+            name: name.to_string(),
+            input: input.clone(),
+            output: None,
+            is_error: false,
+        }));
+    }
+
+    fn push_result(&mut self, id: &str, output: String, is_error: bool) {
+        if let Some(TranscriptEntry::Tool(call)) = self
+            .calls
+            .remove(id)
+            .and_then(|index| self.entries.get_mut(index))
+        {
+            call.output = Some(output);
+            call.is_error = is_error;
+            return;
+        }
+        self.entries.push(TranscriptEntry::Tool(TranscriptToolCall {
+            name: String::new(),
+            input: Value::Null,
+            output: Some(output),
+            is_error,
+        }));
     }
 }
 
-/// 便捷接口：Claude → Codex
-#[tauri::command]
-pub async fn convert_claude_to_codex(
-    session_id: String,
-    project_id: String,
-    project_path: String,
-) -> Result<ConversionResult, String> {
-    convert_session(session_id, "codex".to_string(), project_id, project_path).await
+/// 读取 Claude 会话（跳过子代理的侧链消息与 isMeta 消息）
+fn read_claude_transcript(source: &Path) -> Result<Vec<TranscriptEntry>, String> {
+    let mut transcript = Transcript::default();
+
+    for_each_message::<ClaudeMessage, _>(source, &ConversionContext::detached(), |msg, _| {
+        let is_meta = msg.extra.get("isMeta").and_then(|v| v.as_bool()) == Some(true);
+        if msg.is_sidechain == Some(true) || is_meta {
+            return Ok(());
+        }
+        let text_entry: fn(String) -> TranscriptEntry = match msg.message_type.as_str() {
+            "user" => TranscriptEntry::User,
+            "assistant" => TranscriptEntry::Assistant,
+            _ => return Ok(()),
+        };
+        let Some(message) = &msg.message else {
+            return Ok(());
+        };
+
+        for block in parse_claude_content_blocks(&message.content) {
+            match block {
+                ClaudeContentBlock::Text { text } => transcript.push_text(text_entry, &text),
+                ClaudeContentBlock::Thinking { thinking } => {
+                    transcript.push_text(TranscriptEntry::Reasoning, &thinking)
+                }
+                ClaudeContentBlock::ToolUse { id, name, input } => {
+                    transcript.push_call(&id, &name, &input)
+                }
+                ClaudeContentBlock::ToolResult {
+                    tool_use_id,
+                    content,
+                    is_error,
+                } => transcript.push_result(
+                    &tool_use_id,
+                    tool_result_markdown_text(&content),
+                    is_error == Some(true),
+                ),
+            }
+        }
+        Ok(())
+    })?;
+
+    Ok(transcript.entries)
 }
 
-/// 便捷接口：Codex → Claude
-#[tauri::command]
-pub async fn convert_codex_to_claude(
-    session_id: String,
-    project_id: String,
-    project_path: String,
-) -> Result<ConversionResult, String> {
-    convert_session(session_id, "claude".to_string(), project_id, project_path).await
+/// 读取 Codex 会话：消息与工具调用来自 response_item，推理来自 event_msg（跳过注入的上下文消息）
+fn read_codex_transcript(source: &Path) -> Result<Vec<TranscriptEntry>, String> {
+    let mut transcript = Transcript::default();
+
+    for_each_message::<CodexEvent, _>(source, &ConversionContext::detached(), |event, _| {
+        let Some(payload) = event.payload.as_ref() else {
+            return Ok(());
+        };
+        if event.event_type == "event_msg" {
+            if let Some(text) = codex_reasoning_text(payload) {
+                transcript.push_text(TranscriptEntry::Reasoning, text);
+            }
+            return Ok(());
+        }
+        if event.event_type != "response_item" {
+            return Ok(());
+        }
+
+        let call_id = payload
+            .get("call_id")
+            .and_then(|v| v.as_str())
+            .unwrap_or("");
+        match payload.get("type").and_then(|v| v.as_str()) {
+            Some("message") => {
+                let text_entry: fn(String) -> TranscriptEntry =
+                    match payload.get("role").and_then(|v| v.as_str()) {
+                        Some("user") => TranscriptEntry::User,
+                        Some("assistant") => TranscriptEntry::Assistant,
+                        _ => return Ok(()),
+                    };
+                let texts = payload
+                    .get("content")
+                    .and_then(|v| v.as_array())
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|item| item.get("text").and_then(|t| t.as_str()));
+                for text in texts {
+                    if !super::session::is_codex_context_message(text) {
+                        transcript.push_text(text_entry, text);
+                    }
+                }
+            }
+            Some("function_call") => {
+                let name = payload.get("name").and_then(|v| v.as_str()).unwrap_or("");
+                let arguments = payload
+                    .get("arguments")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("");
+                let input = serde_json::from_str(arguments)
+                    .unwrap_or_else(|_| Value::String(arguments.to_string()));
+                transcript.push_call(call_id, name, &input);
+            }
+            Some("custom_tool_call") => {
+                let name = payload.get("name").and_then(|v| v.as_str()).unwrap_or("");
+                let input = payload.get("input").cloned().unwrap_or(Value::Null);
+                transcript.push_call(call_id, name, &input);
+            }
+            Some("local_shell_call") => {
+                let action = payload.get("action").cloned().unwrap_or(Value::Null);
+                transcript.push_call(call_id, "local_shell", &action);
+            }
+            Some("function_call_output") | Some("custom_tool_call_output") => {
+                let output = payload.get("output").unwrap_or(&Value::Null);
+                let is_error = payload.get("is_error").and_then(|v| v.as_bool()) == Some(true);
+                transcript.push_result(call_id, codex_tool_output_text(output), is_error);
+            }
+            _ => {}
+        }
+        Ok(())
+    })?;
+
+    Ok(transcript.entries)
+}
+
+/// Codex 工具输出：部分版本把 `{"output": ..., "metadata": ...}` 序列化成字符串，只取其中的 output
+fn codex_tool_output_text(output: &Value) -> String {
+    if let Some(text) = output.as_str() {
+        if let Ok(Value::Object(wrapped)) = serde_json::from_str::<Value>(text) {
+            if let Some(inner) = wrapped.get("output").and_then(|v| v.as_str()) {
+                return inner.to_string();
+            }
+        }
+    }
+    tool_result_markdown_text(output)
+}
+
+/// 工具调用的代码块语言与内容：shell 类工具取命令，字符串参数原样输出，其他输出格式化的 JSON
+fn tool_call_markdown_input(name: &str, input: &Value) -> (&'static str, String) {
+    if SHELL_TOOL_NAMES.contains(&name.to_ascii_lowercase().as_str()) {
+        if let Some(command) = shell_command_text(input) {
+            return ("bash", command);
+        }
+    }
+    match input {
+        Value::String(text) => ("text", text.clone()),
+        other => (
+            "json",
+            serde_json::to_string_pretty(other).unwrap_or_default(),
+        ),
+    }
+}
+
+/// 命令可能是字符串或 argv 数组；`["bash", "-lc", "<script>"]` 形式只保留脚本
+fn shell_command_text(input: &Value) -> Option<String> {
+    match input.get("command").or_else(|| input.get("cmd"))? {
+        Value::String(command) => Some(command.clone()),
+        Value::Array(parts) => {
+            let parts: Vec<&str> = parts.iter().filter_map(|p| p.as_str()).collect();
+            match parts.as_slice() {
+                [_, flag, script] if flag.starts_with('-') && flag.ends_with('c') => {
+                    Some(script.to_string())
+                }
+                _ => Some(parts.join(" ")),
+            }
+        }
+        _ => None,
+    }
+}
+
+/// 渲染对话条目为 Markdown，条目之间以空行分隔；`include_tool_calls` 为 false 时省略工具调用
+fn render_transcript_markdown(
+    title: &str,
+    entries: &[TranscriptEntry],
+    include_tool_calls: bool,
+    style: MarkdownStyle,
+) -> String {
+    let mut out = format!("# {}\n", title);
+    // Dialogue 版式中同一角色的连续文本合并在一个标题下
+    let mut speaker: Option<&str> = None;
+
+    for entry in entries {
+        let section = match (style, entry) {
+            (_, TranscriptEntry::Tool(_)) if !include_tool_calls => continue,
+            (MarkdownStyle::Dialogue, TranscriptEntry::Reasoning(_)) => continue,
+            (
+                MarkdownStyle::Dialogue,
+                TranscriptEntry::User(text) | TranscriptEntry::Assistant(text),
+            ) => {
+                let label = match entry {
+                    TranscriptEntry::User(_) => "User",
+                    _ => "Claude",
+                };
+                if speaker != Some(label) {
+                    out.push_str(&format!("\n**{}:**\n", label));
+                    speaker = Some(label);
+                }
+                text.clone()
+            }
+            (MarkdownStyle::Dialogue, TranscriptEntry::Tool(call)) => {
+                let mut parts = Vec::new();
+                if !call.name.is_empty() {
+                    let input = serde_json::to_string_pretty(&call.input).unwrap_or_default();
+                    parts.push(format!(
+                        "Tool call: `{}`\n\n{}",
+                        call.name,
+                        fenced_block("json", &input)
+                    ));
+                }
+                if let Some(output) = &call.output {
+                    let summary = if call.is_error {
+                        "Tool result (error)"
+                    } else {
+                        "Tool result"
+                    };
+                    parts.push(format!(
+                        "<details>\n<summary>{}</summary>\n\n{}\n</details>",
+                        summary,
+                        fenced_block("", output)
+                    ));
+                }
+                parts.join("\n\n")
+            }
+            (MarkdownStyle::Transcript, TranscriptEntry::User(text)) => text
+                .lines()
+                .map(|line| {
+                    if line.is_empty() {
+                        ">".to_string()
+                    } else {
+                        format!("> {}", line)
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+            (MarkdownStyle::Transcript, TranscriptEntry::Assistant(text)) => text.clone(),
+            (MarkdownStyle::Transcript, TranscriptEntry::Reasoning(text)) => {
+                format!(
+                    "<details>\n<summary>Thinking</summary>\n\n{}\n</details>",
+                    text
+                )
+            }
+            (MarkdownStyle::Transcript, TranscriptEntry::Tool(call)) => {
+                let mut parts = Vec::new();
+                if !call.name.is_empty() {
+                    let (language, input) = tool_call_markdown_input(&call.name, &call.input);
+                    parts.push(format!("Tool call: `{}`", call.name));
+                    parts.push(fenced_block(language, &input));
+                }
+                if let Some(output) = &call.output {
+                    parts.push(
+                        if call.is_error {
+                            "Output (error):"
+                        } else {
+                            "Output:"
+                        }
+                        .to_string(),
+                    );
+                    parts.push(fenced_block("text", output));
+                }
+                parts.join("\n\n")
+            }
+        };
+        out.push('\n');
+        out.push_str(&section);
+        out.push('\n');
+    }
+
+    out
+}
+
+/// 工具结果的文本：字符串直接使用，内容块数组取其中的文本，其他结构输出 JSON
+fn tool_result_markdown_text(content: &Value) -> String {
+    match content {
+        Value::String(text) => text.clone(),
+        Value::Array(items) => items
+            .iter()
+            .map(|item| match item.get("text").and_then(|t| t.as_str()) {
+                Some(text) => text.to_string(),
+                None => item.to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+        Value::Null => String::new(),
+        other => serde_json::to_string_pretty(other).unwrap_or_default(),
+    }
+}
+
+/// 代码块围栏比内容中最长的连续反引号多一个，避免内容提前闭合代码块
+fn fenced_block(lang: &str, content: &str) -> String {
+    let longest_run = content.split(|c| c != '`').map(str::len).max().unwrap_or(0);
+    let fence = "`".repeat(longest_run.max(2) + 1);
+    format!("{}{}\n{}\n{}", fence, lang, content.trim_end(), fence)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context(cancelled: bool) -> ConversionContext {
+        ConversionContext::new(
+            "test-conversion".to_string(),
+            Arc::new(AtomicBool::new(cancelled)),
+            |_| {},
+        )
+    }
+
+    fn write_lines(path: &Path, lines: &[Value]) {
+        let content: Vec<String> = lines.iter().map(|l| l.to_string()).collect();
+        std::fs::write(path, content.join("\n") + "\n").unwrap();
+    }
+
+    fn codex_fixture(path: &Path) {
+        let message = |role: &str, kind: &str, text: &str| {
+            serde_json::json!({
+                "type": "response_item",
+                "timestamp": "2025-12-01T09:00:01Z",
+                "payload": {
+                    "type": "message",
+                    "role": role,
+                    "content": [{ "type": kind, "text": text }]
+                }
+            })
+        };
+        write_lines(
+            path,
+            &[
+                serde_json::json!({
+                    "type": "session_meta",
+                    "timestamp": "2025-12-01T09:00:00Z",
+                    "payload": { "id": "abc", "cwd": "/tmp/project" }
+                }),
+                message("user", "input_text", "hello"),
+                message("assistant", "output_text", "hi"),
+            ],
+        );
+    }
+
+    /// 会话行去掉随机生成的 UUID 与转换时间后的 JSON，用于对比两种实现的产物
+    fn without_random_ids(line: &str) -> Value {
+        fn strip_converted_at(value: &mut Value) {
+            match value {
+                Value::Object(map) => {
+                    map.remove("convertedAt");
+                    map.remove("converted_at");
+                    map.values_mut().for_each(strip_converted_at);
+                }
+                Value::Array(items) => items.iter_mut().for_each(strip_converted_at),
+                _ => {}
+            }
+        }
+
+        static UUID: Lazy<regex::Regex> = Lazy::new(|| {
+            regex::Regex::new(r"[0-9a-f]{8}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{12}")
+                .unwrap()
+        });
+        let mut value = serde_json::from_str(&UUID.replace_all(line, "<uuid>")).unwrap();
+        strip_converted_at(&mut value);
+        value
+    }
+
+    fn read_target_lines(path: &Path) -> Vec<Value> {
+        std::fs::read_to_string(path)
+            .unwrap()
+            .lines()
+            .map(without_random_ids)
+            .collect()
+    }
+
+    fn claude_fixture(path: &Path, turns: usize) {
+        let mut lines = Vec::new();
+        for turn in 0..turns {
+            let timestamp = format!("2025-12-01T09:{:02}:{:02}Z", turn / 60, turn % 60);
+            lines.push(serde_json::json!({
+                "type": "user",
+                "timestamp": timestamp,
+                "message": { "role": "user", "content": format!("prompt {}", turn) }
+            }));
+            // The model only appears from the first assistant message on
+            lines.push(serde_json::json!({
+                "type": "assistant",
+                "timestamp": timestamp,
+                "model": "claude-sonnet-4-5",
+                "message": { "role": "assistant", "content": [
+                    { "type": "thinking", "thinking": format!("plan {}", turn) },
+                    { "type": "text", "text": format!("answer {}", turn) },
+                    { "type": "tool_use", "id": format!("toolu_{}", turn), "name": "Bash",
+                      "input": { "command": format!("echo {}", turn) } }
+                ] }
+            }));
+            lines.push(serde_json::json!({
+                "type": "user",
+                "timestamp": timestamp,
+                "message": { "role": "user", "content": [
+                    { "type": "tool_result", "tool_use_id": format!("toolu_{}", turn),
+                      "content": [{ "type": "text", "text": format!("{}", turn) }] }
+                ] }
+            }));
+            lines.push(serde_json::json!({ "type": "system", "subtype": "note" }));
+            lines.push(serde_json::json!({
+                "type": "assistant",
+                "timestamp": timestamp,
+                "message": { "role": "assistant", "content": format!("done {}", turn) }
+            }));
+        }
+        write_lines(path, &lines);
+    }
+
+    fn read_fixture<T: DeserializeOwned>(path: &Path) -> Vec<T> {
+        std::fs::read_to_string(path)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn streaming_claude_to_codex_matches_buffered_conversion() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("session.jsonl");
+        let target = dir.path().join("rollout.jsonl");
+        claude_fixture(&source, 200);
+
+        let converter = ClaudeToCodexConverter::new(
+            "session".to_string(),
+            "project".to_string(),
+            "/tmp/project".to_string(),
+        );
+        let count = converter
+            .convert_file(&source, &target, &context(false))
+            .unwrap()
+            .0;
+
+        // The previous implementation: load every message, then convert and write them all
+        let messages: Vec<ClaudeMessage> = read_fixture(&source);
+        let first_timestamp = messages[0].timestamp.clone().unwrap();
+        let model = messages.iter().find_map(|m| m.model.clone());
+        let mut events = vec![converter.create_session_meta(&first_timestamp, model.as_deref())];
+        for msg in &messages {
+            events.extend(converter.convert_claude_message(msg));
+        }
+        let expected: Vec<Value> = events
+            .iter()
+            .map(|e| without_random_ids(&serde_json::to_string(e).unwrap()))
+            .collect();
+
+        assert_eq!(count, expected.len());
+        assert_eq!(read_target_lines(&target), expected);
+    }
+
+    #[test]
+    fn streaming_codex_to_claude_matches_buffered_conversion() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("rollout.jsonl");
+        let target = dir.path().join("claude.jsonl");
+        codex_fixture(&source);
+
+        let converter = CodexToClaudeConverter::new(
+            "rollout".to_string(),
+            "project".to_string(),
+            "/tmp/project".to_string(),
+        );
+        let count = converter
+            .convert_file(&source, &target, &context(false))
+            .unwrap();
+
+        let events: Vec<CodexEvent> = read_fixture(&source);
+        let mut expected = Vec::new();
+        write_json_line(
+            &mut expected,
+            &file_history_snapshot(events[0].timestamp.as_deref().unwrap()),
+        )
+        .unwrap();
+        let mut prev_uuid = None;
+        for msg in events
+            .iter()
+            .filter_map(|e| converter.convert_codex_event(e))
+        {
+            write_linked(&mut expected, &mut prev_uuid, msg).unwrap();
+        }
+        let expected: Vec<Value> = String::from_utf8(expected)
+            .unwrap()
+            .lines()
+            .map(without_random_ids)
+            .collect();
+
+        assert_eq!(count, expected.len());
+        assert_eq!(read_target_lines(&target), expected);
+    }
+
+    #[test]
+    fn codex_to_claude_streams_linked_messages() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("rollout.jsonl");
+        let target = dir.path().join("out").join("claude.jsonl");
+        codex_fixture(&source);
+
+        let converter = CodexToClaudeConverter::new(
+            "rollout".to_string(),
+            "project".to_string(),
+            "/tmp/project".to_string(),
+        );
+        let count = converter
+            .convert_file(&source, &target, &context(false))
+            .unwrap();
+
+        let lines: Vec<Value> = std::fs::read_to_string(&target)
+            .unwrap()
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(lines.len(), count);
+        assert_eq!(lines[0]["type"], "file-history-snapshot");
+        assert!(lines[0].get("parentUuid").is_none());
+        assert!(lines[1].get("parentUuid").is_none());
+        for pair in lines[1..].windows(2) {
+            assert_eq!(pair[1]["parentUuid"], pair[0]["uuid"]);
+        }
+    }
+
+    #[test]
+    fn cancelled_conversion_removes_partial_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("rollout.jsonl");
+        let target = dir.path().join("claude.jsonl");
+        codex_fixture(&source);
+
+        let converter = CodexToClaudeConverter::new(
+            "rollout".to_string(),
+            "project".to_string(),
+            "/tmp/project".to_string(),
+        );
+        let result = converter.convert_file(&source, &target, &context(true));
+
+        assert_eq!(result.unwrap_err(), CANCELLED_MESSAGE);
+        assert!(!target.exists());
+    }
+
+    #[test]
+    fn codex_import_assigns_new_session_id() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("shared.jsonl");
+        let target = dir.path().join("sessions").join("rollout.jsonl");
+        codex_fixture(&source);
+
+        let count = import_codex_file(&source, &target, "new-id").unwrap();
+
+        let lines: Vec<Value> = std::fs::read_to_string(&target)
+            .unwrap()
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(count, 3);
+        assert_eq!(lines[0]["payload"]["id"], "new-id");
+        assert_eq!(lines[0]["payload"]["cwd"], "/tmp/project");
+        assert_eq!(lines[1]["payload"]["content"][0]["text"], "hello");
+    }
+
+    #[test]
+    fn import_rejects_unparseable_files_before_writing() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("shared.jsonl");
+        let target = dir.path().join("imported.jsonl");
+        std::fs::write(&source, "{\"type\":\"user\"}\nnot json\n").unwrap();
+
+        let result = import_claude_file(&source, &target, "new-id");
+
+        assert!(result.unwrap_err().contains("Line 2"));
+        assert!(!target.exists());
+
+        // 缺少 session_meta 的文件不是可识别的 Codex session
+        std::fs::write(&source, "{\"type\":\"response_item\"}\n").unwrap();
+        assert!(import_codex_file(&source, &target, "new-id").is_err());
+    }
+
+    #[test]
+    fn claude_to_codex_rejects_incomplete_session_before_writing() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("session.jsonl");
+        let target = dir.path().join("rollout.jsonl");
+        write_lines(
+            &source,
+            &[serde_json::json!({
+                "type": "user",
+                "timestamp": "2025-12-01T09:00:00Z",
+                "message": { "role": "user", "content": "hello" }
+            })],
+        );
+
+        let converter = ClaudeToCodexConverter::new(
+            "session".to_string(),
+            "project".to_string(),
+            "/tmp/project".to_string(),
+        );
+        let result = converter.convert_file(&source, &target, &context(false));
+
+        assert_eq!(
+            result.unwrap_err(),
+            t(MessageKey::ConversionSessionIncomplete)
+        );
+        assert!(!target.exists());
+    }
+
+    #[test]
+    fn claude_to_codex_skips_and_reports_corrupted_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("session.jsonl");
+        let target = dir.path().join("rollout.jsonl");
+        let user = serde_json::json!({
+            "type": "user",
+            "timestamp": "2025-12-01T09:00:00Z",
+            "message": { "role": "user", "content": "hello" }
+        });
+        let assistant = serde_json::json!({
+            "type": "assistant",
+            "timestamp": "2025-12-01T09:00:01Z",
+            "message": { "role": "assistant", "content": "hi" }
+        });
+        let mut content = format!("{}\n", user).into_bytes();
+        content.extend_from_slice(b"{\"type\":\"assis\xff\n");
+        content.extend_from_slice(format!("{}\n", assistant).as_bytes());
+        std::fs::write(&source, content).unwrap();
+
+        let converter = ClaudeToCodexConverter::new(
+            "session".to_string(),
+            "project".to_string(),
+            "/tmp/project".to_string(),
+        );
+        let (_, corrupted) = converter
+            .convert_file(&source, &target, &context(false))
+            .unwrap();
+
+        assert_eq!(corrupted.len(), 1);
+        assert_eq!(corrupted[0].line, 2);
+        assert!(!corrupted[0].recovered);
+        assert!(target.exists());
+    }
+
+    #[test]
+    fn exports_codex_reasoning_with_preceding_prompt_index() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("rollout.jsonl");
+        let user = |text: &str| {
+            serde_json::json!({
+                "type": "response_item",
+                "timestamp": "2025-12-01T09:00:01Z",
+                "payload": { "type": "message", "role": "user", "content": [{ "type": "input_text", "text": text }] }
+            })
+        };
+        let reasoning = |text: &str| {
+            serde_json::json!({
+                "type": "event_msg",
+                "timestamp": "2025-12-01T09:00:02Z",
+                "payload": { "type": "agent_reasoning", "text": text }
+            })
+        };
+        write_lines(
+            &source,
+            &[
+                serde_json::json!({ "type": "session_meta", "payload": { "id": "abc", "cwd": "/tmp/project" } }),
+                reasoning("before any prompt"),
+                user("first"),
+                reasoning("thinking about first"),
+                user("second"),
+                reasoning("thinking about second"),
+            ],
+        );
+
+        let mut blocks = read_codex_reasoning(&source).unwrap();
+        assign_prompt_indices(
+            &mut blocks,
+            &extract_codex_prompts_from_file(&source).unwrap(),
+        );
+
+        let summary: Vec<(&str, Option<usize>)> = blocks
+            .iter()
+            .map(|b| (b.text.as_str(), b.prompt_index))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("before any prompt", None),
+                ("thinking about first", Some(0)),
+                ("thinking about second", Some(1)),
+            ]
+        );
+    }
+
+    #[test]
+    fn exports_claude_thinking_blocks_outside_sidechains() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("session.jsonl");
+        let assistant = |thinking: &str, sidechain: bool| {
+            serde_json::json!({
+                "type": "assistant",
+                "isSidechain": sidechain,
+                "timestamp": "2025-12-01T09:00:02Z",
+                "message": {
+                    "role": "assistant",
+                    "content": [
+                        { "type": "thinking", "thinking": thinking },
+                        { "type": "text", "text": "answer" }
+                    ]
+                }
+            })
+        };
+        write_lines(
+            &source,
+            &[
+                assistant("main reasoning", false),
+                assistant("agent reasoning", true),
+            ],
+        );
+
+        let blocks = read_claude_reasoning(&source).unwrap();
+
+        assert_eq!(blocks.len(), 1);
+        assert_eq!(blocks[0].text, "main reasoning");
+        assert_eq!(blocks[0].timestamp.as_deref(), Some("2025-12-01T09:00:02Z"));
+        assert_eq!(blocks[0].line_number, 0);
+    }
+
+    #[test]
+    fn renders_claude_session_as_markdown() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("session.jsonl");
+        write_lines(
+            &source,
+            &[
+                serde_json::json!({ "type": "user", "message": { "role": "user", "content": "List files" } }),
+                serde_json::json!({ "type": "assistant", "message": { "role": "assistant", "content": [
+                    { "type": "thinking", "thinking": "hidden" },
+                    { "type": "text", "text": "Checking." },
+                    { "type": "tool_use", "id": "t1", "name": "Bash", "input": { "command": "ls" } }
+                ] } }),
+                serde_json::json!({ "type": "user", "message": { "role": "user", "content": [
+                    { "type": "tool_result", "tool_use_id": "t1", "content": "a.md\n```" }
+                ] } }),
+                serde_json::json!({ "type": "assistant", "message": { "role": "assistant", "content": [
+                    { "type": "text", "text": "Found a.md." }
+                ] } }),
+                serde_json::json!({ "type": "user", "isSidechain": true, "message": { "role": "user", "content": "agent prompt" } }),
+            ],
+        );
+
+        let entries = read_claude_transcript(&source).unwrap();
+        let markdown = render_transcript_markdown(
+            "Claude Session abc",
+            &entries,
+            true,
+            MarkdownStyle::Dialogue,
+        );
+        assert_eq!(
+            markdown,
+            "# Claude Session abc\n\
+             \n**User:**\n\nList files\n\
+             \n**Claude:**\n\nChecking.\n\
+             \nTool call: `Bash`\n\n```json\n{\n  \"command\": \"ls\"\n}\n```\n\
+             \n<details>\n<summary>Tool result</summary>\n\n````\na.md\n```\n````\n</details>\n\
+             \nFound a.md.\n"
+        );
+
+        let without_tools = render_transcript_markdown(
+            "Claude Session abc",
+            &entries,
+            false,
+            MarkdownStyle::Dialogue,
+        );
+        assert!(!without_tools.contains("Tool call"));
+        assert!(!without_tools.contains("<details>"));
+        assert!(without_tools.ends_with("Checking.\n\nFound a.md.\n"));
+    }
+
+    #[test]
+    fn renders_claude_transcript_with_tool_output_and_thinking() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("session.jsonl");
+        write_lines(
+            &source,
+            &[
+                serde_json::json!({ "type": "user", "message": { "role": "user", "content": "List files\n\nin src" } }),
+                serde_json::json!({ "type": "assistant", "message": { "role": "assistant", "content": [
+                    { "type": "thinking", "thinking": "Use ls." },
+                    { "type": "tool_use", "id": "t1", "name": "Bash", "input": { "command": "ls src" } }
+                ] } }),
+                serde_json::json!({ "type": "user", "message": { "role": "user", "content": [
+                    { "type": "tool_result", "tool_use_id": "t1", "content": "main.rs", "is_error": false }
+                ] } }),
+                serde_json::json!({ "type": "user", "isMeta": true, "message": { "role": "user", "content": "caveat" } }),
+                serde_json::json!({ "type": "assistant", "message": { "role": "assistant", "content": [
+                    { "type": "text", "text": "Found main.rs." }
+                ] } }),
+            ],
+        );
+
+        let entries = read_claude_transcript(&source).unwrap();
+        assert_eq!(
+            render_transcript_markdown(
+                "Claude Session abc",
+                &entries,
+                true,
+                MarkdownStyle::Transcript
+            ),
+            "# Claude Session abc\n\
+             \n> List files\n>\n> in src\n\
+             \n<details>\n<summary>Thinking</summary>\n\nUse ls.\n</details>\n\
+             \nTool call: `Bash`\n\n```bash\nls src\n```\n\nOutput:\n\n```text\nmain.rs\n```\n\
+             \nFound main.rs.\n"
+        );
+    }
+
+    #[test]
+    fn reads_codex_transcript_in_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("rollout.jsonl");
+        let item =
+            |payload: Value| serde_json::json!({ "type": "response_item", "payload": payload });
+        write_lines(
+            &source,
+            &[
+                item(
+                    serde_json::json!({ "type": "message", "role": "user", "content": [
+                    { "type": "input_text", "text": "<environment_context>\n<cwd>/tmp</cwd>\n</environment_context>" },
+                    { "type": "input_text", "text": "run tests" }
+                ] }),
+                ),
+                serde_json::json!({ "type": "event_msg", "payload": { "type": "agent_reasoning", "text": "Run cargo." } }),
+                item(serde_json::json!({
+                    "type": "function_call", "name": "shell", "call_id": "c1",
+                    "arguments": "{\"command\":[\"bash\",\"-lc\",\"cargo test\"]}"
+                })),
+                item(serde_json::json!({
+                    "type": "function_call_output", "call_id": "c1",
+                    "output": "{\"output\":\"ok\",\"metadata\":{\"exit_code\":0}}"
+                })),
+                item(
+                    serde_json::json!({ "type": "function_call_output", "call_id": "missing", "output": "stray" }),
+                ),
+                item(
+                    serde_json::json!({ "type": "message", "role": "assistant", "content": [
+                    { "type": "output_text", "text": "All tests pass." }
+                ] }),
+                ),
+            ],
+        );
+
+        let entries = read_codex_transcript(&source).unwrap();
+        let tool = |name: &str, input: Value, output: &str| {
+            TranscriptEntry::Tool(TranscriptToolCall {
+                name: name.to_string(),
+                input,
+                output: Some(output.to_string()),
+                is_error: false,
+            })
+        };
+        assert_eq!(
+            entries,
+            vec![
+                TranscriptEntry::User("run tests".to_string()),
+                TranscriptEntry::Reasoning("Run cargo.".to_string()),
+                tool(
+                    "shell",
+                    serde_json::json!({ "command": ["bash", "-lc", "cargo test"] }),
+                    "ok"
+                ),
+                tool("", Value::Null, "stray"),
+                TranscriptEntry::Assistant("All tests pass.".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn batch_continues_after_failures_and_keeps_input_order() {
+        let ids: Vec<String> = ["a", "broken", "c"].iter().map(|s| s.to_string()).collect();
+        let progress = Mutex::new(Vec::new());
+
+        let items = run_batch(
+            &ids,
+            2,
+            &AtomicBool::new(false),
+            |id| {
+                let status = if id == "broken" {
+                    BatchItemStatus::Failed
+                } else {
+                    BatchItemStatus::Converted
+                };
+                BatchConversionItem::new(id, status, None)
+            },
+            |_, completed| progress.lock().unwrap().push(completed),
+        );
+
+        let statuses: Vec<(&str, BatchItemStatus)> = items
+            .iter()
+            .map(|item| (item.session_id.as_str(), item.status))
+            .collect();
+        assert_eq!(
+            statuses,
+            vec![
+                ("a", BatchItemStatus::Converted),
+                ("broken", BatchItemStatus::Failed),
+                ("c", BatchItemStatus::Converted),
+            ]
+        );
+        let mut progress = progress.into_inner().unwrap();
+        progress.sort();
+        assert_eq!(progress, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn cancelled_batch_does_not_start_remaining_sessions() {
+        let ids: Vec<String> = ["a", "b", "c"].iter().map(|s| s.to_string()).collect();
+        let cancelled = AtomicBool::new(false);
+
+        let items = run_batch(
+            &ids,
+            1,
+            &cancelled,
+            |id| {
+                cancelled.store(true, Ordering::Relaxed);
+                BatchConversionItem::new(id, BatchItemStatus::Converted, None)
+            },
+            |_, _| {},
+        );
+
+        assert_eq!(items[0].status, BatchItemStatus::Converted);
+        assert_eq!(items[1].status, BatchItemStatus::Cancelled);
+        assert_eq!(items[2].status, BatchItemStatus::Cancelled);
+    }
+
+    #[test]
+    fn finds_conversion_markers_in_existing_outputs() {
+        let dir = tempfile::tempdir().unwrap();
+        let day_dir = dir.path().join("2025").join("12").join("01");
+        std::fs::create_dir_all(&day_dir).unwrap();
+
+        let converter = ClaudeToCodexConverter::new(
+            "claude-session".to_string(),
+            "project".to_string(),
+            "/tmp/project".to_string(),
+        );
+        write_lines(
+            &day_dir.join("rollout-converted.jsonl"),
+            &[
+                serde_json::to_value(converter.create_session_meta("2025-12-01T09:00:00Z", None))
+                    .unwrap(),
+            ],
+        );
+        codex_fixture(&day_dir.join("rollout-native.jsonl"));
+
+        let source = dir.path().join("rollout.jsonl");
+        let claude_dir = dir.path().join("claude");
+        codex_fixture(&source);
+        CodexToClaudeConverter::new(
+            "codex-session".to_string(),
+            "project".to_string(),
+            "/tmp/project".to_string(),
+        )
+        .convert_file(
+            &source,
+            &claude_dir.join("converted.jsonl"),
+            &context(false),
+        )
+        .unwrap();
+
+        let codex_ids = converted_source_ids(&day_dir, 1);
+        assert_eq!(
+            codex_ids.into_iter().collect::<Vec<_>>(),
+            vec!["claude-session".to_string()]
+        );
+        let claude_ids = converted_source_ids(&claude_dir, CONVERSION_MARKER_SCAN_LINES);
+        assert_eq!(
+            claude_ids.into_iter().collect::<Vec<_>>(),
+            vec!["codex-session".to_string()]
+        );
+    }
+
+    #[test]
+    fn gemini_to_claude_splits_tool_calls_and_skips_empty_replies() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("session-2025-12-01T09-00-abc.json");
+        let session = serde_json::json!({
+            "sessionId": "gemini-session",
+            "startTime": "2025-12-01T09:00:00Z",
+            "messages": [
+                { "type": "user", "timestamp": "2025-12-01T09:00:01Z", "content": "list files" },
+                {
+                    "type": "gemini",
+                    "timestamp": "2025-12-01T09:00:02Z",
+                    "content": "",
+                    "model": "gemini-2.5-pro",
+                    "toolCalls": [{
+                        "id": "call-1",
+                        "name": "run_shell_command",
+                        "args": { "command": "ls" },
+                        "status": "success",
+                        "result": [{
+                            "functionResponse": {
+                                "id": "call-1",
+                                "name": "run_shell_command",
+                                "response": { "output": "a.txt" }
+                            }
+                        }]
+                    }]
+                },
+                { "type": "gemini", "timestamp": "2025-12-01T09:00:03Z", "content": "   " },
+                { "type": "info", "timestamp": "2025-12-01T09:00:04Z", "content": "Request cancelled." },
+                { "type": "gemini", "timestamp": "2025-12-01T09:00:05Z", "content": "Found a.txt" }
+            ]
+        });
+        std::fs::write(&source, session.to_string()).unwrap();
+
+        let converter = GeminiToClaudeConverter::new(
+            "gemini-session".to_string(),
+            "project".to_string(),
+            "/tmp/project".to_string(),
+        );
+        let mut output = Vec::new();
+        let count = converter
+            .write_messages(&source, &mut output, &context(false))
+            .unwrap();
+
+        let lines: Vec<Value> = String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(lines.len(), count);
+        assert_eq!(lines[0]["type"], "file-history-snapshot");
+        assert_eq!(lines[1]["conversionSource"]["sessionId"], "gemini-session");
+        assert_eq!(lines[1]["model"], "gemini-2.5-pro");
+        assert!(lines[1].get("parentUuid").is_none());
+        for pair in lines[1..].windows(2) {
+            assert_eq!(pair[1]["parentUuid"], pair[0]["uuid"]);
+        }
+
+        let blocks: Vec<&Value> = lines[2..]
+            .iter()
+            .map(|l| &l["message"]["content"][0])
+            .collect();
+        assert_eq!(blocks.len(), 4);
+        assert_eq!(blocks[0]["text"], "list files");
+        assert_eq!(blocks[1]["type"], "tool_use");
+        assert_eq!(blocks[1]["name"], "bash");
+        assert_eq!(blocks[1]["input"]["command"], "ls");
+        assert_eq!(lines[4]["type"], "user");
+        assert_eq!(blocks[2]["type"], "tool_result");
+        assert_eq!(blocks[2]["tool_use_id"], "call-1");
+        assert_eq!(blocks[2]["content"], "a.txt");
+        assert_eq!(blocks[3]["text"], "Found a.txt");
+    }
+
+    #[test]
+    fn claude_to_gemini_merges_turns_and_attaches_tool_results() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("claude.jsonl");
+        let message = |kind: &str, content: Value| {
+            serde_json::json!({
+                "type": kind,
+                "timestamp": "2025-12-01T09:00:00Z",
+                "message": { "role": kind, "content": content }
+            })
+        };
+        write_lines(
+            &source,
+            &[
+                message("user", Value::String("run the tests".to_string())),
+                message(
+                    "assistant",
+                    serde_json::json!([{ "type": "thinking", "thinking": "plan" }]),
+                ),
+                message(
+                    "assistant",
+                    serde_json::json!([{ "type": "text", "text": "Running them." }]),
+                ),
+                message(
+                    "assistant",
+                    serde_json::json!([{
+                        "type": "tool_use", "id": "toolu_1", "name": "Bash",
+                        "input": { "command": "cargo test" }
+                    }]),
+                ),
+                message(
+                    "user",
+                    serde_json::json!([{
+                        "type": "tool_result", "tool_use_id": "toolu_1",
+                        "content": "ok", "is_error": false
+                    }]),
+                ),
+                message(
+                    "assistant",
+                    serde_json::json!([{ "type": "text", "text": "All green." }]),
+                ),
+            ],
+        );
+
+        let converter = ClaudeToGeminiConverter::new(
+            "claude-session".to_string(),
+            "project".to_string(),
+            "/tmp/project".to_string(),
+        );
+        let build = converter.build_session(&source, &context(false)).unwrap();
+        let messages = build.session["messages"].as_array().unwrap();
+
+        assert_eq!(build.message_count, 3);
+        assert_eq!(build.session["conversionSource"]["engine"], "claude");
+        assert_eq!(messages[0]["type"], "user");
+        assert_eq!(messages[1]["type"], "gemini");
+        assert_eq!(messages[1]["content"], "Running them.");
+        let call = &messages[1]["toolCalls"][0];
+        assert_eq!(call["name"], "run_shell_command");
+        assert_eq!(call["status"], "success");
+        assert_eq!(
+            call["result"][0]["functionResponse"]["response"]["output"],
+            "ok"
+        );
+        assert_eq!(messages[2]["content"], "All green.");
+        assert!(messages[2].get("toolCalls").is_none());
+    }
 }
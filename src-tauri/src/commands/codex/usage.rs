@@ -7,13 +7,16 @@
  * - Model-level statistics
  * - Per-project statistics
  */
-use chrono::{DateTime, Local, NaiveDate};
+use chrono::{DateTime, Local, NaiveDate, TimeZone};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::io::{BufRead, BufReader};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
+use super::super::cost_comparison::{SessionTokenUsage, SessionTokens, TokenRates};
+use super::super::usage_report::UsageRecord;
 use super::config::get_codex_sessions_dir;
+use super::session::{is_codex_context_message, session_cwd};
 
 // ============================================================================
 // Types
@@ -93,83 +96,90 @@ struct ModelPricing {
     cache_read: f64,
 }
 
-fn get_codex_pricing(model: &str) -> ModelPricing {
+/// Fallback for models missing from the price table (codex-mini-latest pricing)
+const DEFAULT_PRICING: ModelPricing = ModelPricing {
+    input: 1.50,
+    output: 6.00,
+    cache_read: 0.375,
+};
+
+/// Looks up the price table entry of a model, None when the model is not listed
+fn lookup_codex_pricing(model: &str) -> Option<ModelPricing> {
     let normalized = model.to_lowercase();
 
     // GPT-5.2 Codex (latest)
     if normalized.contains("5.2-codex") || normalized.contains("5_2_codex") {
-        return ModelPricing {
+        return Some(ModelPricing {
             input: 1.75,
             output: 14.00,
             cache_read: 0.175,
-        };
+        });
     }
 
     // GPT-5.2 (non-codex naming)
     if normalized.contains("gpt-5.2") || normalized.contains("gpt5.2") {
-        return ModelPricing {
+        return Some(ModelPricing {
             input: 1.75,
             output: 14.00,
             cache_read: 0.175,
-        };
+        });
     }
 
     // GPT-5.1-Codex variants
     if normalized.contains("5.1-codex-max") || normalized.contains("5_1_codex_max") {
-        return ModelPricing {
+        return Some(ModelPricing {
             input: 1.25,
             output: 10.00,
             cache_read: 0.125,
-        };
+        });
     }
     if normalized.contains("5.1-codex-mini") || normalized.contains("5_1_codex_mini") {
-        return ModelPricing {
+        return Some(ModelPricing {
             input: 0.25,
             output: 2.00,
             cache_read: 0.025,
-        };
+        });
     }
     if normalized.contains("5.1-codex") || normalized.contains("5_1_codex") {
-        return ModelPricing {
+        return Some(ModelPricing {
             input: 1.25,
             output: 10.00,
             cache_read: 0.125,
-        };
+        });
     }
 
     // GPT-5.1 (non-codex naming)
     if normalized.contains("gpt-5.1") || normalized.contains("gpt5.1") {
-        return ModelPricing {
+        return Some(ModelPricing {
             input: 1.25,
             output: 10.00,
             cache_read: 0.125,
-        };
+        });
     }
 
     // codex-mini-latest (default CLI model)
     if normalized.contains("codex-mini-latest") || normalized.contains("codex_mini_latest") {
-        return ModelPricing {
+        return Some(ModelPricing {
             input: 1.50,
             output: 6.00,
             cache_read: 0.375,
-        };
+        });
     }
 
     // o4-mini
     if normalized.contains("o4-mini") || normalized.contains("o4_mini") {
-        return ModelPricing {
+        return Some(ModelPricing {
             input: 1.10,
             output: 4.40,
             cache_read: 0.275,
-        };
+        });
     }
 
-    // Default to codex-mini-latest pricing
-    ModelPricing {
-        input: 1.50,
-        output: 6.00,
-        cache_read: 0.375,
-    }
+    None
+}
+
+fn get_codex_pricing(model: &str) -> ModelPricing {
+    lookup_codex_pricing(model).unwrap_or(DEFAULT_PRICING)
 }
 
 fn calculate_cost(model: &str, input_tokens: u64, output_tokens: u64, cached_tokens: u64) -> f64 {
@@ -182,6 +192,55 @@ fn calculate_cost(model: &str, input_tokens: u64, output_tokens: u64, cached_tok
     input_cost + output_cost + cache_cost
 }
 
+/// Price table rates of a Codex model, None for models missing from the table
+pub(crate) fn codex_token_rates(model: &str) -> Option<TokenRates> {
+    lookup_codex_pricing(model).map(|pricing| TokenRates {
+        input: pricing.input,
+        output: pricing.output,
+        cache_write: 0.0,
+        cache_read: pricing.cache_read,
+    })
+}
+
+/// Token totals, model and cost of a single Codex session file
+pub(crate) fn codex_session_tokens(path: &Path) -> Option<SessionTokenUsage> {
+    let session = parse_session_for_usage(&path.to_path_buf())?;
+    Some(SessionTokenUsage {
+        tokens: SessionTokens {
+            input_tokens: session.input_tokens,
+            output_tokens: session.output_tokens,
+            cache_creation_tokens: 0,
+            cache_read_tokens: session.cached_input_tokens,
+        },
+        models: vec![session.model],
+        cost: session.total_cost,
+    })
+}
+
+/// Every Codex session usage, timestamped at session creation, for report export
+pub(crate) fn codex_usage_records() -> Vec<UsageRecord> {
+    collect_all_sessions()
+        .into_iter()
+        .filter_map(|session| {
+            let timestamp = Local.timestamp_opt(session.created_at as i64, 0).single()?;
+            Some(UsageRecord {
+                engine: "codex",
+                timestamp,
+                project_path: session.project_path,
+                model: session.model,
+                session_id: session.session_id,
+                tokens: SessionTokens {
+                    input_tokens: session.input_tokens,
+                    output_tokens: session.output_tokens,
+                    cache_creation_tokens: 0,
+                    cache_read_tokens: session.cached_input_tokens,
+                },
+                cost: session.total_cost,
+            })
+        })
+        .collect()
+}
+
 // ============================================================================
 // Session Parsing
 // ============================================================================
@@ -207,17 +266,7 @@ fn parse_session_for_usage(path: &PathBuf) -> Option<CodexSessionUsage> {
         .timestamp() as u64;
 
     // Get cwd and convert from WSL path format if needed
-    let cwd_raw = payload["cwd"].as_str().unwrap_or("");
-    #[cfg(target_os = "windows")]
-    let cwd = {
-        if cwd_raw.starts_with("/mnt/") {
-            super::super::wsl_utils::wsl_to_windows_path(cwd_raw)
-        } else {
-            cwd_raw.to_string()
-        }
-    };
-    #[cfg(not(target_os = "windows"))]
-    let cwd = cwd_raw.to_string();
+    let cwd = session_cwd(payload["cwd"].as_str().unwrap_or(""));
 
     // Initialize accumulators
     let mut total_input_tokens: u64 = 0;
@@ -371,10 +420,7 @@ fn parse_session_for_usage(path: &PathBuf) -> Option<CodexSessionUsage> {
                                 for item in content {
                                     if item["type"].as_str() == Some("input_text") {
                                         if let Some(text) = item["text"].as_str() {
-                                            if !text.contains("<environment_context>")
-                                                && !text.contains("# AGENTS.md")
-                                                && !text.trim().is_empty()
-                                            {
+                                            if !is_codex_context_message(text) && !text.trim().is_empty() {
                                                 first_message = Some(text.to_string());
                                                 break;
                                             }
@@ -327,6 +327,15 @@ impl AutoCompactManager {
     async fn build_compaction_command(
         &self,
         custom_instructions: &Option<String>,
+    ) -> Result<String, String> {
+        self.compaction_instruction(custom_instructions)
+    }
+
+    /// Compaction instruction for the configured strategy, also used to compress
+    /// content outside of a session (e.g. cross-engine review)
+    pub fn compaction_instruction(
+        &self,
+        custom_instructions: &Option<String>,
     ) -> Result<String, String> {
         let config = self.config.lock().map_err(|e| e.to_string())?;
 
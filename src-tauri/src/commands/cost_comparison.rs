@@ -0,0 +1,201 @@
+//! 模型成本对比
+//!
+//! 取会话实际记录的 token 用量，按价格表分别用两个模型的单价重新计价，
+//! 用于评估换用更便宜的模型能省多少：
+//! - Claude: 输入 / 输出 / 缓存写入 / 缓存读取分别计价（按 message id + request id 去重）
+//! - Codex: 输入 / 输出 / 缓存输入计价，与用量统计的算法一致
+//! - Gemini: 只记录输入 / 输出 token
+//!
+//! 价格表沿用各引擎用量统计模块中的定义，未收录的模型直接报错，不回退到默认价格。
+
+use serde::Serialize;
+
+use super::codex::usage::{codex_session_tokens, codex_token_rates};
+use super::gemini::usage::{gemini_session_tokens, gemini_token_rates};
+use super::session_utils::resolve_session_file;
+use super::usage::{claude_session_tokens, claude_token_rates};
+
+/// 每百万 token 的单价（美元）
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TokenRates {
+    pub input: f64,
+    pub output: f64,
+    pub cache_write: f64,
+    pub cache_read: f64,
+}
+
+/// 会话累计的 token 数
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionTokens {
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cache_creation_tokens: u64,
+    pub cache_read_tokens: u64,
+}
+
+/// 单个会话的用量：token 数、使用过的模型与按实际模型计算的成本
+#[derive(Debug, Clone, Default)]
+pub struct SessionTokenUsage {
+    pub tokens: SessionTokens,
+    pub models: Vec<String>,
+    pub cost: f64,
+}
+
+/// 某个模型下的重新计价结果
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelCost {
+    pub model: String,
+    pub input_cost: f64,
+    pub output_cost: f64,
+    /// 缓存写入与缓存读取的费用
+    pub cache_cost: f64,
+    pub total_cost: f64,
+}
+
+/// 两个模型的成本对比
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CostComparison {
+    pub session_id: String,
+    pub engine: String,
+    /// 会话中实际使用过的模型
+    pub models_used: Vec<String>,
+    pub tokens: SessionTokens,
+    /// 按实际模型计算的成本（Claude 优先使用记录中的 costUSD）
+    pub actual_cost: f64,
+    pub model_a: ModelCost,
+    pub model_b: ModelCost,
+    /// model_b 相对 model_a 的差额，负数表示更便宜
+    pub delta: f64,
+    /// 差额占 model_a 成本的百分比，model_a 成本为 0 时为 None
+    pub delta_percent: Option<f64>,
+}
+
+/// 按两个模型的单价重新计价会话的历史 token 用量
+///
+/// `project_id` 的含义与 [`resolve_session_file`] 相同：
+/// Claude 为 ~/.claude/projects 下的目录名，Gemini 为项目路径，Codex 不参与定位
+#[tauri::command]
+pub async fn compare_model_cost(
+    session_id: String,
+    project_id: String,
+    engine: String,
+    model_a: String,
+    model_b: String,
+) -> Result<CostComparison, String> {
+    log::info!(
+        "[CostComparison] Comparing {} session {}: {} vs {}",
+        engine,
+        session_id,
+        model_a,
+        model_b
+    );
+
+    tokio::task::spawn_blocking(move || {
+        let rates_a = token_rates(&engine, &model_a)?;
+        let rates_b = token_rates(&engine, &model_b)?;
+
+        let path = resolve_session_file(&engine, &session_id, &project_id)?;
+        let usage = match engine.as_str() {
+            "claude" => Some(claude_session_tokens(&path)),
+            "codex" => codex_session_tokens(&path),
+            "gemini" => gemini_session_tokens(&path),
+            other => return Err(format!("Unsupported engine: {}", other)),
+        }
+        .filter(|usage| total_tokens(&usage.tokens) > 0)
+        .ok_or_else(|| format!("No token usage recorded in session: {}", session_id))?;
+
+        let model_a = price_tokens(&model_a, &rates_a, &usage.tokens);
+        let model_b = price_tokens(&model_b, &rates_b, &usage.tokens);
+        let delta = model_b.total_cost - model_a.total_cost;
+        let delta_percent = (model_a.total_cost > 0.0).then(|| delta / model_a.total_cost * 100.0);
+
+        Ok(CostComparison {
+            session_id,
+            engine,
+            models_used: usage.models,
+            tokens: usage.tokens,
+            actual_cost: usage.cost,
+            model_a,
+            model_b,
+            delta,
+            delta_percent,
+        })
+    })
+    .await
+    .map_err(|e| format!("Cost comparison task failed: {}", e))?
+}
+
+/// 查询模型在对应引擎价格表中的单价
+fn token_rates(engine: &str, model: &str) -> Result<TokenRates, String> {
+    let rates = match engine {
+        "claude" => claude_token_rates(model),
+        "codex" => codex_token_rates(model),
+        "gemini" => gemini_token_rates(model),
+        other => return Err(format!("Unsupported engine: {}", other)),
+    };
+    rates.ok_or_else(|| format!("Model not found in {} price table: {}", engine, model))
+}
+
+fn total_tokens(tokens: &SessionTokens) -> u64 {
+    tokens.input_tokens
+        + tokens.output_tokens
+        + tokens.cache_creation_tokens
+        + tokens.cache_read_tokens
+}
+
+/// 按单价计算各部分费用（单价为每百万 token）
+fn price_tokens(model: &str, rates: &TokenRates, tokens: &SessionTokens) -> ModelCost {
+    let per_token = |count: u64, rate: f64| count as f64 * rate / 1_000_000.0;
+
+    let input_cost = per_token(tokens.input_tokens, rates.input);
+    let output_cost = per_token(tokens.output_tokens, rates.output);
+    let cache_cost = per_token(tokens.cache_creation_tokens, rates.cache_write)
+        + per_token(tokens.cache_read_tokens, rates.cache_read);
+
+    ModelCost {
+        model: model.to_string(),
+        input_cost,
+        output_cost,
+        cache_cost,
+        total_cost: input_cost + output_cost + cache_cost,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reprices_each_token_kind() {
+        let tokens = SessionTokens {
+            input_tokens: 2_000_000,
+            output_tokens: 500_000,
+            cache_creation_tokens: 1_000_000,
+            cache_read_tokens: 4_000_000,
+        };
+        let rates = TokenRates {
+            input: 3.0,
+            output: 15.0,
+            cache_write: 3.75,
+            cache_read: 0.30,
+        };
+
+        let cost = price_tokens("sonnet", &rates, &tokens);
+        assert_eq!(cost.input_cost, 6.0);
+        assert_eq!(cost.output_cost, 7.5);
+        assert!((cost.cache_cost - 4.95).abs() < 1e-9);
+        assert!((cost.total_cost - 18.45).abs() < 1e-9);
+    }
+
+    #[test]
+    fn unknown_models_and_engines_are_rejected() {
+        assert!(token_rates("codex", "gpt-5.1-codex-max").is_ok());
+        assert!(token_rates("claude", "claude-sonnet-4-5").is_ok());
+        assert!(token_rates("claude", "gpt-4o").is_err());
+        assert!(token_rates("gemini", "not-a-model").is_err());
+        assert!(token_rates("cursor", "gpt-4o").is_err());
+    }
+}
@@ -0,0 +1,622 @@
+//! 跨引擎评审
+//!
+//! 把某个会话中的一条 assistant 回复交给另一个引擎评审：
+//! - 按 `message_ref` 取出回复全文（含代码块）
+//! - 用可配置的框架模板与评审指令拼装提示词（~/.anycode/cross_engine_review.json）
+//! - 回复超过目标引擎上下文的 70% 时，先按自动压缩策略的指令压缩再发送
+//! - 通过各引擎既有的执行命令在当前项目新建会话（或续接指定会话）执行
+//! - 目标会话确定后写入会话标注的 related_sessions，两个会话互相关联
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::ops::ControlFlow;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::broadcast::Receiver;
+
+use super::codex::context::model_context_window;
+use super::context_manager::AutoCompactState;
+use super::project_memory::{content_text, estimate_tokens, request_provider_model};
+use super::prompt_history::{dispatch_prompt_traced, PromptTarget};
+use super::session_annotations::{link_sessions, SessionRef, SessionRelation};
+use super::session_utils::resolve_session_file;
+use super::task_actions::{subscribe_task_completions, TaskCompletionEvent};
+use crate::utils::config_utils::{load_json_config, save_json_config, ConfigPathBuilder};
+use crate::utils::jsonl_reader::{ensure_loadable_in_memory, for_each_line};
+
+/// 回复超过目标引擎上下文的该百分比时先压缩
+const CONTEXT_LIMIT_PERCENT: u64 = 70;
+
+/// 各引擎的上下文窗口（Codex 优先按模型查表）
+const CLAUDE_CONTEXT_WINDOW: u64 = 200_000;
+const DEFAULT_CODEX_CONTEXT_WINDOW: u64 = 272_000;
+const GEMINI_CONTEXT_WINDOW: u64 = 1_000_000;
+
+/// 压缩结果的最大输出 token 数
+const COMPRESSION_MAX_TOKENS: u32 = 8192;
+
+/// 等待评审任务完成的最长时间
+const COMPLETION_TIMEOUT_SECS: u64 = 2 * 60 * 60;
+
+/// 默认评审框架模板，占位符：{source_engine} {instruction} {content}
+const DEFAULT_REVIEW_TEMPLATE: &str = "以下是另一个 AI（{source_engine}）的输出，请评审。\n\n\
+## 评审要求\n{instruction}\n\n\
+## 待评审内容\n<ai_output>\n{content}\n</ai_output>";
+
+/// 未提供评审指令时使用的默认指令
+const DEFAULT_REVIEW_INSTRUCTION: &str = "指出其中的错误、遗漏和风险，并给出具体的改进建议。";
+
+const COMPRESSION_SYSTEM_PROMPT: &str = "Compress the following AI reply so that another model \
+can review it. Keep conclusions, decisions, their reasons and the code blocks that matter for \
+the review verbatim. Output only the compressed content.\n\n";
+
+/// 跨引擎评审配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CrossEngineReviewConfig {
+    /// 评审框架模板，必须包含 {content}
+    pub template: String,
+}
+
+impl Default for CrossEngineReviewConfig {
+    fn default() -> Self {
+        Self {
+            template: DEFAULT_REVIEW_TEMPLATE.to_string(),
+        }
+    }
+}
+
+/// 评审执行选项
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CrossEngineReviewOptions {
+    /// 执行评审的项目路径
+    pub project_path: String,
+    /// 来源会话的定位参数，含义与 `resolve_session_path` 的 project_id_or_path 相同
+    /// （Claude 为 project_id），缺省时使用 project_path
+    #[serde(default)]
+    pub source_project: Option<String>,
+    /// 续接目标引擎的指定会话，缺省时新建会话
+    #[serde(default)]
+    pub target_session_id: Option<String>,
+    #[serde(default)]
+    pub model: Option<String>,
+    /// 执行模式，含义与批量提示词相同（按目标引擎解释）
+    #[serde(default)]
+    pub mode: Option<String>,
+}
+
+/// 评审任务发起结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CrossEngineReviewResult {
+    /// 本次评审的标识，与 `cross-engine-review-linked` 事件中的 reviewId 对应
+    pub review_id: String,
+    pub target_engine: String,
+    /// 目标会话 ID；新建会话时在任务完成后通过 `cross-engine-review-linked` 事件返回
+    pub target_session_id: Option<String>,
+    /// 回复是否经过压缩
+    pub compressed: bool,
+    /// 回复的估算 token 数
+    pub original_tokens: u64,
+    /// 实际发送内容的估算 token 数
+    pub sent_tokens: u64,
+    /// 给用户的说明（如压缩提示）
+    pub notice: Option<String>,
+}
+
+/// 新建的评审会话完成并写入关联后发送的事件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CrossEngineReviewLinked {
+    pub review_id: String,
+    pub target_engine: String,
+    pub target_session_id: Option<String>,
+    pub success: bool,
+    pub error: Option<String>,
+}
+
+/// 会话中的一条 assistant 回复（Claude 按 message.id 合并分段记录）
+#[derive(Debug, Clone, Default)]
+pub(crate) struct AssistantMessage {
+    /// 可用于定位的 ID（Claude 记录 uuid 与 message.id、Gemini 消息 id）
+    pub(crate) ids: Vec<String>,
+    pub(crate) timestamps: Vec<String>,
+    pub(crate) text: String,
+}
+
+/// 获取跨引擎评审配置
+#[tauri::command]
+pub async fn get_cross_engine_review_config() -> Result<CrossEngineReviewConfig, String> {
+    load_json_config(get_config_path()?)
+}
+
+/// 更新跨引擎评审配置
+#[tauri::command]
+pub async fn update_cross_engine_review_config(
+    config: CrossEngineReviewConfig,
+) -> Result<(), String> {
+    if !config.template.contains("{content}") {
+        return Err("评审模板必须包含 {content} 占位符".to_string());
+    }
+    save_json_config(&config, get_config_path()?)
+}
+
+/// 把一条 assistant 回复交给另一个引擎评审
+///
+/// `message_ref` 为 "last"（最后一条回复）、"ts:<timestamp>"（按记录时间戳），
+/// 或回复的 ID（Claude 记录 uuid / message.id、Gemini 消息 id）
+#[tauri::command]
+pub async fn cross_engine_review(
+    app: AppHandle,
+    source_engine: String,
+    session_id: String,
+    message_ref: String,
+    target_engine: String,
+    review_instruction: String,
+    options: CrossEngineReviewOptions,
+) -> Result<CrossEngineReviewResult, String> {
+    log::info!(
+        "cross_engine_review called: {} {} ({}) -> {}",
+        source_engine,
+        session_id,
+        message_ref,
+        target_engine
+    );
+
+    for engine in [&source_engine, &target_engine] {
+        if !matches!(engine.as_str(), "claude" | "codex" | "gemini") {
+            return Err(format!("Unsupported engine: {}", engine));
+        }
+    }
+
+    let content = {
+        let engine = source_engine.clone();
+        let session_id = session_id.clone();
+        let locator = options
+            .source_project
+            .clone()
+            .unwrap_or_else(|| options.project_path.clone());
+        tokio::task::spawn_blocking(move || {
+            let path = resolve_session_file(&engine, &session_id, &locator)?;
+            let messages = read_assistant_messages(&engine, &path)?;
+            select_message(&messages, &message_ref)
+                .map(|message| message.text.clone())
+                .ok_or_else(|| format!("Assistant message not found: {}", message_ref))
+        })
+        .await
+        .map_err(|e| format!("Review message task failed: {}", e))??
+    };
+
+    let window = target_context_window(&target_engine, options.model.as_deref());
+    let original_tokens = estimate_tokens(&content) as u64;
+    let (content, notice) = if exceeds_context_limit(original_tokens, window) {
+        let compressed = compress_for_review(&app, &content).await?;
+        let notice = format!(
+            "回复约 {} tokens，超过 {} 上下文的 {}%，已压缩至约 {} tokens 后发送",
+            original_tokens,
+            engine_label(&target_engine),
+            CONTEXT_LIMIT_PERCENT,
+            estimate_tokens(&compressed)
+        );
+        (compressed, Some(notice))
+    } else {
+        (content, None)
+    };
+    let sent_tokens = estimate_tokens(&content) as u64;
+
+    let config: CrossEngineReviewConfig = load_json_config(get_config_path()?)?;
+    let instruction = if review_instruction.trim().is_empty() {
+        DEFAULT_REVIEW_INSTRUCTION
+    } else {
+        review_instruction.trim()
+    };
+    let prompt = render_template(
+        &config.template,
+        engine_label(&source_engine),
+        instruction,
+        &content,
+    );
+
+    let target = match options.target_session_id.clone() {
+        Some(id) => PromptTarget::Resume(id),
+        None => PromptTarget::New,
+    };
+    let source = SessionRef {
+        engine: source_engine,
+        session_id,
+        project_path: options.project_path.clone(),
+    };
+    let review_id = uuid::Uuid::new_v4().to_string();
+
+    // 先订阅再发送，避免错过很快结束的任务；完成事件按本次执行的 trace id 对应
+    let rx = subscribe_task_completions();
+    dispatch_prompt_traced(
+        app.clone(),
+        &target_engine,
+        &options.project_path,
+        target,
+        &prompt,
+        options.model.clone(),
+        options.mode.clone(),
+        Some(review_id.clone()),
+    )
+    .await?;
+
+    match &options.target_session_id {
+        Some(target_session_id) => {
+            let reviewer = SessionRef {
+                engine: target_engine.clone(),
+                session_id: target_session_id.clone(),
+                project_path: options.project_path.clone(),
+            };
+            link_sessions(&reviewer, &source, SessionRelation::ReviewOf)?;
+        }
+        None => {
+            let app = app.clone();
+            let review_id = review_id.clone();
+            let target_engine = target_engine.clone();
+            let project_path = options.project_path.clone();
+            tokio::spawn(async move {
+                let linked =
+                    link_new_review_session(rx, &target_engine, &project_path, &review_id, &source)
+                        .await;
+                if let Err(e) = &linked {
+                    log::warn!("[CrossEngineReview] {} not linked: {}", review_id, e);
+                }
+                let (target_session_id, error) = match linked {
+                    Ok(id) => (Some(id), None),
+                    Err(e) => (None, Some(e)),
+                };
+                let _ = app.emit(
+                    "cross-engine-review-linked",
+                    CrossEngineReviewLinked {
+                        review_id,
+                        target_engine,
+                        success: target_session_id.is_some(),
+                        target_session_id,
+                        error,
+                    },
+                );
+            });
+        }
+    }
+
+    Ok(CrossEngineReviewResult {
+        review_id,
+        target_engine,
+        target_session_id: options.target_session_id,
+        compressed: notice.is_some(),
+        original_tokens,
+        sent_tokens,
+        notice,
+    })
+}
+
+fn get_config_path() -> Result<PathBuf, String> {
+    Ok(ConfigPathBuilder::from_home_subdir(".anycode")?.build("cross_engine_review.json"))
+}
+
+fn engine_label(engine: &str) -> &'static str {
+    match engine {
+        "codex" => "Codex",
+        "gemini" => "Gemini",
+        _ => "Claude",
+    }
+}
+
+fn target_context_window(engine: &str, model: Option<&str>) -> u64 {
+    match engine {
+        "codex" => model
+            .and_then(model_context_window)
+            .unwrap_or(DEFAULT_CODEX_CONTEXT_WINDOW),
+        "gemini" => GEMINI_CONTEXT_WINDOW,
+        _ => CLAUDE_CONTEXT_WINDOW,
+    }
+}
+
+fn exceeds_context_limit(tokens: u64, window: u64) -> bool {
+    tokens * 100 > window * CONTEXT_LIMIT_PERCENT
+}
+
+/// 按自动压缩策略的指令压缩回复
+async fn compress_for_review(app: &AppHandle, content: &str) -> Result<String, String> {
+    let instruction = app
+        .try_state::<AutoCompactState>()
+        .ok_or("Auto compact manager not initialized")?
+        .0
+        .compaction_instruction(&None)?;
+    let system = format!("{}{}", COMPRESSION_SYSTEM_PROMPT, instruction);
+
+    let compressed = request_provider_model(&system, content, COMPRESSION_MAX_TOKENS).await?;
+    if compressed.trim().is_empty() {
+        return Err("压缩结果为空".to_string());
+    }
+    Ok(compressed)
+}
+
+/// 单遍替换模板占位符，替换进来的内容中的占位符不会被再次展开
+fn render_template(
+    template: &str,
+    source_engine: &str,
+    instruction: &str,
+    content: &str,
+) -> String {
+    let mut rendered = String::with_capacity(template.len() + content.len() + instruction.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        rendered.push_str(&rest[..start]);
+        let tail = &rest[start..];
+        let (value, len) = if tail.starts_with("{source_engine}") {
+            (source_engine, "{source_engine}".len())
+        } else if tail.starts_with("{instruction}") {
+            (instruction, "{instruction}".len())
+        } else if tail.starts_with("{content}") {
+            (content, "{content}".len())
+        } else {
+            ("{", 1)
+        };
+        rendered.push_str(value);
+        rest = &tail[len..];
+    }
+    rendered.push_str(rest);
+    rendered
+}
+
+/// 等待新建的评审会话完成，取完成事件上绑定的 CLI 会话 ID 并与来源会话关联
+async fn link_new_review_session(
+    mut rx: Receiver<TaskCompletionEvent>,
+    engine: &str,
+    project_path: &str,
+    trace_id: &str,
+    source: &SessionRef,
+) -> Result<String, String> {
+    let event = wait_for_completion(&mut rx, trace_id).await?;
+    let session_id = event.cli_session_id.ok_or("未找到评审任务创建的会话")?;
+
+    let reviewer = SessionRef {
+        engine: engine.to_string(),
+        session_id: session_id.clone(),
+        project_path: project_path.to_string(),
+    };
+    link_sessions(&reviewer, source, SessionRelation::ReviewOf)?;
+    Ok(session_id)
+}
+
+async fn wait_for_completion(
+    rx: &mut Receiver<TaskCompletionEvent>,
+    trace_id: &str,
+) -> Result<TaskCompletionEvent, String> {
+    let deadline = Instant::now() + Duration::from_secs(COMPLETION_TIMEOUT_SECS);
+
+    loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        match tokio::time::timeout(remaining, rx.recv()).await {
+            Ok(Ok(event)) => {
+                if event.trace_id.as_deref() == Some(trace_id) {
+                    return Ok(event);
+                }
+            }
+            Ok(Err(RecvError::Lagged(skipped))) => {
+                log::warn!("[CrossEngineReview] Missed {} completion events", skipped);
+            }
+            Ok(Err(RecvError::Closed)) => {
+                return Err("Completion event channel closed".to_string());
+            }
+            Err(_) => {
+                return Err(format!("等待完成超时（{} 秒）", COMPLETION_TIMEOUT_SECS));
+            }
+        }
+    }
+}
+
+// ============================================================================
+// Message Extraction
+// ============================================================================
+
+/// 读取会话中的全部 assistant 回复（只保留有文本的回复）
+pub(crate) fn read_assistant_messages(
+    engine: &str,
+    path: &Path,
+) -> Result<Vec<AssistantMessage>, String> {
+    let mut messages: Vec<AssistantMessage> = Vec::new();
+
+    if engine == "gemini" {
+        ensure_loadable_in_memory(path)?;
+        let content =
+            fs::read_to_string(path).map_err(|e| format!("Failed to read session file: {}", e))?;
+        let data: Value = serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to parse session JSON: {}", e))?;
+        for message in data
+            .get("messages")
+            .and_then(|m| m.as_array())
+            .into_iter()
+            .flatten()
+        {
+            if message.get("type").and_then(|t| t.as_str()) != Some("gemini") {
+                continue;
+            }
+            messages.push(AssistantMessage {
+                ids: string_field(message, "id").into_iter().collect(),
+                timestamps: string_field(message, "timestamp").into_iter().collect(),
+                text: message
+                    .get("content")
+                    .and_then(|c| c.as_str())
+                    .unwrap_or_default()
+                    .to_string(),
+            });
+        }
+    } else {
+        // Claude 的一条回复会按内容块拆成多行记录，用 message.id 合并
+        let mut last_message_id: Option<String> = None;
+        for_each_line(path, |_, line| {
+            let Ok(entry) = serde_json::from_str::<Value>(line) else {
+                return ControlFlow::Continue(());
+            };
+            let Some((message_id, text)) = assistant_record(engine, &entry) else {
+                last_message_id = None;
+                return ControlFlow::Continue(());
+            };
+
+            let continues = message_id.is_some() && message_id == last_message_id;
+            if !continues {
+                messages.push(AssistantMessage::default());
+            }
+            let message = messages.last_mut().expect("message pushed above");
+            message.ids.extend(string_field(&entry, "uuid"));
+            message
+                .ids
+                .extend(message_id.clone().filter(|_| !continues));
+            message.timestamps.extend(string_field(&entry, "timestamp"));
+            if !text.trim().is_empty() {
+                if !message.text.is_empty() {
+                    message.text.push_str("\n\n");
+                }
+                message.text.push_str(&text);
+            }
+            last_message_id = message_id;
+            ControlFlow::Continue(())
+        })
+        .map_err(|e| format!("Failed to read session file: {}", e))?;
+    }
+
+    messages.retain(|message| !message.text.trim().is_empty());
+    Ok(messages)
+}
+
+/// 解析一行 Claude / Codex 记录，是 assistant 回复时返回 (message.id, 文本)
+fn assistant_record(engine: &str, entry: &Value) -> Option<(Option<String>, String)> {
+    match engine {
+        "claude" => {
+            if entry.get("type").and_then(|t| t.as_str()) != Some("assistant")
+                || entry.get("isSidechain").and_then(|v| v.as_bool()) == Some(true)
+            {
+                return None;
+            }
+            let message = entry.get("message")?;
+            let text = message
+                .get("content")
+                .and_then(content_text)
+                .unwrap_or_default();
+            Some((string_field(message, "id"), text))
+        }
+        "codex" => {
+            if entry.get("type").and_then(|t| t.as_str()) != Some("response_item") {
+                return None;
+            }
+            let payload = entry.get("payload")?;
+            if payload.get("type").and_then(|t| t.as_str()) != Some("message")
+                || payload.get("role").and_then(|r| r.as_str()) != Some("assistant")
+            {
+                return None;
+            }
+            Some((None, content_text(payload.get("content")?)?))
+        }
+        _ => None,
+    }
+}
+
+fn string_field(value: &Value, key: &str) -> Option<String> {
+    value
+        .get(key)
+        .and_then(|v| v.as_str())
+        .map(|s| s.to_string())
+}
+
+fn select_message<'a>(
+    messages: &'a [AssistantMessage],
+    message_ref: &str,
+) -> Option<&'a AssistantMessage> {
+    if message_ref == "last" {
+        return messages.last();
+    }
+    if let Some(timestamp) = message_ref.strip_prefix("ts:") {
+        return messages
+            .iter()
+            .find(|message| message.timestamps.iter().any(|t| t == timestamp));
+    }
+    messages
+        .iter()
+        .find(|message| message.ids.iter().any(|id| id == message_ref))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn template_placeholders_are_not_expanded_twice() {
+        let prompt = render_template(
+            "{source_engine} says: {content} / {instruction} {unknown}",
+            "Claude",
+            "check {content}",
+            "use {instruction} here",
+        );
+        assert_eq!(
+            prompt,
+            "Claude says: use {instruction} here / check {content} {unknown}"
+        );
+    }
+
+    #[test]
+    fn merges_claude_records_of_one_reply() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("session.jsonl");
+        let lines = [
+            r#"{"type":"user","uuid":"u1","message":{"content":"plan it"}}"#,
+            r#"{"type":"assistant","uuid":"a1","timestamp":"t1","message":{"id":"msg_1","content":[{"type":"thinking","thinking":"hmm"}]}}"#,
+            r#"{"type":"assistant","uuid":"a2","timestamp":"t2","message":{"id":"msg_1","content":[{"type":"text","text":"Step 1"}]}}"#,
+            r#"{"type":"assistant","uuid":"a3","timestamp":"t3","message":{"id":"msg_1","content":[{"type":"text","text":"```rs\nfn main() {}\n```"}]}}"#,
+            r#"{"type":"assistant","uuid":"a4","isSidechain":true,"message":{"id":"msg_2","content":"side"}}"#,
+            r#"{"type":"assistant","uuid":"a5","timestamp":"t5","message":{"id":"msg_3","content":[{"type":"tool_use","id":"x","name":"Bash","input":{}}]}}"#,
+        ];
+        fs::write(&path, lines.join("\n")).unwrap();
+
+        let messages = read_assistant_messages("claude", &path).unwrap();
+
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].text, "Step 1\n\n```rs\nfn main() {}\n```");
+        for message_ref in ["last", "a1", "a3", "msg_1", "ts:t2"] {
+            assert_eq!(
+                select_message(&messages, message_ref).map(|m| m.text.as_str()),
+                Some(messages[0].text.as_str())
+            );
+        }
+        assert!(select_message(&messages, "a5").is_none());
+    }
+
+    #[test]
+    fn compresses_only_above_context_limit() {
+        assert!(!exceeds_context_limit(140_000, CLAUDE_CONTEXT_WINDOW));
+        assert!(exceeds_context_limit(140_001, CLAUDE_CONTEXT_WINDOW));
+        assert_eq!(
+            target_context_window("codex", Some("unknown-model")),
+            DEFAULT_CODEX_CONTEXT_WINDOW
+        );
+    }
+
+    #[tokio::test]
+    async fn takes_the_cli_session_of_its_own_review_run() {
+        let completion = |trace_id: &str, cli_session_id: &str| TaskCompletionEvent {
+            engine: "gemini".to_string(),
+            project_path: "/work/app".to_string(),
+            session_id: Some(format!("gemini-{}", trace_id)),
+            cli_session_id: Some(cli_session_id.to_string()),
+            mode: None,
+            success: true,
+            duration_ms: 10,
+            trace_id: Some(trace_id.to_string()),
+        };
+        let (tx, mut rx) = tokio::sync::broadcast::channel(8);
+        // 同一项目中同时结束的另一个会话不能被当作评审会话
+        tx.send(completion("other-run", "cli-other")).unwrap();
+        tx.send(completion("review-run", "cli-review")).unwrap();
+
+        let event = wait_for_completion(&mut rx, "review-run").await.unwrap();
+        assert_eq!(event.cli_session_id.as_deref(), Some("cli-review"));
+    }
+}
@@ -0,0 +1,464 @@
+//! 诊断信息收集
+//!
+//! 汇总运行环境、WSL 状态、各 CLI 的检测结果、acemcp sidecar 状态、
+//! 生效配置（敏感字段已打码）以及各引擎会话数量，生成可直接粘贴到 issue 的 JSON。
+
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::path::Path;
+use tauri::AppHandle;
+use tokio::process::Command;
+
+use crate::claude_binary::{
+    detect_binary_for_tool, detect_runtime_environment, find_claude_binary, RuntimeEnvironment,
+};
+use crate::commands::claude::{apply_no_window_async, get_claude_dir};
+use crate::commands::wsl_utils;
+
+/// 打码后的占位值
+pub(crate) const REDACTED: &str = "<redacted>";
+
+/// 诊断报告
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiagnosticsReport {
+    pub generated_at: String,
+    pub app_version: String,
+    pub environment: RuntimeEnvironment,
+    pub wsl: WslDiagnostics,
+    pub binaries: Vec<BinaryDiagnostics>,
+    pub acemcp: AcemcpDiagnostics,
+    /// 生效配置（敏感字段已打码）
+    pub config: Value,
+    pub sessions: SessionCounts,
+}
+
+/// WSL 状态
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WslDiagnostics {
+    pub available: bool,
+    pub distros: Vec<String>,
+    pub default_distro: Option<String>,
+    pub engines: Vec<WslEngineDiagnostics>,
+}
+
+/// 单个引擎的 WSL 运行时状态
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WslEngineDiagnostics {
+    pub engine: String,
+    pub enabled: bool,
+    pub distro: Option<String>,
+    pub path_in_wsl: Option<String>,
+}
+
+/// 可执行文件检测结果
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BinaryDiagnostics {
+    pub tool: String,
+    /// 实际会被使用的路径
+    pub path: Option<String>,
+    pub version: Option<String>,
+    /// 检测来源（PATH、env、用户配置等）
+    pub source: Option<String>,
+    pub error: Option<String>,
+}
+
+/// acemcp sidecar 状态
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AcemcpDiagnostics {
+    pub sidecar_path: Option<String>,
+    pub extracted: bool,
+    pub runs: bool,
+    pub error: Option<String>,
+}
+
+/// 各引擎会话数量
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionCounts {
+    pub claude: usize,
+    pub codex: usize,
+    pub gemini: usize,
+}
+
+/// 键名是否可能包含敏感信息
+pub(crate) fn is_sensitive_key(key: &str) -> bool {
+    let key = key.to_ascii_lowercase();
+    [
+        "key",
+        "token",
+        "secret",
+        "password",
+        "authorization",
+        "cookie",
+        "credential",
+    ]
+    .iter()
+    .any(|needle| key.contains(needle))
+}
+
+/// 递归打码 JSON 中的敏感字段（保留"是否已设置"的信息）
+fn redact_secrets(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, v) in map.iter_mut() {
+                if is_sensitive_key(key) && (v.is_string() || v.is_number()) {
+                    let is_empty = v.as_str().is_some_and(|s| s.is_empty());
+                    if !is_empty {
+                        *v = Value::String(REDACTED.to_string());
+                    }
+                } else {
+                    redact_secrets(v);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(redact_secrets),
+        _ => {}
+    }
+}
+
+fn collect_wsl() -> WslDiagnostics {
+    let codex = wsl_utils::get_wsl_config();
+    let claude = wsl_utils::get_claude_wsl_runtime();
+    let gemini = wsl_utils::get_gemini_wsl_runtime();
+
+    WslDiagnostics {
+        available: wsl_utils::is_wsl_available(),
+        distros: wsl_utils::get_wsl_distros(),
+        default_distro: wsl_utils::get_default_wsl_distro(),
+        engines: vec![
+            WslEngineDiagnostics {
+                engine: "claude".to_string(),
+                enabled: claude.enabled,
+                distro: claude.distro.clone(),
+                path_in_wsl: claude.claude_path_in_wsl.clone(),
+            },
+            WslEngineDiagnostics {
+                engine: "codex".to_string(),
+                enabled: codex.enabled,
+                distro: codex.distro.clone(),
+                path_in_wsl: codex.codex_path_in_wsl.clone(),
+            },
+            WslEngineDiagnostics {
+                engine: "gemini".to_string(),
+                enabled: gemini.enabled,
+                distro: gemini.distro.clone(),
+                path_in_wsl: gemini.gemini_path_in_wsl.clone(),
+            },
+        ],
+    }
+}
+
+/// 结合"实际使用的路径"与统一检测结果（版本、来源）
+fn binary_diagnostics(
+    tool: &str,
+    env_var: &str,
+    effective_path: Result<String, String>,
+) -> BinaryDiagnostics {
+    let (_env, detected) = detect_binary_for_tool(tool, env_var, tool);
+    let (path, error) = match effective_path {
+        Ok(path) => (Some(path), None),
+        Err(e) => (None, Some(e)),
+    };
+    BinaryDiagnostics {
+        tool: tool.to_string(),
+        path,
+        version: detected.as_ref().and_then(|d| d.version.clone()),
+        source: detected.map(|d| d.source),
+        error,
+    }
+}
+
+/// 检测 node（acemcp sidecar 通过 PATH 中的 node 运行）
+async fn node_diagnostics() -> BinaryDiagnostics {
+    let mut cmd = Command::new("node");
+    cmd.args(["-p", "process.execPath + '\\n' + process.version"]);
+    apply_no_window_async(&mut cmd);
+
+    match cmd.output().await {
+        Ok(output) if output.status.success() => {
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            let mut lines = stdout.lines();
+            BinaryDiagnostics {
+                tool: "node".to_string(),
+                path: lines.next().map(|s| s.trim().to_string()),
+                version: lines.next().map(|s| s.trim().to_string()),
+                source: Some("PATH".to_string()),
+                error: None,
+            }
+        }
+        Ok(output) => BinaryDiagnostics {
+            tool: "node".to_string(),
+            path: None,
+            version: None,
+            source: None,
+            error: Some(String::from_utf8_lossy(&output.stderr).trim().to_string()),
+        },
+        Err(e) => BinaryDiagnostics {
+            tool: "node".to_string(),
+            path: None,
+            version: None,
+            source: None,
+            error: Some(e.to_string()),
+        },
+    }
+}
+
+async fn collect_binaries(app: &AppHandle) -> Vec<BinaryDiagnostics> {
+    let mut codex = binary_diagnostics(
+        "codex",
+        "CODEX_PATH",
+        crate::commands::codex::config::get_codex_path(app.clone()).await,
+    );
+    // Codex 的版本以可用性检查（实际执行 --version）为准
+    if let Ok(availability) = crate::commands::codex::config::check_codex_availability().await {
+        codex.version = availability.version.or(codex.version);
+    }
+
+    vec![
+        binary_diagnostics("claude", "CLAUDE_PATH", find_claude_binary(app)),
+        codex,
+        binary_diagnostics(
+            "gemini",
+            "GEMINI_CLI_PATH",
+            crate::commands::gemini::session::find_gemini_binary(),
+        ),
+        node_diagnostics().await,
+    ]
+}
+
+async fn collect_acemcp(app: &AppHandle) -> AcemcpDiagnostics {
+    let (sidecar_path, extracted, error) = match crate::commands::acemcp::sidecar_path() {
+        Ok(path) => {
+            let exists = path.exists();
+            (Some(path.to_string_lossy().to_string()), exists, None)
+        }
+        Err(e) => (None, false, Some(e)),
+    };
+
+    let runs = extracted
+        && crate::commands::acemcp::test_acemcp_availability(app.clone())
+            .await
+            .unwrap_or(false);
+
+    AcemcpDiagnostics {
+        sidecar_path,
+        extracted,
+        runs,
+        error,
+    }
+}
+
+/// 收集生效配置，失败的项记录为错误字符串
+async fn collect_config() -> Value {
+    fn or_error<T: Serialize>(result: Result<T, String>) -> Value {
+        match result {
+            Ok(v) => serde_json::to_value(v).unwrap_or(Value::Null),
+            Err(e) => json!({ "error": e }),
+        }
+    }
+
+    let codex_mode = wsl_utils::get_codex_config();
+    let mut config = json!({
+        "claude": {
+            "settings": or_error(
+                crate::commands::claude::get_claude_settings()
+                    .await
+                    .map(|s| s.data),
+            ),
+        },
+        "codex": {
+            "mode": format!("{:?}", codex_mode.mode),
+            "wslDistro": codex_mode.wsl_distro,
+            "current": or_error(
+                crate::commands::codex::config::get_current_codex_config()
+                    .await
+                    .map_err(String::from)
+                    .map(|c| json!({
+                        "baseUrl": c.base_url,
+                        "model": c.model,
+                        "apiKey": c.api_key,
+                        "config": c.config,
+                    })),
+            ),
+        },
+        "gemini": or_error(
+            crate::commands::gemini::provider::get_current_gemini_provider_config()
+                .await
+                .map_err(String::from)
+                .map(|c| json!({
+                    "baseUrl": c.base_url,
+                    "model": c.model,
+                    "apiKey": c.api_key,
+                    "selectedAuthType": c.selected_auth_type,
+                    "env": c.env,
+                })),
+        ),
+        "acemcp": or_error(crate::commands::acemcp::load_acemcp_config().await),
+    });
+    redact_config_toml(&mut config["codex"]["current"]["config"]);
+    redact_secrets(&mut config);
+    config
+}
+
+/// config.toml 以字符串形式保存，转成 JSON 后再统一打码
+fn redact_config_toml(value: &mut Value) {
+    let Some(text) = value.as_str() else {
+        return;
+    };
+    *value = match toml::from_str::<toml::Table>(text) {
+        Ok(table) => {
+            let mut parsed = serde_json::to_value(table).unwrap_or(Value::Null);
+            // 自定义请求头的值可能是鉴权信息，统一打码
+            if let Some(providers) = parsed
+                .get_mut("model_providers")
+                .and_then(|p| p.as_object_mut())
+            {
+                for provider in providers.values_mut() {
+                    if let Some(headers) = provider
+                        .get_mut("http_headers")
+                        .and_then(|h| h.as_object_mut())
+                    {
+                        for v in headers.values_mut() {
+                            *v = Value::String(REDACTED.to_string());
+                        }
+                    }
+                }
+            }
+            parsed
+        }
+        Err(e) => json!({ "error": format!("Invalid TOML: {}", e) }),
+    };
+}
+
+/// 递归统计目录下满足条件的文件数
+fn count_files(dir: &Path, matches: &dyn Fn(&Path) -> bool) -> usize {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return 0;
+    };
+    entries
+        .flatten()
+        .map(|entry| entry.path())
+        .map(|path| {
+            if path.is_dir() {
+                count_files(&path, matches)
+            } else {
+                usize::from(matches(&path))
+            }
+        })
+        .sum()
+}
+
+fn file_name_matches(path: &Path, pred: impl Fn(&str) -> bool) -> bool {
+    path.file_name().and_then(|n| n.to_str()).is_some_and(pred)
+}
+
+fn collect_session_counts() -> SessionCounts {
+    let claude = get_claude_dir()
+        .map(|dir| {
+            count_files(&dir.join("projects"), &|p| {
+                file_name_matches(p, |n| n.ends_with(".jsonl") && !n.starts_with("agent-"))
+            })
+        })
+        .unwrap_or(0);
+
+    let codex = crate::commands::codex::config::get_codex_sessions_dir()
+        .map(|dir| {
+            count_files(&dir, &|p| {
+                file_name_matches(p, |n| n.starts_with("rollout-") && n.ends_with(".jsonl"))
+            })
+        })
+        .unwrap_or(0);
+
+    // Gemini 会话位于 ~/.gemini/tmp/<project-hash>/chats/*.json
+    let gemini = crate::commands::gemini::config::get_gemini_dir()
+        .map(|dir| {
+            count_files(&dir.join("tmp"), &|p| {
+                p.parent()
+                    .and_then(|parent| parent.file_name())
+                    .is_some_and(|n| n == "chats")
+                    && file_name_matches(p, |n| n.ends_with(".json"))
+            })
+        })
+        .unwrap_or(0);
+
+    SessionCounts {
+        claude,
+        codex,
+        gemini,
+    }
+}
+
+/// 收集诊断信息（用于提交问题报告）
+#[tauri::command]
+pub async fn collect_diagnostics(app: AppHandle) -> Result<DiagnosticsReport, String> {
+    log::info!("Collecting diagnostics report");
+
+    let wsl = tokio::task::spawn_blocking(collect_wsl)
+        .await
+        .map_err(|e| format!("Failed to collect WSL status: {}", e))?;
+    let binaries = collect_binaries(&app).await;
+    let acemcp = collect_acemcp(&app).await;
+    let config = collect_config().await;
+    let sessions = tokio::task::spawn_blocking(collect_session_counts)
+        .await
+        .map_err(|e| format!("Failed to count sessions: {}", e))?;
+
+    Ok(DiagnosticsReport {
+        generated_at: chrono::Utc::now().to_rfc3339(),
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        environment: detect_runtime_environment(),
+        wsl,
+        binaries,
+        acemcp,
+        config,
+        sessions,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn redacts_nested_secrets_but_keeps_other_values() {
+        let mut value = json!({
+            "env": {
+                "ANTHROPIC_AUTH_TOKEN": "sk-live-123",
+                "ANTHROPIC_BASE_URL": "https://gateway.example.com",
+                "GEMINI_API_KEY": ""
+            },
+            "acemcp": { "token": "abc", "batchSize": 10 },
+        });
+        redact_secrets(&mut value);
+
+        assert_eq!(value["env"]["ANTHROPIC_AUTH_TOKEN"], REDACTED);
+        assert_eq!(
+            value["env"]["ANTHROPIC_BASE_URL"],
+            "https://gateway.example.com"
+        );
+        assert_eq!(value["env"]["GEMINI_API_KEY"], "");
+        assert_eq!(value["acemcp"]["token"], REDACTED);
+        assert_eq!(value["acemcp"]["batchSize"], 10);
+    }
+
+    #[test]
+    fn redacts_codex_http_headers() {
+        let mut value = Value::String(
+            "model_provider = \"gw\"\n[model_providers.gw]\nbase_url = \"https://gw\"\n\
+             [model_providers.gw.http_headers]\nX-Org-Id = \"org-1\"\n"
+                .to_string(),
+        );
+        redact_config_toml(&mut value);
+
+        assert_eq!(
+            value["model_providers"]["gw"]["http_headers"]["X-Org-Id"],
+            REDACTED
+        );
+        assert_eq!(value["model_providers"]["gw"]["base_url"], "https://gw");
+    }
+}
@@ -0,0 +1,444 @@
+//! 生效配置预览
+//!
+//! 各子系统的配置分散在多层（内置默认值、用户级文件、项目级文件）。这里复用各子系统的
+//! 路径与默认值，按优先级合并出最终生效的值，并标注每个值来自哪一层、覆盖了哪些层。
+//! 只读，不修改任何配置；敏感字段与诊断报告一样打码。
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use super::acemcp::AcemcpConfigData;
+use super::claude::get_claude_dir;
+use super::diagnostics::{is_sensitive_key, REDACTED};
+use super::permission_config::ClaudeExecutionConfig;
+use super::prompt_tracker::{get_prompt_filter_config_path, PromptFilterConfig};
+use super::wsl_utils::CodexConfig;
+use crate::utils::jsonl_reader::{get_session_reader_config_path, SessionReaderConfig};
+
+/// 配置层（按优先级从低到高）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ConfigLayer {
+    /// 应用或 CLI 内置默认值
+    Default,
+    /// 用户级配置文件
+    User,
+    /// 项目级配置文件（提交到仓库）
+    Project,
+    /// 项目本地配置文件（不提交）
+    ProjectLocal,
+}
+
+/// 单个配置项的生效值及来源
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EffectiveValue {
+    /// 配置键（嵌套键以 "." 连接）
+    pub key: String,
+    pub value: Value,
+    /// 生效值所在的层
+    pub source: ConfigLayer,
+    /// 生效值所在的文件
+    pub path: Option<String>,
+    /// 被覆盖的低优先级层
+    pub overrides: Vec<ConfigLayer>,
+}
+
+/// 单个子系统的生效配置
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubsystemConfig {
+    pub name: String,
+    /// 参与合并的配置文件（按优先级从低到高，含不存在的文件）
+    pub files: Vec<String>,
+    pub values: Vec<EffectiveValue>,
+    /// 读取或解析失败的层
+    pub errors: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EffectiveConfig {
+    pub project_path: Option<String>,
+    pub subsystems: Vec<SubsystemConfig>,
+}
+
+/// 一层配置（已展开为扁平键）
+struct Layer {
+    kind: ConfigLayer,
+    path: Option<PathBuf>,
+    values: Map<String, Value>,
+}
+
+/// 将嵌套对象展开为 "a.b" 形式的键，最多展开 depth 层；数组与标量保持原样
+fn flatten(value: &Value, prefix: &str, depth: usize, out: &mut Map<String, Value>) {
+    match value {
+        Value::Object(map) if depth > 0 && !map.is_empty() => {
+            for (key, v) in map {
+                let key = if prefix.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{}.{}", prefix, key)
+                };
+                flatten(v, &key, depth - 1, out);
+            }
+        }
+        _ => {
+            if !prefix.is_empty() {
+                out.insert(prefix.to_string(), value.clone());
+            }
+        }
+    }
+}
+
+fn flattened(value: &Value, depth: usize) -> Map<String, Value> {
+    let mut out = Map::new();
+    flatten(value, "", depth, &mut out);
+    out
+}
+
+/// 键路径中任一段敏感（或是自定义请求头）时打码
+fn redact_value(key: &str, value: Value) -> Value {
+    let sensitive = key
+        .split('.')
+        .any(|segment| is_sensitive_key(segment) || segment == "http_headers");
+    let is_empty = value.as_str().is_some_and(|s| s.is_empty()) || value.is_null();
+    if sensitive && !is_empty && !value.is_boolean() {
+        Value::String(REDACTED.to_string())
+    } else {
+        value
+    }
+}
+
+/// 按优先级合并各层：高优先级层的同名键覆盖低优先级层
+fn merge_layers(layers: &[Layer]) -> Vec<EffectiveValue> {
+    let mut merged: BTreeMap<String, EffectiveValue> = BTreeMap::new();
+
+    for layer in layers {
+        for (key, value) in &layer.values {
+            let path = layer.path.as_ref().map(|p| p.to_string_lossy().to_string());
+            let value = redact_value(key, value.clone());
+            match merged.get_mut(key) {
+                Some(entry) => {
+                    entry.overrides.push(entry.source);
+                    entry.value = value;
+                    entry.source = layer.kind;
+                    entry.path = path;
+                }
+                None => {
+                    merged.insert(
+                        key.clone(),
+                        EffectiveValue {
+                            key: key.clone(),
+                            value,
+                            source: layer.kind,
+                            path,
+                            overrides: Vec::new(),
+                        },
+                    );
+                }
+            }
+        }
+    }
+
+    merged.into_values().collect()
+}
+
+fn default_layer<T: Serialize>(defaults: &T, depth: usize) -> Layer {
+    Layer {
+        kind: ConfigLayer::Default,
+        path: None,
+        values: flattened(&serde_json::to_value(defaults).unwrap_or(Value::Null), depth),
+    }
+}
+
+/// 读取一层配置文件（不存在时返回 None），parse 负责把文件内容转为 JSON
+fn read_layer(
+    kind: ConfigLayer,
+    path: &Path,
+    depth: usize,
+    parse: fn(&str) -> Result<Value, String>,
+) -> Result<Option<Layer>, String> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let value = parse(&content).map_err(|e| format!("Failed to parse {}: {}", path.display(), e))?;
+    Ok(Some(Layer {
+        kind,
+        path: Some(path.to_path_buf()),
+        values: flattened(&value, depth),
+    }))
+}
+
+fn parse_json(content: &str) -> Result<Value, String> {
+    serde_json::from_str(content).map_err(|e| e.to_string())
+}
+
+fn parse_toml(content: &str) -> Result<Value, String> {
+    let table = toml::from_str::<toml::Table>(content).map_err(|e| e.to_string())?;
+    serde_json::to_value(table).map_err(|e| e.to_string())
+}
+
+/// 组装子系统：依次读取各文件层，失败的层记录错误后跳过
+fn build_subsystem(
+    name: &str,
+    defaults: Option<Layer>,
+    files: Vec<(ConfigLayer, Result<PathBuf, String>)>,
+    depth: usize,
+    parse: fn(&str) -> Result<Value, String>,
+) -> SubsystemConfig {
+    let mut layers: Vec<Layer> = defaults.into_iter().collect();
+    let mut file_list = Vec::new();
+    let mut errors = Vec::new();
+
+    for (kind, path) in files {
+        let path = match path {
+            Ok(path) => path,
+            Err(e) => {
+                errors.push(e);
+                continue;
+            }
+        };
+        file_list.push(path.to_string_lossy().to_string());
+        match read_layer(kind, &path, depth, parse) {
+            Ok(Some(layer)) => layers.push(layer),
+            Ok(None) => {}
+            Err(e) => errors.push(e),
+        }
+    }
+
+    SubsystemConfig {
+        name: name.to_string(),
+        files: file_list,
+        values: merge_layers(&layers),
+        errors,
+    }
+}
+
+fn home_path(parts: &[&str]) -> Result<PathBuf, String> {
+    let mut path = dirs::home_dir().ok_or("Failed to get home directory")?;
+    path.extend(parts);
+    Ok(path)
+}
+
+/// Claude Code settings：用户级 < 项目级 < 项目本地
+fn claude_settings(project: Option<&Path>) -> SubsystemConfig {
+    let mut files = vec![(
+        ConfigLayer::User,
+        get_claude_dir()
+            .map(|d| d.join("settings.json"))
+            .map_err(|e| e.to_string()),
+    )];
+    if let Some(project) = project {
+        files.push((ConfigLayer::Project, Ok(project.join(".claude").join("settings.json"))));
+        files.push((
+            ConfigLayer::ProjectLocal,
+            Ok(project.join(".claude").join("settings.local.json")),
+        ));
+    }
+    // env / permissions 按子键合并
+    build_subsystem("claudeSettings", None, files, 2, parse_json)
+}
+
+/// 执行配置：默认值 < ~/.claude/execution_config.json
+fn execution_config() -> SubsystemConfig {
+    build_subsystem(
+        "executionConfig",
+        Some(default_layer(&ClaudeExecutionConfig::default(), 2)),
+        vec![(
+            ConfigLayer::User,
+            get_claude_dir()
+                .map(|d| d.join("execution_config.json"))
+                .map_err(|e| e.to_string()),
+        )],
+        2,
+        parse_json,
+    )
+}
+
+/// Codex CLI：~/.codex/config.toml（WSL 模式下为 WSL 内的路径）
+fn codex_config() -> SubsystemConfig {
+    build_subsystem(
+        "codex",
+        None,
+        vec![(
+            ConfigLayer::User,
+            super::codex::config::get_codex_config_path(),
+        )],
+        3,
+        parse_toml,
+    )
+}
+
+/// Codex 运行环境（原生 / WSL）：默认值 < ~/.codex/workbench_config.json
+fn codex_runtime() -> SubsystemConfig {
+    build_subsystem(
+        "codexRuntime",
+        Some(default_layer(&CodexConfig::default(), 1)),
+        vec![(
+            ConfigLayer::User,
+            home_path(&[".codex", "workbench_config.json"]),
+        )],
+        1,
+        parse_json,
+    )
+}
+
+/// Gemini CLI：用户级 settings.json 与 .env < 项目级 settings.json
+fn gemini_config(project: Option<&Path>) -> SubsystemConfig {
+    let mut files = vec![(
+        ConfigLayer::User,
+        super::gemini::provider::get_gemini_settings_path(),
+    )];
+    if let Some(project) = project {
+        files.push((ConfigLayer::Project, Ok(project.join(".gemini").join("settings.json"))));
+    }
+    let mut subsystem = build_subsystem("gemini", None, files, 2, parse_json);
+
+    // .env 中的变量以 env.* 展示（用户级）
+    match super::gemini::provider::get_gemini_env_path() {
+        Ok(env_path) => match super::gemini::provider::read_env_file(&env_path) {
+            Ok(env) if !env.is_empty() => {
+                let values: Map<String, Value> = env
+                    .into_iter()
+                    .map(|(k, v)| (format!("env.{}", k), Value::String(v)))
+                    .collect();
+                let env_layer = Layer {
+                    kind: ConfigLayer::User,
+                    path: Some(env_path.clone()),
+                    values,
+                };
+                subsystem.values.extend(merge_layers(&[env_layer]));
+                subsystem.values.sort_by(|a, b| a.key.cmp(&b.key));
+                subsystem
+                    .files
+                    .insert(0, env_path.to_string_lossy().to_string());
+            }
+            Ok(_) => {}
+            Err(e) => subsystem.errors.push(e),
+        },
+        Err(e) => subsystem.errors.push(e),
+    }
+    subsystem
+}
+
+/// acemcp：默认值 < ~/.acemcp/config.toml
+fn acemcp_config() -> SubsystemConfig {
+    let defaults = AcemcpConfigData::default();
+    let mut default_values = Map::new();
+    default_values.insert("BASE_URL".to_string(), Value::from(defaults.base_url));
+    default_values.insert("TOKEN".to_string(), Value::from(defaults.token));
+    default_values.insert("BATCH_SIZE".to_string(), Value::from(defaults.batch_size));
+    default_values.insert(
+        "MAX_LINES_PER_BLOB".to_string(),
+        Value::from(defaults.max_lines_per_blob),
+    );
+    default_values.insert(
+        "REQUEST_TIMEOUT_SECS".to_string(),
+        Value::from(defaults.request_timeout_secs),
+    );
+
+    build_subsystem(
+        "acemcp",
+        Some(Layer {
+            kind: ConfigLayer::Default,
+            path: None,
+            values: default_values,
+        }),
+        vec![(ConfigLayer::User, home_path(&[".acemcp", "config.toml"]))],
+        1,
+        parse_toml,
+    )
+}
+
+fn session_reader_config() -> SubsystemConfig {
+    build_subsystem(
+        "sessionReader",
+        Some(default_layer(&SessionReaderConfig::default(), 1)),
+        vec![(ConfigLayer::User, get_session_reader_config_path())],
+        1,
+        parse_json,
+    )
+}
+
+fn prompt_filter_config() -> SubsystemConfig {
+    build_subsystem(
+        "promptFilter",
+        Some(default_layer(&PromptFilterConfig::default(), 1)),
+        vec![(ConfigLayer::User, get_prompt_filter_config_path())],
+        1,
+        parse_json,
+    )
+}
+
+/// 预览合并后的生效配置（含每个值的来源层）
+#[tauri::command]
+pub async fn get_effective_config(project_path: Option<String>) -> Result<EffectiveConfig, String> {
+    let project_path = project_path.filter(|p| !p.trim().is_empty());
+
+    tokio::task::spawn_blocking(move || {
+        let project = project_path.as_deref().map(Path::new);
+        EffectiveConfig {
+            subsystems: vec![
+                claude_settings(project),
+                execution_config(),
+                codex_config(),
+                codex_runtime(),
+                gemini_config(project),
+                acemcp_config(),
+                session_reader_config(),
+                prompt_filter_config(),
+            ],
+            project_path,
+        }
+    })
+    .await
+    .map_err(|e| format!("Failed to resolve effective config: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn layer(kind: ConfigLayer, value: Value) -> Layer {
+        Layer {
+            kind,
+            path: None,
+            values: flattened(&value, 2),
+        }
+    }
+
+    #[test]
+    fn higher_layers_win_and_record_overrides() {
+        let values = merge_layers(&[
+            layer(ConfigLayer::User, json!({"model": "sonnet", "env": {"A": "1", "B": "2"}})),
+            layer(ConfigLayer::Project, json!({"env": {"B": "3"}})),
+            layer(ConfigLayer::ProjectLocal, json!({"model": "opus"})),
+        ]);
+
+        let get = |key: &str| values.iter().find(|v| v.key == key).unwrap();
+        assert_eq!(get("model").value, json!("opus"));
+        assert_eq!(get("model").source, ConfigLayer::ProjectLocal);
+        assert_eq!(get("model").overrides, vec![ConfigLayer::User]);
+        assert_eq!(get("env.A").source, ConfigLayer::User);
+        assert_eq!(get("env.B").value, json!("3"));
+        assert_eq!(get("env.B").source, ConfigLayer::Project);
+    }
+
+    #[test]
+    fn redacts_sensitive_values() {
+        let values = merge_layers(&[layer(
+            ConfigLayer::User,
+            json!({"env": {"ANTHROPIC_AUTH_TOKEN": "sk-secret", "ANTHROPIC_BASE_URL": "https://x"}}),
+        )]);
+
+        assert_eq!(values[0].key, "env.ANTHROPIC_AUTH_TOKEN");
+        assert_eq!(values[0].value, json!(REDACTED));
+        assert_eq!(values[1].value, json!("https://x"));
+    }
+}
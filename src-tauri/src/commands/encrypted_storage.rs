@@ -0,0 +1,515 @@
+//! 回收站/归档文件的静态加密
+//!
+//! 会话内容离开 CLI 的活跃目录后（目前是自动清理移入的回收站 ~/.anycode/trash），
+//! 可在设置中开启口令加密后再落盘：
+//! - 密钥由口令经 Argon2id 派生，盐与口令校验值保存在 ~/.anycode/encrypted_storage.json
+//! - 文件内容使用 AES-256-GCM 加密，文件头（魔数 + 盐）作为附加认证数据，加密文件名追加 `.enc`
+//! - 还原、列表预览所需的索引信息另存为不含内容的明文元数据，未解锁时也能浏览
+//! - 口令通过 `unlock_encrypted_storage` 提供一次，派生出的密钥保留在内存直到应用退出
+//! - 口令错误、文件被篡改（认证失败）、文件使用其他口令加密分别返回明确的错误
+//!
+//! 活跃会话文件不加密，CLI 需要直接读写。
+
+use aes_gcm::aead::rand_core::RngCore;
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use argon2::{Algorithm, Argon2, Params, Version};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::utils::config_utils::{load_json_config, save_json_config, ConfigPathBuilder};
+
+/// 加密存储未解锁时错误信息的前缀，前端据此弹出口令输入
+pub const STORAGE_LOCKED_ERROR_PREFIX: &str = "STORAGE_LOCKED";
+
+/// 加密文件在原文件名后追加的扩展名
+pub const ENCRYPTED_EXTENSION: &str = "enc";
+
+/// 加密文件的魔数
+const MAGIC: &[u8] = b"ANYCODE-ENC1";
+
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+
+/// 口令校验值加密的内容，解锁时能解密即说明口令正确
+const VERIFIER_PLAINTEXT: &[u8] = b"anycode-encrypted-storage";
+
+/// 持久化配置：~/.anycode/encrypted_storage.json
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EncryptedStorageConfig {
+    /// 新写入的回收站/归档文件是否加密
+    #[serde(default)]
+    enabled: bool,
+    /// Argon2id 盐（base64），首次开启时生成
+    #[serde(default)]
+    salt: Option<String>,
+    /// 用派生密钥加密的校验值（base64）
+    #[serde(default)]
+    verifier: Option<String>,
+}
+
+/// 返回给前端的状态
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EncryptedStorageStatus {
+    pub enabled: bool,
+    /// 曾设置过口令（关闭加密后已有的加密文件仍需解锁才能还原）
+    pub configured: bool,
+    pub unlocked: bool,
+}
+
+/// 迁移已有回收站文件的结果
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EncryptionMigrationReport {
+    pub encrypted_files: usize,
+    /// 已经是加密文件或属于明文元数据而跳过的文件
+    pub skipped_files: usize,
+    pub errors: Vec<String>,
+}
+
+/// 已解锁的密钥
+#[derive(Clone)]
+struct StorageKey {
+    salt: Vec<u8>,
+    key: [u8; 32],
+}
+
+static UNLOCKED_KEY: Lazy<Mutex<Option<StorageKey>>> = Lazy::new(|| Mutex::new(None));
+
+fn get_config_path() -> Result<PathBuf, String> {
+    Ok(ConfigPathBuilder::from_home_subdir(".anycode")?.build("encrypted_storage.json"))
+}
+
+fn load_config() -> Result<EncryptedStorageConfig, String> {
+    load_json_config(&get_config_path()?)
+}
+
+/// 需要加密存储的目录（回收站根目录）
+pub fn archive_roots() -> Result<Vec<PathBuf>, String> {
+    Ok(vec![
+        ConfigPathBuilder::from_home_subdir(".anycode")?.build("trash")
+    ])
+}
+
+// ============================================================================
+// Crypto
+// ============================================================================
+
+fn derive_key(passphrase: &str, salt: &[u8]) -> Result<[u8; 32], String> {
+    let params = Params::new(19 * 1024, 2, 1, Some(32))
+        .map_err(|e| format!("Invalid key derivation parameters: {}", e))?;
+    let mut key = [0u8; 32];
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| format!("Failed to derive key: {}", e))?;
+    Ok(key)
+}
+
+/// 文件头：魔数 + 盐长度（1 字节）+ 盐
+fn header(salt: &[u8]) -> Vec<u8> {
+    let mut header = MAGIC.to_vec();
+    header.push(salt.len() as u8);
+    header.extend_from_slice(salt);
+    header
+}
+
+fn encrypt_with(key: &StorageKey, plaintext: &[u8]) -> Result<Vec<u8>, String> {
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key.key));
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let mut output = header(&key.salt);
+    let ciphertext = cipher
+        .encrypt(
+            &nonce,
+            Payload {
+                msg: plaintext,
+                aad: &output,
+            },
+        )
+        .map_err(|_| "Failed to encrypt data".to_string())?;
+    output.extend_from_slice(&nonce);
+    output.extend_from_slice(&ciphertext);
+    Ok(output)
+}
+
+fn decrypt_with(key: &StorageKey, data: &[u8]) -> Result<Vec<u8>, String> {
+    let salt_len = *data.get(MAGIC.len()).ok_or("加密文件不完整")? as usize;
+    let header_len = MAGIC.len() + 1 + salt_len;
+    if data.len() < header_len + NONCE_LEN {
+        return Err("加密文件不完整".to_string());
+    }
+    let (aad, rest) = data.split_at(header_len);
+    if aad[MAGIC.len() + 1..] != key.salt[..] {
+        return Err("该文件使用其他口令加密，无法用当前口令解密".to_string());
+    }
+    let (nonce, ciphertext) = rest.split_at(NONCE_LEN);
+
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key.key));
+    cipher
+        .decrypt(
+            Nonce::from_slice(nonce),
+            Payload {
+                msg: ciphertext,
+                aad,
+            },
+        )
+        .map_err(|_| "认证失败：加密文件已损坏或被篡改".to_string())
+}
+
+/// 内容是否为本模块写入的加密数据
+pub fn is_encrypted(data: &[u8]) -> bool {
+    data.starts_with(MAGIC)
+}
+
+// ============================================================================
+// Key State
+// ============================================================================
+
+fn unlocked_key() -> Option<StorageKey> {
+    UNLOCKED_KEY.lock().unwrap().clone()
+}
+
+fn require_key() -> Result<StorageKey, String> {
+    unlocked_key().ok_or_else(|| {
+        format!(
+            "{}: 加密存储尚未解锁，请先输入口令",
+            STORAGE_LOCKED_ERROR_PREFIX
+        )
+    })
+}
+
+/// 校验口令并返回密钥，不改变解锁状态
+fn verify_passphrase(
+    config: &EncryptedStorageConfig,
+    passphrase: &str,
+) -> Result<StorageKey, String> {
+    let (Some(salt), Some(verifier)) = (&config.salt, &config.verifier) else {
+        return Err("尚未设置加密口令".to_string());
+    };
+    let salt = BASE64
+        .decode(salt)
+        .map_err(|e| format!("Invalid salt in config: {}", e))?;
+    let verifier = BASE64
+        .decode(verifier)
+        .map_err(|e| format!("Invalid verifier in config: {}", e))?;
+    let key = StorageKey {
+        key: derive_key(passphrase, &salt)?,
+        salt,
+    };
+    match decrypt_with(&key, &verifier) {
+        Ok(plaintext) if plaintext == VERIFIER_PLAINTEXT => Ok(key),
+        _ => Err("口令错误".to_string()),
+    }
+}
+
+fn status(config: &EncryptedStorageConfig) -> EncryptedStorageStatus {
+    EncryptedStorageStatus {
+        enabled: config.enabled,
+        configured: config.verifier.is_some(),
+        unlocked: unlocked_key().is_some(),
+    }
+}
+
+/// 新文件是否需要加密写入
+pub fn encryption_enabled() -> bool {
+    load_config().map(|c| c.enabled).unwrap_or(false)
+}
+
+/// 开启加密时要求已解锁，批量写入前调用，避免写到一半才失败
+pub fn ensure_writable() -> Result<(), String> {
+    if encryption_enabled() {
+        require_key()?;
+    }
+    Ok(())
+}
+
+// ============================================================================
+// File Access
+// ============================================================================
+
+/// 加密后的文件路径：原文件名追加 `.enc`
+pub fn encrypted_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".");
+    name.push(ENCRYPTED_EXTENSION);
+    PathBuf::from(name)
+}
+
+/// 写入回收站/归档文件；开启加密时写入 `<path>.enc`，返回实际写入的路径
+pub fn write_protected(path: &Path, content: &[u8]) -> Result<PathBuf, String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create directory {:?}: {}", parent, e))?;
+    }
+    let (target, data) = if encryption_enabled() {
+        (
+            encrypted_path(path),
+            encrypt_with(&require_key()?, content)?,
+        )
+    } else {
+        (path.to_path_buf(), content.to_vec())
+    };
+    fs::write(&target, data).map_err(|e| format!("Failed to write {:?}: {}", target, e))?;
+    Ok(target)
+}
+
+/// 读取回收站/归档文件，加密文件自动解密
+///
+/// `path` 为原文件名；存在 `<path>.enc` 时优先读取加密文件
+pub fn read_protected(path: &Path) -> Result<Vec<u8>, String> {
+    let encrypted = encrypted_path(path);
+    let source = if encrypted.exists() {
+        encrypted
+    } else {
+        path.to_path_buf()
+    };
+    let data = fs::read(&source).map_err(|e| format!("Failed to read {:?}: {}", source, e))?;
+    if is_encrypted(&data) {
+        decrypt_with(&require_key()?, &data)
+    } else {
+        Ok(data)
+    }
+}
+
+/// 删除回收站/归档文件（明文或加密版本）
+pub fn remove_protected(path: &Path) -> Result<(), String> {
+    for candidate in [encrypted_path(path), path.to_path_buf()] {
+        if candidate.exists() {
+            fs::remove_file(&candidate)
+                .map_err(|e| format!("Failed to remove {:?}: {}", candidate, e))?;
+        }
+    }
+    Ok(())
+}
+
+/// 明文元数据文件（索引）不加密
+fn is_metadata_file(path: &Path) -> bool {
+    path.file_name().and_then(|n| n.to_str()) == Some(TRASH_INDEX_FILE)
+}
+
+/// 回收站每次清理目录下的明文索引文件名
+pub const TRASH_INDEX_FILE: &str = "index.json";
+
+fn encrypt_existing_files(root: &Path, key: &StorageKey, report: &mut EncryptionMigrationReport) {
+    for entry in walkdir::WalkDir::new(root).into_iter().flatten() {
+        let path = entry.path();
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        if is_metadata_file(path) {
+            report.skipped_files += 1;
+            continue;
+        }
+        let result = fs::read(path)
+            .map_err(|e| format!("Failed to read: {}", e))
+            .and_then(|data| {
+                if is_encrypted(&data) {
+                    return Ok(false);
+                }
+                let target = encrypted_path(path);
+                fs::write(&target, encrypt_with(key, &data)?)
+                    .map_err(|e| format!("Failed to write {:?}: {}", target, e))?;
+                fs::remove_file(path).map_err(|e| format!("Failed to remove plaintext: {}", e))?;
+                Ok(true)
+            });
+        match result {
+            Ok(true) => report.encrypted_files += 1,
+            Ok(false) => report.skipped_files += 1,
+            Err(e) => report.errors.push(format!("{:?}: {}", path, e)),
+        }
+    }
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/// 获取加密存储状态
+#[tauri::command]
+pub async fn get_encrypted_storage_status() -> Result<EncryptedStorageStatus, String> {
+    Ok(status(&load_config()?))
+}
+
+/// 开启加密存储
+///
+/// 首次开启时用该口令生成盐与校验值；之前设置过口令时必须提供同一口令
+#[tauri::command]
+pub async fn enable_encrypted_storage(
+    passphrase: String,
+) -> Result<EncryptedStorageStatus, String> {
+    if passphrase.is_empty() {
+        return Err("口令不能为空".to_string());
+    }
+    let mut config = load_config()?;
+
+    let key = tokio::task::spawn_blocking({
+        let config = config.clone();
+        move || {
+            if config.verifier.is_some() {
+                return verify_passphrase(&config, &passphrase);
+            }
+            let mut salt = vec![0u8; SALT_LEN];
+            OsRng.fill_bytes(&mut salt);
+            Ok(StorageKey {
+                key: derive_key(&passphrase, &salt)?,
+                salt,
+            })
+        }
+    })
+    .await
+    .map_err(|e| format!("Key derivation task failed: {}", e))??;
+
+    if config.verifier.is_none() {
+        config.salt = Some(BASE64.encode(&key.salt));
+        config.verifier = Some(BASE64.encode(encrypt_with(&key, VERIFIER_PLAINTEXT)?));
+    }
+    config.enabled = true;
+    save_json_config(&config, &get_config_path()?)?;
+    *UNLOCKED_KEY.lock().unwrap() = Some(key);
+
+    log::info!("[EncryptedStorage] Encryption enabled");
+    Ok(status(&config))
+}
+
+/// 关闭加密存储：之后新写入的文件不再加密，已有的加密文件仍需解锁才能还原
+#[tauri::command]
+pub async fn disable_encrypted_storage() -> Result<EncryptedStorageStatus, String> {
+    let mut config = load_config()?;
+    require_key()?;
+    config.enabled = false;
+    save_json_config(&config, &get_config_path()?)?;
+    log::info!("[EncryptedStorage] Encryption disabled");
+    Ok(status(&config))
+}
+
+/// 提供口令解锁加密存储，密钥保留在内存直到应用退出
+#[tauri::command]
+pub async fn unlock_encrypted_storage(
+    passphrase: String,
+) -> Result<EncryptedStorageStatus, String> {
+    let config = load_config()?;
+    let key = tokio::task::spawn_blocking({
+        let config = config.clone();
+        move || verify_passphrase(&config, &passphrase)
+    })
+    .await
+    .map_err(|e| format!("Key derivation task failed: {}", e))??;
+
+    *UNLOCKED_KEY.lock().unwrap() = Some(key);
+    log::info!("[EncryptedStorage] Unlocked");
+    Ok(status(&config))
+}
+
+/// 把回收站中已有的明文文件加密（明文索引保持不变）
+#[tauri::command]
+pub async fn migrate_existing_archives_to_encrypted() -> Result<EncryptionMigrationReport, String> {
+    if !encryption_enabled() {
+        return Err("请先开启加密存储".to_string());
+    }
+    let key = require_key()?;
+
+    tokio::task::spawn_blocking(move || {
+        let mut report = EncryptionMigrationReport::default();
+        for root in archive_roots()? {
+            if !root.exists() {
+                continue;
+            }
+            // 旧的回收站目录没有索引，先从明文内容生成，加密后列表仍可预览
+            super::gemini::retention::ensure_trash_indexes(&root);
+            encrypt_existing_files(&root, &key, &mut report);
+        }
+        log::info!(
+            "[EncryptedStorage] Migration encrypted {} files, skipped {}, {} errors",
+            report.encrypted_files,
+            report.skipped_files,
+            report.errors.len()
+        );
+        Ok(report)
+    })
+    .await
+    .map_err(|e| format!("Migration task failed: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_key(passphrase: &str, salt: &[u8]) -> StorageKey {
+        StorageKey {
+            key: derive_key(passphrase, salt).unwrap(),
+            salt: salt.to_vec(),
+        }
+    }
+
+    #[test]
+    fn round_trips_and_rejects_tampering_or_wrong_key() {
+        let key = test_key("correct horse", &[7u8; SALT_LEN]);
+        let plaintext = b"{\"sessionId\":\"secret discussion\"}";
+
+        let encrypted = encrypt_with(&key, plaintext).unwrap();
+        assert!(is_encrypted(&encrypted));
+        assert!(!encrypted.windows(b"secret".len()).any(|w| w == b"secret"));
+        assert_eq!(decrypt_with(&key, &encrypted).unwrap(), plaintext);
+
+        let mut tampered = encrypted.clone();
+        let last = tampered.len() - 1;
+        tampered[last] ^= 1;
+        assert!(decrypt_with(&key, &tampered).unwrap_err().contains("篡改"));
+
+        let wrong = test_key("wrong", &[7u8; SALT_LEN]);
+        assert!(decrypt_with(&wrong, &encrypted).is_err());
+
+        let other_salt = test_key("correct horse", &[8u8; SALT_LEN]);
+        assert!(decrypt_with(&other_salt, &encrypted)
+            .unwrap_err()
+            .contains("其他口令"));
+    }
+
+    #[test]
+    fn verifies_passphrase_against_stored_verifier() {
+        let key = test_key("pass", &[1u8; SALT_LEN]);
+        let config = EncryptedStorageConfig {
+            enabled: true,
+            salt: Some(BASE64.encode(&key.salt)),
+            verifier: Some(BASE64.encode(encrypt_with(&key, VERIFIER_PLAINTEXT).unwrap())),
+        };
+        assert_eq!(verify_passphrase(&config, "pass").unwrap().key, key.key);
+        assert_eq!(
+            verify_passphrase(&config, "nope").err().as_deref(),
+            Some("口令错误")
+        );
+    }
+
+    #[test]
+    fn migration_encrypts_files_but_keeps_index_readable() {
+        let dir = tempfile::tempdir().unwrap();
+        let chats = dir.path().join("run").join("tmp").join("p").join("chats");
+        fs::create_dir_all(&chats).unwrap();
+        fs::write(chats.join("session-a.json"), b"secret").unwrap();
+        fs::write(dir.path().join("run").join(TRASH_INDEX_FILE), b"[]").unwrap();
+
+        let key = test_key("pass", &[2u8; SALT_LEN]);
+        let mut report = EncryptionMigrationReport::default();
+        encrypt_existing_files(dir.path(), &key, &mut report);
+
+        assert_eq!(report.encrypted_files, 1);
+        assert!(!chats.join("session-a.json").exists());
+        let encrypted = fs::read(encrypted_path(&chats.join("session-a.json"))).unwrap();
+        assert_eq!(decrypt_with(&key, &encrypted).unwrap(), b"secret");
+        assert_eq!(
+            fs::read(dir.path().join("run").join(TRASH_INDEX_FILE)).unwrap(),
+            b"[]"
+        );
+
+        // 再次迁移时跳过已加密文件
+        let mut again = EncryptionMigrationReport::default();
+        encrypt_existing_files(dir.path(), &key, &mut again);
+        assert_eq!(again.encrypted_files, 0);
+    }
+}
@@ -57,6 +57,12 @@ pub struct HookContext {
     pub session_id: String,
     pub project_path: String,
     pub data: serde_json::Value, // 事件特定数据
+    /// 执行目录（未指定时继承应用当前目录）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub working_dir: Option<String>,
+    /// 额外注入的环境变量
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub env: HashMap<String, String>,
 }
 
 /// Hook执行结果
@@ -148,7 +154,13 @@ impl HookExecutor {
                 .env("HOOK_CONTEXT", &context_json)
                 .env("HOOK_EVENT", &context.event)
                 .env("SESSION_ID", &context.session_id)
-                .env("PROJECT_PATH", &context.project_path);
+                .env("PROJECT_PATH", &context.project_path)
+                .envs(&context.env)
+                .kill_on_drop(true); // 超时后随 child 一起终止
+
+            if let Some(dir) = &context.working_dir {
+                cmd.current_dir(dir);
+            }
 
             #[cfg(target_os = "windows")]
             {
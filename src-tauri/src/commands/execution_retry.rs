@@ -0,0 +1,427 @@
+//! 执行失败自动重试（Codex / Gemini）
+//!
+//! 代理或提供方偶发 502、超时等瞬时错误时，CLI 进程往往很快退出且没有任何有效输出。
+//! 开启后，执行路径在满足以下全部条件时按指数退避用相同的选项重新启动进程：
+//! - 本次尝试失败，且在 `quick_exit_secs` 内结束
+//! - 尚未产生任何有效输出（助手消息、工具调用等），避免重复执行已开始的工作
+//! - stderr / 错误事件中能识别出网络类瞬时错误；认证失败、模型不存在等错误不重试
+//!
+//! 重试期间沿用同一个前端通道 session_id，并发送 `{engine}-retrying` 事件；
+//! 只有最后一次尝试才会发送完成事件与任务完成动作。
+//!
+//! 持久化：~/.anycode/execution_retry.json（默认关闭）
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+use tokio::sync::Notify;
+
+use super::execution_trace::{record_trace_event, TraceFields, STAGE_RETRYING};
+use crate::utils::config_utils::{load_json_config, save_json_config, ConfigPathBuilder};
+
+/// 允许配置的最大尝试次数（含首次）
+const MAX_ALLOWED_ATTEMPTS: u32 = 10;
+
+/// 失败原因中最多保留的字符数
+const MAX_REASON_CHARS: usize = 300;
+
+/// 明确不可重试的错误特征（优先于瞬时错误判断）
+const NON_TRANSIENT_PATTERNS: &[&str] = &[
+    "unauthorized",
+    "forbidden",
+    "invalid api key",
+    "invalid_api_key",
+    "incorrect api key",
+    "api key not valid",
+    "authentication",
+    "permission denied",
+    "model not found",
+    "model_not_found",
+    "does not exist",
+    "invalid model",
+    "unsupported model",
+    "not supported",
+    "quota",
+    "insufficient_quota",
+    "billing",
+];
+
+/// 不可重试的 HTTP 状态码
+const NON_TRANSIENT_STATUS_CODES: &[&str] = &["400", "401", "403", "404"];
+
+/// 网络类瞬时错误特征
+const TRANSIENT_PATTERNS: &[&str] = &[
+    "bad gateway",
+    "service unavailable",
+    "gateway timeout",
+    "timed out",
+    "timeout",
+    "connection reset",
+    "connection refused",
+    "connection closed",
+    "connection aborted",
+    "econnreset",
+    "econnrefused",
+    "etimedout",
+    "socket hang up",
+    "stream disconnected",
+    "error sending request",
+    "network error",
+    "temporarily unavailable",
+    "overloaded",
+    "too many requests",
+    "rate limit",
+];
+
+/// 可重试的 HTTP 状态码
+const TRANSIENT_STATUS_CODES: &[&str] = &["429", "500", "502", "503", "504", "529"];
+
+/// 处于退避等待中的重试（键为前端通道 session_id），取消执行时唤醒并放弃重试
+static PENDING_RETRIES: Lazy<Mutex<HashMap<String, Arc<Notify>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// 自动重试配置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ExecutionRetryConfig {
+    pub enabled: bool,
+    /// 最大尝试次数（含首次执行）
+    pub max_attempts: u32,
+    /// 首次重试前的等待时间，之后每次翻倍
+    pub initial_backoff_ms: u64,
+    /// 单次等待的上限
+    pub max_backoff_ms: u64,
+    /// 只有在该时间内退出的失败才视为启动阶段的瞬时错误
+    pub quick_exit_secs: u64,
+}
+
+impl Default for ExecutionRetryConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_attempts: 3,
+            initial_backoff_ms: 2000,
+            max_backoff_ms: 30_000,
+            quick_exit_secs: 60,
+        }
+    }
+}
+
+/// 一次失败尝试的情况
+#[derive(Debug, Clone)]
+pub struct FailedAttempt {
+    /// 本次尝试序号（从 1 开始）
+    pub attempt: u32,
+    /// 本次尝试的运行时长
+    pub elapsed: Duration,
+    /// 是否已经产生有效输出
+    pub made_progress: bool,
+    /// stderr 与错误事件中的文本
+    pub messages: Vec<String>,
+}
+
+/// 重试计划
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryPlan {
+    /// 下一次尝试的序号
+    pub next_attempt: u32,
+    pub max_attempts: u32,
+    pub delay: Duration,
+    /// 识别到的失败原因
+    pub reason: String,
+}
+
+/// `{engine}-retrying` 事件负载
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RetryingPayload<'a> {
+    session_id: &'a str,
+    engine: &'a str,
+    attempt: u32,
+    max_attempts: u32,
+    delay_ms: u64,
+    reason: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    trace_id: Option<&'a str>,
+}
+
+/// 获取自动重试配置
+#[tauri::command]
+pub async fn get_execution_retry_config() -> Result<ExecutionRetryConfig, String> {
+    load_json_config(get_config_path()?)
+}
+
+/// 更新自动重试配置
+#[tauri::command]
+pub async fn update_execution_retry_config(config: ExecutionRetryConfig) -> Result<(), String> {
+    if config.max_attempts == 0 || config.max_attempts > MAX_ALLOWED_ATTEMPTS {
+        return Err(format!(
+            "最大尝试次数必须在 1 到 {} 之间",
+            MAX_ALLOWED_ATTEMPTS
+        ));
+    }
+    if config.initial_backoff_ms > config.max_backoff_ms {
+        return Err("初始等待时间不能大于等待上限".to_string());
+    }
+    save_json_config(&config, get_config_path()?)
+}
+
+/// 读取配置供执行路径使用，读取失败时按关闭处理
+pub fn load_retry_config() -> ExecutionRetryConfig {
+    get_config_path()
+        .and_then(load_json_config)
+        .unwrap_or_else(|e| {
+            log::warn!("[Retry] Failed to load execution retry config: {}", e);
+            ExecutionRetryConfig::default()
+        })
+}
+
+/// 判断失败的尝试是否应当重试，返回重试计划
+pub fn plan_retry(config: &ExecutionRetryConfig, failed: &FailedAttempt) -> Option<RetryPlan> {
+    if !config.enabled || failed.attempt >= config.max_attempts {
+        return None;
+    }
+    if failed.made_progress || failed.elapsed > Duration::from_secs(config.quick_exit_secs) {
+        return None;
+    }
+    if !is_transient_failure(&failed.messages) {
+        return None;
+    }
+
+    Some(RetryPlan {
+        next_attempt: failed.attempt + 1,
+        max_attempts: config.max_attempts,
+        delay: backoff_delay(config, failed.attempt),
+        reason: failure_reason(&failed.messages),
+    })
+}
+
+/// 第 `attempt` 次尝试失败后的等待时间：初始值按次数翻倍，不超过上限
+pub fn backoff_delay(config: &ExecutionRetryConfig, attempt: u32) -> Duration {
+    let exponent = attempt.saturating_sub(1).min(16);
+    let delay = config
+        .initial_backoff_ms
+        .saturating_mul(1u64 << exponent)
+        .min(config.max_backoff_ms);
+    Duration::from_millis(delay)
+}
+
+/// 错误文本是否为可重试的瞬时错误；同时出现不可重试特征时不重试
+pub fn is_transient_failure(messages: &[String]) -> bool {
+    let text = messages.join("\n").to_lowercase();
+    if text.trim().is_empty() {
+        return false;
+    }
+    if NON_TRANSIENT_PATTERNS.iter().any(|p| text.contains(p))
+        || NON_TRANSIENT_STATUS_CODES
+            .iter()
+            .any(|code| contains_status_code(&text, code))
+    {
+        return false;
+    }
+    TRANSIENT_PATTERNS.iter().any(|p| text.contains(p))
+        || TRANSIENT_STATUS_CODES
+            .iter()
+            .any(|code| contains_status_code(&text, code))
+}
+
+/// 发送 `retrying` 事件并退避等待；等待期间执行被取消时返回 false
+pub async fn wait_before_retry(
+    app: &AppHandle,
+    engine: &str,
+    session_id: &str,
+    plan: &RetryPlan,
+    trace_id: Option<&str>,
+) -> bool {
+    let notify = Arc::new(Notify::new());
+    PENDING_RETRIES
+        .lock()
+        .unwrap()
+        .insert(session_id.to_string(), notify.clone());
+
+    log::warn!(
+        "[Retry] {} session {} failed transiently ({}), retrying attempt {}/{} in {:?}",
+        engine,
+        session_id,
+        plan.reason,
+        plan.next_attempt,
+        plan.max_attempts,
+        plan.delay
+    );
+    let payload = RetryingPayload {
+        session_id,
+        engine,
+        attempt: plan.next_attempt,
+        max_attempts: plan.max_attempts,
+        delay_ms: plan.delay.as_millis() as u64,
+        reason: &plan.reason,
+        trace_id,
+    };
+    if let Some(trace_id) = trace_id {
+        record_trace_event(
+            trace_id,
+            STAGE_RETRYING,
+            TraceFields::engine(engine)
+                .with_session(Some(session_id))
+                .with_detail(serde_json::json!({
+                    "attempt": plan.next_attempt,
+                    "maxAttempts": plan.max_attempts,
+                    "delayMs": payload.delay_ms,
+                    "reason": plan.reason,
+                })),
+        );
+    }
+    let _ = app.emit(&format!("{}-retrying:{}", engine, session_id), &payload);
+    let _ = app.emit(&format!("{}-retrying", engine), &payload);
+
+    tokio::select! {
+        _ = tokio::time::sleep(plan.delay) => {}
+        _ = notify.notified() => {}
+    }
+
+    PENDING_RETRIES.lock().unwrap().remove(session_id).is_some()
+}
+
+/// 取消退避等待中的重试；`session_id` 为 None 时取消该前缀（如 "codex-"）下的全部重试
+///
+/// 返回是否取消了至少一个重试
+pub fn cancel_pending_retries(session_id: Option<&str>, prefix: &str) -> bool {
+    let mut pending = PENDING_RETRIES.lock().unwrap();
+    let keys: Vec<String> = match session_id {
+        Some(sid) => pending.keys().filter(|k| *k == sid).cloned().collect(),
+        None => pending
+            .keys()
+            .filter(|k| k.starts_with(prefix))
+            .cloned()
+            .collect(),
+    };
+    for key in &keys {
+        if let Some(notify) = pending.remove(key) {
+            notify.notify_one();
+        }
+    }
+    !keys.is_empty()
+}
+
+/// 取最能说明失败原因的一行（优先包含瞬时错误特征的行）
+fn failure_reason(messages: &[String]) -> String {
+    let mut lines = messages
+        .iter()
+        .flat_map(|m| m.lines())
+        .map(str::trim)
+        .filter(|l| !l.is_empty());
+    let reason = lines
+        .clone()
+        .find(|line| {
+            let lower = line.to_lowercase();
+            TRANSIENT_PATTERNS.iter().any(|p| lower.contains(p))
+                || TRANSIENT_STATUS_CODES
+                    .iter()
+                    .any(|code| contains_status_code(&lower, code))
+        })
+        .or_else(|| lines.next_back())
+        .unwrap_or_default();
+    reason.chars().take(MAX_REASON_CHARS).collect()
+}
+
+/// 文本中是否出现独立的状态码（前后不是数字）
+pub(crate) fn contains_status_code(text: &str, code: &str) -> bool {
+    text.match_indices(code).any(|(start, _)| {
+        let before = text[..start].chars().next_back();
+        let after = text[start + code.len()..].chars().next();
+        !before.is_some_and(|c| c.is_ascii_digit()) && !after.is_some_and(|c| c.is_ascii_digit())
+    })
+}
+
+fn get_config_path() -> Result<PathBuf, String> {
+    Ok(ConfigPathBuilder::from_home_subdir(".anycode")?.build("execution_retry.json"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn failed(attempt: u32, messages: &[&str]) -> FailedAttempt {
+        FailedAttempt {
+            attempt,
+            elapsed: Duration::from_secs(3),
+            made_progress: false,
+            messages: messages.iter().map(|m| m.to_string()).collect(),
+        }
+    }
+
+    fn enabled_config() -> ExecutionRetryConfig {
+        ExecutionRetryConfig {
+            enabled: true,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn classifies_transient_and_permanent_errors() {
+        let transient = [
+            "unexpected status 502 Bad Gateway: <html>",
+            "stream disconnected before completion: error sending request for url",
+            "Error: request timed out after 60000ms",
+            "read ECONNRESET",
+        ];
+        for message in transient {
+            assert!(is_transient_failure(&[message.to_string()]), "{}", message);
+        }
+
+        let permanent = [
+            "unexpected status 401 Unauthorized: invalid api key",
+            "The model `gpt-9` does not exist or you do not have access to it",
+            "[API Error: 403 Forbidden]",
+            "listening on port 15020",
+            "",
+        ];
+        for message in permanent {
+            assert!(!is_transient_failure(&[message.to_string()]), "{}", message);
+        }
+
+        // 同时出现认证错误与网络错误时不重试
+        assert!(!is_transient_failure(&[
+            "503 Service Unavailable".to_string(),
+            "authentication failed".to_string(),
+        ]));
+    }
+
+    #[test]
+    fn backoff_doubles_up_to_cap() {
+        let config = ExecutionRetryConfig {
+            initial_backoff_ms: 1000,
+            max_backoff_ms: 5000,
+            ..Default::default()
+        };
+        let delays: Vec<u64> = (1..=5)
+            .map(|attempt| backoff_delay(&config, attempt).as_millis() as u64)
+            .collect();
+        assert_eq!(delays, vec![1000, 2000, 4000, 5000, 5000]);
+    }
+
+    #[test]
+    fn plans_retry_only_for_quick_transient_failures() {
+        let config = enabled_config();
+
+        let plan = plan_retry(&config, &failed(1, &["noise", "502 Bad Gateway"])).unwrap();
+        assert_eq!(plan.next_attempt, 2);
+        assert_eq!(plan.delay, Duration::from_millis(2000));
+        assert_eq!(plan.reason, "502 Bad Gateway");
+
+        assert!(plan_retry(&config, &failed(3, &["502 Bad Gateway"])).is_none());
+        assert!(plan_retry(&ExecutionRetryConfig::default(), &failed(1, &["502"])).is_none());
+
+        let mut progressed = failed(1, &["connection reset"]);
+        progressed.made_progress = true;
+        assert!(plan_retry(&config, &progressed).is_none());
+
+        let mut slow = failed(1, &["connection reset"]);
+        slow.elapsed = Duration::from_secs(config.quick_exit_secs + 1);
+        assert!(plan_retry(&config, &slow).is_none());
+    }
+}
@@ -0,0 +1,535 @@
+//! 执行追踪（trace id）
+//!
+//! 一次用户动作（例如点一次发送）对应一个 trace id：前端在发起执行时生成并随执行选项传入，
+//! 未传入时由后端入口生成。trace id 贯穿：
+//! - 执行事件：会话初始化事件、Claude 全局事件信封、重试事件与统一的任务完成事件都带 trace_id
+//! - 日志：关键日志行以 `[trace:<id>]` 开头
+//! - 追踪目录：~/.anycode/traces/<trace_id>/events.jsonl，记录启动、进程、重试、提示词记录、
+//!   代码快照、任务完成动作与结束等阶段
+//! - git：自动提交的 message 尾部带 `Trace-Id: <id>`，提示词 git record 记录 trace_id
+//! - 审计日志：执行开始与结束写入 ~/.anycode/audit/executions.jsonl
+//!
+//! 追踪目录在启动时清理：超过 `TRACE_RETENTION_DAYS` 天未更新的删除，其余最多保留
+//! `MAX_TRACE_DIRS` 个最新的。审计日志不清理。
+//!
+//! `get_trace_timeline` 汇总某个 trace 下的事件序列、各阶段耗时、结束状态与产生的提交。
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs::{self, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+
+use crate::utils::config_utils::ConfigPathBuilder;
+
+/// trace id 的最大长度（同时用作目录名）
+const MAX_TRACE_ID_LEN: usize = 64;
+
+/// git 提交 message 中的 trace 尾部标记
+const COMMIT_TRAILER_KEY: &str = "Trace-Id";
+
+/// 执行开始（引擎入口）
+pub const STAGE_EXECUTION_STARTED: &str = "execution_started";
+/// CLI 进程已启动
+pub const STAGE_PROCESS_SPAWNED: &str = "process_spawned";
+/// 确定了 CLI 会话 ID
+pub const STAGE_SESSION_BOUND: &str = "session_bound";
+/// 瞬时错误后等待重试
+pub const STAGE_RETRYING: &str = "retrying";
+/// 输出卡死后中断进程并续接会话
+pub const STAGE_RECONNECTING: &str = "reconnecting";
+/// 执行结束（与统一的任务完成事件对应）
+pub const STAGE_EXECUTION_FINISHED: &str = "execution_finished";
+/// 提示词已记录（git record 已写入 commit_before）
+pub const STAGE_PROMPT_RECORDED: &str = "prompt_recorded";
+/// 提示词完成后的代码快照（自动提交）
+pub const STAGE_GIT_SNAPSHOT: &str = "git_snapshot";
+/// 任务完成动作已执行
+pub const STAGE_TASK_ACTION: &str = "task_action";
+
+/// 追踪目录保留天数
+const TRACE_RETENTION_DAYS: u64 = 30;
+
+/// 最多保留的追踪目录数
+const MAX_TRACE_DIRS: usize = 500;
+
+/// 同时写入审计日志的阶段
+const AUDITED_STAGES: &[&str] = &[STAGE_EXECUTION_STARTED, STAGE_EXECUTION_FINISHED];
+
+/// 串行化追踪文件与审计日志的追加写入
+static WRITE_LOCK: Mutex<()> = Mutex::new(());
+
+/// 追踪事件（events.jsonl 中的一行）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TraceEvent {
+    pub trace_id: String,
+    /// 事件时间（Unix 毫秒）
+    pub timestamp_ms: i64,
+    pub stage: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub engine: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    /// 阶段结果，如 "success" / "failed"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<Value>,
+}
+
+/// 记录追踪事件时的可选字段
+#[derive(Debug, Clone, Default)]
+pub struct TraceFields {
+    pub engine: Option<String>,
+    pub session_id: Option<String>,
+    pub status: Option<String>,
+    pub detail: Option<Value>,
+}
+
+impl TraceFields {
+    pub fn engine(engine: &str) -> Self {
+        Self {
+            engine: Some(engine.to_string()),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_session(mut self, session_id: Option<&str>) -> Self {
+        self.session_id = session_id.map(|s| s.to_string());
+        self
+    }
+
+    pub fn with_status(mut self, status: &str) -> Self {
+        self.status = Some(status.to_string());
+        self
+    }
+
+    pub fn with_detail(mut self, detail: Value) -> Self {
+        self.detail = Some(detail);
+        self
+    }
+}
+
+/// 时间线中的一个事件
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TraceTimelineEntry {
+    #[serde(flatten)]
+    pub event: TraceEvent,
+    /// 距离 trace 开始的毫秒数
+    pub elapsed_ms: u64,
+    /// 距离上一个事件的毫秒数（即上一阶段耗时）
+    pub since_previous_ms: u64,
+}
+
+/// trace 下产生的提交
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TraceCommit {
+    pub engine: Option<String>,
+    pub session_id: Option<String>,
+    pub prompt_index: Option<u64>,
+    /// 快照完成后的 HEAD
+    pub commit: String,
+    /// 本次是否实际创建了提交（无改动时为 false）
+    pub committed: bool,
+}
+
+/// 一次执行的完整时间线
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TraceTimeline {
+    pub trace_id: String,
+    /// 该 trace 的追踪目录
+    pub log_dir: String,
+    pub started_at_ms: Option<i64>,
+    pub finished_at_ms: Option<i64>,
+    /// 从第一个事件到执行结束（未结束时到最后一个事件）的毫秒数
+    pub duration_ms: Option<u64>,
+    /// 最后一次 execution_finished 的结果，尚未结束时为 None
+    pub status: Option<String>,
+    pub events: Vec<TraceTimelineEntry>,
+    pub commits: Vec<TraceCommit>,
+}
+
+/// 获取某个 trace 的时间线
+#[tauri::command]
+pub async fn get_trace_timeline(trace_id: String) -> Result<TraceTimeline, String> {
+    if !is_valid_trace_id(&trace_id) {
+        return Err(format!("Invalid trace id: {}", trace_id));
+    }
+    let dir = trace_dir(&trace_id)?;
+    let events_path = dir.join("events.jsonl");
+    if !events_path.exists() {
+        return Err(format!("Trace not found: {}", trace_id));
+    }
+
+    let events = read_events(&events_path)?;
+    Ok(build_timeline(
+        &trace_id,
+        dir.to_string_lossy().to_string(),
+        events,
+    ))
+}
+
+/// 使用调用方传入的 trace id，缺失或不合法时生成新的
+pub fn resolve_trace_id(trace_id: Option<String>) -> String {
+    match trace_id {
+        Some(id) if is_valid_trace_id(&id) => id,
+        Some(id) => {
+            let generated = uuid::Uuid::new_v4().to_string();
+            log::warn!(
+                "[trace:{}] Ignoring invalid trace id {:?} from caller",
+                generated,
+                id
+            );
+            generated
+        }
+        None => uuid::Uuid::new_v4().to_string(),
+    }
+}
+
+/// 记录一个追踪事件；写入失败只记日志，不影响执行
+pub fn record_trace_event(trace_id: &str, stage: &str, fields: TraceFields) {
+    if !is_valid_trace_id(trace_id) {
+        return;
+    }
+
+    log::info!(
+        "[trace:{}] {} engine={} session={} status={}",
+        trace_id,
+        stage,
+        fields.engine.as_deref().unwrap_or("-"),
+        fields.session_id.as_deref().unwrap_or("-"),
+        fields.status.as_deref().unwrap_or("-")
+    );
+
+    let event = TraceEvent {
+        trace_id: trace_id.to_string(),
+        timestamp_ms: chrono::Utc::now().timestamp_millis(),
+        stage: stage.to_string(),
+        engine: fields.engine,
+        session_id: fields.session_id,
+        status: fields.status,
+        detail: fields.detail,
+    };
+    if let Err(e) = append_event(&event) {
+        log::warn!("[trace:{}] Failed to record trace event: {}", trace_id, e);
+    }
+}
+
+/// 为自动提交的 message 追加 trace 尾部标记
+pub fn with_trace_trailer(message: String, trace_id: Option<&str>) -> String {
+    match trace_id.filter(|id| is_valid_trace_id(id)) {
+        Some(id) => format!("{}\n\n{}: {}", message, COMMIT_TRAILER_KEY, id),
+        None => message,
+    }
+}
+
+fn append_event(event: &TraceEvent) -> Result<(), String> {
+    let line = serde_json::to_string(event)
+        .map_err(|e| format!("Failed to serialize trace event: {}", e))?;
+    let dir = trace_dir(&event.trace_id)?;
+
+    let _guard = WRITE_LOCK.lock().unwrap();
+    append_line(&dir.join("events.jsonl"), &line)?;
+    if AUDITED_STAGES.contains(&event.stage.as_str()) {
+        append_line(&audit_log_path()?, &line)?;
+    }
+    Ok(())
+}
+
+fn append_line(path: &Path, line: &str) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create directory {:?}: {}", parent, e))?;
+    }
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| format!("Failed to open {:?}: {}", path, e))?;
+    writeln!(file, "{}", line).map_err(|e| format!("Failed to write {:?}: {}", path, e))
+}
+
+fn read_events(path: &Path) -> Result<Vec<TraceEvent>, String> {
+    let file = fs::File::open(path).map_err(|e| format!("Failed to open trace: {}", e))?;
+    Ok(BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| serde_json::from_str(&line).ok())
+        .collect())
+}
+
+/// 按时间排序事件并计算各阶段耗时、结束状态与提交
+fn build_timeline(trace_id: &str, log_dir: String, mut events: Vec<TraceEvent>) -> TraceTimeline {
+    events.sort_by_key(|e| e.timestamp_ms);
+
+    let started_at_ms = events.first().map(|e| e.timestamp_ms);
+    let finished = events
+        .iter()
+        .rev()
+        .find(|e| e.stage == STAGE_EXECUTION_FINISHED);
+    let finished_at_ms = finished.map(|e| e.timestamp_ms);
+    let status = finished.and_then(|e| e.status.clone());
+    let end_ms = finished_at_ms.or_else(|| events.last().map(|e| e.timestamp_ms));
+    let duration_ms = started_at_ms
+        .zip(end_ms)
+        .map(|(start, end)| end.saturating_sub(start).max(0) as u64);
+
+    let commits = events
+        .iter()
+        .filter(|e| e.stage == STAGE_GIT_SNAPSHOT)
+        .filter_map(|e| {
+            let detail = e.detail.as_ref()?;
+            Some(TraceCommit {
+                engine: e.engine.clone(),
+                session_id: e.session_id.clone(),
+                prompt_index: detail.get("promptIndex").and_then(|v| v.as_u64()),
+                commit: detail.get("commit")?.as_str()?.to_string(),
+                committed: detail
+                    .get("committed")
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(false),
+            })
+        })
+        .collect();
+
+    let mut previous_ms = started_at_ms.unwrap_or(0);
+    let entries = events
+        .into_iter()
+        .map(|event| {
+            let elapsed_ms = (event.timestamp_ms - started_at_ms.unwrap_or(0)).max(0) as u64;
+            let since_previous_ms = (event.timestamp_ms - previous_ms).max(0) as u64;
+            previous_ms = event.timestamp_ms;
+            TraceTimelineEntry {
+                event,
+                elapsed_ms,
+                since_previous_ms,
+            }
+        })
+        .collect();
+
+    TraceTimeline {
+        trace_id: trace_id.to_string(),
+        log_dir,
+        started_at_ms,
+        finished_at_ms,
+        duration_ms,
+        status,
+        events: entries,
+        commits,
+    }
+}
+
+/// trace id 同时用作目录名，只接受字母、数字与 '-'、'_'
+fn is_valid_trace_id(trace_id: &str) -> bool {
+    !trace_id.is_empty()
+        && trace_id.len() <= MAX_TRACE_ID_LEN
+        && trace_id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// 审计日志路径（执行开始与结束事件）
+pub(crate) fn audit_log_path() -> Result<PathBuf, String> {
+    Ok(anycode_dir()?.build("audit").join("executions.jsonl"))
+}
+
+fn trace_dir(trace_id: &str) -> Result<PathBuf, String> {
+    Ok(traces_root()?.join(trace_id))
+}
+
+fn traces_root() -> Result<PathBuf, String> {
+    Ok(anycode_dir()?.build("traces"))
+}
+
+fn anycode_dir() -> Result<ConfigPathBuilder, String> {
+    ConfigPathBuilder::from_home_subdir(".anycode")
+}
+
+/// 启动时清理过期的追踪目录
+pub fn run_startup_cleanup() {
+    let root = match traces_root() {
+        Ok(root) => root,
+        Err(e) => {
+            log::warn!("[Trace] Skipping trace cleanup: {}", e);
+            return;
+        }
+    };
+    let _guard = WRITE_LOCK.lock().unwrap();
+    let removed = prune_traces(&root, SystemTime::now());
+    if removed > 0 {
+        log::info!("[Trace] Removed {} expired trace directories", removed);
+    }
+}
+
+/// 删除超过保留期的追踪目录，并只保留最新的 `MAX_TRACE_DIRS` 个，返回删除数量
+///
+/// 以目录（及其 events.jsonl）的最后修改时间判断新旧
+fn prune_traces(root: &Path, now: SystemTime) -> usize {
+    let Ok(entries) = fs::read_dir(root) else {
+        return 0;
+    };
+    let mut traces: Vec<(SystemTime, PathBuf)> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir())
+        .map(|path| (trace_modified(&path), path))
+        .collect();
+    traces.sort_by_key(|(modified, _)| std::cmp::Reverse(*modified));
+
+    let cutoff = now
+        .checked_sub(Duration::from_secs(TRACE_RETENTION_DAYS * 24 * 60 * 60))
+        .unwrap_or(SystemTime::UNIX_EPOCH);
+    let mut removed = 0;
+    for (index, (modified, path)) in traces.iter().enumerate() {
+        if index < MAX_TRACE_DIRS && *modified >= cutoff {
+            continue;
+        }
+        match fs::remove_dir_all(path) {
+            Ok(()) => removed += 1,
+            Err(e) => log::warn!("[Trace] Failed to remove {:?}: {}", path, e),
+        }
+    }
+    removed
+}
+
+fn trace_modified(dir: &Path) -> SystemTime {
+    [dir.join("events.jsonl"), dir.to_path_buf()]
+        .iter()
+        .filter_map(|path| fs::metadata(path).and_then(|m| m.modified()).ok())
+        .max()
+        .unwrap_or(SystemTime::UNIX_EPOCH)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn event(stage: &str, timestamp_ms: i64, fields: TraceFields) -> TraceEvent {
+        TraceEvent {
+            trace_id: "t-1".to_string(),
+            timestamp_ms,
+            stage: stage.to_string(),
+            engine: fields.engine,
+            session_id: fields.session_id,
+            status: fields.status,
+            detail: fields.detail,
+        }
+    }
+
+    #[test]
+    fn timeline_orders_stages_and_collects_commits() {
+        let events = vec![
+            event(
+                STAGE_GIT_SNAPSHOT,
+                1_500,
+                TraceFields::engine("codex")
+                    .with_session(Some("thread-1"))
+                    .with_detail(
+                        json!({ "promptIndex": 2, "commit": "abc123", "committed": true }),
+                    ),
+            ),
+            event(STAGE_EXECUTION_STARTED, 1_000, TraceFields::engine("codex")),
+            event(STAGE_PROCESS_SPAWNED, 1_050, TraceFields::engine("codex")),
+            event(
+                STAGE_EXECUTION_FINISHED,
+                1_400,
+                TraceFields::engine("codex").with_status("success"),
+            ),
+        ];
+
+        let timeline = build_timeline("t-1", "/tmp/t-1".to_string(), events);
+
+        let stages: Vec<&str> = timeline
+            .events
+            .iter()
+            .map(|e| e.event.stage.as_str())
+            .collect();
+        assert_eq!(
+            stages,
+            vec![
+                STAGE_EXECUTION_STARTED,
+                STAGE_PROCESS_SPAWNED,
+                STAGE_EXECUTION_FINISHED,
+                STAGE_GIT_SNAPSHOT
+            ]
+        );
+        assert_eq!(timeline.events[2].elapsed_ms, 400);
+        assert_eq!(timeline.events[2].since_previous_ms, 350);
+        assert_eq!(timeline.duration_ms, Some(400));
+        assert_eq!(timeline.status.as_deref(), Some("success"));
+        assert_eq!(
+            timeline.commits,
+            vec![TraceCommit {
+                engine: Some("codex".to_string()),
+                session_id: Some("thread-1".to_string()),
+                prompt_index: Some(2),
+                commit: "abc123".to_string(),
+                committed: true,
+            }]
+        );
+    }
+
+    #[test]
+    fn trace_ids_are_safe_directory_names() {
+        assert!(is_valid_trace_id("0b6f3c1e-8d2a-4c41-9a57-3f0e2b7d9c10"));
+        assert!(!is_valid_trace_id("../../etc"));
+        assert!(!is_valid_trace_id(""));
+
+        let generated = resolve_trace_id(Some("a/b".to_string()));
+        assert!(is_valid_trace_id(&generated));
+        assert_eq!(resolve_trace_id(Some("run-42".to_string())), "run-42");
+
+        assert_eq!(
+            with_trace_trailer("[Codex] fix prompt #1".to_string(), Some("run-42")),
+            "[Codex] fix prompt #1\n\nTrace-Id: run-42"
+        );
+        assert_eq!(
+            with_trace_trailer("[Codex] fix prompt #1".to_string(), None),
+            "[Codex] fix prompt #1"
+        );
+    }
+
+    #[test]
+    fn prunes_expired_traces_and_caps_the_count() {
+        let root = tempfile::tempdir().unwrap();
+        let write_trace = |id: &str| {
+            let dir = root.path().join(id);
+            fs::create_dir_all(&dir).unwrap();
+            fs::write(dir.join("events.jsonl"), "{}\n").unwrap();
+        };
+        write_trace("first");
+        write_trace("second");
+        fs::write(root.path().join("stray.txt"), "").unwrap();
+
+        let day = Duration::from_secs(24 * 60 * 60);
+        let now = SystemTime::now();
+        assert_eq!(prune_traces(root.path(), now), 0);
+        assert_eq!(prune_traces(root.path(), now + 10 * day), 0);
+
+        let expired = now + (TRACE_RETENTION_DAYS as u32 + 1) * day;
+        assert_eq!(prune_traces(root.path(), expired), 2);
+        assert!(!root.path().join("first").exists());
+        assert!(root.path().join("stray.txt").exists());
+
+        for index in 0..MAX_TRACE_DIRS + 3 {
+            write_trace(&format!("t-{}", index));
+        }
+        assert_eq!(prune_traces(root.path(), now), 3);
+        assert_eq!(
+            fs::read_dir(root.path())
+                .unwrap()
+                .flatten()
+                .filter(|e| e.path().is_dir())
+                .count(),
+            MAX_TRACE_DIRS
+        );
+    }
+}
@@ -1,11 +1,18 @@
 use anyhow::Result;
 use log::{debug, info};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager};
 use walkdir::WalkDir;
 
 use super::claude::get_claude_dir;
+use super::input_validation::{
+    invalid_argument, validate_length, validate_path_arg, validate_path_within, MAX_CONTENT_LEN,
+    MAX_NAME_LEN,
+};
+use super::undo::{self, Undoable};
 
 /// Represents a Plugin
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -117,6 +124,7 @@ fn parse_description_from_content(content: &str) -> Option<String> {
 /// List all subagents in project and user directories
 #[tauri::command]
 pub async fn list_subagents(project_path: Option<String>) -> Result<Vec<SubagentFile>, String> {
+    validate_project_path(&project_path)?;
     info!("Listing subagents");
     let mut agents = Vec::new();
 
@@ -188,6 +196,7 @@ fn scan_agents_directory(dir: &Path, scope: &str) -> Result<Vec<SubagentFile>, S
 pub async fn list_agent_skills(
     project_path: Option<String>,
 ) -> Result<Vec<AgentSkillFile>, String> {
+    validate_project_path(&project_path)?;
     info!("Listing agent skills");
     let mut skills = Vec::new();
 
@@ -270,21 +279,75 @@ fn scan_skills_directory(dir: &Path, scope: &str) -> Result<Vec<AgentSkillFile>,
     Ok(skills)
 }
 
+/// Resolve an extension file and make sure it lives in a scope directory
+/// (~/.claude/<subdir> or <project>/.claude/<subdir>), following `..` and symlinks
+fn resolve_scoped_file(file_path: &str, subdir: &str) -> Result<PathBuf, String> {
+    validate_path_arg("file_path", file_path)?;
+    let path = Path::new(file_path);
+    if !path.is_absolute() {
+        return Err(invalid_argument("file_path", "must be an absolute path"));
+    }
+
+    // Drop `..` lexically first so the scope directory cannot be named and then escaped
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            std::path::Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other),
+        }
+    }
+    let scope_dir = normalized
+        .ancestors()
+        .skip(1)
+        .find(|dir| {
+            dir.file_name().and_then(|s| s.to_str()) == Some(subdir)
+                && dir
+                    .parent()
+                    .and_then(|p| p.file_name())
+                    .and_then(|s| s.to_str())
+                    == Some(".claude")
+        })
+        .ok_or_else(|| {
+            invalid_argument(
+                "file_path",
+                format!(
+                    "must be inside a .claude/{} directory: {}",
+                    subdir, file_path
+                ),
+            )
+        })?;
+
+    validate_path_within(scope_dir, file_path)
+}
+
+/// Validate a project path argument
+fn validate_project_path(project_path: &Option<String>) -> Result<(), String> {
+    match project_path {
+        Some(path) => validate_path_arg("project_path", path),
+        None => Ok(()),
+    }
+}
+
 /// Read a specific subagent file
 #[tauri::command]
 pub async fn read_subagent(file_path: String) -> Result<String, String> {
-    fs::read_to_string(&file_path).map_err(|e| format!("Failed to read subagent file: {}", e))
+    let path = resolve_scoped_file(&file_path, "agents")?;
+    fs::read_to_string(&path).map_err(|e| format!("Failed to read subagent file: {}", e))
 }
 
 /// Read a specific skill file
 #[tauri::command]
 pub async fn read_skill(file_path: String) -> Result<String, String> {
-    fs::read_to_string(&file_path).map_err(|e| format!("Failed to read skill file: {}", e))
+    let path = resolve_scoped_file(&file_path, "skills")?;
+    fs::read_to_string(&path).map_err(|e| format!("Failed to read skill file: {}", e))
 }
 
 /// Open agents directory in file explorer
 #[tauri::command]
 pub async fn open_agents_directory(project_path: Option<String>) -> Result<String, String> {
+    validate_project_path(&project_path)?;
     let agents_dir = if let Some(proj_path) = project_path {
         Path::new(&proj_path).join(".claude").join("agents")
     } else {
@@ -301,6 +364,7 @@ pub async fn open_agents_directory(project_path: Option<String>) -> Result<Strin
 /// Open skills directory in file explorer
 #[tauri::command]
 pub async fn open_skills_directory(project_path: Option<String>) -> Result<String, String> {
+    validate_project_path(&project_path)?;
     let skills_dir = if let Some(proj_path) = project_path {
         Path::new(&proj_path).join(".claude").join("skills")
     } else {
@@ -623,6 +687,7 @@ fn count_plugin_components(plugin_dir: &Path) -> PluginComponents {
 /// Open plugins directory
 #[tauri::command]
 pub async fn open_plugins_directory(project_path: Option<String>) -> Result<String, String> {
+    validate_project_path(&project_path)?;
     let plugins_dir = if let Some(proj_path) = project_path {
         Path::new(&proj_path).join(".claude").join("plugins")
     } else {
@@ -636,35 +701,52 @@ pub async fn open_plugins_directory(project_path: Option<String>) -> Result<Stri
     Ok(plugins_dir.to_string_lossy().to_string())
 }
 
-/// Create a new subagent file
-/// According to Claude Code docs, subagents are .md files in .claude/agents/
-#[tauri::command]
-pub async fn create_subagent(
+/// Validate an agent/skill name (no special characters except hyphens and underscores)
+fn validate_extension_name(name: &str, kind: &str) -> Result<(), String> {
+    let field = format!("{} name", kind);
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(invalid_argument(
+            &field,
+            "can only contain letters, numbers, hyphens, and underscores",
+        ));
+    }
+    validate_length(&field, name, MAX_NAME_LEN)
+}
+
+/// Resolve .claude/<subdir> for the given scope
+fn get_scope_dir(
+    scope: &str,
+    project_path: Option<String>,
+    subdir: &str,
+) -> Result<PathBuf, String> {
+    validate_length("scope", scope, MAX_NAME_LEN)?;
+    if scope == "project" {
+        let proj_path = project_path
+            .ok_or_else(|| invalid_argument("project_path", "is required for project scope"))?;
+        validate_path_arg("project_path", &proj_path)?;
+        Ok(Path::new(&proj_path).join(".claude").join(subdir))
+    } else {
+        Ok(get_claude_dir().map_err(|e| e.to_string())?.join(subdir))
+    }
+}
+
+/// Write a subagent file with its complete content (frontmatter included)
+fn write_subagent_file(
     name: String,
-    description: String,
-    content: String,
+    description: Option<String>,
+    full_content: String,
     scope: String,
     project_path: Option<String>,
 ) -> Result<SubagentFile, String> {
-    info!("Creating subagent: {} (scope: {})", name, scope);
-
-    // Validate name (no special characters except hyphens and underscores)
-    if !name
-        .chars()
-        .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
-    {
-        return Err(
-            "Agent name can only contain letters, numbers, hyphens, and underscores".into(),
-        );
-    }
+    validate_extension_name(&name, "Agent")?;
+    validate_length("content", &full_content, MAX_CONTENT_LEN)?;
 
     // Determine target directory based on scope
-    let agents_dir = if scope == "project" {
-        let proj_path = project_path.ok_or("Project path is required for project scope")?;
-        Path::new(&proj_path).join(".claude").join("agents")
-    } else {
-        get_claude_dir().map_err(|e| e.to_string())?.join("agents")
-    };
+    let agents_dir = get_scope_dir(&scope, project_path, "agents")?;
 
     // Create directory if it doesn't exist
     fs::create_dir_all(&agents_dir)
@@ -678,16 +760,6 @@ pub async fn create_subagent(
         return Err(format!("Subagent '{}' already exists", name));
     }
 
-    // Build file content with frontmatter
-    let full_content = format!(
-        r#"---
-description: {}
----
-
-{}"#,
-        description, content
-    );
-
     // Write file
     fs::write(&file_path, &full_content)
         .map_err(|e| format!("Failed to write subagent file: {}", e))?;
@@ -698,47 +770,27 @@ description: {}
         name,
         path: file_path.to_string_lossy().to_string(),
         scope,
-        description: Some(description),
+        description,
         content: full_content,
     })
 }
 
-/// Create a new Agent Skill
-/// According to Claude Code docs, skills are SKILL.md files in .claude/skills/<skill-name>/
-#[tauri::command]
-pub async fn create_skill(
+/// Write a skill's SKILL.md with its complete content (frontmatter included)
+fn write_skill_file(
     name: String,
-    description: String,
-    content: String,
+    description: Option<String>,
+    full_content: String,
     scope: String,
     project_path: Option<String>,
 ) -> Result<AgentSkillFile, String> {
-    info!("Creating skill: {} (scope: {})", name, scope);
-
-    // Validate name (no special characters except hyphens and underscores)
-    if !name
-        .chars()
-        .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
-    {
-        return Err(
-            "Skill name can only contain letters, numbers, hyphens, and underscores".into(),
-        );
-    }
+    validate_extension_name(&name, "Skill")?;
+    validate_length("content", &full_content, MAX_CONTENT_LEN)?;
 
     // Determine target directory based on scope
-    let skills_dir = if scope == "project" {
-        let proj_path = project_path.ok_or("Project path is required for project scope")?;
-        Path::new(&proj_path).join(".claude").join("skills")
-    } else {
-        get_claude_dir().map_err(|e| e.to_string())?.join("skills")
-    };
-
-    // Create skill subdirectory: .claude/skills/<skill-name>/
-    let skill_dir = skills_dir.join(&name);
-    fs::create_dir_all(&skill_dir)
-        .map_err(|e| format!("Failed to create skill directory: {}", e))?;
+    let skills_dir = get_scope_dir(&scope, project_path, "skills")?;
 
     // Build the file path: .claude/skills/<skill-name>/SKILL.md
+    let skill_dir = skills_dir.join(&name);
     let file_path = skill_dir.join("SKILL.md");
 
     // Check if file already exists
@@ -746,6 +798,62 @@ pub async fn create_skill(
         return Err(format!("Skill '{}' already exists", name));
     }
 
+    // Create skill subdirectory: .claude/skills/<skill-name>/
+    fs::create_dir_all(&skill_dir)
+        .map_err(|e| format!("Failed to create skill directory: {}", e))?;
+
+    // Write file
+    fs::write(&file_path, &full_content)
+        .map_err(|e| format!("Failed to write skill file: {}", e))?;
+
+    info!("Created skill at: {:?}", file_path);
+
+    Ok(AgentSkillFile {
+        name,
+        path: file_path.to_string_lossy().to_string(),
+        scope,
+        description,
+        content: full_content,
+    })
+}
+
+/// Create a new subagent file
+/// According to Claude Code docs, subagents are .md files in .claude/agents/
+#[tauri::command]
+pub async fn create_subagent(
+    name: String,
+    description: String,
+    content: String,
+    scope: String,
+    project_path: Option<String>,
+) -> Result<SubagentFile, String> {
+    info!("Creating subagent: {} (scope: {})", name, scope);
+
+    // Build file content with frontmatter
+    let full_content = format!(
+        r#"---
+description: {}
+---
+
+{}"#,
+        description, content
+    );
+
+    write_subagent_file(name, Some(description), full_content, scope, project_path)
+}
+
+/// Create a new Agent Skill
+/// According to Claude Code docs, skills are SKILL.md files in .claude/skills/<skill-name>/
+#[tauri::command]
+pub async fn create_skill(
+    name: String,
+    description: String,
+    content: String,
+    scope: String,
+    project_path: Option<String>,
+) -> Result<AgentSkillFile, String> {
+    info!("Creating skill: {} (scope: {})", name, scope);
+
     // Build file content with YAML frontmatter (per Claude Code docs)
     let full_content = format!(
         r#"---
@@ -766,19 +874,364 @@ description: {}
         name, description, name, content
     );
 
-    // Write file
-    fs::write(&file_path, &full_content)
-        .map_err(|e| format!("Failed to write skill file: {}", e))?;
+    write_skill_file(name, Some(description), full_content, scope, project_path)
+}
 
-    info!("Created skill at: {:?}", file_path);
+// ============================================================================
+// Agent & Skill Templates
+// ============================================================================
 
-    Ok(AgentSkillFile {
-        name,
-        path: file_path.to_string_lossy().to_string(),
-        scope,
-        description: Some(description),
-        content: full_content,
-    })
+/// Placeholder replaced with the new agent/skill name on creation
+const NAME_PLACEHOLDER: &str = "{{name}}";
+
+/// Built-in subagent templates (id, content)
+const BUILTIN_AGENT_TEMPLATES: &[(&str, &str)] = &[
+    (
+        "code-reviewer",
+        include_str!("../../templates/agents/code-reviewer.md"),
+    ),
+    (
+        "test-writer",
+        include_str!("../../templates/agents/test-writer.md"),
+    ),
+    (
+        "doc-generator",
+        include_str!("../../templates/agents/doc-generator.md"),
+    ),
+];
+
+/// Built-in skill templates (id, content)
+const BUILTIN_SKILL_TEMPLATES: &[(&str, &str)] = &[
+    (
+        "commit-message",
+        include_str!("../../templates/skills/commit-message.md"),
+    ),
+    (
+        "release-notes",
+        include_str!("../../templates/skills/release-notes.md"),
+    ),
+];
+
+#[derive(Debug, Clone, Copy)]
+enum TemplateKind {
+    Agent,
+    Skill,
+}
+
+impl TemplateKind {
+    fn builtin(self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            TemplateKind::Agent => BUILTIN_AGENT_TEMPLATES,
+            TemplateKind::Skill => BUILTIN_SKILL_TEMPLATES,
+        }
+    }
+
+    fn dir_name(self) -> &'static str {
+        match self {
+            TemplateKind::Agent => "agents",
+            TemplateKind::Skill => "skills",
+        }
+    }
+}
+
+/// Represents a subagent or skill template
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExtensionTemplate {
+    /// Template id (file name without extension)
+    pub id: String,
+    /// Description from frontmatter
+    pub description: Option<String>,
+    /// Model from frontmatter (if any)
+    pub model: Option<String>,
+    /// Source: "builtin" or "local"
+    pub source: String,
+    /// Template content with {{name}} placeholders (used for preview)
+    pub content: String,
+}
+
+/// Optional fields replacing the template's frontmatter values
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TemplateOverrides {
+    pub description: Option<String>,
+    pub model: Option<String>,
+}
+
+/// Read a field from the YAML frontmatter (frontmatter only, no fallback)
+fn frontmatter_field(content: &str, key: &str) -> Option<String> {
+    let mut lines = content.lines();
+    if lines.next()?.trim() != "---" {
+        return None;
+    }
+    let prefix = format!("{}:", key);
+    lines
+        .take_while(|line| line.trim() != "---")
+        .find(|line| line.starts_with(&prefix))
+        .map(|line| line[prefix.len()..].trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+/// Set a field in the YAML frontmatter, adding the field (or the frontmatter) if missing
+fn set_frontmatter_field(content: &str, key: &str, value: &str) -> String {
+    let prefix = format!("{}:", key);
+    let field = format!("{}: {}", key, value.replace(['\r', '\n'], " ").trim());
+    let mut lines: Vec<&str> = content.lines().collect();
+
+    if lines.first().map(|line| line.trim()) == Some("---") {
+        if let Some(end) = lines.iter().skip(1).position(|line| line.trim() == "---") {
+            let end = end + 1;
+            match lines[1..end]
+                .iter()
+                .position(|line| line.starts_with(&prefix))
+            {
+                Some(i) => lines[i + 1] = &field,
+                None => lines.insert(end, &field),
+            }
+            let mut result = lines.join("\n");
+            if content.ends_with('\n') {
+                result.push('\n');
+            }
+            return result;
+        }
+    }
+
+    format!("---\n{}\n---\n\n{}", field, content)
+}
+
+/// Fill in the template for a new agent/skill
+fn render_template(content: &str, name: &str, overrides: &TemplateOverrides) -> String {
+    let mut rendered =
+        set_frontmatter_field(&content.replace(NAME_PLACEHOLDER, name), "name", name);
+    if let Some(description) = overrides
+        .description
+        .as_deref()
+        .filter(|d| !d.trim().is_empty())
+    {
+        rendered = set_frontmatter_field(&rendered, "description", description);
+    }
+    if let Some(model) = overrides.model.as_deref().filter(|m| !m.trim().is_empty()) {
+        rendered = set_frontmatter_field(&rendered, "model", model);
+    }
+    rendered
+}
+
+fn build_template(id: &str, content: String, source: &str) -> ExtensionTemplate {
+    ExtensionTemplate {
+        id: id.to_string(),
+        description: frontmatter_field(&content, "description"),
+        model: frontmatter_field(&content, "model"),
+        source: source.to_string(),
+        content,
+    }
+}
+
+/// Local templates directory: <app data>/templates/<agents|skills>/
+fn get_local_templates_dir(app: &AppHandle, kind: TemplateKind) -> Result<PathBuf, String> {
+    let app_data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data directory: {}", e))?;
+    Ok(app_data_dir.join("templates").join(kind.dir_name()))
+}
+
+/// List built-in and local templates; local templates override built-in ones with the same id
+fn list_templates(app: &AppHandle, kind: TemplateKind) -> Result<Vec<ExtensionTemplate>, String> {
+    let mut templates: BTreeMap<String, ExtensionTemplate> = kind
+        .builtin()
+        .iter()
+        .map(|(id, content)| {
+            (
+                id.to_string(),
+                build_template(id, content.to_string(), "builtin"),
+            )
+        })
+        .collect();
+
+    let local_dir = get_local_templates_dir(app, kind)?;
+    if local_dir.exists() {
+        let entries = fs::read_dir(&local_dir)
+            .map_err(|e| format!("Failed to read templates directory: {}", e))?;
+        for path in entries.flatten().map(|entry| entry.path()) {
+            if path.extension().and_then(|e| e.to_str()) != Some("md") {
+                continue;
+            }
+            let Some(id) = path.file_stem().and_then(|s| s.to_str()) else {
+                continue;
+            };
+            match fs::read_to_string(&path) {
+                Ok(content) => {
+                    templates.insert(id.to_string(), build_template(id, content, "local"));
+                }
+                Err(e) => debug!("Failed to read template {:?}: {}", path, e),
+            }
+        }
+    }
+
+    Ok(templates.into_values().collect())
+}
+
+fn find_template(
+    app: &AppHandle,
+    kind: TemplateKind,
+    template_id: &str,
+) -> Result<ExtensionTemplate, String> {
+    list_templates(app, kind)?
+        .into_iter()
+        .find(|template| template.id == template_id)
+        .ok_or_else(|| format!("Template '{}' not found", template_id))
+}
+
+/// List subagent templates (built-in and locally saved)
+#[tauri::command]
+pub async fn list_agent_templates(app: AppHandle) -> Result<Vec<ExtensionTemplate>, String> {
+    list_templates(&app, TemplateKind::Agent)
+}
+
+/// List skill templates (built-in and locally saved)
+#[tauri::command]
+pub async fn list_skill_templates(app: AppHandle) -> Result<Vec<ExtensionTemplate>, String> {
+    list_templates(&app, TemplateKind::Skill)
+}
+
+/// Create a subagent from a template, replacing {{name}} and the optional overrides
+#[tauri::command]
+pub async fn create_subagent_from_template(
+    app: AppHandle,
+    template_id: String,
+    name: String,
+    overrides: Option<TemplateOverrides>,
+    scope: String,
+    project_path: Option<String>,
+) -> Result<SubagentFile, String> {
+    info!(
+        "Creating subagent {} from template {} (scope: {})",
+        name, template_id, scope
+    );
+    validate_extension_name(&name, "Agent")?;
+
+    let template = find_template(&app, TemplateKind::Agent, &template_id)?;
+    let content = render_template(&template.content, &name, &overrides.unwrap_or_default());
+    let description = frontmatter_field(&content, "description");
+
+    write_subagent_file(name, description, content, scope, project_path)
+}
+
+/// Create a skill from a template, replacing {{name}} and the optional overrides
+#[tauri::command]
+pub async fn create_skill_from_template(
+    app: AppHandle,
+    template_id: String,
+    name: String,
+    overrides: Option<TemplateOverrides>,
+    scope: String,
+    project_path: Option<String>,
+) -> Result<AgentSkillFile, String> {
+    info!(
+        "Creating skill {} from template {} (scope: {})",
+        name, template_id, scope
+    );
+    validate_extension_name(&name, "Skill")?;
+
+    let template = find_template(&app, TemplateKind::Skill, &template_id)?;
+    let content = render_template(&template.content, &name, &overrides.unwrap_or_default());
+    let description = frontmatter_field(&content, "description");
+
+    write_skill_file(name, description, content, scope, project_path)
+}
+
+/// Save an existing subagent as a local template (overwrites a local template with the same id)
+#[tauri::command]
+pub async fn save_agent_as_template(
+    app: AppHandle,
+    file_path: String,
+    template_id: String,
+) -> Result<ExtensionTemplate, String> {
+    info!("Saving subagent {} as template {}", file_path, template_id);
+    validate_extension_name(&template_id, "Template")?;
+    let agent_path = resolve_scoped_file(&file_path, "agents")?;
+
+    let content = fs::read_to_string(&agent_path)
+        .map_err(|e| format!("Failed to read subagent file: {}", e))?;
+
+    // Turn the agent's own name back into a placeholder so the template can be reused
+    let content = set_frontmatter_field(&content, "name", NAME_PLACEHOLDER);
+
+    let templates_dir = get_local_templates_dir(&app, TemplateKind::Agent)?;
+    fs::create_dir_all(&templates_dir)
+        .map_err(|e| format!("Failed to create templates directory: {}", e))?;
+    let template_path = templates_dir.join(format!("{}.md", template_id));
+    fs::write(&template_path, &content)
+        .map_err(|e| format!("Failed to write template file: {}", e))?;
+
+    info!("Saved agent template at: {:?}", template_path);
+
+    Ok(build_template(&template_id, content, "local"))
+}
+
+/// Delete a local template (built-in templates cannot be deleted)
+#[tauri::command]
+pub async fn delete_extension_template(
+    app: AppHandle,
+    kind: String,
+    template_id: String,
+) -> Result<(), String> {
+    let kind = match kind.as_str() {
+        "agent" => TemplateKind::Agent,
+        "skill" => TemplateKind::Skill,
+        other => return Err(format!("Unknown template kind: {}", other)),
+    };
+    validate_extension_name(&template_id, "Template")?;
+
+    let template_path = get_local_templates_dir(&app, kind)?.join(format!("{}.md", template_id));
+    if !template_path.is_file() {
+        return Err(format!("Local template not found: {}", template_id));
+    }
+    let content = fs::read_to_string(&template_path)
+        .map_err(|e| format!("Failed to read template file: {}", e))?;
+    fs::remove_file(&template_path)
+        .map_err(|e| format!("Failed to delete template file: {}", e))?;
+
+    info!("Deleted template at: {:?}", template_path);
+    undo::record(&TemplateDeletion {
+        template_id,
+        path: template_path.to_string_lossy().to_string(),
+        content,
+    });
+    Ok(())
+}
+
+/// Write a deleted file back, refusing to overwrite a file created since
+fn restore_deleted_file(path: &str, content: &str) -> Result<(), String> {
+    let path = Path::new(path);
+    if path.exists() {
+        return Err(format!("File already exists: {}", path.display()));
+    }
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory: {}", e))?;
+    }
+    fs::write(path, content).map_err(|e| format!("Failed to restore file: {}", e))
+}
+
+/// Undo record for deleting a local template
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TemplateDeletion {
+    template_id: String,
+    path: String,
+    content: String,
+}
+
+impl Undoable for TemplateDeletion {
+    const KIND: &'static str = "template_deletion";
+
+    fn description(&self) -> String {
+        format!("删除模板 {}", self.template_id)
+    }
+
+    fn undo(self) -> Result<(), String> {
+        restore_deleted_file(&self.path, &self.content)
+    }
 }
 
 // ============================================================================
@@ -851,6 +1304,7 @@ fn parse_command_frontmatter(content: &str) -> (Option<String>, Option<String>)
 pub async fn list_custom_slash_commands(
     project_path: Option<String>,
 ) -> Result<Vec<CustomSlashCommand>, String> {
+    validate_project_path(&project_path)?;
     info!("Listing custom slash commands");
     let mut commands = Vec::new();
 
@@ -946,9 +1400,64 @@ fn scan_commands_directory(dir: &Path, scope: &str) -> Result<Vec<CustomSlashCom
     Ok(commands)
 }
 
+/// Delete a custom slash command file (only .md files under a .claude/commands directory)
+#[tauri::command]
+pub async fn delete_custom_slash_command(path: String) -> Result<(), String> {
+    let resolved = resolve_scoped_file(&path, "commands")?;
+    let file_path = resolved.as_path();
+    if file_path.extension().and_then(|s| s.to_str()) != Some("md") {
+        return Err(invalid_argument(
+            "path",
+            format!("is not a custom slash command file: {}", path),
+        ));
+    }
+    if !file_path.is_file() {
+        return Err(format!("Command file not found: {}", path));
+    }
+
+    let content =
+        fs::read_to_string(file_path).map_err(|e| format!("Failed to read command file: {}", e))?;
+    fs::remove_file(file_path).map_err(|e| format!("Failed to delete command file: {}", e))?;
+
+    info!("Deleted custom slash command at: {}", path);
+    let name = file_path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or_default()
+        .to_string();
+    undo::record(&SlashCommandDeletion {
+        name,
+        path,
+        content,
+    });
+    Ok(())
+}
+
+/// Undo record for deleting a custom slash command
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SlashCommandDeletion {
+    name: String,
+    path: String,
+    content: String,
+}
+
+impl Undoable for SlashCommandDeletion {
+    const KIND: &'static str = "slash_command_deletion";
+
+    fn description(&self) -> String {
+        format!("删除斜杠命令 /{}", self.name)
+    }
+
+    fn undo(self) -> Result<(), String> {
+        restore_deleted_file(&self.path, &self.content)
+    }
+}
+
 /// Open commands directory in file explorer
 #[tauri::command]
 pub async fn open_commands_directory(project_path: Option<String>) -> Result<String, String> {
+    validate_project_path(&project_path)?;
     let commands_dir = if let Some(proj_path) = project_path {
         Path::new(&proj_path).join(".claude").join("commands")
     } else {
@@ -1101,3 +1610,115 @@ fn scan_gemini_commands_directory(dir: &Path, scope: &str) -> Result<Vec<CustomS
 
     Ok(commands)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::super::input_validation::INVALID_ARGUMENT_ERROR_PREFIX;
+    use super::*;
+
+    #[test]
+    fn renders_builtin_template_with_name_and_overrides() {
+        let overrides = TemplateOverrides {
+            description: Some("Reviews Rust code".to_string()),
+            model: Some("opus".to_string()),
+        };
+        let rendered = render_template(BUILTIN_AGENT_TEMPLATES[0].1, "rust-reviewer", &overrides);
+
+        assert!(!rendered.contains(NAME_PLACEHOLDER));
+        assert_eq!(
+            frontmatter_field(&rendered, "name").as_deref(),
+            Some("rust-reviewer")
+        );
+        assert_eq!(
+            frontmatter_field(&rendered, "description").as_deref(),
+            Some("Reviews Rust code")
+        );
+        assert_eq!(
+            frontmatter_field(&rendered, "model").as_deref(),
+            Some("opus")
+        );
+        assert!(frontmatter_field(&rendered, "tools").is_some());
+    }
+
+    #[test]
+    fn adds_missing_frontmatter_fields() {
+        let with_frontmatter =
+            set_frontmatter_field("---\ndescription: x\n---\n\nBody\n", "name", "a");
+        assert_eq!(
+            with_frontmatter,
+            "---\ndescription: x\nname: a\n---\n\nBody\n"
+        );
+
+        let without_frontmatter = set_frontmatter_field("Body", "name", "a");
+        assert_eq!(
+            frontmatter_field(&without_frontmatter, "name").as_deref(),
+            Some("a")
+        );
+        assert!(without_frontmatter.ends_with("Body"));
+    }
+
+    #[tokio::test]
+    async fn read_subagent_rejects_paths_outside_the_agents_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        let agents_dir = dir.path().join("project").join(".claude").join("agents");
+        fs::create_dir_all(&agents_dir).unwrap();
+        fs::write(agents_dir.join("reviewer.md"), "reviewer").unwrap();
+        fs::write(dir.path().join("secret.md"), "secret").unwrap();
+        let path = |p: &Path| p.to_string_lossy().to_string();
+
+        assert_eq!(
+            read_subagent(path(&agents_dir.join("reviewer.md")))
+                .await
+                .unwrap(),
+            "reviewer"
+        );
+
+        let traversal = agents_dir
+            .join("..")
+            .join("..")
+            .join("..")
+            .join("secret.md");
+        let error = read_subagent(path(&traversal)).await.unwrap_err();
+        assert!(
+            error.starts_with(INVALID_ARGUMENT_ERROR_PREFIX),
+            "{}",
+            error
+        );
+        assert!(read_subagent("../../secret.md".to_string()).await.is_err());
+        assert!(read_subagent(path(&dir.path().join("secret.md")))
+            .await
+            .is_err());
+        // Skills are read from .claude/skills only
+        assert!(read_skill(path(&agents_dir.join("reviewer.md")))
+            .await
+            .is_err());
+
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(dir.path().join("secret.md"), agents_dir.join("link.md"))
+                .unwrap();
+            assert!(read_subagent(path(&agents_dir.join("link.md")))
+                .await
+                .is_err());
+        }
+    }
+
+    #[test]
+    fn builtin_templates_have_descriptions() {
+        for (id, content) in BUILTIN_AGENT_TEMPLATES
+            .iter()
+            .chain(BUILTIN_SKILL_TEMPLATES)
+        {
+            assert!(
+                frontmatter_field(content, "description").is_some(),
+                "template {} has no description",
+                id
+            );
+            assert!(
+                content.contains(NAME_PLACEHOLDER),
+                "template {} has no name placeholder",
+                id
+            );
+        }
+    }
+}
@@ -3,11 +3,18 @@
 //! Handles Gemini CLI configuration including authentication methods,
 //! model selection, and user preferences.
 
+use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+use tauri::AppHandle;
 use tokio::sync::OnceCell;
 
+use crate::commands::session_invalidation::invalidate_session;
+use crate::commands::tool_timing::annotate_gemini_detail;
 use crate::commands::wsl_utils;
 
 /// 全局 Gemini WSL 模式配置缓存
@@ -61,6 +68,31 @@ pub struct GeminiConfig {
     /// Custom environment variables
     #[serde(default)]
     pub env: std::collections::HashMap<String, String>,
+
+    /// Automatic cleanup policy for sessions under ~/.gemini/tmp
+    #[serde(default)]
+    pub session_retention: GeminiSessionRetention,
+
+    /// Fields of ~/.gemini/settings.json not managed by the app (telemetry, checkpointing, ...)
+    /// Read-only: filled by get_gemini_config and never written back
+    #[serde(default, skip_serializing_if = "serde_json::Map::is_empty")]
+    pub unmanaged_settings: serde_json::Map<String, serde_json::Value>,
+}
+
+/// Gemini session retention policy
+///
+/// A session is kept if it satisfies any configured rule (union of the kept sets);
+/// with no rule configured nothing is cleaned up.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GeminiSessionRetention {
+    /// Keep sessions active within the last N days
+    pub retention_days: Option<u32>,
+    /// Keep the N most recent sessions per project
+    pub max_sessions: Option<u32>,
+    /// Run the cleanup once when the app starts
+    #[serde(default)]
+    pub cleanup_on_startup: bool,
 }
 
 fn default_model() -> String {
@@ -80,6 +112,8 @@ impl Default for GeminiConfig {
             api_key: None,
             google_cloud_project: None,
             env: std::collections::HashMap::new(),
+            session_retention: GeminiSessionRetention::default(),
+            unmanaged_settings: serde_json::Map::new(),
         }
     }
 }
@@ -140,14 +174,29 @@ pub fn save_gemini_config(config: &GeminiConfig) -> Result<(), String> {
 // ============================================================================
 
 /// Get Gemini configuration
+///
+/// App-managed fields come from ~/.anycode/gemini.json; the remaining fields of
+/// ~/.gemini/settings.json are passed through in `unmanaged_settings`
 #[tauri::command]
 pub async fn get_gemini_config() -> Result<GeminiConfig, String> {
-    load_gemini_config()
+    let mut config = load_gemini_config()?;
+
+    let settings_path = get_gemini_dir()?.join("settings.json");
+    match super::settings::read_settings_file(&settings_path) {
+        Ok(settings) => config.unmanaged_settings = super::settings::unmanaged_settings(&settings),
+        Err(e) => log::warn!("[Gemini] Failed to read settings.json: {}", e),
+    }
+
+    Ok(config)
 }
 
 /// Update Gemini configuration
+///
+/// Only ~/.anycode/gemini.json is written; the passed-through settings.json fields are
+/// dropped so they are never copied into the app config or written back to settings.json
 #[tauri::command]
-pub async fn update_gemini_config(config: GeminiConfig) -> Result<(), String> {
+pub async fn update_gemini_config(mut config: GeminiConfig) -> Result<(), String> {
+    config.unmanaged_settings.clear();
     save_gemini_config(&config)
 }
 
@@ -162,6 +211,7 @@ pub async fn get_gemini_models() -> Result<Vec<GeminiModelInfo>, String> {
             description: "Latest and fastest model (December 17, 2025)".to_string(),
             context_window: 1_000_000,
             is_default: true,
+            capabilities: model_capabilities("gemini-3-flash"),
         },
         GeminiModelInfo {
             id: "gemini-3-pro".to_string(),
@@ -169,6 +219,7 @@ pub async fn get_gemini_models() -> Result<Vec<GeminiModelInfo>, String> {
             description: "Most capable reasoning and coding model".to_string(),
             context_window: 1_000_000,
             is_default: false,
+            capabilities: model_capabilities("gemini-3-pro"),
         },
         GeminiModelInfo {
             id: "gemini-3-pro-preview".to_string(),
@@ -176,6 +227,7 @@ pub async fn get_gemini_models() -> Result<Vec<GeminiModelInfo>, String> {
             description: "Experimental preview version".to_string(),
             context_window: 1_000_000,
             is_default: false,
+            capabilities: model_capabilities("gemini-3-pro-preview"),
         },
         GeminiModelInfo {
             id: "gemini-3-flash-thinking".to_string(),
@@ -183,6 +235,7 @@ pub async fn get_gemini_models() -> Result<Vec<GeminiModelInfo>, String> {
             description: "Flash model with chain-of-thought reasoning".to_string(),
             context_window: 1_000_000,
             is_default: false,
+            capabilities: model_capabilities("gemini-3-flash-thinking"),
         },
     ])
 }
@@ -196,6 +249,84 @@ pub struct GeminiModelInfo {
     pub description: String,
     pub context_window: u64,
     pub is_default: bool,
+    /// Built-in tool support; None for models missing from the capability table
+    pub capabilities: Option<GeminiModelCapabilities>,
+}
+
+/// Built-in tools and input types a Gemini model supports
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GeminiModelCapabilities {
+    /// Google Search grounding (`google_web_search`)
+    pub search_grounding: bool,
+    /// Code execution
+    pub code_execution: bool,
+    /// Image / PDF / audio input
+    pub multimodal: bool,
+}
+
+/// Capability table keyed by model ID prefix; more specific prefixes come first
+const MODEL_CAPABILITIES: &[(&str, GeminiModelCapabilities)] = &[
+    ("gemini-3", GeminiModelCapabilities::ALL),
+    (
+        "gemini-2.5-flash-lite",
+        GeminiModelCapabilities {
+            search_grounding: true,
+            code_execution: false,
+            multimodal: true,
+        },
+    ),
+    ("gemini-2.5", GeminiModelCapabilities::ALL),
+    (
+        "gemini-2.0-flash-lite",
+        GeminiModelCapabilities {
+            search_grounding: false,
+            code_execution: false,
+            multimodal: true,
+        },
+    ),
+    ("gemini-2.0", GeminiModelCapabilities::ALL),
+];
+
+impl GeminiModelCapabilities {
+    const ALL: Self = Self {
+        search_grounding: true,
+        code_execution: true,
+        multimodal: true,
+    };
+}
+
+/// Look up the capabilities of a model, None when the model is unknown
+pub fn model_capabilities(model: &str) -> Option<GeminiModelCapabilities> {
+    let model = model.strip_prefix("models/").unwrap_or(model);
+    MODEL_CAPABILITIES
+        .iter()
+        .find(|(prefix, _)| model.starts_with(prefix))
+        .map(|(_, capabilities)| *capabilities)
+}
+
+/// Gemini CLI built-in tool that grounds answers with Google Search
+pub const SEARCH_GROUNDING_TOOL: &str = "google_web_search";
+
+/// Built-in tools to exclude from this run according to the tool switches
+///
+/// The CLI offers web search by default, so `None` keeps the CLI default and `Some(false)`
+/// excludes it through `tools.exclude`. Turning search on fails before the CLI starts when
+/// the model is known not to support it; models missing from the capability table pass unchecked
+pub fn excluded_builtin_tools(
+    model: &str,
+    enable_search_grounding: Option<bool>,
+) -> Result<Vec<String>, String> {
+    match enable_search_grounding {
+        Some(true) => {
+            if model_capabilities(model).is_some_and(|c| !c.search_grounding) {
+                return Err(format!("模型 {} 不支持联网搜索（search grounding）", model));
+            }
+            Ok(Vec::new())
+        }
+        Some(false) => Ok(vec![SEARCH_GROUNDING_TOOL.to_string()]),
+        None => Ok(Vec::new()),
+    }
 }
 
 // ============================================================================
@@ -266,6 +397,206 @@ pub fn read_session_logs(project_path: &str) -> Result<Vec<GeminiSessionLog>, St
     serde_json::from_str(&content).map_err(|e| format!("Failed to parse logs.json: {}", e))
 }
 
+/// Maximum number of session files kept in the read cache
+const MAX_CACHED_SESSION_FILES: usize = 500;
+
+/// Maximum number of cached files that also keep their full detail
+const MAX_CACHED_SESSION_DETAILS: usize = 20;
+
+/// Read cache for Gemini session files
+///
+/// Entries are checked against the file's (mtime, size); completion, rewind, truncation
+/// and deletion also drop them explicitly via [`invalidate_gemini_session_cache`] so a
+/// rewrite within the filesystem's timestamp granularity is never served stale.
+/// The least recently used entries are evicted (details first, since they hold every
+/// message) once the cache grows past its limits.
+#[derive(Default)]
+struct GeminiSessionCache {
+    /// Session file path -> parsed file
+    files: HashMap<PathBuf, CachedSessionFile>,
+    /// Session ID -> session file path (also used by `find_gemini_session_file`)
+    index: HashMap<String, PathBuf>,
+    /// Monotonic use counter for LRU eviction
+    clock: u64,
+}
+
+struct CachedSessionFile {
+    modified: Option<SystemTime>,
+    len: u64,
+    info: GeminiSessionInfo,
+    /// Full detail, kept only once the session has been opened
+    detail: Option<GeminiSessionDetail>,
+    last_used: u64,
+}
+
+impl GeminiSessionCache {
+    fn tick(&mut self) -> u64 {
+        self.clock += 1;
+        self.clock
+    }
+
+    /// Drop the least recently used files beyond `max_files`, then the details of the least
+    /// recently used files beyond `max_details`
+    fn evict(&mut self, max_files: usize, max_details: usize) {
+        let mut by_use: Vec<(u64, PathBuf)> = self
+            .files
+            .iter()
+            .map(|(path, cached)| (cached.last_used, path.clone()))
+            .collect();
+        by_use.sort();
+
+        let excess = self.files.len().saturating_sub(max_files);
+        for (_, path) in by_use.drain(..excess) {
+            if let Some(cached) = self.files.remove(&path) {
+                if self.index.get(&cached.info.session_id) == Some(&path) {
+                    self.index.remove(&cached.info.session_id);
+                }
+            }
+        }
+
+        by_use.retain(|(_, path)| self.files[path].detail.is_some());
+        let excess = by_use.len().saturating_sub(max_details);
+        for (_, path) in by_use.drain(..excess) {
+            if let Some(cached) = self.files.get_mut(&path) {
+                cached.detail = None;
+            }
+        }
+    }
+}
+
+static GEMINI_SESSION_CACHE: Lazy<Mutex<GeminiSessionCache>> =
+    Lazy::new(|| Mutex::new(GeminiSessionCache::default()));
+
+/// Drop the cached file and index entry of a session
+pub(crate) fn invalidate_gemini_session_cache(session_id: &str) {
+    let Ok(mut cache) = GEMINI_SESSION_CACHE.lock() else {
+        return;
+    };
+    if let Some(path) = cache.index.remove(session_id) {
+        cache.files.remove(&path);
+    }
+    cache
+        .files
+        .retain(|_, cached| cached.info.session_id != session_id);
+}
+
+/// Look up the indexed file of a session, if it still exists
+pub(crate) fn indexed_session_file(session_id: &str) -> Option<PathBuf> {
+    let cache = GEMINI_SESSION_CACHE.lock().ok()?;
+    cache
+        .index
+        .get(session_id)
+        .filter(|path| path.is_file())
+        .cloned()
+}
+
+/// Record the file of a session found by a directory scan
+pub(crate) fn remember_session_file(session_id: &str, path: &Path) {
+    if let Ok(mut cache) = GEMINI_SESSION_CACHE.lock() {
+        cache
+            .index
+            .insert(session_id.to_string(), path.to_path_buf());
+    }
+}
+
+/// Read a session file through the cache; `need_detail` also loads the full messages
+fn with_cached_session<T>(
+    path: &Path,
+    need_detail: bool,
+    read: impl FnOnce(&CachedSessionFile) -> T,
+) -> Result<T, String> {
+    let metadata = fs::metadata(path).map_err(|e| format!("Failed to read session file: {}", e))?;
+    let modified = metadata.modified().ok();
+    let len = metadata.len();
+
+    let cache_lock = || {
+        GEMINI_SESSION_CACHE
+            .lock()
+            .map_err(|e| format!("Gemini session cache poisoned: {}", e))
+    };
+    {
+        let mut cache = cache_lock()?;
+        let now = cache.tick();
+        if let Some(cached) = cache.files.get_mut(path).filter(|cached| {
+            cached.modified == modified
+                && cached.len == len
+                && (!need_detail || cached.detail.is_some())
+        }) {
+            cached.last_used = now;
+            return Ok(read(cached));
+        }
+    }
+
+    // Parse without holding the lock so a large session doesn't block other lookups
+    let detail = read_session_detail_from_path(path)?;
+    let file_name = path
+        .file_name()
+        .and_then(|s| s.to_str())
+        .unwrap_or("")
+        .to_string();
+    let info = GeminiSessionInfo {
+        session_id: detail.session_id.clone(),
+        file_name,
+        start_time: detail.start_time.clone(),
+        last_updated: detail.last_updated.clone(),
+        message_count: detail.messages.len(),
+        first_message: detail
+            .messages
+            .first()
+            .and_then(|m| m.get("content"))
+            .and_then(|c| c.as_str())
+            .map(|s| s.to_string()),
+    };
+    let cached = CachedSessionFile {
+        modified,
+        len,
+        info,
+        detail: need_detail.then_some(detail),
+        last_used: 0,
+    };
+    let result = read(&cached);
+
+    let mut cache = cache_lock()?;
+    let last_used = cache.tick();
+    cache
+        .index
+        .insert(cached.info.session_id.clone(), path.to_path_buf());
+    cache.files.insert(
+        path.to_path_buf(),
+        CachedSessionFile {
+            last_used,
+            ..cached
+        },
+    );
+    cache.evict(MAX_CACHED_SESSION_FILES, MAX_CACHED_SESSION_DETAILS);
+    Ok(result)
+}
+
+/// Find the file of a session in chats/, using the index before scanning the directory
+fn find_session_path(chats_dir: &Path, session_id: &str) -> Result<PathBuf, String> {
+    if let Some(path) = indexed_session_file(session_id).filter(|p| p.starts_with(chats_dir)) {
+        return Ok(path);
+    }
+
+    let entries =
+        fs::read_dir(chats_dir).map_err(|e| format!("Failed to read chats directory: {}", e))?;
+
+    for entry in entries {
+        let entry = entry.map_err(|e| format!("Failed to read directory entry: {}", e))?;
+        let path = entry.path();
+
+        if path.extension().and_then(|s| s.to_str()) == Some("json") {
+            let matches =
+                with_cached_session(&path, false, |cached| cached.info.session_id == session_id);
+            if matches.unwrap_or(false) {
+                return Ok(path);
+            }
+        }
+    }
+
+    Err(format!("Session {} not found", session_id))
+}
+
 /// List all session files in chats/ directory
 pub fn list_session_files(project_path: &str) -> Result<Vec<GeminiSessionInfo>, String> {
     let session_dir = get_project_session_dir(project_path)?;
@@ -285,34 +616,16 @@ pub fn list_session_files(project_path: &str) -> Result<Vec<GeminiSessionInfo>,
         let path = entry.path();
 
         if path.extension().and_then(|s| s.to_str()) == Some("json") {
-            let file_name = path
-                .file_name()
-                .and_then(|s| s.to_str())
-                .unwrap_or("")
-                .to_string();
-
             // Try to read basic info from file
-            if let Ok(detail) = read_session_detail_from_path(&path) {
-                let first_message = detail
-                    .messages
-                    .first()
-                    .and_then(|m| m.get("content"))
-                    .and_then(|c| c.as_str())
-                    .map(|s| s.to_string());
-
+            if let Ok(info) = with_cached_session(&path, false, |cached| cached.info.clone()) {
                 // Skip subagent/task sessions - they start with "Your task is to"
-                if let Some(ref msg) = first_message {
+                if let Some(ref msg) = info.first_message {
                     if msg.trim_start().starts_with("Your task is to") {
                         continue;
                     }
                 }
 
-                sessions.push(GeminiSessionInfo {
-                    session_id: detail.session_id,
-                    file_name,
-                    start_time: detail.start_time,
-                    first_message,
-                });
+                sessions.push(info);
             }
         }
     }
@@ -335,28 +648,13 @@ pub fn read_session_detail(
         return Err("No chats directory found".to_string());
     }
 
-    // Find session file by session_id
-    let entries =
-        fs::read_dir(&chats_dir).map_err(|e| format!("Failed to read chats directory: {}", e))?;
-
-    for entry in entries {
-        let entry = entry.map_err(|e| format!("Failed to read directory entry: {}", e))?;
-        let path = entry.path();
-
-        if path.extension().and_then(|s| s.to_str()) == Some("json") {
-            if let Ok(detail) = read_session_detail_from_path(&path) {
-                if detail.session_id == session_id {
-                    return Ok(detail);
-                }
-            }
-        }
-    }
-
-    Err(format!("Session {} not found", session_id))
+    let path = find_session_path(&chats_dir, session_id)?;
+    with_cached_session(&path, true, |cached| cached.detail.clone())?
+        .ok_or_else(|| format!("Session {} not found", session_id))
 }
 
 /// Helper function to read session detail from a specific file path
-fn read_session_detail_from_path(path: &PathBuf) -> Result<GeminiSessionDetail, String> {
+fn read_session_detail_from_path(path: &Path) -> Result<GeminiSessionDetail, String> {
     let content =
         fs::read_to_string(path).map_err(|e| format!("Failed to read session file: {}", e))?;
 
@@ -387,13 +685,21 @@ pub async fn get_gemini_session_detail(
     project_path: String,
     session_id: String,
 ) -> Result<GeminiSessionDetail, String> {
-    read_session_detail(&project_path, &session_id)
+    let mut detail = read_session_detail(&project_path, &session_id)?;
+    annotate_gemini_detail(&mut detail);
+    Ok(detail)
 }
 
 /// Delete a Gemini session
 #[tauri::command]
-pub async fn delete_gemini_session(project_path: String, session_id: String) -> Result<(), String> {
-    delete_session(&project_path, &session_id)
+pub async fn delete_gemini_session(
+    app: AppHandle,
+    project_path: String,
+    session_id: String,
+) -> Result<(), String> {
+    delete_session(&project_path, &session_id)?;
+    invalidate_session(&app, "gemini", Some(&session_id), Some(&project_path));
+    Ok(())
 }
 
 // ============================================================================
@@ -450,27 +756,11 @@ pub fn delete_session(project_path: &str, session_id: &str) -> Result<(), String
         return Err("No chats directory found".to_string());
     }
 
-    // Find and delete session file by session_id
-    let entries =
-        fs::read_dir(&chats_dir).map_err(|e| format!("Failed to read chats directory: {}", e))?;
-
-    for entry in entries {
-        let entry = entry.map_err(|e| format!("Failed to read directory entry: {}", e))?;
-        let path = entry.path();
-
-        if path.extension().and_then(|s| s.to_str()) == Some("json") {
-            if let Ok(detail) = read_session_detail_from_path(&path) {
-                if detail.session_id == session_id {
-                    fs::remove_file(&path)
-                        .map_err(|e| format!("Failed to delete session file: {}", e))?;
-                    log::info!("Deleted Gemini session: {} at {:?}", session_id, path);
-                    return Ok(());
-                }
-            }
-        }
-    }
-
-    Err(format!("Session {} not found", session_id))
+    let path = find_session_path(&chats_dir, session_id)?;
+    fs::remove_file(&path).map_err(|e| format!("Failed to delete session file: {}", e))?;
+    invalidate_gemini_session_cache(session_id);
+    log::info!("Deleted Gemini session: {} at {:?}", session_id, path);
+    Ok(())
 }
 
 // ============================================================================
@@ -583,3 +873,117 @@ pub async fn set_gemini_wsl_mode_config(mode: String, wsl_distro: Option<String>
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn search_grounding_switch_maps_to_tool_exclusions() {
+        assert_eq!(
+            excluded_builtin_tools("gemini-2.5-pro", None).unwrap(),
+            Vec::<String>::new()
+        );
+        assert_eq!(
+            excluded_builtin_tools("gemini-2.5-pro", Some(true)).unwrap(),
+            Vec::<String>::new()
+        );
+        assert_eq!(
+            excluded_builtin_tools("gemini-2.5-pro", Some(false)).unwrap(),
+            vec![SEARCH_GROUNDING_TOOL.to_string()]
+        );
+        assert!(excluded_builtin_tools("gemini-2.0-flash-lite", Some(true)).is_err());
+        assert!(excluded_builtin_tools("custom-model", Some(true)).is_ok());
+    }
+
+    fn write_session(path: &Path, session_id: &str, message_count: usize) {
+        let messages: Vec<serde_json::Value> = (0..message_count)
+            .map(|i| serde_json::json!({ "type": "user", "content": format!("prompt {}", i) }))
+            .collect();
+        let session = serde_json::json!({
+            "sessionId": session_id,
+            "projectHash": "hash",
+            "startTime": "2025-11-23T10:00:00Z",
+            "lastUpdated": format!("2025-11-23T10:0{}:00Z", message_count),
+            "messages": messages,
+        });
+        fs::write(path, session.to_string()).unwrap();
+    }
+
+    #[test]
+    fn session_cache_follows_rewrites_and_invalidation() {
+        let dir = tempfile::tempdir().unwrap();
+        let session_id = "5f3c1a2b-cache-test";
+        let path = dir.path().join("session-2025-11-23T10-00-5f3c1a2b.json");
+        write_session(&path, session_id, 1);
+
+        let info = with_cached_session(&path, false, |cached| cached.info.clone()).unwrap();
+        assert_eq!(info.message_count, 1);
+        assert_eq!(find_session_path(dir.path(), session_id).unwrap(), path);
+
+        // A rewrite that changes the file is picked up without invalidation
+        write_session(&path, session_id, 3);
+        let info = with_cached_session(&path, false, |cached| cached.info.clone()).unwrap();
+        assert_eq!(info.message_count, 3);
+        assert_eq!(info.last_updated, "2025-11-23T10:03:00Z");
+
+        // Invalidation drops both the parsed file and the index entry
+        invalidate_gemini_session_cache(session_id);
+        assert!(indexed_session_file(session_id).is_none());
+        let detail = with_cached_session(&path, true, |cached| cached.detail.clone())
+            .unwrap()
+            .unwrap();
+        assert_eq!(detail.messages.len(), 3);
+        assert_eq!(indexed_session_file(session_id), Some(path));
+    }
+
+    #[test]
+    fn session_cache_evicts_least_recently_used_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut cache = GeminiSessionCache::default();
+        for i in 0..4 {
+            let path = dir.path().join(format!("session-{}.json", i));
+            write_session(&path, &format!("session-{}", i), 1);
+            let detail = read_session_detail_from_path(&path).unwrap();
+            let info = GeminiSessionInfo {
+                session_id: detail.session_id.clone(),
+                file_name: String::new(),
+                start_time: detail.start_time.clone(),
+                last_updated: detail.last_updated.clone(),
+                message_count: detail.messages.len(),
+                first_message: None,
+            };
+            let last_used = cache.tick();
+            cache.index.insert(info.session_id.clone(), path.clone());
+            cache.files.insert(
+                path,
+                CachedSessionFile {
+                    modified: None,
+                    len: 0,
+                    info,
+                    detail: Some(detail),
+                    last_used,
+                },
+            );
+        }
+        // Using session-0 again makes session-1 the least recently used
+        let now = cache.tick();
+        cache
+            .files
+            .get_mut(&dir.path().join("session-0.json"))
+            .unwrap()
+            .last_used = now;
+
+        cache.evict(3, 1);
+        assert!(!cache.files.contains_key(&dir.path().join("session-1.json")));
+        assert!(!cache.index.contains_key("session-1"));
+        assert_eq!(cache.files.len(), 3);
+        let detailed: Vec<&str> = cache
+            .files
+            .values()
+            .filter(|cached| cached.detail.is_some())
+            .map(|cached| cached.info.session_id.as_str())
+            .collect();
+        assert_eq!(detailed, vec!["session-0"]);
+    }
+}
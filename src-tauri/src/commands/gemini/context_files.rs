@@ -0,0 +1,446 @@
+//! Gemini 上下文文件控制
+//!
+//! Gemini CLI 会自动加载 ~/.gemini 与项目目录（向上到 git 根目录）中的 GEMINI.md 作为指令。
+//! 每次执行可通过 `GeminiContextFiles` 调整：
+//! - `include_default_context = false`：生成一份把 `context.fileName` 指向不存在文件名的系统级
+//!   settings（在原系统 settings 基础上合并），经 `GEMINI_CLI_SYSTEM_SETTINGS_PATH` 交给 CLI，
+//!   本次执行不再加载任何 GEMINI.md
+//! - `extra_context_files`：校验存在、位于工作区内且总大小不超过 200KB，以 `@文件` 引用注入提示词，
+//!   由 CLI 读取文件内容
+//!
+//! 关闭的内置工具（如联网搜索）也通过同一份系统级 settings 的 `tools.exclude` 生效。
+//!
+//! 执行选项未指定时使用项目默认值（~/.anycode/gemini-context-files.json）。
+//! 生效的上下文文件清单随执行开始事件发送；默认上下文只列出全局目录与项目目录及其上级目录中的文件。
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::config::get_gemini_dir;
+use super::settings::read_settings_file;
+use crate::utils::config_utils::{load_json_config, save_json_config, ConfigPathBuilder};
+
+/// 附加上下文文件的总大小上限（字节）
+pub const MAX_EXTRA_CONTEXT_BYTES: u64 = 200 * 1024;
+
+/// Gemini CLI 读取系统级 settings 路径的环境变量
+pub const SYSTEM_SETTINGS_ENV: &str = "GEMINI_CLI_SYSTEM_SETTINGS_PATH";
+
+/// 禁用默认上下文时使用的上下文文件名（不会存在于任何目录中）
+const DISABLED_CONTEXT_FILE_NAME: &str = ".anycode-default-context-disabled.md";
+
+/// 未配置 `context.fileName` 时 CLI 使用的文件名
+const DEFAULT_CONTEXT_FILE_NAME: &str = "GEMINI.md";
+
+/// Context file selection for one Gemini run
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GeminiContextFiles {
+    /// Load GEMINI.md from the global and project directories (CLI default behavior)
+    #[serde(default = "default_true")]
+    pub include_default_context: bool,
+    /// Extra files injected as `@file` references, relative to the project or absolute
+    #[serde(default)]
+    pub extra_context_files: Vec<String>,
+}
+
+impl Default for GeminiContextFiles {
+    fn default() -> Self {
+        Self {
+            include_default_context: true,
+            extra_context_files: Vec::new(),
+        }
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn get_project_defaults_path() -> Result<PathBuf, String> {
+    Ok(ConfigPathBuilder::from_home_subdir(".anycode")?.build("gemini-context-files.json"))
+}
+
+fn load_project_defaults() -> Result<HashMap<String, GeminiContextFiles>, String> {
+    load_json_config(&get_project_defaults_path()?)
+}
+
+/// 项目保存的默认上下文文件配置（未保存时为 CLI 默认行为）
+pub fn project_context_files(project_path: &str) -> GeminiContextFiles {
+    load_project_defaults()
+        .map_err(|e| log::warn!("Failed to load Gemini context file defaults: {}", e))
+        .ok()
+        .and_then(|mut defaults| defaults.remove(project_path))
+        .unwrap_or_default()
+}
+
+/// Get the default context files of a project
+#[tauri::command]
+pub async fn get_gemini_context_files(project_path: String) -> Result<GeminiContextFiles, String> {
+    Ok(project_context_files(&project_path))
+}
+
+/// Save the default context files of a project (the default config removes the entry)
+#[tauri::command]
+pub async fn save_gemini_context_files(
+    project_path: String,
+    config: GeminiContextFiles,
+) -> Result<(), String> {
+    resolve_extra_context_files(&project_path, &[], &config.extra_context_files)?;
+
+    let mut defaults = load_project_defaults()?;
+    if config == GeminiContextFiles::default() {
+        defaults.remove(&project_path);
+    } else {
+        defaults.insert(project_path, config);
+    }
+    save_json_config(&defaults, get_project_defaults_path()?)
+}
+
+/// Preview the context files a run with this config would load
+#[tauri::command]
+pub async fn preview_gemini_context_files(
+    project_path: String,
+    config: GeminiContextFiles,
+) -> Result<Vec<String>, String> {
+    let extra = resolve_extra_context_files(&project_path, &[], &config.extra_context_files)?;
+    Ok(effective_context_files(&project_path, &config, &extra))
+}
+
+/// 校验附加上下文文件并返回规范化后的路径
+///
+/// 文件必须存在、位于项目目录或 include 目录内（CLI 只允许读取工作区内的文件），且总大小不超过上限
+pub fn resolve_extra_context_files(
+    project_path: &str,
+    include_directories: &[String],
+    files: &[String],
+) -> Result<Vec<PathBuf>, String> {
+    let project = Path::new(project_path);
+    let workspace: Vec<PathBuf> = std::iter::once(project_path)
+        .chain(include_directories.iter().map(String::as_str))
+        .filter_map(|dir| fs::canonicalize(dir).ok())
+        .collect();
+
+    let mut resolved: Vec<PathBuf> = Vec::new();
+    let mut total_bytes = 0u64;
+    for file in files.iter().map(|f| f.trim()).filter(|f| !f.is_empty()) {
+        let path = fs::canonicalize(project.join(file))
+            .map_err(|_| format!("上下文文件不存在: {}", file))?;
+        let metadata =
+            fs::metadata(&path).map_err(|e| format!("无法读取上下文文件 {}: {}", file, e))?;
+        if !metadata.is_file() {
+            return Err(format!("上下文文件不是普通文件: {}", file));
+        }
+        if !workspace.iter().any(|dir| path.starts_with(dir)) {
+            return Err(format!("上下文文件必须位于项目目录或附加目录中: {}", file));
+        }
+        if resolved.contains(&path) {
+            continue;
+        }
+        total_bytes += metadata.len();
+        resolved.push(path);
+    }
+
+    if total_bytes > MAX_EXTRA_CONTEXT_BYTES {
+        return Err(format!(
+            "附加上下文文件总大小 {} KB 超过上限 {} KB",
+            total_bytes.div_ceil(1024),
+            MAX_EXTRA_CONTEXT_BYTES / 1024
+        ));
+    }
+    Ok(resolved)
+}
+
+/// 生效的上下文文件清单：默认上下文（启用时）在前，附加文件在后
+pub fn effective_context_files(
+    project_path: &str,
+    config: &GeminiContextFiles,
+    extra: &[PathBuf],
+) -> Vec<String> {
+    let mut files = Vec::new();
+    if config.include_default_context {
+        let global_dir = get_gemini_dir().ok();
+        files.extend(default_context_files(
+            Path::new(project_path),
+            global_dir.as_deref(),
+            &context_file_names(global_dir.as_deref()),
+        ));
+    }
+    files.extend(extra.iter().cloned());
+    files.iter().map(|path| display_path(path)).collect()
+}
+
+/// 在提示词前加上附加上下文文件的 `@` 引用
+///
+/// 项目内的文件使用相对路径；路径中的空格按 CLI 的规则用反斜杠转义
+pub fn prepend_file_references(prompt: &str, files: &[PathBuf], project_path: &str) -> String {
+    if files.is_empty() {
+        return prompt.to_string();
+    }
+    let project = fs::canonicalize(project_path).unwrap_or_else(|_| PathBuf::from(project_path));
+    let references: Vec<String> = files
+        .iter()
+        .map(|path| {
+            let reference = path.strip_prefix(&project).unwrap_or(path);
+            format!(
+                "@{}",
+                display_path(reference)
+                    .replace('\\', "/")
+                    .replace(' ', "\\ ")
+            )
+        })
+        .collect();
+    format!("{}\n\n{}", references.join(" "), prompt)
+}
+
+/// 路径的显示形式（去掉 Windows 规范化路径的 `\\?\` 前缀）
+fn display_path(path: &Path) -> String {
+    let text = path.to_string_lossy();
+    text.strip_prefix(r"\\?\").unwrap_or(&text).to_string()
+}
+
+/// 生成本次执行使用的系统级 settings，返回文件路径
+///
+/// 在原系统 settings 的基础上只覆盖 `context.fileName`（禁用默认上下文时）并追加 `tools.exclude`，
+/// 其余系统级策略保持不变。文件名由覆盖内容决定，并发执行的不同配置互不覆盖
+pub fn write_system_settings_override(
+    disable_default_context: bool,
+    excluded_tools: &[String],
+) -> Result<PathBuf, String> {
+    let dir = ConfigPathBuilder::from_home_subdir(".anycode")?.build("gemini");
+    let mut name = String::from("settings-override");
+    if disable_default_context {
+        name.push_str("-no-context");
+    }
+    for tool in excluded_tools {
+        name.push_str("-no-");
+        name.push_str(tool);
+    }
+    let target = dir.join(format!("{}.json", name));
+
+    let source = std::env::var_os(SYSTEM_SETTINGS_ENV)
+        .map(PathBuf::from)
+        .filter(|path| !path.starts_with(&dir))
+        .unwrap_or_else(default_system_settings_path);
+    let settings = read_settings_file(&source).unwrap_or_else(|e| {
+        log::warn!("Failed to read Gemini system settings {:?}: {}", source, e);
+        serde_json::json!({})
+    });
+    let settings = apply_settings_override(settings, disable_default_context, excluded_tools);
+
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create directory {:?}: {}", parent, e))?;
+    }
+    let content = serde_json::to_string_pretty(&settings)
+        .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+    fs::write(&target, content).map_err(|e| format!("Failed to write {:?}: {}", target, e))?;
+    Ok(target)
+}
+
+/// 在系统 settings 上应用本次执行的覆盖项
+fn apply_settings_override(
+    mut settings: serde_json::Value,
+    disable_default_context: bool,
+    excluded_tools: &[String],
+) -> serde_json::Value {
+    if !settings.is_object() {
+        settings = serde_json::json!({});
+    }
+    if disable_default_context {
+        if !settings["context"].is_object() {
+            settings["context"] = serde_json::json!({});
+        }
+        settings["context"]["fileName"] = serde_json::json!(DISABLED_CONTEXT_FILE_NAME);
+    }
+    if !excluded_tools.is_empty() {
+        if !settings["tools"].is_object() {
+            settings["tools"] = serde_json::json!({});
+        }
+        if !settings["tools"]["exclude"].is_array() {
+            settings["tools"]["exclude"] = serde_json::json!([]);
+        }
+        if let Some(exclude) = settings["tools"]["exclude"].as_array_mut() {
+            for tool in excluded_tools {
+                if !exclude.iter().any(|existing| existing == tool.as_str()) {
+                    exclude.push(serde_json::json!(tool));
+                }
+            }
+        }
+    }
+    settings
+}
+
+/// Gemini CLI 的系统级 settings 默认位置
+fn default_system_settings_path() -> PathBuf {
+    if cfg!(target_os = "windows") {
+        PathBuf::from(r"C:\ProgramData\gemini-cli\settings.json")
+    } else if cfg!(target_os = "macos") {
+        PathBuf::from("/Library/Application Support/GeminiCli/settings.json")
+    } else {
+        PathBuf::from("/etc/gemini-cli/settings.json")
+    }
+}
+
+/// 用户 settings 中 `context.fileName` 配置的上下文文件名（字符串或数组）
+fn context_file_names(global_dir: Option<&Path>) -> Vec<String> {
+    let configured = global_dir
+        .and_then(|dir| read_settings_file(&dir.join("settings.json")).ok())
+        .map(|settings| match &settings["context"]["fileName"] {
+            serde_json::Value::String(name) => vec![name.clone()],
+            serde_json::Value::Array(names) => names
+                .iter()
+                .filter_map(|name| name.as_str().map(str::to_string))
+                .collect(),
+            _ => Vec::new(),
+        })
+        .unwrap_or_default();
+    if configured.is_empty() {
+        vec![DEFAULT_CONTEXT_FILE_NAME.to_string()]
+    } else {
+        configured
+    }
+}
+
+/// 默认会被加载的上下文文件：全局目录，然后从 git 根目录到项目目录逐级
+///
+/// 项目不在 git 仓库中时只检查项目目录本身
+fn default_context_files(
+    project: &Path,
+    global_dir: Option<&Path>,
+    names: &[String],
+) -> Vec<PathBuf> {
+    let mut dirs: Vec<&Path> = match project
+        .ancestors()
+        .position(|dir| dir.join(".git").exists())
+    {
+        Some(root) => project.ancestors().take(root + 1).collect(),
+        None => vec![project],
+    };
+    dirs.reverse();
+
+    global_dir
+        .into_iter()
+        .chain(dirs)
+        .flat_map(|dir| names.iter().map(move |name| dir.join(name)))
+        .filter(|path| path.is_file())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extra_files_are_validated() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = dir.path().join("project");
+        fs::create_dir_all(project.join("docs")).unwrap();
+        fs::write(project.join("docs/llm context.md"), "context").unwrap();
+        fs::write(dir.path().join("outside.md"), "outside").unwrap();
+        let project_path = project.to_string_lossy().to_string();
+
+        let resolved = resolve_extra_context_files(
+            &project_path,
+            &[],
+            &["docs/llm context.md".to_string(), "".to_string()],
+        )
+        .unwrap();
+        assert_eq!(resolved.len(), 1);
+        assert_eq!(
+            prepend_file_references("do it", &resolved, &project_path),
+            "@docs/llm\\ context.md\n\ndo it"
+        );
+
+        assert!(
+            resolve_extra_context_files(&project_path, &[], &["missing.md".to_string()])
+                .unwrap_err()
+                .contains("不存在")
+        );
+        assert!(
+            resolve_extra_context_files(&project_path, &[], &["../outside.md".to_string()])
+                .is_err()
+        );
+        let include = [dir.path().to_string_lossy().to_string()];
+        assert!(resolve_extra_context_files(
+            &project_path,
+            &include,
+            &["../outside.md".to_string()]
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn extra_files_are_limited_in_total_size() {
+        let dir = tempfile::tempdir().unwrap();
+        let half = "x".repeat((MAX_EXTRA_CONTEXT_BYTES / 2 + 1) as usize);
+        fs::write(dir.path().join("a.md"), &half).unwrap();
+        fs::write(dir.path().join("b.md"), &half).unwrap();
+        let project_path = dir.path().to_string_lossy().to_string();
+
+        assert!(resolve_extra_context_files(&project_path, &[], &["a.md".to_string()]).is_ok());
+        assert!(resolve_extra_context_files(
+            &project_path,
+            &[],
+            &["a.md".to_string(), "b.md".to_string()]
+        )
+        .unwrap_err()
+        .contains("200 KB"));
+    }
+
+    #[test]
+    fn settings_override_keeps_system_policies() {
+        let system = serde_json::json!({
+            "telemetry": {"enabled": false},
+            "tools": {"exclude": ["run_shell_command"]}
+        });
+
+        let unchanged = apply_settings_override(system.clone(), false, &[]);
+        assert_eq!(unchanged, system);
+
+        let overridden = apply_settings_override(
+            system,
+            true,
+            &[
+                "google_web_search".to_string(),
+                "run_shell_command".to_string(),
+            ],
+        );
+        assert_eq!(overridden["telemetry"]["enabled"], false);
+        assert_eq!(
+            overridden["context"]["fileName"],
+            DISABLED_CONTEXT_FILE_NAME
+        );
+        assert_eq!(
+            overridden["tools"]["exclude"],
+            serde_json::json!(["run_shell_command", "google_web_search"])
+        );
+    }
+
+    #[test]
+    fn default_context_is_collected_up_to_git_root() {
+        let dir = tempfile::tempdir().unwrap();
+        let global = dir.path().join("global");
+        let repo = dir.path().join("repo");
+        let project = repo.join("packages/app");
+        fs::create_dir_all(&global).unwrap();
+        fs::create_dir_all(repo.join(".git")).unwrap();
+        fs::create_dir_all(&project).unwrap();
+        fs::write(dir.path().join("GEMINI.md"), "above the repo").unwrap();
+        fs::write(global.join("GEMINI.md"), "global").unwrap();
+        fs::write(repo.join("GEMINI.md"), "repo").unwrap();
+        fs::write(project.join("GEMINI.md"), "project").unwrap();
+
+        let names = vec![DEFAULT_CONTEXT_FILE_NAME.to_string()];
+        assert_eq!(
+            default_context_files(&project, Some(&global), &names),
+            vec![
+                global.join("GEMINI.md"),
+                repo.join("GEMINI.md"),
+                project.join("GEMINI.md"),
+            ]
+        );
+    }
+}
@@ -10,16 +10,33 @@ use chrono::Utc;
  */
 use serde::{Deserialize, Serialize};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use tauri::AppHandle;
 
+use super::super::messages::{t, t_with, MessageKey};
 // Import simple_git for rewind operations
 use super::super::simple_git;
+// Protected paths snapshot and post-task check
+use super::super::protected_paths::{
+    attach_restore_records, capture_task_snapshot, check_protected_changes,
+};
+// Shared session path resolution across engines
+use super::super::session_annotations::bind_prompt_trace;
+use super::super::session_invalidation::invalidate_session;
+use super::super::session_utils::resolve_session_file;
+// Import execution tracing
+use super::super::execution_trace::{
+    record_trace_event, with_trace_trailer, TraceFields, STAGE_GIT_SNAPSHOT, STAGE_PROMPT_RECORDED,
+};
 // Import rewind helpers/types shared with Claude
 use super::super::prompt_tracker::{
-    load_execution_config, PromptRecord as ClaudePromptRecord, RewindCapabilities, RewindMode,
+    load_execution_config, report_snapshot_failure, PromptCompletionResult,
+    PromptRecord as ClaudePromptRecord, RewindCapabilities, RewindMode,
 };
 // Import Gemini config helpers
 use super::config::get_gemini_dir;
+use crate::utils::session_lock::{write_session_file, FileSnapshot, SessionLock};
+use crate::utils::timestamp::{deserialize_unix_seconds, parse_timestamp_value};
 
 // Align Gemini prompt record type with Claude prompt tracker representation
 pub type PromptRecord = ClaudePromptRecord;
@@ -35,7 +52,12 @@ pub struct GeminiPromptGitRecord {
     pub prompt_index: usize,
     pub commit_before: String,
     pub commit_after: Option<String>,
-    pub timestamp: String,
+    /// UTC Unix seconds (older records stored an RFC3339 string)
+    #[serde(default, deserialize_with = "deserialize_unix_seconds")]
+    pub timestamp: i64,
+    /// Trace ID of the execution that produced this prompt
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trace_id: Option<String>,
 }
 
 /// Collection of Git records for a Gemini session
@@ -67,20 +89,34 @@ pub fn get_gemini_git_records_dir() -> Result<PathBuf, String> {
 
 /// Get the Gemini sessions directory (for chats/*.json files)
 pub fn get_gemini_sessions_dir(project_path: &str) -> Result<PathBuf, String> {
-    let gemini_dir = get_gemini_dir()?;
+    Ok(gemini_sessions_dir_in(&get_gemini_dir()?, project_path))
+}
 
+/// Session directory of a project under the given Gemini config directory
+pub(crate) fn gemini_sessions_dir_in(gemini_dir: &Path, project_path: &str) -> PathBuf {
     // Hash project path to get session directory
     use super::config::hash_project_path;
     let project_hash = hash_project_path(project_path);
 
-    Ok(gemini_dir.join("tmp").join(project_hash).join("chats"))
+    gemini_dir.join("tmp").join(project_hash).join("chats")
 }
 
 /// Find Gemini session file by session ID
 /// Gemini CLI stores session files with format: session-<date>-<session_id_prefix>.json
 /// where session_id_prefix is the first 8 characters of the full UUID
-/// This function searches by prefix and verifies by reading the internal sessionId field
-fn find_gemini_session_file(sessions_dir: &PathBuf, session_id: &str) -> Result<PathBuf, String> {
+/// This function searches by prefix and verifies by reading the internal sessionId field;
+/// results are remembered in the session cache index until the session is invalidated
+pub(crate) fn find_gemini_session_file(
+    sessions_dir: &PathBuf,
+    session_id: &str,
+) -> Result<PathBuf, String> {
+    use super::config::{indexed_session_file, remember_session_file};
+
+    if let Some(path) = indexed_session_file(session_id).filter(|p| p.starts_with(sessions_dir)) {
+        log::debug!("[Gemini] Using indexed session file: {:?}", path);
+        return Ok(path);
+    }
+
     // Extract the first 8 characters of session_id for filename matching
     // Gemini CLI uses this prefix in the filename
     let session_prefix = if session_id.len() >= 8 {
@@ -125,6 +161,7 @@ fn find_gemini_session_file(sessions_dir: &PathBuf, session_id: &str) -> Result<
                 if let Some(file_session_id) = data.get("sessionId").and_then(|v| v.as_str()) {
                     if file_session_id == session_id {
                         log::info!("[Gemini] Found matching session file: {:?}", candidate);
+                        remember_session_file(session_id, &candidate);
                         return Ok(candidate);
                     }
                 }
@@ -210,12 +247,35 @@ fn extract_gemini_prompts(
     session_id: &str,
     project_path: &str,
 ) -> Result<Vec<PromptRecord>, String> {
-    let sessions_dir = get_gemini_sessions_dir(project_path)?;
+    // Find session file via the shared resolver (handles Gemini's 8-char prefix naming)
+    let session_file = resolve_session_file("gemini", session_id, project_path)?;
 
-    // Find session file using helper function (handles Gemini's 8-char prefix naming)
-    let session_file = find_gemini_session_file(&sessions_dir, session_id)?;
+    let mut prompts = extract_gemini_prompts_from_file(&session_file)?;
 
-    let content = fs::read_to_string(&session_file)
+    // Enrich with git records (if present)
+    let git_records = load_gemini_git_records(session_id)?;
+    for prompt in prompts.iter_mut() {
+        if let Some(record) = git_records
+            .records
+            .iter()
+            .find(|r| r.prompt_index == prompt.index)
+        {
+            prompt.git_commit_before = record.commit_before.clone();
+            prompt.git_commit_after = record.commit_after.clone();
+
+            prompt.fill_missing_timestamp(record.timestamp);
+        }
+    }
+    attach_restore_records("gemini", session_id, &mut prompts);
+
+    Ok(prompts)
+}
+
+/// Extract user prompts from a Gemini chat file (without git record enrichment)
+pub(crate) fn extract_gemini_prompts_from_file(
+    session_file: &Path,
+) -> Result<Vec<PromptRecord>, String> {
+    let content = fs::read_to_string(session_file)
         .map_err(|e| format!("Failed to read session file: {}", e))?;
 
     let session_data: serde_json::Value = serde_json::from_str(&content)
@@ -250,47 +310,26 @@ fn extract_gemini_prompts(
             continue;
         }
 
-        // Extract timestamp
-        let timestamp = message
-            .get("timestamp")
-            .and_then(|t| t.as_str())
-            .and_then(|s| chrono::DateTime::parse_from_rfc3339(s).ok())
-            .map(|dt| dt.timestamp())
-            .unwrap_or_else(|| Utc::now().timestamp());
-
         // All Gemini prompts sent from project interface are marked as "project"
-        prompts.push(PromptRecord {
+        let mut prompt = PromptRecord {
             index: prompt_index,
             text: extracted_text,
             git_commit_before: "NONE".to_string(),
             git_commit_after: None,
-            timestamp,
+            timestamp: 0,
+            timestamp_ms: 0,
             source: "project".to_string(), // Gemini always from project interface
             line_number: 0,                // Gemini uses JSON format, no specific line number
-        });
+            protected_restore: None,
+            reverted_at: None,
+        };
+        // Missing timestamps stay 0 so the git record time can fill them in
+        prompt.set_sent_at(message.get("timestamp").and_then(parse_timestamp_value));
+        prompts.push(prompt);
 
         prompt_index += 1;
     }
 
-    // Enrich with git records (if present)
-    let git_records = load_gemini_git_records(session_id)?;
-    for prompt in prompts.iter_mut() {
-        if let Some(record) = git_records
-            .records
-            .iter()
-            .find(|r| r.prompt_index == prompt.index)
-        {
-            prompt.git_commit_before = record.commit_before.clone();
-            prompt.git_commit_after = record.commit_after.clone();
-
-            if prompt.timestamp == 0 {
-                if let Ok(ts) = chrono::DateTime::parse_from_rfc3339(&record.timestamp) {
-                    prompt.timestamp = ts.timestamp();
-                }
-            }
-        }
-    }
-
     Ok(prompts)
 }
 
@@ -353,9 +392,7 @@ pub async fn check_gemini_rewind_capabilities(
             conversation: true,
             code: false,
             both: false,
-            warning: Some(
-                "Git 操作已在配置中禁用。只能撤回对话历史，无法回滚代码变更。".to_string(),
-            ),
+            warning: Some(t(MessageKey::RewindGitDisabled)),
             source: prompt.source.clone(),
         });
     }
@@ -381,7 +418,7 @@ pub async fn check_gemini_rewind_capabilities(
             code: has_valid_commit,
             both: has_valid_commit,
             warning: if !has_valid_commit {
-                Some("此提示词没有关联的 Git 记录，只能删除消息，无法回滚代码".to_string())
+                Some(t(MessageKey::RewindNoValidCommit))
             } else {
                 None
             },
@@ -396,7 +433,7 @@ pub async fn check_gemini_rewind_capabilities(
             conversation: true,
             code: false,
             both: false,
-            warning: Some("此提示词没有关联的 Git 记录，只能删除消息".to_string()),
+            warning: Some(t(MessageKey::RewindNoGitRecord)),
             source: "project".to_string(),
         })
     }
@@ -412,10 +449,12 @@ pub async fn record_gemini_prompt_sent(
     session_id: String,
     project_path: String,
     _prompt_text: String,
+    trace_id: Option<String>,
 ) -> Result<usize, String> {
     log::info!(
-        "[Gemini Record] Recording prompt sent for session: {}",
-        session_id
+        "[Gemini Record] Recording prompt sent for session: {} (trace_id={})",
+        session_id,
+        trace_id.as_deref().unwrap_or("-")
     );
 
     // Check if Git operations are disabled in config
@@ -431,6 +470,8 @@ pub async fn record_gemini_prompt_sent(
             "[Gemini Record] Returning prompt index #{} (no git record)",
             prompt_index
         );
+        capture_task_snapshot("gemini", &session_id, None, prompt_index, &project_path).await;
+        bind_prompt_trace(trace_id.as_deref(), "gemini", &session_id, prompt_index);
         return Ok(prompt_index);
     }
 
@@ -458,7 +499,8 @@ pub async fn record_gemini_prompt_sent(
         prompt_index,
         commit_before: commit_before.clone(),
         commit_after: None,
-        timestamp: Utc::now().to_rfc3339(),
+        timestamp: Utc::now().timestamp(),
+        trace_id: trace_id.clone(),
     };
 
     git_records.records.push(record);
@@ -469,6 +511,20 @@ pub async fn record_gemini_prompt_sent(
         prompt_index,
         &commit_before[..8.min(commit_before.len())]
     );
+    if let Some(trace_id) = trace_id.as_deref() {
+        record_trace_event(
+            trace_id,
+            STAGE_PROMPT_RECORDED,
+            TraceFields::engine("gemini")
+                .with_session(Some(&session_id))
+                .with_detail(serde_json::json!({
+                    "promptIndex": prompt_index,
+                    "commitBefore": commit_before,
+                })),
+        );
+    }
+    capture_task_snapshot("gemini", &session_id, None, prompt_index, &project_path).await;
+    bind_prompt_trace(trace_id.as_deref(), "gemini", &session_id, prompt_index);
 
     Ok(prompt_index)
 }
@@ -476,17 +532,30 @@ pub async fn record_gemini_prompt_sent(
 /// Record a Gemini prompt completion (called after AI response)
 #[tauri::command]
 pub async fn record_gemini_prompt_completed(
+    app: AppHandle,
     session_id: String,
     project_path: String,
     prompt_index: usize,
     prompt_text: Option<String>,
-) -> Result<(), String> {
+    trace_id: Option<String>,
+) -> Result<PromptCompletionResult, String> {
     log::info!(
-        "[Gemini Record] Recording prompt #{} completed for session: {}",
+        "[Gemini Record] Recording prompt #{} completed for session: {} (trace_id={})",
         prompt_index,
-        session_id
+        session_id,
+        trace_id.as_deref().unwrap_or("-")
     );
 
+    // Compare protected paths with the snapshot taken when the prompt was sent
+    check_protected_changes(
+        &app,
+        "gemini",
+        &session_id,
+        prompt_index,
+        trace_id.as_deref(),
+    )
+    .await;
+
     // Check if Git operations are disabled in config
     let execution_config =
         load_execution_config().map_err(|e| format!("Failed to load execution config: {}", e))?;
@@ -495,30 +564,42 @@ pub async fn record_gemini_prompt_completed(
         log::info!(
             "[Gemini Record] Git operations disabled, skipping git commit and record update"
         );
-        return Ok(());
+        return Ok(PromptCompletionResult::default());
     }
 
     // Auto-commit any changes made by AI
-    let commit_message =
-        build_prompt_commit_message("[Gemini]", prompt_text.as_deref(), prompt_index);
-    match simple_git::git_commit_changes(&project_path, &commit_message) {
-        Ok(true) => {
-            log::info!(
-                "[Gemini Record] Auto-committed changes after prompt #{}",
-                prompt_index
-            );
-        }
-        Ok(false) => {
-            log::debug!(
-                "[Gemini Record] No changes to commit after prompt #{}",
-                prompt_index
-            );
+    let commit_message = with_trace_trailer(
+        build_prompt_commit_message("[Gemini]", prompt_text.as_deref(), prompt_index),
+        trace_id.as_deref(),
+    );
+    let mut result = PromptCompletionResult::default();
+    let committed = match simple_git::git_commit_changes(
+        &project_path,
+        &commit_message,
+        execution_config.git_identity_fallback,
+    ) {
+        Ok(committed) => {
+            if committed {
+                log::info!(
+                    "[Gemini Record] Auto-committed changes after prompt #{}",
+                    prompt_index
+                );
+            } else {
+                log::debug!(
+                    "[Gemini Record] No changes to commit after prompt #{}",
+                    prompt_index
+                );
+            }
+            result.snapshot_created = true;
+            committed
         }
         Err(e) => {
-            log::warn!("[Gemini Record] Failed to auto-commit: {}", e);
-            // Continue anyway
+            // Continue anyway, but tell the user
+            report_snapshot_failure(&app, "gemini", &session_id, prompt_index, &e);
+            result.snapshot_error = Some(e);
+            false
         }
-    }
+    };
 
     // Get current commit (state after AI completion)
     let commit_after = simple_git::git_current_commit(&project_path)
@@ -533,6 +614,9 @@ pub async fn record_gemini_prompt_completed(
         .find(|r| r.prompt_index == prompt_index)
     {
         record.commit_after = Some(commit_after.clone());
+        if record.trace_id.is_none() {
+            record.trace_id = trace_id.clone();
+        }
         save_gemini_git_records(&session_id, &git_records)?;
 
         log::info!(
@@ -546,8 +630,22 @@ pub async fn record_gemini_prompt_completed(
             prompt_index
         );
     }
+    if let Some(trace_id) = trace_id.as_deref() {
+        record_trace_event(
+            trace_id,
+            STAGE_GIT_SNAPSHOT,
+            TraceFields::engine("gemini")
+                .with_session(Some(&session_id))
+                .with_detail(serde_json::json!({
+                    "promptIndex": prompt_index,
+                    "commit": commit_after,
+                    "committed": committed,
+                })),
+        );
+    }
 
-    Ok(())
+    result.commit_after = Some(commit_after);
+    Ok(result)
 }
 
 // ============================================================================
@@ -569,12 +667,12 @@ pub fn truncate_gemini_session_to_prompt(
     project_path: &str,
     prompt_index: usize,
 ) -> Result<(), String> {
-    let sessions_dir = get_gemini_sessions_dir(project_path)?;
-
-    // Find session file using helper function (handles Gemini's 8-char prefix naming)
-    let session_file = find_gemini_session_file(&sessions_dir, session_id)?;
+    // Find session file via the shared resolver (handles Gemini's 8-char prefix naming)
+    let session_file = resolve_session_file("gemini", session_id, project_path)?;
+    let _lock = SessionLock::acquire(&session_file, "gemini rewind")?;
 
     // Read session JSON
+    let snapshot = FileSnapshot::capture(&session_file)?;
     let content = fs::read_to_string(&session_file)
         .map_err(|e| format!("Failed to read session file: {}", e))?;
 
@@ -624,8 +722,7 @@ pub fn truncate_gemini_session_to_prompt(
     let new_content = serde_json::to_string_pretty(&session_data)
         .map_err(|e| format!("Failed to serialize session: {}", e))?;
 
-    fs::write(&session_file, new_content)
-        .map_err(|e| format!("Failed to write session file: {}", e))?;
+    write_session_file(&session_file, &snapshot, new_content)?;
 
     log::info!(
         "[Gemini Rewind] Truncated session to before prompt #{}",
@@ -641,6 +738,7 @@ pub fn truncate_gemini_session_to_prompt(
 /// Revert Gemini session to a specific prompt
 #[tauri::command]
 pub async fn revert_gemini_to_prompt(
+    app: AppHandle,
     session_id: String,
     project_path: String,
     prompt_index: usize,
@@ -680,18 +778,16 @@ pub async fn revert_gemini_to_prompt(
     match mode {
         RewindMode::CodeOnly | RewindMode::Both => {
             if git_operations_disabled {
-                return Err(
-                    "无法回滚代码：Git 操作已在配置中禁用。只能撤回对话历史，无法回滚代码变更。"
-                        .into(),
-                );
+                return Err(t(MessageKey::RewindCodeBlockedGitDisabled));
             }
             if git_record.is_none() {
-                return Err(format!(
-                    "无法回滚代码：提示词 #{} 没有关联的 Git 记录",
-                    prompt_index
+                return Err(t_with(
+                    MessageKey::RewindCodeBlockedNoRecord,
+                    &[("index", &prompt_index)],
                 ));
             }
         }
+        RewindMode::Selective { .. } => return Err(t(MessageKey::RewindSelectiveUnsupported)),
         RewindMode::ConversationOnly => {}
     }
 
@@ -724,6 +820,7 @@ pub async fn revert_gemini_to_prompt(
                     "Auto-stash before Gemini code revert to prompt #{}",
                     prompt_index
                 ),
+                execution_config.git_identity_fallback,
             )
             .map_err(|e| format!("Failed to stash changes: {}", e))?;
 
@@ -809,6 +906,7 @@ pub async fn revert_gemini_to_prompt(
                     &commit_after,
                     &format!("[Gemini Revert] 撤回提示词 #{} 的代码更改", record.prompt_index),
                     3, // Max 3 retries for Git lock conflicts
+                    execution_config.git_identity_fallback,
                 );
 
                 match revert_result {
@@ -848,9 +946,9 @@ pub async fn revert_gemini_to_prompt(
                 simple_git::git_reset_hard(&project_path, &original_head)
                     .map_err(|e| format!("Failed to rollback: {}", e))?;
 
-                return Err(format!(
-                    "撤回失败，已回滚到操作前状态。原因: {}",
-                    failure_message
+                return Err(t_with(
+                    MessageKey::RewindRevertFailedRolledBack,
+                    &[("reason", &failure_message)],
                 ));
             }
 
@@ -872,6 +970,7 @@ pub async fn revert_gemini_to_prompt(
                     "Auto-stash before Gemini full revert to prompt #{}",
                     prompt_index
                 ),
+                execution_config.git_identity_fallback,
             )
             .map_err(|e| format!("Failed to stash changes: {}", e))?;
 
@@ -957,6 +1056,7 @@ pub async fn revert_gemini_to_prompt(
                     &commit_after,
                     &format!("[Gemini Revert] 撤回提示词 #{} 的代码更改", record.prompt_index),
                     3, // Max 3 retries for Git lock conflicts
+                    execution_config.git_identity_fallback,
                 );
 
                 match revert_result {
@@ -996,9 +1096,9 @@ pub async fn revert_gemini_to_prompt(
                 simple_git::git_reset_hard(&project_path, &original_head)
                     .map_err(|e| format!("Failed to rollback: {}", e))?;
 
-                return Err(format!(
-                    "撤回失败，已回滚到操作前状态。原因: {}",
-                    failure_message
+                return Err(t_with(
+                    MessageKey::RewindRevertFailedRolledBack,
+                    &[("reason", &failure_message)],
                 ));
             }
 
@@ -1019,17 +1119,15 @@ pub async fn revert_gemini_to_prompt(
 
                 if let Err(rollback_err) = simple_git::git_reset_hard(&project_path, &original_head) {
                     log::error!("[CRITICAL] Git rollback failed: {}", rollback_err);
-                    return Err(format!(
-                        "会话截断失败且 Git 回滚失败。\n\
-                         会话错误: {}\n\
-                         Git 回滚错误: {}",
-                        e, rollback_err
+                    return Err(t_with(
+                        MessageKey::RewindTruncateFailedRollbackFailed,
+                        &[("reason", &e), ("rollbackError", &rollback_err)],
                     ));
                 }
 
-                return Err(format!(
-                    "会话截断失败，已原子性回滚 Git 更改。原因: {}",
-                    e
+                return Err(t_with(
+                    MessageKey::RewindTruncateFailedRolledBack,
+                    &[("reason", &e)],
                 ));
             }
 
@@ -1044,19 +1142,15 @@ pub async fn revert_gemini_to_prompt(
 
                     if let Err(rollback_err) = simple_git::git_reset_hard(&project_path, &original_head) {
                         log::error!("[CRITICAL] Git rollback failed: {}", rollback_err);
-                        return Err(format!(
-                            "Git 记录截断失败且回滚失败。\n\
-                             记录错误: {}\n\
-                             回滚错误: {}\n\
-                             注意：会话已截断。",
-                            e, rollback_err
+                        return Err(t_with(
+                            MessageKey::RewindRecordsTruncateFailedRollbackFailed,
+                            &[("reason", &e), ("rollbackError", &rollback_err)],
                         ));
                     }
 
-                    return Err(format!(
-                        "Git 记录截断失败，已回滚 Git 更改。\n\
-                         注意：会话已截断但无法回滚。原因: {}",
-                        e
+                    return Err(t_with(
+                        MessageKey::RewindRecordsTruncateFailedRolledBack,
+                        &[("reason", &e)],
                     ));
                 }
             }
@@ -1066,8 +1160,12 @@ pub async fn revert_gemini_to_prompt(
                 prompt_index
             );
         }
+
+        RewindMode::Selective { .. } => return Err(t(MessageKey::RewindSelectiveUnsupported)),
     }
 
+    invalidate_session(&app, "gemini", Some(&session_id), Some(&project_path));
+
     // Return the prompt text for restoring to input (same as Claude's behavior)
     Ok(prompt.text.clone())
 }
@@ -11,10 +11,13 @@
 //! - **Multi-Auth Support**: Google OAuth, API Key, and Vertex AI authentication
 
 pub mod config;
+pub mod context_files;
 pub mod git_ops;
 pub mod parser;
 pub mod provider;
+pub mod retention;
 pub mod session;
+pub mod settings;
 pub mod types;
 pub mod usage;
 
@@ -40,6 +43,11 @@ pub use config::{
 };
 pub use session::{cancel_gemini, check_gemini_installed, execute_gemini};
 
+// Re-export Gemini context file commands
+pub use context_files::{
+    get_gemini_context_files, preview_gemini_context_files, save_gemini_context_files,
+};
+
 // Re-export Gemini Rewind commands
 pub use git_ops::{
     check_gemini_rewind_capabilities, get_gemini_prompt_list, record_gemini_prompt_completed,
@@ -49,8 +57,15 @@ pub use git_ops::{
 // Re-export Gemini Provider commands
 pub use provider::{
     add_gemini_provider_config, clear_gemini_provider_config, delete_gemini_provider_config,
-    get_current_gemini_provider_config, get_gemini_provider_presets, reorder_gemini_provider_configs,
-    switch_gemini_provider, test_gemini_provider_connection, update_gemini_provider_config,
+    get_current_gemini_provider_config, get_gemini_provider_presets,
+    reorder_gemini_provider_configs, switch_gemini_provider, test_all_gemini_providers,
+    test_gemini_provider_connection, update_gemini_provider_config, ProviderTestResult,
+};
+
+// Re-export Gemini Session Retention commands
+pub use retention::{
+    cleanup_gemini_sessions, get_gemini_session_marks, list_gemini_trash,
+    restore_gemini_trash_session, set_gemini_session_mark,
 };
 
 // Re-export Gemini Usage Statistics commands
@@ -3,6 +3,7 @@
 //! Parses stream-json output from Gemini CLI and converts events
 //! to the unified ClaudeStreamMessage format for frontend rendering.
 
+use serde::Serialize;
 use serde_json::{json, Value};
 
 use super::types::{GeminiStats, GeminiStreamEvent, TokenUsage};
@@ -39,6 +40,90 @@ pub fn parse_gemini_line_flexible(line: &str) -> Result<Value, String> {
         .map_err(|e| format!("Failed to parse JSON: {} - line: {}", e, trimmed))
 }
 
+// ============================================================================
+// Streaming Text Buffering
+// ============================================================================
+
+/// Whether the event is a streamed assistant text fragment
+///
+/// These fragments go through `MarkdownStreamBuffer` before being emitted so that
+/// code fences and table rows split across fragments reach the frontend whole
+pub fn is_assistant_delta(event: &GeminiStreamEvent) -> bool {
+    matches!(
+        event,
+        GeminiStreamEvent::Message { role, delta: true, .. } if role == "assistant"
+    )
+}
+
+/// Build an assistant text fragment event for text released by the buffer
+pub fn assistant_delta_event(content: String, timestamp: Option<String>) -> GeminiStreamEvent {
+    GeminiStreamEvent::Message {
+        role: "assistant".to_string(),
+        content,
+        delta: true,
+        timestamp,
+    }
+}
+
+// ============================================================================
+// Search Grounding Citations
+// ============================================================================
+
+/// A source cited by a Google Search grounded answer
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GroundingCitation {
+    /// Citation number used by the `[n]` markers in the answer
+    pub index: u32,
+    pub title: String,
+    pub url: String,
+}
+
+/// Extract the sources block that `google_web_search` appends to its result
+///
+/// The tool ends its output with a `Sources:` line followed by `[n] Title (url)` lines
+pub fn extract_grounding_citations(text: &str) -> Vec<GroundingCitation> {
+    let lines: Vec<&str> = text.lines().collect();
+    let Some(sources_start) = lines
+        .iter()
+        .rposition(|line| line.trim().eq_ignore_ascii_case("sources:"))
+    else {
+        return Vec::new();
+    };
+
+    lines[sources_start + 1..]
+        .iter()
+        .filter_map(|line| parse_citation_line(line))
+        .collect()
+}
+
+fn parse_citation_line(line: &str) -> Option<GroundingCitation> {
+    let rest = line.trim().strip_prefix('[')?;
+    let (index, rest) = rest.split_once(']')?;
+    let index = index.trim().parse().ok()?;
+    let rest = rest.trim().strip_suffix(')')?;
+    let (title, url) = rest.rsplit_once('(')?;
+    let url = url.trim();
+    if !url.starts_with("http://") && !url.starts_with("https://") {
+        return None;
+    }
+    Some(GroundingCitation {
+        index,
+        title: title.trim().to_string(),
+        url: url.to_string(),
+    })
+}
+
+/// Add `geminiMetadata.citations` when a tool result carries grounding sources
+fn attach_grounding_citations(unified: &mut Value, output: &Value) {
+    let Some(text) = output.as_str() else {
+        return;
+    };
+    let citations = extract_grounding_citations(text);
+    if !citations.is_empty() {
+        unified["geminiMetadata"]["citations"] = json!(citations);
+    }
+}
+
 // ============================================================================
 // Event Conversion to Unified Format
 // ============================================================================
@@ -133,7 +218,7 @@ pub fn convert_to_unified_message(event: &GeminiStreamEvent) -> Value {
                 output.clone()
             };
 
-            json!({
+            let mut unified = json!({
                 "type": "user",
                 "message": {
                     "content": [{
@@ -151,7 +236,9 @@ pub fn convert_to_unified_message(event: &GeminiStreamEvent) -> Value {
                     "toolId": tool_id,
                     "status": status
                 }
-            })
+            });
+            attach_grounding_citations(&mut unified, output);
+            unified
         }
 
         GeminiStreamEvent::Error {
@@ -277,7 +364,7 @@ pub fn convert_raw_to_unified_message(raw: &Value) -> Value {
                     .cloned()
                     .or_else(|| raw.get("response").cloned())
                     .unwrap_or(Value::Null);
-                return json!({
+                let mut unified = json!({
                     "type": "user",
                     "message": {
                         "content": [{
@@ -296,6 +383,8 @@ pub fn convert_raw_to_unified_message(raw: &Value) -> Value {
                         "raw": raw
                     }
                 });
+                attach_grounding_citations(&mut unified, &output);
+                return unified;
             }
             "result" => {
                 let stats = raw
@@ -531,6 +620,26 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_extract_grounding_citations() {
+        let output = "Web search results for \"rust 2024\":\n\nRust 2024 shipped in 1.85.[1][2]\n\nSources:\n[1] Announcing Rust 1.85.0 (https://blog.rust-lang.org/2025/02/20/Rust-1.85.0.html)\n[2] The Rust Edition Guide (https://doc.rust-lang.org/edition-guide/)\n";
+        let citations = extract_grounding_citations(output);
+
+        assert_eq!(citations.len(), 2);
+        assert_eq!(citations[0].index, 1);
+        assert_eq!(citations[0].title, "Announcing Rust 1.85.0");
+        assert_eq!(citations[1].url, "https://doc.rust-lang.org/edition-guide/");
+
+        let line = format!(
+            r#"{{"type":"tool_result","tool_id":"t1","status":"success","output":{}}}"#,
+            serde_json::to_string(output).unwrap()
+        );
+        let unified = convert_to_unified_message(&parse_gemini_line(&line).unwrap());
+        assert_eq!(unified["geminiMetadata"]["citations"][1]["index"], 2);
+
+        assert!(extract_grounding_citations("no sources here").is_empty());
+    }
+
     #[test]
     fn test_convert_to_unified() {
         let event = GeminiStreamEvent::Message {
@@ -12,7 +12,13 @@ use std::fs;
 use std::path::PathBuf;
 
 use super::config::get_gemini_dir;
+use super::settings::{read_settings_file, update_settings_file};
+use crate::commands::app_error::AppError;
+use crate::commands::messages::{t, t_with, MessageKey};
+use crate::commands::provider_presets;
+use crate::commands::rate_limiter;
 use crate::commands::wsl_utils;
+use crate::utils::http_headers::{apply_custom_headers, masked_headers, validate_custom_headers};
 
 // ============================================================================
 // Type Definitions
@@ -31,8 +37,17 @@ pub struct GeminiProviderConfig {
     pub is_official: Option<bool>,
     pub is_partner: Option<bool>,
     pub created_at: Option<i64>,
+    /// Extra HTTP headers required by self-hosted gateways (e.g. X-Org-Id)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub custom_headers: Option<HashMap<String, String>>,
+    /// Preset origin; "remote" for presets from the remote manifest
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
 }
 
+/// Gemini CLI 读取自定义请求头的环境变量（格式："Name: value, Name2: value2"）
+const GEMINI_CUSTOM_HEADERS_ENV: &str = "GEMINI_CLI_CUSTOM_HEADERS";
+
 /// Current Gemini configuration from ~/.gemini directory
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -50,12 +65,12 @@ pub struct CurrentGeminiProviderConfig {
 // ============================================================================
 
 /// Get Gemini .env file path
-fn get_gemini_env_path() -> Result<PathBuf, String> {
+pub(crate) fn get_gemini_env_path() -> Result<PathBuf, String> {
     Ok(get_gemini_dir()?.join(".env"))
 }
 
 /// Get Gemini settings.json file path
-fn get_gemini_settings_path() -> Result<PathBuf, String> {
+pub(crate) fn get_gemini_settings_path() -> Result<PathBuf, String> {
     Ok(get_gemini_dir()?.join("settings.json"))
 }
 
@@ -70,7 +85,7 @@ fn get_gemini_providers_path() -> Result<PathBuf, String> {
 // ============================================================================
 
 /// Read .env file and parse into HashMap
-fn read_env_file(path: &PathBuf) -> Result<HashMap<String, String>, String> {
+pub(crate) fn read_env_file(path: &PathBuf) -> Result<HashMap<String, String>, String> {
     if !path.exists() {
         return Ok(HashMap::new());
     }
@@ -121,34 +136,28 @@ fn write_env_file(path: &PathBuf, env_map: &HashMap<String, String>) -> Result<(
     fs::write(path, content).map_err(|e| format!("Failed to write .env file: {}", e))
 }
 
-// ============================================================================
-// settings.json Operations
-// ============================================================================
-
-/// Read settings.json
-fn read_settings_file(path: &PathBuf) -> Result<serde_json::Value, String> {
-    if !path.exists() {
-        return Ok(serde_json::json!({}));
+/// 将自定义请求头编码为 GEMINI_CLI_CUSTOM_HEADERS 的值
+fn encode_custom_headers(headers: &HashMap<String, String>) -> Result<String, String> {
+    let mut names: Vec<&String> = headers.keys().collect();
+    names.sort();
+    let mut parts = Vec::with_capacity(names.len());
+    for name in names {
+        let value = &headers[name];
+        // 该变量以逗号分隔多个 header，值中不能再包含逗号
+        if value.contains(',') {
+            return Err(format!(
+                "Value for header '{}' cannot contain ',' (not supported by Gemini CLI)",
+                name
+            ));
+        }
+        parts.push(format!("{}: {}", name, value));
     }
-
-    let content =
-        fs::read_to_string(path).map_err(|e| format!("Failed to read settings.json: {}", e))?;
-
-    serde_json::from_str(&content).map_err(|e| format!("Failed to parse settings.json: {}", e))
+    Ok(parts.join(", "))
 }
 
-/// Write settings.json (preserves existing fields like mcpServers)
-fn write_settings_file(path: &PathBuf, settings: &serde_json::Value) -> Result<(), String> {
-    // Ensure parent directory exists
-    if let Some(parent) = path.parent() {
-        fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory: {}", e))?;
-    }
-
-    let content = serde_json::to_string_pretty(settings)
-        .map_err(|e| format!("Failed to serialize settings: {}", e))?;
-
-    fs::write(path, content).map_err(|e| format!("Failed to write settings.json: {}", e))
-}
+// ============================================================================
+// settings.json Operations
+// ============================================================================
 
 /// Set selected auth type in settings.json
 fn set_auth_type_in_settings(settings: &mut serde_json::Value, auth_type: &str) {
@@ -168,27 +177,34 @@ fn set_auth_type_in_settings(settings: &mut serde_json::Value, auth_type: &str)
 
 /// Get custom Gemini provider presets
 #[tauri::command]
-pub async fn get_gemini_provider_presets() -> Result<Vec<GeminiProviderConfig>, String> {
+pub async fn get_gemini_provider_presets() -> Result<Vec<GeminiProviderConfig>, AppError> {
     log::info!("[Gemini Provider] Getting provider presets");
 
     let providers_path = get_gemini_providers_path()?;
 
-    if !providers_path.exists() {
-        return Ok(vec![]);
-    }
-
-    let content = fs::read_to_string(&providers_path)
-        .map_err(|e| format!("Failed to read providers.json: {}", e))?;
+    let mut providers: Vec<GeminiProviderConfig> = if providers_path.exists() {
+        let content = fs::read_to_string(&providers_path)
+            .map_err(|e| format!("Failed to read providers.json: {}", e))?;
+        serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to parse providers.json: {}", e))?
+    } else {
+        vec![]
+    };
 
-    let providers: Vec<GeminiProviderConfig> = serde_json::from_str(&content)
-        .map_err(|e| format!("Failed to parse providers.json: {}", e))?;
+    // Append presets from the remote manifest; user presets keep their ids
+    let remote = provider_presets::remote_presets().await;
+    for preset in &remote.gemini {
+        if !providers.iter().any(|p| p.id == preset.id) {
+            providers.push(provider_presets::to_gemini_provider(preset));
+        }
+    }
 
     Ok(providers)
 }
 
 /// Get current Gemini configuration
 #[tauri::command]
-pub async fn get_current_gemini_provider_config() -> Result<CurrentGeminiProviderConfig, String> {
+pub async fn get_current_gemini_provider_config() -> Result<CurrentGeminiProviderConfig, AppError> {
     log::info!("[Gemini Provider] Getting current config");
 
     let env_path = get_gemini_env_path()?;
@@ -230,7 +246,7 @@ pub async fn get_current_gemini_provider_config() -> Result<CurrentGeminiProvide
 /// Switch to a Gemini provider configuration
 /// Supports both Native Windows and WSL modes
 #[tauri::command]
-pub async fn switch_gemini_provider(config: GeminiProviderConfig) -> Result<String, String> {
+pub async fn switch_gemini_provider(config: GeminiProviderConfig) -> Result<String, AppError> {
     log::info!("[Gemini Provider] Switching to provider: {}", config.name);
 
     // Check WSL mode
@@ -253,9 +269,6 @@ pub async fn switch_gemini_provider(config: GeminiProviderConfig) -> Result<Stri
             .map_err(|e| format!("Failed to create .gemini directory at {:?}: {}", gemini_dir, e))?;
     }
 
-    // Read existing settings to preserve mcpServers and other user configs
-    let mut settings = read_settings_file(&settings_path)?;
-
     // Determine if this is official (OAuth) or third-party (API Key)
     let is_official = config.is_official.unwrap_or(false)
         || config.category.as_deref() == Some("official")
@@ -268,28 +281,42 @@ pub async fn switch_gemini_provider(config: GeminiProviderConfig) -> Result<Stri
                 .get("GEMINI_API_KEY")
                 .map_or(true, |s| s.is_empty()));
 
-    if is_official {
+    let auth_type = if is_official {
         // Official (OAuth): Clear env and set auth type to oauth-personal
         log::info!("[Gemini Provider] Setting up for OAuth mode");
 
         // Clear .env (or write empty)
         write_env_file(&env_path, &HashMap::new())?;
 
-        // Set auth type to oauth-personal
-        set_auth_type_in_settings(&mut settings, "oauth-personal");
+        "oauth-personal"
     } else {
         // Third-party (API Key): Write env and set auth type to gemini-api-key
         log::info!("[Gemini Provider] Setting up for API Key mode");
 
-        // Write .env
-        write_env_file(&env_path, &config.env)?;
+        // Write .env (custom headers are passed to Gemini CLI via env)
+        let mut env = config.env.clone();
+        env.remove(GEMINI_CUSTOM_HEADERS_ENV);
+        if let Some(headers) = config.custom_headers.as_ref().filter(|h| !h.is_empty()) {
+            validate_custom_headers(headers)?;
+            log::info!(
+                "[Gemini Provider] Applying custom headers: {:?}",
+                masked_headers(headers)
+            );
+            env.insert(
+                GEMINI_CUSTOM_HEADERS_ENV.to_string(),
+                encode_custom_headers(headers)?,
+            );
+        }
+        write_env_file(&env_path, &env)?;
 
-        // Set auth type to gemini-api-key
-        set_auth_type_in_settings(&mut settings, "gemini-api-key");
-    }
+        "gemini-api-key"
+    };
 
-    // Write settings.json
-    write_settings_file(&settings_path, &settings)?;
+    // Only the auth type is updated; mcpServers and other user configs are preserved
+    update_settings_file(&settings_path, |settings| {
+        set_auth_type_in_settings(settings, auth_type);
+        Ok(())
+    })?;
 
     log::info!(
         "[Gemini Provider] Successfully switched to: {}",
@@ -298,14 +325,26 @@ pub async fn switch_gemini_provider(config: GeminiProviderConfig) -> Result<Stri
 
     // Return success message with mode info
     let mode_info = if is_wsl_mode { " (WSL)" } else { "" };
-    Ok(format!("成功切换到 Gemini 供应商: {}{}", config.name, mode_info))
+    Ok(t_with(
+        MessageKey::ProviderEngineSwitched,
+        &[
+            ("engine", &"Gemini"),
+            ("name", &config.name),
+            ("mode", &mode_info),
+        ],
+    ))
 }
 
 /// Add a new Gemini provider configuration
 #[tauri::command]
-pub async fn add_gemini_provider_config(config: GeminiProviderConfig) -> Result<String, String> {
+pub async fn add_gemini_provider_config(config: GeminiProviderConfig) -> Result<String, AppError> {
     log::info!("[Gemini Provider] Adding provider: {}", config.name);
 
+    if let Some(headers) = &config.custom_headers {
+        validate_custom_headers(headers)?;
+        encode_custom_headers(headers)?;
+    }
+
     let providers_path = get_gemini_providers_path()?;
 
     // Ensure parent directory exists
@@ -326,7 +365,7 @@ pub async fn add_gemini_provider_config(config: GeminiProviderConfig) -> Result<
 
     // Check for duplicate ID
     if providers.iter().any(|p| p.id == config.id) {
-        return Err(format!("Provider with ID '{}' already exists", config.id));
+        return Err(t_with(MessageKey::ProviderIdExists, &[("id", &config.id)]).into());
     }
 
     providers.push(config.clone());
@@ -341,18 +380,28 @@ pub async fn add_gemini_provider_config(config: GeminiProviderConfig) -> Result<
         "[Gemini Provider] Successfully added provider: {}",
         config.name
     );
-    Ok(format!("成功添加 Gemini 供应商: {}", config.name))
+    Ok(t_with(
+        MessageKey::ProviderEngineAdded,
+        &[("engine", &"Gemini"), ("name", &config.name)],
+    ))
 }
 
 /// Update an existing Gemini provider configuration
 #[tauri::command]
-pub async fn update_gemini_provider_config(config: GeminiProviderConfig) -> Result<String, String> {
+pub async fn update_gemini_provider_config(
+    config: GeminiProviderConfig,
+) -> Result<String, AppError> {
     log::info!("[Gemini Provider] Updating provider: {}", config.name);
 
+    if let Some(headers) = &config.custom_headers {
+        validate_custom_headers(headers)?;
+        encode_custom_headers(headers)?;
+    }
+
     let providers_path = get_gemini_providers_path()?;
 
     if !providers_path.exists() {
-        return Err(format!("Provider with ID '{}' not found", config.id));
+        return Err(t_with(MessageKey::ProviderNotFound, &[("id", &config.id)]).into());
     }
 
     let content = fs::read_to_string(&providers_path)
@@ -364,7 +413,7 @@ pub async fn update_gemini_provider_config(config: GeminiProviderConfig) -> Resu
     let index = providers
         .iter()
         .position(|p| p.id == config.id)
-        .ok_or_else(|| format!("Provider with ID '{}' not found", config.id))?;
+        .ok_or_else(|| t_with(MessageKey::ProviderNotFound, &[("id", &config.id)]))?;
 
     providers[index] = config.clone();
 
@@ -378,18 +427,21 @@ pub async fn update_gemini_provider_config(config: GeminiProviderConfig) -> Resu
         "[Gemini Provider] Successfully updated provider: {}",
         config.name
     );
-    Ok(format!("成功更新 Gemini 供应商: {}", config.name))
+    Ok(t_with(
+        MessageKey::ProviderEngineUpdated,
+        &[("engine", &"Gemini"), ("name", &config.name)],
+    ))
 }
 
 /// Delete a Gemini provider configuration
 #[tauri::command]
-pub async fn delete_gemini_provider_config(id: String) -> Result<String, String> {
+pub async fn delete_gemini_provider_config(id: String) -> Result<String, AppError> {
     log::info!("[Gemini Provider] Deleting provider: {}", id);
 
     let providers_path = get_gemini_providers_path()?;
 
     if !providers_path.exists() {
-        return Err(format!("Provider with ID '{}' not found", id));
+        return Err(t_with(MessageKey::ProviderNotFound, &[("id", &id)]).into());
     }
 
     let content = fs::read_to_string(&providers_path)
@@ -402,7 +454,7 @@ pub async fn delete_gemini_provider_config(id: String) -> Result<String, String>
     providers.retain(|p| p.id != id);
 
     if providers.len() == initial_len {
-        return Err(format!("Provider with ID '{}' not found", id));
+        return Err(t_with(MessageKey::ProviderNotFound, &[("id", &id)]).into());
     }
 
     // Save providers
@@ -412,12 +464,15 @@ pub async fn delete_gemini_provider_config(id: String) -> Result<String, String>
         .map_err(|e| format!("Failed to write providers.json: {}", e))?;
 
     log::info!("[Gemini Provider] Successfully deleted provider: {}", id);
-    Ok(format!("成功删除 Gemini 供应商: {}", id))
+    Ok(t_with(
+        MessageKey::ProviderEngineDeleted,
+        &[("engine", &"Gemini"), ("id", &id)],
+    ))
 }
 
 /// Reorder Gemini provider configurations
 #[tauri::command]
-pub async fn reorder_gemini_provider_configs(ids: Vec<String>) -> Result<String, String> {
+pub async fn reorder_gemini_provider_configs(ids: Vec<String>) -> Result<String, AppError> {
     log::info!("[Gemini Provider] Reordering providers");
 
     let providers_path = get_gemini_providers_path()?;
@@ -453,12 +508,15 @@ pub async fn reorder_gemini_provider_configs(ids: Vec<String>) -> Result<String,
         .map_err(|e| format!("Failed to write providers.json: {}", e))?;
 
     log::info!("[Gemini Provider] Successfully reordered providers");
-    Ok("成功重新排序 Gemini 供应商".to_string())
+    Ok(t_with(
+        MessageKey::ProviderEngineReordered,
+        &[("engine", &"Gemini")],
+    ))
 }
 
 /// Clear Gemini provider configuration (reset to official OAuth)
 #[tauri::command]
-pub async fn clear_gemini_provider_config() -> Result<String, String> {
+pub async fn clear_gemini_provider_config() -> Result<String, AppError> {
     log::info!("[Gemini Provider] Clearing config");
 
     let env_path = get_gemini_env_path()?;
@@ -468,46 +526,314 @@ pub async fn clear_gemini_provider_config() -> Result<String, String> {
     write_env_file(&env_path, &HashMap::new())?;
 
     // Reset auth type to OAuth in settings
-    let mut settings = read_settings_file(&settings_path)?;
-    set_auth_type_in_settings(&mut settings, "oauth-personal");
-    write_settings_file(&settings_path, &settings)?;
+    update_settings_file(&settings_path, |settings| {
+        set_auth_type_in_settings(settings, "oauth-personal");
+        Ok(())
+    })?;
 
     log::info!("[Gemini Provider] Successfully cleared config");
-    Ok("成功清理 Gemini 配置，已切换回官方 OAuth 模式".to_string())
+    Ok(t(MessageKey::ProviderGeminiCleared))
 }
 
-/// Test Gemini provider connection
-#[tauri::command]
-pub async fn test_gemini_provider_connection(
-    base_url: String,
-    api_key: Option<String>,
-) -> Result<String, String> {
-    log::info!("[Gemini Provider] Testing connection to: {}", base_url);
-
-    // Simple connectivity test
-    let client = reqwest::Client::builder()
-        .timeout(std::time::Duration::from_secs(10))
-        .build()
-        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+/// Default Gemini API endpoint used when the provider has no base URL
+const DEFAULT_GEMINI_BASE_URL: &str = "https://generativelanguage.googleapis.com";
+
+/// Default Vertex AI location when GOOGLE_CLOUD_LOCATION is not set
+const DEFAULT_VERTEX_LOCATION: &str = "us-central1";
+
+/// HTTP timeout for a single connection test request
+const PROVIDER_TEST_REQUEST_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// Upper bound per provider in `test_all_gemini_providers`, including rate limiter waits
+const PROVIDER_TEST_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(15);
+
+/// Result of a provider connection test
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderTestResult {
+    pub success: bool,
+    /// Round-trip time of the models request in milliseconds
+    pub latency_ms: u64,
+    /// Model ids reported by the provider; empty when listing isn't supported
+    pub available_models: Vec<String>,
+    pub error: Option<String>,
+}
+
+impl ProviderTestResult {
+    fn failed(latency_ms: u64, error: String) -> Self {
+        Self {
+            success: false,
+            latency_ms,
+            available_models: Vec::new(),
+            error: Some(error),
+        }
+    }
+}
+
+/// Where the models listing request of a provider goes
+#[derive(Debug, PartialEq)]
+struct ModelsEndpoint {
+    url: String,
+    /// Key for rate limiting, shared with the CLI requests of the same provider
+    rate_limit_key: String,
+}
+
+fn env_value<'a>(env: &'a HashMap<String, String>, key: &str) -> Option<&'a str> {
+    env.get(key)
+        .map(|value| value.trim())
+        .filter(|value| !value.is_empty())
+}
+
+fn uses_vertex_ai(env: &HashMap<String, String>) -> bool {
+    env_value(env, "GOOGLE_GENAI_USE_VERTEXAI")
+        .map(|value| value.eq_ignore_ascii_case("true") || value == "1")
+        .unwrap_or(false)
+}
+
+/// Resolves the models endpoint for Vertex AI or the Gemini API (incl. gateways)
+fn models_endpoint(env: &HashMap<String, String>) -> ModelsEndpoint {
+    if uses_vertex_ai(env) {
+        let location = env_value(env, "GOOGLE_CLOUD_LOCATION").unwrap_or(DEFAULT_VERTEX_LOCATION);
+        let host = if location == "global" {
+            "https://aiplatform.googleapis.com".to_string()
+        } else {
+            format!("https://{}-aiplatform.googleapis.com", location)
+        };
+        return ModelsEndpoint {
+            url: format!("{}/v1beta1/publishers/google/models", host),
+            rate_limit_key: host,
+        };
+    }
+
+    let base = env_value(env, "GOOGLE_GEMINI_BASE_URL")
+        .unwrap_or(DEFAULT_GEMINI_BASE_URL)
+        .trim_end_matches('/');
+    // The Gemini SDK appends the API version itself, gateways sometimes include it
+    let has_version = base
+        .rsplit('/')
+        .next()
+        .map(|segment| segment.starts_with("v1"))
+        .unwrap_or(false);
+    let url = if has_version {
+        format!("{}/models", base)
+    } else {
+        format!("{}/v1beta/models", base)
+    };
+    ModelsEndpoint {
+        url,
+        rate_limit_key: base.to_string(),
+    }
+}
 
-    let test_url = format!("{}/models", base_url.trim_end_matches('/'));
+/// Extracts model ids from a Gemini, Vertex AI or OpenAI-compatible models response
+fn parse_model_names(body: &serde_json::Value) -> Vec<String> {
+    let mut models: Vec<String> = ["models", "publisherModels", "data"]
+        .iter()
+        .filter_map(|key| body.get(*key).and_then(|list| list.as_array()))
+        .flatten()
+        .filter_map(|model| {
+            model
+                .get("name")
+                .or_else(|| model.get("id"))
+                .and_then(|name| name.as_str())
+        })
+        // "models/gemini-2.5-pro", "publishers/google/models/gemini-2.5-pro"
+        .filter_map(|name| name.rsplit('/').next())
+        .filter(|name| !name.is_empty())
+        .map(String::from)
+        .collect();
+    models.sort();
+    models.dedup();
+    models
+}
+
+/// Runs the connection test of one provider: times the models request and
+/// lists the models when the endpoint supports it
+async fn run_provider_test(config: &GeminiProviderConfig) -> ProviderTestResult {
+    let endpoint = models_endpoint(&config.env);
+    let api_key = env_value(&config.env, "GEMINI_API_KEY")
+        .or_else(|| env_value(&config.env, "GOOGLE_API_KEY"));
+    log::info!(
+        "[Gemini Provider] Testing connection of {} via {}",
+        config.id,
+        endpoint.url
+    );
 
-    let mut request = client.get(&test_url);
+    let client = match reqwest::Client::builder()
+        .timeout(PROVIDER_TEST_REQUEST_TIMEOUT)
+        .build()
+    {
+        Ok(client) => client,
+        Err(e) => {
+            return ProviderTestResult::failed(0, format!("Failed to create HTTP client: {}", e))
+        }
+    };
 
+    let mut request = client.get(&endpoint.url);
     if let Some(key) = api_key {
-        // Gemini API uses x-goog-api-key header
+        // Gemini API and Vertex AI express mode both accept x-goog-api-key
         request = request.header("x-goog-api-key", key);
     }
+    let request = match apply_custom_headers(request, config.custom_headers.as_ref()) {
+        Ok(request) => request,
+        Err(e) => return ProviderTestResult::failed(0, e),
+    };
 
-    match request.send().await {
-        Ok(response) => {
-            let status = response.status();
-            if status.is_success() || status.as_u16() == 401 {
-                Ok(format!("连接测试成功: 端点可达 (状态: {})", status))
-            } else {
-                Ok(format!("连接测试完成，状态: {}", status))
-            }
+    let started = std::time::Instant::now();
+    let response = rate_limiter::send(&endpoint.rate_limit_key, request).await;
+    let latency_ms = started.elapsed().as_millis() as u64;
+
+    let response = match response {
+        Ok(response) => response,
+        Err(e) => {
+            return ProviderTestResult::failed(
+                latency_ms,
+                t_with(MessageKey::ProviderConnectionFailed, &[("error", &e)]),
+            )
         }
-        Err(e) => Err(format!("连接测试失败: {}", e)),
+    };
+
+    let status = response.status();
+    let reachable = |available_models| ProviderTestResult {
+        success: true,
+        latency_ms,
+        available_models,
+        error: None,
+    };
+    match status.as_u16() {
+        200..=299 => {
+            // A reachable endpoint with an unexpected body still passes, just without models
+            let models = response
+                .json::<serde_json::Value>()
+                .await
+                .map(|body| parse_model_names(&body))
+                .unwrap_or_default();
+            reachable(models)
+        }
+        // Listing isn't supported by the provider
+        404 | 405 | 501 => reachable(Vec::new()),
+        // Without an API key (OAuth / ADC auth) the listing can't be authorized here
+        401 | 403 if api_key.is_none() => reachable(Vec::new()),
+        401 | 403 => ProviderTestResult::failed(
+            latency_ms,
+            t_with(
+                MessageKey::ProviderCredentialsRejected,
+                &[("status", &status)],
+            ),
+        ),
+        _ => ProviderTestResult::failed(
+            latency_ms,
+            t_with(MessageKey::ProviderConnectionStatus, &[("status", &status)]),
+        ),
+    }
+}
+
+/// Test Gemini provider connection
+#[tauri::command]
+pub async fn test_gemini_provider_connection(
+    config: GeminiProviderConfig,
+) -> Result<ProviderTestResult, AppError> {
+    Ok(run_provider_test(&config).await)
+}
+
+/// Test every configured Gemini provider concurrently, keyed by provider id
+#[tauri::command]
+pub async fn test_all_gemini_providers() -> Result<HashMap<String, ProviderTestResult>, AppError> {
+    let providers_path = get_gemini_providers_path()?;
+    let providers: Vec<GeminiProviderConfig> = if providers_path.exists() {
+        let content = fs::read_to_string(&providers_path)
+            .map_err(|e| format!("Failed to read providers.json: {}", e))?;
+        serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to parse providers.json: {}", e))?
+    } else {
+        vec![]
+    };
+    log::info!(
+        "[Gemini Provider] Testing {} configured providers",
+        providers.len()
+    );
+
+    let results = futures::future::join_all(providers.iter().map(|config| async move {
+        let result =
+            match tokio::time::timeout(PROVIDER_TEST_TIMEOUT, run_provider_test(config)).await {
+                Ok(result) => result,
+                Err(_) => ProviderTestResult::failed(
+                    PROVIDER_TEST_TIMEOUT.as_millis() as u64,
+                    t(MessageKey::ErrorNetworkTimeout),
+                ),
+            };
+        (config.id.clone(), result)
+    }))
+    .await;
+
+    Ok(results.into_iter().collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn models_url(pairs: &[(&str, &str)]) -> String {
+        let env = pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        models_endpoint(&env).url
+    }
+
+    #[test]
+    fn models_endpoint_covers_api_key_and_vertex_auth() {
+        assert_eq!(
+            models_url(&[]),
+            "https://generativelanguage.googleapis.com/v1beta/models"
+        );
+        let gateway = "GOOGLE_GEMINI_BASE_URL";
+        assert_eq!(
+            models_url(&[(gateway, "https://gw.example.com/")]),
+            "https://gw.example.com/v1beta/models"
+        );
+        assert_eq!(
+            models_url(&[(gateway, "https://gw.example.com/v1")]),
+            "https://gw.example.com/v1/models"
+        );
+
+        // Vertex AI ignores the Gemini API base URL
+        assert_eq!(
+            models_url(&[
+                ("GOOGLE_GENAI_USE_VERTEXAI", "true"),
+                (gateway, "https://gw.example.com"),
+            ]),
+            "https://us-central1-aiplatform.googleapis.com/v1beta1/publishers/google/models"
+        );
+        assert_eq!(
+            models_url(&[
+                ("GOOGLE_GENAI_USE_VERTEXAI", "1"),
+                ("GOOGLE_CLOUD_LOCATION", "global"),
+            ]),
+            "https://aiplatform.googleapis.com/v1beta1/publishers/google/models"
+        );
+    }
+
+    #[test]
+    fn parse_model_names_handles_each_listing_format() {
+        let gemini = serde_json::json!({
+            "models": [{"name": "models/gemini-2.5-pro"}, {"name": "models/gemini-2.5-flash"}]
+        });
+        assert_eq!(
+            parse_model_names(&gemini),
+            vec!["gemini-2.5-flash", "gemini-2.5-pro"]
+        );
+
+        let vertex = serde_json::json!({
+            "publisherModels": [{"name": "publishers/google/models/gemini-2.5-pro"}]
+        });
+        assert_eq!(parse_model_names(&vertex), vec!["gemini-2.5-pro"]);
+
+        let openai = serde_json::json!({
+            "data": [{"id": "gemini-2.5-pro"}, {"id": "gemini-2.5-pro"}]
+        });
+        assert_eq!(parse_model_names(&openai), vec!["gemini-2.5-pro"]);
+
+        assert!(parse_model_names(&serde_json::json!({"error": "nope"})).is_empty());
     }
 }
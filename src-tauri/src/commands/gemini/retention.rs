@@ -0,0 +1,887 @@
+//! Gemini 会话自动清理
+//!
+//! Gemini CLI 从不清理 ~/.gemini/tmp/<hash>/chats 下的会话文件。这里按 GeminiConfig 中的
+//! 保留策略挑出过期会话，移入 ~/.anycode/trash/gemini（保持与 ~/.gemini 相同的目录结构，
+//! 便于手动恢复），并一并移走对应的 git-records。被收藏/打标签/只读标记的会话以及正在运行中
+//! 任务使用的会话不会被清理；每次实际清理都会追加一条审计记录。
+//!
+//! 开启加密存储后移入回收站的文件会被加密；每次清理目录下另存一份不含内容的明文
+//! index.json，用于列出回收站与还原。
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::{BufReader, Write};
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager};
+
+use super::config::{
+    get_gemini_dir, hash_project_path, load_gemini_config, GeminiSessionRetention,
+};
+use super::types::GeminiProcessState;
+use crate::commands::encrypted_storage::{self, TRASH_INDEX_FILE};
+use crate::utils::config_utils::{load_json_config, save_json_config, ConfigPathBuilder};
+use crate::utils::timestamp::parse_timestamp;
+
+// ============================================================================
+// Session Marks (favorite / tags / read-only)
+// ============================================================================
+
+/// User marks on a Gemini session; marked sessions are never cleaned up
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GeminiSessionMark {
+    #[serde(default)]
+    pub favorite: bool,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub read_only: bool,
+}
+
+impl GeminiSessionMark {
+    fn is_protected(&self) -> bool {
+        self.favorite || self.read_only || !self.tags.is_empty()
+    }
+}
+
+fn get_session_marks_path() -> Result<PathBuf, String> {
+    Ok(ConfigPathBuilder::from_home_subdir(".anycode")?.build("gemini-session-marks.json"))
+}
+
+fn load_session_marks() -> Result<HashMap<String, GeminiSessionMark>, String> {
+    load_json_config(&get_session_marks_path()?)
+}
+
+/// Get marks of all Gemini sessions, keyed by session ID
+#[tauri::command]
+pub async fn get_gemini_session_marks() -> Result<HashMap<String, GeminiSessionMark>, String> {
+    load_session_marks()
+}
+
+/// Set the marks of a Gemini session (an empty mark removes the entry)
+#[tauri::command]
+pub async fn set_gemini_session_mark(
+    session_id: String,
+    mark: GeminiSessionMark,
+) -> Result<(), String> {
+    let mut marks = load_session_marks()?;
+    if mark.is_protected() {
+        marks.insert(session_id, mark);
+    } else {
+        marks.remove(&session_id);
+    }
+    save_json_config(&marks, &get_session_marks_path()?)
+}
+
+// ============================================================================
+// Cleanup Types
+// ============================================================================
+
+/// A session selected for cleanup
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GeminiCleanupEntry {
+    pub session_id: String,
+    pub project_hash: String,
+    pub file_name: String,
+    /// Last activity, UTC Unix seconds
+    pub last_active: i64,
+    /// Size of the session file plus its git records
+    pub size_bytes: u64,
+    pub has_git_records: bool,
+}
+
+/// Result of a cleanup run (or the preview of one when `dry_run` is set)
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GeminiCleanupReport {
+    pub dry_run: bool,
+    pub sessions: Vec<GeminiCleanupEntry>,
+    pub freed_bytes: u64,
+    /// Expired sessions kept because they are favorited/tagged/read-only
+    pub skipped_protected: usize,
+    /// Expired sessions kept because a running task uses them
+    pub skipped_running: usize,
+    pub trash_dir: Option<String>,
+    pub errors: Vec<String>,
+}
+
+/// A session in the trash, read from the plaintext index of its cleanup run
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GeminiTrashEntry {
+    #[serde(flatten)]
+    pub entry: GeminiCleanupEntry,
+    /// Directory of the cleanup run that moved the session
+    pub trash_dir: String,
+    /// Whether the session file is encrypted (restoring needs the storage unlocked)
+    pub encrypted: bool,
+}
+
+/// One line of ~/.anycode/audit/gemini-session-cleanup.jsonl
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CleanupAuditRecord<'a> {
+    timestamp: i64,
+    trigger: &'a str,
+    retention: &'a GeminiSessionRetention,
+    sessions: Vec<&'a str>,
+    freed_bytes: u64,
+    trash_dir: Option<&'a str>,
+    errors: &'a [String],
+}
+
+/// Sessions used by running Gemini tasks
+#[derive(Debug, Default)]
+struct RunningSessions {
+    session_ids: HashSet<String>,
+    /// Projects with a task whose CLI session ID is not known yet
+    project_hashes: HashSet<String>,
+}
+
+/// Only the header fields are read; `messages` is skipped
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SessionHeader {
+    session_id: String,
+    #[serde(default)]
+    start_time: String,
+    #[serde(default)]
+    last_updated: String,
+}
+
+#[derive(Debug)]
+struct SessionFile {
+    session_id: String,
+    project_hash: String,
+    path: PathBuf,
+    last_active: DateTime<Utc>,
+    size_bytes: u64,
+}
+
+// ============================================================================
+// Selection
+// ============================================================================
+
+/// Scan ~/.gemini/tmp/*/chats/*.json; unreadable files are left alone
+fn scan_session_files(gemini_dir: &Path) -> Vec<SessionFile> {
+    let mut sessions = Vec::new();
+    let Ok(projects) = fs::read_dir(gemini_dir.join("tmp")) else {
+        return sessions;
+    };
+
+    for project in projects.flatten() {
+        let project_hash = project.file_name().to_string_lossy().to_string();
+        let Ok(chats) = fs::read_dir(project.path().join("chats")) else {
+            continue;
+        };
+
+        for entry in chats.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|s| s.to_str()) != Some("json") {
+                continue;
+            }
+            let Ok(metadata) = entry.metadata() else {
+                continue;
+            };
+            let header = fs::File::open(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|file| {
+                    serde_json::from_reader::<_, SessionHeader>(BufReader::new(file))
+                        .map_err(|e| e.to_string())
+                });
+            let header = match header {
+                Ok(header) => header,
+                Err(e) => {
+                    log::debug!(
+                        "[Gemini Cleanup] Skipping unreadable session {:?}: {}",
+                        path,
+                        e
+                    );
+                    continue;
+                }
+            };
+
+            let last_active = parse_timestamp(&header.last_updated)
+                .or_else(|| parse_timestamp(&header.start_time))
+                .or_else(|| metadata.modified().ok().map(DateTime::<Utc>::from))
+                .unwrap_or_else(Utc::now);
+
+            sessions.push(SessionFile {
+                session_id: header.session_id,
+                project_hash: project_hash.clone(),
+                path,
+                last_active,
+                size_bytes: metadata.len(),
+            });
+        }
+    }
+
+    sessions
+}
+
+/// Pick the sessions that no retention rule keeps
+///
+/// A session is kept if it was active within `retention_days` or is among the
+/// `max_sessions` most recent sessions of its project.
+fn select_expired(
+    sessions: Vec<SessionFile>,
+    retention: &GeminiSessionRetention,
+    now: DateTime<Utc>,
+) -> Vec<SessionFile> {
+    if retention.retention_days.is_none() && retention.max_sessions.is_none() {
+        return Vec::new();
+    }
+    let cutoff = retention
+        .retention_days
+        .map(|days| now - Duration::days(i64::from(days)));
+
+    let mut by_project: HashMap<String, Vec<SessionFile>> = HashMap::new();
+    for session in sessions {
+        by_project
+            .entry(session.project_hash.clone())
+            .or_default()
+            .push(session);
+    }
+
+    let mut expired = Vec::new();
+    for (_, mut project_sessions) in by_project {
+        project_sessions.sort_by(|a, b| b.last_active.cmp(&a.last_active));
+        for (rank, session) in project_sessions.into_iter().enumerate() {
+            let kept_by_age = cutoff.is_some_and(|cutoff| session.last_active >= cutoff);
+            let kept_by_count = retention
+                .max_sessions
+                .is_some_and(|max| rank < max as usize);
+            if !kept_by_age && !kept_by_count {
+                expired.push(session);
+            }
+        }
+    }
+
+    expired.sort_by(|a, b| a.last_active.cmp(&b.last_active));
+    expired
+}
+
+/// Collect cleanup candidates, excluding protected and in-use sessions
+///
+/// Returns (candidates, skipped_protected, skipped_running).
+fn collect_candidates(
+    gemini_dir: &Path,
+    retention: &GeminiSessionRetention,
+    marks: &HashMap<String, GeminiSessionMark>,
+    running: &RunningSessions,
+    now: DateTime<Utc>,
+) -> (Vec<SessionFile>, usize, usize) {
+    let mut skipped_protected = 0;
+    let mut skipped_running = 0;
+
+    // Marked sessions do not take up retention slots
+    let sessions = scan_session_files(gemini_dir)
+        .into_iter()
+        .filter(|session| {
+            let protected = marks
+                .get(&session.session_id)
+                .is_some_and(GeminiSessionMark::is_protected);
+            if protected {
+                skipped_protected += 1;
+            }
+            !protected
+        })
+        .collect();
+
+    let candidates = select_expired(sessions, retention, now)
+        .into_iter()
+        .filter(|session| {
+            let in_use = running.session_ids.contains(&session.session_id)
+                || running.project_hashes.contains(&session.project_hash);
+            if in_use {
+                skipped_running += 1;
+            }
+            !in_use
+        })
+        .collect();
+
+    (candidates, skipped_protected, skipped_running)
+}
+
+// ============================================================================
+// Trash
+// ============================================================================
+
+/// Move a file, falling back to copy + delete across file systems (e.g. WSL UNC paths)
+fn move_file(src: &Path, dest: &Path) -> Result<(), String> {
+    if let Some(parent) = dest.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create trash directory: {}", e))?;
+    }
+    if fs::rename(src, dest).is_ok() {
+        return Ok(());
+    }
+    fs::copy(src, dest).map_err(|e| format!("Failed to copy {:?} to trash: {}", src, e))?;
+    fs::remove_file(src).map_err(|e| format!("Failed to remove {:?}: {}", src, e))
+}
+
+/// Move a file into the trash, encrypting it when encrypted storage is enabled
+fn move_to_trash(src: &Path, dest: &Path) -> Result<(), String> {
+    if !encrypted_storage::encryption_enabled() {
+        return move_file(src, dest);
+    }
+    let content = fs::read(src).map_err(|e| format!("Failed to read {:?}: {}", src, e))?;
+    encrypted_storage::write_protected(dest, &content)?;
+    fs::remove_file(src).map_err(|e| format!("Failed to remove {:?}: {}", src, e))
+}
+
+fn git_records_path(gemini_dir: &Path, session_id: &str) -> PathBuf {
+    gemini_dir
+        .join("git-records")
+        .join(format!("{}.json", session_id))
+}
+
+fn to_entry(gemini_dir: &Path, session: &SessionFile) -> GeminiCleanupEntry {
+    let git_records_size = fs::metadata(git_records_path(gemini_dir, &session.session_id))
+        .ok()
+        .map(|m| m.len());
+    GeminiCleanupEntry {
+        session_id: session.session_id.clone(),
+        project_hash: session.project_hash.clone(),
+        file_name: session
+            .path
+            .file_name()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_default(),
+        last_active: session.last_active.timestamp(),
+        size_bytes: session.size_bytes + git_records_size.unwrap_or(0),
+        has_git_records: git_records_size.is_some(),
+    }
+}
+
+/// Move sessions and their git records into `trash_dir`, mirroring the ~/.gemini layout
+fn move_sessions_to_trash(
+    gemini_dir: &Path,
+    entries: Vec<(GeminiCleanupEntry, PathBuf)>,
+    trash_dir: &Path,
+) -> (Vec<GeminiCleanupEntry>, Vec<String>) {
+    let mut moved = Vec::new();
+    let mut errors = Vec::new();
+
+    for (entry, path) in entries {
+        let dest = trash_dir
+            .join("tmp")
+            .join(&entry.project_hash)
+            .join("chats")
+            .join(&entry.file_name);
+        if let Err(e) = move_to_trash(&path, &dest) {
+            errors.push(format!("{}: {}", entry.session_id, e));
+            continue;
+        }
+
+        if entry.has_git_records {
+            let records = git_records_path(gemini_dir, &entry.session_id);
+            let records_dest = trash_dir
+                .join("git-records")
+                .join(format!("{}.json", entry.session_id));
+            if let Err(e) = move_to_trash(&records, &records_dest) {
+                errors.push(format!("{} (git records): {}", entry.session_id, e));
+            }
+        }
+
+        moved.push(entry);
+    }
+
+    (moved, errors)
+}
+
+fn trash_session_path(trash_dir: &Path, entry: &GeminiCleanupEntry) -> PathBuf {
+    trash_dir
+        .join("tmp")
+        .join(&entry.project_hash)
+        .join("chats")
+        .join(&entry.file_name)
+}
+
+fn load_trash_index(trash_dir: &Path) -> Result<Vec<GeminiCleanupEntry>, String> {
+    let content = fs::read_to_string(trash_dir.join(TRASH_INDEX_FILE))
+        .map_err(|e| format!("Failed to read trash index: {}", e))?;
+    serde_json::from_str(&content).map_err(|e| format!("Failed to parse trash index: {}", e))
+}
+
+/// Write the plaintext index of a cleanup run; it holds metadata only, never content
+fn save_trash_index(trash_dir: &Path, entries: &[GeminiCleanupEntry]) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(entries)
+        .map_err(|e| format!("Failed to serialize trash index: {}", e))?;
+    fs::write(trash_dir.join(TRASH_INDEX_FILE), content)
+        .map_err(|e| format!("Failed to write trash index: {}", e))
+}
+
+fn trash_run_dirs(trash_root: &Path) -> Vec<PathBuf> {
+    let Ok(runs) = fs::read_dir(trash_root.join("gemini")) else {
+        return Vec::new();
+    };
+    let mut dirs: Vec<PathBuf> = runs
+        .flatten()
+        .map(|run| run.path())
+        .filter(|path| path.is_dir())
+        .collect();
+    dirs.sort();
+    dirs
+}
+
+/// Create the index of cleanup runs from before indexes existed, from their plaintext files
+///
+/// Must run before those files get encrypted, otherwise their headers can no longer be read.
+pub fn ensure_trash_indexes(trash_root: &Path) {
+    for run_dir in trash_run_dirs(trash_root) {
+        if run_dir.join(TRASH_INDEX_FILE).exists() {
+            continue;
+        }
+        let entries: Vec<GeminiCleanupEntry> = scan_session_files(&run_dir)
+            .iter()
+            .map(|session| to_entry(&run_dir, session))
+            .collect();
+        if let Err(e) = save_trash_index(&run_dir, &entries) {
+            log::warn!("[Gemini Cleanup] Failed to index {:?}: {}", run_dir, e);
+        }
+    }
+}
+
+fn list_trash(trash_root: &Path) -> Vec<GeminiTrashEntry> {
+    ensure_trash_indexes(trash_root);
+    let mut sessions = Vec::new();
+    for run_dir in trash_run_dirs(trash_root) {
+        let entries = match load_trash_index(&run_dir) {
+            Ok(entries) => entries,
+            Err(e) => {
+                log::warn!("[Gemini Cleanup] Skipping {:?}: {}", run_dir, e);
+                continue;
+            }
+        };
+        for entry in entries {
+            let encrypted =
+                encrypted_storage::encrypted_path(&trash_session_path(&run_dir, &entry)).exists();
+            sessions.push(GeminiTrashEntry {
+                entry,
+                trash_dir: run_dir.to_string_lossy().to_string(),
+                encrypted,
+            });
+        }
+    }
+    sessions.sort_by(|a, b| b.entry.last_active.cmp(&a.entry.last_active));
+    sessions
+}
+
+/// Whether a session was moved to the trash by a cleanup and not restored since
+pub(crate) fn is_session_in_trash(session_id: &str) -> bool {
+    get_trash_root()
+        .map(|root| {
+            list_trash(&root)
+                .iter()
+                .any(|trashed| trashed.entry.session_id == session_id)
+        })
+        .unwrap_or(false)
+}
+
+/// Move a session (and its git records) from a cleanup run back into ~/.gemini
+///
+/// Encrypted files are decrypted on the way; an existing session file is never overwritten.
+fn restore_from_trash(
+    gemini_dir: &Path,
+    trash_dir: &Path,
+    session_id: &str,
+) -> Result<GeminiCleanupEntry, String> {
+    let mut entries = load_trash_index(trash_dir)?;
+    let index = entries
+        .iter()
+        .position(|e| e.session_id == session_id)
+        .ok_or_else(|| format!("Session {} not found in trash", session_id))?;
+    let entry = entries[index].clone();
+
+    let source = trash_session_path(trash_dir, &entry);
+    let dest = trash_session_path(gemini_dir, &entry);
+    if dest.exists() {
+        return Err(format!("Session file already exists: {:?}", dest));
+    }
+    let content = encrypted_storage::read_protected(&source)?;
+    let records_source = git_records_path(trash_dir, session_id);
+    let has_records =
+        encrypted_storage::encrypted_path(&records_source).exists() || records_source.exists();
+    let records = if entry.has_git_records && has_records {
+        Some(encrypted_storage::read_protected(&records_source)?)
+    } else {
+        None
+    };
+
+    if let Some(parent) = dest.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create chats directory: {}", e))?;
+    }
+    fs::write(&dest, content).map_err(|e| format!("Failed to restore session: {}", e))?;
+    if let Some(records) = records {
+        let records_dest = git_records_path(gemini_dir, session_id);
+        if let Some(parent) = records_dest.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create git-records directory: {}", e))?;
+        }
+        fs::write(&records_dest, records)
+            .map_err(|e| format!("Failed to restore git records: {}", e))?;
+        encrypted_storage::remove_protected(&records_source)?;
+    }
+    encrypted_storage::remove_protected(&source)?;
+
+    entries.remove(index);
+    save_trash_index(trash_dir, &entries)?;
+    Ok(entry)
+}
+
+fn get_trash_root() -> Result<PathBuf, String> {
+    Ok(ConfigPathBuilder::from_home_subdir(".anycode")?.build("trash"))
+}
+
+fn append_audit_record(
+    trigger: &str,
+    retention: &GeminiSessionRetention,
+    report: &GeminiCleanupReport,
+) -> Result<(), String> {
+    let audit_dir = ConfigPathBuilder::from_home_subdir(".anycode")?.build("audit");
+    fs::create_dir_all(&audit_dir)
+        .map_err(|e| format!("Failed to create audit directory: {}", e))?;
+
+    let record = CleanupAuditRecord {
+        timestamp: Utc::now().timestamp(),
+        trigger,
+        retention,
+        sessions: report
+            .sessions
+            .iter()
+            .map(|s| s.session_id.as_str())
+            .collect(),
+        freed_bytes: report.freed_bytes,
+        trash_dir: report.trash_dir.as_deref(),
+        errors: &report.errors,
+    };
+    let line = serde_json::to_string(&record)
+        .map_err(|e| format!("Failed to serialize audit record: {}", e))?;
+
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(audit_dir.join("gemini-session-cleanup.jsonl"))
+        .map_err(|e| format!("Failed to open audit log: {}", e))?;
+    writeln!(file, "{}", line).map_err(|e| format!("Failed to write audit log: {}", e))
+}
+
+// ============================================================================
+// Cleanup
+// ============================================================================
+
+async fn running_sessions(app_handle: &AppHandle) -> RunningSessions {
+    let mut running = RunningSessions::default();
+    let Some(state) = app_handle.try_state::<GeminiProcessState>() else {
+        return running;
+    };
+    for handle in state.processes.lock().await.values() {
+        match &handle.cli_session_id {
+            Some(id) => {
+                running.session_ids.insert(id.clone());
+            }
+            None => {
+                running
+                    .project_hashes
+                    .insert(hash_project_path(&handle.project_path));
+            }
+        }
+    }
+    running
+}
+
+fn run_cleanup_blocking(
+    retention: GeminiSessionRetention,
+    running: RunningSessions,
+    dry_run: bool,
+    trigger: &str,
+) -> Result<GeminiCleanupReport, String> {
+    let gemini_dir = get_gemini_dir()?;
+    let marks = load_session_marks()?;
+    let now = Utc::now();
+
+    let (candidates, skipped_protected, skipped_running) =
+        collect_candidates(&gemini_dir, &retention, &marks, &running, now);
+    let entries: Vec<(GeminiCleanupEntry, PathBuf)> = candidates
+        .into_iter()
+        .map(|session| (to_entry(&gemini_dir, &session), session.path))
+        .collect();
+
+    let mut report = GeminiCleanupReport {
+        dry_run,
+        sessions: Vec::new(),
+        freed_bytes: 0,
+        skipped_protected,
+        skipped_running,
+        trash_dir: None,
+        errors: Vec::new(),
+    };
+
+    if dry_run {
+        report.sessions = entries.into_iter().map(|(entry, _)| entry).collect();
+    } else if !entries.is_empty() {
+        encrypted_storage::ensure_writable()?;
+        let trash_dir = get_trash_root()?
+            .join("gemini")
+            .join(now.format("%Y%m%d-%H%M%S%3f").to_string());
+        let (moved, mut errors) = move_sessions_to_trash(&gemini_dir, entries, &trash_dir);
+        if !moved.is_empty() {
+            if let Err(e) = save_trash_index(&trash_dir, &moved) {
+                errors.push(e);
+            }
+        }
+        report.sessions = moved;
+        report.errors = errors;
+        report.trash_dir = Some(trash_dir.to_string_lossy().to_string());
+    }
+    report.freed_bytes = report.sessions.iter().map(|s| s.size_bytes).sum();
+
+    if !dry_run {
+        log::info!(
+            "[Gemini Cleanup] Moved {} sessions ({} bytes) to trash, {} errors",
+            report.sessions.len(),
+            report.freed_bytes,
+            report.errors.len()
+        );
+        if let Err(e) = append_audit_record(trigger, &retention, &report) {
+            log::warn!("[Gemini Cleanup] Failed to write audit record: {}", e);
+        }
+    }
+
+    Ok(report)
+}
+
+async fn run_cleanup(
+    app_handle: &AppHandle,
+    dry_run: bool,
+    trigger: &'static str,
+) -> Result<GeminiCleanupReport, String> {
+    let retention = load_gemini_config()?.session_retention;
+    let running = running_sessions(app_handle).await;
+
+    tokio::task::spawn_blocking(move || run_cleanup_blocking(retention, running, dry_run, trigger))
+        .await
+        .map_err(|e| format!("Cleanup task failed: {}", e))?
+}
+
+/// Clean up Gemini sessions according to the configured retention policy
+///
+/// With `dry_run` nothing is moved; the report lists what would be cleaned up.
+#[tauri::command]
+pub async fn cleanup_gemini_sessions(
+    dry_run: bool,
+    app_handle: AppHandle,
+) -> Result<GeminiCleanupReport, String> {
+    run_cleanup(&app_handle, dry_run, "manual").await
+}
+
+/// List sessions moved to the trash by earlier cleanups, newest first
+#[tauri::command]
+pub async fn list_gemini_trash() -> Result<Vec<GeminiTrashEntry>, String> {
+    let trash_root = get_trash_root()?;
+    tokio::task::spawn_blocking(move || list_trash(&trash_root))
+        .await
+        .map_err(|e| format!("Trash listing task failed: {}", e))
+}
+
+/// Restore a session from the trash; encrypted sessions need the storage unlocked
+#[tauri::command]
+pub async fn restore_gemini_trash_session(
+    trash_dir: String,
+    session_id: String,
+) -> Result<GeminiCleanupEntry, String> {
+    let gemini_root = get_trash_root()?.join("gemini");
+    let trash_dir = PathBuf::from(trash_dir)
+        .canonicalize()
+        .map_err(|e| format!("Invalid trash directory: {}", e))?;
+    let inside_trash = gemini_root
+        .canonicalize()
+        .map(|root| trash_dir.starts_with(root))
+        .unwrap_or(false);
+    if !inside_trash {
+        return Err("Trash directory is outside ~/.anycode/trash/gemini".to_string());
+    }
+    let gemini_dir = get_gemini_dir()?;
+
+    let entry = tokio::task::spawn_blocking(move || {
+        restore_from_trash(&gemini_dir, &trash_dir, &session_id)
+    })
+    .await
+    .map_err(|e| format!("Restore task failed: {}", e))??;
+    log::info!(
+        "[Gemini Cleanup] Restored session {} from trash",
+        entry.session_id
+    );
+    Ok(entry)
+}
+
+/// Run the cleanup on app startup if enabled in the retention policy
+pub async fn run_startup_cleanup(app_handle: AppHandle) {
+    match load_gemini_config() {
+        Ok(config) if config.session_retention.cleanup_on_startup => {}
+        _ => return,
+    }
+    if let Err(e) = run_cleanup(&app_handle, false, "startup").await {
+        log::warn!("[Gemini Cleanup] Startup cleanup failed: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn write_session(gemini_dir: &Path, project: &str, session_id: &str, last_updated: &str) {
+        let chats = gemini_dir.join("tmp").join(project).join("chats");
+        fs::create_dir_all(&chats).unwrap();
+        let content = json!({
+            "sessionId": session_id,
+            "projectHash": project,
+            "startTime": last_updated,
+            "lastUpdated": last_updated,
+            "messages": [{"type": "user", "content": "hi"}],
+        });
+        fs::write(
+            chats.join(format!("session-{}.json", session_id)),
+            content.to_string(),
+        )
+        .unwrap();
+    }
+
+    fn days_ago(now: DateTime<Utc>, days: i64) -> String {
+        (now - Duration::days(days)).to_rfc3339()
+    }
+
+    #[test]
+    fn expired_unmarked_sessions_go_to_trash() {
+        let dir = tempfile::tempdir().unwrap();
+        let gemini_dir = dir.path().join(".gemini");
+        let trash_dir = dir.path().join("trash");
+        let now = Utc::now();
+
+        write_session(&gemini_dir, "p1", "old", &days_ago(now, 45));
+        write_session(&gemini_dir, "p1", "old-favorite", &days_ago(now, 60));
+        write_session(&gemini_dir, "p1", "recent", &days_ago(now, 3));
+        let records = git_records_path(&gemini_dir, "old");
+        fs::create_dir_all(records.parent().unwrap()).unwrap();
+        fs::write(&records, "{}").unwrap();
+
+        let retention = GeminiSessionRetention {
+            retention_days: Some(30),
+            ..Default::default()
+        };
+        let mut marks = HashMap::new();
+        marks.insert(
+            "old-favorite".to_string(),
+            GeminiSessionMark {
+                favorite: true,
+                ..Default::default()
+            },
+        );
+
+        let (candidates, skipped_protected, skipped_running) = collect_candidates(
+            &gemini_dir,
+            &retention,
+            &marks,
+            &RunningSessions::default(),
+            now,
+        );
+        assert_eq!(skipped_protected, 1);
+        assert_eq!(skipped_running, 0);
+        assert_eq!(candidates.len(), 1);
+        assert_eq!(candidates[0].session_id, "old");
+
+        let entries = candidates
+            .into_iter()
+            .map(|s| (to_entry(&gemini_dir, &s), s.path))
+            .collect();
+        let (moved, errors) = move_sessions_to_trash(&gemini_dir, entries, &trash_dir);
+        assert!(errors.is_empty());
+        assert_eq!(moved.len(), 1);
+        assert!(trash_dir.join("tmp/p1/chats/session-old.json").exists());
+        assert!(trash_dir.join("git-records/old.json").exists());
+        assert!(!records.exists());
+        assert!(gemini_dir
+            .join("tmp/p1/chats/session-old-favorite.json")
+            .exists());
+        assert!(gemini_dir.join("tmp/p1/chats/session-recent.json").exists());
+    }
+
+    #[test]
+    fn trashed_sessions_are_indexed_and_restored() {
+        let dir = tempfile::tempdir().unwrap();
+        let gemini_dir = dir.path().join(".gemini");
+        let trash_root = dir.path().join("trash");
+        let run_dir = trash_root.join("gemini").join("20250101-000000000");
+        write_session(&run_dir, "p1", "old", "2025-01-01T00:00:00Z");
+
+        // Runs from before indexes existed get one built from their files
+        let listed = list_trash(&trash_root);
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].entry.session_id, "old");
+        assert!(!listed[0].encrypted);
+
+        restore_from_trash(&gemini_dir, &run_dir, "old").unwrap();
+        assert!(gemini_dir.join("tmp/p1/chats/session-old.json").exists());
+        assert!(!run_dir.join("tmp/p1/chats/session-old.json").exists());
+        assert!(list_trash(&trash_root).is_empty());
+
+        // Never overwrite a live session
+        write_session(&run_dir, "p1", "old", "2025-01-01T00:00:00Z");
+        save_trash_index(
+            &run_dir,
+            &[to_entry(&run_dir, &scan_session_files(&run_dir)[0])],
+        )
+        .unwrap();
+        assert!(restore_from_trash(&gemini_dir, &run_dir, "old").is_err());
+    }
+
+    #[test]
+    fn retention_rules_are_combined_as_union() {
+        let dir = tempfile::tempdir().unwrap();
+        let gemini_dir = dir.path().to_path_buf();
+        let now = Utc::now();
+
+        for (id, days) in [("a", 1), ("b", 10), ("c", 20), ("d", 40)] {
+            write_session(&gemini_dir, "p1", id, &days_ago(now, days));
+        }
+        write_session(&gemini_dir, "p2", "e", &days_ago(now, 90));
+
+        let retention = GeminiSessionRetention {
+            retention_days: Some(5),
+            max_sessions: Some(2),
+            ..Default::default()
+        };
+        let ids = |running: &RunningSessions| {
+            let (candidates, _, _) =
+                collect_candidates(&gemini_dir, &retention, &HashMap::new(), running, now);
+            candidates
+                .into_iter()
+                .map(|s| s.session_id)
+                .collect::<Vec<_>>()
+        };
+
+        // a: kept by age and count; b: kept by count; e: newest of its project
+        assert_eq!(ids(&RunningSessions::default()), vec!["d", "c"]);
+
+        let mut running = RunningSessions::default();
+        running.session_ids.insert("c".to_string());
+        assert_eq!(ids(&running), vec!["d"]);
+
+        let mut running = RunningSessions::default();
+        running.project_hashes.insert("p1".to_string());
+        assert!(ids(&running).is_empty());
+
+        assert!(select_expired(
+            scan_session_files(&gemini_dir),
+            &GeminiSessionRetention::default(),
+            now
+        )
+        .is_empty());
+    }
+}
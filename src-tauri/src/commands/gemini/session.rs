@@ -3,24 +3,60 @@
 //! Handles Gemini CLI execution, streaming output, and process management.
 //! Uses --output-format stream-json for real-time JSONL output.
 
+use std::future::Future;
+use std::pin::Pin;
 use std::process::Stdio;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 
 use tauri::{AppHandle, Emitter, Manager};
 use tokio::io::{AsyncBufReadExt, BufReader};
 use tokio::process::Command;
-use tokio::sync::OnceCell;
+use tokio::sync::{Mutex, OnceCell};
 use tokio::time::{sleep, Duration};
 
-use super::config::{build_gemini_env, load_gemini_config, read_session_detail};
+use super::config::{
+    build_gemini_env, excluded_builtin_tools, load_gemini_config, read_session_detail,
+};
+use super::context_files::{
+    effective_context_files, prepend_file_references, project_context_files,
+    resolve_extra_context_files, write_system_settings_override, SYSTEM_SETTINGS_ENV,
+};
 use super::parser::{
-    convert_raw_to_unified_message, convert_to_unified_message, parse_gemini_line,
-    parse_gemini_line_flexible,
+    assistant_delta_event, convert_raw_to_unified_message, convert_to_unified_message,
+    is_assistant_delta, parse_gemini_line, parse_gemini_line_flexible,
 };
 use super::types::{GeminiExecutionOptions, GeminiInstallStatus, GeminiProcessHandle, GeminiProcessState, GeminiSessionDetail, TokenUsage};
 use crate::claude_binary::detect_binary_for_tool;
-use crate::commands::claude::apply_no_window_async;
+use crate::commands::app_error::{classify_gemini_error, AppError};
+use crate::commands::claude::apply_cancellable_process_async;
+use crate::commands::execution_retry::{
+    cancel_pending_retries, load_retry_config, plan_retry, wait_before_retry, FailedAttempt,
+};
+use crate::commands::execution_trace::{
+    record_trace_event, resolve_trace_id, TraceFields, STAGE_EXECUTION_STARTED,
+    STAGE_PROCESS_SPAWNED, STAGE_SESSION_BOUND,
+};
+use crate::commands::power_inhibit::{self, PowerInhibitHold};
+use crate::commands::process_cancel::{
+    check_session_file, request_exit_and_wait, CancelMode, CancelOutcome, GRACEFUL_EXIT_TIMEOUT,
+};
+use crate::commands::project_memory::{append_memory_to_prompt, compiled_memory_for_execution};
+use crate::commands::prompt_enhancement::enhance_for_execution;
+use crate::commands::protected_paths::append_protected_paths_to_prompt;
+use crate::commands::rate_limiter;
+use crate::commands::session_invalidation::invalidate_session;
+use crate::commands::session_utils::resolve_session_file;
+use crate::commands::stream_stall::{
+    announce_reconnect, classify_gemini_event, emit_stalled, interrupt_stalled_process,
+    new_shared_monitor, record_activity, watch_stream, StallRecovery, StreamActivity,
+    CONTINUE_PROMPT,
+};
+use crate::commands::tool_timing::LiveToolTimer;
 use crate::commands::wsl_utils;
 use crate::process::JobObject;
+use crate::utils::markdown_stream::MarkdownStreamBuffer;
+use crate::utils::session_lock::{lock_for_execution, SessionLockHeartbeat};
 
 // ============================================================================
 // Slash Command Detection
@@ -388,9 +424,9 @@ fn do_check_gemini_installed() -> GeminiInstallStatus {
 /// Execute Gemini CLI with streaming output
 #[tauri::command]
 pub async fn execute_gemini(
-    options: GeminiExecutionOptions,
+    mut options: GeminiExecutionOptions,
     app_handle: AppHandle,
-) -> Result<(), String> {
+) -> Result<(), AppError> {
     // Avoid logging sensitive fields (prompt). Log only non-sensitive metadata.
     log::info!(
         "execute_gemini called: project_path={}, model={:?}, approval_mode={:?}, include_directories_count={}, session_id_present={}, debug={}, prompt_len={}",
@@ -407,6 +443,102 @@ pub async fn execute_gemini(
         options.prompt.len()
     );
 
+    // 续接会话时持有会话锁直到本次执行（含重试）结束，另一个实例同时续接时返回占用错误
+    let session_lock = lock_for_execution(
+        options
+            .session_id
+            .as_deref()
+            .and_then(|sid| resolve_session_file("gemini", sid, &options.project_path).ok()),
+        "gemini resume",
+    )?;
+
+    // 本次生效的上下文文件：未指定时使用项目默认值；先校验附加文件，避免增强之后才发现配置错误
+    let context_files = options
+        .context_files
+        .take()
+        .unwrap_or_else(|| project_context_files(&options.project_path));
+    let extra_context_files = if is_slash_command(&options.prompt) {
+        Vec::new()
+    } else {
+        resolve_extra_context_files(
+            &options.project_path,
+            options.include_directories.as_deref().unwrap_or_default(),
+            &context_files.extra_context_files,
+        )?
+    };
+
+    // Enhance the prompt with acemcp context first; the trace ID links the metadata to the prompt record
+    let trace_id = resolve_trace_id(options.trace_id.take());
+    if let Some(request) = options.enhancement.take() {
+        let prompt = std::mem::take(&mut options.prompt);
+        options.prompt = enhance_for_execution(
+            &app_handle,
+            &options.project_path,
+            prompt,
+            request,
+            &trace_id,
+        )
+        .await?;
+    }
+
+    // Append the compiled project memory to the first prompt of a new session when requested;
+    // a resumed session already carries it in its history
+    if options.use_project_memory && options.session_id.is_none() {
+        if let Some(memory) = compiled_memory_for_execution(&options.project_path) {
+            options.prompt = append_memory_to_prompt(&options.prompt, &memory);
+        }
+    }
+    // Protected paths are always stated as a constraint when configured
+    options.prompt = append_protected_paths_to_prompt(&options.prompt, &options.project_path);
+
+    // 附加上下文文件以 @ 引用注入提示词，由 CLI 读取文件内容
+    options.prompt =
+        prepend_file_references(&options.prompt, &extra_context_files, &options.project_path);
+    options.resolved_context_files =
+        effective_context_files(&options.project_path, &context_files, &extra_context_files);
+    options.context_files = Some(context_files);
+
+    log::info!("[Gemini] Run started with trace_id={}", trace_id);
+    record_trace_event(
+        &trace_id,
+        STAGE_EXECUTION_STARTED,
+        TraceFields::engine("gemini").with_detail(serde_json::json!({
+            "projectPath": options.project_path,
+            "model": options.model,
+            "mode": options.approval_mode,
+            "resume": options.session_id.is_some(),
+            "contextFiles": options.resolved_context_files,
+        })),
+    );
+    options.trace_id = Some(trace_id);
+
+    // Execute process with prompt via stdin
+    let session_id = format!("gemini-{}", uuid::Uuid::new_v4());
+    let power_hold = power_inhibit::acquire(
+        "gemini",
+        &session_id,
+        options.trace_id.as_deref(),
+        &options.project_path,
+    );
+    execute_gemini_process(
+        session_id,
+        options,
+        session_lock,
+        power_hold,
+        1,
+        0,
+        app_handle,
+    )
+    .await
+    .map_err(classify_gemini_error)
+}
+
+/// Build the Gemini CLI command for the given options
+///
+/// Returns the command together with the resolved model and approval mode
+fn build_gemini_command(
+    options: &GeminiExecutionOptions,
+) -> Result<(Command, String, String), String> {
     // Find Gemini binary
     let gemini_path = find_gemini_binary()?;
     let is_wsl = gemini_path.starts_with("WSL:");
@@ -431,6 +563,9 @@ pub async fn execute_gemini(
     args.push("--model".to_string());
     args.push(model.clone());
 
+    // Built-in tools switched off for this run, refusing models known not to support a requested one
+    let excluded_tools = excluded_builtin_tools(model, options.enable_search_grounding)?;
+
     // Add approval mode
     let approval_mode = options
         .approval_mode
@@ -458,7 +593,7 @@ pub async fn execute_gemini(
                             wsl_runtime.distro.as_deref(),
                         )
                     })
-                    .collect::<Vec<_>>()
+                    .collect::<Result<Vec<_>, _>>()?
                     .join(",")
             } else {
                 dirs.join(",")
@@ -474,6 +609,20 @@ pub async fn execute_gemini(
         args.push("--debug".to_string());
     }
 
+    // Disable GEMINI.md loading and built-in tools through a system settings override
+    let include_default_context = options
+        .context_files
+        .as_ref()
+        .is_none_or(|context| context.include_default_context);
+    let settings_override = if include_default_context && excluded_tools.is_empty() {
+        None
+    } else {
+        Some(write_system_settings_override(
+            !include_default_context,
+            &excluded_tools,
+        )?)
+    };
+
     // Note: Prompt will be passed via stdin to support multiline content
     // Command line arguments have length limits and special character issues on Windows
 
@@ -499,7 +648,7 @@ pub async fn execute_gemini(
                 &args,
                 Some(&options.project_path),
                 wsl_runtime.distro.as_deref(),
-            );
+            )?;
 
             // Set environment variables from config
             // Note: Environment variables will be passed to WSL environment
@@ -507,6 +656,13 @@ pub async fn execute_gemini(
             for (key, value) in env_vars {
                 cmd.env(&key, &value);
             }
+            if let Some(path) = &settings_override {
+                let wsl_path = wsl_utils::windows_to_wsl_path_with_distro(
+                    &path.to_string_lossy(),
+                    wsl_runtime.distro.as_deref(),
+                )?;
+                cmd.env(SYSTEM_SETTINGS_ENV, wsl_path);
+            }
 
             cmd
         }
@@ -527,22 +683,20 @@ pub async fn execute_gemini(
         for (key, value) in env_vars {
             cmd.env(&key, &value);
         }
+        if let Some(path) = &settings_override {
+            cmd.env(SYSTEM_SETTINGS_ENV, path);
+        }
 
         cmd
     };
 
-    // Execute process with prompt via stdin
-    execute_gemini_process(
-        cmd,
-        options.project_path,
-        model.clone(),
-        Some(options.prompt),
-        app_handle,
-    )
-    .await
+    Ok((cmd, model.clone(), approval_mode.clone()))
 }
 
 /// Cancel a running Gemini execution
+///
+/// The process is interrupted first so Gemini CLI can save the chat file,
+/// and only force killed if it does not exit within the grace period
 #[tauri::command]
 pub async fn cancel_gemini(
     session_id: Option<String>,
@@ -550,61 +704,143 @@ pub async fn cancel_gemini(
 ) -> Result<(), String> {
     log::info!("cancel_gemini called for session: {:?}", session_id);
 
+    // A run waiting to retry has no process; cancelling it ends the run instead
+    if cancel_pending_retries(session_id.as_deref(), "gemini-") {
+        log::info!(
+            "Cancelled pending Gemini retry for session: {:?}",
+            session_id
+        );
+    }
+
+    // Take the handles out first so the state lock is not held while waiting for exit
     let state: tauri::State<'_, GeminiProcessState> = app_handle.state();
-    let mut processes = state.processes.lock().await;
+    let handles: Vec<(String, GeminiProcessHandle)> = {
+        let mut processes = state.processes.lock().await;
+        match session_id.as_deref() {
+            // Cancel specific session
+            Some(sid) => match processes.remove_entry(sid) {
+                Some(entry) => vec![entry],
+                None => {
+                    log::warn!("No running process found for session: {}", sid);
+                    Vec::new()
+                }
+            },
+            // Cancel all processes
+            None => processes.drain().collect(),
+        }
+    };
 
-    if let Some(sid) = session_id {
-        // Cancel specific session
-        if let Some(mut handle) = processes.remove(&sid) {
-            // Kill the process - JobObject will automatically terminate all child processes when dropped
-            handle
-                .child
-                .kill()
-                .await
-                .map_err(|e| format!("Failed to kill process: {}", e))?;
-            log::info!("Killed Gemini process for session: {} (PID: {})", sid, handle.pid);
+    let outcomes = futures::future::join_all(
+        handles
+            .into_iter()
+            .map(|(sid, handle)| cancel_gemini_process(sid, handle)),
+    )
+    .await;
+    for outcome in outcomes {
+        let _ = app_handle.emit(
+            &format!("gemini-cancelled:{}", outcome.session_id),
+            &outcome,
+        );
+        let _ = app_handle.emit("gemini-cancelled", &outcome);
+    }
 
-            // JobObject is dropped here, killing all child processes (MCP servers, node.exe, etc.)
-            drop(handle.job_object);
+    Ok(())
+}
 
-            // Emit cancellation event
-            let _ = app_handle.emit(&format!("gemini-cancelled:{}", sid), true);
-            let _ = app_handle.emit("gemini-cancelled", true);
-        } else {
-            log::warn!("No running process found for session: {}", sid);
-        }
+/// Interrupts one Gemini process, force kills it after the grace period,
+/// then checks that its chat file is still valid JSON
+async fn cancel_gemini_process(
+    session_id: String,
+    mut handle: GeminiProcessHandle,
+) -> CancelOutcome {
+    let pid = handle.pid;
+    let exited = request_exit_and_wait(pid, GRACEFUL_EXIT_TIMEOUT, || {
+        !matches!(handle.child.try_wait(), Ok(None))
+    })
+    .await;
+    let mode = if exited {
+        CancelMode::Graceful
     } else {
-        // Cancel all processes
-        for (sid, mut handle) in processes.drain() {
-            if let Err(e) = handle.child.kill().await {
-                log::error!("Failed to kill process for session {}: {}", sid, e);
-            } else {
-                log::info!("Killed Gemini process for session: {} (PID: {})", sid, handle.pid);
-            }
-            // JobObject is dropped here, killing all child processes
-            drop(handle.job_object);
+        if let Err(e) = handle.child.kill().await {
+            log::error!("Failed to kill process for session {}: {}", session_id, e);
+        } else {
+            log::info!(
+                "Killed Gemini process for session: {} (PID: {})",
+                session_id,
+                pid
+            );
         }
-        let _ = app_handle.emit("gemini-cancelled", true);
-    }
+        CancelMode::Forced
+    };
+    // JobObject is dropped here, killing all child processes (MCP servers, node.exe, etc.)
+    drop(handle.job_object);
+
+    let session_file = handle
+        .cli_session_id
+        .as_deref()
+        .and_then(|cli_session_id| {
+            resolve_session_file("gemini", cli_session_id, &handle.project_path).ok()
+        })
+        .and_then(|path| {
+            check_session_file(&path)
+                .map_err(|e| log::warn!("Failed to check Gemini session file: {}", e))
+                .ok()
+        });
+    log::info!(
+        "Gemini session {} cancelled ({:?}), session file: {:?}",
+        session_id,
+        mode,
+        session_file
+    );
 
-    Ok(())
+    CancelOutcome {
+        engine: "gemini".to_string(),
+        session_id,
+        pid: Some(pid),
+        mode,
+        session_file,
+    }
 }
 
 // ============================================================================
 // Process Execution
 // ============================================================================
 
+/// Record the real CLI session ID on the running process so other commands
+/// (e.g. session cleanup) can tell which session files are in use
+async fn remember_cli_session_id(
+    app_handle: &AppHandle,
+    backend_session_id: &str,
+    cli_session_id: &str,
+) {
+    let state = app_handle.state::<GeminiProcessState>();
+    let mut processes = state.processes.lock().await;
+    if let Some(handle) = processes.get_mut(backend_session_id) {
+        handle.cli_session_id = Some(cli_session_id.to_string());
+    }
+}
+
 /// Execute a Gemini process and stream output to frontend
 ///
 /// 🔥 斜杠命令支持：斜杠命令通过 -p 参数传递（触发命令解析），普通 prompt 通过 stdin 管道传递
 /// 这样既支持斜杠命令，又避免操作系统命令行长度限制（Windows ~8KB, Linux/macOS ~128KB-2MB）
+///
+/// `attempt` 从 1 开始；瞬时错误重试时沿用同一个 `session_id`、会话锁与休眠阻止。
+/// `stall_reconnects` 为本次运行中输出卡死后已经续接的次数
 async fn execute_gemini_process(
-    mut cmd: Command,
-    project_path: String,
-    model: String,
-    prompt: Option<String>,
+    session_id: String,
+    options: GeminiExecutionOptions,
+    mut session_lock: Option<SessionLockHeartbeat>,
+    mut power_hold: Option<PowerInhibitHold>,
+    attempt: u32,
+    stall_reconnects: u32,
     app_handle: AppHandle,
 ) -> Result<(), String> {
+    let (mut cmd, model, approval_mode) = build_gemini_command(&options)?;
+    let project_path = options.project_path.clone();
+    let prompt = Some(options.prompt.clone());
+    let trace_id = options.trace_id.clone();
+
     // 🔥 关键修复：检测斜杠命令，通过 -p 参数传递以触发命令解析
     // Gemini CLI 在非交互模式下支持斜杠命令（自 v0.1.59 起，PR #8305）
     let use_p_flag = prompt
@@ -628,10 +864,14 @@ async fn execute_gemini_process(
     cmd.stdout(Stdio::piped());
     cmd.stderr(Stdio::piped());
 
-    // Apply platform-specific no-window configuration
-    apply_no_window_async(&mut cmd);
+    // Hide the console window and start a new process group for graceful cancellation
+    apply_cancellable_process_async(&mut cmd);
+
+    // 按当前 provider 的限流配置排队启动
+    rate_limiter::acquire_cli_launch("gemini").await?;
 
     // Spawn process
+    let started_at = std::time::Instant::now();
     let mut child = cmd
         .spawn()
         .map_err(|e| format!("Failed to spawn gemini: {}", e))?;
@@ -674,7 +914,20 @@ async fn execute_gemini_process(
     let pid = child
         .id()
         .ok_or("Failed to get process ID - process may have already exited")?;
-    log::info!("[Gemini] Spawned process with PID: {}", pid);
+    log::info!(
+        "[Gemini] Spawned process with PID: {} (trace_id={})",
+        pid,
+        trace_id.as_deref().unwrap_or("-")
+    );
+    if let Some(trace_id) = trace_id.as_deref() {
+        record_trace_event(
+            trace_id,
+            STAGE_PROCESS_SPAWNED,
+            TraceFields::engine("gemini")
+                .with_session(Some(&session_id))
+                .with_detail(serde_json::json!({ "pid": pid, "attempt": attempt })),
+        );
+    }
 
     // Windows robustness: assign the process to a Job Object so *all* descendants are cleaned up
     // even if Gemini CLI spawns detached node.exe processes (MCP servers).
@@ -699,9 +952,6 @@ async fn execute_gemini_process(
     #[cfg(not(windows))]
     let job_object: Option<JobObject> = None;
 
-    // Generate session ID
-    let session_id = format!("gemini-{}", uuid::Uuid::new_v4());
-
     // Store process in state with PID and JobObject for proper cleanup
     let state: tauri::State<'_, GeminiProcessState> = app_handle.state();
     {
@@ -710,6 +960,8 @@ async fn execute_gemini_process(
             child,
             pid,
             job_object,
+            project_path: project_path.clone(),
+            cli_session_id: None,
         };
         processes.insert(session_id.clone(), handle);
 
@@ -717,29 +969,35 @@ async fn execute_gemini_process(
         *last_session = Some(session_id.clone());
     }
 
-    // Emit session init event
-    let init_payload = serde_json::json!({
-        "type": "system",
-        "subtype": "init",
-        "session_id": session_id,
-        "model": model,
-        "project_path": project_path,
-        "geminiMetadata": {
-            "provider": "gemini",
-            "eventType": "session_init"
-        }
-    });
+    // Emit session init event (a retry keeps the channel the frontend already bound)
+    if attempt == 1 {
+        let init_payload = serde_json::json!({
+            "type": "system",
+            "subtype": "init",
+            "session_id": session_id,
+            "model": model,
+            "project_path": project_path,
+            "trace_id": trace_id,
+            "context_files": options.resolved_context_files,
+            "geminiMetadata": {
+                "provider": "gemini",
+                "eventType": "session_init"
+            }
+        });
 
-    if let Err(e) = app_handle.emit("gemini-session-init", &init_payload) {
-        log::error!("Failed to emit gemini-session-init: {}", e);
-    }
+        if let Err(e) = app_handle.emit("gemini-session-init", &init_payload) {
+            log::error!("Failed to emit gemini-session-init: {}", e);
+        }
 
-    // Also emit as gemini-output for unified handling
-    let init_line = serde_json::to_string(&init_payload).unwrap_or_default();
-    let _ = app_handle.emit(&format!("gemini-output:{}", session_id), &init_line);
-    let _ = app_handle.emit("gemini-output", &init_line);
+        // Also emit as gemini-output for unified handling
+        let init_line = serde_json::to_string(&init_payload).unwrap_or_default();
+        let _ = app_handle.emit(&format!("gemini-output:{}", session_id), &init_line);
+        let _ = app_handle.emit("gemini-output", &init_line);
 
-    log::info!("Gemini session initialized with ID: {}", session_id);
+        log::info!("Gemini session initialized with ID: {}", session_id);
+    } else {
+        log::info!("Gemini session {} starting attempt {}", session_id, attempt);
+    }
 
     // 🔧 FIX: Use channels to track stdout/stderr closure for timeout detection
     let (stdout_done_tx, stdout_done_rx) = tokio::sync::oneshot::channel();
@@ -752,10 +1010,26 @@ async fn execute_gemini_process(
     let session_id_stdout = session_id.clone();
     let session_id_stderr = session_id.clone();
     let session_id_complete = session_id.clone();
+    let trace_id_stdout = trace_id.clone();
+
+    // 自动重试判断：是否已有助手消息或工具调用，以及错误事件与 stderr 中的消息
+    let saw_progress = Arc::new(AtomicBool::new(false));
+    let saw_progress_for_stdout = saw_progress.clone();
+    let failure_messages: Arc<Mutex<Vec<String>>> = Arc::new(Mutex::new(Vec::new()));
+    let failure_messages_for_stdout = failure_messages.clone();
+    let failure_messages_for_stderr = failure_messages.clone();
+    // 输出卡死检测：stdout 任务记录活动，看门狗决定唤醒或续接
+    let stall_monitor = new_shared_monitor(stall_reconnects);
+    let stall_monitor_for_stdout = stall_monitor.clone();
+    let stream_closed = Arc::new(AtomicBool::new(false));
+    let stream_closed_for_stdout = stream_closed.clone();
+    let stall_recovery: Arc<Mutex<Option<(StallRecovery, String)>>> = Arc::new(Mutex::new(None));
+    let stall_recovery_for_complete = stall_recovery.clone();
 
     // Spawn task to read stdout (JSONL events)
     let model_for_messages = model.clone();
     let project_path_for_usage = project_path.clone();
+    let project_path_for_complete = project_path.clone();
     tokio::spawn(async move {
         let mut reader = BufReader::new(stdout).lines();
         let mut real_cli_session_id_emitted = false;
@@ -763,8 +1037,35 @@ async fn execute_gemini_process(
         // Track tool calls to enrich tool_result payloads (e.g., read_file returning empty output)
         let mut tool_calls: std::collections::HashMap<String, (String, serde_json::Value)> =
             std::collections::HashMap::new();
-
-        while let Ok(Some(line)) = reader.next_line().await {
+        // 助手文本片段的结构感知缓冲，避免代码围栏与表格行在片段边界处闪烁
+        let mut text_buffer = MarkdownStreamBuffer::new();
+        let mut last_delta_timestamp: Option<String> = None;
+        let mut tool_timer = LiveToolTimer::default();
+
+        loop {
+            let next_line = match text_buffer.deadline() {
+                Some(deadline) => {
+                    let deadline = tokio::time::Instant::from_std(deadline);
+                    match tokio::time::timeout_at(deadline, reader.next_line()).await {
+                        Ok(next_line) => next_line,
+                        Err(_) => {
+                            // 缓冲超时：强制下发，防止文本卡住
+                            flush_buffered_text(
+                                &mut text_buffer,
+                                last_delta_timestamp.clone(),
+                                &app_handle_stdout,
+                                &session_id_stdout,
+                                &model_for_messages,
+                            );
+                            continue;
+                        }
+                    }
+                }
+                None => reader.next_line().await,
+            };
+            let Ok(Some(line)) = next_line else {
+                break;
+            };
             if line.trim().is_empty() {
                 continue;
             }
@@ -774,6 +1075,25 @@ async fn execute_gemini_process(
 
             // Try to parse and convert to unified format
             let mut unified_message = if let Ok(mut event) = parse_gemini_line(&line) {
+                record_activity(&stall_monitor_for_stdout, classify_gemini_event(&event));
+                match &event {
+                    super::types::GeminiStreamEvent::Message { role, .. }
+                        if role == "assistant" =>
+                    {
+                        saw_progress_for_stdout.store(true, Ordering::Relaxed);
+                    }
+                    super::types::GeminiStreamEvent::ToolUse { .. } => {
+                        saw_progress_for_stdout.store(true, Ordering::Relaxed);
+                    }
+                    super::types::GeminiStreamEvent::Error { message, .. } => {
+                        failure_messages_for_stdout
+                            .lock()
+                            .await
+                            .push(message.clone());
+                    }
+                    _ => {}
+                }
+
                 // 🔧 FIX: Check if this is an init event with real Gemini CLI session ID
                 if !real_cli_session_id_emitted {
                     if let super::types::GeminiStreamEvent::Init {
@@ -782,6 +1102,17 @@ async fn execute_gemini_process(
                     } = event
                     {
                         real_cli_session_id = Some(cli_session_id.clone());
+                        remember_cli_session_id(
+                            &app_handle_stdout,
+                            &session_id_stdout,
+                            cli_session_id,
+                        )
+                        .await;
+                        record_gemini_session_bound(
+                            trace_id_stdout.as_deref(),
+                            &session_id_stdout,
+                            cli_session_id,
+                        );
                         // Emit the real Gemini CLI session ID to frontend
                         log::info!("[Gemini] Detected real CLI session ID: {}", cli_session_id);
                         let cli_session_payload = serde_json::json!({
@@ -897,14 +1228,55 @@ async fn execute_gemini_process(
                     }
                 }
 
+                if is_assistant_delta(&event) {
+                    if let super::types::GeminiStreamEvent::Message {
+                        content, timestamp, ..
+                    } = &mut event
+                    {
+                        last_delta_timestamp = timestamp.clone();
+                        match text_buffer.push(content) {
+                            Some(text) => *content = text,
+                            None => continue,
+                        }
+                    }
+                } else {
+                    // 其他事件下发前先送出缓冲中的文本，保持顺序
+                    flush_buffered_text(
+                        &mut text_buffer,
+                        last_delta_timestamp.clone(),
+                        &app_handle_stdout,
+                        &session_id_stdout,
+                        &model_for_messages,
+                    );
+                }
+
                 convert_to_unified_message(&event)
             } else if let Ok(raw) = parse_gemini_line_flexible(&line) {
+                record_activity(&stall_monitor_for_stdout, StreamActivity::Output);
+                flush_buffered_text(
+                    &mut text_buffer,
+                    last_delta_timestamp.clone(),
+                    &app_handle_stdout,
+                    &session_id_stdout,
+                    &model_for_messages,
+                );
                 // 🔧 FIX: Also check raw JSON for init event with session_id
                 if !real_cli_session_id_emitted {
                     if raw.get("type").and_then(|t| t.as_str()) == Some("init") {
                         if let Some(cli_session_id) = raw.get("session_id").and_then(|s| s.as_str())
                         {
                             real_cli_session_id = Some(cli_session_id.to_string());
+                            remember_cli_session_id(
+                                &app_handle_stdout,
+                                &session_id_stdout,
+                                cli_session_id,
+                            )
+                            .await;
+                            record_gemini_session_bound(
+                                trace_id_stdout.as_deref(),
+                                &session_id_stdout,
+                                cli_session_id,
+                            );
                             log::info!(
                                 "[Gemini] Detected real CLI session ID (raw): {}",
                                 cli_session_id
@@ -924,6 +1296,14 @@ async fn execute_gemini_process(
                 }
                 convert_raw_to_unified_message(&raw)
             } else {
+                record_activity(&stall_monitor_for_stdout, StreamActivity::Output);
+                flush_buffered_text(
+                    &mut text_buffer,
+                    last_delta_timestamp.clone(),
+                    &app_handle_stdout,
+                    &session_id_stdout,
+                    &model_for_messages,
+                );
                 // Fallback: emit raw line as system message
                 serde_json::json!({
                     "type": "system",
@@ -936,40 +1316,25 @@ async fn execute_gemini_process(
                 })
             };
 
-            // Ensure engine/model are present for consistent frontend cost/context calculations
-            if let Some(obj) = unified_message.as_object_mut() {
-                obj.entry("engine")
-                    .or_insert_with(|| serde_json::Value::String("gemini".to_string()));
-
-                let should_set_model = match obj.get("model") {
-                    None => true,
-                    Some(v) => v.is_null() || v.as_str().map(|s| s.trim().is_empty()).unwrap_or(false),
-                };
-                if should_set_model {
-                    obj.insert(
-                        "model".to_string(),
-                        serde_json::Value::String(model_for_messages.clone()),
-                    );
-                }
-            }
-
-            let unified_line = serde_json::to_string(&unified_message).unwrap_or(line.clone());
-
-            // Emit to session-specific channel
-            if let Err(e) = app_handle_stdout.emit(
-                &format!("gemini-output:{}", session_id_stdout),
-                &unified_line,
-            ) {
-                log::error!("Failed to emit gemini-output (session): {}", e);
-            }
-
-            // Also emit to global channel
-            if let Err(e) = app_handle_stdout.emit("gemini-output", &unified_line) {
-                log::error!("Failed to emit gemini-output (global): {}", e);
-            }
+            // tool_result 附上工具耗时
+            tool_timer.observe_claude_message(&mut unified_message);
+            emit_unified_output(
+                &app_handle_stdout,
+                &session_id_stdout,
+                &model_for_messages,
+                &mut unified_message,
+            );
         }
 
+        flush_buffered_text(
+            &mut text_buffer,
+            last_delta_timestamp,
+            &app_handle_stdout,
+            &session_id_stdout,
+            &model_for_messages,
+        );
         log::info!("[Gemini] Stdout closed for session: {}", session_id_stdout);
+        stream_closed_for_stdout.store(true, Ordering::Relaxed);
         // Signal that stdout is done (ignore send error if receiver dropped)
         let _ = stdout_done_tx.send(());
     });
@@ -981,6 +1346,12 @@ async fn execute_gemini_process(
         while let Ok(Some(line)) = reader.next_line().await {
             if !line.trim().is_empty() {
                 log::warn!("Gemini stderr: {}", line);
+                {
+                    let mut messages = failure_messages_for_stderr.lock().await;
+                    if messages.len() < 20 {
+                        messages.push(line.clone());
+                    }
+                }
 
                 // Emit stderr as error event
                 let error_message = serde_json::json!({
@@ -1008,6 +1379,56 @@ async fn execute_gemini_process(
         let _ = stderr_done_tx.send(());
     });
 
+    // 看门狗：进程存活但长时间无输出时中断进程，由完成任务续接会话
+    let app_handle_watchdog = app_handle.clone();
+    let session_id_watchdog = session_id.clone();
+    let trace_id_watchdog = trace_id.clone();
+    tokio::spawn(async move {
+        let Some(recovery) = watch_stream(
+            &app_handle_watchdog,
+            "gemini",
+            &session_id_watchdog,
+            trace_id_watchdog.as_deref(),
+            &stall_monitor,
+            &stream_closed,
+        )
+        .await
+        else {
+            return;
+        };
+
+        // 续接需要 CLI 会话 ID；进程已退出时 stdout 会自行关闭
+        let resume_target = {
+            let state = app_handle_watchdog.state::<GeminiProcessState>();
+            let mut processes = state.processes.lock().await;
+            let Some(handle) = processes.get_mut(&session_id_watchdog) else {
+                return;
+            };
+            if !matches!(handle.child.try_wait(), Ok(None)) {
+                return;
+            }
+            handle.cli_session_id.clone()
+        };
+        let Some(resume_target) = resume_target else {
+            log::warn!(
+                "[Gemini] Session {} stalled before reporting a CLI session ID, cannot resume",
+                session_id_watchdog
+            );
+            emit_stalled(
+                &app_handle_watchdog,
+                "gemini",
+                &session_id_watchdog,
+                std::time::Duration::from_secs(recovery.idle_secs),
+                None,
+                trace_id_watchdog.as_deref(),
+            );
+            return;
+        };
+
+        *stall_recovery.lock().await = Some((recovery, resume_target));
+        interrupt_stalled_process(pid, &stream_closed).await;
+    });
+
     // Spawn task to wait for process completion
     // 🔧 FIX: Add timeout mechanism - if stdout/stderr are closed but process doesn't exit within 30s, force completion
     let state_complete = app_handle.state::<GeminiProcessState>();
@@ -1020,6 +1441,12 @@ async fn execute_gemini_process(
             "[Gemini] Both stdout and stderr closed for session: {}",
             session_id_complete
         );
+        // stdout 已关闭，真实会话 ID（若有）此时已经记录在进程句柄上
+        let cli_session_id = processes_complete
+            .lock()
+            .await
+            .get(&session_id_complete)
+            .and_then(|handle| handle.cli_session_id.clone());
 
         // After streams close, give process up to 30 seconds to exit gracefully
         let timeout_duration = tokio::time::Duration::from_secs(30);
@@ -1042,6 +1469,10 @@ async fn execute_gemini_process(
         })
         .await;
 
+        // 进程已被 cancel_gemini 移除
+        let cancelled =
+            matches!(&wait_result, Ok(Err(e)) if e.kind() == std::io::ErrorKind::NotFound);
+
         let (success, exit_code) = match wait_result {
             Ok(Ok(status)) => {
                 let success = status.success();
@@ -1076,6 +1507,134 @@ async fn execute_gemini_process(
             }
         };
 
+        // 看门狗因输出卡死中断了进程：不发送完成事件，续接会话（执行已被取消时不续接）
+        let stall_recovery = stall_recovery_for_complete
+            .lock()
+            .await
+            .take()
+            .filter(|_| !cancelled);
+
+        // 瞬时错误导致的快速失败：不发送完成事件，按配置重试
+        let retry_plan = if success || cancelled || stall_recovery.is_some() {
+            None
+        } else {
+            plan_retry(
+                &load_retry_config(),
+                &FailedAttempt {
+                    attempt,
+                    elapsed: started_at.elapsed(),
+                    made_progress: saw_progress.load(Ordering::Relaxed),
+                    messages: failure_messages.lock().await.clone(),
+                },
+            )
+        };
+        if let Some((recovery, resume_target)) = stall_recovery {
+            announce_reconnect(
+                &app_handle_complete,
+                "gemini",
+                &session_id_complete,
+                &recovery,
+                &resume_target,
+                trace_id.as_deref(),
+            );
+            let mut options = options;
+            options.session_id = Some(resume_target);
+            options.prompt = CONTINUE_PROMPT.to_string();
+            match respawn_gemini_process(
+                session_id_complete.clone(),
+                options,
+                session_lock.take(),
+                power_hold.take(),
+                attempt + 1,
+                recovery.reconnect,
+                app_handle_complete.clone(),
+            )
+            .await
+            {
+                Ok(()) => return,
+                Err(e) => {
+                    log::error!("[Gemini] Reconnect failed to start: {}", e);
+                    let error_line = serde_json::json!({
+                        "type": "system",
+                        "subtype": "error",
+                        "error": {
+                            "message": format!("Gemini 续接启动失败: {}", e)
+                        },
+                        "geminiMetadata": {
+                            "provider": "gemini",
+                            "eventType": "reconnect"
+                        }
+                    })
+                    .to_string();
+                    let _ = app_handle_complete.emit(
+                        &format!("gemini-error:{}", session_id_complete),
+                        &error_line,
+                    );
+                    let _ = app_handle_complete.emit("gemini-error", &error_line);
+                }
+            }
+        } else if let Some(plan) = retry_plan {
+            if wait_before_retry(
+                &app_handle_complete,
+                "gemini",
+                &session_id_complete,
+                &plan,
+                trace_id.as_deref(),
+            )
+            .await
+            {
+                match respawn_gemini_process(
+                    session_id_complete.clone(),
+                    options,
+                    session_lock.take(),
+                    power_hold.take(),
+                    plan.next_attempt,
+                    stall_reconnects,
+                    app_handle_complete.clone(),
+                )
+                .await
+                {
+                    Ok(()) => return,
+                    Err(e) => {
+                        log::error!("[Gemini] Retry failed to start: {}", e);
+                        let error_line = serde_json::json!({
+                            "type": "system",
+                            "subtype": "error",
+                            "error": {
+                                "message": format!("Gemini 重试启动失败: {}", e)
+                            },
+                            "geminiMetadata": {
+                                "provider": "gemini",
+                                "eventType": "retry"
+                            }
+                        })
+                        .to_string();
+                        let _ = app_handle_complete.emit(
+                            &format!("gemini-error:{}", session_id_complete),
+                            &error_line,
+                        );
+                        let _ = app_handle_complete.emit("gemini-error", &error_line);
+                    }
+                }
+            } else {
+                log::info!(
+                    "[Gemini] Retry cancelled for session: {}",
+                    session_id_complete
+                );
+            }
+        }
+
+        // 先释放会话锁与休眠阻止再发送完成事件，避免紧接着的续接被误判为占用
+        drop(session_lock);
+        drop(power_hold);
+
+        invalidate_session(
+            &app_handle_complete,
+            "gemini",
+            cli_session_id.as_deref(),
+            Some(&project_path_for_complete),
+        );
+
         // Emit completion event
         let complete_payload = serde_json::json!({
             "type": "result",
@@ -1098,7 +1657,107 @@ async fn execute_gemini_process(
         let _ =
             app_handle_complete.emit(&format!("gemini-complete:{}", session_id_complete), success);
         let _ = app_handle_complete.emit("gemini-complete", success);
+
+        crate::commands::task_actions::dispatch_task_completion(
+            &app_handle_complete,
+            crate::commands::task_actions::TaskCompletionEvent {
+                engine: "gemini".to_string(),
+                project_path: project_path_for_complete,
+                session_id: Some(session_id_complete.clone()),
+                cli_session_id,
+                mode: Some(approval_mode),
+                success,
+                duration_ms: started_at.elapsed().as_millis() as u64,
+                trace_id,
+            },
+        );
     });
 
     Ok(())
 }
+
+/// Emit a unified message on the session-specific and global output channels
+fn emit_unified_output(
+    app_handle: &AppHandle,
+    session_id: &str,
+    model: &str,
+    unified_message: &mut serde_json::Value,
+) {
+    // Ensure engine/model are present for consistent frontend cost/context calculations
+    if let Some(obj) = unified_message.as_object_mut() {
+        obj.entry("engine")
+            .or_insert_with(|| serde_json::Value::String("gemini".to_string()));
+
+        let should_set_model = match obj.get("model") {
+            None => true,
+            Some(v) => v.is_null() || v.as_str().map(|s| s.trim().is_empty()).unwrap_or(false),
+        };
+        if should_set_model {
+            obj.insert(
+                "model".to_string(),
+                serde_json::Value::String(model.to_string()),
+            );
+        }
+    }
+
+    let unified_line = serde_json::to_string(&unified_message).unwrap_or_default();
+
+    // Emit to session-specific channel
+    if let Err(e) = app_handle.emit(&format!("gemini-output:{}", session_id), &unified_line) {
+        log::error!("Failed to emit gemini-output (session): {}", e);
+    }
+
+    // Also emit to global channel
+    if let Err(e) = app_handle.emit("gemini-output", &unified_line) {
+        log::error!("Failed to emit gemini-output (global): {}", e);
+    }
+}
+
+/// 送出结构感知缓冲中剩余的助手文本
+fn flush_buffered_text(
+    buffer: &mut MarkdownStreamBuffer,
+    timestamp: Option<String>,
+    app_handle: &AppHandle,
+    session_id: &str,
+    model: &str,
+) {
+    if let Some(text) = buffer.flush() {
+        let mut unified_message =
+            convert_to_unified_message(&assistant_delta_event(text, timestamp));
+        emit_unified_output(app_handle, session_id, model, &mut unified_message);
+    }
+}
+
+/// 记录通道 session_id 与 Gemini CLI 真实会话 ID 的绑定
+fn record_gemini_session_bound(trace_id: Option<&str>, session_id: &str, cli_session_id: &str) {
+    if let Some(trace_id) = trace_id {
+        record_trace_event(
+            trace_id,
+            STAGE_SESSION_BOUND,
+            TraceFields::engine("gemini")
+                .with_session(Some(session_id))
+                .with_detail(serde_json::json!({ "cliSessionId": cli_session_id })),
+        );
+    }
+}
+
+/// 在上一次尝试的完成任务中启动下一次尝试
+fn respawn_gemini_process(
+    session_id: String,
+    options: GeminiExecutionOptions,
+    session_lock: Option<SessionLockHeartbeat>,
+    power_hold: Option<PowerInhibitHold>,
+    attempt: u32,
+    stall_reconnects: u32,
+    app_handle: AppHandle,
+) -> Pin<Box<dyn Future<Output = Result<(), String>> + Send>> {
+    Box::pin(execute_gemini_process(
+        session_id,
+        options,
+        session_lock,
+        power_hold,
+        attempt,
+        stall_reconnects,
+        app_handle,
+    ))
+}
@@ -0,0 +1,203 @@
+//! Gemini settings.json 读写
+//!
+//! ~/.gemini/settings.json 同时会被 Gemini CLI 和用户手工修改，应用只管理其中的
+//! 认证方式（security.auth.selectedType）与 mcpServers。所有写入都走读-改-写：
+//! 在最新内容上只修改应用管理的键，telemetry、checkpointing 等其他字段原样保留；
+//! 写入前比较读取时的内容哈希，文件已被外部修改则重新读取再修改一次，重试后仍冲突则
+//! 返回冲突错误；最终通过临时文件 + rename 原子替换，避免写到一半的文件被 CLI 读到。
+
+use serde_json::{Map, Value};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::Path;
+
+/// 发现文件被外部修改后最多重新读取合并的次数
+const MAX_CONFLICT_RETRIES: usize = 2;
+
+/// settings.json 写入冲突时的错误前缀，前端据此提示稍后重试
+pub const SETTINGS_CONFLICT_ERROR_PREFIX: &str = "SettingsConflict";
+
+/// 应用管理的 settings.json 键路径，其余字段一律透传
+const APP_MANAGED_SETTINGS_PATHS: &[&[&str]] =
+    &[&["security", "auth", "selectedType"], &["mcpServers"]];
+
+/// 读取 settings.json（文件不存在或为空时返回空对象）
+pub fn read_settings_file(path: &Path) -> Result<Value, String> {
+    read_settings_snapshot(path).map(|(settings, _)| settings)
+}
+
+/// 以读-改-写方式更新 settings.json，返回写入后的内容
+///
+/// `apply` 只应修改应用管理的键；发现文件在读取后被外部修改时会在最新内容上重新调用
+pub fn update_settings_file<F>(path: &Path, mut apply: F) -> Result<Value, String>
+where
+    F: FnMut(&mut Value) -> Result<(), String>,
+{
+    for attempt in 0..=MAX_CONFLICT_RETRIES {
+        let (mut settings, read_hash) = read_settings_snapshot(path)?;
+        apply(&mut settings)?;
+
+        if current_content_hash(path)? != read_hash {
+            log::warn!(
+                "[Gemini Settings] {:?} changed while updating (attempt {}), re-reading",
+                path,
+                attempt + 1
+            );
+            continue;
+        }
+
+        write_settings_atomic(path, &settings)?;
+        return Ok(settings);
+    }
+
+    Err(format!(
+        "{}: settings.json 在写入期间被其他程序修改（已重试 {} 次），请稍后再试",
+        SETTINGS_CONFLICT_ERROR_PREFIX, MAX_CONFLICT_RETRIES
+    ))
+}
+
+/// 提取 settings.json 中不由应用管理的字段
+pub fn unmanaged_settings(settings: &Value) -> Map<String, Value> {
+    let mut passthrough = settings.as_object().cloned().unwrap_or_default();
+    for path in APP_MANAGED_SETTINGS_PATHS {
+        remove_path(&mut passthrough, path);
+    }
+    passthrough
+}
+
+/// 读取 settings.json 及其内容哈希（文件不存在时哈希为 None）
+fn read_settings_snapshot(path: &Path) -> Result<(Value, Option<String>), String> {
+    let bytes = match fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            return Ok((Value::Object(Map::new()), None));
+        }
+        Err(e) => return Err(format!("Failed to read settings.json: {}", e)),
+    };
+    let hash = Some(content_hash(&bytes));
+
+    if bytes.iter().all(|b| b.is_ascii_whitespace()) {
+        return Ok((Value::Object(Map::new()), hash));
+    }
+
+    let settings: Value = serde_json::from_slice(&bytes)
+        .map_err(|e| format!("Failed to parse settings.json: {}", e))?;
+    if !settings.is_object() {
+        return Err("Failed to parse settings.json: root must be an object".to_string());
+    }
+
+    Ok((settings, hash))
+}
+
+/// 当前文件内容的哈希（文件不存在时为 None）
+fn current_content_hash(path: &Path) -> Result<Option<String>, String> {
+    match fs::read(path) {
+        Ok(bytes) => Ok(Some(content_hash(&bytes))),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(format!("Failed to read settings.json: {}", e)),
+    }
+}
+
+fn content_hash(bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
+}
+
+/// 先写临时文件再 rename 覆盖
+fn write_settings_atomic(path: &Path, settings: &Value) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory: {}", e))?;
+    }
+
+    let content = serde_json::to_string_pretty(settings)
+        .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+
+    let tmp_path = path.with_extension("json.tmp");
+    fs::write(&tmp_path, content).map_err(|e| format!("Failed to write settings.json: {}", e))?;
+    fs::rename(&tmp_path, path).map_err(|e| {
+        let _ = fs::remove_file(&tmp_path);
+        format!("Failed to replace settings.json: {}", e)
+    })
+}
+
+/// 删除嵌套键，并移除因此变空的父对象
+fn remove_path(object: &mut Map<String, Value>, path: &[&str]) {
+    let Some((first, rest)) = path.split_first() else {
+        return;
+    };
+    if rest.is_empty() {
+        object.remove(*first);
+        return;
+    }
+    if let Some(Value::Object(child)) = object.get_mut(*first) {
+        remove_path(child, rest);
+        if child.is_empty() {
+            object.remove(*first);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn set_auth_type(settings: &mut Value) -> Result<(), String> {
+        settings["security"]["auth"]["selectedType"] = json!("gemini-api-key");
+        Ok(())
+    }
+
+    #[test]
+    fn update_preserves_unknown_fields() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("settings.json");
+        let original = json!({
+            "telemetry": { "enabled": false, "target": "local" },
+            "checkpointing": { "enabled": true },
+            "mcpServers": { "fs": { "command": "npx" } },
+            "security": { "folderTrust": { "enabled": true }, "auth": { "selectedType": "oauth-personal" } }
+        });
+        fs::write(&path, serde_json::to_string_pretty(&original).unwrap()).unwrap();
+
+        update_settings_file(&path, set_auth_type).unwrap();
+
+        let mut expected = original;
+        expected["security"]["auth"]["selectedType"] = json!("gemini-api-key");
+        assert_eq!(read_settings_file(&path).unwrap(), expected);
+    }
+
+    #[test]
+    fn returns_conflict_when_file_keeps_changing() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("settings.json");
+        fs::write(&path, "{}").unwrap();
+
+        let mut writes = 0;
+        let err = update_settings_file(&path, |settings| {
+            // 模拟 Gemini CLI 在读取之后写入了文件
+            writes += 1;
+            fs::write(&path, format!("{{\"external\": {}}}", writes)).unwrap();
+            set_auth_type(settings)
+        })
+        .unwrap_err();
+
+        assert!(err.starts_with(SETTINGS_CONFLICT_ERROR_PREFIX));
+        assert_eq!(writes, MAX_CONFLICT_RETRIES + 1);
+        assert_eq!(read_settings_file(&path).unwrap(), json!({ "external": 3 }));
+    }
+
+    #[test]
+    fn separates_app_managed_fields() {
+        let settings = json!({
+            "checkpointing": { "enabled": true },
+            "mcpServers": {},
+            "security": { "auth": { "selectedType": "oauth-personal" } }
+        });
+
+        let passthrough = unmanaged_settings(&settings);
+
+        assert_eq!(
+            Value::Object(passthrough),
+            json!({ "checkpointing": { "enabled": true } })
+        );
+    }
+}
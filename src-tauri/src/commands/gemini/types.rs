@@ -6,6 +6,9 @@
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
+use super::context_files::GeminiContextFiles;
+use crate::commands::prompt_enhancement::EnhancementRequest;
+
 // ============================================================================
 // Stream Event Types (from --output-format stream-json)
 // ============================================================================
@@ -279,6 +282,31 @@ pub struct GeminiExecutionOptions {
     /// Enable debug mode
     #[serde(default)]
     pub debug: bool,
+
+    /// Append the compiled project memory to the prompt (new sessions only)
+    #[serde(default)]
+    pub use_project_memory: bool,
+
+    /// Trace ID of the user action that started this run (generated when absent)
+    #[serde(default)]
+    pub trace_id: Option<String>,
+
+    /// Enhance the prompt with acemcp project context before running
+    #[serde(default)]
+    pub enhancement: Option<EnhancementRequest>,
+
+    /// GEMINI.md / extra context file selection (project default when absent)
+    #[serde(default)]
+    pub context_files: Option<GeminiContextFiles>,
+
+    /// Google Search switch: None keeps the CLI default (available), Some(false) excludes the tool,
+    /// Some(true) requires a model that supports it
+    #[serde(default)]
+    pub enable_search_grounding: Option<bool>,
+
+    /// Context files that take effect for this run, resolved before the first attempt
+    #[serde(skip)]
+    pub resolved_context_files: Vec<String>,
 }
 
 impl Default for GeminiExecutionOptions {
@@ -291,6 +319,12 @@ impl Default for GeminiExecutionOptions {
             include_directories: None,
             session_id: None,
             debug: false,
+            use_project_memory: false,
+            trace_id: None,
+            enhancement: None,
+            context_files: None,
+            enable_search_grounding: None,
+            resolved_context_files: Vec::new(),
         }
     }
 }
@@ -342,6 +376,10 @@ pub struct GeminiProcessHandle {
     pub pid: u32,
     /// Windows Job Object (kills all child processes when dropped); no-op on non-Windows.
     pub job_object: Option<JobObject>,
+    /// Project the process runs in
+    pub project_path: String,
+    /// Real Gemini CLI session ID, known once the init event arrives
+    pub cli_session_id: Option<String>,
 }
 
 /// Global state to track Gemini processes
@@ -407,5 +445,8 @@ pub struct GeminiSessionInfo {
     pub session_id: String,
     pub file_name: String,
     pub start_time: String,
+    /// Time of the last message (`lastUpdated` in the session file)
+    pub last_updated: String,
+    pub message_count: usize,
     pub first_message: Option<String>,
 }
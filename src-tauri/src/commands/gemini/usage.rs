@@ -7,12 +7,14 @@
  * - Model-level statistics
  * - Per-project statistics
  */
-use chrono::NaiveDate;
+use chrono::{DateTime, Local, NaiveDate};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
+use super::super::cost_comparison::{SessionTokenUsage, SessionTokens, TokenRates};
+use super::super::usage_report::UsageRecord;
 use super::config::get_gemini_dir;
 use super::types::GeminiSessionDetail;
 
@@ -34,6 +36,13 @@ pub struct GeminiSessionUsage {
     pub first_message: Option<String>,
 }
 
+impl GeminiSessionUsage {
+    /// Whether the session recorded any token counts
+    fn has_usage(&self) -> bool {
+        self.input_tokens > 0 || self.output_tokens > 0
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub struct GeminiModelUsage {
@@ -92,69 +101,76 @@ struct ModelPricing {
     cache_read: f64,
 }
 
-fn get_gemini_pricing(model: &str) -> ModelPricing {
+/// Fallback for models missing from the price table (Gemini 2.5 Pro pricing)
+const DEFAULT_PRICING: ModelPricing = ModelPricing {
+    input: 1.25,
+    output: 10.00,
+    cache_read: 0.125,
+};
+
+/// Looks up the price table entry of a model, None when the model is not listed
+fn lookup_gemini_pricing(model: &str) -> Option<ModelPricing> {
     let normalized = model.to_lowercase();
 
     // Gemini 3 Pro Preview
     if normalized.contains("gemini-3-pro") || normalized.contains("gemini_3_pro") {
-        return ModelPricing {
+        return Some(ModelPricing {
             input: 2.00,
             output: 12.00,
             cache_read: 0.20,
-        };
+        });
     }
 
     // Gemini 2.5 Pro
     if normalized.contains("2.5-pro") || normalized.contains("2_5_pro") {
-        return ModelPricing {
+        return Some(ModelPricing {
             input: 1.25,
             output: 10.00,
             cache_read: 0.125,
-        };
+        });
     }
 
     // Gemini 2.5 Flash-Lite
     if normalized.contains("2.5-flash-lite") || normalized.contains("2_5_flash_lite") {
-        return ModelPricing {
+        return Some(ModelPricing {
             input: 0.10,
             output: 0.40,
             cache_read: 0.01,
-        };
+        });
     }
 
     // Gemini 2.5 Flash
     if normalized.contains("2.5-flash") || normalized.contains("2_5_flash") {
-        return ModelPricing {
+        return Some(ModelPricing {
             input: 0.30,
             output: 2.50,
             cache_read: 0.03,
-        };
+        });
     }
 
     // Gemini 2.0 Flash
     if normalized.contains("2.0-flash") || normalized.contains("2_0_flash") {
-        return ModelPricing {
+        return Some(ModelPricing {
             input: 0.10,
             output: 0.40,
             cache_read: 0.025,
-        };
+        });
     }
 
     // Gemini 3 Flash (default for new sessions)
     if normalized.contains("gemini-3-flash") || normalized.contains("gemini_3_flash") {
-        return ModelPricing {
+        return Some(ModelPricing {
             input: 0.30,
             output: 2.50,
             cache_read: 0.03,
-        };
+        });
     }
 
-    // Default to Gemini 2.5 Pro pricing
-    ModelPricing {
-        input: 1.25,
-        output: 10.00,
-        cache_read: 0.125,
-    }
+    None
+}
+
+fn get_gemini_pricing(model: &str) -> ModelPricing {
+    lookup_gemini_pricing(model).unwrap_or(DEFAULT_PRICING)
 }
 
 fn calculate_cost(model: &str, input_tokens: u64, output_tokens: u64) -> f64 {
@@ -166,6 +182,62 @@ fn calculate_cost(model: &str, input_tokens: u64, output_tokens: u64) -> f64 {
     input_cost + output_cost
 }
 
+/// Price table rates of a Gemini model, None for models missing from the table
+///
+/// Cached tokens are not tracked for Gemini sessions, so only input/output rates apply
+pub(crate) fn gemini_token_rates(model: &str) -> Option<TokenRates> {
+    lookup_gemini_pricing(model).map(|pricing| TokenRates {
+        input: pricing.input,
+        output: pricing.output,
+        cache_write: 0.0,
+        cache_read: pricing.cache_read,
+    })
+}
+
+/// Token totals, model and cost of a single Gemini session file
+pub(crate) fn gemini_session_tokens(path: &Path) -> Option<SessionTokenUsage> {
+    let session =
+        parse_session_for_usage(&path.to_path_buf(), "").filter(GeminiSessionUsage::has_usage)?;
+    Some(SessionTokenUsage {
+        tokens: SessionTokens {
+            input_tokens: session.input_tokens,
+            output_tokens: session.output_tokens,
+            ..Default::default()
+        },
+        models: vec![session.model],
+        cost: session.total_cost,
+    })
+}
+
+/// Every Gemini session usage, timestamped at session start, for report export
+///
+/// The project is the `project:<hash>` identifier used by the usage statistics.
+/// Sessions without token data are only included with `include_empty`.
+pub(crate) fn gemini_usage_records(include_empty: bool) -> Vec<UsageRecord> {
+    collect_sessions(include_empty)
+        .into_iter()
+        .filter_map(|session| {
+            let timestamp = DateTime::parse_from_rfc3339(&session.start_time)
+                .ok()?
+                .with_timezone(&Local);
+            Some(UsageRecord {
+                engine: "gemini",
+                timestamp,
+                project_path: session.project_path,
+                model: session.model,
+                session_id: session.session_id,
+                tokens: SessionTokens {
+                    input_tokens: session.input_tokens,
+                    output_tokens: session.output_tokens,
+                    cache_creation_tokens: 0,
+                    cache_read_tokens: 0,
+                },
+                cost: session.total_cost,
+            })
+        })
+        .collect()
+}
+
 // ============================================================================
 // Session Parsing
 // ============================================================================
@@ -218,11 +290,6 @@ fn parse_session_for_usage(
         }
     }
 
-    // Skip empty sessions
-    if total_input_tokens == 0 && total_output_tokens == 0 {
-        return None;
-    }
-
     let total_cost = calculate_cost(&model, total_input_tokens, total_output_tokens);
 
     Some(GeminiSessionUsage {
@@ -239,6 +306,10 @@ fn parse_session_for_usage(
 }
 
 fn collect_all_sessions() -> Vec<GeminiSessionUsage> {
+    collect_sessions(false)
+}
+
+fn collect_sessions(include_empty: bool) -> Vec<GeminiSessionUsage> {
     let gemini_dir = match get_gemini_dir() {
         Ok(dir) => dir,
         Err(_) => return Vec::new(),
@@ -275,7 +346,9 @@ fn collect_all_sessions() -> Vec<GeminiSessionUsage> {
                 for chat_entry in chat_entries.flatten() {
                     let chat_path = chat_entry.path();
                     if chat_path.extension().and_then(|s| s.to_str()) == Some("json") {
-                        if let Some(mut session) = parse_session_for_usage(&chat_path, &project_hash)
+                        if let Some(mut session) =
+                            parse_session_for_usage(&chat_path, &project_hash)
+                                .filter(|session| include_empty || session.has_usage())
                         {
                             // Try to find project path from session data
                             // For now, use the hash as identifier
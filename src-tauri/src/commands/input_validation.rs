@@ -0,0 +1,299 @@
+//! 命令参数校验
+//!
+//! 前端传入的路径与字符串在进入文件系统操作前统一校验：
+//! - `validate_non_empty` / `validate_length`：拒绝空值与超长参数（防止日志与内存异常）
+//! - `validate_path_within`：绝对化并解析符号链接后，确认路径仍位于预期目录内（防止 `../` 穿越）
+//! - `resolve_export_target`：导出类命令的目标路径，绝对化并解析符号链接
+//!
+//! 校验失败返回带 `INVALID_ARGUMENT` 前缀的错误文本，命令边界转换为 `AppError` 时
+//! 归类为 `ErrorCode::InvalidArgument`。
+
+use std::path::{Component, Path, PathBuf, Prefix};
+
+/// 参数校验失败的错误前缀
+pub const INVALID_ARGUMENT_ERROR_PREFIX: &str = "INVALID_ARGUMENT";
+
+/// 路径参数的最大长度（字节）
+pub const MAX_PATH_LEN: usize = 4096;
+/// 名称、ID 类参数的最大长度（字节）
+pub const MAX_NAME_LEN: usize = 256;
+/// 文件内容类参数的最大长度（字节）
+pub const MAX_CONTENT_LEN: usize = 4 * 1024 * 1024;
+
+/// 构造参数校验失败的错误文本
+pub fn invalid_argument(field: &str, reason: impl std::fmt::Display) -> String {
+    format!("{}: {} {}", INVALID_ARGUMENT_ERROR_PREFIX, field, reason)
+}
+
+/// 参数不能为空（仅含空白也视为空）
+pub fn validate_non_empty(field: &str, value: &str) -> Result<(), String> {
+    if value.trim().is_empty() {
+        return Err(invalid_argument(field, "must not be empty"));
+    }
+    Ok(())
+}
+
+/// 参数长度不能超过 `max` 字节
+pub fn validate_length(field: &str, value: &str, max: usize) -> Result<(), String> {
+    if value.len() > max {
+        return Err(invalid_argument(
+            field,
+            format!("is too long ({} bytes, max {})", value.len(), max),
+        ));
+    }
+    Ok(())
+}
+
+/// 路径参数的基本校验：非空、长度、不含 NUL
+pub fn validate_path_arg(field: &str, value: &str) -> Result<(), String> {
+    validate_non_empty(field, value)?;
+    validate_length(field, value, MAX_PATH_LEN)?;
+    if value.contains('\0') {
+        return Err(invalid_argument(field, "contains a NUL character"));
+    }
+    Ok(())
+}
+
+/// 绝对化并解析符号链接
+///
+/// 存在的最长前缀用 `canonicalize` 解析，尚不存在的部分按字面拼接（其中的 `..` 按字面回退），
+/// 因此可用于即将创建的文件。只接受绝对路径。
+pub fn resolve_path(path: &Path) -> Result<PathBuf, String> {
+    if !path.is_absolute() {
+        return Err(invalid_argument(
+            "path",
+            format!("must be absolute: {}", path.display()),
+        ));
+    }
+
+    let mut existing = path.to_path_buf();
+    let mut tail: Vec<Component> = Vec::new();
+    let mut resolved = loop {
+        if let Ok(canonical) = existing.canonicalize() {
+            break canonical;
+        }
+        match path.components().nth(existing.components().count() - 1) {
+            Some(component @ (Component::Normal(_) | Component::ParentDir)) => {
+                tail.push(component);
+                existing.pop();
+            }
+            // 根目录（或盘符）本身不存在时只做字面解析
+            _ => break existing,
+        }
+    };
+
+    for component in tail.into_iter().rev() {
+        match component {
+            Component::ParentDir => {
+                resolved.pop();
+            }
+            other => resolved.push(other),
+        }
+    }
+    Ok(resolved)
+}
+
+/// 校验 `candidate` 位于 `base` 目录内，返回解析后的绝对路径
+///
+/// 相对路径相对于 `base`；两者都会解析符号链接，指向目录外的链接同样被拒绝
+pub fn validate_path_within(base: &Path, candidate: &str) -> Result<PathBuf, String> {
+    validate_path_arg("path", candidate)?;
+
+    let candidate_path = Path::new(candidate);
+    let joined = if candidate_path.is_absolute() {
+        candidate_path.to_path_buf()
+    } else {
+        base.join(candidate_path)
+    };
+    let base = resolve_path(base)?;
+    let resolved = resolve_path(&joined)?;
+
+    if !is_within(&base, &resolved) {
+        return Err(invalid_argument(
+            "path",
+            format!("is outside of {}: {}", base.display(), candidate),
+        ));
+    }
+    Ok(resolved)
+}
+
+/// 导出类命令的目标路径：必须是绝对路径，返回解析符号链接后的路径
+pub fn resolve_export_target(field: &str, target: &str) -> Result<PathBuf, String> {
+    validate_path_arg(field, target)?;
+    let resolved = resolve_path(Path::new(target.trim()))
+        .map_err(|_| invalid_argument(field, format!("must be an absolute path: {}", target)))?;
+    if resolved.parent().is_none() {
+        return Err(invalid_argument(
+            field,
+            format!("must not be a filesystem root: {}", target),
+        ));
+    }
+    Ok(resolved)
+}
+
+/// `path` 是否为 `base` 本身或其子路径（逐个组件比较，Windows 不区分大小写）
+fn is_within(base: &Path, path: &Path) -> bool {
+    let base = path_key(base);
+    let path = path_key(path);
+    path.len() >= base.len() && path[..base.len()] == base[..]
+}
+
+/// 用于比较的路径组件：盘符与 UNC 前缀统一为不带 `\\?\` 的小写形式
+fn path_key(path: &Path) -> Vec<String> {
+    path.components()
+        .map(|component| match component {
+            Component::Prefix(prefix) => prefix_key(prefix.kind()),
+            other => other.as_os_str().to_string_lossy().into_owned(),
+        })
+        .map(|part| {
+            if cfg!(windows) {
+                part.to_lowercase()
+            } else {
+                part
+            }
+        })
+        .collect()
+}
+
+/// `C:` 与 `\\?\c:`、`\\server\share` 与 `\\?\UNC\server\share` 视为相同前缀
+fn prefix_key(prefix: Prefix) -> String {
+    match prefix {
+        Prefix::Disk(drive) | Prefix::VerbatimDisk(drive) => {
+            format!("{}:", (drive as char).to_ascii_lowercase())
+        }
+        Prefix::UNC(server, share) | Prefix::VerbatimUNC(server, share) => format!(
+            r"\\{}\{}",
+            server.to_string_lossy().to_lowercase(),
+            share.to_string_lossy().to_lowercase()
+        ),
+        Prefix::Verbatim(name) => format!(r"\\?\{}", name.to_string_lossy().to_lowercase()),
+        Prefix::DeviceNS(name) => format!(r"\\.\{}", name.to_string_lossy().to_lowercase()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::OsStr;
+
+    #[test]
+    fn rejects_empty_and_oversized_arguments() {
+        assert!(validate_non_empty("name", "agent").is_ok());
+        let error = validate_non_empty("name", "  ").unwrap_err();
+        assert!(error.starts_with(INVALID_ARGUMENT_ERROR_PREFIX));
+
+        assert!(validate_length("name", "abc", 3).is_ok());
+        assert!(validate_length("name", "abcd", 3).is_err());
+
+        let base = tempfile::tempdir().unwrap();
+        assert!(validate_path_within(base.path(), "").is_err());
+        assert!(validate_path_within(base.path(), &"a".repeat(MAX_PATH_LEN + 1)).is_err());
+        assert!(validate_path_within(base.path(), "a\0b").is_err());
+    }
+
+    #[test]
+    fn rejects_parent_dir_traversal() {
+        let dir = tempfile::tempdir().unwrap();
+        let base = dir.path().join("agents");
+        std::fs::create_dir_all(&base).unwrap();
+        std::fs::write(base.join("ok.md"), "ok").unwrap();
+        std::fs::write(dir.path().join("secret.md"), "secret").unwrap();
+
+        let inside = validate_path_within(&base, "ok.md").unwrap();
+        assert_eq!(inside, base.join("ok.md").canonicalize().unwrap());
+        // Files that do not exist yet are resolved literally
+        assert!(validate_path_within(&base, "sub/new.md").is_ok());
+        assert!(validate_path_within(&base, "sub/../new.md").is_ok());
+
+        assert!(validate_path_within(&base, "../secret.md").is_err());
+        assert!(validate_path_within(&base, "missing/../../secret.md").is_err());
+        let absolute = base.join("..").join("..").join("secret.md");
+        assert!(validate_path_within(&base, &absolute.to_string_lossy()).is_err());
+        // A sibling directory sharing the prefix is not inside
+        std::fs::create_dir_all(dir.path().join("agents-other")).unwrap();
+        assert!(validate_path_within(&base, "../agents-other/x.md").is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn rejects_symlinks_pointing_outside() {
+        let dir = tempfile::tempdir().unwrap();
+        let base = dir.path().join("skills");
+        let outside = dir.path().join("outside");
+        std::fs::create_dir_all(&base).unwrap();
+        std::fs::create_dir_all(&outside).unwrap();
+        std::fs::write(outside.join("secret.md"), "secret").unwrap();
+
+        std::os::unix::fs::symlink(outside.join("secret.md"), base.join("link.md")).unwrap();
+        std::os::unix::fs::symlink(&outside, base.join("dir")).unwrap();
+        assert!(validate_path_within(&base, "link.md").is_err());
+        assert!(validate_path_within(&base, "dir/secret.md").is_err());
+        assert!(validate_path_within(&base, "dir/new.md").is_err());
+
+        // A symlinked base directory is resolved as well
+        let linked_base = dir.path().join("linked");
+        std::os::unix::fs::symlink(&base, &linked_base).unwrap();
+        std::fs::write(base.join("ok.md"), "ok").unwrap();
+        assert!(validate_path_within(&linked_base, "ok.md").is_ok());
+        assert!(validate_path_within(&linked_base, &base.join("ok.md").to_string_lossy()).is_ok());
+    }
+
+    #[test]
+    fn drive_and_unc_prefixes_compare_case_insensitively() {
+        assert_eq!(
+            prefix_key(Prefix::Disk(b'C')),
+            prefix_key(Prefix::VerbatimDisk(b'c'))
+        );
+        assert_ne!(
+            prefix_key(Prefix::Disk(b'C')),
+            prefix_key(Prefix::Disk(b'D'))
+        );
+        assert_eq!(
+            prefix_key(Prefix::UNC(OsStr::new("Server"), OsStr::new("Share"))),
+            prefix_key(Prefix::VerbatimUNC(
+                OsStr::new("server"),
+                OsStr::new("share")
+            ))
+        );
+        assert_ne!(
+            prefix_key(Prefix::UNC(OsStr::new("server"), OsStr::new("share"))),
+            prefix_key(Prefix::UNC(OsStr::new("server"), OsStr::new("other")))
+        );
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn windows_paths_with_mixed_drive_case_and_unc() {
+        assert!(is_within(
+            Path::new(r"C:\Users\me\.claude\agents"),
+            Path::new(r"\\?\c:\users\ME\.claude\agents\a.md")
+        ));
+        assert!(!is_within(
+            Path::new(r"C:\Users\me\.claude\agents"),
+            Path::new(r"D:\Users\me\.claude\agents\a.md")
+        ));
+        assert!(is_within(
+            Path::new(r"\\server\share\proj"),
+            Path::new(r"\\?\UNC\SERVER\share\proj\.claude\a.md")
+        ));
+        assert!(!is_within(
+            Path::new(r"\\server\share\proj"),
+            Path::new(r"\\server\other\proj\a.md")
+        ));
+    }
+
+    #[test]
+    fn export_target_must_be_absolute() {
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("out").join("..").join("report.csv");
+        let resolved = resolve_export_target("output_path", &target.to_string_lossy()).unwrap();
+        assert_eq!(
+            resolved,
+            dir.path().canonicalize().unwrap().join("report.csv")
+        );
+
+        let error = resolve_export_target("output_path", "relative/report.csv").unwrap_err();
+        assert!(error.starts_with(INVALID_ARGUMENT_ERROR_PREFIX));
+        assert!(resolve_export_target("output_path", " ").is_err());
+    }
+}
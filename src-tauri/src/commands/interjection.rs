@@ -0,0 +1,385 @@
+//! 任务运行中插话
+//!
+//! Codex（`codex exec`）与 Gemini（`gemini -p`）都以非交互模式运行：提示词通过 stdin 一次性写入后
+//! 即关闭输入，进程运行中无法再追加指示。插话因此采用排队模拟：
+//! - 插话按运行中任务的通道 ID 排队（同时记下 CLI 会话 ID），并发出 `task-interjection` 确认事件
+//! - 统一的任务完成事件到达后，把该任务的全部插话合并为一条提示词，以续接（resume）该会话的方式
+//!   发起下一轮，插话因此作为用户消息写入会话记录
+//! - 任务未成功结束（失败或被取消）时丢弃插话，由前端提示用户
+//! - 无法确定 CLI 会话 ID 时插话标记为失败，不会续接到其他会话
+
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::sync::broadcast::error::RecvError;
+
+use super::codex::CodexProcessState;
+use super::gemini::GeminiProcessState;
+use super::prompt_history::{dispatch_prompt, PromptTarget};
+use super::task_actions::{subscribe_task_completions, TaskCompletionEvent};
+
+/// 插话状态事件
+const INTERJECTION_EVENT: &str = "task-interjection";
+
+/// 待发送的插话：运行中任务的通道 ID → 插话队列
+static PENDING: Lazy<Mutex<HashMap<String, PendingInterjections>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// 完成事件监听只启动一次
+static LISTENER_STARTED: AtomicBool = AtomicBool::new(false);
+
+/// 插话状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InterjectionStatus {
+    /// 已排队，等待本轮结束
+    Queued,
+    /// 已随下一轮发送
+    Dispatched,
+    /// 任务未成功结束或被用户撤回，未发送
+    Discarded,
+    /// 发起下一轮失败
+    Failed,
+}
+
+/// 一条插话
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Interjection {
+    pub id: String,
+    pub engine: String,
+    /// 运行中任务的通道 ID
+    pub session_id: String,
+    pub text: String,
+    pub status: InterjectionStatus,
+    pub created_at: String,
+    pub error: Option<String>,
+}
+
+/// 某个运行中任务的插话队列
+#[derive(Debug, Clone)]
+struct PendingInterjections {
+    /// 已知的 CLI 会话 ID（Codex thread ID / Gemini 会话 ID），用于续接
+    cli_session_id: Option<String>,
+    items: Vec<Interjection>,
+}
+
+/// 按通道 ID 或 CLI 会话 ID 查找运行中的任务，返回 (通道 ID, CLI 会话 ID)
+async fn find_running_task(
+    app: &AppHandle,
+    engine: &str,
+    session_id: &str,
+) -> Option<(String, Option<String>)> {
+    match engine {
+        "codex" => {
+            let state = app.state::<CodexProcessState>();
+            let processes = state.processes.lock().await;
+            processes.iter().find_map(|(channel, handle)| {
+                (channel == session_id || handle.cli_session_id.as_deref() == Some(session_id))
+                    .then(|| (channel.clone(), handle.cli_session_id.clone()))
+            })
+        }
+        "gemini" => {
+            let state = app.state::<GeminiProcessState>();
+            let processes = state.processes.lock().await;
+            processes.iter().find_map(|(channel, handle)| {
+                (channel == session_id || handle.cli_session_id.as_deref() == Some(session_id))
+                    .then(|| (channel.clone(), handle.cli_session_id.clone()))
+            })
+        }
+        _ => None,
+    }
+}
+
+/// 续接用的 CLI 会话 ID：排队时已知的优先，否则取完成事件报告的
+///
+/// 两者都没有时返回 None，不回退到“最近一次会话”，以免插话发到同项目的其他会话
+fn resume_session_id(
+    pending: &PendingInterjections,
+    event: &TaskCompletionEvent,
+) -> Option<String> {
+    pending
+        .cli_session_id
+        .clone()
+        .or_else(|| event.cli_session_id.clone())
+}
+
+/// 把多条插话合并为下一轮的提示词
+fn merge_interjections(items: &[Interjection]) -> String {
+    items
+        .iter()
+        .map(|item| item.text.trim())
+        .filter(|text| !text.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+fn emit_status(
+    app: &AppHandle,
+    items: &mut [Interjection],
+    status: InterjectionStatus,
+    error: Option<&str>,
+) {
+    for item in items {
+        item.status = status;
+        item.error = error.map(|e| e.to_string());
+        let _ = app.emit(INTERJECTION_EVENT, &*item);
+    }
+}
+
+fn ensure_listener(app: &AppHandle) {
+    if LISTENER_STARTED.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    // 在返回前订阅，避免错过很快结束的任务
+    let mut rx = subscribe_task_completions();
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            match rx.recv().await {
+                Ok(event) => {
+                    let app = app.clone();
+                    tauri::async_runtime::spawn(
+                        async move { handle_completion(&app, event).await },
+                    );
+                }
+                Err(RecvError::Lagged(skipped)) => {
+                    log::warn!("[Interjection] Missed {} completion events", skipped);
+                }
+                Err(RecvError::Closed) => break,
+            }
+        }
+        LISTENER_STARTED.store(false, Ordering::SeqCst);
+    });
+}
+
+/// 任务结束：以续接 + 插话文本发起下一轮
+async fn handle_completion(app: &AppHandle, event: TaskCompletionEvent) {
+    let Some(channel) = event.session_id.as_deref() else {
+        return;
+    };
+    let Some(mut pending) = PENDING.lock().unwrap().remove(channel) else {
+        return;
+    };
+
+    if !event.success {
+        log::info!(
+            "[Interjection] {} task {} did not succeed, discarding {} interjection(s)",
+            event.engine,
+            channel,
+            pending.items.len()
+        );
+        emit_status(
+            app,
+            &mut pending.items,
+            InterjectionStatus::Discarded,
+            Some("任务未成功结束，插话未发送"),
+        );
+        return;
+    }
+
+    let Some(cli_session_id) = resume_session_id(&pending, &event) else {
+        log::warn!(
+            "[Interjection] {} task {} has no known CLI session, {} interjection(s) not sent",
+            event.engine,
+            channel,
+            pending.items.len()
+        );
+        emit_status(
+            app,
+            &mut pending.items,
+            InterjectionStatus::Failed,
+            Some("无法确定要续接的会话，插话未发送"),
+        );
+        return;
+    };
+    let target = PromptTarget::Resume(cli_session_id);
+    let prompt = merge_interjections(&pending.items);
+    log::info!(
+        "[Interjection] Resuming {} task {} with {} interjection(s) ({:?})",
+        event.engine,
+        channel,
+        pending.items.len(),
+        target
+    );
+
+    match dispatch_prompt(
+        app.clone(),
+        &event.engine,
+        &event.project_path,
+        target,
+        &prompt,
+        None,
+        event.mode.clone(),
+    )
+    .await
+    {
+        Ok(()) => emit_status(
+            app,
+            &mut pending.items,
+            InterjectionStatus::Dispatched,
+            None,
+        ),
+        Err(e) => {
+            log::error!("[Interjection] Failed to start next turn: {}", e);
+            emit_status(
+                app,
+                &mut pending.items,
+                InterjectionStatus::Failed,
+                Some(&e),
+            );
+        }
+    }
+}
+
+/// 向运行中的 Codex / Gemini 任务追加指示
+///
+/// `session_id` 可以是应用内通道 ID 或 CLI 会话 ID；本轮结束后插话自动作为下一轮发送
+#[tauri::command]
+pub async fn interject_running_task(
+    app: AppHandle,
+    engine: String,
+    session_id: String,
+    text: String,
+) -> Result<Interjection, String> {
+    if !matches!(engine.as_str(), "codex" | "gemini") {
+        return Err(format!(
+            "Interjection is not supported for engine: {}",
+            engine
+        ));
+    }
+    if text.trim().is_empty() {
+        return Err("Interjection text is empty".to_string());
+    }
+    let (channel, cli_session_id) = find_running_task(&app, &engine, &session_id)
+        .await
+        .ok_or_else(|| format!("No running {} task for session {}", engine, session_id))?;
+
+    let interjection = Interjection {
+        id: uuid::Uuid::new_v4().to_string(),
+        engine: engine.clone(),
+        session_id: channel.clone(),
+        text,
+        status: InterjectionStatus::Queued,
+        created_at: chrono::Utc::now().to_rfc3339(),
+        error: None,
+    };
+
+    ensure_listener(&app);
+    {
+        let mut pending = PENDING.lock().unwrap();
+        let entry = pending
+            .entry(channel.clone())
+            .or_insert_with(|| PendingInterjections {
+                cli_session_id: None,
+                items: Vec::new(),
+            });
+        entry.cli_session_id = cli_session_id.or(entry.cli_session_id.take());
+        entry.items.push(interjection.clone());
+    }
+
+    log::info!("[Interjection] Queued for {} task {}", engine, channel);
+    let _ = app.emit(INTERJECTION_EVENT, &interjection);
+    Ok(interjection)
+}
+
+/// 列出某个运行中任务待发送的插话
+#[tauri::command]
+pub async fn list_interjections(session_id: String) -> Result<Vec<Interjection>, String> {
+    let pending = PENDING.lock().unwrap();
+    Ok(pending
+        .iter()
+        .filter(|(channel, entry)| {
+            *channel == &session_id || entry.cli_session_id.as_deref() == Some(session_id.as_str())
+        })
+        .flat_map(|(_, entry)| entry.items.clone())
+        .collect())
+}
+
+/// 撤回一条尚未发送的插话
+#[tauri::command]
+pub async fn cancel_interjection(app: AppHandle, interjection_id: String) -> Result<bool, String> {
+    let removed = {
+        let mut pending = PENDING.lock().unwrap();
+        let removed = pending.values_mut().find_map(|entry| {
+            let index = entry.items.iter().position(|i| i.id == interjection_id)?;
+            Some(entry.items.remove(index))
+        });
+        pending.retain(|_, entry| !entry.items.is_empty());
+        removed
+    };
+
+    match removed {
+        Some(mut item) => {
+            emit_status(
+                &app,
+                std::slice::from_mut(&mut item),
+                InterjectionStatus::Discarded,
+                None,
+            );
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(text: &str) -> Interjection {
+        Interjection {
+            id: text.to_string(),
+            engine: "codex".to_string(),
+            session_id: "channel".to_string(),
+            text: text.to_string(),
+            status: InterjectionStatus::Queued,
+            created_at: String::new(),
+            error: None,
+        }
+    }
+
+    fn completion(cli_session_id: Option<&str>) -> TaskCompletionEvent {
+        TaskCompletionEvent {
+            engine: "codex".to_string(),
+            project_path: "/project".to_string(),
+            session_id: Some("channel".to_string()),
+            cli_session_id: cli_session_id.map(str::to_string),
+            mode: None,
+            success: true,
+            duration_ms: 0,
+            trace_id: None,
+        }
+    }
+
+    #[test]
+    fn merges_interjections_in_order_skipping_blank() {
+        let prompt = merge_interjections(&[item(" also add tests "), item("  "), item("use tabs")]);
+        assert_eq!(prompt, "also add tests\n\nuse tabs");
+    }
+
+    #[test]
+    fn resumes_only_a_known_cli_session() {
+        let queued = PendingInterjections {
+            cli_session_id: Some("thread-queued".to_string()),
+            items: vec![item("go on")],
+        };
+        let unknown = PendingInterjections {
+            cli_session_id: None,
+            items: vec![item("go on")],
+        };
+
+        assert_eq!(
+            resume_session_id(&queued, &completion(Some("thread-event"))).as_deref(),
+            Some("thread-queued")
+        );
+        assert_eq!(
+            resume_session_id(&unknown, &completion(Some("thread-event"))).as_deref(),
+            Some("thread-event")
+        );
+        assert_eq!(resume_session_id(&unknown, &completion(None)), None);
+    }
+}
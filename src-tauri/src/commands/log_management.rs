@@ -0,0 +1,217 @@
+//! 日志管理
+//!
+//! - 运行时调整日志级别（持久化到 ~/.anycode/logging.json）
+//! - 查询当前日志文件路径与大小、打开日志目录
+//! - 导出诊断包：最近的应用日志、最近 5 次任务的执行追踪、诊断报告与脱敏配置，
+//!   所有文本写入 zip 前再打码一次，保证不含明文密钥
+
+use serde::Serialize;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tauri::AppHandle;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+use super::diagnostics::collect_diagnostics;
+use super::file_operations::open_directory_in_explorer;
+use super::input_validation::resolve_export_target;
+use crate::utils::config_utils::ConfigPathBuilder;
+use crate::utils::log_file::{
+    get_log_dir, list_log_files, log_file_path, parse_log_level, redact_sensitive,
+    save_logging_config, LoggingConfig, MAX_LOG_FILES, MAX_LOG_FILE_BYTES,
+};
+
+/// 诊断包中包含的应用日志文件数（当前文件 + 最近一次滚动的文件）
+const BUNDLE_LOG_FILES: usize = 2;
+
+/// 诊断包中包含的任务追踪数
+const BUNDLE_TASK_TRACES: usize = 5;
+
+/// 日志文件信息
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogFileInfo {
+    /// 当前日志文件
+    pub path: String,
+    pub directory: String,
+    /// 当前日志文件大小（字节）
+    pub size_bytes: u64,
+    /// 包含滚动文件在内的总大小（字节）
+    pub total_size_bytes: u64,
+    pub file_count: usize,
+    pub level: String,
+    pub max_file_bytes: u64,
+    pub max_files: usize,
+}
+
+/// 运行时调整日志级别并保存
+#[tauri::command]
+pub async fn set_log_level(level: String) -> Result<String, String> {
+    let filter = parse_log_level(&level)?;
+    log::set_max_level(filter);
+
+    let level = filter.to_string().to_lowercase();
+    save_logging_config(&LoggingConfig {
+        level: level.clone(),
+    })?;
+    log::info!("[LogManagement] Log level set to {}", level);
+    Ok(level)
+}
+
+/// 获取当前日志文件路径、大小与级别
+#[tauri::command]
+pub async fn get_log_file_info() -> Result<LogFileInfo, String> {
+    let dir = get_log_dir()?;
+    let path = log_file_path(&dir, 0);
+    let file_size = |p: &Path| fs::metadata(p).map(|m| m.len()).unwrap_or(0);
+    let files = list_log_files(&dir);
+
+    Ok(LogFileInfo {
+        path: path.to_string_lossy().to_string(),
+        directory: dir.to_string_lossy().to_string(),
+        size_bytes: file_size(&path),
+        total_size_bytes: files.iter().map(|p| file_size(p)).sum(),
+        file_count: files.len(),
+        level: log::max_level().to_string().to_lowercase(),
+        max_file_bytes: MAX_LOG_FILE_BYTES,
+        max_files: MAX_LOG_FILES,
+    })
+}
+
+/// 在系统文件管理器中打开日志目录
+#[tauri::command]
+pub async fn open_log_directory() -> Result<(), String> {
+    let dir = get_log_dir()?;
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create log directory: {}", e))?;
+    open_directory_in_explorer(dir.to_string_lossy().to_string()).await
+}
+
+/// 导出诊断包（zip），返回文件路径
+///
+/// 未指定 `target_path` 时保存到 ~/.anycode/diagnostics/
+#[tauri::command]
+pub async fn export_diagnostic_bundle(
+    app: AppHandle,
+    target_path: Option<String>,
+) -> Result<String, String> {
+    let target_path = target_path
+        .map(|path| resolve_export_target("target_path", &path))
+        .transpose()?;
+    let report = collect_diagnostics(app).await?;
+    let report_json = serde_json::to_string_pretty(&report)
+        .map_err(|e| format!("Failed to serialize diagnostics: {}", e))?;
+    let config_json = serde_json::to_string_pretty(&report.config)
+        .map_err(|e| format!("Failed to serialize config: {}", e))?;
+
+    tokio::task::spawn_blocking(move || {
+        let anycode_dir = ConfigPathBuilder::from_home_subdir(".anycode")?;
+        let target = match target_path {
+            Some(path) => path,
+            None => anycode_dir.build("diagnostics").join(format!(
+                "anycode-diagnostics-{}.zip",
+                chrono::Local::now().format("%Y%m%d-%H%M%S")
+            )),
+        };
+
+        let logs = list_log_files(&get_log_dir()?)
+            .into_iter()
+            .take(BUNDLE_LOG_FILES)
+            .collect::<Vec<_>>();
+        let traces = recent_trace_logs(&anycode_dir.build("traces"), BUNDLE_TASK_TRACES);
+
+        let mut entries = vec![
+            ("diagnostics.json".to_string(), report_json),
+            ("config.json".to_string(), config_json),
+        ];
+        for path in logs.iter().chain(traces.iter()) {
+            let name = match path.strip_prefix(&anycode_dir) {
+                Ok(relative) => relative.to_string_lossy().replace('\\', "/"),
+                Err(_) => continue,
+            };
+            match fs::read(path) {
+                Ok(bytes) => entries.push((name, String::from_utf8_lossy(&bytes).to_string())),
+                Err(e) => log::warn!("[LogManagement] Skipping {:?}: {}", path, e),
+            }
+        }
+
+        write_bundle(&target, &entries)?;
+        log::info!(
+            "[LogManagement] Diagnostic bundle exported to {:?} ({} entries)",
+            target,
+            entries.len()
+        );
+        Ok(target.to_string_lossy().to_string())
+    })
+    .await
+    .map_err(|e| format!("Diagnostic bundle task failed: {}", e))?
+}
+
+/// 最近修改的任务追踪日志（events.jsonl），从新到旧
+fn recent_trace_logs(traces_dir: &Path, limit: usize) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(traces_dir) else {
+        return Vec::new();
+    };
+
+    let mut traces: Vec<_> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let events = entry.path().join("events.jsonl");
+            let modified = fs::metadata(&events).and_then(|m| m.modified()).ok()?;
+            Some((modified, events))
+        })
+        .collect();
+    traces.sort_by(|a, b| b.0.cmp(&a.0));
+    traces
+        .into_iter()
+        .take(limit)
+        .map(|(_, events)| events)
+        .collect()
+}
+
+/// 写入 zip，每个条目在写入前打码
+fn write_bundle(target: &Path, entries: &[(String, String)]) -> Result<(), String> {
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create directory {:?}: {}", parent, e))?;
+    }
+    let file = File::create(target).map_err(|e| format!("Failed to create {:?}: {}", target, e))?;
+
+    let mut zip = ZipWriter::new(file);
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+    for (name, content) in entries {
+        zip.start_file(name.as_str(), options)
+            .map_err(|e| format!("Failed to add {} to bundle: {}", name, e))?;
+        zip.write_all(redact_sensitive(content).as_bytes())
+            .map_err(|e| format!("Failed to write {} to bundle: {}", name, e))?;
+    }
+    zip.finish()
+        .map_err(|e| format!("Failed to finish bundle: {}", e))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+
+    #[test]
+    fn bundle_entries_are_redacted() {
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("out").join("bundle.zip");
+        let entries = vec![(
+            "logs/app.log".to_string(),
+            "calling api with api_key=sk-abcdefghijklmnop\n".to_string(),
+        )];
+        write_bundle(&target, &entries).unwrap();
+
+        let mut archive = zip::ZipArchive::new(File::open(&target).unwrap()).unwrap();
+        let mut content = String::new();
+        archive
+            .by_name("logs/app.log")
+            .unwrap()
+            .read_to_string(&mut content)
+            .unwrap();
+        assert_eq!(content, "calling api with api_key=<redacted>\n");
+    }
+}
@@ -0,0 +1,400 @@
+//! MCP 工具调用统计
+//!
+//! 会话里的 MCP 调用只以 `mcp__server__tool` 这种拼接名出现，本模块把它们结构化：
+//! - 拆出 server 与工具名
+//! - 按调用 ID 配对 call 与 output 事件，计算耗时并提取 is_error
+//! - 按 server / 工具聚合调用次数、平均耗时与失败率
+//!
+//! Claude 与 Codex 会话的事件格式不同，但都先转换为 [`McpCallTracker`] 的调用 / 结果事件，
+//! 配对与耗时计算只实现一次。
+
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::ops::ControlFlow;
+use std::path::Path;
+
+use super::codex::session_converter::{
+    parse_claude_content_blocks, ClaudeContentBlock, ClaudeMessage,
+};
+use super::session_utils::resolve_session_file;
+use crate::utils::jsonl_reader::for_each_line;
+
+/// MCP 工具名前缀
+const MCP_TOOL_PREFIX: &str = "mcp__";
+
+/// 单次 MCP 工具调用
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct McpCall {
+    pub call_id: String,
+    pub server: String,
+    pub tool: String,
+    pub started_at: Option<String>,
+    /// 调用耗时，没有配对到结果时为 None
+    pub duration_ms: Option<u64>,
+    pub is_error: bool,
+}
+
+/// 按 server 或 server + 工具聚合的调用统计
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct McpCallStats {
+    pub server: String,
+    /// 按 server 聚合时为 None
+    pub tool: Option<String>,
+    pub call_count: usize,
+    pub failed_count: usize,
+    /// 失败次数占调用次数的比例（0 ~ 1）
+    pub failure_rate: f64,
+    /// 有耗时记录的调用的平均耗时
+    pub avg_duration_ms: Option<f64>,
+}
+
+/// 会话的 MCP 调用汇总
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct McpSessionSummary {
+    pub session_id: String,
+    pub engine: String,
+    pub total_calls: usize,
+    pub failed_calls: usize,
+    pub servers: Vec<McpCallStats>,
+    pub tools: Vec<McpCallStats>,
+}
+
+/// 拆分 `mcp__server__tool` 形式的工具名
+///
+/// server 名中不会出现 `__`，因此以第一个 `__` 为界，工具名中的 `__` 原样保留
+pub fn parse_mcp_tool_name(name: &str) -> Option<(&str, &str)> {
+    let rest = name.strip_prefix(MCP_TOOL_PREFIX)?;
+    let (server, tool) = rest.split_once("__")?;
+    (!server.is_empty() && !tool.is_empty()).then_some((server, tool))
+}
+
+/// 按调用 ID 配对 MCP 调用与结果
+#[derive(Debug, Default)]
+pub struct McpCallTracker {
+    calls: Vec<McpCall>,
+    index: HashMap<String, usize>,
+}
+
+impl McpCallTracker {
+    /// 记录一次工具调用，非 MCP 工具直接忽略
+    pub fn on_call(&mut self, call_id: &str, tool_name: &str, timestamp: Option<&str>) {
+        let Some((server, tool)) = parse_mcp_tool_name(tool_name) else {
+            return;
+        };
+        self.begin(call_id, server, tool, timestamp);
+    }
+
+    /// 记录一次已知 server / 工具名的调用（Codex 的 mcp_tool_call_begin 事件）
+    pub fn begin(&mut self, call_id: &str, server: &str, tool: &str, timestamp: Option<&str>) {
+        if self.index.contains_key(call_id) {
+            return;
+        }
+        self.index.insert(call_id.to_string(), self.calls.len());
+        self.calls.push(McpCall {
+            call_id: call_id.to_string(),
+            server: server.to_string(),
+            tool: tool.to_string(),
+            started_at: timestamp.map(str::to_string),
+            duration_ms: None,
+            is_error: false,
+        });
+    }
+
+    /// 记录调用结果，耗时按调用与结果的时间戳计算；未知的调用 ID（非 MCP 工具）直接忽略
+    pub fn on_result(&mut self, call_id: &str, timestamp: Option<&str>, is_error: bool) {
+        let Some(call) = self.index.get(call_id).map(|&i| &mut self.calls[i]) else {
+            return;
+        };
+        if call.duration_ms.is_none() {
+            call.duration_ms = call
+                .started_at
+                .as_deref()
+                .zip(timestamp)
+                .and_then(|(start, end)| elapsed_ms(start, end));
+        }
+        call.is_error |= is_error;
+    }
+
+    /// 记录自带耗时的结果（Codex 的 mcp_tool_call_end 事件），优先于时间戳推算的耗时
+    pub fn on_end(&mut self, call_id: &str, duration_ms: Option<u64>, is_error: bool) {
+        let Some(call) = self.index.get(call_id).map(|&i| &mut self.calls[i]) else {
+            return;
+        };
+        if duration_ms.is_some() {
+            call.duration_ms = duration_ms;
+        }
+        call.is_error |= is_error;
+    }
+
+    pub fn into_calls(self) -> Vec<McpCall> {
+        self.calls
+    }
+}
+
+/// 两个 RFC 3339 时间戳之间的毫秒数
+pub(crate) fn elapsed_ms(start: &str, end: &str) -> Option<u64> {
+    let start = chrono::DateTime::parse_from_rfc3339(start).ok()?;
+    let end = chrono::DateTime::parse_from_rfc3339(end).ok()?;
+    u64::try_from((end - start).num_milliseconds()).ok()
+}
+
+/// 按 server / 工具聚合调用
+pub fn summarize_mcp_calls(session_id: &str, engine: &str, calls: &[McpCall]) -> McpSessionSummary {
+    let mut servers: BTreeMap<&str, Vec<&McpCall>> = BTreeMap::new();
+    let mut tools: BTreeMap<(&str, &str), Vec<&McpCall>> = BTreeMap::new();
+    for call in calls {
+        servers.entry(&call.server).or_default().push(call);
+        tools
+            .entry((&call.server, &call.tool))
+            .or_default()
+            .push(call);
+    }
+
+    McpSessionSummary {
+        session_id: session_id.to_string(),
+        engine: engine.to_string(),
+        total_calls: calls.len(),
+        failed_calls: calls.iter().filter(|c| c.is_error).count(),
+        servers: servers
+            .into_iter()
+            .map(|(server, calls)| aggregate(server, None, &calls))
+            .collect(),
+        tools: tools
+            .into_iter()
+            .map(|((server, tool), calls)| aggregate(server, Some(tool), &calls))
+            .collect(),
+    }
+}
+
+fn aggregate(server: &str, tool: Option<&str>, calls: &[&McpCall]) -> McpCallStats {
+    let failed_count = calls.iter().filter(|c| c.is_error).count();
+    let durations: Vec<u64> = calls.iter().filter_map(|c| c.duration_ms).collect();
+    let avg_duration_ms = (!durations.is_empty())
+        .then(|| durations.iter().sum::<u64>() as f64 / durations.len() as f64);
+
+    McpCallStats {
+        server: server.to_string(),
+        tool: tool.map(str::to_string),
+        call_count: calls.len(),
+        failed_count,
+        failure_rate: failed_count as f64 / calls.len() as f64,
+        avg_duration_ms,
+    }
+}
+
+/// 收集 Claude 会话中的 MCP 调用（tool_use 与 tool_result 按 tool_use_id 配对）
+fn collect_claude_mcp_calls(session_path: &Path) -> Result<Vec<McpCall>, String> {
+    let mut tracker = McpCallTracker::default();
+
+    for_each_line(session_path, |_, line| {
+        let Ok(msg) = serde_json::from_str::<ClaudeMessage>(line) else {
+            return ControlFlow::Continue(());
+        };
+        let timestamp = msg.timestamp.as_deref();
+        if let Some(message) = &msg.message {
+            for block in parse_claude_content_blocks(&message.content) {
+                match block {
+                    ClaudeContentBlock::ToolUse { id, name, .. } => {
+                        tracker.on_call(&id, &name, timestamp)
+                    }
+                    ClaudeContentBlock::ToolResult {
+                        tool_use_id,
+                        is_error,
+                        ..
+                    } => tracker.on_result(&tool_use_id, timestamp, is_error.unwrap_or(false)),
+                    _ => {}
+                }
+            }
+        }
+        ControlFlow::Continue(())
+    })
+    .map_err(|e| format!("Failed to read session file: {}", e))?;
+
+    Ok(tracker.into_calls())
+}
+
+/// 收集 Codex 会话中的 MCP 调用
+///
+/// - response_item 的 function_call / function_call_output 按 call_id 配对
+/// - event_msg 的 mcp_tool_call_begin / mcp_tool_call_end 自带 server、工具名与耗时
+fn collect_codex_mcp_calls(session_path: &Path) -> Result<Vec<McpCall>, String> {
+    let mut tracker = McpCallTracker::default();
+
+    for_each_line(session_path, |_, line| {
+        let Ok(event) = serde_json::from_str::<Value>(line) else {
+            return ControlFlow::Continue(());
+        };
+        let timestamp = event["timestamp"].as_str();
+        let payload = &event["payload"];
+        let call_id = payload["call_id"].as_str().unwrap_or_default();
+        if call_id.is_empty() {
+            return ControlFlow::Continue(());
+        }
+
+        match (event["type"].as_str(), payload["type"].as_str()) {
+            (Some("response_item"), Some("function_call")) => {
+                tracker.on_call(
+                    call_id,
+                    payload["name"].as_str().unwrap_or_default(),
+                    timestamp,
+                );
+            }
+            (Some("response_item"), Some("function_call_output")) => {
+                tracker.on_result(call_id, timestamp, codex_output_failed(&payload["output"]));
+            }
+            (Some("event_msg"), Some("mcp_tool_call_begin")) => {
+                let invocation = &payload["invocation"];
+                if let (Some(server), Some(tool)) =
+                    (invocation["server"].as_str(), invocation["tool"].as_str())
+                {
+                    tracker.begin(call_id, server, tool, timestamp);
+                }
+            }
+            (Some("event_msg"), Some("mcp_tool_call_end")) => {
+                let invocation = &payload["invocation"];
+                if let (Some(server), Some(tool)) =
+                    (invocation["server"].as_str(), invocation["tool"].as_str())
+                {
+                    // 没有 begin 事件时以 end 事件补齐调用
+                    tracker.begin(call_id, server, tool, timestamp);
+                }
+                let result = &payload["result"];
+                let is_error =
+                    result.get("Err").is_some() || result["Ok"]["isError"].as_bool() == Some(true);
+                tracker.on_end(call_id, codex_duration_ms(&payload["duration"]), is_error);
+            }
+            _ => {}
+        }
+        ControlFlow::Continue(())
+    })
+    .map_err(|e| format!("Failed to read session file: {}", e))?;
+
+    Ok(tracker.into_calls())
+}
+
+/// function_call_output 是否表示失败
+///
+/// `output` 通常是序列化后的 JSON 字符串（`{"output": ..., "success": false}`），
+/// 需先解析再读取 `success`；纯文本输出视为成功
+fn codex_output_failed(output: &Value) -> bool {
+    let failed = |value: &Value| value["success"].as_bool() == Some(false);
+    match output.as_str() {
+        Some(text) => serde_json::from_str::<Value>(text).is_ok_and(|value| failed(&value)),
+        None => failed(output),
+    }
+}
+
+/// 解析 Codex 的耗时字段（`{ "secs": 1, "nanos": 500000000 }` 或毫秒数）
+pub(crate) fn codex_duration_ms(duration: &Value) -> Option<u64> {
+    if let Some(ms) = duration.as_u64() {
+        return Some(ms);
+    }
+    let secs = duration["secs"].as_u64()?;
+    let nanos = duration["nanos"].as_u64().unwrap_or(0);
+    Some(secs * 1000 + nanos / 1_000_000)
+}
+
+/// 获取会话中 MCP 调用按 server / 工具聚合的统计
+///
+/// `project_id` 的含义与 [`resolve_session_file`] 相同，Codex 会话可传空字符串
+#[tauri::command]
+pub async fn get_session_mcp_summary(
+    session_id: String,
+    project_id: String,
+    engine: String,
+) -> Result<McpSessionSummary, String> {
+    tokio::task::spawn_blocking(move || {
+        let path = resolve_session_file(&engine, &session_id, &project_id)?;
+        let calls = match engine.as_str() {
+            "claude" => collect_claude_mcp_calls(&path)?,
+            "codex" => collect_codex_mcp_calls(&path)?,
+            other => {
+                return Err(format!(
+                    "MCP summary is not supported for engine: {}",
+                    other
+                ))
+            }
+        };
+        log::info!(
+            "[McpCalls] Found {} MCP calls in {} session {}",
+            calls.len(),
+            engine,
+            session_id
+        );
+        Ok(summarize_mcp_calls(&session_id, &engine, &calls))
+    })
+    .await
+    .map_err(|e| format!("MCP summary task failed: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn splits_server_and_tool_names() {
+        assert_eq!(
+            parse_mcp_tool_name("mcp__filesystem__read_file"),
+            Some(("filesystem", "read_file"))
+        );
+        assert_eq!(
+            parse_mcp_tool_name("mcp__acemcp__search__context"),
+            Some(("acemcp", "search__context"))
+        );
+        assert_eq!(parse_mcp_tool_name("mcp__filesystem"), None);
+        assert_eq!(parse_mcp_tool_name("Bash"), None);
+    }
+
+    #[test]
+    fn pairs_claude_calls_with_results() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("session.jsonl");
+        let lines = [
+            r#"{"type":"assistant","timestamp":"2025-01-01T00:00:00.000Z","message":{"role":"assistant","content":[{"type":"tool_use","id":"t1","name":"mcp__filesystem__read_file","input":{}},{"type":"tool_use","id":"t2","name":"Bash","input":{}}]}}"#,
+            r#"{"type":"user","timestamp":"2025-01-01T00:00:00.120Z","message":{"role":"user","content":[{"type":"tool_result","tool_use_id":"t1","content":"ok"},{"type":"tool_result","tool_use_id":"t2","content":"ok"}]}}"#,
+            r#"{"type":"assistant","timestamp":"2025-01-01T00:00:01.000Z","message":{"role":"assistant","content":[{"type":"tool_use","id":"t3","name":"mcp__filesystem__read_file","input":{}}]}}"#,
+            r#"{"type":"user","timestamp":"2025-01-01T00:00:01.080Z","message":{"role":"user","content":[{"type":"tool_result","tool_use_id":"t3","content":"denied","is_error":true}]}}"#,
+        ];
+        fs::write(&path, lines.join("\n")).unwrap();
+
+        let calls = collect_claude_mcp_calls(&path).unwrap();
+        let summary = summarize_mcp_calls("s1", "claude", &calls);
+        assert_eq!(summary.total_calls, 2);
+        assert_eq!(summary.failed_calls, 1);
+
+        let stats = &summary.tools[0];
+        assert_eq!(stats.server, "filesystem");
+        assert_eq!(stats.tool.as_deref(), Some("read_file"));
+        assert_eq!(stats.call_count, 2);
+        assert_eq!(stats.avg_duration_ms, Some(100.0));
+        assert_eq!(stats.failure_rate, 0.5);
+    }
+
+    #[test]
+    fn codex_end_events_carry_duration_and_errors() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("rollout.jsonl");
+        let lines = [
+            r#"{"type":"response_item","timestamp":"2025-01-01T00:00:00.000Z","payload":{"type":"function_call","name":"mcp__github__search","arguments":"{}","call_id":"c1"}}"#,
+            r#"{"type":"response_item","timestamp":"2025-01-01T00:00:02.000Z","payload":{"type":"function_call_output","call_id":"c1","output":"ok"}}"#,
+            r#"{"type":"response_item","timestamp":"2025-01-01T00:00:02.500Z","payload":{"type":"function_call","name":"mcp__github__fetch","arguments":"{}","call_id":"c3"}}"#,
+            r#"{"type":"response_item","timestamp":"2025-01-01T00:00:02.600Z","payload":{"type":"function_call_output","call_id":"c3","output":"{\"output\":\"not found\",\"success\":false}"}}"#,
+            r#"{"type":"event_msg","timestamp":"2025-01-01T00:00:03.000Z","payload":{"type":"mcp_tool_call_end","call_id":"c2","invocation":{"server":"github","tool":"search"},"duration":{"secs":0,"nanos":250000000},"result":{"Err":"timeout"}}}"#,
+        ];
+        fs::write(&path, lines.join("\n")).unwrap();
+
+        let calls = collect_codex_mcp_calls(&path).unwrap();
+        assert_eq!(calls.len(), 3);
+        assert_eq!(calls[0].duration_ms, Some(2000));
+        assert!(!calls[0].is_error);
+        assert_eq!(calls[1].duration_ms, Some(100));
+        assert!(calls[1].is_error);
+        assert_eq!(calls[2].duration_ms, Some(250));
+        assert!(calls[2].is_error);
+    }
+}
@@ -0,0 +1,252 @@
+//! 后端用户可见文案
+//!
+//! 撤回提示、错误说明、provider 操作结果等会直接展示给用户的文案从内嵌的文案表取值
+//! （`locales/zh-CN.json`、`locales/en-US.json`，扁平的 "分类.名称" 键），
+//! 不再在各处硬编码中文。键统一在 [`MessageKey`] 中定义，文案中的 `{name}` 占位符由
+//! [`t_with`] 替换。
+//!
+//! 语言由前端启动及切换界面语言时通过 `set_backend_locale` 同步，默认中文；
+//! 当前语言缺少某个键时回退到中文并记录日志。
+
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::RwLock;
+
+/// 文案语言
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Locale {
+    ZhCn,
+    EnUs,
+}
+
+impl Locale {
+    /// 解析前端的语言代码，zh-TW 等中文变体使用中文文案，其余语言使用英文
+    pub fn from_code(code: &str) -> Self {
+        if code.to_lowercase().starts_with("zh") {
+            Locale::ZhCn
+        } else {
+            Locale::EnUs
+        }
+    }
+}
+
+static BACKEND_LOCALE: RwLock<Locale> = RwLock::new(Locale::ZhCn);
+
+/// 当前的文案语言
+pub fn current_locale() -> Locale {
+    *BACKEND_LOCALE
+        .read()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// 声明文案键：枚举值与文案表中的键一一对应
+macro_rules! message_keys {
+    ($($variant:ident => $key:literal,)*) => {
+        /// 文案表的键
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub enum MessageKey {
+            $($variant,)*
+        }
+
+        impl MessageKey {
+            #[cfg(test)]
+            const ALL: &'static [MessageKey] = &[$(MessageKey::$variant,)*];
+
+            pub fn as_str(self) -> &'static str {
+                match self {
+                    $(MessageKey::$variant => $key,)*
+                }
+            }
+        }
+    };
+}
+
+message_keys! {
+    ErrorAuthFailed => "error.authFailed",
+    ErrorRateLimited => "error.rateLimited",
+    ErrorQuotaExceeded => "error.quotaExceeded",
+    ErrorModelNotFound => "error.modelNotFound",
+    ErrorNetworkTimeout => "error.networkTimeout",
+    ErrorNetworkError => "error.networkError",
+    ErrorServiceUnavailable => "error.serviceUnavailable",
+    ErrorBinaryNotFound => "error.binaryNotFound",
+    ErrorSessionBusy => "error.sessionBusy",
+    ErrorSessionReadOnly => "error.sessionReadOnly",
+    ErrorContextOverflow => "error.contextOverflow",
+    ErrorGitDirty => "error.gitDirty",
+    ErrorInvalidArgument => "error.invalidArgument",
+    ErrorUnknown => "error.unknown",
+
+    RewindGitDisabled => "rewind.gitDisabled",
+    RewindNoValidCommit => "rewind.noValidCommit",
+    RewindNoGitRecord => "rewind.noGitRecord",
+    RewindProjectPromptWithoutRecord => "rewind.projectPromptWithoutRecord",
+    RewindCliPrompt => "rewind.cliPrompt",
+    RewindCodeBlockedGitDisabled => "rewind.codeBlockedGitDisabled",
+    RewindCodeBlockedNoRecord => "rewind.codeBlockedNoRecord",
+    RewindRevertFailedRolledBack => "rewind.revertFailedRolledBack",
+    RewindTruncateFailedRolledBack => "rewind.truncateFailedRolledBack",
+    RewindTruncateFailedRollbackFailed => "rewind.truncateFailedRollbackFailed",
+    RewindRecordsTruncateFailedRolledBack => "rewind.recordsTruncateFailedRolledBack",
+    RewindRecordsTruncateFailedRollbackFailed => "rewind.recordsTruncateFailedRollbackFailed",
+    RewindSelectiveUnsupported => "rewind.selectiveUnsupported",
+    RewindSingleNoChanges => "rewind.singleNoChanges",
+    RewindSingleAlreadyReverted => "rewind.singleAlreadyReverted",
+    RewindSingleConflict => "rewind.singleConflict",
+
+    ConversionSessionEmpty => "conversion.sessionEmpty",
+    ConversionSessionIncomplete => "conversion.sessionIncomplete",
+    ConversionAlreadyTargetEngine => "conversion.alreadyTargetEngine",
+    ConversionSessionNotFound => "conversion.sessionNotFound",
+
+    ProviderSwitched => "provider.switched",
+    ProviderCleared => "provider.cleared",
+    ProviderAdded => "provider.added",
+    ProviderUpdated => "provider.updated",
+    ProviderDeleted => "provider.deleted",
+    ProviderReordered => "provider.reordered",
+    ProviderIdExists => "provider.idExists",
+    ProviderNotFound => "provider.notFound",
+    ProviderAuthRequired => "provider.authRequired",
+    ProviderEngineSwitched => "provider.engineSwitched",
+    ProviderEngineAdded => "provider.engineAdded",
+    ProviderEngineUpdated => "provider.engineUpdated",
+    ProviderEngineDeleted => "provider.engineDeleted",
+    ProviderEngineReordered => "provider.engineReordered",
+    ProviderGeminiCleared => "provider.geminiCleared",
+    ProviderCodexCleared => "provider.codexCleared",
+    ProviderConnectionTested => "provider.connectionTested",
+    ProviderConnectionReachable => "provider.connectionReachable",
+    ProviderConnectionStatus => "provider.connectionStatus",
+    ProviderConnectionFailed => "provider.connectionFailed",
+    ProviderCredentialsRejected => "provider.credentialsRejected",
+
+    ReportDay => "report.day",
+    ReportProject => "report.project",
+    ReportProvider => "report.provider",
+    ReportModel => "report.model",
+    ReportEngine => "report.engine",
+    ReportInputTokens => "report.inputTokens",
+    ReportOutputTokens => "report.outputTokens",
+    ReportCacheCreationTokens => "report.cacheCreationTokens",
+    ReportCacheReadTokens => "report.cacheReadTokens",
+    ReportTotalTokens => "report.totalTokens",
+    ReportCost => "report.cost",
+    ReportTaskCount => "report.taskCount",
+    ReportAvgTaskDuration => "report.avgTaskDuration",
+
+    CodexConfigUnsupported => "codex.configUnsupported",
+    CodexConfigUnsupportedStrict => "codex.configUnsupportedStrict",
+    BackupTargetNotSet => "backup.targetNotSet",
+    BackupAlreadyRunning => "backup.alreadyRunning",
+    BackupNotFound => "backup.notFound",
+    RateLimitWaitTimeout => "rateLimit.waitTimeout",
+    WorkspaceSessionDeleted => "workspace.sessionDeleted",
+    WorkspaceSessionArchived => "workspace.sessionArchived",
+}
+
+type MessageTable = HashMap<String, String>;
+
+fn parse_table(locale: Locale, content: &str) -> MessageTable {
+    serde_json::from_str(content).unwrap_or_else(|e| {
+        log::error!("[Messages] Invalid {:?} message table: {}", locale, e);
+        MessageTable::new()
+    })
+}
+
+static ZH_CN: Lazy<MessageTable> =
+    Lazy::new(|| parse_table(Locale::ZhCn, include_str!("../../locales/zh-CN.json")));
+
+static EN_US: Lazy<MessageTable> =
+    Lazy::new(|| parse_table(Locale::EnUs, include_str!("../../locales/en-US.json")));
+
+fn table(locale: Locale) -> &'static MessageTable {
+    match locale {
+        Locale::ZhCn => &ZH_CN,
+        Locale::EnUs => &EN_US,
+    }
+}
+
+/// 按指定语言取文案，缺失时回退到中文，中文也缺失时返回键名
+pub fn t_in(key: MessageKey, locale: Locale) -> &'static str {
+    if let Some(text) = table(locale).get(key.as_str()) {
+        return text;
+    }
+    log::warn!(
+        "[Messages] Missing {:?} message for '{}', falling back to zh-CN",
+        locale,
+        key.as_str()
+    );
+    match table(Locale::ZhCn).get(key.as_str()) {
+        Some(text) => text,
+        None => key.as_str(),
+    }
+}
+
+/// 按当前语言取文案
+pub fn t(key: MessageKey) -> String {
+    t_in(key, current_locale()).to_string()
+}
+
+/// 按当前语言取文案并替换 `{name}` 占位符
+pub fn t_with(key: MessageKey, args: &[(&str, &dyn fmt::Display)]) -> String {
+    format_message(t_in(key, current_locale()), args)
+}
+
+fn format_message(template: &str, args: &[(&str, &dyn fmt::Display)]) -> String {
+    let mut text = template.to_string();
+    for (name, value) in args {
+        text = text.replace(&format!("{{{}}}", name), &value.to_string());
+    }
+    text
+}
+
+/// 同步界面语言，决定后端返回的用户可见文案使用中文还是英文
+#[tauri::command]
+pub fn set_backend_locale(locale: String) -> Result<(), String> {
+    let locale = Locale::from_code(&locale);
+    *BACKEND_LOCALE
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner()) = locale;
+    log::info!("[Messages] Backend locale set to {:?}", locale);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_key_exists_in_both_tables() {
+        for key in MessageKey::ALL {
+            for locale in [Locale::ZhCn, Locale::EnUs] {
+                assert!(
+                    table(locale).contains_key(key.as_str()),
+                    "{:?} missing {}",
+                    locale,
+                    key.as_str()
+                );
+            }
+        }
+        assert_eq!(ZH_CN.len(), MessageKey::ALL.len());
+        assert_eq!(EN_US.len(), MessageKey::ALL.len());
+    }
+
+    #[test]
+    fn formats_placeholders_per_locale() {
+        let args: &[(&str, &dyn fmt::Display)] = &[("index", &3)];
+        let zh = format_message(
+            t_in(MessageKey::RewindCodeBlockedNoRecord, Locale::ZhCn),
+            args,
+        );
+        let en = format_message(
+            t_in(MessageKey::RewindCodeBlockedNoRecord, Locale::EnUs),
+            args,
+        );
+        assert!(zh.contains("#3") && zh.starts_with("无法回滚代码"));
+        assert!(en.contains("#3") && en.starts_with("Cannot revert code"));
+        assert_eq!(Locale::from_code("zh-TW"), Locale::ZhCn);
+        assert_eq!(Locale::from_code("en"), Locale::EnUs);
+    }
+}
@@ -0,0 +1,470 @@
+//! 启动数据迁移
+//!
+//! 统一管理本地数据格式的演进：每个迁移步骤有固定的 id、描述、影响的数据类别和幂等的执行函数，
+//! 按注册顺序执行。已应用的迁移记录在 `~/.anycode/migrations.json`，启动时只执行未应用的步骤，
+//! 已应用的不会重复执行；某一步失败即停止并报告，后续步骤留到下次启动或手动触发，
+//! 避免在半迁移状态上继续叠加。
+//!
+//! 新增迁移时只需在 [`MIGRATIONS`] 末尾追加步骤，已发布步骤的 id 与顺序不可修改。
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::utils::config_utils::{load_json_config, save_json_config};
+
+/// 防止启动迁移与手动触发并发执行
+static MIGRATION_LOCK: Mutex<()> = Mutex::new(());
+
+/// 迁移影响的数据类别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DataCategory {
+    /// 应用与工具的配置文件
+    Config,
+    /// 提示词对应的 git 记录
+    GitRecords,
+    /// MCP 服务器注册表
+    McpRegistry,
+}
+
+/// 迁移执行环境，测试中可指向临时目录
+pub struct MigrationContext {
+    pub home: PathBuf,
+}
+
+impl MigrationContext {
+    fn from_home_dir() -> Result<Self, String> {
+        let home = dirs::home_dir().ok_or("无法获取用户主目录")?;
+        Ok(Self { home })
+    }
+}
+
+/// 一个迁移步骤，`run` 必须幂等并返回执行摘要
+pub struct Migration {
+    pub id: &'static str,
+    pub description: &'static str,
+    pub categories: &'static [DataCategory],
+    pub run: fn(&MigrationContext) -> Result<String, String>,
+}
+
+/// 按执行顺序注册的迁移步骤
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        id: "0001_acemcp_settings_to_config",
+        description: "将 ~/.acemcp/settings.toml 重命名为 config.toml",
+        categories: &[DataCategory::Config],
+        run: migrate_acemcp_settings,
+    },
+    Migration {
+        id: "0002_git_records_index_keys",
+        description: "备份以提示词哈希为键的旧格式 git 记录",
+        categories: &[DataCategory::GitRecords],
+        run: migrate_hash_key_git_records,
+    },
+    Migration {
+        id: "0003_mcp_registry_to_anycode",
+        description: "将 MCP 注册表从 ~/.claude-workbench 移到 ~/.anycode",
+        categories: &[DataCategory::McpRegistry],
+        run: migrate_mcp_registry_dir,
+    },
+];
+
+/// 已应用的迁移
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AppliedMigration {
+    pub id: String,
+    /// UTC Unix 秒
+    pub applied_at: i64,
+    pub detail: String,
+}
+
+/// 迁移记录文件内容
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct MigrationState {
+    pub applied: Vec<AppliedMigration>,
+}
+
+impl MigrationState {
+    fn find(&self, id: &str) -> Option<&AppliedMigration> {
+        self.applied.iter().find(|m| m.id == id)
+    }
+}
+
+/// 单个迁移的状态
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MigrationInfo {
+    pub id: String,
+    pub description: String,
+    pub categories: Vec<DataCategory>,
+    /// 未应用时为 None
+    pub applied_at: Option<i64>,
+    pub detail: Option<String>,
+}
+
+/// get_migration_status 的返回值
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MigrationStatus {
+    pub migrations: Vec<MigrationInfo>,
+    /// 未应用的迁移 id，按执行顺序
+    pub pending: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum MigrationStepStatus {
+    Applied,
+    /// dry run 中将要执行
+    Planned,
+    Failed,
+}
+
+/// 一次执行中单个步骤的结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MigrationStepResult {
+    pub id: String,
+    pub description: String,
+    pub status: MigrationStepStatus,
+    pub detail: String,
+}
+
+/// run_pending_migrations 的返回值
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MigrationRunReport {
+    pub dry_run: bool,
+    /// 本次处理的步骤，已应用的步骤不在其中
+    pub steps: Vec<MigrationStepResult>,
+    /// 失败的步骤 id，之后的步骤未执行
+    pub stopped_at: Option<String>,
+}
+
+fn get_state_path(ctx: &MigrationContext) -> PathBuf {
+    ctx.home.join(".anycode").join("migrations.json")
+}
+
+/// 获取所有迁移步骤及其应用状态
+#[tauri::command]
+pub async fn get_migration_status() -> Result<MigrationStatus, String> {
+    let ctx = MigrationContext::from_home_dir()?;
+    let state: MigrationState = load_json_config(get_state_path(&ctx))?;
+    Ok(build_status(MIGRATIONS, &state))
+}
+
+/// 手动执行未应用的迁移，`dry_run` 为 true 时只列出将要执行的步骤
+#[tauri::command]
+pub async fn run_pending_migrations(dry_run: Option<bool>) -> Result<MigrationRunReport, String> {
+    let ctx = MigrationContext::from_home_dir()?;
+    run_migrations(&ctx, MIGRATIONS, dry_run.unwrap_or(false))
+}
+
+/// 应用启动时执行未应用的迁移，失败只记录日志，不阻止启动
+pub fn run_startup_migrations() {
+    let result =
+        MigrationContext::from_home_dir().and_then(|ctx| run_migrations(&ctx, MIGRATIONS, false));
+    match result {
+        Ok(report) => {
+            for step in &report.steps {
+                log::info!(
+                    "[Migrations] {} {:?}: {}",
+                    step.id,
+                    step.status,
+                    step.detail
+                );
+            }
+            if let Some(id) = report.stopped_at {
+                log::error!(
+                    "[Migrations] Stopped at {}, remaining migrations will be retried on next start",
+                    id
+                );
+            }
+        }
+        Err(e) => log::error!("[Migrations] Failed to run migrations: {}", e),
+    }
+}
+
+fn build_status(migrations: &[Migration], state: &MigrationState) -> MigrationStatus {
+    let infos: Vec<MigrationInfo> = migrations
+        .iter()
+        .map(|migration| {
+            let applied = state.find(migration.id);
+            MigrationInfo {
+                id: migration.id.to_string(),
+                description: migration.description.to_string(),
+                categories: migration.categories.to_vec(),
+                applied_at: applied.map(|m| m.applied_at),
+                detail: applied.map(|m| m.detail.clone()),
+            }
+        })
+        .collect();
+    let pending = infos
+        .iter()
+        .filter(|info| info.applied_at.is_none())
+        .map(|info| info.id.clone())
+        .collect();
+    MigrationStatus {
+        migrations: infos,
+        pending,
+    }
+}
+
+/// 按顺序执行未应用的迁移；每一步成功后立即写入记录，失败时停止
+fn run_migrations(
+    ctx: &MigrationContext,
+    migrations: &[Migration],
+    dry_run: bool,
+) -> Result<MigrationRunReport, String> {
+    let _guard = MIGRATION_LOCK
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    let state_path = get_state_path(ctx);
+    let mut state: MigrationState = load_json_config(&state_path)?;
+    let mut report = MigrationRunReport {
+        dry_run,
+        steps: Vec::new(),
+        stopped_at: None,
+    };
+
+    for migration in migrations {
+        if state.find(migration.id).is_some() {
+            continue;
+        }
+        let mut step = MigrationStepResult {
+            id: migration.id.to_string(),
+            description: migration.description.to_string(),
+            status: MigrationStepStatus::Planned,
+            detail: String::new(),
+        };
+        if dry_run {
+            report.steps.push(step);
+            continue;
+        }
+
+        match (migration.run)(ctx) {
+            Ok(detail) => {
+                state.applied.push(AppliedMigration {
+                    id: migration.id.to_string(),
+                    applied_at: chrono::Utc::now().timestamp(),
+                    detail: detail.clone(),
+                });
+                save_json_config(&state, &state_path)?;
+                step.status = MigrationStepStatus::Applied;
+                step.detail = detail;
+                report.steps.push(step);
+            }
+            Err(e) => {
+                step.status = MigrationStepStatus::Failed;
+                step.detail = e;
+                report.stopped_at = Some(step.id.clone());
+                report.steps.push(step);
+                break;
+            }
+        }
+    }
+
+    Ok(report)
+}
+
+/// 移动文件，跨设备重命名失败时改为复制后删除
+fn move_file(from: &Path, to: &Path) -> Result<(), String> {
+    if fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+    fs::copy(from, to).map_err(|e| format!("复制 {:?} 到 {:?} 失败: {}", from, to, e))?;
+    fs::remove_file(from).map_err(|e| format!("删除 {:?} 失败: {}", from, e))
+}
+
+/// 0001: acemcp 旧版配置文件名为 settings.toml
+fn migrate_acemcp_settings(ctx: &MigrationContext) -> Result<String, String> {
+    let acemcp_dir = ctx.home.join(".acemcp");
+    let old_file = acemcp_dir.join("settings.toml");
+    let new_file = acemcp_dir.join("config.toml");
+
+    if !old_file.exists() {
+        return Ok("没有旧版 settings.toml".to_string());
+    }
+    if new_file.exists() {
+        return Ok("config.toml 已存在，保留 settings.toml 不做处理".to_string());
+    }
+    move_file(&old_file, &new_file)?;
+    Ok("settings.toml 已重命名为 config.toml".to_string())
+}
+
+/// 0002: 旧版 git 记录以提示词哈希为键，无法可靠地对应到提示词序号，
+/// 备份为 `.hash-format.bak` 后移除，避免新记录写入时覆盖丢失
+fn migrate_hash_key_git_records(ctx: &MigrationContext) -> Result<String, String> {
+    let projects_dir = ctx.home.join(".claude").join("projects");
+    let Ok(projects) = fs::read_dir(&projects_dir) else {
+        return Ok("没有 Claude 项目目录".to_string());
+    };
+
+    let mut backed_up = 0;
+    for project in projects.flatten() {
+        let Ok(sessions) = fs::read_dir(project.path().join("sessions")) else {
+            continue;
+        };
+        for entry in sessions.flatten() {
+            let path = entry.path();
+            let is_records = path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| n.ends_with(".git-records.json"));
+            if !is_records {
+                continue;
+            }
+            let content =
+                fs::read_to_string(&path).map_err(|e| format!("读取 {:?} 失败: {}", path, e))?;
+            if !is_hash_key_records(&content) {
+                continue;
+            }
+            let mut backup = path.clone().into_os_string();
+            backup.push(".hash-format.bak");
+            move_file(&path, Path::new(&backup))?;
+            backed_up += 1;
+        }
+    }
+    Ok(format!("备份了 {} 个旧格式 git 记录文件", backed_up))
+}
+
+/// 键不全是提示词序号的 JSON 对象即为旧格式
+fn is_hash_key_records(content: &str) -> bool {
+    serde_json::from_str::<HashMap<usize, serde_json::Value>>(content).is_err()
+        && serde_json::from_str::<HashMap<String, serde_json::Value>>(content).is_ok()
+}
+
+/// 0003: MCP 注册表原先存放在 ~/.claude-workbench，统一归拢到 ~/.anycode
+fn migrate_mcp_registry_dir(ctx: &MigrationContext) -> Result<String, String> {
+    let old_dir = ctx.home.join(".claude-workbench");
+    let old_file = old_dir.join("mcp-registry.json");
+    let new_file = ctx.home.join(".anycode").join("mcp-registry.json");
+
+    if !old_file.exists() {
+        return Ok("没有旧版 MCP 注册表".to_string());
+    }
+    if new_file.exists() {
+        return Ok("~/.anycode 中已有 MCP 注册表，保留旧文件不做处理".to_string());
+    }
+    if let Some(parent) = new_file.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("创建 {:?} 失败: {}", parent, e))?;
+    }
+    move_file(&old_file, &new_file)?;
+    // 旧目录只有注册表时一并删除，非空时保留
+    let _ = fs::remove_dir(&old_dir);
+    Ok("MCP 注册表已移到 ~/.anycode/mcp-registry.json".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ok_step(_: &MigrationContext) -> Result<String, String> {
+        Ok("ok".to_string())
+    }
+
+    fn failing_step(_: &MigrationContext) -> Result<String, String> {
+        Err("boom".to_string())
+    }
+
+    const STEPS: &[Migration] = &[
+        Migration {
+            id: "a",
+            description: "first",
+            categories: &[DataCategory::Config],
+            run: ok_step,
+        },
+        Migration {
+            id: "b",
+            description: "second",
+            categories: &[DataCategory::GitRecords],
+            run: failing_step,
+        },
+        Migration {
+            id: "c",
+            description: "third",
+            categories: &[DataCategory::Config],
+            run: ok_step,
+        },
+    ];
+
+    fn context() -> (tempfile::TempDir, MigrationContext) {
+        let dir = tempfile::tempdir().unwrap();
+        let ctx = MigrationContext {
+            home: dir.path().to_path_buf(),
+        };
+        (dir, ctx)
+    }
+
+    #[test]
+    fn runs_in_order_and_stops_at_first_failure() {
+        let (_dir, ctx) = context();
+
+        let planned = run_migrations(&ctx, STEPS, true).unwrap();
+        assert_eq!(planned.steps.len(), 3);
+        assert!(!get_state_path(&ctx).exists());
+
+        let report = run_migrations(&ctx, STEPS, false).unwrap();
+        let statuses: Vec<_> = report.steps.iter().map(|s| s.status).collect();
+        assert_eq!(
+            statuses,
+            vec![MigrationStepStatus::Applied, MigrationStepStatus::Failed]
+        );
+        assert_eq!(report.stopped_at.as_deref(), Some("b"));
+
+        // 已应用的步骤不再执行，失败的步骤下次重试
+        let retry = run_migrations(&ctx, STEPS, false).unwrap();
+        assert_eq!(retry.steps[0].id, "b");
+
+        let state: MigrationState = load_json_config(get_state_path(&ctx)).unwrap();
+        let status = build_status(STEPS, &state);
+        assert_eq!(status.pending, vec!["b", "c"]);
+        assert_eq!(status.migrations[0].detail.as_deref(), Some("ok"));
+    }
+
+    #[test]
+    fn registered_migrations_are_idempotent() {
+        let (_dir, ctx) = context();
+        let acemcp = ctx.home.join(".acemcp");
+        fs::create_dir_all(&acemcp).unwrap();
+        fs::write(acemcp.join("settings.toml"), "BASE_URL = \"x\"").unwrap();
+
+        let sessions = ctx.home.join(".claude/projects/p/sessions");
+        fs::create_dir_all(&sessions).unwrap();
+        fs::write(sessions.join("old.git-records.json"), r#"{"abc123":{}}"#).unwrap();
+        fs::write(sessions.join("new.git-records.json"), r#"{"0":{}}"#).unwrap();
+
+        let workbench = ctx.home.join(".claude-workbench");
+        fs::create_dir_all(&workbench).unwrap();
+        fs::write(workbench.join("mcp-registry.json"), "{}").unwrap();
+
+        let report = run_migrations(&ctx, MIGRATIONS, false).unwrap();
+        assert!(report.stopped_at.is_none());
+        assert_eq!(report.steps.len(), MIGRATIONS.len());
+
+        assert!(acemcp.join("config.toml").exists());
+        assert!(!acemcp.join("settings.toml").exists());
+        assert!(sessions
+            .join("old.git-records.json.hash-format.bak")
+            .exists());
+        assert!(!sessions.join("old.git-records.json").exists());
+        assert!(sessions.join("new.git-records.json").exists());
+        assert!(ctx.home.join(".anycode/mcp-registry.json").exists());
+        assert!(!workbench.exists());
+
+        // 每个步骤直接重跑也不改变结果
+        for migration in MIGRATIONS {
+            (migration.run)(&ctx).unwrap();
+        }
+        assert!(sessions.join("new.git-records.json").exists());
+        assert!(run_migrations(&ctx, MIGRATIONS, false)
+            .unwrap()
+            .steps
+            .is_empty());
+    }
+}
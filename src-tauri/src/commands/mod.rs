@@ -1,22 +1,64 @@
 pub mod acemcp;
+pub mod app_error; // 命令错误码与本地化说明
+pub mod backup; // 会话与项目数据定时备份
+pub mod binary_detection; // 二进制检测设置与缓存
 pub mod claude;
 pub mod clipboard;
+pub mod code_blocks; // 会话代码块提取与保存
 pub mod codex; // OpenAI Codex integration
 pub mod context_commands;
 pub mod context_manager;
+pub mod cost_comparison; // 模型成本对比
+pub mod cross_engine_review; // 跨引擎评审
+pub mod diagnostics; // 问题报告诊断信息
+pub mod effective_config; // 生效配置预览
+pub mod encrypted_storage; // 回收站/归档文件静态加密
 pub mod enhanced_hooks;
+pub mod execution_retry; // 执行失败自动重试
+pub mod execution_trace; // 执行追踪（trace id）
 pub mod extensions;
 pub mod file_operations;
 pub mod gemini; // Google Gemini CLI integration
+pub mod input_validation; // 命令参数校验（路径穿越、空值、长度）
+pub mod interjection; // 任务运行中插话
 pub mod git_stats;
+pub mod log_management; // 日志级别与诊断包导出
 pub mod mcp;
+pub mod mcp_calls; // MCP 工具调用统计
+pub mod messages; // 后端用户可见文案表（中/英）
+pub mod migrations; // 启动时按序执行的数据迁移
 pub mod permission_config;
+pub mod power_inhibit; // 任务运行期间阻止系统休眠
+pub mod process_cancel; // 进程优雅取消
 pub mod prompt_tracker;
+pub mod prompt_batch; // 批量提示词执行
+pub mod prompt_enhancement; // 执行时提示词增强
+pub mod prompt_file_snapshots; // 提示词引用文件的内容快照
+pub mod prompt_history; // 跨会话提示词历史与重发
+pub mod project_memory; // 项目记忆
+pub mod project_setup; // 项目 AI 工作流初始化
+pub mod project_workspace; // 项目工作状态（上次的会话、草稿）保存与恢复
+pub mod protected_paths; // 禁改区配置与执行期保护
 pub mod provider;
+pub mod provider_presets; // 远程 provider 预设清单
+pub mod rate_limiter; // provider 维度的客户端限流
+pub mod response_alternatives; // 回复的多版本重新生成
+pub mod session_alias; // 会话 id 漂移后的别名映射
+pub mod session_annotations; // 会话标注（关联会话、提示词增强元数据）
+pub mod session_blobs; // 会话大块工具结果外置存储
+pub mod session_invalidation; // 会话缓存失效与前端刷新通知
+pub mod session_utils; // 跨引擎会话工具
 pub mod simple_git;
 pub mod storage;
+pub mod stream_stall; // 流式输出卡死检测与自动续接
+pub mod task_actions; // 任务完成动作
+pub mod task_resources; // Codex / Gemini 子进程资源监控
+pub mod tool_timing; // 工具调用耗时与慢操作
 pub mod translator;
+pub mod undo; // 全局操作撤销栈
+pub mod url_context; // 提示词中外部 URL 的预抓取
 pub mod url_utils; // API URL 规范化工具
 pub mod usage;
+pub mod usage_report; // 用量报表导出（CSV / JSON）
 pub mod window; // 多窗口管理
 pub mod wsl_utils; // WSL 兼容性工具
@@ -70,6 +70,14 @@ pub struct ClaudeExecutionConfig {
     pub permissions: ClaudePermissionConfig,
     #[serde(default)]
     pub disable_rewind_git_operations: bool,
+    /// 未配置 git 身份时使用一次性身份（AnyCode <anycode@local>）完成自动提交；
+    /// 关闭后改为提示用户自行配置
+    #[serde(default = "default_git_identity_fallback")]
+    pub git_identity_fallback: bool,
+}
+
+fn default_git_identity_fallback() -> bool {
+    true
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -89,6 +97,7 @@ impl Default for ClaudeExecutionConfig {
             verbose: true,
             permissions: ClaudePermissionConfig::default(),
             disable_rewind_git_operations: false,
+            git_identity_fallback: true,
         }
     }
 }
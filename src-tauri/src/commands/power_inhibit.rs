@@ -0,0 +1,401 @@
+//! 任务运行期间阻止系统休眠（Codex / Gemini）
+//!
+//! 长任务运行中系统进入睡眠会挂起 CLI 进程，唤醒后连接往往已经断开。
+//! 执行开始时调用 `acquire` 获得一个持有句柄，句柄在整次执行（含自动重试）结束时释放：
+//! - 多个任务并发时按持有者计数，第一个持有者出现时向系统申请，最后一个释放后撤销
+//! - Windows 使用 `SetThreadExecutionState`，macOS 使用 `caffeinate`（IOPMAssertion），
+//!   Linux 使用 `systemd-inhibit`；辅助进程附带应用 PID，应用异常退出时也会随之结束
+//! - 申请失败只记警告日志，不影响任务执行
+//!
+//! 持久化：~/.anycode/power_inhibit.json（默认开启）
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use crate::utils::config_utils::{load_json_config, save_json_config, ConfigPathBuilder};
+
+/// 全局持有状态
+static STATE: Lazy<Mutex<InhibitState<PlatformInhibitor>>> =
+    Lazy::new(|| Mutex::new(InhibitState::default()));
+
+/// 休眠阻止配置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PowerInhibitConfig {
+    pub enabled: bool,
+    /// 只阻止系统睡眠，允许屏幕按系统设置关闭
+    pub allow_display_sleep: bool,
+}
+
+impl Default for PowerInhibitConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            allow_display_sleep: false,
+        }
+    }
+}
+
+/// 一个持有休眠阻止的任务
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PowerInhibitHolder {
+    /// 引擎类型: "codex" | "gemini"
+    pub engine: String,
+    /// 应用内通道会话 ID
+    pub session_id: String,
+    pub trace_id: Option<String>,
+    pub project_path: String,
+    /// 开始持有的时间（UTC Unix 秒）
+    pub since: i64,
+}
+
+/// 当前休眠阻止状态
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PowerInhibitionStatus {
+    pub enabled: bool,
+    /// 是否已向系统成功申请
+    pub active: bool,
+    pub allow_display_sleep: bool,
+    pub holders: Vec<PowerInhibitHolder>,
+    /// 最近一次申请失败的原因
+    pub last_error: Option<String>,
+}
+
+/// 持有者计数与系统申请句柄
+///
+/// 对申请句柄泛型化，便于脱离具体平台测试计数逻辑
+struct InhibitState<T> {
+    next_id: u64,
+    holders: BTreeMap<u64, PowerInhibitHolder>,
+    inhibitor: Option<T>,
+    last_error: Option<String>,
+}
+
+impl<T> Default for InhibitState<T> {
+    fn default() -> Self {
+        Self {
+            next_id: 1,
+            holders: BTreeMap::new(),
+            inhibitor: None,
+            last_error: None,
+        }
+    }
+}
+
+impl<T> InhibitState<T> {
+    /// 登记持有者，尚未向系统申请时立即申请
+    fn add(
+        &mut self,
+        holder: PowerInhibitHolder,
+        acquire: impl FnOnce() -> Result<T, String>,
+    ) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.holders.insert(id, holder);
+        self.sync(true, acquire);
+        id
+    }
+
+    /// 移除持有者，最后一个持有者移除后撤销申请
+    fn remove(&mut self, id: u64) {
+        self.holders.remove(&id);
+        if self.holders.is_empty() {
+            self.inhibitor = None;
+        }
+    }
+
+    /// 按当前持有者与开关调整系统申请
+    fn sync(&mut self, enabled: bool, acquire: impl FnOnce() -> Result<T, String>) {
+        if !enabled || self.holders.is_empty() {
+            self.inhibitor = None;
+            return;
+        }
+        if self.inhibitor.is_some() {
+            return;
+        }
+        match acquire() {
+            Ok(inhibitor) => {
+                self.inhibitor = Some(inhibitor);
+                self.last_error = None;
+            }
+            Err(e) => {
+                log::warn!("[PowerInhibit] Failed to prevent system sleep: {}", e);
+                self.last_error = Some(e);
+            }
+        }
+    }
+}
+
+/// 任务持有的休眠阻止，drop 时释放
+#[derive(Debug)]
+pub struct PowerInhibitHold {
+    id: u64,
+}
+
+impl Drop for PowerInhibitHold {
+    fn drop(&mut self) {
+        let mut state = STATE.lock().unwrap();
+        state.remove(self.id);
+        if state.holders.is_empty() {
+            log::debug!("[PowerInhibit] All tasks finished, sleep allowed again");
+        }
+    }
+}
+
+/// 为一次执行申请休眠阻止；功能关闭时返回 None
+pub fn acquire(
+    engine: &str,
+    session_id: &str,
+    trace_id: Option<&str>,
+    project_path: &str,
+) -> Option<PowerInhibitHold> {
+    let config = load_config();
+    if !config.enabled {
+        return None;
+    }
+
+    let holder = PowerInhibitHolder {
+        engine: engine.to_string(),
+        session_id: session_id.to_string(),
+        trace_id: trace_id.map(str::to_string),
+        project_path: project_path.to_string(),
+        since: chrono::Utc::now().timestamp(),
+    };
+    let mut state = STATE.lock().unwrap();
+    let id = state.add(holder, || {
+        PlatformInhibitor::acquire(!config.allow_display_sleep)
+    });
+    log::debug!(
+        "[PowerInhibit] {} session {} holds sleep inhibition ({} holder(s))",
+        engine,
+        session_id,
+        state.holders.len()
+    );
+    Some(PowerInhibitHold { id })
+}
+
+fn get_config_path() -> Result<PathBuf, String> {
+    Ok(ConfigPathBuilder::from_home_subdir(".anycode")?.build("power_inhibit.json"))
+}
+
+/// 读取配置，读取失败时按默认值处理
+fn load_config() -> PowerInhibitConfig {
+    get_config_path()
+        .and_then(load_json_config)
+        .unwrap_or_else(|e| {
+            log::warn!("[PowerInhibit] Failed to load config: {}", e);
+            PowerInhibitConfig::default()
+        })
+}
+
+/// 获取休眠阻止配置
+#[tauri::command]
+pub async fn get_power_inhibit_config() -> Result<PowerInhibitConfig, String> {
+    load_json_config(get_config_path()?)
+}
+
+/// 更新休眠阻止配置，立即作用于正在运行的任务
+#[tauri::command]
+pub async fn update_power_inhibit_config(config: PowerInhibitConfig) -> Result<(), String> {
+    save_json_config(&config, get_config_path()?)?;
+
+    let mut state = STATE.lock().unwrap();
+    // 撤销后按新配置重新申请，使屏幕选项立即生效
+    state.inhibitor = None;
+    state.sync(config.enabled, || {
+        PlatformInhibitor::acquire(!config.allow_display_sleep)
+    });
+    Ok(())
+}
+
+/// 获取当前休眠阻止状态及持有的任务
+#[tauri::command]
+pub async fn get_power_inhibition_status() -> Result<PowerInhibitionStatus, String> {
+    let config = load_config();
+    let mut state = STATE.lock().unwrap();
+
+    if let Some(Err(e)) = state.inhibitor.as_mut().map(PlatformInhibitor::check) {
+        log::warn!("[PowerInhibit] Sleep inhibition was lost: {}", e);
+        state.inhibitor = None;
+        state.last_error = Some(e);
+    }
+
+    Ok(PowerInhibitionStatus {
+        enabled: config.enabled,
+        active: state.inhibitor.is_some(),
+        allow_display_sleep: config.allow_display_sleep,
+        holders: state.holders.values().cloned().collect(),
+        last_error: state.last_error.clone(),
+    })
+}
+
+// ============================================================================
+// Platform
+// ============================================================================
+
+/// Windows：执行状态是线程级的，由专用线程设置并保持，通道关闭时恢复
+#[cfg(windows)]
+struct PlatformInhibitor {
+    _release: std::sync::mpsc::Sender<()>,
+}
+
+#[cfg(windows)]
+impl PlatformInhibitor {
+    fn acquire(block_display: bool) -> Result<Self, String> {
+        use windows::Win32::System::Power::{
+            SetThreadExecutionState, ES_CONTINUOUS, ES_DISPLAY_REQUIRED, ES_SYSTEM_REQUIRED,
+        };
+
+        let (release_tx, release_rx) = std::sync::mpsc::channel::<()>();
+        let (ready_tx, ready_rx) = std::sync::mpsc::channel();
+        std::thread::Builder::new()
+            .name("power-inhibit".to_string())
+            .spawn(move || {
+                let mut flags = ES_CONTINUOUS | ES_SYSTEM_REQUIRED;
+                if block_display {
+                    flags |= ES_DISPLAY_REQUIRED;
+                }
+                let acquired = unsafe { SetThreadExecutionState(flags) }.0 != 0;
+                let _ = ready_tx.send(acquired);
+                if acquired {
+                    // 发送端被 drop 后 recv 返回，恢复默认执行状态
+                    let _ = release_rx.recv();
+                    unsafe { SetThreadExecutionState(ES_CONTINUOUS) };
+                }
+            })
+            .map_err(|e| format!("Failed to start power inhibit thread: {}", e))?;
+
+        match ready_rx.recv() {
+            Ok(true) => Ok(Self {
+                _release: release_tx,
+            }),
+            _ => Err("SetThreadExecutionState failed".to_string()),
+        }
+    }
+
+    fn check(&mut self) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+/// macOS / Linux：由辅助进程持有系统断言，结束进程即释放
+#[cfg(not(windows))]
+struct PlatformInhibitor {
+    child: std::process::Child,
+}
+
+#[cfg(not(windows))]
+impl PlatformInhibitor {
+    fn acquire(block_display: bool) -> Result<Self, String> {
+        use std::process::{Command, Stdio};
+
+        let pid = std::process::id().to_string();
+
+        #[cfg(target_os = "macos")]
+        let mut cmd = {
+            let mut cmd = Command::new("caffeinate");
+            cmd.arg("-i");
+            if block_display {
+                cmd.arg("-d");
+            }
+            cmd.args(["-w", &pid]);
+            cmd
+        };
+
+        #[cfg(not(target_os = "macos"))]
+        let mut cmd = {
+            let what = if block_display { "sleep:idle" } else { "sleep" };
+            let mut cmd = Command::new("systemd-inhibit");
+            cmd.arg(format!("--what={}", what))
+                .arg("--who=Any Code")
+                .arg("--why=AI coding task is running")
+                .arg("--mode=block")
+                .args(["tail", "--pid", &pid, "-f", "/dev/null"]);
+            cmd
+        };
+
+        let program = cmd.get_program().to_string_lossy().to_string();
+        let child = cmd
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| format!("Failed to start {}: {}", program, e))?;
+        Ok(Self { child })
+    }
+
+    /// 辅助进程意外退出（例如被策略拒绝）时返回原因
+    fn check(&mut self) -> Result<(), String> {
+        match self.child.try_wait() {
+            Ok(None) => Ok(()),
+            Ok(Some(status)) => Err(format!("Sleep inhibitor exited with {}", status)),
+            Err(e) => Err(format!("Failed to check sleep inhibitor: {}", e)),
+        }
+    }
+}
+
+#[cfg(not(windows))]
+impl Drop for PlatformInhibitor {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+
+    fn holder(session_id: &str) -> PowerInhibitHolder {
+        PowerInhibitHolder {
+            engine: "codex".to_string(),
+            session_id: session_id.to_string(),
+            trace_id: None,
+            project_path: "/tmp/project".to_string(),
+            since: 0,
+        }
+    }
+
+    #[test]
+    fn inhibition_is_shared_by_concurrent_tasks() {
+        let token = Rc::new(());
+        let mut state = InhibitState::default();
+
+        let first = state.add(holder("a"), || Ok(token.clone()));
+        let second = state.add(holder("b"), || -> Result<Rc<()>, String> {
+            panic!("already acquired")
+        });
+        assert_eq!(Rc::strong_count(&token), 2);
+
+        state.remove(first);
+        assert!(state.inhibitor.is_some());
+        assert_eq!(state.holders.len(), 1);
+
+        state.remove(second);
+        assert!(state.inhibitor.is_none());
+        assert_eq!(Rc::strong_count(&token), 1);
+    }
+
+    #[test]
+    fn failed_acquire_is_recorded_and_retried_for_later_tasks() {
+        let mut state: InhibitState<()> = InhibitState::default();
+
+        state.add(holder("a"), || Err("no systemd".to_string()));
+        assert!(state.inhibitor.is_none());
+        assert_eq!(state.holders.len(), 1);
+        assert_eq!(state.last_error.as_deref(), Some("no systemd"));
+
+        state.add(holder("b"), || Ok(()));
+        assert!(state.inhibitor.is_some());
+        assert!(state.last_error.is_none());
+
+        state.sync(false, || Ok(()));
+        assert!(state.inhibitor.is_none());
+    }
+}
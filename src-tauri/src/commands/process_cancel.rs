@@ -0,0 +1,232 @@
+//! 进程优雅取消
+//!
+//! 直接强杀 CLI 时，最后的事件来不及写入会话文件，取消后的会话经常缺尾巴、resume 时上下文不完整。
+//! 三个引擎的取消统一按以下流程处理：
+//! 1. 发送中断信号（Unix：向进程组发送 SIGINT；Windows：CTRL_BREAK，失败时 taskkill 不带 /F）
+//! 2. 最多等待 5 秒，让进程自行退出并写完会话文件
+//! 3. 超时后再强杀进程树
+//! 4. 校验会话文件：JSONL 末尾不完整的行会被截掉，保证 resume 时每一行都能解析；JSON 文件只检查能否解析。
+//!    修复时会话锁仍由被取消的执行持有，写入前比对 mtime/size，期间被其他进程写入则放弃修复
+//!
+//! 取消结果（graceful / forced 与会话文件校验结果）随 `<engine>-cancelled` 事件发送给前端。
+
+use serde::Serialize;
+use std::fs::{self, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::Path;
+use std::time::Duration;
+
+use super::claude::request_process_exit;
+use crate::utils::session_lock::FileSnapshot;
+
+/// 等待进程自行退出的最长时间
+pub const GRACEFUL_EXIT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// 检查进程是否已退出的间隔
+const EXIT_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// 进程的结束方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CancelMode {
+    /// 收到中断信号后在超时前自行退出
+    Graceful,
+    /// 超时或无法发送中断信号，被强制结束
+    Forced,
+}
+
+/// 会话文件校验结果
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionFileCheck {
+    pub path: String,
+    /// 校验（及修复）后文件能否完整解析
+    pub parseable: bool,
+    /// 从 JSONL 末尾截掉的不完整字节数
+    pub truncated_bytes: u64,
+}
+
+/// 取消结果（`<engine>-cancelled` 事件的负载）
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CancelOutcome {
+    pub engine: String,
+    pub session_id: String,
+    pub pid: Option<u32>,
+    pub mode: CancelMode,
+    /// 找不到会话文件（例如 CLI 尚未创建）时为 None
+    pub session_file: Option<SessionFileCheck>,
+}
+
+/// 发送中断信号并等待进程自行退出
+///
+/// `has_exited` 用于轮询进程是否已退出；返回 true 表示进程在超时前退出，
+/// 返回 false 时由调用方强杀（各引擎的强杀方式不同：Job Object、进程树、registry）
+pub async fn request_exit_and_wait(
+    pid: u32,
+    timeout: Duration,
+    mut has_exited: impl FnMut() -> bool,
+) -> bool {
+    if has_exited() {
+        return true;
+    }
+
+    if let Err(e) = request_process_exit(pid) {
+        log::warn!("Failed to request graceful exit of PID {}: {}", pid, e);
+        return false;
+    }
+
+    let started = tokio::time::Instant::now();
+    while started.elapsed() < timeout {
+        tokio::time::sleep(EXIT_POLL_INTERVAL).await;
+        if has_exited() {
+            log::info!(
+                "Process {} exited gracefully after {}ms",
+                pid,
+                started.elapsed().as_millis()
+            );
+            return true;
+        }
+    }
+
+    log::warn!(
+        "Process {} did not exit within {}s, force killing",
+        pid,
+        timeout.as_secs()
+    );
+    false
+}
+
+/// 校验取消后的会话文件
+///
+/// - `.jsonl`：截掉末尾不完整的行（强杀时写到一半的事件），之后每一行都应能解析
+/// - 其他（Gemini 的 `.json`）：只检查能否解析，不做修改
+pub fn check_session_file(path: &Path) -> Result<SessionFileCheck, String> {
+    let is_jsonl = path.extension().is_some_and(|ext| ext == "jsonl");
+    let (parseable, truncated_bytes) = if is_jsonl {
+        let truncated_bytes = truncate_incomplete_jsonl_tail(path)?;
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read session file {:?}: {}", path, e))?;
+        let parseable = content
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .all(|line| serde_json::from_str::<serde_json::Value>(line).is_ok());
+        (parseable, truncated_bytes)
+    } else {
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read session file {:?}: {}", path, e))?;
+        (
+            serde_json::from_str::<serde_json::Value>(&content).is_ok(),
+            0,
+        )
+    };
+
+    if !parseable {
+        log::warn!(
+            "Session file is not fully parseable after cancel: {:?}",
+            path
+        );
+    }
+    Ok(SessionFileCheck {
+        path: path.to_string_lossy().to_string(),
+        parseable,
+        truncated_bytes,
+    })
+}
+
+/// 截掉 JSONL 末尾无法解析的最后一行，返回截掉的字节数
+///
+/// 只处理最后一行：以换行结尾的文件视为完整；否则最后一行能解析时补上换行，
+/// 不能解析时截断到上一个换行处
+fn truncate_incomplete_jsonl_tail(path: &Path) -> Result<u64, String> {
+    let snapshot = FileSnapshot::capture(path)?;
+    let mut file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(path)
+        .map_err(|e| format!("Failed to open session file {:?}: {}", path, e))?;
+    let len = file
+        .metadata()
+        .map_err(|e| format!("Failed to stat session file {:?}: {}", path, e))?
+        .len();
+    if len == 0 {
+        return Ok(0);
+    }
+
+    let mut content = Vec::with_capacity(len as usize);
+    file.read_to_end(&mut content)
+        .map_err(|e| format!("Failed to read session file {:?}: {}", path, e))?;
+    if content.last() == Some(&b'\n') {
+        return Ok(0);
+    }
+
+    let tail_start = content
+        .iter()
+        .rposition(|&b| b == b'\n')
+        .map(|i| i + 1)
+        .unwrap_or(0);
+    let tail = &content[tail_start..];
+    snapshot.ensure_unchanged(path)?;
+    if serde_json::from_slice::<serde_json::Value>(tail).is_ok() {
+        file.seek(SeekFrom::End(0))
+            .and_then(|_| file.write_all(b"\n"))
+            .map_err(|e| format!("Failed to append newline to {:?}: {}", path, e))?;
+        return Ok(0);
+    }
+
+    file.set_len(tail_start as u64)
+        .map_err(|e| format!("Failed to truncate session file {:?}: {}", path, e))?;
+    log::warn!(
+        "Truncated {} bytes of incomplete event from session file {:?}",
+        tail.len(),
+        path
+    );
+    Ok(tail.len() as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn truncates_half_written_event() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("rollout.jsonl");
+        fs::write(
+            &path,
+            "{\"type\":\"session_meta\"}\n{\"type\":\"event_msg\",\"payl",
+        )
+        .unwrap();
+
+        let check = check_session_file(&path).unwrap();
+        assert!(check.parseable);
+        assert_eq!(check.truncated_bytes, 25);
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "{\"type\":\"session_meta\"}\n"
+        );
+    }
+
+    #[test]
+    fn completes_last_line_without_newline() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("session.jsonl");
+        fs::write(&path, "{\"a\":1}\n{\"b\":2}").unwrap();
+
+        let check = check_session_file(&path).unwrap();
+        assert!(check.parseable);
+        assert_eq!(check.truncated_bytes, 0);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "{\"a\":1}\n{\"b\":2}\n");
+    }
+
+    #[test]
+    fn reports_unparseable_json_without_modifying() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("session-1.json");
+        fs::write(&path, "{\"messages\": [").unwrap();
+
+        let check = check_session_file(&path).unwrap();
+        assert!(!check.parseable);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "{\"messages\": [");
+    }
+}
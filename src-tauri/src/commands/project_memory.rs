@@ -0,0 +1,753 @@
+//! 项目记忆（跨会话、跨引擎的事实与决策沉淀）
+//!
+//! 长期项目里的架构决策、命名约定等背景不必每次新会话都重新解释：
+//! - 存储：<project>/.anycode/memory.json，结构化条目（事实 / 决策 / 约定），各带创建时间与来源会话
+//! - 编译：`compile_memory_context` 按 token 预算把条目压缩为一段可注入的上下文文本
+//! - 注入：三引擎执行选项开启 `use_project_memory` 时附加编译后的记忆
+//!   （Claude 走 `--append-system-prompt`，Codex / Gemini 附加到提示词末尾）
+//! - 提取：`extract_memory_from_session` 通过当前 Claude 代理商配置调用模型，
+//!   从一次会话中提取候选条目，由用户确认后再入库
+//!
+//! 记忆文件大小与条目数都有上限，避免注入内容无限膨胀。
+
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::cmp::Reverse;
+use std::fs;
+use std::ops::ControlFlow;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use super::codex::is_codex_context_message;
+use super::provider::get_current_provider_config;
+use super::rate_limiter;
+use super::session_utils::resolve_session_file;
+use super::undo::{self, Undoable};
+use super::url_utils::{normalize_api_url, ApiEndpointType};
+use crate::utils::config_utils::save_json_config;
+use crate::utils::jsonl_reader::{ensure_loadable_in_memory, for_each_line};
+use crate::utils::text::truncate_chars;
+
+/// 记忆文件最多保存的条目数
+const MAX_MEMORY_ENTRIES: usize = 200;
+
+/// 单条记忆最大字符数
+const MAX_ENTRY_CHARS: usize = 1000;
+
+/// 记忆文件大小上限（字节）
+const MAX_MEMORY_FILE_BYTES: u64 = 256 * 1024;
+
+/// 执行时注入记忆的默认 token 预算
+pub const DEFAULT_MEMORY_CONTEXT_TOKENS: usize = 2000;
+
+/// 提取记忆时发送给模型的会话文本上限（保留最近的部分）
+const MAX_TRANSCRIPT_CHARS: usize = 40_000;
+
+/// 会话中单条消息截取的最大字符数
+const MAX_TURN_CHARS: usize = 2000;
+
+/// 单次提取最多返回的候选条目数
+const MAX_EXTRACTED_CANDIDATES: usize = 20;
+
+/// 模型请求超时时间
+const MODEL_REQUEST_TIMEOUT_SECS: u64 = 120;
+
+/// 代理商未配置 base URL / 模型时使用的默认值
+const DEFAULT_ANTHROPIC_BASE_URL: &str = "https://api.anthropic.com";
+const DEFAULT_SMALL_MODEL: &str = "claude-haiku-4-5";
+
+/// 串行化记忆文件的读改写
+static STORE_LOCK: Mutex<()> = Mutex::new(());
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// 记忆条目类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MemoryKind {
+    /// 项目事实（技术栈、目录结构、外部依赖等）
+    Fact,
+    /// 已做出的决策及其理由
+    Decision,
+    /// 需要遵循的约定（命名、代码风格、流程）
+    Convention,
+}
+
+impl MemoryKind {
+    /// 编译上下文时的分组顺序：约定最需要模型遵循，放在最前面
+    const COMPILE_ORDER: [MemoryKind; 3] = [
+        MemoryKind::Convention,
+        MemoryKind::Decision,
+        MemoryKind::Fact,
+    ];
+
+    fn heading(self) -> &'static str {
+        match self {
+            MemoryKind::Fact => "事实",
+            MemoryKind::Decision => "决策",
+            MemoryKind::Convention => "约定",
+        }
+    }
+}
+
+/// 已入库的记忆条目
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MemoryEntry {
+    pub id: String,
+    pub kind: MemoryKind,
+    pub content: String,
+    /// 创建时间（UTC Unix 秒）
+    pub created_at: i64,
+    /// 来源会话 ID（手动添加时为空）
+    #[serde(default)]
+    pub source_session: Option<String>,
+    /// 来源会话的引擎: "claude" | "codex" | "gemini"
+    #[serde(default)]
+    pub source_engine: Option<String>,
+}
+
+/// 新增条目 / 提取出的候选条目
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MemoryEntryInput {
+    pub kind: MemoryKind,
+    pub content: String,
+    #[serde(default)]
+    pub source_session: Option<String>,
+    #[serde(default)]
+    pub source_engine: Option<String>,
+}
+
+/// 记忆文件内容
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ProjectMemory {
+    #[serde(default)]
+    entries: Vec<MemoryEntry>,
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// 新增一条记忆（内容相同的条目已存在时直接返回已有条目）
+#[tauri::command]
+pub async fn add_memory_entry(
+    project_path: String,
+    entry: MemoryEntryInput,
+) -> Result<MemoryEntry, String> {
+    let content = entry.content.trim().to_string();
+    if content.is_empty() {
+        return Err("记忆内容不能为空".to_string());
+    }
+    if content.chars().count() > MAX_ENTRY_CHARS {
+        return Err(format!("单条记忆不能超过 {} 个字符", MAX_ENTRY_CHARS));
+    }
+
+    let _guard = STORE_LOCK.lock().unwrap();
+    let mut memory = load_memory(&project_path)?;
+
+    if let Some(existing) = memory.entries.iter().find(|e| {
+        e.kind == entry.kind && normalize_content(&e.content) == normalize_content(&content)
+    }) {
+        return Ok(existing.clone());
+    }
+    if memory.entries.len() >= MAX_MEMORY_ENTRIES {
+        return Err(format!(
+            "项目记忆已达到 {} 条上限，请先删除不再需要的条目",
+            MAX_MEMORY_ENTRIES
+        ));
+    }
+
+    let new_entry = MemoryEntry {
+        id: uuid::Uuid::new_v4().to_string(),
+        kind: entry.kind,
+        content,
+        created_at: chrono::Utc::now().timestamp(),
+        source_session: entry.source_session.filter(|s| !s.is_empty()),
+        source_engine: entry.source_engine.filter(|s| !s.is_empty()),
+    };
+    memory.entries.push(new_entry.clone());
+    save_memory(&project_path, &memory)?;
+
+    log::info!(
+        "[ProjectMemory] Added {:?} entry {} to {}",
+        new_entry.kind,
+        new_entry.id,
+        project_path
+    );
+    Ok(new_entry)
+}
+
+/// 列出项目的全部记忆（按创建时间倒序）
+#[tauri::command]
+pub async fn list_memory_entries(project_path: String) -> Result<Vec<MemoryEntry>, String> {
+    let mut entries = {
+        let _guard = STORE_LOCK.lock().unwrap();
+        load_memory(&project_path)?.entries
+    };
+    entries.sort_by_key(|e| Reverse(e.created_at));
+    Ok(entries)
+}
+
+/// 删除一条记忆
+#[tauri::command]
+pub async fn delete_memory_entry(project_path: String, entry_id: String) -> Result<(), String> {
+    let _guard = STORE_LOCK.lock().unwrap();
+    let mut memory = load_memory(&project_path)?;
+
+    let index = memory
+        .entries
+        .iter()
+        .position(|e| e.id == entry_id)
+        .ok_or_else(|| format!("Memory entry not found: {}", entry_id))?;
+    let entry = memory.entries.remove(index);
+
+    save_memory(&project_path, &memory)?;
+    undo::record(&MemoryEntryDeletion {
+        project_path,
+        index,
+        entry,
+    });
+    Ok(())
+}
+
+/// 撤销删除记忆条目：按原位置放回
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MemoryEntryDeletion {
+    project_path: String,
+    index: usize,
+    entry: MemoryEntry,
+}
+
+impl Undoable for MemoryEntryDeletion {
+    const KIND: &'static str = "memory_entry_deletion";
+
+    fn description(&self) -> String {
+        format!("删除记忆 {}", truncate_chars(&self.entry.content, 30))
+    }
+
+    fn undo(self) -> Result<(), String> {
+        let _guard = STORE_LOCK.lock().unwrap();
+        let mut memory = load_memory(&self.project_path)?;
+        if memory.entries.iter().any(|e| e.id == self.entry.id) {
+            return Err(format!("Memory entry already exists: {}", self.entry.id));
+        }
+        if memory.entries.len() >= MAX_MEMORY_ENTRIES {
+            return Err(format!(
+                "Project memory is full ({} entries)",
+                MAX_MEMORY_ENTRIES
+            ));
+        }
+        let index = self.index.min(memory.entries.len());
+        memory.entries.insert(index, self.entry);
+        save_memory(&self.project_path, &memory)
+    }
+}
+
+/// 把项目记忆编译为一段可注入的上下文文本（没有条目时返回空字符串）
+#[tauri::command]
+pub async fn compile_memory_context(
+    project_path: String,
+    max_tokens: usize,
+) -> Result<String, String> {
+    let entries = {
+        let _guard = STORE_LOCK.lock().unwrap();
+        load_memory(&project_path)?.entries
+    };
+    Ok(compile_entries(&entries, max_tokens))
+}
+
+/// 用模型从一次会话中提取候选记忆条目（不入库，由用户确认后调用 `add_memory_entry`）
+///
+/// `project_id_or_path` 的含义与 `resolve_session_path` 相同
+#[tauri::command]
+pub async fn extract_memory_from_session(
+    engine: String,
+    session_id: String,
+    project_id_or_path: String,
+) -> Result<Vec<MemoryEntryInput>, String> {
+    log::info!(
+        "extract_memory_from_session called: engine={}, session_id={}",
+        engine,
+        session_id
+    );
+
+    let transcript = {
+        let engine = engine.clone();
+        let session_id = session_id.clone();
+        tokio::task::spawn_blocking(move || {
+            let path = resolve_session_file(&engine, &session_id, &project_id_or_path)?;
+            read_session_transcript(&engine, &path)
+        })
+        .await
+        .map_err(|e| format!("Session transcript task failed: {}", e))??
+    };
+    if transcript.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let reply = request_provider_model(EXTRACTION_SYSTEM_PROMPT, &transcript, 2048).await?;
+    let candidates = parse_memory_candidates(&reply)
+        .into_iter()
+        .map(|candidate| MemoryEntryInput {
+            source_session: Some(session_id.clone()),
+            source_engine: Some(engine.clone()),
+            ..candidate
+        })
+        .collect::<Vec<_>>();
+
+    log::info!(
+        "[ProjectMemory] Extracted {} candidate entries from session {}",
+        candidates.len(),
+        session_id
+    );
+    Ok(candidates)
+}
+
+// ============================================================================
+// Execution Injection
+// ============================================================================
+
+/// 供三引擎执行时使用：编译项目记忆，失败或为空时返回 None（不阻断执行）
+pub fn compiled_memory_for_execution(project_path: &str) -> Option<String> {
+    let entries = {
+        let _guard = STORE_LOCK.lock().unwrap();
+        match load_memory(project_path) {
+            Ok(memory) => memory.entries,
+            Err(e) => {
+                log::warn!("[ProjectMemory] Failed to load memory, skipping: {}", e);
+                return None;
+            }
+        }
+    };
+    let compiled = compile_entries(&entries, DEFAULT_MEMORY_CONTEXT_TOKENS);
+    (!compiled.is_empty()).then_some(compiled)
+}
+
+/// 把编译后的记忆附加到提示词末尾（Codex / Gemini 没有独立的系统提示词注入点）
+pub fn append_memory_to_prompt(prompt: &str, memory: &str) -> String {
+    format!(
+        "{}\n\n--- 项目记忆 (来自 Any Code 项目记忆) ---\n{}",
+        prompt, memory
+    )
+}
+
+// ============================================================================
+// Storage
+// ============================================================================
+
+fn get_memory_path(project_path: &str) -> PathBuf {
+    Path::new(project_path).join(".anycode").join("memory.json")
+}
+
+fn load_memory(project_path: &str) -> Result<ProjectMemory, String> {
+    let path = get_memory_path(project_path);
+    if !path.exists() {
+        return Ok(ProjectMemory::default());
+    }
+
+    let size = fs::metadata(&path)
+        .map_err(|e| format!("Failed to read memory file metadata: {}", e))?
+        .len();
+    if size > MAX_MEMORY_FILE_BYTES {
+        return Err(format!(
+            "项目记忆文件过大（{} KB，上限 {} KB）: {}",
+            size / 1024,
+            MAX_MEMORY_FILE_BYTES / 1024,
+            path.display()
+        ));
+    }
+
+    let content =
+        fs::read_to_string(&path).map_err(|e| format!("Failed to read memory file: {}", e))?;
+    serde_json::from_str(&content).map_err(|e| format!("Failed to parse memory file: {}", e))
+}
+
+fn save_memory(project_path: &str, memory: &ProjectMemory) -> Result<(), String> {
+    let size = serde_json::to_vec_pretty(memory)
+        .map_err(|e| format!("Failed to serialize memory: {}", e))?
+        .len() as u64;
+    if size > MAX_MEMORY_FILE_BYTES {
+        return Err(format!(
+            "项目记忆文件将超过 {} KB 上限，请先删除部分条目",
+            MAX_MEMORY_FILE_BYTES / 1024
+        ));
+    }
+    save_json_config(memory, get_memory_path(project_path))
+}
+
+/// 去重比较用：忽略大小写与多余空白
+fn normalize_content(content: &str) -> String {
+    content
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+// ============================================================================
+// Compilation
+// ============================================================================
+
+/// 粗略估算 token 数：ASCII 约 4 字符一个 token，其他字符（如中文）按每字一个 token
+pub(crate) fn estimate_tokens(text: &str) -> usize {
+    let ascii = text.chars().filter(|c| c.is_ascii()).count();
+    let other = text.chars().count() - ascii;
+    ascii.div_ceil(4) + other
+}
+
+/// 按类型分组、组内新条目优先，在 token 预算内尽量多地放入条目
+fn compile_entries(entries: &[MemoryEntry], max_tokens: usize) -> String {
+    const HEADER: &str =
+        "# 项目记忆\n以下是本项目在以往会话中沉淀的约定、决策与事实，回答时请遵循：\n";
+
+    let mut budget = max_tokens.saturating_sub(estimate_tokens(HEADER));
+    let mut sections = Vec::new();
+
+    for kind in MemoryKind::COMPILE_ORDER {
+        let mut group: Vec<&MemoryEntry> = entries.iter().filter(|e| e.kind == kind).collect();
+        group.sort_by_key(|e| Reverse(e.created_at));
+
+        let heading = format!("\n## {}\n", kind.heading());
+        let heading_tokens = estimate_tokens(&heading);
+        let mut lines = Vec::new();
+
+        for entry in group {
+            let line = format!("- {}\n", entry.content.replace('\n', " "));
+            let cost = estimate_tokens(&line) + if lines.is_empty() { heading_tokens } else { 0 };
+            if cost > budget {
+                continue;
+            }
+            budget -= cost;
+            lines.push(line);
+        }
+
+        if !lines.is_empty() {
+            sections.push(format!("{}{}", heading, lines.concat()));
+        }
+    }
+
+    if sections.is_empty() {
+        return String::new();
+    }
+    format!("{}{}", HEADER, sections.concat())
+        .trim_end()
+        .to_string()
+}
+
+// ============================================================================
+// Extraction
+// ============================================================================
+
+/// 提取 Claude / Codex 消息 content 中的文本（字符串或 text 块数组）
+pub(crate) fn content_text(content: &Value) -> Option<String> {
+    if let Some(text) = content.as_str() {
+        return Some(text.to_string());
+    }
+    let texts: Vec<&str> = content
+        .as_array()?
+        .iter()
+        .filter_map(|block| block.get("text").and_then(|t| t.as_str()))
+        .collect();
+    (!texts.is_empty()).then(|| texts.join("\n"))
+}
+
+/// 解析单条会话记录，返回 (角色, 文本)
+fn transcript_turn(engine: &str, entry: &Value) -> Option<(String, String)> {
+    let (role, text) = match engine {
+        "claude" => {
+            if entry.get("isSidechain").and_then(|v| v.as_bool()) == Some(true)
+                || entry.get("isMeta").and_then(|v| v.as_bool()) == Some(true)
+            {
+                return None;
+            }
+            let role = entry.get("type").and_then(|t| t.as_str())?;
+            if role != "user" && role != "assistant" {
+                return None;
+            }
+            (role, content_text(entry.get("message")?.get("content")?)?)
+        }
+        "codex" => {
+            if entry.get("type").and_then(|t| t.as_str()) != Some("response_item") {
+                return None;
+            }
+            let payload = entry.get("payload")?;
+            if payload.get("type").and_then(|t| t.as_str()) != Some("message") {
+                return None;
+            }
+            let role = payload.get("role").and_then(|r| r.as_str())?;
+            if role != "user" && role != "assistant" {
+                return None;
+            }
+            let text = content_text(payload.get("content")?)?;
+            if role == "user" && is_codex_context_message(&text) {
+                return None;
+            }
+            (role, text)
+        }
+        "gemini" => {
+            let role = match entry.get("type").and_then(|t| t.as_str())? {
+                "user" => "user",
+                "gemini" => "assistant",
+                _ => return None,
+            };
+            (
+                role,
+                entry.get("content").and_then(|c| c.as_str())?.to_string(),
+            )
+        }
+        _ => return None,
+    };
+
+    let text = text.trim();
+    (!text.is_empty()).then(|| (role.to_string(), truncate_chars(text, MAX_TURN_CHARS)))
+}
+
+/// 读取会话文件并拼成对话文本，超出上限时保留最近的部分
+fn read_session_transcript(engine: &str, path: &Path) -> Result<String, String> {
+    let mut turns = Vec::new();
+
+    if engine == "gemini" {
+        ensure_loadable_in_memory(path)?;
+        let content =
+            fs::read_to_string(path).map_err(|e| format!("Failed to read session file: {}", e))?;
+        let data: Value = serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to parse session JSON: {}", e))?;
+        for message in data
+            .get("messages")
+            .and_then(|m| m.as_array())
+            .into_iter()
+            .flatten()
+        {
+            turns.extend(transcript_turn(engine, message));
+        }
+    } else {
+        for_each_line(path, |_, line| {
+            if let Ok(entry) = serde_json::from_str::<Value>(line) {
+                turns.extend(transcript_turn(engine, &entry));
+            }
+            ControlFlow::Continue(())
+        })
+        .map_err(|e| format!("Failed to read session file: {}", e))?;
+    }
+
+    let mut total = 0;
+    let mut kept = Vec::new();
+    for (role, text) in turns.into_iter().rev() {
+        let turn = format!("[{}]\n{}\n", role, text);
+        total += turn.chars().count();
+        if total > MAX_TRANSCRIPT_CHARS && !kept.is_empty() {
+            break;
+        }
+        kept.push(turn);
+    }
+    kept.reverse();
+    Ok(kept.join("\n"))
+}
+
+const EXTRACTION_SYSTEM_PROMPT: &str = "你负责为软件项目整理长期记忆。阅读下面的一次编码会话，\
+提取今后新会话中仍然有用、且无法从代码直接看出的信息：\n\
+- convention：需要遵循的约定（命名、代码风格、目录组织、工作流程）\n\
+- decision：已做出的技术决策及其理由\n\
+- fact：项目背景事实（技术栈、外部依赖、环境要求）\n\
+忽略一次性的任务细节、调试过程和寒暄。每条用一句话独立表述，使用会话所用的语言。\n\
+只输出 JSON 数组，不要输出其他内容，格式：\
+[{\"kind\": \"convention\", \"content\": \"...\"}]；没有值得记录的内容时输出 []。";
+
+/// 通过当前 Claude 代理商配置（settings.json 的 env）调用小模型，返回回复文本
+///
+/// 记忆提取与跨引擎评审的内容压缩共用
+pub(crate) async fn request_provider_model(
+    system: &str,
+    content: &str,
+    max_tokens: u32,
+) -> Result<String, String> {
+    let provider = get_current_provider_config()?;
+    let non_empty = |value: Option<String>| value.filter(|v| !v.trim().is_empty());
+
+    let base_url = non_empty(provider.anthropic_base_url)
+        .unwrap_or_else(|| DEFAULT_ANTHROPIC_BASE_URL.to_string());
+    let url = normalize_api_url(&base_url, ApiEndpointType::Anthropic);
+    let model = non_empty(provider.anthropic_small_fast_model)
+        .or_else(|| non_empty(provider.anthropic_model))
+        .unwrap_or_else(|| DEFAULT_SMALL_MODEL.to_string());
+
+    let client = Client::builder()
+        .timeout(Duration::from_secs(MODEL_REQUEST_TIMEOUT_SECS))
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+    let mut request = client
+        .post(&url)
+        .header("anthropic-version", "2023-06-01")
+        .json(&json!({
+            "model": model,
+            "max_tokens": max_tokens,
+            "system": system,
+            "messages": [{ "role": "user", "content": content }],
+        }));
+    request = if let Some(token) = non_empty(provider.anthropic_auth_token) {
+        request.header("Authorization", format!("Bearer {}", token))
+    } else if let Some(key) = non_empty(provider.anthropic_api_key) {
+        request.header("x-api-key", key)
+    } else {
+        return Err("当前代理商未配置 API 密钥，无法调用模型".to_string());
+    };
+
+    log::info!("[ProjectMemory] Requesting provider model {}", model);
+    let response = rate_limiter::send(&base_url, request)
+        .await
+        .map_err(|e| format!("模型请求失败: {}", e))?;
+    let status = response.status();
+    let body = response
+        .text()
+        .await
+        .map_err(|e| format!("Failed to read model response: {}", e))?;
+    if !status.is_success() {
+        return Err(format!(
+            "模型请求失败 ({}): {}",
+            status,
+            truncate_chars(&body, 500)
+        ));
+    }
+
+    let data: Value = serde_json::from_str(&body)
+        .map_err(|e| format!("Failed to parse model response: {}", e))?;
+    content_text(data.get("content").unwrap_or(&Value::Null))
+        .ok_or_else(|| "模型未返回文本内容".to_string())
+}
+
+/// 从模型回复中解析候选条目（容忍代码块包裹与前后说明文字）
+fn parse_memory_candidates(reply: &str) -> Vec<MemoryEntryInput> {
+    #[derive(Deserialize)]
+    struct Candidate {
+        kind: MemoryKind,
+        content: String,
+    }
+
+    let (Some(start), Some(end)) = (reply.find('['), reply.rfind(']')) else {
+        return Vec::new();
+    };
+    if end < start {
+        return Vec::new();
+    }
+    let Ok(items) = serde_json::from_str::<Vec<Value>>(&reply[start..=end]) else {
+        log::warn!("[ProjectMemory] Extraction reply is not a JSON array");
+        return Vec::new();
+    };
+
+    let mut seen = std::collections::HashSet::new();
+    items
+        .into_iter()
+        .filter_map(|item| serde_json::from_value::<Candidate>(item).ok())
+        .map(|c| (c.kind, c.content.trim().to_string()))
+        .filter(|(_, content)| !content.is_empty() && content.chars().count() <= MAX_ENTRY_CHARS)
+        .filter(|(kind, content)| seen.insert((*kind, normalize_content(content))))
+        .take(MAX_EXTRACTED_CANDIDATES)
+        .map(|(kind, content)| MemoryEntryInput {
+            kind,
+            content,
+            source_session: None,
+            source_engine: None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(kind: MemoryKind, content: &str, created_at: i64) -> MemoryEntry {
+        MemoryEntry {
+            id: content.to_string(),
+            kind,
+            content: content.to_string(),
+            created_at,
+            source_session: None,
+            source_engine: None,
+        }
+    }
+
+    #[test]
+    fn compiles_conventions_first_within_budget() {
+        let entries = vec![
+            entry(MemoryKind::Fact, "Backend is written in Rust", 1),
+            entry(
+                MemoryKind::Convention,
+                "Errors are returned as Result<_, String>",
+                2,
+            ),
+            entry(
+                MemoryKind::Decision,
+                "Use polling instead of a native file watcher",
+                3,
+            ),
+        ];
+
+        let compiled = compile_entries(&entries, 1000);
+        let convention = compiled.find("## 约定").unwrap();
+        let decision = compiled.find("## 决策").unwrap();
+        let fact = compiled.find("## 事实").unwrap();
+        assert!(convention < decision && decision < fact);
+        assert!(compiled.contains("- Errors are returned as Result<_, String>"));
+
+        let tight = compile_entries(&entries, 55);
+        assert!(tight.contains("Errors are returned"));
+        assert!(!tight.contains("Backend is written in Rust"));
+        assert_eq!(compile_entries(&[], 1000), "");
+    }
+
+    #[test]
+    fn parses_candidates_from_fenced_reply() {
+        let reply = "好的：\n```json\n[\
+            {\"kind\": \"convention\", \"content\": \" 使用 camelCase 序列化 \"},\
+            {\"kind\": \"convention\", \"content\": \"使用 camelCase 序列化\"},\
+            {\"kind\": \"opinion\", \"content\": \"ignored\"},\
+            {\"kind\": \"fact\", \"content\": \"\"}\
+        ]\n```";
+
+        let candidates = parse_memory_candidates(reply);
+        assert_eq!(candidates.len(), 1);
+        assert_eq!(candidates[0].kind, MemoryKind::Convention);
+        assert_eq!(candidates[0].content, "使用 camelCase 序列化");
+        assert!(parse_memory_candidates("没有可记录的内容").is_empty());
+    }
+
+    #[test]
+    fn keeps_latest_turns_of_codex_transcript() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("rollout.jsonl");
+        let message = |role: &str, text: &str| {
+            json!({
+                "type": "response_item",
+                "payload": {
+                    "type": "message",
+                    "role": role,
+                    "content": [{ "type": "input_text", "text": text }],
+                },
+            })
+            .to_string()
+        };
+        let lines = [
+            message(
+                "user",
+                "<environment_context>\n<cwd>/tmp</cwd>\n</environment_context>",
+            ),
+            message("user", "rename the helpers"),
+            message("assistant", "Renamed them to snake_case."),
+        ];
+        fs::write(&path, lines.join("\n")).unwrap();
+
+        let transcript = read_session_transcript("codex", &path).unwrap();
+        assert_eq!(
+            transcript,
+            "[user]\nrename the helpers\n\n[assistant]\nRenamed them to snake_case.\n"
+        );
+    }
+}
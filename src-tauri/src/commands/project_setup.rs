@@ -0,0 +1,757 @@
+//! 项目 AI 工作流初始化
+//!
+//! 新项目接入时一次性完成几件重复性的准备工作（每一步都可单独开关）：
+//! - 从内置或用户模板生成 CLAUDE.md
+//! - 创建 .claude/{commands,agents,skills} 目录结构
+//! - 往 .gitignore 追加 CLAUDE.local.md 与 .anycode/ 条目
+//! - 触发 acemcp 后台预索引
+//! - 可选：向所选引擎发送一条引导提示词，创建第一条会话
+//!
+//! 重复执行是幂等的：已存在的文件不覆盖、已有的忽略条目不重复追加，
+//! 引导会话只发送一次（记录在 <project>/.anycode/bootstrap_session.json）。
+//! `dry_run` 时只报告每一步将要执行的操作，不做任何修改。
+//! 用户模板放在 <app data>/templates/claude-md/*.md，与内置模板同 id 时覆盖内置模板。
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager};
+
+use super::acemcp::{load_acemcp_config, preindex_project, PreindexState};
+use super::prompt_history::{dispatch_prompt, PromptTarget};
+
+/// 模板中替换为项目目录名的占位符
+const PROJECT_NAME_PLACEHOLDER: &str = "{{project_name}}";
+
+/// 内置 CLAUDE.md 模板（id, 内容）
+const BUILTIN_CLAUDE_MD_TEMPLATES: &[(&str, &str)] = &[
+    (
+        "default",
+        include_str!("../../templates/claude-md/default.md"),
+    ),
+    (
+        "minimal",
+        include_str!("../../templates/claude-md/minimal.md"),
+    ),
+];
+
+/// 未指定模板时使用的模板 id
+const DEFAULT_TEMPLATE_ID: &str = "default";
+
+/// .claude 下创建的子目录
+const CLAUDE_SUBDIRS: &[&str] = &["commands", "agents", "skills"];
+
+/// 追加到 .gitignore 的条目
+const GITIGNORE_ENTRIES: &[&str] = &["CLAUDE.local.md", ".anycode/"];
+
+/// 追加条目前的注释行
+const GITIGNORE_HEADER: &str = "# Any Code 本地文件（个人指令与项目记忆等）";
+
+/// 引导会话的提示词
+const BOOTSTRAP_PROMPT: &str = "请阅读项目结构并总结：项目用途、主要目录与模块、构建和测试方式，以及你注意到的代码约定。只阅读，不要修改任何文件。";
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// 初始化选项（未提供的字段取默认值）
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ProjectSetupOptions {
+    /// 生成 CLAUDE.md
+    pub claude_md: bool,
+    /// CLAUDE.md 模板 id，默认 "default"
+    pub claude_md_template: Option<String>,
+    /// 创建 .claude/{commands,agents,skills}
+    pub claude_dirs: bool,
+    /// 追加 .gitignore 条目
+    pub gitignore: bool,
+    /// 触发 acemcp 预索引
+    pub preindex: bool,
+    /// 创建引导会话
+    pub bootstrap_session: bool,
+    /// 引导会话使用的引擎（claude / codex / gemini），默认 claude
+    pub engine: Option<String>,
+    /// 引导会话使用的模型，默认使用引擎的默认模型
+    pub model: Option<String>,
+    /// 只报告将要执行的操作
+    pub dry_run: bool,
+}
+
+impl Default for ProjectSetupOptions {
+    fn default() -> Self {
+        Self {
+            claude_md: true,
+            claude_md_template: None,
+            claude_dirs: true,
+            gitignore: true,
+            preindex: true,
+            bootstrap_session: false,
+            engine: None,
+            model: None,
+            dry_run: false,
+        }
+    }
+}
+
+/// 初始化步骤
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SetupStep {
+    ClaudeMd,
+    ClaudeDirs,
+    Gitignore,
+    Preindex,
+    BootstrapSession,
+}
+
+/// 步骤执行结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SetupStepStatus {
+    /// 已执行
+    Applied,
+    /// dry_run：将会执行
+    Planned,
+    /// 未选择或已就绪，无需执行
+    Skipped,
+    Failed,
+}
+
+/// 单个步骤的结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetupStepResult {
+    pub step: SetupStep,
+    pub status: SetupStepStatus,
+    pub detail: String,
+    /// 涉及的文件或目录
+    pub paths: Vec<String>,
+}
+
+/// 初始化结果汇总
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectSetupReport {
+    pub project_path: String,
+    pub dry_run: bool,
+    pub steps: Vec<SetupStepResult>,
+}
+
+/// CLAUDE.md 模板
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClaudeMdTemplate {
+    /// 模板 id（文件名去掉扩展名）
+    pub id: String,
+    /// "builtin" 或 "local"
+    pub source: String,
+    pub content: String,
+}
+
+fn step_result(
+    step: SetupStep,
+    status: SetupStepStatus,
+    detail: impl Into<String>,
+    paths: Vec<String>,
+) -> SetupStepResult {
+    SetupStepResult {
+        step,
+        status,
+        detail: detail.into(),
+        paths,
+    }
+}
+
+fn not_selected(step: SetupStep) -> SetupStepResult {
+    step_result(step, SetupStepStatus::Skipped, "未选择", Vec::new())
+}
+
+// ============================================================================
+// Templates
+// ============================================================================
+
+/// 用户模板目录：<app data>/templates/claude-md/
+fn get_local_templates_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    let app_data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data directory: {}", e))?;
+    Ok(app_data_dir.join("templates").join("claude-md"))
+}
+
+/// 列出内置与用户模板，用户模板覆盖同 id 的内置模板
+fn list_templates(local_dir: &Path) -> Vec<ClaudeMdTemplate> {
+    let mut templates: BTreeMap<String, ClaudeMdTemplate> = BUILTIN_CLAUDE_MD_TEMPLATES
+        .iter()
+        .map(|(id, content)| {
+            (
+                id.to_string(),
+                ClaudeMdTemplate {
+                    id: id.to_string(),
+                    source: "builtin".to_string(),
+                    content: content.to_string(),
+                },
+            )
+        })
+        .collect();
+
+    if let Ok(entries) = fs::read_dir(local_dir) {
+        for path in entries.flatten().map(|entry| entry.path()) {
+            if path.extension().and_then(|e| e.to_str()) != Some("md") {
+                continue;
+            }
+            let Some(id) = path.file_stem().and_then(|s| s.to_str()) else {
+                continue;
+            };
+            match fs::read_to_string(&path) {
+                Ok(content) => {
+                    templates.insert(
+                        id.to_string(),
+                        ClaudeMdTemplate {
+                            id: id.to_string(),
+                            source: "local".to_string(),
+                            content,
+                        },
+                    );
+                }
+                Err(e) => log::debug!("Failed to read CLAUDE.md template {:?}: {}", path, e),
+            }
+        }
+    }
+
+    templates.into_values().collect()
+}
+
+/// 用项目目录名填充模板
+fn render_template(content: &str, project_root: &Path) -> String {
+    let project_name = project_root
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("Project");
+    content.replace(PROJECT_NAME_PLACEHOLDER, project_name)
+}
+
+// ============================================================================
+// Steps
+// ============================================================================
+
+/// 生成 CLAUDE.md，已存在时不覆盖
+fn setup_claude_md(root: &Path, content: &str, dry_run: bool) -> SetupStepResult {
+    let path = root.join("CLAUDE.md");
+    let paths = vec![path.to_string_lossy().to_string()];
+    if path.exists() {
+        return step_result(
+            SetupStep::ClaudeMd,
+            SetupStepStatus::Skipped,
+            "CLAUDE.md 已存在，未覆盖",
+            paths,
+        );
+    }
+    if dry_run {
+        return step_result(
+            SetupStep::ClaudeMd,
+            SetupStepStatus::Planned,
+            "将从模板创建 CLAUDE.md",
+            paths,
+        );
+    }
+
+    // create_new 保证即使并发创建也不会覆盖已有文件
+    let written = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&path)
+        .and_then(|mut file| file.write_all(content.as_bytes()));
+    match written {
+        Ok(()) => step_result(
+            SetupStep::ClaudeMd,
+            SetupStepStatus::Applied,
+            "已从模板创建 CLAUDE.md",
+            paths,
+        ),
+        Err(e) => step_result(
+            SetupStep::ClaudeMd,
+            SetupStepStatus::Failed,
+            format!("创建 CLAUDE.md 失败: {}", e),
+            paths,
+        ),
+    }
+}
+
+/// 创建 .claude 下缺失的子目录
+fn setup_claude_dirs(root: &Path, dry_run: bool) -> SetupStepResult {
+    let missing: Vec<PathBuf> = CLAUDE_SUBDIRS
+        .iter()
+        .map(|dir| root.join(".claude").join(dir))
+        .filter(|dir| !dir.is_dir())
+        .collect();
+    let paths = missing
+        .iter()
+        .map(|dir| dir.to_string_lossy().to_string())
+        .collect();
+
+    if missing.is_empty() {
+        return step_result(
+            SetupStep::ClaudeDirs,
+            SetupStepStatus::Skipped,
+            ".claude 目录结构已存在",
+            paths,
+        );
+    }
+    if dry_run {
+        return step_result(
+            SetupStep::ClaudeDirs,
+            SetupStepStatus::Planned,
+            format!("将创建 {} 个目录", missing.len()),
+            paths,
+        );
+    }
+
+    match missing.iter().try_for_each(fs::create_dir_all) {
+        Ok(()) => step_result(
+            SetupStep::ClaudeDirs,
+            SetupStepStatus::Applied,
+            format!("已创建 {} 个目录", missing.len()),
+            paths,
+        ),
+        Err(e) => step_result(
+            SetupStep::ClaudeDirs,
+            SetupStepStatus::Failed,
+            format!("创建目录失败: {}", e),
+            paths,
+        ),
+    }
+}
+
+/// 忽略规则的比较形式：去掉首尾的 `/`
+fn normalize_ignore_pattern(pattern: &str) -> &str {
+    pattern.trim().trim_start_matches('/').trim_end_matches('/')
+}
+
+/// .gitignore 中尚未包含的条目
+fn missing_gitignore_entries(existing: &str) -> Vec<&'static str> {
+    let present: Vec<&str> = existing.lines().map(normalize_ignore_pattern).collect();
+    GITIGNORE_ENTRIES
+        .iter()
+        .copied()
+        .filter(|entry| !present.contains(&normalize_ignore_pattern(entry)))
+        .collect()
+}
+
+/// 在 .gitignore 末尾追加条目（带注释行，与原内容隔一个空行）
+fn append_gitignore_entries(existing: &str, entries: &[&str]) -> String {
+    let mut content = existing.to_string();
+    if !content.is_empty() {
+        if !content.ends_with('\n') {
+            content.push('\n');
+        }
+        content.push('\n');
+    }
+    content.push_str(GITIGNORE_HEADER);
+    content.push('\n');
+    for entry in entries {
+        content.push_str(entry);
+        content.push('\n');
+    }
+    content
+}
+
+/// 往 .gitignore 追加缺失的条目
+fn setup_gitignore(root: &Path, dry_run: bool) -> SetupStepResult {
+    let path = root.join(".gitignore");
+    let paths = vec![path.to_string_lossy().to_string()];
+    let existing = match fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => {
+            return step_result(
+                SetupStep::Gitignore,
+                SetupStepStatus::Failed,
+                format!("读取 .gitignore 失败: {}", e),
+                paths,
+            )
+        }
+    };
+
+    let missing = missing_gitignore_entries(&existing);
+    if missing.is_empty() {
+        return step_result(
+            SetupStep::Gitignore,
+            SetupStepStatus::Skipped,
+            ".gitignore 已包含所需条目",
+            paths,
+        );
+    }
+    if dry_run {
+        return step_result(
+            SetupStep::Gitignore,
+            SetupStepStatus::Planned,
+            format!("将追加: {}", missing.join(", ")),
+            paths,
+        );
+    }
+
+    match fs::write(&path, append_gitignore_entries(&existing, &missing)) {
+        Ok(()) => step_result(
+            SetupStep::Gitignore,
+            SetupStepStatus::Applied,
+            format!("已追加: {}", missing.join(", ")),
+            paths,
+        ),
+        Err(e) => step_result(
+            SetupStep::Gitignore,
+            SetupStepStatus::Failed,
+            format!("写入 .gitignore 失败: {}", e),
+            paths,
+        ),
+    }
+}
+
+/// 触发 acemcp 后台预索引（未配置 acemcp 时跳过）
+async fn setup_preindex(app: &AppHandle, project_path: &str, dry_run: bool) -> SetupStepResult {
+    let configured = load_acemcp_config()
+        .await
+        .map(|config| !config.base_url.trim().is_empty() && !config.token.trim().is_empty())
+        .unwrap_or(false);
+    if !configured {
+        return step_result(
+            SetupStep::Preindex,
+            SetupStepStatus::Skipped,
+            "未配置 acemcp，跳过预索引",
+            Vec::new(),
+        );
+    }
+    if dry_run {
+        return step_result(
+            SetupStep::Preindex,
+            SetupStepStatus::Planned,
+            "将在后台预索引项目",
+            Vec::new(),
+        );
+    }
+
+    let state = app.state::<PreindexState>();
+    match preindex_project(app.clone(), state, project_path.to_string()).await {
+        Ok(()) => step_result(
+            SetupStep::Preindex,
+            SetupStepStatus::Applied,
+            "已在后台开始预索引",
+            Vec::new(),
+        ),
+        Err(e) => step_result(
+            SetupStep::Preindex,
+            SetupStepStatus::Failed,
+            format!("启动预索引失败: {}", e),
+            Vec::new(),
+        ),
+    }
+}
+
+/// 已发送的引导会话记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BootstrapMarker {
+    engine: String,
+    sent_at: String,
+}
+
+fn bootstrap_marker_path(root: &Path) -> PathBuf {
+    root.join(".anycode").join("bootstrap_session.json")
+}
+
+fn read_bootstrap_marker(root: &Path) -> Option<BootstrapMarker> {
+    let content = fs::read_to_string(bootstrap_marker_path(root)).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+/// 写入引导会话记录；记录已存在时返回 `AlreadyExists`，并发的两次初始化只有一次能发送
+fn claim_bootstrap_marker(root: &Path, engine: &str) -> std::io::Result<()> {
+    let path = bootstrap_marker_path(root);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let marker = BootstrapMarker {
+        engine: engine.to_string(),
+        sent_at: chrono::Utc::now().to_rfc3339(),
+    };
+    let mut file = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&path)?;
+    file.write_all(serde_json::to_string_pretty(&marker)?.as_bytes())
+}
+
+/// 向所选引擎发送引导提示词，创建第一条会话（已发送过时跳过）
+async fn setup_bootstrap_session(
+    app: &AppHandle,
+    project_path: &str,
+    engine: &str,
+    model: Option<String>,
+    dry_run: bool,
+) -> SetupStepResult {
+    let root = Path::new(project_path);
+    let paths = vec![bootstrap_marker_path(root).to_string_lossy().to_string()];
+    if let Some(marker) = read_bootstrap_marker(root) {
+        return step_result(
+            SetupStep::BootstrapSession,
+            SetupStepStatus::Skipped,
+            format!(
+                "已于 {} 向 {} 发送过引导提示词",
+                marker.sent_at, marker.engine
+            ),
+            paths,
+        );
+    }
+    if dry_run {
+        return step_result(
+            SetupStep::BootstrapSession,
+            SetupStepStatus::Planned,
+            format!("将向 {} 发送引导提示词", engine),
+            paths,
+        );
+    }
+
+    match claim_bootstrap_marker(root, engine) {
+        Ok(()) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+            return step_result(
+                SetupStep::BootstrapSession,
+                SetupStepStatus::Skipped,
+                "已发送过引导提示词",
+                paths,
+            );
+        }
+        Err(e) => {
+            return step_result(
+                SetupStep::BootstrapSession,
+                SetupStepStatus::Failed,
+                format!("记录引导会话失败: {}", e),
+                paths,
+            );
+        }
+    }
+
+    match dispatch_prompt(
+        app.clone(),
+        engine,
+        project_path,
+        PromptTarget::New,
+        BOOTSTRAP_PROMPT,
+        model,
+        None,
+    )
+    .await
+    {
+        Ok(()) => step_result(
+            SetupStep::BootstrapSession,
+            SetupStepStatus::Applied,
+            format!("已向 {} 发送引导提示词", engine),
+            paths,
+        ),
+        Err(e) => {
+            // 没有发出去，下次初始化可以重试
+            let _ = fs::remove_file(bootstrap_marker_path(root));
+            step_result(
+                SetupStep::BootstrapSession,
+                SetupStepStatus::Failed,
+                format!("创建引导会话失败: {}", e),
+                paths,
+            )
+        }
+    }
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/// 列出可用的 CLAUDE.md 模板（内置与用户模板）
+#[tauri::command]
+pub async fn list_claude_md_templates(app: AppHandle) -> Result<Vec<ClaudeMdTemplate>, String> {
+    Ok(list_templates(&get_local_templates_dir(&app)?))
+}
+
+/// 按选项初始化项目的 AI 工作流，返回每一步的结果
+///
+/// 参数不合法（目录不存在、模板或引擎未知）时直接返回错误，不执行任何步骤；
+/// 单个步骤失败不影响后续步骤，失败原因记录在对应的步骤结果中
+#[tauri::command]
+pub async fn init_project_ai_setup(
+    app: AppHandle,
+    project_path: String,
+    options: Option<ProjectSetupOptions>,
+) -> Result<ProjectSetupReport, String> {
+    let options = options.unwrap_or_default();
+    let root = PathBuf::from(&project_path);
+    if !root.is_dir() {
+        return Err(format!("项目目录不存在: {}", project_path));
+    }
+
+    let engine = options.engine.as_deref().unwrap_or("claude");
+    if options.bootstrap_session && !matches!(engine, "claude" | "codex" | "gemini") {
+        return Err(format!("Unknown engine: {}", engine));
+    }
+
+    let template = if options.claude_md {
+        let template_id = options
+            .claude_md_template
+            .as_deref()
+            .unwrap_or(DEFAULT_TEMPLATE_ID);
+        let template = list_templates(&get_local_templates_dir(&app)?)
+            .into_iter()
+            .find(|t| t.id == template_id)
+            .ok_or_else(|| format!("Template '{}' not found", template_id))?;
+        Some(render_template(&template.content, &root))
+    } else {
+        None
+    };
+
+    log::info!(
+        "Initializing AI setup for project: {} (dry_run: {})",
+        project_path,
+        options.dry_run
+    );
+
+    let dry_run = options.dry_run;
+    let mut steps = vec![
+        match &template {
+            Some(content) => setup_claude_md(&root, content, dry_run),
+            None => not_selected(SetupStep::ClaudeMd),
+        },
+        if options.claude_dirs {
+            setup_claude_dirs(&root, dry_run)
+        } else {
+            not_selected(SetupStep::ClaudeDirs)
+        },
+        if options.gitignore {
+            setup_gitignore(&root, dry_run)
+        } else {
+            not_selected(SetupStep::Gitignore)
+        },
+    ];
+
+    steps.push(if options.preindex {
+        setup_preindex(&app, &project_path, dry_run).await
+    } else {
+        not_selected(SetupStep::Preindex)
+    });
+
+    steps.push(if options.bootstrap_session {
+        setup_bootstrap_session(&app, &project_path, engine, options.model.clone(), dry_run).await
+    } else {
+        not_selected(SetupStep::BootstrapSession)
+    });
+
+    for step in &steps {
+        log::info!(
+            "[ProjectSetup] {:?}: {:?} - {}",
+            step.step,
+            step.status,
+            step.detail
+        );
+    }
+
+    Ok(ProjectSetupReport {
+        project_path,
+        dry_run,
+        steps,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn steps_are_idempotent_and_dry_run_changes_nothing() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path();
+        let content = render_template(BUILTIN_CLAUDE_MD_TEMPLATES[0].1, root);
+        assert!(!content.contains(PROJECT_NAME_PLACEHOLDER));
+
+        // dry_run 不产生任何文件
+        assert_eq!(
+            setup_claude_md(root, &content, true).status,
+            SetupStepStatus::Planned
+        );
+        assert_eq!(
+            setup_claude_dirs(root, true).status,
+            SetupStepStatus::Planned
+        );
+        assert_eq!(setup_gitignore(root, true).status, SetupStepStatus::Planned);
+        assert_eq!(fs::read_dir(root).unwrap().count(), 0);
+
+        assert_eq!(
+            setup_claude_md(root, &content, false).status,
+            SetupStepStatus::Applied
+        );
+        assert_eq!(
+            setup_claude_dirs(root, false).status,
+            SetupStepStatus::Applied
+        );
+        assert_eq!(
+            setup_gitignore(root, false).status,
+            SetupStepStatus::Applied
+        );
+        assert!(root.join(".claude/skills").is_dir());
+
+        // 重复执行：已有文件不覆盖，条目不重复追加
+        fs::write(root.join("CLAUDE.md"), "custom").unwrap();
+        assert_eq!(
+            setup_claude_md(root, &content, false).status,
+            SetupStepStatus::Skipped
+        );
+        assert_eq!(
+            fs::read_to_string(root.join("CLAUDE.md")).unwrap(),
+            "custom"
+        );
+        assert_eq!(
+            setup_claude_dirs(root, false).status,
+            SetupStepStatus::Skipped
+        );
+        let gitignore = fs::read_to_string(root.join(".gitignore")).unwrap();
+        assert_eq!(
+            setup_gitignore(root, false).status,
+            SetupStepStatus::Skipped
+        );
+        assert_eq!(
+            fs::read_to_string(root.join(".gitignore")).unwrap(),
+            gitignore
+        );
+    }
+
+    #[test]
+    fn gitignore_only_appends_missing_entries() {
+        let existing = "node_modules\n/.anycode";
+        assert_eq!(missing_gitignore_entries(existing), vec!["CLAUDE.local.md"]);
+        assert_eq!(
+            append_gitignore_entries(existing, &["CLAUDE.local.md"]),
+            format!(
+                "node_modules\n/.anycode\n\n{}\nCLAUDE.local.md\n",
+                GITIGNORE_HEADER
+            )
+        );
+        assert_eq!(
+            append_gitignore_entries("", GITIGNORE_ENTRIES),
+            format!("{}\nCLAUDE.local.md\n.anycode/\n", GITIGNORE_HEADER)
+        );
+        assert!(missing_gitignore_entries("CLAUDE.local.md\n.anycode/\n").is_empty());
+    }
+
+    #[test]
+    fn bootstrap_session_is_claimed_only_once() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path();
+        assert!(read_bootstrap_marker(root).is_none());
+
+        claim_bootstrap_marker(root, "codex").unwrap();
+        assert_eq!(
+            claim_bootstrap_marker(root, "claude").unwrap_err().kind(),
+            std::io::ErrorKind::AlreadyExists
+        );
+        assert_eq!(read_bootstrap_marker(root).unwrap().engine, "codex");
+    }
+}
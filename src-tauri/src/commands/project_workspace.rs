@@ -0,0 +1,409 @@
+//! 项目工作状态的保存与恢复
+//!
+//! 重新打开项目时回到上次的工作上下文：当前会话（engine + id）、输入框草稿 id、
+//! 侧边栏选中的面板、撤回面板是否展开，以及前端自行解释的不透明 JSON（滚动位置等）。
+//! 与多窗口布局互补：这里按项目维度保存，窗口布局按窗口维度保存。
+//!
+//! - 存储：~/.anycode/project_workspaces.json，以规范化后的项目路径为键
+//! - 合并保存：请求中未出现的字段保留已保存的值，各处只需提交自己负责的字段
+//! - 写入节流：保存先更新内存，距上次写盘不足 `WRITE_INTERVAL` 时延后合并写入，
+//!   应用退出时由 `flush_pending_states` 写入尚未落盘的状态
+//! - 恢复：上次的会话已被删除或清理到回收站时，`current_session` 置空并返回原因
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use super::claude::normalize_path_for_comparison;
+use super::messages::{t, MessageKey};
+use super::session_utils::resolve_session_file;
+use crate::utils::config_utils::{load_json_config, save_json_config, ConfigPathBuilder};
+
+/// 两次写盘的最小间隔
+const WRITE_INTERVAL: Duration = Duration::from_secs(2);
+
+/// 最多保存的项目数，超出时丢弃最久未更新的项目
+const MAX_PROJECT_STATES: usize = 200;
+
+/// 不透明 UI 状态的大小上限（字节）
+const MAX_UI_STATE_BYTES: usize = 64 * 1024;
+
+/// 上次打开的会话
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceSession {
+    /// 引擎类型: "claude" | "codex" | "gemini"
+    pub engine: String,
+    pub session_id: String,
+    /// Claude 会话所在的 ~/.claude/projects 目录名；其他引擎忽略
+    #[serde(default)]
+    pub project_id: Option<String>,
+}
+
+/// 项目工作状态
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectWorkspaceState {
+    #[serde(default)]
+    pub current_session: Option<WorkspaceSession>,
+    /// 输入框草稿的存储 id
+    #[serde(default)]
+    pub draft_id: Option<String>,
+    /// 侧边栏选中的面板
+    #[serde(default)]
+    pub active_panel: Option<String>,
+    /// 撤回面板是否展开
+    #[serde(default)]
+    pub rewind_panel_expanded: bool,
+    /// 前端自行解释的状态（滚动位置等）
+    #[serde(default)]
+    pub ui_state: serde_json::Value,
+    /// 最后保存时间（Unix 秒），由后端写入
+    #[serde(default)]
+    pub updated_at: i64,
+}
+
+/// 保存请求：字段缺失表示保留原值，显式的 null 表示清空
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceStateUpdate {
+    #[serde(default, deserialize_with = "present")]
+    pub current_session: Option<Option<WorkspaceSession>>,
+    #[serde(default, deserialize_with = "present")]
+    pub draft_id: Option<Option<String>>,
+    #[serde(default, deserialize_with = "present")]
+    pub active_panel: Option<Option<String>>,
+    #[serde(default)]
+    pub rewind_panel_expanded: Option<bool>,
+    #[serde(default, deserialize_with = "present")]
+    pub ui_state: Option<serde_json::Value>,
+}
+
+/// 字段出现即为 Some（包括 null），配合 `#[serde(default)]` 区分缺失与 null
+fn present<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    T::deserialize(deserializer).map(Some)
+}
+
+impl WorkspaceStateUpdate {
+    /// 把请求中出现的字段合并到已保存的状态
+    fn apply_to(self, state: &mut ProjectWorkspaceState) {
+        if let Some(current_session) = self.current_session {
+            state.current_session = current_session;
+        }
+        if let Some(draft_id) = self.draft_id {
+            state.draft_id = draft_id;
+        }
+        if let Some(active_panel) = self.active_panel {
+            state.active_panel = active_panel;
+        }
+        if let Some(expanded) = self.rewind_panel_expanded {
+            state.rewind_panel_expanded = expanded;
+        }
+        if let Some(ui_state) = self.ui_state {
+            state.ui_state = ui_state;
+        }
+    }
+}
+
+/// 恢复结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RestoredWorkspaceState {
+    #[serde(flatten)]
+    pub state: ProjectWorkspaceState,
+    /// 上次的会话已不可用、`current_session` 被置空时的原因
+    pub session_cleared_reason: Option<String>,
+}
+
+/// 会话当前是否还能打开
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SessionAvailability {
+    Available,
+    Deleted,
+    /// 被会话清理移到了回收站
+    Archived,
+}
+
+#[derive(Default)]
+struct WorkspaceStore {
+    /// 首次访问时从磁盘加载
+    states: Option<HashMap<String, ProjectWorkspaceState>>,
+    /// 内存中有尚未写盘的修改
+    dirty: bool,
+    last_write: Option<Instant>,
+    /// 已安排延后写入
+    flush_scheduled: bool,
+}
+
+impl WorkspaceStore {
+    fn states(&mut self) -> &mut HashMap<String, ProjectWorkspaceState> {
+        self.states.get_or_insert_with(|| {
+            get_store_path()
+                .and_then(|path| load_json_config(&path))
+                .unwrap_or_else(|e| {
+                    log::warn!("[Workspace] Failed to load project workspace states: {}", e);
+                    HashMap::new()
+                })
+        })
+    }
+
+    fn write(&mut self) -> Result<(), String> {
+        let states = self.states().clone();
+        save_json_config(&states, get_store_path()?)?;
+        self.dirty = false;
+        self.last_write = Some(Instant::now());
+        Ok(())
+    }
+}
+
+static STORE: Lazy<Mutex<WorkspaceStore>> = Lazy::new(|| Mutex::new(WorkspaceStore::default()));
+
+/// 保存项目工作状态；未提供的字段保留已保存的值
+#[tauri::command]
+pub async fn save_project_workspace_state(
+    project_path: String,
+    state: WorkspaceStateUpdate,
+) -> Result<(), String> {
+    if project_path.trim().is_empty() {
+        return Err("Project path is required".to_string());
+    }
+    let ui_state_bytes = serde_json::to_vec(&state.ui_state)
+        .map_err(|e| format!("Failed to serialize UI state: {}", e))?
+        .len();
+    if ui_state_bytes > MAX_UI_STATE_BYTES {
+        return Err(format!(
+            "UI state is too large ({} bytes, limit {} bytes)",
+            ui_state_bytes, MAX_UI_STATE_BYTES
+        ));
+    }
+
+    let delay = {
+        let mut store = STORE.lock().map_err(|e| e.to_string())?;
+        let states = store.states();
+        let saved = states.entry(workspace_key(&project_path)).or_default();
+        state.apply_to(saved);
+        saved.updated_at = chrono::Utc::now().timestamp();
+        prune_oldest(states, MAX_PROJECT_STATES);
+        store.dirty = true;
+
+        let since_write = store.last_write.map(|at| at.elapsed());
+        match since_write {
+            Some(elapsed) if elapsed < WRITE_INTERVAL => {
+                if store.flush_scheduled {
+                    return Ok(());
+                }
+                store.flush_scheduled = true;
+                WRITE_INTERVAL - elapsed
+            }
+            _ => return store.write(),
+        }
+    };
+
+    tokio::spawn(async move {
+        tokio::time::sleep(delay).await;
+        flush_pending_states();
+    });
+    Ok(())
+}
+
+/// 获取项目工作状态；从未保存过时返回 None
+#[tauri::command]
+pub async fn get_project_workspace_state(
+    project_path: String,
+) -> Result<Option<RestoredWorkspaceState>, String> {
+    let key = workspace_key(&project_path);
+    let saved = {
+        let mut store = STORE.lock().map_err(|e| e.to_string())?;
+        store.states().get(&key).cloned()
+    };
+    let Some(mut state) = saved else {
+        return Ok(None);
+    };
+
+    let Some(session) = state.current_session.clone() else {
+        return Ok(Some(RestoredWorkspaceState {
+            state,
+            session_cleared_reason: None,
+        }));
+    };
+    let availability = {
+        let project_path = project_path.clone();
+        tokio::task::spawn_blocking(move || session_availability(&session, &project_path))
+            .await
+            .map_err(|e| format!("Session lookup task failed: {}", e))?
+    };
+
+    let reason = match availability {
+        SessionAvailability::Available => None,
+        SessionAvailability::Deleted => Some(t(MessageKey::WorkspaceSessionDeleted)),
+        SessionAvailability::Archived => Some(t(MessageKey::WorkspaceSessionArchived)),
+    };
+    if reason.is_some() {
+        log::info!(
+            "[Workspace] Last session of {} is no longer available ({:?})",
+            project_path,
+            availability
+        );
+        state.current_session = None;
+        // 清除已失效的会话，下次打开项目不再提示
+        let mut store = STORE.lock().map_err(|e| e.to_string())?;
+        if let Some(saved) = store.states().get_mut(&key) {
+            saved.current_session = None;
+            store.dirty = true;
+        }
+        if !store.flush_scheduled {
+            store.write()?;
+        }
+    }
+
+    Ok(Some(RestoredWorkspaceState {
+        state,
+        session_cleared_reason: reason,
+    }))
+}
+
+/// 写入尚未落盘的工作状态（延后写入到期与应用退出时调用）
+pub fn flush_pending_states() {
+    let Ok(mut store) = STORE.lock() else {
+        return;
+    };
+    store.flush_scheduled = false;
+    if store.dirty {
+        if let Err(e) = store.write() {
+            log::warn!("[Workspace] Failed to save project workspace states: {}", e);
+        }
+    }
+}
+
+fn session_availability(session: &WorkspaceSession, project_path: &str) -> SessionAvailability {
+    // Claude 按项目目录名定位会话，Gemini 按项目路径，Codex 两者都不需要
+    let project_id_or_path = match (session.engine.as_str(), session.project_id.as_deref()) {
+        ("claude", Some(project_id)) => project_id,
+        _ => project_path,
+    };
+    if resolve_session_file(&session.engine, &session.session_id, project_id_or_path).is_ok() {
+        SessionAvailability::Available
+    } else if session.engine == "gemini"
+        && super::gemini::retention::is_session_in_trash(&session.session_id)
+    {
+        SessionAvailability::Archived
+    } else {
+        SessionAvailability::Deleted
+    }
+}
+
+/// 丢弃最久未更新的项目，直到不超过 `max` 个
+fn prune_oldest(states: &mut HashMap<String, ProjectWorkspaceState>, max: usize) {
+    if states.len() <= max {
+        return;
+    }
+    let mut by_age: Vec<(i64, String)> = states
+        .iter()
+        .map(|(key, state)| (state.updated_at, key.clone()))
+        .collect();
+    by_age.sort();
+    for (_, key) in by_age.into_iter().take(states.len() - max) {
+        states.remove(&key);
+    }
+}
+
+fn workspace_key(project_path: &str) -> String {
+    normalize_path_for_comparison(project_path.trim())
+}
+
+fn get_store_path() -> Result<PathBuf, String> {
+    Ok(ConfigPathBuilder::from_home_subdir(".anycode")?.build("project_workspaces.json"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state(updated_at: i64) -> ProjectWorkspaceState {
+        ProjectWorkspaceState {
+            updated_at,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn project_paths_share_a_key_across_spellings() {
+        assert_eq!(
+            workspace_key("C:\\Work\\App\\"),
+            workspace_key("c:/work/app")
+        );
+        assert_eq!(
+            workspace_key(" /home/me/app/ "),
+            workspace_key("/home/me/app")
+        );
+        assert_ne!(
+            workspace_key("/home/me/app"),
+            workspace_key("/home/me/app2")
+        );
+    }
+
+    #[test]
+    fn pruning_drops_the_least_recently_updated_projects() {
+        let mut states: HashMap<String, ProjectWorkspaceState> = (0..5)
+            .map(|i| (format!("p{}", i), state(100 + i)))
+            .collect();
+        prune_oldest(&mut states, 3);
+
+        let mut kept: Vec<&String> = states.keys().collect();
+        kept.sort();
+        assert_eq!(kept, vec!["p2", "p3", "p4"]);
+    }
+
+    #[test]
+    fn older_state_files_deserialize_with_defaults() {
+        let state: ProjectWorkspaceState =
+            serde_json::from_str(r#"{"currentSession": {"engine": "codex", "sessionId": "abc"}}"#)
+                .unwrap();
+        assert_eq!(
+            state.current_session,
+            Some(WorkspaceSession {
+                engine: "codex".to_string(),
+                session_id: "abc".to_string(),
+                project_id: None,
+            })
+        );
+        assert!(!state.rewind_panel_expanded);
+        assert!(state.ui_state.is_null());
+    }
+
+    #[test]
+    fn saving_keeps_fields_the_update_leaves_out() {
+        let mut saved = ProjectWorkspaceState {
+            draft_id: Some("old-draft".to_string()),
+            active_panel: Some("rewind".to_string()),
+            rewind_panel_expanded: true,
+            ui_state: serde_json::json!({ "scrollTop": 120 }),
+            ..Default::default()
+        };
+
+        let update: WorkspaceStateUpdate = serde_json::from_str(
+            r#"{"currentSession": {"engine": "gemini", "sessionId": "s1"}, "draftId": "s1"}"#,
+        )
+        .unwrap();
+        update.apply_to(&mut saved);
+        assert_eq!(saved.current_session.as_ref().unwrap().session_id, "s1");
+        assert_eq!(saved.draft_id.as_deref(), Some("s1"));
+        assert_eq!(saved.active_panel.as_deref(), Some("rewind"));
+        assert!(saved.rewind_panel_expanded);
+        assert_eq!(saved.ui_state["scrollTop"], 120);
+
+        let update: WorkspaceStateUpdate =
+            serde_json::from_str(r#"{"currentSession": null, "activePanel": null}"#).unwrap();
+        update.apply_to(&mut saved);
+        assert!(saved.current_session.is_none());
+        assert!(saved.active_panel.is_none());
+        assert_eq!(saved.draft_id.as_deref(), Some("s1"));
+    }
+}
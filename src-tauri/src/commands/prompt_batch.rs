@@ -0,0 +1,789 @@
+//! 批量提示词执行（脚本化任务队列）
+//!
+//! 从 JSON 数组或 markdown 清单导入一组提示词，按顺序交给指定引擎执行：
+//! 每条在统一的任务完成事件（`task_actions::subscribe_task_completions`）到达后自动发送下一条。
+//!
+//! - 批次持久化：~/.anycode/prompt_batches.json（保留最近 50 个批次）
+//! - 应用重启时处于运行中的批次会变为"已暂停"，可再次调用 `run_prompt_batch` 继续
+//! - 暂停/取消在当前这条执行结束后生效，不会强制终止正在运行的进程
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+use tokio::sync::broadcast::error::RecvError;
+
+use super::prompt_history::{dispatch_prompt_traced, PromptTarget};
+use super::task_actions::{subscribe_task_completions, TaskCompletionEvent};
+use crate::utils::config_utils::{load_json_config, save_json_config, ConfigPathBuilder};
+
+/// 最多保留的批次数
+const MAX_STORED_BATCHES: usize = 50;
+
+/// 单条提示词等待完成事件的最长时间
+const ITEM_TIMEOUT_SECS: u64 = 2 * 60 * 60;
+
+/// 批次运行控制信号
+const CONTROL_RUN: u8 = 0;
+const CONTROL_PAUSE: u8 = 1;
+const CONTROL_CANCEL: u8 = 2;
+
+/// 串行化批次文件的读改写
+static STORE_LOCK: Mutex<()> = Mutex::new(());
+
+/// 正在运行的批次及其控制信号
+static RUNNING_BATCHES: Lazy<Mutex<HashMap<String, Arc<AtomicU8>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// 导入的单条提示词（可带模式/模型覆盖）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchPromptInput {
+    pub prompt: String,
+    #[serde(default)]
+    pub mode: Option<String>,
+    #[serde(default)]
+    pub model: Option<String>,
+}
+
+/// JSON 导入时允许直接使用字符串
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum BatchPromptEntry {
+    Text(String),
+    Item(BatchPromptInput),
+}
+
+/// 会话策略
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum BatchSessionStrategy {
+    /// 每条提示词新建会话
+    #[default]
+    NewSession,
+    /// 所有提示词依次发送到同一个会话
+    SameSession,
+}
+
+/// 批次执行选项
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchOptions {
+    pub project_path: String,
+    #[serde(default)]
+    pub by: BatchSessionStrategy,
+    /// SameSession 时续接的已有会话；为空则第一条新建会话
+    #[serde(default)]
+    pub session_id: Option<String>,
+    /// 默认模型（单条可覆盖）
+    #[serde(default)]
+    pub model: Option<String>,
+    /// 默认模式（单条可覆盖）
+    #[serde(default)]
+    pub mode: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum BatchItemStatus {
+    Pending,
+    Running,
+    Succeeded,
+    Failed,
+    Cancelled,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum BatchStatus {
+    Ready,
+    Running,
+    Paused,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+/// 批次中的一条提示词及其执行结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchItem {
+    pub index: usize,
+    pub prompt: String,
+    pub mode: Option<String>,
+    pub model: Option<String>,
+    pub status: BatchItemStatus,
+    /// 开始时间（RFC3339）
+    pub started_at: Option<String>,
+    pub duration_ms: Option<u64>,
+    /// 完成事件中的会话 ID
+    pub session_id: Option<String>,
+    /// 结果摘要
+    pub summary: Option<String>,
+    pub error: Option<String>,
+}
+
+/// 提示词批次
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PromptBatch {
+    pub id: String,
+    pub engine: String,
+    pub options: BatchOptions,
+    pub items: Vec<BatchItem>,
+    pub status: BatchStatus,
+    pub stop_on_failure: bool,
+    /// SameSession 模式下续接的会话（Claude 在首条完成后获得 CLI 会话 ID）
+    pub session_id: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct PromptBatchStore {
+    batches: Vec<PromptBatch>,
+}
+
+// ============================================================================
+// Parsing
+// ============================================================================
+
+/// 解析 JSON 数组：元素可以是字符串或 `{ prompt, mode?, model? }`
+fn parse_json_prompts(content: &str) -> Result<Vec<BatchPromptInput>, String> {
+    let entries: Vec<BatchPromptEntry> =
+        serde_json::from_str(content).map_err(|e| format!("Invalid prompt batch JSON: {}", e))?;
+
+    Ok(entries
+        .into_iter()
+        .map(|entry| match entry {
+            BatchPromptEntry::Text(prompt) => BatchPromptInput {
+                prompt,
+                mode: None,
+                model: None,
+            },
+            BatchPromptEntry::Item(item) => item,
+        })
+        .collect())
+}
+
+/// 列表项标记（`-`/`*`/`+`/`1.`/`1)`，可带 `[ ]` 任务框）后的内容
+fn strip_list_marker(line: &str) -> Option<&str> {
+    let trimmed = line.trim_start();
+    if line.len() - trimmed.len() > 3 {
+        return None;
+    }
+
+    let rest = if let Some(rest) = trimmed
+        .strip_prefix("- ")
+        .or_else(|| trimmed.strip_prefix("* "))
+        .or_else(|| trimmed.strip_prefix("+ "))
+    {
+        rest
+    } else {
+        let digits = trimmed.chars().take_while(|c| c.is_ascii_digit()).count();
+        if digits == 0 {
+            return None;
+        }
+        trimmed[digits..]
+            .strip_prefix(". ")
+            .or_else(|| trimmed[digits..].strip_prefix(") "))?
+    };
+
+    let rest = rest.trim_start();
+    Some(
+        ["[ ] ", "[x] ", "[X] "]
+            .iter()
+            .find_map(|checkbox| rest.strip_prefix(checkbox))
+            .unwrap_or(rest),
+    )
+}
+
+/// 解析条目开头的覆盖标记：`[mode: full-auto] [model: gpt-5] 提示词`
+fn parse_item_overrides(text: &str) -> BatchPromptInput {
+    let mut mode = None;
+    let mut model = None;
+    let mut rest = text.trim();
+
+    while let Some(inner) = rest.strip_prefix('[') {
+        let Some(end) = inner.find(']') else {
+            break;
+        };
+        let tag = &inner[..end];
+        let Some((key, value)) = tag.split_once([':', '=']) else {
+            break;
+        };
+        let value = value.trim().to_string();
+        match key.trim().to_ascii_lowercase().as_str() {
+            "mode" => mode = Some(value),
+            "model" => model = Some(value),
+            _ => break,
+        }
+        rest = inner[end + 1..].trim_start();
+    }
+
+    BatchPromptInput {
+        prompt: rest.to_string(),
+        mode,
+        model,
+    }
+}
+
+/// 解析 markdown 清单：每个列表项是一条提示词，缩进的后续行并入上一条
+fn parse_markdown_prompts(content: &str) -> Vec<BatchPromptInput> {
+    let mut items: Vec<String> = Vec::new();
+    let mut in_item = false;
+
+    for line in content.lines() {
+        if let Some(text) = strip_list_marker(line) {
+            items.push(text.to_string());
+            in_item = true;
+        } else if line.trim().is_empty() {
+            continue;
+        } else if in_item && line.starts_with([' ', '\t']) {
+            if let Some(last) = items.last_mut() {
+                last.push('\n');
+                last.push_str(line.trim());
+            }
+        } else {
+            // 标题、段落等非列表内容结束当前条目
+            in_item = false;
+        }
+    }
+
+    items
+        .iter()
+        .map(|item| parse_item_overrides(item))
+        .filter(|item| !item.prompt.trim().is_empty())
+        .collect()
+}
+
+fn parse_prompt_file(path: &str) -> Result<Vec<BatchPromptInput>, String> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read prompt file {}: {}", path, e))?;
+
+    let is_json =
+        path.to_ascii_lowercase().ends_with(".json") || content.trim_start().starts_with('[');
+    if is_json {
+        parse_json_prompts(&content)
+    } else {
+        Ok(parse_markdown_prompts(&content))
+    }
+}
+
+// ============================================================================
+// Storage
+// ============================================================================
+
+fn get_batches_path() -> Result<PathBuf, String> {
+    Ok(ConfigPathBuilder::from_home_subdir(".anycode")?.build("prompt_batches.json"))
+}
+
+fn is_batch_running(batch_id: &str) -> bool {
+    RUNNING_BATCHES.lock().unwrap().contains_key(batch_id)
+}
+
+/// 没有运行器的"运行中"批次（应用重启中断）视为已暂停，中断的条目回到待执行
+fn normalize_interrupted(batch: &mut PromptBatch, running: bool) {
+    if batch.status != BatchStatus::Running || running {
+        return;
+    }
+    batch.status = BatchStatus::Paused;
+    for item in batch
+        .items
+        .iter_mut()
+        .filter(|i| i.status == BatchItemStatus::Running)
+    {
+        item.status = BatchItemStatus::Pending;
+        item.started_at = None;
+        item.error = Some("执行被中断（应用已退出），继续运行时将重新执行".to_string());
+    }
+}
+
+fn load_batch(batch_id: &str) -> Result<PromptBatch, String> {
+    let store: PromptBatchStore = {
+        let _guard = STORE_LOCK.lock().unwrap();
+        load_json_config(get_batches_path()?)?
+    };
+    let mut batch = store
+        .batches
+        .into_iter()
+        .find(|b| b.id == batch_id)
+        .ok_or_else(|| format!("Prompt batch not found: {}", batch_id))?;
+    normalize_interrupted(&mut batch, is_batch_running(batch_id));
+    Ok(batch)
+}
+
+/// 读改写单个批次并通知前端
+fn update_batch<F>(app: &AppHandle, batch_id: &str, f: F) -> Result<PromptBatch, String>
+where
+    F: FnOnce(&mut PromptBatch),
+{
+    let updated = {
+        let _guard = STORE_LOCK.lock().unwrap();
+        let path = get_batches_path()?;
+        let mut store: PromptBatchStore = load_json_config(&path)?;
+        let batch = store
+            .batches
+            .iter_mut()
+            .find(|b| b.id == batch_id)
+            .ok_or_else(|| format!("Prompt batch not found: {}", batch_id))?;
+        f(batch);
+        batch.updated_at = chrono::Utc::now().to_rfc3339();
+        let updated = batch.clone();
+        save_json_config(&store, &path)?;
+        updated
+    };
+
+    let _ = app.emit("prompt-batch-updated", &updated);
+    Ok(updated)
+}
+
+// ============================================================================
+// Runner
+// ============================================================================
+
+/// 等待本条提示词那次执行的完成事件（按发送时指定的 trace id 对应）
+///
+/// 同一项目中并发运行的其他任务带着各自的 trace id，不会误当作本条的完成
+async fn wait_for_completion(
+    rx: &mut tokio::sync::broadcast::Receiver<TaskCompletionEvent>,
+    trace_id: &str,
+    control: &AtomicU8,
+) -> Result<TaskCompletionEvent, String> {
+    let deadline = Instant::now() + Duration::from_secs(ITEM_TIMEOUT_SECS);
+
+    loop {
+        match tokio::time::timeout(Duration::from_secs(1), rx.recv()).await {
+            Ok(Ok(event)) => {
+                if event.trace_id.as_deref() == Some(trace_id) {
+                    return Ok(event);
+                }
+            }
+            Ok(Err(RecvError::Lagged(skipped))) => {
+                log::warn!("[PromptBatch] Missed {} completion events", skipped);
+            }
+            Ok(Err(RecvError::Closed)) => {
+                return Err("Completion event channel closed".to_string());
+            }
+            Err(_) => {
+                if control.load(Ordering::Relaxed) == CONTROL_CANCEL {
+                    return Err("批次已取消".to_string());
+                }
+                if Instant::now() >= deadline {
+                    return Err(format!("等待完成超时（{} 秒）", ITEM_TIMEOUT_SECS));
+                }
+            }
+        }
+    }
+}
+
+/// 计算本条提示词的发送目标
+fn item_target(batch: &PromptBatch) -> PromptTarget {
+    if batch.options.by == BatchSessionStrategy::NewSession {
+        return PromptTarget::New;
+    }
+    if let Some(session_id) = batch
+        .session_id
+        .clone()
+        .or(batch.options.session_id.clone())
+    {
+        return PromptTarget::Resume(session_id);
+    }
+    let started = batch.items.iter().any(|i| {
+        matches!(
+            i.status,
+            BatchItemStatus::Succeeded | BatchItemStatus::Failed
+        )
+    });
+    if started {
+        PromptTarget::ResumeLast
+    } else {
+        PromptTarget::New
+    }
+}
+
+async fn run_batch(app: AppHandle, batch_id: String, control: Arc<AtomicU8>) {
+    loop {
+        let batch = match load_batch(&batch_id) {
+            Ok(batch) => batch,
+            Err(e) => {
+                log::error!("[PromptBatch] {}", e);
+                break;
+            }
+        };
+
+        match control.load(Ordering::Relaxed) {
+            CONTROL_PAUSE => {
+                let _ = update_batch(&app, &batch_id, |b| b.status = BatchStatus::Paused);
+                break;
+            }
+            CONTROL_CANCEL => {
+                let _ = update_batch(&app, &batch_id, |b| {
+                    b.status = BatchStatus::Cancelled;
+                    for item in b
+                        .items
+                        .iter_mut()
+                        .filter(|i| i.status == BatchItemStatus::Pending)
+                    {
+                        item.status = BatchItemStatus::Cancelled;
+                    }
+                });
+                break;
+            }
+            _ => {}
+        }
+
+        let Some(item) = batch
+            .items
+            .iter()
+            .find(|i| i.status == BatchItemStatus::Pending)
+            .cloned()
+        else {
+            let any_failed = batch
+                .items
+                .iter()
+                .any(|i| i.status == BatchItemStatus::Failed);
+            let _ = update_batch(&app, &batch_id, |b| {
+                b.status = if any_failed {
+                    BatchStatus::Failed
+                } else {
+                    BatchStatus::Completed
+                };
+            });
+            break;
+        };
+
+        let target = item_target(&batch);
+        let _ = update_batch(&app, &batch_id, |b| {
+            let i = &mut b.items[item.index];
+            i.status = BatchItemStatus::Running;
+            i.started_at = Some(chrono::Utc::now().to_rfc3339());
+            i.error = None;
+        });
+
+        log::info!(
+            "[PromptBatch] {} item #{} -> {} ({:?})",
+            batch_id,
+            item.index,
+            batch.engine,
+            target
+        );
+
+        // 先订阅再发送，避免错过很快结束的任务
+        let mut rx = subscribe_task_completions();
+        let trace_id = uuid::Uuid::new_v4().to_string();
+        let started = Instant::now();
+        let outcome = match dispatch_prompt_traced(
+            app.clone(),
+            &batch.engine,
+            &batch.options.project_path,
+            target,
+            &item.prompt,
+            item.model.clone().or(batch.options.model.clone()),
+            item.mode.clone().or(batch.options.mode.clone()),
+            Some(trace_id.clone()),
+        )
+        .await
+        {
+            Ok(()) => wait_for_completion(&mut rx, &trace_id, &control).await,
+            Err(e) => Err(e),
+        };
+
+        let cancelled = control.load(Ordering::Relaxed) == CONTROL_CANCEL;
+        let succeeded = matches!(&outcome, Ok(event) if event.success);
+        let _ = update_batch(&app, &batch_id, |b| {
+            let i = &mut b.items[item.index];
+            i.duration_ms = Some(started.elapsed().as_millis() as u64);
+            match &outcome {
+                Ok(event) => {
+                    i.session_id = event.session_id.clone();
+                    i.status = if event.success {
+                        BatchItemStatus::Succeeded
+                    } else {
+                        BatchItemStatus::Failed
+                    };
+                    i.summary = Some(format!(
+                        "{}，耗时 {:.1} 秒",
+                        if event.success {
+                            "执行完成"
+                        } else {
+                            "执行失败"
+                        },
+                        event.duration_ms as f64 / 1000.0
+                    ));
+                    // 只有 Claude 的完成事件携带可续接的 CLI 会话 ID
+                    if b.engine == "claude" && b.options.by == BatchSessionStrategy::SameSession {
+                        b.session_id = event.session_id.clone().or(b.session_id.take());
+                    }
+                }
+                Err(e) => {
+                    i.status = if cancelled {
+                        BatchItemStatus::Cancelled
+                    } else {
+                        BatchItemStatus::Failed
+                    };
+                    i.error = Some(e.clone());
+                }
+            }
+        });
+
+        if !succeeded && !cancelled && batch.stop_on_failure {
+            log::info!(
+                "[PromptBatch] {} stopped after item #{} failed",
+                batch_id,
+                item.index
+            );
+            let _ = update_batch(&app, &batch_id, |b| b.status = BatchStatus::Failed);
+            break;
+        }
+    }
+
+    RUNNING_BATCHES.lock().unwrap().remove(&batch_id);
+}
+
+fn set_control(batch_id: &str, signal: u8) -> bool {
+    match RUNNING_BATCHES.lock().unwrap().get(batch_id) {
+        Some(control) => {
+            control.store(signal, Ordering::Relaxed);
+            true
+        }
+        None => false,
+    }
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/// 导入一组提示词为批次（来自文件或直接传入的数组）
+#[tauri::command]
+pub async fn import_prompt_batch(
+    file_path: Option<String>,
+    prompts: Option<Vec<BatchPromptInput>>,
+    engine: String,
+    options: BatchOptions,
+) -> Result<PromptBatch, String> {
+    if !matches!(engine.as_str(), "claude" | "codex" | "gemini") {
+        return Err(format!("Unsupported engine: {}", engine));
+    }
+    if options.project_path.trim().is_empty() {
+        return Err("Project path is required".to_string());
+    }
+
+    let inputs = match (file_path, prompts) {
+        (Some(path), _) if !path.trim().is_empty() => parse_prompt_file(&path)?,
+        (_, Some(prompts)) => prompts,
+        _ => return Err("Either file_path or prompts is required".to_string()),
+    };
+    let inputs: Vec<BatchPromptInput> = inputs
+        .into_iter()
+        .filter(|p| !p.prompt.trim().is_empty())
+        .collect();
+    if inputs.is_empty() {
+        return Err("No prompts found to import".to_string());
+    }
+
+    let now = chrono::Utc::now().to_rfc3339();
+    let batch = PromptBatch {
+        id: uuid::Uuid::new_v4().to_string(),
+        engine,
+        options,
+        items: inputs
+            .into_iter()
+            .enumerate()
+            .map(|(index, input)| BatchItem {
+                index,
+                prompt: input.prompt,
+                mode: input.mode,
+                model: input.model,
+                status: BatchItemStatus::Pending,
+                started_at: None,
+                duration_ms: None,
+                session_id: None,
+                summary: None,
+                error: None,
+            })
+            .collect(),
+        status: BatchStatus::Ready,
+        stop_on_failure: true,
+        session_id: None,
+        created_at: now.clone(),
+        updated_at: now,
+    };
+
+    {
+        let _guard = STORE_LOCK.lock().unwrap();
+        let path = get_batches_path()?;
+        let mut store: PromptBatchStore = load_json_config(&path)?;
+        store.batches.push(batch.clone());
+        if store.batches.len() > MAX_STORED_BATCHES {
+            let excess = store.batches.len() - MAX_STORED_BATCHES;
+            store.batches.drain(..excess);
+        }
+        save_json_config(&store, &path)?;
+    }
+
+    log::info!(
+        "[PromptBatch] Imported batch {} with {} prompts for {}",
+        batch.id,
+        batch.items.len(),
+        batch.engine
+    );
+    Ok(batch)
+}
+
+/// 启动（或继续）顺序执行批次
+#[tauri::command]
+pub async fn run_prompt_batch(
+    app: AppHandle,
+    batch_id: String,
+    stop_on_failure: bool,
+) -> Result<PromptBatch, String> {
+    let control = {
+        let mut running = RUNNING_BATCHES.lock().unwrap();
+        if running.contains_key(&batch_id) {
+            return Err("Prompt batch is already running".to_string());
+        }
+        let control = Arc::new(AtomicU8::new(CONTROL_RUN));
+        running.insert(batch_id.clone(), control.clone());
+        control
+    };
+
+    let started = update_batch(&app, &batch_id, |b| {
+        normalize_interrupted(b, false);
+        b.stop_on_failure = stop_on_failure;
+        b.status = BatchStatus::Running;
+    });
+    let batch = match started {
+        Ok(batch) => batch,
+        Err(e) => {
+            RUNNING_BATCHES.lock().unwrap().remove(&batch_id);
+            return Err(e);
+        }
+    };
+
+    tokio::spawn(run_batch(app, batch_id, control));
+    Ok(batch)
+}
+
+/// 暂停批次：当前这条结束后不再发送下一条
+#[tauri::command]
+pub async fn pause_prompt_batch(batch_id: String) -> Result<bool, String> {
+    Ok(set_control(&batch_id, CONTROL_PAUSE))
+}
+
+/// 取消整批：剩余提示词标记为已取消
+#[tauri::command]
+pub async fn cancel_prompt_batch(app: AppHandle, batch_id: String) -> Result<bool, String> {
+    if set_control(&batch_id, CONTROL_CANCEL) {
+        return Ok(true);
+    }
+
+    // 未在运行（就绪/暂停）的批次直接标记取消
+    update_batch(&app, &batch_id, |b| {
+        normalize_interrupted(b, false);
+        if matches!(b.status, BatchStatus::Ready | BatchStatus::Paused) {
+            b.status = BatchStatus::Cancelled;
+            for item in b
+                .items
+                .iter_mut()
+                .filter(|i| i.status == BatchItemStatus::Pending)
+            {
+                item.status = BatchItemStatus::Cancelled;
+            }
+        }
+    })?;
+    Ok(false)
+}
+
+/// 获取批次状态（每条的状态、耗时与结果摘要）
+#[tauri::command]
+pub async fn get_batch_status(batch_id: String) -> Result<PromptBatch, String> {
+    load_batch(&batch_id)
+}
+
+/// 列出已保存的批次（最新在前）
+#[tauri::command]
+pub async fn list_prompt_batches() -> Result<Vec<PromptBatch>, String> {
+    let store: PromptBatchStore = {
+        let _guard = STORE_LOCK.lock().unwrap();
+        load_json_config(get_batches_path()?)?
+    };
+
+    Ok(store
+        .batches
+        .into_iter()
+        .rev()
+        .map(|mut batch| {
+            let running = is_batch_running(&batch.id);
+            normalize_interrupted(&mut batch, running);
+            batch
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_markdown_checklist_with_overrides() {
+        let content = "# 重构清单\n\n\
+            - [ ] 拆分 session.rs\n  保持公开接口不变\n\
+            - [mode: full-auto] [model=gpt-5] 更新文档\n\
+            1. 补充单测\n\
+            说明段落不会被导入\n";
+
+        let items = parse_markdown_prompts(content);
+        assert_eq!(items.len(), 3);
+        assert_eq!(items[0].prompt, "拆分 session.rs\n保持公开接口不变");
+        assert_eq!(items[1].prompt, "更新文档");
+        assert_eq!(items[1].mode.as_deref(), Some("full-auto"));
+        assert_eq!(items[1].model.as_deref(), Some("gpt-5"));
+        assert_eq!(items[2].prompt, "补充单测");
+    }
+
+    fn completion(trace_id: &str, success: bool) -> TaskCompletionEvent {
+        TaskCompletionEvent {
+            engine: "codex".to_string(),
+            project_path: "/work/app".to_string(),
+            session_id: Some(format!("codex-{}", trace_id)),
+            cli_session_id: None,
+            mode: None,
+            success,
+            duration_ms: 10,
+            trace_id: Some(trace_id.to_string()),
+        }
+    }
+
+    #[tokio::test]
+    async fn waits_for_the_completion_of_its_own_run() {
+        let (tx, mut rx) = tokio::sync::broadcast::channel(8);
+        // 同一项目中另一个任务先结束
+        tx.send(completion("other-run", false)).unwrap();
+        tx.send(completion("batch-run", true)).unwrap();
+
+        let event = wait_for_completion(&mut rx, "batch-run", &AtomicU8::new(0))
+            .await
+            .unwrap();
+        assert_eq!(event.session_id.as_deref(), Some("codex-batch-run"));
+        assert!(event.success);
+    }
+
+    #[test]
+    fn parses_json_strings_and_objects() {
+        let items =
+            parse_json_prompts(r#"["first", {"prompt": "second", "mode": "read-only"}]"#).unwrap();
+        assert_eq!(items[0].prompt, "first");
+        assert_eq!(items[1].mode.as_deref(), Some("read-only"));
+        assert!(parse_json_prompts("{}").is_err());
+    }
+}
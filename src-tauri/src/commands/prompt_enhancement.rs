@@ -0,0 +1,393 @@
+//! 执行时提示词增强
+//!
+//! 三引擎的执行选项可带 `enhancement`，执行入口在启动 CLI 前调用 acemcp 检索项目上下文并增强提示词，
+//! 前端一次调用即可完成"增强并执行"：
+//! - 增强失败时按 `fail_open`（默认 true）决定以原始提示词继续执行还是直接报错
+//! - 相同提示词与检索参数的完整增强结果缓存 10 分钟，重发或回退重试时不再重复检索
+//! - 增强元数据（注入的片段路径、context token 数、是否命中缓存）写入会话标注，
+//!   可通过 `get_prompt_enhancement_info` 按 prompt_index 查询
+//!
+//! 多轮搜索的实时片段仍通过 acemcp-context-snippet 事件推送，stream_id 为执行的 trace id。
+//!
+//! `fetch_urls` 开启时还会预抓取提示词中的链接并附加网页内容（见 url_context），
+//! `project_context` 为 false 时跳过 acemcp 检索，只做链接预抓取。
+//! 发送前还会为提示词中 @ 引用的文件拍内容快照（`file_snapshots`，默认开启，见 prompt_file_snapshots）。
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+
+use super::acemcp::{enhance_prompt_with_context, enhance_prompt_with_multi_project_context};
+use super::project_memory::estimate_tokens;
+use super::prompt_file_snapshots::{capture_for_execution, extract_file_references};
+use super::session_annotations::{record_prompt_enhancement, PromptEnhancementInfo};
+use super::url_context::{
+    build_url_context, extract_urls, UrlContextFetched, UrlContextOptions, UrlContextResult,
+};
+
+/// 增强结果缓存的有效期
+const CACHE_TTL: Duration = Duration::from_secs(10 * 60);
+
+/// 缓存的最大条目数
+const CACHE_CAPACITY: usize = 32;
+
+/// 执行选项中的增强请求
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EnhancementRequest {
+    /// 检索范围；为空时只检索执行所在的项目，多个项目时按跨仓库方式合并上下文
+    #[serde(default)]
+    pub project_paths: Vec<String>,
+    /// 上下文最大长度（字符），默认 3000
+    pub max_context_length: Option<usize>,
+    /// 是否启用多轮搜索，默认 true
+    pub enable_multi_round: Option<bool>,
+    /// 多轮搜索整体截止时间（秒）
+    pub search_deadline_secs: Option<u64>,
+    /// 用于结合对话历史生成查询的会话 ID 与项目 ID（仅单项目检索；续接 Claude 会话时默认为该会话）
+    pub session_id: Option<String>,
+    pub project_id: Option<String>,
+    /// 增强失败时是否以原始提示词继续执行，默认 true；false 时执行直接报错
+    pub fail_open: Option<bool>,
+    /// 是否检索 acemcp 项目上下文，默认 true；只预抓取链接时为 false
+    pub project_context: Option<bool>,
+    /// 是否预抓取提示词中的 URL 并附加网页内容，默认 false
+    pub fetch_urls: Option<bool>,
+    /// 是否为提示词引用的文件拍内容快照，默认 true
+    pub file_snapshots: Option<bool>,
+    /// 前端解析出的 @ 引用列表；未提供时从提示词中解析。这些文件的检索片段会被剔除
+    pub file_references: Option<Vec<String>>,
+}
+
+/// 一次增强的结果
+#[derive(Debug, Clone)]
+struct Enhanced {
+    prompt: String,
+    partial: bool,
+    error: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+struct CacheEntry {
+    prompt: String,
+    stored_at: Instant,
+}
+
+static ENHANCEMENT_CACHE: Lazy<Mutex<HashMap<String, CacheEntry>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// 增强执行用的提示词，返回实际发送给 CLI 的文本
+///
+/// 增强元数据按 trace id 写入会话标注，由提示词记录配对到具体的 prompt_index
+pub async fn enhance_for_execution(
+    app: &AppHandle,
+    project_path: &str,
+    prompt: String,
+    request: EnhancementRequest,
+    trace_id: &str,
+) -> Result<String, String> {
+    let mut info = PromptEnhancementInfo {
+        trace_id: trace_id.to_string(),
+        project_paths: Vec::new(),
+        snippet_paths: Vec::new(),
+        context_tokens: 0,
+        cache_hit: false,
+        partial: false,
+        error: None,
+        fetched_urls: Vec::new(),
+        failed_urls: Vec::new(),
+        file_snapshots: None,
+        created_at: chrono::Utc::now().timestamp(),
+    };
+
+    // 快照在启动 CLI 之前拍下，即模型看到的版本
+    if request.file_snapshots.unwrap_or(true) {
+        let project_path = project_path.to_string();
+        let references = request.file_references.clone();
+        let original = prompt.clone();
+        info.file_snapshots = tokio::task::spawn_blocking(move || {
+            capture_for_execution(&project_path, &original, references)
+        })
+        .await
+        .unwrap_or_else(|e| {
+            log::warn!("[trace:{}] File snapshot task failed: {}", trace_id, e);
+            None
+        });
+    }
+
+    let mut final_prompt = if request.project_context.unwrap_or(true) {
+        enhance_with_project_context(app, project_path, &prompt, &request, &mut info).await?
+    } else {
+        prompt.clone()
+    };
+
+    // 链接预抓取只看用户原始提示词，网页内容附加在项目上下文之后
+    if request.fetch_urls.unwrap_or(false) {
+        let urls = extract_urls(&prompt);
+        let result = if urls.is_empty() {
+            UrlContextResult::default()
+        } else {
+            build_url_context(&urls, UrlContextOptions::default()).await
+        };
+        log::info!(
+            "[trace:{}] URL prefetch: fetched={}, failed={}, context_tokens={}",
+            trace_id,
+            result.fetched.len(),
+            result.failed.len(),
+            result.context_tokens
+        );
+        let payload = UrlContextFetched {
+            stream_id: trace_id,
+            fetched: result.fetched.iter().map(|p| p.url.as_str()).collect(),
+            failed: &result.failed,
+        };
+        if let Err(e) = app.emit("url-context-fetched", &payload) {
+            log::warn!("Failed to emit url-context-fetched: {}", e);
+        }
+        info.fetched_urls = result.fetched.iter().map(|p| p.url.clone()).collect();
+        info.failed_urls = result.failed.clone();
+        final_prompt.push_str(&result.context);
+    }
+
+    if let Err(e) = record_prompt_enhancement(info) {
+        log::warn!(
+            "[trace:{}] Failed to record prompt enhancement: {}",
+            trace_id,
+            e
+        );
+    }
+
+    Ok(final_prompt)
+}
+
+/// 用 acemcp 项目上下文增强提示词，并把元数据写入 `info`
+async fn enhance_with_project_context(
+    app: &AppHandle,
+    project_path: &str,
+    prompt: &str,
+    request: &EnhancementRequest,
+    info: &mut PromptEnhancementInfo,
+) -> Result<String, String> {
+    let trace_id = info.trace_id.clone();
+    let project_paths = if request.project_paths.is_empty() {
+        vec![project_path.to_string()]
+    } else {
+        request.project_paths.clone()
+    };
+
+    let key = cache_key(prompt, &project_paths, request);
+    let cached = lookup_cache(&key);
+    let cache_hit = cached.is_some();
+    let enhanced = match cached {
+        Some(enhanced_prompt) => Enhanced {
+            prompt: enhanced_prompt,
+            partial: false,
+            error: None,
+        },
+        None => run_enhancement(app, prompt, &project_paths, request, &trace_id).await,
+    };
+    if !cache_hit && enhanced.error.is_none() && !enhanced.partial {
+        store_cache(key, &enhanced.prompt);
+    }
+
+    let context = if enhanced.error.is_some() || enhanced.prompt == prompt {
+        ""
+    } else {
+        enhanced
+            .prompt
+            .strip_prefix(prompt.trim())
+            .unwrap_or(&enhanced.prompt)
+    };
+    info.project_paths = project_paths;
+    info.snippet_paths = context_snippet_paths(context);
+    info.context_tokens = estimate_tokens(context);
+    info.cache_hit = cache_hit;
+    info.partial = enhanced.partial;
+    info.error = enhanced.error.clone();
+    log::info!(
+        "[trace:{}] Prompt enhanced for execution: snippets={}, context_tokens={}, cache_hit={}, error={:?}",
+        trace_id,
+        info.snippet_paths.len(),
+        info.context_tokens,
+        cache_hit,
+        info.error
+    );
+
+    if let Some(error) = enhanced.error {
+        if !request.fail_open.unwrap_or(true) {
+            return Err(format!("Prompt enhancement failed: {}", error));
+        }
+        log::warn!(
+            "[trace:{}] Prompt enhancement failed, continuing with the original prompt: {}",
+            trace_id,
+            error
+        );
+    }
+
+    Ok(if context.is_empty() {
+        prompt.to_string()
+    } else {
+        enhanced.prompt
+    })
+}
+
+async fn run_enhancement(
+    app: &AppHandle,
+    prompt: &str,
+    project_paths: &[String],
+    request: &EnhancementRequest,
+    trace_id: &str,
+) -> Enhanced {
+    let result = if let [project_path] = project_paths {
+        enhance_prompt_with_context(
+            app.clone(),
+            prompt.to_string(),
+            project_path.clone(),
+            request.session_id.clone(),
+            request.project_id.clone(),
+            request.max_context_length,
+            request.enable_multi_round,
+            request.search_deadline_secs,
+            Some(trace_id.to_string()),
+            Some(
+                request
+                    .file_references
+                    .clone()
+                    .unwrap_or_else(|| extract_file_references(prompt)),
+            ),
+            None,
+        )
+        .await
+        .map(|result| Enhanced {
+            prompt: result.enhanced_prompt,
+            partial: result.partial,
+            error: result.error,
+        })
+    } else {
+        enhance_prompt_with_multi_project_context(
+            app.clone(),
+            prompt.to_string(),
+            project_paths.to_vec(),
+            request.max_context_length,
+            request.enable_multi_round,
+            request.search_deadline_secs,
+        )
+        .await
+        .map(|result| Enhanced {
+            prompt: result.enhanced_prompt,
+            partial: result.partial,
+            error: result.error,
+        })
+    };
+
+    result.unwrap_or_else(|e| Enhanced {
+        prompt: prompt.to_string(),
+        partial: false,
+        error: Some(e),
+    })
+}
+
+/// 上下文中代码片段的路径（"Path: xxx" 行），去重并保持出现顺序
+fn context_snippet_paths(context: &str) -> Vec<String> {
+    let mut paths: Vec<String> = Vec::new();
+    for line in context.lines() {
+        let Some(path) = line.trim_start().strip_prefix("Path:") else {
+            continue;
+        };
+        let path = path.trim();
+        if !path.is_empty() && !paths.iter().any(|p| p == path) {
+            paths.push(path.to_string());
+        }
+    }
+    paths
+}
+
+fn cache_key(prompt: &str, project_paths: &[String], request: &EnhancementRequest) -> String {
+    let key = serde_json::json!([
+        prompt,
+        project_paths,
+        request.max_context_length,
+        request.enable_multi_round,
+        request.session_id,
+        request.project_id,
+        request.file_references,
+    ]);
+    format!("{:x}", md5::compute(key.to_string()))
+}
+
+fn lookup_cache(key: &str) -> Option<String> {
+    let mut cache = ENHANCEMENT_CACHE.lock().unwrap();
+    cache.retain(|_, entry| entry.stored_at.elapsed() < CACHE_TTL);
+    cache.get(key).map(|entry| entry.prompt.clone())
+}
+
+fn store_cache(key: String, prompt: &str) {
+    let mut cache = ENHANCEMENT_CACHE.lock().unwrap();
+    insert_bounded(&mut cache, key, prompt.to_string(), Instant::now());
+}
+
+/// 写入缓存，超出容量时淘汰最早写入的条目
+fn insert_bounded(
+    cache: &mut HashMap<String, CacheEntry>,
+    key: String,
+    prompt: String,
+    now: Instant,
+) {
+    cache.insert(
+        key,
+        CacheEntry {
+            prompt,
+            stored_at: now,
+        },
+    );
+    while cache.len() > CACHE_CAPACITY {
+        let Some(oldest) = cache
+            .iter()
+            .min_by_key(|(_, entry)| entry.stored_at)
+            .map(|(key, _)| key.clone())
+        else {
+            break;
+        };
+        cache.remove(&oldest);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extracts_unique_snippet_paths() {
+        let context = "\n\n--- 项目上下文 (来自 acemcp 语义搜索) ---\n\
+            Path: src/main.rs\nfn main() {}\n\n\
+            Path: src/lib.rs\npub mod a;\n\n\
+            Path: src/main.rs\nfn helper() {}";
+
+        assert_eq!(
+            context_snippet_paths(context),
+            vec!["src/main.rs".to_string(), "src/lib.rs".to_string()]
+        );
+        assert!(context_snippet_paths("").is_empty());
+    }
+
+    #[test]
+    fn cache_evicts_oldest_entry_when_full() {
+        let mut cache = HashMap::new();
+        let start = Instant::now();
+        for i in 0..=CACHE_CAPACITY {
+            insert_bounded(
+                &mut cache,
+                format!("key-{}", i),
+                format!("prompt-{}", i),
+                start + Duration::from_secs(i as u64),
+            );
+        }
+
+        assert_eq!(cache.len(), CACHE_CAPACITY);
+        assert!(!cache.contains_key("key-0"));
+        assert!(cache.contains_key(&format!("key-{}", CACHE_CAPACITY)));
+    }
+}
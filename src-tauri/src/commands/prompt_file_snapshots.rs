@@ -0,0 +1,529 @@
+//! 提示词引用文件的内容快照
+//!
+//! 提示词中 @ 引用的文件之后常被改得面目全非，回看会话时无法知道模型当时看到的版本。
+//! 执行入口（带 enhancement）在启动 CLI 前为引用的文件拍快照：
+//! - 记录文件内容的 sha256；不超过 256KB 的文本文件同时保存内容副本，更大的文件只记 hash
+//! - 此时新会话还没有 id，副本先写入暂存目录 ~/.anycode/file_snapshots；快照清单随增强元数据
+//!   按 trace id 与提示词记录配对到 prompt_index（见 session_annotations），配对后副本复制到
+//!   会话的 attachments 目录（与 session_blobs 共用同一 blob 格式）
+//! - `get_prompt_file_snapshots` 返回某条提示词的引用清单与当前文件的差异状态，
+//!   `diff_snapshot_with_current` 返回快照内容与当前内容，由前端渲染差异
+//!
+//! 暂存副本超过 7 天清理；配对前读取时同样从暂存目录取回。
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use super::claude::encode_project_path;
+use super::session_alias::resolve_session_alias;
+use super::session_annotations::{find_prompt_file_snapshot, load_prompt_file_snapshots};
+use super::session_blobs::{attachments_dir, blob_path, read_blob, write_blob};
+use super::session_utils::resolve_session_file;
+use crate::utils::config_utils::ConfigPathBuilder;
+
+/// 保存内容副本的文件大小上限，更大的文件只记 hash
+pub const MAX_SNAPSHOT_CONTENT_BYTES: u64 = 256 * 1024;
+
+/// 单条提示词最多快照的文件数
+const MAX_SNAPSHOT_FILES: usize = 50;
+
+/// 暂存副本的保留时间
+const STAGING_TTL: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// 单个被引用文件的快照
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PromptFileSnapshot {
+    pub id: String,
+    /// 提示词中引用的路径（原样保留，可能相对项目根目录）
+    pub path: String,
+    pub absolute_path: String,
+    /// 发送时文件内容的 sha256
+    pub hash: String,
+    pub size: u64,
+    /// 内容副本的 blob 引用；超过大小上限或不是 UTF-8 文本时为 None
+    pub content_ref: Option<String>,
+}
+
+/// 一条提示词引用的所有文件快照
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PromptFileSnapshots {
+    /// 执行所在的项目（解析相对引用、定位会话文件）
+    pub project_path: String,
+    pub files: Vec<PromptFileSnapshot>,
+}
+
+/// 快照与当前文件的差异状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SnapshotStatus {
+    Unchanged,
+    Modified,
+    Deleted,
+}
+
+/// 引用清单中的一项
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileSnapshotEntry {
+    pub id: String,
+    pub path: String,
+    pub hash: String,
+    pub size: u64,
+    /// 是否保存了内容副本（可与当前版本对比）
+    pub has_content: bool,
+    pub status: SnapshotStatus,
+}
+
+/// 快照内容与当前内容
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotComparison {
+    pub id: String,
+    pub path: String,
+    pub status: SnapshotStatus,
+    /// 快照内容；只记了 hash 或副本已丢失时为 None
+    pub snapshot_content: Option<String>,
+    /// 当前内容；文件已删除、超过大小上限或不是文本时为 None
+    pub current_content: Option<String>,
+}
+
+/// 从提示词中解析 @ 引用（`@src/main.rs`、`@"docs/my file.md"`），去重并保持顺序
+///
+/// 只认行首或空白之后的 @，避免把邮箱地址当作引用
+pub fn extract_file_references(prompt: &str) -> Vec<String> {
+    lazy_static::lazy_static! {
+        static ref FILE_REFERENCE_RE: Regex =
+            Regex::new(r#"(?:^|\s)@(?:"([^"\n]+)"|([^\s"'`,;()\[\]{}<>，。；、]+))"#).unwrap();
+    }
+
+    let mut seen = HashSet::new();
+    FILE_REFERENCE_RE
+        .captures_iter(prompt)
+        .filter_map(|caps| {
+            let reference = caps.get(1).or(caps.get(2))?.as_str();
+            let reference = reference.trim_end_matches(['.', ':', '!', '?', '！', '？']);
+            (!reference.is_empty()).then(|| reference.to_string())
+        })
+        .filter(|reference| seen.insert(reference.clone()))
+        .collect()
+}
+
+/// 引用解析为绝对路径：绝对路径原样使用，否则相对项目根目录
+fn resolve_reference(project_path: &str, reference: &str) -> PathBuf {
+    let path = Path::new(reference);
+    if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        Path::new(project_path).join(path)
+    }
+}
+
+/// 计算文件内容的 sha256，不超过上限时一并返回内容
+fn hash_file(path: &Path) -> Result<(String, u64, Option<Vec<u8>>), String> {
+    let mut file = fs::File::open(path).map_err(|e| format!("Failed to open {:?}: {}", path, e))?;
+    let mut hasher = Sha256::new();
+    let mut size = 0u64;
+    let mut content = Some(Vec::new());
+    let mut buffer = [0u8; 64 * 1024];
+    loop {
+        let read = file
+            .read(&mut buffer)
+            .map_err(|e| format!("Failed to read {:?}: {}", path, e))?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+        size += read as u64;
+        if size > MAX_SNAPSHOT_CONTENT_BYTES {
+            content = None;
+        } else if let Some(content) = content.as_mut() {
+            content.extend_from_slice(&buffer[..read]);
+        }
+    }
+    Ok((format!("{:x}", hasher.finalize()), size, content))
+}
+
+/// 为引用的文件拍快照，副本写入 `staging`；不存在或不是普通文件的引用跳过
+fn capture_snapshots(
+    project_path: &str,
+    references: &[String],
+    staging: &Path,
+) -> PromptFileSnapshots {
+    let mut files = Vec::new();
+    for reference in references.iter().take(MAX_SNAPSHOT_FILES) {
+        let absolute = resolve_reference(project_path, reference);
+        if !absolute.is_file() {
+            continue;
+        }
+        let (hash, size, content) = match hash_file(&absolute) {
+            Ok(hashed) => hashed,
+            Err(e) => {
+                log::warn!("[FileSnapshots] Skipping {}: {}", reference, e);
+                continue;
+            }
+        };
+        let content_ref = content
+            .and_then(|bytes| String::from_utf8(bytes).ok())
+            .and_then(|text| match write_blob(staging, &Value::String(text)) {
+                Ok(reference) => Some(reference),
+                Err(e) => {
+                    log::warn!("[FileSnapshots] Failed to store content copy: {}", e);
+                    None
+                }
+            });
+        files.push(PromptFileSnapshot {
+            id: uuid::Uuid::new_v4().to_string(),
+            path: reference.clone(),
+            absolute_path: absolute.to_string_lossy().to_string(),
+            hash,
+            size,
+            content_ref,
+        });
+    }
+    PromptFileSnapshots {
+        project_path: project_path.to_string(),
+        files,
+    }
+}
+
+fn staging_dir() -> Result<PathBuf, String> {
+    Ok(ConfigPathBuilder::from_home_subdir(".anycode")?.build("file_snapshots"))
+}
+
+/// 清理超过保留时间的暂存副本
+fn prune_staging(staging: &Path, now: SystemTime) {
+    let Ok(entries) = fs::read_dir(staging) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let expired = entry
+            .metadata()
+            .and_then(|meta| meta.modified())
+            .ok()
+            .and_then(|modified| now.duration_since(modified).ok())
+            .is_some_and(|age| age > STAGING_TTL);
+        if expired {
+            let _ = fs::remove_file(entry.path());
+        }
+    }
+}
+
+/// 执行入口发送前调用：为提示词引用的文件拍快照
+///
+/// `references` 为前端解析出的 @ 引用列表，未提供时从提示词中解析；没有可快照的文件时返回 None
+pub fn capture_for_execution(
+    project_path: &str,
+    prompt: &str,
+    references: Option<Vec<String>>,
+) -> Option<PromptFileSnapshots> {
+    let references = references.unwrap_or_else(|| extract_file_references(prompt));
+    if references.is_empty() {
+        return None;
+    }
+    let staging = match staging_dir() {
+        Ok(dir) => dir,
+        Err(e) => {
+            log::warn!("[FileSnapshots] {}", e);
+            return None;
+        }
+    };
+    prune_staging(&staging, SystemTime::now());
+
+    let snapshots = capture_snapshots(project_path, &references, &staging);
+    log::info!(
+        "[FileSnapshots] Captured {} of {} referenced files ({} with content)",
+        snapshots.files.len(),
+        references.len(),
+        snapshots
+            .files
+            .iter()
+            .filter(|f| f.content_ref.is_some())
+            .count()
+    );
+    (!snapshots.files.is_empty()).then_some(snapshots)
+}
+
+/// 会话的 attachments 目录（会话文件尚未生成时返回错误）
+fn session_attachments_dir(
+    engine: &str,
+    session_id: &str,
+    project_path: &str,
+) -> Result<PathBuf, String> {
+    let project = if engine == "claude" {
+        encode_project_path(project_path)
+    } else {
+        project_path.to_string()
+    };
+    resolve_session_file(engine, session_id, &project).map(|file| attachments_dir(&file))
+}
+
+/// 把暂存副本复制到 `target`（已存在的跳过），返回复制的数量
+fn copy_staged_blobs(
+    snapshots: &PromptFileSnapshots,
+    staging: &Path,
+    target: &Path,
+) -> Result<usize, String> {
+    let mut copied = 0;
+    for reference in snapshots
+        .files
+        .iter()
+        .filter_map(|f| f.content_ref.as_deref())
+    {
+        let destination = blob_path(target, reference)?;
+        let source = blob_path(staging, reference)?;
+        if destination.exists() || !source.exists() {
+            continue;
+        }
+        fs::create_dir_all(target)
+            .map_err(|e| format!("Failed to create attachments directory: {}", e))?;
+        fs::copy(&source, &destination)
+            .map_err(|e| format!("Failed to copy snapshot {:?}: {}", source, e))?;
+        copied += 1;
+    }
+    Ok(copied)
+}
+
+/// 快照清单配对到会话后调用：把内容副本复制到会话的 attachments 目录
+///
+/// 失败只记日志，读取时仍可从暂存目录取回
+pub fn store_in_session_attachments(
+    engine: &str,
+    session_id: &str,
+    snapshots: &PromptFileSnapshots,
+) {
+    let result = staging_dir().and_then(|staging| {
+        let target = session_attachments_dir(engine, session_id, &snapshots.project_path)?;
+        copy_staged_blobs(snapshots, &staging, &target)
+    });
+    if let Err(e) = result {
+        log::warn!(
+            "[FileSnapshots] Content copies of {} session {} stay in staging: {}",
+            engine,
+            session_id,
+            e
+        );
+    }
+}
+
+/// 读取快照内容：先找会话的 attachments 目录，再找暂存目录
+fn read_snapshot_content(
+    file: &PromptFileSnapshot,
+    attachments: Option<&Path>,
+    staging: Option<&Path>,
+) -> Option<String> {
+    let reference = file.content_ref.as_deref()?;
+    [attachments, staging]
+        .into_iter()
+        .flatten()
+        .find_map(|dir| read_blob(dir, reference).ok())
+        .and_then(|value| value.as_str().map(str::to_string))
+}
+
+/// 当前文件相对快照的状态，未变化以外的情况同时返回当前内容（不超过上限的文本）
+fn compare_with_current(file: &PromptFileSnapshot) -> (SnapshotStatus, Option<String>) {
+    let path = Path::new(&file.absolute_path);
+    if !path.is_file() {
+        return (SnapshotStatus::Deleted, None);
+    }
+    match hash_file(path) {
+        Ok((hash, _, content)) => {
+            let status = if hash == file.hash {
+                SnapshotStatus::Unchanged
+            } else {
+                SnapshotStatus::Modified
+            };
+            (
+                status,
+                content.and_then(|bytes| String::from_utf8(bytes).ok()),
+            )
+        }
+        Err(e) => {
+            log::warn!("[FileSnapshots] {}", e);
+            (SnapshotStatus::Deleted, None)
+        }
+    }
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// 获取某条提示词发送时引用的文件清单，以及与当前文件的差异状态（没有快照时返回空列表）
+#[tauri::command]
+pub async fn get_prompt_file_snapshots(
+    engine: String,
+    session_id: String,
+    prompt_index: usize,
+) -> Result<Vec<FileSnapshotEntry>, String> {
+    let session_id = resolve_session_alias(&engine, &session_id);
+    let Some(snapshots) = load_prompt_file_snapshots(&engine, &session_id, prompt_index)? else {
+        return Ok(Vec::new());
+    };
+
+    tokio::task::spawn_blocking(move || {
+        snapshots
+            .files
+            .iter()
+            .map(|file| FileSnapshotEntry {
+                id: file.id.clone(),
+                path: file.path.clone(),
+                hash: file.hash.clone(),
+                size: file.size,
+                has_content: file.content_ref.is_some(),
+                status: compare_with_current(file).0,
+            })
+            .collect()
+    })
+    .await
+    .map_err(|e| format!("Snapshot task failed: {}", e))
+}
+
+/// 返回快照内容与当前内容，供前端对比
+#[tauri::command]
+pub async fn diff_snapshot_with_current(snapshot_id: String) -> Result<SnapshotComparison, String> {
+    let (session, snapshots, file) = find_prompt_file_snapshot(&snapshot_id)?
+        .ok_or_else(|| format!("File snapshot not found: {}", snapshot_id))?;
+
+    tokio::task::spawn_blocking(move || {
+        let attachments = session_attachments_dir(
+            &session.engine,
+            &session.session_id,
+            &snapshots.project_path,
+        )
+        .ok();
+        let staging = staging_dir().ok();
+        let snapshot_content =
+            read_snapshot_content(&file, attachments.as_deref(), staging.as_deref());
+        // 配对时会话文件还未生成的，读取时补做复制
+        if let (Some(attachments), Some(staging)) = (&attachments, &staging) {
+            let _ = copy_staged_blobs(&snapshots, staging, attachments);
+        }
+        let (status, current_content) = compare_with_current(&file);
+        SnapshotComparison {
+            id: file.id,
+            path: file.path,
+            status,
+            snapshot_content,
+            current_content,
+        }
+    })
+    .await
+    .map_err(|e| format!("Snapshot task failed: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extracts_at_references_but_not_email_addresses() {
+        let prompt = "看看 @src/main.rs 和 @\"docs/my notes.md\"，联系 dev@example.com。\n\
+                      @src/main.rs again, also @README.md.";
+        assert_eq!(
+            extract_file_references(prompt),
+            vec!["src/main.rs", "docs/my notes.md", "README.md"]
+        );
+        assert!(extract_file_references("no references @ all").is_empty());
+    }
+
+    #[test]
+    fn snapshots_record_hash_content_and_later_changes() {
+        let project = tempfile::tempdir().unwrap();
+        let staging = tempfile::tempdir().unwrap();
+        let root = project.path().to_string_lossy().to_string();
+        fs::write(project.path().join("a.rs"), "fn a() {}").unwrap();
+        fs::write(project.path().join("b.rs"), "fn b() {}").unwrap();
+        fs::write(
+            project.path().join("big.log"),
+            "x".repeat(MAX_SNAPSHOT_CONTENT_BYTES as usize + 1),
+        )
+        .unwrap();
+        fs::create_dir(project.path().join("dir")).unwrap();
+
+        let references: Vec<String> = ["a.rs", "b.rs", "big.log", "dir", "missing.rs"]
+            .iter()
+            .map(|r| r.to_string())
+            .collect();
+        let snapshots = capture_snapshots(&root, &references, staging.path());
+        let paths: Vec<&str> = snapshots.files.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(paths, vec!["a.rs", "b.rs", "big.log"]);
+
+        let (a, b, big) = (
+            &snapshots.files[0],
+            &snapshots.files[1],
+            &snapshots.files[2],
+        );
+        assert_eq!(
+            read_snapshot_content(a, None, Some(staging.path())).as_deref(),
+            Some("fn a() {}")
+        );
+        // Oversized files only keep the hash
+        assert!(big.content_ref.is_none());
+        assert_eq!(big.size, MAX_SNAPSHOT_CONTENT_BYTES + 1);
+
+        fs::write(project.path().join("a.rs"), "fn a() { changed }").unwrap();
+        fs::remove_file(project.path().join("b.rs")).unwrap();
+        let (status, current) = compare_with_current(a);
+        assert_eq!(status, SnapshotStatus::Modified);
+        assert_eq!(current.as_deref(), Some("fn a() { changed }"));
+        assert_eq!(compare_with_current(b).0, SnapshotStatus::Deleted);
+        assert_eq!(compare_with_current(big).0, SnapshotStatus::Unchanged);
+
+        // Content copies move from staging into the session attachments directory
+        let attachments = tempfile::tempdir().unwrap();
+        assert_eq!(
+            copy_staged_blobs(&snapshots, staging.path(), attachments.path()).unwrap(),
+            2
+        );
+        assert_eq!(
+            read_snapshot_content(a, Some(attachments.path()), None).as_deref(),
+            Some("fn a() {}")
+        );
+    }
+
+    #[test]
+    fn reused_staging_blobs_survive_pruning() {
+        let project = tempfile::tempdir().unwrap();
+        let staging = tempfile::tempdir().unwrap();
+        let root = project.path().to_string_lossy().to_string();
+        fs::write(project.path().join("a.rs"), "fn a() {}").unwrap();
+        let references = vec!["a.rs".to_string()];
+
+        let first = capture_snapshots(&root, &references, staging.path());
+        let blob = staging.path().join(format!(
+            "{}.blob",
+            first.files[0]
+                .content_ref
+                .as_deref()
+                .unwrap()
+                .trim_start_matches("blob://")
+        ));
+        let long_ago = SystemTime::now() - STAGING_TTL - Duration::from_secs(60);
+        fs::File::options()
+            .append(true)
+            .open(&blob)
+            .unwrap()
+            .set_modified(long_ago)
+            .unwrap();
+
+        // Capturing the same content again reuses the blob and refreshes its age
+        capture_snapshots(&root, &references, staging.path());
+        prune_staging(staging.path(), SystemTime::now());
+        assert!(blob.exists());
+
+        prune_staging(
+            staging.path(),
+            SystemTime::now() + STAGING_TTL + Duration::from_secs(60),
+        );
+        assert!(!blob.exists());
+    }
+}
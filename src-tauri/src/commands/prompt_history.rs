@@ -0,0 +1,642 @@
+//! 跨会话提示词历史
+//!
+//! 聚合 Claude / Codex / Gemini 三个引擎会话中的用户提示词，按规范化文本去重，
+//! 供"重新发送历史提示词"使用。会话文件按 (mtime, 大小) 缓存解析结果，
+//! 未变化的会话不会重复解析；`collect_prompt_occurrences` 也供全文搜索复用。
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+use tauri::AppHandle;
+
+use super::claude::normalize_path_for_comparison;
+use super::codex::{CodexExecutionMode, CodexExecutionOptions};
+use super::gemini::types::GeminiExecutionOptions;
+use super::session_utils::{claude_session_path, read_codex_session_meta};
+use crate::utils::text::truncate_chars;
+
+/// 列表中单条提示词的最大展示字符数（重发时取全文）
+const MAX_LIST_TEXT_CHARS: usize = 500;
+
+/// 默认返回条数
+const DEFAULT_HISTORY_LIMIT: usize = 100;
+
+/// 重发到 Claude 且未指定模型时使用的模型
+const DEFAULT_CLAUDE_MODEL: &str = "sonnet";
+
+/// 会话中的一次提示词出现
+#[derive(Debug, Clone)]
+pub(crate) struct PromptOccurrence {
+    pub engine: String,
+    pub session_id: String,
+    pub project_path: String,
+    pub text: String,
+    pub timestamp: i64,
+}
+
+/// 去重后的提示词历史条目
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PromptHistoryEntry {
+    /// 规范化文本的哈希，作为重发时的标识
+    pub id: String,
+    /// 提示词文本（超长时截断）
+    pub text: String,
+    pub truncated: bool,
+    /// 最近一次出现的引擎
+    pub engine: String,
+    /// 最近一次出现的会话
+    pub session_id: String,
+    pub project_path: String,
+    /// 最近一次出现的时间（Unix 秒）
+    pub timestamp: i64,
+    /// 出现次数
+    pub occurrences: usize,
+}
+
+/// 重发时对原提示词的修改
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PromptModifications {
+    /// 完整替换后的文本（在原文基础上编辑后的结果）
+    pub text: Option<String>,
+    /// 追加到末尾的补充说明
+    pub append: Option<String>,
+}
+
+/// 重发结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResendPromptResult {
+    pub engine: String,
+    pub project_path: String,
+    /// 续接的会话；新会话为 None
+    pub session_id: Option<String>,
+    /// 实际发送的提示词
+    pub prompt: String,
+}
+
+/// 单个会话文件的解析缓存
+struct CachedSessionPrompts {
+    modified: SystemTime,
+    len: u64,
+    session_id: String,
+    project_path: String,
+    /// (文本, 时间戳)
+    prompts: Vec<(String, i64)>,
+}
+
+static PROMPT_CACHE: Lazy<Mutex<HashMap<PathBuf, CachedSessionPrompts>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// 待解析的会话文件
+struct SessionSource {
+    engine: &'static str,
+    path: PathBuf,
+    /// Claude 会话的 (session_id, project_id, project_path)；其他引擎在解析时读取
+    claude: Option<(String, String, String)>,
+    /// Gemini 会话所属项目（chats 目录按项目哈希存放）
+    project_path: Option<String>,
+}
+
+/// 规范化提示词文本：去除首尾空白并折叠连续空白
+pub(crate) fn normalize_prompt_text(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// 由规范化文本生成条目 ID
+fn prompt_entry_id(normalized: &str) -> String {
+    format!("{:x}", md5::compute(normalized.as_bytes()))
+}
+
+fn read_gemini_session_id(path: &Path) -> Option<String> {
+    let content = std::fs::read_to_string(path).ok()?;
+    let data: serde_json::Value = serde_json::from_str(&content).ok()?;
+    data.get("sessionId")?.as_str().map(|s| s.to_string())
+}
+
+/// 解析单个会话文件中的提示词（缓存未命中时调用）
+fn parse_session_source(source: &SessionSource) -> Option<CachedSessionPrompts> {
+    let metadata = std::fs::metadata(&source.path).ok()?;
+    let modified = metadata.modified().ok()?;
+
+    let (session_id, project_path, records) = match source.engine {
+        "claude" => {
+            let (session_id, project_id, project_path) = source.claude.clone()?;
+            let records =
+                super::prompt_tracker::extract_prompts_from_jsonl(&session_id, &project_id).ok()?;
+            (session_id, project_path, records)
+        }
+        "codex" => {
+            let (session_id, cwd) = read_codex_session_meta(&source.path)?;
+            let cwd = cwd.unwrap_or_default();
+            let records =
+                super::codex::git_ops::extract_codex_prompts_from_file(&source.path).ok()?;
+            (session_id, cwd, records)
+        }
+        "gemini" => {
+            let session_id = read_gemini_session_id(&source.path)?;
+            let records =
+                super::gemini::git_ops::extract_gemini_prompts_from_file(&source.path).ok()?;
+            (session_id, source.project_path.clone()?, records)
+        }
+        _ => return None,
+    };
+
+    Some(CachedSessionPrompts {
+        modified,
+        len: metadata.len(),
+        session_id,
+        project_path,
+        prompts: records
+            .into_iter()
+            .map(|record| (record.text, record.timestamp))
+            .collect(),
+    })
+}
+
+/// 枚举三个引擎的会话文件
+async fn discover_session_sources() -> Vec<SessionSource> {
+    let mut sources = Vec::new();
+    let mut project_paths: HashSet<String> = HashSet::new();
+
+    // Claude: ~/.claude/projects/<project_id>/<session_id>.jsonl
+    if let Ok(projects) = super::claude::list_projects().await {
+        for project in projects {
+            for session_id in &project.sessions {
+                let Ok(path) = claude_session_path(&project.id, session_id) else {
+                    continue;
+                };
+                sources.push(SessionSource {
+                    engine: "claude",
+                    path,
+                    claude: Some((session_id.clone(), project.id.clone(), project.path.clone())),
+                    project_path: None,
+                });
+            }
+            project_paths.insert(project.path);
+        }
+    }
+
+    // Codex: sessions/<yyyy>/<mm>/<dd>/rollout-*.jsonl
+    if let Ok(sessions_dir) = super::codex::get_codex_sessions_dir() {
+        for entry in walkdir::WalkDir::new(&sessions_dir).into_iter().flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|s| s.to_str()) == Some("jsonl") {
+                let cwd = cached_project_path(path)
+                    .or_else(|| read_codex_session_meta(path).and_then(|(_, cwd)| cwd));
+                if let Some(cwd) = cwd.filter(|c| !c.is_empty()) {
+                    project_paths.insert(cwd);
+                }
+                sources.push(SessionSource {
+                    engine: "codex",
+                    path: path.to_path_buf(),
+                    claude: None,
+                    project_path: None,
+                });
+            }
+        }
+    }
+
+    // Gemini: ~/.gemini/tmp/<project_hash>/chats/*.json，只能通过已知项目路径定位
+    for project_path in project_paths {
+        let Ok(chats_dir) = super::gemini::git_ops::get_gemini_sessions_dir(&project_path) else {
+            continue;
+        };
+        let Ok(entries) = std::fs::read_dir(&chats_dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|s| s.to_str()) == Some("json") {
+                sources.push(SessionSource {
+                    engine: "gemini",
+                    path,
+                    claude: None,
+                    project_path: Some(project_path.clone()),
+                });
+            }
+        }
+    }
+
+    sources
+}
+
+/// 移除某个会话的解析缓存（会话被回滚、截断或删除后调用）
+pub(crate) fn invalidate_cached_session(session_id: &str) {
+    if let Ok(mut cache) = PROMPT_CACHE.lock() {
+        cache.retain(|_, cached| cached.session_id != session_id);
+    }
+}
+
+fn cached_project_path(path: &Path) -> Option<String> {
+    let cache = PROMPT_CACHE.lock().ok()?;
+    cache.get(path).map(|cached| cached.project_path.clone())
+}
+
+/// 收集所有会话中的提示词出现记录（走 mtime 缓存）
+pub(crate) async fn collect_prompt_occurrences() -> Result<Vec<PromptOccurrence>, String> {
+    let sources = discover_session_sources().await;
+
+    tokio::task::spawn_blocking(move || {
+        let mut cache = PROMPT_CACHE
+            .lock()
+            .map_err(|e| format!("Prompt cache poisoned: {}", e))?;
+        let mut live_paths = HashSet::new();
+        let mut occurrences = Vec::new();
+
+        for source in sources {
+            let Ok(metadata) = std::fs::metadata(&source.path) else {
+                continue;
+            };
+            let modified = metadata.modified().ok();
+            let fresh = cache.get(&source.path).is_some_and(|cached| {
+                Some(cached.modified) == modified && cached.len == metadata.len()
+            });
+            if !fresh {
+                match parse_session_source(&source) {
+                    Some(parsed) => {
+                        cache.insert(source.path.clone(), parsed);
+                    }
+                    None => {
+                        cache.remove(&source.path);
+                        continue;
+                    }
+                }
+            }
+            live_paths.insert(source.path.clone());
+
+            if let Some(cached) = cache.get(&source.path) {
+                for (text, timestamp) in &cached.prompts {
+                    occurrences.push(PromptOccurrence {
+                        engine: source.engine.to_string(),
+                        session_id: cached.session_id.clone(),
+                        project_path: cached.project_path.clone(),
+                        text: text.clone(),
+                        timestamp: *timestamp,
+                    });
+                }
+            }
+        }
+
+        // 清理已删除会话的缓存
+        cache.retain(|path, _| live_paths.contains(path));
+        Ok(occurrences)
+    })
+    .await
+    .map_err(|e| format!("Prompt history task failed: {}", e))?
+}
+
+/// 按规范化文本去重：相同提示词保留最近一次，记录出现次数，按时间倒序
+fn dedup_prompt_occurrences(
+    occurrences: Vec<PromptOccurrence>,
+) -> Vec<(String, PromptOccurrence, usize)> {
+    let mut grouped: HashMap<String, (PromptOccurrence, usize)> = HashMap::new();
+
+    for occurrence in occurrences {
+        let normalized = normalize_prompt_text(&occurrence.text);
+        if normalized.is_empty() {
+            continue;
+        }
+        match grouped.get_mut(&normalized) {
+            Some((latest, count)) => {
+                *count += 1;
+                if occurrence.timestamp > latest.timestamp {
+                    *latest = occurrence;
+                }
+            }
+            None => {
+                grouped.insert(normalized, (occurrence, 1));
+            }
+        }
+    }
+
+    let mut entries: Vec<_> = grouped
+        .into_iter()
+        .map(|(normalized, (latest, count))| (prompt_entry_id(&normalized), latest, count))
+        .collect();
+    entries.sort_by_key(|entry| std::cmp::Reverse(entry.1.timestamp));
+    entries
+}
+
+/// 提示词发送目标
+#[derive(Debug, Clone)]
+pub(crate) enum PromptTarget {
+    /// 新建会话
+    New,
+    /// 续接指定会话
+    Resume(String),
+    /// 续接该项目最近一次会话
+    ResumeLast,
+}
+
+/// 通过各引擎既有的执行命令发送一条提示词
+///
+/// mode 按引擎解释：Claude 为 "plan"/"default"，Codex 为执行模式（read-only/full-auto/...），
+/// Gemini 为 approval mode
+pub(crate) async fn dispatch_prompt(
+    app: AppHandle,
+    engine: &str,
+    project_path: &str,
+    target: PromptTarget,
+    prompt: &str,
+    model: Option<String>,
+    mode: Option<String>,
+) -> Result<(), String> {
+    dispatch_prompt_traced(app, engine, project_path, target, prompt, model, mode, None).await
+}
+
+/// 与 `dispatch_prompt` 相同，但指定本次执行的 trace id
+///
+/// 完成事件会带回该 trace id，调用方据此只等待自己发起的那次执行，
+/// 不会被同一项目中并发运行的其他任务误触发
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn dispatch_prompt_traced(
+    app: AppHandle,
+    engine: &str,
+    project_path: &str,
+    target: PromptTarget,
+    prompt: &str,
+    model: Option<String>,
+    mode: Option<String>,
+    trace_id: Option<String>,
+) -> Result<(), String> {
+    let project_path = project_path.to_string();
+    let prompt = prompt.to_string();
+
+    match engine {
+        "claude" => {
+            let model = model.unwrap_or_else(|| DEFAULT_CLAUDE_MODEL.to_string());
+            let plan_mode = mode.map(|m| m == "plan");
+            match target {
+                PromptTarget::New => super::claude::execute_claude_code(
+                    app,
+                    project_path,
+                    prompt,
+                    model,
+                    plan_mode,
+                    None,
+                    None,
+                    None,
+                    trace_id,
+                    None,
+                )
+                .await
+                .map_err(String::from),
+                PromptTarget::Resume(session_id) => super::claude::resume_claude_code(
+                    app,
+                    project_path,
+                    session_id,
+                    prompt,
+                    model,
+                    plan_mode,
+                    None,
+                    None,
+                    None,
+                    trace_id,
+                    None,
+                )
+                .await
+                .map_err(String::from),
+                PromptTarget::ResumeLast => super::claude::continue_claude_code(
+                    app,
+                    project_path,
+                    prompt,
+                    model,
+                    plan_mode,
+                    None,
+                    None,
+                    None,
+                    trace_id,
+                    None,
+                )
+                .await
+                .map_err(String::from),
+            }
+        }
+        "codex" => {
+            let mode = match mode {
+                Some(mode) => serde_json::from_value::<CodexExecutionMode>(
+                    serde_json::Value::String(mode.clone()),
+                )
+                .map_err(|_| format!("Invalid Codex mode: {}", mode))?,
+                None => CodexExecutionMode::default(),
+            };
+            let mut options = CodexExecutionOptions {
+                project_path,
+                prompt,
+                mode,
+                model,
+                profile: None,
+                reasoning_effort: None,
+                verbosity: None,
+                strict_config: false,
+                config_overrides: Vec::new(),
+                config_warnings: Vec::new(),
+                json: true,
+                output_schema: None,
+                output_file: None,
+                skip_git_repo_check: false,
+                api_key: None,
+                session_id: None,
+                resume_last: false,
+                check_context_overflow: false,
+                force: false,
+                use_project_memory: false,
+                trace_id,
+                enhancement: None,
+            };
+            match target {
+                PromptTarget::New => super::codex::execute_codex(options, app)
+                    .await
+                    .map_err(String::from),
+                PromptTarget::Resume(session_id) => {
+                    options.session_id = Some(session_id.clone());
+                    super::codex::resume_codex(session_id, options, app)
+                        .await
+                        .map_err(String::from)
+                }
+                PromptTarget::ResumeLast => {
+                    options.resume_last = true;
+                    super::codex::resume_last_codex(options, app)
+                        .await
+                        .map_err(String::from)
+                }
+            }
+        }
+        "gemini" => {
+            let defaults = GeminiExecutionOptions::default();
+            let options = GeminiExecutionOptions {
+                project_path,
+                prompt,
+                model: model.or(defaults.model),
+                approval_mode: mode.or(defaults.approval_mode),
+                session_id: match target {
+                    PromptTarget::New => None,
+                    PromptTarget::Resume(session_id) => Some(session_id),
+                    // Gemini CLI 续接时总是使用 latest
+                    PromptTarget::ResumeLast => Some("latest".to_string()),
+                },
+                trace_id,
+                ..defaults
+            };
+            super::gemini::execute_gemini(options, app)
+                .await
+                .map_err(String::from)
+        }
+        other => Err(format!("Unsupported engine: {}", other)),
+    }
+}
+
+/// 获取跨会话提示词历史（去重、按时间倒序）
+#[tauri::command]
+pub async fn get_prompt_history(
+    project_path: Option<String>,
+    query: Option<String>,
+    limit: Option<usize>,
+) -> Result<Vec<PromptHistoryEntry>, String> {
+    let occurrences = collect_prompt_occurrences().await?;
+
+    let project_filter = project_path
+        .filter(|p| !p.trim().is_empty())
+        .map(|p| normalize_path_for_comparison(&p));
+    let query = query
+        .map(|q| q.trim().to_lowercase())
+        .filter(|q| !q.is_empty());
+
+    let occurrences = occurrences
+        .into_iter()
+        .filter(|o| {
+            project_filter
+                .as_ref()
+                .is_none_or(|p| normalize_path_for_comparison(&o.project_path) == *p)
+        })
+        .collect();
+
+    let entries = dedup_prompt_occurrences(occurrences)
+        .into_iter()
+        .filter(|(_, latest, _)| {
+            query
+                .as_ref()
+                .is_none_or(|q| latest.text.to_lowercase().contains(q))
+        })
+        .take(limit.unwrap_or(DEFAULT_HISTORY_LIMIT))
+        .map(|(id, latest, occurrences)| {
+            let truncated = latest.text.chars().nth(MAX_LIST_TEXT_CHARS).is_some();
+            let text = truncate_chars(&latest.text, MAX_LIST_TEXT_CHARS);
+            PromptHistoryEntry {
+                id,
+                text,
+                truncated,
+                engine: latest.engine,
+                session_id: latest.session_id,
+                project_path: latest.project_path,
+                timestamp: latest.timestamp,
+                occurrences,
+            }
+        })
+        .collect();
+
+    Ok(entries)
+}
+
+/// 将历史提示词（可带修改）发送到指定引擎的新会话或已有会话
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn resend_prompt(
+    app: AppHandle,
+    entry_id: String,
+    target_engine: String,
+    target_session: Option<String>,
+    modifications: Option<PromptModifications>,
+    project_path: Option<String>,
+    model: Option<String>,
+) -> Result<ResendPromptResult, String> {
+    let occurrences = collect_prompt_occurrences().await?;
+    let (_, latest, _) = dedup_prompt_occurrences(occurrences)
+        .into_iter()
+        .find(|(id, _, _)| *id == entry_id)
+        .ok_or_else(|| format!("Prompt history entry not found: {}", entry_id))?;
+
+    let modifications = modifications.unwrap_or_default();
+    let mut prompt = modifications
+        .text
+        .filter(|t| !t.trim().is_empty())
+        .unwrap_or(latest.text);
+    if let Some(append) = modifications.append.filter(|a| !a.trim().is_empty()) {
+        prompt = format!("{}\n\n{}", prompt.trim_end(), append);
+    }
+
+    let project_path = project_path
+        .filter(|p| !p.trim().is_empty())
+        .unwrap_or(latest.project_path);
+    if project_path.is_empty() {
+        return Err("Target project path is unknown".to_string());
+    }
+    let target_session = target_session.filter(|s| !s.trim().is_empty());
+
+    log::info!(
+        "resend_prompt: entry={}, engine={}, resume={}, prompt_len={}",
+        entry_id,
+        target_engine,
+        target_session.is_some(),
+        prompt.len()
+    );
+
+    let target = match &target_session {
+        Some(session_id) => PromptTarget::Resume(session_id.clone()),
+        None => PromptTarget::New,
+    };
+    dispatch_prompt(
+        app,
+        &target_engine,
+        &project_path,
+        target,
+        &prompt,
+        model,
+        None,
+    )
+    .await?;
+
+    Ok(ResendPromptResult {
+        engine: target_engine,
+        project_path,
+        session_id: target_session,
+        prompt,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn occurrence(engine: &str, text: &str, timestamp: i64) -> PromptOccurrence {
+        PromptOccurrence {
+            engine: engine.to_string(),
+            session_id: format!("{}-{}", engine, timestamp),
+            project_path: "/repo".to_string(),
+            text: text.to_string(),
+            timestamp,
+        }
+    }
+
+    #[test]
+    fn dedup_keeps_latest_and_counts_occurrences() {
+        let entries = dedup_prompt_occurrences(vec![
+            occurrence("claude", "fix  the\nbuild", 100),
+            occurrence("codex", "fix the build", 300),
+            occurrence("gemini", "add tests", 200),
+            occurrence("claude", "   ", 400),
+        ]);
+
+        assert_eq!(entries.len(), 2);
+        let (id, latest, count) = &entries[0];
+        assert_eq!(latest.engine, "codex");
+        assert_eq!(*count, 2);
+        assert_eq!(*id, prompt_entry_id("fix the build"));
+        assert_eq!(entries[1].1.text, "add tests");
+    }
+}
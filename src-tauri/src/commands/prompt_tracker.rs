@@ -1,14 +1,35 @@
 use anyhow::{Context, Result};
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use log;
+use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::fs;
-use std::path::PathBuf;
+use std::ops::ControlFlow;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+use tauri::{AppHandle, Emitter};
 
 use super::claude::get_claude_dir;
+use super::execution_trace::{
+    record_trace_event, with_trace_trailer, TraceFields, STAGE_GIT_SNAPSHOT, STAGE_PROMPT_RECORDED,
+};
+use super::messages::{t, t_with, MessageKey};
 use super::permission_config::ClaudeExecutionConfig;
+use super::protected_paths::{
+    attach_restore_records, capture_task_snapshot, check_protected_changes, ProtectedRestore,
+};
+use super::session_alias::resolve_session_alias;
+use super::session_annotations::bind_prompt_trace;
+use super::session_invalidation::invalidate_session;
+use super::session_utils::{claude_project_dir, find_session_path, resolve_session_file};
 use super::simple_git;
+use crate::utils::config_utils::{load_json_config, save_json_config, ConfigPathBuilder};
+use crate::utils::jsonl_reader::{
+    for_each_json_line, normalize_line_endings, read_to_string_lossy,
+};
+use crate::utils::session_lock::{write_session_file, FileSnapshot, SessionLock};
+use crate::utils::timestamp::{deserialize_unix_seconds, parse_timestamp_value};
 
 /// Rewind mode for reverting prompts
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -20,6 +41,10 @@ pub enum RewindMode {
     CodeOnly,
     /// Revert both conversation and code (full revert)
     Both,
+    /// Delete a single prompt from the conversation, keeping earlier and later prompts
+    ///
+    /// Code is never reverted: its changes are entangled with the prompts around it.
+    Selective { target_index: usize },
 }
 
 /// Capabilities for rewinding a specific prompt
@@ -50,12 +75,22 @@ pub struct PromptRecord {
     pub git_commit_before: String,
     /// Git commit after AI completed (optional)
     pub git_commit_after: Option<String>,
-    /// Timestamp when prompt was sent
+    /// Timestamp when prompt was sent (UTC Unix seconds, 0 if unknown)
+    #[serde(deserialize_with = "deserialize_unix_seconds")]
     pub timestamp: i64,
+    /// Same instant in milliseconds, for precise display on the frontend
+    #[serde(default)]
+    pub timestamp_ms: i64,
     /// Prompt source: "project" (sent from project interface with queue-operation) or "cli" (sent from CLI)
     pub source: String,
     /// Line number in the JSONL file (0-based)
     pub line_number: usize,
+    /// Protected files restored after this prompt changed them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub protected_restore: Option<ProtectedRestore>,
+    /// When this prompt's code changes were reverted on their own (UTC Unix seconds)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reverted_at: Option<i64>,
 }
 
 /// Git record for a prompt (stored by content hash)
@@ -66,8 +101,155 @@ pub struct GitRecord {
     pub commit_before: String,
     /// Git commit after AI completed (optional)
     pub commit_after: Option<String>,
-    /// Timestamp when prompt was sent
+    /// Timestamp when prompt was sent (UTC Unix seconds)
+    #[serde(default, deserialize_with = "deserialize_unix_seconds")]
     pub timestamp: i64,
+    /// Trace ID of the execution that produced this prompt
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trace_id: Option<String>,
+    /// When this prompt's code changes were reverted on their own (UTC Unix seconds)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reverted_at: Option<i64>,
+}
+
+impl PromptRecord {
+    /// Set the send time from a parsed UTC timestamp (None leaves it unknown)
+    pub(crate) fn set_sent_at(&mut self, sent_at: Option<DateTime<Utc>>) {
+        self.timestamp = sent_at.map_or(0, |dt| dt.timestamp());
+        self.timestamp_ms = sent_at.map_or(0, |dt| dt.timestamp_millis());
+    }
+
+    /// Fall back to the git record time when the session file had no timestamp
+    pub(crate) fn fill_missing_timestamp(&mut self, unix_seconds: i64) {
+        if self.timestamp == 0 && unix_seconds > 0 {
+            self.timestamp = unix_seconds;
+            self.timestamp_ms = unix_seconds * 1000;
+        }
+    }
+}
+
+/// Result of marking a prompt completed (code snapshot status)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PromptCompletionResult {
+    /// Whether the post-prompt code snapshot (auto-commit) was created
+    pub snapshot_created: bool,
+    /// Why the snapshot could not be created (None on success or when git is disabled)
+    pub snapshot_error: Option<String>,
+    /// HEAD after completion, recorded as git_commit_after
+    pub commit_after: Option<String>,
+}
+
+/// Report a failed post-prompt code snapshot: log it and emit `prompt-snapshot-failed`
+/// so the frontend can tell the user (the completion itself goes on)
+pub(crate) fn report_snapshot_failure(
+    app: &AppHandle,
+    engine: &str,
+    session_id: &str,
+    prompt_index: usize,
+    reason: &str,
+) {
+    log::warn!(
+        "[{}] Failed to auto-commit after prompt #{}: {}",
+        engine,
+        prompt_index,
+        reason
+    );
+    let _ = app.emit(
+        "prompt-snapshot-failed",
+        serde_json::json!({
+            "engine": engine,
+            "sessionId": session_id,
+            "promptIndex": prompt_index,
+            "reason": reason,
+        }),
+    );
+}
+
+/// Default markers identifying skill/slash-command messages (not real user prompts)
+const DEFAULT_SKILL_MARKERS: &[&str] = &["<command-name>", "Launching skill:", "skill is running"];
+
+/// Prompt filtering config (~/.anycode/prompt_filter.json)
+/// Claude Code changes its skill message wording over time; stale markers shift rewind indices,
+/// so the markers are user-adjustable without an app release
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PromptFilterConfig {
+    /// Substrings marking a user message as a skill message
+    #[serde(default = "default_skill_markers")]
+    pub skill_markers: Vec<String>,
+}
+
+fn default_skill_markers() -> Vec<String> {
+    DEFAULT_SKILL_MARKERS.iter().map(|m| m.to_string()).collect()
+}
+
+impl Default for PromptFilterConfig {
+    fn default() -> Self {
+        Self {
+            skill_markers: default_skill_markers(),
+        }
+    }
+}
+
+/// Loaded once, replaced on update
+static PROMPT_FILTER_CONFIG: Lazy<RwLock<PromptFilterConfig>> =
+    Lazy::new(|| RwLock::new(read_prompt_filter_config()));
+
+pub(crate) fn get_prompt_filter_config_path() -> std::result::Result<PathBuf, String> {
+    Ok(ConfigPathBuilder::from_home_subdir(".anycode")?.build("prompt_filter.json"))
+}
+
+fn read_prompt_filter_config() -> PromptFilterConfig {
+    get_prompt_filter_config_path()
+        .and_then(|path| load_json_config(&path))
+        .unwrap_or_else(|e| {
+            log::warn!("Failed to load prompt filter config, using default: {}", e);
+            PromptFilterConfig::default()
+        })
+}
+
+/// Shared predicate: whether a user message is a skill message (excluded from prompt indices)
+fn is_skill_message(text: &str) -> bool {
+    let config = PROMPT_FILTER_CONFIG
+        .read()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    config
+        .skill_markers
+        .iter()
+        .any(|marker| !marker.is_empty() && text.contains(marker.as_str()))
+}
+
+/// Get prompt filtering config (skill message markers)
+#[tauri::command]
+pub async fn get_prompt_filter_config() -> std::result::Result<PromptFilterConfig, String> {
+    Ok(PROMPT_FILTER_CONFIG
+        .read()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .clone())
+}
+
+/// Update prompt filtering config
+#[tauri::command]
+pub async fn update_prompt_filter_config(
+    mut config: PromptFilterConfig,
+) -> std::result::Result<(), String> {
+    config.skill_markers = config
+        .skill_markers
+        .into_iter()
+        .map(|m| m.trim().to_string())
+        .filter(|m| !m.is_empty())
+        .collect();
+
+    log::info!(
+        "Updating prompt filter config: {} skill markers",
+        config.skill_markers.len()
+    );
+    save_json_config(&config, &get_prompt_filter_config_path()?)?;
+    *PROMPT_FILTER_CONFIG
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner()) = config;
+    Ok(())
 }
 
 /// Load execution config from file
@@ -89,10 +271,8 @@ pub fn load_execution_config() -> Result<ClaudeExecutionConfig> {
 
 /// Get path to git records file
 fn get_git_records_path(session_id: &str, project_id: &str) -> Result<PathBuf> {
-    let claude_dir = get_claude_dir().context("Failed to get claude dir")?;
-    let records_path = claude_dir
-        .join("projects")
-        .join(project_id)
+    let records_path = claude_project_dir(project_id)
+        .map_err(anyhow::Error::msg)?
         .join("sessions")
         .join(format!("{}.git-records.json", session_id));
     Ok(records_path)
@@ -107,20 +287,14 @@ fn load_git_records(session_id: &str, project_id: &str) -> Result<HashMap<usize,
 
     let content = fs::read_to_string(&records_path).context("Failed to read git records file")?;
 
-    // Support both old format (String keys) and new format (usize keys)
-    // Try parsing as new format first
-    if let Ok(records) = serde_json::from_str::<HashMap<usize, GitRecord>>(&content) {
-        return Ok(records);
-    }
-
-    // Fallback: try parsing old format and migrate
-    if let Ok(_old_records) = serde_json::from_str::<HashMap<String, GitRecord>>(&content) {
-        log::warn!("Found old hash-based git records format, will migrate to index-based format on next save");
-        // Return empty map - old records cannot be reliably migrated without prompt index info
-        return Ok(HashMap::new());
+    // Old hash-keyed files are backed up by the startup migrations (see migrations)
+    match serde_json::from_str::<HashMap<usize, GitRecord>>(&content) {
+        Ok(records) => Ok(records),
+        Err(e) => {
+            log::warn!("Failed to parse git records {:?}: {}", records_path, e);
+            Ok(HashMap::new())
+        }
     }
-
-    Ok(HashMap::new())
 }
 
 /// Save git records to .git-records.json (using prompt_index as key)
@@ -145,7 +319,7 @@ fn save_git_records(
 }
 
 /// Save a single git record (using prompt_index as key)
-fn save_git_record(
+pub(crate) fn save_git_record(
     session_id: &str,
     project_id: &str,
     prompt_index: usize,
@@ -159,7 +333,7 @@ fn save_git_record(
 }
 
 /// Get a git record by prompt_index
-fn get_git_record(
+pub(crate) fn get_git_record(
     session_id: &str,
     project_id: &str,
     prompt_index: usize,
@@ -168,6 +342,81 @@ fn get_git_record(
     Ok(records.get(&prompt_index).cloned())
 }
 
+/// Get path to the prompt source reclassification sidecar
+fn get_prompt_sources_path(session_id: &str, project_id: &str) -> Result<PathBuf> {
+    let records_path = get_git_records_path(session_id, project_id)?;
+    Ok(records_path.with_file_name(format!("{}.prompt-sources.json", session_id)))
+}
+
+/// Copy the git records and prompt source sidecar of a session to the session continuing it
+///
+/// Used when resuming writes to a new session id; the new session file carries the same
+/// history, so prompt indices stay valid. Existing files of the new session are kept.
+pub(crate) fn copy_session_records(
+    old_session_id: &str,
+    new_session_id: &str,
+    project_id: &str,
+) -> std::result::Result<(), String> {
+    let pairs = [
+        (
+            get_git_records_path(old_session_id, project_id),
+            get_git_records_path(new_session_id, project_id),
+        ),
+        (
+            get_prompt_sources_path(old_session_id, project_id),
+            get_prompt_sources_path(new_session_id, project_id),
+        ),
+    ];
+    for (from, to) in pairs {
+        let (from, to) = (
+            from.map_err(|e| e.to_string())?,
+            to.map_err(|e| e.to_string())?,
+        );
+        if from.exists() && !to.exists() {
+            fs::copy(&from, &to).map_err(|e| format!("Failed to copy {:?}: {}", from, e))?;
+        }
+    }
+    Ok(())
+}
+
+/// Load prompt indices reclassified as "project" (legacy/converted sessions without dequeue markers)
+fn load_reclassified_sources(session_id: &str, project_id: &str) -> Result<BTreeSet<usize>> {
+    let path = get_prompt_sources_path(session_id, project_id)?;
+
+    if !path.exists() {
+        return Ok(BTreeSet::new());
+    }
+
+    let content = fs::read_to_string(&path).context("Failed to read prompt sources file")?;
+    serde_json::from_str(&content).context("Failed to parse prompt sources file")
+}
+
+/// Save reclassified prompt indices (removes the sidecar when empty)
+fn save_reclassified_sources(
+    session_id: &str,
+    project_id: &str,
+    indices: &BTreeSet<usize>,
+) -> Result<()> {
+    let path = get_prompt_sources_path(session_id, project_id)?;
+
+    if indices.is_empty() {
+        if path.exists() {
+            fs::remove_file(&path).context("Failed to remove prompt sources file")?;
+        }
+        return Ok(());
+    }
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).context("Failed to create sessions directory")?;
+    }
+
+    let content =
+        serde_json::to_string_pretty(indices).context("Failed to serialize prompt sources")?;
+    fs::write(&path, content).context("Failed to write prompt sources file")?;
+
+    Ok(())
+}
+
 fn build_prompt_commit_message(
     prefix: &str,
     prompt_text: Option<&str>,
@@ -186,7 +435,7 @@ fn build_prompt_commit_message(
 }
 
 /// Truncate git records (remove records for prompts after the specified index)
-fn truncate_git_records(
+pub(crate) fn truncate_git_records(
     session_id: &str,
     project_id: &str,
     prompts: &[PromptRecord],
@@ -202,6 +451,15 @@ fn truncate_git_records(
     }
 
     save_git_records(session_id, project_id, &records)?;
+
+    // The prompt at prompt_index is removed from the JSONL too, so its index may be reused
+    let mut reclassified = load_reclassified_sources(session_id, project_id)?;
+    let before = reclassified.len();
+    reclassified.retain(|&i| i < prompt_index);
+    if reclassified.len() != before {
+        save_reclassified_sources(session_id, project_id, &reclassified)?;
+    }
+
     log::info!(
         "[Truncate] Truncated git records after prompt #{}",
         prompt_index
@@ -209,188 +467,136 @@ fn truncate_git_records(
     Ok(())
 }
 
-/// Truncate session JSONL file to before a specific prompt
-/// 🆕 Now supports multiple files (main session + agent files)
-fn truncate_session_to_prompt(
+/// Map a prompt index across the removal of prompts `start_index..=end_index`
+fn shift_prompt_index(index: usize, start_index: usize, end_index: usize) -> Option<usize> {
+    if index < start_index {
+        Some(index)
+    } else if index > end_index {
+        Some(index - (end_index - start_index + 1))
+    } else {
+        None
+    }
+}
+
+/// Drop the records of prompts `start_index..=end_index` and shift later ones down
+fn reindex_git_records(
+    records: HashMap<usize, GitRecord>,
+    start_index: usize,
+    end_index: usize,
+) -> HashMap<usize, GitRecord> {
+    records
+        .into_iter()
+        .filter_map(|(i, record)| {
+            shift_prompt_index(i, start_index, end_index).map(|i| (i, record))
+        })
+        .collect()
+}
+
+/// Drop git records of prompts `start_index..=end_index` and re-index later ones
+fn remove_git_records_range(
     session_id: &str,
     project_id: &str,
-    prompt_index: usize,
+    start_index: usize,
+    end_index: usize,
 ) -> Result<()> {
-    let claude_dir = get_claude_dir().context("Failed to get claude dir")?;
-    let project_dir = claude_dir.join("projects").join(project_id);
-    let session_path = project_dir.join(format!("{}.jsonl", session_id));
-
-    if !session_path.exists() {
-        return Ok(()); // No session file, nothing to truncate
+    let records = load_git_records(session_id, project_id)?;
+    if !records.is_empty() {
+        let records = reindex_git_records(records, start_index, end_index);
+        save_git_records(session_id, project_id, &records)?;
     }
 
-    // ========================================================================
-    // Step 1: Process main session file
-    // ========================================================================
-
-    // Read all lines
-    let content = fs::read_to_string(&session_path).context("Failed to read session file")?;
-
-    let lines: Vec<&str> = content.lines().collect();
-
-    // Count user messages and find the line index to truncate at
-    let mut user_message_count = 0;
-    let mut truncate_at_line = 0;
-    let mut found_target = false; // Flag to track if we found the target prompt
-
-    for (line_index, line) in lines.iter().enumerate() {
-        // Parse line as JSON to check message type
-        if let Ok(msg) = serde_json::from_str::<serde_json::Value>(line) {
-            // 🆕 跳过非用户消息类型（新版 Claude 引入的消息类型）
-            let msg_type = msg.get("type").and_then(|t| t.as_str());
-
-            log::debug!("Line {}: type={:?}", line_index, msg_type);
-
-            // 忽略 summary 和 file-history-snapshot 类型
-            if msg_type == Some("summary") || msg_type == Some("file-history-snapshot") {
-                log::debug!(
-                    "Skipping {} message at line {}",
-                    msg_type.unwrap(),
-                    line_index
-                );
-                continue;
-            }
-
-            // 只处理用户消息
-            if msg_type == Some("user") {
-                // 检查是否是侧链消息（agent 消息）
-                let is_sidechain = msg
-                    .get("isSidechain")
-                    .and_then(|v| v.as_bool())
-                    .unwrap_or(false);
-
-                if is_sidechain {
-                    log::debug!("Skipping sidechain user message at line {}", line_index);
-                    continue;
-                }
-
-                // 检查是否有 parent_tool_use_id（子代理的消息）
-                let has_parent_tool_use_id = msg.get("parent_tool_use_id").is_some()
-                    && !msg.get("parent_tool_use_id").unwrap().is_null();
+    let reclassified = load_reclassified_sources(session_id, project_id)?;
+    if !reclassified.is_empty() {
+        let reclassified: BTreeSet<usize> = reclassified
+            .into_iter()
+            .filter_map(|i| shift_prompt_index(i, start_index, end_index))
+            .collect();
+        save_reclassified_sources(session_id, project_id, &reclassified)?;
+    }
 
-                if has_parent_tool_use_id {
-                    log::debug!(
-                        "Skipping subagent message at line {} (has parent_tool_use_id)",
-                        line_index
-                    );
-                    continue;
-                }
+    log::info!(
+        "[Remove Range] Re-indexed git records after removing prompts #{}-#{}",
+        start_index,
+        end_index
+    );
+    Ok(())
+}
 
-                // 提取消息内容（支持字符串和数组两种格式）
-                let content_value = msg.get("message").and_then(|m| m.get("content"));
+/// Text of a message that counts as a real user prompt, None for anything else
+///
+/// Shared by prompt extraction and truncation so both number prompts identically.
+/// Checks run in a fixed order: message type, then sidechain/subagent origin, then content.
+/// Only top-level `text` blocks count as prompt text, so a message carrying nothing but
+/// tool_result blocks (or blank text next to them) is a tool response, not a prompt.
+/// Warmup and Skills messages are excluded as well.
+fn user_prompt_text(msg: &serde_json::Value) -> Option<String> {
+    if msg.get("type").and_then(|t| t.as_str()) != Some("user") {
+        return None;
+    }
 
-                log::debug!(
-                    "Line {}: content_value exists={}",
-                    line_index,
-                    content_value.is_some()
-                );
+    // 侧链消息（agent 消息）与子代理消息（带 parent_tool_use_id）不是用户输入
+    let is_sidechain = msg
+        .get("isSidechain")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+    let has_parent_tool_use_id = msg.get("parent_tool_use_id").is_some_and(|v| !v.is_null());
+    if is_sidechain || has_parent_tool_use_id {
+        return None;
+    }
 
-                let mut extracted_text = String::new();
-                let mut has_text_content = false;
-                let mut has_tool_result = false;
-
-                if let Some(content) = content_value {
-                    if let Some(text) = content.as_str() {
-                        // 字符串格式
-                        extracted_text = text.to_string();
-                        has_text_content = !text.trim().is_empty();
-                        log::debug!(
-                            "Line {}: extracted string content, length={}, has_text={}",
-                            line_index,
-                            extracted_text.len(),
-                            has_text_content
-                        );
-                    } else if let Some(arr) = content.as_array() {
-                        // 数组格式（可能包含 text 和 tool_result）
-                        for item in arr {
-                            if let Some(item_type) = item.get("type").and_then(|t| t.as_str()) {
-                                if item_type == "text" {
-                                    if let Some(text) = item.get("text").and_then(|t| t.as_str()) {
-                                        extracted_text.push_str(text);
-                                        has_text_content = true;
-                                    }
-                                } else if item_type == "tool_result" {
-                                    has_tool_result = true;
-                                }
-                            }
-                        }
-                    }
-                }
+    // 提取消息内容（支持字符串和数组两种格式，数组中只取顶层 text 块）
+    let text = match msg.get("message").and_then(|m| m.get("content"))? {
+        serde_json::Value::String(text) => text.clone(),
+        serde_json::Value::Array(blocks) => blocks
+            .iter()
+            .filter(|block| block.get("type").and_then(|t| t.as_str()) == Some("text"))
+            .filter_map(|block| block.get("text").and_then(|t| t.as_str()))
+            .collect(),
+        _ => return None,
+    };
 
-                // 如果只有 tool_result 没有 text，跳过（这些是工具执行结果，不是用户输入）
-                if has_tool_result && !has_text_content {
-                    log::debug!("Skipping tool-result-only message at line {}", line_index);
-                    continue;
-                }
+    // 只有 tool_result（或空白文本）的消息是工具执行结果，不是用户输入
+    if text.trim().is_empty() {
+        return None;
+    }
 
-                // 必须有文本内容
-                if !has_text_content {
-                    log::debug!("Skipping empty user message at line {}", line_index);
-                    continue;
-                }
+    // ⚡ 排除自动发送的 Warmup 消息与 Skills 消息
+    if text.contains("Warmup") || is_skill_message(&text) {
+        return None;
+    }
 
-                // ⚡ 检查是否是自动发送的 Warmup 消息或 Skills 消息
-                let is_warmup = extracted_text.contains("Warmup");
-                let is_skill_message = extracted_text.contains("<command-name>")
-                    || extracted_text.contains("Launching skill:")
-                    || extracted_text.contains("skill is running");
+    Some(text)
+}
 
-                log::debug!(
-                    "Line {}: is_warmup={}, is_skill={}, text_preview={}",
-                    line_index,
-                    is_warmup,
-                    is_skill_message,
-                    extracted_text.chars().take(20).collect::<String>()
-                );
+/// Find the line index of every real user prompt in a session JSONL
+///
+/// Unparseable lines are skipped; see [`user_prompt_text`] for what counts as a prompt.
+pub(crate) fn find_prompt_lines(lines: &[&str]) -> Vec<usize> {
+    lines
+        .iter()
+        .enumerate()
+        .filter_map(|(line_index, line)| {
+            let msg = serde_json::from_str::<serde_json::Value>(line).ok()?;
+            user_prompt_text(&msg).map(|_| line_index)
+        })
+        .collect()
+}
 
-                if !is_warmup && !is_skill_message {
-                    // 只计算真实用户输入的消息（排除自动 Warmup）
-                    log::info!(
-                        "[OK] Found real user message at line {}, count={}, looking for={}",
-                        line_index,
-                        user_message_count,
-                        prompt_index
-                    );
+/// Keep the session JSONL content before prompt `prompt_index`
+///
+/// Expects `\n` line endings (see `normalize_line_endings`). Fails instead of
+/// clearing the session when the prompt doesn't exist.
+fn truncate_at_prompt(content: &str, prompt_index: usize) -> Result<String> {
+    let lines: Vec<&str> = content.lines().collect();
 
-                    if user_message_count == prompt_index {
-                        // Found the target prompt, truncate before it
-                        truncate_at_line = line_index;
-                        found_target = true; // Mark that we found it
-                        log::info!(
-                            "[TARGET] Target prompt #{} found at line {}",
-                            prompt_index,
-                            line_index
-                        );
-                        break;
-                    }
-                    user_message_count += 1;
-                } else if is_warmup {
-                    log::debug!(
-                        "Skipping Warmup message at line {}: {}",
-                        line_index,
-                        extracted_text.chars().take(50).collect::<String>()
-                    );
-                } else if is_skill_message {
-                    log::debug!(
-                        "Skipping Skills message at line {}: {}",
-                        line_index,
-                        extracted_text.chars().take(50).collect::<String>()
-                    );
-                }
-            }
-        }
-    }
+    // Find the line of every real user prompt
+    let prompt_lines = find_prompt_lines(&lines);
 
     let total_lines = lines.len();
 
     // 安全检查：如果没找到目标 prompt，返回错误而不是清空所有内容
-    if !found_target {
-        if user_message_count == 0 {
+    let Some(&truncate_at_line) = prompt_lines.get(prompt_index) else {
+        if prompt_lines.is_empty() {
             return Err(anyhow::anyhow!(
                 "Prompt #{} not found in session (no user messages found)",
                 prompt_index
@@ -399,10 +605,15 @@ fn truncate_session_to_prompt(
             return Err(anyhow::anyhow!(
                 "Prompt #{} not found in session (only {} user messages found)",
                 prompt_index,
-                user_message_count
+                prompt_lines.len()
             ));
         }
-    }
+    };
+    log::info!(
+        "[TARGET] Target prompt #{} found at line {}",
+        prompt_index,
+        truncate_at_line
+    );
 
     log::info!(
         "Total lines: {}, will keep lines 0..{} (delete prompt #{} at line {} and after)",
@@ -416,19 +627,40 @@ fn truncate_session_to_prompt(
     let truncated_lines: Vec<&str> = lines.into_iter().take(truncate_at_line).collect();
 
     // Join with newline and add final newline if we have content
-    let new_content = if truncated_lines.is_empty() {
-        String::new()
+    if truncated_lines.is_empty() {
+        Ok(String::new())
     } else {
-        truncated_lines.join("\n") + "\n" // Add trailing newline
+        Ok(truncated_lines.join("\n") + "\n") // Add trailing newline
+    }
+}
+
+/// Truncate session JSONL file to before a specific prompt
+/// 🆕 Now supports multiple files (main session + agent files)
+fn truncate_session_to_prompt(
+    session_id: &str,
+    project_id: &str,
+    prompt_index: usize,
+) -> Result<()> {
+    let project_dir = claude_project_dir(project_id).map_err(anyhow::Error::msg)?;
+    let Some(session_path) =
+        find_session_path("claude", session_id, project_id).map_err(anyhow::Error::msg)?
+    else {
+        return Ok(()); // No session file, nothing to truncate
     };
 
-    fs::write(&session_path, new_content).context("Failed to write truncated session")?;
+    // 持有会话锁直到截断完成，避免与另一个实例的执行同时写入
+    let _lock = SessionLock::acquire(&session_path, "claude rewind").map_err(anyhow::Error::msg)?;
 
-    log::info!(
-        "Truncated main session: kept {} lines, deleted {} lines",
-        truncate_at_line,
-        total_lines - truncate_at_line
-    );
+    // ========================================================================
+    // Step 1: Process main session file
+    // ========================================================================
+
+    let snapshot = FileSnapshot::capture(&session_path).map_err(anyhow::Error::msg)?;
+    // 非法 UTF-8 按有损方式解码，单行损坏不影响回滚
+    let content = read_to_string_lossy(&session_path).context("Failed to read session file")?;
+    let new_content = truncate_at_prompt(&normalize_line_endings(content), prompt_index)?;
+
+    write_session_file(&session_path, &snapshot, new_content).map_err(anyhow::Error::msg)?;
 
     // ========================================================================
     // Step 2: Handle agent files (新版 Claude 引入的 sidechain 文件)
@@ -500,6 +732,180 @@ fn truncate_session_to_prompt(
     Ok(())
 }
 
+/// Collect tool_use ids and tool_result tool_use_ids from a message's content blocks
+fn collect_tool_ids(
+    msg: &serde_json::Value,
+    uses: &mut HashSet<String>,
+    results: &mut HashSet<String>,
+) {
+    let Some(blocks) = msg
+        .get("message")
+        .and_then(|m| m.get("content"))
+        .and_then(|c| c.as_array())
+    else {
+        return;
+    };
+    for block in blocks {
+        match block.get("type").and_then(|t| t.as_str()) {
+            Some("tool_use") => {
+                if let Some(id) = block.get("id").and_then(|v| v.as_str()) {
+                    uses.insert(id.to_string());
+                }
+            }
+            Some("tool_result") => {
+                if let Some(id) = block.get("tool_use_id").and_then(|v| v.as_str()) {
+                    results.insert(id.to_string());
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Remove the messages of prompts `start_index..=end_index` from session JSONL content
+///
+/// Later prompts are kept. Messages whose parent was removed are re-linked to the
+/// nearest kept ancestor so the parentUuid chain stays intact. Fails if the range
+/// would separate a tool_use from its tool_result.
+fn excise_prompt_range(content: &str, start_index: usize, end_index: usize) -> Result<String> {
+    let lines: Vec<&str> = content.lines().collect();
+    let prompt_lines = find_prompt_lines(&lines);
+
+    if start_index > end_index {
+        return Err(anyhow::anyhow!(
+            "Invalid prompt range #{}-#{}",
+            start_index,
+            end_index
+        ));
+    }
+    if end_index >= prompt_lines.len() {
+        return Err(anyhow::anyhow!(
+            "Prompt #{} not found in session (only {} user messages found)",
+            end_index,
+            prompt_lines.len()
+        ));
+    }
+
+    let range_start = prompt_lines[start_index];
+    let range_end = prompt_lines
+        .get(end_index + 1)
+        .copied()
+        .unwrap_or(lines.len());
+
+    let parsed: Vec<Option<serde_json::Value>> = lines
+        .iter()
+        .map(|line| serde_json::from_str(line).ok())
+        .collect();
+    let is_removed = |i: usize| {
+        let is_summary = parsed[i]
+            .as_ref()
+            .and_then(|msg| msg.get("type"))
+            .and_then(|t| t.as_str())
+            == Some("summary");
+        (range_start..range_end).contains(&i) && !is_summary
+    };
+
+    // Removed message uuid -> its parentUuid, used to re-link the chain
+    let mut removed_parents: HashMap<String, Option<String>> = HashMap::new();
+    let (mut removed_uses, mut removed_results) = (HashSet::new(), HashSet::new());
+    let (mut kept_uses, mut kept_results) = (HashSet::new(), HashSet::new());
+
+    for (i, msg) in parsed.iter().enumerate() {
+        let Some(msg) = msg else { continue };
+        if is_removed(i) {
+            if let Some(uuid) = msg.get("uuid").and_then(|v| v.as_str()) {
+                let parent = msg.get("parentUuid").and_then(|v| v.as_str());
+                removed_parents.insert(uuid.to_string(), parent.map(String::from));
+            }
+            collect_tool_ids(msg, &mut removed_uses, &mut removed_results);
+        } else {
+            collect_tool_ids(msg, &mut kept_uses, &mut kept_results);
+        }
+    }
+
+    if let Some(id) = removed_uses
+        .intersection(&kept_results)
+        .chain(kept_uses.intersection(&removed_results))
+        .next()
+    {
+        return Err(anyhow::anyhow!(
+            "Cannot remove prompts #{}-#{}: tool call {} would lose its tool_use/tool_result pair",
+            start_index,
+            end_index,
+            id
+        ));
+    }
+
+    let resolve_parent = |parent: &str| -> Option<String> {
+        let mut current = Some(parent.to_string());
+        // Bounded walk guards against malformed cyclic chains
+        for _ in 0..=removed_parents.len() {
+            match current.as_deref().and_then(|p| removed_parents.get(p)) {
+                Some(next) => current = next.clone(),
+                None => break,
+            }
+        }
+        current
+    };
+
+    let mut output = Vec::with_capacity(lines.len());
+    let mut relinked = 0;
+    for (i, line) in lines.iter().enumerate() {
+        if is_removed(i) {
+            continue;
+        }
+        let Some(mut msg) = parsed[i].clone() else {
+            output.push(line.to_string());
+            continue;
+        };
+
+        let mut changed = false;
+        for key in ["parentUuid", "logicalParentUuid"] {
+            let Some(parent) = msg.get(key).and_then(|v| v.as_str()) else {
+                continue;
+            };
+            if removed_parents.contains_key(parent) {
+                let new_parent = resolve_parent(parent);
+                msg[key] = new_parent.map_or(serde_json::Value::Null, serde_json::Value::String);
+                changed = true;
+            }
+        }
+
+        if changed {
+            relinked += 1;
+            output.push(serde_json::to_string(&msg).context("Failed to serialize message")?);
+        } else {
+            output.push(line.to_string());
+        }
+    }
+
+    log::info!(
+        "[Remove Range] Removed {} lines for prompts #{}-#{}, re-linked {} messages",
+        lines.len() - output.len(),
+        start_index,
+        end_index,
+        relinked
+    );
+
+    Ok(if output.is_empty() {
+        String::new()
+    } else {
+        output.join("\n") + "\n"
+    })
+}
+
+/// Texts of prompts `start_index..=end_index` in session JSONL content
+fn prompt_texts_in_range(content: &str, start_index: usize, end_index: usize) -> Vec<String> {
+    let lines: Vec<&str> = content.lines().collect();
+    find_prompt_lines(&lines)
+        .into_iter()
+        .skip(start_index)
+        .take((end_index + 1).saturating_sub(start_index))
+        .filter_map(|i| serde_json::from_str::<serde_json::Value>(lines[i]).ok())
+        .filter_map(|msg| user_prompt_text(&msg))
+        .collect()
+}
+
 /// Record a prompt being sent
 #[tauri::command]
 pub async fn record_prompt_sent(
@@ -507,10 +913,13 @@ pub async fn record_prompt_sent(
     project_id: String,
     project_path: String,
     _prompt_text: String,
+    trace_id: Option<String>,
 ) -> Result<usize, String> {
+    let session_id = resolve_session_alias("claude", &session_id);
     log::info!(
-        "[Record Prompt] Recording prompt sent for session: {}",
-        session_id
+        "[Record Prompt] Recording prompt sent for session: {} (trace_id={})",
+        session_id,
+        trace_id.as_deref().unwrap_or("-")
     );
 
     // Check if Git operations are disabled in config
@@ -527,6 +936,15 @@ pub async fn record_prompt_sent(
             "[Record Prompt] Returning prompt index #{} (no git record)",
             prompt_index
         );
+        capture_task_snapshot(
+            "claude",
+            &session_id,
+            Some(&project_id),
+            prompt_index,
+            &project_path,
+        )
+        .await;
+        bind_prompt_trace(trace_id.as_deref(), "claude", &session_id, prompt_index);
         return Ok(prompt_index);
     }
 
@@ -558,6 +976,8 @@ pub async fn record_prompt_sent(
         commit_before: commit_before.clone(),
         commit_after: None,
         timestamp: Utc::now().timestamp(),
+        trace_id: trace_id.clone(),
+        reverted_at: None,
     };
 
     // 🔧 FIX: Save git record using prompt_index as key (not hash!)
@@ -570,6 +990,27 @@ pub async fn record_prompt_sent(
         prompt_index,
         commit_before
     );
+    if let Some(trace_id) = trace_id.as_deref() {
+        record_trace_event(
+            trace_id,
+            STAGE_PROMPT_RECORDED,
+            TraceFields::engine("claude")
+                .with_session(Some(&session_id))
+                .with_detail(serde_json::json!({
+                    "promptIndex": prompt_index,
+                    "commitBefore": commit_before,
+                })),
+        );
+    }
+    capture_task_snapshot(
+        "claude",
+        &session_id,
+        Some(&project_id),
+        prompt_index,
+        &project_path,
+    )
+    .await;
+    bind_prompt_trace(trace_id.as_deref(), "claude", &session_id, prompt_index);
 
     Ok(prompt_index)
 }
@@ -577,13 +1018,30 @@ pub async fn record_prompt_sent(
 /// Mark a prompt as completed (after AI finishes)
 #[tauri::command]
 pub async fn mark_prompt_completed(
+    app: AppHandle,
     session_id: String,
     project_id: String,
     project_path: String,
     prompt_index: usize,
     prompt_text: Option<String>,
-) -> Result<(), String> {
-    log::info!("Marking prompt #{} completed", prompt_index);
+    trace_id: Option<String>,
+) -> Result<PromptCompletionResult, String> {
+    let session_id = resolve_session_alias("claude", &session_id);
+    log::info!(
+        "Marking prompt #{} completed (trace_id={})",
+        prompt_index,
+        trace_id.as_deref().unwrap_or("-")
+    );
+
+    // Compare protected paths with the snapshot taken when the prompt was sent
+    check_protected_changes(
+        &app,
+        "claude",
+        &session_id,
+        prompt_index,
+        trace_id.as_deref(),
+    )
+    .await;
 
     // Check if Git operations are disabled in config
     let execution_config =
@@ -593,27 +1051,35 @@ pub async fn mark_prompt_completed(
         log::info!(
             "[Mark Complete] Git operations disabled, skipping git commit and record update"
         );
-        return Ok(());
+        return Ok(PromptCompletionResult::default());
     }
 
     // Auto-commit any changes made by AI
     // This ensures each prompt has a distinct git state
-    let commit_message =
-        build_prompt_commit_message("[Claude Code]", prompt_text.as_deref(), prompt_index);
-    match simple_git::git_commit_changes(&project_path, &commit_message) {
-        Ok(true) => {
-            log::info!("Auto-committed changes after prompt #{}", prompt_index);
-        }
-        Ok(false) => {
-            log::debug!("No changes to commit after prompt #{}", prompt_index);
+    let commit_message = with_trace_trailer(
+        build_prompt_commit_message("[Claude Code]", prompt_text.as_deref(), prompt_index),
+        trace_id.as_deref(),
+    );
+    let mut result = PromptCompletionResult::default();
+    let mut new_commit = false;
+    match simple_git::git_commit_changes(
+        &project_path,
+        &commit_message,
+        execution_config.git_identity_fallback,
+    ) {
+        Ok(committed) => {
+            new_commit = committed;
+            if committed {
+                log::info!("Auto-committed changes after prompt #{}", prompt_index);
+            } else {
+                log::debug!("No changes to commit after prompt #{}", prompt_index);
+            }
+            result.snapshot_created = true;
         }
         Err(e) => {
-            log::warn!(
-                "Failed to auto-commit after prompt #{}: {}",
-                prompt_index,
-                e
-            );
-            // Continue anyway, don't fail the whole operation
+            // Continue anyway, don't fail the whole operation, but tell the user
+            report_snapshot_failure(&app, "claude", &session_id, prompt_index, &e);
+            result.snapshot_error = Some(e);
         }
     }
 
@@ -628,6 +1094,9 @@ pub async fn mark_prompt_completed(
 
     // Update commit_after
     git_record.commit_after = Some(commit_after.clone());
+    if git_record.trace_id.is_none() {
+        git_record.trace_id = trace_id.clone();
+    }
 
     // 🔧 FIX: Save updated git record using prompt_index (not hash!)
     save_git_record(&session_id, &project_id, prompt_index, git_record)
@@ -638,18 +1107,289 @@ pub async fn mark_prompt_completed(
         prompt_index,
         commit_after
     );
-    Ok(())
+    if let Some(trace_id) = trace_id.as_deref() {
+        record_trace_event(
+            trace_id,
+            STAGE_GIT_SNAPSHOT,
+            TraceFields::engine("claude")
+                .with_session(Some(&session_id))
+                .with_detail(serde_json::json!({
+                    "promptIndex": prompt_index,
+                    "commit": commit_after,
+                    "committed": new_commit,
+                })),
+        );
+    }
+    result.commit_after = Some(commit_after);
+    Ok(result)
 }
 
-/// Revert to a specific prompt with support for different rewind modes
-#[tauri::command]
-pub async fn revert_to_prompt(
-    session_id: String,
-    project_id: String,
-    project_path: String,
-    prompt_index: usize,
+/// Revert the code changes of prompt #`prompt_index` and every later prompt (newest first)
+///
+/// With `OnConflict::Abort` any failure rolls the repository back to `original_head`.
+/// With `OnConflict::Interactive` a conflict leaves the revert in progress and returns an
+/// error starting with `REVERT_CONFLICT_ERROR_PREFIX`.
+/// Returns the number of reverted commits and of records considered.
+fn revert_prompt_records(
+    session_id: &str,
+    project_id: &str,
+    project_path: &str,
+    prompt_index: usize,
+    original_head: &str,
+    on_conflict: simple_git::OnConflict,
+    allow_identity_fallback: bool,
+) -> Result<(usize, usize), String> {
+    // Load ALL git records for this session
+    let all_git_records = load_git_records(session_id, project_id)
+        .map_err(|e| format!("Failed to load git records: {}", e))?;
+
+    // Filter records for prompt_index and onwards, then sort by index descending
+    let mut records_to_revert: Vec<(usize, GitRecord)> = all_git_records
+        .into_iter()
+        .filter(|(idx, _)| *idx >= prompt_index)
+        .collect();
+
+    // Sort by index descending (newest first) - revert from newest to oldest
+    records_to_revert.sort_by(|a, b| b.0.cmp(&a.0));
+
+    log::info!(
+        "[Precise Revert] Found {} records to revert (prompts {} and onwards)",
+        records_to_revert.len(),
+        prompt_index
+    );
+
+    // Revert each record's commit_before..commit_after in reverse order
+    let mut total_reverted = 0;
+    let mut revert_failed = false;
+    let mut failure_message = String::new();
+    let mut interactive_ranges = Vec::new();
+
+    for (idx, record) in &records_to_revert {
+        // Already reverted on its own, reverting again would re-apply nothing useful
+        if record.reverted_at.is_some() {
+            log::debug!(
+                "[Precise Revert] Skipping prompt #{} - already reverted",
+                idx
+            );
+            continue;
+        }
+
+        // Skip if no commit_after (AI didn't make any changes)
+        let commit_after = match &record.commit_after {
+            Some(c) if c != &record.commit_before => c.clone(),
+            _ => {
+                log::debug!(
+                    "[Precise Revert] Skipping prompt #{} - no code changes",
+                    idx
+                );
+                continue;
+            }
+        };
+
+        let has_changes = match simple_git::git_has_changes_between_commits(
+            project_path,
+            &record.commit_before,
+            &commit_after,
+        ) {
+            Ok(value) => value,
+            Err(e) => {
+                log::warn!(
+                    "[Precise Revert] Failed to check changes for prompt #{}: {}",
+                    idx,
+                    e
+                );
+                revert_failed = true;
+                failure_message = e;
+                break;
+            }
+        };
+
+        if !has_changes {
+            log::debug!("[Precise Revert] Skipping prompt #{} - empty commit", idx);
+            continue;
+        }
+
+        if on_conflict == simple_git::OnConflict::Interactive {
+            interactive_ranges.push(simple_git::RevertRange {
+                commit_before: record.commit_before.clone(),
+                commit_after,
+                message: format!("[Revert] 撤回提示词 #{} 的代码更改", idx),
+            });
+            continue;
+        }
+
+        log::info!(
+            "[Precise Revert] Reverting prompt #{}: {}..{}",
+            idx,
+            &record.commit_before[..8.min(record.commit_before.len())],
+            &commit_after[..8.min(commit_after.len())]
+        );
+
+        let revert_result = simple_git::git_revert_range_with_retry(
+            project_path,
+            &record.commit_before,
+            &commit_after,
+            &format!("[Revert] 撤回提示词 #{} 的代码更改", idx),
+            3, // Max 3 retries for Git lock conflicts
+            allow_identity_fallback,
+        );
+
+        match revert_result {
+            Ok(result) if result.success => {
+                total_reverted += result.commits_reverted;
+                log::info!(
+                    "[Precise Revert] Successfully reverted prompt #{} ({} commits)",
+                    idx,
+                    result.commits_reverted
+                );
+            }
+            Ok(result) => {
+                log::warn!(
+                    "[Precise Revert] Revert conflict for prompt #{}: {}",
+                    idx,
+                    result.message
+                );
+                revert_failed = true;
+                failure_message = result.message;
+                break;
+            }
+            Err(e) => {
+                log::warn!("[Precise Revert] Revert failed for prompt #{}: {}", idx, e);
+                revert_failed = true;
+                failure_message = e;
+                break;
+            }
+        }
+    }
+
+    // Interactive mode reverts the collected ranges together and stops on the first conflict
+    if !revert_failed && !interactive_ranges.is_empty() {
+        match simple_git::git_revert_ranges_interactive(
+            project_path,
+            original_head,
+            interactive_ranges,
+            allow_identity_fallback,
+        ) {
+            Ok(result) if result.has_conflicts => {
+                log::warn!("[Precise Revert] Revert stopped on conflicts, waiting for resolution");
+                return Err(format!(
+                    "{}: {}",
+                    simple_git::REVERT_CONFLICT_ERROR_PREFIX,
+                    result.message
+                ));
+            }
+            Ok(result) => total_reverted = result.commits_reverted,
+            Err(e) => {
+                log::warn!("[Precise Revert] Interactive revert failed: {}", e);
+                revert_failed = true;
+                failure_message = e;
+            }
+        }
+    }
+
+    // If revert failed, rollback to original HEAD (atomic operation)
+    if revert_failed {
+        log::warn!(
+            "[Precise Revert] Rolling back to original HEAD {} due to failure",
+            &original_head[..8.min(original_head.len())]
+        );
+        simple_git::git_reset_hard(project_path, original_head)
+            .map_err(|e| format!("Failed to rollback: {}", e))?;
+
+        return Err(t_with(
+            MessageKey::RewindRevertFailedRolledBack,
+            &[("reason", &failure_message)],
+        ));
+    }
+
+    Ok((total_reverted, records_to_revert.len()))
+}
+
+/// Revert only the code changes of one prompt (`commit_before..commit_after`), keeping later commits
+///
+/// Uncommitted changes are stashed first. A revert that conflicts with later changes is aborted
+/// and the repository is reset to the HEAD it had before. Returns the number of reverted commits.
+pub(crate) fn revert_single_prompt_range(
+    project_path: &str,
+    prompt_index: usize,
+    commit_before: &str,
+    commit_after: Option<&str>,
+    message: &str,
+    allow_identity_fallback: bool,
+) -> Result<usize, String> {
+    let no_changes = || {
+        t_with(
+            MessageKey::RewindSingleNoChanges,
+            &[("index", &prompt_index)],
+        )
+    };
+    let commit_after = match commit_after {
+        Some(c) if c != commit_before => c,
+        _ => return Err(no_changes()),
+    };
+    if !simple_git::git_has_changes_between_commits(project_path, commit_before, commit_after)? {
+        return Err(no_changes());
+    }
+
+    simple_git::git_stash_save(
+        project_path,
+        &format!("Auto-stash before reverting prompt #{} alone", prompt_index),
+        allow_identity_fallback,
+    )
+    .map_err(|e| format!("Failed to stash changes: {}", e))?;
+
+    let original_head = simple_git::git_current_commit(project_path)
+        .map_err(|e| format!("Failed to get current commit: {}", e))?;
+
+    log::info!(
+        "[Single Revert] Reverting prompt #{}: {}..{} (original HEAD: {})",
+        prompt_index,
+        &commit_before[..8.min(commit_before.len())],
+        &commit_after[..8.min(commit_after.len())],
+        &original_head[..8.min(original_head.len())]
+    );
+
+    let failure = match simple_git::git_revert_range_with_retry(
+        project_path,
+        commit_before,
+        commit_after,
+        message,
+        3, // Max 3 retries for Git lock conflicts
+        allow_identity_fallback,
+    ) {
+        Ok(result) if result.success => return Ok(result.commits_reverted),
+        Ok(result) if result.has_conflicts => t_with(
+            MessageKey::RewindSingleConflict,
+            &[("index", &prompt_index), ("reason", &result.message)],
+        ),
+        Ok(result) => t_with(
+            MessageKey::RewindRevertFailedRolledBack,
+            &[("reason", &result.message)],
+        ),
+        Err(e) => t_with(MessageKey::RewindRevertFailedRolledBack, &[("reason", &e)]),
+    };
+
+    log::warn!(
+        "[Single Revert] Rolling back to original HEAD after failed revert of prompt #{}",
+        prompt_index
+    );
+    simple_git::git_reset_hard(project_path, &original_head)
+        .map_err(|e| format!("Failed to rollback: {}", e))?;
+    Err(failure)
+}
+
+/// Revert to a specific prompt with support for different rewind modes
+#[tauri::command]
+pub async fn revert_to_prompt(
+    app: AppHandle,
+    session_id: String,
+    project_id: String,
+    project_path: String,
+    prompt_index: usize,
     mode: RewindMode,
+    on_conflict: Option<simple_git::OnConflict>,
 ) -> Result<String, String> {
+    let session_id = resolve_session_alias("claude", &session_id);
     log::info!(
         "Reverting to prompt #{} in session: {} with mode: {:?}",
         prompt_index,
@@ -683,17 +1423,21 @@ pub async fn revert_to_prompt(
     match mode {
         RewindMode::CodeOnly | RewindMode::Both => {
             if git_operations_disabled {
-                return Err(format!(
-                    "无法回滚代码：Git 操作已在配置中禁用。只能撤回对话历史，无法回滚代码变更。"
-                ));
+                return Err(t(MessageKey::RewindCodeBlockedGitDisabled));
             }
             if git_record.is_none() {
-                return Err(format!(
-                    "无法回滚代码：提示词 #{} 没有关联的 Git 记录（可能来自 CLI 终端）",
-                    prompt_index
+                return Err(t_with(
+                    MessageKey::RewindCodeBlockedNoRecord,
+                    &[("index", &prompt_index)],
                 ));
             }
         }
+        RewindMode::Selective { target_index } if target_index != prompt_index => {
+            return Err(format!(
+                "Selective rewind target #{} does not match prompt #{}",
+                target_index, prompt_index
+            ));
+        }
         _ => {}
     }
 
@@ -721,6 +1465,16 @@ pub async fn revert_to_prompt(
             );
         }
 
+        RewindMode::Selective { .. } => {
+            return remove_prompts_from_session(
+                &session_id,
+                &project_id,
+                prompt_index,
+                prompt_index,
+            )
+            .map(|texts| texts.concat());
+        }
+
         RewindMode::CodeOnly => {
             log::info!("Reverting code only (keeping messages) - revert to state before prompt #{}", prompt_index);
 
@@ -728,6 +1482,7 @@ pub async fn revert_to_prompt(
             simple_git::git_stash_save(
                 &project_path,
                 &format!("Auto-stash before code revert to prompt #{}", prompt_index),
+                execution_config.git_identity_fallback,
             )
             .map_err(|e| format!("Failed to stash changes: {}", e))?;
 
@@ -740,126 +1495,22 @@ pub async fn revert_to_prompt(
                 &original_head[..8.min(original_head.len())]
             );
 
-            // 3. Load ALL git records for this session
-            let all_git_records = load_git_records(&session_id, &project_id)
-                .map_err(|e| format!("Failed to load git records: {}", e))?;
-
-            // 4. Filter records for prompt_index and onwards, then sort by index descending
-            let mut records_to_revert: Vec<(usize, GitRecord)> = all_git_records
-                .into_iter()
-                .filter(|(idx, _)| *idx >= prompt_index)
-                .collect();
-
-            // Sort by index descending (newest first) - revert from newest to oldest
-            records_to_revert.sort_by(|a, b| b.0.cmp(&a.0));
-
-            log::info!(
-                "[Precise Revert] Found {} records to revert (prompts {} and onwards)",
-                records_to_revert.len(),
-                prompt_index
-            );
-
-            // 5. Revert each record's commit_before..commit_after in reverse order
-            let mut total_reverted = 0;
-            let mut revert_failed = false;
-            let mut failure_message = String::new();
-
-            for (idx, record) in &records_to_revert {
-                // Skip if no commit_after (AI didn't make any changes)
-                let commit_after = match &record.commit_after {
-                    Some(c) if c != &record.commit_before => c.clone(),
-                    _ => {
-                        log::debug!("[Precise Revert] Skipping prompt #{} - no code changes", idx);
-                        continue;
-                    }
-                };
-
-                let has_changes = match simple_git::git_has_changes_between_commits(
-                    &project_path,
-                    &record.commit_before,
-                    &commit_after,
-                ) {
-                    Ok(value) => value,
-                    Err(e) => {
-                        log::warn!(
-                            "[Precise Revert] Failed to check changes for prompt #{}: {}",
-                            idx,
-                            e
-                        );
-                        revert_failed = true;
-                        failure_message = e;
-                        break;
-                    }
-                };
-
-                if !has_changes {
-                    log::debug!("[Precise Revert] Skipping prompt #{} - empty commit", idx);
-                    continue;
-                }
-
-                log::info!(
-                    "[Precise Revert] Reverting prompt #{}: {}..{}",
-                    idx,
-                    &record.commit_before[..8.min(record.commit_before.len())],
-                    &commit_after[..8.min(commit_after.len())]
-                );
-
-                let revert_result = simple_git::git_revert_range_with_retry(
-                    &project_path,
-                    &record.commit_before,
-                    &commit_after,
-                    &format!("[Revert] 撤回提示词 #{} 的代码更改", idx),
-                    3, // Max 3 retries for Git lock conflicts
-                );
-
-                match revert_result {
-                    Ok(result) if result.success => {
-                        total_reverted += result.commits_reverted;
-                        log::info!(
-                            "[Precise Revert] Successfully reverted prompt #{} ({} commits)",
-                            idx,
-                            result.commits_reverted
-                        );
-                    }
-                    Ok(result) => {
-                        log::warn!(
-                            "[Precise Revert] Revert conflict for prompt #{}: {}",
-                            idx,
-                            result.message
-                        );
-                        revert_failed = true;
-                        failure_message = result.message;
-                        break;
-                    }
-                    Err(e) => {
-                        log::warn!("[Precise Revert] Revert failed for prompt #{}: {}", idx, e);
-                        revert_failed = true;
-                        failure_message = e;
-                        break;
-                    }
-                }
-            }
-
-            // 6. If revert failed, rollback to original HEAD (atomic operation)
-            if revert_failed {
-                log::warn!(
-                    "[Precise Revert] Rolling back to original HEAD {} due to failure",
-                    &original_head[..8.min(original_head.len())]
-                );
-                simple_git::git_reset_hard(&project_path, &original_head)
-                    .map_err(|e| format!("Failed to rollback: {}", e))?;
-
-                return Err(format!(
-                    "撤回失败，已回滚到操作前状态。原因: {}",
-                    failure_message
-                ));
-            }
+            // 3. Revert the code changes of prompt #N and all later prompts
+            let (total_reverted, records_reverted) = revert_prompt_records(
+                &session_id,
+                &project_id,
+                &project_path,
+                prompt_index,
+                &original_head,
+                on_conflict.unwrap_or_default(),
+                execution_config.git_identity_fallback,
+            )?;
 
             log::info!(
                 "Successfully reverted code to state before prompt #{} (reverted {} commits from {} prompts)",
                 prompt_index,
                 total_reverted,
-                records_to_revert.len()
+                records_reverted
             );
         }
 
@@ -870,6 +1521,7 @@ pub async fn revert_to_prompt(
             simple_git::git_stash_save(
                 &project_path,
                 &format!("Auto-stash before full revert to prompt #{}", prompt_index),
+                execution_config.git_identity_fallback,
             )
             .map_err(|e| format!("Failed to stash changes: {}", e))?;
 
@@ -882,129 +1534,25 @@ pub async fn revert_to_prompt(
                 &original_head[..8.min(original_head.len())]
             );
 
-            // 3. Load ALL git records for this session
-            let all_git_records = load_git_records(&session_id, &project_id)
-                .map_err(|e| format!("Failed to load git records: {}", e))?;
-
-            // 4. Filter records for prompt_index and onwards, then sort by index descending
-            let mut records_to_revert: Vec<(usize, GitRecord)> = all_git_records
-                .into_iter()
-                .filter(|(idx, _)| *idx >= prompt_index)
-                .collect();
-
-            // Sort by index descending (newest first) - revert from newest to oldest
-            records_to_revert.sort_by(|a, b| b.0.cmp(&a.0));
-
-            log::info!(
-                "[Precise Revert] Found {} records to revert (prompts {} and onwards)",
-                records_to_revert.len(),
-                prompt_index
-            );
-
-            // 5. Revert each record's commit_before..commit_after in reverse order
-            let mut total_reverted = 0;
-            let mut revert_failed = false;
-            let mut failure_message = String::new();
-
-            for (idx, record) in &records_to_revert {
-                // Skip if no commit_after (AI didn't make any changes)
-                let commit_after = match &record.commit_after {
-                    Some(c) if c != &record.commit_before => c.clone(),
-                    _ => {
-                        log::debug!("[Precise Revert] Skipping prompt #{} - no code changes", idx);
-                        continue;
-                    }
-                };
-
-                let has_changes = match simple_git::git_has_changes_between_commits(
-                    &project_path,
-                    &record.commit_before,
-                    &commit_after,
-                ) {
-                    Ok(value) => value,
-                    Err(e) => {
-                        log::warn!(
-                            "[Precise Revert] Failed to check changes for prompt #{}: {}",
-                            idx,
-                            e
-                        );
-                        revert_failed = true;
-                        failure_message = e;
-                        break;
-                    }
-                };
-
-                if !has_changes {
-                    log::debug!("[Precise Revert] Skipping prompt #{} - empty commit", idx);
-                    continue;
-                }
-
-                log::info!(
-                    "[Precise Revert] Reverting prompt #{}: {}..{}",
-                    idx,
-                    &record.commit_before[..8.min(record.commit_before.len())],
-                    &commit_after[..8.min(commit_after.len())]
-                );
-
-                let revert_result = simple_git::git_revert_range_with_retry(
-                    &project_path,
-                    &record.commit_before,
-                    &commit_after,
-                    &format!("[Revert] 撤回提示词 #{} 的代码更改", idx),
-                    3, // Max 3 retries for Git lock conflicts
-                );
-
-                match revert_result {
-                    Ok(result) if result.success => {
-                        total_reverted += result.commits_reverted;
-                        log::info!(
-                            "[Precise Revert] Successfully reverted prompt #{} ({} commits)",
-                            idx,
-                            result.commits_reverted
-                        );
-                    }
-                    Ok(result) => {
-                        log::warn!(
-                            "[Precise Revert] Revert conflict for prompt #{}: {}",
-                            idx,
-                            result.message
-                        );
-                        revert_failed = true;
-                        failure_message = result.message;
-                        break;
-                    }
-                    Err(e) => {
-                        log::warn!("[Precise Revert] Revert failed for prompt #{}: {}", idx, e);
-                        revert_failed = true;
-                        failure_message = e;
-                        break;
-                    }
-                }
-            }
-
-            // 6. If revert failed, rollback to original HEAD (atomic operation)
-            if revert_failed {
-                log::warn!(
-                    "[Precise Revert] Rolling back to original HEAD {} due to failure",
-                    &original_head[..8.min(original_head.len())]
-                );
-                simple_git::git_reset_hard(&project_path, &original_head)
-                    .map_err(|e| format!("Failed to rollback: {}", e))?;
-
-                return Err(format!(
-                    "撤回失败，已回滚到操作前状态。原因: {}",
-                    failure_message
-                ));
-            }
+            // 3. Revert the code changes of prompt #N and all later prompts
+            let (total_reverted, records_reverted) = revert_prompt_records(
+                &session_id,
+                &project_id,
+                &project_path,
+                prompt_index,
+                &original_head,
+                on_conflict.unwrap_or_default(),
+                execution_config.git_identity_fallback,
+            )?;
 
             log::info!(
                 "Successfully reverted code to state before prompt #{} (reverted {} commits from {} prompts)",
                 prompt_index,
                 total_reverted,
-                records_to_revert.len()
+                records_reverted
             );
 
-            // 7. Truncate session messages (delete prompt #N and all after)
+            // 4. Truncate session messages (delete prompt #N and all after)
             // 🔧 ATOMIC PROTECTION: If session truncation fails, rollback Git changes
             if let Err(e) = truncate_session_to_prompt(&session_id, &project_id, prompt_index) {
                 log::error!(
@@ -1015,23 +1563,19 @@ pub async fn revert_to_prompt(
                 // Attempt to rollback Git changes
                 if let Err(rollback_err) = simple_git::git_reset_hard(&project_path, &original_head) {
                     log::error!("[CRITICAL] Git rollback failed: {}", rollback_err);
-                    return Err(format!(
-                        "会话文件截断失败，且 Git 回滚也失败，仓库可能处于不一致状态。\n\
-                         会话截断错误: {}\n\
-                         Git 回滚错误: {}\n\
-                         请手动检查仓库状态并运行 'git status'。",
-                        e, rollback_err
+                    return Err(t_with(
+                        MessageKey::RewindTruncateFailedRollbackFailed,
+                        &[("reason", &e), ("rollbackError", &rollback_err)],
                     ));
                 }
 
-                return Err(format!(
-                    "会话文件截断失败，已原子性回滚所有 Git 更改到操作前状态。\n\
-                     原因: {}",
-                    e
+                return Err(t_with(
+                    MessageKey::RewindTruncateFailedRolledBack,
+                    &[("reason", &e)],
                 ));
             }
 
-            // 8. Truncate git records
+            // 5. Truncate git records
             // 🔧 ATOMIC PROTECTION: If git records truncation fails, rollback Git changes
             // Note: Session file is already truncated at this point, cannot easily rollback
             if !git_operations_disabled {
@@ -1044,20 +1588,15 @@ pub async fn revert_to_prompt(
                     // Attempt to rollback Git changes
                     if let Err(rollback_err) = simple_git::git_reset_hard(&project_path, &original_head) {
                         log::error!("[CRITICAL] Git rollback failed: {}", rollback_err);
-                        return Err(format!(
-                            "Git 记录截断失败，且 Git 回滚也失败。\n\
-                             记录截断错误: {}\n\
-                             Git 回滚错误: {}\n\
-                             注意：会话文件已截断但无法回滚。",
-                            e, rollback_err
+                        return Err(t_with(
+                            MessageKey::RewindRecordsTruncateFailedRollbackFailed,
+                            &[("reason", &e), ("rollbackError", &rollback_err)],
                         ));
                     }
 
-                    return Err(format!(
-                        "Git 记录截断失败，已回滚 Git 更改到操作前状态。\n\
-                         注意：会话文件已截断但无法回滚，可能需要手动恢复。\n\
-                         原因: {}",
-                        e
+                    return Err(t_with(
+                        MessageKey::RewindRecordsTruncateFailedRolledBack,
+                        &[("reason", &e)],
                     ));
                 }
             } else {
@@ -1071,18 +1610,181 @@ pub async fn revert_to_prompt(
         }
     }
 
+    invalidate_session(&app, "claude", Some(&session_id), Some(&project_path));
+
     // Return the prompt text for restoring to input
     Ok(prompt.text.clone())
 }
 
+/// Remove prompts `start_index..=end_index` from the conversation while keeping later prompts
+///
+/// Only the conversation is edited; code changes made by the removed prompts stay in place.
+#[tauri::command]
+pub async fn remove_prompt_range(
+    app: AppHandle,
+    session_id: String,
+    project_id: String,
+    start_index: usize,
+    end_index: usize,
+) -> Result<(), String> {
+    let session_id = resolve_session_alias("claude", &session_id);
+    remove_prompts_from_session(&session_id, &project_id, start_index, end_index)?;
+    invalidate_session(&app, "claude", Some(&session_id), None);
+    Ok(())
+}
+
+/// Delete a single prompt (and the replies up to the next prompt) from the middle of a session
+///
+/// Later git records are re-indexed; no git revert is attempted. Returns the text of the
+/// deleted prompt so it can be restored to the input field.
+#[tauri::command]
+pub async fn revert_single_prompt(
+    app: AppHandle,
+    session_id: String,
+    project_id: String,
+    target_index: usize,
+) -> Result<String, String> {
+    let session_id = resolve_session_alias("claude", &session_id);
+    let texts = remove_prompts_from_session(&session_id, &project_id, target_index, target_index)?;
+    invalidate_session(&app, "claude", Some(&session_id), None);
+    Ok(texts.concat())
+}
+
+/// Revert only the code changes of prompt #`prompt_index`, keeping later prompts and the conversation
+///
+/// Refuses when the changes conflict with later prompts (the repository is left as it was).
+/// The git record is kept and marked as reverted. Returns the number of reverted commits.
+#[tauri::command]
+pub async fn revert_single_prompt_code(
+    session_id: String,
+    project_id: String,
+    project_path: String,
+    prompt_index: usize,
+) -> Result<usize, String> {
+    let session_id = resolve_session_alias("claude", &session_id);
+    log::info!(
+        "Reverting code of prompt #{} alone in session: {}",
+        prompt_index,
+        session_id
+    );
+
+    let execution_config =
+        load_execution_config().map_err(|e| format!("Failed to load execution config: {}", e))?;
+    if execution_config.disable_rewind_git_operations {
+        return Err(t(MessageKey::RewindCodeBlockedGitDisabled));
+    }
+
+    let mut records = load_git_records(&session_id, &project_id)
+        .map_err(|e| format!("Failed to load git records: {}", e))?;
+    let reverted = revert_single_prompt_record(
+        &mut records,
+        &project_path,
+        prompt_index,
+        execution_config.git_identity_fallback,
+    )?;
+    save_git_records(&session_id, &project_id, &records)
+        .map_err(|e| format!("Failed to save git records: {}", e))?;
+
+    log::info!(
+        "Reverted code of prompt #{} alone ({} commits)",
+        prompt_index,
+        reverted
+    );
+    Ok(reverted)
+}
+
+/// Revert the code of the prompt's git record and mark the record as reverted
+///
+/// A record that was already reverted is refused without touching the repository.
+fn revert_single_prompt_record(
+    records: &mut HashMap<usize, GitRecord>,
+    project_path: &str,
+    prompt_index: usize,
+    allow_identity_fallback: bool,
+) -> Result<usize, String> {
+    let record = records.get_mut(&prompt_index).ok_or_else(|| {
+        t_with(
+            MessageKey::RewindCodeBlockedNoRecord,
+            &[("index", &prompt_index)],
+        )
+    })?;
+    if record.reverted_at.is_some() {
+        return Err(t_with(
+            MessageKey::RewindSingleAlreadyReverted,
+            &[("index", &prompt_index)],
+        ));
+    }
+
+    let reverted = revert_single_prompt_range(
+        project_path,
+        prompt_index,
+        &record.commit_before,
+        record.commit_after.as_deref(),
+        &format!("[Revert] 单独撤回提示词 #{} 的代码更改", prompt_index),
+        allow_identity_fallback,
+    )?;
+    record.reverted_at = Some(Utc::now().timestamp());
+    Ok(reverted)
+}
+
+/// Excise prompts `start_index..=end_index` from the session file and re-index git records
+///
+/// Returns the texts of the removed prompts.
+fn remove_prompts_from_session(
+    session_id: &str,
+    project_id: &str,
+    start_index: usize,
+    end_index: usize,
+) -> Result<Vec<String>, String> {
+    log::info!(
+        "Removing prompts #{}-#{} from session: {}",
+        start_index,
+        end_index,
+        session_id
+    );
+
+    let session_path = resolve_session_file("claude", session_id, project_id)?;
+    let removed_texts = excise_prompts_from_file(&session_path, start_index, end_index)?;
+
+    remove_git_records_range(session_id, project_id, start_index, end_index)
+        .map_err(|e| format!("Failed to re-index git records: {}", e))?;
+
+    Ok(removed_texts)
+}
+
+/// Excise prompts `start_index..=end_index` from a session file under its session lock
+///
+/// Returns the texts of the removed prompts.
+fn excise_prompts_from_file(
+    session_path: &Path,
+    start_index: usize,
+    end_index: usize,
+) -> Result<Vec<String>, String> {
+    let _lock = SessionLock::acquire(session_path, "claude remove prompts")?;
+
+    let snapshot = FileSnapshot::capture(session_path)?;
+    let content = read_to_string_lossy(session_path)
+        .map(normalize_line_endings)
+        .map_err(|e| format!("Failed to read session file: {}", e))?;
+    let new_content = excise_prompt_range(&content, start_index, end_index)
+        .map_err(|e| format!("Failed to remove prompts: {}", e))?;
+    let removed_texts = prompt_texts_in_range(&content, start_index, end_index);
+    write_session_file(session_path, &snapshot, new_content)?;
+
+    Ok(removed_texts)
+}
+
 /// Get all prompts for a session (for debugging)
 #[tauri::command]
 pub async fn get_prompt_list(
     session_id: String,
     project_id: String,
 ) -> Result<Vec<PromptRecord>, String> {
-    extract_prompts_from_jsonl(&session_id, &project_id)
-        .map_err(|e| format!("Failed to extract prompts from JSONL: {}", e))
+    let session_id = resolve_session_alias("claude", &session_id);
+    let mut prompts = extract_prompts_from_jsonl(&session_id, &project_id)
+        .map_err(|e| format!("Failed to extract prompts from JSONL: {}", e))?;
+    attach_restore_records("claude", &session_id, &mut prompts);
+    Ok(prompts)
 }
 
 /// Check rewind capabilities for a specific prompt
@@ -1093,6 +1795,7 @@ pub async fn check_rewind_capabilities(
     project_id: String,
     prompt_index: usize,
 ) -> Result<RewindCapabilities, String> {
+    let session_id = resolve_session_alias("claude", &session_id);
     log::info!(
         "Checking rewind capabilities for prompt #{} in session: {}",
         prompt_index,
@@ -1129,9 +1832,7 @@ pub async fn check_rewind_capabilities(
             conversation: true,
             code: false,
             both: false,
-            warning: Some(
-                "Git 操作已在配置中禁用。只能撤回对话历史，无法回滚代码变更。".to_string(),
-            ),
+            warning: Some(t(MessageKey::RewindGitDisabled)),
             source: prompt.source.clone(),
         });
     }
@@ -1157,7 +1858,7 @@ pub async fn check_rewind_capabilities(
                 code: has_valid_commit,
                 both: has_valid_commit,
                 warning: if !has_valid_commit {
-                    Some("此提示词没有关联的 Git 记录，只能删除消息，无法回滚代码".to_string())
+                    Some(t(MessageKey::RewindNoValidCommit))
                 } else {
                     None
                 },
@@ -1173,9 +1874,7 @@ pub async fn check_rewind_capabilities(
                 conversation: true,
                 code: false,
                 both: false,
-                warning: Some(
-                    "此提示词来自项目界面，但没有找到 Git 记录，只能删除消息".to_string(),
-                ),
+                warning: Some(t(MessageKey::RewindProjectPromptWithoutRecord)),
                 source: "project".to_string(),
             })
         }
@@ -1189,7 +1888,7 @@ pub async fn check_rewind_capabilities(
             conversation: true,
             code: false,
             both: false,
-            warning: Some("此提示词来自 CLI 终端，只能删除消息，无法回滚代码".to_string()),
+            warning: Some(t(MessageKey::RewindCliPrompt)),
             source: "cli".to_string(),
         })
     }
@@ -1198,139 +1897,169 @@ pub async fn check_rewind_capabilities(
 /// Extract prompts from JSONL session file
 /// This function reads the .jsonl file and extracts all user prompts
 /// This is the single source of truth for all prompts (both CLI and project interface)
-fn extract_prompts_from_jsonl(session_id: &str, project_id: &str) -> Result<Vec<PromptRecord>> {
-    let claude_dir = get_claude_dir().context("Failed to get claude dir")?;
-    let session_path = claude_dir
-        .join("projects")
-        .join(project_id)
-        .join(format!("{}.jsonl", session_id));
-
-    if !session_path.exists() {
+/// Sources reclassified via `reclassify_prompt_sources` override the dequeue-marker detection
+pub(crate) fn extract_prompts_from_jsonl(
+    session_id: &str,
+    project_id: &str,
+) -> Result<Vec<PromptRecord>> {
+    let Some(session_path) =
+        find_session_path("claude", session_id, project_id).map_err(anyhow::Error::msg)?
+    else {
         return Ok(Vec::new());
+    };
+
+    let mut prompts = read_session_prompts(&session_path)?;
+
+    let reclassified = load_reclassified_sources(session_id, project_id)?;
+    for prompt in prompts.iter_mut() {
+        if reclassified.contains(&prompt.index) {
+            prompt.source = "project".to_string();
+        }
     }
 
-    let content = fs::read_to_string(&session_path).context("Failed to read session file")?;
+    Ok(prompts)
+}
 
+/// Read the prompts of a session file; the source comes from the dequeue markers only
+fn read_session_prompts(session_path: &Path) -> Result<Vec<PromptRecord>> {
     let mut prompts = Vec::new();
     let mut prompt_index = 0;
     let mut pending_dequeue = false;
 
-    for (line_idx, line) in content.lines().enumerate() {
-        if let Ok(msg) = serde_json::from_str::<serde_json::Value>(line) {
-            let msg_type = msg.get("type").and_then(|t| t.as_str());
-
-            // Check for dequeue operation
-            if msg_type == Some("queue-operation") {
-                let operation = msg.get("operation").and_then(|o| o.as_str());
-                if operation == Some("dequeue") {
-                    pending_dequeue = true;
-                    continue;
-                }
-            }
-
-            // Skip non-user message types
-            if msg_type != Some("user") {
-                continue;
+    // Stream line by line so huge sessions never live in memory as a whole;
+    // corrupted lines are skipped without shifting the prompt numbering
+    let corrupted_lines = for_each_json_line(session_path, |line_idx, msg: serde_json::Value| {
+        let msg_type = msg.get("type").and_then(|t| t.as_str());
+
+        // Check for dequeue operation
+        if msg_type == Some("queue-operation") {
+            let operation = msg.get("operation").and_then(|o| o.as_str());
+            if operation == Some("dequeue") {
+                pending_dequeue = true;
+                return ControlFlow::Continue(());
             }
+        }
 
-            // Skip sidechain messages (agent messages)
-            let is_sidechain = msg
-                .get("isSidechain")
-                .and_then(|v| v.as_bool())
-                .unwrap_or(false);
-
-            if is_sidechain {
-                continue;
-            }
+        let Some(extracted_text) = user_prompt_text(&msg) else {
+            return ControlFlow::Continue(());
+        };
 
-            // Skip subagent messages (has parent_tool_use_id)
-            let has_parent_tool_use_id = msg.get("parent_tool_use_id").is_some()
-                && !msg.get("parent_tool_use_id").unwrap().is_null();
+        // Extract timestamp (RFC3339 with any offset, normalized to UTC)
+        let sent_at = msg.get("timestamp").and_then(parse_timestamp_value);
 
-            if has_parent_tool_use_id {
-                continue;
-            }
-
-            // Extract text content
-            let content_value = msg.get("message").and_then(|m| m.get("content"));
-            let mut extracted_text = String::new();
-            let mut has_text_content = false;
-            let mut has_tool_result = false;
-
-            if let Some(content) = content_value {
-                if let Some(text) = content.as_str() {
-                    extracted_text = text.to_string();
-                    has_text_content = !text.trim().is_empty();
-                } else if let Some(arr) = content.as_array() {
-                    for item in arr {
-                        if let Some(item_type) = item.get("type").and_then(|t| t.as_str()) {
-                            if item_type == "text" {
-                                if let Some(text) = item.get("text").and_then(|t| t.as_str()) {
-                                    extracted_text.push_str(text);
-                                    has_text_content = true;
-                                }
-                            } else if item_type == "tool_result" {
-                                has_tool_result = true;
-                            }
-                        }
-                    }
-                }
-            }
+        // Determine source
+        let source = if pending_dequeue {
+            "project".to_string()
+        } else {
+            "cli".to_string()
+        };
+
+        // Reset pending_dequeue
+        pending_dequeue = false;
+
+        // Create prompt record
+        let mut prompt = PromptRecord {
+            index: prompt_index,
+            text: extracted_text,
+            git_commit_before: "NONE".to_string(), // Will be filled later from git records
+            git_commit_after: None,
+            timestamp: 0,
+            timestamp_ms: 0,
+            source,
+            line_number: line_idx,
+            protected_restore: None,
+            reverted_at: None,
+        };
+        prompt.set_sent_at(sent_at);
+        prompts.push(prompt);
+
+        prompt_index += 1;
+        ControlFlow::Continue(())
+    })
+    .context("Failed to read session file")?;
+
+    if !corrupted_lines.is_empty() {
+        log::warn!(
+            "Skipped {} corrupted lines while extracting prompts from {}",
+            corrupted_lines.len(),
+            session_path.display()
+        );
+    }
 
-            // Skip tool-result-only messages
-            if has_tool_result && !has_text_content {
-                continue;
-            }
+    Ok(prompts)
+}
 
-            // Must have text content
-            if !has_text_content {
-                continue;
-            }
+/// Result of re-deriving prompt sources for a session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReclassifyResult {
+    /// Prompt indices upgraded from "cli" to "project" in this run
+    pub reclassified: Vec<usize>,
+    /// Total number of prompts now treated as "project"
+    pub project_count: usize,
+    /// Total number of prompts in the session
+    pub total: usize,
+}
 
-            // Skip Warmup and Skills messages
-            let is_warmup = extracted_text.contains("Warmup");
-            let is_skill_message = extracted_text.contains("<command-name>")
-                || extracted_text.contains("Launching skill:")
-                || extracted_text.contains("skill is running");
+/// Re-derive prompt sources for an existing session
+/// Sessions created before the queue-operation marker existed (or imported via conversion)
+/// report every prompt as "cli"; prompts that have a git record are upgraded to "project"
+/// and persisted in a sidecar so `check_rewind_capabilities` offers code revert
+#[tauri::command]
+pub async fn reclassify_prompt_sources(
+    session_id: String,
+    project_id: String,
+) -> Result<ReclassifyResult, String> {
+    let session_id = resolve_session_alias("claude", &session_id);
+    let prompts = extract_prompts_from_jsonl(&session_id, &project_id)
+        .map_err(|e| format!("Failed to extract prompts from JSONL: {}", e))?;
+    let git_records = load_git_records(&session_id, &project_id)
+        .map_err(|e| format!("Failed to load git records: {}", e))?;
+    let mut sidecar = load_reclassified_sources(&session_id, &project_id)
+        .map_err(|e| format!("Failed to load prompt sources: {}", e))?;
 
-            if is_warmup || is_skill_message {
-                continue;
-            }
+    let reclassified = reclassify_sources(&prompts, &git_records, &mut sidecar);
+    if !reclassified.is_empty() {
+        save_reclassified_sources(&session_id, &project_id, &sidecar)
+            .map_err(|e| format!("Failed to save prompt sources: {}", e))?;
+    }
 
-            // Extract timestamp
-            let timestamp = msg
-                .get("timestamp")
-                .and_then(|t| t.as_str())
-                .and_then(|s| chrono::DateTime::parse_from_rfc3339(s).ok())
-                .map(|dt| dt.timestamp())
-                .unwrap_or_else(|| Utc::now().timestamp());
-
-            // Determine source
-            let source = if pending_dequeue {
-                "project".to_string()
-            } else {
-                "cli".to_string()
-            };
+    let project_count = prompts
+        .iter()
+        .filter(|p| p.source == "project" || reclassified.contains(&p.index))
+        .count();
 
-            // Reset pending_dequeue
-            pending_dequeue = false;
-
-            // Create prompt record
-            prompts.push(PromptRecord {
-                index: prompt_index,
-                text: extracted_text,
-                git_commit_before: "NONE".to_string(), // Will be filled later from git records
-                git_commit_after: None,
-                timestamp,
-                source,
-                line_number: line_idx,
-            });
+    log::info!(
+        "[Reclassify] Session {}: upgraded {} prompts to project ({} / {} total)",
+        session_id,
+        reclassified.len(),
+        project_count,
+        prompts.len()
+    );
 
-            prompt_index += 1;
-        }
-    }
+    Ok(ReclassifyResult {
+        reclassified,
+        project_count,
+        total: prompts.len(),
+    })
+}
 
-    Ok(prompts)
+/// CLI-sourced prompts that have a git record were sent by the app: add them to the sidecar
+///
+/// Returns the indices that were upgraded to "project".
+fn reclassify_sources(
+    prompts: &[PromptRecord],
+    git_records: &HashMap<usize, GitRecord>,
+    sidecar: &mut BTreeSet<usize>,
+) -> Vec<usize> {
+    prompts
+        .iter()
+        .filter(|p| p.source != "project" && git_records.contains_key(&p.index))
+        .map(|p| {
+            sidecar.insert(p.index);
+            p.index
+        })
+        .collect()
 }
 
 /// Get unified prompt list with git records from .git-records.json
@@ -1340,6 +2069,7 @@ pub async fn get_unified_prompt_list(
     session_id: String,
     project_id: String,
 ) -> Result<Vec<PromptRecord>, String> {
+    let session_id = resolve_session_alias("claude", &session_id);
     log::info!("Getting unified prompt list for session: {}", session_id);
 
     // Get all prompts from .jsonl (single source of truth)
@@ -1362,6 +2092,8 @@ pub async fn get_unified_prompt_list(
             if let Some(record) = git_records.get(&prompt.index) {
                 prompt.git_commit_before = record.commit_before.clone();
                 prompt.git_commit_after = record.commit_after.clone();
+                prompt.reverted_at = record.reverted_at;
+                prompt.fill_missing_timestamp(record.timestamp);
                 log::debug!(
                     "[Unified List] Enriched prompt #{} with git commits",
                     prompt.index
@@ -1386,5 +2118,366 @@ pub async fn get_unified_prompt_list(
         cli_count
     );
 
+    attach_restore_records("claude", &session_id, &mut prompts);
     Ok(prompts)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+
+    fn user(uuid: &str, parent: Option<&str>, text: &str) -> Value {
+        json!({"type": "user", "uuid": uuid, "parentUuid": parent,
+               "message": {"role": "user", "content": text}})
+    }
+
+    fn assistant(uuid: &str, parent: &str, content: Value) -> Value {
+        json!({"type": "assistant", "uuid": uuid, "parentUuid": parent,
+               "message": {"role": "assistant", "content": content}})
+    }
+
+    fn tool_result(uuid: &str, parent: &str, tool_use_id: &str) -> Value {
+        json!({"type": "user", "uuid": uuid, "parentUuid": parent,
+               "message": {"role": "user", "content": [
+                   {"type": "tool_result", "tool_use_id": tool_use_id, "content": "ok"}]}})
+    }
+
+    fn to_jsonl(messages: &[Value]) -> String {
+        messages.iter().map(|m| m.to_string() + "\n").collect()
+    }
+
+    #[test]
+    fn removing_a_middle_prompt_relinks_later_messages() {
+        let content = to_jsonl(&[
+            user("u0", None, "first"),
+            assistant(
+                "a0",
+                "u0",
+                json!([{"type": "tool_use", "id": "t0", "name": "Read"}]),
+            ),
+            tool_result("r0", "a0", "t0"),
+            assistant("a0b", "r0", json!("done")),
+            user("u1", Some("a0b"), "wrong path"),
+            assistant(
+                "a1",
+                "u1",
+                json!([{"type": "tool_use", "id": "t1", "name": "Edit"}]),
+            ),
+            tool_result("r1", "a1", "t1"),
+            json!({"type": "summary", "summary": "kept", "leafUuid": "r1"}),
+            user("u2", Some("r1"), "separate idea"),
+            assistant("a2", "u2", json!("ok")),
+        ]);
+
+        let result = excise_prompt_range(&content, 1, 1).unwrap();
+        let messages: Vec<Value> = result
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        let uuids: Vec<&str> = messages.iter().filter_map(|m| m["uuid"].as_str()).collect();
+
+        assert_eq!(uuids, vec!["u0", "a0", "r0", "a0b", "u2", "a2"]);
+        assert!(messages.iter().any(|m| m["type"] == "summary"));
+        let u2 = messages.iter().find(|m| m["uuid"] == "u2").unwrap();
+        assert_eq!(u2["parentUuid"], "a0b");
+        assert_eq!(prompt_texts_in_range(&content, 1, 1), vec!["wrong path"]);
+
+        assert!(excise_prompt_range(&content, 2, 3).is_err());
+        assert!(excise_prompt_range(&content, 2, 1).is_err());
+    }
+
+    #[test]
+    fn truncation_writes_lf_for_crlf_session_files() {
+        // Session file resaved on Windows with CRLF line endings
+        let content = to_jsonl(&[
+            user("u0", None, "first"),
+            assistant("a0", "u0", json!("ok")),
+            user("u1", Some("a0"), "second"),
+            assistant("a1", "u1", json!("ok")),
+        ])
+        .replace('\n', "\r\n");
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        std::io::Write::write_all(&mut file, content.as_bytes()).unwrap();
+
+        let content = normalize_line_endings(read_to_string_lossy(file.path()).unwrap());
+        let truncated = truncate_at_prompt(&content, 1).unwrap();
+
+        assert!(!truncated.contains('\r'));
+        let messages: Vec<Value> = truncated
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        let uuids: Vec<&str> = messages.iter().filter_map(|m| m["uuid"].as_str()).collect();
+        assert_eq!(uuids, vec!["u0", "a0"]);
+        assert_eq!(truncate_at_prompt(&content, 0).unwrap(), "");
+        assert!(truncate_at_prompt(&content, 2).is_err());
+    }
+
+    #[test]
+    fn refuses_to_split_a_tool_call() {
+        let content = to_jsonl(&[
+            user("u0", None, "start"),
+            assistant(
+                "a0",
+                "u0",
+                json!([{"type": "tool_use", "id": "t0", "name": "Bash"}]),
+            ),
+            user("u1", Some("a0"), "interrupting"),
+            tool_result("r0", "u1", "t0"),
+        ]);
+
+        assert!(excise_prompt_range(&content, 0, 0).is_err());
+    }
+
+    #[test]
+    fn tool_results_never_count_as_prompts() {
+        let mixed = json!({"type": "user", "uuid": "m0", "parentUuid": "a1",
+            "message": {"role": "user", "content": [
+                {"type": "tool_result", "tool_use_id": "t1", "content": "ok"},
+                {"type": "text", "text": "also check the tests"}]}});
+        let blank_text = json!({"type": "user", "uuid": "b0", "parentUuid": "a2",
+            "message": {"role": "user", "content": [
+                {"type": "tool_result", "tool_use_id": "t2", "content": [
+                    {"type": "text", "text": "nested output"}]},
+                {"type": "text", "text": "  "}]}});
+        let subagent = json!({"type": "user", "uuid": "s0", "parent_tool_use_id": "t3",
+            "message": {"role": "user", "content": "subagent task"}});
+        let content = to_jsonl(&[
+            user("u0", None, "fix the bug"),
+            assistant(
+                "a0",
+                "u0",
+                json!([
+                    {"type": "tool_use", "id": "t0", "name": "Read"},
+                    {"type": "text", "text": "Reading the file"}
+                ]),
+            ),
+            tool_result("r0", "a0", "t0"),
+            assistant(
+                "a1",
+                "r0",
+                json!([
+                    {"type": "tool_use", "id": "t1", "name": "Edit"},
+                    {"type": "text", "text": "Editing"}
+                ]),
+            ),
+            mixed,
+            assistant(
+                "a2",
+                "m0",
+                json!([{"type": "tool_use", "id": "t2", "name": "Bash"}]),
+            ),
+            blank_text,
+            subagent,
+            assistant("a3", "b0", json!("done")),
+            user("u1", Some("a3"), "now write docs"),
+            assistant("a4", "u1", json!("ok")),
+        ]);
+
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        std::io::Write::write_all(&mut file, content.as_bytes()).unwrap();
+        let prompts = read_session_prompts(file.path()).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+
+        let texts: Vec<&str> = prompts.iter().map(|p| p.text.as_str()).collect();
+        assert_eq!(
+            texts,
+            vec!["fix the bug", "also check the tests", "now write docs"]
+        );
+        let prompt_lines: Vec<usize> = prompts.iter().map(|p| p.line_number).collect();
+        assert_eq!(prompt_lines, vec![0, 4, 9]);
+        assert_eq!(find_prompt_lines(&lines), prompt_lines);
+        assert_eq!(
+            prompts.iter().map(|p| p.index).collect::<Vec<_>>(),
+            vec![0, 1, 2]
+        );
+    }
+
+    #[test]
+    fn revert_single_prompt_removes_the_prompt_and_renumbers_later_records() {
+        let content = to_jsonl(&[
+            user("u0", None, "first"),
+            assistant("a0", "u0", json!("ok")),
+            user("u1", Some("a0"), "wrong path"),
+            assistant("a1", "u1", json!("ok")),
+            user("u2", Some("a1"), "third"),
+            assistant("a2", "u2", json!("ok")),
+        ]);
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        std::io::Write::write_all(&mut file, content.as_bytes()).unwrap();
+
+        let removed = excise_prompts_from_file(file.path(), 1, 1).unwrap();
+        assert_eq!(removed, vec!["wrong path"]);
+        let prompts = read_session_prompts(file.path()).unwrap();
+        let texts: Vec<&str> = prompts.iter().map(|p| p.text.as_str()).collect();
+        assert_eq!(texts, vec!["first", "third"]);
+        assert!(excise_prompts_from_file(file.path(), 2, 2).is_err());
+
+        let record = |commit: &str| GitRecord {
+            commit_before: commit.to_string(),
+            commit_after: None,
+            timestamp: 0,
+            trace_id: None,
+            reverted_at: None,
+        };
+        let records: HashMap<usize, GitRecord> =
+            [(0, record("c0")), (1, record("c1")), (2, record("c2"))]
+                .into_iter()
+                .collect();
+        let mut reindexed: Vec<(usize, String)> = reindex_git_records(records, 1, 1)
+            .into_iter()
+            .map(|(i, r)| (i, r.commit_before))
+            .collect();
+        reindexed.sort();
+        assert_eq!(
+            reindexed,
+            vec![(0, "c0".to_string()), (1, "c2".to_string())]
+        );
+    }
+
+    #[test]
+    fn git_record_indices_shift_past_removed_range() {
+        assert_eq!(shift_prompt_index(1, 2, 4), Some(1));
+        assert_eq!(shift_prompt_index(3, 2, 4), None);
+        assert_eq!(shift_prompt_index(5, 2, 4), Some(2));
+    }
+
+    fn git(dir: &Path, args: &[&str]) -> String {
+        let output = std::process::Command::new("git")
+            .args(["-c", "user.name=test", "-c", "user.email=test@local"])
+            .args(args)
+            .current_dir(dir)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "git {:?} failed", args);
+        String::from_utf8_lossy(&output.stdout).trim().to_string()
+    }
+
+    fn commit_files(dir: &Path, files: &[(&str, &str)], message: &str) -> String {
+        for (name, content) in files {
+            fs::write(dir.join(name), content).unwrap();
+        }
+        git(dir, &["add", "-A"]);
+        git(dir, &["commit", "-q", "-m", message]);
+        git(dir, &["rev-parse", "HEAD"])
+    }
+
+    #[test]
+    fn reverts_one_prompt_and_refuses_when_later_prompts_conflict() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path();
+        let project = path.to_str().unwrap();
+        git(path, &["init", "-q"]);
+        let base = commit_files(path, &[("a.txt", "a\n"), ("b.txt", "b\n")], "base");
+        let p0 = commit_files(path, &[("a.txt", "A0\n")], "prompt 0");
+        let p1 = commit_files(path, &[("b.txt", "B1\n")], "prompt 1");
+        let p2 = commit_files(path, &[("b.txt", "B2\n")], "prompt 2");
+
+        // Prompt #0 is undone while the later prompts' changes stay
+        let reverted =
+            revert_single_prompt_range(project, 0, &base, Some(&p0), "[Revert] #0", true);
+        assert_eq!(reverted, Ok(1));
+        assert_eq!(fs::read_to_string(path.join("a.txt")).unwrap(), "a\n");
+        assert_eq!(fs::read_to_string(path.join("b.txt")).unwrap(), "B2\n");
+        assert_eq!(git(path, &["rev-parse", "HEAD~1"]), p2);
+
+        // Prompt #2 rewrote prompt #1's line, so #1 cannot be reverted alone
+        let head = git(path, &["rev-parse", "HEAD"]);
+        let error = revert_single_prompt_range(project, 1, &p0, Some(&p1), "[Revert] #1", true)
+            .unwrap_err();
+        assert!(error.contains("#1"), "{}", error);
+        assert_eq!(git(path, &["rev-parse", "HEAD"]), head);
+        assert_eq!(git(path, &["status", "--porcelain"]), "");
+        assert_eq!(fs::read_to_string(path.join("b.txt")).unwrap(), "B2\n");
+
+        assert!(
+            revert_single_prompt_range(project, 2, &p2, Some(&p2), "[Revert] #2", true).is_err()
+        );
+        assert!(revert_single_prompt_range(project, 2, &p1, None, "[Revert] #2", true).is_err());
+    }
+
+    #[test]
+    fn reverted_records_are_skipped() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path();
+        let project = path.to_str().unwrap();
+        git(path, &["init", "-q"]);
+        let base = commit_files(path, &[("a.txt", "a\n")], "base");
+        let p0 = commit_files(path, &[("a.txt", "A0\n")], "prompt 0");
+        let mut records: HashMap<usize, GitRecord> = [(
+            0,
+            GitRecord {
+                commit_before: base,
+                commit_after: Some(p0),
+                timestamp: 0,
+                trace_id: None,
+                reverted_at: None,
+            },
+        )]
+        .into_iter()
+        .collect();
+
+        assert_eq!(
+            revert_single_prompt_record(&mut records, project, 0, true),
+            Ok(1)
+        );
+        assert!(records[&0].reverted_at.is_some());
+        let head = git(path, &["rev-parse", "HEAD"]);
+
+        // A second revert of the same prompt is refused and leaves the repository alone
+        let error = revert_single_prompt_record(&mut records, project, 0, true).unwrap_err();
+        assert_eq!(
+            error,
+            t_with(MessageKey::RewindSingleAlreadyReverted, &[("index", &0)])
+        );
+        assert_eq!(git(path, &["rev-parse", "HEAD"]), head);
+        assert!(revert_single_prompt_record(&mut records, project, 1, true).is_err());
+    }
+
+    #[test]
+    fn reclassifies_cli_prompts_that_have_git_records() {
+        let content = to_jsonl(&[
+            json!({ "type": "queue-operation", "operation": "dequeue" }),
+            user("u0", None, "from the app"),
+            assistant("a0", "u0", json!("ok")),
+            user("u1", Some("a0"), "legacy app prompt"),
+            assistant("a1", "u1", json!("ok")),
+            user("u2", Some("a1"), "typed in the CLI"),
+            assistant("a2", "u2", json!("ok")),
+        ]);
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        std::io::Write::write_all(&mut file, content.as_bytes()).unwrap();
+        let prompts = read_session_prompts(file.path()).unwrap();
+        let sources: Vec<&str> = prompts.iter().map(|p| p.source.as_str()).collect();
+        assert_eq!(sources, vec!["project", "cli", "cli"]);
+
+        let record = GitRecord {
+            commit_before: "c".to_string(),
+            commit_after: None,
+            timestamp: 0,
+            trace_id: None,
+            reverted_at: None,
+        };
+        let git_records: HashMap<usize, GitRecord> =
+            [(0, record.clone()), (1, record)].into_iter().collect();
+        let mut sidecar = BTreeSet::new();
+
+        // Only the CLI prompt with a git record is upgraded; project prompts stay as they are
+        assert_eq!(
+            reclassify_sources(&prompts, &git_records, &mut sidecar),
+            vec![1]
+        );
+        assert_eq!(sidecar, BTreeSet::from([1]));
+
+        // Running it again on the reclassified list changes nothing
+        let mut prompts = prompts;
+        for prompt in prompts.iter_mut() {
+            if sidecar.contains(&prompt.index) {
+                prompt.source = "project".to_string();
+            }
+        }
+        assert!(reclassify_sources(&prompts, &git_records, &mut sidecar).is_empty());
+        assert_eq!(prompts[2].source, "cli");
+    }
+}
@@ -0,0 +1,1193 @@
+//! 禁改区（AI 不允许改动的路径）
+//!
+//! migrations、法务文案、锁文件这类路径不允许模型改动，但仅靠提示很难保证，这里分两层保护：
+//! - 提示词注入：执行时把禁改清单作为约束附加（Claude 走 `--append-system-prompt`，
+//!   Codex / Gemini 附加到提示词末尾）
+//! - 事后校验：记录提示词发送时（`record_*_prompt_sent`）为命中禁改规则的文件建立任务快照，
+//!   任务完成时（`mark_prompt_completed` / `record_*_prompt_completed`）与快照比对，
+//!   发现修改、删除或新建时发送 `protected-paths-violation` 事件；
+//!   `revert_protected_changes` 从快照把这些文件恢复到任务前状态，恢复记录显示在撤回面板中
+//!
+//! 配置：
+//! - 项目级：<project>/.anycode/protected-paths.json（glob 列表，相对项目根目录）
+//! - 全局默认：~/.anycode/protected_paths.json（总开关、默认 glob、是否注入提示词）
+//!
+//! 规则不含 `/` 时匹配任意层级的文件或目录名（如 `Cargo.lock`、`*.lock`、`migrations`），
+//! 含 `/` 时从项目根目录匹配；命中目录即保护其下所有文件。
+//!
+//! 快照：~/.anycode/protected-snapshots/<engine>-<session>/prompt-<index>.json，
+//! 文件内容按 sha256 去重存放在同目录的 blobs/ 下。
+
+use glob::{MatchOptions, Pattern};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+use tauri::{AppHandle, Emitter};
+use walkdir::WalkDir;
+
+use super::codex::git_ops::{load_codex_git_records, save_codex_git_records};
+use super::gemini::git_ops::{load_gemini_git_records, save_gemini_git_records};
+use super::prompt_tracker::{self, load_execution_config, PromptRecord};
+use super::simple_git;
+use crate::utils::config_utils::{load_json_config, save_json_config, ConfigPathBuilder};
+
+/// 扫描项目时跳过的目录
+const WALK_SKIPPED_DIRS: &[&str] = &[".git", "node_modules"];
+
+/// 单次快照最多记录的文件数
+const MAX_PROTECTED_FILES: usize = 5000;
+
+/// 超过该大小的文件只记录哈希（能发现改动，但无法从快照恢复）
+const MAX_BLOB_BYTES: u64 = 10 * 1024 * 1024;
+
+/// 每个会话保留的快照数（按提示词序号保留最新的）
+const MAX_SNAPSHOTS_PER_SESSION: usize = 50;
+
+/// 超过该时长未使用的会话快照目录会被清理
+const SNAPSHOT_RETENTION: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
+/// 串行化快照的读改写
+static SNAPSHOT_LOCK: Mutex<()> = Mutex::new(());
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// 全局设置（~/.anycode/protected_paths.json）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProtectedPathsSettings {
+    /// 总开关，关闭后不注入约束也不做事后校验
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// 对所有项目生效的默认规则
+    #[serde(default)]
+    pub default_patterns: Vec<String>,
+    /// 执行时是否把禁改清单附加到系统指令 / 提示词
+    #[serde(default = "default_true")]
+    pub inject_into_prompt: bool,
+}
+
+impl Default for ProtectedPathsSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            default_patterns: Vec::new(),
+            inject_into_prompt: true,
+        }
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// 项目级配置（<project>/.anycode/protected-paths.json）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ProjectProtectedPaths {
+    #[serde(default)]
+    patterns: Vec<String>,
+}
+
+/// 项目的禁改区配置与最终生效的规则
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProtectedPathsInfo {
+    pub project_patterns: Vec<String>,
+    pub default_patterns: Vec<String>,
+    /// 全局默认 + 项目规则（去重，总开关关闭时为空）
+    pub effective_patterns: Vec<String>,
+    pub enabled: bool,
+    pub config_path: String,
+}
+
+/// 禁改文件的变化类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProtectedChangeKind {
+    Modified,
+    Deleted,
+    Created,
+}
+
+/// 一个被改动的禁改文件
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProtectedChange {
+    /// 相对项目根目录的路径（`/` 分隔）
+    pub path: String,
+    pub kind: ProtectedChangeKind,
+}
+
+/// 任务结束后发现的禁改区改动（`protected-paths-violation` 事件）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProtectedViolation {
+    pub engine: String,
+    pub session_id: String,
+    pub prompt_index: usize,
+    pub project_path: String,
+    pub changes: Vec<ProtectedChange>,
+    /// UTC Unix 秒
+    pub detected_at: i64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trace_id: Option<String>,
+}
+
+/// 一次禁改区恢复的记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProtectedRestore {
+    /// UTC Unix 秒
+    pub restored_at: i64,
+    /// 已恢复到任务前状态的文件（新建的文件已删除）
+    pub restored: Vec<String>,
+    /// 超过大小上限、快照中没有内容而无法恢复的文件
+    pub skipped: Vec<String>,
+    /// 恢复后自动提交的 commit（未启用撤回 git 操作时为空）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commit: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SnapshotFile {
+    path: String,
+    hash: String,
+    /// 内容是否已存入 blobs/
+    stored: bool,
+}
+
+/// 一次任务开始前的禁改区快照
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TaskSnapshot {
+    engine: String,
+    session_id: String,
+    /// Claude 的项目 ID（用于定位 git 记录）
+    #[serde(default)]
+    project_id: Option<String>,
+    prompt_index: usize,
+    project_path: String,
+    patterns: Vec<String>,
+    created_at: i64,
+    /// 文件数超过上限，未记录的文件不参与"新建"判断
+    #[serde(default)]
+    truncated: bool,
+    files: Vec<SnapshotFile>,
+    #[serde(default)]
+    violation: Option<ProtectedViolation>,
+    #[serde(default)]
+    restore: Option<ProtectedRestore>,
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/// 获取全局禁改区设置
+#[tauri::command]
+pub async fn get_protected_paths_settings() -> Result<ProtectedPathsSettings, String> {
+    load_settings()
+}
+
+/// 保存全局禁改区设置
+#[tauri::command]
+pub async fn save_protected_paths_settings(
+    mut settings: ProtectedPathsSettings,
+) -> Result<(), String> {
+    settings.default_patterns = normalize_patterns(&settings.default_patterns)?;
+    save_json_config(&settings, get_settings_path()?)
+}
+
+/// 获取项目的禁改区配置
+#[tauri::command]
+pub async fn get_protected_paths(project_path: String) -> Result<ProtectedPathsInfo, String> {
+    let settings = load_settings()?;
+    let project = load_project_config(&project_path)?;
+    Ok(ProtectedPathsInfo {
+        effective_patterns: merge_patterns(&settings, &project.patterns),
+        project_patterns: project.patterns,
+        default_patterns: settings.default_patterns,
+        enabled: settings.enabled,
+        config_path: get_project_config_path(&project_path)
+            .to_string_lossy()
+            .to_string(),
+    })
+}
+
+/// 保存项目的禁改区规则，返回规范化后的列表
+#[tauri::command]
+pub async fn save_project_protected_paths(
+    project_path: String,
+    patterns: Vec<String>,
+) -> Result<Vec<String>, String> {
+    if !Path::new(&project_path).is_dir() {
+        return Err(format!("项目目录不存在: {}", project_path));
+    }
+    let patterns = normalize_patterns(&patterns)?;
+    save_json_config(
+        &ProjectProtectedPaths {
+            patterns: patterns.clone(),
+        },
+        get_project_config_path(&project_path),
+    )?;
+    log::info!(
+        "[ProtectedPaths] Saved {} pattern(s) for {}",
+        patterns.len(),
+        project_path
+    );
+    Ok(patterns)
+}
+
+/// 把提示词执行期间被改动的禁改文件恢复到任务前状态
+///
+/// 内容来自提示词发送时的任务快照；启用撤回 git 操作时恢复结果会自动提交，
+/// 若该提交紧跟在提示词的 commit_after 之后，则并入该提示词的记录，撤回时一并处理。
+#[tauri::command]
+pub async fn revert_protected_changes(
+    engine: String,
+    session_id: String,
+    prompt_index: usize,
+) -> Result<ProtectedRestore, String> {
+    tokio::task::spawn_blocking(move || {
+        let _guard = SNAPSHOT_LOCK.lock().unwrap();
+        let dir = get_session_dir(&engine, &session_id)?;
+        let mut snapshot = load_snapshot(&dir, prompt_index)?
+            .ok_or_else(|| format!("没有找到提示词 #{} 的禁改区快照", prompt_index))?;
+
+        let mut restore = restore_from_snapshot(&dir, &snapshot)?;
+        restore.commit = commit_restore(&snapshot, restore.restored.len());
+        log::info!(
+            "[ProtectedPaths] Restored {} file(s) after {} prompt #{} (skipped {})",
+            restore.restored.len(),
+            snapshot.engine,
+            prompt_index,
+            restore.skipped.len()
+        );
+
+        snapshot.restore = Some(restore.clone());
+        save_snapshot(&dir, &snapshot)?;
+        Ok(restore)
+    })
+    .await
+    .map_err(|e| format!("Protected paths task failed: {}", e))?
+}
+
+/// 按快照恢复被改动的禁改文件：删除新建的文件，从快照内容写回修改或删除的文件
+///
+/// 只记录了哈希的大文件无法恢复，列入 skipped
+fn restore_from_snapshot(dir: &Path, snapshot: &TaskSnapshot) -> Result<ProtectedRestore, String> {
+    let changes = diff_against_snapshot(snapshot)?;
+    if changes.is_empty() {
+        return Err("禁改区文件与任务前一致，无需恢复".to_string());
+    }
+
+    let project_root = Path::new(&snapshot.project_path);
+    let stored: HashSet<&str> = snapshot
+        .files
+        .iter()
+        .filter(|f| f.stored)
+        .map(|f| f.path.as_str())
+        .collect();
+    let mut restore = ProtectedRestore {
+        restored_at: chrono::Utc::now().timestamp(),
+        restored: Vec::new(),
+        skipped: Vec::new(),
+        commit: None,
+    };
+
+    for change in &changes {
+        let target = project_root.join(&change.path);
+        match change.kind {
+            ProtectedChangeKind::Created => {
+                fs::remove_file(&target)
+                    .map_err(|e| format!("删除新建的禁改文件失败 {}: {}", change.path, e))?;
+            }
+            ProtectedChangeKind::Modified | ProtectedChangeKind::Deleted => {
+                if !stored.contains(change.path.as_str()) {
+                    restore.skipped.push(change.path.clone());
+                    continue;
+                }
+                let hash = snapshot
+                    .files
+                    .iter()
+                    .find(|f| f.path == change.path)
+                    .map(|f| f.hash.as_str())
+                    .unwrap_or_default();
+                let content = fs::read(dir.join("blobs").join(hash))
+                    .map_err(|e| format!("读取快照内容失败 {}: {}", change.path, e))?;
+                if let Some(parent) = target.parent() {
+                    fs::create_dir_all(parent)
+                        .map_err(|e| format!("创建目录失败 {}: {}", parent.display(), e))?;
+                }
+                fs::write(&target, content)
+                    .map_err(|e| format!("恢复禁改文件失败 {}: {}", change.path, e))?;
+            }
+        }
+        restore.restored.push(change.path.clone());
+    }
+    Ok(restore)
+}
+
+// ============================================================================
+// Execution Hooks
+// ============================================================================
+
+/// 执行时附加到系统指令 / 提示词的禁改约束；未开启或没有规则时返回 None
+pub fn protected_paths_instruction(project_path: &str) -> Option<String> {
+    let settings = load_settings_or_default();
+    if !settings.enabled || !settings.inject_into_prompt {
+        return None;
+    }
+    let patterns = effective_patterns(&settings, project_path);
+    if patterns.is_empty() {
+        return None;
+    }
+    Some(format!(
+        "--- 禁改区 (来自 Any Code 受保护路径) ---\n\
+         以下路径禁止修改、删除或在其中新建文件（glob 规则，相对项目根目录）：\n{}\n\
+         如果完成任务必须改动这些路径，请停止并向用户说明原因，不要自行修改。",
+        patterns
+            .iter()
+            .map(|p| format!("- {}", p))
+            .collect::<Vec<_>>()
+            .join("\n")
+    ))
+}
+
+/// 把禁改约束附加到提示词末尾（Codex / Gemini 没有独立的系统提示词注入点）
+pub fn append_protected_paths_to_prompt(prompt: &str, project_path: &str) -> String {
+    match protected_paths_instruction(project_path) {
+        Some(instruction) => format!("{}\n\n{}", prompt, instruction),
+        None => prompt.to_string(),
+    }
+}
+
+/// 提示词发送时为命中禁改规则的文件建立任务快照（失败只记录日志，不影响执行）
+///
+/// 扫描和哈希在阻塞线程池中进行，不占用异步运行时
+pub async fn capture_task_snapshot(
+    engine: &str,
+    session_id: &str,
+    project_id: Option<&str>,
+    prompt_index: usize,
+    project_path: &str,
+) {
+    let engine = engine.to_string();
+    let session_id = session_id.to_string();
+    let project_id = project_id.map(str::to_string);
+    let project_path = project_path.to_string();
+    let result = tokio::task::spawn_blocking(move || {
+        let _guard = SNAPSHOT_LOCK.lock().unwrap();
+        capture_snapshot_locked(
+            &engine,
+            &session_id,
+            project_id.as_deref(),
+            prompt_index,
+            &project_path,
+        )
+        .map_err(|e| format!("{} prompt #{}: {}", engine, prompt_index, e))
+    })
+    .await
+    .map_err(|e| e.to_string())
+    .and_then(|result| result);
+    if let Err(e) = result {
+        log::warn!(
+            "[ProtectedPaths] Failed to snapshot protected files for {}",
+            e
+        );
+    }
+}
+
+/// 任务完成时与快照比对，发现禁改文件被改动时发送 `protected-paths-violation` 事件
+pub async fn check_protected_changes(
+    app: &AppHandle,
+    engine: &str,
+    session_id: &str,
+    prompt_index: usize,
+    trace_id: Option<&str>,
+) -> Option<ProtectedViolation> {
+    let violation_engine = engine.to_string();
+    let violation_session = session_id.to_string();
+    let trace_id = trace_id.map(str::to_string);
+    let result = tokio::task::spawn_blocking(move || {
+        let _guard = SNAPSHOT_LOCK.lock().unwrap();
+        let dir = get_session_dir(&violation_engine, &violation_session)?;
+        let Some(mut snapshot) = load_snapshot(&dir, prompt_index)? else {
+            return Ok(None);
+        };
+        let changes = diff_against_snapshot(&snapshot)?;
+        if changes.is_empty() {
+            return Ok(None);
+        }
+        let violation = ProtectedViolation {
+            engine: violation_engine,
+            session_id: violation_session,
+            prompt_index,
+            project_path: snapshot.project_path.clone(),
+            changes,
+            detected_at: chrono::Utc::now().timestamp(),
+            trace_id,
+        };
+        snapshot.violation = Some(violation.clone());
+        save_snapshot(&dir, &snapshot)?;
+        Ok(Some(violation))
+    })
+    .await
+    .map_err(|e| e.to_string())
+    .and_then(|result| result);
+
+    match result {
+        Ok(Some(violation)) => {
+            log::warn!(
+                "[ProtectedPaths] {} prompt #{} changed {} protected file(s): {}",
+                engine,
+                prompt_index,
+                violation.changes.len(),
+                violation
+                    .changes
+                    .iter()
+                    .map(|c| c.path.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+            let _ = app.emit("protected-paths-violation", &violation);
+            Some(violation)
+        }
+        Ok(None) => None,
+        Err(e) => {
+            log::warn!(
+                "[ProtectedPaths] Failed to check protected files for {} prompt #{}: {}",
+                engine,
+                prompt_index,
+                e
+            );
+            None
+        }
+    }
+}
+
+/// 把禁改区恢复记录附加到撤回面板的提示词列表
+pub fn attach_restore_records(engine: &str, session_id: &str, prompts: &mut [PromptRecord]) {
+    let Ok(dir) = get_session_dir(engine, session_id) else {
+        return;
+    };
+    if !dir.exists() {
+        return;
+    }
+    for prompt in prompts.iter_mut() {
+        if let Ok(Some(snapshot)) = load_snapshot(&dir, prompt.index) {
+            prompt.protected_restore = snapshot.restore;
+        }
+    }
+}
+
+// ============================================================================
+// Matching
+// ============================================================================
+
+/// 编译后的禁改规则
+struct ProtectedMatcher {
+    patterns: Vec<(Pattern, bool)>,
+}
+
+impl ProtectedMatcher {
+    fn new(patterns: &[String]) -> Self {
+        Self {
+            patterns: patterns
+                .iter()
+                .filter_map(|p| {
+                    Pattern::new(p)
+                        .ok()
+                        .map(|compiled| (compiled, !p.contains('/')))
+                })
+                .collect(),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+
+    /// 路径（`/` 分隔，相对项目根目录）本身或任一上级目录命中规则
+    fn is_protected(&self, rel_path: &str) -> bool {
+        let options = MatchOptions {
+            case_sensitive: !cfg!(windows),
+            require_literal_separator: true,
+            require_literal_leading_dot: false,
+        };
+        let components: Vec<&str> = rel_path.split('/').collect();
+        self.patterns.iter().any(|(pattern, any_depth)| {
+            if *any_depth {
+                components
+                    .iter()
+                    .any(|name| pattern.matches_with(name, options))
+            } else {
+                (1..=components.len())
+                    .any(|depth| pattern.matches_with(&components[..depth].join("/"), options))
+            }
+        })
+    }
+}
+
+/// 规范化规则：统一分隔符，去掉开头的 `./` `/` 与结尾的 `/`，去重并校验 glob 语法
+fn normalize_patterns(patterns: &[String]) -> Result<Vec<String>, String> {
+    let mut normalized: Vec<String> = Vec::new();
+    for raw in patterns {
+        let mut pattern = raw.trim().replace('\\', "/");
+        while let Some(rest) = pattern.strip_prefix("./") {
+            pattern = rest.to_string();
+        }
+        let pattern = pattern.trim_matches('/').to_string();
+        if pattern.is_empty() || normalized.contains(&pattern) {
+            continue;
+        }
+        Pattern::new(&pattern).map_err(|e| format!("无效的 glob 规则 '{}': {}", raw, e))?;
+        normalized.push(pattern);
+    }
+    Ok(normalized)
+}
+
+fn merge_patterns(settings: &ProtectedPathsSettings, project_patterns: &[String]) -> Vec<String> {
+    if !settings.enabled {
+        return Vec::new();
+    }
+    let mut merged = settings.default_patterns.clone();
+    for pattern in project_patterns {
+        if !merged.contains(pattern) {
+            merged.push(pattern.clone());
+        }
+    }
+    merged
+}
+
+fn effective_patterns(settings: &ProtectedPathsSettings, project_path: &str) -> Vec<String> {
+    let project = load_project_config(project_path).unwrap_or_else(|e| {
+        log::warn!("[ProtectedPaths] Failed to load project config: {}", e);
+        ProjectProtectedPaths::default()
+    });
+    merge_patterns(settings, &project.patterns)
+}
+
+// ============================================================================
+// Snapshots
+// ============================================================================
+
+/// 列出项目中命中规则的文件（相对路径 → 绝对路径），超过上限时标记截断
+///
+/// git 仓库按 `git ls-files` 列出（跳过 .gitignore 忽略的构建产物、依赖目录），
+/// 非 git 目录或 git 调用失败时遍历目录
+fn scan_protected_files(
+    project_path: &str,
+    matcher: &ProtectedMatcher,
+) -> (BTreeMap<String, PathBuf>, bool) {
+    let root = Path::new(project_path);
+    if simple_git::is_git_repo(project_path) {
+        match simple_git::git_list_files(project_path) {
+            Ok(paths) => {
+                let mut files = BTreeMap::new();
+                for rel_path in paths {
+                    if !matcher.is_protected(&rel_path) {
+                        continue;
+                    }
+                    let path = root.join(&rel_path);
+                    // 已跟踪但在工作区被删除的文件不算现存文件
+                    if !path.is_file() {
+                        continue;
+                    }
+                    if files.len() >= MAX_PROTECTED_FILES {
+                        return (files, true);
+                    }
+                    files.insert(rel_path, path);
+                }
+                return (files, false);
+            }
+            Err(e) => log::debug!("[ProtectedPaths] Falling back to directory walk: {}", e),
+        }
+    }
+
+    let mut files = BTreeMap::new();
+    let walker = WalkDir::new(root).into_iter().filter_entry(|entry| {
+        entry.depth() == 0
+            || !entry.file_type().is_dir()
+            || !entry
+                .file_name()
+                .to_str()
+                .is_some_and(|name| WALK_SKIPPED_DIRS.contains(&name))
+    });
+    for entry in walker.flatten() {
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let Ok(relative) = entry.path().strip_prefix(root) else {
+            continue;
+        };
+        let rel_path = relative.to_string_lossy().replace('\\', "/");
+        if !matcher.is_protected(&rel_path) {
+            continue;
+        }
+        if files.len() >= MAX_PROTECTED_FILES {
+            return (files, true);
+        }
+        files.insert(rel_path, entry.into_path());
+    }
+    (files, false)
+}
+
+fn hash_file(path: &Path) -> io::Result<String> {
+    let mut file = fs::File::open(path)?;
+    let mut hasher = Sha256::new();
+    io::copy(&mut file, &mut hasher)?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+fn capture_snapshot_locked(
+    engine: &str,
+    session_id: &str,
+    project_id: Option<&str>,
+    prompt_index: usize,
+    project_path: &str,
+) -> Result<(), String> {
+    let dir = get_session_dir(engine, session_id)?;
+    // 撤回后提示词序号会被复用，同序号及之后的旧快照已失效
+    remove_snapshots_from(&dir, prompt_index);
+
+    let settings = load_settings_or_default();
+    let patterns = effective_patterns(&settings, project_path);
+    let matcher = ProtectedMatcher::new(&patterns);
+    if matcher.is_empty() {
+        return Ok(());
+    }
+
+    let (paths, truncated) = scan_protected_files(project_path, &matcher);
+    if truncated {
+        log::warn!(
+            "[ProtectedPaths] More than {} protected files, snapshot truncated",
+            MAX_PROTECTED_FILES
+        );
+    }
+
+    let blobs_dir = dir.join("blobs");
+    fs::create_dir_all(&blobs_dir)
+        .map_err(|e| format!("Failed to create snapshot directory: {}", e))?;
+    let mut files = Vec::with_capacity(paths.len());
+    for (rel_path, abs_path) in paths {
+        let size = match fs::metadata(&abs_path) {
+            Ok(metadata) => metadata.len(),
+            Err(e) => {
+                log::debug!("[ProtectedPaths] Skipping {}: {}", rel_path, e);
+                continue;
+            }
+        };
+        let hash = hash_file(&abs_path).map_err(|e| format!("读取 {} 失败: {}", rel_path, e))?;
+        let stored = size <= MAX_BLOB_BYTES;
+        let blob = blobs_dir.join(&hash);
+        if stored && !blob.exists() {
+            fs::copy(&abs_path, &blob).map_err(|e| format!("保存 {} 快照失败: {}", rel_path, e))?;
+        }
+        files.push(SnapshotFile {
+            path: rel_path,
+            hash,
+            stored,
+        });
+    }
+
+    let snapshot = TaskSnapshot {
+        engine: engine.to_string(),
+        session_id: session_id.to_string(),
+        project_id: project_id.map(str::to_string),
+        prompt_index,
+        project_path: project_path.to_string(),
+        patterns,
+        created_at: chrono::Utc::now().timestamp(),
+        truncated,
+        files,
+        violation: None,
+        restore: None,
+    };
+    save_snapshot(&dir, &snapshot)?;
+    log::info!(
+        "[ProtectedPaths] Snapshot {} protected file(s) before {} prompt #{}",
+        snapshot.files.len(),
+        engine,
+        prompt_index
+    );
+
+    prune_session_snapshots(&dir);
+    prune_stale_sessions(&dir);
+    Ok(())
+}
+
+/// 当前状态与快照的差异（按路径排序）
+fn diff_against_snapshot(snapshot: &TaskSnapshot) -> Result<Vec<ProtectedChange>, String> {
+    let matcher = ProtectedMatcher::new(&snapshot.patterns);
+    let (current, _) = scan_protected_files(&snapshot.project_path, &matcher);
+    let project_root = Path::new(&snapshot.project_path);
+
+    let mut changes = Vec::new();
+    let mut known = HashSet::new();
+    for file in &snapshot.files {
+        known.insert(file.path.as_str());
+        let path = project_root.join(&file.path);
+        if !path.is_file() {
+            changes.push(ProtectedChange {
+                path: file.path.clone(),
+                kind: ProtectedChangeKind::Deleted,
+            });
+            continue;
+        }
+        let hash = hash_file(&path).map_err(|e| format!("读取 {} 失败: {}", file.path, e))?;
+        if hash != file.hash {
+            changes.push(ProtectedChange {
+                path: file.path.clone(),
+                kind: ProtectedChangeKind::Modified,
+            });
+        }
+    }
+    if !snapshot.truncated {
+        changes.extend(
+            current
+                .into_keys()
+                .filter(|path| !known.contains(path.as_str()))
+                .map(|path| ProtectedChange {
+                    path,
+                    kind: ProtectedChangeKind::Created,
+                }),
+        );
+    }
+    changes.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(changes)
+}
+
+/// 启用撤回 git 操作时提交恢复结果，返回提交后的 HEAD
+fn commit_restore(snapshot: &TaskSnapshot, restored_count: usize) -> Option<String> {
+    let config = load_execution_config().ok()?;
+    if config.disable_rewind_git_operations || !simple_git::is_git_repo(&snapshot.project_path) {
+        return None;
+    }
+
+    let previous = simple_git::git_current_commit(&snapshot.project_path).ok()?;
+    let message = format!(
+        "[AnyCode] Restore {} protected file(s) changed by prompt #{}",
+        restored_count, snapshot.prompt_index
+    );
+    if let Err(e) = simple_git::git_commit_changes(
+        &snapshot.project_path,
+        &message,
+        config.git_identity_fallback,
+    ) {
+        log::warn!("[ProtectedPaths] Failed to commit restored files: {}", e);
+        return None;
+    }
+    let commit = simple_git::git_current_commit(&snapshot.project_path).ok()?;
+
+    match extend_commit_after(snapshot, &previous, &commit) {
+        Ok(true) => log::info!(
+            "[ProtectedPaths] Prompt #{} commit_after advanced to restore commit",
+            snapshot.prompt_index
+        ),
+        Ok(false) => {}
+        Err(e) => log::warn!("[ProtectedPaths] Failed to update git record: {}", e),
+    }
+    Some(commit)
+}
+
+/// 恢复提交紧跟在提示词的 commit_after 之后时，把它并入该提示词的 git 记录
+fn extend_commit_after(
+    snapshot: &TaskSnapshot,
+    previous: &str,
+    commit: &str,
+) -> Result<bool, String> {
+    let index = snapshot.prompt_index;
+    match snapshot.engine.as_str() {
+        "codex" => {
+            let mut records = load_codex_git_records(&snapshot.session_id)?;
+            let Some(record) = records
+                .records
+                .iter_mut()
+                .find(|r| r.prompt_index == index && r.commit_after.as_deref() == Some(previous))
+            else {
+                return Ok(false);
+            };
+            record.commit_after = Some(commit.to_string());
+            save_codex_git_records(&snapshot.session_id, &records)?;
+        }
+        "gemini" => {
+            let mut records = load_gemini_git_records(&snapshot.session_id)?;
+            let Some(record) = records
+                .records
+                .iter_mut()
+                .find(|r| r.prompt_index == index && r.commit_after.as_deref() == Some(previous))
+            else {
+                return Ok(false);
+            };
+            record.commit_after = Some(commit.to_string());
+            save_gemini_git_records(&snapshot.session_id, &records)?;
+        }
+        _ => {
+            let Some(project_id) = snapshot.project_id.as_deref() else {
+                return Ok(false);
+            };
+            let Some(mut record) =
+                prompt_tracker::get_git_record(&snapshot.session_id, project_id, index)
+                    .map_err(|e| e.to_string())?
+            else {
+                return Ok(false);
+            };
+            if record.commit_after.as_deref() != Some(previous) {
+                return Ok(false);
+            }
+            record.commit_after = Some(commit.to_string());
+            prompt_tracker::save_git_record(&snapshot.session_id, project_id, index, record)
+                .map_err(|e| e.to_string())?;
+        }
+    }
+    Ok(true)
+}
+
+// ============================================================================
+// Storage
+// ============================================================================
+
+fn get_settings_path() -> Result<PathBuf, String> {
+    Ok(ConfigPathBuilder::from_home_subdir(".anycode")?.build("protected_paths.json"))
+}
+
+fn load_settings() -> Result<ProtectedPathsSettings, String> {
+    load_json_config(get_settings_path()?)
+}
+
+fn load_settings_or_default() -> ProtectedPathsSettings {
+    load_settings().unwrap_or_else(|e| {
+        log::warn!("[ProtectedPaths] Failed to load settings: {}", e);
+        ProtectedPathsSettings::default()
+    })
+}
+
+fn get_project_config_path(project_path: &str) -> PathBuf {
+    Path::new(project_path)
+        .join(".anycode")
+        .join("protected-paths.json")
+}
+
+fn load_project_config(project_path: &str) -> Result<ProjectProtectedPaths, String> {
+    load_json_config(get_project_config_path(project_path))
+}
+
+fn get_snapshots_root() -> Result<PathBuf, String> {
+    Ok(ConfigPathBuilder::from_home_subdir(".anycode")?.build("protected-snapshots"))
+}
+
+fn get_session_dir(engine: &str, session_id: &str) -> Result<PathBuf, String> {
+    let safe_id: String = session_id
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    Ok(get_snapshots_root()?.join(format!("{}-{}", engine, safe_id)))
+}
+
+fn snapshot_path(dir: &Path, prompt_index: usize) -> PathBuf {
+    dir.join(format!("prompt-{}.json", prompt_index))
+}
+
+fn snapshot_index(path: &Path) -> Option<usize> {
+    path.file_name()?
+        .to_str()?
+        .strip_prefix("prompt-")?
+        .strip_suffix(".json")?
+        .parse()
+        .ok()
+}
+
+fn load_snapshot(dir: &Path, prompt_index: usize) -> Result<Option<TaskSnapshot>, String> {
+    load_json_config(snapshot_path(dir, prompt_index))
+}
+
+fn save_snapshot(dir: &Path, snapshot: &TaskSnapshot) -> Result<(), String> {
+    save_json_config(snapshot, snapshot_path(dir, snapshot.prompt_index))
+}
+
+fn list_snapshot_indices(dir: &Path) -> Vec<usize> {
+    let mut indices: Vec<usize> = fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter_map(|entry| snapshot_index(&entry.path()))
+                .collect()
+        })
+        .unwrap_or_default();
+    indices.sort_unstable();
+    indices
+}
+
+fn remove_snapshots_from(dir: &Path, prompt_index: usize) {
+    for index in list_snapshot_indices(dir) {
+        if index >= prompt_index {
+            let _ = fs::remove_file(snapshot_path(dir, index));
+        }
+    }
+}
+
+/// 只保留最新的若干个快照，并删除不再被引用的文件内容
+fn prune_session_snapshots(dir: &Path) {
+    let indices = list_snapshot_indices(dir);
+    if indices.len() > MAX_SNAPSHOTS_PER_SESSION {
+        for index in &indices[..indices.len() - MAX_SNAPSHOTS_PER_SESSION] {
+            let _ = fs::remove_file(snapshot_path(dir, *index));
+        }
+    }
+
+    let referenced: HashSet<String> = list_snapshot_indices(dir)
+        .into_iter()
+        .filter_map(|index| load_snapshot(dir, index).ok().flatten())
+        .flat_map(|snapshot| snapshot.files.into_iter().map(|f| f.hash))
+        .collect();
+    if let Ok(entries) = fs::read_dir(dir.join("blobs")) {
+        for entry in entries.flatten() {
+            if !referenced.contains(entry.file_name().to_string_lossy().as_ref()) {
+                let _ = fs::remove_file(entry.path());
+            }
+        }
+    }
+}
+
+/// 清理长时间未使用的会话快照目录（当前会话除外）
+fn prune_stale_sessions(current: &Path) {
+    let Ok(root) = get_snapshots_root() else {
+        return;
+    };
+    let Ok(entries) = fs::read_dir(root) else {
+        return;
+    };
+    let now = SystemTime::now();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path == current || !path.is_dir() {
+            continue;
+        }
+        let stale = entry
+            .metadata()
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|modified| now.duration_since(modified).ok())
+            .is_some_and(|age| age > SNAPSHOT_RETENTION);
+        if stale {
+            log::debug!(
+                "[ProtectedPaths] Removing stale snapshots: {}",
+                path.display()
+            );
+            let _ = fs::remove_dir_all(&path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matcher(patterns: &[&str]) -> ProtectedMatcher {
+        let patterns: Vec<String> = patterns.iter().map(|p| p.to_string()).collect();
+        ProtectedMatcher::new(&normalize_patterns(&patterns).unwrap())
+    }
+
+    #[test]
+    fn matches_names_at_any_depth_and_anchored_paths() {
+        let m = matcher(&["Cargo.lock", "migrations/", "./legal/*.md", "*.pem"]);
+        assert!(m.is_protected("Cargo.lock"));
+        assert!(m.is_protected("src-tauri/Cargo.lock"));
+        assert!(m.is_protected("db/migrations/0001_init.sql"));
+        assert!(m.is_protected("legal/terms.md"));
+        assert!(m.is_protected("certs/server.pem"));
+        assert!(!m.is_protected("docs/legal/terms.md"));
+        assert!(!m.is_protected("legal/drafts/terms.md"));
+        assert!(!m.is_protected("src/main.rs"));
+
+        assert!(normalize_patterns(&["[".to_string()]).is_err());
+        assert_eq!(
+            normalize_patterns(&[" /a/b/ ".to_string(), "a/b".to_string(), String::new()]).unwrap(),
+            vec!["a/b".to_string()]
+        );
+    }
+
+    #[test]
+    fn detects_modified_deleted_and_created_files() {
+        let project = tempfile::tempdir().unwrap();
+        let root = project.path();
+        fs::create_dir_all(root.join("migrations")).unwrap();
+        fs::write(root.join("Cargo.lock"), "v1").unwrap();
+        fs::write(root.join("migrations/0001.sql"), "create").unwrap();
+        fs::write(root.join("main.rs"), "fn main() {}").unwrap();
+
+        let patterns = vec!["Cargo.lock".to_string(), "migrations".to_string()];
+        let (files, truncated) =
+            scan_protected_files(&root.to_string_lossy(), &ProtectedMatcher::new(&patterns));
+        assert!(!truncated);
+        assert_eq!(files.len(), 2);
+
+        let snapshot = TaskSnapshot {
+            engine: "codex".to_string(),
+            session_id: "s".to_string(),
+            project_id: None,
+            prompt_index: 0,
+            project_path: root.to_string_lossy().to_string(),
+            patterns,
+            created_at: 0,
+            truncated: false,
+            files: files
+                .iter()
+                .map(|(path, abs)| SnapshotFile {
+                    path: path.clone(),
+                    hash: hash_file(abs).unwrap(),
+                    stored: true,
+                })
+                .collect(),
+            violation: None,
+            restore: None,
+        };
+
+        fs::write(root.join("main.rs"), "fn main() { changed }").unwrap();
+        assert!(diff_against_snapshot(&snapshot).unwrap().is_empty());
+
+        fs::write(root.join("Cargo.lock"), "v2").unwrap();
+        fs::remove_file(root.join("migrations/0001.sql")).unwrap();
+        fs::write(root.join("migrations/0002.sql"), "alter").unwrap();
+        let changes = diff_against_snapshot(&snapshot).unwrap();
+        assert_eq!(
+            changes,
+            vec![
+                ProtectedChange {
+                    path: "Cargo.lock".to_string(),
+                    kind: ProtectedChangeKind::Modified,
+                },
+                ProtectedChange {
+                    path: "migrations/0001.sql".to_string(),
+                    kind: ProtectedChangeKind::Deleted,
+                },
+                ProtectedChange {
+                    path: "migrations/0002.sql".to_string(),
+                    kind: ProtectedChangeKind::Created,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn skips_gitignored_files_in_git_repos() {
+        let project = tempfile::tempdir().unwrap();
+        let root = project.path();
+        let git = |args: &[&str]| {
+            let status = std::process::Command::new("git")
+                .args(args)
+                .current_dir(root)
+                .output()
+                .unwrap()
+                .status;
+            assert!(status.success(), "git {:?} failed", args);
+        };
+        git(&["init", "-q"]);
+        fs::create_dir_all(root.join("target/debug")).unwrap();
+        fs::write(root.join(".gitignore"), "target/\n").unwrap();
+        fs::write(root.join("app.lock"), "tracked").unwrap();
+        fs::write(root.join("new.lock"), "untracked").unwrap();
+        fs::write(root.join("target/debug/build.lock"), "ignored").unwrap();
+        git(&["add", "app.lock"]);
+
+        let (files, truncated) = scan_protected_files(
+            &root.to_string_lossy(),
+            &ProtectedMatcher::new(&["*.lock".to_string()]),
+        );
+        assert!(!truncated);
+        assert_eq!(
+            files.keys().cloned().collect::<Vec<_>>(),
+            vec!["app.lock".to_string(), "new.lock".to_string()]
+        );
+    }
+
+    #[test]
+    fn restores_protected_files_from_snapshot() {
+        let project = tempfile::tempdir().unwrap();
+        let store = tempfile::tempdir().unwrap();
+        let root = project.path();
+        let blobs = store.path().join("blobs");
+        fs::create_dir_all(root.join("migrations")).unwrap();
+        fs::create_dir_all(&blobs).unwrap();
+        fs::write(root.join("Cargo.lock"), "v1").unwrap();
+        fs::write(root.join("migrations/0001.sql"), "create").unwrap();
+        fs::write(root.join("migrations/big.sql"), "huge").unwrap();
+
+        let patterns = vec!["Cargo.lock".to_string(), "migrations".to_string()];
+        let (files, _) =
+            scan_protected_files(&root.to_string_lossy(), &ProtectedMatcher::new(&patterns));
+        let snapshot = TaskSnapshot {
+            engine: "codex".to_string(),
+            session_id: "s".to_string(),
+            project_id: None,
+            prompt_index: 0,
+            project_path: root.to_string_lossy().to_string(),
+            patterns,
+            created_at: 0,
+            truncated: false,
+            files: files
+                .iter()
+                .map(|(path, abs)| {
+                    let hash = hash_file(abs).unwrap();
+                    // 大文件只记录哈希，不保存内容
+                    let stored = path != "migrations/big.sql";
+                    if stored {
+                        fs::copy(abs, blobs.join(&hash)).unwrap();
+                    }
+                    SnapshotFile {
+                        path: path.clone(),
+                        hash,
+                        stored,
+                    }
+                })
+                .collect(),
+            violation: None,
+            restore: None,
+        };
+        assert!(restore_from_snapshot(store.path(), &snapshot).is_err());
+
+        fs::write(root.join("Cargo.lock"), "v2").unwrap();
+        fs::remove_file(root.join("migrations/0001.sql")).unwrap();
+        fs::write(root.join("migrations/0002.sql"), "alter").unwrap();
+        fs::write(root.join("migrations/big.sql"), "changed").unwrap();
+
+        let restore = restore_from_snapshot(store.path(), &snapshot).unwrap();
+        assert_eq!(
+            restore.restored,
+            vec![
+                "Cargo.lock".to_string(),
+                "migrations/0001.sql".to_string(),
+                "migrations/0002.sql".to_string(),
+            ]
+        );
+        assert_eq!(restore.skipped, vec!["migrations/big.sql".to_string()]);
+        assert_eq!(fs::read_to_string(root.join("Cargo.lock")).unwrap(), "v1");
+        assert_eq!(
+            fs::read_to_string(root.join("migrations/0001.sql")).unwrap(),
+            "create"
+        );
+        assert!(!root.join("migrations/0002.sql").exists());
+        assert_eq!(
+            diff_against_snapshot(&snapshot).unwrap(),
+            vec![ProtectedChange {
+                path: "migrations/big.sql".to_string(),
+                kind: ProtectedChangeKind::Modified,
+            }]
+        );
+    }
+}
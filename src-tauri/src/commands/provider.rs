@@ -4,6 +4,9 @@ use std::fs;
 use std::path::PathBuf;
 use tauri::{command, AppHandle};
 
+use super::app_error::AppError;
+use super::messages::{t, t_with, MessageKey};
+use super::rate_limiter;
 use super::url_utils::{normalize_api_url, normalize_base_url, ApiEndpointType};
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -114,18 +117,18 @@ fn load_legacy_providers() -> Result<Vec<ProviderConfig>, String> {
 
 // CRUD 操作 - 获取所有代理商预设（从遗留文件读取）
 #[command]
-pub fn get_provider_presets() -> Result<Vec<ProviderConfig>, String> {
-    load_legacy_providers()
+pub fn get_provider_presets() -> Result<Vec<ProviderConfig>, AppError> {
+    Ok(load_legacy_providers()?)
 }
 
 // CRUD 操作 - 添加代理商预设（写入遗留文件，保持兼容性）
 #[command]
-pub fn add_provider_config(config: ProviderConfig) -> Result<String, String> {
+pub fn add_provider_config(config: ProviderConfig) -> Result<String, AppError> {
     let mut providers = load_legacy_providers()?;
 
     // 检查ID是否已存在
     if providers.iter().any(|p| p.id == config.id) {
-        return Err(format!("ID '{}' 已存在，请使用不同的ID", config.id));
+        return Err(t_with(MessageKey::ProviderIdExists, &[("id", &config.id)]).into());
     }
 
     providers.push(config.clone());
@@ -137,18 +140,18 @@ pub fn add_provider_config(config: ProviderConfig) -> Result<String, String> {
 
     fs::write(&legacy_path, content).map_err(|e| format!("写入配置文件失败: {}", e))?;
 
-    Ok(format!("成功添加代理商配置: {}", config.name))
+    Ok(t_with(MessageKey::ProviderAdded, &[("name", &config.name)]))
 }
 
 // CRUD 操作 - 更新代理商预设
 #[command]
-pub fn update_provider_config(config: ProviderConfig) -> Result<String, String> {
+pub fn update_provider_config(config: ProviderConfig) -> Result<String, AppError> {
     let mut providers = load_legacy_providers()?;
 
     let index = providers
         .iter()
         .position(|p| p.id == config.id)
-        .ok_or_else(|| format!("未找到ID为 '{}' 的配置", config.id))?;
+        .ok_or_else(|| t_with(MessageKey::ProviderNotFound, &[("id", &config.id)]))?;
 
     providers[index] = config.clone();
 
@@ -159,18 +162,21 @@ pub fn update_provider_config(config: ProviderConfig) -> Result<String, String>
 
     fs::write(&legacy_path, content).map_err(|e| format!("写入配置文件失败: {}", e))?;
 
-    Ok(format!("成功更新代理商配置: {}", config.name))
+    Ok(t_with(
+        MessageKey::ProviderUpdated,
+        &[("name", &config.name)],
+    ))
 }
 
 // CRUD 操作 - 删除代理商预设
 #[command]
-pub fn delete_provider_config(id: String) -> Result<String, String> {
+pub fn delete_provider_config(id: String) -> Result<String, AppError> {
     let mut providers = load_legacy_providers()?;
 
     let index = providers
         .iter()
         .position(|p| p.id == id)
-        .ok_or_else(|| format!("未找到ID为 '{}' 的配置", id))?;
+        .ok_or_else(|| t_with(MessageKey::ProviderNotFound, &[("id", &id)]))?;
 
     let deleted_config = providers.remove(index);
 
@@ -181,23 +187,26 @@ pub fn delete_provider_config(id: String) -> Result<String, String> {
 
     fs::write(&legacy_path, content).map_err(|e| format!("写入配置文件失败: {}", e))?;
 
-    Ok(format!("成功删除代理商配置: {}", deleted_config.name))
+    Ok(t_with(
+        MessageKey::ProviderDeleted,
+        &[("name", &deleted_config.name)],
+    ))
 }
 
 // CRUD 操作 - 获取单个代理商预设
 #[command]
-pub fn get_provider_config(id: String) -> Result<ProviderConfig, String> {
+pub fn get_provider_config(id: String) -> Result<ProviderConfig, AppError> {
     let providers = load_legacy_providers()?;
 
     providers
         .into_iter()
         .find(|p| p.id == id)
-        .ok_or_else(|| format!("未找到ID为 '{}' 的配置", id))
+        .ok_or_else(|| t_with(MessageKey::ProviderNotFound, &[("id", &id)]).into())
 }
 
 // CRUD 操作 - 重新排序代理商预设
 #[command]
-pub fn reorder_provider_configs(ids: Vec<String>) -> Result<String, String> {
+pub fn reorder_provider_configs(ids: Vec<String>) -> Result<String, AppError> {
     let providers = load_legacy_providers()?;
 
     // 根据传入的 ID 顺序重新排列
@@ -222,12 +231,12 @@ pub fn reorder_provider_configs(ids: Vec<String>) -> Result<String, String> {
 
     fs::write(&legacy_path, content).map_err(|e| format!("写入配置文件失败: {}", e))?;
 
-    Ok("成功重新排序代理商配置".to_string())
+    Ok(t(MessageKey::ProviderReordered))
 }
 
 // 获取当前代理商配置（从settings.json的env字段和apiKeyHelper字段读取）
 #[command]
-pub fn get_current_provider_config() -> Result<CurrentConfig, String> {
+pub fn get_current_provider_config() -> Result<CurrentConfig, AppError> {
     let settings = load_settings()?;
 
     let empty_map = serde_json::Map::new();
@@ -281,7 +290,7 @@ pub fn get_current_provider_config() -> Result<CurrentConfig, String> {
 pub async fn switch_provider_config(
     _app: AppHandle,
     config: ProviderConfig,
-) -> Result<String, String> {
+) -> Result<String, AppError> {
     log::info!(
         "开始切换代理商配置: {} - {}",
         config.name,
@@ -295,7 +304,7 @@ pub async fn switch_provider_config(
 
     // 确保env字段存在
     if !settings.is_object() {
-        return Err("settings.json格式错误".to_string());
+        return Err("settings.json格式错误".into());
     }
 
     let settings_obj = settings.as_object_mut().unwrap();
@@ -413,9 +422,9 @@ pub async fn switch_provider_config(
 
     log::info!("代理商配置切换完成: {}", config.name);
 
-    Ok(format!(
-        "✅ 已成功切换到 {} ({})\n\n配置已写入 ~/.claude/settings.json，即时生效！",
-        config.name, config.description
+    Ok(t_with(
+        MessageKey::ProviderSwitched,
+        &[("name", &config.name), ("description", &config.description)],
     ))
 }
 
@@ -425,7 +434,7 @@ fn validate_third_party_config(config: &ProviderConfig) -> Result<(), String> {
     if config.base_url != "https://api.anthropic.com" {
         // 确保有认证信息
         if config.auth_token.is_none() && config.api_key.is_none() {
-            return Err("第三方API需要设置认证令牌或API密钥".to_string());
+            return Err(t(MessageKey::ProviderAuthRequired));
         }
 
         // 检查模型名称（可选，但建议填写）
@@ -458,7 +467,7 @@ fn validate_third_party_config(config: &ProviderConfig) -> Result<(), String> {
 
 // 清理代理商配置（清理settings.json的env字段中的ANTHROPIC变量和apiKeyHelper字段）
 #[command]
-pub async fn clear_provider_config(_app: AppHandle) -> Result<String, String> {
+pub async fn clear_provider_config(_app: AppHandle) -> Result<String, AppError> {
     log::info!("开始清理代理商配置");
 
     let mut settings = load_settings()?;
@@ -484,12 +493,12 @@ pub async fn clear_provider_config(_app: AppHandle) -> Result<String, String> {
 
     log::info!("代理商配置清理完成");
 
-    Ok("✅ 已清理所有ANTHROPIC环境变量和apiKeyHelper配置\n\n配置已从 ~/.claude/settings.json 中移除！".to_string())
+    Ok(t(MessageKey::ProviderCleared))
 }
 
 // 测试代理商连接
 #[command]
-pub fn test_provider_connection(base_url: String) -> Result<String, String> {
+pub fn test_provider_connection(base_url: String) -> Result<String, AppError> {
     // 智能规范化 API URL（支持用户输入简化的基础 URL）
     let test_url = normalize_api_url(&base_url, ApiEndpointType::Anthropic);
 
@@ -497,7 +506,10 @@ pub fn test_provider_connection(base_url: String) -> Result<String, String> {
 
     // 这里可以实现实际的HTTP请求测试
     // 目前返回一个简单的成功消息
-    Ok(format!("连接测试完成：{}", test_url))
+    Ok(t_with(
+        MessageKey::ProviderConnectionTested,
+        &[("url", &test_url)],
+    ))
 }
 
 /// API Key 用量查询结果
@@ -525,7 +537,7 @@ pub struct ApiKeyUsage {
 pub async fn query_provider_usage(
     base_url: String,
     api_key: String,
-) -> Result<ApiKeyUsage, String> {
+) -> Result<ApiKeyUsage, AppError> {
     use reqwest::Client;
 
     log::info!("开始查询 API Key 用量: {}", base_url);
@@ -542,17 +554,17 @@ pub async fn query_provider_usage(
     let subscription_url = format!("{}/v1/dashboard/billing/subscription", normalized_base);
     log::info!("查询订阅信息: {}", subscription_url);
 
-    let subscription_response = client
+    let subscription_request = client
         .get(&subscription_url)
-        .header("Authorization", format!("Bearer {}", api_key))
-        .send()
+        .header("Authorization", format!("Bearer {}", api_key));
+    let subscription_response = rate_limiter::send(&normalized_base, subscription_request)
         .await
         .map_err(|e| format!("请求订阅信息失败: {}", e))?;
 
     if !subscription_response.status().is_success() {
         let status = subscription_response.status();
         let body = subscription_response.text().await.unwrap_or_default();
-        return Err(format!("订阅信息查询失败: {} - {}", status, body));
+        return Err(format!("订阅信息查询失败: {} - {}", status, body).into());
     }
 
     let subscription_data: Value = subscription_response
@@ -585,17 +597,17 @@ pub async fn query_provider_usage(
     );
     log::info!("查询使用情况: {}", usage_url);
 
-    let usage_response = client
+    let usage_request = client
         .get(&usage_url)
-        .header("Authorization", format!("Bearer {}", api_key))
-        .send()
+        .header("Authorization", format!("Bearer {}", api_key));
+    let usage_response = rate_limiter::send(&normalized_base, usage_request)
         .await
         .map_err(|e| format!("请求使用情况失败: {}", e))?;
 
     if !usage_response.status().is_success() {
         let status = usage_response.status();
         let body = usage_response.text().await.unwrap_or_default();
-        return Err(format!("使用情况查询失败: {} - {}", status, body));
+        return Err(format!("使用情况查询失败: {} - {}", status, body).into());
     }
 
     let usage_data: Value = usage_response
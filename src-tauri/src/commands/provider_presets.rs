@@ -0,0 +1,682 @@
+//! 远程 provider 预设清单
+//!
+//! 内置预设随应用发布，新服务商要等下个版本才能出现在列表里。这里从可配置的清单 URL
+//! 拉取额外的 Codex / Gemini 预设：
+//! - 清单地址默认指向项目维护的 JSON，设置为空字符串即禁用远程预设
+//! - 每个条目校验名称、base_url、鉴权方式、模型列表与说明（Codex 预设还可带推理强度与输出详略），
+//!   不合格的条目跳过；清单中的 URL 必须是 https
+//! - 拉取结果缓存 7 天；拉取失败时静默回退到缓存（即使已过期），没有缓存则只用内置预设
+//! - 自动拉取失败后 30 分钟内不再重试，避免每次打开列表都等待超时；手动刷新不受限制
+//! - 清单响应超过 1 MiB 视为异常，直接放弃
+//! - 远程预设与内置预设（由前端提供）共存，同名时以远程为准
+//!
+//! 持久化：~/.anycode/provider_presets.json（清单地址）、
+//! ~/.anycode/provider_presets_cache.json（缓存）
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use super::codex::capabilities::{validate_reasoning_effort, validate_verbosity};
+use super::codex::CodexProviderConfig;
+use super::gemini::provider::GeminiProviderConfig;
+use super::url_utils::validate_https_url;
+use crate::utils::config_utils::{load_json_config, save_json_config, ConfigPathBuilder};
+
+/// 项目维护的默认清单
+pub const DEFAULT_MANIFEST_URL: &str =
+    "https://raw.githubusercontent.com/yunsheng111/Any-code/main/provider-presets.json";
+
+/// 远程预设的来源标记
+pub const SOURCE_REMOTE: &str = "remote";
+
+/// 支持的清单格式版本
+const MANIFEST_VERSION: u32 = 1;
+
+/// 缓存有效期（7 天）
+const CACHE_TTL_SECS: i64 = 7 * 24 * 60 * 60;
+
+/// 拉取清单的超时时间
+const FETCH_TIMEOUT_SECS: u64 = 10;
+
+/// 自动拉取失败后的重试间隔（30 分钟）
+const FAILURE_BACKOFF_SECS: i64 = 30 * 60;
+
+/// 清单响应体的大小上限
+const MAX_MANIFEST_BYTES: usize = 1024 * 1024;
+
+/// 最近一次拉取失败：(清单地址, 时间戳)
+static LAST_FAILURE: Lazy<Mutex<Option<(String, i64)>>> = Lazy::new(|| Mutex::new(None));
+
+/// 单个清单的条目上限，防止异常清单撑爆列表
+const MAX_PRESETS_PER_ENGINE: usize = 100;
+
+/// 远程预设配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderPresetsConfig {
+    /// 清单地址，空字符串表示禁用
+    #[serde(default = "default_manifest_url")]
+    pub manifest_url: String,
+}
+
+fn default_manifest_url() -> String {
+    DEFAULT_MANIFEST_URL.to_string()
+}
+
+impl Default for ProviderPresetsConfig {
+    fn default() -> Self {
+        Self {
+            manifest_url: default_manifest_url(),
+        }
+    }
+}
+
+/// 预设的鉴权方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PresetAuthType {
+    /// 需要用户填写 API Key
+    ApiKey,
+    /// 使用 CLI 自带的登录（官方服务）
+    Oauth,
+}
+
+/// 清单中的一条预设（校验通过后的形态）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoteProviderPreset {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    /// api_key 鉴权时必填
+    #[serde(default)]
+    pub base_url: Option<String>,
+    pub auth_type: PresetAuthType,
+    /// 第一个模型作为默认模型
+    pub models: Vec<String>,
+    #[serde(default)]
+    pub website_url: Option<String>,
+    #[serde(default)]
+    pub category: Option<String>,
+    /// Codex 推理强度（model_reasoning_effort），api_key 鉴权未设置时默认 high
+    #[serde(default)]
+    pub reasoning_effort: Option<String>,
+    /// Codex 输出详略（model_verbosity）
+    #[serde(default)]
+    pub verbosity: Option<String>,
+}
+
+/// 清单文件；条目先按原始 JSON 读取，逐条校验，单个坏条目不影响其他条目
+#[derive(Debug, Deserialize)]
+struct RemoteManifest {
+    version: u32,
+    #[serde(default)]
+    codex: Vec<Value>,
+    #[serde(default)]
+    gemini: Vec<Value>,
+}
+
+/// 校验后的远程预设
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RemotePresets {
+    #[serde(default)]
+    pub codex: Vec<RemoteProviderPreset>,
+    #[serde(default)]
+    pub gemini: Vec<RemoteProviderPreset>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PresetCache {
+    #[serde(default)]
+    manifest_url: String,
+    #[serde(default)]
+    fetched_at: i64,
+    #[serde(default)]
+    presets: RemotePresets,
+}
+
+/// 刷新结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RemotePresetsStatus {
+    pub manifest_url: String,
+    /// 缓存写入时间（Unix 秒），从未成功拉取时为 None
+    pub fetched_at: Option<i64>,
+    pub codex_count: usize,
+    pub gemini_count: usize,
+}
+
+fn get_config_path() -> Result<PathBuf, String> {
+    Ok(ConfigPathBuilder::from_home_subdir(".anycode")?.build("provider_presets.json"))
+}
+
+fn get_cache_path() -> Result<PathBuf, String> {
+    Ok(ConfigPathBuilder::from_home_subdir(".anycode")?.build("provider_presets_cache.json"))
+}
+
+fn load_config() -> ProviderPresetsConfig {
+    get_config_path()
+        .and_then(load_json_config)
+        .unwrap_or_else(|e| {
+            log::warn!("[Provider Presets] Failed to load config: {}", e);
+            ProviderPresetsConfig::default()
+        })
+}
+
+/// 读取对应清单地址的缓存，地址变更后旧缓存作废
+fn load_cache(manifest_url: &str) -> Option<PresetCache> {
+    let cache: PresetCache = get_cache_path().and_then(load_json_config).ok()?;
+    (cache.manifest_url == manifest_url && cache.fetched_at > 0).then_some(cache)
+}
+
+fn status_of(manifest_url: &str, cache: Option<&PresetCache>) -> RemotePresetsStatus {
+    RemotePresetsStatus {
+        manifest_url: manifest_url.to_string(),
+        fetched_at: cache.map(|c| c.fetched_at),
+        codex_count: cache.map_or(0, |c| c.presets.codex.len()),
+        gemini_count: cache.map_or(0, |c| c.presets.gemini.len()),
+    }
+}
+
+/// 校验单条预设
+fn validate_preset(raw: Value) -> Result<RemoteProviderPreset, String> {
+    let mut preset: RemoteProviderPreset =
+        serde_json::from_value(raw).map_err(|e| format!("invalid preset: {}", e))?;
+
+    preset.id = preset.id.trim().to_string();
+    preset.name = preset.name.trim().to_string();
+    preset.description = preset.description.trim().to_string();
+
+    if preset.id.is_empty()
+        || !preset
+            .id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(format!("invalid preset id '{}'", preset.id));
+    }
+    if preset.name.is_empty() {
+        return Err(format!("preset '{}' has no name", preset.id));
+    }
+    if preset.description.is_empty() {
+        return Err(format!("preset '{}' has no description", preset.id));
+    }
+
+    preset.models = preset
+        .models
+        .iter()
+        .map(|m| m.trim().to_string())
+        .filter(|m| !m.is_empty())
+        .collect();
+    if preset.models.is_empty() {
+        return Err(format!("preset '{}' has no models", preset.id));
+    }
+
+    preset.base_url = match preset.base_url.as_deref().map(str::trim) {
+        Some(url) if !url.is_empty() => Some(validate_https_url(url)?),
+        _ => None,
+    };
+    if preset.auth_type == PresetAuthType::ApiKey && preset.base_url.is_none() {
+        return Err(format!("preset '{}' requires a base_url", preset.id));
+    }
+
+    preset.website_url = match preset.website_url.as_deref().map(str::trim) {
+        Some(url) if !url.is_empty() => Some(validate_https_url(url)?),
+        _ => None,
+    };
+
+    preset.reasoning_effort = match preset.reasoning_effort.as_deref().map(str::trim) {
+        Some(effort) if !effort.is_empty() => Some(validate_reasoning_effort(effort)?),
+        _ => None,
+    };
+    preset.verbosity = match preset.verbosity.as_deref().map(str::trim) {
+        Some(verbosity) if !verbosity.is_empty() => Some(validate_verbosity(verbosity)?),
+        _ => None,
+    };
+
+    Ok(preset)
+}
+
+/// 校验一组预设：跳过不合格与重复 id 的条目
+fn validate_presets(engine: &str, raw: Vec<Value>) -> Vec<RemoteProviderPreset> {
+    let mut seen = HashSet::new();
+    let mut presets = Vec::new();
+
+    for entry in raw {
+        match validate_preset(entry) {
+            Ok(preset) if seen.insert(preset.id.clone()) => presets.push(preset),
+            Ok(preset) => {
+                log::warn!(
+                    "[Provider Presets] Skipping duplicate {} preset '{}'",
+                    engine,
+                    preset.id
+                );
+            }
+            Err(e) => log::warn!("[Provider Presets] Skipping {} preset: {}", engine, e),
+        }
+        if presets.len() >= MAX_PRESETS_PER_ENGINE {
+            break;
+        }
+    }
+
+    presets
+}
+
+/// 解析并校验清单
+fn parse_manifest(value: Value) -> Result<RemotePresets, String> {
+    let manifest: RemoteManifest =
+        serde_json::from_value(value).map_err(|e| format!("Invalid preset manifest: {}", e))?;
+
+    if manifest.version != MANIFEST_VERSION {
+        return Err(format!(
+            "Unsupported preset manifest version {} (expected {})",
+            manifest.version, MANIFEST_VERSION
+        ));
+    }
+
+    Ok(RemotePresets {
+        codex: validate_presets("codex", manifest.codex),
+        gemini: validate_presets("gemini", manifest.gemini),
+    })
+}
+
+/// 追加一段响应体，超过大小上限时报错
+fn push_limited(body: &mut Vec<u8>, chunk: &[u8]) -> Result<(), String> {
+    if body.len() + chunk.len() > MAX_MANIFEST_BYTES {
+        return Err(format!(
+            "Preset manifest exceeds {} KiB",
+            MAX_MANIFEST_BYTES / 1024
+        ));
+    }
+    body.extend_from_slice(chunk);
+    Ok(())
+}
+
+/// 同一清单地址在失败后的重试间隔内
+fn in_failure_backoff(last_failure: Option<&(String, i64)>, manifest_url: &str, now: i64) -> bool {
+    last_failure.is_some_and(|(url, failed_at)| {
+        url == manifest_url && now - failed_at < FAILURE_BACKOFF_SECS
+    })
+}
+
+/// 拉取清单并写入缓存
+async fn fetch_and_cache(manifest_url: &str) -> Result<PresetCache, String> {
+    let url = validate_https_url(manifest_url)?;
+
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(FETCH_TIMEOUT_SECS))
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+
+    let mut response = client
+        .get(&url)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to fetch preset manifest: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!(
+            "Failed to fetch preset manifest: HTTP {}",
+            response.status()
+        ));
+    }
+
+    if response
+        .content_length()
+        .is_some_and(|len| len > MAX_MANIFEST_BYTES as u64)
+    {
+        return Err(format!(
+            "Preset manifest exceeds {} KiB",
+            MAX_MANIFEST_BYTES / 1024
+        ));
+    }
+    // Content-Length 可能缺失或不实，按实际读到的字节数再限制一次
+    let mut body = Vec::new();
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|e| format!("Failed to fetch preset manifest: {}", e))?
+    {
+        push_limited(&mut body, &chunk)?;
+    }
+
+    let value: Value = serde_json::from_slice(&body)
+        .map_err(|e| format!("Failed to parse preset manifest: {}", e))?;
+
+    let cache = PresetCache {
+        manifest_url: manifest_url.to_string(),
+        fetched_at: chrono::Utc::now().timestamp(),
+        presets: parse_manifest(value)?,
+    };
+    save_json_config(&cache, get_cache_path()?)?;
+    *LAST_FAILURE.lock().unwrap() = None;
+
+    log::info!(
+        "[Provider Presets] Fetched {} codex and {} gemini presets from {}",
+        cache.presets.codex.len(),
+        cache.presets.gemini.len(),
+        url
+    );
+    Ok(cache)
+}
+
+/// 当前可用的远程预设
+///
+/// 缓存未过期时直接使用；否则重新拉取，失败时回退到旧缓存或空列表，从不报错。
+/// 失败后的重试间隔内不再拉取，直接回退
+pub async fn remote_presets() -> RemotePresets {
+    let manifest_url = load_config().manifest_url.trim().to_string();
+    if manifest_url.is_empty() {
+        return RemotePresets::default();
+    }
+
+    let cached = load_cache(&manifest_url);
+    if let Some(cache) = &cached {
+        if chrono::Utc::now().timestamp() - cache.fetched_at < CACHE_TTL_SECS {
+            return cache.presets.clone();
+        }
+    }
+
+    let now = chrono::Utc::now().timestamp();
+    if in_failure_backoff(LAST_FAILURE.lock().unwrap().as_ref(), &manifest_url, now) {
+        return cached.map(|c| c.presets).unwrap_or_default();
+    }
+
+    match fetch_and_cache(&manifest_url).await {
+        Ok(cache) => cache.presets,
+        Err(e) => {
+            log::warn!("[Provider Presets] {}, using cached presets", e);
+            *LAST_FAILURE.lock().unwrap() = Some((manifest_url, now));
+            cached.map(|c| c.presets).unwrap_or_default()
+        }
+    }
+}
+
+/// 预设分类，未知分类归为第三方
+fn normalize_category(category: Option<&str>, allowed: &[&str]) -> String {
+    category
+        .filter(|c| allowed.contains(c))
+        .unwrap_or("third_party")
+        .to_string()
+}
+
+/// 生成符合 TOML 键名规范的 provider 名称（与前端 generateThirdPartyConfig 一致）
+fn toml_provider_key(id: &str) -> String {
+    let key: String = id
+        .to_lowercase()
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let key = key.trim_matches('_');
+    if key.is_empty() {
+        "custom".to_string()
+    } else {
+        key.to_string()
+    }
+}
+
+/// 生成 Codex config.toml；api_key 鉴权时写入 model_providers 段
+fn codex_config_toml(preset: &RemoteProviderPreset) -> String {
+    use toml_edit::{value, DocumentMut, Item, Table};
+
+    let mut doc = DocumentMut::new();
+    let model = &preset.models[0];
+
+    match preset.base_url.as_deref() {
+        Some(base_url) if preset.auth_type == PresetAuthType::ApiKey => {
+            let key = toml_provider_key(&preset.id);
+            doc["model_provider"] = value(key.as_str());
+            doc["model"] = value(model.as_str());
+            doc["model_reasoning_effort"] =
+                value(preset.reasoning_effort.as_deref().unwrap_or("high"));
+            doc["disable_response_storage"] = value(true);
+
+            let mut provider = Table::new();
+            provider["name"] = value(key.as_str());
+            provider["base_url"] = value(base_url);
+            provider["wire_api"] = value("responses");
+            provider["requires_openai_auth"] = value(true);
+
+            let mut providers = Table::new();
+            providers.set_implicit(true);
+            providers.insert(&key, Item::Table(provider));
+            doc["model_providers"] = Item::Table(providers);
+        }
+        _ => {
+            doc["model"] = value(model.as_str());
+            if let Some(effort) = preset.reasoning_effort.as_deref() {
+                doc["model_reasoning_effort"] = value(effort);
+            }
+        }
+    }
+
+    if let Some(verbosity) = preset.verbosity.as_deref() {
+        doc["model_verbosity"] = value(verbosity);
+    }
+
+    doc.to_string().trim_end().to_string()
+}
+
+/// 远程预设转换为 Codex provider 配置
+pub fn to_codex_provider(preset: &RemoteProviderPreset) -> CodexProviderConfig {
+    let auth = match preset.auth_type {
+        PresetAuthType::ApiKey => serde_json::json!({ "OPENAI_API_KEY": "" }),
+        PresetAuthType::Oauth => serde_json::json!({}),
+    };
+
+    CodexProviderConfig {
+        id: preset.id.clone(),
+        name: preset.name.clone(),
+        description: Some(preset.description.clone()),
+        website_url: preset.website_url.clone(),
+        category: Some(normalize_category(
+            preset.category.as_deref(),
+            &["official", "cn_official", "aggregator", "third_party"],
+        )),
+        auth,
+        config: codex_config_toml(preset),
+        is_official: Some(preset.auth_type == PresetAuthType::Oauth),
+        is_partner: None,
+        created_at: None,
+        custom_headers: None,
+        source: Some(SOURCE_REMOTE.to_string()),
+    }
+}
+
+/// 远程预设转换为 Gemini provider 配置
+pub fn to_gemini_provider(preset: &RemoteProviderPreset) -> GeminiProviderConfig {
+    let mut env = HashMap::new();
+    if let (PresetAuthType::ApiKey, Some(base_url)) = (preset.auth_type, &preset.base_url) {
+        env.insert("GOOGLE_GEMINI_BASE_URL".to_string(), base_url.clone());
+        env.insert("GEMINI_API_KEY".to_string(), String::new());
+        env.insert("GEMINI_MODEL".to_string(), preset.models[0].clone());
+    }
+
+    GeminiProviderConfig {
+        id: preset.id.clone(),
+        name: preset.name.clone(),
+        description: Some(preset.description.clone()),
+        website_url: preset.website_url.clone(),
+        category: Some(normalize_category(
+            preset.category.as_deref(),
+            &["official", "third_party"],
+        )),
+        env,
+        is_official: Some(preset.auth_type == PresetAuthType::Oauth),
+        is_partner: None,
+        created_at: None,
+        custom_headers: None,
+        source: Some(SOURCE_REMOTE.to_string()),
+    }
+}
+
+/// 获取远程预设配置
+#[tauri::command]
+pub async fn get_provider_presets_config() -> Result<ProviderPresetsConfig, String> {
+    Ok(load_config())
+}
+
+/// 更新远程预设配置；清单地址为空表示禁用
+#[tauri::command]
+pub async fn update_provider_presets_config(
+    config: ProviderPresetsConfig,
+) -> Result<ProviderPresetsConfig, String> {
+    let manifest_url = config.manifest_url.trim();
+    let manifest_url = if manifest_url.is_empty() {
+        String::new()
+    } else {
+        validate_https_url(manifest_url)?
+    };
+
+    let config = ProviderPresetsConfig { manifest_url };
+    save_json_config(&config, get_config_path()?)?;
+    Ok(config)
+}
+
+/// 立即重新拉取清单（忽略缓存有效期）
+///
+/// 与列表加载不同，手动刷新失败时返回错误
+#[tauri::command]
+pub async fn refresh_provider_presets() -> Result<RemotePresetsStatus, String> {
+    let manifest_url = load_config().manifest_url.trim().to_string();
+    if manifest_url.is_empty() {
+        return Ok(status_of(&manifest_url, None));
+    }
+
+    let cache = fetch_and_cache(&manifest_url).await?;
+    Ok(status_of(&manifest_url, Some(&cache)))
+}
+
+/// 获取远程预设缓存状态（不触发拉取）
+#[tauri::command]
+pub async fn get_provider_presets_status() -> Result<RemotePresetsStatus, String> {
+    let manifest_url = load_config().manifest_url.trim().to_string();
+    let cache = load_cache(&manifest_url);
+    Ok(status_of(&manifest_url, cache.as_ref()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn manifest_validation_skips_invalid_entries() {
+        let presets = parse_manifest(json!({
+            "version": 1,
+            "codex": [
+                {
+                    "id": "relay",
+                    "name": "Relay",
+                    "description": "A relay",
+                    "baseUrl": "https://relay.example.com/v1/",
+                    "authType": "api_key",
+                    "models": ["gpt-5-codex", " "]
+                },
+                { "id": "plain-http", "name": "Plain", "description": "x",
+                  "baseUrl": "http://relay.example.com", "authType": "api_key", "models": ["m"] },
+                { "id": "no-url", "name": "No URL", "description": "x",
+                  "authType": "api_key", "models": ["m"] },
+                { "id": "no-models", "name": "No models", "description": "x",
+                  "baseUrl": "https://x.example.com", "authType": "api_key", "models": [] },
+                { "id": "relay", "name": "Duplicate", "description": "x",
+                  "baseUrl": "https://x.example.com", "authType": "api_key", "models": ["m"] },
+                { "name": "Missing id" }
+            ],
+            "gemini": [
+                { "id": "official", "name": "Official", "description": "OAuth login",
+                  "authType": "oauth", "models": ["gemini-3-pro-preview"] }
+            ]
+        }))
+        .unwrap();
+
+        assert_eq!(presets.codex.len(), 1);
+        assert_eq!(
+            presets.codex[0].base_url.as_deref(),
+            Some("https://relay.example.com/v1")
+        );
+        assert_eq!(presets.codex[0].models, vec!["gpt-5-codex"]);
+        assert_eq!(presets.gemini.len(), 1);
+
+        assert!(parse_manifest(json!({ "version": 2, "codex": [] })).is_err());
+    }
+
+    #[test]
+    fn remote_presets_convert_to_provider_configs() {
+        let preset = RemoteProviderPreset {
+            id: "my-relay".to_string(),
+            name: "My Relay".to_string(),
+            description: "A relay".to_string(),
+            base_url: Some("https://relay.example.com/v1".to_string()),
+            auth_type: PresetAuthType::ApiKey,
+            models: vec!["gpt-5-codex".to_string()],
+            website_url: None,
+            category: Some("unknown".to_string()),
+            reasoning_effort: None,
+            verbosity: Some("low".to_string()),
+        };
+
+        let codex = to_codex_provider(&preset);
+        let config: toml::Value = toml::from_str(&codex.config).unwrap();
+        assert_eq!(config["model_provider"].as_str(), Some("my_relay"));
+        assert_eq!(config["model"].as_str(), Some("gpt-5-codex"));
+        assert_eq!(config["model_reasoning_effort"].as_str(), Some("high"));
+        assert_eq!(config["model_verbosity"].as_str(), Some("low"));
+        assert_eq!(
+            config["model_providers"]["my_relay"]["base_url"].as_str(),
+            Some("https://relay.example.com/v1")
+        );
+        assert_eq!(codex.category.as_deref(), Some("third_party"));
+        assert_eq!(codex.source.as_deref(), Some(SOURCE_REMOTE));
+
+        let gemini = to_gemini_provider(&preset);
+        assert_eq!(
+            gemini.env.get("GOOGLE_GEMINI_BASE_URL").map(String::as_str),
+            Some("https://relay.example.com/v1")
+        );
+        assert_eq!(
+            gemini.env.get("GEMINI_MODEL").map(String::as_str),
+            Some("gpt-5-codex")
+        );
+    }
+
+    #[test]
+    fn failed_fetches_back_off_per_manifest_url() {
+        let failure = ("https://a.example.com/presets.json".to_string(), 1_000);
+        let url = failure.0.clone();
+
+        assert!(!in_failure_backoff(None, &url, 1_000));
+        assert!(in_failure_backoff(Some(&failure), &url, 1_000 + 60));
+        assert!(!in_failure_backoff(
+            Some(&failure),
+            &url,
+            1_000 + FAILURE_BACKOFF_SECS
+        ));
+        assert!(!in_failure_backoff(
+            Some(&failure),
+            "https://b.example.com/presets.json",
+            1_000 + 60
+        ));
+    }
+
+    #[test]
+    fn manifest_body_is_capped() {
+        let mut body = Vec::new();
+        push_limited(&mut body, &vec![b' '; MAX_MANIFEST_BYTES - 1]).unwrap();
+        push_limited(&mut body, b"{").unwrap();
+        assert!(push_limited(&mut body, b"}").is_err());
+        assert_eq!(body.len(), MAX_MANIFEST_BYTES);
+    }
+}
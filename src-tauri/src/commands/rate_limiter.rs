@@ -0,0 +1,575 @@
+//! provider 维度的客户端限流
+//!
+//! 批量任务、翻译、记忆提取等同时请求同一个中转站时容易触发服务端限流甚至被封 key。
+//! 每个 provider（按 base URL 的 origin 区分）可配置最大并发数与每分钟请求数：
+//! - 并发：信号量，请求结束释放后放行下一个
+//! - 频率：令牌桶，每秒补充 `requests_per_minute / 60` 个，容量为一秒的量，请求被平滑放行
+//! - 超限的请求按到达顺序排队，超过最长等待时间返回 `RATE_LIMITED` 错误
+//! - 服务端返回 429 时按 Retry-After 暂停该 provider，并把动态限额临时减半，
+//!   冷却期后恢复配置值；同时发送 `provider-rate-limited` 事件
+//!
+//! 经由 provider 的 HTTP 请求通过 `send` 发送，CLI 任务启动前调用 `acquire_cli_launch`。
+//!
+//! 持久化：~/.anycode/provider_rate_limits.json
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tokio::time::Instant;
+
+use super::messages::{t_with, MessageKey};
+use crate::utils::config_utils::{load_json_config, save_json_config, ConfigPathBuilder};
+
+/// 排队超时错误的前缀（小写后命中 app_error 的 rate_limit 特征，归类为 RateLimited）
+pub const RATE_LIMITED_ERROR_PREFIX: &str = "RATE_LIMITED";
+
+/// 未配置时的最长排队等待（秒）
+const DEFAULT_MAX_WAIT_SECS: u64 = 60;
+/// 429 未携带 Retry-After 时的退避时长
+const DEFAULT_RETRY_AFTER: Duration = Duration::from_secs(10);
+/// 单次 Retry-After 退避的上限，避免异常响应长时间卡住队列
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(300);
+/// 429 后动态限额的冷却期
+const THROTTLE_COOLDOWN: Duration = Duration::from_secs(300);
+/// `send` 收到 429 后的自动重试次数
+const MAX_RATE_LIMITED_RETRIES: usize = 2;
+/// 统计最近请求数的窗口
+const RECENT_WINDOW: Duration = Duration::from_secs(60);
+
+static CONFIG: Lazy<Mutex<RateLimitConfig>> = Lazy::new(|| {
+    Mutex::new(
+        get_config_path()
+            .and_then(load_json_config)
+            .unwrap_or_default(),
+    )
+});
+
+static LIMITERS: Lazy<Mutex<HashMap<String, Arc<ProviderLimiter>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// 用于发送 429 事件，启动时由 `init` 设置
+static APP_HANDLE: OnceLock<AppHandle> = OnceLock::new();
+
+/// 单个 provider 的限额
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderRateLimit {
+    /// provider 的 base URL（按 origin 匹配，路径部分不影响）
+    pub base_url: String,
+    /// 最大并发请求数，None 表示不限
+    #[serde(default)]
+    pub max_concurrent_requests: Option<u32>,
+    /// 每分钟请求数，None 表示不限
+    #[serde(default)]
+    pub requests_per_minute: Option<u32>,
+}
+
+/// 限流配置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RateLimitConfig {
+    /// 排队的最长等待时间（秒）
+    pub max_wait_secs: u64,
+    pub providers: Vec<ProviderRateLimit>,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            max_wait_secs: DEFAULT_MAX_WAIT_SECS,
+            providers: Vec::new(),
+        }
+    }
+}
+
+/// 某个 provider 限流器的当前状态
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderLimiterStatus {
+    pub provider: String,
+    pub max_concurrent_requests: Option<u32>,
+    pub requests_per_minute: Option<u32>,
+    /// 429 后临时下调的每分钟请求数，未下调时为 None
+    pub throttled_requests_per_minute: Option<u32>,
+    pub in_flight: usize,
+    pub queued: usize,
+    pub requests_last_minute: usize,
+    /// Retry-After 退避的剩余秒数
+    pub backoff_remaining_secs: u64,
+}
+
+/// 429 事件
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderRateLimitedEvent {
+    pub provider: String,
+    pub retry_after_secs: u64,
+    pub throttled_requests_per_minute: u32,
+}
+
+fn get_config_path() -> Result<PathBuf, String> {
+    Ok(ConfigPathBuilder::from_home_subdir(".anycode")?.build("provider_rate_limits.json"))
+}
+
+/// 以 base URL 的 origin（小写）作为 provider 标识，无法解析时使用去掉末尾 / 的原文
+pub fn provider_key(base_url: &str) -> String {
+    let trimmed = base_url.trim();
+    match reqwest::Url::parse(trimmed) {
+        Ok(url) if url.has_host() => url.origin().ascii_serialization().to_lowercase(),
+        _ => trimmed.trim_end_matches('/').to_lowercase(),
+    }
+}
+
+/// 持有期间占用一个并发名额
+pub struct RatePermit {
+    _permit: Option<OwnedSemaphorePermit>,
+}
+
+/// 令牌桶与请求记录
+#[derive(Debug)]
+struct LimiterState {
+    tokens: f64,
+    last_refill: Instant,
+    recent: VecDeque<Instant>,
+    backoff_until: Option<Instant>,
+    /// 429 后临时下调的每分钟请求数及其失效时间
+    throttle: Option<(u32, Instant)>,
+}
+
+/// 单个 provider 的限流器
+struct ProviderLimiter {
+    limits: ProviderRateLimit,
+    semaphore: Option<Arc<Semaphore>>,
+    /// 等待令牌的轮次（tokio Mutex 先到先得），保证排队顺序
+    turn: tokio::sync::Mutex<()>,
+    state: Mutex<LimiterState>,
+    queued: AtomicUsize,
+}
+
+/// 排队计数，离开队列（拿到名额或超时）时减一
+struct QueuedGuard<'a>(&'a AtomicUsize);
+
+impl Drop for QueuedGuard<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+impl ProviderLimiter {
+    fn new(limits: ProviderRateLimit) -> Self {
+        let semaphore = limits
+            .max_concurrent_requests
+            .filter(|n| *n > 0)
+            .map(|n| Arc::new(Semaphore::new(n as usize)));
+        let capacity = limits
+            .requests_per_minute
+            .map(bucket_capacity)
+            .unwrap_or(1.0);
+        Self {
+            limits,
+            semaphore,
+            turn: tokio::sync::Mutex::new(()),
+            state: Mutex::new(LimiterState {
+                tokens: capacity,
+                last_refill: Instant::now(),
+                recent: VecDeque::new(),
+                backoff_until: None,
+                throttle: None,
+            }),
+            queued: AtomicUsize::new(0),
+        }
+    }
+
+    /// 当前生效的每分钟请求数（冷却期内取下调后的值）
+    fn effective_rpm(&self, state: &mut LimiterState, now: Instant) -> Option<u32> {
+        if let Some((rpm, until)) = state.throttle {
+            if now < until {
+                return Some(rpm);
+            }
+            state.throttle = None;
+        }
+        self.limits.requests_per_minute.filter(|rpm| *rpm > 0)
+    }
+
+    /// 尝试取一个令牌；取不到时返回需要等待的时长
+    fn try_take(&self, now: Instant) -> Option<Duration> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+
+        if let Some(until) = state.backoff_until {
+            if now < until {
+                return Some(until - now);
+            }
+            state.backoff_until = None;
+        }
+
+        if let Some(rpm) = self.effective_rpm(&mut state, now) {
+            let rate = rpm as f64 / 60.0;
+            let elapsed = now.duration_since(state.last_refill).as_secs_f64();
+            state.tokens = (state.tokens + elapsed * rate).min(bucket_capacity(rpm));
+            state.last_refill = now;
+            if state.tokens < 1.0 {
+                return Some(Duration::from_secs_f64((1.0 - state.tokens) / rate));
+            }
+            state.tokens -= 1.0;
+        }
+
+        state.recent.push_back(now);
+        None
+    }
+
+    /// 排队获取并发名额与令牌，超过 `max_wait` 返回 Err
+    async fn acquire(&self, max_wait: Duration) -> Result<RatePermit, ()> {
+        self.queued.fetch_add(1, Ordering::Relaxed);
+        let _queued = QueuedGuard(&self.queued);
+        let deadline = Instant::now() + max_wait;
+
+        let waited = tokio::time::timeout_at(deadline, async {
+            let permit = match &self.semaphore {
+                Some(semaphore) => Some(semaphore.clone().acquire_owned().await.map_err(|_| ())?),
+                None => None,
+            };
+            let _turn = self.turn.lock().await;
+            while let Some(wait) = self.try_take(Instant::now()) {
+                tokio::time::sleep(wait).await;
+            }
+            Ok(RatePermit { _permit: permit })
+        })
+        .await;
+
+        waited.map_err(|_| ())?
+    }
+
+    /// 记录一次 429：暂停到 Retry-After 之后，并把动态限额减半
+    fn record_rate_limited(&self, retry_after: Duration) -> u32 {
+        let now = Instant::now();
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+
+        let until = now + retry_after;
+        state.backoff_until = Some(state.backoff_until.map_or(until, |b| b.max(until)));
+
+        let current = self.effective_rpm(&mut state, now).unwrap_or_else(|| {
+            let recent = recent_count(&mut state.recent, now);
+            u32::try_from(recent).unwrap_or(u32::MAX)
+        });
+        let throttled = (current / 2).max(1);
+        state.throttle = Some((throttled, now + THROTTLE_COOLDOWN));
+        state.tokens = 0.0;
+        state.last_refill = until;
+        throttled
+    }
+
+    fn status(&self, provider: &str) -> ProviderLimiterStatus {
+        let now = Instant::now();
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let throttled = state
+            .throttle
+            .filter(|(_, until)| now < *until)
+            .map(|(rpm, _)| rpm);
+        let in_flight = match (&self.semaphore, self.limits.max_concurrent_requests) {
+            (Some(semaphore), Some(max)) => {
+                (max as usize).saturating_sub(semaphore.available_permits())
+            }
+            _ => 0,
+        };
+
+        ProviderLimiterStatus {
+            provider: provider.to_string(),
+            max_concurrent_requests: self.limits.max_concurrent_requests,
+            requests_per_minute: self.limits.requests_per_minute,
+            throttled_requests_per_minute: throttled,
+            in_flight,
+            queued: self.queued.load(Ordering::Relaxed),
+            requests_last_minute: recent_count(&mut state.recent, now),
+            backoff_remaining_secs: state
+                .backoff_until
+                .map(|until| until.saturating_duration_since(now).as_secs())
+                .unwrap_or(0),
+        }
+    }
+}
+
+/// 令牌桶容量：一秒的补充量，至少 1
+fn bucket_capacity(rpm: u32) -> f64 {
+    (rpm as f64 / 60.0).ceil().max(1.0)
+}
+
+/// 丢弃窗口外的记录并返回最近一分钟的请求数
+fn recent_count(recent: &mut VecDeque<Instant>, now: Instant) -> usize {
+    while recent
+        .front()
+        .is_some_and(|t| now.duration_since(*t) >= RECENT_WINDOW)
+    {
+        recent.pop_front();
+    }
+    recent.len()
+}
+
+/// 取 provider 的限流器；配置变更后重建（进行中的请求继续持有旧名额）
+fn limiter_for(key: &str) -> (Arc<ProviderLimiter>, Duration) {
+    let (limits, max_wait) = {
+        let config = CONFIG.lock().unwrap_or_else(|e| e.into_inner());
+        let limits = config
+            .providers
+            .iter()
+            .find(|p| provider_key(&p.base_url) == key)
+            .cloned()
+            .map(|p| ProviderRateLimit {
+                base_url: key.to_string(),
+                ..p
+            })
+            .unwrap_or_else(|| ProviderRateLimit {
+                base_url: key.to_string(),
+                ..Default::default()
+            });
+        (limits, Duration::from_secs(config.max_wait_secs))
+    };
+
+    let mut limiters = LIMITERS.lock().unwrap_or_else(|e| e.into_inner());
+    let limiter = match limiters.get(key) {
+        Some(existing) if existing.limits == limits => existing.clone(),
+        _ => {
+            let limiter = Arc::new(ProviderLimiter::new(limits));
+            limiters.insert(key.to_string(), limiter.clone());
+            limiter
+        }
+    };
+    (limiter, max_wait)
+}
+
+/// 排队等待 provider 的请求名额，超时返回 `RATE_LIMITED` 错误
+pub async fn acquire(base_url: &str) -> Result<RatePermit, String> {
+    let key = provider_key(base_url);
+    let (limiter, max_wait) = limiter_for(&key);
+    limiter.acquire(max_wait).await.map_err(|_| {
+        log::warn!("[RateLimiter] Waited more than {:?} for {}", max_wait, key);
+        format!(
+            "{}: {}",
+            RATE_LIMITED_ERROR_PREFIX,
+            t_with(
+                MessageKey::RateLimitWaitTimeout,
+                &[("provider", &key), ("secs", &max_wait.as_secs())],
+            )
+        )
+    })
+}
+
+/// 记录 provider 返回的 429，并通知前端
+pub fn report_rate_limited(base_url: &str, retry_after: Option<Duration>) {
+    let key = provider_key(base_url);
+    let retry_after = retry_after
+        .unwrap_or(DEFAULT_RETRY_AFTER)
+        .min(MAX_RETRY_AFTER);
+    let (limiter, _) = limiter_for(&key);
+    let throttled = limiter.record_rate_limited(retry_after);
+    log::warn!(
+        "[RateLimiter] {} returned 429, backing off {:?} and throttling to {} req/min",
+        key,
+        retry_after,
+        throttled
+    );
+
+    if let Some(app) = APP_HANDLE.get() {
+        let _ = app.emit(
+            "provider-rate-limited",
+            ProviderRateLimitedEvent {
+                provider: key,
+                retry_after_secs: retry_after.as_secs(),
+                throttled_requests_per_minute: throttled,
+            },
+        );
+    }
+}
+
+/// 解析 Retry-After 头（秒数形式）
+fn retry_after(response: &reqwest::Response) -> Option<Duration> {
+    response
+        .headers()
+        .get(reqwest::header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse::<f64>()
+        .ok()
+        .filter(|secs| secs.is_finite() && *secs >= 0.0)
+        .map(Duration::from_secs_f64)
+}
+
+/// 经限流器发送请求；收到 429 时记录退避并自动重试，重试用尽后原样返回响应
+pub async fn send(
+    base_url: &str,
+    mut request: reqwest::RequestBuilder,
+) -> Result<reqwest::Response, String> {
+    let mut attempt = 0;
+    loop {
+        // 请求体不可复制时（流式 body）只发送一次
+        let retry = if attempt < MAX_RATE_LIMITED_RETRIES {
+            request.try_clone()
+        } else {
+            None
+        };
+
+        let permit = acquire(base_url).await?;
+        let response = request.send().await.map_err(|e| e.to_string())?;
+        drop(permit);
+
+        if response.status() != reqwest::StatusCode::TOO_MANY_REQUESTS {
+            return Ok(response);
+        }
+        report_rate_limited(base_url, retry_after(&response));
+        match retry {
+            Some(next) => {
+                request = next;
+                attempt += 1;
+            }
+            None => return Ok(response),
+        }
+    }
+}
+
+/// CLI 任务启动前按当前 provider 排队（只限制启动频率，不占用并发名额）
+///
+/// 使用官方默认端点（未配置 base URL）时不限流
+pub async fn acquire_cli_launch(engine: &str) -> Result<(), String> {
+    let base_url = match engine {
+        "claude" => super::provider::get_current_provider_config()
+            .ok()
+            .and_then(|c| c.anthropic_base_url),
+        "codex" => super::codex::config::get_current_codex_config()
+            .await
+            .ok()
+            .and_then(|c| c.base_url),
+        "gemini" => super::gemini::provider::get_current_gemini_provider_config()
+            .await
+            .ok()
+            .and_then(|c| c.base_url),
+        _ => None,
+    };
+
+    match base_url.filter(|url| !url.trim().is_empty()) {
+        Some(base_url) => acquire(&base_url).await.map(drop),
+        None => Ok(()),
+    }
+}
+
+/// 启动时保存 AppHandle，用于发送 429 事件
+pub fn init(app: AppHandle) {
+    let _ = APP_HANDLE.set(app);
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// 获取限流配置
+#[tauri::command]
+pub async fn get_provider_rate_limits() -> Result<RateLimitConfig, String> {
+    Ok(CONFIG.lock().map_err(|e| e.to_string())?.clone())
+}
+
+/// 更新限流配置，立即对后续请求生效
+#[tauri::command]
+pub async fn update_provider_rate_limits(config: RateLimitConfig) -> Result<(), String> {
+    let mut config = config;
+    config.max_wait_secs = config.max_wait_secs.max(1);
+    config.providers.retain(|p| !p.base_url.trim().is_empty());
+
+    save_json_config(&config, get_config_path()?)?;
+    *CONFIG.lock().map_err(|e| e.to_string())? = config;
+    Ok(())
+}
+
+/// 查询各 provider 限流器的状态（排队数、最近一分钟请求数等）
+#[tauri::command]
+pub async fn get_rate_limiter_status() -> Result<Vec<ProviderLimiterStatus>, String> {
+    let limiters = LIMITERS.lock().map_err(|e| e.to_string())?;
+    let mut statuses: Vec<_> = limiters
+        .iter()
+        .map(|(key, limiter)| limiter.status(key))
+        .collect();
+    statuses.sort_by(|a, b| a.provider.cmp(&b.provider));
+    Ok(statuses)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::app_error::{AppError, ErrorCode};
+
+    fn limiter(max_concurrent: Option<u32>, rpm: Option<u32>) -> ProviderLimiter {
+        ProviderLimiter::new(ProviderRateLimit {
+            base_url: "https://relay.example.com".to_string(),
+            max_concurrent_requests: max_concurrent,
+            requests_per_minute: rpm,
+        })
+    }
+
+    #[test]
+    fn providers_are_keyed_by_origin() {
+        assert_eq!(
+            provider_key("https://Relay.example.com/v1/"),
+            "https://relay.example.com"
+        );
+        assert_eq!(
+            provider_key("http://localhost:8080/api"),
+            "http://localhost:8080"
+        );
+    }
+
+    #[tokio::test]
+    async fn concurrent_requests_are_smoothed_to_the_configured_rate() {
+        // 10 req/s：前 10 个立即放行，其余按 100ms 间隔排队
+        let limiter = Arc::new(limiter(None, Some(600)));
+        let started = Instant::now();
+        let requests = (0..15).map(|_| {
+            let limiter = limiter.clone();
+            tokio::spawn(async move {
+                limiter.acquire(Duration::from_secs(5)).await.unwrap();
+                Instant::now()
+            })
+        });
+        let mut finished: Vec<Instant> = futures::future::join_all(requests)
+            .await
+            .into_iter()
+            .map(|r| r.unwrap())
+            .collect();
+        finished.sort();
+
+        assert!(finished[9] - started < Duration::from_millis(50));
+        assert!(finished[14] - started >= Duration::from_millis(450));
+        assert_eq!(limiter.status("p").requests_last_minute, 15);
+        assert_eq!(limiter.status("p").queued, 0);
+    }
+
+    #[tokio::test]
+    async fn waiting_past_the_deadline_is_rate_limited() {
+        let limiter = limiter(Some(1), None);
+        let _held = limiter.acquire(Duration::from_secs(1)).await.unwrap();
+        assert_eq!(limiter.status("p").in_flight, 1);
+
+        assert!(limiter.acquire(Duration::from_millis(50)).await.is_err());
+        assert_eq!(limiter.status("p").queued, 0);
+
+        let error = format!("{}: waited too long", RATE_LIMITED_ERROR_PREFIX);
+        assert_eq!(AppError::from(error).code, ErrorCode::RateLimited);
+    }
+
+    #[tokio::test]
+    async fn rate_limited_response_backs_off_and_halves_the_rate() {
+        let limiter = limiter(None, Some(600));
+        let throttled = limiter.record_rate_limited(Duration::from_millis(200));
+        assert_eq!(throttled, 300);
+
+        let status = limiter.status("p");
+        assert_eq!(status.throttled_requests_per_minute, Some(300));
+
+        let started = Instant::now();
+        limiter.acquire(Duration::from_secs(2)).await.unwrap();
+        assert!(started.elapsed() >= Duration::from_millis(190));
+    }
+}
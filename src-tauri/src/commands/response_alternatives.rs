@@ -0,0 +1,587 @@
+//! 回复的多版本重新生成
+//!
+//! 对某条提示词"再生成一次"而不删除原有历史：
+//! - 把会话 fork 到该提示词之前（复制之前的记录到新会话文件），再通过各引擎既有的执行命令
+//!   在 fork 中重发同一提示词；原会话保持不变
+//! - fork 会话记录在原会话标注的 response_alternatives 中，同一提示词的多个版本可并排查看
+//! - 采用某个版本时交换两个会话文件在该提示词之后的内容：主会话沿所选版本继续，
+//!   被替换的原回复转存到 fork 会话中，仍可再次采用。写入前两个文件都会备份
+//!
+//! 仅支持 Claude / Codex；Gemini 会话按项目哈希存储且没有逐行记录，无法 fork
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::AppHandle;
+
+use super::claude::encode_project_path;
+use super::codex::git_ops::{
+    find_codex_prompt_lines, get_codex_prompt_text, truncate_codex_git_records,
+};
+use super::prompt_history::{dispatch_prompt, PromptTarget};
+use super::prompt_tracker::{extract_prompts_from_jsonl, find_prompt_lines, truncate_git_records};
+use super::session_annotations::{
+    add_response_alternative, load_response_alternatives, ResponseAlternative,
+};
+use super::session_utils::resolve_session_file;
+use crate::utils::jsonl_reader::{ensure_loadable_in_memory, read_to_string_lossy};
+use crate::utils::session_lock::{write_session_file, FileSnapshot, SessionLock};
+use crate::utils::text::truncate_chars;
+
+/// 版本摘要中回复文本的最大字符数
+const REPLY_PREVIEW_MAX_CHARS: usize = 4000;
+
+/// 重新生成的执行选项
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RegenerateOptions {
+    /// 会话所属项目路径
+    pub project_path: String,
+    /// 使用的模型（为空时使用引擎默认模型）
+    #[serde(default)]
+    pub model: Option<String>,
+    /// 执行模式，含义同 prompt_history::dispatch_prompt
+    #[serde(default)]
+    pub mode: Option<String>,
+}
+
+/// 同一提示词的一个回复版本
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResponseVersion {
+    /// 版本所在会话
+    pub session_id: String,
+    /// 是否为主会话（采用后主会话的内容即为所选版本）
+    pub is_mainline: bool,
+    /// 重新生成的时间（Unix 秒），主会话为 None
+    pub created_at: Option<i64>,
+    /// 回复文本（超出上限时截断）
+    pub reply_preview: String,
+    /// 回复中 assistant 消息的条数，为 0 表示尚未生成或生成失败
+    pub message_count: usize,
+    /// 会话文件是否仍然存在
+    pub available: bool,
+}
+
+/// 对会话中的第 `prompt_index` 条提示词重新生成回复
+///
+/// 返回承载新版本的 fork 会话 ID；执行在后台进行，完成后通过 `get_response_alternatives` 查看
+#[tauri::command]
+pub async fn regenerate_response(
+    app: AppHandle,
+    engine: String,
+    session_id: String,
+    prompt_index: usize,
+    options: RegenerateOptions,
+) -> Result<String, String> {
+    log::info!(
+        "regenerate_response called: engine={}, session_id={}, prompt_index={}",
+        engine,
+        session_id,
+        prompt_index
+    );
+    ensure_forkable(&engine)?;
+
+    let project_id = project_id_for(&engine, &options.project_path);
+    let source_path = resolve_session_file(&engine, &session_id, &project_id)?;
+    ensure_loadable_in_memory(&source_path)?;
+    let content = read_to_string_lossy(&source_path)
+        .map_err(|e| format!("Failed to read session file: {}", e))?;
+    let lines: Vec<&str> = content.lines().collect();
+
+    let prompt_line = *prompt_lines(&engine, &lines)
+        .get(prompt_index)
+        .ok_or_else(|| format!("Prompt #{} not found in session", prompt_index))?;
+    if lines[..prompt_line].iter().all(|l| l.trim().is_empty()) {
+        return Err(format!(
+            "提示词 #{} 之前没有会话记录，无法 fork，请直接新建会话发送",
+            prompt_index
+        ));
+    }
+    let prompt = prompt_text(&engine, &session_id, &project_id, prompt_index)?;
+
+    let fork_id = uuid::Uuid::new_v4().to_string();
+    let fork_path = fork_file_path(&engine, &source_path, &fork_id)?;
+    let fork_content = join_lines(
+        lines[..prompt_line]
+            .iter()
+            .map(|line| rewrite_session_id(&engine, line, &fork_id)),
+    );
+    fs::write(&fork_path, fork_content)
+        .map_err(|e| format!("Failed to write forked session {:?}: {}", fork_path, e))?;
+    log::info!(
+        "[Regenerate] Forked {} session {} before prompt #{} into {:?}",
+        engine,
+        session_id,
+        prompt_index,
+        fork_path
+    );
+
+    if let Err(e) = dispatch_prompt(
+        app,
+        &engine,
+        &options.project_path,
+        PromptTarget::Resume(fork_id.clone()),
+        &prompt,
+        options.model,
+        options.mode,
+    )
+    .await
+    {
+        let _ = fs::remove_file(&fork_path);
+        return Err(e);
+    }
+
+    add_response_alternative(
+        &engine,
+        &session_id,
+        prompt_index,
+        ResponseAlternative {
+            session_id: fork_id.clone(),
+            created_at: chrono::Utc::now().timestamp(),
+        },
+    )?;
+
+    Ok(fork_id)
+}
+
+/// 获取某条提示词的所有回复版本摘要，主会话排在最前
+#[tauri::command]
+pub async fn get_response_alternatives(
+    engine: String,
+    session_id: String,
+    prompt_index: usize,
+    project_path: String,
+) -> Result<Vec<ResponseVersion>, String> {
+    let project_id = project_id_for(&engine, &project_path);
+    let alternatives = load_response_alternatives(&engine, &session_id, prompt_index)?;
+
+    let mut versions = Vec::with_capacity(alternatives.len() + 1);
+    versions.push(read_version(
+        &engine,
+        &session_id,
+        &project_id,
+        prompt_index,
+        None,
+    ));
+    for alternative in alternatives {
+        versions.push(read_version(
+            &engine,
+            &alternative.session_id,
+            &project_id,
+            prompt_index,
+            Some(alternative.created_at),
+        ));
+    }
+    Ok(versions)
+}
+
+/// 采用某个回复版本作为主线
+///
+/// 交换主会话与 fork 会话在该提示词之后的内容，写入前备份两个文件为 `*.bak-<时间戳>`；
+/// 主会话中该提示词之后的 Git 记录随之失效并被清除
+#[tauri::command]
+pub async fn adopt_alternative(
+    engine: String,
+    session_id: String,
+    prompt_index: usize,
+    alternative_session_id: String,
+    project_path: String,
+) -> Result<(), String> {
+    log::info!(
+        "adopt_alternative called: engine={}, session_id={}, prompt_index={}, alternative={}",
+        engine,
+        session_id,
+        prompt_index,
+        alternative_session_id
+    );
+    ensure_forkable(&engine)?;
+
+    let alternatives = load_response_alternatives(&engine, &session_id, prompt_index)?;
+    if !alternatives
+        .iter()
+        .any(|a| a.session_id == alternative_session_id)
+    {
+        return Err(format!(
+            "会话 {} 不是提示词 #{} 的回复版本",
+            alternative_session_id, prompt_index
+        ));
+    }
+
+    let project_id = project_id_for(&engine, &project_path);
+    let main_path = resolve_session_file(&engine, &session_id, &project_id)?;
+    let alt_path = resolve_session_file(&engine, &alternative_session_id, &project_id)?;
+
+    // 两个会话都可能正在执行，持有锁直到写入完成
+    let _main_lock = SessionLock::acquire(&main_path, "adopt alternative")?;
+    let _alt_lock = SessionLock::acquire(&alt_path, "adopt alternative")?;
+
+    ensure_loadable_in_memory(&main_path)?;
+    ensure_loadable_in_memory(&alt_path)?;
+    let main_snapshot = FileSnapshot::capture(&main_path)?;
+    let alt_snapshot = FileSnapshot::capture(&alt_path)?;
+    let main_content = read_to_string_lossy(&main_path)
+        .map_err(|e| format!("Failed to read session file: {}", e))?;
+    let alt_content = read_to_string_lossy(&alt_path)
+        .map_err(|e| format!("Failed to read session file: {}", e))?;
+
+    // Claude 的 Git 记录按提示词序号保存，需要交换前的提示词列表
+    let claude_prompts = if engine == "claude" {
+        extract_prompts_from_jsonl(&session_id, &project_id).map_err(|e| e.to_string())?
+    } else {
+        Vec::new()
+    };
+
+    let (new_main, new_alt) = swap_tails(
+        &engine,
+        (&main_content, &session_id),
+        (&alt_content, &alternative_session_id),
+        prompt_index,
+    )?;
+
+    let timestamp = chrono::Utc::now().timestamp();
+    backup_session_file(&main_path, timestamp)?;
+    backup_session_file(&alt_path, timestamp)?;
+
+    write_session_file(&main_path, &main_snapshot, new_main)?;
+    write_session_file(&alt_path, &alt_snapshot, new_alt)?;
+
+    match engine.as_str() {
+        "claude" => truncate_git_records(&session_id, &project_id, &claude_prompts, prompt_index)
+            .map_err(|e| e.to_string())?,
+        _ => truncate_codex_git_records(&session_id, prompt_index)?,
+    }
+
+    log::info!(
+        "[Regenerate] Adopted {} as mainline of {} session {} at prompt #{}",
+        alternative_session_id,
+        engine,
+        session_id,
+        prompt_index
+    );
+    Ok(())
+}
+
+fn ensure_forkable(engine: &str) -> Result<(), String> {
+    match engine {
+        "claude" | "codex" => Ok(()),
+        "gemini" => Err("Gemini 会话暂不支持重新生成回复".to_string()),
+        other => Err(format!("Unknown engine: {}", other)),
+    }
+}
+
+/// Claude 会话按编码后的项目路径定位；Codex 只按会话 ID 定位
+fn project_id_for(engine: &str, project_path: &str) -> String {
+    if engine == "claude" {
+        encode_project_path(project_path)
+    } else {
+        String::new()
+    }
+}
+
+/// 每条真实用户提示词所在的行号，与撤回使用的提示词编号一致
+fn prompt_lines(engine: &str, lines: &[&str]) -> Vec<usize> {
+    if engine == "claude" {
+        find_prompt_lines(lines)
+    } else {
+        find_codex_prompt_lines(lines)
+    }
+}
+
+fn prompt_text(
+    engine: &str,
+    session_id: &str,
+    project_id: &str,
+    prompt_index: usize,
+) -> Result<String, String> {
+    if engine == "codex" {
+        return get_codex_prompt_text(session_id, prompt_index);
+    }
+    extract_prompts_from_jsonl(session_id, project_id)
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .find(|p| p.index == prompt_index)
+        .map(|p| p.text)
+        .ok_or_else(|| format!("Prompt #{} not found in session", prompt_index))
+}
+
+/// fork 会话文件与原会话放在同一目录，文件名遵循各 CLI 按会话 ID 查找的规则
+fn fork_file_path(engine: &str, source_path: &Path, fork_id: &str) -> Result<PathBuf, String> {
+    let dir = source_path
+        .parent()
+        .ok_or_else(|| format!("Invalid session path: {:?}", source_path))?;
+    let file_name = if engine == "claude" {
+        format!("{}.jsonl", fork_id)
+    } else {
+        format!(
+            "rollout-{}-{}.jsonl",
+            chrono::Local::now().format("%Y-%m-%dT%H-%M-%S"),
+            fork_id
+        )
+    };
+    Ok(dir.join(file_name))
+}
+
+/// 把记录中的会话 ID 改写为 `session_id`
+///
+/// Claude 每条记录都带 sessionId；Codex 只在首行 session_meta 中记录。无法解析的行原样保留
+fn rewrite_session_id(engine: &str, line: &str, session_id: &str) -> String {
+    let Ok(mut record) = serde_json::from_str::<Value>(line) else {
+        return line.to_string();
+    };
+    let target = if engine == "claude" {
+        record.get_mut("sessionId")
+    } else if record["type"].as_str() == Some("session_meta") {
+        record.get_mut("payload").and_then(|p| p.get_mut("id"))
+    } else {
+        None
+    };
+    match target {
+        Some(value) if value.is_string() => {
+            *value = Value::String(session_id.to_string());
+            record.to_string()
+        }
+        _ => line.to_string(),
+    }
+}
+
+fn join_lines(lines: impl Iterator<Item = String>) -> String {
+    let mut content = String::new();
+    for line in lines {
+        content.push_str(&line);
+        content.push('\n');
+    }
+    content
+}
+
+/// 交换两个会话在第 `prompt_index` 条提示词（含）之后的内容，返回 (新主会话, 新 fork 会话)
+///
+/// 提示词之前的部分各自保留，换入的记录改写为所在会话的 ID
+fn swap_tails(
+    engine: &str,
+    (main_content, main_id): (&str, &str),
+    (alt_content, alt_id): (&str, &str),
+    prompt_index: usize,
+) -> Result<(String, String), String> {
+    let main_lines: Vec<&str> = main_content.lines().collect();
+    let alt_lines: Vec<&str> = alt_content.lines().collect();
+
+    let main_at = *prompt_lines(engine, &main_lines)
+        .get(prompt_index)
+        .ok_or_else(|| format!("Prompt #{} not found in main session", prompt_index))?;
+    let alt_at = *prompt_lines(engine, &alt_lines)
+        .get(prompt_index)
+        .ok_or_else(|| {
+            format!(
+                "Prompt #{} not found in alternative session (not generated yet?)",
+                prompt_index
+            )
+        })?;
+
+    let new_main = join_lines(
+        main_lines[..main_at]
+            .iter()
+            .map(|line| line.to_string())
+            .chain(
+                alt_lines[alt_at..]
+                    .iter()
+                    .map(|line| rewrite_session_id(engine, line, main_id)),
+            ),
+    );
+    let new_alt = join_lines(
+        alt_lines[..alt_at]
+            .iter()
+            .map(|line| line.to_string())
+            .chain(
+                main_lines[main_at..]
+                    .iter()
+                    .map(|line| rewrite_session_id(engine, line, alt_id)),
+            ),
+    );
+    Ok((new_main, new_alt))
+}
+
+fn backup_session_file(path: &Path, timestamp: i64) -> Result<(), String> {
+    let mut backup = path.as_os_str().to_owned();
+    backup.push(format!(".bak-{}", timestamp));
+    fs::copy(path, &backup)
+        .map_err(|e| format!("Failed to back up session file {:?}: {}", path, e))?;
+    log::info!("[Regenerate] Backed up {:?} to {:?}", path, backup);
+    Ok(())
+}
+
+fn read_version(
+    engine: &str,
+    session_id: &str,
+    project_id: &str,
+    prompt_index: usize,
+    created_at: Option<i64>,
+) -> ResponseVersion {
+    let reply = resolve_session_file(engine, session_id, project_id)
+        .and_then(|path| {
+            ensure_loadable_in_memory(&path)?;
+            read_to_string_lossy(&path).map_err(|e| e.to_string())
+        })
+        .map(|content| reply_after_prompt(engine, &content, prompt_index));
+
+    let (reply_preview, message_count) = match &reply {
+        Ok((text, count)) => (truncate_chars(text, REPLY_PREVIEW_MAX_CHARS), *count),
+        Err(e) => {
+            log::warn!(
+                "[Regenerate] Failed to read {} session {}: {}",
+                engine,
+                session_id,
+                e
+            );
+            (String::new(), 0)
+        }
+    };
+    ResponseVersion {
+        session_id: session_id.to_string(),
+        is_mainline: created_at.is_none(),
+        created_at,
+        reply_preview,
+        message_count,
+        available: reply.is_ok(),
+    }
+}
+
+/// 第 `prompt_index` 条提示词到下一条提示词之间的 assistant 文本，以及 assistant 消息条数
+fn reply_after_prompt(engine: &str, content: &str, prompt_index: usize) -> (String, usize) {
+    let lines: Vec<&str> = content.lines().collect();
+    let prompt_lines = prompt_lines(engine, &lines);
+    let Some(&start) = prompt_lines.get(prompt_index) else {
+        return (String::new(), 0);
+    };
+    let end = prompt_lines
+        .get(prompt_index + 1)
+        .copied()
+        .unwrap_or(lines.len());
+
+    let mut texts = Vec::new();
+    let mut message_count = 0;
+    for line in &lines[start + 1..end] {
+        let Ok(record) = serde_json::from_str::<Value>(line) else {
+            continue;
+        };
+        let Some(text) = assistant_text(engine, &record) else {
+            continue;
+        };
+        message_count += 1;
+        if !text.trim().is_empty() {
+            texts.push(text);
+        }
+    }
+    (texts.join("\n\n"), message_count)
+}
+
+fn assistant_text(engine: &str, record: &Value) -> Option<String> {
+    let (content, text_type) = if engine == "claude" {
+        if record["type"].as_str() != Some("assistant")
+            || record["isSidechain"].as_bool().unwrap_or(false)
+        {
+            return None;
+        }
+        (record["message"]["content"].as_array()?, "text")
+    } else {
+        let payload = &record["payload"];
+        if record["type"].as_str() != Some("response_item")
+            || payload["type"].as_str() != Some("message")
+            || payload["role"].as_str() != Some("assistant")
+        {
+            return None;
+        }
+        (payload["content"].as_array()?, "output_text")
+    };
+    Some(
+        content
+            .iter()
+            .filter(|block| block["type"].as_str() == Some(text_type))
+            .filter_map(|block| block["text"].as_str())
+            .collect::<Vec<_>>()
+            .join("\n"),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn claude_line(session_id: &str, record_type: &str, text: &str) -> String {
+        let content = if record_type == "user" {
+            json!(text)
+        } else {
+            json!([{ "type": "text", "text": text }])
+        };
+        json!({
+            "type": record_type,
+            "sessionId": session_id,
+            "message": { "role": record_type, "content": content },
+        })
+        .to_string()
+    }
+
+    fn claude_session(session_id: &str, turns: &[(&str, &str)]) -> String {
+        let lines: Vec<String> = turns
+            .iter()
+            .flat_map(|(prompt, reply)| {
+                [
+                    claude_line(session_id, "user", prompt),
+                    claude_line(session_id, "assistant", reply),
+                ]
+            })
+            .collect();
+        join_lines(lines.into_iter())
+    }
+
+    #[test]
+    fn swapping_tails_keeps_prefix_and_rewrites_session_ids() {
+        let main = claude_session("main", &[("first", "a1"), ("second", "original")]);
+        let alt = claude_session("alt", &[("first", "a1"), ("second", "regenerated")]);
+
+        let (new_main, new_alt) = swap_tails("claude", (&main, "main"), (&alt, "alt"), 1).unwrap();
+
+        assert_eq!(
+            reply_after_prompt("claude", &new_main, 1),
+            ("regenerated".to_string(), 1)
+        );
+        assert_eq!(
+            reply_after_prompt("claude", &new_alt, 1),
+            ("original".to_string(), 1)
+        );
+        assert!(new_main
+            .lines()
+            .all(|l| l.contains("\"sessionId\":\"main\"")));
+        assert!(new_alt.lines().all(|l| l.contains("\"sessionId\":\"alt\"")));
+        assert_eq!(
+            new_main.lines().next(),
+            main.lines().next(),
+            "lines before the prompt are left untouched"
+        );
+    }
+
+    #[test]
+    fn swapping_requires_the_prompt_in_the_alternative() {
+        let main = claude_session("main", &[("first", "a1"), ("second", "original")]);
+        let alt = claude_session("alt", &[("first", "a1")]);
+
+        assert!(swap_tails("claude", (&main, "main"), (&alt, "alt"), 1).is_err());
+    }
+
+    #[test]
+    fn codex_session_id_is_only_rewritten_in_session_meta() {
+        let meta = json!({ "type": "session_meta", "payload": { "id": "old", "cwd": "/p" } });
+        let item = json!({ "type": "response_item", "payload": { "id": "old" } });
+
+        let rewritten: Value =
+            serde_json::from_str(&rewrite_session_id("codex", &meta.to_string(), "new")).unwrap();
+        assert_eq!(rewritten["payload"]["id"], "new");
+        assert_eq!(
+            rewrite_session_id("codex", &item.to_string(), "new"),
+            item.to_string()
+        );
+        assert_eq!(rewrite_session_id("codex", "not json", "new"), "not json");
+    }
+}
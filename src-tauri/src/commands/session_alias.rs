@@ -0,0 +1,220 @@
+//! 会话 ID 别名
+//!
+//! 从应用里 resume Claude 会话时，CLI 有时会以新的 session id 写出后续消息（fork 行为）。
+//! 执行过程中解析到 system/init 事件里的 session_id 与请求的不一致时：
+//! - 记录 旧 id → 新 id 的别名，之后以旧 id 发起的查询（历史、提示词列表、撤回、标注、resume）
+//!   自动解析到新 id；别名可以串联，按链解析到最新的 id
+//! - 把会话标注与 git-records 等附属数据迁移到新 id
+//! - 发出 `session:id-changed` 事件，前端据此切换到新 id 并迁移草稿
+//!
+//! 列出会话时隐藏已有新会话接续的旧会话，避免同一段对话看起来重复。
+//!
+//! 持久化：~/.anycode/session_aliases.json
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter};
+
+use crate::utils::config_utils::{load_json_config, save_json_config, ConfigPathBuilder};
+
+/// 会话 id 变化事件
+pub const SESSION_ID_CHANGED_EVENT: &str = "session:id-changed";
+
+/// 别名链的最大解析深度，防止异常数据导致死循环
+const MAX_ALIAS_DEPTH: usize = 32;
+
+/// 串行化别名文件的读改写
+static STORE_LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AliasStore {
+    /// 键为 `engine:旧 session_id`，值为新 session_id
+    #[serde(default)]
+    aliases: HashMap<String, String>,
+}
+
+/// `session:id-changed` 事件负载
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionIdChanged {
+    pub engine: String,
+    pub old_session_id: String,
+    pub new_session_id: String,
+    pub project_path: String,
+    /// 发起执行的标签页
+    pub tab_id: Option<String>,
+}
+
+fn alias_key(engine: &str, session_id: &str) -> String {
+    format!("{}:{}", engine, session_id)
+}
+
+fn get_aliases_path() -> Result<PathBuf, String> {
+    Ok(ConfigPathBuilder::from_home_subdir(".anycode")?.build("session_aliases.json"))
+}
+
+fn load_store() -> AliasStore {
+    get_aliases_path()
+        .and_then(load_json_config)
+        .unwrap_or_else(|e| {
+            log::warn!("[Session Alias] Failed to load aliases: {}", e);
+            AliasStore::default()
+        })
+}
+
+impl AliasStore {
+    /// 沿别名链解析到最新的 id（没有别名时原样返回）
+    fn resolve(&self, engine: &str, session_id: &str) -> String {
+        let mut current = session_id.to_string();
+        for _ in 0..MAX_ALIAS_DEPTH {
+            match self.aliases.get(&alias_key(engine, &current)) {
+                Some(next) => current = next.clone(),
+                None => break,
+            }
+        }
+        current
+    }
+
+    /// 记录别名；新 id 解析回旧 id（会形成环）时忽略，返回是否写入
+    fn insert(&mut self, engine: &str, old_session_id: &str, new_session_id: &str) -> bool {
+        if old_session_id == new_session_id
+            || self.resolve(engine, new_session_id) == old_session_id
+        {
+            return false;
+        }
+        self.aliases.insert(
+            alias_key(engine, old_session_id),
+            new_session_id.to_string(),
+        );
+        true
+    }
+
+    /// 保留没有被同一列表中的新会话接续的会话
+    fn retain_latest<T>(&self, engine: &str, sessions: &mut Vec<T>, id: impl Fn(&T) -> &str) {
+        let present: HashSet<String> = sessions.iter().map(|s| id(s).to_string()).collect();
+        sessions.retain(|s| {
+            let resolved = self.resolve(engine, id(s));
+            resolved == id(s) || !present.contains(&resolved)
+        });
+    }
+}
+
+/// 把可能已被接续的旧 session id 解析为最新的 id
+pub fn resolve_session_alias(engine: &str, session_id: &str) -> String {
+    let _guard = STORE_LOCK.lock().unwrap();
+    load_store().resolve(engine, session_id)
+}
+
+/// 记录 旧 id → 新 id 的别名
+pub fn record_session_alias(
+    engine: &str,
+    old_session_id: &str,
+    new_session_id: &str,
+) -> Result<(), String> {
+    let _guard = STORE_LOCK.lock().unwrap();
+    let path = get_aliases_path()?;
+    let mut store: AliasStore = load_json_config(&path)?;
+    if store.insert(engine, old_session_id, new_session_id) {
+        save_json_config(&store, &path)?;
+    }
+    Ok(())
+}
+
+/// 列出会话时合并别名：隐藏已由同一列表中的新会话接续的旧会话
+pub fn merge_aliased_sessions<T>(engine: &str, sessions: &mut Vec<T>, id: impl Fn(&T) -> &str) {
+    let _guard = STORE_LOCK.lock().unwrap();
+    load_store().retain_latest(engine, sessions, id);
+}
+
+/// 处理执行中的会话 id 漂移：记录别名、迁移附属数据并通知前端
+///
+/// 各步骤失败只记录日志，不影响正在进行的执行
+pub fn handle_session_id_drift(
+    app: &AppHandle,
+    engine: &str,
+    project_path: &str,
+    old_session_id: &str,
+    new_session_id: &str,
+    tab_id: Option<&str>,
+) {
+    log::warn!(
+        "[Session Alias] {} session id changed on resume: {} -> {}",
+        engine,
+        old_session_id,
+        new_session_id
+    );
+
+    if let Err(e) = record_session_alias(engine, old_session_id, new_session_id) {
+        log::warn!("[Session Alias] Failed to record alias: {}", e);
+    }
+    if let Err(e) = super::session_annotations::migrate_session_annotation(
+        engine,
+        old_session_id,
+        new_session_id,
+    ) {
+        log::warn!("[Session Alias] Failed to migrate annotations: {}", e);
+    }
+    if engine == "claude" {
+        let project_id = super::claude::encode_project_path(project_path);
+        if let Err(e) =
+            super::prompt_tracker::copy_session_records(old_session_id, new_session_id, &project_id)
+        {
+            log::warn!("[Session Alias] Failed to migrate git records: {}", e);
+        }
+    }
+
+    let payload = SessionIdChanged {
+        engine: engine.to_string(),
+        old_session_id: old_session_id.to_string(),
+        new_session_id: new_session_id.to_string(),
+        project_path: project_path.to_string(),
+        tab_id: tab_id.map(str::to_string),
+    };
+    if let Err(e) = app.emit(SESSION_ID_CHANGED_EVENT, &payload) {
+        log::warn!(
+            "[Session Alias] Failed to emit {}: {}",
+            SESSION_ID_CHANGED_EVENT,
+            e
+        );
+    }
+}
+
+/// 解析会话的最新 id（前端打开旧标签页或链接时使用）
+#[tauri::command]
+pub async fn resolve_session_id(engine: String, session_id: String) -> Result<String, String> {
+    Ok(resolve_session_alias(&engine, &session_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn aliases_resolve_through_chains_and_ignore_cycles() {
+        let mut store = AliasStore::default();
+        assert!(store.insert("claude", "a", "b"));
+        assert!(store.insert("claude", "b", "c"));
+        assert_eq!(store.resolve("claude", "a"), "c");
+        assert_eq!(store.resolve("claude", "c"), "c");
+        assert_eq!(store.resolve("codex", "a"), "a");
+
+        assert!(!store.insert("claude", "c", "a"));
+        assert!(!store.insert("claude", "c", "c"));
+        assert_eq!(store.resolve("claude", "a"), "c");
+    }
+
+    #[test]
+    fn listing_hides_sessions_continued_by_a_listed_session() {
+        let mut store = AliasStore::default();
+        store.insert("claude", "old", "new");
+        store.insert("claude", "gone", "elsewhere");
+
+        let mut sessions = vec!["old", "new", "gone", "other"];
+        store.retain_latest("claude", &mut sessions, |s| s);
+        // "gone" stays visible: the session continuing it is not in this project
+        assert_eq!(sessions, vec!["new", "gone", "other"]);
+    }
+}
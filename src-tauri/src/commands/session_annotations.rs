@@ -0,0 +1,675 @@
+//! 会话标注存储
+//!
+//! 以 `engine:session_id` 为键保存会话文件之外的附加信息：
+//! - 关联会话（related_sessions），例如跨引擎评审时被评审的会话与执行评审的会话互相关联
+//! - 提示词增强元数据（prompt_enhancements），以 prompt_index 为键。执行入口增强提示词时还不知道
+//!   会话与 prompt_index，先按 trace id 暂存；提示词记录（record_*_prompt_sent）确定二者后再配对写入，
+//!   两者先后顺序不固定，先到的一方暂存等待另一方。发送时引用文件的快照清单随增强元数据一起配对
+//!   （见 prompt_file_snapshots）
+//! - 回复的其他版本（response_alternatives），以 prompt_index 为键，记录重新生成时 fork 出的会话
+//!
+//! 持久化：~/.anycode/session_annotations.json
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use super::prompt_file_snapshots::{
+    store_in_session_attachments, PromptFileSnapshot, PromptFileSnapshots,
+};
+use super::session_alias::resolve_session_alias;
+use super::url_context::FailedUrl;
+use crate::utils::config_utils::{load_json_config, save_json_config, ConfigPathBuilder};
+
+/// 串行化标注文件的读改写
+static STORE_LOCK: Mutex<()> = Mutex::new(());
+
+/// 未配对的暂存项保留时间（秒），超时后丢弃
+const PENDING_TTL_SECS: i64 = 60 * 60;
+
+/// 会话之间的关联类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionRelation {
+    /// 本会话评审了关联会话中的回复
+    ReviewOf,
+    /// 本会话中的回复被关联会话评审
+    ReviewedBy,
+}
+
+impl SessionRelation {
+    fn inverse(self) -> Self {
+        match self {
+            SessionRelation::ReviewOf => SessionRelation::ReviewedBy,
+            SessionRelation::ReviewedBy => SessionRelation::ReviewOf,
+        }
+    }
+}
+
+/// 关联会话
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RelatedSession {
+    /// 引擎类型: "claude" | "codex" | "gemini"
+    pub engine: String,
+    pub session_id: String,
+    /// 关联会话所属项目路径
+    pub project_path: String,
+    pub relation: SessionRelation,
+    /// 关联创建时间（Unix 秒）
+    pub created_at: i64,
+}
+
+/// 提示词的增强元数据
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PromptEnhancementInfo {
+    pub trace_id: String,
+    /// 检索上下文的项目路径
+    pub project_paths: Vec<String>,
+    /// 注入的代码片段路径（去重，按出现顺序）
+    pub snippet_paths: Vec<String>,
+    /// 注入上下文的估算 token 数
+    pub context_tokens: usize,
+    /// 是否直接使用了缓存的增强结果
+    pub cache_hit: bool,
+    /// 多轮搜索是否因超时只返回了部分结果
+    #[serde(default)]
+    pub partial: bool,
+    /// 增强失败时的错误（fail_open 时仍以原始提示词执行）
+    #[serde(default)]
+    pub error: Option<String>,
+    /// 预抓取并附加到提示词的链接
+    #[serde(default)]
+    pub fetched_urls: Vec<String>,
+    /// 预抓取失败的链接与原因
+    #[serde(default)]
+    pub failed_urls: Vec<FailedUrl>,
+    /// 发送时提示词引用文件的快照
+    #[serde(default)]
+    pub file_snapshots: Option<PromptFileSnapshots>,
+    /// 增强时间（Unix 秒）
+    pub created_at: i64,
+}
+
+/// 单个会话的标注
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionAnnotation {
+    #[serde(default)]
+    pub related_sessions: Vec<RelatedSession>,
+    /// 键为 prompt_index
+    #[serde(default)]
+    pub prompt_enhancements: BTreeMap<usize, PromptEnhancementInfo>,
+    /// 键为 prompt_index，值为按生成顺序排列的其他回复版本
+    #[serde(default)]
+    pub response_alternatives: BTreeMap<usize, Vec<ResponseAlternative>>,
+}
+
+/// 同一提示词的另一个回复版本
+///
+/// fork 会话与原会话在该提示词之前的历史相同，提示词在 fork 中的序号不变
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResponseAlternative {
+    /// 版本所在的 fork 会话
+    pub session_id: String,
+    /// 生成时间（Unix 秒）
+    pub created_at: i64,
+}
+
+/// 已记录的提示词（会话与 prompt_index）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PromptRef {
+    engine: String,
+    session_id: String,
+    prompt_index: usize,
+    created_at: i64,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AnnotationStore {
+    /// 键为 `engine:session_id`
+    #[serde(default)]
+    sessions: HashMap<String, SessionAnnotation>,
+    /// 尚未确定会话的增强元数据，键为 trace id
+    #[serde(default)]
+    pending_enhancements: HashMap<String, PromptEnhancementInfo>,
+    /// 尚未收到增强元数据的已记录提示词，键为 trace id
+    #[serde(default)]
+    pending_prompts: HashMap<String, PromptRef>,
+}
+
+/// 关联中的一端
+#[derive(Debug, Clone)]
+pub struct SessionRef {
+    pub engine: String,
+    pub session_id: String,
+    pub project_path: String,
+}
+
+/// 获取会话标注（没有标注时返回空标注）
+#[tauri::command]
+pub async fn get_session_annotation(
+    engine: String,
+    session_id: String,
+) -> Result<SessionAnnotation, String> {
+    let session_id = resolve_session_alias(&engine, &session_id);
+    let _guard = STORE_LOCK.lock().unwrap();
+    let mut store: AnnotationStore = load_json_config(get_annotations_path()?)?;
+    Ok(store
+        .sessions
+        .remove(&annotation_key(&engine, &session_id))
+        .unwrap_or_default())
+}
+
+/// 获取某条提示词的增强元数据（未增强时返回 None）
+#[tauri::command]
+pub async fn get_prompt_enhancement_info(
+    engine: String,
+    session_id: String,
+    prompt_index: usize,
+) -> Result<Option<PromptEnhancementInfo>, String> {
+    let session_id = resolve_session_alias(&engine, &session_id);
+    let _guard = STORE_LOCK.lock().unwrap();
+    let mut store: AnnotationStore = load_json_config(get_annotations_path()?)?;
+    Ok(store
+        .sessions
+        .remove(&annotation_key(&engine, &session_id))
+        .and_then(|mut annotation| annotation.prompt_enhancements.remove(&prompt_index)))
+}
+
+/// 获取某条提示词发送时引用文件的快照清单
+pub fn load_prompt_file_snapshots(
+    engine: &str,
+    session_id: &str,
+    prompt_index: usize,
+) -> Result<Option<PromptFileSnapshots>, String> {
+    let _guard = STORE_LOCK.lock().unwrap();
+    let mut store: AnnotationStore = load_json_config(get_annotations_path()?)?;
+    Ok(store
+        .sessions
+        .remove(&annotation_key(engine, session_id))
+        .and_then(|mut annotation| annotation.prompt_enhancements.remove(&prompt_index))
+        .and_then(|info| info.file_snapshots))
+}
+
+/// 按快照 id 查找文件快照及其所属会话
+pub fn find_prompt_file_snapshot(
+    snapshot_id: &str,
+) -> Result<Option<(SessionRef, PromptFileSnapshots, PromptFileSnapshot)>, String> {
+    let _guard = STORE_LOCK.lock().unwrap();
+    let store: AnnotationStore = load_json_config(get_annotations_path()?)?;
+    for (key, annotation) in store.sessions {
+        let Some((engine, session_id)) = key.split_once(':') else {
+            continue;
+        };
+        for info in annotation.prompt_enhancements.into_values() {
+            let Some(snapshots) = info.file_snapshots else {
+                continue;
+            };
+            if let Some(file) = snapshots.files.iter().find(|f| f.id == snapshot_id) {
+                let session = SessionRef {
+                    engine: engine.to_string(),
+                    session_id: session_id.to_string(),
+                    project_path: snapshots.project_path.clone(),
+                };
+                let file = file.clone();
+                return Ok(Some((session, snapshots, file)));
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// 记录执行入口中一次提示词增强的元数据（按 trace id 等待与提示词记录配对）
+pub fn record_prompt_enhancement(info: PromptEnhancementInfo) -> Result<(), String> {
+    let attached = {
+        let _guard = STORE_LOCK.lock().unwrap();
+        let path = get_annotations_path()?;
+        let mut store: AnnotationStore = load_json_config(&path)?;
+
+        prune_pending(&mut store, info.created_at);
+        let attached = pair_enhancement(&mut store, info);
+        save_json_config(&store, &path)?;
+        attached
+    };
+    store_attached_snapshots(attached);
+    Ok(())
+}
+
+/// 配对成功后把快照的内容副本存入会话的 attachments 目录
+fn store_attached_snapshots(attached: Option<(PromptRef, PromptFileSnapshots)>) {
+    if let Some((prompt, snapshots)) = attached {
+        store_in_session_attachments(&prompt.engine, &prompt.session_id, &snapshots);
+    }
+}
+
+/// 提示词记录确定会话与 prompt_index 后调用，与同一 trace 的增强元数据配对
+///
+/// 失败只记日志，不影响提示词记录
+pub fn bind_prompt_trace(
+    trace_id: Option<&str>,
+    engine: &str,
+    session_id: &str,
+    prompt_index: usize,
+) {
+    let Some(trace_id) = trace_id else {
+        return;
+    };
+    let prompt = PromptRef {
+        engine: engine.to_string(),
+        session_id: session_id.to_string(),
+        prompt_index,
+        created_at: chrono::Utc::now().timestamp(),
+    };
+    if let Err(e) = save_prompt_ref(trace_id, prompt) {
+        log::warn!(
+            "[trace:{}] Failed to bind prompt #{} of {} session {}: {}",
+            trace_id,
+            prompt_index,
+            engine,
+            session_id,
+            e
+        );
+    }
+}
+
+fn save_prompt_ref(trace_id: &str, prompt: PromptRef) -> Result<(), String> {
+    let attached = {
+        let _guard = STORE_LOCK.lock().unwrap();
+        let path = get_annotations_path()?;
+        let mut store: AnnotationStore = load_json_config(&path)?;
+
+        prune_pending(&mut store, prompt.created_at);
+        let attached = pair_prompt(&mut store, trace_id, prompt);
+        save_json_config(&store, &path)?;
+        attached
+    };
+    store_attached_snapshots(attached);
+    Ok(())
+}
+
+/// 增强元数据先到：已有同一 trace 的提示词记录时直接写入会话，否则暂存
+///
+/// 配对成功且带有文件快照时返回提示词与快照清单
+fn pair_enhancement(
+    store: &mut AnnotationStore,
+    info: PromptEnhancementInfo,
+) -> Option<(PromptRef, PromptFileSnapshots)> {
+    match store.pending_prompts.remove(&info.trace_id) {
+        Some(prompt) => attach_enhancement(store, prompt, info),
+        None => {
+            store
+                .pending_enhancements
+                .insert(info.trace_id.clone(), info);
+            None
+        }
+    }
+}
+
+/// 提示词记录先到：已有同一 trace 的增强元数据时直接写入会话，否则暂存
+fn pair_prompt(
+    store: &mut AnnotationStore,
+    trace_id: &str,
+    prompt: PromptRef,
+) -> Option<(PromptRef, PromptFileSnapshots)> {
+    match store.pending_enhancements.remove(trace_id) {
+        Some(info) => attach_enhancement(store, prompt, info),
+        None => {
+            store.pending_prompts.insert(trace_id.to_string(), prompt);
+            None
+        }
+    }
+}
+
+fn attach_enhancement(
+    store: &mut AnnotationStore,
+    prompt: PromptRef,
+    info: PromptEnhancementInfo,
+) -> Option<(PromptRef, PromptFileSnapshots)> {
+    let snapshots = info.file_snapshots.clone();
+    store
+        .sessions
+        .entry(annotation_key(&prompt.engine, &prompt.session_id))
+        .or_default()
+        .prompt_enhancements
+        .insert(prompt.prompt_index, info);
+    snapshots.map(|snapshots| (prompt, snapshots))
+}
+
+/// 丢弃超时仍未配对的暂存项（例如未开启增强时的提示词记录）
+fn prune_pending(store: &mut AnnotationStore, now: i64) {
+    store
+        .pending_enhancements
+        .retain(|_, info| now - info.created_at < PENDING_TTL_SECS);
+    store
+        .pending_prompts
+        .retain(|_, prompt| now - prompt.created_at < PENDING_TTL_SECS);
+}
+
+/// 双向关联两个会话：`from` 以 `relation` 指向 `to`，`to` 以反向关系指回 `from`
+///
+/// 同一关联重复写入时保持幂等
+pub fn link_sessions(
+    from: &SessionRef,
+    to: &SessionRef,
+    relation: SessionRelation,
+) -> Result<(), String> {
+    let _guard = STORE_LOCK.lock().unwrap();
+    let path = get_annotations_path()?;
+    let mut store: AnnotationStore = load_json_config(&path)?;
+
+    add_related_session(&mut store, from, to, relation);
+    add_related_session(&mut store, to, from, relation.inverse());
+
+    save_json_config(&store, &path)
+}
+
+fn add_related_session(
+    store: &mut AnnotationStore,
+    owner: &SessionRef,
+    related: &SessionRef,
+    relation: SessionRelation,
+) {
+    let annotation = store
+        .sessions
+        .entry(annotation_key(&owner.engine, &owner.session_id))
+        .or_default();
+    let exists = annotation.related_sessions.iter().any(|r| {
+        r.engine == related.engine && r.session_id == related.session_id && r.relation == relation
+    });
+    if !exists {
+        annotation.related_sessions.push(RelatedSession {
+            engine: related.engine.clone(),
+            session_id: related.session_id.clone(),
+            project_path: related.project_path.clone(),
+            relation,
+            created_at: chrono::Utc::now().timestamp(),
+        });
+    }
+}
+
+/// 为会话中某条提示词记录一个新的回复版本
+pub fn add_response_alternative(
+    engine: &str,
+    session_id: &str,
+    prompt_index: usize,
+    alternative: ResponseAlternative,
+) -> Result<(), String> {
+    let _guard = STORE_LOCK.lock().unwrap();
+    let path = get_annotations_path()?;
+    let mut store: AnnotationStore = load_json_config(&path)?;
+
+    let alternatives = store
+        .sessions
+        .entry(annotation_key(engine, session_id))
+        .or_default()
+        .response_alternatives
+        .entry(prompt_index)
+        .or_default();
+    if !alternatives
+        .iter()
+        .any(|a| a.session_id == alternative.session_id)
+    {
+        alternatives.push(alternative);
+    }
+
+    save_json_config(&store, &path)
+}
+
+/// 获取会话中某条提示词记录的所有回复版本（不含原会话本身）
+pub fn load_response_alternatives(
+    engine: &str,
+    session_id: &str,
+    prompt_index: usize,
+) -> Result<Vec<ResponseAlternative>, String> {
+    let _guard = STORE_LOCK.lock().unwrap();
+    let mut store: AnnotationStore = load_json_config(get_annotations_path()?)?;
+    Ok(store
+        .sessions
+        .remove(&annotation_key(engine, session_id))
+        .and_then(|mut annotation| annotation.response_alternatives.remove(&prompt_index))
+        .unwrap_or_default())
+}
+
+/// 会话 id 漂移后把旧会话的标注并入新会话
+///
+/// 新会话已有的条目优先；其他会话中指向旧 id 的关联与回复版本同时改为指向新 id
+pub fn migrate_session_annotation(
+    engine: &str,
+    old_session_id: &str,
+    new_session_id: &str,
+) -> Result<(), String> {
+    let _guard = STORE_LOCK.lock().unwrap();
+    let path = get_annotations_path()?;
+    let mut store: AnnotationStore = load_json_config(&path)?;
+    if migrate_annotation(&mut store, engine, old_session_id, new_session_id) {
+        save_json_config(&store, &path)?;
+    }
+    Ok(())
+}
+
+fn migrate_annotation(
+    store: &mut AnnotationStore,
+    engine: &str,
+    old_session_id: &str,
+    new_session_id: &str,
+) -> bool {
+    let mut changed = false;
+
+    if let Some(old) = store
+        .sessions
+        .remove(&annotation_key(engine, old_session_id))
+    {
+        let annotation = store
+            .sessions
+            .entry(annotation_key(engine, new_session_id))
+            .or_default();
+        for related in old.related_sessions {
+            let exists = annotation.related_sessions.iter().any(|r| {
+                r.engine == related.engine
+                    && r.session_id == related.session_id
+                    && r.relation == related.relation
+            });
+            if !exists {
+                annotation.related_sessions.push(related);
+            }
+        }
+        for (index, info) in old.prompt_enhancements {
+            annotation.prompt_enhancements.entry(index).or_insert(info);
+        }
+        for (index, alternatives) in old.response_alternatives {
+            let merged = annotation.response_alternatives.entry(index).or_default();
+            for alternative in alternatives {
+                if !merged
+                    .iter()
+                    .any(|a| a.session_id == alternative.session_id)
+                {
+                    merged.push(alternative);
+                }
+            }
+        }
+        changed = true;
+    }
+
+    for annotation in store.sessions.values_mut() {
+        for related in &mut annotation.related_sessions {
+            if related.engine == engine && related.session_id == old_session_id {
+                related.session_id = new_session_id.to_string();
+                changed = true;
+            }
+        }
+        for alternatives in annotation.response_alternatives.values_mut() {
+            for alternative in alternatives.iter_mut() {
+                if alternative.session_id == old_session_id {
+                    alternative.session_id = new_session_id.to_string();
+                    changed = true;
+                }
+            }
+        }
+    }
+    for prompt in store.pending_prompts.values_mut() {
+        if prompt.engine == engine && prompt.session_id == old_session_id {
+            prompt.session_id = new_session_id.to_string();
+            changed = true;
+        }
+    }
+
+    changed
+}
+
+fn annotation_key(engine: &str, session_id: &str) -> String {
+    format!("{}:{}", engine, session_id)
+}
+
+fn get_annotations_path() -> Result<PathBuf, String> {
+    Ok(ConfigPathBuilder::from_home_subdir(".anycode")?.build("session_annotations.json"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(engine: &str, session_id: &str) -> SessionRef {
+        SessionRef {
+            engine: engine.to_string(),
+            session_id: session_id.to_string(),
+            project_path: "/work/app".to_string(),
+        }
+    }
+
+    #[test]
+    fn links_are_bidirectional_and_idempotent() {
+        let mut store = AnnotationStore::default();
+        let reviewer = session("codex", "c-1");
+        let reviewed = session("claude", "a-1");
+
+        for _ in 0..2 {
+            add_related_session(&mut store, &reviewer, &reviewed, SessionRelation::ReviewOf);
+            add_related_session(
+                &mut store,
+                &reviewed,
+                &reviewer,
+                SessionRelation::ReviewOf.inverse(),
+            );
+        }
+
+        let codex = &store.sessions["codex:c-1"].related_sessions;
+        assert_eq!(codex.len(), 1);
+        assert_eq!(codex[0].session_id, "a-1");
+        assert_eq!(codex[0].relation, SessionRelation::ReviewOf);
+
+        let claude = &store.sessions["claude:a-1"].related_sessions;
+        assert_eq!(claude.len(), 1);
+        assert_eq!(claude[0].engine, "codex");
+        assert_eq!(claude[0].relation, SessionRelation::ReviewedBy);
+    }
+
+    fn enhancement(trace_id: &str) -> PromptEnhancementInfo {
+        PromptEnhancementInfo {
+            trace_id: trace_id.to_string(),
+            project_paths: vec!["/work/app".to_string()],
+            snippet_paths: vec!["src/main.rs".to_string()],
+            context_tokens: 120,
+            cache_hit: false,
+            partial: false,
+            error: None,
+            fetched_urls: Vec::new(),
+            failed_urls: Vec::new(),
+            file_snapshots: None,
+            created_at: 1_000,
+        }
+    }
+
+    fn prompt_ref(session_id: &str, prompt_index: usize) -> PromptRef {
+        PromptRef {
+            engine: "codex".to_string(),
+            session_id: session_id.to_string(),
+            prompt_index,
+            created_at: 1_000,
+        }
+    }
+
+    #[test]
+    fn enhancement_pairs_with_prompt_in_either_order() {
+        let mut store = AnnotationStore::default();
+
+        // 新会话：先执行（增强），会话确定后才记录提示词
+        pair_enhancement(&mut store, enhancement("t-1"));
+        pair_prompt(&mut store, "t-1", prompt_ref("c-1", 0));
+        // 恢复会话：先记录提示词，再执行
+        pair_prompt(&mut store, "t-2", prompt_ref("c-1", 1));
+        pair_enhancement(&mut store, enhancement("t-2"));
+
+        let enhancements = &store.sessions["codex:c-1"].prompt_enhancements;
+        assert_eq!(enhancements[&0].trace_id, "t-1");
+        assert_eq!(enhancements[&1].trace_id, "t-2");
+        assert!(store.pending_enhancements.is_empty());
+        assert!(store.pending_prompts.is_empty());
+
+        // 未增强的提示词记录超时后被清理
+        pair_prompt(&mut store, "t-3", prompt_ref("c-1", 2));
+        prune_pending(&mut store, 1_000 + PENDING_TTL_SECS);
+        assert!(store.pending_prompts.is_empty());
+    }
+
+    #[test]
+    fn file_snapshots_are_returned_once_paired() {
+        let mut store = AnnotationStore::default();
+        let mut info = enhancement("t-1");
+        info.file_snapshots = Some(PromptFileSnapshots {
+            project_path: "/work/app".to_string(),
+            files: Vec::new(),
+        });
+
+        assert!(pair_enhancement(&mut store, info).is_none());
+        let (prompt, snapshots) = pair_prompt(&mut store, "t-1", prompt_ref("c-1", 3)).unwrap();
+        assert_eq!(
+            (prompt.session_id.as_str(), prompt.prompt_index),
+            ("c-1", 3)
+        );
+        assert_eq!(snapshots.project_path, "/work/app");
+
+        // Enhancements without snapshots pair silently
+        pair_prompt(&mut store, "t-2", prompt_ref("c-1", 4));
+        assert!(pair_enhancement(&mut store, enhancement("t-2")).is_none());
+        assert_eq!(store.sessions["codex:c-1"].prompt_enhancements.len(), 2);
+    }
+
+    #[test]
+    fn drifted_session_annotations_move_to_the_new_id() {
+        let mut store = AnnotationStore::default();
+        let old = session("claude", "old");
+        let reviewer = session("codex", "c-1");
+        add_related_session(&mut store, &reviewer, &old, SessionRelation::ReviewOf);
+        add_related_session(&mut store, &old, &reviewer, SessionRelation::ReviewedBy);
+        store
+            .sessions
+            .get_mut("claude:old")
+            .unwrap()
+            .prompt_enhancements
+            .insert(0, enhancement("t-1"));
+        store
+            .sessions
+            .entry("claude:new".to_string())
+            .or_default()
+            .prompt_enhancements
+            .insert(1, enhancement("t-2"));
+
+        assert!(migrate_annotation(&mut store, "claude", "old", "new"));
+        assert!(!store.sessions.contains_key("claude:old"));
+        let migrated = &store.sessions["claude:new"];
+        assert_eq!(migrated.related_sessions.len(), 1);
+        assert_eq!(migrated.prompt_enhancements.len(), 2);
+        assert_eq!(
+            store.sessions["codex:c-1"].related_sessions[0].session_id,
+            "new"
+        );
+
+        assert!(!migrate_annotation(&mut store, "claude", "old", "new"));
+    }
+}
@@ -0,0 +1,540 @@
+//! 会话大块工具结果的外置存储
+//!
+//! 读了一个大 JSON、base64 图片这类工具结果会整段嵌在会话 JSONL 里，单个会话文件轻松过百 MB，
+//! 拖慢所有读取路径。本应用控制的写入路径（转换器产物、导入）会把超过阈值的工具结果写到会话旁的
+//! `attachments/<session>/<hash>.blob`，消息中只保留占位字符串（前 200 个字符的预览 + 引用标记）：
+//!
+//! ```text
+//! 前 200 个字符…
+//!
+//! [anycode-blob blob://<sha256> 123456 bytes]
+//! ```
+//!
+//! 占位保持字符串类型，tool_result 的 `content`、Codex 的 `output` 仍符合 CLI 的会话格式，
+//! `claude --resume` / `codex resume` 读取外置后的会话不会出错（只是看到预览）。
+//!
+//! 外置的字段：
+//! - Claude：`message.content[]` 中 tool_result 的 `content`，以及消息顶层的 `toolUseResult`
+//! - Codex：`function_call_output` / `custom_tool_call_output` 的 `payload.output`
+//!
+//! blob 保存字段原值的 JSON 序列化（字符串或内容块数组），按 sha256 去重；
+//! 历史加载时占位原样返回前端，需要全文时通过 `get_message_blob` 按需取回，导出与转换时可内联还原。
+//! CLI 自己写入的会话无法改变写法，`optimize_session_storage` 可离线把已有会话改写为外置格式（备份原文件）。
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io::{BufWriter, Write};
+use std::ops::ControlFlow;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use super::session_utils::resolve_session_file;
+use crate::utils::jsonl_reader::for_each_raw_line;
+use crate::utils::session_lock::{FileSnapshot, SessionLock};
+use crate::utils::text::truncate_chars;
+
+/// 默认外置阈值：序列化后超过 64KB 的工具结果
+pub const DEFAULT_BLOB_THRESHOLD: usize = 64 * 1024;
+
+/// 占位中保留的预览字符数
+const PREVIEW_CHARS: usize = 200;
+
+/// 占位引用的协议前缀
+pub const BLOB_SCHEME: &str = "blob://";
+
+/// 占位字符串最后一行的引用标记：`[anycode-blob blob://<sha256> <字节数> bytes]`
+const BLOB_MARKER_PREFIX: &str = "[anycode-blob ";
+const BLOB_MARKER_SUFFIX: &str = " bytes]";
+
+/// 会话存储优化结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionStorageReport {
+    /// 优化前的文件大小（字节）
+    pub original_bytes: u64,
+    /// 优化后的文件大小（字节）
+    pub optimized_bytes: u64,
+    /// 外置的工具结果数
+    pub externalized_count: usize,
+    /// 原文件备份路径（没有需要外置的内容时不改写、不备份）
+    pub backup_path: Option<String>,
+}
+
+/// 会话文件对应的 attachments 目录：`<会话目录>/attachments/<文件名去扩展名>`
+pub fn attachments_dir(session_file: &Path) -> PathBuf {
+    let stem = session_file.file_stem().unwrap_or_default();
+    session_file.with_file_name("attachments").join(stem)
+}
+
+/// 占位字符串：预览 + 空行 + 引用标记
+fn placeholder_text(preview: &str, reference: &str, size: usize) -> String {
+    format!(
+        "{}\n\n{}{} {}{}",
+        preview, BLOB_MARKER_PREFIX, reference, size, BLOB_MARKER_SUFFIX
+    )
+}
+
+/// 占位字符串中的 blob 哈希；不是占位时返回 None
+pub fn blob_ref_hash(value: &Value) -> Option<&str> {
+    let (_, marker) = value.as_str()?.rsplit_once("\n\n")?;
+    let (reference, size) = marker
+        .strip_prefix(BLOB_MARKER_PREFIX)?
+        .strip_suffix(BLOB_MARKER_SUFFIX)?
+        .split_once(' ')?;
+    size.parse::<usize>().ok()?;
+    let hash = reference.strip_prefix(BLOB_SCHEME)?;
+    (hash.len() == 64 && hash.bytes().all(|b| b.is_ascii_hexdigit())).then_some(hash)
+}
+
+/// 校验引用并返回 blob 文件路径（只接受 sha256 十六进制，防止路径穿越）
+pub fn blob_path(dir: &Path, reference: &str) -> Result<PathBuf, String> {
+    let hash = reference.strip_prefix(BLOB_SCHEME).unwrap_or(reference);
+    if hash.len() != 64 || !hash.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(format!("Invalid blob reference: {}", reference));
+    }
+    Ok(dir.join(format!("{}.blob", hash)))
+}
+
+/// 读取 blob，返回外置前的字段原值
+pub fn read_blob(dir: &Path, reference: &str) -> Result<Value, String> {
+    let path = blob_path(dir, reference)?;
+    let bytes = fs::read(&path).map_err(|e| format!("Failed to read blob {:?}: {}", path, e))?;
+    serde_json::from_slice(&bytes).map_err(|e| format!("Failed to parse blob {:?}: {}", path, e))
+}
+
+/// 占位中的预览：字符串取本身，内容块数组取其中的文本，其余取 JSON 文本
+fn preview_text(value: &Value) -> String {
+    let text = match value {
+        Value::String(s) => s.clone(),
+        Value::Array(items) => {
+            let texts: Vec<&str> = items
+                .iter()
+                .filter_map(|item| item.get("text").and_then(|t| t.as_str()))
+                .collect();
+            if texts.is_empty() {
+                value.to_string()
+            } else {
+                texts.join("\n")
+            }
+        }
+        _ => value.to_string(),
+    };
+    truncate_chars(&text, PREVIEW_CHARS)
+}
+
+/// 把已序列化的值写成 blob，返回引用（`blob://<sha256>`）
+///
+/// 内容相同的 blob 已存在时直接复用，并刷新修改时间：
+/// 快照暂存目录按修改时间清理，仍在引用的 blob 不能被当作过期文件删掉
+fn write_blob_bytes(dir: &Path, bytes: &[u8]) -> Result<String, String> {
+    let hash = format!("{:x}", Sha256::digest(bytes));
+    let path = dir.join(format!("{}.blob", hash));
+    if path.exists() {
+        let touched = fs::File::options()
+            .append(true)
+            .open(&path)
+            .and_then(|file| file.set_modified(SystemTime::now()));
+        if let Err(e) = touched {
+            log::warn!("[SessionBlobs] Failed to refresh blob {:?}: {}", path, e);
+        }
+    } else {
+        fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create attachments directory: {}", e))?;
+        fs::write(&path, bytes).map_err(|e| format!("Failed to write blob {:?}: {}", path, e))?;
+    }
+    Ok(format!("{}{}", BLOB_SCHEME, hash))
+}
+
+/// 把任意值写成 blob，返回引用；`read_blob` 取回原值
+pub fn write_blob(dir: &Path, value: &Value) -> Result<String, String> {
+    let bytes =
+        serde_json::to_vec(value).map_err(|e| format!("Failed to serialize blob: {}", e))?;
+    write_blob_bytes(dir, &bytes)
+}
+
+/// 序列化后超过阈值时把字段写成 blob 并替换为占位，返回是否外置
+fn externalize_value(value: &mut Value, dir: &Path, threshold: usize) -> Result<bool, String> {
+    if blob_ref_hash(value).is_some() {
+        return Ok(false);
+    }
+    let bytes =
+        serde_json::to_vec(value).map_err(|e| format!("Failed to serialize tool result: {}", e))?;
+    if bytes.len() <= threshold {
+        return Ok(false);
+    }
+
+    let reference = write_blob_bytes(dir, &bytes)?;
+    *value = Value::String(placeholder_text(
+        &preview_text(value),
+        &reference,
+        bytes.len(),
+    ));
+    Ok(true)
+}
+
+/// 外置一条会话记录（Claude 消息或 Codex 事件）中超过阈值的工具结果，返回外置的字段数
+pub fn externalize_entry(entry: &mut Value, dir: &Path, threshold: usize) -> Result<usize, String> {
+    let mut count = 0;
+
+    // Claude: message.content[] 中的 tool_result
+    if let Some(blocks) = entry
+        .pointer_mut("/message/content")
+        .and_then(|c| c.as_array_mut())
+    {
+        for block in blocks {
+            if block.get("type").and_then(|t| t.as_str()) != Some("tool_result") {
+                continue;
+            }
+            if let Some(content) = block.get_mut("content") {
+                count += externalize_value(content, dir, threshold)? as usize;
+            }
+        }
+    }
+
+    // Claude: 顶层 toolUseResult 是工具结果的结构化副本，通常同样巨大
+    if let Some(result) = entry.get_mut("toolUseResult") {
+        count += externalize_value(result, dir, threshold)? as usize;
+    }
+
+    // Codex: function_call_output / custom_tool_call_output
+    if let Some(payload) = entry.get_mut("payload") {
+        let is_output = matches!(
+            payload.get("type").and_then(|t| t.as_str()),
+            Some("function_call_output" | "custom_tool_call_output")
+        );
+        if is_output {
+            if let Some(output) = payload.get_mut("output") {
+                count += externalize_value(output, dir, threshold)? as usize;
+            }
+        }
+    }
+
+    Ok(count)
+}
+
+/// 把一条记录中的所有占位替换为 blob 原值，返回还原的数量
+///
+/// blob 缺失或损坏时保留占位并记录警告，不中断读取
+pub fn inline_blobs(value: &mut Value, dir: &Path) -> usize {
+    if let Some(hash) = blob_ref_hash(value) {
+        return match read_blob(dir, hash) {
+            Ok(original) => {
+                *value = original;
+                1
+            }
+            Err(e) => {
+                log::warn!("[SessionBlobs] Keeping placeholder: {}", e);
+                0
+            }
+        };
+    }
+
+    match value {
+        Value::Array(items) => items.iter_mut().map(|item| inline_blobs(item, dir)).sum(),
+        Value::Object(map) => map.values_mut().map(|item| inline_blobs(item, dir)).sum(),
+        _ => 0,
+    }
+}
+
+/// 逐行把 `source` 改写为外置格式写到 `target`，blob 写入 `dir`；返回外置的字段数
+///
+/// 不超过阈值的行不可能含有需要外置的字段，原样复制；无法解析的行同样原样保留
+fn externalize_lines(
+    source: &Path,
+    target: &Path,
+    dir: &Path,
+    threshold: usize,
+) -> Result<usize, String> {
+    let file = fs::File::create(target)
+        .map_err(|e| format!("Failed to create session file {:?}: {}", target, e))?;
+    let mut writer = BufWriter::new(file);
+    let mut count = 0;
+    let mut result = Ok(());
+
+    for_each_raw_line(source, |_, line| {
+        let written = if line.len() > threshold {
+            match serde_json::from_slice::<Value>(line) {
+                Ok(mut entry) => externalize_entry(&mut entry, dir, threshold).and_then(|n| {
+                    count += n;
+                    let line = serde_json::to_vec(&entry)
+                        .map_err(|e| format!("Failed to serialize line: {}", e))?;
+                    writer
+                        .write_all(&line)
+                        .map_err(|e| format!("Failed to write line: {}", e))
+                }),
+                Err(_) => writer
+                    .write_all(line)
+                    .map_err(|e| format!("Failed to write line: {}", e)),
+            }
+        } else {
+            writer
+                .write_all(line)
+                .map_err(|e| format!("Failed to write line: {}", e))
+        };
+        match written.and_then(|()| {
+            writer
+                .write_all(b"\n")
+                .map_err(|e| format!("Failed to write line: {}", e))
+        }) {
+            Ok(()) => ControlFlow::Continue(()),
+            Err(e) => {
+                result = Err(e);
+                ControlFlow::Break(())
+            }
+        }
+    })
+    .map_err(|e| format!("Failed to read session file: {}", e))?;
+
+    result?;
+    writer
+        .flush()
+        .map_err(|e| format!("Failed to flush session file: {}", e))?;
+    Ok(count)
+}
+
+/// 改写过程中的临时文件
+fn optimizing_path(session_file: &Path) -> PathBuf {
+    let mut name = session_file.file_name().unwrap_or_default().to_os_string();
+    name.push(".optimizing");
+    session_file.with_file_name(name)
+}
+
+/// 把本应用刚写出的会话文件改写为外置格式（转换、导入等写入路径使用），返回外置的字段数
+///
+/// 没有需要外置的内容时不改写
+pub fn externalize_session_file(session_file: &Path, threshold: usize) -> Result<usize, String> {
+    let temp = optimizing_path(session_file);
+    let dir = attachments_dir(session_file);
+    let count = match externalize_lines(session_file, &temp, &dir, threshold) {
+        Ok(count) => count,
+        Err(e) => {
+            let _ = fs::remove_file(&temp);
+            return Err(e);
+        }
+    };
+
+    if count == 0 {
+        let _ = fs::remove_file(&temp);
+    } else {
+        fs::rename(&temp, session_file)
+            .map_err(|e| format!("Failed to replace session file: {}", e))?;
+    }
+    Ok(count)
+}
+
+/// 离线把已有会话改写为外置格式
+///
+/// 持有会话锁改写到临时文件，确认期间会话未被修改后替换；原文件保留为
+/// `<session>.jsonl.bak-<timestamp>`
+pub fn optimize_session_file(
+    session_file: &Path,
+    threshold: usize,
+) -> Result<SessionStorageReport, String> {
+    let _lock = SessionLock::acquire(session_file, "optimize session storage")?;
+    let snapshot = FileSnapshot::capture(session_file)?;
+    let original_bytes = fs::metadata(session_file)
+        .map_err(|e| format!("Failed to stat session file: {}", e))?
+        .len();
+
+    let temp = optimizing_path(session_file);
+    let dir = attachments_dir(session_file);
+    let externalized_count = match externalize_lines(session_file, &temp, &dir, threshold) {
+        Ok(count) => count,
+        Err(e) => {
+            let _ = fs::remove_file(&temp);
+            return Err(e);
+        }
+    };
+    if externalized_count == 0 {
+        let _ = fs::remove_file(&temp);
+        return Ok(SessionStorageReport {
+            original_bytes,
+            optimized_bytes: original_bytes,
+            externalized_count,
+            backup_path: None,
+        });
+    }
+
+    let mut backup_name = session_file.file_name().unwrap_or_default().to_os_string();
+    backup_name.push(format!(
+        ".bak-{}",
+        chrono::Utc::now().format("%Y%m%d%H%M%S")
+    ));
+    let backup_path = session_file.with_file_name(backup_name);
+    let replaced = snapshot
+        .ensure_unchanged(session_file)
+        .and_then(|()| {
+            fs::copy(session_file, &backup_path)
+                .map_err(|e| format!("Failed to back up session file: {}", e))
+        })
+        .and_then(|_| {
+            fs::rename(&temp, session_file)
+                .map_err(|e| format!("Failed to replace session file: {}", e))
+        });
+    if let Err(e) = replaced {
+        let _ = fs::remove_file(&temp);
+        return Err(e);
+    }
+
+    let optimized_bytes = fs::metadata(session_file)
+        .map_err(|e| format!("Failed to stat session file: {}", e))?
+        .len();
+    log::info!(
+        "[SessionBlobs] Optimized {:?}: {} -> {} bytes, {} tool results externalized, backup at {:?}",
+        session_file,
+        original_bytes,
+        optimized_bytes,
+        externalized_count,
+        backup_path
+    );
+    Ok(SessionStorageReport {
+        original_bytes,
+        optimized_bytes,
+        externalized_count,
+        backup_path: Some(backup_path.to_string_lossy().to_string()),
+    })
+}
+
+/// 定位支持外置存储的会话文件（Gemini 会话为单个 JSON 文件，不支持）
+fn resolve_jsonl_session(
+    engine: &str,
+    session_id: &str,
+    project_id_or_path: &str,
+) -> Result<PathBuf, String> {
+    if engine == "gemini" {
+        return Err("Gemini sessions do not support external blob storage".to_string());
+    }
+    resolve_session_file(engine, session_id, project_id_or_path)
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// 把已有会话中超过阈值（默认 64KB）的工具结果改写为外置存储，原文件备份
+///
+/// `project_id_or_path`：Claude 传项目 ID，Codex 可传空字符串
+#[tauri::command]
+pub async fn optimize_session_storage(
+    engine: String,
+    session_id: String,
+    project_id_or_path: String,
+    threshold_bytes: Option<usize>,
+) -> Result<SessionStorageReport, String> {
+    let session_file = resolve_jsonl_session(&engine, &session_id, &project_id_or_path)?;
+    let threshold = threshold_bytes
+        .filter(|t| *t > 0)
+        .unwrap_or(DEFAULT_BLOB_THRESHOLD);
+    tokio::task::spawn_blocking(move || optimize_session_file(&session_file, threshold))
+        .await
+        .map_err(|e| format!("Optimize task failed: {}", e))?
+}
+
+/// 取回占位引用的工具结果全文（字段原值：字符串或内容块数组）
+#[tauri::command]
+pub async fn get_message_blob(
+    engine: String,
+    session_id: String,
+    project_id_or_path: String,
+    reference: String,
+) -> Result<Value, String> {
+    let session_file = resolve_jsonl_session(&engine, &session_id, &project_id_or_path)?;
+    read_blob(&attachments_dir(&session_file), &reference)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn big(n: usize) -> String {
+        "x".repeat(n)
+    }
+
+    #[test]
+    fn externalizes_large_tool_results_and_inlines_them_back() {
+        let dir = tempfile::tempdir().unwrap();
+        let blobs = dir.path().join("attachments").join("s1");
+        let original = json!({
+            "type": "user",
+            "message": {"role": "user", "content": [
+                {"type": "tool_result", "tool_use_id": "t1", "content": big(100)},
+                {"type": "tool_result", "tool_use_id": "t2", "content": [{"type": "text", "text": big(5000)}]}
+            ]},
+            "toolUseResult": {"stdout": big(5000)}
+        });
+
+        let mut entry = original.clone();
+        assert_eq!(externalize_entry(&mut entry, &blobs, 1024).unwrap(), 2);
+        assert_eq!(entry["message"]["content"][0]["content"], big(100));
+        // 占位仍是字符串，CLI 续接会话时按普通工具结果读取
+        let placeholder = entry["message"]["content"][1]["content"].as_str().unwrap();
+        assert!(
+            placeholder.starts_with(&format!("{}…\n\n[anycode-blob blob://", big(PREVIEW_CHARS)))
+        );
+        assert!(blob_ref_hash(&entry["message"]["content"][1]["content"]).is_some());
+        assert!(entry["toolUseResult"].is_string());
+        assert!(blob_ref_hash(&entry["toolUseResult"]).is_some());
+        assert!(
+            blob_ref_hash(&json!("plain output\n\n[anycode-blob blob://abc 1 bytes]")).is_none()
+        );
+
+        // 已外置的字段不会重复处理
+        assert_eq!(externalize_entry(&mut entry, &blobs, 1024).unwrap(), 0);
+
+        assert_eq!(inline_blobs(&mut entry, &blobs), 2);
+        assert_eq!(entry, original);
+    }
+
+    #[test]
+    fn externalizes_codex_function_call_output() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut event = json!({
+            "type": "response_item",
+            "payload": {"type": "function_call_output", "call_id": "c1", "output": big(4096)}
+        });
+        assert_eq!(externalize_entry(&mut event, dir.path(), 1024).unwrap(), 1);
+
+        assert!(event["payload"]["output"].is_string());
+        let hash = blob_ref_hash(&event["payload"]["output"]).unwrap();
+        assert_eq!(read_blob(dir.path(), hash).unwrap(), json!(big(4096)));
+        assert!(read_blob(dir.path(), "blob://../../etc/passwd").is_err());
+    }
+
+    #[test]
+    fn optimize_shrinks_session_and_keeps_backup() {
+        let dir = tempfile::tempdir().unwrap();
+        let session = dir.path().join("s1.jsonl");
+        let small = json!({"type": "user", "message": {"role": "user", "content": "hi"}});
+        let heavy = json!({
+            "type": "user",
+            "message": {"role": "user", "content": [
+                {"type": "tool_result", "tool_use_id": "t1", "content": big(512 * 1024)}
+            ]}
+        });
+        let content = format!("{}\n{}\n", small, heavy);
+        fs::write(&session, &content).unwrap();
+
+        let report = optimize_session_file(&session, DEFAULT_BLOB_THRESHOLD).unwrap();
+        assert_eq!(report.externalized_count, 1);
+        assert!(report.optimized_bytes * 10 < report.original_bytes);
+        let backup = report.backup_path.unwrap();
+        assert_eq!(fs::read_to_string(&backup).unwrap(), content);
+
+        let optimized = fs::read_to_string(&session).unwrap();
+        let lines: Vec<Value> = optimized
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(lines[0], small);
+        let mut restored = lines[1].clone();
+        assert_eq!(inline_blobs(&mut restored, &attachments_dir(&session)), 1);
+        assert_eq!(restored, heavy);
+
+        // 再次优化没有可外置的内容，不改写也不备份
+        let again = optimize_session_file(&session, DEFAULT_BLOB_THRESHOLD).unwrap();
+        assert_eq!(again.externalized_count, 0);
+        assert!(again.backup_path.is_none());
+    }
+}
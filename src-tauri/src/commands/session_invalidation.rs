@@ -0,0 +1,57 @@
+//! 会话缓存失效
+//!
+//! 执行完成、回滚、截断或删除会话后，清除该会话在后端各读缓存中的条目
+//! （Gemini 会话读缓存与文件索引、Codex 会话索引、跨会话提示词历史），
+//! 再发送 `sessions:invalidated` 事件，前端据此重新拉取会话列表与详情，
+//! 消息数与最后活动时间无需手动刷新即可更新。
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+
+/// 会话失效事件名
+pub const SESSIONS_INVALIDATED_EVENT: &str = "sessions:invalidated";
+
+/// `sessions:invalidated` 事件负载
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionsInvalidated {
+    /// 引擎类型: "claude" | "codex" | "gemini"
+    pub engine: String,
+    /// 会话 ID；新会话尚未拿到 CLI 会话 ID 时为 None，前端刷新该引擎的整个列表
+    pub session_id: Option<String>,
+    /// 会话所属项目路径（已知时）
+    pub project_path: Option<String>,
+}
+
+/// 清除会话缓存并通知前端重新拉取
+pub fn invalidate_session(
+    app: &AppHandle,
+    engine: &str,
+    session_id: Option<&str>,
+    project_path: Option<&str>,
+) {
+    if let Some(session_id) = session_id {
+        clear_cached_session(engine, session_id);
+    }
+
+    let payload = SessionsInvalidated {
+        engine: engine.to_string(),
+        session_id: session_id.map(String::from),
+        project_path: project_path.map(String::from),
+    };
+    if let Err(e) = app.emit(SESSIONS_INVALIDATED_EVENT, &payload) {
+        log::warn!("Failed to emit {}: {}", SESSIONS_INVALIDATED_EVENT, e);
+    }
+}
+
+/// 清除单个会话在后端读缓存中的条目
+pub(crate) fn clear_cached_session(engine: &str, session_id: &str) {
+    log::debug!("Invalidating cached {} session: {}", engine, session_id);
+    match engine {
+        "gemini" => super::gemini::config::invalidate_gemini_session_cache(session_id),
+        "codex" => super::codex::session::invalidate_codex_session_index(session_id),
+        // Claude 会话列表每次都直接读取文件，没有会话级缓存
+        _ => {}
+    }
+    super::prompt_history::invalidate_cached_session(session_id);
+}
@@ -0,0 +1,787 @@
+//! 跨引擎会话工具
+//!
+//! 提供与具体引擎无关的会话辅助能力：
+//! - 校验会话记录的项目路径是否仍然存在（项目被删除/移动后提前预警）
+//! - 为已迁移的项目推测新位置（同名目录）
+//! - 按引擎解析会话文件的完整路径（所有会话路径查找的唯一入口）
+//! - 按文件大小列出会话，找出占用空间最多的会话以便清理
+//! - 查找项目最近更新的会话（任务完成后找回 CLI 会话 ID）
+//! - 会话文件内存加载上限配置
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::HashSet;
+use std::fs;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+use super::claude::get_claude_dir;
+use super::claude::normalize_path_for_comparison;
+use crate::utils::jsonl_reader::{
+    load_session_reader_config, save_session_reader_config, SessionReaderConfig,
+};
+
+/// 读取 Claude 会话文件时，查找 cwd 字段最多扫描的行数
+const CWD_SCAN_LINE_LIMIT: usize = 20;
+
+/// 会话文件不存在时的错误前缀，前端据此区分“会话已被删除”与其他读取失败
+pub const SESSION_NOT_FOUND_ERROR_PREFIX: &str = "NotFound";
+
+/// 统计会话记录数时的读取缓冲区大小
+const RECORD_COUNT_BUFFER_SIZE: usize = 64 * 1024;
+
+/// 会话项目路径校验结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectValidity {
+    /// 引擎类型: "claude" | "codex" | "gemini"
+    pub engine: String,
+    /// 会话 ID
+    pub session_id: String,
+    /// 会话中记录的项目路径（无法读取时为 None）
+    pub recorded_path: Option<String>,
+    /// 记录的路径是否存在
+    pub exists: bool,
+    /// 记录的路径是否可访问（是目录且可读取）
+    pub accessible: bool,
+    /// 推测的新位置（找到同名目录时）
+    pub suggested_path: Option<String>,
+    /// 所有候选位置（按可信度排序）
+    pub candidates: Vec<String>,
+    /// 给前端展示的提示信息
+    pub message: Option<String>,
+}
+
+/// 检查会话记录的项目路径是否仍然存在且可访问
+///
+/// - Claude: `project_id` 为 ~/.claude/projects 下的目录名，路径取自会话文件中的 cwd
+/// - Codex: 路径取自 session_meta 中的 cwd，`project_id` 不参与定位
+/// - Gemini: 会话按项目路径哈希存储，`project_id` 即项目路径
+#[tauri::command]
+pub async fn validate_session_project(
+    engine: String,
+    session_id: String,
+    project_id: String,
+) -> Result<ProjectValidity, String> {
+    log::info!(
+        "validate_session_project called: engine={}, session_id={}, project_id={}",
+        engine,
+        session_id,
+        project_id
+    );
+
+    let recorded_path = match engine.as_str() {
+        "claude" => read_claude_session_cwd(&session_id, &project_id)?,
+        "codex" => read_codex_session_cwd(&session_id)?,
+        "gemini" => Some(project_id.clone()),
+        _ => return Err(format!("Unknown engine: {}", engine)),
+    };
+
+    let Some(recorded) = recorded_path.filter(|p| !p.trim().is_empty()) else {
+        return Ok(ProjectValidity {
+            engine,
+            session_id,
+            recorded_path: None,
+            exists: false,
+            accessible: false,
+            suggested_path: None,
+            candidates: Vec::new(),
+            message: Some("会话中未记录项目路径".to_string()),
+        });
+    };
+
+    let path = Path::new(&recorded);
+    let exists = path.exists();
+    let accessible = path.is_dir() && fs::read_dir(path).is_ok();
+
+    if accessible {
+        return Ok(ProjectValidity {
+            engine,
+            session_id,
+            recorded_path: Some(recorded),
+            exists,
+            accessible,
+            suggested_path: None,
+            candidates: Vec::new(),
+            message: None,
+        });
+    }
+
+    let known_paths = collect_known_project_paths().await;
+    let candidates = find_relocation_candidates(&recorded, &known_paths);
+    let suggested_path = candidates.first().cloned();
+
+    let message = if exists {
+        format!("项目路径存在但无法访问: {}", recorded)
+    } else if let Some(ref suggestion) = suggested_path {
+        format!("项目路径不存在: {}，可能已移动到 {}", recorded, suggestion)
+    } else {
+        format!("项目路径不存在: {}", recorded)
+    };
+
+    log::warn!("[SessionUtils] {}", message);
+
+    Ok(ProjectValidity {
+        engine,
+        session_id,
+        recorded_path: Some(recorded),
+        exists,
+        accessible,
+        suggested_path,
+        candidates,
+        message: Some(message),
+    })
+}
+
+/// 获取会话文件读取配置（内存加载上限）
+#[tauri::command]
+pub async fn get_session_reader_config() -> Result<SessionReaderConfig, String> {
+    Ok(load_session_reader_config())
+}
+
+/// 更新会话文件读取配置
+#[tauri::command]
+pub async fn update_session_reader_config(config: SessionReaderConfig) -> Result<(), String> {
+    if config.max_in_memory_mb == 0 {
+        return Err("内存加载上限必须大于 0 MB".to_string());
+    }
+    log::info!(
+        "Updating session reader config: max_in_memory_mb={}",
+        config.max_in_memory_mb
+    );
+    save_session_reader_config(&config)
+}
+
+/// 获取会话文件的完整路径
+///
+/// `project_id_or_path` 的含义与 [`resolve_session_file`] 相同；
+/// 会话文件不存在时返回以 `NotFound` 开头的错误
+#[tauri::command]
+pub async fn resolve_session_path(
+    engine: String,
+    session_id: String,
+    project_id_or_path: String,
+) -> Result<String, String> {
+    tokio::task::spawn_blocking(move || {
+        resolve_session_file(&engine, &session_id, &project_id_or_path)
+            .map(|path| path.to_string_lossy().to_string())
+    })
+    .await
+    .map_err(|e| format!("Session path resolution task failed: {}", e))?
+}
+
+// ============================================================================
+// Session Path Resolution
+// ============================================================================
+
+/// 按引擎解析会话文件的完整路径
+///
+/// - Claude: `project_id_or_path` 为 ~/.claude/projects 下的目录名
+/// - Codex: 在 sessions 目录中按 session_meta 的 id 查找，`project_id_or_path` 不参与定位
+/// - Gemini: `project_id_or_path` 为项目路径，在其哈希目录的 chats 下查找
+pub fn resolve_session_file(
+    engine: &str,
+    session_id: &str,
+    project_id_or_path: &str,
+) -> Result<PathBuf, String> {
+    find_session_path(engine, session_id, project_id_or_path)?
+        .ok_or_else(|| session_not_found_error(engine, session_id))
+}
+
+/// 与 `resolve_session_file` 相同，但会话文件不存在时返回 `Ok(None)`
+pub fn find_session_path(
+    engine: &str,
+    session_id: &str,
+    project_id_or_path: &str,
+) -> Result<Option<PathBuf>, String> {
+    let root = match engine {
+        "claude" => claude_projects_dir()?,
+        "codex" => super::codex::get_codex_sessions_dir()?,
+        "gemini" => super::gemini::config::get_gemini_dir()?,
+        _ => return Err(format!("Unknown engine: {}", engine)),
+    };
+    Ok(find_session_in(
+        engine,
+        &root,
+        session_id,
+        project_id_or_path,
+    ))
+}
+
+/// 在引擎的根目录（Claude projects、Codex sessions、Gemini 配置目录）下查找会话文件
+fn find_session_in(
+    engine: &str,
+    root: &Path,
+    session_id: &str,
+    project_id_or_path: &str,
+) -> Option<PathBuf> {
+    match engine {
+        "claude" => {
+            let session_path = root
+                .join(project_id_or_path)
+                .join(format!("{}.jsonl", session_id));
+            session_path.is_file().then_some(session_path)
+        }
+        "codex" => super::codex::find_session_file(root, session_id),
+        "gemini" => {
+            let sessions_dir =
+                super::gemini::git_ops::gemini_sessions_dir_in(root, project_id_or_path);
+            if sessions_dir.is_dir() {
+                super::gemini::git_ops::find_gemini_session_file(&sessions_dir, session_id).ok()
+            } else {
+                None
+            }
+        }
+        _ => None,
+    }
+}
+
+/// ~/.claude/projects
+fn claude_projects_dir() -> Result<PathBuf, String> {
+    Ok(get_claude_dir()
+        .map_err(|e| format!("Failed to get Claude directory: {}", e))?
+        .join("projects"))
+}
+
+/// Claude 项目目录：~/.claude/projects/<project_id>
+pub fn claude_project_dir(project_id: &str) -> Result<PathBuf, String> {
+    Ok(claude_projects_dir()?.join(project_id))
+}
+
+/// Claude 会话文件的路径，不检查是否存在（写出新会话时使用；读取已有会话请用 `resolve_session_file`）
+pub fn claude_session_path(project_id: &str, session_id: &str) -> Result<PathBuf, String> {
+    Ok(claude_project_dir(project_id)?.join(format!("{}.jsonl", session_id)))
+}
+
+/// 构造会话文件不存在的错误信息
+fn session_not_found_error(engine: &str, session_id: &str) -> String {
+    format!(
+        "{}: Session file not found for {} session: {}",
+        SESSION_NOT_FOUND_ERROR_PREFIX, engine, session_id
+    )
+}
+
+// ============================================================================
+// Session Size Statistics
+// ============================================================================
+
+/// 会话文件占用统计
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionSize {
+    pub session_id: String,
+    /// Claude 为项目目录名，Codex 为会话记录的 cwd，Gemini 为项目哈希目录名
+    pub project_id: Option<String>,
+    pub file_path: String,
+    pub size_bytes: u64,
+    /// Claude/Codex 为 JSONL 记录行数，Gemini 为 messages 数量
+    pub message_count: usize,
+    /// 会话文件最后修改时间（Unix 秒）
+    pub last_updated: i64,
+}
+
+/// 扫描阶段收集的会话文件信息（只 stat，不解析内容）
+struct SessionFileStat {
+    path: PathBuf,
+    session_id: Option<String>,
+    project_id: Option<String>,
+    size_bytes: u64,
+    last_updated: i64,
+}
+
+/// 按文件大小降序列出会话，用于找出占用空间最多的会话
+///
+/// - Claude: `project_id` 为 ~/.claude/projects 下的目录名
+/// - Codex: `project_id` 为项目路径，按 session_meta 记录的 cwd 过滤
+/// - Gemini: `project_id` 为项目路径，只扫描其哈希目录
+///
+/// 不传 `project_id` 时扫描该引擎的全部会话
+#[tauri::command]
+pub async fn list_sessions_by_size(
+    engine: String,
+    project_id: Option<String>,
+    limit: usize,
+) -> Result<Vec<SessionSize>, String> {
+    tokio::task::spawn_blocking(move || {
+        collect_sessions_by_size(&engine, project_id.as_deref(), limit)
+    })
+    .await
+    .map_err(|e| format!("Session size scan task failed: {}", e))?
+}
+
+/// 先 stat 全部会话文件并按大小排序截断，只对保留下来的会话统计消息数
+fn collect_sessions_by_size(
+    engine: &str,
+    project_id: Option<&str>,
+    limit: usize,
+) -> Result<Vec<SessionSize>, String> {
+    let mut files = match engine {
+        "claude" => scan_claude_session_files(project_id)?,
+        "codex" => scan_codex_session_files(project_id)?,
+        "gemini" => scan_gemini_session_files(project_id)?,
+        _ => return Err(format!("Unknown engine: {}", engine)),
+    };
+
+    files.sort_by_key(|file| Reverse(file.size_bytes));
+    files.truncate(limit);
+
+    Ok(files
+        .into_iter()
+        .map(|file| {
+            let (session_id, message_count) = if engine == "gemini" {
+                read_gemini_session_summary(&file.path)
+            } else {
+                (None, count_jsonl_records(&file.path))
+            };
+            let session_id = file
+                .session_id
+                .or(session_id)
+                .unwrap_or_else(|| file_stem(&file.path));
+
+            SessionSize {
+                session_id,
+                project_id: file.project_id,
+                file_path: file.path.to_string_lossy().to_string(),
+                size_bytes: file.size_bytes,
+                message_count,
+                last_updated: file.last_updated,
+            }
+        })
+        .collect())
+}
+
+/// 扫描 ~/.claude/projects 下的会话文件，会话 ID 即文件名
+fn scan_claude_session_files(project_id: Option<&str>) -> Result<Vec<SessionFileStat>, String> {
+    let projects_dir = claude_projects_dir()?;
+    let project_dirs: Vec<PathBuf> = match project_id {
+        Some(id) => vec![projects_dir.join(id)],
+        None => match fs::read_dir(&projects_dir) {
+            Ok(entries) => entries.flatten().map(|entry| entry.path()).collect(),
+            Err(_) => Vec::new(),
+        },
+    };
+
+    let mut files = Vec::new();
+    for project_dir in project_dirs {
+        let project_name = project_dir
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string());
+        let Ok(entries) = fs::read_dir(&project_dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("jsonl") {
+                continue;
+            }
+            if let Some(mut stat) = stat_session_file(&path) {
+                stat.session_id = Some(file_stem(&path));
+                stat.project_id = project_name.clone();
+                files.push(stat);
+            }
+        }
+    }
+
+    Ok(files)
+}
+
+/// 扫描 Codex sessions 目录，只读取首行的 session_meta 获取会话 ID 与 cwd
+fn scan_codex_session_files(project_path: Option<&str>) -> Result<Vec<SessionFileStat>, String> {
+    let sessions_dir = super::codex::get_codex_sessions_dir()?;
+    let wanted_project = project_path.map(normalize_path_for_comparison);
+
+    let mut files = Vec::new();
+    for entry in WalkDir::new(&sessions_dir).into_iter().flatten() {
+        let path = entry.path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some("jsonl") {
+            continue;
+        }
+        let Some((session_id, cwd)) = read_codex_session_meta(path) else {
+            continue;
+        };
+        if let Some(ref wanted) = wanted_project {
+            if cwd.as_deref().map(normalize_path_for_comparison).as_ref() != Some(wanted) {
+                continue;
+            }
+        }
+        if let Some(mut stat) = stat_session_file(path) {
+            stat.session_id = Some(session_id);
+            stat.project_id = cwd;
+            files.push(stat);
+        }
+    }
+
+    Ok(files)
+}
+
+/// 扫描 ~/.gemini/tmp/<hash>/chats 下的会话文件（会话 ID 在截断后再读取）
+fn scan_gemini_session_files(project_path: Option<&str>) -> Result<Vec<SessionFileStat>, String> {
+    let chats_dirs: Vec<PathBuf> = match project_path {
+        Some(path) => vec![super::gemini::git_ops::get_gemini_sessions_dir(path)?],
+        None => {
+            let tmp_dir = super::gemini::config::get_gemini_dir()?.join("tmp");
+            match fs::read_dir(&tmp_dir) {
+                Ok(entries) => entries
+                    .flatten()
+                    .map(|entry| entry.path().join("chats"))
+                    .collect(),
+                Err(_) => Vec::new(),
+            }
+        }
+    };
+
+    let mut files = Vec::new();
+    for chats_dir in chats_dirs {
+        let project_hash = chats_dir
+            .parent()
+            .and_then(|dir| dir.file_name())
+            .map(|name| name.to_string_lossy().to_string());
+        let Ok(entries) = fs::read_dir(&chats_dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+                continue;
+            }
+            if let Some(mut stat) = stat_session_file(&path) {
+                stat.project_id = project_hash.clone();
+                files.push(stat);
+            }
+        }
+    }
+
+    Ok(files)
+}
+
+/// 读取文件大小与修改时间，非普通文件返回 None
+fn stat_session_file(path: &Path) -> Option<SessionFileStat> {
+    let metadata = fs::metadata(path).ok()?;
+    if !metadata.is_file() {
+        return None;
+    }
+    let last_updated = metadata
+        .modified()
+        .map(|time| DateTime::<Utc>::from(time).timestamp())
+        .unwrap_or(0);
+
+    Some(SessionFileStat {
+        path: path.to_path_buf(),
+        session_id: None,
+        project_id: None,
+        size_bytes: metadata.len(),
+        last_updated,
+    })
+}
+
+/// 从 Codex 会话文件首行的 session_meta 中读取会话 ID 与 cwd
+pub(crate) fn read_codex_session_meta(path: &Path) -> Option<(String, Option<String>)> {
+    let file = fs::File::open(path).ok()?;
+    let first_line = BufReader::new(file).lines().next()?.ok()?;
+    let meta: serde_json::Value = serde_json::from_str(&first_line).ok()?;
+    if meta["type"].as_str() != Some("session_meta") {
+        return None;
+    }
+
+    let session_id = meta["payload"]["id"].as_str()?.to_string();
+    let cwd = meta["payload"]["cwd"].as_str().map(|s| s.to_string());
+    Some((session_id, cwd))
+}
+
+/// 读取 Gemini 会话文件中的 sessionId 与消息数量
+fn read_gemini_session_summary(path: &Path) -> (Option<String>, usize) {
+    let json = fs::read(path)
+        .ok()
+        .and_then(|bytes| serde_json::from_slice::<serde_json::Value>(&bytes).ok());
+    let Some(json) = json else {
+        return (None, 0);
+    };
+
+    let session_id = json["sessionId"].as_str().map(|s| s.to_string());
+    let message_count = json["messages"].as_array().map_or(0, |m| m.len());
+    (session_id, message_count)
+}
+
+/// 按换行符统计 JSONL 记录数，不解析 JSON
+fn count_jsonl_records(path: &Path) -> usize {
+    let Ok(file) = fs::File::open(path) else {
+        return 0;
+    };
+    let mut reader = BufReader::with_capacity(RECORD_COUNT_BUFFER_SIZE, file);
+    let mut count = 0;
+    let mut ends_with_newline = true;
+
+    loop {
+        let buf = match reader.fill_buf() {
+            Ok(buf) if !buf.is_empty() => buf,
+            _ => break,
+        };
+        count += buf.iter().filter(|&&byte| byte == b'\n').count();
+        ends_with_newline = buf.last() == Some(&b'\n');
+        let len = buf.len();
+        reader.consume(len);
+    }
+
+    // 最后一行没有换行符时也算一条记录
+    if !ends_with_newline {
+        count += 1;
+    }
+    count
+}
+
+/// 文件名（不含扩展名）
+fn file_stem(path: &Path) -> String {
+    path.file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+/// 从 Claude 会话文件中读取记录的 cwd
+fn read_claude_session_cwd(session_id: &str, project_id: &str) -> Result<Option<String>, String> {
+    let session_path = resolve_session_file("claude", session_id, project_id)?;
+
+    read_cwd_from_jsonl(&session_path)
+}
+
+/// 扫描 JSONL 前若干行，返回第一个 cwd 字段
+///
+/// JSON 解析已还原转义，cwd 需原样返回；再折叠反斜杠会把 UNC 路径 `\\server\share` 变成 `\server\share`
+fn read_cwd_from_jsonl(session_path: &Path) -> Result<Option<String>, String> {
+    let file =
+        fs::File::open(session_path).map_err(|e| format!("Failed to open session file: {}", e))?;
+    let reader = BufReader::new(file);
+
+    for line in reader.lines().take(CWD_SCAN_LINE_LIMIT).flatten() {
+        if let Ok(json) = serde_json::from_str::<serde_json::Value>(&line) {
+            if let Some(cwd) = json.get("cwd").and_then(|v| v.as_str()) {
+                return Ok(Some(cwd.to_string()));
+            }
+        }
+    }
+
+    Ok(None)
+}
+
+/// 从 Codex 会话的 session_meta 中读取记录的 cwd
+fn read_codex_session_cwd(session_id: &str) -> Result<Option<String>, String> {
+    let session_file = resolve_session_file("codex", session_id, "")?;
+
+    Ok(super::codex::parse_codex_session_file(&session_file).map(|s| s.project_path))
+}
+
+/// 汇总各引擎已知的项目路径（仅保留当前存在的目录）
+async fn collect_known_project_paths() -> Vec<String> {
+    let mut paths = Vec::new();
+
+    match super::claude::list_projects().await {
+        Ok(projects) => paths.extend(projects.into_iter().map(|p| p.path)),
+        Err(e) => log::debug!("[SessionUtils] Failed to list Claude projects: {}", e),
+    }
+
+    match super::codex::list_codex_sessions(None, None).await {
+        Ok(sessions) => paths.extend(sessions.into_iter().map(|s| s.project_path)),
+        Err(e) => log::debug!("[SessionUtils] Failed to list Codex sessions: {}", e),
+    }
+
+    let mut seen = HashSet::new();
+    paths
+        .into_iter()
+        .filter(|p| !p.is_empty() && Path::new(p).is_dir())
+        .filter(|p| seen.insert(normalize_path_for_comparison(p)))
+        .collect()
+}
+
+/// 根据同名目录推测项目的新位置
+///
+/// 优先级：
+/// 1. 记录路径各级仍存在的祖先目录下的同名子目录（项目在同一棵目录树内移动）
+/// 2. 其他引擎已知项目中目录名相同的路径
+fn find_relocation_candidates(recorded: &str, known_paths: &[String]) -> Vec<String> {
+    let normalized = recorded.replace('\\', "/");
+    let recorded_path = Path::new(&normalized);
+    let Some(basename) = recorded_path.file_name().and_then(|n| n.to_str()) else {
+        return Vec::new();
+    };
+    let recorded_key = normalize_path_for_comparison(recorded);
+
+    let mut seen = HashSet::new();
+    let mut candidates = Vec::new();
+    let mut push_candidate = |candidate: PathBuf, candidates: &mut Vec<String>| {
+        let candidate_str = candidate.to_string_lossy().to_string();
+        let key = normalize_path_for_comparison(&candidate_str);
+        if key != recorded_key && candidate.is_dir() && seen.insert(key) {
+            candidates.push(candidate_str);
+        }
+    };
+
+    for ancestor in Path::new(recorded).ancestors().skip(1) {
+        if ancestor.as_os_str().is_empty() || !ancestor.is_dir() {
+            continue;
+        }
+        push_candidate(ancestor.join(basename), &mut candidates);
+    }
+
+    for known in known_paths {
+        let known_normalized = known.replace('\\', "/");
+        let known_basename = Path::new(&known_normalized)
+            .file_name()
+            .and_then(|n| n.to_str());
+        if known_basename.map(|n| n.eq_ignore_ascii_case(basename)) == Some(true) {
+            push_candidate(PathBuf::from(known), &mut candidates);
+        }
+    }
+
+    candidates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_same_basename_in_surviving_ancestor() {
+        let temp = tempfile::tempdir().unwrap();
+        let moved = temp.path().join("my-project");
+        fs::create_dir_all(&moved).unwrap();
+
+        let recorded = temp.path().join("old").join("my-project");
+        let candidates = find_relocation_candidates(&recorded.to_string_lossy(), &[]);
+
+        assert_eq!(candidates, vec![moved.to_string_lossy().to_string()]);
+    }
+
+    #[test]
+    fn uses_known_project_paths_with_same_basename() {
+        let temp = tempfile::tempdir().unwrap();
+        // 罕见的目录名，避免祖先目录扫描命中宿主机上真实存在的同名目录（如 /app）
+        let elsewhere = temp.path().join("workspace").join("relocated-anycode-app");
+        let unrelated = temp.path().join("workspace").join("other");
+        fs::create_dir_all(&elsewhere).unwrap();
+        fs::create_dir_all(&unrelated).unwrap();
+
+        let known = vec![
+            unrelated.to_string_lossy().to_string(),
+            elsewhere.to_string_lossy().to_string(),
+        ];
+        let candidates =
+            find_relocation_candidates("/nonexistent/deleted/relocated-anycode-app", &known);
+
+        assert_eq!(candidates, vec![elsewhere.to_string_lossy().to_string()]);
+    }
+
+    #[test]
+    fn counts_jsonl_records_with_and_without_trailing_newline() {
+        let temp = tempfile::tempdir().unwrap();
+        let with_newline = temp.path().join("a.jsonl");
+        let without_newline = temp.path().join("b.jsonl");
+        let empty = temp.path().join("c.jsonl");
+        fs::write(&with_newline, "{\"a\":1}\n{\"b\":2}\n").unwrap();
+        fs::write(&without_newline, "{\"a\":1}\n{\"b\":2}").unwrap();
+        fs::write(&empty, "").unwrap();
+
+        assert_eq!(count_jsonl_records(&with_newline), 2);
+        assert_eq!(count_jsonl_records(&without_newline), 2);
+        assert_eq!(count_jsonl_records(&empty), 0);
+    }
+
+    #[test]
+    fn keeps_unc_cwd_intact() {
+        let temp = tempfile::tempdir().unwrap();
+        let session = temp.path().join("abc.jsonl");
+        fs::write(
+            &session,
+            r#"{"type":"summary"}
+{"cwd":"\\\\server\\share\\repo"}
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            read_cwd_from_jsonl(&session).unwrap(),
+            Some(r"\\server\share\repo".to_string())
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_engine_when_resolving_session_path() {
+        let err = resolve_session_file("copilot", "abc", "/tmp/project").unwrap_err();
+
+        assert_eq!(err, "Unknown engine: copilot");
+    }
+
+    #[test]
+    fn resolves_claude_session_by_project_directory() {
+        let root = tempfile::tempdir().unwrap();
+        let project = root.path().join("-home-me-app");
+        fs::create_dir_all(&project).unwrap();
+        fs::write(project.join("abc.jsonl"), "{}\n").unwrap();
+
+        assert_eq!(
+            find_session_in("claude", root.path(), "abc", "-home-me-app"),
+            Some(project.join("abc.jsonl"))
+        );
+        assert_eq!(
+            find_session_in("claude", root.path(), "abc", "-other"),
+            None
+        );
+    }
+
+    #[test]
+    fn resolves_codex_session_by_meta_id() {
+        let root = tempfile::tempdir().unwrap();
+        let day = root.path().join("2025").join("12").join("01");
+        fs::create_dir_all(&day).unwrap();
+        let rollout = day.join("rollout-2025-12-01T09-00-00-abc.jsonl");
+        fs::write(
+            &rollout,
+            "{\"type\":\"session_meta\",\"payload\":{\"id\":\"abc\"}}\n",
+        )
+        .unwrap();
+
+        // 项目参数不参与 Codex 会话的定位
+        assert_eq!(
+            find_session_in("codex", root.path(), "abc", ""),
+            Some(rollout)
+        );
+    }
+
+    #[test]
+    fn resolves_gemini_session_under_project_hash() {
+        let root = tempfile::tempdir().unwrap();
+        let session_id = "1234abcd-0000-0000-0000-000000000000";
+        let chats = super::super::gemini::git_ops::gemini_sessions_dir_in(root.path(), "/work/app");
+        fs::create_dir_all(&chats).unwrap();
+        let session = chats.join("session-2025-12-01T09-00-1234abcd.json");
+        fs::write(&session, format!("{{\"sessionId\":\"{}\"}}", session_id)).unwrap();
+
+        assert_eq!(
+            find_session_in("gemini", root.path(), session_id, "/work/app"),
+            Some(session)
+        );
+        assert_eq!(
+            find_session_in("gemini", root.path(), session_id, "/work/other"),
+            None
+        );
+    }
+
+    #[test]
+    fn missing_sessions_are_reported_as_not_found() {
+        let root = tempfile::tempdir().unwrap();
+        for (engine, project) in [("claude", "-app"), ("codex", ""), ("gemini", "/work/app")] {
+            assert_eq!(
+                find_session_in(engine, root.path(), "missing", project),
+                None
+            );
+        }
+
+        let err = session_not_found_error("codex", "missing");
+        assert!(err.starts_with(SESSION_NOT_FOUND_ERROR_PREFIX));
+        assert!(err.ends_with("codex session: missing"));
+    }
+}
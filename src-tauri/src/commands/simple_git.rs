@@ -1,10 +1,70 @@
 use log;
-use std::path::Path;
+use std::fs;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
+use super::input_validation::validate_path_within;
+
 #[cfg(target_os = "windows")]
 use std::os::windows::process::CommandExt;
 
+/// 一次性提交身份（仓库及全局均未配置 user.name/user.email 时使用，不写入任何 git 配置）
+const FALLBACK_USER_NAME: &str = "AnyCode";
+const FALLBACK_USER_EMAIL: &str = "anycode@local";
+
+/// Read an effective git config value (local + global + system)
+fn git_config_value(project_path: &str, key: &str) -> Option<String> {
+    let mut cmd = Command::new("git");
+    cmd.args(["config", "--get", key]);
+    cmd.current_dir(project_path);
+
+    #[cfg(target_os = "windows")]
+    cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
+
+    let output = cmd.output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    let value = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!value.is_empty()).then_some(value)
+}
+
+/// Check whether the repository has a usable commit identity (user.name and user.email)
+pub fn has_git_identity(project_path: &str) -> bool {
+    git_config_value(project_path, "user.name").is_some()
+        && git_config_value(project_path, "user.email").is_some()
+}
+
+/// Build `-c user.name=... -c user.email=...` arguments for commits
+/// Returns an empty list when the identity is configured; when missing, either the one-off
+/// fallback identity (allow_fallback) or an error asking the user to configure git
+fn commit_identity_args(project_path: &str, allow_fallback: bool) -> Result<Vec<String>, String> {
+    if has_git_identity(project_path) {
+        return Ok(Vec::new());
+    }
+
+    if !allow_fallback {
+        return Err(
+            "Git 用户身份未配置（user.name / user.email），请执行 git config --global user.name 与 user.email 后重试"
+                .to_string(),
+        );
+    }
+
+    log::info!(
+        "Git identity missing in {}, committing with one-off identity {} <{}>",
+        project_path,
+        FALLBACK_USER_NAME,
+        FALLBACK_USER_EMAIL
+    );
+    Ok(vec![
+        "-c".to_string(),
+        format!("user.name={}", FALLBACK_USER_NAME),
+        "-c".to_string(),
+        format!("user.email={}", FALLBACK_USER_EMAIL),
+    ])
+}
+
 /// Check if a directory is a Git repository
 pub fn is_git_repo(project_path: &str) -> bool {
     Path::new(project_path).join(".git").exists()
@@ -48,20 +108,8 @@ pub fn ensure_git_repo(project_path: &str) -> Result<(), String> {
         log::info!("Git repository exists but has no commits, creating initial commit");
     }
 
-    // Configure Git user if not set (needed for commits)
-    let mut config_name = Command::new("git");
-    config_name.args(["config", "user.name", "Claude Workbench"]);
-    config_name.current_dir(project_path);
-    #[cfg(target_os = "windows")]
-    config_name.creation_flags(0x08000000);
-    let _ = config_name.output();
-
-    let mut config_email = Command::new("git");
-    config_email.args(["config", "user.email", "ai@claude.workbench"]);
-    config_email.current_dir(project_path);
-    #[cfg(target_os = "windows")]
-    config_email.creation_flags(0x08000000);
-    let _ = config_email.output();
+    // Use a one-off identity if none is configured (never touches the user's git config)
+    let identity_args = commit_identity_args(project_path, true)?;
 
     // CRITICAL: Add all existing files first to preserve user code!
     log::info!("Adding all existing files to git staging area...");
@@ -84,6 +132,7 @@ pub fn ensure_git_repo(project_path: &str) -> Result<(), String> {
     // Create initial commit with all current files
     // Use --allow-empty as fallback in case there are no files
     let mut commit_cmd = Command::new("git");
+    commit_cmd.args(&identity_args);
     commit_cmd.args([
         "commit",
         "--allow-empty",
@@ -137,9 +186,96 @@ pub fn git_current_commit(project_path: &str) -> Result<String, String> {
     Ok(commit)
 }
 
+/// List files with uncommitted changes (staged, unstaged and untracked)
+/// Paths are relative to project_path, using "/" separators
+pub fn git_uncommitted_files(project_path: &str) -> Result<Vec<String>, String> {
+    let mut files = Vec::new();
+
+    for args in [
+        &["diff", "--name-only", "--relative", "HEAD"][..],
+        &["ls-files", "--others", "--exclude-standard"][..],
+    ] {
+        let mut cmd = Command::new("git");
+        cmd.args(args);
+        cmd.current_dir(project_path);
+
+        #[cfg(target_os = "windows")]
+        cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
+
+        let output = cmd
+            .output()
+            .map_err(|e| format!("Failed to list uncommitted files: {}", e))?;
+
+        if !output.status.success() {
+            return Err(format!(
+                "Git {} failed: {}",
+                args[0],
+                String::from_utf8_lossy(&output.stderr)
+            ));
+        }
+
+        files.extend(
+            String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+                .map(str::to_string),
+        );
+    }
+
+    files.sort();
+    files.dedup();
+    Ok(files)
+}
+
+/// List tracked and untracked files, skipping those excluded by .gitignore
+/// Paths are relative to project_path, using "/" separators
+pub fn git_list_files(project_path: &str) -> Result<Vec<String>, String> {
+    let mut cmd = Command::new("git");
+    cmd.args([
+        "ls-files",
+        "-z",
+        "--cached",
+        "--others",
+        "--exclude-standard",
+    ]);
+    cmd.current_dir(project_path);
+
+    #[cfg(target_os = "windows")]
+    cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
+
+    let output = cmd
+        .output()
+        .map_err(|e| format!("Failed to list files: {}", e))?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "Git ls-files failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+
+    let mut files: Vec<String> = String::from_utf8_lossy(&output.stdout)
+        .split('\0')
+        .filter(|path| !path.is_empty())
+        .map(str::to_string)
+        .collect();
+    files.sort();
+    files.dedup();
+    Ok(files)
+}
+
 /// Commit all changes with a message
+/// allow_identity_fallback: commit with a one-off identity when user.name/email are missing
 /// Returns: Ok(true) if committed, Ok(false) if no changes, Err if failed
-pub fn git_commit_changes(project_path: &str, message: &str) -> Result<bool, String> {
+pub fn git_commit_changes(
+    project_path: &str,
+    message: &str,
+    allow_identity_fallback: bool,
+) -> Result<bool, String> {
+    ensure_clean_repo_state(project_path)?;
+    let identity_args = commit_identity_args(project_path, allow_identity_fallback)?;
+
     // Stage all changes
     let mut add_cmd = Command::new("git");
     add_cmd.args(["add", "-A"]);
@@ -161,6 +297,7 @@ pub fn git_commit_changes(project_path: &str, message: &str) -> Result<bool, Str
 
     // Commit changes (always create a commit, even if empty)
     let mut commit_cmd = Command::new("git");
+    commit_cmd.args(&identity_args);
     commit_cmd.args(["commit", "--allow-empty", "-m", message]);
     commit_cmd.current_dir(project_path);
 
@@ -280,11 +417,18 @@ pub fn git_revert_range_with_retry(
     commit_after: &str,
     message: &str,
     max_retries: u32,
+    allow_identity_fallback: bool,
 ) -> Result<RevertResult, String> {
     let mut last_error = String::new();
 
     for attempt in 0..max_retries {
-        match git_revert_range(project_path, commit_before, commit_after, message) {
+        match git_revert_range(
+            project_path,
+            commit_before,
+            commit_after,
+            message,
+            allow_identity_fallback,
+        ) {
             Ok(result) => {
                 if attempt > 0 {
                     log::info!(
@@ -359,7 +503,32 @@ pub fn git_revert_range(
     commit_before: &str,
     commit_after: &str,
     message: &str,
+    allow_identity_fallback: bool,
 ) -> Result<RevertResult, String> {
+    git_revert_range_with_strategy(
+        project_path,
+        commit_before,
+        commit_after,
+        message,
+        OnConflict::Abort,
+        allow_identity_fallback,
+    )
+}
+
+/// Precisely revert a range of commits, handling conflicts according to `on_conflict`
+///
+/// With `OnConflict::Interactive` a conflicting revert is left in progress (not aborted)
+/// so the conflicts can be resolved with resolve_revert_conflict.
+pub fn git_revert_range_with_strategy(
+    project_path: &str,
+    commit_before: &str,
+    commit_after: &str,
+    message: &str,
+    on_conflict: OnConflict,
+    allow_identity_fallback: bool,
+) -> Result<RevertResult, String> {
+    ensure_clean_repo_state(project_path)?;
+
     log::info!(
         "[Precise Revert] Reverting range {}..{} in {}",
         &commit_before[..8.min(commit_before.len())],
@@ -379,6 +548,9 @@ pub fn git_revert_range(
         });
     }
 
+    // Check the identity up front so a missing one does not leave a half-done revert behind
+    let identity_args = commit_identity_args(project_path, allow_identity_fallback)?;
+
     // Count commits in range
     let commit_count = git_commit_count_between(project_path, commit_before, commit_after)
         .unwrap_or(1);
@@ -413,6 +585,20 @@ pub fn git_revert_range(
 
         // Check if it's a conflict error
         if stderr.contains("conflict") || stderr.contains("CONFLICT") {
+            if on_conflict == OnConflict::Interactive {
+                log::warn!("[Precise Revert] Conflicts detected, leaving revert in progress");
+                return Ok(RevertResult {
+                    success: false,
+                    commits_reverted: 0,
+                    new_commit: None,
+                    message: format!(
+                        "撤回时发生冲突，请逐块选择保留的内容后继续。\n详情: {}",
+                        stderr.lines().take(3).collect::<Vec<_>>().join("\n")
+                    ),
+                    has_conflicts: true,
+                });
+            }
+
             log::warn!("[Precise Revert] Conflicts detected, attempting to abort");
 
             // Abort the revert
@@ -456,6 +642,8 @@ pub fn git_revert_range(
 
     if !has_changes {
         log::info!("[Precise Revert] No changes after revert (already at target state)");
+        // Nothing to commit, so clear the REVERT_HEAD left by --no-commit ourselves
+        let _ = run_git(project_path, &["revert", "--quit"]);
         return Ok(RevertResult {
             success: true,
             commits_reverted: commit_count,
@@ -467,6 +655,7 @@ pub fn git_revert_range(
 
     // Commit the reverted changes
     let mut commit_cmd = Command::new("git");
+    commit_cmd.args(&identity_args);
     commit_cmd.args(["commit", "-m", message]);
     commit_cmd.current_dir(project_path);
     #[cfg(target_os = "windows")]
@@ -514,11 +703,604 @@ pub fn precise_revert_code(
         &commit_after[..8.min(commit_after.len())]
     );
 
-    git_revert_range(&project_path, &commit_before, &commit_after, &message)
+    let allow_identity_fallback = super::prompt_tracker::load_execution_config()
+        .map(|config| config.git_identity_fallback)
+        .unwrap_or(true);
+    git_revert_range(
+        &project_path,
+        &commit_before,
+        &commit_after,
+        &message,
+        allow_identity_fallback,
+    )
+}
+
+// ============================================================================
+// Interactive Conflict Resolution (撤回冲突的交互式解决)
+// ============================================================================
+
+/// Prefix of the error returned when an interactive revert stops on conflicts;
+/// the frontend matches on it to open the conflict resolver
+pub const REVERT_CONFLICT_ERROR_PREFIX: &str = "REVERT_CONFLICT";
+
+/// Unfinished interactive revert, stored inside .git so it survives restarts
+const PENDING_REVERT_FILE: &str = "anycode-revert.json";
+
+/// How a revert handles conflicts
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OnConflict {
+    /// Abort the revert and report the conflict
+    #[default]
+    Abort,
+    /// Leave the revert in progress so conflicts can be resolved hunk by hunk
+    Interactive,
+}
+
+/// Git operation currently in progress in a repository
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GitRepoState {
+    Clean,
+    Reverting,
+    Merging,
+    CherryPicking,
+    Rebasing,
+}
+
+/// Detect an in-progress git operation (including an unfinished interactive revert)
+pub fn git_repo_state(project_path: &str) -> GitRepoState {
+    let git_dir = Path::new(project_path).join(".git");
+    if git_dir.join("REVERT_HEAD").exists() || pending_revert_path(project_path).exists() {
+        GitRepoState::Reverting
+    } else if git_dir.join("rebase-merge").exists() || git_dir.join("rebase-apply").exists() {
+        GitRepoState::Rebasing
+    } else if git_dir.join("MERGE_HEAD").exists() {
+        GitRepoState::Merging
+    } else if git_dir.join("CHERRY_PICK_HEAD").exists() {
+        GitRepoState::CherryPicking
+    } else {
+        GitRepoState::Clean
+    }
+}
+
+/// Refuse to start another git operation while one is in progress
+pub fn ensure_clean_repo_state(project_path: &str) -> Result<(), String> {
+    match git_repo_state(project_path) {
+        GitRepoState::Clean => Ok(()),
+        GitRepoState::Reverting => Err("仓库中有进行中的撤回，请先解决冲突或放弃撤回".to_string()),
+        state => Err(format!("仓库正处于 {:?} 状态，请先完成或中止该操作", state)),
+    }
+}
+
+/// One commit range to revert
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RevertRange {
+    pub commit_before: String,
+    pub commit_after: String,
+    /// Message of the revert commit created for this range
+    pub message: String,
+}
+
+/// Interactive revert stopped on conflicts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PendingRevert {
+    /// HEAD before the whole revert started (abort_revert returns here)
+    original_head: String,
+    /// HEAD before the conflicting range started; its revert is squashed onto it
+    range_base: String,
+    current: RevertRange,
+    /// Ranges still to revert after the current one
+    remaining: Vec<RevertRange>,
+    /// Commits reverted by the ranges that already finished
+    commits_reverted: usize,
+    /// Whether the commits may use the one-off identity (execution config at revert start)
+    #[serde(default = "default_allow_identity_fallback")]
+    allow_identity_fallback: bool,
+}
+
+fn default_allow_identity_fallback() -> bool {
+    true
+}
+
+/// One conflict block parsed from the conflict markers of a file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConflictHunk {
+    /// Text currently at HEAD
+    pub ours: String,
+    /// Text of the common ancestor (only present with diff3 markers)
+    pub base: Option<String>,
+    /// Text the revert wants to restore
+    pub theirs: String,
+}
+
+/// A conflicted file and its conflict blocks (empty for non-text conflicts)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConflictFile {
+    pub path: String,
+    pub hunks: Vec<ConflictHunk>,
+}
+
+/// Details of an interactive revert stopped on conflicts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RevertConflict {
+    pub original_head: String,
+    /// Message of the range being reverted
+    pub message: String,
+    pub files: Vec<ConflictFile>,
+    /// Ranges that will be reverted once this one is resolved
+    pub remaining_ranges: usize,
+}
+
+/// Which side of a conflict block to keep
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HunkChoice {
+    Ours,
+    Theirs,
+    Base,
+}
+
+/// Choices for the conflict blocks of one file, in file order
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConflictResolution {
+    pub path: String,
+    pub choices: Vec<HunkChoice>,
+}
+
+enum ConflictSegment {
+    Text(String),
+    Conflict(ConflictHunk),
+}
+
+/// Split a file into plain text and conflict blocks (two-way or diff3 markers)
+fn split_conflict_segments(content: &str) -> Vec<ConflictSegment> {
+    #[derive(PartialEq)]
+    enum Side {
+        Ours,
+        Base,
+        Theirs,
+    }
+
+    let mut segments = Vec::new();
+    let mut text = String::new();
+    // Current block: side being read, parsed hunk and raw lines (kept if the block never closes)
+    let mut block: Option<(Side, ConflictHunk, String)> = None;
+
+    for line in content.split_inclusive('\n') {
+        let Some((side, hunk, raw)) = block.as_mut() else {
+            if line.starts_with("<<<<<<<") {
+                if !text.is_empty() {
+                    segments.push(ConflictSegment::Text(std::mem::take(&mut text)));
+                }
+                let hunk = ConflictHunk {
+                    ours: String::new(),
+                    base: None,
+                    theirs: String::new(),
+                };
+                block = Some((Side::Ours, hunk, line.to_string()));
+            } else {
+                text.push_str(line);
+            }
+            continue;
+        };
+
+        raw.push_str(line);
+        if *side == Side::Ours && line.starts_with("|||||||") {
+            hunk.base = Some(String::new());
+            *side = Side::Base;
+        } else if *side != Side::Theirs && line.trim_end() == "=======" {
+            *side = Side::Theirs;
+        } else if *side == Side::Theirs && line.starts_with(">>>>>>>") {
+            if let Some((_, hunk, _)) = block.take() {
+                segments.push(ConflictSegment::Conflict(hunk));
+            }
+        } else {
+            match side {
+                Side::Ours => hunk.ours.push_str(line),
+                Side::Base => hunk.base.get_or_insert_with(String::new).push_str(line),
+                Side::Theirs => hunk.theirs.push_str(line),
+            }
+        }
+    }
+
+    if let Some((_, _, raw)) = block {
+        text.push_str(&raw);
+    }
+    if !text.is_empty() {
+        segments.push(ConflictSegment::Text(text));
+    }
+    segments
+}
+
+/// Parse the conflict blocks of a file
+pub fn parse_conflict_markers(content: &str) -> Vec<ConflictHunk> {
+    split_conflict_segments(content)
+        .into_iter()
+        .filter_map(|segment| match segment {
+            ConflictSegment::Conflict(hunk) => Some(hunk),
+            ConflictSegment::Text(_) => None,
+        })
+        .collect()
+}
+
+/// Replace each conflict block with the chosen side
+pub fn apply_conflict_choices(content: &str, choices: &[HunkChoice]) -> Result<String, String> {
+    let segments = split_conflict_segments(content);
+    let hunk_count = segments
+        .iter()
+        .filter(|segment| matches!(segment, ConflictSegment::Conflict(_)))
+        .count();
+    if hunk_count != choices.len() {
+        return Err(format!(
+            "冲突块数量不匹配：文件中有 {} 个冲突块，收到 {} 个选择",
+            hunk_count,
+            choices.len()
+        ));
+    }
+
+    let mut choices = choices.iter();
+    let mut resolved = String::with_capacity(content.len());
+    for segment in segments {
+        match segment {
+            ConflictSegment::Text(text) => resolved.push_str(&text),
+            ConflictSegment::Conflict(hunk) => match choices.next() {
+                Some(HunkChoice::Ours) => resolved.push_str(&hunk.ours),
+                Some(HunkChoice::Theirs) => resolved.push_str(&hunk.theirs),
+                Some(HunkChoice::Base) => resolved.push_str(
+                    hunk.base
+                        .as_deref()
+                        .ok_or("冲突块没有 base 内容，无法选择 base")?,
+                ),
+                None => unreachable!("choice count checked above"),
+            },
+        }
+    }
+    Ok(resolved)
+}
+
+/// Run a git command in the project
+fn run_git<S: AsRef<std::ffi::OsStr>>(
+    project_path: &str,
+    args: &[S],
+) -> Result<std::process::Output, String> {
+    let mut cmd = Command::new("git");
+    cmd.args(args);
+    cmd.current_dir(project_path);
+
+    #[cfg(target_os = "windows")]
+    cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
+
+    cmd.output()
+        .map_err(|e| format!("Failed to execute git: {}", e))
+}
+
+fn pending_revert_path(project_path: &str) -> PathBuf {
+    Path::new(project_path)
+        .join(".git")
+        .join(PENDING_REVERT_FILE)
+}
+
+fn load_pending_revert(project_path: &str) -> Result<Option<PendingRevert>, String> {
+    let path = pending_revert_path(project_path);
+    if !path.exists() {
+        return Ok(None);
+    }
+    let content =
+        fs::read_to_string(&path).map_err(|e| format!("Failed to read pending revert: {}", e))?;
+    serde_json::from_str(&content)
+        .map(Some)
+        .map_err(|e| format!("Failed to parse pending revert: {}", e))
+}
+
+fn save_pending_revert(project_path: &str, pending: &PendingRevert) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(pending)
+        .map_err(|e| format!("Failed to serialize pending revert: {}", e))?;
+    fs::write(pending_revert_path(project_path), content)
+        .map_err(|e| format!("Failed to save pending revert: {}", e))
+}
+
+fn clear_pending_revert(project_path: &str) {
+    let path = pending_revert_path(project_path);
+    if path.exists() {
+        if let Err(e) = fs::remove_file(&path) {
+            log::warn!("Failed to remove pending revert state: {}", e);
+        }
+    }
+}
+
+/// Files that still have unresolved conflicts
+fn unmerged_files(project_path: &str) -> Result<Vec<String>, String> {
+    let output = run_git(project_path, &["diff", "--name-only", "--diff-filter=U"])?;
+    if !output.status.success() {
+        return Err(format!(
+            "Git diff failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter(|line| !line.is_empty())
+        .map(String::from)
+        .collect())
+}
+
+/// Whether the index differs from HEAD
+fn has_staged_changes(project_path: &str) -> Result<bool, String> {
+    let output = run_git(project_path, &["diff", "--cached", "--quiet"])?;
+    match output.status.code() {
+        Some(0) => Ok(false),
+        Some(1) => Ok(true),
+        _ => Err(format!(
+            "Git diff failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        )),
+    }
+}
+
+/// Conflict blocks of every unmerged file
+///
+/// Unresolved files are rewritten with diff3 markers so the base text is available.
+fn revert_conflict_files(project_path: &str) -> Result<Vec<ConflictFile>, String> {
+    unmerged_files(project_path)?
+        .into_iter()
+        .map(|path| {
+            let checkout = run_git(project_path, &["checkout", "--conflict=diff3", "--", &path])?;
+            if !checkout.status.success() {
+                log::debug!("Cannot rewrite conflict markers for {}", path);
+            }
+            let hunks = fs::read_to_string(Path::new(project_path).join(&path))
+                .map(|content| parse_conflict_markers(&content))
+                .unwrap_or_default();
+            Ok(ConflictFile { path, hunks })
+        })
+        .collect()
+}
+
+/// Revert ranges one after another; on a conflict the revert is left in progress
+/// and the remaining plan is saved for resolve_revert_conflict
+fn run_revert_ranges(
+    project_path: &str,
+    original_head: &str,
+    ranges: Vec<RevertRange>,
+    mut commits_reverted: usize,
+    allow_identity_fallback: bool,
+) -> Result<RevertResult, String> {
+    let mut ranges = ranges.into_iter();
+    while let Some(range) = ranges.next() {
+        let range_base = git_current_commit(project_path)?;
+        let result = git_revert_range_with_strategy(
+            project_path,
+            &range.commit_before,
+            &range.commit_after,
+            &range.message,
+            OnConflict::Interactive,
+            allow_identity_fallback,
+        )?;
+
+        if result.has_conflicts {
+            save_pending_revert(
+                project_path,
+                &PendingRevert {
+                    original_head: original_head.to_string(),
+                    range_base,
+                    current: range,
+                    remaining: ranges.collect(),
+                    commits_reverted,
+                    allow_identity_fallback,
+                },
+            )?;
+            return Ok(RevertResult {
+                success: false,
+                commits_reverted,
+                new_commit: None,
+                message: result.message,
+                has_conflicts: true,
+            });
+        }
+        commits_reverted += result.commits_reverted;
+    }
+
+    Ok(RevertResult {
+        success: true,
+        commits_reverted,
+        new_commit: git_current_commit(project_path).ok(),
+        message: format!("成功撤回 {} 个提交的代码更改", commits_reverted),
+        has_conflicts: false,
+    })
+}
+
+/// Revert several ranges in order, stopping on the first conflict with the revert
+/// left in progress (see get_revert_conflict / resolve_revert_conflict / abort_revert)
+pub fn git_revert_ranges_interactive(
+    project_path: &str,
+    original_head: &str,
+    ranges: Vec<RevertRange>,
+    allow_identity_fallback: bool,
+) -> Result<RevertResult, String> {
+    run_revert_ranges(
+        project_path,
+        original_head,
+        ranges,
+        0,
+        allow_identity_fallback,
+    )
+}
+
+/// Continue the conflicting range after all its conflicts are staged, then the remaining ranges
+fn continue_pending_revert(
+    project_path: &str,
+    pending: PendingRevert,
+) -> Result<RevertResult, String> {
+    // git refuses --continue when the resolved commit ends up empty; skip it instead
+    let step = if has_staged_changes(project_path)? {
+        "--continue"
+    } else {
+        "--skip"
+    };
+    let mut args = commit_identity_args(project_path, pending.allow_identity_fallback)?;
+    args.extend(["-c", "core.editor=true", "revert", step].map(String::from));
+    let output = run_git(project_path, &args)?;
+
+    if !output.status.success() {
+        if !unmerged_files(project_path)?.is_empty() {
+            log::warn!("[Precise Revert] Next commit in range conflicts as well");
+            return Ok(RevertResult {
+                success: false,
+                commits_reverted: pending.commits_reverted,
+                new_commit: None,
+                message: "撤回范围中的后续提交也发生冲突，请继续选择".to_string(),
+                has_conflicts: true,
+            });
+        }
+        return Err(format!(
+            "Git revert {} failed: {}",
+            step,
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+
+    // Squash the commits made by --continue (and reverts still staged) into one revert commit
+    let _ = run_git(project_path, &["revert", "--quit"]);
+    let reset = run_git(project_path, &["reset", "--soft", &pending.range_base])?;
+    if !reset.status.success() {
+        return Err(format!(
+            "Git reset failed: {}",
+            String::from_utf8_lossy(&reset.stderr)
+        ));
+    }
+    if has_staged_changes(project_path)? {
+        let mut args = commit_identity_args(project_path, pending.allow_identity_fallback)?;
+        args.extend(["commit", "-m", &pending.current.message].map(String::from));
+        let commit = run_git(project_path, &args)?;
+        if !commit.status.success() {
+            return Err(format!(
+                "Failed to commit revert: {}",
+                String::from_utf8_lossy(&commit.stderr)
+            ));
+        }
+    }
+    clear_pending_revert(project_path);
+
+    let range_commits = git_commit_count_between(
+        project_path,
+        &pending.current.commit_before,
+        &pending.current.commit_after,
+    )
+    .unwrap_or(1);
+    log::info!(
+        "[Precise Revert] Resolved conflicting range ({} commits), {} ranges remaining",
+        range_commits,
+        pending.remaining.len()
+    );
+
+    run_revert_ranges(
+        project_path,
+        &pending.original_head,
+        pending.remaining,
+        pending.commits_reverted + range_commits,
+        pending.allow_identity_fallback,
+    )
+}
+
+/// Tauri command: get the conflicts of an interactive revert (None if none is in progress)
+#[tauri::command]
+pub fn get_revert_conflict(project_path: String) -> Result<Option<RevertConflict>, String> {
+    let Some(pending) = load_pending_revert(&project_path)? else {
+        return Ok(None);
+    };
+
+    Ok(Some(RevertConflict {
+        original_head: pending.original_head,
+        message: pending.current.message,
+        files: revert_conflict_files(&project_path)?,
+        remaining_ranges: pending.remaining.len(),
+    }))
+}
+
+/// Tauri command: write the chosen conflict blocks, stage them and continue the revert
+///
+/// Returns has_conflicts = true while files remain unresolved or a later commit conflicts.
+#[tauri::command]
+pub fn resolve_revert_conflict(
+    project_path: String,
+    resolutions: Vec<ConflictResolution>,
+) -> Result<RevertResult, String> {
+    let pending = load_pending_revert(&project_path)?.ok_or("没有进行中的撤回冲突".to_string())?;
+
+    // Reject paths outside the project before touching any file
+    let file_paths = resolutions
+        .iter()
+        .map(|resolution| validate_path_within(Path::new(&project_path), &resolution.path))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    for (resolution, file_path) in resolutions.iter().zip(&file_paths) {
+        let content = fs::read_to_string(file_path)
+            .map_err(|e| format!("Failed to read {}: {}", resolution.path, e))?;
+        let resolved = apply_conflict_choices(&content, &resolution.choices)
+            .map_err(|e| format!("{}: {}", resolution.path, e))?;
+        fs::write(file_path, resolved)
+            .map_err(|e| format!("Failed to write {}: {}", resolution.path, e))?;
+
+        let add = run_git(&project_path, &["add", "--", resolution.path.as_str()])?;
+        if !add.status.success() {
+            return Err(format!(
+                "Git add failed: {}",
+                String::from_utf8_lossy(&add.stderr)
+            ));
+        }
+    }
+
+    let unresolved = unmerged_files(&project_path)?;
+    if !unresolved.is_empty() {
+        return Ok(RevertResult {
+            success: false,
+            commits_reverted: pending.commits_reverted,
+            new_commit: None,
+            message: format!(
+                "仍有 {} 个文件存在冲突: {}",
+                unresolved.len(),
+                unresolved.join(", ")
+            ),
+            has_conflicts: true,
+        });
+    }
+
+    continue_pending_revert(&project_path, pending)
+}
+
+/// Tauri command: abandon an interactive revert and return to the HEAD before it started
+#[tauri::command]
+pub fn abort_revert(project_path: String) -> Result<(), String> {
+    let pending = load_pending_revert(&project_path)?;
+
+    // Fails harmlessly when git itself has no revert in progress (between ranges)
+    let _ = run_git(&project_path, &["revert", "--abort"]);
+    if let Some(pending) = pending {
+        git_reset_hard(&project_path, &pending.original_head)?;
+    }
+    clear_pending_revert(&project_path);
+
+    log::info!(
+        "[Precise Revert] Aborted interactive revert in {}",
+        project_path
+    );
+    Ok(())
 }
 
 /// Save uncommitted changes to stash
-pub fn git_stash_save(project_path: &str, message: &str) -> Result<(), String> {
+pub fn git_stash_save(
+    project_path: &str,
+    message: &str,
+    allow_identity_fallback: bool,
+) -> Result<(), String> {
+    ensure_clean_repo_state(project_path)?;
+
     // Check if there are uncommitted changes
     let mut status_cmd = Command::new("git");
     status_cmd.args(["status", "--porcelain"]);
@@ -538,7 +1320,9 @@ pub fn git_stash_save(project_path: &str, message: &str) -> Result<(), String> {
 
     log::info!("Stashing uncommitted changes: {}", message);
 
+    let identity_args = commit_identity_args(project_path, allow_identity_fallback)?;
     let mut stash_cmd = Command::new("git");
+    stash_cmd.args(&identity_args);
     stash_cmd.args(["stash", "save", "-u", message]);
     stash_cmd.current_dir(project_path);
 
@@ -559,6 +1343,82 @@ pub fn git_stash_save(project_path: &str, message: &str) -> Result<(), String> {
     Ok(())
 }
 
+/// Reject branch/tag names git would parse as options
+fn validate_ref_name(kind: &str, name: &str) -> Result<(), String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(format!("The {} name is empty", kind));
+    }
+    if name.starts_with('-') {
+        return Err(format!(
+            "Invalid {} name (must not start with '-'): {}",
+            kind, name
+        ));
+    }
+    Ok(())
+}
+
+/// Create a branch at HEAD without switching to it
+pub fn git_create_branch(project_path: &str, branch_name: &str) -> Result<(), String> {
+    validate_ref_name("branch", branch_name)?;
+    let mut cmd = Command::new("git");
+    cmd.args(["branch", branch_name]);
+    cmd.current_dir(project_path);
+
+    #[cfg(target_os = "windows")]
+    cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
+
+    let output = cmd
+        .output()
+        .map_err(|e| format!("Failed to create branch: {}", e))?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "Git branch failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    log::info!("Created branch {} at HEAD in {}", branch_name, project_path);
+    Ok(())
+}
+
+/// Create a tag at HEAD (annotated when a message is given)
+pub fn git_create_tag(
+    project_path: &str,
+    tag_name: &str,
+    message: Option<&str>,
+) -> Result<(), String> {
+    validate_ref_name("tag", tag_name)?;
+    let mut cmd = Command::new("git");
+    if message.is_some() {
+        // Annotated tags record a tagger identity
+        cmd.args(commit_identity_args(project_path, true)?);
+    }
+    match message {
+        Some(msg) => cmd.args(["tag", "-a", tag_name, "-m", msg]),
+        None => cmd.args(["tag", tag_name]),
+    };
+    cmd.current_dir(project_path);
+
+    #[cfg(target_os = "windows")]
+    cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
+
+    let output = cmd
+        .output()
+        .map_err(|e| format!("Failed to create tag: {}", e))?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "Git tag failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    log::info!("Created tag {} at HEAD in {}", tag_name, project_path);
+    Ok(())
+}
+
 /// Tauri command: Check and initialize Git repository
 #[tauri::command]
 pub fn check_and_init_git(project_path: String) -> Result<bool, String> {
@@ -785,3 +1645,166 @@ pub fn check_reset_safety(
     })
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn git(dir: &Path, args: &[&str]) -> String {
+        let output = Command::new("git")
+            .args(["-c", "user.name=test", "-c", "user.email=test@local"])
+            .args(args)
+            .current_dir(dir)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "git {:?} failed", args);
+        String::from_utf8_lossy(&output.stdout).trim().to_string()
+    }
+
+    fn commit_file(dir: &Path, content: &str, message: &str) -> String {
+        fs::write(dir.join("f.txt"), content).unwrap();
+        git(dir, &["add", "-A"]);
+        git(dir, &["commit", "-q", "-m", message]);
+        git(dir, &["rev-parse", "HEAD"])
+    }
+
+    #[test]
+    fn parses_and_applies_two_way_and_diff3_markers() {
+        let content = "a\n<<<<<<< ours\nB2\n||||||| base\nB1\n=======\nb\n>>>>>>> theirs\nc\n\
+                       <<<<<<< HEAD\nx\n=======\ny\n>>>>>>> parent\n";
+        let hunks = parse_conflict_markers(content);
+        assert_eq!(hunks.len(), 2);
+        assert_eq!(hunks[0].ours, "B2\n");
+        assert_eq!(hunks[0].base.as_deref(), Some("B1\n"));
+        assert_eq!(hunks[0].theirs, "b\n");
+        assert_eq!(hunks[1].base, None);
+
+        let resolved =
+            apply_conflict_choices(content, &[HunkChoice::Theirs, HunkChoice::Ours]).unwrap();
+        assert_eq!(resolved, "a\nb\nc\nx\n");
+        assert!(apply_conflict_choices(content, &[HunkChoice::Theirs]).is_err());
+        assert!(apply_conflict_choices(content, &[HunkChoice::Ours, HunkChoice::Base]).is_err());
+    }
+
+    #[test]
+    fn interactive_revert_resolves_conflict_with_theirs() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path();
+        let project = path.to_str().unwrap();
+        git(path, &["init", "-q"]);
+        let base = commit_file(path, "a\nb\nc\n", "base");
+        let prompt = commit_file(path, "a\nB1\nc\n", "prompt");
+        let head = commit_file(path, "a\nB2\nc\n", "later");
+
+        let ranges = vec![RevertRange {
+            commit_before: base,
+            commit_after: prompt,
+            message: "[Revert] prompt".to_string(),
+        }];
+        let result = git_revert_ranges_interactive(project, &head, ranges, true).unwrap();
+        assert!(result.has_conflicts);
+        assert_eq!(git_repo_state(project), GitRepoState::Reverting);
+        assert!(ensure_clean_repo_state(project).is_err());
+
+        let conflict = get_revert_conflict(project.to_string()).unwrap().unwrap();
+        assert_eq!(conflict.files.len(), 1);
+        assert_eq!(conflict.files[0].path, "f.txt");
+        assert_eq!(conflict.files[0].hunks.len(), 1);
+        assert_eq!(conflict.files[0].hunks[0].theirs, "b\n");
+
+        let escape = ConflictResolution {
+            path: "../outside.txt".to_string(),
+            choices: vec![HunkChoice::Theirs],
+        };
+        assert!(resolve_revert_conflict(project.to_string(), vec![escape]).is_err());
+        assert_eq!(git_repo_state(project), GitRepoState::Reverting);
+
+        let result = resolve_revert_conflict(
+            project.to_string(),
+            vec![ConflictResolution {
+                path: "f.txt".to_string(),
+                choices: vec![HunkChoice::Theirs],
+            }],
+        )
+        .unwrap();
+        assert!(result.success);
+        assert_eq!(result.commits_reverted, 1);
+
+        assert_eq!(fs::read_to_string(path.join("f.txt")).unwrap(), "a\nb\nc\n");
+        assert_eq!(git(path, &["status", "--porcelain"]), "");
+        assert_eq!(git(path, &["log", "-1", "--format=%s"]), "[Revert] prompt");
+        assert_eq!(git(path, &["rev-parse", "HEAD~1"]), head);
+        assert_eq!(git_repo_state(project), GitRepoState::Clean);
+        assert!(get_revert_conflict(project.to_string()).unwrap().is_none());
+    }
+
+    #[test]
+    fn abort_revert_restores_original_head() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path();
+        let project = path.to_str().unwrap();
+        git(path, &["init", "-q"]);
+        let base = commit_file(path, "a\nb\nc\n", "base");
+        let prompt = commit_file(path, "a\nB1\nc\n", "prompt");
+        let head = commit_file(path, "a\nB2\nc\n", "later");
+
+        let ranges = vec![RevertRange {
+            commit_before: base,
+            commit_after: prompt,
+            message: "[Revert] prompt".to_string(),
+        }];
+        assert!(
+            git_revert_ranges_interactive(project, &head, ranges, true)
+                .unwrap()
+                .has_conflicts
+        );
+
+        abort_revert(project.to_string()).unwrap();
+        assert_eq!(git(path, &["rev-parse", "HEAD"]), head);
+        assert_eq!(git(path, &["status", "--porcelain"]), "");
+        assert_eq!(git_repo_state(project), GitRepoState::Clean);
+    }
+
+    #[test]
+    fn rejects_ref_names_that_look_like_options() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path();
+        let project = path.to_str().unwrap();
+        git(path, &["init", "-q"]);
+        commit_file(path, "a\n", "base");
+
+        assert!(git_create_branch(project, "--delete").is_err());
+        assert!(git_create_tag(project, "-f", None).is_err());
+        assert!(git_create_tag(project, " ", None).is_err());
+
+        git_create_branch(project, "checkpoint").unwrap();
+        git_create_tag(project, "v1", None).unwrap();
+        assert_eq!(git(path, &["branch", "--list", "checkpoint"]), "checkpoint");
+        assert_eq!(git(path, &["tag", "--list"]), "v1");
+    }
+
+    #[test]
+    fn revert_and_stash_follow_the_identity_fallback_setting() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path();
+        let project = path.to_str().unwrap();
+        git(path, &["init", "-q"]);
+        // Empty local values hide any global identity
+        git(path, &["config", "user.name", ""]);
+        git(path, &["config", "user.email", ""]);
+        let base = commit_file(path, "a\n", "base");
+        let prompt = commit_file(path, "b\n", "prompt");
+        assert!(!has_git_identity(project));
+
+        assert!(git_revert_range(project, &base, &prompt, "[Revert] prompt", false).is_err());
+        assert_eq!(git(path, &["rev-parse", "HEAD"]), prompt);
+        let result = git_revert_range(project, &base, &prompt, "[Revert] prompt", true).unwrap();
+        assert!(result.success);
+        assert_eq!(fs::read_to_string(path.join("f.txt")).unwrap(), "a\n");
+
+        fs::write(path.join("f.txt"), "dirty\n").unwrap();
+        assert!(git_stash_save(project, "before revert", false).is_err());
+        git_stash_save(project, "before revert", true).unwrap();
+        assert_eq!(git(path, &["status", "--porcelain"]), "");
+        assert_eq!(git(path, &["stash", "list"]).lines().count(), 1);
+    }
+}
@@ -0,0 +1,573 @@
+//! 流式输出卡死检测与自动续接（Codex / Gemini）
+//!
+//! 代理断流或 CLI 内部卡住时，进程仍然存活，但 stdout 长时间没有任何输出，前端一直停在加载态。
+//! 每次运行维护一个 `StallMonitor`，它同时承担空闲超时的职责：
+//! - 每收到一行输出就记录时间，并根据事件类型跟踪正在执行的工具
+//! - 超过 `stall_threshold_secs` 没有输出时，若仍有工具在执行则视为正常等待，不做干预
+//! - 否则进入唤醒阶段：发送 `{engine}-stalled` 提示并给予 `wake_grace_secs` 宽限期。
+//!   exec 模式下 stdin 写完提示词后立即关闭（CLI 以 EOF 作为输入结束），无法再写入换行唤醒进程，
+//!   因此唤醒阶段只等待输出自行恢复
+//! - 宽限期内仍无输出，则优雅中断进程，并以 resume 方式续接同一会话（最多 `max_reconnects` 次），
+//!   续接前发送 `{engine}-reconnecting` 事件并记录追踪
+//! - 关闭自动恢复或续接次数用完时，只发送一次 `{engine}-stalled` 提示
+//!
+//! 续接沿用同一个前端通道 session_id，只有最后一次运行才会发送完成事件。
+//!
+//! 持久化：~/.anycode/stream_stall.json（默认开启自动恢复）
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+
+use super::claude::{kill_process_tree, request_process_exit};
+use super::execution_trace::{record_trace_event, TraceFields, STAGE_RECONNECTING};
+use super::gemini::types::GeminiStreamEvent;
+use super::process_cancel::GRACEFUL_EXIT_TIMEOUT;
+use crate::utils::config_utils::{load_json_config, save_json_config, ConfigPathBuilder};
+
+/// 卡死判断的最小阈值，避免正常的模型思考被误判
+const MIN_STALL_THRESHOLD_SECS: u64 = 30;
+
+/// 允许配置的最大续接次数
+const MAX_ALLOWED_RECONNECTS: u32 = 10;
+
+/// 看门狗检查间隔
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// 续接时发送给 CLI 的提示词
+pub const CONTINUE_PROMPT: &str =
+    "The previous response stream was interrupted. Continue the task from where you left off.";
+
+/// 卡死检测配置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct StreamStallConfig {
+    /// 卡死后自动中断并续接；关闭时只提示
+    pub auto_recover: bool,
+    /// 无输出多久视为卡死
+    pub stall_threshold_secs: u64,
+    /// 唤醒后等待输出恢复的时间
+    pub wake_grace_secs: u64,
+    /// 单次运行最多自动续接的次数
+    pub max_reconnects: u32,
+}
+
+impl Default for StreamStallConfig {
+    fn default() -> Self {
+        Self {
+            auto_recover: true,
+            stall_threshold_secs: 120,
+            wake_grace_secs: 30,
+            max_reconnects: 2,
+        }
+    }
+}
+
+/// 一行输出对卡死判断的意义
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StreamActivity {
+    /// 普通输出（消息片段、推理、状态事件等）
+    Output,
+    /// 工具开始执行，完成前的静默属于正常等待
+    ToolStarted,
+    /// 工具执行结束
+    ToolFinished,
+}
+
+/// 监视器所处阶段
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum StallPhase {
+    /// 正常输出中
+    Active,
+    /// 已发出唤醒提示，等待输出恢复
+    Nudged { since: Instant },
+    /// 已提示卡死（不续接），输出恢复后回到 Active
+    Notified,
+    /// 已决定续接，本次运行不再检查
+    Recovering,
+}
+
+/// 一次检查的结论
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StallAction {
+    None,
+    /// 进入唤醒阶段，宽限期后仍无输出将续接
+    Nudge {
+        idle: Duration,
+    },
+    /// 中断进程并续接，`reconnect` 为本次续接的序号（从 1 开始）
+    Recover {
+        idle: Duration,
+        reconnect: u32,
+    },
+    /// 卡死但不续接（已关闭自动恢复或次数用完）
+    Notify {
+        idle: Duration,
+    },
+}
+
+/// 单次运行的空闲 / 卡死状态机
+#[derive(Debug)]
+pub struct StallMonitor {
+    config: StreamStallConfig,
+    last_activity: Instant,
+    running_tools: usize,
+    /// 之前的运行已经续接的次数
+    reconnects: u32,
+    phase: StallPhase,
+}
+
+impl StallMonitor {
+    pub fn new(config: StreamStallConfig, reconnects: u32, now: Instant) -> Self {
+        Self {
+            config,
+            last_activity: now,
+            running_tools: 0,
+            reconnects,
+            phase: StallPhase::Active,
+        }
+    }
+
+    /// 记录一行输出
+    pub fn on_activity(&mut self, activity: StreamActivity, now: Instant) {
+        self.last_activity = now;
+        match activity {
+            StreamActivity::Output => {}
+            StreamActivity::ToolStarted => self.running_tools += 1,
+            StreamActivity::ToolFinished => {
+                self.running_tools = self.running_tools.saturating_sub(1)
+            }
+        }
+        if self.phase != StallPhase::Recovering {
+            self.phase = StallPhase::Active;
+        }
+    }
+
+    /// 定期检查是否卡死
+    pub fn poll(&mut self, now: Instant) -> StallAction {
+        let idle = now.saturating_duration_since(self.last_activity);
+        // 工具执行中（命令、MCP 调用等）的静默属于正常等待
+        if self.running_tools > 0 || idle < Duration::from_secs(self.config.stall_threshold_secs) {
+            return StallAction::None;
+        }
+
+        match self.phase {
+            StallPhase::Active => {
+                if self.config.auto_recover && self.reconnects < self.config.max_reconnects {
+                    self.phase = StallPhase::Nudged { since: now };
+                    StallAction::Nudge { idle }
+                } else {
+                    self.phase = StallPhase::Notified;
+                    StallAction::Notify { idle }
+                }
+            }
+            StallPhase::Nudged { since }
+                if now.saturating_duration_since(since)
+                    >= Duration::from_secs(self.config.wake_grace_secs) =>
+            {
+                self.phase = StallPhase::Recovering;
+                StallAction::Recover {
+                    idle,
+                    reconnect: self.reconnects + 1,
+                }
+            }
+            _ => StallAction::None,
+        }
+    }
+}
+
+/// 运行中共享的监视器（stdout 读取任务与看门狗共用）
+pub type SharedStallMonitor = Arc<Mutex<StallMonitor>>;
+
+/// 为一次运行创建监视器
+pub fn new_shared_monitor(reconnects: u32) -> SharedStallMonitor {
+    Arc::new(Mutex::new(StallMonitor::new(
+        load_stall_config(),
+        reconnects,
+        Instant::now(),
+    )))
+}
+
+/// stdout 读取任务中记录一行输出
+pub fn record_activity(monitor: &SharedStallMonitor, activity: StreamActivity) {
+    monitor
+        .lock()
+        .unwrap()
+        .on_activity(activity, Instant::now());
+}
+
+/// 会长时间静默执行的 Codex 条目类型（todo_list 会持续到回合结束，不计入）
+const CODEX_TOOL_ITEM_TYPES: &[&str] = &[
+    "command_execution",
+    "mcp_tool_call",
+    "web_search",
+    "file_change",
+];
+
+/// Codex JSONL 事件的活动类型
+pub fn classify_codex_event(event: &Value) -> StreamActivity {
+    let is_tool_item = event["item"]["type"]
+        .as_str()
+        .is_some_and(|t| CODEX_TOOL_ITEM_TYPES.contains(&t));
+    match event["type"].as_str() {
+        Some("item.started") if is_tool_item => StreamActivity::ToolStarted,
+        Some("item.completed") if is_tool_item => StreamActivity::ToolFinished,
+        _ => StreamActivity::Output,
+    }
+}
+
+/// Gemini stream-json 事件的活动类型
+pub fn classify_gemini_event(event: &GeminiStreamEvent) -> StreamActivity {
+    match event {
+        GeminiStreamEvent::ToolUse { .. } => StreamActivity::ToolStarted,
+        GeminiStreamEvent::ToolResult { .. } => StreamActivity::ToolFinished,
+        _ => StreamActivity::Output,
+    }
+}
+
+/// 看门狗决定续接时的信息
+#[derive(Debug, Clone, PartialEq)]
+pub struct StallRecovery {
+    /// 本次续接的序号（从 1 开始）
+    pub reconnect: u32,
+    pub max_reconnects: u32,
+    pub idle_secs: u64,
+}
+
+/// `{engine}-stalled` 事件负载
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct StalledPayload<'a> {
+    session_id: &'a str,
+    engine: &'a str,
+    idle_secs: u64,
+    /// 宽限期后是否会自动续接
+    will_reconnect: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    grace_secs: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    trace_id: Option<&'a str>,
+}
+
+/// `{engine}-reconnecting` 事件负载
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ReconnectingPayload<'a> {
+    session_id: &'a str,
+    engine: &'a str,
+    reconnect: u32,
+    max_reconnects: u32,
+    idle_secs: u64,
+    reason: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    trace_id: Option<&'a str>,
+}
+
+/// 获取卡死检测配置
+#[tauri::command]
+pub async fn get_stream_stall_config() -> Result<StreamStallConfig, String> {
+    load_json_config(get_config_path()?)
+}
+
+/// 更新卡死检测配置
+#[tauri::command]
+pub async fn update_stream_stall_config(config: StreamStallConfig) -> Result<(), String> {
+    if config.stall_threshold_secs < MIN_STALL_THRESHOLD_SECS {
+        return Err(format!(
+            "卡死判断阈值不能小于 {} 秒",
+            MIN_STALL_THRESHOLD_SECS
+        ));
+    }
+    if config.max_reconnects > MAX_ALLOWED_RECONNECTS {
+        return Err(format!("最大续接次数不能超过 {}", MAX_ALLOWED_RECONNECTS));
+    }
+    save_json_config(&config, get_config_path()?)
+}
+
+/// 读取配置供执行路径使用，读取失败时使用默认值
+pub fn load_stall_config() -> StreamStallConfig {
+    get_config_path()
+        .and_then(load_json_config)
+        .unwrap_or_else(|e| {
+            log::warn!("[Stall] Failed to load stream stall config: {}", e);
+            StreamStallConfig::default()
+        })
+}
+
+/// 看门狗：定期检查监视器，发送卡死提示；需要续接时返回续接信息
+///
+/// stdout 关闭（`stream_closed` 置位）后返回 None
+pub async fn watch_stream(
+    app: &AppHandle,
+    engine: &str,
+    session_id: &str,
+    trace_id: Option<&str>,
+    monitor: &SharedStallMonitor,
+    stream_closed: &AtomicBool,
+) -> Option<StallRecovery> {
+    loop {
+        tokio::time::sleep(POLL_INTERVAL).await;
+        if stream_closed.load(Ordering::Relaxed) {
+            return None;
+        }
+        let (action, config) = {
+            let mut monitor = monitor.lock().unwrap();
+            (monitor.poll(Instant::now()), monitor.config.clone())
+        };
+        match action {
+            StallAction::None => {}
+            StallAction::Nudge { idle } => {
+                log::warn!(
+                    "[Stall] {} session {} produced no output for {}s, waiting {}s before reconnecting",
+                    engine,
+                    session_id,
+                    idle.as_secs(),
+                    config.wake_grace_secs
+                );
+                emit_stalled(
+                    app,
+                    engine,
+                    session_id,
+                    idle,
+                    Some(config.wake_grace_secs),
+                    trace_id,
+                );
+            }
+            StallAction::Notify { idle } => {
+                log::warn!(
+                    "[Stall] {} session {} produced no output for {}s (auto recovery off or exhausted)",
+                    engine,
+                    session_id,
+                    idle.as_secs()
+                );
+                emit_stalled(app, engine, session_id, idle, None, trace_id);
+            }
+            StallAction::Recover { idle, reconnect } => {
+                return Some(StallRecovery {
+                    reconnect,
+                    max_reconnects: config.max_reconnects,
+                    idle_secs: idle.as_secs(),
+                })
+            }
+        }
+    }
+}
+
+/// 发送卡死提示；`grace_secs` 为 None 表示不会自动续接
+pub fn emit_stalled(
+    app: &AppHandle,
+    engine: &str,
+    session_id: &str,
+    idle: Duration,
+    grace_secs: Option<u64>,
+    trace_id: Option<&str>,
+) {
+    let payload = StalledPayload {
+        session_id,
+        engine,
+        idle_secs: idle.as_secs(),
+        will_reconnect: grace_secs.is_some(),
+        grace_secs,
+        trace_id,
+    };
+    let _ = app.emit(&format!("{}-stalled:{}", engine, session_id), &payload);
+    let _ = app.emit(&format!("{}-stalled", engine), &payload);
+}
+
+/// 优雅中断卡住的进程：先发送中断信号，超时仍未关闭 stdout 则强杀进程树
+pub async fn interrupt_stalled_process(pid: u32, stream_closed: &AtomicBool) {
+    if let Err(e) = request_process_exit(pid) {
+        log::warn!("[Stall] Failed to interrupt PID {}: {}", pid, e);
+    } else {
+        let started = tokio::time::Instant::now();
+        while started.elapsed() < GRACEFUL_EXIT_TIMEOUT {
+            if stream_closed.load(Ordering::Relaxed) {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+    }
+    log::warn!(
+        "[Stall] PID {} did not exit after interrupt, killing process tree",
+        pid
+    );
+    if let Err(e) = kill_process_tree(pid) {
+        log::error!("[Stall] Failed to kill process tree of PID {}: {}", pid, e);
+    }
+}
+
+/// 续接前发送 `reconnecting` 事件并记录追踪
+pub fn announce_reconnect(
+    app: &AppHandle,
+    engine: &str,
+    session_id: &str,
+    recovery: &StallRecovery,
+    resume_target: &str,
+    trace_id: Option<&str>,
+) {
+    let reason = format!("{} 秒无输出", recovery.idle_secs);
+    log::warn!(
+        "[Stall] {} session {} stalled ({}), resuming {} (reconnect {}/{})",
+        engine,
+        session_id,
+        reason,
+        resume_target,
+        recovery.reconnect,
+        recovery.max_reconnects
+    );
+    if let Some(trace_id) = trace_id {
+        record_trace_event(
+            trace_id,
+            STAGE_RECONNECTING,
+            TraceFields::engine(engine)
+                .with_session(Some(session_id))
+                .with_detail(serde_json::json!({
+                    "reconnect": recovery.reconnect,
+                    "maxReconnects": recovery.max_reconnects,
+                    "idleSecs": recovery.idle_secs,
+                    "resumeTarget": resume_target,
+                })),
+        );
+    }
+    let payload = ReconnectingPayload {
+        session_id,
+        engine,
+        reconnect: recovery.reconnect,
+        max_reconnects: recovery.max_reconnects,
+        idle_secs: recovery.idle_secs,
+        reason,
+        trace_id,
+    };
+    let _ = app.emit(&format!("{}-reconnecting:{}", engine, session_id), &payload);
+    let _ = app.emit(&format!("{}-reconnecting", engine), &payload);
+}
+
+fn get_config_path() -> Result<PathBuf, String> {
+    Ok(ConfigPathBuilder::from_home_subdir(".anycode")?.build("stream_stall.json"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(auto_recover: bool) -> StreamStallConfig {
+        StreamStallConfig {
+            auto_recover,
+            stall_threshold_secs: 120,
+            wake_grace_secs: 30,
+            max_reconnects: 2,
+        }
+    }
+
+    fn secs(n: u64) -> Duration {
+        Duration::from_secs(n)
+    }
+
+    #[test]
+    fn nudges_then_recovers_when_output_stays_silent() {
+        let start = Instant::now();
+        let mut monitor = StallMonitor::new(config(true), 0, start);
+
+        assert_eq!(monitor.poll(start + secs(60)), StallAction::None);
+        assert_eq!(
+            monitor.poll(start + secs(125)),
+            StallAction::Nudge { idle: secs(125) }
+        );
+        assert_eq!(monitor.poll(start + secs(140)), StallAction::None);
+        assert_eq!(
+            monitor.poll(start + secs(160)),
+            StallAction::Recover {
+                idle: secs(160),
+                reconnect: 1
+            }
+        );
+        // 已决定续接，不再重复触发
+        assert_eq!(monitor.poll(start + secs(300)), StallAction::None);
+    }
+
+    #[test]
+    fn output_during_grace_period_cancels_recovery() {
+        let start = Instant::now();
+        let mut monitor = StallMonitor::new(config(true), 0, start);
+
+        assert!(matches!(
+            monitor.poll(start + secs(125)),
+            StallAction::Nudge { .. }
+        ));
+        monitor.on_activity(StreamActivity::Output, start + secs(130));
+        assert_eq!(monitor.poll(start + secs(200)), StallAction::None);
+        assert!(matches!(
+            monitor.poll(start + secs(251)),
+            StallAction::Nudge { .. }
+        ));
+    }
+
+    #[test]
+    fn running_tools_are_a_normal_wait() {
+        let start = Instant::now();
+        let mut monitor = StallMonitor::new(config(true), 0, start);
+
+        let started = serde_json::json!({
+            "type": "item.started",
+            "item": { "id": "item_1", "type": "command_execution" }
+        });
+        monitor.on_activity(classify_codex_event(&started), start + secs(1));
+        assert_eq!(monitor.poll(start + secs(600)), StallAction::None);
+
+        let completed = serde_json::json!({
+            "type": "item.completed",
+            "item": { "id": "item_1", "type": "command_execution" }
+        });
+        monitor.on_activity(classify_codex_event(&completed), start + secs(601));
+        assert!(matches!(
+            monitor.poll(start + secs(722)),
+            StallAction::Nudge { .. }
+        ));
+    }
+
+    #[test]
+    fn only_notifies_when_recovery_is_off_or_exhausted() {
+        let start = Instant::now();
+        let mut disabled = StallMonitor::new(config(false), 0, start);
+        assert_eq!(
+            disabled.poll(start + secs(121)),
+            StallAction::Notify { idle: secs(121) }
+        );
+        assert_eq!(disabled.poll(start + secs(200)), StallAction::None);
+
+        let mut exhausted = StallMonitor::new(config(true), 2, start);
+        assert!(matches!(
+            exhausted.poll(start + secs(121)),
+            StallAction::Notify { .. }
+        ));
+    }
+
+    #[test]
+    fn classifies_tool_events() {
+        let todo = serde_json::json!({
+            "type": "item.started",
+            "item": { "id": "item_2", "type": "todo_list" }
+        });
+        assert_eq!(classify_codex_event(&todo), StreamActivity::Output);
+        let message = serde_json::json!({
+            "type": "item.completed",
+            "item": { "id": "item_3", "type": "agent_message" }
+        });
+        assert_eq!(classify_codex_event(&message), StreamActivity::Output);
+
+        let tool_use = GeminiStreamEvent::ToolUse {
+            tool_name: "run_shell_command".to_string(),
+            tool_id: "t1".to_string(),
+            parameters: serde_json::json!({}),
+            timestamp: None,
+        };
+        assert_eq!(
+            classify_gemini_event(&tool_use),
+            StreamActivity::ToolStarted
+        );
+    }
+}
@@ -0,0 +1,774 @@
+//! 任务完成动作
+//!
+//! 应用层的任务级自动化：Claude / Codex / Gemini 任务结束时，按顺序评估用户配置的规则，
+//! 对匹配的规则依次执行动作（系统通知、脚本、git 分支/标签）。
+//!
+//! 与 `enhanced_hooks` 的区别：这里不是 Claude Code 的会话钩子，而是由应用在统一的完成事件上触发；
+//! 脚本动作复用 `HookExecutor` 的执行能力。
+//!
+//! - 规则配置：~/.anycode/task_actions.json
+//! - 执行历史：~/.anycode/task_action_history.json（保留最近 200 条）
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+use std::time::Instant;
+use tauri::{AppHandle, Emitter};
+use tauri_plugin_notification::NotificationExt;
+use tokio::sync::broadcast;
+
+use super::claude::normalize_path_for_comparison;
+use super::enhanced_hooks::{EnhancedHook, HookContext, HookExecutor};
+use super::execution_trace::{
+    record_trace_event, TraceFields, STAGE_EXECUTION_FINISHED, STAGE_TASK_ACTION,
+};
+use super::simple_git::{git_create_branch, git_create_tag, is_git_repo};
+use super::undo::{self, Undoable};
+use crate::utils::config_utils::{load_json_config, save_json_config, ConfigPathBuilder};
+use crate::utils::text::truncate_chars;
+
+/// 脚本动作默认超时（秒）
+const DEFAULT_SCRIPT_TIMEOUT_SECS: u64 = 60;
+
+/// 执行历史最多保留的条数
+const MAX_HISTORY_RECORDS: usize = 200;
+
+/// 历史记录中输出内容的最大长度（字符）
+const MAX_OUTPUT_CHARS: usize = 2000;
+
+/// 串行化历史文件的读改写，避免多个任务同时结束时互相覆盖
+static HISTORY_LOCK: Mutex<()> = Mutex::new(());
+
+/// 完成事件广播，供后端其他模块（如批量提示词执行）等待任务结束
+static COMPLETION_TX: Lazy<broadcast::Sender<TaskCompletionEvent>> =
+    Lazy::new(|| broadcast::channel(64).0);
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// 统一的任务完成事件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskCompletionEvent {
+    /// 引擎类型: "claude" | "codex" | "gemini"
+    pub engine: String,
+    /// 项目路径
+    pub project_path: String,
+    /// 会话 ID（Claude 为 CLI 会话 ID，Codex/Gemini 为应用内通道 ID）
+    pub session_id: Option<String>,
+    /// 该通道绑定的 CLI 真实会话 ID（CLI 未报告时为空）
+    #[serde(default)]
+    pub cli_session_id: Option<String>,
+    /// 执行模式（Claude: default/plan，Codex: read-only/full-auto/danger-full-access，Gemini: approval mode）
+    pub mode: Option<String>,
+    /// 任务是否成功
+    pub success: bool,
+    /// 任务耗时（毫秒）
+    pub duration_ms: u64,
+    /// 触发本次执行的用户动作 trace id
+    #[serde(default)]
+    pub trace_id: Option<String>,
+}
+
+/// 规则对任务结果的要求
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TaskOutcome {
+    #[default]
+    Any,
+    Success,
+    Failure,
+}
+
+/// 规则触发条件（所有字段同时满足才匹配，列表为空表示不限制）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskActionCondition {
+    #[serde(default)]
+    pub engines: Vec<String>,
+    /// 项目路径，匹配该目录及其子目录
+    #[serde(default)]
+    pub project_paths: Vec<String>,
+    #[serde(default)]
+    pub modes: Vec<String>,
+    #[serde(default)]
+    pub outcome: TaskOutcome,
+    /// 耗时阈值（秒），任务耗时不低于该值才匹配
+    #[serde(default)]
+    pub min_duration_secs: Option<u64>,
+}
+
+/// 任务完成后执行的动作
+///
+/// 文本字段支持占位符：`{engine}` `{session_id}` `{project_name}` `{status}` `{duration}` `{timestamp}` `{trace_id}`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum TaskAction {
+    /// 系统通知
+    #[serde(rename_all = "camelCase")]
+    Notify {
+        title: Option<String>,
+        body: Option<String>,
+    },
+    /// 在项目目录下执行脚本，任务上下文通过 TASK_* 环境变量传入
+    #[serde(rename_all = "camelCase")]
+    Script {
+        command: String,
+        timeout_secs: Option<u64>,
+    },
+    /// 在 HEAD 创建分支（不切换当前分支）
+    #[serde(rename_all = "camelCase")]
+    GitBranch { name: String },
+    /// 在 HEAD 打 tag（有 message 时为附注标签）
+    #[serde(rename_all = "camelCase")]
+    GitTag {
+        name: String,
+        message: Option<String>,
+    },
+}
+
+impl TaskAction {
+    fn type_name(&self) -> &'static str {
+        match self {
+            TaskAction::Notify { .. } => "notify",
+            TaskAction::Script { .. } => "script",
+            TaskAction::GitBranch { .. } => "gitBranch",
+            TaskAction::GitTag { .. } => "gitTag",
+        }
+    }
+}
+
+/// 任务完成动作规则
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskActionRule {
+    /// 规则 ID（新建时可为空，由后端生成）
+    #[serde(default)]
+    pub id: String,
+    pub name: String,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    #[serde(default)]
+    pub condition: TaskActionCondition,
+    #[serde(default)]
+    pub actions: Vec<TaskAction>,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// 规则配置文件（列表顺序即评估顺序）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskActionsConfig {
+    #[serde(default)]
+    pub rules: Vec<TaskActionRule>,
+}
+
+/// 单个动作的执行记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskActionRecord {
+    pub id: String,
+    pub rule_id: String,
+    pub rule_name: String,
+    pub action_type: String,
+    pub engine: String,
+    pub project_path: String,
+    pub session_id: Option<String>,
+    /// 触发动作的任务是否成功
+    pub task_success: bool,
+    /// 动作本身是否执行成功
+    pub success: bool,
+    pub output: Option<String>,
+    pub error: Option<String>,
+    /// 执行时间（RFC 3339）
+    pub executed_at: String,
+    pub duration_ms: u64,
+    #[serde(default)]
+    pub trace_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TaskActionHistory {
+    #[serde(default)]
+    records: Vec<TaskActionRecord>,
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// 获取所有任务完成动作规则（按评估顺序）
+#[tauri::command]
+pub async fn get_task_action_rules() -> Result<Vec<TaskActionRule>, String> {
+    Ok(load_config()?.rules)
+}
+
+/// 新增或更新规则（id 为空或不存在时追加到末尾）
+#[tauri::command]
+pub async fn save_task_action_rule(mut rule: TaskActionRule) -> Result<TaskActionRule, String> {
+    if rule.name.trim().is_empty() {
+        return Err("规则名称不能为空".to_string());
+    }
+    validate_actions(&rule.actions)?;
+
+    let mut config = load_config()?;
+    if rule.id.is_empty() {
+        rule.id = uuid::Uuid::new_v4().to_string();
+    }
+
+    match config.rules.iter_mut().find(|r| r.id == rule.id) {
+        Some(existing) => *existing = rule.clone(),
+        None => config.rules.push(rule.clone()),
+    }
+
+    save_config(&config)?;
+    log::info!("[TaskActions] Saved rule: {} ({})", rule.name, rule.id);
+    Ok(rule)
+}
+
+/// 删除规则
+#[tauri::command]
+pub async fn delete_task_action_rule(rule_id: String) -> Result<(), String> {
+    let mut config = load_config()?;
+    let index = config
+        .rules
+        .iter()
+        .position(|r| r.id == rule_id)
+        .ok_or_else(|| format!("Rule not found: {}", rule_id))?;
+    let rule = config.rules.remove(index);
+    save_config(&config)?;
+    undo::record(&TaskActionRuleDeletion { index, rule });
+    Ok(())
+}
+
+/// 撤销删除规则：按原评估顺序放回
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskActionRuleDeletion {
+    index: usize,
+    rule: TaskActionRule,
+}
+
+impl Undoable for TaskActionRuleDeletion {
+    const KIND: &'static str = "task_action_rule_deletion";
+
+    fn description(&self) -> String {
+        format!("删除完成动作规则 {}", self.rule.name)
+    }
+
+    fn undo(self) -> Result<(), String> {
+        let mut config = load_config()?;
+        if config.rules.iter().any(|r| r.id == self.rule.id) {
+            return Err(format!("Rule already exists: {}", self.rule.id));
+        }
+        let index = self.index.min(config.rules.len());
+        config.rules.insert(index, self.rule);
+        save_config(&config)
+    }
+}
+
+/// 按给定的 ID 顺序重新排列规则（未列出的规则保持原有相对顺序并排在末尾）
+#[tauri::command]
+pub async fn reorder_task_action_rules(
+    rule_ids: Vec<String>,
+) -> Result<Vec<TaskActionRule>, String> {
+    let mut config = load_config()?;
+    let mut remaining = std::mem::take(&mut config.rules);
+
+    for id in &rule_ids {
+        if let Some(pos) = remaining.iter().position(|r| &r.id == id) {
+            config.rules.push(remaining.remove(pos));
+        }
+    }
+    config.rules.extend(remaining);
+
+    save_config(&config)?;
+    Ok(config.rules)
+}
+
+/// 查询动作执行历史（最新的在前）
+#[tauri::command]
+pub async fn get_task_action_history(
+    limit: Option<usize>,
+    rule_id: Option<String>,
+) -> Result<Vec<TaskActionRecord>, String> {
+    let history = {
+        let _guard = lock_history();
+        load_history()?
+    };
+
+    Ok(history
+        .records
+        .into_iter()
+        .rev()
+        .filter(|r| rule_id.as_ref().is_none_or(|id| &r.rule_id == id))
+        .take(limit.unwrap_or(MAX_HISTORY_RECORDS))
+        .collect())
+}
+
+/// 清空动作执行历史
+#[tauri::command]
+pub async fn clear_task_action_history() -> Result<(), String> {
+    let _guard = lock_history();
+    save_json_config(&TaskActionHistory::default(), &get_history_path()?)
+}
+
+// ============================================================================
+// Dispatch
+// ============================================================================
+
+/// 订阅统一的任务完成事件
+pub fn subscribe_task_completions() -> broadcast::Receiver<TaskCompletionEvent> {
+    COMPLETION_TX.subscribe()
+}
+
+/// 分发任务完成事件：在后台按顺序评估规则并执行匹配的动作，不阻塞调用方
+pub fn dispatch_task_completion(app: &AppHandle, event: TaskCompletionEvent) {
+    if let Some(trace_id) = event.trace_id.as_deref() {
+        record_trace_event(
+            trace_id,
+            STAGE_EXECUTION_FINISHED,
+            TraceFields::engine(&event.engine)
+                .with_session(event.session_id.as_deref())
+                .with_status(if event.success { "success" } else { "failed" })
+                .with_detail(serde_json::json!({
+                    "durationMs": event.duration_ms,
+                    "mode": event.mode,
+                    "projectPath": event.project_path,
+                })),
+        );
+    }
+
+    // 没有订阅者时发送失败属于正常情况
+    let _ = COMPLETION_TX.send(event.clone());
+
+    let app = app.clone();
+    tokio::spawn(async move {
+        let rules = match load_config() {
+            Ok(config) => config.rules,
+            Err(e) => {
+                log::warn!("[TaskActions] Failed to load rules: {}", e);
+                return;
+            }
+        };
+
+        for rule in rules.iter().filter(|r| r.enabled) {
+            if !rule_matches(&rule.condition, &event) {
+                continue;
+            }
+            log::info!(
+                "[TaskActions] Rule '{}' matched {} task (success={}, {}ms)",
+                rule.name,
+                event.engine,
+                event.success,
+                event.duration_ms
+            );
+
+            // 单个动作失败只记录，不影响后续动作与规则
+            for action in &rule.actions {
+                let record = execute_action(&app, rule, action, &event).await;
+                if !record.success {
+                    log::warn!(
+                        "[TaskActions] Action {} of rule '{}' failed: {}",
+                        record.action_type,
+                        rule.name,
+                        record.error.as_deref().unwrap_or("unknown error")
+                    );
+                }
+                if let Some(trace_id) = record.trace_id.as_deref() {
+                    record_trace_event(
+                        trace_id,
+                        STAGE_TASK_ACTION,
+                        TraceFields::engine(&record.engine)
+                            .with_session(record.session_id.as_deref())
+                            .with_status(if record.success { "success" } else { "failed" })
+                            .with_detail(serde_json::json!({
+                                "ruleName": record.rule_name,
+                                "actionType": record.action_type,
+                                "error": record.error,
+                            })),
+                    );
+                }
+                let _ = app.emit("task-action-executed", &record);
+                if let Err(e) = append_history(record) {
+                    log::warn!("[TaskActions] Failed to save history: {}", e);
+                }
+            }
+        }
+    });
+}
+
+/// 判断事件是否满足规则条件
+fn rule_matches(condition: &TaskActionCondition, event: &TaskCompletionEvent) -> bool {
+    if !condition.engines.is_empty()
+        && !condition
+            .engines
+            .iter()
+            .any(|e| e.eq_ignore_ascii_case(&event.engine))
+    {
+        return false;
+    }
+
+    if !condition.project_paths.is_empty() {
+        let project = normalize_path_for_comparison(&event.project_path);
+        let matched = condition.project_paths.iter().any(|p| {
+            let prefix = normalize_path_for_comparison(p);
+            let prefix = prefix.trim_end_matches('/');
+            project == prefix || project.starts_with(&format!("{}/", prefix))
+        });
+        if !matched {
+            return false;
+        }
+    }
+
+    if !condition.modes.is_empty() {
+        let Some(mode) = event.mode.as_deref() else {
+            return false;
+        };
+        if !condition.modes.iter().any(|m| m.eq_ignore_ascii_case(mode)) {
+            return false;
+        }
+    }
+
+    match condition.outcome {
+        TaskOutcome::Any => {}
+        TaskOutcome::Success if !event.success => return false,
+        TaskOutcome::Failure if event.success => return false,
+        _ => {}
+    }
+
+    if let Some(min_secs) = condition.min_duration_secs {
+        if event.duration_ms < min_secs.saturating_mul(1000) {
+            return false;
+        }
+    }
+
+    true
+}
+
+/// 执行单个动作并生成执行记录
+async fn execute_action(
+    app: &AppHandle,
+    rule: &TaskActionRule,
+    action: &TaskAction,
+    event: &TaskCompletionEvent,
+) -> TaskActionRecord {
+    let start = Instant::now();
+    let placeholders = build_placeholders(event);
+
+    let result: Result<Option<String>, String> = match action {
+        TaskAction::Notify { title, body } => {
+            let title = render_template(
+                title.as_deref().unwrap_or("{engine} 任务{status}"),
+                &placeholders,
+            );
+            let body = render_template(
+                body.as_deref()
+                    .unwrap_or("{project_name} · 耗时 {duration}"),
+                &placeholders,
+            );
+            app.notification()
+                .builder()
+                .title(title)
+                .body(body)
+                .show()
+                .map(|_| None)
+                .map_err(|e| format!("Failed to show notification: {}", e))
+        }
+        TaskAction::Script {
+            command,
+            timeout_secs,
+        } => run_script(app, command, *timeout_secs, event, &placeholders).await,
+        TaskAction::GitBranch { name } => {
+            let name = render_template(name, &placeholders);
+            ensure_git_project(&event.project_path)
+                .and_then(|_| git_create_branch(&event.project_path, &name))
+                .map(|_| Some(name))
+        }
+        TaskAction::GitTag { name, message } => {
+            let name = render_template(name, &placeholders);
+            let message = message
+                .as_deref()
+                .map(|m| render_template(m, &placeholders));
+            ensure_git_project(&event.project_path)
+                .and_then(|_| git_create_tag(&event.project_path, &name, message.as_deref()))
+                .map(|_| Some(name))
+        }
+    };
+
+    let (success, output, error) = match result {
+        Ok(output) => (true, output, None),
+        Err(e) => (false, None, Some(e)),
+    };
+
+    TaskActionRecord {
+        id: uuid::Uuid::new_v4().to_string(),
+        rule_id: rule.id.clone(),
+        rule_name: rule.name.clone(),
+        action_type: action.type_name().to_string(),
+        engine: event.engine.clone(),
+        project_path: event.project_path.clone(),
+        session_id: event.session_id.clone(),
+        task_success: event.success,
+        success,
+        output: output.map(|o| truncate_chars(&o, MAX_OUTPUT_CHARS)),
+        error: error.map(|e| truncate_chars(&e, MAX_OUTPUT_CHARS)),
+        executed_at: chrono::Utc::now().to_rfc3339(),
+        duration_ms: start.elapsed().as_millis() as u64,
+        trace_id: event.trace_id.clone(),
+    }
+}
+
+/// 通过 HookExecutor 在项目目录下执行脚本
+async fn run_script(
+    app: &AppHandle,
+    command: &str,
+    timeout_secs: Option<u64>,
+    event: &TaskCompletionEvent,
+    placeholders: &HashMap<&'static str, String>,
+) -> Result<Option<String>, String> {
+    let hook = EnhancedHook {
+        command: command.to_string(),
+        timeout: Some(timeout_secs.unwrap_or(DEFAULT_SCRIPT_TIMEOUT_SECS)),
+        retry: None,
+        condition: None,
+        on_success: None,
+        on_failure: None,
+    };
+
+    let mut env = HashMap::new();
+    env.insert("TASK_ENGINE".to_string(), event.engine.clone());
+    env.insert("TASK_PROJECT_PATH".to_string(), event.project_path.clone());
+    env.insert(
+        "TASK_SESSION_ID".to_string(),
+        event.session_id.clone().unwrap_or_default(),
+    );
+    env.insert(
+        "TASK_MODE".to_string(),
+        event.mode.clone().unwrap_or_default(),
+    );
+    env.insert("TASK_SUCCESS".to_string(), event.success.to_string());
+    env.insert(
+        "TASK_DURATION_MS".to_string(),
+        event.duration_ms.to_string(),
+    );
+    env.insert("TASK_STATUS".to_string(), placeholders["status"].clone());
+    env.insert(
+        "TASK_TRACE_ID".to_string(),
+        placeholders["trace_id"].clone(),
+    );
+
+    let working_dir = Some(event.project_path.clone()).filter(|p| Path::new(p).is_dir());
+
+    let context = HookContext {
+        event: "TaskComplete".to_string(),
+        session_id: event.session_id.clone().unwrap_or_default(),
+        project_path: event.project_path.clone(),
+        data: serde_json::to_value(event).unwrap_or_default(),
+        working_dir,
+        env,
+    };
+
+    let result = HookExecutor::new(app.clone())
+        .execute_hook(&hook, &context)
+        .await?;
+
+    if result.success {
+        Ok(Some(result.output))
+    } else {
+        Err(result
+            .error
+            .unwrap_or_else(|| "Script exited with non-zero status".to_string()))
+    }
+}
+
+fn ensure_git_project(project_path: &str) -> Result<(), String> {
+    if is_git_repo(project_path) {
+        Ok(())
+    } else {
+        Err(format!("Not a git repository: {}", project_path))
+    }
+}
+
+fn validate_actions(actions: &[TaskAction]) -> Result<(), String> {
+    for action in actions {
+        let empty = match action {
+            TaskAction::Notify { .. } => false,
+            TaskAction::Script { command, .. } => command.trim().is_empty(),
+            TaskAction::GitBranch { name } | TaskAction::GitTag { name, .. } => {
+                name.trim().is_empty()
+            }
+        };
+        if empty {
+            return Err(format!("动作 {} 缺少必填内容", action.type_name()));
+        }
+    }
+    Ok(())
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+fn build_placeholders(event: &TaskCompletionEvent) -> HashMap<&'static str, String> {
+    let project_name = Path::new(&event.project_path.replace('\\', "/"))
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| event.project_path.clone());
+
+    let secs = event.duration_ms / 1000;
+    let duration = if secs >= 60 {
+        format!("{}m{}s", secs / 60, secs % 60)
+    } else {
+        format!("{}s", secs)
+    };
+
+    let mut map = HashMap::new();
+    map.insert("engine", event.engine.clone());
+    map.insert("session_id", event.session_id.clone().unwrap_or_default());
+    map.insert("project_name", project_name);
+    map.insert(
+        "status",
+        if event.success { "success" } else { "failure" }.to_string(),
+    );
+    map.insert("duration", duration);
+    map.insert("trace_id", event.trace_id.clone().unwrap_or_default());
+    map.insert(
+        "timestamp",
+        chrono::Local::now().format("%Y%m%d-%H%M%S").to_string(),
+    );
+    map
+}
+
+fn render_template(template: &str, placeholders: &HashMap<&'static str, String>) -> String {
+    placeholders
+        .iter()
+        .fold(template.to_string(), |acc, (key, value)| {
+            acc.replace(&format!("{{{}}}", key), value)
+        })
+}
+
+fn get_config_path() -> Result<PathBuf, String> {
+    Ok(ConfigPathBuilder::from_home_subdir(".anycode")?.build("task_actions.json"))
+}
+
+fn get_history_path() -> Result<PathBuf, String> {
+    Ok(ConfigPathBuilder::from_home_subdir(".anycode")?.build("task_action_history.json"))
+}
+
+fn load_config() -> Result<TaskActionsConfig, String> {
+    load_json_config(get_config_path()?)
+}
+
+fn save_config(config: &TaskActionsConfig) -> Result<(), String> {
+    save_json_config(config, &get_config_path()?)
+}
+
+/// 获取历史文件锁；锁只保护文件读写，持有者 panic 后仍可继续使用
+fn lock_history() -> MutexGuard<'static, ()> {
+    HISTORY_LOCK
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+fn load_history() -> Result<TaskActionHistory, String> {
+    load_json_config(get_history_path()?)
+}
+
+fn append_history(record: TaskActionRecord) -> Result<(), String> {
+    let _guard = lock_history();
+    let mut history = load_history().unwrap_or_default();
+    history.records.push(record);
+    if history.records.len() > MAX_HISTORY_RECORDS {
+        let excess = history.records.len() - MAX_HISTORY_RECORDS;
+        history.records.drain(..excess);
+    }
+    save_json_config(&history, &get_history_path()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(engine: &str, success: bool, duration_secs: u64) -> TaskCompletionEvent {
+        TaskCompletionEvent {
+            engine: engine.to_string(),
+            project_path: "/home/user/work/app".to_string(),
+            session_id: Some("s1".to_string()),
+            cli_session_id: None,
+            mode: Some("full-auto".to_string()),
+            success,
+            duration_ms: duration_secs * 1000,
+            trace_id: None,
+        }
+    }
+
+    #[test]
+    fn failure_and_long_success_rules() {
+        let notify_on_failure = TaskActionCondition {
+            outcome: TaskOutcome::Failure,
+            ..Default::default()
+        };
+        let test_after_long_success = TaskActionCondition {
+            outcome: TaskOutcome::Success,
+            min_duration_secs: Some(600),
+            ..Default::default()
+        };
+
+        assert!(rule_matches(&notify_on_failure, &event("codex", false, 5)));
+        assert!(!rule_matches(&notify_on_failure, &event("codex", true, 5)));
+
+        assert!(rule_matches(
+            &test_after_long_success,
+            &event("claude", true, 601)
+        ));
+        assert!(!rule_matches(
+            &test_after_long_success,
+            &event("claude", true, 599)
+        ));
+        assert!(!rule_matches(
+            &test_after_long_success,
+            &event("claude", false, 900)
+        ));
+    }
+
+    #[test]
+    fn engine_project_and_mode_filters() {
+        let condition = TaskActionCondition {
+            engines: vec!["Codex".to_string()],
+            project_paths: vec!["/home/user/work/".to_string()],
+            modes: vec!["full-auto".to_string()],
+            ..Default::default()
+        };
+
+        assert!(rule_matches(&condition, &event("codex", true, 1)));
+        assert!(!rule_matches(&condition, &event("gemini", true, 1)));
+
+        let mut other_project = event("codex", true, 1);
+        other_project.project_path = "/home/user/workspace/app".to_string();
+        assert!(!rule_matches(&condition, &other_project));
+
+        let mut no_mode = event("codex", true, 1);
+        no_mode.mode = None;
+        assert!(!rule_matches(&condition, &no_mode));
+    }
+
+    #[test]
+    fn renders_placeholders() {
+        let placeholders = build_placeholders(&event("claude", false, 125));
+        assert_eq!(
+            render_template(
+                "{engine}/{project_name}: {status} in {duration}",
+                &placeholders
+            ),
+            "claude/app: failure in 2m5s"
+        );
+    }
+}
@@ -0,0 +1,558 @@
+//! Codex / Gemini 子进程资源监控
+//!
+//! CLI 调用的脚本陷入死循环或内存泄漏时，界面上只能看到任务仍在运行。
+//! - 有任务运行时每 `SAMPLE_INTERVAL` 采样一次各任务的进程树（CLI 进程及全部子孙进程）的 CPU 与内存
+//! - CPU 以单核百分比计，多核并行时可超过 100%
+//! - CPU 持续高于阈值达到设定时长、或内存高于阈值时发出 `task:resource-warning` 事件，
+//!   附当前占用与进程树快照；占用回落到阈值以下后才会再次告警
+//! - `kill_task_subprocess` 只结束进程树中的某个子孙进程（连同它的子进程），任务本身继续运行
+//! - 读不到的进程信息（权限不足等）按缺失处理，采样不报错
+//!
+//! 阈值持久化：~/.anycode/task_resource_monitor.json
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate, System, UpdateKind};
+use tauri::{AppHandle, Emitter, Manager};
+
+use super::codex::CodexProcessState;
+use super::gemini::GeminiProcessState;
+use crate::utils::config_utils::{load_json_config, save_json_config, ConfigPathBuilder};
+
+/// 资源告警事件
+const RESOURCE_WARNING_EVENT: &str = "task:resource-warning";
+
+/// 采样间隔
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(5);
+
+/// 各任务最近一次采样与告警状态：(引擎, 通道 ID) → 监控状态
+static MONITORS: Lazy<Mutex<HashMap<(String, String), TaskMonitor>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// 资源监控配置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TaskResourceConfig {
+    pub enabled: bool,
+    /// CPU 告警阈值（单核百分比）
+    pub cpu_threshold_percent: f32,
+    /// CPU 持续高于阈值多久后告警（分钟）
+    pub cpu_sustained_minutes: u64,
+    /// 内存告警阈值（MB）
+    pub memory_threshold_mb: u64,
+}
+
+impl Default for TaskResourceConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            cpu_threshold_percent: 90.0,
+            cpu_sustained_minutes: 10,
+            memory_threshold_mb: 4096,
+        }
+    }
+}
+
+/// 进程树中的一个进程
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProcessSnapshot {
+    pub pid: u32,
+    pub parent_pid: Option<u32>,
+    pub name: String,
+    /// 读不到时为空字符串
+    pub command_line: String,
+    pub cpu_percent: f32,
+    pub memory_bytes: u64,
+    /// 进程启动时间（Unix 秒）
+    pub start_time: u64,
+}
+
+/// 一个任务进程树的资源占用
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskResourceUsage {
+    /// 整棵进程树的 CPU 合计（单核百分比）
+    pub cpu_percent: f32,
+    pub memory_bytes: u64,
+    /// 根进程（CLI）在前，其余按层级排列
+    pub processes: Vec<ProcessSnapshot>,
+    /// 采样时间（Unix 秒）
+    pub sampled_at: i64,
+}
+
+/// 告警类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ResourceWarningKind {
+    Cpu,
+    Memory,
+}
+
+/// 资源告警（`task:resource-warning` 事件的负载）
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskResourceWarning {
+    pub engine: String,
+    /// 应用内通道会话 ID
+    pub session_id: String,
+    pub kind: ResourceWarningKind,
+    /// 占用开始超过阈值的时间（Unix 秒）
+    pub since: i64,
+    pub usage: TaskResourceUsage,
+}
+
+/// 运行中的 Codex / Gemini 任务
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunningAiTask {
+    /// 引擎类型: "codex" | "gemini"
+    pub engine: String,
+    /// 应用内通道会话 ID
+    pub session_id: String,
+    pub cli_session_id: Option<String>,
+    pub pid: u32,
+    /// Codex 不记录项目路径
+    pub project_path: Option<String>,
+    /// 最近一次采样；尚未采样、监控关闭或读不到进程时为 None
+    pub resources: Option<TaskResourceUsage>,
+}
+
+/// 单个任务的告警状态
+#[derive(Debug, Default)]
+struct TaskMonitor {
+    usage: Option<TaskResourceUsage>,
+    cpu_high_since: Option<i64>,
+    cpu_warned: bool,
+    memory_high_since: Option<i64>,
+}
+
+impl TaskMonitor {
+    /// 记录一次采样，返回需要发出的告警及其开始时间
+    fn observe(
+        &mut self,
+        usage: TaskResourceUsage,
+        config: &TaskResourceConfig,
+    ) -> Vec<(ResourceWarningKind, i64)> {
+        let now = usage.sampled_at;
+        let mut warnings = Vec::new();
+
+        if usage.cpu_percent > config.cpu_threshold_percent {
+            let since = *self.cpu_high_since.get_or_insert(now);
+            let sustained = (config.cpu_sustained_minutes * 60) as i64;
+            if !self.cpu_warned && now - since >= sustained {
+                self.cpu_warned = true;
+                warnings.push((ResourceWarningKind::Cpu, since));
+            }
+        } else {
+            self.cpu_high_since = None;
+            self.cpu_warned = false;
+        }
+
+        if usage.memory_bytes > config.memory_threshold_mb * 1024 * 1024 {
+            if self.memory_high_since.is_none() {
+                self.memory_high_since = Some(now);
+                warnings.push((ResourceWarningKind::Memory, now));
+            }
+        } else {
+            self.memory_high_since = None;
+        }
+
+        self.usage = Some(usage);
+        warnings
+    }
+}
+
+/// 运行中任务的根进程
+struct TaskProcess {
+    engine: &'static str,
+    session_id: String,
+    cli_session_id: Option<String>,
+    pid: u32,
+    project_path: Option<String>,
+}
+
+async fn running_task_processes(app: &AppHandle) -> Vec<TaskProcess> {
+    let mut tasks = Vec::new();
+    {
+        let state = app.state::<CodexProcessState>();
+        let processes = state.processes.lock().await;
+        tasks.extend(processes.iter().map(|(channel, handle)| TaskProcess {
+            engine: "codex",
+            session_id: channel.clone(),
+            cli_session_id: handle.cli_session_id.clone(),
+            pid: handle.pid,
+            project_path: None,
+        }));
+    }
+    {
+        let state = app.state::<GeminiProcessState>();
+        let processes = state.processes.lock().await;
+        tasks.extend(processes.iter().map(|(channel, handle)| TaskProcess {
+            engine: "gemini",
+            session_id: channel.clone(),
+            cli_session_id: handle.cli_session_id.clone(),
+            pid: handle.pid,
+            project_path: Some(handle.project_path.clone()),
+        }));
+    }
+    tasks
+}
+
+/// 刷新进程表并返回全部进程的快照
+fn snapshot_processes(system: &mut System, with_usage: bool) -> HashMap<u32, ProcessSnapshot> {
+    let mut refresh = ProcessRefreshKind::nothing().with_cmd(UpdateKind::OnlyIfNotSet);
+    if with_usage {
+        refresh = refresh.with_cpu().with_memory();
+    }
+    system.refresh_processes_specifics(ProcessesToUpdate::All, true, refresh);
+
+    system
+        .processes()
+        .iter()
+        .map(|(pid, process)| {
+            let command_line = process
+                .cmd()
+                .iter()
+                .map(|arg| arg.to_string_lossy())
+                .collect::<Vec<_>>()
+                .join(" ");
+            let snapshot = ProcessSnapshot {
+                pid: pid.as_u32(),
+                parent_pid: process.parent().map(Pid::as_u32),
+                name: process.name().to_string_lossy().to_string(),
+                command_line,
+                cpu_percent: process.cpu_usage(),
+                memory_bytes: process.memory(),
+                start_time: process.start_time(),
+            };
+            (snapshot.pid, snapshot)
+        })
+        .collect()
+}
+
+/// 从根进程出发收集进程树（根在前，按层级排列）；根进程不存在时返回空
+///
+/// 父进程退出后其 PID 可能被无关进程复用（Windows 上尤为常见），
+/// 因此只把启动时间不早于父进程的进程视为它的子进程
+fn collect_tree(root: u32, processes: &HashMap<u32, ProcessSnapshot>) -> Vec<ProcessSnapshot> {
+    let Some(root_process) = processes.get(&root) else {
+        return Vec::new();
+    };
+
+    let mut children: HashMap<u32, Vec<&ProcessSnapshot>> = HashMap::new();
+    for process in processes.values() {
+        if let Some(parent) = process.parent_pid {
+            if parent != process.pid {
+                children.entry(parent).or_default().push(process);
+            }
+        }
+    }
+
+    for kids in children.values_mut() {
+        kids.sort_by_key(|process| process.pid);
+    }
+
+    let mut tree = vec![root_process.clone()];
+    let mut visited = HashSet::from([root]);
+    let mut queue = VecDeque::from([root_process]);
+    while let Some(parent) = queue.pop_front() {
+        for &child in children.get(&parent.pid).into_iter().flatten() {
+            if child.start_time >= parent.start_time && visited.insert(child.pid) {
+                tree.push(child.clone());
+                queue.push_back(child);
+            }
+        }
+    }
+    tree
+}
+
+fn summarize(processes: Vec<ProcessSnapshot>, sampled_at: i64) -> TaskResourceUsage {
+    TaskResourceUsage {
+        cpu_percent: processes.iter().map(|process| process.cpu_percent).sum(),
+        memory_bytes: processes.iter().map(|process| process.memory_bytes).sum(),
+        processes,
+        sampled_at,
+    }
+}
+
+/// 后台采样循环，应用启动时调用一次
+pub async fn run_resource_monitor(app: AppHandle) {
+    // CPU 占用按两次刷新之间的差值计算，System 需要跨采样保留
+    let mut system = System::new();
+    loop {
+        tokio::time::sleep(SAMPLE_INTERVAL).await;
+
+        let tasks = running_task_processes(&app).await;
+        let config = if tasks.is_empty() {
+            None
+        } else {
+            Some(load_config()).filter(|config| config.enabled)
+        };
+        let Some(config) = config else {
+            if let Ok(mut monitors) = MONITORS.lock() {
+                monitors.clear();
+            }
+            continue;
+        };
+
+        let sampled = tokio::task::spawn_blocking(move || {
+            let processes = snapshot_processes(&mut system, true);
+            (system, processes)
+        })
+        .await;
+        let processes = match sampled {
+            Ok((returned, processes)) => {
+                system = returned;
+                processes
+            }
+            Err(e) => {
+                log::warn!("[TaskResources] Sampling task failed: {}", e);
+                system = System::new();
+                continue;
+            }
+        };
+
+        let sampled_at = chrono::Utc::now().timestamp();
+        let mut warnings = Vec::new();
+        if let Ok(mut monitors) = MONITORS.lock() {
+            monitors.retain(|(engine, session_id), _| {
+                tasks
+                    .iter()
+                    .any(|task| task.engine == engine && &task.session_id == session_id)
+            });
+            for task in &tasks {
+                let tree = collect_tree(task.pid, &processes);
+                let monitor = monitors
+                    .entry((task.engine.to_string(), task.session_id.clone()))
+                    .or_default();
+                if tree.is_empty() {
+                    // 读不到根进程（刚退出或无权限），跳过本次采样
+                    monitor.usage = None;
+                    continue;
+                }
+                let usage = summarize(tree, sampled_at);
+                for (kind, since) in monitor.observe(usage.clone(), &config) {
+                    warnings.push(TaskResourceWarning {
+                        engine: task.engine.to_string(),
+                        session_id: task.session_id.clone(),
+                        kind,
+                        since,
+                        usage: usage.clone(),
+                    });
+                }
+            }
+        }
+
+        for warning in warnings {
+            log::warn!(
+                "[TaskResources] {} task {} exceeded the {:?} threshold (CPU {:.0}%, memory {} MB)",
+                warning.engine,
+                warning.session_id,
+                warning.kind,
+                warning.usage.cpu_percent,
+                warning.usage.memory_bytes / 1024 / 1024
+            );
+            let _ = app.emit(RESOURCE_WARNING_EVENT, &warning);
+        }
+    }
+}
+
+/// 列出运行中的 Codex / Gemini 任务及其最近一次资源采样
+#[tauri::command]
+pub async fn list_running_ai_tasks(app: AppHandle) -> Result<Vec<RunningAiTask>, String> {
+    let tasks = running_task_processes(&app).await;
+    let monitors = MONITORS.lock().map_err(|e| e.to_string())?;
+    Ok(tasks
+        .into_iter()
+        .map(|task| {
+            let resources = monitors
+                .get(&(task.engine.to_string(), task.session_id.clone()))
+                .and_then(|monitor| monitor.usage.clone());
+            RunningAiTask {
+                engine: task.engine.to_string(),
+                session_id: task.session_id,
+                cli_session_id: task.cli_session_id,
+                pid: task.pid,
+                project_path: task.project_path,
+                resources,
+            }
+        })
+        .collect())
+}
+
+/// 结束运行中任务的某个子孙进程（连同它的子进程），不取消任务本身
+#[tauri::command]
+pub async fn kill_task_subprocess(app: AppHandle, pid: u32) -> Result<(), String> {
+    let tasks = running_task_processes(&app).await;
+    if tasks.iter().any(|task| task.pid == pid) {
+        return Err(format!(
+            "PID {} is the task's CLI process; cancel the task instead",
+            pid
+        ));
+    }
+    let roots: Vec<u32> = tasks.iter().map(|task| task.pid).collect();
+
+    tokio::task::spawn_blocking(move || {
+        let mut system = System::new();
+        let processes = snapshot_processes(&mut system, false);
+        let owned = roots
+            .iter()
+            .any(|&root| collect_tree(root, &processes).iter().any(|p| p.pid == pid));
+        if !owned {
+            return Err(format!(
+                "PID {} does not belong to a running Codex or Gemini task",
+                pid
+            ));
+        }
+
+        // 先结束最深层的进程，避免子进程被重新挂到其他父进程下
+        let subtree = collect_tree(pid, &processes);
+        for process in subtree.iter().skip(1).rev() {
+            let killed = system
+                .process(Pid::from_u32(process.pid))
+                .is_some_and(|p| p.kill());
+            if !killed {
+                log::warn!(
+                    "[TaskResources] Failed to kill descendant PID {} of {}",
+                    process.pid,
+                    pid
+                );
+            }
+        }
+        let killed = system.process(Pid::from_u32(pid)).is_some_and(|p| p.kill());
+        if !killed {
+            return Err(format!(
+                "Failed to terminate PID {} (access denied or already exited)",
+                pid
+            ));
+        }
+        log::info!(
+            "[TaskResources] Killed task subprocess {} ({} process(es))",
+            pid,
+            subtree.len()
+        );
+        Ok(())
+    })
+    .await
+    .map_err(|e| format!("Kill task failed: {}", e))?
+}
+
+fn get_config_path() -> Result<PathBuf, String> {
+    Ok(ConfigPathBuilder::from_home_subdir(".anycode")?.build("task_resource_monitor.json"))
+}
+
+/// 读取配置，读取失败时按默认值处理
+fn load_config() -> TaskResourceConfig {
+    get_config_path()
+        .and_then(load_json_config)
+        .unwrap_or_else(|e| {
+            log::warn!("[TaskResources] Failed to load config: {}", e);
+            TaskResourceConfig::default()
+        })
+}
+
+/// 获取资源监控配置
+#[tauri::command]
+pub async fn get_task_resource_config() -> Result<TaskResourceConfig, String> {
+    load_json_config(get_config_path()?)
+}
+
+/// 更新资源监控配置，下一次采样起生效
+#[tauri::command]
+pub async fn update_task_resource_config(config: TaskResourceConfig) -> Result<(), String> {
+    if config.cpu_threshold_percent <= 0.0 || config.memory_threshold_mb == 0 {
+        return Err("Resource thresholds must be greater than zero".to_string());
+    }
+    save_json_config(&config, get_config_path()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn process(pid: u32, parent_pid: Option<u32>, start_time: u64) -> ProcessSnapshot {
+        ProcessSnapshot {
+            pid,
+            parent_pid,
+            name: format!("p{}", pid),
+            command_line: String::new(),
+            cpu_percent: 0.0,
+            memory_bytes: 0,
+            start_time,
+        }
+    }
+
+    fn usage(cpu_percent: f32, memory_mb: u64, sampled_at: i64) -> TaskResourceUsage {
+        TaskResourceUsage {
+            cpu_percent,
+            memory_bytes: memory_mb * 1024 * 1024,
+            processes: Vec::new(),
+            sampled_at,
+        }
+    }
+
+    #[test]
+    fn process_tree_skips_processes_older_than_a_reused_parent_pid() {
+        let processes: HashMap<u32, ProcessSnapshot> = [
+            process(10, Some(1), 100),
+            process(11, Some(10), 110),
+            process(12, Some(11), 120),
+            // 父进程 20 已退出，PID 复用后挂到了同一个 PID 下
+            process(13, Some(10), 50),
+            process(20, Some(1), 100),
+        ]
+        .into_iter()
+        .map(|p| (p.pid, p))
+        .collect();
+
+        let pids: Vec<u32> = collect_tree(10, &processes).iter().map(|p| p.pid).collect();
+        assert_eq!(pids, vec![10, 11, 12]);
+        assert!(collect_tree(99, &processes).is_empty());
+    }
+
+    #[test]
+    fn cpu_warning_requires_sustained_load_and_rearms_after_recovery() {
+        let config = TaskResourceConfig {
+            cpu_sustained_minutes: 1,
+            ..Default::default()
+        };
+        let mut monitor = TaskMonitor::default();
+
+        assert!(monitor.observe(usage(100.0, 10, 0), &config).is_empty());
+        assert!(monitor.observe(usage(100.0, 10, 30), &config).is_empty());
+        assert_eq!(
+            monitor.observe(usage(100.0, 10, 60), &config),
+            vec![(ResourceWarningKind::Cpu, 0)]
+        );
+        assert!(monitor.observe(usage(100.0, 10, 120), &config).is_empty());
+
+        assert!(monitor.observe(usage(5.0, 10, 125), &config).is_empty());
+        assert!(monitor.observe(usage(100.0, 10, 130), &config).is_empty());
+        assert_eq!(
+            monitor.observe(usage(100.0, 10, 190), &config),
+            vec![(ResourceWarningKind::Cpu, 130)]
+        );
+    }
+
+    #[test]
+    fn memory_warning_fires_once_per_excursion() {
+        let config = TaskResourceConfig::default();
+        let mut monitor = TaskMonitor::default();
+
+        assert_eq!(
+            monitor.observe(usage(0.0, 5000, 10), &config),
+            vec![(ResourceWarningKind::Memory, 10)]
+        );
+        assert!(monitor.observe(usage(0.0, 5000, 15), &config).is_empty());
+        assert!(monitor.observe(usage(0.0, 100, 20), &config).is_empty());
+        assert_eq!(
+            monitor.observe(usage(0.0, 4097, 25), &config),
+            vec![(ResourceWarningKind::Memory, 25)]
+        );
+    }
+}
@@ -0,0 +1,580 @@
+//! 工具调用耗时
+//!
+//! 回看会话时需要知道哪一步工具调用最慢（常见的是某个 shell 命令跑了几分钟）。本模块按调用 ID
+//! 配对工具调用与结果，用两端时间戳之差计算 `duration_ms`：
+//! - Claude：tool_use 与 tool_result（按 tool_use_id）
+//! - Codex：function_call / custom_tool_call / local_shell_call 与对应 output（按 call_id），
+//!   exec_command_end / mcp_tool_call_end 自带的耗时优先
+//! - Gemini：会话文件中 toolCalls 的记录时间相对所在消息的时间
+//!
+//! 历史加载时耗时写入工具调用块（tool_use 块、Codex 的调用事件 payload、Gemini 的 toolCall）的
+//! `duration_ms` 字段；流式执行时由 [`LiveToolTimer`] 在开始事件处计时，并写入结束事件。
+//! 时间戳缺失、无法解析或结果早于调用（乱序）时耗时为 None。
+
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::ops::ControlFlow;
+use std::path::Path;
+use std::time::Instant;
+
+use super::gemini::types::GeminiSessionDetail;
+use super::mcp_calls::{codex_duration_ms, elapsed_ms};
+use super::session_utils::resolve_session_file;
+use crate::utils::jsonl_reader::for_each_line;
+use crate::utils::text::truncate_chars;
+
+/// 工具耗时字段名（工具块与流式结束事件共用）
+const DURATION_FIELD: &str = "duration_ms";
+
+/// 参数摘要的最大长度（字符）
+const INPUT_SUMMARY_MAX_CHARS: usize = 160;
+
+/// 参数摘要优先展示的字段
+const SUMMARY_KEYS: &[&str] = &[
+    "command",
+    "cmd",
+    "file_path",
+    "path",
+    "pattern",
+    "query",
+    "url",
+    "description",
+];
+
+/// 单次工具调用及其耗时
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolCallTiming {
+    pub call_id: String,
+    pub tool_name: String,
+    /// 参数摘要（命令、文件路径等）
+    pub input_summary: String,
+    pub started_at: Option<String>,
+    /// 调用耗时，没有配对到结果或时间戳不可用时为 None
+    pub duration_ms: Option<u64>,
+    /// 所在消息在会话文件中的序号（Claude / Codex 为行号，Gemini 为消息下标，从 0 开始）
+    pub message_index: usize,
+    /// 所在消息的 ID（Claude 的 uuid、Gemini 的消息 id；Codex 事件没有 ID）
+    pub message_id: Option<String>,
+}
+
+/// 按调用 ID 配对工具调用与结果
+#[derive(Debug, Default)]
+struct ToolTimingTracker {
+    calls: Vec<ToolCallTiming>,
+    index: HashMap<String, usize>,
+}
+
+impl ToolTimingTracker {
+    fn on_call(
+        &mut self,
+        call_id: &str,
+        tool_name: &str,
+        input: &Value,
+        timestamp: Option<&str>,
+        message_index: usize,
+        message_id: Option<&str>,
+    ) {
+        if call_id.is_empty() || self.index.contains_key(call_id) {
+            return;
+        }
+        self.index.insert(call_id.to_string(), self.calls.len());
+        self.calls.push(ToolCallTiming {
+            call_id: call_id.to_string(),
+            tool_name: tool_name.to_string(),
+            input_summary: summarize_input(input),
+            started_at: timestamp.map(str::to_string),
+            duration_ms: None,
+            message_index,
+            message_id: message_id.map(str::to_string),
+        });
+    }
+
+    /// 记录调用结果，耗时按调用与结果的时间戳计算；只取第一个结果
+    fn on_result(&mut self, call_id: &str, timestamp: Option<&str>) {
+        let Some(call) = self.index.get(call_id).map(|&i| &mut self.calls[i]) else {
+            return;
+        };
+        if call.duration_ms.is_none() {
+            call.duration_ms = call
+                .started_at
+                .as_deref()
+                .zip(timestamp)
+                .and_then(|(start, end)| elapsed_ms(start, end));
+        }
+    }
+
+    /// 记录 CLI 自带的耗时，优先于时间戳推算的耗时
+    fn on_duration(&mut self, call_id: &str, duration_ms: Option<u64>) {
+        if let (Some(&i), Some(duration_ms)) = (self.index.get(call_id), duration_ms) {
+            self.calls[i].duration_ms = Some(duration_ms);
+        }
+    }
+
+    fn durations(&self) -> HashMap<String, u64> {
+        self.calls
+            .iter()
+            .filter_map(|c| c.duration_ms.map(|d| (c.call_id.clone(), d)))
+            .collect()
+    }
+
+    fn into_calls(self) -> Vec<ToolCallTiming> {
+        self.calls
+    }
+}
+
+/// 生成参数摘要：优先取命令、路径等关键字段，否则取紧凑 JSON，超长截断
+fn summarize_input(input: &Value) -> String {
+    let summary = SUMMARY_KEYS
+        .iter()
+        .find_map(|key| match input.get(*key) {
+            Some(Value::String(s)) if !s.trim().is_empty() => Some(s.trim().to_string()),
+            // Codex 的 shell 命令是参数数组
+            Some(Value::Array(parts)) if !parts.is_empty() => Some(
+                parts
+                    .iter()
+                    .map(|p| p.as_str().map_or_else(|| p.to_string(), str::to_string))
+                    .collect::<Vec<_>>()
+                    .join(" "),
+            ),
+            _ => None,
+        })
+        .unwrap_or_else(|| match input {
+            Value::Null => String::new(),
+            Value::String(s) => s.trim().to_string(),
+            other => other.to_string(),
+        });
+
+    let summary = summary.split_whitespace().collect::<Vec<_>>().join(" ");
+    truncate_chars(&summary, INPUT_SUMMARY_MAX_CHARS)
+}
+
+// ============================================================================
+// Per-engine event tracking
+// ============================================================================
+
+fn content_blocks(message: &Value) -> &[Value] {
+    message["message"]["content"]
+        .as_array()
+        .map(Vec::as_slice)
+        .unwrap_or_default()
+}
+
+/// 记录一条 Claude 消息中的 tool_use / tool_result
+fn track_claude_message(tracker: &mut ToolTimingTracker, index: usize, message: &Value) {
+    let timestamp = message["timestamp"].as_str();
+    for block in content_blocks(message) {
+        match block["type"].as_str() {
+            Some("tool_use") => tracker.on_call(
+                block["id"].as_str().unwrap_or_default(),
+                block["name"].as_str().unwrap_or_default(),
+                &block["input"],
+                timestamp,
+                index,
+                message["uuid"].as_str(),
+            ),
+            Some("tool_result") => {
+                tracker.on_result(block["tool_use_id"].as_str().unwrap_or_default(), timestamp)
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Codex 工具调用事件的参数（function_call 的 arguments 是 JSON 字符串）
+fn codex_call_input(payload: &Value) -> Value {
+    match payload["type"].as_str() {
+        Some("function_call") => payload["arguments"]
+            .as_str()
+            .and_then(|args| serde_json::from_str(args).ok())
+            .unwrap_or_else(|| payload["arguments"].clone()),
+        Some("local_shell_call") => payload["action"].clone(),
+        _ => payload["input"].clone(),
+    }
+}
+
+/// 是否为 Codex 的工具调用事件
+fn is_codex_call(payload: &Value) -> bool {
+    matches!(
+        payload["type"].as_str(),
+        Some("function_call" | "custom_tool_call" | "local_shell_call")
+    )
+}
+
+/// 记录一条 Codex 事件中的工具调用 / 结果
+fn track_codex_event(tracker: &mut ToolTimingTracker, index: usize, event: &Value) {
+    let timestamp = event["timestamp"].as_str();
+    let payload = &event["payload"];
+    let Some(call_id) = payload["call_id"].as_str() else {
+        return;
+    };
+
+    match (event["type"].as_str(), payload["type"].as_str()) {
+        (Some("response_item"), _) if is_codex_call(payload) => {
+            let name = payload["name"].as_str().unwrap_or("shell");
+            tracker.on_call(
+                call_id,
+                name,
+                &codex_call_input(payload),
+                timestamp,
+                index,
+                None,
+            );
+        }
+        (
+            Some("response_item"),
+            Some("function_call_output" | "custom_tool_call_output" | "local_shell_call_output"),
+        ) => tracker.on_result(call_id, timestamp),
+        (Some("event_msg"), Some("exec_command_end" | "mcp_tool_call_end")) => {
+            tracker.on_duration(call_id, codex_duration_ms(&payload["duration"]));
+        }
+        _ => {}
+    }
+}
+
+/// 记录 Gemini 会话文件中的工具调用：调用时间取所在消息的时间，结束时间取 toolCall 的记录时间
+fn track_gemini_detail(tracker: &mut ToolTimingTracker, detail: &GeminiSessionDetail) {
+    for (index, message) in detail.messages.iter().enumerate() {
+        let Some(tool_calls) = message["toolCalls"].as_array() else {
+            continue;
+        };
+        for call in tool_calls {
+            let call_id = call["id"].as_str().unwrap_or_default();
+            tracker.on_call(
+                call_id,
+                call["name"].as_str().unwrap_or_default(),
+                &call["args"],
+                message["timestamp"].as_str(),
+                index,
+                message["id"].as_str(),
+            );
+            tracker.on_result(call_id, call["timestamp"].as_str());
+        }
+    }
+}
+
+// ============================================================================
+// History annotation
+// ============================================================================
+
+/// 为 Claude 历史消息中的 tool_use 块附上 `duration_ms`
+pub fn annotate_claude_history(messages: &mut [Value]) {
+    let mut tracker = ToolTimingTracker::default();
+    for (index, message) in messages.iter().enumerate() {
+        track_claude_message(&mut tracker, index, message);
+    }
+    let durations = tracker.durations();
+    if durations.is_empty() {
+        return;
+    }
+
+    for message in messages.iter_mut() {
+        let Some(content) = message["message"]["content"].as_array_mut() else {
+            continue;
+        };
+        for block in content.iter_mut() {
+            if block["type"] != "tool_use" {
+                continue;
+            }
+            if let Some(duration) = block["id"].as_str().and_then(|id| durations.get(id)) {
+                block[DURATION_FIELD] = Value::from(*duration);
+            }
+        }
+    }
+}
+
+/// 为 Codex 历史事件中的工具调用 payload 附上 `duration_ms`
+pub fn annotate_codex_history(events: &mut [Value]) {
+    let mut tracker = ToolTimingTracker::default();
+    for (index, event) in events.iter().enumerate() {
+        track_codex_event(&mut tracker, index, event);
+    }
+    let durations = tracker.durations();
+    if durations.is_empty() {
+        return;
+    }
+
+    for event in events.iter_mut() {
+        let payload = &mut event["payload"];
+        if !is_codex_call(payload) {
+            continue;
+        }
+        if let Some(duration) = payload["call_id"].as_str().and_then(|id| durations.get(id)) {
+            payload[DURATION_FIELD] = Value::from(*duration);
+        }
+    }
+}
+
+/// 为 Gemini 会话详情中的 toolCalls 附上 `duration_ms`
+pub fn annotate_gemini_detail(detail: &mut GeminiSessionDetail) {
+    let mut tracker = ToolTimingTracker::default();
+    track_gemini_detail(&mut tracker, detail);
+    let durations = tracker.durations();
+    if durations.is_empty() {
+        return;
+    }
+
+    for message in detail.messages.iter_mut() {
+        let Some(tool_calls) = message["toolCalls"].as_array_mut() else {
+            continue;
+        };
+        for call in tool_calls.iter_mut() {
+            if let Some(duration) = call["id"].as_str().and_then(|id| durations.get(id)) {
+                call[DURATION_FIELD] = Value::from(*duration);
+            }
+        }
+    }
+}
+
+// ============================================================================
+// Live timing
+// ============================================================================
+
+/// 流式执行时的工具计时：开始事件处记下时刻，结束事件里带上 `duration_ms`
+#[derive(Debug, Default)]
+pub struct LiveToolTimer {
+    started: HashMap<String, Instant>,
+}
+
+impl LiveToolTimer {
+    fn start(&mut self, id: &str) {
+        if !id.is_empty() {
+            self.started
+                .entry(id.to_string())
+                .or_insert_with(Instant::now);
+        }
+    }
+
+    fn finish(&mut self, id: &str) -> Option<u64> {
+        let started = self.started.remove(id)?;
+        u64::try_from(started.elapsed().as_millis()).ok()
+    }
+
+    /// 处理 Claude 格式的消息（Claude 输出与 Gemini 统一消息）：tool_use 开始计时，
+    /// tool_result 块写入耗时。返回消息是否被修改
+    pub fn observe_claude_message(&mut self, message: &mut Value) -> bool {
+        let Some(content) = message["message"]["content"].as_array_mut() else {
+            return false;
+        };
+
+        let mut changed = false;
+        for block in content.iter_mut() {
+            match block["type"].as_str() {
+                Some("tool_use") => self.start(block["id"].as_str().unwrap_or_default()),
+                Some("tool_result") => {
+                    let id = block["tool_use_id"]
+                        .as_str()
+                        .unwrap_or_default()
+                        .to_string();
+                    if let Some(duration) = self.finish(&id) {
+                        block[DURATION_FIELD] = Value::from(duration);
+                        changed = true;
+                    }
+                }
+                _ => {}
+            }
+        }
+        changed
+    }
+
+    /// 处理 `codex exec --json` 事件：item.started 开始计时，item.completed 的 item 写入耗时。
+    /// 返回事件是否被修改
+    pub fn observe_codex_event(&mut self, event: &mut Value) -> bool {
+        let id = event["item"]["id"].as_str().unwrap_or_default().to_string();
+        match event["type"].as_str() {
+            Some("item.started") => {
+                self.start(&id);
+                false
+            }
+            Some("item.completed") => match self.finish(&id) {
+                Some(duration) => {
+                    event["item"][DURATION_FIELD] = Value::from(duration);
+                    true
+                }
+                None => false,
+            },
+            _ => false,
+        }
+    }
+}
+
+// ============================================================================
+// Slow operations
+// ============================================================================
+
+fn collect_claude_tool_calls(session_path: &Path) -> Result<Vec<ToolCallTiming>, String> {
+    let mut tracker = ToolTimingTracker::default();
+    for_each_line(session_path, |index, line| {
+        if let Ok(message) = serde_json::from_str::<Value>(line) {
+            track_claude_message(&mut tracker, index, &message);
+        }
+        ControlFlow::Continue(())
+    })
+    .map_err(|e| format!("Failed to read session file: {}", e))?;
+    Ok(tracker.into_calls())
+}
+
+fn collect_codex_tool_calls(session_path: &Path) -> Result<Vec<ToolCallTiming>, String> {
+    let mut tracker = ToolTimingTracker::default();
+    for_each_line(session_path, |index, line| {
+        if let Ok(event) = serde_json::from_str::<Value>(line) {
+            track_codex_event(&mut tracker, index, &event);
+        }
+        ControlFlow::Continue(())
+    })
+    .map_err(|e| format!("Failed to read session file: {}", e))?;
+    Ok(tracker.into_calls())
+}
+
+fn collect_gemini_tool_calls(session_path: &Path) -> Result<Vec<ToolCallTiming>, String> {
+    let content = std::fs::read_to_string(session_path)
+        .map_err(|e| format!("Failed to read session file: {}", e))?;
+    let detail: GeminiSessionDetail = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse session file: {}", e))?;
+
+    let mut tracker = ToolTimingTracker::default();
+    track_gemini_detail(&mut tracker, &detail);
+    Ok(tracker.into_calls())
+}
+
+/// 取耗时不低于阈值的调用，按耗时从长到短排序
+fn slow_operations(calls: Vec<ToolCallTiming>, threshold_ms: u64) -> Vec<ToolCallTiming> {
+    let mut slow: Vec<ToolCallTiming> = calls
+        .into_iter()
+        .filter(|c| c.duration_ms.is_some_and(|d| d >= threshold_ms))
+        .collect();
+    slow.sort_by_key(|c| std::cmp::Reverse(c.duration_ms));
+    slow
+}
+
+/// 列出会话中耗时超过阈值的工具调用
+///
+/// `project_id` 的含义与 [`resolve_session_file`] 相同（Claude 为项目目录名，Gemini 为项目路径，
+/// Codex 可传空字符串）
+#[tauri::command]
+pub async fn get_session_slow_operations(
+    engine: String,
+    session_id: String,
+    project_id: String,
+    threshold_ms: u64,
+) -> Result<Vec<ToolCallTiming>, String> {
+    tokio::task::spawn_blocking(move || {
+        let path = resolve_session_file(&engine, &session_id, &project_id)?;
+        let calls = match engine.as_str() {
+            "claude" => collect_claude_tool_calls(&path)?,
+            "codex" => collect_codex_tool_calls(&path)?,
+            "gemini" => collect_gemini_tool_calls(&path)?,
+            other => return Err(format!("Unknown engine: {}", other)),
+        };
+        let slow = slow_operations(calls, threshold_ms);
+        log::info!(
+            "[ToolTiming] Found {} tool calls over {}ms in {} session {}",
+            slow.len(),
+            threshold_ms,
+            engine,
+            session_id
+        );
+        Ok(slow)
+    })
+    .await
+    .map_err(|e| format!("Slow operations task failed: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::fs;
+
+    #[test]
+    fn claude_history_gets_durations_and_slow_calls_are_listed() {
+        let mut messages = vec![
+            json!({"type":"assistant","uuid":"m1","timestamp":"2025-01-01T00:00:00.000Z","message":{"role":"assistant","content":[
+                {"type":"tool_use","id":"t1","name":"Bash","input":{"command":"cargo build --release"}},
+                {"type":"tool_use","id":"t2","name":"Read","input":{"file_path":"src/main.rs"}},
+                {"type":"tool_use","id":"t3","name":"Grep","input":{"pattern":"x"}}
+            ]}}),
+            json!({"type":"user","timestamp":"2025-01-01T00:05:00.000Z","message":{"role":"user","content":[
+                {"type":"tool_result","tool_use_id":"t1","content":"ok"}
+            ]}}),
+            // 没有时间戳的结果
+            json!({"type":"user","message":{"role":"user","content":[
+                {"type":"tool_result","tool_use_id":"t2","content":"ok"}
+            ]}}),
+            // 结果早于调用（乱序）
+            json!({"type":"user","timestamp":"2024-12-31T23:59:59.000Z","message":{"role":"user","content":[
+                {"type":"tool_result","tool_use_id":"t3","content":"ok"}
+            ]}}),
+        ];
+
+        let mut tracker = ToolTimingTracker::default();
+        for (index, message) in messages.iter().enumerate() {
+            track_claude_message(&mut tracker, index, message);
+        }
+        let slow = slow_operations(tracker.into_calls(), 60_000);
+        assert_eq!(slow.len(), 1);
+        assert_eq!(slow[0].tool_name, "Bash");
+        assert_eq!(slow[0].input_summary, "cargo build --release");
+        assert_eq!(slow[0].duration_ms, Some(300_000));
+        assert_eq!(slow[0].message_id.as_deref(), Some("m1"));
+
+        annotate_claude_history(&mut messages);
+        let blocks = messages[0]["message"]["content"].as_array().unwrap();
+        assert_eq!(blocks[0][DURATION_FIELD], json!(300_000));
+        assert!(blocks[1].get(DURATION_FIELD).is_none());
+        assert!(blocks[2].get(DURATION_FIELD).is_none());
+    }
+
+    #[test]
+    fn codex_calls_prefer_exec_durations() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("rollout.jsonl");
+        let lines = [
+            r#"{"type":"response_item","timestamp":"2025-01-01T00:00:00.000Z","payload":{"type":"function_call","name":"shell","arguments":"{\"command\":[\"bash\",\"-lc\",\"npm test\"]}","call_id":"c1"}}"#,
+            r#"{"type":"event_msg","timestamp":"2025-01-01T00:04:59.000Z","payload":{"type":"exec_command_end","call_id":"c1","exit_code":0,"duration":{"secs":298,"nanos":500000000}}}"#,
+            r#"{"type":"response_item","timestamp":"2025-01-01T00:05:00.000Z","payload":{"type":"function_call_output","call_id":"c1","output":"ok"}}"#,
+            r#"{"type":"response_item","timestamp":"2025-01-01T00:05:01.000Z","payload":{"type":"custom_tool_call","name":"apply_patch","input":"*** Begin Patch","call_id":"c2"}}"#,
+            r#"{"type":"response_item","timestamp":"2025-01-01T00:05:01.250Z","payload":{"type":"custom_tool_call_output","call_id":"c2","output":"ok"}}"#,
+        ];
+        fs::write(&path, lines.join("\n")).unwrap();
+
+        let calls = collect_codex_tool_calls(&path).unwrap();
+        assert_eq!(calls.len(), 2);
+        assert_eq!(calls[0].input_summary, "bash -lc npm test");
+        assert_eq!(calls[0].duration_ms, Some(298_500));
+        assert_eq!(calls[0].message_index, 0);
+        assert_eq!(calls[1].duration_ms, Some(250));
+
+        let mut events: Vec<Value> = lines
+            .iter()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        annotate_codex_history(&mut events);
+        assert_eq!(events[0]["payload"][DURATION_FIELD], json!(298_500));
+        assert_eq!(events[3]["payload"][DURATION_FIELD], json!(250));
+        assert!(events[2]["payload"].get(DURATION_FIELD).is_none());
+    }
+
+    #[test]
+    fn live_timer_annotates_end_events() {
+        let mut timer = LiveToolTimer::default();
+
+        let mut call = json!({"type":"assistant","message":{"content":[{"type":"tool_use","id":"t1","name":"Bash","input":{}}]}});
+        assert!(!timer.observe_claude_message(&mut call));
+        let mut result = json!({"type":"user","message":{"content":[{"type":"tool_result","tool_use_id":"t1","content":"ok"}]}});
+        assert!(timer.observe_claude_message(&mut result));
+        assert!(result["message"]["content"][0][DURATION_FIELD].is_u64());
+        // 未计时的结果不修改
+        let mut orphan = json!({"type":"user","message":{"content":[{"type":"tool_result","tool_use_id":"t9","content":"ok"}]}});
+        assert!(!timer.observe_claude_message(&mut orphan));
+
+        let mut started =
+            json!({"type":"item.started","item":{"id":"item_1","type":"command_execution"}});
+        assert!(!timer.observe_codex_event(&mut started));
+        let mut completed =
+            json!({"type":"item.completed","item":{"id":"item_1","type":"command_execution"}});
+        assert!(timer.observe_codex_event(&mut completed));
+        assert!(completed["item"][DURATION_FIELD].is_u64());
+    }
+}
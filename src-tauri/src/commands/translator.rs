@@ -9,6 +9,7 @@ use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tokio::sync::Mutex;
 
+use super::rate_limiter;
 use super::url_utils::{normalize_api_url, ApiEndpointType};
 
 /// 翻译配置
@@ -64,6 +65,9 @@ impl CacheEntry {
 }
 
 /// 翻译服务
+///
+/// 克隆后共享同一个 HTTP 客户端与缓存，便于并发翻译时不长时间持有全局锁
+#[derive(Clone)]
 pub struct TranslationService {
     config: TranslationConfig,
     client: Client,
@@ -256,15 +260,16 @@ impl TranslationService {
         let api_url = normalize_api_url(&self.config.api_base_url, ApiEndpointType::OpenAI);
         debug!("Using normalized API URL: {}", api_url);
 
-        let response = self
+        let request = self
             .client
             .post(&api_url)
             .header("Authorization", format!("Bearer {}", self.config.api_key))
             .header("Content-Type", "application/json")
-            .json(&request_body)
-            .send()
+            .json(&request_body);
+        // 经 provider 限流器发送，并发翻译会被平滑排队
+        let response = rate_limiter::send(&self.config.api_base_url, request)
             .await
-            .context("Failed to send translation request")?;
+            .map_err(|e| anyhow::anyhow!("Failed to send translation request: {}", e))?;
 
         if !response.status().is_success() {
             let status = response.status();
@@ -457,7 +462,7 @@ fn get_translation_service() -> Arc<Mutex<TranslationService>> {
 /// 翻译文本（公共接口）
 pub async fn translate_text(text: &str, target_lang: Option<&str>) -> Result<String> {
     let service_arc = get_translation_service();
-    let service = service_arc.lock().await;
+    let service = service_arc.lock().await.clone();
     service.translate(text, target_lang).await
 }
 
@@ -478,7 +483,7 @@ pub async fn translate_batch(
     target_lang: Option<String>,
 ) -> Result<Vec<String>, String> {
     let service_arc = get_translation_service();
-    let service = service_arc.lock().await;
+    let service = service_arc.lock().await.clone();
     let target = target_lang.as_deref();
 
     service
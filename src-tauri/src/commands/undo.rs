@@ -0,0 +1,211 @@
+//! 全局操作撤销栈
+//!
+//! 回收站与备份覆盖了会话、项目文件等大操作；记忆条目、规则、模板这类小操作误删后
+//! 由这里兜底：
+//! - 可逆操作执行成功后调用 `record`，把反向操作所需的数据序列化压入撤销栈
+//! - 每类操作实现 `Undoable`（类型标识、人类可读描述、反向操作），并在 `run_undo` 中登记
+//! - 栈只保留最近 20 条，持久化到 ~/.anycode/undo_stack.json，跨应用重启保留
+//! - `undo_last_operation` 执行栈顶的反向操作；反向操作失败时该条目同样出栈，
+//!   避免一条无法撤销的记录挡住更早的记录
+
+use once_cell::sync::Lazy;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use super::claude::ProjectRemoval;
+use super::codex::profiles::CodexProfileDeletion;
+use super::extensions::{SlashCommandDeletion, TemplateDeletion};
+use super::project_memory::MemoryEntryDeletion;
+use super::task_actions::TaskActionRuleDeletion;
+use crate::utils::config_utils::{load_json_config, save_json_config, ConfigPathBuilder};
+
+/// 撤销栈保留的最大条目数
+const MAX_UNDO_ENTRIES: usize = 20;
+
+/// 内存中的撤销栈，首次访问时从磁盘加载
+static STACK: Lazy<Mutex<Option<UndoStack>>> = Lazy::new(|| Mutex::new(None));
+
+/// 一类可撤销操作
+pub trait Undoable: Serialize + DeserializeOwned {
+    /// 操作类型标识，持久化后据此分派反向操作
+    const KIND: &'static str;
+
+    /// 人类可读描述，例如"删除模板 reviewer"
+    fn description(&self) -> String;
+
+    /// 执行反向操作
+    fn undo(self) -> Result<(), String>;
+}
+
+/// 栈中的一条记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct UndoEntry {
+    id: String,
+    kind: String,
+    description: String,
+    /// 记录时间（UTC Unix 秒）
+    created_at: i64,
+    /// 反向操作所需的数据
+    payload: Value,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct UndoStack {
+    #[serde(default)]
+    entries: Vec<UndoEntry>,
+}
+
+/// 提供给前端展示的撤销记录（最新的在前）
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UndoOperation {
+    pub id: String,
+    pub kind: String,
+    pub description: String,
+    pub created_at: i64,
+}
+
+fn get_stack_path() -> Result<PathBuf, String> {
+    Ok(ConfigPathBuilder::from_home_subdir(".anycode")?.build("undo_stack.json"))
+}
+
+/// 在已加载的撤销栈上执行操作，修改后持久化
+fn with_stack<R>(f: impl FnOnce(&mut UndoStack) -> R) -> Result<R, String> {
+    let mut guard = STACK.lock().unwrap();
+    if guard.is_none() {
+        let loaded: UndoStack = load_json_config(get_stack_path()?).unwrap_or_else(|e| {
+            log::warn!("[Undo] Failed to load undo stack, starting empty: {}", e);
+            UndoStack::default()
+        });
+        *guard = Some(loaded);
+    }
+    let stack = guard.as_mut().expect("undo stack loaded above");
+    let result = f(stack);
+    save_json_config(stack, &get_stack_path()?)?;
+    Ok(result)
+}
+
+fn push_entry(stack: &mut UndoStack, entry: UndoEntry) {
+    stack.entries.push(entry);
+    if stack.entries.len() > MAX_UNDO_ENTRIES {
+        let overflow = stack.entries.len() - MAX_UNDO_ENTRIES;
+        stack.entries.drain(..overflow);
+    }
+}
+
+/// 记录一次已成功执行的可逆操作
+///
+/// 撤销栈只是兜底，记录失败不影响原操作，仅记日志
+pub fn record<T: Undoable>(operation: &T) {
+    let payload = match serde_json::to_value(operation) {
+        Ok(payload) => payload,
+        Err(e) => {
+            log::warn!("[Undo] Failed to serialize {} operation: {}", T::KIND, e);
+            return;
+        }
+    };
+    let entry = UndoEntry {
+        id: uuid::Uuid::new_v4().to_string(),
+        kind: T::KIND.to_string(),
+        description: operation.description(),
+        created_at: chrono::Utc::now().timestamp(),
+        payload,
+    };
+    log::debug!("[Undo] Recorded: {}", entry.description);
+    if let Err(e) = with_stack(|stack| push_entry(stack, entry)) {
+        log::warn!("[Undo] Failed to persist undo stack: {}", e);
+    }
+}
+
+fn run<T: Undoable>(payload: Value) -> Result<(), String> {
+    serde_json::from_value::<T>(payload)
+        .map_err(|e| format!("Invalid undo data for {}: {}", T::KIND, e))?
+        .undo()
+}
+
+/// 按类型标识分派反向操作；新增可撤销操作时在此登记
+fn run_undo(entry: UndoEntry) -> Result<(), String> {
+    match entry.kind.as_str() {
+        MemoryEntryDeletion::KIND => run::<MemoryEntryDeletion>(entry.payload),
+        TaskActionRuleDeletion::KIND => run::<TaskActionRuleDeletion>(entry.payload),
+        ProjectRemoval::KIND => run::<ProjectRemoval>(entry.payload),
+        CodexProfileDeletion::KIND => run::<CodexProfileDeletion>(entry.payload),
+        TemplateDeletion::KIND => run::<TemplateDeletion>(entry.payload),
+        SlashCommandDeletion::KIND => run::<SlashCommandDeletion>(entry.payload),
+        other => Err(format!("Unknown undo operation: {}", other)),
+    }
+}
+
+/// 列出撤销栈（最新的在前）
+#[tauri::command]
+pub async fn get_undo_stack() -> Result<Vec<UndoOperation>, String> {
+    with_stack(|stack| {
+        stack
+            .entries
+            .iter()
+            .rev()
+            .map(|entry| UndoOperation {
+                id: entry.id.clone(),
+                kind: entry.kind.clone(),
+                description: entry.description.clone(),
+                created_at: entry.created_at,
+            })
+            .collect()
+    })
+}
+
+/// 撤销最近一次操作，返回被撤销操作的描述
+#[tauri::command]
+pub async fn undo_last_operation() -> Result<String, String> {
+    let entry =
+        with_stack(|stack| stack.entries.pop())?.ok_or_else(|| "Nothing to undo".to_string())?;
+    let description = entry.description.clone();
+
+    match run_undo(entry) {
+        Ok(()) => {
+            log::info!("[Undo] Undone: {}", description);
+            Ok(description)
+        }
+        Err(e) => {
+            log::warn!("[Undo] Failed to undo '{}': {}", description, e);
+            Err(format!("撤销「{}」失败：{}", description, e))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(n: usize) -> UndoEntry {
+        UndoEntry {
+            id: n.to_string(),
+            kind: "test".to_string(),
+            description: format!("op {}", n),
+            created_at: n as i64,
+            payload: Value::Null,
+        }
+    }
+
+    #[test]
+    fn keeps_only_the_most_recent_entries() {
+        let mut stack = UndoStack::default();
+        for n in 0..25 {
+            push_entry(&mut stack, entry(n));
+        }
+
+        assert_eq!(stack.entries.len(), MAX_UNDO_ENTRIES);
+        assert_eq!(stack.entries.first().unwrap().id, "5");
+        assert_eq!(stack.entries.last().unwrap().id, "24");
+    }
+
+    #[test]
+    fn unknown_kind_is_rejected() {
+        let err = run_undo(entry(1)).unwrap_err();
+        assert!(err.contains("test"));
+    }
+}
@@ -0,0 +1,801 @@
+//! 提示词中外部 URL 的预抓取
+//!
+//! Codex exec 的非交互模式不一定会联网，Gemini 有时也会拒绝抓取网页。
+//! 发送前由应用抓取提示词里的链接，把正文附加到提示词末尾（带来源 URL 标注）：
+//! - 只允许 http/https，可配置域名白名单（为空时不限制），重定向同样受白名单约束
+//! - 单个请求超时 10 秒、响应体最大 2MB
+//! - HTML 剥离脚本与样式，优先取 main/article 主内容区，转成简单的 Markdown 文本
+//! - 登录墙、反爬虫验证页面会被识别为失败，不附加到提示词
+//! - 所有页面共享 token 预算，按页面大小分配，超出部分截断
+//!
+//! 可独立调用 `fetch_url_context`，也可在执行选项的 `enhancement.fetch_urls` 中开启，
+//! 后者会发送 `url-context-fetched` 事件（stream_id 为执行的 trace id）反馈抓取结果。
+//!
+//! 持久化：~/.anycode/url_fetch.json
+
+use once_cell::sync::Lazy;
+use regex::{Captures, Regex};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::time::Duration;
+
+use super::project_memory::estimate_tokens;
+use crate::utils::config_utils::{load_json_config, save_json_config, ConfigPathBuilder};
+
+/// 单个请求的超时
+const FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 响应体大小上限
+const MAX_BODY_BYTES: usize = 2 * 1024 * 1024;
+
+/// 单次最多抓取的 URL 数
+const MAX_URLS: usize = 5;
+
+/// 最多跟随的重定向次数
+const MAX_REDIRECTS: usize = 5;
+
+/// 正文短于该长度时才按登录墙特征判断，避免误伤提到“登录”的普通文档
+const LOGIN_WALL_MAX_CHARS: usize = 1500;
+
+/// 重定向后的路径中出现这些片段视为登录页
+const LOGIN_PATH_SEGMENTS: &[&str] = &[
+    "login",
+    "signin",
+    "sign-in",
+    "sign_in",
+    "sso",
+    "oauth",
+    "auth",
+    "authorize",
+    "servicelogin",
+];
+
+/// 登录墙正文特征
+const LOGIN_WALL_PATTERNS: &[&str] = &[
+    "sign in", "log in", "login", "password", "登录", "登入", "密码",
+];
+
+/// 反爬虫 / 人机验证页面特征
+const BOT_CHECK_PATTERNS: &[&str] = &[
+    "just a moment",
+    "attention required",
+    "verify you are human",
+    "are you a robot",
+    "captcha",
+    "enable javascript and cookies",
+    "access denied",
+    "人机验证",
+    "安全验证",
+];
+
+/// 提示词中的 URL
+static URL_PATTERN: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"https?://[^\s<>"'`\)\]\}，。；！？、（）《》【】]+"#).unwrap());
+
+static TITLE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?is)<title\b[^>]*>(.*?)</title\s*>").unwrap());
+
+static COMMENT: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?s)<!--.*?-->").unwrap());
+
+/// 整块丢弃的元素（regex 不支持反向引用，按标签分别构造）
+static NOISE_BLOCKS: Lazy<Vec<Regex>> = Lazy::new(|| {
+    [
+        "script", "style", "noscript", "svg", "template", "iframe", "head",
+    ]
+    .iter()
+    .map(|tag| element_regex(tag))
+    .collect()
+});
+
+/// 主内容区之外的页面框架
+static LAYOUT_BLOCKS: Lazy<Vec<Regex>> = Lazy::new(|| {
+    ["nav", "header", "footer", "aside", "form", "button"]
+        .iter()
+        .map(|tag| element_regex(tag))
+        .collect()
+});
+
+static MAIN_CONTENT: Lazy<Vec<Regex>> = Lazy::new(|| {
+    ["main", "article"]
+        .iter()
+        .map(|tag| Regex::new(&format!(r"(?is)<{0}\b[^>]*>(.*?)</{0}\s*>", tag)).unwrap())
+        .collect()
+});
+
+static BODY: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?is)<body\b[^>]*>(.*)</body\s*>").unwrap());
+
+static PRE: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?is)<pre\b[^>]*>(.*?)</pre\s*>").unwrap());
+
+static HEADING: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?is)<h([1-6])\b[^>]*>(.*?)</h[1-6]\s*>").unwrap());
+
+static LIST_ITEM: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?i)<li\b[^>]*>").unwrap());
+
+static BLOCK_BREAK: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)<br\s*/?>|</?(?:p|div|section|tr|table|ul|ol|blockquote|dl|dt|dd)\b[^>]*>")
+        .unwrap()
+});
+
+static TAG: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?s)<[^>]+>").unwrap());
+
+static WHITESPACE: Lazy<Regex> = Lazy::new(|| Regex::new(r"\s+").unwrap());
+
+static ENTITY: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"&(#[0-9]{1,7}|#[xX][0-9a-fA-F]{1,6}|[a-zA-Z]{2,8});").unwrap());
+
+fn element_regex(tag: &str) -> Regex {
+    Regex::new(&format!(r"(?is)<{0}\b[^>]*>.*?</{0}\s*>", tag)).unwrap()
+}
+
+/// URL 抓取配置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct UrlFetchConfig {
+    /// 允许抓取的域名（含子域名），为空时不限制
+    pub allowed_domains: Vec<String>,
+    /// 附加到提示词的网页内容总 token 预算
+    pub token_budget: usize,
+}
+
+impl Default for UrlFetchConfig {
+    fn default() -> Self {
+        Self {
+            allowed_domains: Vec::new(),
+            token_budget: 4000,
+        }
+    }
+}
+
+/// 单次抓取的选项，未指定的项使用配置值
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct UrlContextOptions {
+    pub token_budget: Option<usize>,
+    pub allowed_domains: Option<Vec<String>>,
+}
+
+/// 抓取成功的页面
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FetchedUrl {
+    pub url: String,
+    /// 重定向后的最终地址
+    pub final_url: String,
+    pub title: Option<String>,
+    /// 附加到提示词的正文（已截断到预算内）
+    pub content: String,
+    pub tokens: usize,
+    pub truncated: bool,
+}
+
+/// 抓取失败的 URL 与原因
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FailedUrl {
+    pub url: String,
+    pub reason: String,
+}
+
+/// 抓取结果
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UrlContextResult {
+    pub fetched: Vec<FetchedUrl>,
+    pub failed: Vec<FailedUrl>,
+    /// 附加到提示词末尾的文本，没有成功抓取的页面时为空
+    pub context: String,
+    pub context_tokens: usize,
+}
+
+/// `url-context-fetched` 事件负载
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UrlContextFetched<'a> {
+    pub stream_id: &'a str,
+    pub fetched: Vec<&'a str>,
+    pub failed: &'a [FailedUrl],
+}
+
+/// 抓取前的页面正文
+struct PageText {
+    url: String,
+    final_url: String,
+    title: Option<String>,
+    text: String,
+}
+
+/// 获取 URL 抓取配置
+#[tauri::command]
+pub async fn get_url_fetch_config() -> Result<UrlFetchConfig, String> {
+    load_json_config(get_config_path()?)
+}
+
+/// 更新 URL 抓取配置
+#[tauri::command]
+pub async fn update_url_fetch_config(mut config: UrlFetchConfig) -> Result<(), String> {
+    config.allowed_domains = normalize_domains(&config.allowed_domains);
+    if config.token_budget == 0 {
+        return Err("token 预算必须大于 0".to_string());
+    }
+    save_json_config(&config, get_config_path()?)
+}
+
+/// 抓取 URL 并生成附加到提示词的上下文
+#[tauri::command]
+pub async fn fetch_url_context(
+    urls: Vec<String>,
+    options: Option<UrlContextOptions>,
+) -> Result<UrlContextResult, String> {
+    if urls.is_empty() {
+        return Err("没有需要抓取的 URL".to_string());
+    }
+    Ok(build_url_context(&urls, options.unwrap_or_default()).await)
+}
+
+/// 读取配置供执行路径使用，读取失败时使用默认值
+pub fn load_url_fetch_config() -> UrlFetchConfig {
+    get_config_path()
+        .and_then(load_json_config)
+        .unwrap_or_else(|e| {
+            log::warn!("[UrlContext] Failed to load url fetch config: {}", e);
+            UrlFetchConfig::default()
+        })
+}
+
+/// 提取文本中的 URL（去重、去掉末尾标点，最多 MAX_URLS 个）
+pub fn extract_urls(text: &str) -> Vec<String> {
+    let mut urls: Vec<String> = Vec::new();
+    for m in URL_PATTERN.find_iter(text) {
+        let url = m
+            .as_str()
+            .trim_end_matches(['.', ',', ';', ':', '!', '?', '\'']);
+        if !urls.iter().any(|u| u == url) {
+            urls.push(url.to_string());
+        }
+        if urls.len() >= MAX_URLS {
+            break;
+        }
+    }
+    urls
+}
+
+/// 抓取全部 URL，按预算截断后拼接上下文
+pub async fn build_url_context(urls: &[String], options: UrlContextOptions) -> UrlContextResult {
+    let config = load_url_fetch_config();
+    let allowed = normalize_domains(
+        options
+            .allowed_domains
+            .as_deref()
+            .unwrap_or(&config.allowed_domains),
+    );
+    let budget = options.token_budget.unwrap_or(config.token_budget);
+
+    let mut result = UrlContextResult::default();
+    let mut unique: Vec<&String> = Vec::new();
+    for url in urls {
+        if !unique.contains(&url) {
+            unique.push(url);
+        }
+    }
+    for url in unique.iter().skip(MAX_URLS) {
+        result.failed.push(FailedUrl {
+            url: url.to_string(),
+            reason: format!("单次最多抓取 {} 个 URL", MAX_URLS),
+        });
+    }
+
+    let client = match build_client(&allowed) {
+        Ok(client) => client,
+        Err(reason) => {
+            result
+                .failed
+                .extend(unique.iter().take(MAX_URLS).map(|url| FailedUrl {
+                    url: url.to_string(),
+                    reason: reason.clone(),
+                }));
+            return result;
+        }
+    };
+
+    let outcomes = futures::future::join_all(
+        unique
+            .iter()
+            .take(MAX_URLS)
+            .map(|url| fetch_page(&client, url, &allowed)),
+    )
+    .await;
+
+    let mut pages = Vec::new();
+    for (url, outcome) in unique.iter().zip(outcomes) {
+        match outcome {
+            Ok(page) => pages.push(page),
+            Err(reason) => {
+                log::info!("[UrlContext] Failed to fetch {}: {}", url, reason);
+                result.failed.push(FailedUrl {
+                    url: url.to_string(),
+                    reason,
+                });
+            }
+        }
+    }
+
+    let sizes: Vec<usize> = pages.iter().map(|p| estimate_tokens(&p.text)).collect();
+    let budgets = allocate_budget(&sizes, budget);
+    for (page, page_budget) in pages.into_iter().zip(budgets) {
+        let (content, truncated) = truncate_to_tokens(&page.text, page_budget);
+        result.fetched.push(FetchedUrl {
+            tokens: estimate_tokens(&content),
+            url: page.url,
+            final_url: page.final_url,
+            title: page.title,
+            content,
+            truncated,
+        });
+    }
+
+    result.context = format_context(&result.fetched);
+    result.context_tokens = estimate_tokens(&result.context);
+    result
+}
+
+fn build_client(allowed: &[String]) -> Result<reqwest::Client, String> {
+    let allowed_for_redirect = allowed.to_vec();
+    reqwest::Client::builder()
+        .timeout(FETCH_TIMEOUT)
+        .user_agent(concat!("Any-Code/", env!("CARGO_PKG_VERSION")))
+        .redirect(reqwest::redirect::Policy::custom(move |attempt| {
+            if attempt.previous().len() >= MAX_REDIRECTS {
+                attempt.error(format!("重定向超过 {} 次", MAX_REDIRECTS))
+            } else if let Err(reason) = check_url(attempt.url(), &allowed_for_redirect) {
+                attempt.error(reason)
+            } else {
+                attempt.follow()
+            }
+        }))
+        .build()
+        .map_err(|e| format!("创建 HTTP 客户端失败: {}", e))
+}
+
+/// 抓取单个页面并转成纯文本
+async fn fetch_page(
+    client: &reqwest::Client,
+    raw_url: &str,
+    allowed: &[String],
+) -> Result<PageText, String> {
+    let url = reqwest::Url::parse(raw_url).map_err(|e| format!("URL 无效: {}", e))?;
+    check_url(&url, allowed)?;
+
+    let mut response = client
+        .get(url)
+        .send()
+        .await
+        .map_err(describe_request_error)?;
+    let status = response.status();
+    let final_url = response.url().clone();
+    if let Some(reason) = describe_blocked_status(status.as_u16()) {
+        return Err(reason);
+    }
+    if is_login_url(&final_url) {
+        return Err("被重定向到登录页面，需要登录后才能查看".to_string());
+    }
+    if !status.is_success() {
+        return Err(format!("HTTP {}", status));
+    }
+    if response
+        .content_length()
+        .is_some_and(|len| len as usize > MAX_BODY_BYTES)
+    {
+        return Err(body_too_large());
+    }
+
+    let content_type = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("")
+        .to_ascii_lowercase();
+
+    let mut body = Vec::new();
+    while let Some(chunk) = response.chunk().await.map_err(describe_request_error)? {
+        if body.len() + chunk.len() > MAX_BODY_BYTES {
+            return Err(body_too_large());
+        }
+        body.extend_from_slice(&chunk);
+    }
+    let raw = String::from_utf8_lossy(&body);
+
+    let is_html = content_type.contains("html")
+        || (content_type.is_empty() && raw.trim_start().starts_with('<'));
+    let (title, text) = if is_html {
+        html_to_text(&raw)
+    } else if content_type.is_empty()
+        || content_type.starts_with("text/")
+        || content_type.contains("json")
+        || content_type.contains("xml")
+        || content_type.contains("markdown")
+    {
+        (None, raw.trim().to_string())
+    } else {
+        return Err(format!("不支持的内容类型: {}", content_type));
+    };
+
+    if let Some(reason) = detect_blocked_page(title.as_deref(), &text) {
+        return Err(reason);
+    }
+    if text.is_empty() {
+        return Err("页面没有可提取的文本内容（可能需要执行 JavaScript 渲染）".to_string());
+    }
+
+    Ok(PageText {
+        url: raw_url.to_string(),
+        final_url: final_url.to_string(),
+        title,
+        text,
+    })
+}
+
+/// 校验协议与域名白名单
+fn check_url(url: &reqwest::Url, allowed: &[String]) -> Result<(), String> {
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(format!("只允许 http/https 链接: {}", url.scheme()));
+    }
+    let host = url
+        .host_str()
+        .ok_or_else(|| "URL 缺少主机名".to_string())?
+        .to_ascii_lowercase();
+    if !allowed.is_empty()
+        && !allowed
+            .iter()
+            .any(|domain| host == *domain || host.ends_with(&format!(".{}", domain)))
+    {
+        return Err(format!("域名 {} 不在白名单中", host));
+    }
+    Ok(())
+}
+
+fn normalize_domains(domains: &[String]) -> Vec<String> {
+    let mut normalized: Vec<String> = Vec::new();
+    for domain in domains {
+        let domain = domain
+            .trim()
+            .trim_start_matches("*.")
+            .trim_matches('.')
+            .to_ascii_lowercase();
+        if !domain.is_empty() && !normalized.contains(&domain) {
+            normalized.push(domain);
+        }
+    }
+    normalized
+}
+
+fn describe_request_error(error: reqwest::Error) -> String {
+    if error.is_timeout() {
+        return format!("请求超时（{} 秒）", FETCH_TIMEOUT.as_secs());
+    }
+    // reqwest 的顶层错误只有"error sending request"，附上底层原因
+    let mut detail = error.to_string();
+    let mut source = std::error::Error::source(&error);
+    while let Some(cause) = source {
+        detail = format!("{}: {}", detail, cause);
+        source = cause.source();
+    }
+    if error.is_redirect() {
+        format!("重定向被拒绝: {}", detail)
+    } else if error.is_connect() {
+        format!("无法连接: {}", detail)
+    } else {
+        format!("请求失败: {}", detail)
+    }
+}
+
+fn describe_blocked_status(status: u16) -> Option<String> {
+    match status {
+        401 | 407 => Some(format!("需要登录或认证（HTTP {}）", status)),
+        403 => Some("无权访问，可能需要登录或被站点拦截（HTTP 403）".to_string()),
+        429 => Some("被目标站点限流（HTTP 429）".to_string()),
+        _ => None,
+    }
+}
+
+fn body_too_large() -> String {
+    format!("响应超过 {}MB 上限", MAX_BODY_BYTES / 1024 / 1024)
+}
+
+fn is_login_url(url: &reqwest::Url) -> bool {
+    url.path()
+        .to_ascii_lowercase()
+        .split('/')
+        .any(|segment| LOGIN_PATH_SEGMENTS.contains(&segment))
+}
+
+/// 识别登录墙与反爬虫验证页面
+fn detect_blocked_page(title: Option<&str>, text: &str) -> Option<String> {
+    let title = title.unwrap_or("").to_lowercase();
+    let head: String = text
+        .chars()
+        .take(LOGIN_WALL_MAX_CHARS)
+        .collect::<String>()
+        .to_lowercase();
+    let is_short = text.chars().count() <= LOGIN_WALL_MAX_CHARS;
+
+    if BOT_CHECK_PATTERNS
+        .iter()
+        .any(|p| title.contains(p) || (is_short && head.contains(p)))
+    {
+        return Some("遇到反爬虫或人机验证页面，无法获取正文".to_string());
+    }
+    if is_short
+        && LOGIN_WALL_PATTERNS
+            .iter()
+            .any(|p| title.contains(p) || head.contains(p))
+    {
+        return Some("页面内容是登录墙，需要登录后才能查看".to_string());
+    }
+    None
+}
+
+/// HTML 转成简单的 Markdown 文本，返回标题与正文
+fn html_to_text(html: &str) -> (Option<String>, String) {
+    let title = TITLE
+        .captures(html)
+        .map(|c| collapse_whitespace(&decode_entities(&strip_tags(&c[1]))))
+        .filter(|t| !t.is_empty());
+
+    let mut html = COMMENT.replace_all(html, "").into_owned();
+    for noise in NOISE_BLOCKS.iter() {
+        html = noise.replace_all(&html, "").into_owned();
+    }
+
+    // 主内容区：最长的 main/article，否则整个 body
+    let main = MAIN_CONTENT
+        .iter()
+        .flat_map(|re| re.captures_iter(&html).map(|c| c[1].to_string()))
+        .max_by_key(|segment| strip_tags(segment).trim().len())
+        .filter(|segment| !strip_tags(segment).trim().is_empty());
+    let mut content = match main {
+        Some(segment) => segment,
+        None => BODY
+            .captures(&html)
+            .map(|c| c[1].to_string())
+            .unwrap_or(html),
+    };
+    for layout in LAYOUT_BLOCKS.iter() {
+        content = layout.replace_all(&content, "").into_owned();
+    }
+
+    // 代码块先换成占位符，保留其中的换行与缩进
+    let mut code_blocks = Vec::new();
+    let content = PRE.replace_all(&content, |c: &Captures| {
+        code_blocks.push(decode_entities(&strip_tags(&c[1])));
+        format!("\u{0}{}\u{0}", code_blocks.len() - 1)
+    });
+
+    let content = WHITESPACE.replace_all(&content, " ");
+    let content = HEADING.replace_all(&content, |c: &Captures| {
+        let level: usize = c[1].parse().unwrap_or(1);
+        format!("\n\n{} {}\n\n", "#".repeat(level), strip_tags(&c[2]).trim())
+    });
+    let content = LIST_ITEM.replace_all(&content, "\n- ");
+    let content = BLOCK_BREAK.replace_all(&content, "\n");
+    let mut text = decode_entities(&strip_tags(&content));
+    for (i, code) in code_blocks.iter().enumerate() {
+        text = text.replace(
+            &format!("\u{0}{}\u{0}", i),
+            &format!("\n```\n{}\n```\n", code.trim_matches('\n')),
+        );
+    }
+
+    (title, tidy_lines(&text))
+}
+
+fn strip_tags(html: &str) -> String {
+    TAG.replace_all(html, "").into_owned()
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    WHITESPACE.replace_all(text, " ").trim().to_string()
+}
+
+fn decode_entities(text: &str) -> String {
+    ENTITY
+        .replace_all(text, |c: &Captures| {
+            let entity = &c[1];
+            let decoded = if let Some(hex) = entity
+                .strip_prefix("#x")
+                .or_else(|| entity.strip_prefix("#X"))
+            {
+                u32::from_str_radix(hex, 16).ok().and_then(char::from_u32)
+            } else if let Some(dec) = entity.strip_prefix('#') {
+                dec.parse().ok().and_then(char::from_u32)
+            } else {
+                match entity {
+                    "amp" => Some('&'),
+                    "lt" => Some('<'),
+                    "gt" => Some('>'),
+                    "quot" => Some('"'),
+                    "apos" => Some('\''),
+                    "nbsp" => Some(' '),
+                    "mdash" => Some('—'),
+                    "ndash" => Some('–'),
+                    "hellip" => Some('…'),
+                    "copy" => Some('©'),
+                    _ => None,
+                }
+            };
+            decoded
+                .map(String::from)
+                .unwrap_or_else(|| c[0].to_string())
+        })
+        .into_owned()
+}
+
+/// 去掉行尾空白，合并连续空行
+fn tidy_lines(text: &str) -> String {
+    let mut lines: Vec<&str> = Vec::new();
+    let mut in_code = false;
+    for line in text.lines() {
+        let line = if in_code {
+            line.trim_end()
+        } else {
+            line.trim()
+        };
+        if line.starts_with("```") {
+            in_code = !in_code;
+        }
+        if line.is_empty() && lines.last().is_none_or(|last| last.is_empty()) {
+            continue;
+        }
+        lines.push(line);
+    }
+    while lines.last().is_some_and(|last| last.is_empty()) {
+        lines.pop();
+    }
+    lines.join("\n")
+}
+
+/// 按页面大小分配预算：小页面用多少给多少，剩余的平均分给更大的页面
+fn allocate_budget(sizes: &[usize], budget: usize) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..sizes.len()).collect();
+    order.sort_by_key(|&i| sizes[i]);
+
+    let mut budgets = vec![0; sizes.len()];
+    let mut remaining = budget;
+    for (position, &i) in order.iter().enumerate() {
+        let share = remaining / (sizes.len() - position);
+        budgets[i] = sizes[i].min(share);
+        remaining -= budgets[i];
+    }
+    budgets
+}
+
+/// 截断到 token 预算内，尽量在段落或行尾处截断
+fn truncate_to_tokens(text: &str, budget: usize) -> (String, bool) {
+    if estimate_tokens(text) <= budget {
+        return (text.to_string(), false);
+    }
+
+    let mut ascii: usize = 0;
+    let mut other = 0;
+    let mut end = 0;
+    for (index, c) in text.char_indices() {
+        if c.is_ascii() {
+            ascii += 1;
+        } else {
+            other += 1;
+        }
+        if ascii.div_ceil(4) + other > budget {
+            break;
+        }
+        end = index + c.len_utf8();
+    }
+
+    let mut cut = &text[..end];
+    if let Some(newline) = cut.rfind('\n') {
+        if newline >= end * 4 / 5 {
+            cut = &cut[..newline];
+        }
+    }
+    (format!("{}\n…（已截断）", cut.trim_end()), true)
+}
+
+fn format_context(pages: &[FetchedUrl]) -> String {
+    if pages.is_empty() {
+        return String::new();
+    }
+    let mut context = String::from("\n\n--- 引用的网页内容（发送前由应用抓取） ---");
+    for (i, page) in pages.iter().enumerate() {
+        context.push_str(&format!("\n\n[{}] 来源: {}", i + 1, page.final_url));
+        if let Some(title) = &page.title {
+            context.push_str(&format!("\n标题: {}", title));
+        }
+        context.push_str("\n\n");
+        context.push_str(&page.content);
+    }
+    context
+}
+
+fn get_config_path() -> Result<PathBuf, String> {
+    Ok(ConfigPathBuilder::from_home_subdir(".anycode")?.build("url_fetch.json"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extracts_urls_without_trailing_punctuation() {
+        let prompt = "参考 https://docs.rs/regex/latest/regex/。还有 (https://example.com/a?b=1), \
+            以及 https://docs.rs/regex/latest/regex/ 和 ftp://example.com";
+
+        assert_eq!(
+            extract_urls(prompt),
+            vec![
+                "https://docs.rs/regex/latest/regex/".to_string(),
+                "https://example.com/a?b=1".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn checks_scheme_and_domain_allowlist() {
+        let allowed = normalize_domains(&["*.Example.com".to_string(), " ".to_string()]);
+        assert_eq!(allowed, vec!["example.com".to_string()]);
+
+        let ok = reqwest::Url::parse("https://docs.example.com/guide").unwrap();
+        let other = reqwest::Url::parse("https://evil.com/?example.com").unwrap();
+        let suffix = reqwest::Url::parse("https://notexample.com/").unwrap();
+        let file = reqwest::Url::parse("file:///etc/passwd").unwrap();
+
+        assert!(check_url(&ok, &allowed).is_ok());
+        assert!(check_url(&other, &allowed).is_err());
+        assert!(check_url(&suffix, &allowed).is_err());
+        assert!(check_url(&file, &[]).is_err());
+        assert!(check_url(&other, &[]).is_ok());
+    }
+
+    #[test]
+    fn converts_main_content_to_markdown() {
+        let html = r#"<html><head><title>Guide &amp; Tips</title><style>p{}</style></head>
+            <body><nav>Home | Docs</nav>
+            <main><h2>Install</h2><p>Run   the
+            command:</p><pre><code>cargo add  regex
+cargo build</code></pre><ul><li>fast</li><li>safe &lt;3</li></ul>
+            <script>alert(1)</script></main>
+            <footer>Copyright</footer></body></html>"#;
+
+        let (title, text) = html_to_text(html);
+        assert_eq!(title.as_deref(), Some("Guide & Tips"));
+        assert_eq!(
+            text,
+            "## Install\n\nRun the command:\n\n```\ncargo add  regex\ncargo build\n```\n\n- fast\n- safe <3"
+        );
+    }
+
+    #[test]
+    fn detects_login_walls_and_bot_checks() {
+        assert!(detect_blocked_page(Some("Just a moment..."), "Checking your browser").is_some());
+        assert!(detect_blocked_page(None, "Please sign in to continue. Password").is_some());
+        assert!(detect_blocked_page(Some("Docs"), &"content ".repeat(400)).is_none());
+
+        let login = reqwest::Url::parse("https://example.com/login?next=/docs").unwrap();
+        assert!(is_login_url(&login));
+        let docs = reqwest::Url::parse("https://example.com/blog/login-tips").unwrap();
+        assert!(!is_login_url(&docs));
+    }
+
+    #[test]
+    fn allocates_and_truncates_to_budget() {
+        assert_eq!(allocate_budget(&[100, 5000, 50], 1000), vec![100, 850, 50]);
+        assert_eq!(allocate_budget(&[3000, 3000], 1000), vec![500, 500]);
+
+        let text = "line one\nline two\n".repeat(100);
+        let (cut, truncated) = truncate_to_tokens(&text, 50);
+        assert!(truncated);
+        assert!(cut.ends_with("…（已截断）"));
+        assert!(estimate_tokens(cut.trim_end_matches("\n…（已截断）")) <= 50);
+        assert_eq!(
+            truncate_to_tokens("short", 50),
+            ("short".to_string(), false)
+        );
+    }
+}
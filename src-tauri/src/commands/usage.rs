@@ -6,9 +6,18 @@ use serde::{Deserialize, Serialize};
 use serde_json;
 use std::collections::{HashMap, HashSet};
 use std::fs;
-use std::path::PathBuf;
+use std::io::{BufWriter, Write};
+use std::ops::ControlFlow;
+use std::path::{Path, PathBuf};
 use tauri::command;
 
+use super::codex::usage::codex_usage_records;
+use super::cost_comparison::{SessionTokenUsage, SessionTokens, TokenRates};
+use super::gemini::usage::gemini_usage_records;
+use super::input_validation::resolve_export_target;
+use super::usage_report::{write_csv_line, UsageRecord};
+use crate::utils::jsonl_reader::for_each_line;
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct UsageEntry {
     timestamp: String,
@@ -241,120 +250,196 @@ fn calculate_cost(model: &str, usage: &UsageData) -> f64 {
     cost
 }
 
+/// Price table rates of a Claude model, None for models missing from the table
+pub(crate) fn claude_token_rates(model: &str) -> Option<TokenRates> {
+    let family = parse_model_family(model);
+    if family == ModelFamily::Unknown {
+        return None;
+    }
+    let pricing = ModelPricing::for_family(family);
+    Some(TokenRates {
+        input: pricing.input,
+        output: pricing.output,
+        cache_write: pricing.cache_write,
+        cache_read: pricing.cache_read,
+    })
+}
+
+/// Token totals, models and recorded cost of a single Claude session file
+pub(crate) fn claude_session_tokens(path: &Path) -> SessionTokenUsage {
+    let (entries, _) = parse_jsonl_file(&path.to_path_buf(), "", &mut HashSet::new());
+
+    let mut usage = SessionTokenUsage::default();
+    for entry in entries {
+        usage.tokens.input_tokens += entry.input_tokens;
+        usage.tokens.output_tokens += entry.output_tokens;
+        usage.tokens.cache_creation_tokens += entry.cache_creation_tokens;
+        usage.tokens.cache_read_tokens += entry.cache_read_tokens;
+        usage.cost += entry.cost;
+        if !usage.models.contains(&entry.model) {
+            usage.models.push(entry.model);
+        }
+    }
+    usage
+}
+
+/// Every Claude usage entry with a parseable timestamp, for report export
+pub(crate) fn claude_usage_records() -> Result<Vec<UsageRecord>, String> {
+    let claude_path = dirs::home_dir()
+        .ok_or("Failed to get home directory")?
+        .join(".claude");
+
+    Ok(get_all_usage_entries(&claude_path)
+        .into_iter()
+        .filter_map(|entry| {
+            let timestamp = DateTime::parse_from_rfc3339(&entry.timestamp)
+                .ok()?
+                .with_timezone(&Local);
+            Some(UsageRecord {
+                engine: "claude",
+                timestamp,
+                project_path: entry.project_path,
+                model: entry.model,
+                session_id: entry.session_id,
+                tokens: SessionTokens {
+                    input_tokens: entry.input_tokens,
+                    output_tokens: entry.output_tokens,
+                    cache_creation_tokens: entry.cache_creation_tokens,
+                    cache_read_tokens: entry.cache_read_tokens,
+                },
+                cost: entry.cost,
+            })
+        })
+        .collect())
+}
+
+/// Parses the usage entries of a session file, also returning the project path from its `cwd`
 fn parse_jsonl_file(
     path: &PathBuf,
     encoded_project_name: &str,
     processed_hashes: &mut HashSet<String>,
-) -> Vec<UsageEntry> {
+) -> (Vec<UsageEntry>, Option<String>) {
     let mut entries = Vec::new();
     let mut actual_project_path: Option<String> = None;
 
-    if let Ok(content) = fs::read_to_string(path) {
-        // Extract session ID from the file path
-        let session_id = path
-            .parent()
-            .and_then(|p| p.file_name())
-            .and_then(|n| n.to_str())
-            .unwrap_or("unknown")
-            .to_string();
-
-        for line in content.lines() {
-            if line.trim().is_empty() {
-                continue;
+    // Extract session ID from the file path
+    let session_id = path
+        .parent()
+        .and_then(|p| p.file_name())
+        .and_then(|n| n.to_str())
+        .unwrap_or("unknown")
+        .to_string();
+
+    // Stream line by line so huge sessions never live in memory as a whole
+    let scan = for_each_line(path, |_, line| {
+        if line.trim().is_empty() {
+            return ControlFlow::Continue(());
+        }
+
+        if let Ok(json_value) = serde_json::from_str::<serde_json::Value>(line) {
+            // Extract the actual project path from cwd if we haven't already
+            if actual_project_path.is_none() {
+                if let Some(cwd) = json_value.get("cwd").and_then(|v| v.as_str()) {
+                    actual_project_path = Some(cwd.to_string());
+                }
             }
 
-            if let Ok(json_value) = serde_json::from_str::<serde_json::Value>(line) {
-                // Extract the actual project path from cwd if we haven't already
-                if actual_project_path.is_none() {
-                    if let Some(cwd) = json_value.get("cwd").and_then(|v| v.as_str()) {
-                        actual_project_path = Some(cwd.to_string());
+            // Try to parse as JsonlEntry for usage data
+            if let Ok(entry) = serde_json::from_value::<JsonlEntry>(json_value) {
+                if let Some(message) = &entry.message {
+                    // Deduplication based on message ID and request ID
+                    if let (Some(msg_id), Some(req_id)) = (&message.id, &entry.request_id) {
+                        let unique_hash = format!("{}:{}", msg_id, req_id);
+                        if processed_hashes.contains(&unique_hash) {
+                            return ControlFlow::Continue(()); // Skip duplicate entry
+                        }
+                        processed_hashes.insert(unique_hash);
                     }
-                }
 
-                // Try to parse as JsonlEntry for usage data
-                if let Ok(entry) = serde_json::from_value::<JsonlEntry>(json_value) {
-                    if let Some(message) = &entry.message {
-                        // Deduplication based on message ID and request ID
-                        if let (Some(msg_id), Some(req_id)) = (&message.id, &entry.request_id) {
-                            let unique_hash = format!("{}:{}", msg_id, req_id);
-                            if processed_hashes.contains(&unique_hash) {
-                                continue; // Skip duplicate entry
-                            }
-                            processed_hashes.insert(unique_hash);
+                    if let Some(usage) = &message.usage {
+                        // Skip entries without meaningful token usage
+                        if usage.input_tokens.unwrap_or(0) == 0
+                            && usage.output_tokens.unwrap_or(0) == 0
+                            && usage.cache_creation_input_tokens.unwrap_or(0) == 0
+                            && usage.cache_read_input_tokens.unwrap_or(0) == 0
+                        {
+                            return ControlFlow::Continue(());
                         }
 
-                        if let Some(usage) = &message.usage {
-                            // Skip entries without meaningful token usage
-                            if usage.input_tokens.unwrap_or(0) == 0
-                                && usage.output_tokens.unwrap_or(0) == 0
-                                && usage.cache_creation_input_tokens.unwrap_or(0) == 0
-                                && usage.cache_read_input_tokens.unwrap_or(0) == 0
-                            {
-                                continue;
+                        let cost = entry.cost_usd.unwrap_or_else(|| {
+                            if let Some(model_str) = &message.model {
+                                calculate_cost(model_str, usage)
+                            } else {
+                                0.0
                             }
+                        });
 
-                            let cost = entry.cost_usd.unwrap_or_else(|| {
-                                if let Some(model_str) = &message.model {
-                                    calculate_cost(model_str, usage)
-                                } else {
-                                    0.0
-                                }
-                            });
+                        // Use actual project path if found, otherwise use encoded name
+                        let project_path = actual_project_path
+                            .clone()
+                            .unwrap_or_else(|| encoded_project_name.to_string());
 
-                            // Use actual project path if found, otherwise use encoded name
-                            let project_path = actual_project_path
+                        entries.push(UsageEntry {
+                            timestamp: entry.timestamp,
+                            model: message
+                                .model
                                 .clone()
-                                .unwrap_or_else(|| encoded_project_name.to_string());
-
-                            entries.push(UsageEntry {
-                                timestamp: entry.timestamp,
-                                model: message
-                                    .model
-                                    .clone()
-                                    .unwrap_or_else(|| "unknown".to_string()),
-                                input_tokens: usage.input_tokens.unwrap_or(0),
-                                output_tokens: usage.output_tokens.unwrap_or(0),
-                                cache_creation_tokens: usage
-                                    .cache_creation_input_tokens
-                                    .unwrap_or(0),
-                                cache_read_tokens: usage.cache_read_input_tokens.unwrap_or(0),
-                                cost,
-                                session_id: entry.session_id.unwrap_or_else(|| session_id.clone()),
-                                project_path,
-                            });
-                        }
+                                .unwrap_or_else(|| "unknown".to_string()),
+                            input_tokens: usage.input_tokens.unwrap_or(0),
+                            output_tokens: usage.output_tokens.unwrap_or(0),
+                            cache_creation_tokens: usage
+                                .cache_creation_input_tokens
+                                .unwrap_or(0),
+                            cache_read_tokens: usage.cache_read_input_tokens.unwrap_or(0),
+                            cost,
+                            session_id: entry.session_id.unwrap_or_else(|| session_id.clone()),
+                            project_path,
+                        });
                     }
                 }
             }
         }
+        ControlFlow::Continue(())
+    });
+    // Keep the entries parsed before a read error; the error is only logged
+    if let Err(e) = scan {
+        log::warn!("Failed to read usage file {}: {}", path.display(), e);
     }
 
-    entries
+    (entries, actual_project_path)
 }
 
 fn get_earliest_timestamp(path: &PathBuf) -> Option<String> {
-    if let Ok(content) = fs::read_to_string(path) {
-        let mut earliest_timestamp: Option<String> = None;
-        for line in content.lines() {
-            if let Ok(json_value) = serde_json::from_str::<serde_json::Value>(line) {
-                if let Some(timestamp_str) = json_value.get("timestamp").and_then(|v| v.as_str()) {
-                    if let Some(current_earliest) = &earliest_timestamp {
-                        if timestamp_str < current_earliest.as_str() {
-                            earliest_timestamp = Some(timestamp_str.to_string());
-                        }
-                    } else {
+    let mut earliest_timestamp: Option<String> = None;
+    for_each_line(path, |_, line| {
+        if let Ok(json_value) = serde_json::from_str::<serde_json::Value>(line) {
+            if let Some(timestamp_str) = json_value.get("timestamp").and_then(|v| v.as_str()) {
+                if let Some(current_earliest) = &earliest_timestamp {
+                    if timestamp_str < current_earliest.as_str() {
                         earliest_timestamp = Some(timestamp_str.to_string());
                     }
+                } else {
+                    earliest_timestamp = Some(timestamp_str.to_string());
                 }
             }
         }
-        return earliest_timestamp;
-    }
-    None
+        ControlFlow::Continue(())
+    })
+    .ok()?;
+    earliest_timestamp
 }
 
-fn get_all_usage_entries(claude_path: &PathBuf) -> Vec<UsageEntry> {
-    let mut all_entries = Vec::new();
+/// Usage entries of one Claude session file
+struct SessionFileScan {
+    session_id: String,
+    project_path: String,
+    /// Earliest timestamp in the file
+    started_at: Option<String>,
+    entries: Vec<UsageEntry>,
+}
+
+/// Scans every Claude session file in chronological order, deduplicating entries across files
+fn scan_session_files(claude_path: &Path) -> Vec<SessionFileScan> {
     let mut processed_hashes = HashSet::new();
     let projects_dir = claude_path.join("projects");
 
@@ -379,12 +464,42 @@ fn get_all_usage_entries(claude_path: &PathBuf) -> Vec<UsageEntry> {
 
     // Sort files by their earliest timestamp to ensure chronological processing
     // and deterministic deduplication
-    files_to_process.sort_by_cached_key(|(path, _)| get_earliest_timestamp(path));
+    let mut files: Vec<(PathBuf, String, Option<String>)> = files_to_process
+        .into_iter()
+        .map(|(path, project_name)| {
+            let started_at = get_earliest_timestamp(&path);
+            (path, project_name, started_at)
+        })
+        .collect();
+    files.sort_by(|a, b| a.2.cmp(&b.2));
 
-    for (path, project_name) in files_to_process {
-        let entries = parse_jsonl_file(&path, &project_name, &mut processed_hashes);
-        all_entries.extend(entries);
-    }
+    files
+        .into_iter()
+        .map(|(path, project_name, started_at)| {
+            let (entries, cwd) = parse_jsonl_file(&path, &project_name, &mut processed_hashes);
+            let session_id = entries.first().map_or_else(
+                || {
+                    path.file_stem()
+                        .map(|s| s.to_string_lossy().to_string())
+                        .unwrap_or_default()
+                },
+                |e| e.session_id.clone(),
+            );
+            SessionFileScan {
+                session_id,
+                project_path: cwd.unwrap_or(project_name),
+                started_at,
+                entries,
+            }
+        })
+        .collect()
+}
+
+fn get_all_usage_entries(claude_path: &PathBuf) -> Vec<UsageEntry> {
+    let mut all_entries: Vec<UsageEntry> = scan_session_files(claude_path)
+        .into_iter()
+        .flat_map(|scan| scan.entries)
+        .collect();
 
     // Sort by timestamp
     all_entries.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
@@ -802,3 +917,283 @@ pub fn get_session_stats(
 
     Ok(by_session)
 }
+
+/// Engines accepted by the usage CSV export
+const EXPORT_ENGINES: [&str; 3] = ["claude", "codex", "gemini"];
+
+/// One session row of the usage CSV export
+#[derive(Debug, Clone, Default, PartialEq)]
+struct SessionUsageRow {
+    engine: &'static str,
+    session_id: String,
+    project_path: String,
+    models: Vec<String>,
+    tokens: SessionTokens,
+    cost: f64,
+}
+
+impl SessionUsageRow {
+    fn add(&mut self, model: &str, tokens: &SessionTokens, cost: f64) {
+        if !model.is_empty() && !self.models.iter().any(|m| m == model) {
+            self.models.push(model.to_string());
+        }
+        self.tokens.input_tokens += tokens.input_tokens;
+        self.tokens.output_tokens += tokens.output_tokens;
+        self.tokens.cache_creation_tokens += tokens.cache_creation_tokens;
+        self.tokens.cache_read_tokens += tokens.cache_read_tokens;
+        self.cost += cost;
+    }
+}
+
+/// Parses a `YYYY-MM-DD` or RFC 3339 date argument
+fn parse_date_arg(value: &str, label: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(value.trim(), "%Y-%m-%d").or_else(|_| {
+        DateTime::parse_from_rfc3339(value.trim())
+            .map(|dt| dt.with_timezone(&Local).date_naive())
+            .map_err(|e| format!("Invalid {} date: {}", label, e))
+    })
+}
+
+/// Local date of an RFC 3339 timestamp
+fn local_date(timestamp: &str) -> Option<NaiveDate> {
+    DateTime::parse_from_rfc3339(timestamp)
+        .ok()
+        .map(|dt| dt.with_timezone(&Local).date_naive())
+}
+
+/// Groups Claude usage into one row per session, counting only usage inside the range
+///
+/// Sessions without any usage block get a zero row when they started inside the range.
+fn claude_session_rows(
+    scans: Vec<SessionFileScan>,
+    start: NaiveDate,
+    end: NaiveDate,
+) -> Vec<SessionUsageRow> {
+    let in_range = |date: Option<NaiveDate>| date.is_some_and(|d| d >= start && d <= end);
+
+    let mut rows: Vec<SessionUsageRow> = Vec::new();
+    let mut index: HashMap<String, usize> = HashMap::new();
+    for scan in scans {
+        let entries: Vec<&UsageEntry> = scan
+            .entries
+            .iter()
+            .filter(|e| in_range(local_date(&e.timestamp)))
+            .collect();
+        let empty_in_range =
+            scan.entries.is_empty() && in_range(scan.started_at.as_deref().and_then(local_date));
+        if entries.is_empty() && !empty_in_range {
+            continue;
+        }
+
+        // Subagent files share the session ID of their parent session
+        let row_index = *index.entry(scan.session_id.clone()).or_insert_with(|| {
+            rows.push(SessionUsageRow {
+                engine: "claude",
+                session_id: scan.session_id.clone(),
+                project_path: scan.project_path.clone(),
+                ..Default::default()
+            });
+            rows.len() - 1
+        });
+        for entry in entries {
+            let tokens = SessionTokens {
+                input_tokens: entry.input_tokens,
+                output_tokens: entry.output_tokens,
+                cache_creation_tokens: entry.cache_creation_tokens,
+                cache_read_tokens: entry.cache_read_tokens,
+            };
+            rows[row_index].add(&entry.model, &tokens, entry.cost);
+        }
+    }
+    rows
+}
+
+/// Codex / Gemini records are already one per session, dated by session start
+fn record_rows(
+    records: Vec<UsageRecord>,
+    start: NaiveDate,
+    end: NaiveDate,
+) -> Vec<SessionUsageRow> {
+    records
+        .into_iter()
+        .filter(|r| {
+            let date = r.timestamp.date_naive();
+            date >= start && date <= end
+        })
+        .map(|r| {
+            let mut row = SessionUsageRow {
+                engine: r.engine,
+                session_id: r.session_id,
+                project_path: r.project_path,
+                ..Default::default()
+            };
+            row.add(&r.model, &r.tokens, r.cost);
+            row
+        })
+        .collect()
+}
+
+fn write_session_csv(path: &Path, rows: &[SessionUsageRow]) -> Result<(), String> {
+    let file = fs::File::create(path)
+        .map_err(|e| format!("Failed to create {}: {}", path.display(), e))?;
+    let mut writer = BufWriter::new(file);
+
+    let write = |writer: &mut BufWriter<fs::File>| -> std::io::Result<()> {
+        // UTF-8 BOM so spreadsheet apps detect the encoding
+        writer.write_all("\u{FEFF}".as_bytes())?;
+        let header = [
+            "engine",
+            "session_id",
+            "project_path",
+            "model",
+            "input_tokens",
+            "output_tokens",
+            "cache_creation_tokens",
+            "cache_read_tokens",
+            "cost_usd",
+        ];
+        write_csv_line(writer, &header.map(String::from))?;
+        for row in rows {
+            write_csv_line(
+                writer,
+                &[
+                    row.engine.to_string(),
+                    row.session_id.clone(),
+                    row.project_path.clone(),
+                    row.models.join(";"),
+                    row.tokens.input_tokens.to_string(),
+                    row.tokens.output_tokens.to_string(),
+                    row.tokens.cache_creation_tokens.to_string(),
+                    row.tokens.cache_read_tokens.to_string(),
+                    format!("{:.6}", row.cost),
+                ],
+            )?;
+        }
+        writer.flush()
+    };
+    write(&mut writer).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+/// Exports per-session usage between two dates (inclusive, local time) to a CSV file
+///
+/// `engine` limits the export to "claude", "codex" or "gemini". Claude usage is dated per
+/// message, Codex and Gemini sessions by their start time, matching the usage dashboard.
+/// Sessions without usage data are written with zero tokens. Returns the number of rows written.
+#[command]
+pub async fn export_usage_csv(
+    start_date: String,
+    end_date: String,
+    engine: Option<String>,
+    output_path: String,
+) -> Result<usize, String> {
+    let start = parse_date_arg(&start_date, "start")?;
+    let end = parse_date_arg(&end_date, "end")?;
+    if start > end {
+        return Err(format!(
+            "Start date {} is after end date {}",
+            start_date, end_date
+        ));
+    }
+    let engine = match engine.as_deref().map(str::trim) {
+        None | Some("") | Some("all") => None,
+        Some(name) => Some(
+            EXPORT_ENGINES
+                .into_iter()
+                .find(|e| *e == name)
+                .ok_or_else(|| format!("Unknown engine: {}", name))?,
+        ),
+    };
+    let output_path = resolve_export_target("output_path", &output_path)?;
+
+    tokio::task::spawn_blocking(move || {
+        let wants = |name: &str| engine.is_none_or(|e| e == name);
+
+        let mut rows = Vec::new();
+        if wants("claude") {
+            let claude_path = dirs::home_dir()
+                .ok_or("Failed to get home directory")?
+                .join(".claude");
+            rows.extend(claude_session_rows(
+                scan_session_files(&claude_path),
+                start,
+                end,
+            ));
+        }
+        if wants("codex") {
+            rows.extend(record_rows(codex_usage_records(), start, end));
+        }
+        if wants("gemini") {
+            rows.extend(record_rows(gemini_usage_records(true), start, end));
+        }
+
+        write_session_csv(&output_path, &rows)?;
+        log::info!(
+            "[Usage] Exported {} session rows ({:?}) to {}",
+            rows.len(),
+            engine,
+            output_path
+        );
+        Ok(rows.len())
+    })
+    .await
+    .map_err(|e| format!("Usage export task failed: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(timestamp: &str, model: &str, input: u64, cost: f64) -> UsageEntry {
+        UsageEntry {
+            timestamp: timestamp.to_string(),
+            model: model.to_string(),
+            input_tokens: input,
+            output_tokens: 10,
+            cache_creation_tokens: 0,
+            cache_read_tokens: 5,
+            cost,
+            session_id: "s1".to_string(),
+            project_path: "/work/app".to_string(),
+        }
+    }
+
+    #[test]
+    fn claude_rows_keep_sessions_without_usage() {
+        let date = |s: &str| NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap();
+        let scans = vec![
+            SessionFileScan {
+                session_id: "s1".to_string(),
+                project_path: "/work/app".to_string(),
+                started_at: Some("2026-03-02T12:00:00Z".to_string()),
+                entries: vec![
+                    entry("2026-03-02T12:00:00Z", "claude-sonnet-4-5", 100, 0.5),
+                    entry("2026-03-03T12:00:00Z", "claude-opus-4-5", 50, 1.0),
+                    entry("2026-04-20T12:00:00Z", "claude-opus-4-5", 999, 9.0),
+                ],
+            },
+            SessionFileScan {
+                session_id: "empty".to_string(),
+                project_path: "/work/other".to_string(),
+                started_at: Some("2026-03-05T12:00:00Z".to_string()),
+                entries: Vec::new(),
+            },
+            SessionFileScan {
+                session_id: "old".to_string(),
+                project_path: "/work/other".to_string(),
+                started_at: Some("2025-01-01T12:00:00Z".to_string()),
+                entries: Vec::new(),
+            },
+        ];
+
+        let rows = claude_session_rows(scans, date("2026-03-01"), date("2026-03-31"));
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].session_id, "s1");
+        assert_eq!(rows[0].tokens.input_tokens, 150);
+        assert_eq!(rows[0].tokens.cache_read_tokens, 10);
+        assert_eq!(rows[0].models, vec!["claude-sonnet-4-5", "claude-opus-4-5"]);
+        assert!((rows[0].cost - 1.5).abs() < 1e-9);
+        assert_eq!(rows[1].session_id, "empty");
+        assert_eq!(rows[1].tokens, SessionTokens::default());
+        assert_eq!(rows[1].cost, 0.0);
+    }
+}
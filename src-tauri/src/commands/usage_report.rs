@@ -0,0 +1,627 @@
+//! 用量报表导出
+//!
+//! 按本地时区的日期范围汇总三个引擎的用量，按 day / project / provider / model / engine 的任意组合分组，
+//! 导出为 CSV 或 JSON：
+//! - token 与费用：复用各引擎用量统计的采集与计价（Claude 按消息时间，Codex / Gemini 按会话开始时间计入），
+//!   合计与用量统计页面一致
+//! - 任务次数与平均时长：取审计日志中的执行结束事件；分组含模型或供应商时按会话归属到用量最多的模型，
+//!   找不到会话时归属到同引擎同项目用量最多的模型
+//! - 供应商按模型名推断（anthropic / openai / google ...）
+//! - CSV 带 UTF-8 BOM，表头跟随后端语言，Excel 可直接打开
+//!
+//! 行逐条写入文件，超过 10 万行也不会在内存中拼出整个文件。
+
+use chrono::{DateTime, Local, NaiveDate, TimeZone};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+
+use super::codex::usage::codex_usage_records;
+use super::cost_comparison::SessionTokens;
+use super::execution_trace::{audit_log_path, TraceEvent, STAGE_EXECUTION_FINISHED};
+use super::gemini::config::hash_project_path;
+use super::gemini::usage::gemini_usage_records;
+use super::input_validation::resolve_export_target;
+use super::messages::{t, MessageKey};
+use super::usage::claude_usage_records;
+
+/// 单条用量记录（Claude 为一条消息，Codex / Gemini 为一个会话）
+#[derive(Debug, Clone)]
+pub struct UsageRecord {
+    pub engine: &'static str,
+    pub timestamp: DateTime<Local>,
+    pub project_path: String,
+    pub model: String,
+    pub session_id: String,
+    pub tokens: SessionTokens,
+    pub cost: f64,
+}
+
+/// 分组维度
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UsageGroupKey {
+    Day,
+    Project,
+    Provider,
+    Model,
+    Engine,
+}
+
+/// 导出格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UsageReportFormat {
+    Csv,
+    Json,
+}
+
+/// 报表中的一行，只有参与分组的维度字段有值
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageReportRow {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub day: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub project: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub provider: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub engine: Option<String>,
+    #[serde(flatten)]
+    pub tokens: SessionTokens,
+    pub total_tokens: u64,
+    /// 估算费用（美元）
+    pub cost: f64,
+    pub task_count: u64,
+    /// 平均任务时长（秒），没有任务时为 0
+    pub avg_task_duration_secs: f64,
+    #[serde(skip)]
+    task_duration_ms: u64,
+}
+
+/// 导出结果摘要
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageReportSummary {
+    pub output_path: String,
+    pub format: UsageReportFormat,
+    pub row_count: usize,
+    pub total_cost: f64,
+    pub total_tokens: u64,
+    pub task_count: u64,
+}
+
+/// 一次任务（审计日志中的执行结束事件）
+#[derive(Debug, Clone)]
+struct TaskRecord {
+    engine: String,
+    timestamp: DateTime<Local>,
+    project_path: String,
+    session_id: Option<String>,
+    duration_ms: u64,
+}
+
+/// 一条记录在各维度上的取值
+struct Dimensions<'a> {
+    day: String,
+    project: &'a str,
+    provider: &'static str,
+    model: &'a str,
+    engine: &'a str,
+}
+
+impl Dimensions<'_> {
+    fn value(&self, key: UsageGroupKey) -> &str {
+        match key {
+            UsageGroupKey::Day => &self.day,
+            UsageGroupKey::Project => self.project,
+            UsageGroupKey::Provider => self.provider,
+            UsageGroupKey::Model => self.model,
+            UsageGroupKey::Engine => self.engine,
+        }
+    }
+}
+
+/// 导出用量报表到指定路径
+///
+/// `from` / `to` 为本地日期（YYYY-MM-DD，含两端），`group_by` 为空时只输出一行合计。
+#[tauri::command]
+pub async fn export_usage_report(
+    from: String,
+    to: String,
+    group_by: Vec<UsageGroupKey>,
+    format: UsageReportFormat,
+    output_path: String,
+) -> Result<UsageReportSummary, String> {
+    let from_date = parse_date(&from)?;
+    let to_date = parse_date(&to)?;
+    if from_date > to_date {
+        return Err(format!("Start date {} is after end date {}", from, to));
+    }
+    let target_path = resolve_export_target("output_path", &output_path)?;
+
+    let mut keys: Vec<UsageGroupKey> = Vec::new();
+    for key in group_by {
+        if !keys.contains(&key) {
+            keys.push(key);
+        }
+    }
+
+    tokio::task::spawn_blocking(move || {
+        let mut records = claude_usage_records()?;
+        records.extend(codex_usage_records());
+        records.extend(gemini_usage_records(false));
+        let tasks = load_task_records();
+
+        let rows = build_rows(records, tasks, from_date, to_date, &keys);
+        let summary = UsageReportSummary {
+            output_path: output_path.clone(),
+            format,
+            row_count: rows.len(),
+            total_cost: rows.iter().map(|r| r.cost).sum(),
+            total_tokens: rows.iter().map(|r| r.total_tokens).sum(),
+            task_count: rows.iter().map(|r| r.task_count).sum(),
+        };
+
+        write_report(&target_path, format, &from, &to, &keys, &rows, &summary)?;
+        log::info!(
+            "[UsageReport] Exported {} rows ({:?}) to {}",
+            summary.row_count,
+            format,
+            output_path
+        );
+        Ok(summary)
+    })
+    .await
+    .map_err(|e| format!("Usage report task failed: {}", e))?
+}
+
+fn parse_date(value: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(value.trim(), "%Y-%m-%d")
+        .map_err(|e| format!("Invalid date '{}': {}", value, e))
+}
+
+/// 按模型名推断供应商
+fn provider_of(model: &str) -> &'static str {
+    let model = model.to_lowercase();
+    if model.contains("claude") {
+        "anthropic"
+    } else if model.contains("gemini") {
+        "google"
+    } else if model.starts_with("gpt")
+        || model.contains("codex")
+        || ["o1", "o3", "o4"].iter().any(|p| model.starts_with(p))
+    {
+        "openai"
+    } else if model.contains("deepseek") {
+        "deepseek"
+    } else if model.contains("qwen") {
+        "alibaba"
+    } else if model.contains("glm") {
+        "zhipu"
+    } else if model.contains("kimi") || model.contains("moonshot") {
+        "moonshot"
+    } else {
+        "other"
+    }
+}
+
+/// 读取审计日志中的执行结束事件
+fn load_task_records() -> Vec<TaskRecord> {
+    let path = match audit_log_path() {
+        Ok(path) => path,
+        Err(_) => return Vec::new(),
+    };
+    let file = match File::open(&path) {
+        Ok(file) => file,
+        Err(_) => return Vec::new(),
+    };
+
+    BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .filter_map(|line| serde_json::from_str::<TraceEvent>(&line).ok())
+        .filter(|event| event.stage == STAGE_EXECUTION_FINISHED)
+        .filter_map(|event| {
+            let detail = event.detail.unwrap_or_default();
+            Some(TaskRecord {
+                engine: event.engine?,
+                timestamp: Local.timestamp_millis_opt(event.timestamp_ms).single()?,
+                project_path: detail
+                    .get("projectPath")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or_default()
+                    .to_string(),
+                session_id: event.session_id,
+                duration_ms: detail
+                    .get("durationMs")
+                    .and_then(|v| v.as_u64())
+                    .unwrap_or(0),
+            })
+        })
+        .collect()
+}
+
+/// 每个键下用量（token 数）最多的模型
+fn dominant_models<'a>(
+    entries: impl Iterator<Item = ((&'a str, &'a str), &'a str, u64)>,
+) -> HashMap<(String, String), String> {
+    let mut totals: HashMap<(&str, &str), HashMap<&str, u64>> = HashMap::new();
+    for (key, model, tokens) in entries {
+        *totals.entry(key).or_default().entry(model).or_default() += tokens;
+    }
+    totals
+        .into_iter()
+        .filter_map(|((a, b), models)| {
+            let (model, _) = models
+                .into_iter()
+                .max_by(|x, y| x.1.cmp(&y.1).then_with(|| y.0.cmp(x.0)))?;
+            Some(((a.to_string(), b.to_string()), model.to_string()))
+        })
+        .collect()
+}
+
+fn total_tokens(tokens: &SessionTokens) -> u64 {
+    tokens.input_tokens
+        + tokens.output_tokens
+        + tokens.cache_creation_tokens
+        + tokens.cache_read_tokens
+}
+
+fn new_row(keys: &[UsageGroupKey], values: &[String]) -> UsageReportRow {
+    let mut row = UsageReportRow::default();
+    for (key, value) in keys.iter().zip(values) {
+        let field = match key {
+            UsageGroupKey::Day => &mut row.day,
+            UsageGroupKey::Project => &mut row.project,
+            UsageGroupKey::Provider => &mut row.provider,
+            UsageGroupKey::Model => &mut row.model,
+            UsageGroupKey::Engine => &mut row.engine,
+        };
+        *field = Some(value.clone());
+    }
+    row
+}
+
+/// 取（必要时创建）维度取值对应的行
+fn row_for<'a>(
+    rows: &'a mut BTreeMap<Vec<String>, UsageReportRow>,
+    keys: &[UsageGroupKey],
+    dims: Dimensions,
+) -> &'a mut UsageReportRow {
+    let values: Vec<String> = keys.iter().map(|k| dims.value(*k).to_string()).collect();
+    rows.entry(values.clone())
+        .or_insert_with(|| new_row(keys, &values))
+}
+
+/// 过滤日期范围并按维度汇总
+fn build_rows(
+    mut records: Vec<UsageRecord>,
+    tasks: Vec<TaskRecord>,
+    from: NaiveDate,
+    to: NaiveDate,
+    keys: &[UsageGroupKey],
+) -> Vec<UsageReportRow> {
+    let in_range = |timestamp: &DateTime<Local>| {
+        let date = timestamp.date_naive();
+        date >= from && date <= to
+    };
+
+    // Gemini 用量只知道项目路径的哈希，借任务记录中的真实路径还原
+    let gemini_projects: HashMap<String, String> = tasks
+        .iter()
+        .filter(|task| task.engine == "gemini" && !task.project_path.is_empty())
+        .map(|task| {
+            (
+                format!("project:{}", hash_project_path(&task.project_path)),
+                task.project_path.clone(),
+            )
+        })
+        .collect();
+    for record in records.iter_mut().filter(|r| r.engine == "gemini") {
+        if let Some(path) = gemini_projects.get(&record.project_path) {
+            record.project_path = path.clone();
+        }
+    }
+
+    let session_models = dominant_models(records.iter().map(|r| {
+        (
+            (r.engine, r.session_id.as_str()),
+            r.model.as_str(),
+            total_tokens(&r.tokens),
+        )
+    }));
+    records.retain(|r| in_range(&r.timestamp));
+    let project_models = dominant_models(records.iter().map(|r| {
+        (
+            (r.engine, r.project_path.as_str()),
+            r.model.as_str(),
+            total_tokens(&r.tokens),
+        )
+    }));
+
+    let mut rows: BTreeMap<Vec<String>, UsageReportRow> = BTreeMap::new();
+
+    for record in &records {
+        let row = row_for(
+            &mut rows,
+            keys,
+            Dimensions {
+                day: record.timestamp.format("%Y-%m-%d").to_string(),
+                project: &record.project_path,
+                provider: provider_of(&record.model),
+                model: &record.model,
+                engine: record.engine,
+            },
+        );
+        row.tokens.input_tokens += record.tokens.input_tokens;
+        row.tokens.output_tokens += record.tokens.output_tokens;
+        row.tokens.cache_creation_tokens += record.tokens.cache_creation_tokens;
+        row.tokens.cache_read_tokens += record.tokens.cache_read_tokens;
+        row.total_tokens += total_tokens(&record.tokens);
+        row.cost += record.cost;
+    }
+
+    for task in tasks.iter().filter(|t| in_range(&t.timestamp)) {
+        let model = task
+            .session_id
+            .as_ref()
+            .and_then(|id| session_models.get(&(task.engine.clone(), id.clone())))
+            .or_else(|| project_models.get(&(task.engine.clone(), task.project_path.clone())))
+            .map(String::as_str)
+            .unwrap_or("unknown");
+        let row = row_for(
+            &mut rows,
+            keys,
+            Dimensions {
+                day: task.timestamp.format("%Y-%m-%d").to_string(),
+                project: &task.project_path,
+                provider: provider_of(model),
+                model,
+                engine: &task.engine,
+            },
+        );
+        row.task_count += 1;
+        row.task_duration_ms += task.duration_ms;
+    }
+
+    rows.into_values()
+        .map(|mut row| {
+            if row.task_count > 0 {
+                let avg_ms = row.task_duration_ms as f64 / row.task_count as f64;
+                row.avg_task_duration_secs = (avg_ms / 100.0).round() / 10.0;
+            }
+            row
+        })
+        .collect()
+}
+
+/// 写出报表；失败时删除写了一半的文件
+fn write_report(
+    path: &Path,
+    format: UsageReportFormat,
+    from: &str,
+    to: &str,
+    keys: &[UsageGroupKey],
+    rows: &[UsageReportRow],
+    summary: &UsageReportSummary,
+) -> Result<(), String> {
+    let result = File::create(path).and_then(|file| {
+        let mut writer = BufWriter::new(file);
+        match format {
+            UsageReportFormat::Csv => write_csv(&mut writer, keys, rows)?,
+            UsageReportFormat::Json => write_json(&mut writer, from, to, keys, rows, summary)?,
+        }
+        writer.flush()
+    });
+
+    result.map_err(|e| {
+        let _ = fs::remove_file(path);
+        format!("Failed to write usage report {}: {}", path.display(), e)
+    })
+}
+
+fn column_title(key: UsageGroupKey) -> String {
+    t(match key {
+        UsageGroupKey::Day => MessageKey::ReportDay,
+        UsageGroupKey::Project => MessageKey::ReportProject,
+        UsageGroupKey::Provider => MessageKey::ReportProvider,
+        UsageGroupKey::Model => MessageKey::ReportModel,
+        UsageGroupKey::Engine => MessageKey::ReportEngine,
+    })
+}
+
+/// CSV 字段转义：含逗号、引号或换行时加引号并双写引号
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// 写出一行 CSV，字段按需转义，CRLF 换行
+pub(crate) fn write_csv_line(writer: &mut impl Write, fields: &[String]) -> io::Result<()> {
+    let line: Vec<String> = fields.iter().map(|f| csv_field(f)).collect();
+    writer.write_all(line.join(",").as_bytes())?;
+    writer.write_all(b"\r\n")
+}
+
+fn write_csv(
+    writer: &mut impl Write,
+    keys: &[UsageGroupKey],
+    rows: &[UsageReportRow],
+) -> io::Result<()> {
+    // UTF-8 BOM，让 Excel 按 UTF-8 识别中文
+    writer.write_all("\u{FEFF}".as_bytes())?;
+
+    let mut header: Vec<String> = keys.iter().map(|k| column_title(*k)).collect();
+    header.extend(
+        [
+            MessageKey::ReportInputTokens,
+            MessageKey::ReportOutputTokens,
+            MessageKey::ReportCacheCreationTokens,
+            MessageKey::ReportCacheReadTokens,
+            MessageKey::ReportTotalTokens,
+            MessageKey::ReportCost,
+            MessageKey::ReportTaskCount,
+            MessageKey::ReportAvgTaskDuration,
+        ]
+        .into_iter()
+        .map(t),
+    );
+    write_csv_line(writer, &header)?;
+
+    for row in rows {
+        let mut fields: Vec<String> = keys
+            .iter()
+            .map(|key| {
+                let value = match key {
+                    UsageGroupKey::Day => &row.day,
+                    UsageGroupKey::Project => &row.project,
+                    UsageGroupKey::Provider => &row.provider,
+                    UsageGroupKey::Model => &row.model,
+                    UsageGroupKey::Engine => &row.engine,
+                };
+                value.clone().unwrap_or_default()
+            })
+            .collect();
+        fields.extend([
+            row.tokens.input_tokens.to_string(),
+            row.tokens.output_tokens.to_string(),
+            row.tokens.cache_creation_tokens.to_string(),
+            row.tokens.cache_read_tokens.to_string(),
+            row.total_tokens.to_string(),
+            format!("{:.4}", row.cost),
+            row.task_count.to_string(),
+            format!("{:.1}", row.avg_task_duration_secs),
+        ]);
+        write_csv_line(writer, &fields)?;
+    }
+    Ok(())
+}
+
+fn write_json(
+    writer: &mut impl Write,
+    from: &str,
+    to: &str,
+    keys: &[UsageGroupKey],
+    rows: &[UsageReportRow],
+    summary: &UsageReportSummary,
+) -> io::Result<()> {
+    write!(
+        writer,
+        "{{\"from\":{},\"to\":{},\"groupBy\":{},\"rowCount\":{},\"totalCost\":{},\"totalTokens\":{},\"taskCount\":{},\"rows\":[",
+        serde_json::to_string(from)?,
+        serde_json::to_string(to)?,
+        serde_json::to_string(keys)?,
+        summary.row_count,
+        serde_json::to_string(&summary.total_cost)?,
+        summary.total_tokens,
+        summary.task_count
+    )?;
+    for (i, row) in rows.iter().enumerate() {
+        if i > 0 {
+            writer.write_all(b",")?;
+        }
+        writer.write_all(b"\n")?;
+        serde_json::to_writer(&mut *writer, row)?;
+    }
+    writer.write_all(b"\n]}\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(
+        engine: &'static str,
+        day: u32,
+        project: &str,
+        model: &str,
+        tokens: u64,
+    ) -> UsageRecord {
+        UsageRecord {
+            engine,
+            timestamp: Local.with_ymd_and_hms(2026, 9, day, 12, 0, 0).unwrap(),
+            project_path: project.to_string(),
+            model: model.to_string(),
+            session_id: format!("{}-{}", engine, project),
+            tokens: SessionTokens {
+                input_tokens: tokens,
+                output_tokens: tokens,
+                cache_creation_tokens: 0,
+                cache_read_tokens: 0,
+            },
+            cost: tokens as f64 / 1000.0,
+        }
+    }
+
+    fn task(engine: &str, day: u32, project: &str, duration_ms: u64) -> TaskRecord {
+        TaskRecord {
+            engine: engine.to_string(),
+            timestamp: Local.with_ymd_and_hms(2026, 9, day, 13, 0, 0).unwrap(),
+            project_path: project.to_string(),
+            session_id: None,
+            duration_ms,
+        }
+    }
+
+    #[test]
+    fn groups_by_project_and_model_within_local_date_range() {
+        let records = vec![
+            record("claude", 1, "/a", "claude-sonnet-4-5", 100),
+            record("claude", 2, "/a", "claude-sonnet-4-5", 300),
+            record("codex", 2, "/b", "gpt-5-codex", 50),
+            record("codex", 10, "/b", "gpt-5-codex", 999),
+        ];
+        let tasks = vec![
+            task("claude", 1, "/a", 2_000),
+            task("claude", 2, "/a", 4_000),
+            task("codex", 10, "/b", 9_000),
+        ];
+        let from = NaiveDate::from_ymd_opt(2026, 9, 1).unwrap();
+        let to = NaiveDate::from_ymd_opt(2026, 9, 5).unwrap();
+
+        let rows = build_rows(
+            records,
+            tasks,
+            from,
+            to,
+            &[UsageGroupKey::Project, UsageGroupKey::Model],
+        );
+
+        assert_eq!(rows.len(), 2);
+        let claude = &rows[0];
+        assert_eq!(claude.project.as_deref(), Some("/a"));
+        assert_eq!(claude.model.as_deref(), Some("claude-sonnet-4-5"));
+        assert_eq!(claude.day, None);
+        assert_eq!(claude.total_tokens, 800);
+        assert!((claude.cost - 0.4).abs() < 1e-9);
+        assert_eq!(claude.task_count, 2);
+        assert_eq!(claude.avg_task_duration_secs, 3.0);
+
+        let codex = &rows[1];
+        assert_eq!(codex.total_tokens, 100);
+        assert_eq!(codex.task_count, 0);
+    }
+
+    #[test]
+    fn csv_has_bom_and_escapes_fields() {
+        let mut row = new_row(&[UsageGroupKey::Project], &["/项目,一".to_string()]);
+        row.cost = 1.5;
+        let mut out = Vec::new();
+        write_csv(&mut out, &[UsageGroupKey::Project], &[row]).unwrap();
+
+        let text = String::from_utf8(out).unwrap();
+        assert!(text.starts_with('\u{FEFF}'));
+        let data_line = text.lines().nth(1).unwrap();
+        assert!(data_line.starts_with("\"/项目,一\",0,0,0,0,0,1.5000,0,0.0"));
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+    }
+}
@@ -1723,6 +1723,32 @@ pub fn get_wsl_claude_dir() -> Option<PathBuf> {
 // 路径转换函数
 // ============================================================================
 
+/// 规范化路径段：去掉空段与 "."，按字面处理 ".."（不会越过根）
+fn normalize_segments<'a>(segments: impl Iterator<Item = &'a str>) -> Vec<&'a str> {
+    let mut normalized = Vec::new();
+    for segment in segments {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                normalized.pop();
+            }
+            _ => normalized.push(segment),
+        }
+    }
+    normalized
+}
+
+/// 去掉 Windows 扩展长度前缀：\\?\C:\... -> C:\...，\\?\UNC\host\... -> \\host\...
+fn strip_verbatim_prefix(path: &str) -> String {
+    if let Some(rest) = path.strip_prefix(r"\\?\UNC\") {
+        format!(r"\\{}", rest)
+    } else if let Some(rest) = path.strip_prefix(r"\\?\") {
+        rest.to_string()
+    } else {
+        path.to_string()
+    }
+}
+
 /// 尝试把 Windows 下的 WSL UNC 路径解析为 WSL 内路径。
 ///
 /// 支持：
@@ -1731,17 +1757,14 @@ pub fn get_wsl_claude_dir() -> Option<PathBuf> {
 ///
 /// 返回 (distro, wsl_path)
 fn try_parse_wsl_unc_path(windows_path: &str) -> Option<(String, String)> {
-    let raw = windows_path.trim();
+    let raw = strip_verbatim_prefix(windows_path.trim());
     if !(raw.starts_with("\\\\") || raw.starts_with("//")) {
         return None;
     }
 
-    // 统一为反斜杠，便于解析
-    let normalized = raw.replace('/', "\\");
-
-    let mut parts = normalized
-        .trim_start_matches("\\\\")
-        .split('\\')
+    let mut parts = raw
+        .trim_start_matches(['\\', '/'])
+        .split(['\\', '/'])
         .filter(|s| !s.is_empty());
 
     let host = parts.next()?.to_lowercase();
@@ -1750,142 +1773,295 @@ fn try_parse_wsl_unc_path(windows_path: &str) -> Option<(String, String)> {
     }
 
     let distro = parts.next()?.to_string();
-    let rest: Vec<&str> = parts.collect();
-    let wsl_path = if rest.is_empty() {
-        "/".to_string()
-    } else {
-        format!("/{}", rest.join("/"))
-    };
+    let wsl_path = format!("/{}", normalize_segments(parts).join("/"));
 
     Some((distro, wsl_path))
 }
 
-/// 将 Windows 路径转换为 WSL 路径
+/// 映射的网络驱动器对应的 UNC 根路径（\\server\share\），本地盘符返回 None
+#[cfg(target_os = "windows")]
+fn network_drive_unc_root(drive: char) -> Option<String> {
+    let canonical = std::fs::canonicalize(format!("{}:\\", drive)).ok()?;
+    let canonical = canonical.to_string_lossy();
+    canonical
+        .starts_with(r"\\?\UNC\")
+        .then(|| strip_verbatim_prefix(&canonical))
+}
+
+#[cfg(not(target_os = "windows"))]
+fn network_drive_unc_root(_drive: char) -> Option<String> {
+    None
+}
+
+/// 盘符是否为映射的网络驱动器
+fn is_network_drive(drive: char) -> bool {
+    network_drive_unc_root(drive).is_some()
+}
+
+/// 是否为指向网络共享的 UNC 路径（不含 \\wsl$ 等 WSL UNC）
+fn is_network_unc_path(path: &str) -> bool {
+    (path.starts_with("\\\\") || path.starts_with("//")) && try_parse_wsl_unc_path(path).is_none()
+}
+
+/// 在 WSL 中调用 wslpath 转换路径（`-u`：Windows → WSL，`-w`：WSL → Windows），失败返回 None
 ///
-/// # Examples
-/// ```
-/// assert_eq!(windows_to_wsl_path("C:\\Users\\test"), "/mnt/c/Users/test");
-/// assert_eq!(windows_to_wsl_path("D:\\Projects"), "/mnt/d/Projects");
-/// ```
-pub fn windows_to_wsl_path(windows_path: &str) -> String {
-    // 处理 WSL UNC 路径（支持）
-    if let Some((_distro, wsl_path)) = try_parse_wsl_unc_path(windows_path) {
-        log::debug!("[WSL] UNC->WSL Path converted: {} -> {}", windows_path, wsl_path);
-        return wsl_path;
+/// `-w` 在解析会话列表时会对同一路径反复调用，结果在进程内缓存
+#[cfg(target_os = "windows")]
+fn run_wslpath(flag: &str, path: &str, distro: Option<&str>) -> Option<String> {
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+
+    static CACHE: OnceLock<Mutex<HashMap<(String, String), Option<String>>>> = OnceLock::new();
+    let cache = (flag == "-w").then(|| CACHE.get_or_init(|| Mutex::new(HashMap::new())));
+    let cache_key = (distro.unwrap_or_default().to_string(), path.to_string());
+    if let Some(cached) = cache
+        .and_then(|cache| cache.lock().ok())
+        .and_then(|cache| cache.get(&cache_key).cloned())
+    {
+        return cached;
     }
 
-    // 其他 UNC 路径（不支持）
-    if windows_path.starts_with("\\\\") {
-        log::warn!("[WSL] UNC paths are not supported (except WSL): {}", windows_path);
-        return windows_path.to_string();
+    let mut cmd = Command::new("wsl");
+    if let Some(d) = distro {
+        cmd.arg("-d").arg(d);
     }
+    cmd.args(["--", "wslpath", "-a", flag, path]);
+    cmd.creation_flags(CREATE_NO_WINDOW);
+
+    let converted = cmd
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        .filter(|converted| match flag {
+            "-u" => converted.starts_with('/'),
+            _ => !converted.is_empty() && !converted.starts_with('/'),
+        });
+    debug!(
+        "[WSL] wslpath {} (distro={:?}): {} -> {:?}",
+        flag, distro, path, converted
+    );
 
-    // 检查是否为标准 Windows 路径 (C:\...)
-    if windows_path.len() >= 2 && windows_path.chars().nth(1) == Some(':') {
-        let drive = windows_path
-            .chars()
-            .next()
-            .unwrap()
-            .to_lowercase()
-            .next()
-            .unwrap();
-        let rest = &windows_path[2..].replace('\\', "/");
-        let wsl_path = format!("/mnt/{}{}", drive, rest);
-        log::debug!("[WSL] Path converted: {} -> {}", windows_path, wsl_path);
-        return wsl_path;
+    if let Some(mut cache) = cache.and_then(|cache| cache.lock().ok()) {
+        cache.insert(cache_key, converted.clone());
     }
+    converted
+}
 
-    // 如果已经是 WSL 路径或相对路径，统一分隔符后返回
-    windows_path.replace('\\', "/")
+#[cfg(not(target_os = "windows"))]
+fn run_wslpath(_flag: &str, _path: &str, _distro: Option<&str>) -> Option<String> {
+    None
 }
 
-/// 将 Windows 路径转换为 WSL 路径（优先使用 wslpath，自动适配不同发行版的挂载策略）。
-///
-/// - 若输入是 \\wsl... UNC，则直接解析为 WSL 路径（同时可用于推断 distro）
-/// - 若输入是盘符路径（C:\\...），在 Windows 上尝试：wsl [-d <distro>] -- wslpath -a -u <path>
-/// - 失败则回退到 windows_to_wsl_path 的 /mnt/<drive> 规则
-#[cfg(target_os = "windows")]
-pub fn windows_to_wsl_path_with_distro(windows_path: &str, distro: Option<&str>) -> String {
-    if windows_path.trim().is_empty() {
-        return windows_path.to_string();
+/// windows_to_wsl_path 的实现，网络驱动器判断可注入以便测试
+fn convert_windows_to_wsl(
+    windows_path: &str,
+    is_network_drive: impl Fn(char) -> bool,
+) -> Result<String, String> {
+    let path = strip_verbatim_prefix(windows_path.trim());
+    if path.is_empty() {
+        return Err("Path is empty".to_string());
     }
 
     // 已是 WSL 路径
-    if windows_path.starts_with('/') {
-        return windows_path.to_string();
+    if path.starts_with('/') && !path.starts_with("//") {
+        return Ok(format!(
+            "/{}",
+            normalize_segments(path.split('/')).join("/")
+        ));
     }
 
-    // WSL UNC 路径
-    if let Some((_d, wsl_path)) = try_parse_wsl_unc_path(windows_path) {
-        return wsl_path;
+    // WSL UNC 路径（\\wsl$\... 或 \\wsl.localhost\...）
+    if let Some((_distro, wsl_path)) = try_parse_wsl_unc_path(&path) {
+        return Ok(wsl_path);
     }
 
-    // 盘符路径：尽量用 wslpath 来得到正确挂载点
-    if windows_path.len() >= 2 && windows_path.chars().nth(1) == Some(':') {
-        let mut cmd = Command::new("wsl");
-        if let Some(d) = distro {
-            cmd.arg("-d").arg(d);
+    // 其他 UNC 路径（网络共享）没有固定的挂载点，需用户以 drvfs 挂载后经 wslpath 解析
+    if path.starts_with("\\\\") || path.starts_with("//") {
+        return Err(format!(
+            "Network paths must be mounted in WSL (drvfs) before use: {}",
+            windows_path
+        ));
+    }
+
+    // 盘符路径 (C:\...)
+    let bytes = path.as_bytes();
+    if bytes.len() >= 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':' {
+        let drive = (bytes[0] as char).to_ascii_lowercase();
+        let rest = &path[2..];
+        if !rest.is_empty() && !rest.starts_with(['\\', '/']) {
+            return Err(format!(
+                "Drive-relative paths cannot be converted to WSL paths: {}",
+                windows_path
+            ));
+        }
+        if is_network_drive(drive) {
+            return Err(format!(
+                "Network drive {}: must be mounted in WSL (drvfs) before use: {}",
+                drive.to_ascii_uppercase(),
+                windows_path
+            ));
         }
-        cmd.arg("--");
-        cmd.arg("wslpath");
-        cmd.arg("-a");
-        cmd.arg("-u");
-        cmd.arg(windows_path);
-        cmd.creation_flags(CREATE_NO_WINDOW);
 
-        if let Ok(output) = cmd.output() {
-            if output.status.success() {
-                let wsl_path = String::from_utf8_lossy(&output.stdout).trim().to_string();
-                if !wsl_path.is_empty() && wsl_path.starts_with('/') {
-                    log::debug!(
-                        "[WSL] wslpath converted (distro={:?}): {} -> {}",
-                        distro,
-                        windows_path,
-                        wsl_path
-                    );
-                    return wsl_path;
-                }
-            }
+        let mut wsl_path = format!("/mnt/{}", drive);
+        for segment in normalize_segments(rest.split(['\\', '/'])) {
+            wsl_path.push('/');
+            wsl_path.push_str(segment);
         }
+        return Ok(wsl_path);
     }
 
-    windows_to_wsl_path(windows_path)
+    Err(format!(
+        "Relative paths cannot be converted to WSL paths: {}",
+        windows_path
+    ))
 }
 
-#[cfg(not(target_os = "windows"))]
-pub fn windows_to_wsl_path_with_distro(windows_path: &str, _distro: Option<&str>) -> String {
-    windows_to_wsl_path(windows_path)
+/// 将 Windows 路径转换为 WSL 路径
+///
+/// - 盘符路径（大小写均可）映射到 /mnt/<drive>，空格与非 ASCII 字符原样保留
+/// - \\wsl$\<distro>\... 与 \\wsl.localhost\<distro>\... 转为 WSL 内路径
+/// - 已是 WSL 路径时只做规范化
+/// - 相对路径、网络共享与映射的网络驱动器返回错误
+///
+/// # Examples
+/// ```
+/// assert_eq!(windows_to_wsl_path("C:\\Users\\test").unwrap(), "/mnt/c/Users/test");
+/// assert_eq!(windows_to_wsl_path(r"\\wsl$\Ubuntu\home\me").unwrap(), "/home/me");
+/// ```
+pub fn windows_to_wsl_path(windows_path: &str) -> Result<String, String> {
+    let wsl_path = convert_windows_to_wsl(windows_path, is_network_drive)?;
+    log::debug!("[WSL] Path converted: {} -> {}", windows_path, wsl_path);
+    Ok(wsl_path)
+}
+
+/// windows_to_wsl_path_with_distro 的实现，网络驱动器解析与 wslpath 调用可注入以便测试
+fn resolve_windows_to_wsl(
+    windows_path: &str,
+    network_drive_root: impl Fn(char) -> Option<String>,
+    wslpath: impl Fn(&str) -> Option<String>,
+) -> Result<String, String> {
+    let path = strip_verbatim_prefix(windows_path.trim());
+
+    // 映射的网络驱动器（Z:\...）换成它指向的 UNC 路径
+    let bytes = path.as_bytes();
+    let network_path = (bytes.len() >= 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':')
+        .then(|| network_drive_root(bytes[0].to_ascii_lowercase() as char))
+        .flatten()
+        .map(|root| {
+            let rest = path[2..].trim_start_matches(['\\', '/']).replace('/', "\\");
+            format!("{}\\{}", root.trim_end_matches('\\'), rest)
+        });
+
+    match convert_windows_to_wsl(&path, |_| network_path.is_some()) {
+        // 已是 WSL 路径或 WSL UNC 路径时无需 wslpath
+        Ok(wsl_path) if path.starts_with('/') || try_parse_wsl_unc_path(&path).is_some() => {
+            Ok(wsl_path)
+        }
+        // 盘符路径：优先用 wslpath 得到实际挂载点（automount root 不一定是 /mnt）
+        Ok(wsl_path) => Ok(wslpath(&path).unwrap_or(wsl_path)),
+        // 网络共享：以 drvfs 挂载后 wslpath 才能解析出挂载点
+        Err(e) if is_network_unc_path(&path) => wslpath(&path).ok_or(e),
+        Err(e) => match network_path {
+            Some(unc_path) => wslpath(&unc_path).ok_or(e),
+            None => Err(e),
+        },
+    }
+}
+
+/// 将 Windows 路径转换为 WSL 路径（优先使用 wslpath，自动适配不同发行版的挂载策略）。
+///
+/// - 若输入是 \\wsl... UNC，则直接解析为 WSL 路径（同时可用于推断 distro）
+/// - 若输入是盘符路径（C:\\...），在 Windows 上尝试：wsl [-d <distro>] -- wslpath -a -u <path>，
+///   失败则回退到 windows_to_wsl_path 的 /mnt/<drive> 规则
+/// - 网络共享（\\server\share\...）与映射的网络驱动器只能经 wslpath 解析为 drvfs 挂载点，未挂载时报错
+pub fn windows_to_wsl_path_with_distro(
+    windows_path: &str,
+    distro: Option<&str>,
+) -> Result<String, String> {
+    resolve_windows_to_wsl(windows_path, network_drive_unc_root, |path| {
+        run_wslpath("-u", path, distro)
+    })
 }
 
 /// 将 WSL 路径转换为 Windows 路径
 ///
+/// - /mnt/<drive>/... 转为盘符路径（盘符大写）
+/// - 其他 Linux 路径转为 \\wsl.localhost\<distro>\...，未提供 distro 时报错
+/// - 已是 Windows 路径（盘符或 UNC）时统一分隔符后返回
+/// - 相对路径返回错误
+///
 /// # Examples
 /// ```
-/// assert_eq!(wsl_to_windows_path("/mnt/c/Users/test"), "C:\\Users\\test");
-/// assert_eq!(wsl_to_windows_path("/home/user"), "/home/user"); // 无法转换
+/// assert_eq!(wsl_to_windows_path("/mnt/c/Users/test", None).unwrap(), "C:\\Users\\test");
+/// assert_eq!(
+///     wsl_to_windows_path("/home/user", Some("Ubuntu")).unwrap(),
+///     r"\\wsl.localhost\Ubuntu\home\user"
+/// );
 /// ```
-pub fn wsl_to_windows_path(wsl_path: &str) -> String {
-    if wsl_path.starts_with("/mnt/") && wsl_path.len() >= 6 {
-        let drive = wsl_path
-            .chars()
-            .nth(5)
-            .unwrap()
-            .to_uppercase()
-            .next()
-            .unwrap();
-        let mut rest = wsl_path[6..].replace('/', "\\");
-        if rest.is_empty() {
-            rest = "\\".to_string();
-        } else if !rest.starts_with('\\') {
-            rest = format!("\\{}", rest);
-        }
-        let windows_path = format!("{}:{}", drive, rest);
-        log::debug!("[WSL] Path converted: {} -> {}", wsl_path, windows_path);
-        return windows_path;
-    }
-
-    // 无法转换的路径（如 /home/user）原样返回
-    wsl_path.to_string()
+pub fn wsl_to_windows_path(wsl_path: &str, distro: Option<&str>) -> Result<String, String> {
+    convert_wsl_to_windows(wsl_path, distro, |path| run_wslpath("-w", path, distro))
+}
+
+/// wsl_to_windows_path 的实现，wslpath 调用可注入以便测试
+fn convert_wsl_to_windows(
+    wsl_path: &str,
+    distro: Option<&str>,
+    wslpath: impl Fn(&str) -> Option<String>,
+) -> Result<String, String> {
+    let path = strip_verbatim_prefix(wsl_path.trim());
+    if path.is_empty() {
+        return Err("Path is empty".to_string());
+    }
+
+    // 已是 Windows 路径
+    let bytes = path.as_bytes();
+    let is_drive_path = bytes.len() >= 3
+        && bytes[0].is_ascii_alphabetic()
+        && bytes[1] == b':'
+        && (bytes[2] == b'\\' || bytes[2] == b'/');
+    if is_drive_path || path.starts_with("\\\\") {
+        return Ok(path.replace('/', "\\"));
+    }
+
+    if !path.starts_with('/') {
+        return Err(format!(
+            "Relative paths cannot be converted to Windows paths: {}",
+            wsl_path
+        ));
+    }
+
+    let segments = normalize_segments(path.split('/'));
+
+    // /mnt/<drive>/...
+    if let ["mnt", drive, rest @ ..] = segments.as_slice() {
+        let mut chars = drive.chars();
+        if let (Some(letter), None) = (chars.next(), chars.next()) {
+            if letter.is_ascii_alphabetic() {
+                let windows_path = format!("{}:\\{}", letter.to_ascii_uppercase(), rest.join("\\"));
+                log::debug!("[WSL] Path converted: {} -> {}", wsl_path, windows_path);
+                return Ok(windows_path);
+            }
+        }
+
+        // 其他 /mnt 下的目录可能是以 drvfs 挂载的网络共享，由 wslpath 还原为 UNC 路径
+        if let Some(windows_path) = wslpath(&format!("/{}", segments.join("/"))) {
+            return Ok(windows_path);
+        }
+    }
+
+    // WSL 内部路径：通过 \\wsl.localhost\<distro> 访问
+    match distro.map(str::trim).filter(|d| !d.is_empty()) {
+        Some(distro) => Ok(format!(
+            r"\\wsl.localhost\{}\{}",
+            distro,
+            segments.join("\\")
+        )),
+        None => Err(format!(
+            "A WSL distro is required to convert {} to a Windows path",
+            wsl_path
+        )),
+    }
 }
 
 /// 构建从 Windows 访问 WSL 文件系统的 UNC 路径
@@ -1950,7 +2126,7 @@ pub fn build_wsl_command_async(
     args: &[String],
     working_dir: Option<&str>,
     distro: Option<&str>,
-) -> tokio::process::Command {
+) -> Result<tokio::process::Command, String> {
     let mut cmd = tokio::process::Command::new("wsl");
 
     // 如果 working_dir 是 \\wsl... UNC，则优先用其 distro（避免用户选择的目录在另一个发行版里）
@@ -1969,7 +2145,7 @@ pub fn build_wsl_command_async(
             } else {
                 (
                     distro.map(|d| d.to_string()),
-                    Some(windows_to_wsl_path_with_distro(dir, distro)),
+                    Some(windows_to_wsl_path_with_distro(dir, distro)?),
                 )
             }
         }
@@ -2006,7 +2182,7 @@ pub fn build_wsl_command_async(
         args
     );
 
-    cmd
+    Ok(cmd)
 }
 
 #[cfg(not(target_os = "windows"))]
@@ -2015,13 +2191,13 @@ pub fn build_wsl_command_async(
     args: &[String],
     _working_dir: Option<&str>,
     _distro: Option<&str>,
-) -> tokio::process::Command {
+) -> Result<tokio::process::Command, String> {
     // 非 Windows 平台直接执行命令
     let mut cmd = tokio::process::Command::new(program);
     for arg in args {
         cmd.arg(arg);
     }
-    cmd
+    Ok(cmd)
 }
 
 // ============================================================================
@@ -2034,31 +2210,174 @@ mod tests {
 
     #[test]
     fn test_windows_to_wsl_path() {
-        assert_eq!(windows_to_wsl_path("C:\\Users\\test"), "/mnt/c/Users/test");
-        assert_eq!(
-            windows_to_wsl_path("D:\\Projects\\app"),
-            "/mnt/d/Projects/app"
-        );
-        assert_eq!(windows_to_wsl_path("c:\\lower"), "/mnt/c/lower");
-        assert_eq!(windows_to_wsl_path("C:\\"), "/mnt/c/");
+        let no_network = |_: char| false;
+        let cases: &[(&str, &str)] = &[
+            (r"C:\Users\test", "/mnt/c/Users/test"),
+            (r"D:\Projects\app", "/mnt/d/Projects/app"),
+            (r"c:\lower", "/mnt/c/lower"),
+            ("C:/mixed/slashes\\path", "/mnt/c/mixed/slashes/path"),
+            (r"C:\", "/mnt/c"),
+            ("C:", "/mnt/c"),
+            (r"C:\Users\test\", "/mnt/c/Users/test"),
+            (r"C:\My Projects\demo app", "/mnt/c/My Projects/demo app"),
+            (r"D:\代码\项目 一", "/mnt/d/代码/项目 一"),
+            (r"C:\a\.\b\..\c", "/mnt/c/a/c"),
+            (r"\\?\C:\long\path", "/mnt/c/long/path"),
+            (r"\\wsl.localhost\Ubuntu\home\user\proj", "/home/user/proj"),
+            (r"\\wsl$\Debian\mnt\c\Users\me", "/mnt/c/Users/me"),
+            (
+                r"\\WSL$\Ubuntu\home\张三\my project",
+                "/home/张三/my project",
+            ),
+            (r"\\?\UNC\wsl.localhost\Ubuntu\home\user", "/home/user"),
+            ("//wsl.localhost/Ubuntu/srv", "/srv"),
+            (r"\\wsl$\Ubuntu", "/"),
+            ("/home/user//proj/", "/home/user/proj"),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(
+                convert_windows_to_wsl(input, no_network).as_deref(),
+                Ok(*expected),
+                "input: {}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn test_windows_to_wsl_path_errors() {
+        let cases: &[(&str, &str)] = &[
+            ("", "empty"),
+            ("relative\\path", "Relative"),
+            ("./proj", "Relative"),
+            ("C:relative", "Drive-relative"),
+            (r"\\server\share\proj", "Network paths"),
+            (r"\\?\UNC\server\share", "Network paths"),
+            (r"Z:\shared\proj", "Network drive Z:"),
+        ];
+        for (input, expected) in cases {
+            let err = convert_windows_to_wsl(input, |drive| drive == 'z').unwrap_err();
+            assert!(err.contains(expected), "input: {}, error: {}", input, err);
+        }
+    }
+
+    #[test]
+    fn test_wsl_to_windows_path() {
+        let cases: &[(&str, Option<&str>, &str)] = &[
+            ("/mnt/c/Users/test", None, r"C:\Users\test"),
+            ("/mnt/d/Projects", None, r"D:\Projects"),
+            ("/mnt/c", None, r"C:\"),
+            ("/mnt/c/", None, r"C:\"),
+            (
+                "/mnt/e/My Projects/中文 目录",
+                None,
+                r"E:\My Projects\中文 目录",
+            ),
+            (
+                "/home/user",
+                Some("Ubuntu"),
+                r"\\wsl.localhost\Ubuntu\home\user",
+            ),
+            (
+                "/home/张三/my project",
+                Some("Debian"),
+                r"\\wsl.localhost\Debian\home\张三\my project",
+            ),
+            (
+                "/mnt/wsl/shared",
+                Some("Ubuntu"),
+                r"\\wsl.localhost\Ubuntu\mnt\wsl\shared",
+            ),
+            ("/", Some("Ubuntu"), r"\\wsl.localhost\Ubuntu\"),
+            (r"C:\already\windows", None, r"C:\already\windows"),
+            (r"\\wsl$\Ubuntu\home", None, r"\\wsl$\Ubuntu\home"),
+        ];
+        for (input, distro, expected) in cases {
+            assert_eq!(
+                wsl_to_windows_path(input, *distro).as_deref(),
+                Ok(*expected),
+                "input: {}",
+                input
+            );
+        }
+
+        assert!(wsl_to_windows_path("/home/user", None).is_err());
+        assert!(wsl_to_windows_path("relative/path", Some("Ubuntu")).is_err());
+        assert!(wsl_to_windows_path("", None).is_err());
+    }
+
+    #[test]
+    fn test_path_round_trip() {
+        for path in [r"C:\Users\张三\My Project", r"D:\a b\c"] {
+            let wsl = convert_windows_to_wsl(path, |_| false).unwrap();
+            assert_eq!(wsl_to_windows_path(&wsl, None).unwrap(), path);
+        }
+        let wsl = "/home/user/项目 A";
+        let unc = wsl_to_windows_path(wsl, Some("Ubuntu")).unwrap();
+        assert_eq!(convert_windows_to_wsl(&unc, |_| false).unwrap(), wsl);
+    }
+
+    /// 模拟把 \\server\share 以 drvfs 挂载到 /mnt/share 的 wslpath
+    fn fake_wslpath_u(path: &str) -> Option<String> {
+        let rest = path.strip_prefix(r"\\server\share")?;
+        Some(format!("/mnt/share{}", rest.replace('\\', "/")))
+    }
+
+    fn fake_wslpath_w(path: &str) -> Option<String> {
+        let rest = path.strip_prefix("/mnt/share")?;
+        Some(format!(r"\\server\share{}", rest.replace('/', "\\")))
+    }
 
-        // WSL UNC paths
+    #[test]
+    fn test_path_round_trip_with_network_shares() {
+        for path in [
+            r"C:\Users\名前\проект",
+            r"D:\My Projects (new)\app",
+            r"\\server\share\repo",
+        ] {
+            let wsl = resolve_windows_to_wsl(path, |_| None, fake_wslpath_u).unwrap();
+            assert_eq!(
+                convert_wsl_to_windows(&wsl, Some("Ubuntu"), fake_wslpath_w).unwrap(),
+                path,
+                "wsl path: {}",
+                wsl
+            );
+        }
         assert_eq!(
-            windows_to_wsl_path(r"\\wsl.localhost\Ubuntu\home\user\proj"),
-            "/home/user/proj"
+            resolve_windows_to_wsl(r"D:\My Projects (new)\app", |_| None, |_| None).unwrap(),
+            "/mnt/d/My Projects (new)/app"
         );
         assert_eq!(
-            windows_to_wsl_path(r"\\wsl$\Debian\mnt\c\Users\me"),
-            "/mnt/c/Users/me"
+            resolve_windows_to_wsl(r"\\server\share\repo", |_| None, fake_wslpath_u).unwrap(),
+            "/mnt/share/repo"
         );
     }
 
     #[test]
-    fn test_wsl_to_windows_path() {
-        assert_eq!(wsl_to_windows_path("/mnt/c/Users/test"), "C:\\Users\\test");
-        assert_eq!(wsl_to_windows_path("/mnt/d/Projects"), "D:\\Projects");
-        assert_eq!(wsl_to_windows_path("/home/user"), "/home/user"); // 不转换
-        assert_eq!(wsl_to_windows_path("/mnt/c"), "C:\\"); // 边界情况
+    fn test_network_paths_resolve_through_wslpath() {
+        // 映射的网络驱动器先换成它指向的 UNC 路径
+        let z_drive = |drive: char| (drive == 'z').then(|| r"\\server\share\".to_string());
+        assert_eq!(
+            resolve_windows_to_wsl(r"Z:\repo\src", z_drive, fake_wslpath_u).unwrap(),
+            "/mnt/share/repo/src"
+        );
+
+        // 未挂载的共享保留原来的错误
+        let err =
+            resolve_windows_to_wsl(r"\\nas\dev\project", |_| None, fake_wslpath_u).unwrap_err();
+        assert!(err.contains("Network paths"), "{}", err);
+        let err = resolve_windows_to_wsl(r"Z:\repo", z_drive, |_| None).unwrap_err();
+        assert!(err.contains("Network drive Z:"), "{}", err);
+
+        // wslpath 不认识的 /mnt 目录按 WSL 内部路径处理，盘符挂载点不经过 wslpath
+        assert_eq!(
+            convert_wsl_to_windows("/mnt/wsl/shared", Some("Ubuntu"), fake_wslpath_w).unwrap(),
+            r"\\wsl.localhost\Ubuntu\mnt\wsl\shared"
+        );
+        assert_eq!(
+            convert_wsl_to_windows("/mnt/c/repo", None, |_| Some(r"\\other".into())).unwrap(),
+            r"C:\repo"
+        );
     }
 
     #[test]
@@ -11,6 +11,8 @@ use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 
+use crate::commands::gemini::settings::update_settings_file;
+
 /// 获取 Gemini 配置文件路径
 fn user_config_path() -> PathBuf {
     let home_dir = dirs::home_dir().expect("Failed to get home directory");
@@ -32,26 +34,6 @@ fn read_json_value(path: &Path) -> Result<Value, String> {
     Ok(value)
 }
 
-/// 原子写入 JSON 文件
-fn write_json_value(path: &Path, value: &Value) -> Result<(), String> {
-    if let Some(parent) = path.parent() {
-        fs::create_dir_all(parent)
-            .map_err(|e| format!("创建目录失败: {}", e))?;
-    }
-
-    let json = serde_json::to_string_pretty(value)
-        .map_err(|e| format!("序列化 JSON 失败: {}", e))?;
-
-    let tmp_path = path.with_extension("tmp");
-    fs::write(&tmp_path, json.as_bytes())
-        .map_err(|e| format!("写入临时文件失败: {}", e))?;
-
-    fs::rename(&tmp_path, path)
-        .map_err(|e| format!("重命名文件失败: {}", e))?;
-
-    Ok(())
-}
-
 /// 读取 Gemini settings.json 中的 mcpServers 映射
 ///
 /// 执行反向格式转换以保持与统一 MCP 结构的兼容性：
@@ -89,7 +71,6 @@ pub fn read_mcp_servers_map() -> Result<HashMap<String, Value>, String> {
 /// 仅覆盖 mcpServers，其他字段保持不变
 pub fn set_mcp_servers_map(servers: &HashMap<String, Value>) -> Result<(), String> {
     let path = user_config_path();
-    let mut root = read_json_value(&path)?;
 
     // 构建 mcpServers 对象
     let mut out: Map<String, Value> = Map::new();
@@ -132,13 +113,13 @@ pub fn set_mcp_servers_map(servers: &HashMap<String, Value>) -> Result<(), Strin
         out.insert(id.clone(), Value::Object(obj));
     }
 
-    {
+    // 读-改-写：只替换 mcpServers，其他字段保持不变，并防止与 Gemini CLI 的并发写入互相覆盖
+    update_settings_file(&path, |root| {
         let obj = root
             .as_object_mut()
             .ok_or_else(|| "配置文件根必须是对象".to_string())?;
-        obj.insert("mcpServers".into(), Value::Object(out));
-    }
-
-    write_json_value(&path, &root)?;
+        obj.insert("mcpServers".into(), Value::Object(out.clone()));
+        Ok(())
+    })?;
     Ok(())
 }
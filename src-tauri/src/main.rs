@@ -17,8 +17,20 @@ use claude_binary::init_shell_environment;
 use std::sync::{Arc, Mutex};
 
 use commands::acemcp::{
-    enhance_prompt_with_context, export_acemcp_sidecar, get_extracted_sidecar_path,
-    load_acemcp_config, preindex_project, save_acemcp_config, test_acemcp_availability,
+    cancel_preindex, enhance_prompt_with_context, enhance_prompt_with_multi_project_context,
+    export_acemcp_sidecar, get_acemcp_keyword_config, get_extracted_sidecar_path,
+    get_preindex_status, load_acemcp_config, preindex_project, preview_prompt_keywords,
+    reindex_changed_files, save_acemcp_config, save_acemcp_keyword_config,
+    test_acemcp_availability, watch_project_changes, AcemcpClientPool, PreindexState,
+};
+use commands::app_error::classify_execution_error;
+use commands::backup::{
+    backup_now, get_backup_config, list_backups, restore_from_backup, update_backup_config,
+};
+use commands::messages::set_backend_locale;
+use commands::encrypted_storage::{
+    disable_encrypted_storage, enable_encrypted_storage, get_encrypted_storage_status,
+    migrate_existing_archives_to_encrypted, unlock_encrypted_storage,
 };
 use commands::claude::{
     cancel_claude_execution, check_claude_version, clear_custom_claude_path, continue_claude_code,
@@ -27,8 +39,10 @@ use commands::claude::{
     get_claude_path, get_claude_permission_config, get_claude_session_output, get_claude_settings,
     get_codex_system_prompt, get_hooks_config, get_permission_presets, get_project_sessions,
     get_system_prompt, list_directory_contents, list_hidden_projects, list_projects,
-    list_running_claude_sessions, load_session_history, open_new_session, read_claude_md_file,
+    get_agent_messages, list_running_claude_sessions, list_session_agents, load_session_history,
+    open_new_session, read_claude_md_file, read_project_gitignore, repair_claude_session,
     reset_claude_execution_config, restore_project, resume_claude_code, save_claude_md_file,
+    save_project_gitignore,
     save_claude_settings, save_codex_system_prompt, save_system_prompt, search_files,
     set_custom_claude_path, update_claude_execution_config, update_claude_permission_config,
     update_hooks_config, update_thinking_mode, validate_hook_command, validate_permission_config,
@@ -48,30 +62,75 @@ use commands::mcp::{
     mcp_get_engine_servers, mcp_upsert_engine_server, mcp_delete_engine_server,
     mcp_toggle_engine_server, mcp_get_engine_servers_with_status,
 };
+use commands::mcp_calls::get_session_mcp_summary;
+use commands::tool_timing::get_session_slow_operations;
 use commands::storage::{init_database, AgentDb};
 
 use commands::clipboard::{read_from_clipboard, save_clipboard_image, write_to_clipboard};
 use commands::prompt_tracker::{
-    check_rewind_capabilities, get_prompt_list, get_unified_prompt_list, mark_prompt_completed,
-    record_prompt_sent, revert_to_prompt,
+    check_rewind_capabilities, get_prompt_filter_config, get_prompt_list, get_unified_prompt_list,
+    mark_prompt_completed, reclassify_prompt_sources, record_prompt_sent, remove_prompt_range,
+    revert_single_prompt, revert_single_prompt_code, revert_to_prompt, update_prompt_filter_config,
 };
 use commands::provider::{
     add_provider_config, clear_provider_config, delete_provider_config,
     get_current_provider_config, get_provider_config, get_provider_presets, query_provider_usage,
     reorder_provider_configs, switch_provider_config, test_provider_connection, update_provider_config,
 };
-use commands::simple_git::{check_and_init_git, check_reset_safety, precise_revert_code};
+use commands::provider_presets::{
+    get_provider_presets_config, get_provider_presets_status, refresh_provider_presets,
+    update_provider_presets_config,
+};
+use commands::response_alternatives::{
+    adopt_alternative, get_response_alternatives, regenerate_response,
+};
+use commands::project_workspace::{get_project_workspace_state, save_project_workspace_state};
+use commands::session_alias::resolve_session_id;
+use commands::url_context::{fetch_url_context, get_url_fetch_config, update_url_fetch_config};
+use commands::prompt_file_snapshots::{diff_snapshot_with_current, get_prompt_file_snapshots};
+use commands::session_annotations::{get_prompt_enhancement_info, get_session_annotation};
+use commands::session_blobs::{get_message_blob, optimize_session_storage};
+use commands::session_utils::{
+    get_session_reader_config, list_sessions_by_size, resolve_session_path,
+    update_session_reader_config, validate_session_project,
+};
+use commands::simple_git::{
+    abort_revert, check_and_init_git, check_reset_safety, get_revert_conflict, precise_revert_code,
+    resolve_revert_conflict,
+};
 use commands::storage::{
     storage_analyze_query, storage_delete_row, storage_execute_sql, storage_get_performance_stats,
     storage_insert_row, storage_list_tables, storage_read_table, storage_reset_database,
     storage_update_row,
 };
+use commands::stream_stall::{get_stream_stall_config, update_stream_stall_config};
+use commands::migrations::{get_migration_status, run_pending_migrations};
+use commands::power_inhibit::{
+    get_power_inhibit_config, get_power_inhibition_status, update_power_inhibit_config,
+};
+use commands::execution_trace::get_trace_timeline;
+use commands::rate_limiter::{
+    get_provider_rate_limits, get_rate_limiter_status, update_provider_rate_limits,
+};
+use commands::task_actions::{
+    clear_task_action_history, delete_task_action_rule, get_task_action_history,
+    get_task_action_rules, reorder_task_action_rules, save_task_action_rule,
+};
+use commands::task_resources::{
+    get_task_resource_config, kill_task_subprocess, list_running_ai_tasks,
+    update_task_resource_config,
+};
+use commands::undo::{get_undo_stack, undo_last_operation};
 use commands::translator::{
     clear_translation_cache, detect_text_language, get_translation_cache_stats,
     get_translation_config, init_translation_service_command, translate, translate_batch,
     update_translation_config,
 };
-use commands::usage::{get_session_stats, get_usage_by_date_range, get_usage_stats};
+use commands::usage::{
+    export_usage_csv, get_session_stats, get_usage_by_date_range, get_usage_stats,
+};
+use commands::usage_report::export_usage_report;
+use commands::cost_comparison::compare_model_cost;
 use commands::window::{
     broadcast_to_session_windows, close_session_window, create_session_window, emit_to_window,
     focus_session_window, list_session_windows, set_titlebar_theme,
@@ -80,6 +139,7 @@ use commands::window::{
 use commands::codex::{
     add_codex_provider_config,
     cancel_codex,
+    cancel_conversion,
     check_codex_availability,
     check_codex_rewind_capabilities,
     clear_codex_provider_config,
@@ -88,18 +148,30 @@ use commands::codex::{
     convert_codex_to_claude,
     // Session conversion
     convert_session,
+    convert_sessions_batch,
+    // Codex profiles
+    create_codex_profile,
+    delete_codex_profile,
     delete_codex_provider_config,
     delete_codex_session,
+    estimate_codex_context,
     execute_codex,
+    export_reasoning,
+    export_session_markdown,
+    export_session_to_markdown,
     // Codex mode configuration
+    // Codex config overrides
+    get_codex_capabilities,
     get_codex_mode_config,
     get_codex_path,
+    get_codex_profiles,
     get_codex_prompt_list,
     // Codex provider management
     get_codex_provider_presets,
     // Codex usage statistics
     get_codex_usage_stats,
     get_current_codex_config,
+    import_session_file,
     list_codex_sessions,
     load_codex_session_history,
     record_codex_prompt_completed,
@@ -108,11 +180,13 @@ use commands::codex::{
     reorder_codex_provider_configs,
     resume_codex,
     resume_last_codex,
+    revert_codex_single_prompt_code,
     revert_codex_to_prompt,
     set_codex_mode_config,
     set_custom_codex_path,
     switch_codex_provider,
     test_codex_provider_connection,
+    update_codex_profile,
     update_codex_provider_config,
     update_codex_reasoning_level,
     validate_codex_path_cmd,
@@ -122,10 +196,12 @@ use commands::enhanced_hooks::{
     execute_pre_commit_review, test_hook_condition, trigger_hook_event,
 };
 use commands::extensions::{
-    create_skill, create_subagent, list_agent_skills, list_custom_slash_commands,
-    list_gemini_custom_slash_commands, list_plugins, list_subagents, open_agents_directory,
+    create_skill, create_skill_from_template, create_subagent, create_subagent_from_template,
+    delete_custom_slash_command, delete_extension_template, list_agent_skills,
+    list_agent_templates, list_custom_slash_commands, list_gemini_custom_slash_commands,
+    list_plugins, list_skill_templates, list_subagents, open_agents_directory,
     open_commands_directory, open_plugins_directory, open_skills_directory, read_skill,
-    read_subagent,
+    read_subagent, save_agent_as_template,
 };
 use commands::file_operations::{open_directory_in_explorer, open_file_with_default_app};
 use commands::gemini::{
@@ -133,12 +209,16 @@ use commands::gemini::{
     cancel_gemini,
     check_gemini_installed,
     check_gemini_rewind_capabilities,
+    // Gemini Session Retention
+    cleanup_gemini_sessions,
     clear_gemini_provider_config,
     delete_gemini_provider_config,
     delete_gemini_session,
     execute_gemini,
     get_current_gemini_provider_config,
     get_gemini_config,
+    // Gemini context files
+    get_gemini_context_files,
     get_gemini_models,
     // Gemini Rewind commands
     get_gemini_prompt_list,
@@ -146,19 +226,26 @@ use commands::gemini::{
     get_gemini_provider_presets,
     get_gemini_session_detail,
     get_gemini_session_logs,
+    get_gemini_session_marks,
     get_gemini_system_prompt,
     // Gemini Usage Statistics
     get_gemini_usage_stats,
     // Gemini WSL commands
     get_gemini_wsl_mode_config,
     list_gemini_sessions,
+    list_gemini_trash,
+    preview_gemini_context_files,
     record_gemini_prompt_completed,
     record_gemini_prompt_sent,
     reorder_gemini_provider_configs,
+    restore_gemini_trash_session,
     revert_gemini_to_prompt,
+    save_gemini_context_files,
     save_gemini_system_prompt,
+    set_gemini_session_mark,
     set_gemini_wsl_mode_config,
     switch_gemini_provider,
+    test_all_gemini_providers,
     test_gemini_provider_connection,
     update_gemini_config,
     update_gemini_provider_config,
@@ -170,8 +257,8 @@ use tauri::{Manager, WindowEvent};
 use tauri_plugin_window_state::Builder as WindowStatePlugin;
 
 fn main() {
-    // Initialize logger
-    env_logger::init();
+    // Initialize logger (stderr + rotating log file with secrets redacted)
+    utils::log_file::init_logging();
 
     tauri::Builder::default()
         .plugin(tauri_plugin_dialog::init())
@@ -182,6 +269,7 @@ fn main() {
         .plugin(tauri_plugin_updater::Builder::new().build())
         .plugin(tauri_plugin_process::init())
         .plugin(tauri_plugin_clipboard_manager::init())
+        .plugin(tauri_plugin_notification::init())
         .plugin(
             WindowStatePlugin::default()
                 .with_state_flags(tauri_plugin_window_state::StateFlags::all())
@@ -192,6 +280,9 @@ fn main() {
             // This must be done early to ensure CLI tools (claude, codex, etc.) can be found
             init_shell_environment();
 
+            // Bring local data up to date before any service reads it
+            commands::migrations::run_startup_migrations();
+
             // Initialize database for storage operations
             let conn = init_database(&app.handle()).expect("Failed to initialize database");
             app.manage(AgentDb(Mutex::new(conn)));
@@ -208,6 +299,12 @@ fn main() {
             // Initialize Gemini process state
             app.manage(GeminiProcessState::default());
 
+            // Initialize acemcp pre-indexing state
+            app.manage(PreindexState::default());
+
+            // Initialize the pooled acemcp sidecar client
+            app.manage(AcemcpClientPool::default());
+
             // Initialize auto-compact manager for context management
             let auto_compact_manager =
                 Arc::new(commands::context_manager::AutoCompactManager::new());
@@ -233,6 +330,31 @@ fn main() {
                 commands::translator::init_translation_service_with_saved_config().await;
             });
 
+            // Clean up old Gemini sessions if enabled in the retention policy
+            let app_handle_for_gemini_cleanup = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                commands::gemini::retention::run_startup_cleanup(app_handle_for_gemini_cleanup)
+                    .await;
+            });
+
+            // Remove expired execution trace directories
+            tauri::async_runtime::spawn_blocking(commands::execution_trace::run_startup_cleanup);
+
+            // Provider rate limiter emits 429 events through the app handle
+            commands::rate_limiter::init(app.handle().clone());
+
+            // Scheduled backup of sessions and app data
+            let app_handle_for_backup = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                commands::backup::run_backup_scheduler(app_handle_for_backup).await;
+            });
+
+            // Sample CPU / memory of running Codex and Gemini process trees
+            let app_handle_for_resources = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                commands::task_resources::run_resource_monitor(app_handle_for_resources).await;
+            });
+
             // Fallback window show mechanism for macOS
             // In case frontend JS fails to execute window.show()
             if let Some(main_window) = app.get_webview_window("main") {
@@ -307,7 +429,12 @@ fn main() {
             find_claude_md_files,
             read_claude_md_file,
             save_claude_md_file,
+            read_project_gitignore,
+            save_project_gitignore,
             load_session_history,
+            repair_claude_session,
+            list_session_agents,
+            get_agent_messages,
             execute_claude_code,
             continue_claude_code,
             resume_claude_code,
@@ -336,10 +463,18 @@ fn main() {
             set_claude_wsl_mode_config,
             // Acemcp Integration
             enhance_prompt_with_context,
+            enhance_prompt_with_multi_project_context,
             test_acemcp_availability,
+            preview_prompt_keywords,
             save_acemcp_config,
             load_acemcp_config,
+            get_acemcp_keyword_config,
+            save_acemcp_keyword_config,
             preindex_project,
+            cancel_preindex,
+            get_preindex_status,
+            reindex_changed_files,
+            watch_project_changes,
             export_acemcp_sidecar,
             get_extracted_sidecar_path,
             // Enhanced Hooks Automation
@@ -350,6 +485,9 @@ fn main() {
             get_usage_stats,
             get_usage_by_date_range,
             get_session_stats,
+            compare_model_cost,
+            export_usage_report,
+            export_usage_csv,
             // MCP (Model Context Protocol)
             mcp_add,
             mcp_list,
@@ -380,6 +518,8 @@ fn main() {
             mcp_delete_engine_server,
             mcp_toggle_engine_server,
             mcp_get_engine_servers_with_status,
+            get_session_mcp_summary,
+            get_session_slow_operations,
             // Storage Management
             storage_list_tables,
             storage_read_table,
@@ -406,6 +546,11 @@ fn main() {
             get_provider_config,
             query_provider_usage,
             reorder_provider_configs,
+            // Remote Provider Presets
+            get_provider_presets_config,
+            update_provider_presets_config,
+            refresh_provider_presets,
+            get_provider_presets_status,
             // Translation
             translate,
             translate_batch,
@@ -432,12 +577,21 @@ fn main() {
             check_and_init_git,
             check_reset_safety,
             precise_revert_code,
+            get_revert_conflict,
+            resolve_revert_conflict,
+            abort_revert,
             record_prompt_sent,
             mark_prompt_completed,
             revert_to_prompt,
+            remove_prompt_range,
+            revert_single_prompt,
+            revert_single_prompt_code,
             get_prompt_list,
             get_unified_prompt_list,
             check_rewind_capabilities,
+            reclassify_prompt_sources,
+            get_prompt_filter_config,
+            update_prompt_filter_config,
             // Claude Extensions (Plugins, Subagents, Skills & Custom Commands)
             list_plugins,
             list_subagents,
@@ -448,6 +602,13 @@ fn main() {
             read_skill,
             create_subagent,
             create_skill,
+            list_agent_templates,
+            list_skill_templates,
+            create_subagent_from_template,
+            create_skill_from_template,
+            save_agent_as_template,
+            delete_extension_template,
+            delete_custom_slash_command,
             open_plugins_directory,
             open_agents_directory,
             open_skills_directory,
@@ -462,6 +623,7 @@ fn main() {
             execute_codex,
             resume_codex,
             resume_last_codex,
+            estimate_codex_context,
             cancel_codex,
             list_codex_sessions,
             delete_codex_session,
@@ -476,6 +638,7 @@ fn main() {
             record_codex_prompt_sent,
             record_codex_prompt_completed,
             revert_codex_to_prompt,
+            revert_codex_single_prompt_code,
             // Codex custom path
             validate_codex_path_cmd,
             set_custom_codex_path,
@@ -492,12 +655,150 @@ fn main() {
             test_codex_provider_connection,
             update_codex_reasoning_level,
             reorder_codex_provider_configs,
+            // Codex Profiles
+            get_codex_profiles,
+            create_codex_profile,
+            update_codex_profile,
+            delete_codex_profile,
+            // Codex Config Overrides
+            get_codex_capabilities,
             // Codex Usage Statistics
             get_codex_usage_stats,
             // Session Conversion (Claude ↔ Codex)
             convert_session,
+            convert_sessions_batch,
             convert_claude_to_codex,
             convert_codex_to_claude,
+            cancel_conversion,
+            import_session_file,
+            export_reasoning,
+            export_session_to_markdown,
+            export_session_markdown,
+            // Cross-engine Session Utilities
+            validate_session_project,
+            resolve_session_path,
+            list_sessions_by_size,
+            get_session_reader_config,
+            update_session_reader_config,
+            get_session_annotation,
+            get_prompt_enhancement_info,
+            resolve_session_id,
+            // Session Blob Storage
+            optimize_session_storage,
+            get_message_blob,
+            // Prompt File Snapshots
+            get_prompt_file_snapshots,
+            diff_snapshot_with_current,
+            // URL Prefetch
+            fetch_url_context,
+            get_url_fetch_config,
+            update_url_fetch_config,
+            // Response Alternatives
+            regenerate_response,
+            get_response_alternatives,
+            adopt_alternative,
+            // Error Codes & Backend Messages
+            set_backend_locale,
+            classify_execution_error,
+            // Encrypted Storage
+            get_encrypted_storage_status,
+            enable_encrypted_storage,
+            disable_encrypted_storage,
+            unlock_encrypted_storage,
+            migrate_existing_archives_to_encrypted,
+            // Running Task Interjection
+            interject_running_task,
+            list_interjections,
+            cancel_interjection,
+            // Code Block Extraction
+            extract_code_blocks,
+            save_code_blocks,
+            // Diagnostics
+            collect_diagnostics,
+            // Binary Detection
+            get_binary_detection_settings,
+            save_binary_detection_settings,
+            list_binary_candidates,
+            invalidate_binary_cache,
+            // Log Management
+            set_log_level,
+            get_log_file_info,
+            open_log_directory,
+            export_diagnostic_bundle,
+            // Effective Config Preview
+            get_effective_config,
+            // Prompt History
+            get_prompt_history,
+            resend_prompt,
+            // Prompt Batches
+            import_prompt_batch,
+            run_prompt_batch,
+            pause_prompt_batch,
+            cancel_prompt_batch,
+            get_batch_status,
+            list_prompt_batches,
+            // Project Memory
+            add_memory_entry,
+            list_memory_entries,
+            delete_memory_entry,
+            compile_memory_context,
+            extract_memory_from_session,
+            // Project AI Setup
+            list_claude_md_templates,
+            init_project_ai_setup,
+            // Project Workspace State
+            save_project_workspace_state,
+            get_project_workspace_state,
+            // Protected Paths
+            get_protected_paths,
+            save_project_protected_paths,
+            get_protected_paths_settings,
+            save_protected_paths_settings,
+            revert_protected_changes,
+            // Cross-engine Review
+            cross_engine_review,
+            get_cross_engine_review_config,
+            update_cross_engine_review_config,
+            // Execution Retry
+            get_execution_retry_config,
+            update_execution_retry_config,
+            // Stream Stall Recovery
+            get_stream_stall_config,
+            update_stream_stall_config,
+            // Data Migrations
+            get_migration_status,
+            run_pending_migrations,
+            // Power Inhibit
+            get_power_inhibit_config,
+            update_power_inhibit_config,
+            get_power_inhibition_status,
+            // Provider Rate Limits
+            get_provider_rate_limits,
+            update_provider_rate_limits,
+            get_rate_limiter_status,
+            // Scheduled Backup
+            get_backup_config,
+            update_backup_config,
+            backup_now,
+            list_backups,
+            restore_from_backup,
+            // Execution Trace
+            get_trace_timeline,
+            // Task Completion Actions
+            get_task_action_rules,
+            save_task_action_rule,
+            delete_task_action_rule,
+            reorder_task_action_rules,
+            get_task_action_history,
+            clear_task_action_history,
+            // Task Resource Monitor
+            list_running_ai_tasks,
+            kill_task_subprocess,
+            get_task_resource_config,
+            update_task_resource_config,
+            // Undo Stack
+            get_undo_stack,
+            undo_last_operation,
             // Window Management (Multi-window support)
             create_session_window,
             close_session_window,
@@ -518,9 +819,19 @@ fn main() {
             list_gemini_sessions,
             get_gemini_session_detail,
             delete_gemini_session,
+            // Gemini Session Retention
+            cleanup_gemini_sessions,
+            get_gemini_session_marks,
+            set_gemini_session_mark,
+            list_gemini_trash,
+            restore_gemini_trash_session,
             // Gemini System Prompt
             get_gemini_system_prompt,
             save_gemini_system_prompt,
+            // Gemini Context Files
+            get_gemini_context_files,
+            save_gemini_context_files,
+            preview_gemini_context_files,
             // Gemini Rewind Commands
             get_gemini_prompt_list,
             check_gemini_rewind_capabilities,
@@ -536,6 +847,7 @@ fn main() {
             delete_gemini_provider_config,
             clear_gemini_provider_config,
             test_gemini_provider_connection,
+            test_all_gemini_providers,
             reorder_gemini_provider_configs,
             // Gemini WSL Commands
             get_gemini_wsl_mode_config,
@@ -543,6 +855,13 @@ fn main() {
             // Gemini Usage Statistics
             get_gemini_usage_stats,
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| {
+            // 退出时关闭共享的 acemcp sidecar，并写入尚未落盘的项目工作状态
+            if let tauri::RunEvent::Exit = event {
+                tauri::async_runtime::block_on(commands::acemcp::shutdown_shared_client(app));
+                commands::project_workspace::flush_pending_states();
+            }
+        });
 }
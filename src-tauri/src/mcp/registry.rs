@@ -4,8 +4,10 @@
 //! 这解决了禁用工具后刷新页面导致工具消失的问题。
 //!
 //! ## 存储位置
-//! - Windows: %USERPROFILE%\.claude-workbench\mcp-registry.json
-//! - macOS/Linux: ~/.claude-workbench/mcp-registry.json
+//! - Windows: %USERPROFILE%\.anycode\mcp-registry.json
+//! - macOS/Linux: ~/.anycode/mcp-registry.json
+//!
+//! 旧版存放在 ~/.claude-workbench，由启动迁移移到新位置（见 commands::migrations）
 //!
 //! ## 数据结构
 //! ```json
@@ -27,6 +29,8 @@ use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 
+use crate::utils::config_utils::ConfigPathBuilder;
+
 /// 注册表中的服务器条目
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RegistryEntry {
@@ -50,8 +54,9 @@ pub struct McpRegistry {
 
 /// 获取注册表文件路径
 fn registry_path() -> PathBuf {
-    let home_dir = dirs::home_dir().expect("Failed to get home directory");
-    home_dir.join(".claude-workbench").join("mcp-registry.json")
+    ConfigPathBuilder::from_home_subdir(".anycode")
+        .expect("Failed to get home directory")
+        .build("mcp-registry.json")
 }
 
 /// 确保注册表目录存在
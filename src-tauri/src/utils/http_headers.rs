@@ -0,0 +1,125 @@
+//! 供应商自定义请求头工具
+//!
+//! 自建网关通常要求除 API key 之外的额外鉴权 header（如 X-Org-Id）。
+//! 这里统一处理 header 名/值的合法性校验、日志打码以及附加到 reqwest 请求上。
+
+use std::collections::HashMap;
+
+/// 不允许通过自定义 header 覆盖的请求头（由连接逻辑或 HTTP 层自行管理）
+const RESERVED_HEADERS: &[&str] = &["host", "content-length", "transfer-encoding", "connection"];
+
+/// header 名是否为合法的 token（RFC 9110 tchar 字符集）
+fn is_valid_header_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.bytes().all(|b| {
+            b.is_ascii_alphanumeric()
+                || matches!(
+                    b,
+                    b'!' | b'#'
+                        | b'$'
+                        | b'%'
+                        | b'&'
+                        | b'\''
+                        | b'*'
+                        | b'+'
+                        | b'-'
+                        | b'.'
+                        | b'^'
+                        | b'_'
+                        | b'`'
+                        | b'|'
+                        | b'~'
+                )
+        })
+}
+
+/// header 值是否合法（可见 ASCII、空格与制表符，不允许换行）
+fn is_valid_header_value(value: &str) -> bool {
+    value
+        .bytes()
+        .all(|b| b == b'\t' || b == b' ' || b.is_ascii_graphic())
+}
+
+/// 校验自定义请求头
+pub fn validate_custom_headers(headers: &HashMap<String, String>) -> Result<(), String> {
+    for (name, value) in headers {
+        if !is_valid_header_name(name) {
+            return Err(format!(
+                "Invalid header name '{}': only token characters are allowed",
+                name
+            ));
+        }
+        if RESERVED_HEADERS.contains(&name.to_ascii_lowercase().as_str()) {
+            return Err(format!("Header '{}' cannot be customized", name));
+        }
+        if !is_valid_header_value(value) {
+            return Err(format!("Invalid value for header '{}'", name));
+        }
+    }
+    Ok(())
+}
+
+/// 对 header 值打码：仅保留前 2 个字符
+pub fn mask_header_value(value: &str) -> String {
+    let visible: String = value.chars().take(2).collect();
+    if value.chars().count() <= 4 {
+        "****".to_string()
+    } else {
+        format!("{}****", visible)
+    }
+}
+
+/// 返回打码后的 header 表（用于日志与导出）
+pub fn masked_headers(headers: &HashMap<String, String>) -> HashMap<String, String> {
+    headers
+        .iter()
+        .map(|(name, value)| (name.clone(), mask_header_value(value)))
+        .collect()
+}
+
+/// 将自定义请求头附加到请求上
+pub fn apply_custom_headers(
+    mut request: reqwest::RequestBuilder,
+    headers: Option<&HashMap<String, String>>,
+) -> Result<reqwest::RequestBuilder, String> {
+    if let Some(headers) = headers {
+        validate_custom_headers(headers)?;
+        log::debug!("Applying custom headers: {:?}", masked_headers(headers));
+        for (name, value) in headers {
+            request = request.header(name.as_str(), value.as_str());
+        }
+    }
+    Ok(request)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn accepts_gateway_headers() {
+        let h = headers(&[("X-Org-Id", "org-123"), ("X-Request-Source", "any code")]);
+        assert!(validate_custom_headers(&h).is_ok());
+    }
+
+    #[test]
+    fn rejects_invalid_names_values_and_reserved_headers() {
+        assert!(validate_custom_headers(&headers(&[("X Org", "1")])).is_err());
+        assert!(validate_custom_headers(&headers(&[("X-Org:", "1")])).is_err());
+        assert!(validate_custom_headers(&headers(&[("X-Org", "a\r\nb")])).is_err());
+        assert!(validate_custom_headers(&headers(&[("Host", "evil")])).is_err());
+    }
+
+    #[test]
+    fn masks_values() {
+        assert_eq!(mask_header_value("org-123456"), "or****");
+        assert_eq!(mask_header_value("abc"), "****");
+    }
+}
@@ -0,0 +1,384 @@
+//! JSONL 会话文件流式读取工具
+//!
+//! 会话文件可能因为超大的工具输出而达到数百 MB，一次性 `read_to_string`
+//! 再解析会同时持有原文与解析结果，容易耗尽内存。本模块提供：
+//!
+//! - `for_each_line`: 基于 `BufReader` 的逐行读取，只复用一个行缓冲区
+//! - `for_each_json_line`: 逐行解析 JSON，单行损坏时跳过并记录到 `CorruptedLine` 列表
+//! - `repair_jsonl`: 去除无法解析的行，供会话修复使用
+//! - `read_to_string_lossy`: 整体读取并对非法 UTF-8 做有损解码
+//! - `normalize_line_endings`: 改写会话前将 CRLF 行尾统一为 LF
+//! - `ensure_loadable_in_memory`: 需要整体加载到内存前的大小守卫
+//! - `SessionReaderConfig`: 内存加载上限配置（~/.anycode/session_reader.json）
+//!
+//! # 使用示例
+//!
+//! ```ignore
+//! use std::ops::ControlFlow;
+//! use crate::utils::jsonl_reader::for_each_line;
+//!
+//! for_each_line(&path, |line_idx, line| {
+//!     println!("{}: {}", line_idx, line);
+//!     ControlFlow::Continue(())
+//! })?;
+//! ```
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{BufRead, BufReader};
+use std::ops::ControlFlow;
+use std::path::{Path, PathBuf};
+
+use super::config_utils::{load_json_config, save_json_config, ConfigPathBuilder};
+
+/// 默认的内存加载上限（MB）
+const DEFAULT_MAX_IN_MEMORY_MB: u64 = 100;
+
+/// 会话文件读取配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionReaderConfig {
+    /// 允许整体加载到内存的最大文件大小（MB），超过则拒绝加载
+    #[serde(default = "default_max_in_memory_mb")]
+    pub max_in_memory_mb: u64,
+}
+
+fn default_max_in_memory_mb() -> u64 {
+    DEFAULT_MAX_IN_MEMORY_MB
+}
+
+impl Default for SessionReaderConfig {
+    fn default() -> Self {
+        Self {
+            max_in_memory_mb: DEFAULT_MAX_IN_MEMORY_MB,
+        }
+    }
+}
+
+impl SessionReaderConfig {
+    /// 上限（字节）
+    pub fn max_in_memory_bytes(&self) -> u64 {
+        self.max_in_memory_mb.saturating_mul(1024 * 1024)
+    }
+}
+
+/// 配置文件路径：~/.anycode/session_reader.json
+pub(crate) fn get_session_reader_config_path() -> Result<PathBuf, String> {
+    Ok(ConfigPathBuilder::from_home_subdir(".anycode")?.build("session_reader.json"))
+}
+
+/// 加载会话读取配置（不存在时返回默认值）
+pub fn load_session_reader_config() -> SessionReaderConfig {
+    get_session_reader_config_path()
+        .and_then(|path| load_json_config(&path))
+        .unwrap_or_else(|e| {
+            log::warn!("Failed to load session reader config, using default: {}", e);
+            SessionReaderConfig::default()
+        })
+}
+
+/// 保存会话读取配置
+pub fn save_session_reader_config(config: &SessionReaderConfig) -> Result<(), String> {
+    let path = get_session_reader_config_path()?;
+    save_json_config(config, &path)
+}
+
+/// 会话文件中一行的损坏类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CorruptionKind {
+    /// JSON 语法错误（例如写到一半的行）
+    InvalidJson,
+    /// 非法 UTF-8
+    InvalidUtf8,
+}
+
+/// 解析失败（或经有损解码才成功）的行
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CorruptedLine {
+    /// 行号（从 1 开始，与编辑器一致）
+    pub line: usize,
+    pub kind: CorruptionKind,
+    /// 有损解码后仍能解析，内容已保留（其余损坏行被跳过）
+    #[serde(default)]
+    pub recovered: bool,
+}
+
+/// 逐行读取原始字节（行尾的 `\n` / `\r\n` 已去除），行号从 0 开始
+pub fn for_each_raw_line<P, F>(path: P, mut f: F) -> std::io::Result<()>
+where
+    P: AsRef<Path>,
+    F: FnMut(usize, &[u8]) -> ControlFlow<()>,
+{
+    let file = fs::File::open(path.as_ref())?;
+    let mut reader = BufReader::new(file);
+    let mut buf: Vec<u8> = Vec::new();
+    let mut line_idx = 0;
+
+    loop {
+        buf.clear();
+        if reader.read_until(b'\n', &mut buf)? == 0 {
+            break;
+        }
+
+        if buf.last() == Some(&b'\n') {
+            buf.pop();
+            if buf.last() == Some(&b'\r') {
+                buf.pop();
+            }
+        }
+
+        if let ControlFlow::Break(()) = f(line_idx, &buf) {
+            break;
+        }
+        line_idx += 1;
+    }
+
+    Ok(())
+}
+
+/// 逐行读取 JSONL 文件
+///
+/// - 回调参数为 (行号, 行内容)，行号从 0 开始且包含空行，与 `str::lines().enumerate()` 一致
+/// - 行尾的 `\n` / `\r\n` 会被去除
+/// - 非 UTF-8 内容按有损方式解码，不会中断读取
+/// - 回调返回 `ControlFlow::Break(())` 时提前结束
+pub fn for_each_line<P, F>(path: P, mut f: F) -> std::io::Result<()>
+where
+    P: AsRef<Path>,
+    F: FnMut(usize, &str) -> ControlFlow<()>,
+{
+    for_each_raw_line(path, |line_idx, bytes| {
+        f(line_idx, &String::from_utf8_lossy(bytes))
+    })
+}
+
+/// 解析一行 JSON
+///
+/// 空行返回 `(None, None)`；非法 UTF-8 先按有损解码再尝试解析，
+/// 无论成功与否都记为 `InvalidUtf8`，以便区分于单纯的 JSON 语法错误
+pub fn parse_json_line<T: DeserializeOwned>(
+    line_idx: usize,
+    bytes: &[u8],
+) -> (Option<T>, Option<CorruptedLine>) {
+    if bytes.iter().all(u8::is_ascii_whitespace) {
+        return (None, None);
+    }
+    let corrupted = |kind, recovered| CorruptedLine {
+        line: line_idx + 1,
+        kind,
+        recovered,
+    };
+
+    match std::str::from_utf8(bytes) {
+        Ok(line) => match serde_json::from_str(line) {
+            Ok(value) => (Some(value), None),
+            Err(_) => (None, Some(corrupted(CorruptionKind::InvalidJson, false))),
+        },
+        Err(_) => match serde_json::from_str(&String::from_utf8_lossy(bytes)) {
+            Ok(value) => (
+                Some(value),
+                Some(corrupted(CorruptionKind::InvalidUtf8, true)),
+            ),
+            Err(_) => (None, Some(corrupted(CorruptionKind::InvalidUtf8, false))),
+        },
+    }
+}
+
+/// 逐行解析 JSONL 文件，单行损坏不会中断读取
+///
+/// 回调参数为 (行号, 解析结果)；返回所有损坏行，调用方据此提示或修复
+pub fn for_each_json_line<P, T, F>(path: P, mut f: F) -> std::io::Result<Vec<CorruptedLine>>
+where
+    P: AsRef<Path>,
+    T: DeserializeOwned,
+    F: FnMut(usize, T) -> ControlFlow<()>,
+{
+    let mut corrupted_lines = Vec::new();
+    for_each_raw_line(path, |line_idx, bytes| {
+        let (value, corrupted) = parse_json_line(line_idx, bytes);
+        corrupted_lines.extend(corrupted);
+        match value {
+            Some(value) => f(line_idx, value),
+            None => ControlFlow::Continue(()),
+        }
+    })?;
+    Ok(corrupted_lines)
+}
+
+/// 去除 JSONL 内容中无法解析的行
+///
+/// 有损解码后能解析的行以解码后的文本保留，空行原样保留；
+/// 返回修复后的内容与处理过的损坏行
+pub fn repair_jsonl(content: &[u8]) -> (Vec<u8>, Vec<CorruptedLine>) {
+    let mut repaired = Vec::with_capacity(content.len());
+    let mut corrupted_lines = Vec::new();
+
+    for (line_idx, raw) in content.split_inclusive(|&b| b == b'\n').enumerate() {
+        let body = raw.strip_suffix(b"\n").unwrap_or(raw);
+        let body = body.strip_suffix(b"\r").unwrap_or(body);
+        let ending = &raw[body.len()..];
+
+        match parse_json_line::<serde::de::IgnoredAny>(line_idx, body) {
+            (_, None) => repaired.extend_from_slice(raw),
+            (Some(_), Some(corrupted)) => {
+                repaired.extend_from_slice(String::from_utf8_lossy(body).as_bytes());
+                repaired.extend_from_slice(ending);
+                corrupted_lines.push(corrupted);
+            }
+            (None, Some(corrupted)) => corrupted_lines.push(corrupted),
+        }
+    }
+
+    (repaired, corrupted_lines)
+}
+
+/// 整体读取文本文件，非法 UTF-8 按有损方式解码而不是直接失败
+pub fn read_to_string_lossy<P: AsRef<Path>>(path: P) -> std::io::Result<String> {
+    let bytes = fs::read(path)?;
+    Ok(String::from_utf8(bytes)
+        .unwrap_or_else(|e| String::from_utf8_lossy(e.as_bytes()).into_owned()))
+}
+
+/// 将 `\r\n` 行尾统一为 `\n`
+///
+/// 在 Windows 上被编辑器另存过的会话文件会带 CRLF；整体改写会话前先统一，
+/// 写回的文件才不会混用两种换行
+pub fn normalize_line_endings(content: String) -> String {
+    if content.contains('\r') {
+        content.replace("\r\n", "\n")
+    } else {
+        content
+    }
+}
+
+/// 检查文件是否允许整体加载到内存
+///
+/// 返回文件大小（字节）；超过配置上限时返回错误，调用方应改用 `for_each_line` 流式处理
+pub fn ensure_loadable_in_memory<P: AsRef<Path>>(path: P) -> Result<u64, String> {
+    let path = path.as_ref();
+    let size = fs::metadata(path)
+        .map_err(|e| format!("Failed to read file metadata {:?}: {}", path, e))?
+        .len();
+
+    let config = load_session_reader_config();
+    if size > config.max_in_memory_bytes() {
+        return Err(format!(
+            "会话文件过大（{:.1} MB），超过内存加载上限（{} MB）: {:?}",
+            size as f64 / (1024.0 * 1024.0),
+            config.max_in_memory_mb,
+            path
+        ));
+    }
+
+    Ok(size)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn collect_lines(content: &[u8]) -> Vec<(usize, String)> {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(content).unwrap();
+
+        let mut lines = Vec::new();
+        for_each_line(file.path(), |idx, line| {
+            lines.push((idx, line.to_string()));
+            ControlFlow::Continue(())
+        })
+        .unwrap();
+        lines
+    }
+
+    #[test]
+    fn test_line_numbers_match_str_lines() {
+        let content = "{\"a\":1}\n\n{\"b\":2}\r\n{\"c\":3}";
+        let expected: Vec<(usize, String)> = content
+            .lines()
+            .enumerate()
+            .map(|(i, l)| (i, l.to_string()))
+            .collect();
+        assert_eq!(collect_lines(content.as_bytes()), expected);
+    }
+
+    #[test]
+    fn test_break_stops_reading() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(b"1\n2\n3\n").unwrap();
+
+        let mut seen = Vec::new();
+        for_each_line(file.path(), |_, line| {
+            seen.push(line.to_string());
+            if seen.len() == 2 {
+                ControlFlow::Break(())
+            } else {
+                ControlFlow::Continue(())
+            }
+        })
+        .unwrap();
+        assert_eq!(seen, vec!["1", "2"]);
+    }
+
+    #[test]
+    fn test_json_lines_skip_and_report_corruption() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(b"{\"a\":1}\n{\"b\":\n\n{\"c\":\"\xff\"}\n\xfe{\n{\"d\":4}\n")
+            .unwrap();
+
+        let mut values = Vec::new();
+        let corrupted = for_each_json_line(file.path(), |idx, value: serde_json::Value| {
+            values.push((idx, value));
+            ControlFlow::Continue(())
+        })
+        .unwrap();
+
+        let indices: Vec<usize> = values.iter().map(|(idx, _)| *idx).collect();
+        assert_eq!(indices, vec![0, 3, 5]);
+        assert_eq!(values[1].1["c"], "\u{fffd}");
+        assert_eq!(
+            corrupted,
+            vec![
+                CorruptedLine {
+                    line: 2,
+                    kind: CorruptionKind::InvalidJson,
+                    recovered: false
+                },
+                CorruptedLine {
+                    line: 4,
+                    kind: CorruptionKind::InvalidUtf8,
+                    recovered: true
+                },
+                CorruptedLine {
+                    line: 5,
+                    kind: CorruptionKind::InvalidUtf8,
+                    recovered: false
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_repair_drops_unparseable_lines() {
+        let (repaired, corrupted) = repair_jsonl(b"{\"a\":1}\r\n{\"b\":\n\n[\"\xff\"]\n{\"d\":4}");
+        assert_eq!(
+            repaired,
+            "{\"a\":1}\r\n\n[\"\u{fffd}\"]\n{\"d\":4}".as_bytes()
+        );
+        assert_eq!(corrupted.len(), 2);
+        assert!(corrupted[1].recovered);
+
+        let clean = b"{\"a\":1}\n{\"b\":2}\n";
+        assert_eq!(repair_jsonl(clean), (clean.to_vec(), Vec::new()));
+    }
+
+    #[test]
+    fn test_invalid_utf8_is_lossy() {
+        let lines = collect_lines(b"ok\n\xff\xfe\nok2\n");
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[2].1, "ok2");
+    }
+}
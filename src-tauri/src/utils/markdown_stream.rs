@@ -0,0 +1,289 @@
+//! 流式 Markdown 文本的结构感知缓冲
+//!
+//! CLI 的流式输出按 token 切片下发文本，片段边界常落在代码围栏或表格行中间，
+//! 前端逐片渲染时围栏反复开合、表格错乱，翻译管线也会拿到半行 Markdown。
+//! `MarkdownStreamBuffer` 位于文本事件下发之前：
+//!
+//! - 普通文本不缓冲，原样立即下发，保持低延迟
+//! - 处于代码围栏内时缓冲到围栏闭合，表格行或疑似围栏行缓冲到行尾，再合并下发
+//! - 缓冲超过 8KB 或最早片段等待超过 500ms 时强制下发，防止卡住
+//!
+//! 缓冲只改变片段的切分位置，全部下发的文本拼接后与原始流完全一致。
+//!
+//! # 使用示例
+//!
+//! ```ignore
+//! let mut buffer = MarkdownStreamBuffer::new();
+//! for chunk in chunks {
+//!     if let Some(text) = buffer.push(&chunk) {
+//!         emit(text);
+//!     }
+//! }
+//! if let Some(text) = buffer.flush() {
+//!     emit(text);
+//! }
+//! ```
+
+use std::time::{Duration, Instant};
+
+/// 默认缓冲上限
+pub const DEFAULT_MAX_BUFFERED_BYTES: usize = 8 * 1024;
+
+/// 默认最长等待时间
+pub const DEFAULT_MAX_DELAY: Duration = Duration::from_millis(500);
+
+/// 已打开的代码围栏：围栏字符与长度
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Fence {
+    marker: char,
+    len: usize,
+}
+
+/// 行首（忽略缩进）连续的围栏字符数
+fn fence_run(line: &str, marker: char) -> usize {
+    line.trim_start()
+        .chars()
+        .take_while(|&c| c == marker)
+        .count()
+}
+
+/// 处理一整行后的围栏状态
+fn next_fence_state(fence: Option<Fence>, line: &str) -> Option<Fence> {
+    match fence {
+        None => ['`', '~'].into_iter().find_map(|marker| {
+            let len = fence_run(line, marker);
+            (len >= 3).then_some(Fence { marker, len })
+        }),
+        Some(open) => {
+            let trimmed = line.trim_start();
+            let len = fence_run(line, open.marker);
+            let closes =
+                len >= open.len && trimmed.trim_start_matches(open.marker).trim().is_empty();
+            if closes {
+                None
+            } else {
+                Some(open)
+            }
+        }
+    }
+}
+
+/// 尚未结束的一行是否需要等到行尾：表格行，或可能成为围栏的行首
+fn holds_partial_line(line: &str) -> bool {
+    let trimmed = line.trim_start();
+    if trimmed.is_empty() {
+        return false;
+    }
+    trimmed.starts_with('|')
+        || ["```", "~~~"]
+            .iter()
+            .any(|fence| trimmed.starts_with(fence) || fence.starts_with(trimmed))
+}
+
+/// 结构感知的流式文本缓冲
+#[derive(Debug)]
+pub struct MarkdownStreamBuffer {
+    pending: String,
+    pending_since: Option<Instant>,
+    /// 已下发文本末尾的围栏状态
+    fence: Option<Fence>,
+    /// 已下发文本中尚未结束的最后一行
+    line: String,
+    max_bytes: usize,
+    max_delay: Duration,
+}
+
+impl Default for MarkdownStreamBuffer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MarkdownStreamBuffer {
+    pub fn new() -> Self {
+        Self::with_limits(DEFAULT_MAX_BUFFERED_BYTES, DEFAULT_MAX_DELAY)
+    }
+
+    pub fn with_limits(max_bytes: usize, max_delay: Duration) -> Self {
+        Self {
+            pending: String::new(),
+            pending_since: None,
+            fence: None,
+            line: String::new(),
+            max_bytes,
+            max_delay,
+        }
+    }
+
+    /// 追加一个文本片段，返回现在可以下发的文本
+    pub fn push(&mut self, chunk: &str) -> Option<String> {
+        self.push_at(chunk, Instant::now())
+    }
+
+    fn push_at(&mut self, chunk: &str, now: Instant) -> Option<String> {
+        if chunk.is_empty() {
+            return None;
+        }
+        if self.pending.is_empty() {
+            self.pending_since = Some(now);
+        }
+        self.pending.push_str(chunk);
+
+        if self.pending.len() >= self.max_bytes || self.is_overdue(now) {
+            return self.flush();
+        }
+        let safe_end = self.safe_end();
+        self.take(safe_end)
+    }
+
+    /// 强制下发全部缓冲文本（流结束、超时或其他事件插入前调用）
+    pub fn flush(&mut self) -> Option<String> {
+        let len = self.pending.len();
+        self.take(len)
+    }
+
+    /// 缓冲文本必须下发的时间点；为空表示没有缓冲
+    pub fn deadline(&self) -> Option<Instant> {
+        self.pending_since.map(|since| since + self.max_delay)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    fn is_overdue(&self, now: Instant) -> bool {
+        self.deadline().is_some_and(|deadline| now >= deadline)
+    }
+
+    /// 缓冲区中可以安全下发的前缀长度
+    fn safe_end(&self) -> usize {
+        let mut fence = self.fence;
+        let mut safe_end = 0;
+        let mut line_start = 0;
+        let mut prefix = self.line.as_str();
+
+        for (idx, _) in self.pending.match_indices('\n') {
+            let line = format!("{}{}", prefix, &self.pending[line_start..idx]);
+            fence = next_fence_state(fence, &line);
+            if fence.is_none() {
+                safe_end = idx + 1;
+            }
+            prefix = "";
+            line_start = idx + 1;
+        }
+
+        let tail = format!("{}{}", prefix, &self.pending[line_start..]);
+        if fence.is_none() && !holds_partial_line(&tail) {
+            safe_end = self.pending.len();
+        }
+        safe_end
+    }
+
+    /// 下发缓冲区前 `end` 字节，并推进围栏与行状态
+    fn take(&mut self, end: usize) -> Option<String> {
+        if end == 0 {
+            return None;
+        }
+        let rest = self.pending.split_off(end);
+        let emitted = std::mem::replace(&mut self.pending, rest);
+        if self.pending.is_empty() {
+            self.pending_since = None;
+        }
+
+        let mut segments = emitted.split('\n').peekable();
+        while let Some(segment) = segments.next() {
+            self.line.push_str(segment);
+            if segments.peek().is_some() {
+                self.fence = next_fence_state(self.fence, &self.line);
+                self.line.clear();
+            }
+        }
+        Some(emitted)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stream(buffer: &mut MarkdownStreamBuffer, chunks: &[&str]) -> Vec<String> {
+        let mut out: Vec<String> = chunks.iter().filter_map(|c| buffer.push(c)).collect();
+        out.extend(buffer.flush());
+        out
+    }
+
+    #[test]
+    fn code_block_across_ten_fragments_is_emitted_once_closed() {
+        let chunks = [
+            "Here is the fix:\n",
+            "``",
+            "`rust\n",
+            "fn main",
+            "() {\n",
+            "    println!(",
+            "\"hi\");\n",
+            "}\n",
+            "``",
+            "`\nDone.",
+        ];
+        let mut buffer = MarkdownStreamBuffer::new();
+        let out = stream(&mut buffer, &chunks);
+
+        assert_eq!(
+            out,
+            vec![
+                "Here is the fix:\n",
+                "```rust\nfn main() {\n    println!(\"hi\");\n}\n```\nDone.",
+            ]
+        );
+        assert_eq!(out.concat(), chunks.concat());
+    }
+
+    #[test]
+    fn table_row_is_held_until_line_end() {
+        let mut buffer = MarkdownStreamBuffer::new();
+        assert_eq!(buffer.push("Result"), Some("Result".into()));
+        assert_eq!(
+            buffer.push(":\n| a | b |\n| -"),
+            Some(":\n| a | b |\n".into())
+        );
+        assert_eq!(buffer.push("-- | ---"), None);
+        assert_eq!(
+            buffer.push(" |\nafter"),
+            Some("| --- | --- |\nafter".into())
+        );
+        assert!(buffer.is_empty());
+    }
+
+    #[test]
+    fn plain_text_passes_through_and_inline_pipes_are_not_held() {
+        let chunks = ["Use ", "`a | b`", " here", "\nnext"];
+        let mut buffer = MarkdownStreamBuffer::new();
+        let out: Vec<_> = chunks.iter().map(|c| buffer.push(c)).collect();
+        assert!(out.iter().all(Option::is_some));
+    }
+
+    #[test]
+    fn forces_out_after_timeout_or_size_limit() {
+        let start = Instant::now();
+        let mut buffer = MarkdownStreamBuffer::with_limits(16, Duration::from_millis(500));
+        assert_eq!(buffer.push_at("```\nlet x", start), None);
+        assert_eq!(
+            buffer.push_at(" = 1;", start + Duration::from_millis(600)),
+            Some("```\nlet x = 1;".into())
+        );
+        // 强制下发后仍处于围栏中，闭合前继续缓冲
+        assert_eq!(
+            buffer.push_at("\nlet y", start + Duration::from_millis(650)),
+            None
+        );
+        assert_eq!(
+            buffer.push_at(" = 2;\nlet z = 3;\n", start + Duration::from_millis(700)),
+            Some("\nlet y = 2;\nlet z = 3;\n".into())
+        );
+        assert_eq!(
+            buffer.push_at("```\n", start + Duration::from_millis(710)),
+            Some("```\n".into())
+        );
+    }
+}
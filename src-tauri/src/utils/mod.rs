@@ -3,3 +3,10 @@
 /// 包含各种通用的辅助功能
 
 pub mod config_utils;
+pub mod http_headers;
+pub mod jsonl_reader;
+pub mod log_file;
+pub mod markdown_stream;
+pub mod session_lock;
+pub mod text;
+pub mod timestamp;
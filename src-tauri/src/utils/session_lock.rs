@@ -0,0 +1,462 @@
+//! 会话文件咨询锁
+//!
+//! 两个应用实例（或实例与终端 CLI）同时写同一个会话文件会导致 JSONL 行交错损坏。
+//! 应用自己的写路径（resume 执行、回退截断、删除提示词、删除会话）都先获取 `<会话>.lock`：
+//! - 锁文件以 create_new 原子创建，内容为持有者 pid、获取/刷新时间戳与用途
+//! - 持有者进程已退出或超过 10 分钟未刷新的锁视为陈旧锁（panic/崩溃后遗留），可被接管
+//! - 执行期间通过心跳每分钟刷新时间戳，执行结束时释放；释放只删除自己持有的锁
+//! - 获取失败返回以 `SESSION_IN_USE` 开头、附带持有者信息 JSON 的错误
+//!
+//! 终端 CLI 的外部写入无法强制加锁，改写会话文件前比对读取时的 mtime/size，
+//! 发现外部并发修改时中止写入。
+
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+/// 会话被占用时错误信息的前缀，前端据此识别并展示持有者信息
+pub const SESSION_IN_USE_ERROR_PREFIX: &str = "SESSION_IN_USE";
+
+/// 锁未刷新超过该时长即视为陈旧锁
+pub const LOCK_EXPIRY: Duration = Duration::from_secs(10 * 60);
+
+/// 执行期间刷新锁时间戳的间隔
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(60);
+
+/// 锁文件刚创建、内容尚未写完时的宽限期，期间无法解析的锁文件仍视为被占用
+const UNREADABLE_LOCK_GRACE: Duration = Duration::from_secs(5);
+
+/// 接管陈旧锁时与其他进程竞争的最大重试次数
+const ACQUIRE_ATTEMPTS: usize = 3;
+
+/// 锁文件内容：持有者信息
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LockHolder {
+    pub pid: u32,
+    /// 获取锁的时间（Unix 秒）
+    pub acquired_at: i64,
+    /// 最近一次刷新的时间（Unix 秒）
+    pub updated_at: i64,
+    /// 持有锁的操作，例如 "claude resume"、"codex rewind"
+    pub purpose: String,
+    /// 区分同一进程的多次获取，释放与刷新时只处理自己的锁
+    pub token: String,
+}
+
+/// `SESSION_IN_USE` 错误附带的信息
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SessionInUse<'a> {
+    session_file: String,
+    lock_file: String,
+    holder: Option<&'a LockHolder>,
+    message: String,
+}
+
+/// 已获取的会话锁，drop 时释放
+#[derive(Debug)]
+pub struct SessionLock {
+    lock_path: PathBuf,
+    holder: LockHolder,
+}
+
+impl SessionLock {
+    /// 获取会话文件的锁；锁被其他存活的持有者占用时返回 `SESSION_IN_USE` 错误
+    pub fn acquire(session_file: &Path, purpose: &str) -> Result<Self, String> {
+        let lock_path = lock_path_for(session_file);
+        let now = now_secs();
+        let holder = LockHolder {
+            pid: std::process::id(),
+            acquired_at: now,
+            updated_at: now,
+            purpose: purpose.to_string(),
+            token: uuid::Uuid::new_v4().to_string(),
+        };
+
+        for _ in 0..ACQUIRE_ATTEMPTS {
+            match OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&lock_path)
+            {
+                Ok(mut file) => {
+                    let content = serde_json::to_vec(&holder)
+                        .map_err(|e| format!("Failed to serialize session lock: {}", e))?;
+                    if let Err(e) = file.write_all(&content) {
+                        let _ = fs::remove_file(&lock_path);
+                        return Err(format!(
+                            "Failed to write session lock {:?}: {}",
+                            lock_path, e
+                        ));
+                    }
+                    log::debug!("Acquired session lock {:?} for {}", lock_path, purpose);
+                    return Ok(Self { lock_path, holder });
+                }
+                Err(e) if e.kind() == ErrorKind::AlreadyExists => {
+                    let existing = read_holder(&lock_path);
+                    if !is_stale(existing.as_ref(), &lock_path) {
+                        return Err(session_in_use_error(
+                            session_file,
+                            &lock_path,
+                            existing.as_ref(),
+                        ));
+                    }
+                    // 删除前再读一次，避免删掉刚被其他进程接管的新锁
+                    if read_holder(&lock_path) != existing {
+                        continue;
+                    }
+                    log::warn!(
+                        "Taking over stale session lock {:?} (holder: {:?})",
+                        lock_path,
+                        existing
+                    );
+                    match fs::remove_file(&lock_path) {
+                        Ok(()) => {}
+                        Err(e) if e.kind() == ErrorKind::NotFound => {}
+                        Err(e) => {
+                            return Err(format!(
+                                "Failed to remove stale session lock {:?}: {}",
+                                lock_path, e
+                            ))
+                        }
+                    }
+                }
+                Err(e) => {
+                    return Err(format!(
+                        "Failed to create session lock {:?}: {}",
+                        lock_path, e
+                    ))
+                }
+            }
+        }
+
+        let existing = read_holder(&lock_path);
+        Err(session_in_use_error(
+            session_file,
+            &lock_path,
+            existing.as_ref(),
+        ))
+    }
+
+    /// 刷新锁的时间戳；锁已被其他进程接管时返回错误
+    pub fn refresh(&mut self) -> Result<(), String> {
+        match read_holder(&self.lock_path) {
+            Some(current) if current.token == self.holder.token => {}
+            current => {
+                return Err(format!(
+                    "Session lock {:?} is no longer held (current holder: {:?})",
+                    self.lock_path, current
+                ))
+            }
+        }
+        self.holder.updated_at = now_secs();
+        let content = serde_json::to_vec(&self.holder)
+            .map_err(|e| format!("Failed to serialize session lock: {}", e))?;
+        fs::write(&self.lock_path, content)
+            .map_err(|e| format!("Failed to refresh session lock {:?}: {}", self.lock_path, e))
+    }
+
+    /// 转为执行期间持有的锁：后台定时刷新时间戳，返回的句柄 drop 时立即释放
+    pub fn keep_alive(self) -> SessionLockHeartbeat {
+        let lock = Arc::new(Mutex::new(self));
+        let weak = Arc::downgrade(&lock);
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(HEARTBEAT_INTERVAL).await;
+                let Some(lock) = weak.upgrade() else {
+                    break;
+                };
+                let mut lock = lock.lock().unwrap();
+                if let Err(e) = lock.refresh() {
+                    log::warn!("{}", e);
+                }
+            }
+        });
+        SessionLockHeartbeat { _lock: lock }
+    }
+}
+
+impl Drop for SessionLock {
+    fn drop(&mut self) {
+        if read_holder(&self.lock_path).is_some_and(|h| h.token == self.holder.token) {
+            if let Err(e) = fs::remove_file(&self.lock_path) {
+                log::warn!("Failed to release session lock {:?}: {}", self.lock_path, e);
+            } else {
+                log::debug!("Released session lock {:?}", self.lock_path);
+            }
+        }
+    }
+}
+
+/// 执行期间持有的会话锁（见 [`SessionLock::keep_alive`]）
+#[derive(Debug)]
+pub struct SessionLockHeartbeat {
+    _lock: Arc<Mutex<SessionLock>>,
+}
+
+/// 获取会话锁并在执行期间保持；会话文件不存在（新会话或找不到）时不加锁
+pub fn lock_for_execution(
+    session_file: Option<PathBuf>,
+    purpose: &str,
+) -> Result<Option<SessionLockHeartbeat>, String> {
+    match session_file {
+        Some(path) if path.exists() => {
+            SessionLock::acquire(&path, purpose).map(|lock| Some(lock.keep_alive()))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// 会话文件在读取时的状态，写入前比对以发现外部并发修改
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileSnapshot {
+    len: u64,
+    modified: Option<SystemTime>,
+}
+
+impl FileSnapshot {
+    pub fn capture(path: &Path) -> Result<Self, String> {
+        let metadata = fs::metadata(path)
+            .map_err(|e| format!("Failed to stat session file {:?}: {}", path, e))?;
+        Ok(Self {
+            len: metadata.len(),
+            modified: metadata.modified().ok(),
+        })
+    }
+
+    /// 文件大小或修改时间与读取时不同则返回错误
+    pub fn ensure_unchanged(&self, path: &Path) -> Result<(), String> {
+        if Self::capture(path)? == *self {
+            Ok(())
+        } else {
+            Err(format!(
+                "会话文件 {:?} 在读取后被其他进程修改（可能有终端中的 CLI 正在使用该会话），已中止写入，请稍后重试",
+                path
+            ))
+        }
+    }
+}
+
+/// 确认会话文件自读取后未被修改，再写入新内容
+pub fn write_session_file(
+    path: &Path,
+    snapshot: &FileSnapshot,
+    content: impl AsRef<[u8]>,
+) -> Result<(), String> {
+    snapshot.ensure_unchanged(path)?;
+    fs::write(path, content).map_err(|e| format!("Failed to write session file {:?}: {}", path, e))
+}
+
+fn lock_path_for(session_file: &Path) -> PathBuf {
+    session_file.with_extension("lock")
+}
+
+fn read_holder(lock_path: &Path) -> Option<LockHolder> {
+    let content = fs::read(lock_path).ok()?;
+    serde_json::from_slice(&content).ok()
+}
+
+fn now_secs() -> i64 {
+    chrono::Utc::now().timestamp()
+}
+
+/// 持有者已退出或锁过期即为陈旧锁；无法解析的锁文件超过宽限期后同样视为陈旧
+fn is_stale(holder: Option<&LockHolder>, lock_path: &Path) -> bool {
+    match holder {
+        Some(holder) => {
+            let age = now_secs().saturating_sub(holder.updated_at);
+            age > LOCK_EXPIRY.as_secs() as i64 || !is_process_alive(holder.pid)
+        }
+        None => fs::metadata(lock_path)
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|modified| modified.elapsed().ok())
+            .is_none_or(|age| age > UNREADABLE_LOCK_GRACE),
+    }
+}
+
+fn session_in_use_error(
+    session_file: &Path,
+    lock_path: &Path,
+    holder: Option<&LockHolder>,
+) -> String {
+    let message = match holder {
+        Some(holder) => format!(
+            "会话正被另一个进程使用（PID {}，{}），请等待其结束后重试",
+            holder.pid, holder.purpose
+        ),
+        None => "会话正被另一个进程使用，请等待其结束后重试".to_string(),
+    };
+    let detail = SessionInUse {
+        session_file: session_file.to_string_lossy().to_string(),
+        lock_file: lock_path.to_string_lossy().to_string(),
+        holder,
+        message,
+    };
+    format!(
+        "{}: {}",
+        SESSION_IN_USE_ERROR_PREFIX,
+        serde_json::to_string(&detail).unwrap_or_default()
+    )
+}
+
+/// 检查进程是否存活；无法判断时按存活处理，交给过期时间兜底
+fn is_process_alive(pid: u32) -> bool {
+    if pid == std::process::id() {
+        return true;
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::process::CommandExt;
+
+        match std::process::Command::new("tasklist")
+            .args(["/FI", &format!("PID eq {}", pid), "/NH", "/FO", "CSV"])
+            .creation_flags(0x08000000) // CREATE_NO_WINDOW
+            .output()
+        {
+            Ok(output) if output.status.success() => {
+                String::from_utf8_lossy(&output.stdout).contains(&format!("\"{}\"", pid))
+            }
+            _ => true,
+        }
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        match std::process::Command::new("kill")
+            .args(["-0", &pid.to_string()])
+            .output()
+        {
+            Ok(output) if output.status.success() => true,
+            Ok(output) => !String::from_utf8_lossy(&output.stderr)
+                .to_lowercase()
+                .contains("no such process"),
+            Err(_) => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session_file(dir: &Path) -> PathBuf {
+        let path = dir.join("session.jsonl");
+        fs::write(&path, "{\"type\":\"user\"}\n").unwrap();
+        path
+    }
+
+    fn write_holder(session_file: &Path, pid: u32, updated_at: i64) {
+        let holder = LockHolder {
+            pid,
+            acquired_at: updated_at,
+            updated_at,
+            purpose: "test".to_string(),
+            token: "other".to_string(),
+        };
+        fs::write(
+            lock_path_for(session_file),
+            serde_json::to_vec(&holder).unwrap(),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn second_acquire_reports_holder_until_released() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = session_file(dir.path());
+
+        let lock = SessionLock::acquire(&path, "claude resume").unwrap();
+        let error = SessionLock::acquire(&path, "codex rewind").unwrap_err();
+        assert!(error.starts_with(SESSION_IN_USE_ERROR_PREFIX));
+        let detail: serde_json::Value = serde_json::from_str(
+            error
+                .strip_prefix(&format!("{}: ", SESSION_IN_USE_ERROR_PREFIX))
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(detail["holder"]["pid"], std::process::id());
+        assert_eq!(detail["holder"]["purpose"], "claude resume");
+
+        drop(lock);
+        assert!(!lock_path_for(&path).exists());
+        assert!(SessionLock::acquire(&path, "codex rewind").is_ok());
+    }
+
+    #[test]
+    fn stale_locks_are_taken_over() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = session_file(dir.path());
+
+        // 持有者仍存活但超过过期时间未刷新
+        let expired = now_secs() - LOCK_EXPIRY.as_secs() as i64 - 1;
+        write_holder(&path, std::process::id(), expired);
+        let lock = SessionLock::acquire(&path, "resume").unwrap();
+        assert_eq!(
+            read_holder(&lock_path_for(&path)).unwrap().purpose,
+            "resume"
+        );
+        drop(lock);
+
+        // 无法解析且超过宽限期的锁文件
+        let lock_path = lock_path_for(&path);
+        fs::write(&lock_path, "{\"pid\":").unwrap();
+        let old = SystemTime::now() - Duration::from_secs(60);
+        fs::File::options()
+            .write(true)
+            .open(&lock_path)
+            .unwrap()
+            .set_modified(old)
+            .unwrap();
+        assert!(SessionLock::acquire(&path, "resume").is_ok());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn lock_of_exited_process_is_taken_over() {
+        let mut child = std::process::Command::new("true").spawn().unwrap();
+        let pid = child.id();
+        child.wait().unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = session_file(dir.path());
+        write_holder(&path, pid, now_secs());
+
+        assert!(SessionLock::acquire(&path, "resume").is_ok());
+    }
+
+    #[test]
+    fn release_keeps_lock_taken_over_by_others() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = session_file(dir.path());
+
+        let mut lock = SessionLock::acquire(&path, "resume").unwrap();
+        write_holder(&path, std::process::id(), now_secs());
+
+        assert!(lock.refresh().is_err());
+        drop(lock);
+        assert_eq!(read_holder(&lock_path_for(&path)).unwrap().token, "other");
+    }
+
+    #[test]
+    fn write_aborts_after_external_modification() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = session_file(dir.path());
+
+        let snapshot = FileSnapshot::capture(&path).unwrap();
+        write_session_file(&path, &snapshot, "").unwrap();
+
+        let snapshot = FileSnapshot::capture(&path).unwrap();
+        fs::write(&path, "{\"type\":\"assistant\"}\n").unwrap();
+        assert!(write_session_file(&path, &snapshot, "").is_err());
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "{\"type\":\"assistant\"}\n"
+        );
+    }
+}
@@ -0,0 +1,21 @@
+//! 文本处理辅助函数
+
+/// 截取前 `max_chars` 个字符（按字符而非字节计数），被截断时追加 `…`
+pub fn truncate_chars(text: &str, max_chars: usize) -> String {
+    match text.char_indices().nth(max_chars) {
+        Some((idx, _)) => format!("{}…", &text[..idx]),
+        None => text.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn truncates_on_char_boundary() {
+        assert_eq!(truncate_chars("提示词历史", 2), "提示…");
+        assert_eq!(truncate_chars("short", 10), "short");
+        assert_eq!(truncate_chars("exact", 5), "exact");
+    }
+}
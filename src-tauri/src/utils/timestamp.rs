@@ -0,0 +1,106 @@
+//! 时间戳统一处理
+//!
+//! 提示词记录与 git 记录的时间统一存为 UTC Unix 秒。会话文件中的时间是带偏移量的 RFC3339
+//! 字符串，旧版 Codex/Gemini git 记录也以 RFC3339 字符串保存，这里集中做解析与兼容反序列化。
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Deserializer};
+use serde_json::Value;
+
+/// 绝对值不小于该值的数字按毫秒处理（秒级时间戳要到 5138 年才会达到）
+const MILLIS_THRESHOLD: i64 = 100_000_000_000;
+
+fn from_unix_number(n: i64) -> Option<DateTime<Utc>> {
+    if n.abs() >= MILLIS_THRESHOLD {
+        DateTime::from_timestamp_millis(n)
+    } else {
+        DateTime::from_timestamp(n, 0)
+    }
+}
+
+/// 解析 RFC3339（任意偏移量）或 Unix 秒/毫秒数字字符串，统一转为 UTC
+pub fn parse_timestamp(s: &str) -> Option<DateTime<Utc>> {
+    let s = s.trim();
+    if let Ok(dt) = DateTime::parse_from_rfc3339(s) {
+        return Some(dt.with_timezone(&Utc));
+    }
+    s.parse::<i64>().ok().and_then(from_unix_number)
+}
+
+/// 解析 JSON 中的时间（字符串或数字）
+pub fn parse_timestamp_value(value: &Value) -> Option<DateTime<Utc>> {
+    match value {
+        Value::String(s) => parse_timestamp(s),
+        Value::Number(n) => n
+            .as_i64()
+            .or_else(|| n.as_f64().map(|f| f as i64))
+            .and_then(from_unix_number),
+        _ => None,
+    }
+}
+
+/// 反序列化为 UTC Unix 秒，兼容旧数据中的 RFC3339 字符串；null 视为未知（0）
+pub fn deserialize_unix_seconds<'de, D>(deserializer: D) -> Result<i64, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value = Value::deserialize(deserializer)?;
+    if value.is_null() {
+        return Ok(0);
+    }
+    parse_timestamp_value(&value)
+        .map(|dt| dt.timestamp())
+        .ok_or_else(|| serde::de::Error::custom(format!("invalid timestamp: {}", value)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn parses_offsets_to_the_same_instant() {
+        let utc = parse_timestamp("2025-01-02T03:04:05.678Z").unwrap();
+        let shanghai = parse_timestamp("2025-01-02T11:04:05.678+08:00").unwrap();
+        let new_york = parse_timestamp("2025-01-01T22:04:05.678-05:00").unwrap();
+
+        assert_eq!(utc, shanghai);
+        assert_eq!(utc, new_york);
+        assert_eq!(utc.timestamp(), 1_735_787_045);
+        assert_eq!(utc.timestamp_millis(), 1_735_787_045_678);
+    }
+
+    #[test]
+    fn parses_unix_seconds_and_millis() {
+        assert_eq!(
+            parse_timestamp_value(&json!(1_735_787_045)).unwrap().timestamp(),
+            1_735_787_045
+        );
+        assert_eq!(
+            parse_timestamp_value(&json!(1_735_787_045_678_i64))
+                .unwrap()
+                .timestamp_millis(),
+            1_735_787_045_678
+        );
+        assert_eq!(parse_timestamp("1735787045").unwrap().timestamp(), 1_735_787_045);
+        assert!(parse_timestamp("not a time").is_none());
+    }
+
+    #[derive(Deserialize)]
+    struct Record {
+        #[serde(deserialize_with = "deserialize_unix_seconds")]
+        timestamp: i64,
+    }
+
+    #[test]
+    fn deserializes_legacy_and_current_formats() {
+        let legacy: Record =
+            serde_json::from_value(json!({"timestamp": "2025-01-02T11:04:05.123456+08:00"}))
+                .unwrap();
+        let current: Record = serde_json::from_value(json!({"timestamp": 1_735_787_045})).unwrap();
+
+        assert_eq!(legacy.timestamp, 1_735_787_045);
+        assert_eq!(current.timestamp, legacy.timestamp);
+        assert!(serde_json::from_value::<Record>(json!({"timestamp": "garbage"})).is_err());
+    }
+}
@@ -9,6 +9,8 @@ import { api } from "@/lib/api";
 import { cn } from "@/lib/utils";
 import { copyTextToClipboard } from "@/lib/clipboard";
 import { useTranslation } from "@/hooks/useTranslation";
+import { AcemcpKeywordPreview } from "@/components/AcemcpKeywordPreview";
+import { AcemcpKeywordLists } from "@/components/AcemcpKeywordLists";
 
 interface AcemcpConfigSettingsProps {
   className?: string;
@@ -19,6 +21,8 @@ interface AcemcpConfig {
   token: string;
   batchSize?: number;
   maxLinesPerBlob?: number;
+  requestTimeoutSecs?: number;
+  idleTimeoutMins?: number;
 }
 
 export function AcemcpConfigSettings({ className }: AcemcpConfigSettingsProps) {
@@ -28,6 +32,8 @@ export function AcemcpConfigSettings({ className }: AcemcpConfigSettingsProps) {
     token: '',
     batchSize: 10,
     maxLinesPerBlob: 800,
+    requestTimeoutSecs: 60,
+    idleTimeoutMins: 10,
   });
 
   const [showToken, setShowToken] = useState(false);
@@ -62,7 +68,9 @@ export function AcemcpConfigSettings({ className }: AcemcpConfigSettingsProps) {
         config.baseUrl,
         config.token,
         config.batchSize,
-        config.maxLinesPerBlob
+        config.maxLinesPerBlob,
+        config.requestTimeoutSecs,
+        config.idleTimeoutMins
       );
       setHasChanges(false);
       setTestStatus('idle');
@@ -80,6 +88,8 @@ export function AcemcpConfigSettings({ className }: AcemcpConfigSettingsProps) {
       token: '',
       batchSize: 10,
       maxLinesPerBlob: 800,
+      requestTimeoutSecs: 60,
+      idleTimeoutMins: 10,
     });
     setHasChanges(true);
   };
@@ -253,6 +263,36 @@ export function AcemcpConfigSettings({ className }: AcemcpConfigSettingsProps) {
                   {t('acemcp.maxFileLinesDefault')}
                 </p>
               </div>
+
+              <div>
+                <Label htmlFor="acemcp-request-timeout">{t('acemcp.requestTimeout')}</Label>
+                <Input
+                  id="acemcp-request-timeout"
+                  type="number"
+                  min="10"
+                  max="1800"
+                  value={config.requestTimeoutSecs || 60}
+                  onChange={(e) => handleChange('requestTimeoutSecs', parseInt(e.target.value) || 60)}
+                />
+                <p className="text-xs text-muted-foreground mt-1">
+                  {t('acemcp.requestTimeoutDefault')}
+                </p>
+              </div>
+
+              <div>
+                <Label htmlFor="acemcp-idle-timeout">{t('acemcp.idleTimeout')}</Label>
+                <Input
+                  id="acemcp-idle-timeout"
+                  type="number"
+                  min="1"
+                  max="240"
+                  value={config.idleTimeoutMins || 10}
+                  onChange={(e) => handleChange('idleTimeoutMins', parseInt(e.target.value) || 10)}
+                />
+                <p className="text-xs text-muted-foreground mt-1">
+                  {t('acemcp.idleTimeoutDefault')}
+                </p>
+              </div>
             </div>
 
             {/* Test Connection */}
@@ -334,6 +374,12 @@ export function AcemcpConfigSettings({ className }: AcemcpConfigSettingsProps) {
                 {t('acemcp.projectContextHint')}
               </p>
             </Card>
+
+            {/* Custom Keywords */}
+            <AcemcpKeywordLists />
+
+            {/* Keyword Preview */}
+            <AcemcpKeywordPreview />
           </div>
         )}
       </Card>
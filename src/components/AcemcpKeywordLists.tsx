@@ -0,0 +1,106 @@
+import { useEffect, useState } from "react";
+import { Save } from "lucide-react";
+import { Button } from "@/components/ui/button";
+import { Label } from "@/components/ui/label";
+import { Textarea } from "@/components/ui/textarea";
+import { api, type AcemcpKeywordConfig } from "@/lib/api";
+import { useTranslation } from "@/hooks/useTranslation";
+
+type KeywordListKey = keyof AcemcpKeywordConfig;
+
+const LIST_KEYS: KeywordListKey[] = ["abbreviations", "stopwords", "chinese_words"];
+
+const toText = (words: string[]) => words.join("\n");
+
+const toWords = (text: string) =>
+  text
+    .split(/[\n,，]/)
+    .map((word) => word.trim())
+    .filter(Boolean);
+
+/**
+ * 自定义关键词词库：编辑 ~/.acemcp/keywords.toml，与内置词库合并后用于提示词关键词提取
+ */
+export function AcemcpKeywordLists() {
+  const { t } = useTranslation();
+  const [lists, setLists] = useState<Record<KeywordListKey, string>>({
+    abbreviations: "",
+    stopwords: "",
+    chinese_words: "",
+  });
+  const [hasChanges, setHasChanges] = useState(false);
+  const [isSaving, setIsSaving] = useState(false);
+  const [error, setError] = useState<string | null>(null);
+
+  const applyConfig = (config: AcemcpKeywordConfig) => {
+    setLists({
+      abbreviations: toText(config.abbreviations),
+      stopwords: toText(config.stopwords),
+      chinese_words: toText(config.chinese_words),
+    });
+    setHasChanges(false);
+  };
+
+  useEffect(() => {
+    api
+      .getAcemcpKeywordConfig()
+      .then(applyConfig)
+      .catch((err) => setError(String(err)));
+  }, []);
+
+  const handleSave = async () => {
+    setIsSaving(true);
+    setError(null);
+    try {
+      applyConfig(
+        await api.saveAcemcpKeywordConfig({
+          abbreviations: toWords(lists.abbreviations),
+          stopwords: toWords(lists.stopwords),
+          chinese_words: toWords(lists.chinese_words),
+        })
+      );
+    } catch (err) {
+      setError(String(err));
+    } finally {
+      setIsSaving(false);
+    }
+  };
+
+  return (
+    <div className="space-y-3">
+      <div>
+        <Label>{t('acemcp.keywordLists')}</Label>
+        <p className="text-xs text-muted-foreground mt-1">{t('acemcp.keywordListsDescription')}</p>
+      </div>
+      <div className="grid grid-cols-1 md:grid-cols-3 gap-3">
+        {LIST_KEYS.map((key) => (
+          <div key={key} className="space-y-1">
+            <Label htmlFor={`acemcp-keywords-${key}`} className="text-sm font-normal">
+              {t(`acemcp.keywordList.${key}`)}
+            </Label>
+            <Textarea
+              id={`acemcp-keywords-${key}`}
+              value={lists[key]}
+              onChange={(e) => {
+                setLists((prev) => ({ ...prev, [key]: e.target.value }));
+                setHasChanges(true);
+              }}
+              placeholder={t(`acemcp.keywordListPlaceholder.${key}`)}
+              rows={4}
+              className="font-mono text-xs"
+            />
+          </div>
+        ))}
+      </div>
+
+      {error && <p className="text-xs text-destructive">{error}</p>}
+
+      <div className="flex justify-end">
+        <Button onClick={handleSave} size="sm" variant="outline" disabled={!hasChanges || isSaving}>
+          <Save className="h-4 w-4 mr-2" />
+          {isSaving ? t('common.saving') : t('acemcp.saveKeywordLists')}
+        </Button>
+      </div>
+    </div>
+  );
+}
@@ -0,0 +1,98 @@
+import { useState } from "react";
+import { Search } from "lucide-react";
+import { Button } from "@/components/ui/button";
+import { Label } from "@/components/ui/label";
+import { Switch } from "@/components/ui/switch";
+import { Textarea } from "@/components/ui/textarea";
+import { Badge } from "@/components/ui/badge";
+import { api, type PromptKeywordsPreview } from "@/lib/api";
+import { useTranslation } from "@/hooks/useTranslation";
+
+/**
+ * 关键词预览：查看 acemcp 从提示词中提取的关键词与各轮搜索查询，无需启动 sidecar 或建立索引
+ */
+export function AcemcpKeywordPreview() {
+  const { t } = useTranslation();
+  const [prompt, setPrompt] = useState("");
+  const [multiRound, setMultiRound] = useState(true);
+  const [preview, setPreview] = useState<PromptKeywordsPreview | null>(null);
+  const [error, setError] = useState<string | null>(null);
+
+  const handlePreview = async () => {
+    setError(null);
+    try {
+      setPreview(await api.previewPromptKeywords(prompt, multiRound));
+    } catch (err) {
+      setPreview(null);
+      setError(String(err));
+    }
+  };
+
+  const renderKeywords = (label: string, keywords: string[]) => (
+    <div className="flex flex-wrap items-center gap-1">
+      <span className="text-xs text-muted-foreground mr-1">{label}</span>
+      {keywords.length === 0 ? (
+        <span className="text-xs text-muted-foreground">—</span>
+      ) : (
+        keywords.map((keyword) => (
+          <Badge key={keyword} variant="secondary" className="font-mono text-xs">
+            {keyword}
+          </Badge>
+        ))
+      )}
+    </div>
+  );
+
+  return (
+    <div className="space-y-3">
+      <div>
+        <Label htmlFor="acemcp-keyword-preview">{t('acemcp.keywordPreview')}</Label>
+        <p className="text-xs text-muted-foreground mt-1">{t('acemcp.keywordPreviewDescription')}</p>
+      </div>
+      <Textarea
+        id="acemcp-keyword-preview"
+        value={prompt}
+        onChange={(e) => setPrompt(e.target.value)}
+        placeholder={t('acemcp.keywordPreviewPlaceholder')}
+        rows={3}
+      />
+      <div className="flex items-center justify-between">
+        <div className="flex items-center gap-2">
+          <Switch id="acemcp-keyword-multi-round" checked={multiRound} onCheckedChange={setMultiRound} />
+          <Label htmlFor="acemcp-keyword-multi-round" className="text-sm font-normal">
+            {t('acemcp.multiRoundSearch')}
+          </Label>
+        </div>
+        <Button onClick={handlePreview} size="sm" variant="outline" disabled={!prompt.trim()}>
+          <Search className="h-4 w-4 mr-2" />
+          {t('acemcp.previewKeywords')}
+        </Button>
+      </div>
+
+      {error && <p className="text-xs text-destructive">{error}</p>}
+
+      {preview && (
+        <div className="space-y-2 rounded-md border p-3">
+          {renderKeywords(t('acemcp.englishKeywords'), preview.keywords.english)}
+          {renderKeywords(t('acemcp.chineseKeywords'), preview.keywords.chinese)}
+          <div>
+            <p className="text-xs text-muted-foreground mb-1">
+              {t('acemcp.searchQueries', { count: preview.queries.length })}
+            </p>
+            {preview.queries.length === 0 ? (
+              <p className="text-xs text-muted-foreground">{t('acemcp.noKeywords')}</p>
+            ) : (
+              <ol className="list-decimal list-inside space-y-0.5">
+                {preview.queries.map((query, index) => (
+                  <li key={index} className="font-mono text-xs">
+                    {query}
+                  </li>
+                ))}
+              </ol>
+            )}
+          </div>
+        </div>
+      )}
+    </div>
+  );
+}
@@ -0,0 +1,205 @@
+/**
+ * BatchConvertDialog - 批量转换项目会话
+ *
+ * 把项目下某个引擎的全部会话转换到另一个引擎：可先试运行预览，
+ * 转换中按会话显示进度并支持取消，结束后列出失败与跳过的会话
+ */
+
+import { useEffect, useRef, useState } from "react";
+import { listen, type UnlistenFn } from "@tauri-apps/api/event";
+import { Eye, Loader2, RefreshCw, X } from "lucide-react";
+import { Button } from "@/components/ui/button";
+import { Checkbox } from "@/components/ui/checkbox";
+import { Label } from "@/components/ui/label";
+import { Progress } from "@/components/ui/progress";
+import {
+  Dialog,
+  DialogContent,
+  DialogDescription,
+  DialogFooter,
+  DialogHeader,
+  DialogTitle,
+} from "@/components/ui/dialog";
+import { api } from "@/lib/api";
+import type { BatchConversionProgress, BatchConversionReport } from "@/lib/api";
+
+type ConvertEngine = "claude" | "codex";
+
+const ENGINE_LABELS: Record<ConvertEngine, string> = {
+  claude: "Claude",
+  codex: "Codex",
+};
+
+const STATUS_LABELS: Record<string, string> = {
+  converted: "已转换",
+  skipped: "已跳过",
+  failed: "失败",
+  cancelled: "已取消",
+};
+
+export interface BatchConvertDialogProps {
+  open: boolean;
+  onOpenChange: (open: boolean) => void;
+  /** 源引擎（目标为另一个引擎） */
+  sourceEngine: ConvertEngine;
+  /** 源引擎会话数（仅用于提示） */
+  sessionCount: number;
+  projectId: string;
+  projectPath: string;
+  /** 实际转换（非试运行）结束后回调，用于刷新会话列表 */
+  onConverted?: () => void;
+}
+
+export function BatchConvertDialog({
+  open,
+  onOpenChange,
+  sourceEngine,
+  sessionCount,
+  projectId,
+  projectPath,
+  onConverted,
+}: BatchConvertDialogProps) {
+  const targetEngine: ConvertEngine = sourceEngine === "claude" ? "codex" : "claude";
+  const [force, setForce] = useState(false);
+  const [parallel, setParallel] = useState(false);
+  const [running, setRunning] = useState<"preview" | "convert" | null>(null);
+  const [progress, setProgress] = useState<BatchConversionProgress | null>(null);
+  const [report, setReport] = useState<BatchConversionReport | null>(null);
+  const [error, setError] = useState<string | null>(null);
+  const batchIdRef = useRef<string | null>(null);
+
+  useEffect(() => {
+    if (!open) return;
+    setProgress(null);
+    setReport(null);
+    setError(null);
+  }, [open, sourceEngine]);
+
+  useEffect(() => {
+    let unlisten: UnlistenFn | undefined;
+    listen<BatchConversionProgress>("conversion:batch-progress", (event) => {
+      if (event.payload.batchId === batchIdRef.current) {
+        setProgress(event.payload);
+      }
+    }).then((fn) => {
+      unlisten = fn;
+    });
+    return () => unlisten?.();
+  }, []);
+
+  const run = async (dryRun: boolean) => {
+    const batchId = crypto.randomUUID();
+    batchIdRef.current = batchId;
+    setRunning(dryRun ? "preview" : "convert");
+    setProgress(null);
+    setReport(null);
+    setError(null);
+    try {
+      const result = await api.convertSessionsBatch(null, projectId, projectPath, targetEngine, sourceEngine, {
+        dryRun,
+        force,
+        concurrency: parallel ? 2 : 1,
+        batchId,
+      });
+      setReport(result);
+      if (!dryRun && result.converted > 0) {
+        onConverted?.();
+      }
+    } catch (err) {
+      setError(String(err));
+    } finally {
+      batchIdRef.current = null;
+      setRunning(null);
+    }
+  };
+
+  const handleCancel = async () => {
+    if (batchIdRef.current) {
+      await api.cancelConversion(batchIdRef.current);
+    }
+  };
+
+  const problems = report?.items.filter((item) => item.status === "failed" || item.status === "skipped") ?? [];
+
+  return (
+    <Dialog open={open} onOpenChange={(next) => !running && onOpenChange(next)}>
+      <DialogContent className="max-w-lg">
+        <DialogHeader>
+          <DialogTitle>
+            批量转换：{ENGINE_LABELS[sourceEngine]} → {ENGINE_LABELS[targetEngine]}
+          </DialogTitle>
+          <DialogDescription>
+            将该项目下的 {sessionCount} 个 {ENGINE_LABELS[sourceEngine]} 会话转换为 {ENGINE_LABELS[targetEngine]} 会话，
+            原会话保留不变。单个会话失败不影响其余会话。
+          </DialogDescription>
+        </DialogHeader>
+
+        <div className="space-y-3">
+          <label className="flex items-center gap-2 text-sm cursor-pointer">
+            <Checkbox checked={force} onCheckedChange={(checked) => setForce(!!checked)} disabled={running !== null} />
+            <Label className="cursor-pointer">重新转换已转换过的会话</Label>
+          </label>
+          <label className="flex items-center gap-2 text-sm cursor-pointer">
+            <Checkbox checked={parallel} onCheckedChange={(checked) => setParallel(!!checked)} disabled={running !== null} />
+            <Label className="cursor-pointer">同时转换 2 个会话</Label>
+          </label>
+
+          {progress && running && (
+            <div className="space-y-1">
+              <Progress value={(progress.completed / Math.max(progress.total, 1)) * 100} className="h-2" />
+              <p className="text-xs text-muted-foreground">
+                {progress.completed} / {progress.total} · {progress.sessionId.slice(0, 8)} {STATUS_LABELS[progress.status]}
+              </p>
+            </div>
+          )}
+
+          {report && (
+            <div className="rounded-md p-3 bg-muted/30 space-y-2 text-sm">
+              <p>
+                {report.dryRun ? "预览：" : ""}
+                共 {report.total} 个，{report.dryRun ? "可转换" : "已转换"} {report.converted} 个，
+                跳过 {report.skipped} 个，失败 {report.failed} 个
+                {report.cancelled && "（已取消）"}
+              </p>
+              {problems.length > 0 && (
+                <ul className="text-xs space-y-1 max-h-48 overflow-auto">
+                  {problems.map((item) => (
+                    <li key={item.sessionId} className="break-all">
+                      <span className="font-mono">{item.sessionId.slice(0, 8)}</span>
+                      {" "}{STATUS_LABELS[item.status]}
+                      {item.error && <span className="text-muted-foreground">：{item.error}</span>}
+                    </li>
+                  ))}
+                </ul>
+              )}
+            </div>
+          )}
+
+          {error && (
+            <div className="text-xs text-destructive bg-destructive/10 rounded-lg p-3 whitespace-pre-wrap">
+              {error}
+            </div>
+          )}
+        </div>
+
+        <DialogFooter>
+          {running ? (
+            <Button variant="outline" onClick={handleCancel} className="gap-2">
+              <X className="h-4 w-4" />
+              取消
+            </Button>
+          ) : (
+            <Button variant="outline" onClick={() => run(true)} className="gap-2">
+              <Eye className="h-4 w-4" />
+              预览
+            </Button>
+          )}
+          <Button onClick={() => run(false)} disabled={running !== null} className="gap-2">
+            {running === "convert" ? <Loader2 className="h-4 w-4 animate-spin" /> : <RefreshCw className="h-4 w-4" />}
+            开始转换
+          </Button>
+        </DialogFooter>
+      </DialogContent>
+    </Dialog>
+  );
+}
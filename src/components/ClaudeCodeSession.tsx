@@ -8,7 +8,7 @@ import {
   List
 } from "lucide-react";
 import { Button } from "@/components/ui/button";
-import { api, type Session, type Project } from "@/lib/api";
+import { api, parseSessionInUseError, type Session, type Project, type CorruptedLine, REVERT_CONFLICT_ERROR_PREFIX } from "@/lib/api";
 import { cn } from "@/lib/utils";
 import { type UnlistenFn } from "@tauri-apps/api/event";
 import { FloatingPromptInput, type FloatingPromptInputRef, type ModelType } from "./FloatingPromptInput";
@@ -33,9 +33,14 @@ import { PlanApprovalDialog } from '@/components/dialogs/PlanApprovalDialog';
 import { PlanModeStatusBar } from '@/components/widgets/system/PlanModeStatusBar';
 import { UserQuestionProvider, useUserQuestion } from '@/contexts/UserQuestionContext';
 import { AskUserQuestionDialog } from '@/components/dialogs/AskUserQuestionDialog';
+import { RevertConflictDialog } from '@/components/dialogs/RevertConflictDialog';
 import { codexConverter } from '@/lib/codexConverter';
 import { convertGeminiSessionDetailToClaudeMessages } from '@/lib/geminiConverter';
 import { SessionHeader } from "./session/SessionHeader";
+import { RelatedSessionsBar } from "./session/RelatedSessionsBar";
+import { McpCallSummaryBar } from "./session/McpCallSummaryBar";
+import { SlowOperationsBar } from "./session/SlowOperationsBar";
+import { CorruptedLinesBar } from "./session/CorruptedLinesBar";
 import { SessionMessages, type SessionMessagesRef } from "./session/SessionMessages";
 
 import * as SessionHelpers from '@/lib/sessionHelpers';
@@ -117,6 +122,8 @@ const ClaudeCodeSessionInner: React.FC<ClaudeCodeSessionProps> = ({
   const [sessionNotFound, setSessionNotFound] = useState(false);
   const [claudeSessionId, setClaudeSessionId] = useState<string | null>(null);
   const [codexRateLimits, setCodexRateLimits] = useState<CodexRateLimits | null>(null);
+  // 加载 Claude 会话时跳过的损坏行
+  const [corruptedLines, setCorruptedLines] = useState<CorruptedLine[]>([]);
 
   // Plan Mode state - 使用 Context（方案 B-1）
   const {
@@ -160,10 +167,12 @@ const ClaudeCodeSessionInner: React.FC<ClaudeCodeSessionProps> = ({
   });
 
   // Queued prompts state
-  const [queuedPrompts, setQueuedPrompts] = useState<Array<{ id: string; prompt: string; model: ModelType }>>([]);
+  const [queuedPrompts, setQueuedPrompts] = useState<Array<{ id: string; prompt: string; model: ModelType; source?: 'interjection' }>>([]);
 
   // State for revert prompt picker (defined early for useKeyboardShortcuts)
   const [showRevertPicker, setShowRevertPicker] = useState(false);
+  // 交互式撤回停在冲突上时记录撤回参数，冲突解决后继续
+  const [pendingRevertConflict, setPendingRevertConflict] = useState<{ promptIndex: number; mode: import('@/lib/api').RewindMode } | null>(null);
 
   // State for prompt navigator
   const [showPromptNavigator, setShowPromptNavigator] = useState(false);
@@ -299,7 +308,7 @@ const ClaudeCodeSessionInner: React.FC<ClaudeCodeSessionProps> = ({
   const hasActiveSessionRef = useRef(false);
   const floatingPromptRef = useRef<FloatingPromptInputRef>(null);
   const sessionMessagesRef = useRef<SessionMessagesRef>(null);
-  const queuedPromptsRef = useRef<Array<{ id: string; prompt: string; model: ModelType }>>([]);
+  const queuedPromptsRef = useRef<Array<{ id: string; prompt: string; model: ModelType; source?: 'interjection' }>>([]);
   const isMountedRef = useRef(true);
   const isListeningRef = useRef(false);
 
@@ -339,6 +348,7 @@ const ClaudeCodeSessionInner: React.FC<ClaudeCodeSessionProps> = ({
     setRawJsonlOutput,
     setClaudeSessionId,
     setCodexRateLimits,
+    setCorruptedLines,
     initializeProgressiveTranslation,
     processMessageWithTranslation,
     onSessionNotFound: handleSessionNotFound
@@ -409,8 +419,13 @@ const ClaudeCodeSessionInner: React.FC<ClaudeCodeSessionProps> = ({
     executionEngine: executionEngineConfig.engine, // 🆕 Codex integration
     codexMode: executionEngineConfig.codexMode,    // 🆕 Codex integration
     codexModel: executionEngineConfig.codexModel,  // 🆕 Codex integration
+    codexProfile: executionEngineConfig.codexProfile,
+    codexReasoningEffort: executionEngineConfig.codexReasoningLevel,
+    codexVerbosity: executionEngineConfig.codexVerbosity,
+    codexStrictConfig: executionEngineConfig.codexStrictConfig,
     geminiModel: executionEngineConfig.geminiModel,           // 🆕 Gemini integration
     geminiApprovalMode: executionEngineConfig.geminiApprovalMode, // 🆕 Gemini integration
+    geminiSearchGrounding: executionEngineConfig.geminiSearchGrounding,
     hasActiveSessionRef,
     unlistenRefs,
     isMountedRef,
@@ -801,6 +816,41 @@ const ClaudeCodeSessionInner: React.FC<ClaudeCodeSessionProps> = ({
     setShowPromptNavigator(false);
   }, []);
 
+  // 重新加载消息历史（根据引擎类型使用不同的 API），撤回与采用回复版本后调用
+  const reloadSessionMessages = useCallback(async () => {
+    if (!effectiveSession) return;
+    const sessionEngine = effectiveSession.engine || executionEngineConfig.engine || 'claude';
+    const isGemini = sessionEngine === 'gemini';
+
+    if (isGemini) {
+      // Gemini 使用专门的 API 加载历史
+      const geminiDetail = await api.getGeminiSessionDetail(projectPath, effectiveSession.id);
+      setMessages(convertGeminiSessionDetailToClaudeMessages(geminiDetail) as any);
+    } else {
+      // Claude/Codex 使用原有 API
+      const history = await api.loadSessionHistory(
+        effectiveSession.id,
+        effectiveSession.project_id,
+        sessionEngine as any
+      );
+
+      if (sessionEngine === 'codex' && Array.isArray(history)) {
+        // 将 Codex 事件转换为消息格式（与 useSessionStream 保持一致）
+        codexConverter.reset();
+        const convertedMessages: any[] = [];
+        for (const event of history) {
+          const msg = codexConverter.convertEventObject(event as any);
+          if (msg) convertedMessages.push(msg);
+        }
+        setMessages(convertedMessages);
+      } else if (Array.isArray(history)) {
+        setMessages(history);
+      } else if (history && typeof history === 'object' && 'messages' in history) {
+        setMessages((history as any).messages);
+      }
+    }
+  }, [effectiveSession, projectPath, executionEngineConfig.engine]);
+
   const handleRevert = useCallback(async (promptIndex: number, mode: import('@/lib/api').RewindMode = 'both') => {
     if (!effectiveSession) return;
 
@@ -830,37 +880,11 @@ const ClaudeCodeSessionInner: React.FC<ClaudeCodeSessionProps> = ({
             effectiveSession.project_id,
             projectPath,
             promptIndex,
-            mode
+            mode,
+            'interactive'
           );
 
-      // 重新加载消息历史（根据引擎类型使用不同的 API）
-      if (isGemini) {
-        // Gemini 使用专门的 API 加载历史
-        const geminiDetail = await api.getGeminiSessionDetail(projectPath, effectiveSession.id);
-        setMessages(convertGeminiSessionDetailToClaudeMessages(geminiDetail) as any);
-      } else {
-        // Claude/Codex 使用原有 API
-        const history = await api.loadSessionHistory(
-          effectiveSession.id,
-          effectiveSession.project_id,
-          sessionEngine as any
-        );
-
-        if (sessionEngine === 'codex' && Array.isArray(history)) {
-          // 将 Codex 事件转换为消息格式（与 useSessionStream 保持一致）
-          codexConverter.reset();
-          const convertedMessages: any[] = [];
-          for (const event of history) {
-            const msg = codexConverter.convertEventObject(event as any);
-            if (msg) convertedMessages.push(msg);
-          }
-          setMessages(convertedMessages);
-        } else if (Array.isArray(history)) {
-          setMessages(history);
-        } else if (history && typeof history === 'object' && 'messages' in history) {
-          setMessages((history as any).messages);
-        }
-      }
+      await reloadSessionMessages();
 
       // 恢复提示词到输入框（仅在对话撤回模式下）
       if ((mode === 'conversation_only' || mode === 'both') && floatingPromptRef.current && promptText) {
@@ -871,10 +895,15 @@ const ClaudeCodeSessionInner: React.FC<ClaudeCodeSessionProps> = ({
       setError('');
 
     } catch (error) {
+      // 代码撤回停在冲突上：打开冲突解决对话框，而不是直接报错
+      if (String(error).includes(REVERT_CONFLICT_ERROR_PREFIX)) {
+        setPendingRevertConflict({ promptIndex, mode });
+        return;
+      }
       console.error('[Prompt Revert] Failed to revert:', error);
-      setError('__REVERT_FAILED__:' + error);
+      setError('__REVERT_FAILED__:' + (parseSessionInUseError(error)?.message ?? error));
     }
-  }, [effectiveSession, projectPath, claudeSettings?.hideWarmupMessages, executionEngineConfig.engine]);
+  }, [effectiveSession, projectPath, claudeSettings?.hideWarmupMessages, executionEngineConfig.engine, reloadSessionMessages]);
 
   // Cleanup event listeners and track mount state
   // ⚠️ IMPORTANT: No dependencies! Only cleanup on real unmount
@@ -906,6 +935,7 @@ const ClaudeCodeSessionInner: React.FC<ClaudeCodeSessionProps> = ({
       settings={claudeSettings}
       onLinkDetected={handleLinkDetected}
       onRevert={handleRevert}
+      onReloadSession={reloadSessionMessages}
       getPromptIndexForMessage={getPromptIndexForMessage}
     >
       <SessionMessages
@@ -919,6 +949,46 @@ const ClaudeCodeSessionInner: React.FC<ClaudeCodeSessionProps> = ({
     </SessionProvider>
   );
 
+  // 跨引擎评审等建立的关联会话
+  const relatedSessionsBar = effectiveSession && (
+    <RelatedSessionsBar
+      engine={effectiveSession.engine || executionEngineConfig.engine || 'claude'}
+      sessionId={effectiveSession.id}
+    />
+  );
+
+  // 会话文件中无法解析、加载时被跳过的行
+  const corruptedLinesBar = effectiveSession && (
+    <CorruptedLinesBar
+      key={effectiveSession.id}
+      corruptedLines={corruptedLines}
+      sessionId={effectiveSession.id}
+      projectId={effectiveSession.project_id}
+      onRepaired={loadSessionHistory}
+    />
+  );
+
+  // 会话中 MCP 调用的汇总（Gemini 会话不记录 MCP 调用名）
+  const sessionEngine = effectiveSession?.engine || executionEngineConfig.engine || 'claude';
+  const mcpCallSummaryBar = effectiveSession && sessionEngine !== 'gemini' && (
+    <McpCallSummaryBar
+      engine={sessionEngine}
+      sessionId={effectiveSession.id}
+      projectId={effectiveSession.project_id}
+      isRunning={isLoading}
+    />
+  );
+
+  // 会话中耗时超过阈值的工具调用（Gemini 会话按项目路径定位）
+  const slowOperationsBar = effectiveSession && (
+    <SlowOperationsBar
+      engine={sessionEngine}
+      sessionId={effectiveSession.id}
+      projectId={sessionEngine === 'gemini' ? effectiveSession.project_path : effectiveSession.project_id}
+      isRunning={isLoading}
+    />
+  );
+
   // Show project path input only when:
   // 1. No initial session prop AND
   // 2. No extracted session info (from successful first response)
@@ -973,6 +1043,10 @@ const ClaudeCodeSessionInner: React.FC<ClaudeCodeSessionProps> = ({
               left={
                 <div className="h-full flex flex-col">
                   {projectPathInput}
+                  {relatedSessionsBar}
+                  {corruptedLinesBar}
+                  {mcpCallSummaryBar}
+                  {slowOperationsBar}
                   <PlanModeStatusBar isPlanMode={isPlanMode} />
                   {messagesList}
                 </div>
@@ -997,6 +1071,10 @@ const ClaudeCodeSessionInner: React.FC<ClaudeCodeSessionProps> = ({
             // 消息区域独立滚动，输入区域固定在底部
             <div className="h-full flex flex-col relative">
               {projectPathInput}
+              {relatedSessionsBar}
+              {corruptedLinesBar}
+              {mcpCallSummaryBar}
+              {slowOperationsBar}
               <PlanModeStatusBar isPlanMode={isPlanMode} />
               {messagesList}
 
@@ -1135,7 +1213,9 @@ const ClaudeCodeSessionInner: React.FC<ClaudeCodeSessionProps> = ({
                       <div className="flex items-center gap-2 mb-1">
                         <span className="text-xs font-medium text-muted-foreground">#{index + 1}</span>
                         <span className="text-xs px-1.5 py-0.5 bg-primary/10 text-primary rounded">
-                          {queuedPrompt.model === "opus" ? "Opus" : queuedPrompt.model === "sonnet1m" ? "Sonnet 1M" : "Sonnet"}
+                          {queuedPrompt.source === 'interjection'
+                            ? t('session.interjection')
+                            : queuedPrompt.model === "opus" ? "Opus" : queuedPrompt.model === "sonnet1m" ? "Sonnet 1M" : "Sonnet"}
                         </span>
                       </div>
                       <p className="text-sm line-clamp-2 break-words">{queuedPrompt.prompt}</p>
@@ -1144,7 +1224,13 @@ const ClaudeCodeSessionInner: React.FC<ClaudeCodeSessionProps> = ({
                       variant="ghost"
                       size="icon"
                       className="h-6 w-6 flex-shrink-0"
-                      onClick={() => setQueuedPrompts(prev => prev.filter(p => p.id !== queuedPrompt.id))}
+                      onClick={() => {
+                        if (queuedPrompt.source === 'interjection') {
+                          api.cancelInterjection(queuedPrompt.id).catch(err =>
+                            console.warn('[ClaudeCodeSession] Failed to cancel interjection:', err));
+                        }
+                        setQueuedPrompts(prev => prev.filter(p => p.id !== queuedPrompt.id));
+                      }}
                     >
                       <X className="h-3 w-3" />
                     </Button>
@@ -1205,6 +1291,21 @@ const ClaudeCodeSessionInner: React.FC<ClaudeCodeSessionProps> = ({
           onReject={rejectPlan}
         />
 
+        {/* Revert Conflict Dialog - 交互式撤回冲突逐块解决 */}
+        <RevertConflictDialog
+          open={pendingRevertConflict !== null}
+          projectPath={projectPath}
+          onResolved={() => {
+            const pending = pendingRevertConflict;
+            setPendingRevertConflict(null);
+            // 代码已撤回完成，"both" 模式还需要撤回对话
+            if (pending?.mode === 'both') {
+              handleRevert(pending.promptIndex, 'conversation_only');
+            }
+          }}
+          onAborted={() => setPendingRevertConflict(null)}
+        />
+
         {/* 🆕 User Question Dialog - AskUserQuestion 自动触发 */}
         <AskUserQuestionDialog
           open={showQuestionDialog}
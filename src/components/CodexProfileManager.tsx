@@ -0,0 +1,202 @@
+import { useEffect, useState } from 'react';
+import { Edit, Layers, Plus, RefreshCw, Trash } from 'lucide-react';
+import { Button } from "@/components/ui/button";
+import { Card } from "@/components/ui/card";
+import { Badge } from "@/components/ui/badge";
+import { Input } from "@/components/ui/input";
+import { Label } from "@/components/ui/label";
+import { Dialog, DialogContent, DialogFooter, DialogHeader, DialogTitle } from "@/components/ui/dialog";
+import { api, type CodexProfile, type CodexProfileInput } from '@/lib/api';
+import { useTranslation } from "@/hooks/useTranslation";
+
+interface CodexProfileManagerProps {
+  setToast: (toast: { message: string; type: 'success' | 'error' } | null) => void;
+}
+
+const FIELDS: { key: keyof CodexProfileInput; placeholder: string }[] = [
+  { key: 'model', placeholder: 'gpt-5.2-codex' },
+  { key: 'modelProvider', placeholder: 'openai' },
+  { key: 'approvalPolicy', placeholder: 'untrusted | on-failure | on-request | never' },
+  { key: 'sandboxMode', placeholder: 'read-only | workspace-write | danger-full-access' },
+  { key: 'modelReasoningEffort', placeholder: 'low | medium | high | xhigh' },
+  { key: 'modelVerbosity', placeholder: 'low | medium | high' },
+];
+
+const EMPTY_INPUT: CodexProfileInput = {};
+
+/**
+ * Codex 命名 profile 管理：编辑 config.toml 中的 [profiles.*] 段，其他内容保持不变
+ */
+export default function CodexProfileManager({ setToast }: CodexProfileManagerProps) {
+  const { t } = useTranslation();
+  const [profiles, setProfiles] = useState<CodexProfile[]>([]);
+  const [loading, setLoading] = useState(false);
+  const [editing, setEditing] = useState<{ original: string | null; name: string; input: CodexProfileInput } | null>(null);
+  const [saving, setSaving] = useState(false);
+
+  const loadProfiles = async () => {
+    setLoading(true);
+    try {
+      setProfiles(await api.getCodexProfiles());
+    } catch (error) {
+      setToast({ message: String(error), type: 'error' });
+    } finally {
+      setLoading(false);
+    }
+  };
+
+  useEffect(() => {
+    loadProfiles();
+    // eslint-disable-next-line react-hooks/exhaustive-deps
+  }, []);
+
+  const handleSave = async () => {
+    if (!editing) return;
+    setSaving(true);
+    try {
+      if (editing.original) {
+        await api.updateCodexProfile(editing.original, editing.input);
+      } else {
+        await api.createCodexProfile(editing.name.trim(), editing.input);
+      }
+      setToast({ message: t('codexProfile.saved'), type: 'success' });
+      setEditing(null);
+      await loadProfiles();
+    } catch (error) {
+      setToast({ message: String(error), type: 'error' });
+    } finally {
+      setSaving(false);
+    }
+  };
+
+  const handleDelete = async (name: string) => {
+    try {
+      await api.deleteCodexProfile(name);
+      setToast({ message: t('codexProfile.deleted', { name }), type: 'success' });
+      await loadProfiles();
+    } catch (error) {
+      setToast({ message: String(error), type: 'error' });
+    }
+  };
+
+  return (
+    <div className="space-y-3">
+      <div className="flex items-center justify-between">
+        <div>
+          <h2 className="text-sm font-medium flex items-center gap-2">
+            <Layers className="h-4 w-4" />
+            {t('codexProfile.title')}
+          </h2>
+          <p className="text-xs text-muted-foreground">{t('codexProfile.description')}</p>
+        </div>
+        <div className="flex items-center gap-2">
+          <Button variant="ghost" size="sm" onClick={loadProfiles} disabled={loading} className="text-xs">
+            <RefreshCw className={`h-3 w-3 ${loading ? 'animate-spin' : ''}`} aria-hidden="true" />
+          </Button>
+          <Button
+            variant="outline"
+            size="sm"
+            onClick={() => setEditing({ original: null, name: '', input: EMPTY_INPUT })}
+            className="text-xs"
+          >
+            <Plus className="h-3 w-3 mr-1" aria-hidden="true" />
+            {t('codexProfile.add')}
+          </Button>
+        </div>
+      </div>
+
+      {profiles.length === 0 && !loading && (
+        <p className="text-xs text-muted-foreground">{t('codexProfile.empty')}</p>
+      )}
+
+      {profiles.map((profile) => (
+        <Card key={profile.name} className="p-3">
+          <div className="flex items-center justify-between gap-2">
+            <div className="min-w-0">
+              <div className="flex items-center gap-2">
+                <span className="font-mono text-sm">{profile.name}</span>
+                {profile.isDefault && <Badge variant="secondary">{t('codexProfile.default')}</Badge>}
+              </div>
+              <p className="text-xs text-muted-foreground truncate">
+                {[profile.model, profile.modelProvider, profile.approvalPolicy, profile.sandboxMode, profile.modelReasoningEffort, profile.modelVerbosity]
+                  .filter(Boolean)
+                  .join(' · ') || t('codexProfile.noFields')}
+              </p>
+            </div>
+            <div className="flex items-center gap-1 shrink-0">
+              <Button
+                variant="ghost"
+                size="sm"
+                onClick={() => setEditing({
+                  original: profile.name,
+                  name: profile.name,
+                  input: {
+                    model: profile.model,
+                    modelProvider: profile.modelProvider,
+                    approvalPolicy: profile.approvalPolicy,
+                    sandboxMode: profile.sandboxMode,
+                    modelReasoningEffort: profile.modelReasoningEffort,
+                    modelVerbosity: profile.modelVerbosity,
+                  },
+                })}
+                aria-label={t('codexProfile.edit')}
+              >
+                <Edit className="h-3 w-3" aria-hidden="true" />
+              </Button>
+              <Button
+                variant="ghost"
+                size="sm"
+                onClick={() => handleDelete(profile.name)}
+                aria-label={t('codexProfile.delete')}
+              >
+                <Trash className="h-3 w-3" aria-hidden="true" />
+              </Button>
+            </div>
+          </div>
+        </Card>
+      ))}
+
+      <Dialog open={editing !== null} onOpenChange={(open) => !open && !saving && setEditing(null)}>
+        <DialogContent className="max-w-md">
+          <DialogHeader>
+            <DialogTitle>{editing?.original ? t('codexProfile.edit') : t('codexProfile.add')}</DialogTitle>
+          </DialogHeader>
+          {editing && (
+            <div className="space-y-3">
+              <div className="space-y-1">
+                <Label className="text-xs">{t('codexProfile.name')}</Label>
+                <Input
+                  value={editing.name}
+                  onChange={(e) => setEditing({ ...editing, name: e.target.value })}
+                  disabled={editing.original !== null}
+                  placeholder="fast"
+                  className="font-mono text-xs"
+                />
+              </div>
+              {FIELDS.map(({ key, placeholder }) => (
+                <div key={key} className="space-y-1">
+                  <Label className="text-xs">{t(`codexProfile.fields.${key}`)}</Label>
+                  <Input
+                    value={editing.input[key] ?? ''}
+                    onChange={(e) => setEditing({ ...editing, input: { ...editing.input, [key]: e.target.value } })}
+                    placeholder={placeholder}
+                    className="font-mono text-xs"
+                  />
+                </div>
+              ))}
+            </div>
+          )}
+          <DialogFooter>
+            <Button variant="outline" onClick={() => setEditing(null)} disabled={saving}>
+              {t('buttons.cancel')}
+            </Button>
+            <Button onClick={handleSave} disabled={saving || !editing?.name.trim()}>
+              {saving && <RefreshCw className="h-3 w-3 mr-1 animate-spin" aria-hidden="true" />}
+              {t('codexProfile.save')}
+            </Button>
+          </DialogFooter>
+        </DialogContent>
+      </Dialog>
+    </div>
+  );
+}
@@ -22,11 +22,12 @@ import {
   Key,
   Settings2,
 } from 'lucide-react';
-import { type CodexProviderConfig } from '@/lib/api';
+import { api, type CodexProviderConfig } from '@/lib/api';
 import { Toast } from '@/components/ui/toast';
 import { cn } from '@/lib/utils';
 import {
   codexProviderPresets,
+  mergeRemotePresets,
   generateThirdPartyAuth,
   generateThirdPartyConfig,
   extractBaseUrlFromConfig,
@@ -37,6 +38,7 @@ import {
   type ProviderCategory,
 } from '@/config/codexProviderPresets';
 import { useTranslation } from "@/hooks/useTranslation";
+import { parseCustomHeaders, formatCustomHeaders } from '@/lib/customHeaders';
 
 interface CodexProviderFormProps {
   initialData?: CodexProviderConfig;
@@ -52,6 +54,7 @@ export default function CodexProviderForm({
   const { t } = useTranslation();
   // 预设选择
   const [selectedPreset, setSelectedPreset] = useState<string>('');
+  const [presetOptions, setPresetOptions] = useState(codexProviderPresets);
 
   // 基础字段
   const [name, setName] = useState(initialData?.name || '');
@@ -69,6 +72,7 @@ export default function CodexProviderForm({
   // 原始配置（用于高级编辑）
   const [authJson, setAuthJson] = useState('{}');
   const [configToml, setConfigToml] = useState('');
+  const [customHeadersText, setCustomHeadersText] = useState(formatCustomHeaders(initialData?.customHeaders));
 
   // 状态
   const [loading, setLoading] = useState(false);
@@ -89,10 +93,18 @@ export default function CodexProviderForm({
     }
   }, [initialData]);
 
+  // 新建时附上远程清单中的预设
+  useEffect(() => {
+    if (initialData) return;
+    api.getCodexProviderPresets()
+      .then((configs) => setPresetOptions(mergeRemotePresets(configs.filter(c => c.source === 'remote'))))
+      .catch((error) => console.warn('Failed to load remote Codex presets:', error));
+  }, [initialData]);
+
   // 预设选择变更处理
   const handlePresetChange = useCallback((presetId: string) => {
     setSelectedPreset(presetId);
-    const preset = codexProviderPresets.find(p => p.id === presetId);
+    const preset = presetOptions.find(p => p.id === presetId);
     if (preset) {
       setName(preset.name);
       setDescription(preset.description || '');
@@ -104,7 +116,7 @@ export default function CodexProviderForm({
       setAuthJson(JSON.stringify(preset.auth, null, 2));
       setConfigToml(preset.config);
     }
-  }, []);
+  }, [presetOptions]);
 
   // API Key 变更时更新 auth.json
   const handleApiKeyChange = useCallback((newKey: string) => {
@@ -190,6 +202,12 @@ export default function CodexProviderForm({
         finalConfig = '';
       }
 
+      const parsedHeaders = parseCustomHeaders(customHeadersText);
+      if (parsedHeaders.error) {
+        setToastMessage({ message: t('provider.customHeadersInvalid', { line: parsedHeaders.error }), type: 'error' });
+        return;
+      }
+
       const submitData: Omit<CodexProviderConfig, 'id'> = {
         name: name.trim(),
         description: description.trim(),
@@ -198,6 +216,7 @@ export default function CodexProviderForm({
         auth: finalAuth,
         config: finalConfig,
         isOfficial: category === 'official',
+        customHeaders: category === 'official' ? undefined : parsedHeaders.headers,
       };
 
       await onSubmit(submitData);
@@ -230,7 +249,7 @@ export default function CodexProviderForm({
                 <SelectValue placeholder={t('provider.selectPresetPlaceholder')} />
               </SelectTrigger>
               <SelectContent>
-                {codexProviderPresets.map((preset) => (
+                {presetOptions.map((preset) => (
                   <SelectItem key={preset.id} value={preset.id}>
                     <div className="flex items-center gap-2">
                       <span>{preset.name}</span>
@@ -439,6 +458,25 @@ export default function CodexProviderForm({
                   {t('provider.configTomlHelp')}
                 </p>
               </div>
+
+              {/* custom headers */}
+              <div className="space-y-2">
+                <Label htmlFor="customHeaders" className="flex items-center gap-2">
+                  <FileCode className="h-4 w-4" />
+                  {t('provider.customHeadersLabel')}
+                </Label>
+                <Textarea
+                  id="customHeaders"
+                  value={customHeadersText}
+                  onChange={(e) => setCustomHeadersText(e.target.value)}
+                  placeholder={'X-Org-Id: your-org\nX-Request-Source: any-code'}
+                  className="font-mono text-xs h-20"
+                  disabled={loading}
+                />
+                <p className="text-xs text-muted-foreground">
+                  {t('provider.customHeadersHelp')}
+                </p>
+              </div>
             </div>
           )}
         </div>
@@ -22,8 +22,10 @@ import {
 import { api, type CodexProviderConfig, type CurrentCodexConfig } from '@/lib/api';
 import { Toast } from '@/components/ui/toast';
 import CodexProviderForm from './CodexProviderForm';
+import CodexProfileManager from './CodexProfileManager';
 import {
   codexProviderPresets,
+  mergeRemotePresets,
   extractApiKeyFromAuth,
   extractBaseUrlFromConfig,
   extractModelFromConfig,
@@ -78,8 +80,10 @@ export default function CodexProviderManager({ onBack }: CodexProviderManagerPro
         console.warn('Failed to load current Codex config:', error);
       }
 
-      // 合并内置预设和自定义预设
-      const builtInPresets: CodexProviderConfig[] = codexProviderPresets
+      // 远程清单预设与内置预设合并（同名以远程为准），用户预设放在后面
+      const remotePresets = customPresets.filter(p => p.source === 'remote');
+      const userPresets = customPresets.filter(p => p.source !== 'remote');
+      const builtInPresets: CodexProviderConfig[] = mergeRemotePresets(remotePresets)
         .filter(p => !p.isCustomTemplate) // 排除自定义模板
         .map(p => ({
           id: p.id,
@@ -91,10 +95,10 @@ export default function CodexProviderManager({ onBack }: CodexProviderManagerPro
           config: p.config,
           isOfficial: p.isOfficial,
           isPartner: p.isPartner,
+          source: p.source,
         }));
 
-      // 自定义预设放在内置预设后面
-      setPresets([...builtInPresets, ...customPresets]);
+      setPresets([...builtInPresets, ...userPresets]);
       setCurrentConfig(config);
     } catch (error) {
       console.error('Failed to load Codex provider data:', error);
@@ -137,7 +141,7 @@ export default function CodexProviderManager({ onBack }: CodexProviderManagerPro
       setTesting(config.id);
       const baseUrl = extractBaseUrlFromConfig(config.config);
       const apiKey = extractApiKeyFromAuth(config.auth);
-      const message = await api.testCodexProviderConnection(baseUrl, apiKey);
+      const message = await api.testCodexProviderConnection(baseUrl, apiKey, config.customHeaders);
       setToastMessage({ message, type: 'success' });
     } catch (error) {
       console.error('Failed to test Codex connection:', error);
@@ -251,6 +255,10 @@ export default function CodexProviderManager({ onBack }: CodexProviderManagerPro
 
   // 判断是否为内置预设（不能删除）
   const isBuiltInPreset = (config: CodexProviderConfig): boolean => {
+    // 远程清单预设同样只读
+    if (config.source === 'remote') {
+      return true;
+    }
     // 如果配置有 createdAt 字段（即使值为 null），说明是用户自定义的配置
     // 内置预设从代码中加载，不会有 createdAt 字段
     if ('createdAt' in config) {
@@ -395,6 +403,11 @@ export default function CodexProviderManager({ onBack }: CodexProviderManagerPro
                           {t('provider.partner')}
                         </Badge>
                       )}
+                      {config.source === 'remote' && (
+                        <Badge variant="outline" className="text-xs bg-purple-50 dark:bg-purple-950">
+                          {t('provider.remotePreset')}
+                        </Badge>
+                      )}
                       {config.category && (
                         <Badge variant="outline" className="text-xs">
                           {t(getCategoryKey(config.category))}
@@ -523,6 +536,10 @@ export default function CodexProviderManager({ onBack }: CodexProviderManagerPro
               </Button>
             </div>
           )}
+
+          <div className="border-t pt-4">
+            <CodexProfileManager setToast={setToastMessage} />
+          </div>
         </div>
       </div>
 
@@ -5,7 +5,7 @@
  * with appropriate configuration options for each.
  */
 
-import React, { useState } from 'react';
+import React, { useEffect, useState } from 'react';
 import { Settings, Zap, Check, Monitor, Terminal, Sparkles } from 'lucide-react';
 import { Button } from '@/components/ui/button';
 import {
@@ -17,11 +17,13 @@ import {
 } from '@/components/ui/select';
 import { Popover } from '@/components/ui/popover';
 import { Label } from '@/components/ui/label';
-import { api } from '@/lib/api';
+import { Switch } from '@/components/ui/switch';
+import { api, type CodexCapabilities, type CodexProfile } from '@/lib/api';
 import { relaunchApp } from '@/lib/updater';
 import { ask, message } from '@tauri-apps/plugin-dialog';
 import { useEngineStatus } from '@/hooks/useEngineStatus';
-import type { CodexExecutionMode } from '@/types/codex';
+import type { CodexExecutionMode, CodexVerbosity } from '@/types/codex';
+import type { GeminiModelInfo } from '@/types/gemini';
 
 // ============================================================================
 // Type Definitions
@@ -36,12 +38,20 @@ export interface ExecutionEngineConfig {
   // Codex-specific config
   codexMode?: CodexExecutionMode;
   codexModel?: string;
+  /** Named profile from Codex config.toml (`--profile`) */
+  codexProfile?: string;
   codexApiKey?: string;
   /** Codex reasoning effort level: low, medium, high, xhigh */
   codexReasoningLevel?: 'low' | 'medium' | 'high' | 'xhigh';
+  /** Codex output verbosity (`-c model_verbosity`), CLI default when unset */
+  codexVerbosity?: CodexVerbosity;
+  /** Refuse to run when the Codex CLI does not support the reasoning/verbosity override */
+  codexStrictConfig?: boolean;
   // Gemini-specific config
   geminiModel?: string;
   geminiApprovalMode?: 'auto_edit' | 'yolo' | 'default';
+  /** Google Search switch; undefined keeps the CLI default (available) */
+  geminiSearchGrounding?: boolean;
 }
 
 interface CodexModeConfig {
@@ -54,6 +64,12 @@ interface CodexModeConfig {
   isWindows: boolean;
 }
 
+/** Select value for running without a Codex profile */
+const NO_CODEX_PROFILE = '__none__';
+
+/** Select value for leaving verbosity to the CLI default */
+const DEFAULT_CODEX_VERBOSITY = '__default__';
+
 // Gemini WSL mode configuration (similar to Codex)
 export type GeminiRuntimeMode = 'auto' | 'native' | 'wsl';
 
@@ -100,6 +116,9 @@ export const ExecutionEngineSelector: React.FC<ExecutionEngineSelectorProps> = (
 }) => {
   const [showSettings, setShowSettings] = useState(false);
   const [savingConfig, setSavingConfig] = useState(false);
+  const [codexProfiles, setCodexProfiles] = useState<CodexProfile[]>([]);
+  const [codexCapabilities, setCodexCapabilities] = useState<CodexCapabilities | null>(null);
+  const [geminiModels, setGeminiModels] = useState<GeminiModelInfo[]>([]);
 
   // 使用全局缓存的引擎状态（包括模式配置）
   const {
@@ -367,6 +386,33 @@ export const ExecutionEngineSelector: React.FC<ExecutionEngineSelectorProps> = (
     });
   };
 
+  // 打开设置时读取 config.toml 中的 profile（WSL 模式下为 WSL 侧配置）
+  useEffect(() => {
+    if (!showSettings || value.engine !== 'codex') return;
+    api.getCodexProfiles()
+      .then(setCodexProfiles)
+      .catch((error) => console.warn('[ExecutionEngineSelector] Failed to load Codex profiles:', error));
+    api.getCodexCapabilities()
+      .then(setCodexCapabilities)
+      .catch((error) => console.warn('[ExecutionEngineSelector] Failed to load Codex capabilities:', error));
+  }, [showSettings, value.engine]);
+
+  const handleCodexProfileChange = (profile: string) => {
+    onChange({
+      ...value,
+      codexProfile: profile === NO_CODEX_PROFILE ? undefined : profile,
+    });
+  };
+
+  const selectedCodexProfile = codexProfiles.find((p) => p.name === value.codexProfile);
+
+  const handleCodexVerbosityChange = (verbosity: string) => {
+    onChange({
+      ...value,
+      codexVerbosity: verbosity === DEFAULT_CODEX_VERBOSITY ? undefined : verbosity as CodexVerbosity,
+    });
+  };
+
   const handleGeminiApprovalModeChange = (mode: 'auto_edit' | 'yolo' | 'default') => {
     onChange({
       ...value,
@@ -374,6 +420,17 @@ export const ExecutionEngineSelector: React.FC<ExecutionEngineSelectorProps> = (
     });
   };
 
+  // 打开设置时读取模型能力，用于禁用当前模型不支持的内置工具开关
+  useEffect(() => {
+    if (!showSettings || value.engine !== 'gemini') return;
+    api.getGeminiModels()
+      .then(setGeminiModels)
+      .catch((error) => console.warn('[ExecutionEngineSelector] Failed to load Gemini models:', error));
+  }, [showSettings, value.engine]);
+
+  // 未知模型不做限制，由 CLI 自行处理
+  const geminiCapabilities = geminiModels.find((m) => m.id === value.geminiModel)?.capabilities;
+
   // Get display name for current engine
   const getEngineDisplayName = () => {
     switch (value.engine) {
@@ -494,6 +551,80 @@ export const ExecutionEngineSelector: React.FC<ExecutionEngineSelectorProps> = (
                 </Select>
               </div>
 
+              {/* Profile */}
+              <div className="space-y-2">
+                <Label className="text-sm font-medium">Profile</Label>
+                <Select
+                  value={value.codexProfile || NO_CODEX_PROFILE}
+                  onValueChange={handleCodexProfileChange}
+                >
+                  <SelectTrigger>
+                    <SelectValue />
+                  </SelectTrigger>
+                  <SelectContent>
+                    <SelectItem value={NO_CODEX_PROFILE}>不使用 profile</SelectItem>
+                    {value.codexProfile && !selectedCodexProfile && (
+                      <SelectItem value={value.codexProfile}>{value.codexProfile}（未找到）</SelectItem>
+                    )}
+                    {codexProfiles.map((profile) => (
+                      <SelectItem key={profile.name} value={profile.name}>
+                        <div>
+                          <div className="font-medium">
+                            {profile.name}{profile.isDefault ? '（默认）' : ''}
+                          </div>
+                          <div className="text-xs text-muted-foreground">
+                            {[profile.model, profile.approvalPolicy, profile.sandboxMode].filter(Boolean).join(' · ') || '无模型/审批设置'}
+                          </div>
+                        </div>
+                      </SelectItem>
+                    ))}
+                  </SelectContent>
+                </Select>
+                {selectedCodexProfile && (selectedCodexProfile.approvalPolicy || selectedCodexProfile.sandboxMode || selectedCodexProfile.model) && (
+                  <p className="text-xs text-muted-foreground">
+                    profile 中设置的模型与审批策略优先于执行模式和模型选择
+                  </p>
+                )}
+              </div>
+
+              {/* Verbosity */}
+              <div className="space-y-2">
+                <Label className="text-sm font-medium">输出详略</Label>
+                <Select
+                  value={value.codexVerbosity || DEFAULT_CODEX_VERBOSITY}
+                  onValueChange={handleCodexVerbosityChange}
+                >
+                  <SelectTrigger>
+                    <SelectValue />
+                  </SelectTrigger>
+                  <SelectContent>
+                    <SelectItem value={DEFAULT_CODEX_VERBOSITY}>CLI 默认</SelectItem>
+                    <SelectItem value="low">简洁 (low)</SelectItem>
+                    <SelectItem value="medium">适中 (medium)</SelectItem>
+                    <SelectItem value="high">详细 (high)</SelectItem>
+                  </SelectContent>
+                </Select>
+                {codexCapabilities?.unsupportedKeys.includes('model_verbosity') && (
+                  <p className="text-xs text-muted-foreground">
+                    当前 Codex CLI{codexCapabilities.version ? `（${codexCapabilities.version}）` : ''}不支持该设置，请升级后使用
+                  </p>
+                )}
+              </div>
+
+              {/* Strict config */}
+              <div className="flex items-center justify-between gap-2">
+                <div>
+                  <Label className="text-sm font-medium">严格配置检查</Label>
+                  <p className="text-xs text-muted-foreground">
+                    当前 CLI 不支持推理强度或输出详略设置时拒绝执行，关闭时忽略该设置并提示
+                  </p>
+                </div>
+                <Switch
+                  checked={!!value.codexStrictConfig}
+                  onCheckedChange={(checked) => onChange({ ...value, codexStrictConfig: checked })}
+                />
+              </div>
+
               {/* Status */}
               <div className="rounded-md border p-2 bg-muted/50">
                 <div className="flex items-center gap-2 text-xs">
@@ -646,6 +777,24 @@ export const ExecutionEngineSelector: React.FC<ExecutionEngineSelectorProps> = (
                 </Select>
               </div>
 
+              {/* Built-in tools */}
+              <div className="space-y-2">
+                <Label className="text-sm font-medium">内置工具</Label>
+                <div className="flex items-center justify-between">
+                  <div>
+                    <div className="text-sm">联网搜索</div>
+                    <div className="text-xs text-muted-foreground">
+                      {geminiCapabilities && !geminiCapabilities.searchGrounding ? '当前模型不支持' : '用 Google 搜索为回答提供引用来源'}
+                    </div>
+                  </div>
+                  <Switch
+                    checked={value.geminiSearchGrounding ?? geminiCapabilities?.searchGrounding ?? true}
+                    disabled={geminiCapabilities ? !geminiCapabilities.searchGrounding : false}
+                    onCheckedChange={(checked) => onChange({ ...value, geminiSearchGrounding: checked })}
+                  />
+                </div>
+              </div>
+
               {/* Status */}
               <div className="rounded-md border p-2 bg-muted/50">
                 <div className="flex items-center gap-2 text-xs">
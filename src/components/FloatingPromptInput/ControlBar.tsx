@@ -1,11 +1,11 @@
-import React, { useMemo } from "react";
+import React, { useMemo, useState } from "react";
 import { useTranslation } from "react-i18next";
 import { Button } from "@/components/ui/button";
 import { Badge } from "@/components/ui/badge";
 import { Popover } from "@/components/ui/popover";
 import { DropdownMenu, DropdownMenuContent, DropdownMenuItem, DropdownMenuTrigger, DropdownMenuSeparator } from "@/components/ui/dropdown-menu";
 import { Switch } from "@/components/ui/switch";
-import { Wand2, ChevronDown, DollarSign, Info, Settings, Code2, Zap } from "lucide-react";
+import { Wand2, ChevronDown, DollarSign, Info, Settings, Code2, Zap, Send, Link, FileClock } from "lucide-react";
 import { motion } from "framer-motion";
 import { formatDuration } from "@/lib/pricing";
 import { ExecutionEngineSelector, type ExecutionEngineConfig } from "@/components/ExecutionEngineSelector";
@@ -20,6 +20,14 @@ import { SessionToolbar } from "@/components/SessionToolbar";
 import { ContextWindowIndicator } from "@/components/widgets/ContextWindowIndicator";
 import { ModelType, ModelConfig } from "./types";
 import type { CodexRateLimits } from "@/types/codex";
+import {
+  isEnhanceOnSendEnabled,
+  isFileSnapshotEnabled,
+  isUrlPrefetchEnabled,
+  setEnhanceOnSendEnabled,
+  setFileSnapshotEnabled,
+  setUrlPrefetchEnabled,
+} from "@/lib/enhanceOnSend";
 
 interface ControlBarProps {
   disabled?: boolean;
@@ -89,6 +97,9 @@ export const ControlBar: React.FC<ControlBarProps> = ({
   onSend
 }) => {
   const { t } = useTranslation();
+  const [enhanceOnSend, setEnhanceOnSend] = useState(isEnhanceOnSendEnabled);
+  const [prefetchUrls, setPrefetchUrls] = useState(isUrlPrefetchEnabled);
+  const [fileSnapshots, setFileSnapshots] = useState(isFileSnapshotEnabled);
 
   const contextWindowModel =
     executionEngineConfig.engine === 'codex'
@@ -306,6 +317,75 @@ export const ControlBar: React.FC<ControlBarProps> = ({
                   />
                 </label>
               </div>
+              {/* Enhance On Send Switch */}
+              <div className="px-2 py-1.5">
+                <label className="flex items-center justify-between cursor-pointer hover:bg-accent/50 rounded px-2 py-1.5 transition-colors">
+                  <div className="flex items-center gap-2">
+                    <Send className={`h-4 w-4 ${enhanceOnSend ? 'text-primary' : 'text-muted-foreground'}`} />
+                    <div>
+                      <div className={`text-sm font-medium ${enhanceOnSend ? 'text-primary' : ''}`}>
+                        {t('promptInput.enhanceOnSend')}
+                      </div>
+                      <p className="text-xs text-muted-foreground">
+                        {t('promptInput.enhanceOnSendDescription')}
+                      </p>
+                    </div>
+                  </div>
+                  <Switch
+                    checked={enhanceOnSend}
+                    onCheckedChange={(checked) => {
+                      setEnhanceOnSend(checked);
+                      setEnhanceOnSendEnabled(checked);
+                    }}
+                  />
+                </label>
+              </div>
+              {/* Prefetch URLs Switch */}
+              <div className="px-2 py-1.5">
+                <label className="flex items-center justify-between cursor-pointer hover:bg-accent/50 rounded px-2 py-1.5 transition-colors">
+                  <div className="flex items-center gap-2">
+                    <Link className={`h-4 w-4 ${prefetchUrls ? 'text-primary' : 'text-muted-foreground'}`} />
+                    <div>
+                      <div className={`text-sm font-medium ${prefetchUrls ? 'text-primary' : ''}`}>
+                        {t('promptInput.prefetchUrls')}
+                      </div>
+                      <p className="text-xs text-muted-foreground">
+                        {t('promptInput.prefetchUrlsDescription')}
+                      </p>
+                    </div>
+                  </div>
+                  <Switch
+                    checked={prefetchUrls}
+                    onCheckedChange={(checked) => {
+                      setPrefetchUrls(checked);
+                      setUrlPrefetchEnabled(checked);
+                    }}
+                  />
+                </label>
+              </div>
+              {/* File Snapshots Switch */}
+              <div className="px-2 py-1.5">
+                <label className="flex items-center justify-between cursor-pointer hover:bg-accent/50 rounded px-2 py-1.5 transition-colors">
+                  <div className="flex items-center gap-2">
+                    <FileClock className={`h-4 w-4 ${fileSnapshots ? 'text-primary' : 'text-muted-foreground'}`} />
+                    <div>
+                      <div className={`text-sm font-medium ${fileSnapshots ? 'text-primary' : ''}`}>
+                        {t('promptInput.fileSnapshots')}
+                      </div>
+                      <p className="text-xs text-muted-foreground">
+                        {t('promptInput.fileSnapshotsDescription')}
+                      </p>
+                    </div>
+                  </div>
+                  <Switch
+                    checked={fileSnapshots}
+                    onCheckedChange={(checked) => {
+                      setFileSnapshots(checked);
+                      setFileSnapshotEnabled(checked);
+                    }}
+                  />
+                </label>
+              </div>
               <DropdownMenuSeparator className="bg-border/50" />
             </>
           )}
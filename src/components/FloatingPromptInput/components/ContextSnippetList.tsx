@@ -0,0 +1,51 @@
+/**
+ * ContextSnippetList Component
+ *
+ * 提示词增强期间实时显示 acemcp 多轮搜索找到的代码片段，
+ * 便于在结果明显偏题时提前中止
+ */
+
+import React from 'react';
+import { Search } from 'lucide-react';
+import { cn } from '@/lib/utils';
+import type { AcemcpContextSnippet } from '@/lib/api';
+
+interface ContextSnippetListProps {
+  /** 已找到的片段（按到达顺序） */
+  snippets: AcemcpContextSnippet[];
+  /** 自定义类名 */
+  className?: string;
+}
+
+/**
+ * 片段的标题行（"Path: xxx" 中的路径）
+ */
+function snippetTitle(snippet: string): string {
+  const firstLine = snippet.trimStart().split('\n', 1)[0] || '';
+  return firstLine.replace(/^Path:\s*/, '') || firstLine;
+}
+
+export const ContextSnippetList: React.FC<ContextSnippetListProps> = ({ snippets, className }) => {
+  if (snippets.length === 0) return null;
+
+  return (
+    <div className={cn('rounded-md border border-border/50 bg-muted/30 text-xs', className)}>
+      <div className="flex items-center gap-1.5 px-2.5 py-1.5 border-b border-border/50 text-muted-foreground">
+        <Search className="h-3.5 w-3.5" />
+        <span>已找到 {snippets.length} 个上下文片段</span>
+      </div>
+      <ul className="max-h-32 overflow-y-auto divide-y divide-border/30">
+        {snippets.map((item) => (
+          <li
+            key={item.index}
+            className="flex items-center gap-2 px-2.5 py-1 font-mono"
+            title={item.snippet}
+          >
+            <span className="shrink-0 text-muted-foreground/70">#{item.round}</span>
+            <span className="truncate">{snippetTitle(item.snippet)}</span>
+          </li>
+        ))}
+      </ul>
+    </div>
+  );
+};
@@ -8,6 +8,23 @@ interface UseDraftPersistenceOptions {
   onRestore?: (draft: string) => void;
 }
 
+/**
+ * 会话 id 变化（resume 时 CLI 换用新 id）后把草稿迁移到新会话，新会话已有草稿时保留新会话的
+ */
+export function migrateDraft(oldSessionId: string, newSessionId: string) {
+  try {
+    const oldKey = `${DRAFT_KEY_PREFIX}${oldSessionId}`;
+    const newKey = `${DRAFT_KEY_PREFIX}${newSessionId}`;
+    const draft = localStorage.getItem(oldKey);
+    if (draft && !localStorage.getItem(newKey)) {
+      localStorage.setItem(newKey, draft);
+    }
+    localStorage.removeItem(oldKey);
+  } catch (error) {
+    console.warn('[DraftPersistence] Failed to migrate draft:', error);
+  }
+}
+
 /**
  * 草稿持久化 Hook
  * 使用 localStorage 保存和恢复输入框草稿，支持按会话隔离
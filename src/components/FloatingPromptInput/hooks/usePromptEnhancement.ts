@@ -1,5 +1,6 @@
 import { useState } from "react";
-import { api } from "@/lib/api";
+import { listen } from "@tauri-apps/api/event";
+import { api, type AcemcpContextSnippet } from "@/lib/api";
 import { callEnhancementAPI, getProvider } from "@/lib/promptEnhancementService";
 import { enhancePromptWithDualAPI } from "@/lib/dualAPIEnhancement";
 import { loadContextConfig } from "@/lib/promptContextConfig";
@@ -66,6 +67,30 @@ function updateTextareaWithUndo(textarea: HTMLTextAreaElement, newText: string)
   }
 }
 
+/**
+ * 提取提示词中已经提供给模型的文件路径：@ 引用（含带引号的形式）与粘贴的项目内绝对路径
+ * 增强时据此剔除同一文件的 acemcp 片段，避免重复占用上下文
+ */
+function extractReferencedPaths(text: string, projectPath?: string): string[] {
+  const paths = new Set<string>();
+  const withoutQuoted = text.replace(/@"([^"]+)"/g, (_, path: string) => {
+    paths.add(path);
+    return '';
+  });
+  for (const match of withoutQuoted.matchAll(/@([^@\s]+)/g)) {
+    paths.add(match[1]);
+  }
+  if (projectPath) {
+    const root = projectPath.replace(/\\/g, '/').replace(/\/+$/, '');
+    for (const token of withoutQuoted.split(/\s+/)) {
+      if (token.replace(/\\/g, '/').startsWith(`${root}/`)) {
+        paths.add(token);
+      }
+    }
+  }
+  return Array.from(paths).filter((path) => !path.startsWith('data:'));
+}
+
 export function usePromptEnhancement({
   prompt,
   isExpanded,
@@ -81,6 +106,8 @@ export function usePromptEnhancement({
   enableMultiRound = true, // 🆕 默认启用多轮搜索
 }: UsePromptEnhancementOptions) {
   const [isEnhancing, setIsEnhancing] = useState(false);
+  // 多轮搜索过程中实时找到的代码片段（增强结束后清空）
+  const [contextSnippets, setContextSnippets] = useState<AcemcpContextSnippet[]>([]);
 
   // 🆕 智能上下文提取开关（默认启用）
   const [enableDualAPI, setEnableDualAPI] = useState(() => {
@@ -97,6 +124,15 @@ export function usePromptEnhancement({
       return null;
     }
 
+    // 多轮搜索每找到一个新片段就推送过来，实时展示便于发现偏题
+    const streamId = crypto.randomUUID();
+    setContextSnippets([]);
+    const unlisten = await listen<AcemcpContextSnippet>("acemcp-context-snippet", (event) => {
+      if (event.payload.streamId === streamId) {
+        setContextSnippets((prev) => [...prev, event.payload]);
+      }
+    });
+
     try {
       // 🆕 传递会话信息以启用历史上下文感知
       const result = await api.enhancePromptWithContext(
@@ -105,7 +141,10 @@ export function usePromptEnhancement({
         sessionId,        // 🆕 传递会话 ID
         projectId,        // 🆕 传递项目 ID
         3000,
-        enableMultiRound  // 🆕 启用多轮搜索
+        enableMultiRound, // 🆕 启用多轮搜索
+        undefined,
+        streamId,
+        extractReferencedPaths(prompt, projectPath)
       );
 
       if (result.acemcpUsed && result.contextCount > 0) {
@@ -125,6 +164,8 @@ export function usePromptEnhancement({
     } catch (error) {
       console.error('[getProjectContext] Failed:', error);
       return null;
+    } finally {
+      unlisten();
     }
   };
 
@@ -216,11 +257,13 @@ export function usePromptEnhancement({
       }
     } finally {
       setIsEnhancing(false);
+      setContextSnippets([]);
     }
   };
 
   return {
     isEnhancing,
+    contextSnippets,     // 多轮搜索实时片段
     handleEnhancePromptWithAPI,
     enableDualAPI,       // 🆕 暴露智能上下文开关状态
     setEnableDualAPI,    // 🆕 暴露开关控制函数
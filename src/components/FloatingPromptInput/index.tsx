@@ -20,6 +20,7 @@ import { getDefaultModel } from "./defaultModelStorage";
 import { InputArea } from "./InputArea";
 import { AttachmentPreview } from "./AttachmentPreview";
 import { ControlBar } from "./ControlBar";
+import { ContextSnippetList } from "./components/ContextSnippetList";
 import { ExpandedModal } from "./ExpandedModal";
 
 // Re-export types for external use
@@ -217,6 +218,7 @@ const FloatingPromptInputInner = (
 
   const {
     isEnhancing,
+    contextSnippets,
     handleEnhancePromptWithAPI,
     enableDualAPI,
     setEnableDualAPI,
@@ -644,6 +646,8 @@ const FloatingPromptInputInner = (
             engine={currentEngine}
           />
 
+          {isEnhancing && <ContextSnippetList snippets={contextSnippets} />}
+
           <ControlBar
             disabled={disabled}
             isLoading={isLoading}
@@ -0,0 +1,146 @@
+/**
+ * GeminiContextFilesPanel - Gemini 上下文文件控制
+ *
+ * 按项目设置 Gemini 执行时是否加载默认的 GEMINI.md 层级，
+ * 以及额外注入的工作区文件；可预览本次执行将加载的文件清单
+ */
+
+import { useEffect, useState } from "react";
+import { Eye, FileText, Loader2, Save } from "lucide-react";
+import { Button } from "@/components/ui/button";
+import { Card } from "@/components/ui/card";
+import { Label } from "@/components/ui/label";
+import { Switch } from "@/components/ui/switch";
+import { Textarea } from "@/components/ui/textarea";
+import { api } from "@/lib/api";
+import type { GeminiContextFiles } from "@/types/gemini";
+
+export interface GeminiContextFilesPanelProps {
+  /** 项目路径 */
+  projectPath: string;
+}
+
+export function GeminiContextFilesPanel({ projectPath }: GeminiContextFilesPanelProps) {
+  const [includeDefault, setIncludeDefault] = useState(true);
+  const [extraText, setExtraText] = useState("");
+  const [preview, setPreview] = useState<string[] | null>(null);
+  const [busy, setBusy] = useState<"save" | "preview" | null>(null);
+  const [error, setError] = useState<string | null>(null);
+  const [saved, setSaved] = useState(false);
+
+  useEffect(() => {
+    setError(null);
+    setPreview(null);
+    api
+      .getGeminiContextFiles(projectPath)
+      .then((config) => {
+        setIncludeDefault(config.includeDefaultContext);
+        setExtraText(config.extraContextFiles.join("\n"));
+      })
+      .catch((err) => setError(String(err)));
+  }, [projectPath]);
+
+  const currentConfig = (): GeminiContextFiles => ({
+    includeDefaultContext: includeDefault,
+    extraContextFiles: extraText
+      .split("\n")
+      .map((line) => line.trim())
+      .filter(Boolean),
+  });
+
+  const run = async (action: "save" | "preview", fn: () => Promise<void>) => {
+    setBusy(action);
+    setError(null);
+    setSaved(false);
+    try {
+      await fn();
+    } catch (err) {
+      setError(String(err));
+    } finally {
+      setBusy(null);
+    }
+  };
+
+  const handleSave = () =>
+    run("save", async () => {
+      await api.saveGeminiContextFiles(projectPath, currentConfig());
+      setSaved(true);
+    });
+
+  const handlePreview = () =>
+    run("preview", async () => {
+      setPreview(await api.previewGeminiContextFiles(projectPath, currentConfig()));
+    });
+
+  return (
+    <Card className="p-6 space-y-6">
+      <div className="flex items-start justify-between gap-4">
+        <div>
+          <h3 className="text-lg font-semibold mb-2 flex items-center gap-2">
+            <FileText className="h-5 w-5" />
+            Gemini 上下文文件
+          </h3>
+          <p className="text-sm text-muted-foreground">
+            Gemini CLI 默认会加载全局与项目目录中的
+            <code className="mx-1 px-2 py-1 bg-muted rounded text-xs">GEMINI.md</code>
+            作为指令。这里的设置作为该项目每次执行的默认值。
+          </p>
+        </div>
+        <div className="flex items-center gap-2 shrink-0">
+          <Label htmlFor="gemini-default-context" className="text-sm">加载默认上下文</Label>
+          <Switch
+            id="gemini-default-context"
+            checked={includeDefault}
+            onCheckedChange={setIncludeDefault}
+          />
+        </div>
+      </div>
+
+      <div className="space-y-2">
+        <Label className="text-sm">附加上下文文件</Label>
+        <Textarea
+          value={extraText}
+          onChange={(e) => setExtraText(e.target.value)}
+          placeholder={"每行一个路径（相对项目目录或绝对路径），例如：\ndocs/architecture.md"}
+          rows={4}
+          className="font-mono text-xs"
+        />
+        <p className="text-xs text-muted-foreground">
+          文件需位于工作区内，总大小不超过 200KB，以 @ 引用的形式附加到提示词
+        </p>
+      </div>
+
+      <div className="flex items-center gap-2">
+        <Button size="sm" onClick={handleSave} disabled={busy !== null} className="gap-2">
+          {busy === "save" ? <Loader2 className="h-4 w-4 animate-spin" /> : <Save className="h-4 w-4" />}
+          保存
+        </Button>
+        <Button size="sm" variant="outline" onClick={handlePreview} disabled={busy !== null} className="gap-2">
+          {busy === "preview" ? <Loader2 className="h-4 w-4 animate-spin" /> : <Eye className="h-4 w-4" />}
+          预览生效文件
+        </Button>
+        {saved && <span className="text-xs text-muted-foreground">已保存</span>}
+      </div>
+
+      {error && (
+        <div className="text-xs text-destructive bg-destructive/10 rounded-lg p-3 whitespace-pre-wrap">
+          {error}
+        </div>
+      )}
+
+      {preview !== null && (
+        <div className="rounded-md p-3 bg-muted/30 max-h-64 overflow-auto">
+          {preview.length === 0 ? (
+            <p className="text-xs text-muted-foreground">（不加载任何上下文文件）</p>
+          ) : (
+            <ul className="text-xs font-mono space-y-1">
+              {preview.map((file) => (
+                <li key={file} className="break-all">{file}</li>
+              ))}
+            </ul>
+          )}
+        </div>
+      )}
+    </Card>
+  );
+}
@@ -3,6 +3,7 @@ import { Button } from "@/components/ui/button";
 import { Input } from "@/components/ui/input";
 import { Label } from "@/components/ui/label";
 import { Card } from "@/components/ui/card";
+import { Textarea } from "@/components/ui/textarea";
 import {
   Select,
   SelectContent,
@@ -20,11 +21,12 @@ import {
   Sparkles,
   Key,
 } from 'lucide-react';
-import { type GeminiProviderConfig } from '@/lib/api';
+import { api, type GeminiProviderConfig } from '@/lib/api';
 import { Toast } from '@/components/ui/toast';
 import { cn } from '@/lib/utils';
 import {
   geminiProviderPresets,
+  mergeRemotePresets,
   generateThirdPartyEnv,
   extractApiKeyFromEnv,
   extractBaseUrlFromEnv,
@@ -32,6 +34,7 @@ import {
   type ProviderCategory,
 } from '@/config/geminiProviderPresets';
 import { useTranslation } from "@/hooks/useTranslation";
+import { parseCustomHeaders, formatCustomHeaders } from '@/lib/customHeaders';
 
 interface GeminiProviderFormProps {
   initialData?: GeminiProviderConfig;
@@ -47,6 +50,7 @@ export default function GeminiProviderForm({
   const { t } = useTranslation();
   // 预设选择
   const [selectedPreset, setSelectedPreset] = useState<string>('');
+  const [presetOptions, setPresetOptions] = useState(geminiProviderPresets);
 
   // 基础字段
   const [name, setName] = useState(initialData?.name || '');
@@ -60,6 +64,7 @@ export default function GeminiProviderForm({
   const [apiKey, setApiKey] = useState('');
   const [baseUrl, setBaseUrl] = useState('');
   const [modelName, setModelName] = useState('gemini-3-pro-preview');
+  const [customHeadersText, setCustomHeadersText] = useState(formatCustomHeaders(initialData?.customHeaders));
 
   // 状态
   const [loading, setLoading] = useState(false);
@@ -77,10 +82,18 @@ export default function GeminiProviderForm({
     }
   }, [initialData]);
 
+  // 新建时附上远程清单中的预设
+  useEffect(() => {
+    if (initialData) return;
+    api.getGeminiProviderPresets()
+      .then((configs) => setPresetOptions(mergeRemotePresets(configs.filter(c => c.source === 'remote'))))
+      .catch((error) => console.warn('Failed to load remote Gemini presets:', error));
+  }, [initialData]);
+
   // 预设选择变更处理
   const handlePresetChange = useCallback((presetId: string) => {
     setSelectedPreset(presetId);
-    const preset = geminiProviderPresets.find(p => p.id === presetId);
+    const preset = presetOptions.find(p => p.id === presetId);
     if (preset) {
       setName(preset.name);
       setDescription(preset.description || '');
@@ -90,7 +103,7 @@ export default function GeminiProviderForm({
       setBaseUrl(extractBaseUrlFromEnv(preset.env));
       setModelName(extractModelFromEnv(preset.env) || 'gemini-3-pro-preview');
     }
-  }, []);
+  }, [presetOptions]);
 
   // 验证表单
   const validateForm = (): string | null => {
@@ -139,6 +152,12 @@ export default function GeminiProviderForm({
         finalEnv = generateThirdPartyEnv(apiKey, baseUrl, modelName);
       }
 
+      const parsedHeaders = parseCustomHeaders(customHeadersText);
+      if (parsedHeaders.error) {
+        setToastMessage({ message: t('provider.customHeadersInvalid', { line: parsedHeaders.error }), type: 'error' });
+        return;
+      }
+
       const submitData: Omit<GeminiProviderConfig, 'id'> = {
         name: name.trim(),
         description: description.trim(),
@@ -146,6 +165,7 @@ export default function GeminiProviderForm({
         category,
         env: finalEnv,
         isOfficial: category === 'official',
+        customHeaders: category === 'official' ? undefined : parsedHeaders.headers,
       };
 
       await onSubmit(submitData);
@@ -178,7 +198,7 @@ export default function GeminiProviderForm({
                 <SelectValue placeholder={t('provider.selectPresetPlaceholder')} />
               </SelectTrigger>
               <SelectContent>
-                {geminiProviderPresets.map((preset) => (
+                {presetOptions.map((preset) => (
                   <SelectItem key={preset.id} value={preset.id}>
                     <div className="flex items-center gap-2">
                       <span>{preset.name}</span>
@@ -331,6 +351,22 @@ export default function GeminiProviderForm({
                 {t('provider.modelWriteToGemini')}
               </p>
             </div>
+
+            {/* Custom Headers */}
+            <div className="space-y-2">
+              <Label htmlFor="customHeaders">{t('provider.customHeadersLabel')}</Label>
+              <Textarea
+                id="customHeaders"
+                value={customHeadersText}
+                onChange={(e) => setCustomHeadersText(e.target.value)}
+                placeholder={'X-Org-Id: your-org\nX-Request-Source: any-code'}
+                className="font-mono text-xs h-20"
+                disabled={loading}
+              />
+              <p className="text-xs text-muted-foreground">
+                {t('provider.customHeadersHelp')}
+              </p>
+            </div>
           </div>
         )}
 
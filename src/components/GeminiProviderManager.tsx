@@ -18,12 +18,19 @@ import {
   Trash,
   Sparkles,
   ExternalLink,
+  Activity,
 } from 'lucide-react';
-import { api, type GeminiProviderConfig, type CurrentGeminiProviderConfig } from '@/lib/api';
+import {
+  api,
+  type GeminiProviderConfig,
+  type CurrentGeminiProviderConfig,
+  type ProviderTestResult,
+} from '@/lib/api';
 import { Toast } from '@/components/ui/toast';
 import GeminiProviderForm from './GeminiProviderForm';
 import {
   geminiProviderPresets,
+  mergeRemotePresets,
   extractApiKeyFromEnv,
   extractBaseUrlFromEnv,
   extractModelFromEnv,
@@ -43,6 +50,9 @@ export default function GeminiProviderManager({ onBack }: GeminiProviderManagerP
   const [loading, setLoading] = useState(true);
   const [switching, setSwitching] = useState<string | null>(null);
   const [testing, setTesting] = useState<string | null>(null);
+  const [testingAll, setTestingAll] = useState(false);
+  // 最近一次连接测试结果（按供应商 ID），用于对比各供应商的延迟与模型数
+  const [testResults, setTestResults] = useState<Record<string, ProviderTestResult>>({});
   const [deleting, setDeleting] = useState<string | null>(null);
   const [showCurrentConfig, setShowCurrentConfig] = useState(false);
   const [showTokens, setShowTokens] = useState(false);
@@ -78,8 +88,10 @@ export default function GeminiProviderManager({ onBack }: GeminiProviderManagerP
         console.warn('Failed to load current Gemini config:', error);
       }
 
-      // 合并内置预设和自定义预设
-      const builtInPresets: GeminiProviderConfig[] = geminiProviderPresets
+      // 远程清单预设与内置预设合并（同名以远程为准），用户预设放在后面
+      const remotePresets = customPresets.filter(p => p.source === 'remote');
+      const userPresets = customPresets.filter(p => p.source !== 'remote');
+      const builtInPresets: GeminiProviderConfig[] = mergeRemotePresets(remotePresets)
         .filter(p => !p.isCustomTemplate) // 排除自定义模板
         .map(p => ({
           id: p.id,
@@ -90,10 +102,10 @@ export default function GeminiProviderManager({ onBack }: GeminiProviderManagerP
           env: p.env,
           isOfficial: p.category === 'official',
           isPartner: p.isPartner,
+          source: p.source,
         }));
 
-      // 自定义预设放在内置预设后面
-      setPresets([...builtInPresets, ...customPresets]);
+      setPresets([...builtInPresets, ...userPresets]);
       setCurrentConfig(config);
     } catch (error) {
       console.error('Failed to load Gemini provider data:', error);
@@ -131,13 +143,21 @@ export default function GeminiProviderManager({ onBack }: GeminiProviderManagerP
     }
   };
 
+  const describeTestResult = (result: ProviderTestResult) => {
+    if (!result.success) {
+      return result.error || t('provider.connectionTestFailed');
+    }
+    return result.availableModels.length > 0
+      ? t('provider.connectionLatencyModels', { latency: result.latencyMs, count: result.availableModels.length })
+      : t('provider.connectionLatency', { latency: result.latencyMs });
+  };
+
   const testConnection = async (config: GeminiProviderConfig) => {
     try {
       setTesting(config.id);
-      const baseUrl = extractBaseUrlFromEnv(config.env);
-      const apiKey = extractApiKeyFromEnv(config.env);
-      const message = await api.testGeminiProviderConnection(baseUrl, apiKey);
-      setToastMessage({ message, type: 'success' });
+      const result = await api.testGeminiProviderConnection(config);
+      setTestResults((prev) => ({ ...prev, [config.id]: result }));
+      setToastMessage({ message: describeTestResult(result), type: result.success ? 'success' : 'error' });
     } catch (error) {
       console.error('Failed to test Gemini connection:', error);
       setToastMessage({ message: t('provider.connectionTestFailed'), type: 'error' });
@@ -146,6 +166,19 @@ export default function GeminiProviderManager({ onBack }: GeminiProviderManagerP
     }
   };
 
+  const testAllProviders = async () => {
+    try {
+      setTestingAll(true);
+      const results = await api.testAllGeminiProviders();
+      setTestResults((prev) => ({ ...prev, ...results }));
+    } catch (error) {
+      console.error('Failed to test Gemini providers:', error);
+      setToastMessage({ message: t('provider.testAllFailed'), type: 'error' });
+    } finally {
+      setTestingAll(false);
+    }
+  };
+
   const handleAddProvider = () => {
     setEditingProvider(null);
     setShowForm(true);
@@ -250,7 +283,7 @@ export default function GeminiProviderManager({ onBack }: GeminiProviderManagerP
 
   // 判断是否为内置预设（不能删除）
   const isBuiltInPreset = (config: GeminiProviderConfig): boolean => {
-    return geminiProviderPresets.some(p => p.id === config.id);
+    return config.source === 'remote' || geminiProviderPresets.some(p => p.id === config.id);
   };
 
   const maskToken = (token: string): string => {
@@ -318,6 +351,20 @@ export default function GeminiProviderManager({ onBack }: GeminiProviderManagerP
             <Plus className="h-3 w-3 mr-1" aria-hidden="true" />
             {t('provider.addProvider')}
           </Button>
+          <Button
+            variant="outline"
+            size="sm"
+            onClick={testAllProviders}
+            disabled={testingAll || presets.length === 0}
+            className="text-xs"
+          >
+            {testingAll ? (
+              <RefreshCw className="h-3 w-3 mr-1 animate-spin" aria-hidden="true" />
+            ) : (
+              <Activity className="h-3 w-3 mr-1" aria-hidden="true" />
+            )}
+            {t('provider.testAllProviders')}
+          </Button>
           <Button
             variant="outline"
             size="sm"
@@ -388,6 +435,11 @@ export default function GeminiProviderManager({ onBack }: GeminiProviderManagerP
                           {t('provider.partner')}
                         </Badge>
                       )}
+                      {config.source === 'remote' && (
+                        <Badge variant="outline" className="text-xs bg-purple-50 dark:bg-purple-950">
+                          {t('provider.remotePreset')}
+                        </Badge>
+                      )}
                       {config.category && (
                         <Badge variant="outline" className="text-xs">
                           {t(getCategoryKey(config.category))}
@@ -428,6 +480,19 @@ export default function GeminiProviderManager({ onBack }: GeminiProviderManagerP
                           )}
                         </>
                       )}
+                      {testResults[config.id] && (
+                        <p
+                          className={`flex items-center gap-1 ${testResults[config.id].success ? 'text-green-600' : 'text-red-600'}`}
+                          title={testResults[config.id].availableModels.join(', ') || undefined}
+                        >
+                          {testResults[config.id].success ? (
+                            <Check className="h-3 w-3" aria-hidden="true" />
+                          ) : (
+                            <AlertCircle className="h-3 w-3" aria-hidden="true" />
+                          )}
+                          {describeTestResult(testResults[config.id])}
+                        </p>
+                      )}
                     </div>
                   </div>
 
@@ -8,7 +8,8 @@
  */
 
 import React, { useState, useEffect, useRef } from 'react';
-import { api } from '@/lib/api';
+import { listen } from '@tauri-apps/api/event';
+import { api, SESSIONS_INVALIDATED_EVENT, type SessionsInvalidated } from '@/lib/api';
 import type { GeminiSessionDetail } from '@/types/gemini';
 import { Button } from '@/components/ui/button';
 import { ScrollArea } from '@/components/ui/scroll-area';
@@ -48,6 +49,19 @@ export const GeminiSessionDetailViewer: React.FC<GeminiSessionDetailViewerProps>
     }
   }, [projectPath, sessionId]);
 
+  // 会话被执行、回滚或截断后重新加载详情
+  useEffect(() => {
+    if (!projectPath || !sessionId) return;
+    const unlisten = listen<SessionsInvalidated>(SESSIONS_INVALIDATED_EVENT, (event) => {
+      const { engine, sessionId: changedId } = event.payload;
+      if (engine !== 'gemini' || (changedId && changedId !== sessionId)) return;
+      loadSession();
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, [projectPath, sessionId]);
+
   // 进入历史会话详情时，默认滚动到最底部以显示最新消息
   useEffect(() => {
     if (!session) return;
@@ -6,7 +6,8 @@
  */
 
 import React, { useState, useEffect } from 'react';
-import { api } from '@/lib/api';
+import { listen } from '@tauri-apps/api/event';
+import { api, SESSIONS_INVALIDATED_EVENT, type SessionsInvalidated } from '@/lib/api';
 import type { GeminiSessionInfo } from '@/types/gemini';
 import { Button } from '@/components/ui/button';
 import { ScrollArea } from '@/components/ui/scroll-area';
@@ -36,6 +37,19 @@ export const GeminiSessionHistoryPanel: React.FC<GeminiSessionHistoryPanelProps>
     }
   }, [projectPath]);
 
+  // Refetch after a run completes or a session is rewound/deleted
+  useEffect(() => {
+    if (!projectPath) return;
+    const unlisten = listen<SessionsInvalidated>(SESSIONS_INVALIDATED_EVENT, (event) => {
+      const { engine, projectPath: changedPath } = event.payload;
+      if (engine !== 'gemini' || (changedPath && changedPath !== projectPath)) return;
+      loadSessions();
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, [projectPath]);
+
   const loadSessions = async () => {
     if (!projectPath) return;
 
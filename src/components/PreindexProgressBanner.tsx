@@ -0,0 +1,109 @@
+/**
+ * PreindexProgressBanner - 语义索引进度提示条
+ *
+ * 监听 acemcp-preindex-progress 事件：选择项目后的后台预索引进行中时显示提示条（可取消），
+ * 完成时短暂显示文件数与耗时，失败时保留错误信息直到关闭
+ */
+
+import { useEffect, useRef, useState } from "react";
+import { listen } from "@tauri-apps/api/event";
+import { AlertCircle, CheckCircle2, Loader2, X } from "lucide-react";
+import { Button } from "@/components/ui/button";
+import { useTranslation } from "@/hooks/useTranslation";
+import { cn } from "@/lib/utils";
+import { api, type PreindexProgress } from "@/lib/api";
+
+/** 完成提示的显示时长 */
+const COMPLETED_VISIBLE_MS = 4000;
+
+const projectName = (projectPath: string) =>
+  projectPath.replace(/[\\/]+$/, "").split(/[\\/]/).pop() || projectPath;
+
+/**
+ * 语义索引进度提示条（全局挂载）
+ */
+export function PreindexProgressBanner() {
+  const { t } = useTranslation();
+  const [progress, setProgress] = useState<PreindexProgress | null>(null);
+  const hideTimer = useRef<ReturnType<typeof setTimeout> | null>(null);
+
+  useEffect(() => {
+    let unlisten: (() => void) | undefined;
+    listen<PreindexProgress>("acemcp-preindex-progress", (event) => {
+      if (hideTimer.current) {
+        clearTimeout(hideTimer.current);
+        hideTimer.current = null;
+      }
+      const next = event.payload;
+      if (next.stage === "cancelled") {
+        setProgress((prev) => (prev?.projectPath === next.projectPath ? null : prev));
+        return;
+      }
+      setProgress(next);
+      if (next.stage === "completed") {
+        hideTimer.current = setTimeout(() => setProgress(null), COMPLETED_VISIBLE_MS);
+      }
+    })
+      .then((fn) => {
+        unlisten = fn;
+      })
+      .catch((err) => console.error("Failed to setup acemcp-preindex-progress listener:", err));
+    return () => {
+      if (unlisten) unlisten();
+      if (hideTimer.current) clearTimeout(hideTimer.current);
+    };
+  }, []);
+
+  if (!progress) return null;
+
+  const project = projectName(progress.projectPath);
+  const seconds = (progress.elapsedMs / 1000).toFixed(1);
+  const running = progress.stage === "started" || progress.stage === "indexing";
+
+  let text: string;
+  if (running) {
+    text = t("acemcp.preindex.indexing", { project });
+  } else if (progress.stage === "completed") {
+    text = progress.fileCount !== null
+      ? t("acemcp.preindex.completedFiles", { project, count: progress.fileCount, seconds })
+      : t("acemcp.preindex.completed", { project, seconds });
+  } else {
+    text = t("acemcp.preindex.failed", { project, error: progress.error ?? "" });
+  }
+
+  return (
+    <div
+      className={cn(
+        "flex items-center gap-2 px-4 py-1.5 text-xs border-b",
+        progress.stage === "failed"
+          ? "bg-destructive/10 text-destructive border-destructive/20"
+          : "bg-muted/60 text-muted-foreground border-border"
+      )}
+    >
+      {running && <Loader2 className="h-3.5 w-3.5 flex-shrink-0 animate-spin" />}
+      {progress.stage === "completed" && <CheckCircle2 className="h-3.5 w-3.5 flex-shrink-0 text-green-600" />}
+      {progress.stage === "failed" && <AlertCircle className="h-3.5 w-3.5 flex-shrink-0" />}
+      <span className="flex-1 truncate" title={text}>{text}</span>
+      {running ? (
+        <Button
+          variant="ghost"
+          size="sm"
+          className="h-6 px-2 text-xs"
+          onClick={() => api.cancelPreindex(progress.projectPath).catch(console.error)}
+        >
+          {t("acemcp.preindex.cancel")}
+        </Button>
+      ) : (
+        <Button
+          variant="ghost"
+          size="icon"
+          className="h-6 w-6"
+          aria-label={t("acemcp.preindex.dismiss")}
+          onClick={() => setProgress(null)}
+        >
+          <X className="h-3.5 w-3.5" />
+        </Button>
+      )}
+    </div>
+  );
+}
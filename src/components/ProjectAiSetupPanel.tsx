@@ -0,0 +1,189 @@
+/**
+ * ProjectAiSetupPanel - 项目 AI 工作流初始化
+ *
+ * 一次完成新项目接入的准备工作：生成 CLAUDE.md、创建 .claude 目录结构、
+ * 追加 .gitignore 条目、触发预索引，并可选地创建一条引导会话。
+ * 先预览（dry run）再执行；重复执行不会覆盖已有文件
+ */
+
+import { useEffect, useState } from "react";
+import { CheckCircle2, CircleDashed, Eye, Loader2, MinusCircle, Rocket, XCircle } from "lucide-react";
+import { Button } from "@/components/ui/button";
+import { Card } from "@/components/ui/card";
+import { Checkbox } from "@/components/ui/checkbox";
+import { Label } from "@/components/ui/label";
+import { SelectComponent } from "@/components/ui/select";
+import { api } from "@/lib/api";
+import type {
+  ClaudeMdTemplate,
+  Project,
+  ProjectSetupOptions,
+  ProjectSetupReport,
+  SetupStep,
+  SetupStepStatus,
+} from "@/lib/api";
+import { cn } from "@/lib/utils";
+
+const STEP_LABELS: Record<SetupStep, string> = {
+  claudeMd: "从模板生成 CLAUDE.md",
+  claudeDirs: "创建 .claude/{commands,agents,skills}",
+  gitignore: "在 .gitignore 中忽略 CLAUDE.local.md 与 .anycode/",
+  preindex: "预索引项目（需已配置 acemcp）",
+  bootstrapSession: "创建引导会话（让 AI 阅读并总结项目结构）",
+};
+
+const STATUS_ICONS: Record<SetupStepStatus, { icon: typeof CheckCircle2; className: string }> = {
+  applied: { icon: CheckCircle2, className: "text-green-600" },
+  planned: { icon: CircleDashed, className: "text-blue-600" },
+  skipped: { icon: MinusCircle, className: "text-muted-foreground" },
+  failed: { icon: XCircle, className: "text-destructive" },
+};
+
+const ENGINE_OPTIONS = [
+  { value: "claude", label: "Claude" },
+  { value: "codex", label: "Codex" },
+  { value: "gemini", label: "Gemini" },
+];
+
+export interface ProjectAiSetupPanelProps {
+  project: Project;
+}
+
+export function ProjectAiSetupPanel({ project }: ProjectAiSetupPanelProps) {
+  const [steps, setSteps] = useState<Record<SetupStep, boolean>>({
+    claudeMd: true,
+    claudeDirs: true,
+    gitignore: true,
+    preindex: true,
+    bootstrapSession: false,
+  });
+  const [templates, setTemplates] = useState<ClaudeMdTemplate[]>([]);
+  const [templateId, setTemplateId] = useState("default");
+  const [engine, setEngine] = useState<NonNullable<ProjectSetupOptions["engine"]>>("claude");
+  const [report, setReport] = useState<ProjectSetupReport | null>(null);
+  const [busy, setBusy] = useState<"preview" | "apply" | null>(null);
+  const [error, setError] = useState<string | null>(null);
+
+  useEffect(() => {
+    api.listClaudeMdTemplates()
+      .then(setTemplates)
+      .catch((err) => console.warn("Failed to load CLAUDE.md templates:", err));
+  }, []);
+
+  useEffect(() => {
+    setReport(null);
+    setError(null);
+  }, [project.path]);
+
+  const run = async (dryRun: boolean) => {
+    setBusy(dryRun ? "preview" : "apply");
+    setError(null);
+    try {
+      setReport(await api.initProjectAiSetup(project.path, {
+        ...steps,
+        claudeMdTemplate: templateId,
+        engine,
+        dryRun,
+      }));
+    } catch (err) {
+      setError(String(err));
+    } finally {
+      setBusy(null);
+    }
+  };
+
+  const toggle = (step: SetupStep, checked: boolean) => {
+    setSteps((prev) => ({ ...prev, [step]: checked }));
+    setReport(null);
+  };
+
+  return (
+    <Card className="p-6 space-y-6">
+      <div>
+        <h3 className="text-lg font-semibold mb-2 flex items-center gap-2">
+          <Rocket className="h-5 w-5" />
+          AI 工作流初始化
+        </h3>
+        <p className="text-sm text-muted-foreground">
+          为项目准备 AI 协作所需的文件与索引。已存在的文件不会被覆盖，可以放心重复执行。
+        </p>
+      </div>
+
+      <div className="space-y-3">
+        {(Object.keys(STEP_LABELS) as SetupStep[]).map((step) => (
+          <div key={step} className="flex items-center gap-3">
+            <Checkbox
+              id={`project-setup-${step}`}
+              checked={steps[step]}
+              onCheckedChange={(checked) => toggle(step, !!checked)}
+            />
+            <Label htmlFor={`project-setup-${step}`} className="text-sm font-normal cursor-pointer">
+              {STEP_LABELS[step]}
+            </Label>
+            {step === "claudeMd" && steps.claudeMd && templates.length > 0 && (
+              <SelectComponent
+                value={templateId}
+                onValueChange={setTemplateId}
+                options={templates.map((t) => ({
+                  value: t.id,
+                  label: t.source === "local" ? `${t.id}（自定义）` : t.id,
+                }))}
+                className="w-40"
+              />
+            )}
+            {step === "bootstrapSession" && steps.bootstrapSession && (
+              <SelectComponent
+                value={engine}
+                onValueChange={(value) => setEngine(value as typeof engine)}
+                options={ENGINE_OPTIONS}
+                className="w-32"
+              />
+            )}
+          </div>
+        ))}
+      </div>
+
+      <div className="flex items-center gap-2">
+        <Button size="sm" variant="outline" onClick={() => run(true)} disabled={busy !== null} className="gap-2">
+          {busy === "preview" ? <Loader2 className="h-4 w-4 animate-spin" /> : <Eye className="h-4 w-4" />}
+          预览
+        </Button>
+        <Button size="sm" onClick={() => run(false)} disabled={busy !== null} className="gap-2">
+          {busy === "apply" ? <Loader2 className="h-4 w-4 animate-spin" /> : <Rocket className="h-4 w-4" />}
+          执行初始化
+        </Button>
+      </div>
+
+      {error && (
+        <div className="text-xs text-destructive bg-destructive/10 rounded-lg p-3 whitespace-pre-wrap">
+          {error}
+        </div>
+      )}
+
+      {report && (
+        <div className="space-y-2">
+          <Label className="text-sm">{report.dryRun ? "预览结果（未做任何修改）" : "执行结果"}</Label>
+          <div className="divide-y rounded-lg border">
+            {report.steps.map((result) => {
+              const { icon: Icon, className } = STATUS_ICONS[result.status];
+              return (
+                <div key={result.step} className="flex items-start gap-3 p-3 text-sm">
+                  <Icon className={cn("h-4 w-4 mt-0.5 shrink-0", className)} />
+                  <div className="flex-1 min-w-0">
+                    <p>{STEP_LABELS[result.step]}</p>
+                    <p className="text-xs text-muted-foreground mt-1">{result.detail}</p>
+                    {result.paths.length > 0 && (
+                      <p className="text-xs text-muted-foreground font-mono mt-1 break-all whitespace-pre-wrap">
+                        {result.paths.join("\n")}
+                      </p>
+                    )}
+                  </div>
+                </div>
+              );
+            })}
+          </div>
+        </div>
+      )}
+    </Card>
+  );
+}
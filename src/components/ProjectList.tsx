@@ -11,7 +11,8 @@ import {
   List
 } from "lucide-react";
 import { Button } from "@/components/ui/button";
-import { api } from "@/lib/api";
+import { listen } from "@tauri-apps/api/event";
+import { api, SESSIONS_INVALIDATED_EVENT, type SessionsInvalidated } from "@/lib/api";
 import type { CodexSession } from "@/types/codex";
 import type { GeminiSessionInfo } from "@/types/gemini";
 import {
@@ -129,18 +130,19 @@ export const ProjectList: React.FC<ProjectListProps> = ({
   const currentProjects = projects.slice(startIndex, endIndex);
   
   // Load Codex sessions for counting
+  const loadCodexSessions = useCallback(async () => {
+    try {
+      const sessions = await api.listCodexSessions();
+      setCodexSessions(sessions);
+    } catch (error) {
+      console.error('Failed to load Codex sessions:', error);
+      // Continue with empty array - won't block UI
+    }
+  }, []);
+
   useEffect(() => {
-    const loadCodexSessions = async () => {
-      try {
-        const sessions = await api.listCodexSessions();
-        setCodexSessions(sessions);
-      } catch (error) {
-        console.error('Failed to load Codex sessions:', error);
-        // Continue with empty array - won't block UI
-      }
-    };
     loadCodexSessions();
-  }, []);
+  }, [loadCodexSessions]);
 
   // Load Gemini sessions for each project
   const loadGeminiSessions = useCallback(async (projectPath: string) => {
@@ -165,6 +167,25 @@ export const ProjectList: React.FC<ProjectListProps> = ({
     });
   }, [currentProjects, geminiSessionsMap, loadGeminiSessions]);
 
+  // Keep session counts current after runs, rewinds and deletions
+  useEffect(() => {
+    const unlisten = listen<SessionsInvalidated>(SESSIONS_INVALIDATED_EVENT, (event) => {
+      const { engine, projectPath } = event.payload;
+      if (engine === 'codex') {
+        loadCodexSessions();
+      } else if (engine === 'gemini') {
+        if (projectPath) {
+          loadGeminiSessions(projectPath);
+        } else {
+          setGeminiSessionsMap(new Map());
+        }
+      }
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, [loadCodexSessions, loadGeminiSessions]);
+
   // Reset to page 1 if projects change
   React.useEffect(() => {
     setCurrentPage(1);
@@ -0,0 +1,280 @@
+/**
+ * ProjectMemoryPanel - 项目记忆管理
+ *
+ * 管理 <project>/.anycode/memory.json 中的事实、决策与约定：
+ * 手动添加/删除条目、从历史会话提取候选条目并确认入库、
+ * 预览注入内容，以及按项目开关执行时的记忆注入
+ */
+
+import { useCallback, useEffect, useState } from "react";
+import { Brain, Eye, Loader2, Plus, Sparkles, Trash2 } from "lucide-react";
+import { Button } from "@/components/ui/button";
+import { Card } from "@/components/ui/card";
+import { Badge } from "@/components/ui/badge";
+import { Checkbox } from "@/components/ui/checkbox";
+import { Label } from "@/components/ui/label";
+import { Switch } from "@/components/ui/switch";
+import { Textarea } from "@/components/ui/textarea";
+import { SelectComponent } from "@/components/ui/select";
+import { api } from "@/lib/api";
+import type { MemoryEntry, MemoryEntryInput, MemoryKind, Project } from "@/lib/api";
+import { isProjectMemoryEnabled, setProjectMemoryEnabled } from "@/lib/projectMemory";
+
+/** 预览与执行注入使用相同的 token 预算（与后端 DEFAULT_MEMORY_CONTEXT_TOKENS 一致） */
+const PREVIEW_MAX_TOKENS = 2000;
+
+const KIND_LABELS: Record<MemoryKind, string> = {
+  convention: "约定",
+  decision: "决策",
+  fact: "事实",
+};
+
+const KIND_OPTIONS = (Object.keys(KIND_LABELS) as MemoryKind[]).map((kind) => ({
+  value: kind,
+  label: KIND_LABELS[kind],
+}));
+
+export interface ProjectMemoryPanelProps {
+  /** 所属项目（Claude 会话列表用于提取候选条目） */
+  project: Project;
+}
+
+export function ProjectMemoryPanel({ project }: ProjectMemoryPanelProps) {
+  const [entries, setEntries] = useState<MemoryEntry[]>([]);
+  const [enabled, setEnabled] = useState(() => isProjectMemoryEnabled(project.path));
+  const [kind, setKind] = useState<MemoryKind>("convention");
+  const [content, setContent] = useState("");
+  const [sessionId, setSessionId] = useState(project.sessions[0] ?? "");
+  const [candidates, setCandidates] = useState<MemoryEntryInput[]>([]);
+  const [selected, setSelected] = useState<boolean[]>([]);
+  const [preview, setPreview] = useState<string | null>(null);
+  const [busy, setBusy] = useState<"add" | "extract" | "confirm" | null>(null);
+  const [error, setError] = useState<string | null>(null);
+
+  const loadEntries = useCallback(async () => {
+    setEntries(await api.listMemoryEntries(project.path));
+  }, [project.path]);
+
+  useEffect(() => {
+    setEnabled(isProjectMemoryEnabled(project.path));
+    setError(null);
+    loadEntries().catch((err) => setError(String(err)));
+  }, [project.path, loadEntries]);
+
+  const run = async (action: "add" | "extract" | "confirm", fn: () => Promise<void>) => {
+    setBusy(action);
+    setError(null);
+    try {
+      await fn();
+    } catch (err) {
+      setError(String(err));
+    } finally {
+      setBusy(null);
+    }
+  };
+
+  const handleToggle = (checked: boolean) => {
+    setEnabled(checked);
+    setProjectMemoryEnabled(project.path, checked);
+  };
+
+  const handleAdd = () =>
+    run("add", async () => {
+      await api.addMemoryEntry(project.path, { kind, content });
+      setContent("");
+      setPreview(null);
+      await loadEntries();
+    });
+
+  const handleDelete = (entryId: string) =>
+    run("add", async () => {
+      await api.deleteMemoryEntry(project.path, entryId);
+      setPreview(null);
+      await loadEntries();
+    });
+
+  const handleExtract = () =>
+    run("extract", async () => {
+      const result = await api.extractMemoryFromSession("claude", sessionId, project.id);
+      setCandidates(result);
+      setSelected(result.map(() => true));
+      if (result.length === 0) {
+        setError("该会话中没有提取到值得记录的内容");
+      }
+    });
+
+  const handleConfirm = () =>
+    run("confirm", async () => {
+      for (const candidate of candidates.filter((_, index) => selected[index])) {
+        await api.addMemoryEntry(project.path, candidate);
+      }
+      setCandidates([]);
+      setSelected([]);
+      setPreview(null);
+      await loadEntries();
+    });
+
+  const handlePreview = async () => {
+    try {
+      setPreview(await api.compileMemoryContext(project.path, PREVIEW_MAX_TOKENS));
+    } catch (err) {
+      setError(String(err));
+    }
+  };
+
+  return (
+    <Card className="p-6 space-y-6">
+      <div className="flex items-start justify-between gap-4">
+        <div>
+          <h3 className="text-lg font-semibold mb-2 flex items-center gap-2">
+            <Brain className="h-5 w-5" />
+            项目记忆
+          </h3>
+          <p className="text-sm text-muted-foreground">
+            跨会话沉淀的事实、决策与约定，存储在
+            <code className="mx-1 px-2 py-1 bg-muted rounded text-xs">.anycode/memory.json</code>
+            中。开启注入后，Claude / Codex / Gemini 执行时会自动附带这些记忆。
+          </p>
+        </div>
+        <div className="flex items-center gap-2 shrink-0">
+          <Label htmlFor="use-project-memory" className="text-sm">执行时注入</Label>
+          <Switch id="use-project-memory" checked={enabled} onCheckedChange={handleToggle} />
+        </div>
+      </div>
+
+      {/* 手动添加 */}
+      <div className="space-y-2">
+        <div className="flex items-center gap-2">
+          <SelectComponent
+            value={kind}
+            onValueChange={(value) => setKind(value as MemoryKind)}
+            options={KIND_OPTIONS}
+            className="w-28"
+          />
+          <span className="text-xs text-muted-foreground">每条用一句话独立表述</span>
+        </div>
+        <Textarea
+          value={content}
+          onChange={(e) => setContent(e.target.value)}
+          placeholder="例如：后端命令统一返回 Result<_, String>，错误信息用中文"
+          rows={2}
+        />
+        <Button
+          size="sm"
+          onClick={handleAdd}
+          disabled={busy !== null || !content.trim()}
+          className="gap-2"
+        >
+          {busy === "add" ? <Loader2 className="h-4 w-4 animate-spin" /> : <Plus className="h-4 w-4" />}
+          添加
+        </Button>
+      </div>
+
+      {/* 从会话提取 */}
+      <div className="space-y-2">
+        <Label className="text-sm">从历史会话提取</Label>
+        <div className="flex items-center gap-2">
+          <SelectComponent
+            value={sessionId}
+            onValueChange={setSessionId}
+            options={project.sessions.map((id) => ({ value: id, label: id.slice(0, 8) }))}
+            placeholder="选择会话"
+            disabled={project.sessions.length === 0}
+            className="w-48"
+          />
+          <Button
+            size="sm"
+            variant="outline"
+            onClick={handleExtract}
+            disabled={busy !== null || !sessionId}
+            className="gap-2"
+          >
+            {busy === "extract" ? (
+              <Loader2 className="h-4 w-4 animate-spin" />
+            ) : (
+              <Sparkles className="h-4 w-4" />
+            )}
+            提取候选条目
+          </Button>
+        </div>
+
+        {candidates.length > 0 && (
+          <div className="rounded-lg border p-3 space-y-2">
+            {candidates.map((candidate, index) => (
+              <label key={index} className="flex items-start gap-2 text-sm cursor-pointer">
+                <Checkbox
+                  checked={selected[index] ?? false}
+                  onCheckedChange={(checked) =>
+                    setSelected((prev) => prev.map((value, i) => (i === index ? !!checked : value)))
+                  }
+                />
+                <Badge variant="outline" className="shrink-0">{KIND_LABELS[candidate.kind]}</Badge>
+                <span>{candidate.content}</span>
+              </label>
+            ))}
+            <Button
+              size="sm"
+              onClick={handleConfirm}
+              disabled={busy !== null || !selected.some(Boolean)}
+              className="gap-2"
+            >
+              {busy === "confirm" && <Loader2 className="h-4 w-4 animate-spin" />}
+              将选中的条目入库
+            </Button>
+          </div>
+        )}
+      </div>
+
+      {error && (
+        <div className="text-xs text-destructive bg-destructive/10 rounded-lg p-3 whitespace-pre-wrap">
+          {error}
+        </div>
+      )}
+
+      {/* 已入库条目 */}
+      <div className="space-y-2">
+        <div className="flex items-center justify-between">
+          <Label className="text-sm">已入库（{entries.length}）</Label>
+          <Button size="sm" variant="ghost" onClick={handlePreview} className="gap-2">
+            <Eye className="h-4 w-4" />
+            预览注入内容
+          </Button>
+        </div>
+
+        {preview !== null && (
+          <pre className="text-xs font-mono whitespace-pre-wrap rounded-md p-3 bg-muted/30 max-h-64 overflow-auto">
+            {preview || "（暂无记忆）"}
+          </pre>
+        )}
+
+        {entries.length === 0 ? (
+          <p className="text-sm text-muted-foreground">还没有记忆条目</p>
+        ) : (
+          <div className="divide-y rounded-lg border">
+            {entries.map((entry) => (
+              <div key={entry.id} className="flex items-start gap-3 p-3 text-sm">
+                <Badge variant="outline" className="shrink-0">{KIND_LABELS[entry.kind]}</Badge>
+                <div className="flex-1 min-w-0">
+                  <p className="break-words">{entry.content}</p>
+                  <p className="text-xs text-muted-foreground mt-1">
+                    {new Date(entry.createdAt * 1000).toLocaleString()}
+                    {entry.sourceSession && ` · 来自 ${entry.sourceEngine ?? ""} 会话 ${entry.sourceSession.slice(0, 8)}`}
+                  </p>
+                </div>
+                <Button
+                  size="sm"
+                  variant="ghost"
+                  onClick={() => handleDelete(entry.id)}
+                  disabled={busy !== null}
+                  className="h-7 w-7 p-0"
+                >
+                  <Trash2 className="h-4 w-4" />
+                </Button>
+              </div>
+            ))}
+          </div>
+        )}
+      </div>
+    </Card>
+  );
+}
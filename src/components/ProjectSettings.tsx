@@ -4,13 +4,21 @@
 
 import React, { useState, useEffect } from 'react';
 import { HooksEditor } from '@/components/HooksEditor';
+import { ProjectMemoryPanel } from '@/components/ProjectMemoryPanel';
+import { ProjectAiSetupPanel } from '@/components/ProjectAiSetupPanel';
+import { GeminiContextFilesPanel } from '@/components/GeminiContextFilesPanel';
+import { ProtectedPathsPanel } from '@/components/ProtectedPathsPanel';
 import { api } from '@/lib/api';
 import {
   AlertTriangle,
   ArrowLeft,
+  Brain,
+  FileText,
   Settings,
   FolderOpen,
   GitBranch,
+  Lock,
+  Rocket,
   Shield
 } from 'lucide-react';
 import { Button } from '@/components/ui/button';
@@ -46,29 +54,22 @@ export const ProjectSettings: React.FC<ProjectSettingsProps> = ({
   const checkGitIgnore = async () => {
     try {
       // Check if .claude/settings.local.json is in .gitignore
-      const gitignorePath = `${project.path}/.gitignore`;
-      const gitignoreContent = await api.readClaudeMdFile(gitignorePath);
+      const gitignoreContent = await api.readProjectGitignore(project.path);
       setGitIgnoreLocal(gitignoreContent.includes('.claude/settings.local.json'));
     } catch {
-      // .gitignore might not exist
+      // Project directory might be unavailable
       setGitIgnoreLocal(false);
     }
   };
 
   const addToGitIgnore = async () => {
     try {
-      const gitignorePath = `${project.path}/.gitignore`;
-      let content = '';
-      
-      try {
-        content = await api.readClaudeMdFile(gitignorePath);
-      } catch {
-        // File doesn't exist, create it
-      }
+      // Empty when the file doesn't exist yet; saving creates it
+      let content = await api.readProjectGitignore(project.path);
       
       if (!content.includes('.claude/settings.local.json')) {
         content += '\n# Claude local settings (machine-specific)\n.claude/settings.local.json\n';
-        await api.saveClaudeMdFile(gitignorePath, content);
+        await api.saveProjectGitignore(project.path, content);
         setGitIgnoreLocal(true);
         setToast({ message: t('projectSettings.addedToGitignore'), type: 'success' });
       }
@@ -116,6 +117,22 @@ export const ProjectSettings: React.FC<ProjectSettingsProps> = ({
                 <Shield className="h-4 w-4" />
                 {t('projectSettings.localHooks')}
               </TabsTrigger>
+              <TabsTrigger value="memory" className="gap-2">
+                <Brain className="h-4 w-4" />
+                {t('projectSettings.projectMemory')}
+              </TabsTrigger>
+              <TabsTrigger value="gemini" className="gap-2">
+                <FileText className="h-4 w-4" />
+                {t('projectSettings.geminiContext')}
+              </TabsTrigger>
+              <TabsTrigger value="protected" className="gap-2">
+                <Lock className="h-4 w-4" />
+                {t('projectSettings.protectedPaths')}
+              </TabsTrigger>
+              <TabsTrigger value="setup" className="gap-2">
+                <Rocket className="h-4 w-4" />
+                {t('projectSettings.aiSetup')}
+              </TabsTrigger>
             </TabsList>
 
             <TabsContent value="project" className="space-y-6">
@@ -175,6 +192,22 @@ export const ProjectSettings: React.FC<ProjectSettingsProps> = ({
                 </div>
               </Card>
             </TabsContent>
+
+            <TabsContent value="memory" className="space-y-6">
+              <ProjectMemoryPanel project={project} />
+            </TabsContent>
+
+            <TabsContent value="gemini" className="space-y-6">
+              <GeminiContextFilesPanel projectPath={project.path} />
+            </TabsContent>
+
+            <TabsContent value="protected" className="space-y-6">
+              <ProtectedPathsPanel projectPath={project.path} />
+            </TabsContent>
+
+            <TabsContent value="setup" className="space-y-6">
+              <ProjectAiSetupPanel project={project} />
+            </TabsContent>
           </Tabs>
         </div>
       </div>
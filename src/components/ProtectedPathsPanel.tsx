@@ -0,0 +1,127 @@
+/**
+ * ProtectedPathsPanel - 项目禁改区
+ *
+ * 按项目配置 AI 不允许改动的路径（glob），执行时作为约束附加到提示词，
+ * 任务结束后若这些文件被改动会弹出告警并可一键恢复
+ */
+
+import { useEffect, useState } from "react";
+import { Loader2, Lock, Save } from "lucide-react";
+import { Button } from "@/components/ui/button";
+import { Card } from "@/components/ui/card";
+import { Label } from "@/components/ui/label";
+import { Textarea } from "@/components/ui/textarea";
+import { api } from "@/lib/api";
+import type { ProtectedPathsInfo } from "@/lib/api";
+
+export interface ProtectedPathsPanelProps {
+  /** 项目路径 */
+  projectPath: string;
+}
+
+const parsePatterns = (text: string) =>
+  text
+    .split("\n")
+    .map((line) => line.trim())
+    .filter(Boolean);
+
+export function ProtectedPathsPanel({ projectPath }: ProtectedPathsPanelProps) {
+  const [info, setInfo] = useState<ProtectedPathsInfo | null>(null);
+  const [patternsText, setPatternsText] = useState("");
+  const [busy, setBusy] = useState(false);
+  const [error, setError] = useState<string | null>(null);
+  const [saved, setSaved] = useState(false);
+
+  const applyInfo = (next: ProtectedPathsInfo) => {
+    setInfo(next);
+    setPatternsText(next.projectPatterns.join("\n"));
+  };
+
+  useEffect(() => {
+    setError(null);
+    setSaved(false);
+    api
+      .getProtectedPaths(projectPath)
+      .then(applyInfo)
+      .catch((err) => setError(String(err)));
+  }, [projectPath]);
+
+  const handleSave = async () => {
+    setBusy(true);
+    setError(null);
+    setSaved(false);
+    try {
+      await api.saveProjectProtectedPaths(projectPath, parsePatterns(patternsText));
+      applyInfo(await api.getProtectedPaths(projectPath));
+      setSaved(true);
+    } catch (err) {
+      setError(String(err));
+    } finally {
+      setBusy(false);
+    }
+  };
+
+  return (
+    <Card className="p-6 space-y-6">
+      <div>
+        <h3 className="text-lg font-semibold mb-2 flex items-center gap-2">
+          <Lock className="h-5 w-5" />
+          禁改区
+        </h3>
+        <p className="text-sm text-muted-foreground">
+          AI 不允许改动的路径。执行时会作为约束附加到系统指令或提示词；任务结束后若这些文件被修改、删除或新建，
+          会立即告警并可一键恢复到任务前状态。规则保存在
+          <code className="mx-1 px-2 py-1 bg-muted rounded text-xs">.anycode/protected-paths.json</code>
+          中。
+        </p>
+      </div>
+
+      {info && !info.enabled && (
+        <div className="text-xs text-amber-600 bg-amber-500/10 rounded-lg p-3">
+          禁改区保护已在全局设置中关闭，以下规则暂不生效
+        </div>
+      )}
+
+      <div className="space-y-2">
+        <Label className="text-sm">项目规则</Label>
+        <Textarea
+          value={patternsText}
+          onChange={(e) => setPatternsText(e.target.value)}
+          placeholder={"每行一个 glob 规则，例如：\nCargo.lock\nmigrations/\nlegal/*.md"}
+          rows={6}
+          className="font-mono text-xs"
+        />
+        <p className="text-xs text-muted-foreground">
+          不含 / 的规则匹配任意层级的文件或目录名，含 / 的规则从项目根目录开始匹配；命中目录即保护其下所有文件
+        </p>
+      </div>
+
+      {info && info.defaultPatterns.length > 0 && (
+        <div className="space-y-1">
+          <Label className="text-sm">全局默认规则</Label>
+          <div className="flex flex-wrap gap-1">
+            {info.defaultPatterns.map((pattern) => (
+              <code key={pattern} className="px-2 py-0.5 bg-muted rounded text-xs">
+                {pattern}
+              </code>
+            ))}
+          </div>
+        </div>
+      )}
+
+      <div className="flex items-center gap-2">
+        <Button size="sm" onClick={handleSave} disabled={busy} className="gap-2">
+          {busy ? <Loader2 className="h-4 w-4 animate-spin" /> : <Save className="h-4 w-4" />}
+          保存
+        </Button>
+        {saved && <span className="text-xs text-muted-foreground">已保存</span>}
+      </div>
+
+      {error && (
+        <div className="text-xs text-destructive bg-destructive/10 rounded-lg p-3 whitespace-pre-wrap">
+          {error}
+        </div>
+      )}
+    </Card>
+  );
+}
@@ -3,14 +3,15 @@
  *
  * 按两次 ESC 键时显示，允许用户选择要撤回的提示词
  * 智能识别每个提示词的撤回能力（CLI/项目界面）
+ * Claude / Codex 还可以只撤回某一条提示词的代码更改，之后的提示词与对话保持不变
  */
 
 import React, { useState, useEffect, useRef, useMemo } from 'react';
 import { motion, AnimatePresence } from 'framer-motion';
-import { Clock, ArrowLeft, MessageSquare, X, Terminal, FolderGit2, AlertCircle } from 'lucide-react';
+import { Clock, ArrowLeft, MessageSquare, X, Terminal, FolderGit2, AlertCircle, ShieldCheck, Undo2 } from 'lucide-react';
 import { cn } from '@/lib/utils';
 import { api } from '@/lib/api';
-import type { RewindMode, RewindCapabilities } from '@/lib/api';
+import type { RewindMode, RewindCapabilities, ProtectedRestore } from '@/lib/api';
 
 interface PromptEntry {
   /** 提示词索引（从0开始，后端分配的准确索引） */
@@ -23,6 +24,10 @@ interface PromptEntry {
   source: string;
   /** 撤回能力（异步加载） */
   capabilities?: RewindCapabilities;
+  /** 禁改区恢复记录 */
+  protectedRestore?: ProtectedRestore;
+  /** 单独撤回代码的时间（UTC 秒），未撤回时为空 */
+  revertedAt?: number;
   /** 加载状态 */
   loading: boolean;
 }
@@ -67,6 +72,7 @@ export const RevertPromptPicker: React.FC<RevertPromptPickerProps> = ({
   const [selectedIndex, setSelectedIndex] = useState(0);
   const [selectedMode, setSelectedMode] = useState<RewindMode>('both');
   const [prompts, setPrompts] = useState<PromptEntry[]>([]);
+  const [singleRevert, setSingleRevert] = useState<{ running: boolean; error?: string }>({ running: false });
   const listRef = useRef<HTMLDivElement>(null);
   const selectedItemRef = useRef<HTMLDivElement>(null);
   const isCodex = engine === 'codex';
@@ -81,7 +87,7 @@ export const RevertPromptPicker: React.FC<RevertPromptPickerProps> = ({
           ? await api.getCodexPromptList(sessionId)
           : isGemini
           ? await api.getGeminiPromptList(sessionId, projectPath)
-          : await api.getPromptList(sessionId, projectId);
+          : await api.getUnifiedPromptList(sessionId, projectId);
 
         if (promptRecords.length === 0) {
           onClose();
@@ -94,6 +100,8 @@ export const RevertPromptPicker: React.FC<RevertPromptPickerProps> = ({
           content: record.text,
           preview: truncateText(record.text),
           source: record.source,
+          protectedRestore: record.protectedRestore,
+          revertedAt: record.revertedAt,
           loading: true,
         }));
 
@@ -165,6 +173,36 @@ export const RevertPromptPicker: React.FC<RevertPromptPickerProps> = ({
     return prompts[selectedIndex]?.capabilities;
   }, [prompts, selectedIndex]);
 
+  // 单独撤回代码：Gemini 不支持，已撤回过的提示词不能再撤回
+  const canRevertSingle =
+    !isGemini &&
+    !!currentCapabilities?.code &&
+    !prompts[selectedIndex]?.revertedAt &&
+    !singleRevert.running;
+
+  const handleRevertSingleCode = async () => {
+    const prompt = prompts[selectedIndex];
+    if (!prompt || !canRevertSingle) return;
+    setSingleRevert({ running: true });
+    try {
+      if (isCodex) {
+        await api.revertCodexSinglePromptCode(sessionId, projectPath, prompt.index);
+      } else {
+        await api.revertSinglePromptCode(sessionId, projectId, projectPath, prompt.index);
+      }
+      const revertedAt = Math.floor(Date.now() / 1000);
+      setPrompts(prev => prev.map(p => (p.index === prompt.index ? { ...p, revertedAt } : p)));
+      setSingleRevert({ running: false });
+    } catch (error) {
+      setSingleRevert({ running: false, error: String(error) });
+    }
+  };
+
+  // 切换提示词时清除上一次单独撤回的错误
+  useEffect(() => {
+    setSingleRevert(prev => (prev.error ? { running: prev.running } : prev));
+  }, [selectedIndex]);
+
   // 根据当前选中提示词的能力，自动调整选中的模式
   useEffect(() => {
     if (!currentCapabilities) return;
@@ -246,12 +284,17 @@ export const RevertPromptPicker: React.FC<RevertPromptPickerProps> = ({
             setSelectedMode('both');
           }
           break;
+
+        case '4':
+          e.preventDefault();
+          handleRevertSingleCode();
+          break;
       }
     };
 
     document.addEventListener('keydown', handleKeyDown, { capture: true });
     return () => document.removeEventListener('keydown', handleKeyDown, { capture: true });
-  }, [prompts, selectedIndex, selectedMode, currentCapabilities, onSelect, onClose]);
+  }, [prompts, selectedIndex, selectedMode, currentCapabilities, onSelect, onClose, handleRevertSingleCode]);
 
   if (prompts.length === 0) {
     return null;
@@ -346,6 +389,32 @@ export const RevertPromptPicker: React.FC<RevertPromptPickerProps> = ({
               </button>
             </div>
 
+            {/* 单独撤回这一条提示词的代码 */}
+            {!isGemini && (
+              <button
+                onClick={handleRevertSingleCode}
+                disabled={!canRevertSingle}
+                className={cn(
+                  'mt-2 w-full px-3 py-2 text-sm rounded-md transition-colors',
+                  canRevertSingle
+                    ? 'bg-white dark:bg-gray-700 text-gray-700 dark:text-gray-300 hover:bg-gray-100 dark:hover:bg-gray-600'
+                    : 'bg-gray-200 dark:bg-gray-800 text-gray-400 dark:text-gray-600 cursor-not-allowed opacity-50'
+                )}
+              >
+                <span className="font-mono text-xs mr-1">[4]</span>
+                {singleRevert.running ? '正在撤回...' : '仅撤回这一条的代码（保留之后的提示词和对话）'}
+              </button>
+            )}
+
+            {singleRevert.error && (
+              <div className="mt-3 flex items-start gap-2 p-2 bg-red-50 dark:bg-red-900/20 border border-red-200 dark:border-red-800 rounded-md">
+                <AlertCircle className="w-4 h-4 text-red-600 dark:text-red-400 flex-shrink-0 mt-0.5" />
+                <p className="text-xs text-red-700 dark:text-red-300 whitespace-pre-wrap">
+                  {singleRevert.error}
+                </p>
+              </div>
+            )}
+
             {/* 警告信息 */}
             {currentCapabilities?.warning && (
               <motion.div
@@ -425,6 +494,25 @@ export const RevertPromptPicker: React.FC<RevertPromptPickerProps> = ({
                         </span>
                       )}
 
+                      {/* 禁改区恢复标记 */}
+                      {prompt.protectedRestore && (
+                        <span
+                          className="inline-flex items-center gap-1 px-1.5 py-0.5 rounded text-xs font-medium bg-amber-100 dark:bg-amber-900/30 text-amber-700 dark:text-amber-300"
+                          title={prompt.protectedRestore.restored.join('\n')}
+                        >
+                          <ShieldCheck className="w-3 h-3" />
+                          已恢复 {prompt.protectedRestore.restored.length} 个禁改文件
+                        </span>
+                      )}
+
+                      {/* 单独撤回标记 */}
+                      {prompt.revertedAt && (
+                        <span className="inline-flex items-center gap-1 px-1.5 py-0.5 rounded text-xs font-medium bg-red-100 dark:bg-red-900/30 text-red-700 dark:text-red-300">
+                          <Undo2 className="w-3 h-3" />
+                          代码已撤回
+                        </span>
+                      )}
+
                       <Clock className="w-3 h-3 text-gray-400 dark:text-gray-500" />
                     </div>
                     <p className="text-sm text-gray-900 dark:text-gray-100 break-words">
@@ -468,6 +556,11 @@ export const RevertPromptPicker: React.FC<RevertPromptPickerProps> = ({
                 <span className="font-mono">Enter</span> 确认 |{' '}
                 <span className="font-mono">ESC</span> 取消 |{' '}
                 <span className="font-mono">1/2/3</span> 切换模式
+                {!isGemini && (
+                  <>
+                    {' '}| <span className="font-mono">4</span> 单独撤回代码
+                  </>
+                )}
               </p>
             </div>
           </div>
@@ -0,0 +1,184 @@
+import React, { useEffect, useState } from "react";
+import { AlertTriangle, ChevronDown, ChevronRight, Cpu, Loader2, XCircle } from "lucide-react";
+import { listen, type UnlistenFn } from "@tauri-apps/api/event";
+import { Card, CardContent } from "@/components/ui/card";
+import { Button } from "@/components/ui/button";
+import { useTranslation } from "@/hooks/useTranslation";
+import { api, type RunningAiTask, type TaskResourceWarning } from "@/lib/api";
+import { cn } from "@/lib/utils";
+
+/** 与后端采样间隔一致 */
+const REFRESH_INTERVAL_MS = 5000;
+
+export const formatMemory = (bytes: number) =>
+  bytes >= 1024 * 1024 * 1024
+    ? `${(bytes / 1024 / 1024 / 1024).toFixed(1)} GB`
+    : `${Math.round(bytes / 1024 / 1024)} MB`;
+
+interface RunningAiTasksProps {
+  className?: string;
+}
+
+/**
+ * 运行中的 Codex / Gemini 任务：显示进程树实时 CPU / 内存，可单独终止失控的子进程
+ */
+export const RunningAiTasks: React.FC<RunningAiTasksProps> = ({ className }) => {
+  const { t } = useTranslation();
+  const [tasks, setTasks] = useState<RunningAiTask[]>([]);
+  const [warnings, setWarnings] = useState<Record<string, TaskResourceWarning>>({});
+  const [expanded, setExpanded] = useState<Record<string, boolean>>({});
+  const [killing, setKilling] = useState<number | null>(null);
+  const [error, setError] = useState<string | null>(null);
+
+  const loadTasks = async () => {
+    try {
+      const next = await api.listRunningAiTasks();
+      setTasks(next);
+      // 已结束任务的告警不再显示
+      setWarnings((prev) => {
+        const running = new Set(next.map((task) => task.sessionId));
+        return Object.fromEntries(Object.entries(prev).filter(([sessionId]) => running.has(sessionId)));
+      });
+    } catch (err) {
+      console.error("Failed to load running AI tasks:", err);
+    }
+  };
+
+  useEffect(() => {
+    loadTasks();
+    const timer = setInterval(loadTasks, REFRESH_INTERVAL_MS);
+
+    let unlisten: UnlistenFn | undefined;
+    listen<TaskResourceWarning>("task:resource-warning", (event) => {
+      setWarnings((prev) => ({ ...prev, [event.payload.sessionId]: event.payload }));
+      setExpanded((prev) => ({ ...prev, [event.payload.sessionId]: true }));
+    })
+      .then((fn) => {
+        unlisten = fn;
+      })
+      .catch((err) => console.error("Failed to setup task:resource-warning listener:", err));
+
+    return () => {
+      clearInterval(timer);
+      if (unlisten) unlisten();
+    };
+  }, []);
+
+  const handleKill = async (pid: number) => {
+    setKilling(pid);
+    try {
+      await api.killTaskSubprocess(pid);
+      setError(null);
+      await loadTasks();
+    } catch (err) {
+      setError(String(err));
+    } finally {
+      setKilling(null);
+    }
+  };
+
+  if (tasks.length === 0) {
+    return null;
+  }
+
+  return (
+    <div className={cn("space-y-3", className)}>
+      <div className="flex items-center gap-2">
+        <div className="w-2 h-2 bg-green-500 rounded-full" />
+        <h3 className="text-sm font-medium">{t('runningTasks.title')}</h3>
+        <span className="text-xs text-muted-foreground">
+          {t('runningTasks.count', { count: tasks.length })}
+        </span>
+      </div>
+
+      {error && <p className="text-xs text-destructive">{error}</p>}
+
+      <div className="space-y-2">
+        {tasks.map((task) => {
+          const warning = warnings[task.sessionId];
+          const isExpanded = expanded[task.sessionId] ?? false;
+          const processes = task.resources?.processes ?? [];
+
+          return (
+            <Card key={`${task.engine}:${task.sessionId}`} className={cn(warning && "border-destructive/50")}>
+              <CardContent className="p-3 space-y-2">
+                <button
+                  type="button"
+                  className="flex w-full items-center gap-3 text-left"
+                  onClick={() => setExpanded((prev) => ({ ...prev, [task.sessionId]: !isExpanded }))}
+                >
+                  {isExpanded
+                    ? <ChevronDown className="h-4 w-4 flex-shrink-0 text-muted-foreground" />
+                    : <ChevronRight className="h-4 w-4 flex-shrink-0 text-muted-foreground" />}
+                  <Cpu className="h-4 w-4 flex-shrink-0 text-green-600" />
+                  <div className="flex-1 min-w-0">
+                    <p className="text-xs font-medium">
+                      {task.engine === 'codex' ? 'Codex' : 'Gemini'}
+                      <span className="ml-2 font-mono text-muted-foreground">PID {task.pid}</span>
+                    </p>
+                    {task.projectPath && (
+                      <p className="text-xs text-muted-foreground truncate">{task.projectPath}</p>
+                    )}
+                  </div>
+                  <span className="text-xs font-mono text-muted-foreground flex-shrink-0">
+                    {task.resources
+                      ? t('runningTasks.usage', {
+                          cpu: task.resources.cpuPercent.toFixed(0),
+                          memory: formatMemory(task.resources.memoryBytes),
+                        })
+                      : t('runningTasks.notSampled')}
+                  </span>
+                </button>
+
+                {warning && (
+                  <p className="flex items-center gap-1.5 text-xs text-destructive">
+                    <AlertTriangle className="h-3.5 w-3.5 flex-shrink-0" />
+                    {warning.kind === 'cpu'
+                      ? t('runningTasks.cpuWarning', { minutes: Math.round((warning.usage.sampledAt - warning.since) / 60) })
+                      : t('runningTasks.memoryWarning', { memory: formatMemory(warning.usage.memoryBytes) })}
+                  </p>
+                )}
+
+                {isExpanded && processes.length > 0 && (
+                  <div className="space-y-1 border-t pt-2">
+                    {processes.map((process) => (
+                      <div key={process.pid} className="flex items-center gap-2 text-xs">
+                        <span className="font-mono w-16 flex-shrink-0 text-muted-foreground">{process.pid}</span>
+                        <span
+                          className="flex-1 min-w-0 truncate font-mono"
+                          title={process.commandLine || process.name}
+                        >
+                          {process.commandLine || process.name}
+                        </span>
+                        <span className="font-mono w-28 flex-shrink-0 text-right text-muted-foreground">
+                          {process.cpuPercent.toFixed(0)}% · {formatMemory(process.memoryBytes)}
+                        </span>
+                        {process.pid === task.pid ? (
+                          <span className="w-7 flex-shrink-0" />
+                        ) : (
+                          <Button
+                            variant="ghost"
+                            size="icon"
+                            className="h-6 w-7 flex-shrink-0 text-destructive"
+                            aria-label={t('runningTasks.killProcess')}
+                            title={t('runningTasks.killProcess')}
+                            disabled={killing !== null}
+                            onClick={() => handleKill(process.pid)}
+                          >
+                            {killing === process.pid
+                              ? <Loader2 className="h-3.5 w-3.5 animate-spin" />
+                              : <XCircle className="h-3.5 w-3.5" />}
+                          </Button>
+                        )}
+                      </div>
+                    ))}
+                  </div>
+                )}
+              </CardContent>
+            </Card>
+          );
+        })}
+      </div>
+    </div>
+  );
+};
@@ -17,6 +17,21 @@ import { formatUnixTimestamp, formatISOTimestamp, truncateText, getFirstLine } f
 import type { Session, ClaudeMdFile } from "@/lib/api";
 import { api } from "@/lib/api";
 import { useTranslation } from '@/hooks/useTranslation';
+import { BatchConvertDialog } from "@/components/BatchConvertDialog";
+
+type ConvertEngine = 'claude' | 'codex' | 'gemini';
+
+const ENGINE_NAMES: Record<ConvertEngine, string> = {
+  claude: 'Claude',
+  codex: 'Codex',
+  gemini: 'Gemini',
+};
+
+/**
+ * Codex / Gemini sessions convert to Claude; Claude sessions convert to Codex
+ */
+const getConvertTarget = (engine?: string): ConvertEngine =>
+  engine === 'codex' || engine === 'gemini' ? 'claude' : 'codex';
 
 interface SessionListProps {
   /**
@@ -54,7 +69,15 @@ interface SessionListProps {
   /**
    * Callback when a session should be converted
    */
-  onSessionConvert?: (sessionId: string, targetEngine: 'claude' | 'codex', projectId: string, projectPath: string) => Promise<void>;
+  onSessionConvert?: (sessionId: string, targetEngine: ConvertEngine, projectId: string, projectPath: string) => Promise<void>;
+  /**
+   * Project ID (directory name), required for batch conversion
+   */
+  projectId?: string;
+  /**
+   * Callback after a batch conversion wrote new sessions
+   */
+  onSessionsConverted?: () => void;
   /**
    * Optional className for styling
    */
@@ -89,6 +112,8 @@ export const SessionList: React.FC<SessionListProps> = ({
   onEditClaudeFile,
   onNewSession,
   onSessionConvert,
+  projectId,
+  onSessionsConverted,
   className,
 }) => {
   const { t } = useTranslation();
@@ -103,6 +128,7 @@ export const SessionList: React.FC<SessionListProps> = ({
   const [convertDialogOpen, setConvertDialogOpen] = useState(false);
   const [sessionToConvert, setSessionToConvert] = useState<Session | null>(null);
   const [isConverting, setIsConverting] = useState(false);
+  const [batchConvertOpen, setBatchConvertOpen] = useState(false);
 
   // Multi-selection mode
   const [isSelectionMode, setIsSelectionMode] = useState(false);
@@ -302,7 +328,7 @@ export const SessionList: React.FC<SessionListProps> = ({
 
     try {
       setIsConverting(true);
-      const targetEngine = sessionToConvert.engine === 'codex' ? 'claude' : 'codex';
+      const targetEngine = getConvertTarget(sessionToConvert.engine);
       await onSessionConvert(sessionToConvert.id, targetEngine, sessionToConvert.project_id, projectPath);
       setConvertDialogOpen(false);
       setSessionToConvert(null);
@@ -459,6 +485,18 @@ export const SessionList: React.FC<SessionListProps> = ({
             </Button>
           )}
 
+          {!isSelectionMode && projectId && onSessionsConverted
+            && (sessionFilter === 'claude' || sessionFilter === 'codex') && filteredSessions.length > 0 && (
+            <Button
+              variant="outline"
+              size="sm"
+              onClick={() => setBatchConvertOpen(true)}
+            >
+              <RefreshCw className="h-4 w-4 mr-2" />
+              {t('sessionList.batchConvert')}
+            </Button>
+          )}
+
           {onSessionsBatchDelete && validSessions.length > 0 && (
             <Button
               variant={isSelectionMode ? "default" : "outline"}
@@ -540,7 +578,10 @@ export const SessionList: React.FC<SessionListProps> = ({
                 <div className="flex-1 min-w-0 space-y-0.5">
                   {/* First message preview with engine badge */}
                   <div className="flex items-center gap-2">
-                    <p className="text-sm font-medium truncate text-foreground group-hover:text-primary transition-colors flex-1 min-w-0">
+                    <p className={cn(
+                      "text-sm font-medium truncate text-foreground group-hover:text-primary transition-colors flex-1 min-w-0",
+                      session.first_message_source && session.first_message_source !== 'user' && "italic text-muted-foreground"
+                    )}>
                       {firstMessagePreview}
                     </p>
                     {/* 🆕 Engine type badge */}
@@ -583,8 +624,8 @@ export const SessionList: React.FC<SessionListProps> = ({
               <button
                 onClick={(e) => handleConvertClick(e, session)}
                 className="px-3 py-2.5 opacity-0 group-hover:opacity-100 group-focus-within:opacity-100 transition-opacity hover:bg-primary/10 text-primary"
-                aria-label={t('sessionList.convertTo', { engine: session.engine === 'codex' ? 'Claude' : 'Codex' })}
-                title={t('sessionList.experimentalConvert', { engine: session.engine === 'codex' ? 'Claude' : 'Codex' })}
+                aria-label={t('sessionList.convertTo', { engine: ENGINE_NAMES[getConvertTarget(session.engine)] })}
+                title={t('sessionList.experimentalConvert', { engine: ENGINE_NAMES[getConvertTarget(session.engine)] })}
               >
                 <RefreshCw className="h-4 w-4" aria-hidden="true" />
               </button>
@@ -679,7 +720,7 @@ export const SessionList: React.FC<SessionListProps> = ({
             </div>
 
             <p className="text-sm text-muted-foreground mb-4">
-              {t('sessionList.confirmConvertTo', { engine: sessionToConvert?.engine === 'codex' ? 'Claude' : 'Codex' })}
+              {t('sessionList.confirmConvertTo', { engine: ENGINE_NAMES[getConvertTarget(sessionToConvert?.engine)] })}
             </p>
             <div className="space-y-3">
               {sessionToConvert && (
@@ -690,6 +731,11 @@ export const SessionList: React.FC<SessionListProps> = ({
                         <Bot className="h-3 w-3" />
                         Codex
                       </span>
+                    ) : sessionToConvert.engine === 'gemini' ? (
+                      <span className="inline-flex items-center gap-1 px-2 py-1 rounded text-xs font-medium bg-purple-500/10 text-purple-600 dark:text-purple-400 border border-purple-500/20">
+                        <Sparkles className="h-3 w-3" />
+                        Gemini
+                      </span>
                     ) : (
                       <span className="inline-flex items-center gap-1 px-2 py-1 rounded text-xs font-medium bg-orange-500/10 text-orange-600 dark:text-orange-400 border border-orange-500/20">
                         <Zap className="h-3 w-3" />
@@ -697,7 +743,7 @@ export const SessionList: React.FC<SessionListProps> = ({
                       </span>
                     )}
                     <RefreshCw className="h-4 w-4 text-muted-foreground" />
-                    {sessionToConvert.engine === 'codex' ? (
+                    {getConvertTarget(sessionToConvert.engine) === 'claude' ? (
                       <span className="inline-flex items-center gap-1 px-2 py-1 rounded text-xs font-medium bg-orange-500/10 text-orange-600 dark:text-orange-400 border border-orange-500/20">
                         <Zap className="h-3 w-3" />
                         Claude
@@ -760,6 +806,18 @@ export const SessionList: React.FC<SessionListProps> = ({
           </DialogFooter>
         </DialogContent>
       </Dialog>
+
+      {projectId && (sessionFilter === 'claude' || sessionFilter === 'codex') && (
+        <BatchConvertDialog
+          open={batchConvertOpen}
+          onOpenChange={setBatchConvertOpen}
+          sourceEngine={sessionFilter}
+          sessionCount={filteredSessions.length}
+          projectId={projectId}
+          projectPath={projectPath}
+          onConverted={onSessionsConverted}
+        />
+      )}
     </div>
   );
 }; 
\ No newline at end of file
@@ -4,7 +4,7 @@
  */
 
 import React, { useState } from 'react';
-import { FileDown, Check, FileText, FileJson, FileCode2, Copy } from 'lucide-react';
+import { FileDown, Check, FileText, FileJson, FileCode2, Copy, Code2, Archive } from 'lucide-react';
 import { Button } from '@/components/ui/button';
 import {
   DropdownMenu,
@@ -15,9 +15,11 @@ import {
   DropdownMenuLabel,
 } from '@/components/ui/dropdown-menu';
 import { cn } from '@/lib/utils';
+import { CodeBlocksDialog } from '@/components/dialogs/CodeBlocksDialog';
 import { exportSession, copyToClipboard, exportAsJsonl, exportAsMarkdown, exportAsJson } from '@/lib/sessionExport';
+import { formatBlobSize, inlineSessionBlobs } from '@/lib/sessionBlobs';
 import type { ClaudeStreamMessage } from '@/types/claude';
-import type { Session } from '@/lib/api';
+import { api, type Session } from '@/lib/api';
 
 interface SessionToolbarProps {
   /** 当前会话的消息列表 */
@@ -49,6 +51,7 @@ export const SessionToolbar: React.FC<SessionToolbarProps> = ({
   const [actionStatus, setActionStatus] = useState<'idle' | 'success' | 'error'>('idle');
   const [statusMessage, setStatusMessage] = useState<string>('');
   const [isMenuOpen, setIsMenuOpen] = useState(false);
+  const [showCodeBlocks, setShowCodeBlocks] = useState(false);
 
   // 没有消息或正在流式输出时禁用
   const hasMessages = messages.length > 0;
@@ -73,18 +76,19 @@ export const SessionToolbar: React.FC<SessionToolbarProps> = ({
     try {
       let content: string;
       let label: string;
+      const fullMessages = await inlineSessionBlobs(messages, session);
 
       switch (format) {
         case 'jsonl':
-          content = exportAsJsonl(messages);
+          content = exportAsJsonl(fullMessages);
           label = 'JSONL';
           break;
         case 'markdown':
-          content = exportAsMarkdown(messages, session);
+          content = exportAsMarkdown(fullMessages, session);
           label = 'Markdown';
           break;
         case 'json':
-          content = exportAsJson(messages, session);
+          content = exportAsJson(fullMessages, session);
           label = 'JSON';
           break;
       }
@@ -116,6 +120,32 @@ export const SessionToolbar: React.FC<SessionToolbarProps> = ({
     }
   };
 
+  /**
+   * 把会话中的大块工具结果改写为外置存储（原文件备份）
+   */
+  const handleOptimize = async () => {
+    if (!session) return;
+    setIsMenuOpen(false);
+    try {
+      const report = await api.optimizeSessionStorage(
+        session.engine ?? 'claude',
+        session.id,
+        session.project_id
+      );
+      if (report.externalizedCount === 0) {
+        showStatus('success', '无需优化');
+      } else {
+        showStatus(
+          'success',
+          `${formatBlobSize(report.originalBytes)} → ${formatBlobSize(report.optimizedBytes)}`
+        );
+      }
+    } catch (error) {
+      console.error('优化会话存储失败:', error);
+      showStatus('error', '优化失败');
+    }
+  };
+
   return (
     <div className={cn('flex items-center gap-2', className)}>
       <DropdownMenu open={isMenuOpen} onOpenChange={setIsMenuOpen}>
@@ -192,8 +222,46 @@ export const SessionToolbar: React.FC<SessionToolbarProps> = ({
               <span className="text-xs text-muted-foreground">人类可读文档</span>
             </div>
           </DropdownMenuItem>
+
+          {session && (
+            <>
+              <DropdownMenuSeparator />
+              <DropdownMenuItem
+                onClick={() => {
+                  setIsMenuOpen(false);
+                  setShowCodeBlocks(true);
+                }}
+              >
+                <Code2 className="h-4 w-4 mr-2" />
+                <div className="flex flex-col">
+                  <span className="text-sm">提取代码块</span>
+                  <span className="text-xs text-muted-foreground">收集回复中的代码并保存为文件</span>
+                </div>
+              </DropdownMenuItem>
+              {session.engine !== 'gemini' && (
+                <DropdownMenuItem onClick={handleOptimize}>
+                  <Archive className="h-4 w-4 mr-2" />
+                  <div className="flex flex-col">
+                    <span className="text-sm">优化存储</span>
+                    <span className="text-xs text-muted-foreground">大块工具结果外置到附件，原文件备份</span>
+                  </div>
+                </DropdownMenuItem>
+              )}
+            </>
+          )}
         </DropdownMenuContent>
       </DropdownMenu>
+
+      {session && (
+        <CodeBlocksDialog
+          open={showCodeBlocks}
+          onOpenChange={setShowCodeBlocks}
+          engine={session.engine ?? 'claude'}
+          sessionId={session.id}
+          projectPath={session.project_path}
+          projectId={session.project_id}
+        />
+      )}
     </div>
   );
 };
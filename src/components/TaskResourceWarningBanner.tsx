@@ -0,0 +1,98 @@
+/**
+ * TaskResourceWarningBanner - 子进程资源告警提示条
+ *
+ * 监听 task:resource-warning 事件：Codex / Gemini 进程树 CPU 持续过高或内存过大时显示，
+ * 并给出占用最高的子进程，可直接终止它而不取消整个任务
+ */
+
+import { useEffect, useState } from "react";
+import { listen } from "@tauri-apps/api/event";
+import { AlertTriangle, X } from "lucide-react";
+import { Button } from "@/components/ui/button";
+import { useTranslation } from "@/hooks/useTranslation";
+import { api, type ProcessSnapshot, type TaskResourceWarning } from "@/lib/api";
+import { formatMemory } from "@/components/RunningAiTasks";
+
+/** 告警类型对应的占用最高的子进程（不含 CLI 进程本身） */
+const heaviestSubprocess = (warning: TaskResourceWarning): ProcessSnapshot | null => {
+  const [, ...descendants] = warning.usage.processes;
+  const weight = (process: ProcessSnapshot) =>
+    warning.kind === "cpu" ? process.cpuPercent : process.memoryBytes;
+  return descendants.reduce<ProcessSnapshot | null>(
+    (top, process) => (!top || weight(process) > weight(top) ? process : top),
+    null
+  );
+};
+
+/**
+ * 子进程资源告警提示条（全局挂载）
+ */
+export function TaskResourceWarningBanner() {
+  const { t } = useTranslation();
+  const [warning, setWarning] = useState<TaskResourceWarning | null>(null);
+  const [error, setError] = useState<string | null>(null);
+
+  useEffect(() => {
+    let unlisten: (() => void) | undefined;
+    listen<TaskResourceWarning>("task:resource-warning", (event) => {
+      setWarning(event.payload);
+      setError(null);
+    })
+      .then((fn) => {
+        unlisten = fn;
+      })
+      .catch((err) => console.error("Failed to setup task:resource-warning listener:", err));
+    return () => {
+      if (unlisten) unlisten();
+    };
+  }, []);
+
+  if (!warning) return null;
+
+  const engine = warning.engine === "codex" ? "Codex" : "Gemini";
+  const text = warning.kind === "cpu"
+    ? t("runningTasks.cpuWarningBanner", {
+        engine,
+        cpu: warning.usage.cpuPercent.toFixed(0),
+        minutes: Math.round((warning.usage.sampledAt - warning.since) / 60),
+      })
+    : t("runningTasks.memoryWarningBanner", { engine, memory: formatMemory(warning.usage.memoryBytes) });
+  const target = heaviestSubprocess(warning);
+
+  const handleKill = async () => {
+    if (!target) return;
+    try {
+      await api.killTaskSubprocess(target.pid);
+      setWarning(null);
+    } catch (err) {
+      setError(String(err));
+    }
+  };
+
+  return (
+    <div className="flex items-center gap-2 px-4 py-1.5 text-xs border-b bg-destructive/10 text-destructive border-destructive/20">
+      <AlertTriangle className="h-3.5 w-3.5 flex-shrink-0" />
+      <span className="flex-1 truncate" title={error ?? text}>{error ?? text}</span>
+      {target && (
+        <Button
+          variant="ghost"
+          size="sm"
+          className="h-6 px-2 text-xs"
+          title={target.commandLine || target.name}
+          onClick={handleKill}
+        >
+          {t("runningTasks.killNamed", { name: target.name, pid: target.pid })}
+        </Button>
+      )}
+      <Button
+        variant="ghost"
+        size="icon"
+        className="h-6 w-6"
+        aria-label={t("runningTasks.dismiss")}
+        onClick={() => setWarning(null)}
+      >
+        <X className="h-3.5 w-3.5" />
+      </Button>
+    </div>
+  );
+}
@@ -18,8 +18,10 @@ import {
   ChevronLeft,
   ChevronRight,
   ArrowLeft,
-  Layers
+  Layers,
+  Download
 } from "lucide-react";
+import { UsageReportExportDialog } from "@/components/dialogs/UsageReportExportDialog";
 
 interface UsageDashboardProps {
   /**
@@ -47,6 +49,7 @@ export const UsageDashboard: React.FC<UsageDashboardProps> = ({ onBack }) => {
 
   // Multi-engine state
   const [selectedEngine, setSelectedEngine] = useState<EngineType | "all">("all");
+  const [showExportDialog, setShowExportDialog] = useState(false);
   const [codexStats, setCodexStats] = useState<CodexUsageStats | null>(null);
   const [geminiStats, setGeminiStats] = useState<GeminiUsageStats | null>(null);
 
@@ -483,6 +486,15 @@ export const UsageDashboard: React.FC<UsageDashboardProps> = ({ onBack }) => {
               <ArrowLeft className="h-4 w-4 mr-2" />
               {t('usageDashboard.backToHome')}
             </Button>
+            <Button
+              variant="outline"
+              size="sm"
+              onClick={() => setShowExportDialog(true)}
+              className="gap-2"
+            >
+              <Download className="h-4 w-4" />
+              {t('usageDashboard.exportReport')}
+            </Button>
           </div>
           <div className="flex items-center justify-between">
             <div>
@@ -1022,6 +1034,12 @@ export const UsageDashboard: React.FC<UsageDashboardProps> = ({ onBack }) => {
           ) : null}
         </div>
       </div>
+
+      <UsageReportExportDialog
+        open={showExportDialog}
+        onOpenChange={setShowExportDialog}
+        engine={selectedEngine}
+      />
     </div>
   );
 };
\ No newline at end of file
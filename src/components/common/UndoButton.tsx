@@ -0,0 +1,88 @@
+import { useCallback, useEffect, useState } from 'react';
+import { Undo2 } from 'lucide-react';
+import { useTranslation } from 'react-i18next';
+import { Button } from '@/components/ui/button';
+import {
+  Tooltip,
+  TooltipContent,
+  TooltipProvider,
+  TooltipTrigger,
+} from '@/components/ui/tooltip';
+import { api, type UndoOperation } from '@/lib/api';
+
+interface UndoButtonProps {
+  className?: string;
+}
+
+/**
+ * 全局撤销按钮：提示中显示栈顶可撤销操作，点击执行撤销
+ *
+ * 撤销成功后广播 `undo-applied` 事件，相关列表可据此刷新
+ */
+export function UndoButton({ className }: UndoButtonProps) {
+  const { t } = useTranslation();
+  const [top, setTop] = useState<UndoOperation | null>(null);
+  const [message, setMessage] = useState<string | null>(null);
+  const [undoing, setUndoing] = useState(false);
+
+  const refresh = useCallback(async () => {
+    try {
+      const stack = await api.getUndoStack();
+      setTop(stack[0] ?? null);
+    } catch {
+      setTop(null);
+    }
+  }, []);
+
+  useEffect(() => {
+    refresh();
+  }, [refresh]);
+
+  const handleUndo = async () => {
+    setUndoing(true);
+    try {
+      const description = await api.undoLastOperation();
+      setMessage(t('undo.undone', { description }));
+      window.dispatchEvent(new CustomEvent('undo-applied'));
+    } catch (error) {
+      setMessage(String(error));
+    } finally {
+      setUndoing(false);
+      await refresh();
+    }
+  };
+
+  return (
+    <TooltipProvider>
+      <Tooltip
+        onOpenChange={(open) => {
+          if (open) {
+            refresh();
+          } else {
+            setMessage(null);
+          }
+        }}
+      >
+        <TooltipTrigger asChild>
+          <span>
+            <Button
+              variant="ghost"
+              size="icon"
+              onClick={handleUndo}
+              disabled={!top || undoing}
+              className={className ?? 'w-8 h-8 text-muted-foreground hover:text-foreground'}
+              aria-label={t('undo.button')}
+            >
+              <Undo2 className="w-4 h-4" />
+            </Button>
+          </span>
+        </TooltipTrigger>
+        <TooltipContent side="right">
+          <p>
+            {message ?? (top ? t('undo.available', { description: top.description }) : t('undo.empty'))}
+          </p>
+        </TooltipContent>
+      </Tooltip>
+    </TooltipProvider>
+  );
+}
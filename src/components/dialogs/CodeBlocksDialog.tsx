@@ -0,0 +1,237 @@
+/**
+ * CodeBlocksDialog - 会话代码块提取
+ *
+ * 一次性列出会话中所有 AI 回复里的代码块（相同内容只保留最新的一段），
+ * 显示从上下文推测的文件名，可修改文件名后把选中的代码块批量保存到项目目录
+ */
+
+import { useEffect, useMemo, useState } from "react";
+import { Code2, Loader2, Save } from "lucide-react";
+import {
+  Dialog,
+  DialogContent,
+  DialogHeader,
+  DialogTitle,
+  DialogDescription,
+  DialogFooter,
+} from "@/components/ui/dialog";
+import { Button } from "@/components/ui/button";
+import { Checkbox } from "@/components/ui/checkbox";
+import { Input } from "@/components/ui/input";
+import { Label } from "@/components/ui/label";
+import { SelectComponent } from "@/components/ui/select";
+import { api } from "@/lib/api";
+import type { CodeBlock, CodeBlockOverwritePolicy, SaveCodeBlocksReport } from "@/lib/api";
+
+type Engine = "claude" | "codex" | "gemini";
+
+const ALL_LANGUAGES = "__all__";
+
+const POLICY_OPTIONS: { value: CodeBlockOverwritePolicy; label: string }[] = [
+  { value: "rename", label: "已存在时改名" },
+  { value: "skip", label: "已存在时跳过" },
+  { value: "overwrite", label: "已存在时覆盖" },
+];
+
+const STATUS_LABELS: Record<string, string> = {
+  written: "已写入",
+  overwritten: "已覆盖",
+  renamed: "已改名写入",
+  skipped: "已跳过",
+  failed: "失败",
+};
+
+export interface CodeBlocksDialogProps {
+  open: boolean;
+  onOpenChange: (open: boolean) => void;
+  engine: Engine;
+  sessionId: string;
+  /** 项目路径（默认保存目录；Gemini 会话按此定位） */
+  projectPath: string;
+  /** Claude 会话的项目 ID */
+  projectId?: string | null;
+}
+
+export function CodeBlocksDialog({
+  open,
+  onOpenChange,
+  engine,
+  sessionId,
+  projectPath,
+  projectId,
+}: CodeBlocksDialogProps) {
+  const [blocks, setBlocks] = useState<CodeBlock[]>([]);
+  const [selected, setSelected] = useState<Set<string>>(new Set());
+  const [filenames, setFilenames] = useState<Record<string, string>>({});
+  const [language, setLanguage] = useState(ALL_LANGUAGES);
+  const [targetDir, setTargetDir] = useState(projectPath);
+  const [policy, setPolicy] = useState<CodeBlockOverwritePolicy>("rename");
+  const [busy, setBusy] = useState<"extract" | "save" | null>(null);
+  const [report, setReport] = useState<SaveCodeBlocksReport | null>(null);
+  const [error, setError] = useState<string | null>(null);
+
+  useEffect(() => {
+    if (!open) return;
+    setReport(null);
+    setError(null);
+    setLanguage(ALL_LANGUAGES);
+    setTargetDir(projectPath);
+    setBusy("extract");
+    const locator = engine === "claude" ? projectId || projectPath : projectPath;
+    api
+      .extractCodeBlocks(engine, sessionId, locator)
+      .then((result) => {
+        setBlocks(result);
+        setSelected(new Set(result.map((block) => block.id)));
+        setFilenames(Object.fromEntries(result.map((block) => [block.id, block.suggestedFilename ?? ""])));
+      })
+      .catch((err) => setError(String(err)))
+      .finally(() => setBusy(null));
+  }, [open, engine, sessionId, projectPath, projectId]);
+
+  const languageOptions = useMemo(() => {
+    const languages = Array.from(new Set(blocks.map((block) => block.language).filter(Boolean))) as string[];
+    return [
+      { value: ALL_LANGUAGES, label: "全部语言" },
+      ...languages.sort().map((lang) => ({ value: lang, label: lang })),
+    ];
+  }, [blocks]);
+
+  const visible = blocks.filter((block) => language === ALL_LANGUAGES || block.language === language);
+  const toSave = visible.filter((block) => selected.has(block.id));
+
+  const toggle = (id: string, checked: boolean) => {
+    setSelected((prev) => {
+      const next = new Set(prev);
+      if (checked) next.add(id);
+      else next.delete(id);
+      return next;
+    });
+  };
+
+  const handleSave = async () => {
+    setBusy("save");
+    setError(null);
+    setReport(null);
+    try {
+      const payload = toSave.map((block) => ({
+        ...block,
+        suggestedFilename: filenames[block.id]?.trim() || null,
+      }));
+      setReport(await api.saveCodeBlocks(payload, targetDir.trim(), policy));
+    } catch (err) {
+      setError(String(err));
+    } finally {
+      setBusy(null);
+    }
+  };
+
+  return (
+    <Dialog open={open} onOpenChange={(next) => busy !== "save" && onOpenChange(next)}>
+      <DialogContent className="max-w-3xl">
+        <DialogHeader>
+          <DialogTitle className="flex items-center gap-2">
+            <Code2 className="h-5 w-5" />
+            提取代码块
+          </DialogTitle>
+          <DialogDescription>
+            会话中 AI 回复里的全部代码块，相同内容只保留最新的一段。文件名留空时保存为 snippet_N.ext
+          </DialogDescription>
+        </DialogHeader>
+
+        <div className="space-y-3">
+          <div className="flex items-center gap-2">
+            <SelectComponent
+              value={language}
+              onValueChange={setLanguage}
+              options={languageOptions}
+              className="w-40"
+            />
+            <span className="text-xs text-muted-foreground">
+              共 {visible.length} 段，已选 {toSave.length} 段
+            </span>
+          </div>
+
+          <div className="max-h-[45vh] overflow-auto space-y-2 pr-1">
+            {busy === "extract" && (
+              <div className="flex items-center gap-2 text-sm text-muted-foreground">
+                <Loader2 className="h-4 w-4 animate-spin" />
+                正在提取...
+              </div>
+            )}
+            {busy !== "extract" && visible.length === 0 && !error && (
+              <p className="text-sm text-muted-foreground">会话中没有代码块</p>
+            )}
+            {visible.map((block) => (
+              <div key={block.id} className="rounded-md border p-2 space-y-2">
+                <div className="flex items-center gap-2">
+                  <Checkbox
+                    checked={selected.has(block.id)}
+                    onCheckedChange={(checked) => toggle(block.id, !!checked)}
+                  />
+                  <span className="text-xs text-muted-foreground shrink-0">
+                    #{block.messageIndex + 1} · {block.language || "text"}
+                  </span>
+                  <Input
+                    value={filenames[block.id] ?? ""}
+                    onChange={(e) => setFilenames((prev) => ({ ...prev, [block.id]: e.target.value }))}
+                    placeholder="文件名（可选）"
+                    className="h-7 text-xs font-mono"
+                  />
+                </div>
+                <pre className="text-xs bg-muted/40 rounded p-2 max-h-32 overflow-auto whitespace-pre">
+                  {block.content}
+                </pre>
+              </div>
+            ))}
+          </div>
+
+          <div className="flex items-center gap-2">
+            <Label className="text-sm shrink-0">保存到</Label>
+            <Input
+              value={targetDir}
+              onChange={(e) => setTargetDir(e.target.value)}
+              className="font-mono text-xs"
+            />
+            <SelectComponent
+              value={policy}
+              onValueChange={(value) => setPolicy(value as CodeBlockOverwritePolicy)}
+              options={POLICY_OPTIONS}
+              className="w-40"
+            />
+          </div>
+
+          {report && (
+            <div className="rounded-md p-3 bg-muted/30 space-y-1 text-xs max-h-40 overflow-auto">
+              <p className="text-sm">
+                写入 {report.written} 个，跳过 {report.skipped} 个，失败 {report.failed} 个
+              </p>
+              {report.items.map((item) => (
+                <p key={item.id} className="font-mono break-all">
+                  {STATUS_LABELS[item.status]}：{item.path}
+                  {item.error && <span className="text-destructive"> {item.error}</span>}
+                </p>
+              ))}
+            </div>
+          )}
+
+          {error && (
+            <div className="text-xs text-destructive bg-destructive/10 rounded-lg p-3 whitespace-pre-wrap">
+              {error}
+            </div>
+          )}
+        </div>
+
+        <DialogFooter>
+          <Button variant="outline" onClick={() => onOpenChange(false)} disabled={busy === "save"}>
+            关闭
+          </Button>
+          <Button onClick={handleSave} disabled={busy !== null || toSave.length === 0 || !targetDir.trim()} className="gap-2">
+            {busy === "save" ? <Loader2 className="h-4 w-4 animate-spin" /> : <Save className="h-4 w-4" />}
+            保存选中的代码块
+          </Button>
+        </DialogFooter>
+      </DialogContent>
+    </Dialog>
+  );
+}
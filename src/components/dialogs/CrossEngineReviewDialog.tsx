@@ -0,0 +1,182 @@
+/**
+ * CrossEngineReviewDialog - 跨引擎评审对话框
+ *
+ * 把一条 AI 回复交给另一个引擎评审：选择评审引擎、填写评审要求，
+ * 后端取出回复全文并按评审模板拼装提示词，在当前项目新建会话（或续接指定会话）执行。
+ * 评审会话确定后两个会话会互相出现在对方的关联会话中
+ */
+
+import { useEffect, useState } from "react";
+import { Loader2, Scale } from "lucide-react";
+import {
+  Dialog,
+  DialogContent,
+  DialogHeader,
+  DialogTitle,
+  DialogDescription,
+  DialogFooter,
+} from "@/components/ui/dialog";
+import { Button } from "@/components/ui/button";
+import { Input } from "@/components/ui/input";
+import { Label } from "@/components/ui/label";
+import { Textarea } from "@/components/ui/textarea";
+import { SelectComponent } from "@/components/ui/select";
+import { api } from "@/lib/api";
+import type { CrossEngineReviewResult } from "@/lib/api";
+
+type Engine = "claude" | "codex" | "gemini";
+
+const ENGINE_LABELS: Record<Engine, string> = {
+  claude: "Claude",
+  codex: "Codex",
+  gemini: "Gemini",
+};
+
+export interface CrossEngineReviewDialogProps {
+  /** 是否显示对话框 */
+  open: boolean;
+  onOpenChange: (open: boolean) => void;
+  /** 被评审回复所在会话的引擎 */
+  sourceEngine: Engine;
+  /** 被评审回复所在会话 */
+  sessionId: string;
+  /** 回复定位："last"、"ts:<timestamp>" 或回复 ID */
+  messageRef: string;
+  /** 执行评审的项目路径 */
+  projectPath: string;
+  /** Claude 来源会话的项目 ID */
+  projectId?: string | null;
+}
+
+/**
+ * 跨引擎评审对话框
+ */
+export function CrossEngineReviewDialog({
+  open,
+  onOpenChange,
+  sourceEngine,
+  sessionId,
+  messageRef,
+  projectPath,
+  projectId,
+}: CrossEngineReviewDialogProps) {
+  const targetOptions = (Object.keys(ENGINE_LABELS) as Engine[])
+    .filter((engine) => engine !== sourceEngine)
+    .map((engine) => ({ value: engine, label: ENGINE_LABELS[engine] }));
+
+  const [targetEngine, setTargetEngine] = useState<Engine>(targetOptions[0].value);
+  const [instruction, setInstruction] = useState("");
+  const [targetSessionId, setTargetSessionId] = useState("");
+  const [busy, setBusy] = useState(false);
+  const [result, setResult] = useState<CrossEngineReviewResult | null>(null);
+  const [error, setError] = useState<string | null>(null);
+
+  useEffect(() => {
+    if (!open) return;
+    setResult(null);
+    setError(null);
+  }, [open]);
+
+  const handleSubmit = async () => {
+    setBusy(true);
+    setError(null);
+    try {
+      setResult(
+        await api.crossEngineReview(sourceEngine, sessionId, messageRef, targetEngine, instruction, {
+          projectPath,
+          sourceProject: sourceEngine === "claude" ? projectId ?? undefined : undefined,
+          targetSessionId: targetSessionId.trim() || undefined,
+        })
+      );
+    } catch (err) {
+      setError(String(err));
+    } finally {
+      setBusy(false);
+    }
+  };
+
+  return (
+    <Dialog open={open} onOpenChange={(isOpen) => !busy && onOpenChange(isOpen)}>
+      <DialogContent className="sm:max-w-lg">
+        <DialogHeader>
+          <div className="flex items-center gap-2">
+            <div className="h-10 w-10 rounded-full bg-primary/10 flex items-center justify-center">
+              <Scale className="h-5 w-5 text-primary" />
+            </div>
+            <div>
+              <DialogTitle className="text-lg">交给其他引擎评审</DialogTitle>
+              <DialogDescription>
+                将这条 {ENGINE_LABELS[sourceEngine]} 回复的全文连同评审要求发送给所选引擎
+              </DialogDescription>
+            </div>
+          </div>
+        </DialogHeader>
+
+        {result ? (
+          <div className="space-y-2 text-sm">
+            <p>
+              已交给 {ENGINE_LABELS[result.targetEngine]} 评审
+              {result.targetSessionId
+                ? `（会话 ${result.targetSessionId.slice(0, 8)}）`
+                : "，评审会话完成后会出现在本会话的关联会话中"}
+            </p>
+            {result.notice && (
+              <p className="text-xs text-amber-600 bg-amber-500/10 rounded-lg p-3">{result.notice}</p>
+            )}
+          </div>
+        ) : (
+          <div className="space-y-4">
+            <div className="space-y-2">
+              <Label className="text-sm">评审引擎</Label>
+              <SelectComponent
+                value={targetEngine}
+                onValueChange={(value) => setTargetEngine(value as Engine)}
+                options={targetOptions}
+                className="w-40"
+              />
+            </div>
+            <div className="space-y-2">
+              <Label className="text-sm">评审要求</Label>
+              <Textarea
+                value={instruction}
+                onChange={(e) => setInstruction(e.target.value)}
+                placeholder="例如：重点检查并发安全和边界条件（留空则使用默认要求）"
+                rows={3}
+              />
+            </div>
+            <div className="space-y-2">
+              <Label className="text-sm">续接会话 ID（可选，留空则新建会话）</Label>
+              <Input
+                value={targetSessionId}
+                onChange={(e) => setTargetSessionId(e.target.value)}
+                placeholder={`${ENGINE_LABELS[targetEngine]} 会话 ID`}
+              />
+            </div>
+          </div>
+        )}
+
+        {error && (
+          <div className="text-xs text-destructive bg-destructive/10 rounded-lg p-3 whitespace-pre-wrap">
+            {error}
+          </div>
+        )}
+
+        <DialogFooter className="gap-2 sm:gap-2">
+          {result ? (
+            <Button onClick={() => onOpenChange(false)}>完成</Button>
+          ) : (
+            <>
+              <Button variant="outline" onClick={() => onOpenChange(false)} disabled={busy}>
+                取消
+              </Button>
+              <Button onClick={handleSubmit} disabled={busy} className="gap-2">
+                {busy ? <Loader2 className="h-4 w-4 animate-spin" /> : <Scale className="h-4 w-4" />}
+                开始评审
+              </Button>
+            </>
+          )}
+        </DialogFooter>
+      </DialogContent>
+    </Dialog>
+  );
+}
@@ -0,0 +1,147 @@
+/**
+ * ProtectedPathsAlertDialog - 禁改区告警
+ *
+ * 监听 protected-paths-violation 事件：任务结束后发现禁改文件被修改、删除或新建时立即弹出，
+ * 列出被改动的文件，可一键恢复到任务前状态（恢复记录会显示在撤回面板中），也可以保留改动
+ */
+
+import { useEffect, useState } from "react";
+import { listen } from "@tauri-apps/api/event";
+import { Loader2, RotateCcw, ShieldAlert } from "lucide-react";
+import {
+  Dialog,
+  DialogContent,
+  DialogHeader,
+  DialogTitle,
+  DialogDescription,
+  DialogFooter,
+} from "@/components/ui/dialog";
+import { Button } from "@/components/ui/button";
+import { ScrollArea } from "@/components/ui/scroll-area";
+import { api } from "@/lib/api";
+import type { ProtectedChangeKind, ProtectedRestore, ProtectedViolation } from "@/lib/api";
+
+const KIND_LABELS: Record<ProtectedChangeKind, string> = {
+  modified: "修改",
+  deleted: "删除",
+  created: "新建",
+};
+
+const ENGINE_LABELS: Record<ProtectedViolation["engine"], string> = {
+  claude: "Claude",
+  codex: "Codex",
+  gemini: "Gemini",
+};
+
+/**
+ * 禁改区告警对话框（全局挂载）
+ */
+export function ProtectedPathsAlertDialog() {
+  const [queue, setQueue] = useState<ProtectedViolation[]>([]);
+  const [busy, setBusy] = useState(false);
+  const [error, setError] = useState<string | null>(null);
+  const [restore, setRestore] = useState<ProtectedRestore | null>(null);
+
+  useEffect(() => {
+    let unlisten: (() => void) | undefined;
+    listen<ProtectedViolation>("protected-paths-violation", (event) => {
+      setQueue((prev) => [...prev, event.payload]);
+    })
+      .then((fn) => {
+        unlisten = fn;
+      })
+      .catch((err) => console.error("Failed to setup protected-paths-violation listener:", err));
+    return () => {
+      if (unlisten) unlisten();
+    };
+  }, []);
+
+  const violation = queue[0];
+
+  const dismiss = () => {
+    setQueue((prev) => prev.slice(1));
+    setError(null);
+    setRestore(null);
+  };
+
+  const handleRestore = async () => {
+    if (!violation) return;
+    setBusy(true);
+    setError(null);
+    try {
+      setRestore(
+        await api.revertProtectedChanges(violation.engine, violation.sessionId, violation.promptIndex)
+      );
+    } catch (err) {
+      setError(String(err));
+    } finally {
+      setBusy(false);
+    }
+  };
+
+  return (
+    <Dialog open={!!violation} onOpenChange={(isOpen) => !isOpen && !busy && dismiss()}>
+      <DialogContent className="sm:max-w-xl max-h-[80vh] flex flex-col">
+        <DialogHeader>
+          <div className="flex items-center gap-2">
+            <div className="h-10 w-10 rounded-full bg-destructive/10 flex items-center justify-center">
+              <ShieldAlert className="h-5 w-5 text-destructive" />
+            </div>
+            <div>
+              <DialogTitle className="text-lg">禁改区文件被改动</DialogTitle>
+              <DialogDescription>
+                {violation &&
+                  `${ENGINE_LABELS[violation.engine]} 第 ${violation.promptIndex + 1} 条提示词改动了 ${violation.changes.length} 个禁改文件`}
+                {queue.length > 1 && <span className="ml-1">（还有 {queue.length - 1} 条告警）</span>}
+              </DialogDescription>
+            </div>
+          </div>
+        </DialogHeader>
+
+        <ScrollArea className="flex-1 min-h-0 max-h-[320px] my-2 pr-3">
+          <ul className="space-y-1">
+            {violation?.changes.map((change) => (
+              <li key={change.path} className="flex items-center gap-2 text-sm">
+                <span className="shrink-0 rounded px-1.5 py-0.5 text-xs bg-muted">
+                  {KIND_LABELS[change.kind]}
+                </span>
+                <span className="font-mono text-xs break-all">{change.path}</span>
+              </li>
+            ))}
+          </ul>
+        </ScrollArea>
+
+        {restore && (
+          <div className="text-xs text-muted-foreground bg-muted/40 rounded-lg p-3 whitespace-pre-wrap">
+            {`已恢复 ${restore.restored.length} 个文件`}
+            {restore.commit && `，并提交 ${restore.commit.slice(0, 8)}`}
+            {restore.skipped.length > 0 &&
+              `\n以下文件超过快照大小上限，需要手动处理：\n${restore.skipped.join("\n")}`}
+          </div>
+        )}
+
+        {error && (
+          <div className="text-xs text-destructive bg-destructive/10 rounded-lg p-3 whitespace-pre-wrap">
+            {error}
+          </div>
+        )}
+
+        <DialogFooter>
+          {restore ? (
+            <Button onClick={dismiss}>完成</Button>
+          ) : (
+            <>
+              <Button variant="outline" onClick={dismiss} disabled={busy}>
+                保留改动
+              </Button>
+              <Button variant="destructive" onClick={handleRestore} disabled={busy} className="gap-2">
+                {busy ? <Loader2 className="h-4 w-4 animate-spin" /> : <RotateCcw className="h-4 w-4" />}
+                恢复到任务前状态
+              </Button>
+            </>
+          )}
+        </DialogFooter>
+      </DialogContent>
+    </Dialog>
+  );
+}
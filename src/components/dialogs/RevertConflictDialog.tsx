@@ -0,0 +1,223 @@
+/**
+ * RevertConflictDialog - 撤回冲突解决对话框
+ *
+ * 交互式撤回遇到冲突时显示，逐块选择保留当前内容（ours）、
+ * 撤回目标（theirs）或共同祖先（base），确认后继续撤回；
+ * 也可以随时放弃撤回（关闭对话框同样视为放弃），回到撤回前的 HEAD
+ */
+
+import { useCallback, useEffect, useState } from "react";
+import { GitMerge, Loader2, Play, XCircle } from "lucide-react";
+import {
+  Dialog,
+  DialogContent,
+  DialogHeader,
+  DialogTitle,
+  DialogDescription,
+  DialogFooter,
+} from "@/components/ui/dialog";
+import { Button } from "@/components/ui/button";
+import { ScrollArea } from "@/components/ui/scroll-area";
+import { cn } from "@/lib/utils";
+import { api } from "@/lib/api";
+import type { HunkChoice, RevertConflict, RevertResult } from "@/lib/api";
+
+export interface RevertConflictDialogProps {
+  /** 是否显示对话框 */
+  open: boolean;
+  /** 发生冲突的项目路径 */
+  projectPath: string;
+  /** 冲突全部解决、撤回完成 */
+  onResolved: (result: RevertResult) => void;
+  /** 已放弃撤回 */
+  onAborted: () => void;
+}
+
+const CHOICE_LABELS: Record<HunkChoice, string> = {
+  ours: "保留当前",
+  theirs: "撤回目标",
+  base: "原始版本",
+};
+
+/**
+ * 撤回冲突解决对话框
+ */
+export function RevertConflictDialog({
+  open,
+  projectPath,
+  onResolved,
+  onAborted,
+}: RevertConflictDialogProps) {
+  const [conflict, setConflict] = useState<RevertConflict | null>(null);
+  const [choices, setChoices] = useState<Record<string, HunkChoice[]>>({});
+  const [busy, setBusy] = useState(false);
+  const [error, setError] = useState<string | null>(null);
+
+  const loadConflict = useCallback(async () => {
+    const current = await api.getRevertConflict(projectPath);
+    setConflict(current);
+    // 默认选择撤回目标，与撤回的意图一致
+    setChoices(
+      Object.fromEntries(
+        (current?.files ?? []).map((file) => [
+          file.path,
+          file.hunks.map((): HunkChoice => "theirs"),
+        ])
+      )
+    );
+  }, [projectPath]);
+
+  useEffect(() => {
+    if (!open) return;
+    setError(null);
+    loadConflict().catch((err) => setError(String(err)));
+  }, [open, loadConflict]);
+
+  const setChoice = (path: string, index: number, choice: HunkChoice) => {
+    setChoices((prev) => {
+      const next = [...(prev[path] ?? [])];
+      next[index] = choice;
+      return { ...prev, [path]: next };
+    });
+  };
+
+  const unresolvable = conflict?.files.filter((file) => file.hunks.length === 0) ?? [];
+
+  const handleResolve = async () => {
+    if (!conflict) return;
+    setBusy(true);
+    setError(null);
+    try {
+      const result = await api.resolveRevertConflict(
+        projectPath,
+        conflict.files.map((file) => ({
+          path: file.path,
+          choices: choices[file.path] ?? [],
+        }))
+      );
+      if (result.hasConflicts) {
+        // 范围内的后续提交或后续记录又发生冲突，继续逐块选择
+        setError(result.message);
+        await loadConflict();
+      } else {
+        onResolved(result);
+      }
+    } catch (err) {
+      setError(String(err));
+    } finally {
+      setBusy(false);
+    }
+  };
+
+  const handleAbort = async () => {
+    setBusy(true);
+    setError(null);
+    try {
+      await api.abortRevert(projectPath);
+      onAborted();
+    } catch (err) {
+      setError(String(err));
+    } finally {
+      setBusy(false);
+    }
+  };
+
+  return (
+    <Dialog open={open} onOpenChange={(isOpen) => !isOpen && !busy && handleAbort()}>
+      <DialogContent className="sm:max-w-3xl max-h-[85vh] flex flex-col">
+        <DialogHeader>
+          <div className="flex items-center gap-2">
+            <div className="h-10 w-10 rounded-full bg-amber-500/10 flex items-center justify-center">
+              <GitMerge className="h-5 w-5 text-amber-500" />
+            </div>
+            <div>
+              <DialogTitle className="text-lg">撤回时发生冲突</DialogTitle>
+              <DialogDescription>
+                {conflict?.message ?? "正在读取冲突..."}
+                {conflict && conflict.remainingRanges > 0 && (
+                  <span className="ml-1">（之后还有 {conflict.remainingRanges} 段待撤回）</span>
+                )}
+              </DialogDescription>
+            </div>
+          </div>
+        </DialogHeader>
+
+        <ScrollArea className="flex-1 min-h-0 h-[420px] my-2 pr-3">
+          <div className="space-y-4">
+            {conflict?.files.map((file) => (
+              <div key={file.path} className="rounded-lg border">
+                <div className="px-3 py-2 border-b bg-muted/40 text-sm font-mono truncate">
+                  {file.path}
+                </div>
+                {file.hunks.length === 0 ? (
+                  <div className="p-3 text-xs text-muted-foreground">
+                    该文件不是文本内容冲突（如删除/修改冲突），无法逐块选择，只能放弃撤回
+                  </div>
+                ) : (
+                  file.hunks.map((hunk, index) => {
+                    const selected = choices[file.path]?.[index] ?? "theirs";
+                    const options: HunkChoice[] =
+                      hunk.base != null ? ["ours", "theirs", "base"] : ["ours", "theirs"];
+                    return (
+                      <div key={index} className="p-3 space-y-2 border-b last:border-b-0">
+                        <div className="flex items-center gap-2 text-xs">
+                          <span className="text-muted-foreground">冲突块 {index + 1}</span>
+                          <div className="ml-auto flex gap-1">
+                            {options.map((option) => (
+                              <Button
+                                key={option}
+                                size="sm"
+                                variant={selected === option ? "default" : "outline"}
+                                className="h-7 text-xs"
+                                onClick={() => setChoice(file.path, index, option)}
+                              >
+                                {CHOICE_LABELS[option]}
+                              </Button>
+                            ))}
+                          </div>
+                        </div>
+                        <pre
+                          className={cn(
+                            "text-xs font-mono whitespace-pre-wrap rounded-md p-2 bg-muted/30",
+                            "max-h-48 overflow-auto"
+                          )}
+                        >
+                          {(selected === "ours"
+                            ? hunk.ours
+                            : selected === "base"
+                            ? hunk.base
+                            : hunk.theirs) || "（空）"}
+                        </pre>
+                      </div>
+                    );
+                  })
+                )}
+              </div>
+            ))}
+          </div>
+        </ScrollArea>
+
+        {error && (
+          <div className="text-xs text-destructive bg-destructive/10 rounded-lg p-3 whitespace-pre-wrap">
+            {error}
+          </div>
+        )}
+
+        <DialogFooter className="gap-2 sm:gap-2">
+          <Button variant="outline" onClick={handleAbort} disabled={busy} className="gap-2">
+            <XCircle className="h-4 w-4" />
+            放弃撤回
+          </Button>
+          <Button
+            onClick={handleResolve}
+            disabled={busy || !conflict || unresolvable.length > 0}
+            className="gap-2"
+          >
+            {busy ? <Loader2 className="h-4 w-4 animate-spin" /> : <Play className="h-4 w-4" />}
+            应用并继续撤回
+          </Button>
+        </DialogFooter>
+      </DialogContent>
+    </Dialog>
+  );
+}
@@ -0,0 +1,212 @@
+/**
+ * UsageReportExportDialog - 用量报表导出
+ *
+ * 选择日期范围（本地时区）、分组维度与格式后，由用户选择保存位置，
+ * 后端汇总三个引擎的用量并写出 CSV / JSON，完成后显示总行数与总费用；
+ * 也可按会话导出明细 CSV（限定为仪表盘当前选中的引擎）
+ */
+
+import { useEffect, useState } from "react";
+import { useTranslation } from "react-i18next";
+import { save } from "@tauri-apps/plugin-dialog";
+import { Download, Loader2 } from "lucide-react";
+import {
+  Dialog,
+  DialogContent,
+  DialogHeader,
+  DialogTitle,
+  DialogDescription,
+  DialogFooter,
+} from "@/components/ui/dialog";
+import { Button } from "@/components/ui/button";
+import { Checkbox } from "@/components/ui/checkbox";
+import { Input } from "@/components/ui/input";
+import { Label } from "@/components/ui/label";
+import { SelectComponent } from "@/components/ui/select";
+import { api } from "@/lib/api";
+import type { UsageGroupKey, UsageReportFormat, UsageReportSummary } from "@/lib/api";
+import { ENGINE_LABELS, type EngineType } from "@/types/usage";
+
+const GROUP_KEYS: UsageGroupKey[] = ["day", "project", "provider", "model", "engine"];
+
+export interface UsageReportExportDialogProps {
+  open: boolean;
+  onOpenChange: (open: boolean) => void;
+  /** 按会话导出时限定的引擎 */
+  engine?: EngineType | "all";
+}
+
+const formatDate = (date: Date) => {
+  const pad = (n: number) => String(n).padStart(2, "0");
+  return `${date.getFullYear()}-${pad(date.getMonth() + 1)}-${pad(date.getDate())}`;
+};
+
+/** 默认导出上个自然月 */
+const lastMonthRange = () => {
+  const now = new Date();
+  const from = new Date(now.getFullYear(), now.getMonth() - 1, 1);
+  const to = new Date(now.getFullYear(), now.getMonth(), 0);
+  return { from: formatDate(from), to: formatDate(to) };
+};
+
+export function UsageReportExportDialog({ open, onOpenChange, engine = "all" }: UsageReportExportDialogProps) {
+  const { t } = useTranslation();
+  const [from, setFrom] = useState(() => lastMonthRange().from);
+  const [to, setTo] = useState(() => lastMonthRange().to);
+  const [groupBy, setGroupBy] = useState<UsageGroupKey[]>(["project", "model"]);
+  const [format, setFormat] = useState<UsageReportFormat>("csv");
+  const [busy, setBusy] = useState(false);
+  const [error, setError] = useState<string | null>(null);
+  const [summary, setSummary] = useState<UsageReportSummary | null>(null);
+  const [sessionExport, setSessionExport] = useState<{ rows: number; path: string } | null>(null);
+
+  useEffect(() => {
+    if (!open) return;
+    setError(null);
+    setSummary(null);
+    setSessionExport(null);
+  }, [open]);
+
+  const toggleKey = (key: UsageGroupKey, checked: boolean) => {
+    setGroupBy((prev) =>
+      checked
+        ? GROUP_KEYS.filter((k) => k === key || prev.includes(k))
+        : prev.filter((k) => k !== key)
+    );
+  };
+
+  const handleExport = async () => {
+    setError(null);
+    setSummary(null);
+    setSessionExport(null);
+    const outputPath = await save({
+      defaultPath: `usage-report-${from}_${to}.${format}`,
+      filters: [{ name: format.toUpperCase(), extensions: [format] }],
+    });
+    if (!outputPath) return;
+
+    setBusy(true);
+    try {
+      setSummary(await api.exportUsageReport(from, to, groupBy, format, outputPath));
+    } catch (err) {
+      setError(String(err));
+    } finally {
+      setBusy(false);
+    }
+  };
+
+  const handleExportSessions = async () => {
+    setError(null);
+    setSummary(null);
+    setSessionExport(null);
+    const suffix = engine === "all" ? "" : `-${engine}`;
+    const outputPath = await save({
+      defaultPath: `usage-sessions${suffix}-${from}_${to}.csv`,
+      filters: [{ name: "CSV", extensions: ["csv"] }],
+    });
+    if (!outputPath) return;
+
+    setBusy(true);
+    try {
+      const rows = await api.exportUsageCsv(from, to, engine === "all" ? undefined : engine, outputPath);
+      setSessionExport({ rows, path: outputPath });
+    } catch (err) {
+      setError(String(err));
+    } finally {
+      setBusy(false);
+    }
+  };
+
+  return (
+    <Dialog open={open} onOpenChange={(isOpen) => !busy && onOpenChange(isOpen)}>
+      <DialogContent className="sm:max-w-lg">
+        <DialogHeader>
+          <DialogTitle>{t('usageDashboard.exportReport')}</DialogTitle>
+          <DialogDescription>{t('usageDashboard.exportDescription')}</DialogDescription>
+        </DialogHeader>
+
+        <div className="space-y-4 py-2">
+          <div className="grid grid-cols-2 gap-3">
+            <div className="space-y-1">
+              <Label htmlFor="usageReportFrom" className="text-sm">
+                {t('usageDashboard.exportFrom')}
+              </Label>
+              <Input id="usageReportFrom" type="date" value={from} onChange={(e) => setFrom(e.target.value)} />
+            </div>
+            <div className="space-y-1">
+              <Label htmlFor="usageReportTo" className="text-sm">
+                {t('usageDashboard.exportTo')}
+              </Label>
+              <Input id="usageReportTo" type="date" value={to} onChange={(e) => setTo(e.target.value)} />
+            </div>
+          </div>
+
+          <div className="space-y-2">
+            <Label className="text-sm">{t('usageDashboard.exportGroupBy')}</Label>
+            <div className="flex flex-wrap gap-4">
+              {GROUP_KEYS.map((key) => (
+                <label key={key} className="flex items-center gap-2 text-sm cursor-pointer">
+                  <Checkbox
+                    checked={groupBy.includes(key)}
+                    onCheckedChange={(checked) => toggleKey(key, checked === true)}
+                  />
+                  {t(`usageDashboard.groupBy.${key}`)}
+                </label>
+              ))}
+            </div>
+          </div>
+
+          <div className="space-y-1">
+            <Label className="text-sm">{t('usageDashboard.exportFormat')}</Label>
+            <SelectComponent
+              value={format}
+              onValueChange={(value) => setFormat(value as UsageReportFormat)}
+              options={[
+                { value: "csv", label: "CSV" },
+                { value: "json", label: "JSON" },
+              ]}
+              className="w-40"
+            />
+          </div>
+
+          {summary && (
+            <div className="text-xs text-muted-foreground bg-muted/40 rounded-lg p-3 break-all">
+              {t('usageDashboard.exportDone', {
+                rows: summary.rowCount,
+                cost: `$${summary.totalCost.toFixed(2)}`,
+                path: summary.outputPath,
+              })}
+            </div>
+          )}
+
+          {sessionExport && (
+            <div className="text-xs text-muted-foreground bg-muted/40 rounded-lg p-3 break-all">
+              {t('usageDashboard.exportSessionsDone', sessionExport)}
+            </div>
+          )}
+
+          {error && (
+            <div className="text-xs text-destructive bg-destructive/10 rounded-lg p-3 whitespace-pre-wrap">
+              {error}
+            </div>
+          )}
+        </div>
+
+        <DialogFooter>
+          <Button variant="outline" onClick={() => onOpenChange(false)} disabled={busy}>
+            {t('buttons.close')}
+          </Button>
+          <Button variant="outline" onClick={handleExportSessions} disabled={busy || !from || !to}>
+            {engine === "all"
+              ? t('usageDashboard.exportSessions')
+              : t('usageDashboard.exportEngineSessions', { engine: ENGINE_LABELS[engine] })}
+          </Button>
+          <Button onClick={handleExport} disabled={busy || !from || !to} className="gap-2">
+            {busy ? <Loader2 className="h-4 w-4 animate-spin" /> : <Download className="h-4 w-4" />}
+            {t('usageDashboard.export')}
+          </Button>
+        </DialogFooter>
+      </DialogContent>
+    </Dialog>
+  );
+}
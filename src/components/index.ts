@@ -37,6 +37,7 @@ export * from "./ui/scroll-area";
 
 // Session components
 export * from "./RunningClaudeSessions";
+export * from "./RunningAiTasks";
 export * from "./TabManager";
 export * from "./TabSessionWrapper";
 export * from "./TabIndicator";
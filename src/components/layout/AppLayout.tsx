@@ -5,6 +5,9 @@ import { useUpdate } from '@/contexts/UpdateContext';
 import { message } from '@tauri-apps/plugin-dialog';
 import { UpdateDialog } from '@/components/dialogs/UpdateDialog';
 import { AboutDialog } from '@/components/dialogs/AboutDialog';
+import { ProtectedPathsAlertDialog } from '@/components/dialogs/ProtectedPathsAlertDialog';
+import { PreindexProgressBanner } from '@/components/PreindexProgressBanner';
+import { TaskResourceWarningBanner } from '@/components/TaskResourceWarningBanner';
 
 interface AppLayoutProps {
   children: ReactNode;
@@ -57,6 +60,9 @@ export const AppLayout: React.FC<AppLayoutProps> = ({ children }) => {
 
       {/* Main Content Area */}
       <main className="flex-1 relative flex flex-col min-w-0 overflow-hidden z-10">
+        <PreindexProgressBanner />
+        <TaskResourceWarningBanner />
+
         {/* Content */}
         <div className="flex-1 overflow-y-auto overflow-x-hidden scroll-smooth">
           {children}
@@ -71,6 +77,8 @@ export const AppLayout: React.FC<AppLayoutProps> = ({ children }) => {
         onClose={() => setShowAboutDialog(false)}
         onCheckUpdate={handleCheckUpdate}
       />
+
+      <ProtectedPathsAlertDialog />
     </div>
   );
 };
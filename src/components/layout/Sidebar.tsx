@@ -26,6 +26,7 @@ import {
 import { UnifiedEngineStatus } from '@/components/UnifiedEngineStatus';
 import { UpdateBadge } from '@/components/common/UpdateBadge';
 import { ThemeToggle } from '@/components/ui/theme-toggle';
+import { UndoButton } from '@/components/common/UndoButton';
 
 interface SidebarProps {
   currentView: View;
@@ -189,6 +190,8 @@ export const Sidebar: React.FC<SidebarProps> = ({
             </Tooltip>
           </TooltipProvider>
 
+          <UndoButton />
+
           {onAboutClick && (
             <TooltipProvider>
               <Tooltip>
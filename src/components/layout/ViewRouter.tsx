@@ -8,6 +8,7 @@ import { Button } from "@/components/ui/button";
 import { ProjectList } from "@/components/ProjectList";
 import { SessionList } from "@/components/SessionList";
 import { RunningClaudeSessions } from "@/components/RunningClaudeSessions";
+import { RunningAiTasks } from "@/components/RunningAiTasks";
 import { MarkdownEditor } from "@/components/MarkdownEditor";
 import { CodexMarkdownEditor } from "@/components/CodexMarkdownEditor";
 import { GeminiMarkdownEditor } from "@/components/GeminiMarkdownEditor";
@@ -135,6 +136,35 @@ export const ViewRouter: React.FC = () => {
     };
   }, [loadProjects, selectedProject, refreshSessions]);
 
+  // 重新打开项目时回到上次的会话（每个项目每次启动只自动恢复一次），草稿随会话 id 自动恢复
+  const restoredProjectsRef = useRef<Set<string>>(new Set());
+  const selectedProjectPathRef = useRef<string | null>(null);
+  selectedProjectPathRef.current = selectedProject?.path ?? null;
+  useEffect(() => {
+    if (!selectedProject || loading || currentView !== "projects") return;
+    const projectPath = selectedProject.path;
+    if (restoredProjectsRef.current.has(projectPath)) return;
+    restoredProjectsRef.current.add(projectPath);
+
+    api.getProjectWorkspaceState(projectPath)
+      .then((restored) => {
+        // 等待期间用户已离开该项目时不再跳转
+        if (!restored || selectedProjectPathRef.current !== projectPath) return;
+        if (restored.sessionClearedReason) {
+          setToast({ message: restored.sessionClearedReason, type: "info" });
+          return;
+        }
+        const last = restored.currentSession;
+        const session = last && sessions.find(s => s.id === last.sessionId);
+        if (!session) return;
+        const result = openSessionInBackground(session);
+        switchToTab(result.tabId);
+        navigateTo("claude-tab-manager");
+        setToast({ message: t('viewRouter.sessionRestored'), type: "info" });
+      })
+      .catch(() => {});
+  }, [selectedProject, loading, currentView, sessions, openSessionInBackground, switchToTab, navigateTo, t]);
+
   // Handlers
   const handleSessionDelete = async (sessionId: string, projectId: string) => {
     try {
@@ -219,14 +249,14 @@ export const ViewRouter: React.FC = () => {
     }
   };
 
-  const handleSessionConvert = async (sessionId: string, targetEngine: 'claude' | 'codex', projectId: string, projectPath: string) => {
+  const handleSessionConvert = async (sessionId: string, targetEngine: 'claude' | 'codex' | 'gemini', projectId: string, projectPath: string) => {
     try {
       const result = await api.convertSession(sessionId, targetEngine, projectId, projectPath);
 
       if (result.success) {
         refreshSessions();
         setToast({
-          message: `会话已成功转换到 ${targetEngine === 'claude' ? 'Claude' : 'Codex'}！新会话 ID: ${result.newSessionId.substring(0, 8)}...`,
+          message: `会话已成功转换到 ${{ claude: 'Claude', codex: 'Codex', gemini: 'Gemini' }[targetEngine]}！新会话 ID: ${result.newSessionId.substring(0, 8)}...`,
           type: "success"
         });
       } else {
@@ -360,6 +390,11 @@ export const ViewRouter: React.FC = () => {
                         onSessionDelete={handleSessionDelete}
                         onSessionsBatchDelete={handleSessionsBatchDelete}
                         onSessionConvert={handleSessionConvert}
+                        projectId={selectedProject.id}
+                        onSessionsConverted={() => {
+                          refreshSessions();
+                          loadProjects();
+                        }}
                         onSessionClick={(session) => {
                           const result = openSessionInBackground(session);
                           switchToTab(result.tabId);
@@ -390,6 +425,8 @@ export const ViewRouter: React.FC = () => {
                         }}
                       />
 
+                      <RunningAiTasks className="mb-4" />
+
                       {projects.length > 0 ? (
                         <ProjectList
                           projects={projects}
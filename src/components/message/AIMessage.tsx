@@ -1,4 +1,4 @@
-import React from "react";
+import React, { useState } from "react";
 import { ClaudeIcon } from "@/components/icons/ClaudeIcon";
 import { CodexIcon } from "@/components/icons/CodexIcon";
 import { GeminiIcon } from "@/components/icons/GeminiIcon";
@@ -7,6 +7,8 @@ import { MessageContent } from "./MessageContent";
 import { ToolCallsGroup } from "./ToolCallsGroup";
 import { ThinkingBlock } from "./ThinkingBlock";
 import { MessageActions } from "./MessageActions";
+import { CrossEngineReviewDialog } from "@/components/dialogs/CrossEngineReviewDialog";
+import { useOptionalSession } from "@/contexts/SessionContext";
 import { cn } from "@/lib/utils";
 import { tokenExtractor } from "@/lib/tokenExtractor";
 import { formatTimestamp } from "@/lib/messageUtils";
@@ -111,6 +113,8 @@ export const AIMessage: React.FC<AIMessageProps> = ({
   const hasTools = hasToolCalls(message);
   const hasThinking = hasThinkingBlock(message);
   const thinkingContent = hasThinking ? extractThinkingContent(message) : '';
+  const sessionContext = useOptionalSession();
+  const [showReviewDialog, setShowReviewDialog] = useState(false);
 
   // Detect engine type for avatar styling
   const isCodexMessage = (message as any).engine === 'codex';
@@ -140,6 +144,13 @@ export const AIMessage: React.FC<AIMessageProps> = ({
   })() : null;
 
   const assistantName = isGeminiMessage ? 'Gemini' : isCodexMessage ? 'Codex' : 'Claude';
+  const sourceEngine = isGeminiMessage ? 'gemini' : isCodexMessage ? 'codex' : 'claude';
+
+  // 跨引擎评审：优先按记录 uuid 定位回复，其次按记录时间戳
+  const reviewSessionId = sessionContext?.sessionId;
+  const reviewMessageRef = (message as any).uuid
+    ?? ((message as any).timestamp ? `ts:${(message as any).timestamp}` : 'last');
+  const canReview = !!text && !isStreaming && !!reviewSessionId && !!sessionContext?.projectPath;
   
   // Select icon based on engine
   const Icon = isGeminiMessage ? GeminiIcon : isCodexMessage ? CodexIcon : ClaudeIcon;
@@ -180,7 +191,10 @@ export const AIMessage: React.FC<AIMessageProps> = ({
           <div className="flex-1 min-w-0 space-y-1 relative">
             {/* Actions Toolbar - Visible on Hover */}
             <div className="absolute -top-2 right-0 opacity-0 group-hover:opacity-100 transition-opacity duration-200 z-10">
-              <MessageActions content={text || thinkingContent} />
+              <MessageActions
+                content={text || thinkingContent}
+                onReview={canReview ? () => setShowReviewDialog(true) : undefined}
+              />
             </div>
 
             {/* Main Content */}
@@ -217,6 +231,18 @@ export const AIMessage: React.FC<AIMessageProps> = ({
           </div>
         </div>
       </MessageBubble>
+
+      {showReviewDialog && sessionContext && reviewSessionId && (
+        <CrossEngineReviewDialog
+          open={showReviewDialog}
+          onOpenChange={setShowReviewDialog}
+          sourceEngine={sourceEngine}
+          sessionId={reviewSessionId}
+          messageRef={reviewMessageRef}
+          projectPath={sessionContext.projectPath}
+          projectId={sessionContext.projectId}
+        />
+      )}
     </div>
   );
 };
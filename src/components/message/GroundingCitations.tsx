@@ -0,0 +1,46 @@
+/**
+ * GroundingCitations - 联网搜索引用来源列表
+ *
+ * Gemini 的 google_web_search 结果会附带来源，后端解析为 geminiMetadata.citations；
+ * 这里按回答中 [n] 标记的编号列出，点击在浏览器中打开
+ */
+
+import React from 'react';
+import { Globe } from 'lucide-react';
+import { open as openUrl } from '@tauri-apps/plugin-shell';
+import { useTranslation } from '@/hooks/useTranslation';
+import type { GeminiGroundingCitation } from '@/types/gemini';
+
+interface GroundingCitationsProps {
+  citations: GeminiGroundingCitation[];
+}
+
+export const GroundingCitations: React.FC<GroundingCitationsProps> = ({ citations }) => {
+  const { t } = useTranslation();
+
+  if (citations.length === 0) return null;
+
+  return (
+    <div className="mt-2 rounded-md border border-border bg-muted/30 px-3 py-2 text-xs">
+      <div className="flex items-center gap-1.5 font-medium text-muted-foreground mb-1">
+        <Globe className="w-3 h-3" />
+        {t('tools.groundingSources', { count: citations.length })}
+      </div>
+      <ol className="space-y-0.5">
+        {citations.map((citation) => (
+          <li key={`${citation.index}-${citation.url}`} className="flex gap-1.5 min-w-0">
+            <span className="text-muted-foreground shrink-0">[{citation.index}]</span>
+            <button
+              type="button"
+              onClick={() => openUrl(citation.url)}
+              className="truncate text-left text-primary hover:underline"
+              title={citation.url}
+            >
+              {citation.title || citation.url}
+            </button>
+          </li>
+        ))}
+      </ol>
+    </div>
+  );
+};
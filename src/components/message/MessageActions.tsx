@@ -1,6 +1,6 @@
 import React, { useState } from "react";
 import { useTranslation } from "react-i18next";
-import { Copy, Check, RefreshCw, Edit2, AlertCircle } from "lucide-react";
+import { Copy, Check, RefreshCw, Edit2, AlertCircle, Scale } from "lucide-react";
 import { Button } from "@/components/ui/button";
 import {
   Tooltip,
@@ -15,6 +15,8 @@ interface MessageActionsProps {
   content: string;
   onRegenerate?: () => void;
   onEdit?: () => void;
+  /** 交给其他引擎评审 */
+  onReview?: () => void;
   className?: string;
 }
 
@@ -22,6 +24,7 @@ export const MessageActions: React.FC<MessageActionsProps> = ({
   content,
   onRegenerate,
   onEdit,
+  onReview,
   className,
 }) => {
   const { t } = useTranslation();
@@ -103,6 +106,22 @@ export const MessageActions: React.FC<MessageActionsProps> = ({
             <TooltipContent>{t('message.editMessage')}</TooltipContent>
           </Tooltip>
         )}
+
+        {onReview && (
+          <Tooltip>
+            <TooltipTrigger asChild>
+              <Button
+                variant="ghost"
+                size="icon-xs"
+                onClick={onReview}
+                className="h-6 w-6 text-muted-foreground hover:text-foreground hover:bg-muted"
+              >
+                <Scale className="h-3.5 w-3.5" />
+              </Button>
+            </TooltipTrigger>
+            <TooltipContent>{t('session.crossEngineReview')}</TooltipContent>
+          </Tooltip>
+        )}
       </div>
     </TooltipProvider>
   );
@@ -0,0 +1,159 @@
+/**
+ * PromptFileSnapshotsDialog - 提示词引用文件的快照
+ *
+ * 列出提示词发送时 @ 引用的文件及其与当前版本的比较状态，
+ * 选中保存了内容的文件时显示快照与当前内容的差异
+ */
+
+import React, { useEffect, useState } from "react";
+import * as Diff from "diff";
+import { FileText, Loader2 } from "lucide-react";
+import { Badge } from "@/components/ui/badge";
+import { Dialog, DialogContent, DialogHeader, DialogTitle, DialogDescription } from "@/components/ui/dialog";
+import { useTranslation } from "@/hooks/useTranslation";
+import { cn } from "@/lib/utils";
+import {
+  api,
+  type FileSnapshotComparison,
+  type FileSnapshotStatus,
+  type PromptFileSnapshotEntry,
+} from "@/lib/api";
+
+interface PromptFileSnapshotsDialogProps {
+  open: boolean;
+  onOpenChange: (open: boolean) => void;
+  engine: 'claude' | 'codex' | 'gemini';
+  sessionId: string;
+  promptIndex: number;
+}
+
+const STATUS_CLASS: Record<FileSnapshotStatus, string> = {
+  unchanged: "text-muted-foreground",
+  modified: "text-amber-600 dark:text-amber-400 border-amber-500/40",
+  deleted: "text-destructive border-destructive/40",
+};
+
+export const PromptFileSnapshotsDialog: React.FC<PromptFileSnapshotsDialogProps> = ({
+  open,
+  onOpenChange,
+  engine,
+  sessionId,
+  promptIndex,
+}) => {
+  const { t } = useTranslation();
+  const [entries, setEntries] = useState<PromptFileSnapshotEntry[] | null>(null);
+  const [selected, setSelected] = useState<string | null>(null);
+  const [comparison, setComparison] = useState<FileSnapshotComparison | null>(null);
+  const [loadingDiff, setLoadingDiff] = useState(false);
+  const [error, setError] = useState<string | null>(null);
+
+  useEffect(() => {
+    if (!open) return;
+    setError(null);
+    api
+      .getPromptFileSnapshots(engine, sessionId, promptIndex)
+      .then(setEntries)
+      .catch((err) => setError(String(err)));
+  }, [open, engine, sessionId, promptIndex]);
+
+  const handleSelect = async (entry: PromptFileSnapshotEntry) => {
+    setSelected(entry.id);
+    setComparison(null);
+    if (!entry.hasContent) return;
+    setLoadingDiff(true);
+    setError(null);
+    try {
+      setComparison(await api.diffSnapshotWithCurrent(entry.id));
+    } catch (err) {
+      setError(String(err));
+    } finally {
+      setLoadingDiff(false);
+    }
+  };
+
+  const selectedEntry = entries?.find((entry) => entry.id === selected);
+
+  const renderComparison = () => {
+    if (!selectedEntry) {
+      return <span className="text-muted-foreground">{t('message.fileSnapshots.selectFile')}</span>;
+    }
+    if (!selectedEntry.hasContent) {
+      return <span className="text-muted-foreground">{t('message.fileSnapshots.hashOnly')}</span>;
+    }
+    if (loadingDiff || !comparison) {
+      return <Loader2 className="h-4 w-4 animate-spin text-muted-foreground" />;
+    }
+    if (comparison.status === 'unchanged') {
+      return <span className="text-muted-foreground">{t('message.fileSnapshots.noChanges')}</span>;
+    }
+    if (comparison.currentContent === null && comparison.status !== 'deleted') {
+      return <span className="text-muted-foreground">{t('message.fileSnapshots.currentUnavailable')}</span>;
+    }
+    const parts = Diff.diffLines(comparison.snapshotContent ?? '', comparison.currentContent ?? '');
+    return (
+      <pre className="text-xs font-mono">
+        {parts.map((part, index) => (
+          <span
+            key={index}
+            className={cn(
+              "block whitespace-pre-wrap break-all",
+              part.added && "bg-green-500/10 text-green-700 dark:text-green-400",
+              part.removed && "bg-red-500/10 text-red-700 dark:text-red-400",
+              !part.added && !part.removed && "text-muted-foreground"
+            )}
+          >
+            {part.value
+              .replace(/\n$/, '')
+              .split('\n')
+              .map((line) => `${part.added ? '+' : part.removed ? '-' : ' '} ${line}`)
+              .join('\n')}
+          </span>
+        ))}
+      </pre>
+    );
+  };
+
+  return (
+    <Dialog open={open} onOpenChange={onOpenChange}>
+      <DialogContent className="sm:max-w-4xl">
+        <DialogHeader>
+          <DialogTitle>{t('message.fileSnapshots.title', { index: promptIndex + 1 })}</DialogTitle>
+          <DialogDescription>{t('message.fileSnapshots.description')}</DialogDescription>
+        </DialogHeader>
+
+        {error && <p className="text-sm text-destructive break-words">{error}</p>}
+
+        {entries === null ? (
+          <Loader2 className="h-4 w-4 animate-spin text-muted-foreground" />
+        ) : entries.length === 0 ? (
+          <p className="text-sm text-muted-foreground">{t('message.fileSnapshots.empty')}</p>
+        ) : (
+          <div className="flex gap-3 min-h-[240px]">
+            <div className="w-64 flex-shrink-0 space-y-1 overflow-y-auto max-h-[60vh]">
+              {entries.map((entry) => (
+                <button
+                  key={entry.id}
+                  type="button"
+                  onClick={() => handleSelect(entry)}
+                  className={cn(
+                    "w-full flex items-center gap-2 rounded-md px-2 py-1.5 text-left text-xs hover:bg-accent/50 transition-colors",
+                    selected === entry.id && "bg-accent"
+                  )}
+                >
+                  <FileText className="h-3.5 w-3.5 flex-shrink-0 text-muted-foreground" />
+                  <span className="flex-1 truncate font-mono" title={entry.path}>{entry.path}</span>
+                  <Badge variant="outline" className={cn("font-normal", STATUS_CLASS[entry.status])}>
+                    {t(`message.fileSnapshots.status.${entry.status}`)}
+                  </Badge>
+                </button>
+              ))}
+            </div>
+            <div className="flex-1 min-w-0 rounded-md border border-border px-3 py-2 overflow-auto max-h-[60vh] text-sm">
+              {renderComparison()}
+            </div>
+          </div>
+        )}
+      </DialogContent>
+    </Dialog>
+  );
+};
@@ -0,0 +1,159 @@
+/**
+ * ResponseAlternativesDialog - 回复的多个版本
+ *
+ * 并排显示同一提示词的原回复与重新生成的版本，可继续重新生成或采用某个版本作为主线。
+ * 新版本在 fork 会话中后台生成，生成期间定时刷新
+ */
+
+import React, { useCallback, useEffect, useState } from "react";
+import { Check, Loader2, RefreshCw } from "lucide-react";
+import { Button } from "@/components/ui/button";
+import { Badge } from "@/components/ui/badge";
+import { Dialog, DialogContent, DialogHeader, DialogTitle, DialogDescription } from "@/components/ui/dialog";
+import { useTranslation } from "@/hooks/useTranslation";
+import { useOptionalSession } from "@/contexts/SessionContext";
+import { api, type ResponseVersion } from "@/lib/api";
+
+/** 存在尚未生成完的版本时的刷新间隔 */
+const POLL_INTERVAL_MS = 3000;
+
+interface ResponseAlternativesDialogProps {
+  open: boolean;
+  onOpenChange: (open: boolean) => void;
+  engine: 'claude' | 'codex';
+  sessionId: string;
+  projectPath: string;
+  promptIndex: number;
+}
+
+export const ResponseAlternativesDialog: React.FC<ResponseAlternativesDialogProps> = ({
+  open,
+  onOpenChange,
+  engine,
+  sessionId,
+  projectPath,
+  promptIndex,
+}) => {
+  const { t } = useTranslation();
+  const session = useOptionalSession();
+  const [versions, setVersions] = useState<ResponseVersion[]>([]);
+  // 'regenerate'，或正在采用的版本所在会话 ID
+  const [busy, setBusy] = useState<string | null>(null);
+  const [error, setError] = useState<string | null>(null);
+
+  const load = useCallback(async () => {
+    try {
+      setVersions(await api.getResponseAlternatives(engine, sessionId, promptIndex, projectPath));
+    } catch (err) {
+      setError(String(err));
+    }
+  }, [engine, sessionId, promptIndex, projectPath]);
+
+  useEffect(() => {
+    if (open) {
+      setError(null);
+      load();
+    }
+  }, [open, load]);
+
+  const generating = versions.some((v) => !v.isMainline && v.available && v.messageCount === 0);
+
+  useEffect(() => {
+    if (!open || !generating) return;
+    const timer = setInterval(load, POLL_INTERVAL_MS);
+    return () => clearInterval(timer);
+  }, [open, generating, load]);
+
+  const handleRegenerate = async () => {
+    setBusy('regenerate');
+    setError(null);
+    try {
+      await api.regenerateResponse(engine, sessionId, promptIndex, { projectPath });
+      await load();
+    } catch (err) {
+      setError(String(err));
+    } finally {
+      setBusy(null);
+    }
+  };
+
+  const handleAdopt = async (version: ResponseVersion) => {
+    setBusy(version.sessionId);
+    setError(null);
+    try {
+      await api.adoptAlternative(engine, sessionId, promptIndex, version.sessionId, projectPath);
+      await session?.onReloadSession?.();
+      await load();
+    } catch (err) {
+      setError(String(err));
+    } finally {
+      setBusy(null);
+    }
+  };
+
+  return (
+    <Dialog open={open} onOpenChange={onOpenChange}>
+      <DialogContent className="sm:max-w-5xl">
+        <DialogHeader>
+          <DialogTitle>{t('responseAlternatives.title', { index: promptIndex + 1 })}</DialogTitle>
+          <DialogDescription>{t('responseAlternatives.description')}</DialogDescription>
+        </DialogHeader>
+
+        {error && <p className="text-sm text-destructive break-words">{error}</p>}
+
+        <div className="flex gap-3 overflow-x-auto pb-1">
+          {versions.map((version, index) => (
+            <div
+              key={version.sessionId}
+              className="flex flex-col min-w-[280px] flex-1 rounded-md border border-border"
+            >
+              <div className="flex items-center justify-between gap-2 px-3 py-2 border-b border-border text-xs">
+                <div className="flex items-center gap-1.5 min-w-0">
+                  <span className="font-medium">{t('responseAlternatives.version', { index: index + 1 })}</span>
+                  {version.isMainline && (
+                    <Badge variant="secondary" className="font-normal">{t('responseAlternatives.mainline')}</Badge>
+                  )}
+                </div>
+                {!version.isMainline && (
+                  <Button
+                    size="sm"
+                    variant="outline"
+                    className="h-6 px-2 text-xs"
+                    disabled={busy !== null || !version.available || version.messageCount === 0}
+                    onClick={() => handleAdopt(version)}
+                  >
+                    {busy === version.sessionId
+                      ? <Loader2 className="h-3 w-3 mr-1 animate-spin" />
+                      : <Check className="h-3 w-3 mr-1" />}
+                    {t('responseAlternatives.adopt')}
+                  </Button>
+                )}
+              </div>
+              <div className="px-3 py-2 text-sm whitespace-pre-wrap break-words max-h-[60vh] overflow-y-auto">
+                {!version.available
+                  ? <span className="text-muted-foreground">{t('responseAlternatives.unavailable')}</span>
+                  : version.messageCount === 0
+                  ? (
+                    <span className="flex items-center gap-1.5 text-muted-foreground">
+                      <Loader2 className="h-3.5 w-3.5 animate-spin" />
+                      {t('responseAlternatives.generating')}
+                    </span>
+                  )
+                  : version.replyPreview}
+              </div>
+            </div>
+          ))}
+        </div>
+
+        <div className="flex justify-end">
+          <Button size="sm" onClick={handleRegenerate} disabled={busy !== null}>
+            {busy === 'regenerate'
+              ? <Loader2 className="h-4 w-4 mr-1.5 animate-spin" />
+              : <RefreshCw className="h-4 w-4 mr-1.5" />}
+            {t('responseAlternatives.regenerate')}
+          </Button>
+        </div>
+      </DialogContent>
+    </Dialog>
+  );
+};
@@ -7,19 +7,30 @@
  */
 
 import React, { memo, useState, useMemo, useRef, useEffect } from 'react';
-import { ChevronDown, ChevronRight, Wrench, AlertCircle, CheckCircle, Loader2 } from 'lucide-react';
+import { ChevronDown, ChevronRight, Wrench, AlertCircle, CheckCircle, Loader2, Timer, Archive } from 'lucide-react';
 import { cn } from '@/lib/utils';
 import { toolRegistry } from '@/lib/toolRegistry';
 import { useToolResults } from '@/hooks/useToolResults';
 import { useTranslation } from '@/hooks/useTranslation';
+import { GroundingCitations } from './GroundingCitations';
 import type { ClaudeStreamMessage } from '@/types/claude';
 import type { ToolResultEntry } from '@/contexts/MessagesContext';
+import type { McpCallInfo } from '@/lib/mcpToolCalls';
+import { SLOW_TOOL_THRESHOLD_MS, formatToolDuration, readDurationMs } from '@/lib/toolTiming';
+import { formatBlobSize, loadMessageBlob, parseBlobRef } from '@/lib/sessionBlobs';
+import { useOptionalSession } from '@/contexts/SessionContext';
 
 interface ToolCall {
   id: string;
   type: 'tool_use';
   name: string;
   input?: Record<string, any>;
+  /** 子代理会话文件（仅 Task 工具） */
+  agent_file?: string;
+  /** MCP 调用的解析字段（仅 mcp__ 工具） */
+  mcp?: McpCallInfo;
+  /** 工具耗时（历史加载时由后端按时间戳计算） */
+  duration_ms?: number;
 }
 
 export interface ToolCallsGroupProps {
@@ -244,14 +255,40 @@ interface SingleToolCallProps {
 const SingleToolCallComponent: React.FC<SingleToolCallProps> = ({ tool, result, status, onLinkDetected, index, total }) => {
   const { t } = useTranslation();
   const renderer = toolRegistry.getRenderer(tool.name);
+  const sessionContext = useOptionalSession();
+
+  // 外置存储的工具结果：先显示预览，按需取回全文
+  const rawContent = result?.content;
+  const blobRef = parseBlobRef(rawContent);
+  const [blobContent, setBlobContent] = useState<any>(undefined);
+  const [blobStatus, setBlobStatus] = useState<'idle' | 'loading' | 'error'>('idle');
+
+  const loadBlob = async () => {
+    if (!blobRef || !sessionContext?.sessionId) return;
+    const engine = result?.sourceMessage?.engine ?? sessionContext.session?.engine ?? 'claude';
+    const projectIdOrPath = engine === 'claude' ? sessionContext.projectId : sessionContext.projectPath;
+    setBlobStatus('loading');
+    try {
+      setBlobContent(await loadMessageBlob(blobRef, engine, sessionContext.sessionId, projectIdOrPath ?? ''));
+      setBlobStatus('idle');
+    } catch (error) {
+      console.error('Failed to load tool result blob:', error);
+      setBlobStatus('error');
+    }
+  };
 
   const normalizedResult = result
     ? {
-        content: result.content,
+        content: blobRef ? blobContent ?? blobRef.preview : result.content,
         is_error: result.isError,
       }
     : undefined;
 
+  const citations = result?.sourceMessage?.geminiMetadata?.citations;
+
+  // MCP 工具的耗时由 MCP 组件自行展示
+  const durationMs = tool.mcp ? undefined : readDurationMs(tool) ?? result?.durationMs;
+
   // 判断是否正在流式输出（工具执行中）
   const isStreaming = status === 'pending';
 
@@ -261,6 +298,8 @@ const SingleToolCallComponent: React.FC<SingleToolCallProps> = ({ tool, result,
     input: tool.input,
     result: normalizedResult,
     toolId: tool.id,
+    agentFile: tool.agent_file,
+    mcp: tool.mcp,
     onLinkDetected,
     isStreaming,
   };
@@ -305,6 +344,26 @@ const SingleToolCallComponent: React.FC<SingleToolCallProps> = ({ tool, result,
         </div>
       )}
 
+      {blobRef && blobContent === undefined && (
+        <div className="mb-1 flex items-center gap-2 text-[11px] text-muted-foreground">
+          <Archive className="h-3 w-3" aria-hidden="true" />
+          <span>{t('tools.blobPreview', { size: formatBlobSize(blobRef.size) })}</span>
+          {sessionContext?.sessionId && (
+            <button
+              onClick={loadBlob}
+              disabled={blobStatus === 'loading'}
+              className="text-primary underline underline-offset-2 disabled:opacity-50"
+            >
+              {blobStatus === 'loading'
+                ? t('tools.loadingFullResult')
+                : blobStatus === 'error'
+                  ? t('tools.loadFullResultFailed')
+                  : t('tools.loadFullResult')}
+            </button>
+          )}
+        </div>
+      )}
+
       {/* 使用注册的工具渲染器 */}
       {renderer ? (
         <div className="tool-widget-container">
@@ -313,6 +372,22 @@ const SingleToolCallComponent: React.FC<SingleToolCallProps> = ({ tool, result,
       ) : (
         <FallbackToolRender tool={tool} result={normalizedResult} />
       )}
+
+      {durationMs !== undefined && (
+        <div
+          className={cn(
+            'mt-1 flex items-center gap-1 text-[11px] font-mono',
+            durationMs >= SLOW_TOOL_THRESHOLD_MS ? 'text-amber-600' : 'text-muted-foreground/70'
+          )}
+          title={durationMs >= SLOW_TOOL_THRESHOLD_MS ? t('tools.slowOperation') : undefined}
+        >
+          <Timer className="h-3 w-3" aria-hidden="true" />
+          {t('tools.duration', { duration: formatToolDuration(durationMs) })}
+        </div>
+      )}
+
+      {/* Gemini 联网搜索的引用来源 */}
+      {citations && <GroundingCitations citations={citations} />}
     </div>
   );
 };
@@ -1,8 +1,10 @@
 import React, { useState, useEffect, useRef, useMemo } from "react";
-import { Undo2, AlertTriangle, ChevronDown, ChevronUp, User } from "lucide-react";
+import { Undo2, AlertTriangle, ChevronDown, ChevronUp, User, Layers, FileClock } from "lucide-react";
 import { MessageBubble } from "./MessageBubble";
 import { MessageImagePreview, extractImagesFromContent, extractImagePathsFromText } from "./MessageImagePreview";
 import { MessageActions } from "./MessageActions";
+import { ResponseAlternativesDialog } from "./ResponseAlternativesDialog";
+import { PromptFileSnapshotsDialog } from "./PromptFileSnapshotsDialog";
 import { Button } from "@/components/ui/button";
 import { Tooltip, TooltipContent, TooltipProvider, TooltipTrigger } from "@/components/ui/tooltip";
 import { Dialog, DialogContent, DialogHeader, DialogTitle, DialogDescription, DialogFooter } from "@/components/ui/dialog";
@@ -11,6 +13,7 @@ import { cn } from "@/lib/utils";
 import type { ClaudeStreamMessage } from '@/types/claude';
 import type { RewindCapabilities, RewindMode } from '@/lib/api';
 import { formatTimestamp } from "@/lib/messageUtils";
+import { extractFileReferences } from "@/lib/enhanceOnSend";
 import { api } from '@/lib/api';
 import { useTranslation } from "@/hooks/useTranslation";
 
@@ -274,6 +277,8 @@ export const UserMessage: React.FC<UserMessageProps> = ({
   const [showConfirmDialog, setShowConfirmDialog] = useState(false);
   const [capabilities, setCapabilities] = useState<RewindCapabilities | null>(null);
   const [isLoadingCapabilities, setIsLoadingCapabilities] = useState(false);
+  const [showAlternatives, setShowAlternatives] = useState(false);
+  const [showFileSnapshots, setShowFileSnapshots] = useState(false);
 
   // 🆕 折叠功能相关状态
   const [isExpanded, setIsExpanded] = useState(false);
@@ -373,6 +378,11 @@ export const UserMessage: React.FC<UserMessageProps> = ({
   };
 
   const showRevertButton = promptIndex !== undefined && promptIndex >= 0 && onRevert;
+  // 重新生成回复需要 fork 会话，仅 Claude / Codex 支持
+  const canRegenerate = showRevertButton && (engine === 'claude' || engine === 'codex') && !!sessionId && !!projectPath;
+  // 提示词中有 @ 引用文件时可查看发送时的文件快照
+  const hasFileReferences = extractFileReferences(text).length > 0;
+  const canShowFileSnapshots = showRevertButton && !!sessionId && hasFileReferences;
   const hasWarning = capabilities && !capabilities.code;
 
   return (
@@ -424,7 +434,7 @@ export const UserMessage: React.FC<UserMessageProps> = ({
                   {displayContent}
                   {/* 占位符，确保文字不遮挡绝对定位的按钮 */}
                   {showRevertButton && !isSkills && !isCommandOutput && !isSlashCommand && (
-                    <span className="inline-block w-8 h-4 align-middle select-none" aria-hidden="true" />
+                    <span className={cn("inline-block h-4 align-middle select-none", canRegenerate ? "w-14" : "w-8")} aria-hidden="true" />
                   )}
                 </div>
 
@@ -472,6 +482,54 @@ export const UserMessage: React.FC<UserMessageProps> = ({
                 </TooltipProvider>
               )}
 
+              {/* 回复版本按钮 */}
+              {canRegenerate && (
+                <TooltipProvider>
+                  <Tooltip>
+                    <TooltipTrigger asChild>
+                      <Button
+                        variant="ghost"
+                        size="sm"
+                        className="h-6 w-6 p-0 rounded-md text-muted-foreground/40 hover:text-foreground hover:bg-black/5 dark:hover:bg-white/10 transition-all"
+                        onClick={(e) => {
+                          e.stopPropagation();
+                          setShowAlternatives(true);
+                        }}
+                      >
+                        <Layers className="h-4 w-4" />
+                      </Button>
+                    </TooltipTrigger>
+                    <TooltipContent side="top">
+                      {t('message.responseVersions')}
+                    </TooltipContent>
+                  </Tooltip>
+                </TooltipProvider>
+              )}
+
+              {/* 引用文件快照按钮 */}
+              {canShowFileSnapshots && (
+                <TooltipProvider>
+                  <Tooltip>
+                    <TooltipTrigger asChild>
+                      <Button
+                        variant="ghost"
+                        size="sm"
+                        className="h-6 w-6 p-0 rounded-md text-muted-foreground/40 hover:text-foreground hover:bg-black/5 dark:hover:bg-white/10 transition-all"
+                        onClick={(e) => {
+                          e.stopPropagation();
+                          setShowFileSnapshots(true);
+                        }}
+                      >
+                        <FileClock className="h-4 w-4" />
+                      </Button>
+                    </TooltipTrigger>
+                    <TooltipContent side="top">
+                      {t('message.referencedFiles')}
+                    </TooltipContent>
+                  </Tooltip>
+                </TooltipProvider>
+              )}
+
               {/* 撤回按钮 */}
               <TooltipProvider>
                 <Tooltip>
@@ -524,6 +582,29 @@ export const UserMessage: React.FC<UserMessageProps> = ({
       </div>
     </div>
 
+      {/* 回复版本对话框 */}
+      {showAlternatives && canRegenerate && (
+        <ResponseAlternativesDialog
+          open={showAlternatives}
+          onOpenChange={setShowAlternatives}
+          engine={engine}
+          sessionId={sessionId!}
+          projectPath={projectPath!}
+          promptIndex={promptIndex!}
+        />
+      )}
+
+      {/* 引用文件快照对话框 */}
+      {showFileSnapshots && canShowFileSnapshots && (
+        <PromptFileSnapshotsDialog
+          open={showFileSnapshots}
+          onOpenChange={setShowFileSnapshots}
+          engine={engine}
+          sessionId={sessionId!}
+          promptIndex={promptIndex!}
+        />
+      )}
+
       {/* 撤回确认对话框 - 三模式选择 */}
       {showConfirmDialog && (
         <Dialog open={showConfirmDialog} onOpenChange={setShowConfirmDialog}>
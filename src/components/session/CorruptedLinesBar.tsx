@@ -0,0 +1,65 @@
+/**
+ * CorruptedLinesBar - 会话损坏行提示条
+ *
+ * Claude 会话文件中个别行损坏（写到一半、非法 UTF-8）时，加载会跳过这些行；
+ * 这里提示被跳过的行号，并提供修复（去除损坏行，原文件自动备份）
+ */
+
+import { useState } from "react";
+import { AlertTriangle, Loader2, Wrench } from "lucide-react";
+import { Button } from "@/components/ui/button";
+import { api } from "@/lib/api";
+import type { CorruptedLine } from "@/lib/api";
+
+export interface CorruptedLinesBarProps {
+  corruptedLines: CorruptedLine[];
+  sessionId: string;
+  projectId: string;
+  /** 修复完成后重新加载会话 */
+  onRepaired: () => void;
+}
+
+const describe = (line: CorruptedLine): string =>
+  `第 ${line.line} 行（${line.kind === "invalid_utf8" ? "UTF-8 错误" : "JSON 语法错误"}）`;
+
+export function CorruptedLinesBar({ corruptedLines, sessionId, projectId, onRepaired }: CorruptedLinesBarProps) {
+  const [repairing, setRepairing] = useState(false);
+  const [message, setMessage] = useState<string | null>(null);
+
+  const skipped = corruptedLines.filter((line) => !line.recovered);
+  if (skipped.length === 0 && !message) return null;
+
+  const handleRepair = async () => {
+    setRepairing(true);
+    try {
+      const report = await api.repairClaudeSession(sessionId, projectId);
+      setMessage(report.backup_path ? `已修复，原文件备份在 ${report.backup_path}` : "会话文件无需修复");
+      onRepaired();
+    } catch (error) {
+      setMessage(`修复失败：${error}`);
+    } finally {
+      setRepairing(false);
+    }
+  };
+
+  return (
+    <div className="flex items-center gap-2 px-4 py-1.5 border-b text-xs text-amber-600 dark:text-amber-400">
+      <AlertTriangle className="h-3.5 w-3.5 shrink-0" />
+      <span className="truncate" title={skipped.map(describe).join("\n")}>
+        {message ?? `本会话有 ${skipped.length} 行无法解析，已跳过：${skipped.slice(0, 5).map(describe).join("、")}${skipped.length > 5 ? " 等" : ""}`}
+      </span>
+      {!message && (
+        <Button
+          variant="ghost"
+          size="sm"
+          className="h-6 px-2 text-xs shrink-0 ml-auto"
+          onClick={handleRepair}
+          disabled={repairing}
+        >
+          {repairing ? <Loader2 className="h-3 w-3 mr-1 animate-spin" /> : <Wrench className="h-3 w-3 mr-1" />}
+          修复
+        </Button>
+      )}
+    </div>
+  );
+}
@@ -0,0 +1,73 @@
+/**
+ * McpCallSummaryBar - MCP 调用汇总条
+ *
+ * 按 server / 工具显示会话中 MCP 调用的次数、平均耗时与失败次数，
+ * 每次执行结束后重新统计
+ */
+
+import { useCallback, useEffect, useState } from "react";
+import { Package2 } from "lucide-react";
+import { Badge } from "@/components/ui/badge";
+import { api } from "@/lib/api";
+import type { McpCallStats, McpSessionSummary } from "@/lib/api";
+import { cn } from "@/lib/utils";
+
+export interface McpCallSummaryBarProps {
+  engine: McpSessionSummary["engine"];
+  sessionId: string;
+  /** Claude 会话的项目目录名，Codex 会话不需要 */
+  projectId: string;
+  /** 是否正在执行（执行结束后刷新统计） */
+  isRunning: boolean;
+}
+
+const describe = (stats: McpCallStats): string => {
+  const parts = [`${stats.server} / ${stats.tool}`, `${stats.callCount} 次`];
+  if (stats.avgDurationMs !== null) {
+    parts.push(`平均 ${Math.round(stats.avgDurationMs)}ms`);
+  }
+  if (stats.failedCount > 0) {
+    parts.push(`${stats.failedCount} 次失败`);
+  }
+  return parts.join(" · ");
+};
+
+export function McpCallSummaryBar({ engine, sessionId, projectId, isRunning }: McpCallSummaryBarProps) {
+  const [summary, setSummary] = useState<McpSessionSummary | null>(null);
+
+  const load = useCallback(async () => {
+    try {
+      setSummary(await api.getSessionMcpSummary(sessionId, projectId, engine));
+    } catch {
+      setSummary(null);
+    }
+  }, [engine, sessionId, projectId]);
+
+  useEffect(() => {
+    if (!isRunning) {
+      load();
+    }
+  }, [load, isRunning]);
+
+  if (!summary || summary.totalCalls === 0) return null;
+
+  return (
+    <div className="flex items-center gap-2 px-4 py-1.5 border-b text-xs text-muted-foreground overflow-x-auto">
+      <Package2 className="h-3.5 w-3.5 shrink-0" />
+      <span className="shrink-0">
+        MCP 调用 {summary.totalCalls} 次
+        {summary.failedCalls > 0 && `，失败 ${summary.failedCalls} 次`}
+      </span>
+      {summary.tools.map((stats) => (
+        <Badge
+          key={`${stats.server}:${stats.tool}`}
+          variant="outline"
+          className={cn("shrink-0 font-normal", stats.failedCount > 0 && "border-red-500/40")}
+          title={`失败率 ${(stats.failureRate * 100).toFixed(1)}%`}
+        >
+          {describe(stats)}
+        </Badge>
+      ))}
+    </div>
+  );
+}
@@ -0,0 +1,80 @@
+/**
+ * RelatedSessionsBar - 关联会话条
+ *
+ * 显示会话标注中的 related_sessions（如跨引擎评审中互相关联的会话），
+ * 新的评审会话完成关联后自动刷新
+ */
+
+import { useCallback, useEffect, useState } from "react";
+import { Link2 } from "lucide-react";
+import { listen, type UnlistenFn } from "@tauri-apps/api/event";
+import { Badge } from "@/components/ui/badge";
+import { api } from "@/lib/api";
+import type { RelatedSession } from "@/lib/api";
+
+const ENGINE_LABELS: Record<RelatedSession["engine"], string> = {
+  claude: "Claude",
+  codex: "Codex",
+  gemini: "Gemini",
+};
+
+export interface RelatedSessionsBarProps {
+  engine: RelatedSession["engine"];
+  sessionId: string;
+}
+
+const describe = (related: RelatedSession): string => {
+  const target = `${ENGINE_LABELS[related.engine]} 会话 ${related.sessionId.slice(0, 8)}`;
+  return related.relation === "review_of" ? `评审了 ${target}` : `被 ${target} 评审`;
+};
+
+export function RelatedSessionsBar({ engine, sessionId }: RelatedSessionsBarProps) {
+  const [related, setRelated] = useState<RelatedSession[]>([]);
+
+  const load = useCallback(async () => {
+    try {
+      const annotation = await api.getSessionAnnotation(engine, sessionId);
+      setRelated(annotation.relatedSessions);
+    } catch {
+      setRelated([]);
+    }
+  }, [engine, sessionId]);
+
+  useEffect(() => {
+    let unlisten: UnlistenFn | null = null;
+    let disposed = false;
+    load();
+    listen("cross-engine-review-linked", () => {
+      load();
+    }).then((fn) => {
+      if (disposed) {
+        fn();
+      } else {
+        unlisten = fn;
+      }
+    });
+    return () => {
+      disposed = true;
+      unlisten?.();
+    };
+  }, [load]);
+
+  if (related.length === 0) return null;
+
+  return (
+    <div className="flex items-center gap-2 px-4 py-1.5 border-b text-xs text-muted-foreground overflow-x-auto">
+      <Link2 className="h-3.5 w-3.5 shrink-0" />
+      <span className="shrink-0">关联会话</span>
+      {related.map((item) => (
+        <Badge
+          key={`${item.engine}:${item.sessionId}:${item.relation}`}
+          variant="outline"
+          className="shrink-0 font-normal"
+          title={`${item.sessionId}\n${item.projectPath}`}
+        >
+          {describe(item)}
+        </Badge>
+      ))}
+    </div>
+  );
+}
@@ -0,0 +1,63 @@
+/**
+ * SlowOperationsBar - 慢操作汇总条
+ *
+ * 列出会话中耗时超过阈值的工具调用（工具名、参数摘要、耗时），
+ * 每次执行结束后重新统计
+ */
+
+import { useCallback, useEffect, useState } from "react";
+import { Timer } from "lucide-react";
+import { Badge } from "@/components/ui/badge";
+import { api } from "@/lib/api";
+import type { ToolCallTiming } from "@/lib/api";
+import { SLOW_TOOL_THRESHOLD_MS, formatToolDuration } from "@/lib/toolTiming";
+
+/** 最多展示的慢操作数量 */
+const MAX_VISIBLE = 5;
+
+export interface SlowOperationsBarProps {
+  engine: "claude" | "codex" | "gemini";
+  sessionId: string;
+  /** Claude 为项目目录名，Gemini 为项目路径，Codex 不需要 */
+  projectId: string;
+  /** 是否正在执行（执行结束后刷新统计） */
+  isRunning: boolean;
+}
+
+export function SlowOperationsBar({ engine, sessionId, projectId, isRunning }: SlowOperationsBarProps) {
+  const [operations, setOperations] = useState<ToolCallTiming[]>([]);
+
+  const load = useCallback(async () => {
+    try {
+      setOperations(await api.getSessionSlowOperations(engine, sessionId, projectId, SLOW_TOOL_THRESHOLD_MS));
+    } catch {
+      setOperations([]);
+    }
+  }, [engine, sessionId, projectId]);
+
+  useEffect(() => {
+    if (!isRunning) {
+      load();
+    }
+  }, [load, isRunning]);
+
+  if (operations.length === 0) return null;
+
+  return (
+    <div className="flex items-center gap-2 px-4 py-1.5 border-b text-xs text-muted-foreground overflow-x-auto">
+      <Timer className="h-3.5 w-3.5 shrink-0 text-amber-600" />
+      <span className="shrink-0">慢操作 {operations.length} 个</span>
+      {operations.slice(0, MAX_VISIBLE).map((op) => (
+        <Badge
+          key={op.callId}
+          variant="outline"
+          className="shrink-0 font-normal border-amber-500/40 max-w-[320px] truncate"
+          title={op.inputSummary}
+        >
+          {op.toolName} · {formatToolDuration(op.durationMs ?? 0)}
+          {op.inputSummary && ` · ${op.inputSummary}`}
+        </Badge>
+      ))}
+    </div>
+  );
+}
@@ -0,0 +1,276 @@
+import React, { useEffect, useState } from "react";
+import { listen } from "@tauri-apps/api/event";
+import { open } from "@tauri-apps/plugin-dialog";
+import { Archive, FolderOpen, Loader2, RotateCcw } from "lucide-react";
+import { Button } from "@/components/ui/button";
+import { Checkbox } from "@/components/ui/checkbox";
+import { Input } from "@/components/ui/input";
+import { Label } from "@/components/ui/label";
+import { SelectComponent } from "@/components/ui/select";
+import { Switch } from "@/components/ui/switch";
+import { useTranslation } from "@/hooks/useTranslation";
+import {
+  api,
+  type BackupCategory,
+  type BackupConfig,
+  type BackupFinished,
+  type BackupInfo,
+  type BackupProgress,
+} from "@/lib/api";
+
+const CATEGORIES: BackupCategory[] = ["sessions", "gitRecords", "appData", "config"];
+
+interface BackupSettingsProps {
+  setToast: (toast: { message: string; type: 'success' | 'error' } | null) => void;
+}
+
+const formatSize = (bytes: number) =>
+  bytes >= 1024 * 1024 ? `${(bytes / 1024 / 1024).toFixed(1)} MB` : `${Math.ceil(bytes / 1024)} KB`;
+
+/**
+ * 定时备份设置：目标目录、频率、范围与保留份数，可立即备份，并从某份备份按分类还原
+ */
+export const BackupSettings: React.FC<BackupSettingsProps> = ({ setToast }) => {
+  const { t } = useTranslation();
+  const [config, setConfig] = useState<BackupConfig | null>(null);
+  const [backups, setBackups] = useState<BackupInfo[]>([]);
+  const [progress, setProgress] = useState<BackupProgress | null>(null);
+  const [busy, setBusy] = useState(false);
+  const [restoreId, setRestoreId] = useState<string>("");
+  const [restoreCategories, setRestoreCategories] = useState<BackupCategory[]>(["sessions"]);
+  const [restoreOverwrite, setRestoreOverwrite] = useState(false);
+
+  const loadBackups = () => {
+    api.listBackups()
+      .then((list) => {
+        setBackups(list);
+        setRestoreId((current) => current || list[0]?.id || "");
+      })
+      .catch(() => setBackups([]));
+  };
+
+  useEffect(() => {
+    api.getBackupConfig()
+      .then(setConfig)
+      .catch((error) => console.warn("Failed to load backup config:", error));
+    loadBackups();
+
+    const unlistenProgress = listen<BackupProgress>("backup-progress", (event) => setProgress(event.payload));
+    // 定时备份也会触发，完成后刷新列表
+    const unlistenFinished = listen<BackupFinished>("backup-finished", () => {
+      setProgress(null);
+      loadBackups();
+    });
+    return () => {
+      unlistenProgress.then((fn) => fn());
+      unlistenFinished.then((fn) => fn());
+    };
+  }, []);
+
+  const handleChange = async (next: BackupConfig) => {
+    const previous = config;
+    setConfig(next);
+    try {
+      await api.updateBackupConfig(next);
+      if (next.targetDir !== previous?.targetDir) loadBackups();
+    } catch (error) {
+      setConfig(previous);
+      setToast({ message: String(error), type: "error" });
+    }
+  };
+
+  const handlePickTarget = async () => {
+    if (!config) return;
+    const selected = await open({ directory: true, multiple: false, title: t('backup.targetDir') });
+    if (typeof selected === "string") handleChange({ ...config, targetDir: selected });
+  };
+
+  const toggle = (list: BackupCategory[], category: BackupCategory, checked: boolean) =>
+    checked
+      ? CATEGORIES.filter((c) => c === category || list.includes(c))
+      : list.filter((c) => c !== category);
+
+  const handleBackupNow = async () => {
+    setBusy(true);
+    try {
+      const result = await api.backupNow();
+      setToast({
+        message: t('backup.done', {
+          files: result.backup.fileCount,
+          copied: result.copiedFiles,
+          errors: result.errors.length,
+        }),
+        type: result.errors.length === 0 ? "success" : "error",
+      });
+    } catch (error) {
+      setToast({ message: String(error), type: "error" });
+    } finally {
+      setBusy(false);
+    }
+  };
+
+  const handleRestore = async () => {
+    if (!restoreId || !window.confirm(t('backup.restoreConfirm', { id: restoreId }))) return;
+    setBusy(true);
+    try {
+      const result = await api.restoreFromBackup(restoreId, restoreCategories, restoreOverwrite);
+      setToast({
+        message: t('backup.restored', {
+          count: result.restoredFiles,
+          skipped: result.skippedExisting,
+          moved: result.movedToSafety,
+          errors: result.errors.length,
+        }),
+        type: result.errors.length === 0 ? "success" : "error",
+      });
+    } catch (error) {
+      setToast({ message: String(error), type: "error" });
+    } finally {
+      setBusy(false);
+    }
+  };
+
+  if (!config) return null;
+
+  const hasTarget = !!config.targetDir?.trim();
+
+  return (
+    <div className="space-y-4">
+      <div className="flex items-center justify-between">
+        <div className="space-y-0.5 flex-1">
+          <Label htmlFor="scheduledBackup" className="flex items-center gap-2">
+            <Archive className="h-4 w-4" aria-hidden="true" />
+            {t('backup.title')}
+          </Label>
+          <p className="text-xs text-muted-foreground">{t('backup.description')}</p>
+        </div>
+        <Switch
+          id="scheduledBackup"
+          checked={config.enabled}
+          disabled={!hasTarget}
+          onCheckedChange={(enabled) => handleChange({ ...config, enabled })}
+        />
+      </div>
+
+      <div className="space-y-2">
+        <Label htmlFor="backupTargetDir">{t('backup.targetDir')}</Label>
+        <div className="flex gap-2">
+          <Input
+            id="backupTargetDir"
+            value={config.targetDir ?? ""}
+            placeholder={t('backup.targetDirPlaceholder')}
+            onChange={(e) => setConfig({ ...config, targetDir: e.target.value })}
+            onBlur={(e) => handleChange({ ...config, targetDir: e.target.value.trim() || null })}
+          />
+          <Button size="sm" variant="outline" onClick={handlePickTarget} aria-label={t('backup.targetDir')}>
+            <FolderOpen className="h-4 w-4" />
+          </Button>
+        </div>
+      </div>
+
+      <div className="grid grid-cols-2 gap-4">
+        <div className="space-y-1">
+          <Label>{t('backup.frequency')}</Label>
+          <SelectComponent
+            value={config.frequency}
+            onValueChange={(value) => handleChange({ ...config, frequency: value as BackupConfig['frequency'] })}
+            options={[
+              { value: "daily", label: t('backup.daily') },
+              { value: "weekly", label: t('backup.weekly') },
+            ]}
+          />
+        </div>
+        <div className="space-y-1">
+          <Label htmlFor="backupKeepCount">{t('backup.keepCount')}</Label>
+          <Input
+            id="backupKeepCount"
+            type="number"
+            min="1"
+            max="365"
+            value={config.keepCount}
+            onChange={(e) => handleChange({ ...config, keepCount: Math.max(1, parseInt(e.target.value) || 1) })}
+          />
+        </div>
+      </div>
+
+      <div className="space-y-2">
+        <Label>{t('backup.categories')}</Label>
+        <div className="flex flex-wrap gap-4">
+          {CATEGORIES.map((category) => (
+            <label key={category} className="flex items-center gap-2 text-sm cursor-pointer">
+              <Checkbox
+                checked={config.categories.includes(category)}
+                onCheckedChange={(checked) =>
+                  handleChange({ ...config, categories: toggle(config.categories, category, checked === true) })
+                }
+              />
+              {t(`backup.category.${category}`)}
+            </label>
+          ))}
+        </div>
+      </div>
+
+      <div className="flex items-center justify-between gap-2 text-xs text-muted-foreground">
+        <span>
+          {progress
+            ? t('backup.progress', { processed: progress.processed, total: progress.total })
+            : backups[0]
+              ? t('backup.lastBackup', {
+                time: new Date(backups[0].createdAt * 1000).toLocaleString(),
+                size: formatSize(backups[0].totalBytes),
+              })
+              : t('backup.noBackups')}
+        </span>
+        <Button size="sm" variant="outline" disabled={busy || !hasTarget} onClick={handleBackupNow}>
+          {busy && progress ? <Loader2 className="h-4 w-4 mr-2 animate-spin" /> : null}
+          {t('backup.backupNow')}
+        </Button>
+      </div>
+
+      {backups.length > 0 && (
+        <div className="space-y-2">
+          <Label>{t('backup.restore')}</Label>
+          <div className="flex flex-wrap items-center gap-4">
+            <SelectComponent
+              value={restoreId}
+              onValueChange={setRestoreId}
+              options={backups.map((backup) => ({
+                value: backup.id,
+                label: `${backup.id} (${backup.fileCount})`,
+              }))}
+              className="w-56"
+            />
+            {CATEGORIES.map((category) => (
+              <label key={category} className="flex items-center gap-2 text-sm cursor-pointer">
+                <Checkbox
+                  checked={restoreCategories.includes(category)}
+                  onCheckedChange={(checked) =>
+                    setRestoreCategories(toggle(restoreCategories, category, checked === true))
+                  }
+                />
+                {t(`backup.category.${category}`)}
+              </label>
+            ))}
+            <label className="flex items-center gap-2 text-sm cursor-pointer">
+              <Checkbox
+                checked={restoreOverwrite}
+                onCheckedChange={(checked) => setRestoreOverwrite(checked === true)}
+              />
+              {t('backup.restoreOverwrite')}
+            </label>
+            <Button
+              size="sm"
+              variant="outline"
+              disabled={busy || !restoreId || restoreCategories.length === 0}
+              onClick={handleRestore}
+            >
+              <RotateCcw className="h-4 w-4 mr-2" />
+              {t('backup.restoreButton')}
+            </Button>
+          </div>
+          <p className="text-xs text-muted-foreground">{t('backup.restoreHint')}</p>
+        </div>
+      )}
+    </div>
+  );
+};
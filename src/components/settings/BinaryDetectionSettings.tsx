@@ -0,0 +1,184 @@
+import React, { useEffect, useState } from "react";
+import { ArrowDown, ArrowUp, Ban, Loader2, RefreshCw, Save } from "lucide-react";
+import { Button } from "@/components/ui/button";
+import { Badge } from "@/components/ui/badge";
+import { Label } from "@/components/ui/label";
+import { Textarea } from "@/components/ui/textarea";
+import { cn } from "@/lib/utils";
+import { useTranslation } from "@/hooks/useTranslation";
+import { api, type BinaryDetection, type BinaryOrigin, type BinaryTool } from "@/lib/api";
+
+const TOOLS: BinaryTool[] = ["claude", "codex", "gemini"];
+const ORIGINS: BinaryOrigin[] = ["custom", "path", "nvm", "volta", "bun", "homebrew"];
+
+interface BinaryDetectionSettingsProps {
+  setToast: (toast: { message: string; type: 'success' | 'error' } | null) => void;
+}
+
+/**
+ * 二进制检测设置：查看各来源检测到的候选，调整来源优先顺序与黑名单路径
+ */
+export const BinaryDetectionSettings: React.FC<BinaryDetectionSettingsProps> = ({ setToast }) => {
+  const { t } = useTranslation();
+  const [tool, setTool] = useState<BinaryTool>("claude");
+  const [sourcePriority, setSourcePriority] = useState<BinaryOrigin[]>([]);
+  const [blockedText, setBlockedText] = useState("");
+  const [detection, setDetection] = useState<BinaryDetection | null>(null);
+  const [busy, setBusy] = useState<"detect" | "save" | null>(null);
+
+  const detect = async (refresh: boolean) => {
+    setBusy("detect");
+    try {
+      setDetection(await api.listBinaryCandidates(tool, refresh));
+    } catch (error) {
+      setToast({ message: String(error), type: "error" });
+    } finally {
+      setBusy(null);
+    }
+  };
+
+  useEffect(() => {
+    setDetection(null);
+    api.getBinaryDetectionSettings(tool)
+      .then((settings) => {
+        setSourcePriority(settings.sourcePriority);
+        setBlockedText(settings.blockedPaths.join("\n"));
+      })
+      .catch((error) => console.warn("Failed to load binary detection settings:", error));
+    detect(false);
+    // eslint-disable-next-line react-hooks/exhaustive-deps
+  }, [tool]);
+
+  // 展示顺序：已设置的优先顺序在前，其余按默认顺序
+  const orderedOrigins = [...sourcePriority, ...ORIGINS.filter((o) => !sourcePriority.includes(o))];
+
+  const moveOrigin = (index: number, delta: number) => {
+    const next = [...orderedOrigins];
+    const target = index + delta;
+    if (target < 0 || target >= next.length) return;
+    [next[index], next[target]] = [next[target], next[index]];
+    setSourcePriority(next);
+  };
+
+  const blockPath = (path: string) => {
+    const lines = blockedText.split("\n").map((line) => line.trim()).filter(Boolean);
+    if (!lines.includes(path)) {
+      setBlockedText([...lines, path].join("\n"));
+    }
+  };
+
+  const handleSave = async () => {
+    setBusy("save");
+    try {
+      const saved = await api.saveBinaryDetectionSettings(tool, {
+        sourcePriority,
+        blockedPaths: blockedText.split("\n").map((line) => line.trim()).filter(Boolean),
+      });
+      setSourcePriority(saved.sourcePriority);
+      setBlockedText(saved.blockedPaths.join("\n"));
+      setToast({ message: t('binaryDetection.saved'), type: "success" });
+      setDetection(await api.listBinaryCandidates(tool, true));
+    } catch (error) {
+      setToast({ message: String(error), type: "error" });
+    } finally {
+      setBusy(null);
+    }
+  };
+
+  return (
+    <div className="space-y-4">
+      <div className="flex items-center justify-between">
+        <div>
+          <Label className="text-sm font-medium">{t('binaryDetection.title')}</Label>
+          <p className="text-xs text-muted-foreground">{t('binaryDetection.description')}</p>
+        </div>
+        <div className="flex gap-1">
+          {TOOLS.map((item) => (
+            <Button
+              key={item}
+              variant={tool === item ? "default" : "outline"}
+              size="sm"
+              onClick={() => setTool(item)}
+              disabled={busy !== null}
+            >
+              {item}
+            </Button>
+          ))}
+        </div>
+      </div>
+
+      <div className="space-y-2">
+        <div className="flex items-center justify-between">
+          <Label className="text-xs">{t('binaryDetection.candidates')}</Label>
+          <Button variant="ghost" size="sm" onClick={() => detect(true)} disabled={busy !== null}>
+            {busy === "detect" ? <Loader2 className="h-3 w-3 mr-1 animate-spin" /> : <RefreshCw className="h-3 w-3 mr-1" />}
+            {t('binaryDetection.redetect')}
+          </Button>
+        </div>
+        {detection && detection.candidates.length === 0 && (
+          <p className="text-xs text-muted-foreground">{t('binaryDetection.noCandidates')}</p>
+        )}
+        {detection?.candidates.map((candidate, index) => (
+          <div
+            key={candidate.path}
+            className={cn(
+              "flex items-center gap-2 rounded-md border px-3 py-2 text-xs",
+              index === 0 && "border-primary bg-primary/5"
+            )}
+          >
+            <Badge variant="outline">{candidate.origin}</Badge>
+            <span className="font-mono break-all flex-1">{candidate.path}</span>
+            <span className="text-muted-foreground shrink-0">{candidate.version ?? t('binaryDetection.unknownVersion')}</span>
+            {index === 0 ? (
+              <Badge>{t('binaryDetection.inUse')}</Badge>
+            ) : (
+              <Button
+                variant="ghost"
+                size="sm"
+                className="h-6 px-2"
+                title={t('binaryDetection.block')}
+                onClick={() => blockPath(candidate.path)}
+              >
+                <Ban className="h-3 w-3" />
+              </Button>
+            )}
+          </div>
+        ))}
+      </div>
+
+      <div className="space-y-2">
+        <Label className="text-xs">{t('binaryDetection.sourcePriority')}</Label>
+        <div className="flex flex-wrap gap-2">
+          {orderedOrigins.map((origin, index) => (
+            <div key={origin} className="flex items-center gap-1 rounded-md border px-2 py-1 text-xs">
+              <span className="text-muted-foreground">{index + 1}.</span>
+              <span>{origin}</span>
+              <button type="button" onClick={() => moveOrigin(index, -1)} disabled={index === 0}>
+                <ArrowUp className="h-3 w-3" />
+              </button>
+              <button type="button" onClick={() => moveOrigin(index, 1)} disabled={index === orderedOrigins.length - 1}>
+                <ArrowDown className="h-3 w-3" />
+              </button>
+            </div>
+          ))}
+        </div>
+      </div>
+
+      <div className="space-y-2">
+        <Label className="text-xs">{t('binaryDetection.blockedPaths')}</Label>
+        <Textarea
+          value={blockedText}
+          onChange={(e) => setBlockedText(e.target.value)}
+          placeholder={t('binaryDetection.blockedPathsPlaceholder')}
+          rows={3}
+          className="font-mono text-xs"
+        />
+      </div>
+
+      <Button size="sm" onClick={handleSave} disabled={busy !== null}>
+        {busy === "save" ? <Loader2 className="h-4 w-4 mr-1 animate-spin" /> : <Save className="h-4 w-4 mr-1" />}
+        {t('binaryDetection.save')}
+      </Button>
+    </div>
+  );
+};
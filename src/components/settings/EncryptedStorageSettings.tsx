@@ -0,0 +1,132 @@
+import React, { useEffect, useState } from "react";
+import { Lock, LockOpen } from "lucide-react";
+import { Button } from "@/components/ui/button";
+import { Input } from "@/components/ui/input";
+import { Label } from "@/components/ui/label";
+import { Switch } from "@/components/ui/switch";
+import { useTranslation } from "@/hooks/useTranslation";
+import { api, type EncryptedStorageStatus } from "@/lib/api";
+
+interface EncryptedStorageSettingsProps {
+  setToast: (toast: { message: string; type: 'success' | 'error' } | null) => void;
+}
+
+/**
+ * 回收站/归档加密设置：开启时设置口令，之后每次启动应用需解锁一次，可加密已有文件
+ */
+export const EncryptedStorageSettings: React.FC<EncryptedStorageSettingsProps> = ({ setToast }) => {
+  const { t } = useTranslation();
+  const [status, setStatus] = useState<EncryptedStorageStatus | null>(null);
+  const [passphrase, setPassphrase] = useState("");
+  const [busy, setBusy] = useState(false);
+
+  useEffect(() => {
+    api.getEncryptedStorageStatus()
+      .then(setStatus)
+      .catch((error) => console.warn("Failed to load encrypted storage status:", error));
+  }, []);
+
+  const run = async (action: () => Promise<void>) => {
+    setBusy(true);
+    try {
+      await action();
+    } catch (error) {
+      setToast({ message: String(error), type: "error" });
+    } finally {
+      setBusy(false);
+    }
+  };
+
+  const handleEnable = () => run(async () => {
+    setStatus(await api.enableEncryptedStorage(passphrase));
+    setPassphrase("");
+    setToast({ message: t('encryptedStorage.enabled'), type: "success" });
+  });
+
+  const handleUnlock = () => run(async () => {
+    setStatus(await api.unlockEncryptedStorage(passphrase));
+    setPassphrase("");
+    setToast({ message: t('encryptedStorage.unlocked'), type: "success" });
+  });
+
+  const handleDisable = () => run(async () => {
+    setStatus(await api.disableEncryptedStorage());
+    setToast({ message: t('encryptedStorage.disabled'), type: "success" });
+  });
+
+  const handleMigrate = () => run(async () => {
+    const report = await api.migrateExistingArchivesToEncrypted();
+    setToast(report.errors.length === 0
+      ? { message: t('encryptedStorage.migrated', { count: report.encryptedFiles }), type: "success" }
+      : {
+        message: t('encryptedStorage.migrateErrors', { count: report.encryptedFiles, errors: report.errors.length }),
+        type: "error",
+      });
+  });
+
+  if (!status) return null;
+
+  // 开启需要口令；已加密的文件与关闭加密都需要先解锁
+  const needsPassphrase = !status.unlocked && (status.enabled || status.configured);
+
+  return (
+    <div className="space-y-4">
+      <div className="flex items-center justify-between">
+        <div className="space-y-0.5 flex-1">
+          <Label htmlFor="encryptedStorage" className="flex items-center gap-2">
+            {status.unlocked
+              ? <LockOpen className="h-4 w-4" aria-hidden="true" />
+              : <Lock className="h-4 w-4" aria-hidden="true" />}
+            {t('encryptedStorage.title')}
+          </Label>
+          <p className="text-xs text-muted-foreground">{t('encryptedStorage.description')}</p>
+        </div>
+        <Switch
+          id="encryptedStorage"
+          checked={status.enabled}
+          disabled={busy || (status.enabled ? !status.unlocked : !passphrase)}
+          onCheckedChange={(enabled) => (enabled ? handleEnable() : handleDisable())}
+        />
+      </div>
+
+      {(!status.enabled || needsPassphrase) && (
+        <div className="space-y-2">
+          <Label htmlFor="encryptedStoragePassphrase">{t('encryptedStorage.passphrase')}</Label>
+          <div className="flex gap-2">
+            <Input
+              id="encryptedStoragePassphrase"
+              type="password"
+              value={passphrase}
+              placeholder={t('encryptedStorage.passphrasePlaceholder')}
+              onChange={(e) => setPassphrase(e.target.value)}
+            />
+            {needsPassphrase && (
+              <Button size="sm" disabled={busy || !passphrase} onClick={handleUnlock}>
+                {t('encryptedStorage.unlock')}
+              </Button>
+            )}
+            {!status.enabled && (
+              <Button size="sm" variant="outline" disabled={busy || !passphrase} onClick={handleEnable}>
+                {t('encryptedStorage.enable')}
+              </Button>
+            )}
+          </div>
+          {!status.configured && (
+            <p className="text-xs text-muted-foreground">{t('encryptedStorage.forgetWarning')}</p>
+          )}
+        </div>
+      )}
+
+      {status.enabled && (
+        <div className="flex items-center justify-between gap-2 text-xs text-muted-foreground">
+          <span>{status.unlocked ? t('encryptedStorage.unlockedHint') : t('encryptedStorage.locked')}</span>
+          {status.unlocked && (
+            <Button size="sm" variant="outline" disabled={busy} onClick={handleMigrate}>
+              {t('encryptedStorage.migrate')}
+            </Button>
+          )}
+        </div>
+      )}
+    </div>
+  );
+};
@@ -8,9 +8,21 @@ import { Switch } from "@/components/ui/switch";
 import { Card } from "@/components/ui/card";
 import { cn } from "@/lib/utils";
 import { LanguageSelector } from "../LanguageSelector";
+import { BinaryDetectionSettings } from "./BinaryDetectionSettings";
+import { PowerInhibitSettings } from "./PowerInhibitSettings";
+import { TaskResourceSettings } from "./TaskResourceSettings";
+import { StreamStallSettings } from "./StreamStallSettings";
+import { UrlFetchSettings } from "./UrlFetchSettings";
+import { ProtectedPathsSettings } from "./ProtectedPathsSettings";
+import { EncryptedStorageSettings } from "./EncryptedStorageSettings";
+import { BackupSettings } from "./BackupSettings";
+import { ProviderRateLimitSettings } from "./ProviderRateLimitSettings";
+import { ProviderPresetsSettings } from "./ProviderPresetsSettings";
 import { useTheme } from "@/contexts/ThemeContext";
 import { useTranslation } from "@/hooks/useTranslation";
-import { api, type ClaudeSettings } from "@/lib/api";
+import { api, type ClaudeSettings, type LogFileInfo, type LogLevel } from "@/lib/api";
+
+const LOG_LEVELS: LogLevel[] = ['error', 'warn', 'info', 'debug', 'trace'];
 
 interface GeneralSettingsProps {
   settings: ClaudeSettings | null;
@@ -52,6 +64,19 @@ export const GeneralSettings: React.FC<GeneralSettingsProps> = ({
     }
   });
 
+  // Log management state
+  const [logInfo, setLogInfo] = useState<LogFileInfo | null>(null);
+  const [exportingBundle, setExportingBundle] = useState(false);
+
+  /**
+   * 初始化时加载日志文件信息
+   */
+  useEffect(() => {
+    api.getLogFileInfo()
+      .then(setLogInfo)
+      .catch((error) => console.warn("Failed to load log file info:", error));
+  }, []);
+
   /**
    * 初始化时加载当前 Codex 路径，并在 refresh 事件触发时同步
    */
@@ -245,6 +270,34 @@ export const GeneralSettings: React.FC<GeneralSettingsProps> = ({
     }
   };
 
+  /**
+   * 切换日志级别（立即生效并持久化）
+   */
+  const handleLogLevelChange = async (level: LogLevel) => {
+    try {
+      const applied = await api.setLogLevel(level);
+      setLogInfo(await api.getLogFileInfo());
+      setToast({ message: t('generalSettings.logLevelChanged', { level: applied }), type: "success" });
+    } catch (error) {
+      setToast({ message: String(error), type: "error" });
+    }
+  };
+
+  /**
+   * 导出诊断包到 ~/.anycode/diagnostics/
+   */
+  const handleExportDiagnosticBundle = async () => {
+    setExportingBundle(true);
+    try {
+      const path = await api.exportDiagnosticBundle();
+      setToast({ message: t('generalSettings.diagnosticBundleExported', { path }), type: "success" });
+    } catch (error) {
+      setToast({ message: String(error), type: "error" });
+    } finally {
+      setExportingBundle(false);
+    }
+  };
+
   return (
     <Card className="p-6 space-y-6">
       <div>
@@ -388,6 +441,57 @@ export const GeneralSettings: React.FC<GeneralSettingsProps> = ({
               {t('generalSettings.chatRetentionDaysDescription')}
             </p>
           </div>
+
+          {/* Log Level */}
+          <div className="space-y-2">
+            <div className="flex items-center justify-between">
+              <div className="space-y-0.5 flex-1">
+                <Label>{t('generalSettings.logLevel')}</Label>
+                <p className="text-xs text-muted-foreground">
+                  {t('generalSettings.logLevelDescription')}
+                </p>
+              </div>
+              <div className="flex items-center gap-1">
+                {LOG_LEVELS.map((level) => (
+                  <Button
+                    key={level}
+                    variant={logInfo?.level === level ? 'default' : 'outline'}
+                    size="sm"
+                    onClick={() => handleLogLevelChange(level)}
+                  >
+                    {level}
+                  </Button>
+                ))}
+              </div>
+            </div>
+            {logInfo && (
+              <p className="text-xs text-muted-foreground font-mono break-all">
+                {t('generalSettings.logFile', {
+                  path: logInfo.path,
+                  size: (logInfo.sizeBytes / 1024 / 1024).toFixed(2),
+                })}
+              </p>
+            )}
+            <div className="flex items-center gap-2">
+              <Button
+                variant="outline"
+                size="sm"
+                onClick={() => api.openLogDirectory().catch((error) => setToast({ message: String(error), type: "error" }))}
+              >
+                {t('generalSettings.openLogDirectory')}
+              </Button>
+              <Button
+                variant="outline"
+                size="sm"
+                onClick={handleExportDiagnosticBundle}
+                disabled={exportingBundle}
+                title={t('generalSettings.exportDiagnosticBundleDescription')}
+              >
+                {exportingBundle && <Loader2 className="h-3 w-3 mr-1 animate-spin" />}
+                {t('generalSettings.exportDiagnosticBundle')}
+              </Button>
+            </div>
+          </div>
           
 
           {/* Custom Claude Path Configuration */}
@@ -586,6 +690,56 @@ export const GeneralSettings: React.FC<GeneralSettingsProps> = ({
               </AnimatePresence>
             </div>
           </div>
+
+          {/* Sleep Prevention */}
+          <div className="border-t pt-4">
+            <PowerInhibitSettings setToast={setToast} />
+          </div>
+
+          {/* Subprocess Resource Monitor */}
+          <div className="border-t pt-4">
+            <TaskResourceSettings setToast={setToast} />
+          </div>
+
+          {/* Stalled Stream Recovery */}
+          <div className="border-t pt-4">
+            <StreamStallSettings setToast={setToast} />
+          </div>
+
+          {/* URL Prefetch */}
+          <div className="border-t pt-4">
+            <UrlFetchSettings setToast={setToast} />
+          </div>
+
+          {/* Protected Paths */}
+          <div className="border-t pt-4">
+            <ProtectedPathsSettings setToast={setToast} />
+          </div>
+
+          {/* Trash/Archive Encryption */}
+          <div className="border-t pt-4">
+            <EncryptedStorageSettings setToast={setToast} />
+          </div>
+
+          {/* Scheduled Backup */}
+          <div className="border-t pt-4">
+            <BackupSettings setToast={setToast} />
+          </div>
+
+          {/* Provider Rate Limits */}
+          <div className="border-t pt-4">
+            <ProviderRateLimitSettings setToast={setToast} />
+          </div>
+
+          {/* Remote Provider Presets */}
+          <div className="border-t pt-4">
+            <ProviderPresetsSettings setToast={setToast} />
+          </div>
+
+          {/* Binary Detection */}
+          <div className="border-t pt-4">
+            <BinaryDetectionSettings setToast={setToast} />
+          </div>
         </div>
       </div>
     </Card>
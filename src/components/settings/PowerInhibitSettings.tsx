@@ -0,0 +1,98 @@
+import React, { useEffect, useState } from "react";
+import { Moon } from "lucide-react";
+import { Label } from "@/components/ui/label";
+import { Switch } from "@/components/ui/switch";
+import { useTranslation } from "@/hooks/useTranslation";
+import { api, type PowerInhibitConfig, type PowerInhibitionStatus } from "@/lib/api";
+
+interface PowerInhibitSettingsProps {
+  setToast: (toast: { message: string; type: 'success' | 'error' } | null) => void;
+}
+
+/**
+ * 休眠阻止设置：Codex / Gemini 任务运行期间防止系统睡眠，并显示当前持有的任务
+ */
+export const PowerInhibitSettings: React.FC<PowerInhibitSettingsProps> = ({ setToast }) => {
+  const { t } = useTranslation();
+  const [config, setConfig] = useState<PowerInhibitConfig | null>(null);
+  const [status, setStatus] = useState<PowerInhibitionStatus | null>(null);
+
+  const loadStatus = () => {
+    api.getPowerInhibitionStatus()
+      .then(setStatus)
+      .catch((error) => console.warn("Failed to load power inhibition status:", error));
+  };
+
+  useEffect(() => {
+    api.getPowerInhibitConfig()
+      .then(setConfig)
+      .catch((error) => console.warn("Failed to load power inhibit config:", error));
+    loadStatus();
+  }, []);
+
+  const handleChange = async (next: PowerInhibitConfig) => {
+    const previous = config;
+    setConfig(next);
+    try {
+      await api.updatePowerInhibitConfig(next);
+      loadStatus();
+    } catch (error) {
+      setConfig(previous);
+      setToast({ message: String(error), type: "error" });
+    }
+  };
+
+  if (!config) return null;
+
+  const holders = status?.holders ?? [];
+
+  return (
+    <div className="space-y-4">
+      <div className="flex items-center justify-between">
+        <div className="space-y-0.5 flex-1">
+          <Label htmlFor="powerInhibit" className="flex items-center gap-2">
+            <Moon className="h-4 w-4" aria-hidden="true" />
+            {t('powerInhibit.title')}
+          </Label>
+          <p className="text-xs text-muted-foreground">{t('powerInhibit.description')}</p>
+        </div>
+        <Switch
+          id="powerInhibit"
+          checked={config.enabled}
+          onCheckedChange={(enabled) => handleChange({ ...config, enabled })}
+        />
+      </div>
+
+      {config.enabled && (
+        <div className="flex items-center justify-between">
+          <div className="space-y-0.5 flex-1">
+            <Label htmlFor="allowDisplaySleep">{t('powerInhibit.allowDisplaySleep')}</Label>
+            <p className="text-xs text-muted-foreground">{t('powerInhibit.allowDisplaySleepDescription')}</p>
+          </div>
+          <Switch
+            id="allowDisplaySleep"
+            checked={config.allowDisplaySleep}
+            onCheckedChange={(allowDisplaySleep) => handleChange({ ...config, allowDisplaySleep })}
+          />
+        </div>
+      )}
+
+      {status && config.enabled && (
+        <div className="text-xs text-muted-foreground space-y-1">
+          <p>
+            {holders.length === 0
+              ? t('powerInhibit.idle')
+              : status.active
+                ? t('powerInhibit.active', { count: holders.length })
+                : t('powerInhibit.failed', { error: status.lastError ?? '' })}
+          </p>
+          {holders.map((holder) => (
+            <p key={holder.sessionId} className="font-mono truncate" title={holder.projectPath}>
+              {holder.engine} · {holder.projectPath}
+            </p>
+          ))}
+        </div>
+      )}
+    </div>
+  );
+};
@@ -0,0 +1,98 @@
+import React, { useEffect, useState } from "react";
+import { Lock } from "lucide-react";
+import { Input } from "@/components/ui/input";
+import { Label } from "@/components/ui/label";
+import { Switch } from "@/components/ui/switch";
+import { useTranslation } from "@/hooks/useTranslation";
+import { api, type ProtectedPathsSettings as ProtectedPathsConfig } from "@/lib/api";
+
+interface ProtectedPathsSettingsProps {
+  setToast: (toast: { message: string; type: 'success' | 'error' } | null) => void;
+}
+
+const parsePatterns = (value: string) =>
+  value.split(/[,\n]+/).map((p) => p.trim()).filter(Boolean);
+
+/**
+ * 禁改区全局默认：总开关、对所有项目生效的规则、是否注入提示词
+ */
+export const ProtectedPathsSettings: React.FC<ProtectedPathsSettingsProps> = ({ setToast }) => {
+  const { t } = useTranslation();
+  const [config, setConfig] = useState<ProtectedPathsConfig | null>(null);
+  const [patterns, setPatterns] = useState("");
+
+  const applyConfig = (next: ProtectedPathsConfig) => {
+    setConfig(next);
+    setPatterns(next.defaultPatterns.join(", "));
+  };
+
+  useEffect(() => {
+    api.getProtectedPathsSettings()
+      .then(applyConfig)
+      .catch((error) => console.warn("Failed to load protected paths settings:", error));
+  }, []);
+
+  const handleChange = async (next: ProtectedPathsConfig) => {
+    const previous = config;
+    setConfig(next);
+    try {
+      await api.saveProtectedPathsSettings(next);
+      applyConfig(await api.getProtectedPathsSettings());
+    } catch (error) {
+      if (previous) applyConfig(previous);
+      setToast({ message: String(error), type: "error" });
+    }
+  };
+
+  if (!config) return null;
+
+  return (
+    <div className="space-y-3">
+      <div className="flex items-center justify-between">
+        <div className="space-y-0.5 flex-1">
+          <Label htmlFor="protectedPathsEnabled" className="flex items-center gap-2">
+            <Lock className="h-4 w-4" aria-hidden="true" />
+            {t('protectedPaths.title')}
+          </Label>
+          <p className="text-xs text-muted-foreground">{t('protectedPaths.description')}</p>
+        </div>
+        <Switch
+          id="protectedPathsEnabled"
+          checked={config.enabled}
+          onCheckedChange={(enabled) => handleChange({ ...config, enabled })}
+        />
+      </div>
+      {config.enabled && (
+        <>
+          <div className="space-y-1">
+            <Label htmlFor="protectedPathsDefaults" className="text-xs">
+              {t('protectedPaths.defaultPatterns')}
+            </Label>
+            <Input
+              id="protectedPathsDefaults"
+              value={patterns}
+              placeholder={t('protectedPaths.defaultPatternsPlaceholder')}
+              onChange={(e) => setPatterns(e.target.value)}
+              onBlur={() => handleChange({ ...config, defaultPatterns: parsePatterns(patterns) })}
+            />
+          </div>
+          <div className="flex items-center justify-between">
+            <div className="space-y-0.5 flex-1">
+              <Label htmlFor="protectedPathsInject" className="text-xs">
+                {t('protectedPaths.injectIntoPrompt')}
+              </Label>
+              <p className="text-xs text-muted-foreground">
+                {t('protectedPaths.injectIntoPromptDescription')}
+              </p>
+            </div>
+            <Switch
+              id="protectedPathsInject"
+              checked={config.injectIntoPrompt}
+              onCheckedChange={(injectIntoPrompt) => handleChange({ ...config, injectIntoPrompt })}
+            />
+          </div>
+        </>
+      )}
+    </div>
+  );
+};
@@ -0,0 +1,106 @@
+import React, { useEffect, useState } from "react";
+import { RefreshCw } from "lucide-react";
+import { Button } from "@/components/ui/button";
+import { Input } from "@/components/ui/input";
+import { Label } from "@/components/ui/label";
+import { useTranslation } from "@/hooks/useTranslation";
+import { api, type RemotePresetsStatus } from "@/lib/api";
+
+interface ProviderPresetsSettingsProps {
+  setToast: (toast: { message: string; type: 'success' | 'error' } | null) => void;
+}
+
+/**
+ * 远程 provider 预设清单设置：清单地址（留空禁用）与手动刷新
+ */
+export const ProviderPresetsSettings: React.FC<ProviderPresetsSettingsProps> = ({ setToast }) => {
+  const { t } = useTranslation();
+  const [manifestUrl, setManifestUrl] = useState<string | null>(null);
+  const [status, setStatus] = useState<RemotePresetsStatus | null>(null);
+  const [busy, setBusy] = useState(false);
+
+  useEffect(() => {
+    api.getProviderPresetsConfig()
+      .then((config) => setManifestUrl(config.manifestUrl))
+      .catch((error) => console.warn("Failed to load provider presets config:", error));
+    api.getProviderPresetsStatus()
+      .then(setStatus)
+      .catch((error) => console.warn("Failed to load provider presets status:", error));
+  }, []);
+
+  const handleSave = async () => {
+    if (manifestUrl === null) return;
+    setBusy(true);
+    try {
+      const config = await api.updateProviderPresetsConfig({ manifestUrl });
+      setManifestUrl(config.manifestUrl);
+      setStatus(await api.getProviderPresetsStatus());
+      setToast({ message: t('providerPresets.saved'), type: "success" });
+    } catch (error) {
+      setToast({ message: String(error), type: "error" });
+    } finally {
+      setBusy(false);
+    }
+  };
+
+  const handleRefresh = async () => {
+    setBusy(true);
+    try {
+      const result = await api.refreshProviderPresets();
+      setStatus(result);
+      setToast({
+        message: result.manifestUrl
+          ? t('providerPresets.refreshed', { codex: result.codexCount, gemini: result.geminiCount })
+          : t('providerPresets.disabled'),
+        type: "success",
+      });
+    } catch (error) {
+      setToast({ message: t('providerPresets.refreshFailed', { error: String(error) }), type: "error" });
+    } finally {
+      setBusy(false);
+    }
+  };
+
+  if (manifestUrl === null) return null;
+
+  return (
+    <div className="space-y-2">
+      <div className="space-y-0.5">
+        <Label htmlFor="providerPresetsManifestUrl">{t('providerPresets.title')}</Label>
+        <p className="text-xs text-muted-foreground">{t('providerPresets.description')}</p>
+      </div>
+      <div className="flex gap-2">
+        <Input
+          id="providerPresetsManifestUrl"
+          value={manifestUrl}
+          placeholder="https://"
+          onChange={(e) => setManifestUrl(e.target.value)}
+        />
+        <Button size="sm" variant="outline" disabled={busy} onClick={handleSave}>
+          {t('providerPresets.save')}
+        </Button>
+        <Button
+          size="sm"
+          variant="outline"
+          disabled={busy || !status?.manifestUrl}
+          onClick={handleRefresh}
+          aria-label={t('providerPresets.refresh')}
+          title={t('providerPresets.refresh')}
+        >
+          <RefreshCw className={busy ? "h-4 w-4 animate-spin" : "h-4 w-4"} aria-hidden="true" />
+        </Button>
+      </div>
+      {status?.manifestUrl && (
+        <p className="text-xs text-muted-foreground">
+          {status.fetchedAt
+            ? t('providerPresets.lastFetched', {
+              time: new Date(status.fetchedAt * 1000).toLocaleString(),
+              codex: status.codexCount,
+              gemini: status.geminiCount,
+            })
+            : t('providerPresets.neverFetched')}
+        </p>
+      )}
+    </div>
+  );
+};
@@ -0,0 +1,180 @@
+import React, { useEffect, useState } from "react";
+import { listen } from "@tauri-apps/api/event";
+import { Gauge, Plus, X } from "lucide-react";
+import { Button } from "@/components/ui/button";
+import { Input } from "@/components/ui/input";
+import { Label } from "@/components/ui/label";
+import { useTranslation } from "@/hooks/useTranslation";
+import {
+  api,
+  type ProviderLimiterStatus,
+  type ProviderRateLimit,
+  type ProviderRateLimitedEvent,
+  type RateLimitConfig,
+} from "@/lib/api";
+
+interface ProviderRateLimitSettingsProps {
+  setToast: (toast: { message: string; type: 'success' | 'error' } | null) => void;
+}
+
+const parseLimit = (value: string): number | null => {
+  const parsed = parseInt(value, 10);
+  return Number.isFinite(parsed) && parsed > 0 ? parsed : null;
+};
+
+/**
+ * Provider 限流：按 base URL 限制并发数与每分钟请求数，显示排队情况，收到 429 时提示
+ */
+export const ProviderRateLimitSettings: React.FC<ProviderRateLimitSettingsProps> = ({ setToast }) => {
+  const { t } = useTranslation();
+  const [config, setConfig] = useState<RateLimitConfig | null>(null);
+  const [status, setStatus] = useState<ProviderLimiterStatus[]>([]);
+
+  const loadStatus = () => {
+    api.getRateLimiterStatus()
+      .then(setStatus)
+      .catch((error) => console.warn("Failed to load rate limiter status:", error));
+  };
+
+  useEffect(() => {
+    api.getProviderRateLimits()
+      .then(setConfig)
+      .catch((error) => console.warn("Failed to load provider rate limits:", error));
+    loadStatus();
+    const timer = setInterval(loadStatus, 5000);
+
+    const unlisten = listen<ProviderRateLimitedEvent>("provider-rate-limited", (event) => {
+      const { provider, retryAfterSecs, throttledRequestsPerMinute } = event.payload;
+      setToast({
+        message: t('rateLimit.rateLimitedToast', {
+          provider,
+          secs: retryAfterSecs,
+          rpm: throttledRequestsPerMinute,
+        }),
+        type: "error",
+      });
+      loadStatus();
+    });
+    return () => {
+      clearInterval(timer);
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
+  const save = async (next: RateLimitConfig) => {
+    const previous = config;
+    setConfig(next);
+    try {
+      await api.updateProviderRateLimits(next);
+    } catch (error) {
+      setConfig(previous);
+      setToast({ message: String(error), type: "error" });
+    }
+  };
+
+  const updateProvider = (index: number, patch: Partial<ProviderRateLimit>) => {
+    if (!config) return;
+    const providers = config.providers.map((p, i) => (i === index ? { ...p, ...patch } : p));
+    setConfig({ ...config, providers });
+  };
+
+  if (!config) return null;
+
+  return (
+    <div className="space-y-3">
+      <div className="space-y-0.5">
+        <Label className="flex items-center gap-2">
+          <Gauge className="h-4 w-4" aria-hidden="true" />
+          {t('rateLimit.title')}
+        </Label>
+        <p className="text-xs text-muted-foreground">{t('rateLimit.description')}</p>
+      </div>
+
+      {config.providers.map((provider, index) => (
+        <div key={index} className="flex items-center gap-2">
+          <Input
+            value={provider.baseUrl}
+            placeholder={t('rateLimit.baseUrlPlaceholder')}
+            onChange={(e) => updateProvider(index, { baseUrl: e.target.value })}
+            onBlur={() => save(config)}
+            className="flex-1"
+          />
+          <Input
+            type="number"
+            min={1}
+            value={provider.maxConcurrentRequests ?? ""}
+            placeholder={t('rateLimit.maxConcurrent')}
+            title={t('rateLimit.maxConcurrent')}
+            onChange={(e) => updateProvider(index, { maxConcurrentRequests: parseLimit(e.target.value) })}
+            onBlur={() => save(config)}
+            className="w-24"
+          />
+          <Input
+            type="number"
+            min={1}
+            value={provider.requestsPerMinute ?? ""}
+            placeholder={t('rateLimit.requestsPerMinute')}
+            title={t('rateLimit.requestsPerMinute')}
+            onChange={(e) => updateProvider(index, { requestsPerMinute: parseLimit(e.target.value) })}
+            onBlur={() => save(config)}
+            className="w-24"
+          />
+          <Button
+            variant="ghost"
+            size="icon"
+            aria-label={t('rateLimit.remove')}
+            onClick={() => save({ ...config, providers: config.providers.filter((_, i) => i !== index) })}
+          >
+            <X className="h-4 w-4" />
+          </Button>
+        </div>
+      ))}
+
+      <div className="flex items-center justify-between gap-2">
+        <Button
+          variant="outline"
+          size="sm"
+          onClick={() =>
+            setConfig({ ...config, providers: [...config.providers, { baseUrl: "" }] })
+          }
+        >
+          <Plus className="h-4 w-4 mr-1" />
+          {t('rateLimit.add')}
+        </Button>
+        <div className="flex items-center gap-2">
+          <Label htmlFor="rateLimitMaxWait" className="text-xs">
+            {t('rateLimit.maxWait')}
+          </Label>
+          <Input
+            id="rateLimitMaxWait"
+            type="number"
+            min={1}
+            value={config.maxWaitSecs}
+            onChange={(e) => setConfig({ ...config, maxWaitSecs: parseLimit(e.target.value) ?? 1 })}
+            onBlur={() => save(config)}
+            className="w-20"
+          />
+        </div>
+      </div>
+
+      {status.length > 0 && (
+        <div className="text-xs text-muted-foreground space-y-1">
+          {status.map((s) => (
+            <p key={s.provider} className="font-mono truncate">
+              {s.provider} ·{" "}
+              {t('rateLimit.status', {
+                inFlight: s.inFlight,
+                queued: s.queued,
+                lastMinute: s.requestsLastMinute,
+              })}
+              {s.backoffRemainingSecs > 0 &&
+                ` · ${t('rateLimit.backoff', { secs: s.backoffRemainingSecs })}`}
+              {s.throttledRequestsPerMinute != null &&
+                ` · ${t('rateLimit.throttled', { rpm: s.throttledRequestsPerMinute })}`}
+            </p>
+          ))}
+        </div>
+      )}
+    </div>
+  );
+};
@@ -0,0 +1,62 @@
+import React, { useEffect, useState } from "react";
+import { Activity } from "lucide-react";
+import { Label } from "@/components/ui/label";
+import { Switch } from "@/components/ui/switch";
+import { useTranslation } from "@/hooks/useTranslation";
+import { api, type StreamStallConfig } from "@/lib/api";
+
+interface StreamStallSettingsProps {
+  setToast: (toast: { message: string; type: 'success' | 'error' } | null) => void;
+}
+
+/**
+ * 输出卡死自动恢复：Codex / Gemini 长时间无输出时中断进程并续接会话
+ */
+export const StreamStallSettings: React.FC<StreamStallSettingsProps> = ({ setToast }) => {
+  const { t } = useTranslation();
+  const [config, setConfig] = useState<StreamStallConfig | null>(null);
+
+  useEffect(() => {
+    api.getStreamStallConfig()
+      .then(setConfig)
+      .catch((error) => console.warn("Failed to load stream stall config:", error));
+  }, []);
+
+  const handleChange = async (next: StreamStallConfig) => {
+    const previous = config;
+    setConfig(next);
+    try {
+      await api.updateStreamStallConfig(next);
+    } catch (error) {
+      setConfig(previous);
+      setToast({ message: String(error), type: "error" });
+    }
+  };
+
+  if (!config) return null;
+
+  return (
+    <div className="flex items-center justify-between">
+      <div className="space-y-0.5 flex-1">
+        <Label htmlFor="streamStallAutoRecover" className="flex items-center gap-2">
+          <Activity className="h-4 w-4" aria-hidden="true" />
+          {t('streamStall.title')}
+        </Label>
+        <p className="text-xs text-muted-foreground">
+          {config.autoRecover
+            ? t('streamStall.description', {
+                threshold: config.stallThresholdSecs,
+                grace: config.wakeGraceSecs,
+                max: config.maxReconnects,
+              })
+            : t('streamStall.disabledDescription', { threshold: config.stallThresholdSecs })}
+        </p>
+      </div>
+      <Switch
+        id="streamStallAutoRecover"
+        checked={config.autoRecover}
+        onCheckedChange={(autoRecover) => handleChange({ ...config, autoRecover })}
+      />
+    </div>
+  );
+};
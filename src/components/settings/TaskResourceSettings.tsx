@@ -0,0 +1,106 @@
+import React, { useEffect, useState } from "react";
+import { Cpu } from "lucide-react";
+import { Input } from "@/components/ui/input";
+import { Label } from "@/components/ui/label";
+import { Switch } from "@/components/ui/switch";
+import { useTranslation } from "@/hooks/useTranslation";
+import { api, type TaskResourceConfig } from "@/lib/api";
+
+interface TaskResourceSettingsProps {
+  setToast: (toast: { message: string; type: 'success' | 'error' } | null) => void;
+}
+
+type ThresholdField = 'cpuThresholdPercent' | 'cpuSustainedMinutes' | 'memoryThresholdMb';
+
+const THRESHOLD_FIELDS: ThresholdField[] = ['cpuThresholdPercent', 'cpuSustainedMinutes', 'memoryThresholdMb'];
+
+/**
+ * 子进程资源监控：Codex / Gemini 进程树 CPU 持续过高或内存过大时告警
+ */
+export const TaskResourceSettings: React.FC<TaskResourceSettingsProps> = ({ setToast }) => {
+  const { t } = useTranslation();
+  const [config, setConfig] = useState<TaskResourceConfig | null>(null);
+  const [drafts, setDrafts] = useState<Record<ThresholdField, string>>({
+    cpuThresholdPercent: "",
+    cpuSustainedMinutes: "",
+    memoryThresholdMb: "",
+  });
+
+  const applyConfig = (next: TaskResourceConfig) => {
+    setConfig(next);
+    setDrafts({
+      cpuThresholdPercent: String(next.cpuThresholdPercent),
+      cpuSustainedMinutes: String(next.cpuSustainedMinutes),
+      memoryThresholdMb: String(next.memoryThresholdMb),
+    });
+  };
+
+  useEffect(() => {
+    api.getTaskResourceConfig()
+      .then(applyConfig)
+      .catch((error) => console.warn("Failed to load task resource config:", error));
+  }, []);
+
+  const handleSave = async (next: TaskResourceConfig) => {
+    if (!config) return;
+    const previous = config;
+    applyConfig(next);
+    try {
+      await api.updateTaskResourceConfig(next);
+    } catch (error) {
+      applyConfig(previous);
+      setToast({ message: String(error), type: "error" });
+    }
+  };
+
+  const handleThresholdBlur = (field: ThresholdField) => {
+    if (!config) return;
+    const value = Number(drafts[field]);
+    if (!Number.isFinite(value) || value === config[field]) {
+      setDrafts({ ...drafts, [field]: String(config[field]) });
+      return;
+    }
+    handleSave({ ...config, [field]: field === 'cpuThresholdPercent' ? value : Math.round(value) });
+  };
+
+  if (!config) return null;
+
+  return (
+    <div className="space-y-3">
+      <div className="flex items-center justify-between">
+        <div className="space-y-0.5 flex-1">
+          <Label htmlFor="taskResourceMonitor" className="flex items-center gap-2">
+            <Cpu className="h-4 w-4" aria-hidden="true" />
+            {t('taskResources.title')}
+          </Label>
+          <p className="text-xs text-muted-foreground">{t('taskResources.description')}</p>
+        </div>
+        <Switch
+          id="taskResourceMonitor"
+          checked={config.enabled}
+          onCheckedChange={(enabled) => handleSave({ ...config, enabled })}
+        />
+      </div>
+
+      {config.enabled && (
+        <div className="grid grid-cols-3 gap-3">
+          {THRESHOLD_FIELDS.map((field) => (
+            <div key={field} className="space-y-1">
+              <Label htmlFor={`taskResource-${field}`} className="text-xs">
+                {t(`taskResources.${field}`)}
+              </Label>
+              <Input
+                id={`taskResource-${field}`}
+                type="number"
+                min={1}
+                value={drafts[field]}
+                onChange={(e) => setDrafts({ ...drafts, [field]: e.target.value })}
+                onBlur={() => handleThresholdBlur(field)}
+              />
+            </div>
+          ))}
+        </div>
+      )}
+    </div>
+  );
+};
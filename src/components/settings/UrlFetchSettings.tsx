@@ -0,0 +1,87 @@
+import React, { useEffect, useState } from "react";
+import { Link } from "lucide-react";
+import { Input } from "@/components/ui/input";
+import { Label } from "@/components/ui/label";
+import { useTranslation } from "@/hooks/useTranslation";
+import { api, type UrlFetchConfig } from "@/lib/api";
+
+interface UrlFetchSettingsProps {
+  setToast: (toast: { message: string; type: 'success' | 'error' } | null) => void;
+}
+
+const parseDomains = (value: string) =>
+  value.split(/[,\s]+/).map((d) => d.trim()).filter(Boolean);
+
+/**
+ * 链接预抓取：发送时允许抓取的域名与附加内容的 token 预算
+ */
+export const UrlFetchSettings: React.FC<UrlFetchSettingsProps> = ({ setToast }) => {
+  const { t } = useTranslation();
+  const [config, setConfig] = useState<UrlFetchConfig | null>(null);
+  const [domains, setDomains] = useState("");
+  const [budget, setBudget] = useState("");
+
+  const applyConfig = (next: UrlFetchConfig) => {
+    setConfig(next);
+    setDomains(next.allowedDomains.join(", "));
+    setBudget(String(next.tokenBudget));
+  };
+
+  useEffect(() => {
+    api.getUrlFetchConfig()
+      .then(applyConfig)
+      .catch((error) => console.warn("Failed to load URL fetch config:", error));
+  }, []);
+
+  const handleSave = async () => {
+    if (!config) return;
+    const next: UrlFetchConfig = {
+      allowedDomains: parseDomains(domains),
+      tokenBudget: Number.parseInt(budget, 10) || 0,
+    };
+    try {
+      await api.updateUrlFetchConfig(next);
+      applyConfig(await api.getUrlFetchConfig());
+    } catch (error) {
+      applyConfig(config);
+      setToast({ message: String(error), type: "error" });
+    }
+  };
+
+  if (!config) return null;
+
+  return (
+    <div className="space-y-3">
+      <div className="space-y-0.5">
+        <Label className="flex items-center gap-2">
+          <Link className="h-4 w-4" aria-hidden="true" />
+          {t('urlFetch.title')}
+        </Label>
+        <p className="text-xs text-muted-foreground">{t('urlFetch.description')}</p>
+      </div>
+      <div className="grid grid-cols-[1fr_8rem] gap-3">
+        <div className="space-y-1">
+          <Label htmlFor="urlFetchDomains" className="text-xs">{t('urlFetch.allowedDomains')}</Label>
+          <Input
+            id="urlFetchDomains"
+            value={domains}
+            placeholder={t('urlFetch.allowedDomainsPlaceholder')}
+            onChange={(e) => setDomains(e.target.value)}
+            onBlur={handleSave}
+          />
+        </div>
+        <div className="space-y-1">
+          <Label htmlFor="urlFetchBudget" className="text-xs">{t('urlFetch.tokenBudget')}</Label>
+          <Input
+            id="urlFetchBudget"
+            type="number"
+            min={1}
+            value={budget}
+            onChange={(e) => setBudget(e.target.value)}
+            onBlur={handleSave}
+          />
+        </div>
+      </div>
+    </div>
+  );
+};
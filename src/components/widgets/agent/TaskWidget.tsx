@@ -6,8 +6,9 @@
  */
 
 import React, { useState } from "react";
-import { Bot, Sparkles, Zap, ChevronRight } from "lucide-react";
+import { Bot, Sparkles, Zap, ChevronRight, Loader2, Wrench } from "lucide-react";
 import { cn } from "@/lib/utils";
+import { api } from "@/lib/api";
 
 export interface TaskWidgetProps {
   /** 任务描述 */
@@ -18,6 +19,8 @@ export interface TaskWidgetProps {
   result?: any;
   /** 子代理类型 */
   subagentType?: string;
+  /** 子代理会话文件（agent-*.jsonl），存在时可展开查看子代理过程 */
+  agentFile?: string;
 }
 
 /**
@@ -46,11 +49,128 @@ function getSubagentTypeLabel(type?: string): string {
   return SUBAGENT_TYPE_LABELS[type] || type;
 }
 
+/**
+ * 子代理过程中的单条消息摘要
+ */
+interface AgentStep {
+  role: "user" | "assistant";
+  text?: string;
+  tools: string[];
+}
+
+/**
+ * 将子代理 JSONL 消息转换为用于展示的步骤列表（跳过纯 tool_result 的用户消息）
+ */
+function toAgentSteps(messages: any[]): AgentStep[] {
+  const steps: AgentStep[] = [];
+  for (const entry of messages) {
+    const role = entry?.message?.role;
+    if (role !== "user" && role !== "assistant") continue;
+
+    const content = entry.message.content;
+    const blocks: any[] = typeof content === "string" ? [{ type: "text", text: content }] : Array.isArray(content) ? content : [];
+    const text = blocks
+      .filter((b) => b?.type === "text" && typeof b.text === "string")
+      .map((b) => b.text)
+      .join("\n")
+      .trim();
+    const tools = blocks.filter((b) => b?.type === "tool_use").map((b) => String(b.name ?? "tool"));
+
+    if (!text && tools.length === 0) continue;
+    steps.push({ role, text: text || undefined, tools });
+  }
+  return steps;
+}
+
+/**
+ * 子代理过程（懒加载 agent-*.jsonl）
+ */
+const AgentProcess: React.FC<{ agentFile: string }> = ({ agentFile }) => {
+  const [isOpen, setIsOpen] = useState(false);
+  const [steps, setSteps] = useState<AgentStep[] | null>(null);
+  const [error, setError] = useState<string | null>(null);
+  const [loading, setLoading] = useState(false);
+
+  const toggle = async () => {
+    const next = !isOpen;
+    setIsOpen(next);
+    if (!next || steps || loading) return;
+
+    setLoading(true);
+    setError(null);
+    try {
+      const messages = await api.getAgentMessages(agentFile);
+      setSteps(toAgentSteps(messages));
+    } catch (err) {
+      setError(err instanceof Error ? err.message : String(err));
+    } finally {
+      setLoading(false);
+    }
+  };
+
+  return (
+    <div className="space-y-2">
+      <button
+        onClick={toggle}
+        className="flex items-center gap-1.5 text-xs font-medium text-muted-foreground hover:text-foreground transition-colors"
+      >
+        <ChevronRight className={cn("h-3 w-3 transition-transform", isOpen && "rotate-90")} />
+        <span>展开查看子代理过程</span>
+        {steps && <span className="text-muted-foreground/70">({steps.length})</span>}
+      </button>
+
+      {isOpen && (
+        <div className="rounded-lg border bg-muted/30 p-3 space-y-2 max-h-[480px] overflow-y-auto">
+          {loading && (
+            <div className="flex items-center gap-2 text-xs text-muted-foreground">
+              <Loader2 className="h-3 w-3 animate-spin" />
+              <span>加载子代理会话...</span>
+            </div>
+          )}
+          {error && <div className="text-xs text-red-600">加载失败: {error}</div>}
+          {steps && steps.length === 0 && (
+            <div className="text-xs text-muted-foreground">子代理会话为空</div>
+          )}
+          {steps?.map((step, i) => (
+            <div key={i} className="space-y-1">
+              <div
+                className={cn(
+                  "text-[11px] font-medium",
+                  step.role === "assistant" ? "text-blue-600 dark:text-blue-400" : "text-muted-foreground"
+                )}
+              >
+                {step.role === "assistant" ? "子代理" : "任务"}
+              </div>
+              {step.text && (
+                <pre className="text-xs font-mono text-foreground whitespace-pre-wrap">{step.text}</pre>
+              )}
+              {step.tools.length > 0 && (
+                <div className="flex flex-wrap gap-1">
+                  {step.tools.map((tool, j) => (
+                    <span
+                      key={j}
+                      className="inline-flex items-center gap-1 rounded bg-muted px-1.5 py-0.5 text-[11px] font-mono text-muted-foreground"
+                    >
+                      <Wrench className="h-2.5 w-2.5" />
+                      {tool}
+                    </span>
+                  ))}
+                </div>
+              )}
+            </div>
+          ))}
+        </div>
+      )}
+    </div>
+  );
+};
+
 export const TaskWidget: React.FC<TaskWidgetProps> = ({
   description,
   prompt,
   result: _result,
   subagentType,
+  agentFile,
 }) => {
   const [isExpanded, setIsExpanded] = useState(false);
 
@@ -103,6 +223,9 @@ export const TaskWidget: React.FC<TaskWidgetProps> = ({
             )}
           </div>
         )}
+
+        {/* 子代理过程（可折叠，按需加载） */}
+        {agentFile && <AgentProcess agentFile={agentFile} />}
       </div>
     </div>
   );
@@ -12,6 +12,7 @@ import { Prism as SyntaxHighlighter } from "react-syntax-highlighter";
 import { getClaudeSyntaxTheme } from "@/lib/claudeSyntaxTheme";
 import { useTheme } from "@/contexts/ThemeContext";
 import { cn } from "@/lib/utils";
+import { parseMcpToolName, type McpCallInfo } from "@/lib/mcpToolCalls";
 
 /** 结果折叠高度阈值 */
 const RESULT_COLLAPSE_HEIGHT = 300;
@@ -26,6 +27,8 @@ export interface MCPWidgetProps {
     content?: any;
     is_error?: boolean;
   };
+  /** 消息层解析出的 server / 工具名 / 耗时 */
+  mcp?: McpCallInfo;
 }
 
 /**
@@ -41,6 +44,7 @@ export const MCPWidget: React.FC<MCPWidgetProps> = ({
   toolName,
   input,
   result,
+  mcp,
 }) => {
   const { t } = useTranslation();
   const { theme } = useTheme();
@@ -124,9 +128,17 @@ export const MCPWidget: React.FC<MCPWidgetProps> = ({
 
   // 解析工具名称
   // 格式: mcp__namespace__method
-  const parts = toolName.split('__');
-  const namespace = parts[1] || '';
-  const method = parts[2] || '';
+  const parsedName = mcp ?? parseMcpToolName(toolName);
+  const namespace = parsedName?.server || '';
+  const method = parsedName?.tool || '';
+  const durationMs = mcp?.durationMs;
+
+  /**
+   * 格式化耗时
+   */
+  const formatDuration = (ms: number) => {
+    return ms < 1000 ? `${Math.round(ms)}ms` : `${(ms / 1000).toFixed(1)}s`;
+  };
 
   /**
    * 格式化命名空间显示
@@ -205,6 +217,12 @@ export const MCPWidget: React.FC<MCPWidgetProps> = ({
               {statusIcon}
               <span className={cn("font-medium hidden sm:inline", statusColor)}>{statusText}</span>
             </div>
+
+            {hasResult && durationMs !== undefined && (
+              <span className="text-muted-foreground/60 font-mono">
+                {formatDuration(durationMs)}
+              </span>
+            )}
             
             {(hasInput || hasResult) && (
               <span className="text-muted-foreground/60 font-mono hidden sm:inline">
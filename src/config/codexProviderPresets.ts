@@ -3,6 +3,8 @@
  * 移植自 cc-switch2 项目
  */
 
+import type { CodexProviderConfig, PresetSource } from '@/lib/api';
+
 export type ProviderCategory =
   | "official"      // 官方
   | "cn_official"   // 国产官方
@@ -26,6 +28,7 @@ export interface CodexProviderPreset {
   // 请求地址候选列表（用于地址管理/测速）
   endpointCandidates?: string[];
   description?: string; // 描述
+  source?: PresetSource; // 来源：内置或远程清单
 }
 
 /**
@@ -175,6 +178,33 @@ requires_openai_auth = true`,
   },
 ];
 
+/**
+ * 合并远程清单中的预设：与内置预设同名时以远程为准，自定义模板保持在最后
+ */
+export function mergeRemotePresets(remotePresets: CodexProviderConfig[]): CodexProviderPreset[] {
+  const remoteNames = new Set(remotePresets.map(p => p.name));
+  const builtIn = codexProviderPresets
+    .filter(p => !remoteNames.has(p.name))
+    .map(p => ({ ...p, source: 'builtin' as const }));
+  const remote: CodexProviderPreset[] = remotePresets.map(p => ({
+    id: p.id,
+    name: p.name,
+    websiteUrl: p.websiteUrl || "",
+    auth: p.auth,
+    config: p.config,
+    isOfficial: p.isOfficial,
+    category: p.category,
+    description: p.description,
+    source: 'remote',
+  }));
+
+  return [
+    ...builtIn.filter(p => !p.isCustomTemplate),
+    ...remote,
+    ...builtIn.filter(p => p.isCustomTemplate),
+  ];
+}
+
 /**
  * 根据 ID 获取预设
  */
@@ -3,6 +3,8 @@
  * 参考 cc-switch2 项目的实现
  */
 
+import type { GeminiProviderConfig as RemoteGeminiProviderConfig, PresetSource } from '@/lib/api';
+
 export type ProviderCategory =
   | "official"      // 官方
   | "third_party"   // 第三方供应商
@@ -26,6 +28,7 @@ export interface GeminiProviderPreset {
   partnerPromotionKey?: string;
   endpointCandidates?: string[];
   isCustomTemplate?: boolean;
+  source?: PresetSource; // 来源：内置或远程清单
 }
 
 /**
@@ -120,6 +123,33 @@ export const geminiProviderPresets: GeminiProviderPreset[] = [
   },
 ];
 
+/**
+ * 合并远程清单中的预设：与内置预设同名时以远程为准，自定义模板保持在最后
+ */
+export function mergeRemotePresets(remotePresets: RemoteGeminiProviderConfig[]): GeminiProviderPreset[] {
+  const remoteNames = new Set(remotePresets.map(p => p.name));
+  const builtIn = geminiProviderPresets
+    .filter(p => !remoteNames.has(p.name))
+    .map(p => ({ ...p, source: 'builtin' as const }));
+  const remote: GeminiProviderPreset[] = remotePresets.map(p => ({
+    id: p.id,
+    name: p.name,
+    websiteUrl: p.websiteUrl || "",
+    env: p.env,
+    baseURL: p.env.GOOGLE_GEMINI_BASE_URL || undefined,
+    model: p.env.GEMINI_MODEL || undefined,
+    description: p.description,
+    category: p.category,
+    source: 'remote',
+  }));
+
+  return [
+    ...builtIn.filter(p => !p.isCustomTemplate),
+    ...remote,
+    ...builtIn.filter(p => p.isCustomTemplate),
+  ];
+}
+
 /**
  * 根据 ID 获取预设
  */
@@ -1,10 +1,13 @@
 import React from "react";
 import type { ClaudeStreamMessage } from "@/types/claude";
+import { readDurationMs } from "@/lib/toolTiming";
 
 export interface ToolResultEntry {
   toolUseId: string;
   content?: any;
   isError?: boolean;
+  /** 工具耗时（流式执行时由后端写入 tool_result） */
+  durationMs?: number;
   sourceMessage?: ClaudeStreamMessage;
 }
 
@@ -44,6 +47,7 @@ const buildToolResultMap = (messages: ClaudeStreamMessage[]): Map<string, ToolRe
             toolUseId: item.tool_use_id,
             content: item.content ?? item.result ?? item,
             isError: Boolean(item.is_error),
+            durationMs: readDurationMs(item),
             sourceMessage: msg,
           });
         }
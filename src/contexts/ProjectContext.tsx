@@ -112,7 +112,7 @@ export const ProjectProvider: React.FC<{ children: ReactNode }> = ({ children })
           created_at: new Date(info.startTime).getTime() / 1000, // Convert to Unix timestamp
           first_message: info.firstMessage,
           message_timestamp: info.startTime,
-          last_message_timestamp: info.startTime,
+          last_message_timestamp: info.lastUpdated || info.startTime,
           engine: 'gemini' as const,
         }));
       } catch (geminiErr) {
@@ -127,8 +127,9 @@ export const ProjectProvider: React.FC<{ children: ReactNode }> = ({ children })
       setSessions(allSessions);
       setSelectedProject(project);
 
-      // Background indexing
+      // Background indexing, then keep the index in sync with saved files
       api.preindexProject(project.path).catch(console.error);
+      api.watchProjectChanges(project.path).catch(console.error);
     } catch (err) {
       console.error("Failed to load sessions:", err);
       setError(t('common.loadingSessions'));
@@ -156,7 +157,7 @@ export const ProjectProvider: React.FC<{ children: ReactNode }> = ({ children })
             created_at: new Date(info.startTime).getTime() / 1000,
             first_message: info.firstMessage,
             message_timestamp: info.startTime,
-            last_message_timestamp: info.startTime,
+            last_message_timestamp: info.lastUpdated || info.startTime,
             engine: 'gemini' as const,
           }));
         } catch (geminiErr) {
@@ -199,6 +200,12 @@ export const ProjectProvider: React.FC<{ children: ReactNode }> = ({ children })
     loadProjects();
   }, [loadProjects]);
 
+  // 撤销移除项目后刷新列表
+  useEffect(() => {
+    window.addEventListener('undo-applied', loadProjects);
+    return () => window.removeEventListener('undo-applied', loadProjects);
+  }, [loadProjects]);
+
   return (
     <ProjectContext.Provider value={{
       projects,
@@ -38,6 +38,8 @@ interface SessionContextValue {
   // 回调函数
   onLinkDetected?: (url: string) => void;
   onRevert?: (promptIndex: number, mode: RewindMode) => void;
+  /** 重新加载当前会话的消息（会话文件在外部被改写后调用） */
+  onReloadSession?: () => void | Promise<void>;
   getPromptIndexForMessage?: (index: number) => number;
 }
 
@@ -51,6 +53,7 @@ interface SessionProviderProps {
   settings: SessionSettings;
   onLinkDetected?: (url: string) => void;
   onRevert?: (promptIndex: number, mode: RewindMode) => void;
+  onReloadSession?: () => void | Promise<void>;
   getPromptIndexForMessage?: (index: number) => number;
   children: React.ReactNode;
 }
@@ -63,6 +66,7 @@ export const SessionProvider: React.FC<SessionProviderProps> = ({
   settings,
   onLinkDetected,
   onRevert,
+  onReloadSession,
   getPromptIndexForMessage,
   children,
 }) => {
@@ -77,6 +81,7 @@ export const SessionProvider: React.FC<SessionProviderProps> = ({
       settings,
       onLinkDetected,
       onRevert,
+      onReloadSession,
       getPromptIndexForMessage,
     }),
     [
@@ -87,6 +92,7 @@ export const SessionProvider: React.FC<SessionProviderProps> = ({
       settings,
       onLinkDetected,
       onRevert,
+      onReloadSession,
       getPromptIndexForMessage,
     ]
   );
@@ -111,4 +117,13 @@ export const useSession = (): SessionContextValue => {
   return context;
 };
 
+/**
+ * 可选地获取 Session Context
+ *
+ * 供也会在 SessionProvider 之外渲染的组件使用（如独立的消息预览），不在 Provider 内时返回 undefined
+ */
+export const useOptionalSession = (): SessionContextValue | undefined => {
+  return React.useContext(SessionContext);
+};
+
 SessionProvider.displayName = "SessionProvider";
@@ -7,6 +7,7 @@
 
 import { useMemo } from 'react';
 import type { ClaudeStreamMessage } from '@/types/claude';
+import { annotateMcpToolCalls } from '@/lib/mcpToolCalls';
 
 /**
  * 过滤选项
@@ -98,6 +99,8 @@ function isWarmupMessage(message: ClaudeStreamMessage): boolean {
  * 3. 跳过空内容的用户消息
  * 4. （可选）跳过 Warmup 消息及其回复
  *
+ * 保留的 MCP tool_use 块会附加 server / 工具名 / 耗时等解析字段（见 annotateMcpToolCalls）
+ *
  * @param messages - 原始消息列表
  * @param options - 过滤选项
  * @returns 过滤后的可显示消息列表
@@ -132,7 +135,7 @@ export function useDisplayableMessages(
       
     }
 
-    return messages.filter((message, index) => {
+    return annotateMcpToolCalls(messages).filter((message, index) => {
       // 规则 0：隐藏 Warmup 消息及其回复
       if (hideWarmupMessages && warmupIndices.has(index)) {
         return false;
@@ -3,6 +3,7 @@ import { listen } from '@tauri-apps/api/event';
 import { useProject } from '@/contexts/ProjectContext';
 import { useNavigation } from '@/contexts/NavigationContext';
 import { useTabs } from '@/hooks/useTabs';
+import { SESSIONS_INVALIDATED_EVENT, type SessionsInvalidated } from '@/lib/api';
 
 type ClaudeCompletePayload = { tab_id?: string | null; payload: boolean } | boolean;
 
@@ -73,6 +74,30 @@ export const useGlobalEvents = () => {
     };
   }, [selectedProject, loadProjects, refreshSessions]);
 
+  // Refetch the session list when sessions of the selected project change on disk
+  useEffect(() => {
+    let unlisten: (() => void) | undefined;
+    const normalizePath = (p: string) => p.replace(/\\/g, '/').replace(/\/+$/, '').toLowerCase();
+
+    const setupListener = async () => {
+      try {
+        unlisten = await listen<SessionsInvalidated>(SESSIONS_INVALIDATED_EVENT, async (event) => {
+          if (!selectedProject) return;
+          const { projectPath } = event.payload;
+          if (projectPath && normalizePath(projectPath) !== normalizePath(selectedProject.path)) return;
+          await refreshSessions();
+        });
+      } catch (err) {
+        console.error('Failed to setup sessions:invalidated listener:', err);
+      }
+    };
+
+    setupListener();
+    return () => {
+      if (unlisten) unlisten();
+    };
+  }, [selectedProject, refreshSessions]);
+
   // Handle Prompt API Settings
   useEffect(() => {
     const handleOpenPromptAPISettings = () => {
@@ -14,13 +14,16 @@
 
 import { useCallback, useRef, useEffect } from 'react';
 import { listen, type UnlistenFn } from '@tauri-apps/api/event';
-import { api, type Session } from '@/lib/api';
+import { api, AppCommandError, parseSessionInUseError, SESSION_ID_CHANGED_EVENT, type ExecutionRetrying, type Session, type SessionIdChanged, type StreamReconnecting, type StreamStalled, type UrlContextFetched } from '@/lib/api';
 import { translationMiddleware, isSlashCommand, type TranslationResult } from '@/lib/translationMiddleware';
 import type { ClaudeStreamMessage } from '@/types/claude';
 import type { ModelType } from '@/components/FloatingPromptInput/types';
+import { migrateDraft } from '@/components/FloatingPromptInput/hooks/useDraftPersistence';
 // 🔧 FIX: 导入 CodexEventConverter 类，在每个会话中创建独立实例避免全局单例污染
 import { CodexEventConverter, extractCodexRateLimitsFromEvent } from '@/lib/codexConverter';
-import type { CodexExecutionMode, CodexRateLimits } from '@/types/codex';
+import { isProjectMemoryEnabled } from '@/lib/projectMemory';
+import { buildEnhancementRequest, extractFileReferences } from '@/lib/enhanceOnSend';
+import type { CodexExecutionMode, CodexRateLimits, CodexReasoningEffort, CodexVerbosity } from '@/types/codex';
 
 // ============================================================================
 // Global Type Declarations
@@ -34,12 +37,14 @@ declare global {
       projectPath: string;
       promptIndex: number;
       promptText: string;
+      traceId?: string;
     };
     __geminiPendingPrompt?: {
       sessionId: string;
       projectPath: string;
       promptIndex: number;
       promptText: string;
+      traceId?: string;
     };
     __geminiPendingSession?: {
       sessionId: string;
@@ -56,6 +61,8 @@ interface QueuedPrompt {
   id: string;
   prompt: string;
   model: ModelType;
+  /** 插话：已交给后端，本轮结束后由后端续接会话发送 */
+  source?: 'interjection';
 }
 
 interface UsePromptExecutionConfig {
@@ -74,8 +81,13 @@ interface UsePromptExecutionConfig {
   executionEngine?: 'claude' | 'codex' | 'gemini'; // 执行引擎选择 (默认: 'claude')
   codexMode?: CodexExecutionMode;       // Codex 执行模式
   codexModel?: string;                  // Codex 模型 (e.g., 'gpt-5.2')
+  codexProfile?: string;                // Codex config.toml 中的命名 profile
+  codexReasoningEffort?: CodexReasoningEffort; // Codex 推理强度（-c model_reasoning_effort）
+  codexVerbosity?: CodexVerbosity;      // Codex 输出详略（-c model_verbosity）
+  codexStrictConfig?: boolean;          // CLI 不支持上述配置项时拒绝执行
   geminiModel?: string;                 // Gemini 模型 (e.g., 'gemini-3-flash')
   geminiApprovalMode?: 'auto_edit' | 'yolo' | 'default'; // Gemini 审批模式
+  geminiSearchGrounding?: boolean;      // Gemini 联网搜索（search grounding）
 
   // Refs
   hasActiveSessionRef: React.MutableRefObject<boolean>;
@@ -114,6 +126,39 @@ const normalizeClaudeGlobalPayload = <T,>(payload: ClaudeGlobalEventPayload<T>)
   return { tabId: null, payload: payload as T };
 };
 
+/** 自动重试提示（codex-retrying / gemini-retrying 事件） */
+const describeRetry = ({ attempt, maxAttempts, delayMs, reason }: ExecutionRetrying) => {
+  const seconds = Math.max(1, Math.round(delayMs / 1000));
+  return `提供方暂时不可用，${seconds} 秒后自动重试（第 ${attempt}/${maxAttempts} 次）${reason ? `：${reason}` : ''}`;
+};
+
+/** 输出卡死提示（codex-stalled / gemini-stalled 事件） */
+const describeStall = ({ idleSecs, willReconnect, graceSecs }: StreamStalled) =>
+  willReconnect
+    ? `已 ${idleSecs} 秒没有输出，${graceSecs ?? 0} 秒内仍无响应将自动中断并续接会话`
+    : `已 ${idleSecs} 秒没有输出，任务可能已卡住，可以手动取消后重试`;
+
+/** 自动续接提示（codex-reconnecting / gemini-reconnecting 事件） */
+const describeReconnect = ({ reconnect, maxReconnects, reason }: StreamReconnecting) =>
+  `输出卡死（${reason}），正在续接会话（第 ${reconnect}/${maxReconnects} 次）`;
+
+/** 链接预抓取失败提示（url-context-fetched 事件） */
+const describeUrlFailures = ({ failed }: UrlContextFetched) =>
+  `链接预抓取失败，以下链接的内容未附加到提示词：\n${failed.map((f) => `${f.url}: ${f.reason}`).join('\n')}`;
+
+/** 已识别错误码的命令错误：当前语言的说明 + 原始错误 */
+const describeCommandError = (error: AppCommandError) =>
+  error.code !== 'unknown' && error.details ? `${error.message}\n${error.details}` : error.message;
+
+/** 执行过程中通过事件上报的错误文本，按引擎归类后附上当前语言的说明 */
+const localizeExecutionError = async (engine: 'claude' | 'codex' | 'gemini', raw: string) => {
+  try {
+    return describeCommandError(new AppCommandError(await api.classifyExecutionError(engine, raw)));
+  } catch {
+    return raw;
+  }
+};
+
 // ============================================================================
 // Hook Implementation
 // ============================================================================
@@ -131,8 +176,13 @@ export function usePromptExecution(config: UsePromptExecutionConfig): UsePromptE
     executionEngine = 'claude', // 🆕 默认使用 Claude Code
     codexMode = 'read-only',     // 🆕 Codex 默认只读模式
     codexModel,                  // 🆕 Codex 模型
+    codexProfile,                // Codex 命名 profile
+    codexReasoningEffort,        // Codex 推理强度
+    codexVerbosity,              // Codex 输出详略
+    codexStrictConfig,           // Codex 配置项严格模式
     geminiModel,                 // 🆕 Gemini 模型
     geminiApprovalMode,          // 🆕 Gemini 审批模式
+    geminiSearchGrounding,       // Gemini 联网搜索
     hasActiveSessionRef,
     unlistenRefs,
     isMountedRef,
@@ -232,6 +282,19 @@ export function usePromptExecution(config: UsePromptExecutionConfig): UsePromptE
     }
   }, [effectiveSession?.id, extractedSessionInfo?.sessionId, setCodexRateLimits, updateCodexRateLimits]);
 
+  /**
+   * 取出下一条待发送的本地队列提示词
+   * 插话由后端在本轮结束后续接会话发送，这里只需移出队列
+   */
+  const takeNextQueuedPrompt = useCallback((): QueuedPrompt | undefined => {
+    const localPrompts = queuedPromptsRef.current.filter(p => p.source !== 'interjection');
+    const [nextPrompt, ...remainingPrompts] = localPrompts;
+    if (localPrompts.length !== queuedPromptsRef.current.length || nextPrompt) {
+      setQueuedPrompts(remainingPrompts);
+    }
+    return nextPrompt;
+  }, [queuedPromptsRef, setQueuedPrompts]);
+
   // ============================================================================
   // Main Prompt Execution Function
   // ============================================================================
@@ -260,6 +323,16 @@ export function usePromptExecution(config: UsePromptExecutionConfig): UsePromptE
         prompt,
         model
       };
+      // Codex/Gemini 运行中：作为插话交给后端，失败时退回本地队列
+      if ((executionEngine === 'codex' || executionEngine === 'gemini') && claudeSessionId && !isSlashCommandInput) {
+        try {
+          const interjection = await api.interjectRunningTask(executionEngine, claudeSessionId, prompt);
+          newPrompt.id = interjection.id;
+          newPrompt.source = 'interjection';
+        } catch (err) {
+          console.warn('[usePromptExecution] Interjection rejected, queueing locally:', err);
+        }
+      }
       setQueuedPrompts(prev => [...prev, newPrompt]);
       return;
     }
@@ -272,6 +345,8 @@ export function usePromptExecution(config: UsePromptExecutionConfig): UsePromptE
       // Record prompt sent (save Git state before sending)
       // Only record real user input, exclude auto Warmup and Skills messages
       let recordedPromptIndex = -1;
+      // 本次发送的 trace id，贯穿执行、事件、日志与自动提交
+      const traceId = crypto.randomUUID();
       const isUserInitiated = !prompt.includes('Warmup') 
         && !prompt.includes('<command-name>')
         && !prompt.includes('Launching skill:');
@@ -296,7 +371,8 @@ export function usePromptExecution(config: UsePromptExecutionConfig): UsePromptE
             recordedPromptIndex = await api.recordCodexPromptSent(
               effectiveSession.id,
               projectPath,
-              prompt
+              prompt,
+              traceId
             );
             
             if (codexPendingInfo) {
@@ -313,7 +389,8 @@ export function usePromptExecution(config: UsePromptExecutionConfig): UsePromptE
               effectiveSession.id,
               effectiveSession.project_id,
               projectPath,
-              prompt
+              prompt,
+              traceId
             );
             
           }
@@ -423,7 +500,7 @@ export function usePromptExecution(config: UsePromptExecutionConfig): UsePromptE
                 // If this is a new Codex session and prompt not yet recorded, record now
                 if (isUserInitiated && codexPendingInfo && codexPendingInfo.promptIndex === undefined) {
                   // 🔧 FIX: Store Promise to allow processCodexComplete to wait for it
-                  pendingPromptRecordingPromise = api.recordCodexPromptSent(codexThreadId, projectPath, codexPendingInfo.promptText)
+                  pendingPromptRecordingPromise = api.recordCodexPromptSent(codexThreadId, projectPath, codexPendingInfo.promptText, traceId)
                     .then((idx) => {
                       codexPendingInfo.promptIndex = idx;
                       codexPendingInfo.sessionId = codexThreadId;
@@ -431,7 +508,8 @@ export function usePromptExecution(config: UsePromptExecutionConfig): UsePromptE
                         sessionId: codexThreadId,
                         projectPath,
                         promptIndex: idx,
-                        promptText: codexPendingInfo.promptText
+                        promptText: codexPendingInfo.promptText,
+                        traceId
                       };
                     })
                     .catch(err => {
@@ -443,7 +521,8 @@ export function usePromptExecution(config: UsePromptExecutionConfig): UsePromptE
                     sessionId: codexThreadId,
                     projectPath,
                     promptIndex: codexPendingInfo.promptIndex,
-                    promptText: codexPendingInfo.promptText
+                    promptText: codexPendingInfo.promptText,
+                    traceId
                   };
                 }
               }
@@ -483,12 +562,16 @@ export function usePromptExecution(config: UsePromptExecutionConfig): UsePromptE
             if (window.__codexPendingPrompt) {
               const pendingPrompt = window.__codexPendingPrompt;
               try {
-                await api.recordCodexPromptCompleted(
+                const result = await api.recordCodexPromptCompleted(
                   pendingPrompt.sessionId,
                   pendingPrompt.projectPath,
                   pendingPrompt.promptIndex,
-                  pendingPrompt.promptText
+                  pendingPrompt.promptText,
+                  pendingPrompt.traceId
                 );
+                if (result?.snapshotError) {
+                  setError(`本条提示词的代码快照未能创建：${result.snapshotError}`);
+                }
               } catch (err) {
                 console.warn('[usePromptExecution] Failed to record Codex prompt completion:', err);
               }
@@ -499,10 +582,8 @@ export function usePromptExecution(config: UsePromptExecutionConfig): UsePromptE
             await refreshCodexRateLimitsFromHistory();
 
             // Process queued prompts
-            if (queuedPromptsRef.current.length > 0) {
-              const [nextPrompt, ...remainingPrompts] = queuedPromptsRef.current;
-              setQueuedPrompts(remainingPrompts);
-
+            const nextPrompt = takeNextQueuedPrompt();
+            if (nextPrompt) {
               setTimeout(() => {
                 handleSendPrompt(nextPrompt.prompt, nextPrompt.model);
               }, 100);
@@ -527,7 +608,7 @@ export function usePromptExecution(config: UsePromptExecutionConfig): UsePromptE
           // Helper function to process Codex errors (确保退出加载态并清理监听，避免前端“无反应”)
           const processCodexError = async (payload: string) => {
             const parsed = parseCodexErrorPayload(payload);
-            setError(parsed.message);
+            setError(await localizeExecutionError('codex', parsed.message));
             setIsLoading(false);
             hasActiveSessionRef.current = false;
             isListeningRef.current = false;
@@ -542,10 +623,8 @@ export function usePromptExecution(config: UsePromptExecutionConfig): UsePromptE
             }
 
             // 继续处理队列（与完成逻辑一致）
-            if (queuedPromptsRef.current.length > 0) {
-              const [nextPrompt, ...remainingPrompts] = queuedPromptsRef.current;
-              setQueuedPrompts(remainingPrompts);
-
+            const nextPrompt = takeNextQueuedPrompt();
+            if (nextPrompt) {
               setTimeout(() => {
                 handleSendPrompt(nextPrompt.prompt, nextPrompt.model);
               }, 100);
@@ -567,17 +646,41 @@ export function usePromptExecution(config: UsePromptExecutionConfig): UsePromptE
               await processCodexError(evt.payload);
             });
 
+            // 瞬时错误自动重试：保持加载态，仅提示用户
+            const specificRetryingUnlisten = await listen<ExecutionRetrying>(`codex-retrying:${sessionId}`, (evt) => {
+              setError(describeRetry(evt.payload));
+            });
+
+            // 输出卡死：提示并在续接时保持加载态
+            const specificStalledUnlisten = await listen<StreamStalled>(`codex-stalled:${sessionId}`, (evt) => {
+              setError(describeStall(evt.payload));
+            });
+            const specificReconnectingUnlisten = await listen<StreamReconnecting>(`codex-reconnecting:${sessionId}`, (evt) => {
+              setError(describeReconnect(evt.payload));
+            });
+
             // Replace existing listeners with session-specific ones
             unlistenRefs.current.forEach((u) => u && typeof u === 'function' && u());
-            unlistenRefs.current = [specificOutputUnlisten, specificCompleteUnlisten, specificErrorUnlisten];
+            unlistenRefs.current = [
+              specificOutputUnlisten,
+              specificCompleteUnlisten,
+              specificErrorUnlisten,
+              specificRetryingUnlisten,
+              specificStalledUnlisten,
+              specificReconnectingUnlisten,
+            ];
           };
 
           // 🔧 FIX: Listen for session init event to get session ID for channel isolation
-          const codexSessionInitUnlisten = await listen<{ type: string; session_id: string }>('codex-session-init', async (evt) => {
+          const codexSessionInitUnlisten = await listen<{ type: string; session_id: string; config_warnings?: string[] }>('codex-session-init', async (evt) => {
             // 🔧 FIX: Only process if this tab has an active session
             if (!hasActiveSessionRef.current) return;
             if (evt.payload.session_id && !currentCodexSessionId) {
               currentCodexSessionId = evt.payload.session_id;
+              // 当前 CLI 不支持而被剔除的配置项（推理强度 / 输出详略）
+              if (evt.payload.config_warnings?.length) {
+                setError(evt.payload.config_warnings.join('\n'));
+              }
               // 🔧 FIX: Set claudeSessionId to the backend channel ID for reconnection and cancellation
               // This is different from the Codex thread_id which is used for resuming sessions
               setClaudeSessionId(currentCodexSessionId);
@@ -910,12 +1013,16 @@ export function usePromptExecution(config: UsePromptExecutionConfig): UsePromptE
             if (window.__geminiPendingPrompt) {
               const pendingPrompt = window.__geminiPendingPrompt;
               try {
-                await api.recordGeminiPromptCompleted(
+                const result = await api.recordGeminiPromptCompleted(
                   pendingPrompt.sessionId,
                   pendingPrompt.projectPath,
                   pendingPrompt.promptIndex,
-                  pendingPrompt.promptText
+                  pendingPrompt.promptText,
+                  pendingPrompt.traceId
                 );
+                if (result?.snapshotError) {
+                  setError(`本条提示词的代码快照未能创建：${result.snapshotError}`);
+                }
               } catch (err) {
                 console.warn('[usePromptExecution] Failed to record Gemini prompt completion:', err);
               }
@@ -927,10 +1034,8 @@ export function usePromptExecution(config: UsePromptExecutionConfig): UsePromptE
             delete window.__geminiPendingSession;
 
             // Process queued prompts
-            if (queuedPromptsRef.current.length > 0) {
-              const [nextPrompt, ...remainingPrompts] = queuedPromptsRef.current;
-              setQueuedPrompts(remainingPrompts);
-
+            const nextPrompt = takeNextQueuedPrompt();
+            if (nextPrompt) {
               setTimeout(() => {
                 handleSendPrompt(nextPrompt.prompt, nextPrompt.model);
               }, 100);
@@ -948,9 +1053,28 @@ export function usePromptExecution(config: UsePromptExecutionConfig): UsePromptE
               await processGeminiComplete();
             });
 
+            // 瞬时错误自动重试：保持加载态，仅提示用户
+            const specificRetryingUnlisten = await listen<ExecutionRetrying>(`gemini-retrying:${sessionId}`, (evt) => {
+              setError(describeRetry(evt.payload));
+            });
+
+            // 输出卡死：提示并在续接时保持加载态
+            const specificStalledUnlisten = await listen<StreamStalled>(`gemini-stalled:${sessionId}`, (evt) => {
+              setError(describeStall(evt.payload));
+            });
+            const specificReconnectingUnlisten = await listen<StreamReconnecting>(`gemini-reconnecting:${sessionId}`, (evt) => {
+              setError(describeReconnect(evt.payload));
+            });
+
             // 🔧 FIX: Append session-specific listeners instead of replacing all
             // This preserves global listeners like geminiCliSessionIdUnlisten
-            unlistenRefs.current.push(specificOutputUnlisten, specificCompleteUnlisten);
+            unlistenRefs.current.push(
+              specificOutputUnlisten,
+              specificCompleteUnlisten,
+              specificRetryingUnlisten,
+              specificStalledUnlisten,
+              specificReconnectingUnlisten,
+            );
           };
 
           // Listen for session init event (backend emits this with backend channel ID)
@@ -983,7 +1107,7 @@ export function usePromptExecution(config: UsePromptExecutionConfig): UsePromptE
 
             // 🔧 FIX: Record prompt sent using REAL Gemini CLI session ID
             if (isUserInitiated && geminiPendingInfo && geminiPendingInfo.promptIndex === undefined) {
-              pendingGeminiPromptRecordingPromise = api.recordGeminiPromptSent(realCliSessionId, projectPath, geminiPendingInfo.promptText)
+              pendingGeminiPromptRecordingPromise = api.recordGeminiPromptSent(realCliSessionId, projectPath, geminiPendingInfo.promptText, traceId)
                 .then((idx) => {
                   geminiPendingInfo.promptIndex = idx;
                   geminiPendingInfo.sessionId = realCliSessionId;
@@ -991,7 +1115,8 @@ export function usePromptExecution(config: UsePromptExecutionConfig): UsePromptE
                     sessionId: realCliSessionId,
                     projectPath,
                     promptIndex: idx,
-                    promptText: geminiPendingInfo.promptText
+                    promptText: geminiPendingInfo.promptText,
+                    traceId
                   };
                 })
                 .catch(err => {
@@ -1024,12 +1149,13 @@ export function usePromptExecution(config: UsePromptExecutionConfig): UsePromptE
           const geminiErrorUnlisten = await listen<string>('gemini-error', (evt) => {
             if (!hasActiveSessionRef.current) return;
             console.error('[usePromptExecution] Gemini error:', evt.payload);
+            let raw = evt.payload;
             try {
-              const data = JSON.parse(evt.payload);
-              setError(data.error?.message || evt.payload);
+              raw = JSON.parse(evt.payload).error?.message || evt.payload;
             } catch {
-              setError(evt.payload);
+              /* plain text error */
             }
+            localizeExecutionError('gemini', raw).then(setError);
           });
 
           // 🔧 FIX: 移除全局完成事件监听器,避免跨会话串流
@@ -1138,7 +1264,8 @@ export function usePromptExecution(config: UsePromptExecutionConfig): UsePromptE
                         sid,
                         projectId,
                         projectPath,
-                        prompt
+                        prompt,
+                        traceId
                       );
                       hasRecordedPrompt = true;
                       
@@ -1155,7 +1282,7 @@ export function usePromptExecution(config: UsePromptExecutionConfig): UsePromptE
 
           const specificErrorUnlisten = await listen<string>(`claude-error:${sid}`, (evt) => {
             console.error('Claude error (scoped):', evt.payload);
-            setError(evt.payload);
+            localizeExecutionError('claude', evt.payload).then(setError);
           });
 
           const specificCompleteUnlisten = await listen<boolean>(`claude-complete:${sid}`, () => {
@@ -1221,8 +1348,12 @@ export function usePromptExecution(config: UsePromptExecutionConfig): UsePromptE
                 projectId,
                 projectPath,
                 recordedPromptIndex,
-                prompt
-              ).then(() => {
+                prompt,
+                traceId
+              ).then((result) => {
+                if (result?.snapshotError) {
+                  setError(`本条提示词的代码快照未能创建：${result.snapshotError}`);
+                }
               }).catch(err => {
                 console.error('[Prompt Revert] Failed to mark completed:', err);
               });
@@ -1242,10 +1373,8 @@ export function usePromptExecution(config: UsePromptExecutionConfig): UsePromptE
           // Reset currentSessionId to allow detection of new session_id
           currentSessionId = null;
           // Process queued prompts after completion
-          if (queuedPromptsRef.current.length > 0) {
-            const [nextPrompt, ...remainingPrompts] = queuedPromptsRef.current;
-            setQueuedPrompts(remainingPrompts);
-
+          const nextPrompt = takeNextQueuedPrompt();
+          if (nextPrompt) {
             // Small delay to ensure UI updates
             setTimeout(() => {
               handleSendPrompt(nextPrompt.prompt, nextPrompt.model);
@@ -1256,6 +1385,22 @@ export function usePromptExecution(config: UsePromptExecutionConfig): UsePromptE
         // Track if we've recorded the prompt for new sessions
         let hasRecordedPrompt = recordedPromptIndex >= 0;
 
+        // ====================================================================
+        // Session ID Drift
+        // ====================================================================
+        // resume 时 CLI 可能以新的 session_id 写出后续消息（fork）。后端在转发该 init
+        // 消息之前发出 session:id-changed，这里接受新 id，并把草稿与撤回面板切到新会话
+        const driftedSessionIds = new Set<string>();
+        const sessionIdChangedUnlisten = await listen<SessionIdChanged>(SESSION_ID_CHANGED_EVENT, (evt) => {
+          const { engine, oldSessionId, newSessionId, tabId } = evt.payload;
+          if (engine !== 'claude') return;
+          // 优先按标签页匹配：后端 resume 前可能已把本标签页的旧 id 解析为更新的 id
+          if (tabId ? tabId !== tabIdRef.current : oldSessionId !== currentSessionId) return;
+          driftedSessionIds.add(newSessionId);
+          migrateDraft(currentSessionId || oldSessionId, newSessionId);
+          setExtractedSessionInfo(prev => (prev ? { ...prev, sessionId: newSessionId } : prev));
+        });
+
         // ====================================================================
         // Generic Listeners (Catch-all) - FIXED to prevent cross-session data leakage
         // ====================================================================
@@ -1299,7 +1444,7 @@ export function usePromptExecution(config: UsePromptExecutionConfig): UsePromptE
             // 🔒 CRITICAL FIX #1: 使用 session_id 验证消息是否属于当前会话
             // 这是最重要的检查：如果消息包含 session_id，且我们已经有 claudeSessionId，
             // 则只处理匹配的消息（解决同一项目下多个会话的串扰问题）
-            if (msg.session_id && claudeSessionId && msg.session_id !== claudeSessionId) {
+            if (msg.session_id && claudeSessionId && msg.session_id !== claudeSessionId && !driftedSessionIds.has(msg.session_id)) {
               // 消息来自不同会话，忽略
               return;
             }
@@ -1347,7 +1492,8 @@ export function usePromptExecution(config: UsePromptExecutionConfig): UsePromptE
                         msg.session_id,
                         projectId,
                         projectPath,
-                        prompt
+                        prompt,
+                        traceId
                       );
                       hasRecordedPrompt = true;
                       
@@ -1394,7 +1540,8 @@ export function usePromptExecution(config: UsePromptExecutionConfig): UsePromptE
                       currentSessionId,
                       projectId,
                       projectPath,
-                      prompt
+                      prompt,
+                      traceId
                     );
                     hasRecordedPrompt = true;
                     
@@ -1439,7 +1586,7 @@ export function usePromptExecution(config: UsePromptExecutionConfig): UsePromptE
         });
 
         // Store the generic unlisteners for now; they may be replaced later.
-        unlistenRefs.current = [genericOutputUnlisten, genericErrorUnlisten, genericCompleteUnlisten];
+        unlistenRefs.current = [sessionIdChangedUnlisten, genericOutputUnlisten, genericErrorUnlisten, genericCompleteUnlisten];
 
         } // End of Claude Code event listener setup
 
@@ -1534,6 +1681,24 @@ export function usePromptExecution(config: UsePromptExecutionConfig): UsePromptE
 
       // Execute the appropriate command based on execution engine
       // Use processedPrompt (potentially translated) for API calls
+      const useProjectMemory = isProjectMemoryEnabled(projectPath);
+      // 发送时增强：由执行命令在后端完成增强（斜杠命令原样发送）
+      const enhancement = isSlashCommand(processedPrompt)
+        ? undefined
+        : buildEnhancementRequest(
+            executionEngine === 'claude' ? effectiveSession?.id : undefined,
+            executionEngine === 'claude' ? effectiveSession?.project_id : undefined,
+            extractFileReferences(processedPrompt)
+          );
+      if (enhancement?.fetchUrls) {
+        // 抓取失败不阻断发送，只提示哪些链接没有附加
+        const urlContextUnlisten = await listen<UrlContextFetched>('url-context-fetched', (evt) => {
+          if (evt.payload.streamId === traceId && evt.payload.failed.length > 0) {
+            setError(describeUrlFailures(evt.payload));
+          }
+        });
+        unlistenRefs.current.push(urlContextUnlisten);
+      }
       if (executionEngine === 'codex') {
         // ====================================================================
         // 🆕 Codex Execution Branch
@@ -1552,16 +1717,36 @@ export function usePromptExecution(config: UsePromptExecutionConfig): UsePromptE
               prompt: processedPrompt,
               mode: codexMode || 'read-only',
               model: codexModel || model,
-              json: true
+              profile: codexProfile,
+              reasoningEffort: codexReasoningEffort,
+              verbosity: codexVerbosity,
+              strictConfig: codexStrictConfig,
+              json: true,
+              checkContextOverflow: true,
+              useProjectMemory,
+              traceId,
+              enhancement
             });
           } catch (resumeError) {
+            // Context overflow is a deliberate refusal; resuming the last session would hit it too.
+            // A session locked by another process must not be written through the fallback either
+            if (resumeError instanceof AppCommandError && (resumeError.code === 'context_overflow' || resumeError.code === 'session_busy')) {
+              throw resumeError;
+            }
             // Fallback to resume last if specific resume fails
             await api.resumeLastCodex({
               projectPath,
               prompt: processedPrompt,
               mode: codexMode || 'read-only',
               model: codexModel || model,
-              json: true
+              profile: codexProfile,
+              reasoningEffort: codexReasoningEffort,
+              verbosity: codexVerbosity,
+              strictConfig: codexStrictConfig,
+              json: true,
+              useProjectMemory,
+              traceId,
+              enhancement
             });
           }
         } else {
@@ -1572,7 +1757,14 @@ export function usePromptExecution(config: UsePromptExecutionConfig): UsePromptE
             prompt: processedPrompt,
             mode: codexMode || 'read-only',
             model: codexModel || model,
-            json: true
+            profile: codexProfile,
+            reasoningEffort: codexReasoningEffort,
+            verbosity: codexVerbosity,
+            strictConfig: codexStrictConfig,
+            json: true,
+            useProjectMemory,
+            traceId,
+            enhancement
           });
         }
 
@@ -1586,7 +1778,8 @@ export function usePromptExecution(config: UsePromptExecutionConfig): UsePromptE
             sessionId: pendingSessionId,
             projectPath,
             promptIndex: pendingIndex,
-            promptText: prompt
+            promptText: prompt,
+            traceId
           };
         }
       } else if (executionEngine === 'gemini') {
@@ -1612,7 +1805,11 @@ export function usePromptExecution(config: UsePromptExecutionConfig): UsePromptE
           model: geminiModel || 'gemini-3-flash',
           approvalMode: geminiApprovalMode || 'auto_edit',
           sessionId: sessionId,  // 🔑 Pass session ID for resumption
-          debug: false
+          debug: false,
+          useProjectMemory,
+          traceId,
+          enhancement,
+          enableSearchGrounding: geminiSearchGrounding
         });
 
         // 🆕 Store pending prompt info for completion recording
@@ -1625,7 +1822,8 @@ export function usePromptExecution(config: UsePromptExecutionConfig): UsePromptE
             sessionId: pendingSessionId,
             projectPath,
             promptIndex: pendingIndex,
-            promptText: prompt
+            promptText: prompt,
+            traceId
           };
         }
 
@@ -1640,16 +1838,20 @@ export function usePromptExecution(config: UsePromptExecutionConfig): UsePromptE
         if (effectiveSession && !isFirstPrompt) {
           // Resume existing session
           try {
-            await api.resumeClaudeCode(projectPath, effectiveSession.id, processedPrompt, model, currentPlanMode, maxThinkingTokens, tabId);
+            await api.resumeClaudeCode(projectPath, effectiveSession.id, processedPrompt, model, currentPlanMode, maxThinkingTokens, tabId, useProjectMemory, traceId, enhancement);
           } catch (resumeError) {
+            // 会话正被另一个进程使用时不能回退到 continue，否则会写入同一个会话
+            if (parseSessionInUseError(resumeError)) {
+              throw resumeError;
+            }
             console.warn('[usePromptExecution] Resume failed, falling back to continue mode:', resumeError);
             // Fallback to continue mode if resume fails
-            await api.continueClaudeCode(projectPath, processedPrompt, model, currentPlanMode, maxThinkingTokens, tabId);
+            await api.continueClaudeCode(projectPath, processedPrompt, model, currentPlanMode, maxThinkingTokens, tabId, useProjectMemory, traceId, enhancement);
           }
         } else {
           // Start new session
           setIsFirstPrompt(false);
-          await api.executeClaudeCode(projectPath, processedPrompt, model, currentPlanMode, maxThinkingTokens, tabId);
+          await api.executeClaudeCode(projectPath, processedPrompt, model, currentPlanMode, maxThinkingTokens, tabId, useProjectMemory, traceId, enhancement);
         }
       }
 
@@ -1658,7 +1860,10 @@ export function usePromptExecution(config: UsePromptExecutionConfig): UsePromptE
       // 7️⃣ Error Handling
       // ========================================================================
       console.error("Failed to send prompt:", err);
-      setError("发送提示失败");
+      setError(
+        parseSessionInUseError(err)?.message
+          ?? (err instanceof AppCommandError ? describeCommandError(err) : "发送提示失败")
+      );
       setIsLoading(false);
       hasActiveSessionRef.current = false;
       // Reset session state on error
@@ -1676,8 +1881,13 @@ export function usePromptExecution(config: UsePromptExecutionConfig): UsePromptE
     executionEngine,  // 🆕 Codex/Gemini integration
     codexMode,        // 🆕 Codex integration
     codexModel,       // 🆕 Codex integration
+    codexProfile,
+    codexReasoningEffort,
+    codexVerbosity,
+    codexStrictConfig,
     geminiModel,      // 🆕 Gemini integration
     geminiApprovalMode, // 🆕 Gemini integration
+    geminiSearchGrounding,
     hasActiveSessionRef,
     unlistenRefs,
     isMountedRef,
@@ -1694,7 +1904,8 @@ export function usePromptExecution(config: UsePromptExecutionConfig): UsePromptE
     setIsFirstPrompt,
     processMessageWithTranslation,
     refreshCodexRateLimitsFromHistory,
-    updateCodexRateLimits
+    updateCodexRateLimits,
+    takeNextQueuedPrompt
   ]);
 
   // ============================================================================
@@ -13,7 +13,7 @@
 
 import { useCallback, useEffect, useRef } from 'react';
 import { listen, type UnlistenFn } from '@tauri-apps/api/event';
-import { api, type Session } from '@/lib/api';
+import { api, type CorruptedLine, type Session } from '@/lib/api';
 import { normalizeUsageData } from '@/lib/utils';
 import type { ClaudeStreamMessage } from '@/types/claude';
 import type { CodexRateLimits } from '@/types/codex';
@@ -65,6 +65,11 @@ interface UseSessionStreamConfig {
   setClaudeSessionId: (sessionId: string) => void;
   setCodexRateLimits?: React.Dispatch<React.SetStateAction<CodexRateLimits | null>>;
 
+  /**
+   * Claude 会话加载时跳过的损坏行
+   */
+  setCorruptedLines?: (lines: CorruptedLine[]) => void;
+
   /**
    * 翻译初始化（兼容 useSessionLifecycle，当前禁用）
    */
@@ -122,6 +127,7 @@ export function useSessionStream(config: UseSessionStreamConfig): UseSessionStre
     setRawJsonlOutput,
     setClaudeSessionId,
     setCodexRateLimits,
+    setCorruptedLines,
     processMessageWithTranslation,
     onSessionNotFound,
   } = config;
@@ -181,23 +187,24 @@ export function useSessionStream(config: UseSessionStreamConfig): UseSessionStre
           console.error('[useSessionStream] Failed to load Gemini session:', err);
           throw err;
         }
+      } else if (engine === 'claude') {
+        const claudeHistory = await api.loadClaudeSessionHistory(session.id, session.project_id);
+        history = claudeHistory.messages;
+        setCorruptedLines?.(claudeHistory.corrupted_lines);
       } else {
-        // Claude/Codex
         history = await api.loadSessionHistory(session.id, session.project_id, engine);
 
         // Codex 消息需要转换
-        if (engine === 'codex') {
-          codexConverter.reset();
-          const converted: ClaudeStreamMessage[] = [];
-          for (const event of history) {
-            const msg = codexConverter.convertEventObject(event);
-            if (msg) converted.push(msg);
-          }
-          history = converted;
+        codexConverter.reset();
+        const converted: ClaudeStreamMessage[] = [];
+        for (const event of history) {
+          const msg = codexConverter.convertEventObject(event);
+          if (msg) converted.push(msg);
+        }
+        history = converted;
 
-          if (setCodexRateLimits) {
-            setCodexRateLimits(codexConverter.getRateLimits());
-          }
+        if (setCodexRateLimits) {
+          setCodexRateLimits(codexConverter.getRateLimits());
         }
       }
 
@@ -310,6 +317,7 @@ export function useSessionStream(config: UseSessionStreamConfig): UseSessionStre
     setMessages,
     setRawJsonlOutput,
     setCodexRateLimits,
+    setCorruptedLines,
     onSessionNotFound,
   ]);
 
@@ -1,5 +1,5 @@
 import { useState, useCallback, useRef, useContext, createContext, ReactNode, useEffect } from 'react';
-import type { Session } from '@/lib/api';
+import { api, type Session } from '@/lib/api';
 import { createSessionWindow, emitWindowSyncEvent, onWindowSyncEvent, isSessionWindow } from '@/lib/windowManager';
 
 /**
@@ -142,6 +142,24 @@ export const TabProvider: React.FC<TabProviderProps> = ({ children }) => {
     }
   }, [tabs, activeTabId]);
 
+  // 记录活动标签页所属项目的当前会话与草稿，重新打开该项目时据此恢复（见 ViewRouter）
+  // 只提交这两个字段，面板与 uiState 等由后端保留已保存的值
+  const activeTab = tabs.find(tab => tab.id === activeTabId);
+  const activeProjectPath = activeTab?.projectPath || activeTab?.session?.project_path;
+  const activeSessionId = activeTab?.session?.id;
+  const activeProjectId = activeTab?.session?.project_id;
+  const activeEngine = activeTab?.engine || activeTab?.session?.engine || 'claude';
+  useEffect(() => {
+    if (!activeProjectPath || isSessionWindow()) return;
+    api.saveProjectWorkspaceState(activeProjectPath, {
+      currentSession: activeSessionId
+        ? { engine: activeEngine, sessionId: activeSessionId, projectId: activeProjectId }
+        : null,
+      // 与 useDraftPersistence 的存储 key 对应：新会话的草稿保存在 global 下
+      draftId: activeSessionId ?? 'global',
+    }).catch(() => {});
+  }, [activeProjectPath, activeSessionId, activeProjectId, activeEngine]);
+
   // ✨ REFACTORED: Compute TabSession with isActive (simplified)
   const tabsWithActive: TabSession[] = tabs.map(tab => ({
     ...tab,
@@ -1,6 +1,7 @@
 import i18n from 'i18next';
 import { initReactI18next } from 'react-i18next';
 import LanguageDetector from 'i18next-browser-languagedetector';
+import { api } from '@/lib/api';
 
 // Import language resources
 import en from './locales/en.json';
@@ -38,4 +39,11 @@ i18n
     },
   });
 
+// Backend messages (errors, rewind warnings, provider results) follow the UI language
+const syncBackendLanguage = (language: string) => {
+  api.setBackendLocale(language).catch(() => {});
+};
+syncBackendLanguage(i18n.language);
+i18n.on('languageChanged', syncBackendLanguage);
+
 export default i18n;
@@ -49,6 +49,8 @@ export interface Session {
   created_at: number;
   /** First user message content (if available) */
   first_message?: string;
+  /** Codex only: where first_message came from when no user text was found */
+  first_message_source?: import('@/types/codex').CodexFirstMessageSource;
   /** Timestamp of the first user message (if available) */
   message_timestamp?: string;
   /** Timestamp of the last message in the session (if available) - ISO string */
@@ -60,4108 +62,8293 @@ export interface Session {
 }
 
 /**
- * Session conversion source information
+ * Diagnostics report for bug reports (secrets in `config` are redacted)
  */
-export interface ConversionSource {
-  /** Source engine type: "claude" | "codex" */
-  engine: string;
-  /** Source session ID */
-  sessionId: string;
-  /** Conversion timestamp (ISO 8601) */
-  convertedAt: string;
-  /** Source project path */
-  sourceProjectPath: string;
+export interface DiagnosticsReport {
+  generatedAt: string;
+  appVersion: string;
+  environment: { os: string; arch: string; is_wsl: boolean; is_container: boolean; distro?: string };
+  wsl: {
+    available: boolean;
+    distros: string[];
+    defaultDistro?: string;
+    engines: { engine: string; enabled: boolean; distro?: string; pathInWsl?: string }[];
+  };
+  binaries: { tool: string; path?: string; version?: string; source?: string; error?: string }[];
+  acemcp: { sidecarPath?: string; extracted: boolean; runs: boolean; error?: string };
+  config: Record<string, any>;
+  sessions: { claude: number; codex: number; gemini: number };
 }
 
-/**
- * Session conversion result
- */
-export interface ConversionResult {
-  /** Whether conversion succeeded */
-  success: boolean;
-  /** New generated session ID */
-  newSessionId: string;
-  /** Target engine type */
-  targetEngine: string;
-  /** Number of messages converted */
-  messageCount: number;
-  /** Conversion source information */
-  source: ConversionSource;
-  /** Target file path */
-  targetPath: string;
-  /** Error message if conversion failed */
-  error?: string;
-}
+export type BinaryTool = "claude" | "codex" | "gemini";
+
+/** Binary source label used for detection priority */
+export type BinaryOrigin = "path" | "nvm" | "volta" | "bun" | "homebrew" | "custom";
 
 /**
- * Represents the settings from ~/.claude/settings.json
+ * Per-tool binary detection settings (stored in ~/.claude/binaries.json)
  */
-export interface ClaudeSettings {
-  [key: string]: any;
+export interface BinaryDetectionSettings {
+  /** Origins in preference order; unlisted origins come last */
+  sourcePriority: BinaryOrigin[];
+  /** Path prefixes excluded from detection */
+  blockedPaths: string[];
 }
 
-/**
- * Permission mode for Claude execution
- */
-export enum PermissionMode {
-  Interactive = "Interactive",
-  AcceptEdits = "AcceptEdits",
-  ReadOnly = "ReadOnly",
-  Plan = "Plan",
+export interface BinaryCandidate {
+  path: string;
+  version: string | null;
+  /** How the candidate was found (env:XXX / PATH / common-path / user-config ...) */
+  source: string;
+  origin: BinaryOrigin;
 }
 
 /**
- * Permission configuration for Claude execution
+ * All detected candidates for a tool, in selection order (the first one is used)
  */
-export interface ClaudePermissionConfig {
-  allowed_tools: string[];
-  disallowed_tools: string[];
-  permission_mode: PermissionMode;
-  auto_approve_edits: boolean;
-  enable_dangerous_skip: boolean;
+export interface BinaryDetection {
+  tool: BinaryTool;
+  candidates: BinaryCandidate[];
+  /** Unix seconds */
+  detectedAt: number;
+  fingerprint: string;
 }
 
 /**
- * Output format for Claude execution
+ * Application log file status
  */
-export enum OutputFormat {
-  StreamJson = "StreamJson",
-  Json = "Json",
-  Text = "Text",
+export interface LogFileInfo {
+  /** Current log file */
+  path: string;
+  directory: string;
+  sizeBytes: number;
+  /** Including rotated files */
+  totalSizeBytes: number;
+  fileCount: number;
+  level: LogLevel;
+  maxFileBytes: number;
+  maxFiles: number;
 }
 
+export type LogLevel = 'off' | 'error' | 'warn' | 'info' | 'debug' | 'trace';
+
 /**
- * Claude execution configuration
+ * Result of cancelling a CLI run (payload of `<engine>-cancelled` events)
  */
-export interface ClaudeExecutionConfig {
-  output_format: OutputFormat;
-  timeout_seconds: number | null;
-  max_tokens: number | null;
-  max_thinking_tokens: number | null;
-  verbose: boolean;
-  permissions: ClaudePermissionConfig;
-  disable_rewind_git_operations: boolean;
+export interface CancelOutcome {
+  engine: 'claude' | 'codex' | 'gemini';
+  sessionId: string;
+  pid?: number;
+  /** graceful: exited after the interrupt signal; forced: killed after the timeout */
+  mode: 'graceful' | 'forced';
+  /** Session file check after cancel, absent when the CLI had not created one */
+  sessionFile?: { path: string; parseable: boolean; truncatedBytes: number };
 }
 
 /**
- * Represents the Claude Code version status
+ * Config layer, lowest to highest priority
  */
-export interface ClaudeVersionStatus {
-  /** Whether Claude Code is installed and working */
-  is_installed: boolean;
-  /** The version string if available */
-  version?: string;
-  /** The full output from the command */
-  output: string;
-}
+export type ConfigLayer = 'default' | 'user' | 'project' | 'projectLocal';
 
 /**
- * Represents a CLAUDE.md file found in the project
+ * A resolved config value and the layer it came from (secrets are redacted)
  */
-export interface ClaudeMdFile {
-  /** Relative path from the project root */
-  relative_path: string;
-  /** Absolute path to the file */
-  absolute_path: string;
-  /** File size in bytes */
-  size: number;
-  /** Last modified timestamp */
-  modified: number;
+export interface EffectiveValue {
+  /** Nested keys are joined with "." */
+  key: string;
+  value: any;
+  source: ConfigLayer;
+  path?: string;
+  /** Lower-priority layers overridden by this value */
+  overrides: ConfigLayer[];
 }
 
-/**
- * Represents a file or directory entry
- */
-export interface FileEntry {
-  name: string;
-  path: string;
-  is_directory: boolean;
-  size: number;
-  extension?: string;
+export interface EffectiveConfig {
+  projectPath?: string;
+  subsystems: {
+    name: string;
+    files: string[];
+    values: EffectiveValue[];
+    errors: string[];
+  }[];
 }
 
 /**
- * Rewind mode for reverting prompts
+ * Subagent or skill template (content keeps the {{name}} placeholder for preview)
  */
-export type RewindMode = "conversation_only" | "code_only" | "both";
+export interface ExtensionTemplate {
+  id: string;
+  description?: string;
+  model?: string;
+  source: 'builtin' | 'local';
+  content: string;
+}
 
 /**
- * Capabilities for rewinding a specific prompt
+ * A reversible operation on the global undo stack
  */
-export interface RewindCapabilities {
-  /** Can revert conversation (always true) */
-  conversation: boolean;
-  /** Can revert code (true if has git_commit_before) */
-  code: boolean;
-  /** Can revert both (true if has git_commit_before) */
-  both: boolean;
-  /** Warning message if code revert is not available */
-  warning?: string;
-  /** Prompt source indicator */
-  source: "project" | "cli";
+export interface UndoOperation {
+  id: string;
+  kind: string;
+  /** Human-readable description, e.g. "删除模板 reviewer" */
+  description: string;
+  /** Unix timestamp (seconds) */
+  createdAt: number;
 }
 
 /**
- * Information about the safety of a git reset operation
- * Used to warn users when reverting might lose commits from other engines or user manual commits
+ * Optional fields replacing a template's frontmatter values
  */
-export interface ResetSafetyInfo {
-  /** Number of commits that will be lost */
-  commitsToLose: number;
-  /** Whether there are commits from other engines (Claude/Codex/Gemini) */
-  hasOtherEngineCommits: boolean;
-  /** Whether there are user manual commits */
-  hasUserCommits: boolean;
-  /** List of commit summaries that will be lost (max 10) */
-  commitsSummary: string[];
-  /** Whether it's safe to proceed without warning */
-  safeToProceed: boolean;
-  /** Warning message if not safe */
-  warning: string | null;
+export interface TemplateOverrides {
+  description?: string;
+  model?: string;
 }
 
 /**
- * A record of a user prompt
+ * Deduplicated user prompt aggregated across Claude / Codex / Gemini sessions
  */
-export interface PromptRecord {
-  /** Index of this prompt (0, 1, 2...) */
-  index: number;
-  /** The prompt text user entered */
+export interface PromptHistoryEntry {
+  /** Hash of the normalized prompt text, used by resendPrompt */
+  id: string;
+  /** Prompt text, truncated when too long */
   text: string;
-  /** Git commit before sending this prompt */
-  gitCommitBefore: string;
-  /** Git commit after AI completed (optional) */
-  gitCommitAfter?: string;
-  /** Timestamp when prompt was sent */
+  truncated: boolean;
+  engine: 'claude' | 'codex' | 'gemini';
+  sessionId: string;
+  projectPath: string;
+  /** Unix seconds of the latest occurrence */
   timestamp: number;
-  /** Prompt source: "project" (from project interface) or "cli" (from CLI) */
-  source: string;
+  occurrences: number;
 }
 
-
-// Usage Dashboard types
-export interface UsageEntry {
-  project: string;
-  timestamp: string;
-  model: string;
-  input_tokens: number;
-  output_tokens: number;
-  cache_write_tokens: number;
-  cache_read_tokens: number;
-  cost: number;
+export interface PromptModifications {
+  /** Edited full text replacing the original prompt */
+  text?: string;
+  /** Extra note appended to the prompt */
+  append?: string;
 }
 
-export interface ModelUsage {
-  model: string;
-  total_cost: number;
-  total_tokens: number;
-  input_tokens: number;
-  output_tokens: number;
-  cache_creation_tokens: number;
-  cache_read_tokens: number;
-  session_count: number;
+export interface BatchPromptInput {
+  prompt: string;
+  /** Per-prompt mode override (Claude: plan/default, Codex: execution mode, Gemini: approval mode) */
+  mode?: string;
+  model?: string;
 }
 
-export interface DailyUsage {
-  date: string;
-  total_cost: number;
-  total_tokens: number;
-  models_used: string[];
+export interface BatchOptions {
+  projectPath: string;
+  /** New session per prompt, or all prompts in one session */
+  by?: 'newSession' | 'sameSession';
+  /** Existing session to continue when by = sameSession */
+  sessionId?: string;
+  model?: string;
+  mode?: string;
 }
 
-export interface ProjectUsage {
-  project_path: string;
-  project_name: string;
-  total_cost: number;
-  total_tokens: number;
-  session_count: number;
-  last_used: string;
-}
+export type BatchItemStatus = 'pending' | 'running' | 'succeeded' | 'failed' | 'cancelled';
+export type BatchStatus = 'ready' | 'running' | 'paused' | 'completed' | 'failed' | 'cancelled';
 
-export interface ApiBaseUrlUsage {
-  api_base_url: string;
-  total_cost: number;
-  total_tokens: number;
-  input_tokens: number;
-  output_tokens: number;
-  cache_creation_tokens: number;
-  cache_read_tokens: number;
-  session_count: number;
+export interface BatchItem {
+  index: number;
+  prompt: string;
+  mode?: string;
+  model?: string;
+  status: BatchItemStatus;
+  startedAt?: string;
+  durationMs?: number;
+  sessionId?: string;
+  summary?: string;
+  error?: string;
 }
 
-export interface UsageStats {
-  total_cost: number;
-  total_tokens: number;
-  total_input_tokens: number;
-  total_output_tokens: number;
-  total_cache_creation_tokens: number;
-  total_cache_read_tokens: number;
-  total_sessions: number;
-  by_model: ModelUsage[];
-  by_date: DailyUsage[];
-  by_project: ProjectUsage[];
-  by_api_base_url?: ApiBaseUrlUsage[];
+/**
+ * Sequentially executed prompt batch (persisted in ~/.anycode/prompt_batches.json)
+ */
+export interface PromptBatch {
+  id: string;
+  engine: 'claude' | 'codex' | 'gemini';
+  options: BatchOptions;
+  items: BatchItem[];
+  status: BatchStatus;
+  stopOnFailure: boolean;
+  sessionId?: string;
+  createdAt: string;
+  updatedAt: string;
 }
 
-export interface UsageOverview {
-  total_cost: number;
-  total_sessions: number;
-  total_tokens: number;
-  today_cost: number;
-  week_cost: number;
-  top_model?: string;
-  top_project?: string;
+/**
+ * Kind of a project memory entry
+ * - fact: project background (stack, dependencies, environment)
+ * - decision: a decision that was made and why
+ * - convention: a rule to follow (naming, style, workflow)
+ */
+export type MemoryKind = 'fact' | 'decision' | 'convention';
+
+/**
+ * Project memory entry (stored in <project>/.anycode/memory.json)
+ */
+export interface MemoryEntry {
+  id: string;
+  kind: MemoryKind;
+  content: string;
+  /** Creation time (UTC Unix seconds) */
+  createdAt: number;
+  /** Session the entry was extracted from */
+  sourceSession?: string;
+  sourceEngine?: 'claude' | 'codex' | 'gemini';
 }
 
-export interface SessionCacheTokens {
-  session_id: string;
-  total_cache_creation_tokens: number;
-  total_cache_read_tokens: number;
+/**
+ * A new memory entry, or a candidate extracted from a session
+ */
+export interface MemoryEntryInput {
+  kind: MemoryKind;
+  content: string;
+  sourceSession?: string;
+  sourceEngine?: 'claude' | 'codex' | 'gemini';
 }
 
 /**
- * Provider configuration for API switching
+ * Options for initializing a project's AI workflow (omitted fields use the defaults)
  */
-export interface ProviderConfig {
-  id: string;
-  name: string;
-  description: string;
-  base_url: string;
-  auth_token?: string;
-  api_key?: string;
-  api_key_helper?: string;
+export interface ProjectSetupOptions {
+  /** Generate CLAUDE.md from a template (default true) */
+  claudeMd?: boolean;
+  /** CLAUDE.md template id (default "default") */
+  claudeMdTemplate?: string;
+  /** Create .claude/{commands,agents,skills} (default true) */
+  claudeDirs?: boolean;
+  /** Append CLAUDE.local.md and .anycode/ to .gitignore (default true) */
+  gitignore?: boolean;
+  /** Trigger acemcp pre-indexing (default true) */
+  preindex?: boolean;
+  /** Send a bootstrap prompt to start the first session (default false) */
+  bootstrapSession?: boolean;
+  engine?: 'claude' | 'codex' | 'gemini';
   model?: string;
-  enable_auto_api_key_helper?: boolean;
+  /** Only report what would be done */
+  dryRun?: boolean;
+}
+
+export type SetupStep = 'claudeMd' | 'claudeDirs' | 'gitignore' | 'preindex' | 'bootstrapSession';
+
+export type SetupStepStatus = 'applied' | 'planned' | 'skipped' | 'failed';
+
+export interface SetupStepResult {
+  step: SetupStep;
+  status: SetupStepStatus;
+  detail: string;
+  /** Files or directories the step touches */
+  paths: string[];
+}
+
+export interface ProjectSetupReport {
+  projectPath: string;
+  dryRun: boolean;
+  steps: SetupStepResult[];
 }
 
 /**
- * Current provider configuration from environment variables
+ * Session that was open when the project was last used
  */
-export interface CurrentProviderConfig {
-  anthropic_base_url?: string;
-  anthropic_auth_token?: string;
-  anthropic_api_key?: string;
-  anthropic_api_key_helper?: string;
-  anthropic_model?: string;
+export interface WorkspaceSession {
+  engine: 'claude' | 'codex' | 'gemini';
+  sessionId: string;
+  /** Claude only: directory name under ~/.claude/projects */
+  projectId?: string | null;
 }
 
 /**
- * API Key usage information
+ * Per-project working state, restored when the project is opened again
  */
-export interface ApiKeyUsage {
-  /** Total balance in USD */
-  total_balance: number;
-  /** Used balance in USD */
-  used_balance: number;
-  /** Remaining balance in USD */
-  remaining_balance: number;
-  /** Whether the balance is unlimited */
-  is_unlimited: boolean;
-  /** Access expiration timestamp (0 means never expires) */
-  access_until: number;
-  /** Query start date */
-  query_start_date: string;
-  /** Query end date */
-  query_end_date: string;
+export interface ProjectWorkspaceState {
+  currentSession?: WorkspaceSession | null;
+  /** Storage id of the prompt input draft */
+  draftId?: string | null;
+  /** Sidebar panel that was selected */
+  activePanel?: string | null;
+  rewindPanelExpanded?: boolean;
+  /** Opaque frontend state (scroll positions etc.), at most 64 KB */
+  uiState?: unknown;
+  /** Unix seconds, set by the backend */
+  updatedAt?: number;
+}
+
+export interface RestoredWorkspaceState extends ProjectWorkspaceState {
+  /** Why currentSession was cleared (session deleted or moved to the trash) */
+  sessionClearedReason?: string | null;
 }
 
 /**
- * Codex provider configuration for OpenAI Codex API switching
+ * CLAUDE.md template (built-in or from <app data>/templates/claude-md)
  */
-export interface CodexProviderConfig {
+export interface ClaudeMdTemplate {
   id: string;
-  name: string;
-  description?: string;
-  websiteUrl?: string;
-  category?: 'official' | 'cn_official' | 'aggregator' | 'third_party' | 'custom';
-  auth: Record<string, any>; // 写入 ~/.codex/auth.json
-  config: string; // 写入 ~/.codex/config.toml（TOML 字符串）
-  isOfficial?: boolean;
-  isPartner?: boolean;
-  createdAt?: number;
+  source: 'builtin' | 'local';
+  content: string;
 }
 
 /**
- * Current Codex provider configuration from ~/.codex directory
+ * Global protected paths settings (~/.anycode/protected_paths.json)
  */
-export interface CurrentCodexConfig {
-  auth: Record<string, any>; // ~/.codex/auth.json 内容
-  config: string; // ~/.codex/config.toml 内容
-  apiKey?: string; // 从 auth 中提取的 API Key
-  baseUrl?: string; // 从 config 中提取的 Base URL
-  model?: string; // 从 config 中提取的模型名称
+export interface ProtectedPathsSettings {
+  /** Master switch: no prompt constraint and no post-task check when off */
+  enabled: boolean;
+  /** Glob patterns applied to every project */
+  defaultPatterns: string[];
+  /** Append the protected list to the system prompt / prompt on execution */
+  injectIntoPrompt: boolean;
 }
 
 /**
- * Gemini provider configuration for Gemini API switching
+ * Protected paths of a project (<project>/.anycode/protected-paths.json)
  */
-export interface GeminiProviderConfig {
-  id: string;
-  name: string;
-  description?: string;
-  websiteUrl?: string;
-  category?: 'official' | 'third_party' | 'custom';
-  env: Record<string, string>; // 环境变量，写入 ~/.gemini/.env
-  isOfficial?: boolean;
-  isPartner?: boolean;
-  createdAt?: number;
+export interface ProtectedPathsInfo {
+  projectPatterns: string[];
+  defaultPatterns: string[];
+  /** Global defaults + project patterns (empty when the master switch is off) */
+  effectivePatterns: string[];
+  enabled: boolean;
+  configPath: string;
+}
+
+export type ProtectedChangeKind = 'modified' | 'deleted' | 'created';
+
+export interface ProtectedChange {
+  /** Path relative to the project root, "/" separated */
+  path: string;
+  kind: ProtectedChangeKind;
 }
 
 /**
- * Current Gemini provider configuration from ~/.gemini directory
+ * Protected files changed by a prompt ("protected-paths-violation" event)
  */
-export interface CurrentGeminiProviderConfig {
-  env: Record<string, string>; // ~/.gemini/.env 内容
-  settings: Record<string, any>; // ~/.gemini/settings.json 内容
-  apiKey?: string; // 从 env 中提取的 API Key
-  baseUrl?: string; // 从 env 中提取的 Base URL
-  model?: string; // 从 env 中提取的模型
-  selectedAuthType?: string; // 认证类型
+export interface ProtectedViolation {
+  engine: 'claude' | 'codex' | 'gemini';
+  sessionId: string;
+  promptIndex: number;
+  projectPath: string;
+  changes: ProtectedChange[];
+  /** UTC Unix seconds */
+  detectedAt: number;
+  traceId?: string;
 }
 
-// ============================================================================
-// MCP 多应用支持类型定义（新版）
-// ============================================================================
-
 /**
- * MCP 服务器规范（实际配置）
+ * Result of restoring protected files to their state before the prompt
  */
-export interface MCPServerSpec {
-  /** 传输类型：stdio/http/sse */
-  type?: "stdio" | "http" | "sse";
-  /** 命令（stdio 类型） */
-  command?: string;
-  /** 命令参数 */
-  args?: string[];
-  /** 环境变量 */
-  env?: Record<string, string>;
-  /** 工作目录（stdio 类型） */
-  cwd?: string;
-  /** URL（http/sse 类型） */
-  url?: string;
-  /** 请求头（http/sse 类型） */
-  headers?: Record<string, string>;
+export interface ProtectedRestore {
+  /** UTC Unix seconds */
+  restoredAt: number;
+  restored: string[];
+  /** Files too large to keep in the snapshot, which could not be restored */
+  skipped: string[];
+  /** Commit created for the restore (absent when rewind git operations are disabled) */
+  commit?: string;
 }
 
 /**
- * MCP 应用启用状态
+ * Kind of local data a migration rewrites
  */
-export interface McpApps {
-  /** 是否在 Claude 中启用 */
-  claude: boolean;
-  /** 是否在 Codex 中启用 */
-  codex: boolean;
-  /** 是否在 Gemini 中启用 */
-  gemini: boolean;
-}
+export type MigrationDataCategory = 'config' | 'gitRecords' | 'mcpRegistry';
 
 /**
- * MCP 服务器（统一结构）
+ * A registered data migration and whether it has been applied
  */
-export interface McpServer {
-  /** 服务器 ID */
+export interface MigrationInfo {
   id: string;
-  /** 显示名称 */
-  name: string;
-  /** 服务器配置 */
-  server: MCPServerSpec;
-  /** 应用启用状态 */
-  apps: McpApps;
-  /** 描述 */
-  description?: string;
-  /** 主页 */
-  homepage?: string;
-  /** 文档链接 */
-  docs?: string;
-  /** 标签 */
-  tags?: string[];
+  description: string;
+  categories: MigrationDataCategory[];
+  /** UTC Unix seconds; null when not applied yet */
+  appliedAt?: number | null;
+  /** Summary recorded when the migration was applied */
+  detail?: string | null;
+}
+
+export interface MigrationStatus {
+  /** All migrations in execution order */
+  migrations: MigrationInfo[];
+  /** IDs of the migrations not applied yet, in order */
+  pending: string[];
+}
+
+export interface MigrationStepResult {
+  id: string;
+  description: string;
+  status: 'applied' | 'planned' | 'failed';
+  detail: string;
+}
+
+export interface MigrationRunReport {
+  dryRun: boolean;
+  /** Steps handled by this run; already applied migrations are not listed */
+  steps: MigrationStepResult[];
+  /** ID of the failed step; the following steps were not run */
+  stoppedAt?: string | null;
 }
 
 /**
- * MCP 状态
+ * How a related session is linked to the session it is annotated on
+ * - review_of: this session reviewed a reply from the related session
+ * - reviewed_by: a reply in this session was reviewed by the related session
  */
-export interface McpStatus {
-  /** 用户配置文件路径 */
-  user_config_path: string;
-  /** 配置文件是否存在 */
-  user_config_exists: boolean;
-  /** 服务器数量 */
-  server_count: number;
+export type SessionRelation = 'review_of' | 'reviewed_by';
+
+export interface RelatedSession {
+  engine: 'claude' | 'codex' | 'gemini';
+  sessionId: string;
+  projectPath: string;
+  relation: SessionRelation;
+  /** Link creation time (UTC Unix seconds) */
+  createdAt: number;
 }
 
 /**
- * 带启用状态的 MCP 服务器条目
+ * Annotations stored for a session outside its session file
  */
-export interface McpServerWithStatus {
-  /** 服务器 ID */
-  id: string;
-  /** 服务器配置 */
-  spec: MCPServerSpec;
-  /** 是否启用 */
-  enabled: boolean;
+export interface SessionAnnotation {
+  relatedSessions: RelatedSession[];
+  /** Enhancement metadata keyed by prompt index */
+  promptEnhancements: Record<number, PromptEnhancementInfo>;
+  /** Regenerated reply versions keyed by prompt index */
+  responseAlternatives: Record<number, ResponseAlternative[]>;
 }
 
-// ============================================================================
-// 旧版 MCP 类型（兼容性保留，后续可删除）
-// ============================================================================
-
 /**
- * @deprecated 使用 McpServer 代替
+ * A regenerated reply version stored in a forked session
  */
-export interface MCPServer {
-  name: string;
-  transport: string;
-  command?: string;
-  args: string[];
-  env: Record<string, string>;
-  url?: string;
-  scope: string;
-  is_active: boolean;
-  status: ServerStatus;
+export interface ResponseAlternative {
+  sessionId: string;
+  /** UTC Unix seconds */
+  createdAt: number;
 }
 
 /**
- * @deprecated
+ * Options for regenerating a reply
  */
-export interface ServerStatus {
-  running: boolean;
-  error?: string;
-  last_checked?: number;
+export interface RegenerateOptions {
+  projectPath: string;
+  model?: string;
+  /** Engine-specific mode: "plan"/"default" for Claude, execution mode for Codex */
+  mode?: string;
 }
 
 /**
- * MCP configuration for project scope (.mcp.json)
+ * Summary of one reply version of a prompt
  */
-export interface MCPProjectConfig {
-  mcpServers: Record<string, MCPServerConfig>;
+export interface ResponseVersion {
+  sessionId: string;
+  /** The main session; after adopting, it holds the adopted version */
+  isMainline: boolean;
+  /** UTC Unix seconds, null for the main session */
+  createdAt: number | null;
+  /** Reply text, truncated when long */
+  replyPreview: string;
+  /** Assistant messages in the reply; 0 while still generating or after a failure */
+  messageCount: number;
+  /** Whether the session file still exists */
+  available: boolean;
 }
 
 /**
- * Individual server configuration in .mcp.json
+ * Enhancement metadata of a prompt that was enhanced as part of its execution
  */
-export interface MCPServerConfig {
-  command: string;
-  args: string[];
-  env: Record<string, string>;
+export interface PromptEnhancementInfo {
+  traceId: string;
+  /** Projects searched for context */
+  projectPaths: string[];
+  /** Paths of the injected snippets, in order of appearance */
+  snippetPaths: string[];
+  /** Estimated tokens of the injected context */
+  contextTokens: number;
+  /** Whether a cached enhancement result was reused */
+  cacheHit: boolean;
+  partial: boolean;
+  /** Set when enhancement failed and the original prompt was sent (fail open) */
+  error?: string | null;
+  /** URLs whose page content was appended */
+  fetchedUrls?: string[];
+  /** URLs that could not be prefetched */
+  failedUrls?: FailedUrl[];
+  /** Snapshots of the files the prompt referenced when it was sent */
+  fileSnapshots?: { projectPath: string; files: unknown[] } | null;
+  /** UTC Unix seconds */
+  createdAt: number;
 }
 
+/**
+ * How a referenced file compares with the version captured when the prompt was sent
+ */
+export type FileSnapshotStatus = 'unchanged' | 'modified' | 'deleted';
 
+/**
+ * A file referenced (@) by a prompt, as captured when the prompt was sent
+ */
+export interface PromptFileSnapshotEntry {
+  id: string;
+  /** Path as referenced in the prompt */
+  path: string;
+  /** SHA-256 of the content at send time */
+  hash: string;
+  size: number;
+  /** Whether a content copy was kept (files up to 256KB); otherwise only the hash is known */
+  hasContent: boolean;
+  status: FileSnapshotStatus;
+}
 
 /**
- * Result of saving clipboard image
+ * Snapshot content next to the current content of a referenced file
  */
-export interface SavedImageResult {
-  success: boolean;
-  file_path?: string;
-  error?: string;
+export interface FileSnapshotComparison {
+  id: string;
+  path: string;
+  status: FileSnapshotStatus;
+  /** Null when only the hash was recorded */
+  snapshotContent: string | null;
+  /** Null when the file was deleted, is larger than 256KB or is not text */
+  currentContent: string | null;
 }
 
 /**
- * Result of adding a server
+ * Cross-engine review settings (~/.anycode/cross_engine_review.json)
  */
-export interface AddServerResult {
-  success: boolean;
-  message: string;
-  server_name?: string;
+export interface CrossEngineReviewConfig {
+  /** Framing template; placeholders: {source_engine} {instruction} {content} */
+  template: string;
+}
+
+export interface CrossEngineReviewOptions {
+  /** Project the review runs in */
+  projectPath: string;
+  /** Locates the source session: Claude project ID (defaults to projectPath) */
+  sourceProject?: string;
+  /** Resume this session of the target engine instead of starting a new one */
+  targetSessionId?: string;
+  model?: string;
+  mode?: string;
+}
+
+export interface CrossEngineReviewResult {
+  reviewId: string;
+  targetEngine: 'claude' | 'codex' | 'gemini';
+  /** Known up front only when resuming; otherwise sent with the cross-engine-review-linked event */
+  targetSessionId?: string | null;
+  /** Whether the reply was compressed to fit the target context */
+  compressed: boolean;
+  originalTokens: number;
+  sentTokens: number;
+  notice?: string | null;
 }
 
 /**
- * Translation configuration interface
+ * A supplementary instruction sent to a running Codex/Gemini task.
+ * Queued until the current turn ends, then sent as the next turn of the same session.
  */
-export interface TranslationConfig {
-  enabled: boolean;
-  api_base_url: string;
-  api_key: string;
-  model: string;
-  timeout_seconds: number;
-  cache_ttl_seconds: number;
+export interface Interjection {
+  id: string;
+  engine: 'codex' | 'gemini';
+  /** Channel ID of the running task */
+  sessionId: string;
+  text: string;
+  status: 'queued' | 'dispatched' | 'discarded' | 'failed';
+  createdAt: string;
+  error?: string | null;
 }
 
 /**
- * Translation cache statistics
+ * A fenced code block found in a session's assistant replies
  */
-export interface TranslationCacheStats {
-  total_entries: number;
-  expired_entries: number;
-  active_entries: number;
+export interface CodeBlock {
+  /** "{messageIndex}-{block index}" */
+  id: string;
+  language?: string | null;
+  content: string;
+  /** Index of the assistant reply (0-based) */
+  messageIndex: number;
+  /** Target file name mentioned by the model around the block */
+  suggestedFilename?: string | null;
+}
+
+export interface CodeBlockFilters {
+  /** Only keep these languages (aliases such as rs/rust match) */
+  languages?: string[];
+  /** Keep only the newest block for identical content (default true) */
+  dedupe?: boolean;
 }
 
+export type CodeBlockOverwritePolicy = 'skip' | 'overwrite' | 'rename';
+
+export interface SavedCodeBlock {
+  id: string;
+  path?: string | null;
+  status: 'written' | 'overwritten' | 'renamed' | 'skipped' | 'failed';
+  error?: string | null;
+}
+
+export interface SaveCodeBlocksReport {
+  targetDir: string;
+  items: SavedCodeBlock[];
+  written: number;
+  skipped: number;
+  failed: number;
+}
 
 /**
- * Auto-compact configuration
+ * Payload of the cross-engine-review-linked event
  */
-export interface AutoCompactConfig {
-  /** Enable automatic compaction */
+export interface CrossEngineReviewLinked {
+  reviewId: string;
+  targetEngine: 'claude' | 'codex' | 'gemini';
+  targetSessionId?: string | null;
+  success: boolean;
+  error?: string | null;
+}
+
+/**
+ * Auto-retry settings for Codex/Gemini runs that fail on transient provider errors
+ * (~/.anycode/execution_retry.json)
+ */
+export interface ExecutionRetryConfig {
   enabled: boolean;
-  /** Maximum context tokens before triggering compaction */
-  max_context_tokens: number;
-  /** Threshold percentage to trigger compaction (0.0-1.0) */
-  compaction_threshold: number;
-  /** Minimum time between compactions in seconds */
-  min_compaction_interval: number;
-  /** Strategy for compaction */
-  compaction_strategy: CompactionStrategy;
-  /** Whether to preserve recent messages */
-  preserve_recent_messages: boolean;
-  /** Number of recent messages to preserve */
-  preserve_message_count: number;
-  /** Custom compaction instructions */
-  custom_instructions?: string;
+  /** Total attempts including the first run */
+  maxAttempts: number;
+  /** Wait before the first retry; doubles for each further retry */
+  initialBackoffMs: number;
+  maxBackoffMs: number;
+  /** Only failures within this many seconds of starting are retried */
+  quickExitSecs: number;
 }
 
 /**
- * Compaction strategies
+ * Stalled-stream detection for Codex/Gemini runs (~/.anycode/stream_stall.json)
  */
-export type CompactionStrategy =
-  | 'Smart'
-  | 'Aggressive'
-  | 'Conservative'
-  | { Custom: string };
+export interface StreamStallConfig {
+  /** Interrupt a stalled run and resume its session; when off, only warn */
+  autoRecover: boolean;
+  /** Seconds without output (and no tool running) before a run counts as stalled */
+  stallThresholdSecs: number;
+  /** Seconds to wait for output to resume after the stall warning */
+  wakeGraceSecs: number;
+  /** Resumes allowed per run */
+  maxReconnects: number;
+}
 
 /**
- * Session context information
+ * Sleep prevention while Codex/Gemini runs are in progress
+ * (~/.anycode/power_inhibit.json)
  */
-export interface SessionContext {
-  session_id: string;
-  project_path: string;
-  current_tokens: number;
-  message_count: number;
-  last_compaction?: string; // ISO timestamp
-  compaction_count: number;
-  model: string;
-  status: SessionStatus;
+export interface PowerInhibitConfig {
+  enabled: boolean;
+  /** Only block system sleep; let the display turn off as usual */
+  allowDisplaySleep: boolean;
 }
 
 /**
- * Session status
+ * A run currently holding the sleep inhibition
  */
-export type SessionStatus =
-  | 'Active'
-  | 'Idle'
-  | 'Compacting'
-  | { CompactionFailed: string };
+export interface PowerInhibitHolder {
+  engine: 'codex' | 'gemini';
+  sessionId: string;
+  traceId?: string | null;
+  projectPath: string;
+  /** Unix seconds */
+  since: number;
+}
 
 /**
- * Auto-compact status information
+ * Current sleep inhibition state
  */
-export interface AutoCompactStatus {
+export interface PowerInhibitionStatus {
   enabled: boolean;
-  is_monitoring: boolean;
-  sessions_count: number;
-  total_compactions: number;
-  max_context_tokens: number;
-  compaction_threshold: number;
+  /** Whether the OS-level inhibition is currently held */
+  active: boolean;
+  allowDisplaySleep: boolean;
+  holders: PowerInhibitHolder[];
+  /** Why the last attempt to inhibit sleep failed */
+  lastError?: string | null;
+}
+
+/**
+ * CPU / memory thresholds for running Codex/Gemini process trees
+ * (~/.anycode/task_resource_monitor.json)
+ */
+export interface TaskResourceConfig {
+  enabled: boolean;
+  /** CPU threshold as a percentage of one core */
+  cpuThresholdPercent: number;
+  /** How long CPU must stay above the threshold before warning */
+  cpuSustainedMinutes: number;
+  memoryThresholdMb: number;
+}
+
+/**
+ * One process in a task's process tree
+ */
+export interface ProcessSnapshot {
+  pid: number;
+  parentPid?: number | null;
+  name: string;
+  /** Empty when the command line cannot be read */
+  commandLine: string;
+  cpuPercent: number;
+  memoryBytes: number;
+  /** Unix seconds */
+  startTime: number;
+}
+
+/**
+ * Resource usage of a task's process tree (the CLI process comes first)
+ */
+export interface TaskResourceUsage {
+  /** Sum over the tree, as a percentage of one core (may exceed 100) */
+  cpuPercent: number;
+  memoryBytes: number;
+  processes: ProcessSnapshot[];
+  /** Unix seconds */
+  sampledAt: number;
+}
+
+/**
+ * A running Codex/Gemini task with its latest resource sample
+ */
+export interface RunningAiTask {
+  engine: 'codex' | 'gemini';
+  sessionId: string;
+  cliSessionId?: string | null;
+  pid: number;
+  projectPath?: string | null;
+  /** Null until sampled, while monitoring is off, or when the process cannot be read */
+  resources?: TaskResourceUsage | null;
+}
+
+/**
+ * Payload of the "task:resource-warning" event
+ */
+export interface TaskResourceWarning {
+  engine: 'codex' | 'gemini';
+  sessionId: string;
+  kind: 'cpu' | 'memory';
+  /** Unix seconds when usage first exceeded the threshold */
+  since: number;
+  usage: TaskResourceUsage;
+}
+
+/**
+ * Client-side rate limit for one provider, matched by the origin of its base URL
+ */
+export interface ProviderRateLimit {
+  baseUrl: string;
+  /** Requests allowed in flight at once; unset means unlimited */
+  maxConcurrentRequests?: number | null;
+  /** Requests allowed per minute; unset means unlimited */
+  requestsPerMinute?: number | null;
+}
+
+/**
+ * Per-provider rate limits (~/.anycode/provider_rate_limits.json)
+ */
+export interface RateLimitConfig {
+  /** Seconds a request may wait in the queue before failing as rate limited */
+  maxWaitSecs: number;
+  providers: ProviderRateLimit[];
+}
+
+/**
+ * Live state of one provider's limiter
+ */
+export interface ProviderLimiterStatus {
+  provider: string;
+  maxConcurrentRequests?: number | null;
+  requestsPerMinute?: number | null;
+  /** Reduced rate in effect after a 429 response */
+  throttledRequestsPerMinute?: number | null;
+  inFlight: number;
+  queued: number;
+  requestsLastMinute: number;
+  /** Seconds left before queued requests may be sent again */
+  backoffRemainingSecs: number;
+}
+
+/**
+ * Payload of the `provider-rate-limited` event
+ */
+export interface ProviderRateLimitedEvent {
+  provider: string;
+  retryAfterSecs: number;
+  throttledRequestsPerMinute: number;
+}
+
+export type BackupCategory = 'sessions' | 'gitRecords' | 'appData' | 'config';
+
+/**
+ * Scheduled backup settings (~/.anycode/backup.json)
+ */
+export interface BackupConfig {
+  /** Run backups on schedule; manual backups work either way */
+  enabled: boolean;
+  /** Backups go to <targetDir>/anycode-backups/<date> */
+  targetDir?: string | null;
+  frequency: 'daily' | 'weekly';
+  categories: BackupCategory[];
+  /** Number of most recent backups to keep */
+  keepCount: number;
+}
+
+/**
+ * A complete backup in the target directory
+ */
+export interface BackupInfo {
+  id: string;
+  /** Unix seconds */
+  createdAt: number;
+  trigger: 'manual' | 'scheduled';
+  categories: BackupCategory[];
+  fileCount: number;
+  totalBytes: number;
+  path: string;
+}
+
+/**
+ * Result of one backup run
+ */
+export interface BackupResult {
+  backup: BackupInfo;
+  /** Files copied in this run; the rest are reused from the previous backup */
+  copiedFiles: number;
+  linkedFiles: number;
+  copiedBytes: number;
+  /** Old backups removed by the keep count */
+  removedBackups: string[];
+  errors: string[];
+}
+
+/**
+ * Payload of the `backup-progress` event
+ */
+export interface BackupProgress {
+  backupId: string;
+  phase: 'scanning' | 'copying' | 'cleanup';
+  processed: number;
+  total: number;
+}
+
+/**
+ * Payload of the `backup-finished` event
+ */
+export interface BackupFinished {
+  trigger: 'manual' | 'scheduled';
+  result?: BackupResult | null;
+  error?: string | null;
+}
+
+/**
+ * Result of restoring categories from a backup
+ */
+export interface RestoreResult {
+  backupId: string;
+  restoredFiles: number;
+  unchangedFiles: number;
+  /** Existing files that differ from the backup but were kept because overwriting was not requested */
+  skippedExisting: number;
+  /** Existing files moved aside before being overwritten */
+  movedToSafety: number;
+  safetyDir?: string | null;
+  errors: string[];
+}
+
+/**
+ * Emitted when a resumed session continues under a new session ID
+ */
+export const SESSION_ID_CHANGED_EVENT = 'session:id-changed';
+
+/**
+ * Payload of the session:id-changed event
+ */
+export interface SessionIdChanged {
+  engine: 'claude' | 'codex' | 'gemini';
+  oldSessionId: string;
+  newSessionId: string;
+  projectPath: string;
+  /** Tab that started the run, when known */
+  tabId?: string | null;
+}
+
+/**
+ * Emitted after a run completes or a session is rewound, truncated or deleted;
+ * session lists and details should be refetched
+ */
+export const SESSIONS_INVALIDATED_EVENT = 'sessions:invalidated';
+
+/**
+ * Payload of the sessions:invalidated event
+ */
+export interface SessionsInvalidated {
+  engine: 'claude' | 'codex' | 'gemini';
+  /** Null when a new session has not reported its CLI session ID yet */
+  sessionId: string | null;
+  projectPath: string | null;
+}
+
+/**
+ * Error prefix returned while encrypted storage is enabled but not unlocked yet
+ */
+export const STORAGE_LOCKED_ERROR_PREFIX = 'STORAGE_LOCKED';
+
+/**
+ * State of the at-rest encryption for trash/archive files
+ */
+export interface EncryptedStorageStatus {
+  /** New trash/archive files are encrypted */
+  enabled: boolean;
+  /** A passphrase has been set before (existing encrypted files still need it) */
+  configured: boolean;
+  /** The passphrase was entered in this app session */
+  unlocked: boolean;
+}
+
+/**
+ * Result of encrypting existing trash files
+ */
+export interface EncryptionMigrationReport {
+  encryptedFiles: number;
+  /** Already encrypted files and plaintext indexes */
+  skippedFiles: number;
+  errors: string[];
+}
+
+/**
+ * Payload of the codex-retrying / gemini-retrying events
+ */
+export interface ExecutionRetrying {
+  sessionId: string;
+  engine: 'codex' | 'gemini';
+  /** The attempt about to start */
+  attempt: number;
+  maxAttempts: number;
+  delayMs: number;
+  reason: string;
+  traceId?: string;
+}
+
+/**
+ * Payload of the codex-stalled / gemini-stalled events
+ */
+export interface StreamStalled {
+  sessionId: string;
+  engine: 'codex' | 'gemini';
+  idleSecs: number;
+  /** Whether the run will be resumed if no output arrives within graceSecs */
+  willReconnect: boolean;
+  graceSecs?: number;
+  traceId?: string;
+}
+
+/**
+ * Payload of the codex-reconnecting / gemini-reconnecting events
+ */
+export interface StreamReconnecting {
+  sessionId: string;
+  engine: 'codex' | 'gemini';
+  /** The resume about to start (1-based) */
+  reconnect: number;
+  maxReconnects: number;
+  idleSecs: number;
+  reason: string;
+  traceId?: string;
+}
+
+/**
+ * One stage recorded under a trace id (~/.anycode/traces/<traceId>/events.jsonl)
+ */
+export interface TraceTimelineEntry {
+  traceId: string;
+  timestampMs: number;
+  /** execution_started | process_spawned | session_bound | retrying | reconnecting | execution_finished | prompt_recorded | git_snapshot | task_action */
+  stage: string;
+  engine?: string;
+  sessionId?: string;
+  status?: string;
+  detail?: Record<string, unknown>;
+  /** Milliseconds since the first event of the trace */
+  elapsedMs: number;
+  /** Milliseconds since the previous event */
+  sincePreviousMs: number;
 }
 
-/**
- * Import result for multiple servers
- */
-export interface ImportResult {
-  imported_count: number;
-  failed_count: number;
-  servers: ImportServerResult[];
-}
+/**
+ * Commit produced by an auto-commit under a trace id
+ */
+export interface TraceCommit {
+  engine?: string;
+  sessionId?: string;
+  promptIndex?: number;
+  commit: string;
+  /** false when there was nothing to commit and HEAD was unchanged */
+  committed: boolean;
+}
+
+/**
+ * Full timeline of one user action, returned by get_trace_timeline
+ */
+export interface TraceTimeline {
+  traceId: string;
+  logDir: string;
+  startedAtMs?: number;
+  finishedAtMs?: number;
+  durationMs?: number;
+  status?: string;
+  events: TraceTimelineEntry[];
+  commits: TraceCommit[];
+}
+
+export interface ResendPromptResult {
+  engine: string;
+  projectPath: string;
+  sessionId?: string;
+  prompt: string;
+}
+
+/**
+ * Background acemcp pre-indexing status of a project
+ */
+export type PreindexStatus = 'Indexing' | 'Completed' | 'Failed' | 'Cancelled';
+
+/**
+ * Last known pre-indexing status of a project
+ */
+export interface PreindexStatusInfo {
+  status: PreindexStatus;
+  /** Failure reason, only set when status is "Failed" */
+  error?: string | null;
+}
+
+/**
+ * Stage of a background pre-index run, reported by "acemcp-preindex-progress" events
+ */
+export type PreindexStage = 'started' | 'indexing' | 'completed' | 'failed' | 'cancelled';
+
+/**
+ * Payload of the acemcp-preindex-progress event
+ */
+export interface PreindexProgress {
+  projectPath: string;
+  stage: PreindexStage;
+  /** Project files covered by the index; reported on completion, null above the watch limit */
+  fileCount: number | null;
+  /** Time since the pre-index started */
+  elapsedMs: number;
+  error: string | null;
+}
+
+/**
+ * How changed files were reindexed by acemcp
+ * - Incremental: only the changed files were reindexed
+ * - Full: the sidecar has no incremental indexing, the whole project was synced
+ * - Skipped: nothing to index, or a pre-index was already running
+ */
+export type ReindexMode = 'Incremental' | 'Full' | 'Skipped';
+
+/**
+ * Enhances the prompt with acemcp context inside an execute command (enhance and run in one call)
+ */
+export interface EnhancementRequest {
+  /** Projects to search; defaults to the execution project, several merge cross-repo context */
+  projectPaths?: string[];
+  /** Maximum context length in characters (default 3000) */
+  maxContextLength?: number;
+  enableMultiRound?: boolean;
+  searchDeadlineSecs?: number;
+  /** Session and project used to build history-aware queries (single project only); default to the resumed Claude session */
+  sessionId?: string;
+  projectId?: string;
+  /** Continue with the original prompt when enhancement fails (default true) */
+  failOpen?: boolean;
+  /** Search acemcp for project context (default true) */
+  projectContext?: boolean;
+  /** Prefetch URLs referenced in the prompt and append the page content (default false) */
+  fetchUrls?: boolean;
+  /** Snapshot the files referenced with @ before sending (default true) */
+  fileSnapshots?: boolean;
+  /** @ references parsed by the frontend; parsed from the prompt when omitted. Their context snippets are left out */
+  fileReferences?: string[];
+}
+
+/**
+ * URL prefetch settings (~/.anycode/url_fetch.json)
+ */
+export interface UrlFetchConfig {
+  /** Domains (including subdomains) allowed to be fetched; empty means any */
+  allowedDomains: string[];
+  /** Total token budget of the page content appended to a prompt */
+  tokenBudget: number;
+}
+
+/**
+ * Per-call overrides of the URL prefetch settings
+ */
+export interface UrlContextOptions {
+  tokenBudget?: number;
+  allowedDomains?: string[];
+}
+
+/**
+ * A page fetched for the prompt context
+ */
+export interface FetchedUrl {
+  url: string;
+  /** Final address after redirects */
+  finalUrl: string;
+  title?: string | null;
+  /** Page text appended to the prompt, truncated to the budget */
+  content: string;
+  tokens: number;
+  truncated: boolean;
+}
+
+/**
+ * A URL that could not be fetched and why
+ */
+export interface FailedUrl {
+  url: string;
+  reason: string;
+}
+
+/**
+ * Result of fetchUrlContext
+ */
+export interface UrlContextResult {
+  fetched: FetchedUrl[];
+  failed: FailedUrl[];
+  /** Text appended to the prompt; empty when no page was fetched */
+  context: string;
+  contextTokens: number;
+}
+
+/**
+ * URLs prefetched while enhancing a prompt ("url-context-fetched" event)
+ */
+export interface UrlContextFetched {
+  /** The trace ID of the execution */
+  streamId: string;
+  fetched: string[];
+  failed: FailedUrl[];
+}
+
+/**
+ * A new unique snippet found during multi-round acemcp search ("acemcp-context-snippet" event)
+ */
+export interface AcemcpContextSnippet {
+  /** The streamId passed to enhancePromptWithContext (the trace ID for enhancements run by execute commands) */
+  streamId: string | null;
+  projectPath: string;
+  /** Search round the snippet came from (1-based) */
+  round: number;
+  /** Position of the snippet within this enhancement (0-based) */
+  index: number;
+  snippet: string;
+}
+
+/**
+ * A code snippet from an acemcp context search
+ */
+export interface ContextSnippet {
+  /** Source file as reported by the sidecar */
+  path: string;
+  /** 1-based line range; null when the sidecar output has no line numbers */
+  startLine: number | null;
+  endLine: number | null;
+  text: string;
+  /** Relevance score; null when the sidecar does not report one */
+  score: number | null;
+}
+
+/**
+ * Keywords acemcp extracts from a prompt
+ */
+export interface ExtractedKeywords {
+  /** All keywords joined into one query */
+  combined: string;
+  /** Standalone keywords searched in separate rounds */
+  individual: string[];
+  chinese: string[];
+  english: string[];
+}
+
+/**
+ * Result of previewPromptKeywords
+ */
+export interface PromptKeywordsPreview {
+  keywords: ExtractedKeywords;
+  /** Search queries in round order */
+  queries: string[];
+}
+
+/**
+ * User-defined keyword lists merged with acemcp's built-in lists
+ */
+export interface AcemcpKeywordConfig {
+  /** Extra short technical terms (e.g. "isr", "dma") */
+  abbreviations: string[];
+  /** Words never used as keywords; also suppresses built-in abbreviations and Chinese words */
+  stopwords: string[];
+  /** Extra Chinese technical terms (e.g. "寄存器") */
+  chinese_words: string[];
+}
+
+/**
+ * Represents a Claude subagent (sidechain) session stored in an agent-*.jsonl file
+ */
+export interface SessionAgent {
+  /** The agent ID (from the file name) */
+  agent_id: string;
+  /** Absolute path of the agent-*.jsonl file */
+  agent_file: string;
+  /** The Task tool_use ID in the main session that spawned this agent */
+  parent_tool_use_id?: string;
+  /** Number of message entries in the agent file */
+  message_count: number;
+  /** Timestamp of the first message (ISO string) */
+  started_at?: string;
+  /** Timestamp of the last message (ISO string) */
+  ended_at?: string;
+  /** The first user message of the agent, i.e. the task it was given */
+  task_description?: string;
+}
+
+/**
+ * Session conversion source information
+ */
+export interface ConversionSource {
+  /** Source engine type: "claude" | "codex" */
+  engine: string;
+  /** Source session ID */
+  sessionId: string;
+  /** Conversion timestamp (ISO 8601) */
+  convertedAt: string;
+  /** Source project path */
+  sourceProjectPath: string;
+}
+
+/**
+ * A session file line that could not be parsed cleanly
+ */
+export interface CorruptedLine {
+  /** 1-based line number */
+  line: number;
+  kind: 'invalid_json' | 'invalid_utf8';
+  /** Parsed after lossy UTF-8 decoding and kept; other corrupted lines are skipped */
+  recovered: boolean;
+}
+
+/**
+ * Claude session history with the corrupted lines skipped while loading
+ */
+export interface SessionHistory {
+  messages: any[];
+  corrupted_lines: CorruptedLine[];
+}
+
+/**
+ * Result of repairing a Claude session file
+ */
+export interface SessionRepairReport {
+  corrupted_lines: CorruptedLine[];
+  /** Backup of the original file (null when nothing needed repairing) */
+  backup_path: string | null;
+}
+
+/**
+ * Session conversion result
+ */
+export interface ConversionResult {
+  /** Whether conversion succeeded */
+  success: boolean;
+  /** New generated session ID */
+  newSessionId: string;
+  /** Target engine type */
+  targetEngine: string;
+  /** Number of messages converted */
+  messageCount: number;
+  /** Conversion source information */
+  source: ConversionSource;
+  /** Target file path */
+  targetPath: string;
+  /** Error message if conversion failed */
+  error?: string;
+  /** Whether this was a dry run (no target file written) */
+  dryRun?: boolean;
+  /** Corrupted lines of the source session that were skipped (or kept after lossy decoding) */
+  corruptedLines?: CorruptedLine[];
+}
+
+/**
+ * Options for convertSessionsBatch
+ */
+export interface BatchConversionOptions {
+  /** Only preview the conversion, no files are written */
+  dryRun?: boolean;
+  /** Convert even when the target already has a conversion of the same source session */
+  force?: boolean;
+  /** Sessions converted in parallel (1 or 2, default 1) */
+  concurrency?: number;
+  /** Batch ID used for progress events and cancelConversion */
+  batchId?: string;
+}
+
+export type BatchItemStatus = 'converted' | 'skipped' | 'failed' | 'cancelled';
+
+/**
+ * Result of one session in a batch conversion
+ */
+export interface BatchConversionItem {
+  sessionId: string;
+  status: BatchItemStatus;
+  result?: ConversionResult;
+  /** Failure or skip reason */
+  error?: string;
+}
+
+/**
+ * Summary report returned by convertSessionsBatch
+ */
+export interface BatchConversionReport {
+  batchId: string;
+  targetEngine: string;
+  dryRun: boolean;
+  total: number;
+  converted: number;
+  skipped: number;
+  failed: number;
+  /** Whether the batch was cancelled */
+  cancelled: boolean;
+  /** Per-session results in input order */
+  items: BatchConversionItem[];
+}
+
+/**
+ * Batch conversion progress (payload of the `conversion:batch-progress` event, sent per session)
+ */
+export interface BatchConversionProgress {
+  batchId: string;
+  sessionId: string;
+  status: BatchItemStatus;
+  error?: string;
+  /** Sessions processed so far, including this one */
+  completed: number;
+  total: number;
+}
+
+/**
+ * Session conversion progress (payload of the `conversion:progress` event)
+ */
+export interface ConversionProgress {
+  /** Conversion ID passed to convertSession */
+  conversionId: string;
+  /** Source session ID */
+  sessionId: string;
+  /** Number of source messages processed */
+  processed: number;
+  /** Estimated total source messages (exact once done) */
+  estimatedTotal: number;
+  /** Whether all messages have been processed */
+  done: boolean;
+}
+
+/**
+ * A reasoning block of a session (Claude thinking / Codex reasoning)
+ */
+export interface ReasoningBlock {
+  text: string;
+  /** Timestamp of the message containing the block (ISO 8601) */
+  timestamp?: string | null;
+  /** Index of the closest preceding user prompt; null if before the first prompt */
+  promptIndex?: number | null;
+  /** Line number in the session file (0-based) */
+  lineNumber: number;
+}
+
+/**
+ * Represents the settings from ~/.claude/settings.json
+ */
+export interface ClaudeSettings {
+  [key: string]: any;
+}
+
+/**
+ * Permission mode for Claude execution
+ */
+export enum PermissionMode {
+  Interactive = "Interactive",
+  AcceptEdits = "AcceptEdits",
+  ReadOnly = "ReadOnly",
+  Plan = "Plan",
+}
+
+/**
+ * Permission configuration for Claude execution
+ */
+export interface ClaudePermissionConfig {
+  allowed_tools: string[];
+  disallowed_tools: string[];
+  permission_mode: PermissionMode;
+  auto_approve_edits: boolean;
+  enable_dangerous_skip: boolean;
+}
+
+/**
+ * Output format for Claude execution
+ */
+export enum OutputFormat {
+  StreamJson = "StreamJson",
+  Json = "Json",
+  Text = "Text",
+}
+
+/**
+ * Claude execution configuration
+ */
+export interface ClaudeExecutionConfig {
+  output_format: OutputFormat;
+  timeout_seconds: number | null;
+  max_tokens: number | null;
+  max_thinking_tokens: number | null;
+  verbose: boolean;
+  permissions: ClaudePermissionConfig;
+  disable_rewind_git_operations: boolean;
+  /** Commit with a one-off "AnyCode <anycode@local>" identity when git user.name/email are missing */
+  git_identity_fallback?: boolean;
+}
+
+/**
+ * Result of marking a prompt completed (post-prompt code snapshot status)
+ */
+export interface PromptCompletionResult {
+  snapshotCreated: boolean;
+  /** Why the code snapshot could not be created */
+  snapshotError?: string;
+  commitAfter?: string;
+}
+
+/**
+ * Represents the Claude Code version status
+ */
+export interface ClaudeVersionStatus {
+  /** Whether Claude Code is installed and working */
+  is_installed: boolean;
+  /** The version string if available */
+  version?: string;
+  /** The full output from the command */
+  output: string;
+}
+
+/**
+ * Represents a CLAUDE.md file found in the project
+ */
+export interface ClaudeMdFile {
+  /** Relative path from the project root */
+  relative_path: string;
+  /** Absolute path to the file */
+  absolute_path: string;
+  /** File size in bytes */
+  size: number;
+  /** Last modified timestamp */
+  modified: number;
+}
+
+/**
+ * Represents a file or directory entry
+ */
+export interface FileEntry {
+  name: string;
+  path: string;
+  is_directory: boolean;
+  size: number;
+  extension?: string;
+}
+
+/**
+ * Rewind mode for reverting prompts
+ */
+export type RewindMode = "conversation_only" | "code_only" | "both";
+
+/**
+ * Capabilities for rewinding a specific prompt
+ */
+export interface RewindCapabilities {
+  /** Can revert conversation (always true) */
+  conversation: boolean;
+  /** Can revert code (true if has git_commit_before) */
+  code: boolean;
+  /** Can revert both (true if has git_commit_before) */
+  both: boolean;
+  /** Warning message if code revert is not available */
+  warning?: string;
+  /** Prompt source indicator */
+  source: "project" | "cli";
+}
+
+/**
+ * Skill message markers used when counting prompts (~/.anycode/prompt_filter.json)
+ */
+export interface PromptFilterConfig {
+  /** Substrings marking a user message as a skill message (excluded from prompt indices) */
+  skillMarkers: string[];
+}
+
+/**
+ * Result of re-deriving prompt sources for a legacy/converted session
+ */
+export interface ReclassifyResult {
+  /** Prompt indices upgraded from "cli" to "project" */
+  reclassified: number[];
+  projectCount: number;
+  total: number;
+}
+
+/**
+ * Information about the safety of a git reset operation
+ * Used to warn users when reverting might lose commits from other engines or user manual commits
+ */
+export interface ResetSafetyInfo {
+  /** Number of commits that will be lost */
+  commitsToLose: number;
+  /** Whether there are commits from other engines (Claude/Codex/Gemini) */
+  hasOtherEngineCommits: boolean;
+  /** Whether there are user manual commits */
+  hasUserCommits: boolean;
+  /** List of commit summaries that will be lost (max 10) */
+  commitsSummary: string[];
+  /** Whether it's safe to proceed without warning */
+  safeToProceed: boolean;
+  /** Warning message if not safe */
+  warning: string | null;
+}
+
+/**
+ * Prefix of the error returned when a session file cannot be found for any engine
+ */
+export const SESSION_NOT_FOUND_ERROR_PREFIX = "NotFound";
+
+/**
+ * Prefix of the error returned when ~/.gemini/settings.json kept changing during an update
+ */
+export const GEMINI_SETTINGS_CONFLICT_ERROR_PREFIX = "SettingsConflict";
+
+/**
+ * Disk usage of a session file, as returned by listSessionsBySize
+ */
+export interface SessionSize {
+  sessionId: string;
+  /** Claude project ID, Codex session cwd, or Gemini project hash */
+  projectId?: string | null;
+  filePath: string;
+  sizeBytes: number;
+  /** JSONL record count for Claude/Codex, message count for Gemini */
+  messageCount: number;
+  /** Last modification time of the session file (Unix seconds) */
+  lastUpdated: number;
+}
+
+/**
+ * Parsed form of the placeholder string left in a session message for a tool result moved to
+ * attachments/<session>/<hash>.blob (see parseBlobRef). Fetch the full value with api.getMessageBlob(..., $ref)
+ */
+export interface BlobRef {
+  /** blob://<sha256> */
+  $ref: string;
+  /** Size of the stored value in bytes */
+  size: number;
+  /** First 200 characters of the tool result */
+  preview: string;
+}
+
+/**
+ * Result of optimizeSessionStorage
+ */
+export interface SessionStorageReport {
+  originalBytes: number;
+  optimizedBytes: number;
+  /** Number of tool results moved to blob attachments */
+  externalizedCount: number;
+  /** Backup of the original file (null when nothing was externalized) */
+  backupPath: string | null;
+}
+
+/**
+ * How a code revert handles conflicts
+ * - abort: roll back and report the conflict (default)
+ * - interactive: leave the revert in progress so conflicts can be resolved hunk by hunk
+ */
+export type RevertConflictStrategy = "abort" | "interactive";
+
+/**
+ * Prefix of the revert error returned when an interactive revert stopped on conflicts
+ */
+export const REVERT_CONFLICT_ERROR_PREFIX = "REVERT_CONFLICT";
+
+/**
+ * Prefix of the error returned when another process holds the session file lock
+ */
+export const SESSION_IN_USE_ERROR_PREFIX = "SESSION_IN_USE";
+
+/**
+ * Holder recorded in a session lock file
+ */
+export interface SessionLockHolder {
+  pid: number;
+  /** Unix seconds */
+  acquiredAt: number;
+  /** Last heartbeat, Unix seconds */
+  updatedAt: number;
+  /** Operation holding the lock, e.g. "claude resume" */
+  purpose: string;
+  token: string;
+}
+
+/**
+ * Details attached to a SESSION_IN_USE error
+ */
+export interface SessionInUse {
+  sessionFile: string;
+  lockFile: string;
+  /** Null when the lock file could not be read */
+  holder?: SessionLockHolder | null;
+  /** Localized message to show to the user */
+  message: string;
+}
+
+/**
+ * Stable error codes returned by execution and provider commands
+ */
+export type AppErrorCode =
+  | "auth_failed"
+  | "rate_limited"
+  | "quota_exceeded"
+  | "model_not_found"
+  | "network_timeout"
+  | "network_error"
+  | "service_unavailable"
+  | "binary_not_found"
+  | "session_busy"
+  | "session_read_only"
+  | "context_overflow"
+  | "git_dirty"
+  | "invalid_argument"
+  | "unknown";
+
+/**
+ * Action the UI can offer for a command error
+ */
+export type SuggestedAction =
+  | "check_api_key"
+  | "wait_and_retry"
+  | "check_billing"
+  | "choose_model"
+  | "check_network"
+  | "retry"
+  | "install_cli"
+  | "wait_for_session"
+  | "compact_session"
+  | "commit_changes";
+
+/**
+ * Structured error serialized by the backend
+ */
+export interface AppErrorPayload {
+  code: AppErrorCode;
+  /** Explanation in the app language; the raw text for unknown errors */
+  message: string;
+  /** Raw error text (CLI stderr, HTTP body, SESSION_IN_USE payload, ...) */
+  details?: string | null;
+  retryable: boolean;
+  suggestedAction?: SuggestedAction | null;
+}
+
+/**
+ * Error thrown by API wrappers of commands that return structured errors
+ *
+ * `String(error)` yields the localized message, so callers that only display
+ * the error keep working unchanged
+ */
+export class AppCommandError extends Error {
+  readonly code: AppErrorCode;
+  readonly details: string | null;
+  readonly retryable: boolean;
+  readonly suggestedAction: SuggestedAction | null;
+
+  constructor(payload: AppErrorPayload) {
+    super(payload.message);
+    this.name = "AppCommandError";
+    this.code = payload.code;
+    this.details = payload.details ?? null;
+    this.retryable = payload.retryable;
+    this.suggestedAction = payload.suggestedAction ?? null;
+  }
+
+  toString(): string {
+    return this.message;
+  }
+}
+
+function isAppErrorPayload(error: unknown): error is AppErrorPayload {
+  return (
+    typeof error === "object" &&
+    error !== null &&
+    typeof (error as AppErrorPayload).code === "string" &&
+    typeof (error as AppErrorPayload).message === "string"
+  );
+}
+
+/**
+ * Convert a structured backend error into an AppCommandError, passing other errors through
+ */
+export function toCommandError(error: unknown): unknown {
+  return isAppErrorPayload(error) && !(error instanceof Error) ? new AppCommandError(error) : error;
+}
+
+/**
+ * Raw error text, including protocol prefixes such as SESSION_IN_USE
+ */
+export function rawErrorText(error: unknown): string {
+  if (error instanceof AppCommandError) {
+    return error.details ?? error.message;
+  }
+  return String(error);
+}
+
+/**
+ * Parse a SESSION_IN_USE error, returning null for any other error
+ */
+export function parseSessionInUseError(error: unknown): SessionInUse | null {
+  const text = rawErrorText(error);
+  const marker = `${SESSION_IN_USE_ERROR_PREFIX}:`;
+  const start = text.indexOf(marker);
+  if (start < 0) {
+    return null;
+  }
+  try {
+    return JSON.parse(text.slice(start + marker.length)) as SessionInUse;
+  } catch {
+    return null;
+  }
+}
+
+/**
+ * Result of a precise revert step
+ */
+export interface RevertResult {
+  success: boolean;
+  commitsReverted: number;
+  newCommit?: string | null;
+  message: string;
+  /** True while conflicts still need to be resolved */
+  hasConflicts: boolean;
+}
+
+/**
+ * One conflict block parsed from the conflict markers of a file
+ */
+export interface ConflictHunk {
+  /** Text currently at HEAD */
+  ours: string;
+  /** Text of the common ancestor */
+  base?: string | null;
+  /** Text the revert wants to restore */
+  theirs: string;
+}
+
+/**
+ * A conflicted file (hunks is empty for non-text conflicts, which can only be aborted)
+ */
+export interface ConflictFile {
+  path: string;
+  hunks: ConflictHunk[];
+}
+
+/**
+ * Details of an interactive revert stopped on conflicts
+ */
+export interface RevertConflict {
+  originalHead: string;
+  /** Message of the range being reverted */
+  message: string;
+  files: ConflictFile[];
+  /** Ranges that will be reverted once this one is resolved */
+  remainingRanges: number;
+}
+
+export type HunkChoice = "ours" | "theirs" | "base";
+
+/**
+ * Choices for the conflict blocks of one file, in file order
+ */
+export interface ConflictResolution {
+  path: string;
+  choices: HunkChoice[];
+}
+
+/**
+ * A record of a user prompt
+ */
+export interface PromptRecord {
+  /** Index of this prompt (0, 1, 2...) */
+  index: number;
+  /** The prompt text user entered */
+  text: string;
+  /** Git commit before sending this prompt */
+  gitCommitBefore: string;
+  /** Git commit after AI completed (optional) */
+  gitCommitAfter?: string;
+  /** Timestamp when prompt was sent (UTC Unix seconds, 0 if unknown) */
+  timestamp: number;
+  /** Same instant in milliseconds, pass directly to `new Date()` */
+  timestampMs: number;
+  /** Prompt source: "project" (from project interface) or "cli" (from CLI) */
+  source: string;
+  /** Protected files restored after this prompt changed them */
+  protectedRestore?: ProtectedRestore;
+  /** When this prompt's code changes were reverted on their own (UTC Unix seconds) */
+  revertedAt?: number;
+}
+
+
+// Usage Dashboard types
+export interface UsageEntry {
+  project: string;
+  timestamp: string;
+  model: string;
+  input_tokens: number;
+  output_tokens: number;
+  cache_write_tokens: number;
+  cache_read_tokens: number;
+  cost: number;
+}
+
+export interface ModelUsage {
+  model: string;
+  total_cost: number;
+  total_tokens: number;
+  input_tokens: number;
+  output_tokens: number;
+  cache_creation_tokens: number;
+  cache_read_tokens: number;
+  session_count: number;
+}
+
+export interface DailyUsage {
+  date: string;
+  total_cost: number;
+  total_tokens: number;
+  models_used: string[];
+}
+
+export interface ProjectUsage {
+  project_path: string;
+  project_name: string;
+  total_cost: number;
+  total_tokens: number;
+  session_count: number;
+  last_used: string;
+}
+
+export interface ApiBaseUrlUsage {
+  api_base_url: string;
+  total_cost: number;
+  total_tokens: number;
+  input_tokens: number;
+  output_tokens: number;
+  cache_creation_tokens: number;
+  cache_read_tokens: number;
+  session_count: number;
+}
+
+export interface UsageStats {
+  total_cost: number;
+  total_tokens: number;
+  total_input_tokens: number;
+  total_output_tokens: number;
+  total_cache_creation_tokens: number;
+  total_cache_read_tokens: number;
+  total_sessions: number;
+  by_model: ModelUsage[];
+  by_date: DailyUsage[];
+  by_project: ProjectUsage[];
+  by_api_base_url?: ApiBaseUrlUsage[];
+}
+
+export interface SessionTokens {
+  inputTokens: number;
+  outputTokens: number;
+  cacheCreationTokens: number;
+  cacheReadTokens: number;
+}
+
+export interface ModelCost {
+  model: string;
+  inputCost: number;
+  outputCost: number;
+  cacheCost: number;
+  totalCost: number;
+}
+
+export interface CostComparison {
+  sessionId: string;
+  engine: string;
+  modelsUsed: string[];
+  tokens: SessionTokens;
+  actualCost: number;
+  modelA: ModelCost;
+  modelB: ModelCost;
+  /** modelB total minus modelA total; negative means modelB is cheaper */
+  delta: number;
+  deltaPercent?: number | null;
+}
+
+/** Dimension a usage report can be grouped by */
+export type UsageGroupKey = "day" | "project" | "provider" | "model" | "engine";
+
+export type UsageReportFormat = "csv" | "json";
+
+/** Summary returned after a usage report has been written */
+export interface UsageReportSummary {
+  outputPath: string;
+  format: UsageReportFormat;
+  rowCount: number;
+  /** Estimated cost in USD */
+  totalCost: number;
+  totalTokens: number;
+  taskCount: number;
+}
+
+export interface UsageOverview {
+  total_cost: number;
+  total_sessions: number;
+  total_tokens: number;
+  today_cost: number;
+  week_cost: number;
+  top_model?: string;
+  top_project?: string;
+}
+
+export interface SessionCacheTokens {
+  session_id: string;
+  total_cache_creation_tokens: number;
+  total_cache_read_tokens: number;
+}
+
+/**
+ * Provider configuration for API switching
+ */
+export interface ProviderConfig {
+  id: string;
+  name: string;
+  description: string;
+  base_url: string;
+  auth_token?: string;
+  api_key?: string;
+  api_key_helper?: string;
+  model?: string;
+  enable_auto_api_key_helper?: boolean;
+}
+
+/**
+ * Current provider configuration from environment variables
+ */
+export interface CurrentProviderConfig {
+  anthropic_base_url?: string;
+  anthropic_auth_token?: string;
+  anthropic_api_key?: string;
+  anthropic_api_key_helper?: string;
+  anthropic_model?: string;
+}
+
+/**
+ * API Key usage information
+ */
+export interface ApiKeyUsage {
+  /** Total balance in USD */
+  total_balance: number;
+  /** Used balance in USD */
+  used_balance: number;
+  /** Remaining balance in USD */
+  remaining_balance: number;
+  /** Whether the balance is unlimited */
+  is_unlimited: boolean;
+  /** Access expiration timestamp (0 means never expires) */
+  access_until: number;
+  /** Query start date */
+  query_start_date: string;
+  /** Query end date */
+  query_end_date: string;
+}
+
+/**
+ * Origin of a provider preset shown in the provider managers
+ */
+export type PresetSource = 'builtin' | 'remote';
+
+/**
+ * Remote provider preset manifest settings
+ */
+export interface ProviderPresetsConfig {
+  /** Manifest URL (https); empty disables remote presets */
+  manifestUrl: string;
+}
+
+/**
+ * Cached state of the remote provider preset manifest
+ */
+export interface RemotePresetsStatus {
+  manifestUrl: string;
+  /** Unix seconds of the last successful fetch */
+  fetchedAt?: number | null;
+  codexCount: number;
+  geminiCount: number;
+}
+
+/**
+ * Codex provider configuration for OpenAI Codex API switching
+ */
+export interface CodexProviderConfig {
+  id: string;
+  name: string;
+  description?: string;
+  websiteUrl?: string;
+  category?: 'official' | 'cn_official' | 'aggregator' | 'third_party' | 'custom';
+  auth: Record<string, any>; // 写入 ~/.codex/auth.json
+  config: string; // 写入 ~/.codex/config.toml（TOML 字符串）
+  isOfficial?: boolean;
+  isPartner?: boolean;
+  createdAt?: number;
+  customHeaders?: Record<string, string>; // 额外请求头，写入当前 model_provider 的 http_headers
+  source?: PresetSource; // 预设来源：内置或远程清单（用户预设不设置）
+}
+
+/**
+ * Current Codex provider configuration from ~/.codex directory
+ */
+export interface CurrentCodexConfig {
+  auth: Record<string, any>; // ~/.codex/auth.json 内容
+  config: string; // ~/.codex/config.toml 内容
+  apiKey?: string; // 从 auth 中提取的 API Key
+  baseUrl?: string; // 从 config 中提取的 Base URL
+  model?: string; // 从 config 中提取的模型名称
+}
+
+/**
+ * Named Codex profile from `[profiles.<name>]` in config.toml
+ */
+export interface CodexProfile {
+  name: string;
+  model?: string | null;
+  modelProvider?: string | null;
+  approvalPolicy?: string | null;
+  sandboxMode?: string | null;
+  modelReasoningEffort?: string | null;
+  modelVerbosity?: string | null;
+  /** Selected by the top-level `profile` key in config.toml */
+  isDefault: boolean;
+}
+
+/**
+ * Config keys the installed Codex CLI accepts as `-c key=value` overrides
+ */
+export interface CodexCapabilities {
+  /** Parsed CLI version (x.y.z); null when it could not be detected */
+  version: string | null;
+  supportedKeys: string[];
+  unsupportedKeys: string[];
+}
+
+/**
+ * Editable fields of a Codex profile; empty fields are removed from the section
+ */
+export type CodexProfileInput = Omit<CodexProfile, 'name' | 'isDefault'>;
+
+/**
+ * Gemini provider configuration for Gemini API switching
+ */
+export interface GeminiProviderConfig {
+  id: string;
+  name: string;
+  description?: string;
+  websiteUrl?: string;
+  category?: 'official' | 'third_party' | 'custom';
+  env: Record<string, string>; // 环境变量，写入 ~/.gemini/.env
+  isOfficial?: boolean;
+  isPartner?: boolean;
+  createdAt?: number;
+  customHeaders?: Record<string, string>; // 额外请求头，通过 GEMINI_CLI_CUSTOM_HEADERS 传给 Gemini CLI
+  source?: PresetSource; // 预设来源：内置或远程清单（用户预设不设置）
+}
+
+/**
+ * Result of a Gemini provider connection test
+ */
+export interface ProviderTestResult {
+  success: boolean;
+  latencyMs: number; // 模型列表请求的往返耗时
+  availableModels: string[]; // 供应商返回的模型 ID；不支持列出模型时为空
+  error?: string | null;
+}
+
+/**
+ * Current Gemini provider configuration from ~/.gemini directory
+ */
+export interface CurrentGeminiProviderConfig {
+  env: Record<string, string>; // ~/.gemini/.env 内容
+  settings: Record<string, any>; // ~/.gemini/settings.json 内容
+  apiKey?: string; // 从 env 中提取的 API Key
+  baseUrl?: string; // 从 env 中提取的 Base URL
+  model?: string; // 从 env 中提取的模型
+  selectedAuthType?: string; // 认证类型
+}
+
+// ============================================================================
+// MCP 多应用支持类型定义（新版）
+// ============================================================================
+
+/**
+ * MCP 服务器规范（实际配置）
+ */
+export interface MCPServerSpec {
+  /** 传输类型：stdio/http/sse */
+  type?: "stdio" | "http" | "sse";
+  /** 命令（stdio 类型） */
+  command?: string;
+  /** 命令参数 */
+  args?: string[];
+  /** 环境变量 */
+  env?: Record<string, string>;
+  /** 工作目录（stdio 类型） */
+  cwd?: string;
+  /** URL（http/sse 类型） */
+  url?: string;
+  /** 请求头（http/sse 类型） */
+  headers?: Record<string, string>;
+}
+
+/**
+ * MCP 应用启用状态
+ */
+export interface McpApps {
+  /** 是否在 Claude 中启用 */
+  claude: boolean;
+  /** 是否在 Codex 中启用 */
+  codex: boolean;
+  /** 是否在 Gemini 中启用 */
+  gemini: boolean;
+}
+
+/**
+ * MCP 服务器（统一结构）
+ */
+export interface McpServer {
+  /** 服务器 ID */
+  id: string;
+  /** 显示名称 */
+  name: string;
+  /** 服务器配置 */
+  server: MCPServerSpec;
+  /** 应用启用状态 */
+  apps: McpApps;
+  /** 描述 */
+  description?: string;
+  /** 主页 */
+  homepage?: string;
+  /** 文档链接 */
+  docs?: string;
+  /** 标签 */
+  tags?: string[];
+}
+
+/**
+ * 会话中 MCP 调用按 server 或 server + 工具聚合的统计
+ */
+export interface McpCallStats {
+  server: string;
+  /** 按 server 聚合时为 null */
+  tool: string | null;
+  callCount: number;
+  failedCount: number;
+  /** 失败率（0 ~ 1） */
+  failureRate: number;
+  /** 平均耗时（毫秒），没有耗时记录时为 null */
+  avgDurationMs: number | null;
+}
+
+/**
+ * 会话的 MCP 调用汇总
+ */
+export interface McpSessionSummary {
+  sessionId: string;
+  engine: "claude" | "codex";
+  totalCalls: number;
+  failedCalls: number;
+  servers: McpCallStats[];
+  tools: McpCallStats[];
+}
+
+/**
+ * 会话中的单次工具调用及其耗时（慢操作列表）
+ */
+export interface ToolCallTiming {
+  callId: string;
+  toolName: string;
+  /** 参数摘要（命令、文件路径等） */
+  inputSummary: string;
+  startedAt: string | null;
+  durationMs: number | null;
+  /** 所在消息在会话文件中的序号 */
+  messageIndex: number;
+  messageId: string | null;
+}
+
+/**
+ * MCP 状态
+ */
+export interface McpStatus {
+  /** 用户配置文件路径 */
+  user_config_path: string;
+  /** 配置文件是否存在 */
+  user_config_exists: boolean;
+  /** 服务器数量 */
+  server_count: number;
+}
+
+/**
+ * 带启用状态的 MCP 服务器条目
+ */
+export interface McpServerWithStatus {
+  /** 服务器 ID */
+  id: string;
+  /** 服务器配置 */
+  spec: MCPServerSpec;
+  /** 是否启用 */
+  enabled: boolean;
+}
+
+// ============================================================================
+// 旧版 MCP 类型（兼容性保留，后续可删除）
+// ============================================================================
+
+/**
+ * @deprecated 使用 McpServer 代替
+ */
+export interface MCPServer {
+  name: string;
+  transport: string;
+  command?: string;
+  args: string[];
+  env: Record<string, string>;
+  url?: string;
+  scope: string;
+  is_active: boolean;
+  status: ServerStatus;
+}
+
+/**
+ * @deprecated
+ */
+export interface ServerStatus {
+  running: boolean;
+  error?: string;
+  last_checked?: number;
+}
+
+/**
+ * MCP configuration for project scope (.mcp.json)
+ */
+export interface MCPProjectConfig {
+  mcpServers: Record<string, MCPServerConfig>;
+}
+
+/**
+ * Individual server configuration in .mcp.json
+ */
+export interface MCPServerConfig {
+  command: string;
+  args: string[];
+  env: Record<string, string>;
+}
+
+
+
+/**
+ * Result of saving clipboard image
+ */
+export interface SavedImageResult {
+  success: boolean;
+  file_path?: string;
+  error?: string;
+}
+
+/**
+ * Result of adding a server
+ */
+export interface AddServerResult {
+  success: boolean;
+  message: string;
+  server_name?: string;
+}
+
+/**
+ * Translation configuration interface
+ */
+export interface TranslationConfig {
+  enabled: boolean;
+  api_base_url: string;
+  api_key: string;
+  model: string;
+  timeout_seconds: number;
+  cache_ttl_seconds: number;
+}
+
+/**
+ * Translation cache statistics
+ */
+export interface TranslationCacheStats {
+  total_entries: number;
+  expired_entries: number;
+  active_entries: number;
+}
+
+
+/**
+ * Auto-compact configuration
+ */
+export interface AutoCompactConfig {
+  /** Enable automatic compaction */
+  enabled: boolean;
+  /** Maximum context tokens before triggering compaction */
+  max_context_tokens: number;
+  /** Threshold percentage to trigger compaction (0.0-1.0) */
+  compaction_threshold: number;
+  /** Minimum time between compactions in seconds */
+  min_compaction_interval: number;
+  /** Strategy for compaction */
+  compaction_strategy: CompactionStrategy;
+  /** Whether to preserve recent messages */
+  preserve_recent_messages: boolean;
+  /** Number of recent messages to preserve */
+  preserve_message_count: number;
+  /** Custom compaction instructions */
+  custom_instructions?: string;
+}
+
+/**
+ * Compaction strategies
+ */
+export type CompactionStrategy =
+  | 'Smart'
+  | 'Aggressive'
+  | 'Conservative'
+  | { Custom: string };
+
+/**
+ * Session context information
+ */
+export interface SessionContext {
+  session_id: string;
+  project_path: string;
+  current_tokens: number;
+  message_count: number;
+  last_compaction?: string; // ISO timestamp
+  compaction_count: number;
+  model: string;
+  status: SessionStatus;
+}
+
+/**
+ * Session status
+ */
+export type SessionStatus =
+  | 'Active'
+  | 'Idle'
+  | 'Compacting'
+  | { CompactionFailed: string };
+
+/**
+ * Auto-compact status information
+ */
+export interface AutoCompactStatus {
+  enabled: boolean;
+  is_monitoring: boolean;
+  sessions_count: number;
+  total_compactions: number;
+  max_context_tokens: number;
+  compaction_threshold: number;
+}
+
+/**
+ * Import result for multiple servers
+ */
+export interface ImportResult {
+  imported_count: number;
+  failed_count: number;
+  servers: ImportServerResult[];
+}
+
+/**
+ * Result for individual server import
+ */
+export interface ImportServerResult {
+  name: string;
+  success: boolean;
+  error?: string;
+}
+
+/**
+ * API client for interacting with the Rust backend
+ */
+export const api = {
+  /**
+   * Lists all projects in the ~/.claude/projects directory
+   * @returns Promise resolving to an array of projects
+   */
+  async listProjects(): Promise<Project[]> {
+    try {
+      return await invoke<Project[]>("list_projects");
+    } catch (error) {
+      console.error("Failed to list projects:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Retrieves sessions for a specific project (both Claude and Codex)
+   * @param projectId - The ID of the project to retrieve sessions for
+   * @param projectPath - Optional project path to filter Codex sessions (if not provided, tries to infer from Claude sessions)
+   * @returns Promise resolving to an array of sessions
+   */
+  async getProjectSessions(projectId: string, projectPath?: string): Promise<Session[]> {
+    try {
+      // Get Claude sessions
+      const claudeSessions = await invoke<Session[]>('get_project_sessions', { projectId });
+
+      const targetPath = projectPath || claudeSessions[0]?.project_path;
+
+      // Get Codex sessions of this project (filtered by the backend index)
+      // If we don't have a target path, we can't filter, so return no Codex sessions
+      const codexSessions = targetPath
+        ? await this.listCodexSessions(undefined, { projectPathFilter: targetPath })
+        : [];
+
+      const filteredCodexSessions: Session[] = codexSessions
+        .map(cs => ({
+          id: cs.id,
+          project_id: projectId,
+          project_path: cs.projectPath,
+          created_at: cs.createdAt,
+          model: cs.model || 'gpt-5.1-codex-max',
+          engine: 'codex' as const,
+          // 🆕 Use actual first message from JSONL file
+          first_message: cs.firstMessage || `Codex Session`,
+          first_message_source: cs.firstMessageSource,
+          last_message_timestamp: cs.lastMessageTimestamp,
+        }));
+
+      // Merge and sort by creation time
+      const allSessions = [...claudeSessions.map(s => ({ ...s, engine: 'claude' as const })), ...filteredCodexSessions];
+      allSessions.sort((a, b) => b.created_at - a.created_at);
+
+      return allSessions;
+    } catch (error) {
+      console.error("Failed to get project sessions:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Deletes a session and all its associated data
+   * @param sessionId - The session ID to delete
+   * @param projectId - The project ID this session belongs to
+   * @returns Promise resolving to success message
+   */
+  async deleteSession(sessionId: string, projectId: string): Promise<string> {
+    try {
+      return await invoke<string>('delete_session', { sessionId, projectId });
+    } catch (error) {
+      console.error("Failed to delete session:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Deletes multiple sessions in batch
+   * @param sessionIds - Array of session IDs to delete
+   * @param projectId - The project ID these sessions belong to
+   * @returns Promise resolving to success message
+   */
+  async deleteSessionsBatch(sessionIds: string[], projectId: string): Promise<string> {
+    try {
+      return await invoke<string>('delete_sessions_batch', { sessionIds, projectId });
+    } catch (error) {
+      console.error("Failed to batch delete sessions:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Removes a project from the project list (without deleting files)
+   * @param projectId - The ID of the project to remove from list
+   * @returns Promise resolving to success message
+   */
+  async deleteProject(projectId: string): Promise<string> {
+    try {
+      return await invoke<string>('delete_project', { projectId });
+    } catch (error) {
+      console.error("Failed to remove project from list:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Restores a hidden project back to the project list
+   * @param projectId - The ID of the project to restore
+   * @returns Promise resolving to success message
+   */
+  async restoreProject(projectId: string): Promise<string> {
+    try {
+      return await invoke<string>('restore_project', { projectId });
+    } catch (error) {
+      console.error("Failed to restore project:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Lists all hidden projects
+   * @returns Promise resolving to array of hidden project IDs
+   */
+  async listHiddenProjects(): Promise<string[]> {
+    try {
+      return await invoke<string[]>('list_hidden_projects');
+    } catch (error) {
+      console.error("Failed to list hidden projects:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Permanently delete a project and all its files
+   * @param projectId - The project ID to permanently delete
+   * @returns Promise resolving to success message
+   */
+  async deleteProjectPermanently(projectId: string): Promise<string> {
+    try {
+      return await invoke<string>('delete_project_permanently', { projectId });
+    } catch (error) {
+      console.error("Failed to permanently delete project:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Reads the Claude settings file
+   * @returns Promise resolving to the settings object
+  */
+  async getClaudeSettings(): Promise<ClaudeSettings> {
+    try {
+      // Due to #[serde(flatten)] in Rust, the result is directly the settings object
+      return await invoke<ClaudeSettings>("get_claude_settings");
+    } catch (error) {
+      console.error("Failed to get Claude settings:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Opens a new Claude Code session
+   * @param path - Optional path to open the session in
+   * @returns Promise resolving when the session is opened
+   */
+  async openNewSession(path?: string): Promise<string> {
+    try {
+      return await invoke<string>("open_new_session", { path });
+    } catch (error) {
+      console.error("Failed to open new session:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Reads the CLAUDE.md system prompt file
+   * @returns Promise resolving to the system prompt content
+   */
+  async getSystemPrompt(): Promise<string> {
+    try {
+      return await invoke<string>("get_system_prompt");
+    } catch (error) {
+      console.error("Failed to get system prompt:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Checks if Claude Code is installed and gets its version
+   * @returns Promise resolving to the version status
+   */
+  async checkClaudeVersion(): Promise<ClaudeVersionStatus> {
+    try {
+      return await invoke<ClaudeVersionStatus>("check_claude_version");
+    } catch (error) {
+      console.error("Failed to check Claude version:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Saves the CLAUDE.md system prompt file
+   * @param content - The new content for the system prompt
+   * @returns Promise resolving when the file is saved
+   */
+  async saveSystemPrompt(content: string): Promise<string> {
+    try {
+      return await invoke<string>("save_system_prompt", { content });
+    } catch (error) {
+      console.error("Failed to save system prompt:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Reads the AGENTS.md system prompt file from Codex directory
+   * @returns Promise resolving to the Codex system prompt content
+   */
+  async getCodexSystemPrompt(): Promise<string> {
+    try {
+      return await invoke<string>("get_codex_system_prompt");
+    } catch (error) {
+      console.error("Failed to get Codex system prompt:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Saves the AGENTS.md system prompt file to Codex directory
+   * @param content - The new content for the Codex system prompt
+   * @returns Promise resolving when the file is saved
+   */
+  async saveCodexSystemPrompt(content: string): Promise<string> {
+    try {
+      return await invoke<string>("save_codex_system_prompt", { content });
+    } catch (error) {
+      console.error("Failed to save Codex system prompt:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Reads the GEMINI.md system prompt file from Gemini directory
+   * @returns Promise resolving to the content of GEMINI.md
+   */
+  async getGeminiSystemPrompt(): Promise<string> {
+    try {
+      return await invoke<string>("get_gemini_system_prompt");
+    } catch (error) {
+      console.error("Failed to get Gemini system prompt:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Saves the GEMINI.md system prompt file to Gemini directory
+   * @param content - The new content for the Gemini system prompt
+   * @returns Promise resolving when the file is saved
+   */
+  async saveGeminiSystemPrompt(content: string): Promise<string> {
+    try {
+      return await invoke<string>("save_gemini_system_prompt", { content });
+    } catch (error) {
+      console.error("Failed to save Gemini system prompt:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Saves the Claude settings file
+   * @param settings - The settings object to save
+   * @returns Promise resolving when the settings are saved
+   */
+  async saveClaudeSettings(settings: ClaudeSettings): Promise<string> {
+    try {
+      return await invoke<string>("save_claude_settings", { settings });
+    } catch (error) {
+      console.error("Failed to save Claude settings:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Updates the thinking mode by modifying MAX_THINKING_TOKENS in settings.json
+   * @param enabled - Whether to enable thinking mode
+   * @param tokens - Optional token limit (defaults to 10000)
+   * @returns Promise resolving when the settings are updated
+   */
+  async updateThinkingMode(enabled: boolean, tokens?: number): Promise<string> {
+    try {
+      return await invoke<string>("update_thinking_mode", { enabled, tokens });
+    } catch (error) {
+      console.error("Failed to update thinking mode:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Get Claude execution configuration
+   * @returns Promise resolving to the current execution config
+   */
+  async getClaudeExecutionConfig(): Promise<ClaudeExecutionConfig> {
+    try {
+      return await invoke<ClaudeExecutionConfig>("get_claude_execution_config");
+    } catch (error) {
+      console.error("Failed to get Claude execution config:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Update Claude execution configuration
+   * @param config - The new execution configuration
+   * @returns Promise resolving when the config is saved
+   */
+  async updateClaudeExecutionConfig(config: ClaudeExecutionConfig): Promise<void> {
+    try {
+      return await invoke<void>("update_claude_execution_config", { config });
+    } catch (error) {
+      console.error("Failed to update Claude execution config:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Finds all CLAUDE.md files in a project directory
+   * @param projectPath - The absolute path to the project
+   * @returns Promise resolving to an array of CLAUDE.md files
+   */
+  async findClaudeMdFiles(projectPath: string): Promise<ClaudeMdFile[]> {
+    try {
+      return await invoke<ClaudeMdFile[]>("find_claude_md_files", { projectPath });
+    } catch (error) {
+      console.error("Failed to find CLAUDE.md files:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Reads a specific CLAUDE.md file
+   * @param filePath - The absolute path to the file
+   * @returns Promise resolving to the file content
+   */
+  async readClaudeMdFile(filePath: string): Promise<string> {
+    try {
+      return await invoke<string>("read_claude_md_file", { filePath });
+    } catch (error) {
+      console.error("Failed to read CLAUDE.md file:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Saves a specific CLAUDE.md file
+   * @param filePath - The absolute path to the file
+   * @param content - The new content for the file
+   * @returns Promise resolving when the file is saved
+   */
+  async saveClaudeMdFile(filePath: string, content: string): Promise<string> {
+    try {
+      return await invoke<string>("save_claude_md_file", { filePath, content });
+    } catch (error) {
+      console.error("Failed to save CLAUDE.md file:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Reads the .gitignore at the root of a project
+   * @param projectPath - The project directory
+   * @returns Promise resolving to the file content (empty when it does not exist)
+   */
+  async readProjectGitignore(projectPath: string): Promise<string> {
+    try {
+      return await invoke<string>("read_project_gitignore", { projectPath });
+    } catch (error) {
+      console.error("Failed to read .gitignore:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Saves the .gitignore at the root of a project
+   * @param projectPath - The project directory
+   * @param content - The new content for the file
+   */
+  async saveProjectGitignore(projectPath: string, content: string): Promise<void> {
+    try {
+      await invoke("save_project_gitignore", { projectPath, content });
+    } catch (error) {
+      console.error("Failed to save .gitignore:", error);
+      throw error;
+    }
+  },
+
+
+  /**
+   * Loads the JSONL history for a specific session (Claude or Codex)
+   */
+  async loadSessionHistory(sessionId: string, projectId: string, engine?: 'claude' | 'codex'): Promise<any[]> {
+    // For Codex sessions, read directly from .codex/sessions
+    if (engine === 'codex') {
+      return this.loadCodexSessionHistory(sessionId);
+    }
+    // For Claude sessions, use existing backend
+    const history = await this.loadClaudeSessionHistory(sessionId, projectId);
+    return history.messages;
+  },
+
+  /**
+   * Loads a Claude session's history along with the corrupted lines that were skipped
+   */
+  async loadClaudeSessionHistory(sessionId: string, projectId: string): Promise<SessionHistory> {
+    return invoke<SessionHistory>("load_session_history", { sessionId, projectId });
+  },
+
+  /**
+   * Repairs a Claude session file by dropping corrupted lines (the original is backed up)
+   */
+  async repairClaudeSession(sessionId: string, projectId: string): Promise<SessionRepairReport> {
+    try {
+      return await invoke<SessionRepairReport>("repair_claude_session", { sessionId, projectId });
+    } catch (error) {
+      console.error("Failed to repair Claude session:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Resolves the full path of a session file
+   * @param engine - Engine the session belongs to
+   * @param sessionId - The session ID
+   * @param projectIdOrPath - Claude project ID, or the project path for Gemini (ignored for Codex)
+   * @returns Promise resolving to the session file path; rejects with a
+   *          SESSION_NOT_FOUND_ERROR_PREFIX error when the file does not exist
+   */
+  async resolveSessionPath(
+    engine: 'claude' | 'codex' | 'gemini',
+    sessionId: string,
+    projectIdOrPath: string
+  ): Promise<string> {
+    try {
+      return await invoke<string>("resolve_session_path", { engine, sessionId, projectIdOrPath });
+    } catch (error) {
+      console.error("Failed to resolve session path:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Lists sessions sorted by file size (largest first) to find what to clean up
+   * @param engine - The engine whose sessions to scan
+   * @param projectId - Claude project ID, or the project path for Codex/Gemini; omit to scan all projects
+   * @param limit - Maximum number of sessions to return
+   * @returns Promise resolving to the largest sessions
+   */
+  async listSessionsBySize(
+    engine: 'claude' | 'codex' | 'gemini',
+    projectId: string | undefined,
+    limit: number
+  ): Promise<SessionSize[]> {
+    try {
+      return await invoke<SessionSize[]>("list_sessions_by_size", { engine, projectId, limit });
+    } catch (error) {
+      console.error("Failed to list sessions by size:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Rewrites a Claude/Codex session so tool results over the threshold are stored as blob
+   * attachments beside the session file (the original file is backed up)
+   * @param engine - The engine of the session (Gemini sessions are not supported)
+   * @param sessionId - The session ID
+   * @param projectIdOrPath - Claude project ID (ignored for Codex)
+   * @param thresholdBytes - Externalize tool results larger than this (defaults to 64KB)
+   * @returns Promise resolving to the size before and after the rewrite
+   */
+  async optimizeSessionStorage(
+    engine: 'claude' | 'codex' | 'gemini',
+    sessionId: string,
+    projectIdOrPath: string,
+    thresholdBytes?: number
+  ): Promise<SessionStorageReport> {
+    try {
+      return await invoke<SessionStorageReport>("optimize_session_storage", {
+        engine,
+        sessionId,
+        projectIdOrPath,
+        thresholdBytes,
+      });
+    } catch (error) {
+      console.error("Failed to optimize session storage:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Fetches the full tool result behind a blob placeholder
+   * @param engine - The engine of the session
+   * @param sessionId - The session ID
+   * @param projectIdOrPath - Claude project ID (ignored for Codex)
+   * @param reference - The placeholder's $ref (blob://<hash>)
+   * @returns Promise resolving to the original value (a string or an array of content blocks)
+   */
+  async getMessageBlob(
+    engine: 'claude' | 'codex' | 'gemini',
+    sessionId: string,
+    projectIdOrPath: string,
+    reference: string
+  ): Promise<any> {
+    try {
+      return await invoke<any>("get_message_blob", { engine, sessionId, projectIdOrPath, reference });
+    } catch (error) {
+      console.error("Failed to get message blob:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Gets the annotations (related sessions) stored for a session
+   * @param engine - The engine of the session
+   * @param sessionId - The session ID
+   * @returns Promise resolving to the annotation (empty when none is stored)
+   */
+  async getSessionAnnotation(
+    engine: 'claude' | 'codex' | 'gemini',
+    sessionId: string
+  ): Promise<SessionAnnotation> {
+    try {
+      return await invoke<SessionAnnotation>("get_session_annotation", { engine, sessionId });
+    } catch (error) {
+      console.error("Failed to get session annotation:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Gets the enhancement metadata of a prompt enhanced during execution
+   * @param engine - The engine of the session
+   * @param sessionId - The session ID
+   * @param promptIndex - The prompt index
+   * @returns Promise resolving to the metadata, or null when the prompt was not enhanced
+   */
+  async getPromptEnhancementInfo(
+    engine: 'claude' | 'codex' | 'gemini',
+    sessionId: string,
+    promptIndex: number
+  ): Promise<PromptEnhancementInfo | null> {
+    try {
+      return await invoke<PromptEnhancementInfo | null>("get_prompt_enhancement_info", { engine, sessionId, promptIndex });
+    } catch (error) {
+      console.error("Failed to get prompt enhancement info:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Lists the files a prompt referenced when it was sent and how they compare with the current files
+   * @param engine - The engine of the session
+   * @param sessionId - The session ID
+   * @param promptIndex - The prompt index
+   * @returns Promise resolving to the referenced files (empty when no snapshot was taken)
+   */
+  async getPromptFileSnapshots(
+    engine: 'claude' | 'codex' | 'gemini',
+    sessionId: string,
+    promptIndex: number
+  ): Promise<PromptFileSnapshotEntry[]> {
+    try {
+      return await invoke<PromptFileSnapshotEntry[]>("get_prompt_file_snapshots", { engine, sessionId, promptIndex });
+    } catch (error) {
+      console.error("Failed to get prompt file snapshots:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Loads a file snapshot together with the current file content for comparison
+   * @param snapshotId - The snapshot ID from getPromptFileSnapshots
+   * @returns Promise resolving to both versions of the file
+   */
+  async diffSnapshotWithCurrent(snapshotId: string): Promise<FileSnapshotComparison> {
+    try {
+      return await invoke<FileSnapshotComparison>("diff_snapshot_with_current", { snapshotId });
+    } catch (error) {
+      console.error("Failed to compare file snapshot:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Fetches web pages and builds context text to append to a prompt
+   * @param urls - The http(s) URLs to fetch
+   * @param options - Optional overrides of the token budget and domain allowlist
+   * @returns Promise resolving to the fetched pages, failures with reasons and the context text
+   */
+  async fetchUrlContext(urls: string[], options?: UrlContextOptions): Promise<UrlContextResult> {
+    try {
+      return await invoke<UrlContextResult>("fetch_url_context", { urls, options });
+    } catch (error) {
+      console.error("Failed to fetch URL context:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Gets the URL prefetch settings
+   * @returns Promise resolving to the settings
+   */
+  async getUrlFetchConfig(): Promise<UrlFetchConfig> {
+    try {
+      return await invoke<UrlFetchConfig>("get_url_fetch_config");
+    } catch (error) {
+      console.error("Failed to get URL fetch config:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Updates the URL prefetch settings
+   * @param config - The new settings
+   */
+  async updateUrlFetchConfig(config: UrlFetchConfig): Promise<void> {
+    try {
+      await invoke("update_url_fetch_config", { config });
+    } catch (error) {
+      console.error("Failed to update URL fetch config:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Regenerates the reply to a prompt in a forked session, keeping the original
+   * @param engine - The engine of the session (Claude or Codex)
+   * @param sessionId - The session ID
+   * @param promptIndex - The prompt index
+   * @param options - Project path, model and mode for the new run
+   * @returns Promise resolving to the ID of the forked session holding the new version
+   */
+  async regenerateResponse(
+    engine: 'claude' | 'codex',
+    sessionId: string,
+    promptIndex: number,
+    options: RegenerateOptions
+  ): Promise<string> {
+    try {
+      return await invoke<string>("regenerate_response", { engine, sessionId, promptIndex, options });
+    } catch (error) {
+      console.error("Failed to regenerate response:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Gets every reply version of a prompt, main session first
+   * @param engine - The engine of the session
+   * @param sessionId - The session ID
+   * @param promptIndex - The prompt index
+   * @param projectPath - The project path of the session
+   * @returns Promise resolving to the version summaries
+   */
+  async getResponseAlternatives(
+    engine: 'claude' | 'codex',
+    sessionId: string,
+    promptIndex: number,
+    projectPath: string
+  ): Promise<ResponseVersion[]> {
+    try {
+      return await invoke<ResponseVersion[]>("get_response_alternatives", { engine, sessionId, promptIndex, projectPath });
+    } catch (error) {
+      console.error("Failed to get response alternatives:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Makes a regenerated version the mainline of the session (both files are backed up first)
+   * @param engine - The engine of the session
+   * @param sessionId - The main session ID
+   * @param promptIndex - The prompt index
+   * @param alternativeSessionId - The forked session holding the version to adopt
+   * @param projectPath - The project path of the session
+   */
+  async adoptAlternative(
+    engine: 'claude' | 'codex',
+    sessionId: string,
+    promptIndex: number,
+    alternativeSessionId: string,
+    projectPath: string
+  ): Promise<void> {
+    try {
+      await invoke("adopt_alternative", { engine, sessionId, promptIndex, alternativeSessionId, projectPath });
+    } catch (error) {
+      console.error("Failed to adopt alternative:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Sets the language of user-facing messages returned by the backend
+   * (error descriptions, rewind warnings, provider results)
+   * @param locale - UI language code (zh, zh-TW, en); Chinese variants use zh-CN, others en-US
+   */
+  async setBackendLocale(locale: string): Promise<void> {
+    try {
+      await invoke("set_backend_locale", { locale });
+    } catch (error) {
+      console.error("Failed to set backend locale:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Classifies an error reported during execution (stderr, error events) into an error code
+   * @param engine - claude, codex or gemini
+   * @param message - Raw error text
+   */
+  async classifyExecutionError(engine: string, message: string): Promise<AppErrorPayload> {
+    try {
+      return await invoke<AppErrorPayload>("classify_execution_error", { engine, message });
+    } catch (error) {
+      console.error("Failed to classify execution error:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Lists the subagent (sidechain) sessions spawned by a Claude session
+   * @param sessionId - The main session ID
+   * @param projectId - The project ID
+   * @returns Promise resolving to the agent-*.jsonl summaries of the session
+   */
+  async listSessionAgents(sessionId: string, projectId: string): Promise<SessionAgent[]> {
+    return invoke<SessionAgent[]>("list_session_agents", { sessionId, projectId });
+  },
+
+  /**
+   * Loads the messages of a Claude subagent session file (agent-*.jsonl)
+   * @param agentFile - Absolute path of the agent file (from listSessionAgents or a Task tool_use block)
+   * @returns Promise resolving to the raw Claude JSONL messages of the subagent
+   */
+  async getAgentMessages(agentFile: string): Promise<any[]> {
+    return invoke<any[]>("get_agent_messages", { agentFile });
+  },
+
+  /**
+   * 🆕 Loads Codex session history from JSONL file
+   */
+  async loadCodexSessionHistory(sessionId: string): Promise<any[]> {
+    try {
+      return await invoke("load_codex_session_history", { sessionId });
+    } catch (error) {
+      console.error("Failed to load Codex session history:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Executes a new interactive Claude Code session with streaming output
+   * @param planMode - Enable Plan Mode for read-only research and planning
+   * @param tabId - Unique identifier for the tab, used to filter global events
+   * @param useProjectMemory - Inject the compiled project memory into the system prompt
+   * @param traceId - Trace ID of the user action that started this run
+   * @param enhancement - Enhance the prompt with acemcp project context before running
+   */
+  async executeClaudeCode(projectPath: string, prompt: string, model: string, planMode?: boolean, maxThinkingTokens?: number, tabId?: string, useProjectMemory?: boolean, traceId?: string, enhancement?: EnhancementRequest): Promise<void> {
+    try {
+      return await invoke("execute_claude_code", { projectPath, prompt, model, planMode, maxThinkingTokens, tabId, useProjectMemory, traceId, enhancement });
+    } catch (error) {
+      console.error("Failed to execute Claude Code:", error);
+      throw toCommandError(error);
+    }
+  },
+
+  /**
+   * Continues an existing Claude Code conversation with streaming output
+   * @param planMode - Enable Plan Mode for read-only research and planning
+   * @param tabId - Unique identifier for the tab, used to filter global events
+   * @param useProjectMemory - Inject the compiled project memory into the system prompt
+   * @param traceId - Trace ID of the user action that started this run
+   * @param enhancement - Enhance the prompt with acemcp project context before running
+   */
+  async continueClaudeCode(projectPath: string, prompt: string, model: string, planMode?: boolean, maxThinkingTokens?: number, tabId?: string, useProjectMemory?: boolean, traceId?: string, enhancement?: EnhancementRequest): Promise<void> {
+    try {
+      return await invoke("continue_claude_code", { projectPath, prompt, model, planMode, maxThinkingTokens, tabId, useProjectMemory, traceId, enhancement });
+    } catch (error) {
+      console.error("Failed to continue Claude Code:", error);
+      throw toCommandError(error);
+    }
+  },
+
+  /**
+   * Resumes an existing Claude Code session by ID with streaming output
+   * @param planMode - Enable Plan Mode for read-only research and planning
+   * @param tabId - Unique identifier for the tab, used to filter global events
+   * @param useProjectMemory - Inject the compiled project memory into the system prompt
+   * @param traceId - Trace ID of the user action that started this run
+   * @param enhancement - Enhance the prompt with acemcp project context before running
+   */
+  async resumeClaudeCode(projectPath: string, sessionId: string, prompt: string, model: string, planMode?: boolean, maxThinkingTokens?: number, tabId?: string, useProjectMemory?: boolean, traceId?: string, enhancement?: EnhancementRequest): Promise<void> {
+    try {
+      return await invoke("resume_claude_code", { projectPath, sessionId, prompt, model, planMode, maxThinkingTokens, tabId, useProjectMemory, traceId, enhancement });
+    } catch (error) {
+      console.error("Failed to resume Claude Code:", error);
+      throw toCommandError(error);
+    }
+  },
+
+  /**
+   * Cancels the currently running Claude Code execution
+   *
+   * The CLI is interrupted first and force killed after 5s; the result is emitted
+   * as a CancelOutcome on the `<engine>-cancelled` event
+   * @param sessionId - Optional session ID to cancel a specific session
+   */
+  async cancelClaudeExecution(sessionId?: string): Promise<void> {
+    return invoke("cancel_claude_execution", { sessionId });
+  },
+
+  /**
+   * Lists all currently running Claude sessions
+   * @returns Promise resolving to list of running Claude sessions
+   */
+  async listRunningClaudeSessions(): Promise<any[]> {
+    return invoke("list_running_claude_sessions");
+  },
+
+  /**
+   * Gets live output from a Claude session
+   * @param sessionId - The session ID to get output for
+   * @returns Promise resolving to the current live output
+   */
+  async getClaudeSessionOutput(sessionId: string): Promise<string> {
+    return invoke("get_claude_session_output", { sessionId });
+  },
+
+  /**
+   * Lists files and directories in a given path
+   */
+  async listDirectoryContents(directoryPath: string): Promise<FileEntry[]> {
+    return invoke("list_directory_contents", { directoryPath });
+  },
+
+  /**
+   * Searches for files and directories matching a pattern
+   */
+  async searchFiles(basePath: string, query: string): Promise<FileEntry[]> {
+    return invoke("search_files", { basePath, query });
+  },
+
+  /**
+   * Gets overall usage statistics
+   * @returns Promise resolving to usage statistics
+   */
+  async getUsageStats(): Promise<UsageStats> {
+    try {
+      return await invoke<UsageStats>("get_usage_stats");
+    } catch (error) {
+      console.error("Failed to get usage stats:", error);
+      throw error;
+    }
+  },
+
+
+  /**
+   * Gets usage statistics filtered by date range
+   * @param startDate - Start date (ISO format)
+   * @param endDate - End date (ISO format)
+   * @returns Promise resolving to usage statistics
+   */
+  async getUsageByDateRange(startDate: string, endDate: string): Promise<UsageStats> {
+    try {
+      return await invoke<UsageStats>("get_usage_by_date_range", { startDate, endDate });
+    } catch (error) {
+      console.error("Failed to get usage by date range:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Gets usage statistics grouped by session
+   * @param since - Optional start date (YYYYMMDD)
+   * @param until - Optional end date (YYYYMMDD)
+   * @param order - Optional sort order ('asc' or 'desc')
+   * @returns Promise resolving to an array of session usage data
+   */
+  async getSessionStats(
+    since?: string,
+    until?: string,
+    order?: "asc" | "desc"
+  ): Promise<ProjectUsage[]> {
+    try {
+      return await invoke<ProjectUsage[]>("get_session_stats", {
+        since,
+        until,
+        order,
+      });
+    } catch (error) {
+      console.error("Failed to get session stats:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Re-prices a session's recorded token usage under two models
+   * @param sessionId - The session ID
+   * @param projectId - Claude project directory name, or project path for Gemini
+   * @param engine - The engine that produced the session
+   * @param modelA - Baseline model
+   * @param modelB - Model to compare against
+   * @returns Promise resolving to the cost comparison
+   */
+  async compareModelCost(
+    sessionId: string,
+    projectId: string,
+    engine: "claude" | "codex" | "gemini",
+    modelA: string,
+    modelB: string
+  ): Promise<CostComparison> {
+    try {
+      return await invoke<CostComparison>("compare_model_cost", {
+        sessionId,
+        projectId,
+        engine,
+        modelA,
+        modelB,
+      });
+    } catch (error) {
+      console.error("Failed to compare model cost:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Exports a usage report for all engines to a file
+   * @param from - Start date in local time (YYYY-MM-DD, inclusive)
+   * @param to - End date in local time (YYYY-MM-DD, inclusive)
+   * @param groupBy - Dimensions to group rows by, empty for a single total row
+   * @param format - Output format
+   * @param outputPath - Destination file chosen by the user
+   * @returns Promise resolving to the row count and totals of the report
+   */
+  async exportUsageReport(
+    from: string,
+    to: string,
+    groupBy: UsageGroupKey[],
+    format: UsageReportFormat,
+    outputPath: string
+  ): Promise<UsageReportSummary> {
+    try {
+      return await invoke<UsageReportSummary>("export_usage_report", {
+        from,
+        to,
+        groupBy,
+        format,
+        outputPath,
+      });
+    } catch (error) {
+      console.error("Failed to export usage report:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Exports one CSV row per session with token counts and estimated cost
+   * Sessions without usage data are written with zero tokens
+   * @param startDate - Start date (YYYY-MM-DD, inclusive)
+   * @param endDate - End date (YYYY-MM-DD, inclusive)
+   * @param engine - Only export this engine's sessions; all engines when omitted
+   * @param outputPath - Destination file chosen by the user
+   * @returns Promise resolving to the number of rows written
+   */
+  async exportUsageCsv(
+    startDate: string,
+    endDate: string,
+    engine: 'claude' | 'codex' | 'gemini' | undefined,
+    outputPath: string
+  ): Promise<number> {
+    try {
+      return await invoke<number>("export_usage_csv", {
+        startDate,
+        endDate,
+        engine,
+        outputPath,
+      });
+    } catch (error) {
+      console.error("Failed to export usage CSV:", error);
+      throw error;
+    }
+  },
+
+
+
+
+  /**
+   * Gets cache tokens for a specific session
+   * @param sessionId - The session ID to get cache tokens for
+   * @returns Promise resolving to session cache tokens
+   */
+  async getSessionCacheTokens(sessionId: string): Promise<SessionCacheTokens> {
+    try {
+      return await invoke<SessionCacheTokens>("get_session_cache_tokens", { sessionId });
+    } catch (error) {
+      console.error("Failed to get session cache tokens:", error);
+      throw error;
+    }
+  },
+
+  // ============================================================================
+  // CODEX USAGE STATISTICS
+  // ============================================================================
+
+  /**
+   * Gets Codex usage statistics
+   * @param startDate - Optional start date (YYYY-MM-DD)
+   * @param endDate - Optional end date (YYYY-MM-DD)
+   * @returns Promise resolving to Codex usage statistics
+   */
+  async getCodexUsageStats(
+    startDate?: string,
+    endDate?: string
+  ): Promise<import('@/types/usage').CodexUsageStats> {
+    try {
+      return await invoke<import('@/types/usage').CodexUsageStats>("get_codex_usage_stats", {
+        startDate,
+        endDate,
+      });
+    } catch (error) {
+      console.error("Failed to get Codex usage stats:", error);
+      throw error;
+    }
+  },
+
+  // ============================================================================
+  // GEMINI USAGE STATISTICS
+  // ============================================================================
+
+  /**
+   * Gets Gemini usage statistics
+   * @param startDate - Optional start date (YYYY-MM-DD)
+   * @param endDate - Optional end date (YYYY-MM-DD)
+   * @returns Promise resolving to Gemini usage statistics
+   */
+  async getGeminiUsageStats(
+    startDate?: string,
+    endDate?: string
+  ): Promise<import('@/types/usage').GeminiUsageStats> {
+    try {
+      return await invoke<import('@/types/usage').GeminiUsageStats>("get_gemini_usage_stats", {
+        startDate,
+        endDate,
+      });
+    } catch (error) {
+      console.error("Failed to get Gemini usage stats:", error);
+      throw error;
+    }
+  },
+
+  // ============================================================================
+  // MCP SERVER OPERATIONS
+  // ============================================================================
+
+  /**
+   * Adds a new MCP server
+   */
+  async mcpAdd(
+    name: string,
+    transport: string,
+    command?: string,
+    args: string[] = [],
+    env: Record<string, string> = {},
+    url?: string,
+    scope: string = "local"
+  ): Promise<AddServerResult> {
+    try {
+      return await invoke<AddServerResult>("mcp_add", {
+        name,
+        transport,
+        command,
+        args,
+        env,
+        url,
+        scope
+      });
+    } catch (error) {
+      console.error("Failed to add MCP server:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Lists all configured MCP servers
+   */
+  async mcpList(): Promise<MCPServer[]> {
+    try {
+      return await invoke<MCPServer[]>("mcp_list");
+    } catch (error) {
+      console.error("API: Failed to list MCP servers:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Gets details for a specific MCP server
+   */
+  async mcpGet(name: string): Promise<MCPServer> {
+    try {
+      return await invoke<MCPServer>("mcp_get", { name });
+    } catch (error) {
+      console.error("Failed to get MCP server:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Removes an MCP server
+   */
+  async mcpRemove(name: string): Promise<string> {
+    try {
+      return await invoke<string>("mcp_remove", { name });
+    } catch (error) {
+      console.error("Failed to remove MCP server:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Adds an MCP server from JSON configuration
+   */
+  async mcpAddJson(name: string, jsonConfig: string, scope: string = "local"): Promise<AddServerResult> {
+    try {
+      return await invoke<AddServerResult>("mcp_add_json", { name, jsonConfig, scope });
+    } catch (error) {
+      console.error("Failed to add MCP server from JSON:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Imports MCP servers from Claude Desktop
+   */
+  async mcpAddFromClaudeDesktop(scope: string = "local"): Promise<ImportResult> {
+    try {
+      return await invoke<ImportResult>("mcp_add_from_claude_desktop", { scope });
+    } catch (error) {
+      console.error("Failed to import from Claude Desktop:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Starts Claude Code as an MCP server
+   */
+  async mcpServe(): Promise<string> {
+    try {
+      return await invoke<string>("mcp_serve");
+    } catch (error) {
+      console.error("Failed to start MCP server:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Tests connection to an MCP server
+   */
+  async mcpTestConnection(name: string): Promise<string> {
+    try {
+      return await invoke<string>("mcp_test_connection", { name });
+    } catch (error) {
+      console.error("Failed to test MCP connection:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Exports MCP server configuration from .claude.json
+   */
+  async mcpExportConfig(): Promise<string> {
+    try {
+      return await invoke<string>("mcp_export_config");
+    } catch (error) {
+      console.error("Failed to export MCP configuration:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Resets project-scoped server approval choices
+   */
+  async mcpResetProjectChoices(): Promise<string> {
+    try {
+      return await invoke<string>("mcp_reset_project_choices");
+    } catch (error) {
+      console.error("Failed to reset project choices:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Gets the status of MCP servers
+   */
+  async mcpGetServerStatus(): Promise<Record<string, ServerStatus>> {
+    try {
+      return await invoke<Record<string, ServerStatus>>("mcp_get_server_status");
+    } catch (error) {
+      console.error("Failed to get server status:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Reads .mcp.json from the current project
+   */
+  async mcpReadProjectConfig(projectPath: string): Promise<MCPProjectConfig> {
+    try {
+      return await invoke<MCPProjectConfig>("mcp_read_project_config", { projectPath });
+    } catch (error) {
+      console.error("Failed to read project MCP config:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Saves .mcp.json to the current project
+   */
+  async mcpSaveProjectConfig(projectPath: string, config: MCPProjectConfig): Promise<string> {
+    try {
+      return await invoke<string>("mcp_save_project_config", { projectPath, config });
+    } catch (error) {
+      console.error("Failed to save project MCP config:", error);
+      throw error;
+    }
+  },
+
+  // ============================================================================
+  // MCP 多应用支持方法（新版）
+  // ============================================================================
+
+  /**
+   * 获取 Claude MCP 配置状态
+   */
+  async mcpGetStatus(): Promise<McpStatus> {
+    try {
+      return await invoke<McpStatus>("mcp_get_claude_status");
+    } catch (error) {
+      console.error("Failed to get MCP status:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * 获取所有 MCP 服务器（从 Claude 配置）
+   * @deprecated 使用 mcpGetUnifiedServers 获取真实的多应用状态
+   */
+  async mcpGetAllServers(): Promise<Record<string, MCPServerSpec>> {
+    try {
+      return await invoke<Record<string, MCPServerSpec>>("mcp_get_all_servers");
+    } catch (error) {
+      console.error("Failed to get all MCP servers:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * 获取所有应用的 MCP 服务器统一视图（推荐）
+   *
+   * 返回合并后的服务器列表，每个服务器的 apps 字段显示真实的启用状态
+   * @deprecated 使用 mcpGetEngineServers 代替，按引擎独立管理
+   */
+  async mcpGetUnifiedServers(): Promise<Record<string, McpServer>> {
+    try {
+      return await invoke<Record<string, McpServer>>("mcp_get_unified_servers");
+    } catch (error) {
+      console.error("Failed to get unified MCP servers:", error);
+      throw error;
+    }
+  },
+
+  // ============================================================================
+  // 多引擎独立隔离控制 API（新设计）
+  // ============================================================================
+
+  /**
+   * 获取指定引擎的 MCP 服务器列表
+   *
+   * @param engine 引擎名称（"claude" | "codex" | "gemini"）
+   * @returns 该引擎的 MCP 服务器映射
+   */
+  async mcpGetEngineServers(
+    engine: "claude" | "codex" | "gemini"
+  ): Promise<Record<string, MCPServerSpec>> {
+    try {
+      return await invoke<Record<string, MCPServerSpec>>("mcp_get_engine_servers", {
+        engine,
+      });
+    } catch (error) {
+      console.error(`Failed to get ${engine} MCP servers:`, error);
+      throw error;
+    }
+  },
+
+  /**
+   * 在指定引擎中添加或更新 MCP 服务器
+   *
+   * @param engine 引擎名称（"claude" | "codex" | "gemini"）
+   * @param id 服务器 ID
+   * @param serverSpec 服务器规范
+   */
+  async mcpUpsertEngineServer(
+    engine: "claude" | "codex" | "gemini",
+    id: string,
+    serverSpec: MCPServerSpec
+  ): Promise<string> {
+    try {
+      return await invoke<string>("mcp_upsert_engine_server", {
+        engine,
+        id,
+        serverSpec,
+      });
+    } catch (error) {
+      console.error(`Failed to upsert ${engine} MCP server:`, error);
+      throw error;
+    }
+  },
+
+  /**
+   * 从指定引擎中删除 MCP 服务器
+   *
+   * @param engine 引擎名称（"claude" | "codex" | "gemini"）
+   * @param id 服务器 ID
+   */
+  async mcpDeleteEngineServer(
+    engine: "claude" | "codex" | "gemini",
+    id: string
+  ): Promise<string> {
+    try {
+      return await invoke<string>("mcp_delete_engine_server", {
+        engine,
+        id,
+      });
+    } catch (error) {
+      console.error(`Failed to delete ${engine} MCP server:`, error);
+      throw error;
+    }
+  },
+
+  /**
+   * 切换指定引擎中 MCP 服务器的启用状态
+   *
+   * @param engine 引擎名称（"claude" | "codex" | "gemini"）
+   * @param id 服务器 ID
+   * @param serverSpec 服务器规范
+   * @param enabled 启用状态
+   */
+  async mcpToggleEngineServer(
+    engine: "claude" | "codex" | "gemini",
+    id: string,
+    serverSpec: MCPServerSpec,
+    enabled: boolean
+  ): Promise<string> {
+    try {
+      return await invoke<string>("mcp_toggle_engine_server", {
+        engine,
+        id,
+        serverSpec,
+        enabled,
+      });
+    } catch (error) {
+      console.error(`Failed to toggle ${engine} MCP server:`, error);
+      throw error;
+    }
+  },
+
+  /**
+   * 带启用状态的 MCP 服务器条目
+   */
+  // McpServerWithStatus 类型定义在下方
+
+  /**
+   * 获取指定引擎的 MCP 服务器列表（包含禁用的服务器）
+   *
+   * @param engine 引擎名称（"claude" | "codex" | "gemini"）
+   * @returns 该引擎的 MCP 服务器列表（包含启用状态）
+   */
+  async mcpGetEngineServersWithStatus(
+    engine: "claude" | "codex" | "gemini"
+  ): Promise<McpServerWithStatus[]> {
+    try {
+      return await invoke<McpServerWithStatus[]>("mcp_get_engine_servers_with_status", {
+        engine,
+      });
+    } catch (error) {
+      console.error(`Failed to get ${engine} MCP servers with status:`, error);
+      throw error;
+    }
+  },
+
+  /**
+   * 添加或更新 MCP 服务器（支持多应用）
+   */
+  async mcpUpsertServer(
+    id: string,
+    name: string,
+    serverSpec: MCPServerSpec,
+    apps: McpApps
+  ): Promise<string> {
+    try {
+      return await invoke<string>("mcp_upsert_server", {
+        id,
+        name,
+        serverSpec,
+        apps,
+      });
+    } catch (error) {
+      console.error("Failed to upsert MCP server:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * 删除 MCP 服务器（从所有应用）
+   */
+  async mcpDeleteServer(id: string, apps: McpApps): Promise<string> {
+    try {
+      return await invoke<string>("mcp_delete_server", { id, apps });
+    } catch (error) {
+      console.error("Failed to delete MCP server:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * 切换 MCP 服务器在指定应用的启用状态
+   */
+  async mcpToggleApp(
+    id: string,
+    serverSpec: MCPServerSpec,
+    app: string,
+    enabled: boolean
+  ): Promise<string> {
+    try {
+      return await invoke<string>("mcp_toggle_app", {
+        id,
+        serverSpec,
+        app,
+        enabled,
+      });
+    } catch (error) {
+      console.error("Failed to toggle MCP app:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * 从指定应用导入 MCP 服务器
+   */
+  async mcpImportFromApp(app: string): Promise<string[]> {
+    try {
+      return await invoke<string[]>("mcp_import_from_app", { app });
+    } catch (error) {
+      console.error("Failed to import from app:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * 验证命令是否在 PATH 中可用
+   */
+  async mcpValidateCommand(cmd: string): Promise<boolean> {
+    try {
+      return await invoke<boolean>("mcp_validate_command", { cmd });
+    } catch (error) {
+      console.error("Failed to validate command:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * 读取 Claude MCP 配置文本内容
+   */
+  async mcpReadClaudeConfig(): Promise<string | null> {
+    try {
+      return await invoke<string | null>("mcp_read_claude_config");
+    } catch (error) {
+      console.error("Failed to read Claude MCP config:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * 获取会话中 MCP 调用按 server / 工具聚合的调用次数、平均耗时与失败率
+   * @param projectId - Claude 会话的项目目录名，Codex 会话可传空字符串
+   */
+  async getSessionMcpSummary(
+    sessionId: string,
+    projectId: string,
+    engine: "claude" | "codex"
+  ): Promise<McpSessionSummary> {
+    try {
+      return await invoke<McpSessionSummary>("get_session_mcp_summary", {
+        sessionId,
+        projectId,
+        engine,
+      });
+    } catch (error) {
+      console.error("Failed to get session MCP summary:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * 列出会话中耗时不低于阈值的工具调用（按耗时从长到短）
+   * @param projectId - Claude 为项目目录名，Gemini 为项目路径，Codex 可传空字符串
+   */
+  async getSessionSlowOperations(
+    engine: "claude" | "codex" | "gemini",
+    sessionId: string,
+    projectId: string,
+    thresholdMs: number
+  ): Promise<ToolCallTiming[]> {
+    try {
+      return await invoke<ToolCallTiming[]>("get_session_slow_operations", {
+        engine,
+        sessionId,
+        projectId,
+        thresholdMs,
+      });
+    } catch (error) {
+      console.error("Failed to get session slow operations:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Get the stored Claude binary path from settings
+   * @returns Promise resolving to the path if set, null otherwise
+   */
+  async getClaudeBinaryPath(): Promise<string | null> {
+    try {
+      return await invoke<string | null>("get_claude_binary_path");
+    } catch (error) {
+      console.error("Failed to get Claude binary path:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Set the Claude binary path in settings
+   * @param path - The absolute path to the Claude binary
+   * @returns Promise resolving when the path is saved
+   */
+  async setClaudeBinaryPath(path: string): Promise<void> {
+    try {
+      return await invoke<void>("set_claude_binary_path", { path });
+    } catch (error) {
+      console.error("Failed to set Claude binary path:", error);
+      throw error;
+    }
+  },
+
+
+  // Storage API methods
+
+  /**
+   * Lists all tables in the SQLite database
+   * @returns Promise resolving to an array of table information
+   */
+  async storageListTables(): Promise<any[]> {
+    try {
+      return await invoke<any[]>("storage_list_tables");
+    } catch (error) {
+      console.error("Failed to list tables:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Reads table data with pagination
+   * @param tableName - Name of the table to read
+   * @param page - Page number (1-indexed)
+   * @param pageSize - Number of rows per page
+   * @param searchQuery - Optional search query
+   * @returns Promise resolving to table data with pagination info
+   */
+  async storageReadTable(
+    tableName: string,
+    page: number,
+    pageSize: number,
+    searchQuery?: string
+  ): Promise<any> {
+    try {
+      return await invoke<any>("storage_read_table", {
+        tableName,
+        page,
+        pageSize,
+        searchQuery,
+      });
+    } catch (error) {
+      console.error("Failed to read table:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Updates a row in a table
+   * @param tableName - Name of the table
+   * @param primaryKeyValues - Map of primary key column names to values
+   * @param updates - Map of column names to new values
+   * @returns Promise resolving when the row is updated
+   */
+  async storageUpdateRow(
+    tableName: string,
+    primaryKeyValues: Record<string, any>,
+    updates: Record<string, any>
+  ): Promise<void> {
+    try {
+      return await invoke<void>("storage_update_row", {
+        tableName,
+        primaryKeyValues,
+        updates,
+      });
+    } catch (error) {
+      console.error("Failed to update row:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Deletes a row from a table
+   * @param tableName - Name of the table
+   * @param primaryKeyValues - Map of primary key column names to values
+   * @returns Promise resolving when the row is deleted
+   */
+  async storageDeleteRow(
+    tableName: string,
+    primaryKeyValues: Record<string, any>
+  ): Promise<void> {
+    try {
+      return await invoke<void>("storage_delete_row", {
+        tableName,
+        primaryKeyValues,
+      });
+    } catch (error) {
+      console.error("Failed to delete row:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Inserts a new row into a table
+   * @param tableName - Name of the table
+   * @param values - Map of column names to values
+   * @returns Promise resolving to the last insert row ID
+   */
+  async storageInsertRow(
+    tableName: string,
+    values: Record<string, any>
+  ): Promise<number> {
+    try {
+      return await invoke<number>("storage_insert_row", {
+        tableName,
+        values,
+      });
+    } catch (error) {
+      console.error("Failed to insert row:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Executes a raw SQL query
+   * @param query - SQL query string
+   * @returns Promise resolving to query result
+   */
+  async storageExecuteSql(query: string): Promise<any> {
+    try {
+      return await invoke<any>("storage_execute_sql", { query });
+    } catch (error) {
+      console.error("Failed to execute SQL:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Resets the entire database
+   * @returns Promise resolving when the database is reset
+   */
+  async storageResetDatabase(): Promise<void> {
+    try {
+      return await invoke<void>("storage_reset_database");
+    } catch (error) {
+      console.error("Failed to reset database:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Get hooks configuration for a specific scope
+   * @param scope - The configuration scope: 'user', 'project', or 'local'
+   * @param projectPath - Project path (required for project and local scopes)
+   * @returns Promise resolving to the hooks configuration
+   */
+  async getHooksConfig(scope: 'user' | 'project' | 'local', projectPath?: string): Promise<HooksConfiguration> {
+    try {
+      return await invoke<HooksConfiguration>("get_hooks_config", { scope, projectPath });
+    } catch (error) {
+      console.error("Failed to get hooks config:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Update hooks configuration for a specific scope
+   * @param scope - The configuration scope: 'user', 'project', or 'local'
+   * @param hooks - The hooks configuration to save
+   * @param projectPath - Project path (required for project and local scopes)
+   * @returns Promise resolving to success message
+   */
+  async updateHooksConfig(
+    scope: 'user' | 'project' | 'local',
+    hooks: HooksConfiguration,
+    projectPath?: string
+  ): Promise<string> {
+    try {
+      return await invoke<string>("update_hooks_config", { scope, projectPath, hooks });
+    } catch (error) {
+      console.error("Failed to update hooks config:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Validate a hook command syntax
+   * @param command - The shell command to validate
+   * @returns Promise resolving to validation result
+   */
+  async validateHookCommand(command: string): Promise<{ valid: boolean; message: string }> {
+    try {
+      return await invoke<{ valid: boolean; message: string }>("validate_hook_command", { command });
+    } catch (error) {
+      console.error("Failed to validate hook command:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Get merged hooks configuration (respecting priority)
+   * @param projectPath - The project path
+   * @returns Promise resolving to merged hooks configuration
+   */
+  async getMergedHooksConfig(projectPath: string): Promise<HooksConfiguration> {
+    try {
+      const [userHooks, projectHooks, localHooks] = await Promise.all([
+        this.getHooksConfig('user'),
+        this.getHooksConfig('project', projectPath),
+        this.getHooksConfig('local', projectPath)
+      ]);
+
+      return HooksManager.mergeConfigs(userHooks, projectHooks, localHooks);
+    } catch (error) {
+      console.error("Failed to get merged hooks config:", error);
+      throw error;
+    }
+  },
+
+
+  /**
+   * Set custom Claude CLI path
+   * @param customPath - Path to custom Claude CLI executable
+   * @returns Promise resolving when path is set successfully
+   */
+  async setCustomClaudePath(customPath: string): Promise<void> {
+    try {
+      return await invoke<void>("set_custom_claude_path", { customPath });
+    } catch (error) {
+      console.error("Failed to set custom Claude path:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Get current Claude CLI path (custom or auto-detected)
+   * @returns Promise resolving to current Claude CLI path
+   */
+  async getClaudePath(): Promise<string> {
+    try {
+      return await invoke<string>("get_claude_path");
+    } catch (error) {
+      console.error("Failed to get Claude path:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Clear custom Claude CLI path and revert to auto-detection
+   * @returns Promise resolving when custom path is cleared
+   */
+  async clearCustomClaudePath(): Promise<void> {
+    try {
+      return await invoke<void>("clear_custom_claude_path");
+    } catch (error) {
+      console.error("Failed to clear custom Claude path:", error);
+      throw error;
+    }
+  },
+
+
+
+  // Clipboard API methods
+
+  /**
+   * Saves clipboard image data to a temporary file
+   * @param base64Data - Base64 encoded image data
+   * @param format - Optional image format
+   * @returns Promise resolving to saved image result
+   */
+  async saveClipboardImage(base64Data: string, format?: string): Promise<SavedImageResult> {
+    try {
+      return await invoke<SavedImageResult>("save_clipboard_image", { base64Data, format });
+    } catch (error) {
+      console.error("Failed to save clipboard image:", error);
+      throw error;
+    }
+  },
+
+  // Provider Management API methods
+
+  /**
+   * Gets the list of preset provider configurations
+   * @returns Promise resolving to array of provider configurations
+   */
+  async getProviderPresets(): Promise<ProviderConfig[]> {
+    try {
+      return await invoke<ProviderConfig[]>("get_provider_presets");
+    } catch (error) {
+      console.error("Failed to get provider presets:", error);
+      throw toCommandError(error);
+    }
+  },
+
+  /**
+   * Gets the current provider configuration from environment variables
+   * @returns Promise resolving to current configuration
+   */
+  async getCurrentProviderConfig(): Promise<CurrentProviderConfig> {
+    try {
+      return await invoke<CurrentProviderConfig>("get_current_provider_config");
+    } catch (error) {
+      console.error("Failed to get current provider config:", error);
+      throw toCommandError(error);
+    }
+  },
+
+  /**
+   * Switches to a new provider configuration
+   * @param config - The provider configuration to switch to
+   * @returns Promise resolving to success message
+   */
+  async switchProviderConfig(config: ProviderConfig): Promise<string> {
+    try {
+      return await invoke<string>("switch_provider_config", { config });
+    } catch (error) {
+      console.error("Failed to switch provider config:", error);
+      throw toCommandError(error);
+    }
+  },
+
+  /**
+   * Clears all provider-related environment variables
+   * @returns Promise resolving to success message
+   */
+  async clearProviderConfig(): Promise<string> {
+    try {
+      return await invoke<string>("clear_provider_config");
+    } catch (error) {
+      console.error("Failed to clear provider config:", error);
+      throw toCommandError(error);
+    }
+  },
+
+  /**
+   * Tests connection to a provider endpoint
+   * @param baseUrl - The base URL to test
+   * @returns Promise resolving to test result message
+   */
+  async testProviderConnection(baseUrl: string): Promise<string> {
+    try {
+      return await invoke<string>("test_provider_connection", { baseUrl });
+    } catch (error) {
+      console.error("Failed to test provider connection:", error);
+      throw toCommandError(error);
+    }
+  },
+
+  /**
+   * Adds a new provider configuration
+   * @param config - The provider configuration to add
+   * @returns Promise resolving to success message
+   */
+  async addProviderConfig(config: Omit<ProviderConfig, 'id'>): Promise<string> {
+    // Generate ID from name
+    const id = config.name
+      .toLowerCase()
+      .replace(/[^a-z0-9]/g, '-')
+      .replace(/-+/g, '-')
+      .replace(/^-|-$/g, '');
+
+    const fullConfig: ProviderConfig = {
+      ...config,
+      id
+    };
+
+    try {
+      return await invoke<string>("add_provider_config", { config: fullConfig });
+    } catch (error) {
+      console.error("Failed to add provider config:", error);
+      throw toCommandError(error);
+    }
+  },
+
+  /**
+   * Updates an existing provider configuration
+   * @param config - The provider configuration to update (with id)
+   * @returns Promise resolving to success message
+   */
+  async updateProviderConfig(config: ProviderConfig): Promise<string> {
+    try {
+      return await invoke<string>("update_provider_config", { config });
+    } catch (error) {
+      console.error("Failed to update provider config:", error);
+      throw toCommandError(error);
+    }
+  },
+
+  /**
+   * Deletes a provider configuration by ID
+   * @param id - The ID of the provider configuration to delete
+   * @returns Promise resolving to success message
+   */
+  async deleteProviderConfig(id: string): Promise<string> {
+    try {
+      return await invoke<string>("delete_provider_config", { id });
+    } catch (error) {
+      console.error("Failed to delete provider config:", error);
+      throw toCommandError(error);
+    }
+  },
+
+  /**
+   * Gets a single provider configuration by ID
+   * @param id - The ID of the provider configuration to get
+   * @returns Promise resolving to provider configuration
+   */
+  async getProviderConfig(id: string): Promise<ProviderConfig> {
+    try {
+      return await invoke<ProviderConfig>("get_provider_config", { id });
+    } catch (error) {
+      console.error("Failed to get provider config:", error);
+      throw toCommandError(error);
+    }
+  },
+
+  /**
+   * Queries API Key usage/balance from the provider
+   * @param baseUrl - The base URL of the provider API
+   * @param apiKey - The API key to query usage for
+   * @returns Promise resolving to API key usage information
+   */
+  async queryProviderUsage(baseUrl: string, apiKey: string): Promise<ApiKeyUsage> {
+    try {
+      return await invoke<ApiKeyUsage>("query_provider_usage", { baseUrl, apiKey });
+    } catch (error) {
+      console.error("Failed to query provider usage:", error);
+      throw toCommandError(error);
+    }
+  },
+
+  /**
+   * Reorders provider configurations
+   * @param ids - Array of provider IDs in the desired order
+   * @returns Promise resolving to success message
+   */
+  async reorderProviderConfigs(ids: string[]): Promise<string> {
+    try {
+      return await invoke<string>("reorder_provider_configs", { ids });
+    } catch (error) {
+      console.error("Failed to reorder provider configs:", error);
+      throw toCommandError(error);
+    }
+  },
+
+
+  // ============================================================================
+  // ACEMCP INTEGRATION
+  // ============================================================================
+
+  /**
+   * Enhances a prompt by adding project context from acemcp semantic search
+   * 🆕 v2: 支持历史上下文感知和多轮搜索
+   *
+   * @param prompt - The original prompt to enhance
+   * @param projectPath - Path to the project directory
+   * @param sessionId - 🆕 Optional session ID for history-aware search
+   * @param projectId - 🆕 Optional project ID for history-aware search
+   * @param maxContextLength - Maximum length of context to include (default: 3000)
+   * @param enableMultiRound - 🆕 Enable multi-round search for better coverage (default: true)
+   * @param searchDeadlineSecs - Overall deadline for concurrent multi-round search (default: 20)
+   * @param streamId - Optional id echoed in `acemcp-context-snippet` events, which stream each new multi-round snippet as it is found
+   * @param alreadyIncludedPaths - Files already referenced in the prompt (@ mentions, pasted paths); their snippets are left out
+   * @param excludeRecentDiff - Also leave out snippets of files with uncommitted changes
+   * @returns Promise resolving to enhancement result
+   */
+  async enhancePromptWithContext(
+    prompt: string,
+    projectPath: string,
+    sessionId?: string,
+    projectId?: string,
+    maxContextLength?: number,
+    enableMultiRound?: boolean,
+    searchDeadlineSecs?: number,
+    streamId?: string,
+    alreadyIncludedPaths?: string[],
+    excludeRecentDiff?: boolean
+  ): Promise<{
+    originalPrompt: string;
+    enhancedPrompt: string;
+    contextCount: number;
+    acemcpUsed: boolean;
+    partial: boolean;
+    /** Snippets left out because their files were already included */
+    excludedCount: number;
+    /** Structured snippets behind the context block, for jumping to source */
+    snippets: ContextSnippet[];
+    error?: string;
+  }> {
+    try {
+      return await invoke("enhance_prompt_with_context", {
+        prompt,
+        projectPath,
+        sessionId,
+        projectId,
+        maxContextLength,
+        enableMultiRound,
+        searchDeadlineSecs,
+        streamId,
+        alreadyIncludedPaths,
+        excludeRecentDiff,
+      });
+    } catch (error) {
+      console.error("Failed to enhance prompt with context:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Enhance a prompt with acemcp context merged from multiple projects (cross-repo prompts)
+   * @param prompt - The original prompt
+   * @param projectPaths - Project paths to search; the context budget is split evenly between them
+   * @param maxContextLength - Total context budget across all projects (default: 3000)
+   * @param enableMultiRound - Enable multi-round search per project (default: true)
+   * @param searchDeadlineSecs - Overall deadline for each project's search (default: 20)
+   * @returns Promise resolving to the merged enhancement result with per-project snippet counts
+   */
+  async enhancePromptWithMultiProjectContext(
+    prompt: string,
+    projectPaths: string[],
+    maxContextLength?: number,
+    enableMultiRound?: boolean,
+    searchDeadlineSecs?: number
+  ): Promise<{
+    originalPrompt: string;
+    enhancedPrompt: string;
+    contextCount: number;
+    acemcpUsed: boolean;
+    partial: boolean;
+    projects: {
+      projectPath: string;
+      snippetCount: number;
+      partial: boolean;
+      error?: string;
+    }[];
+    error?: string;
+  }> {
+    try {
+      return await invoke("enhance_prompt_with_multi_project_context", {
+        prompt,
+        projectPaths,
+        maxContextLength,
+        enableMultiRound,
+        searchDeadlineSecs,
+      });
+    } catch (error) {
+      console.error("Failed to enhance prompt with multi-project context:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Tests if acemcp is available and can be used
+   * @returns Promise resolving to true if acemcp is available
+   */
+  async testAcemcpAvailability(): Promise<boolean> {
+    try {
+      return await invoke<boolean>("test_acemcp_availability");
+    } catch (error) {
+      console.error("Failed to test acemcp availability:", error);
+      return false;
+    }
+  },
+
+  /**
+   * Previews the keywords and search queries acemcp would derive from a prompt,
+   * without starting the sidecar or touching the index
+   * @param prompt - The prompt to analyze
+   * @param enableMultiRound - Include the multi-round queries (default: true)
+   * @returns Promise resolving to the extracted keywords and queries
+   */
+  async previewPromptKeywords(prompt: string, enableMultiRound?: boolean): Promise<PromptKeywordsPreview> {
+    try {
+      return await invoke<PromptKeywordsPreview>("preview_prompt_keywords", { prompt, enableMultiRound });
+    } catch (error) {
+      console.error("Failed to preview prompt keywords:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Saves acemcp configuration to ~/.acemcp/settings.toml
+   */
+  async saveAcemcpConfig(
+    baseUrl: string,
+    token: string,
+    batchSize?: number,
+    maxLinesPerBlob?: number,
+    requestTimeoutSecs?: number,
+    idleTimeoutMins?: number
+  ): Promise<void> {
+    try {
+      return await invoke("save_acemcp_config", {
+        baseUrl,
+        token,
+        batchSize,
+        maxLinesPerBlob,
+        requestTimeoutSecs,
+        idleTimeoutMins,
+      });
+    } catch (error) {
+      console.error("Failed to save acemcp config:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Loads acemcp configuration from ~/.acemcp/settings.toml
+   */
+  async loadAcemcpConfig(): Promise<{
+    baseUrl: string;
+    token: string;
+    batchSize?: number;
+    maxLinesPerBlob?: number;
+    /** Timeout for search/index requests in seconds */
+    requestTimeoutSecs?: number;
+    /** Minutes an idle sidecar process is kept alive before shutdown */
+    idleTimeoutMins?: number;
+  }> {
+    try {
+      return await invoke("load_acemcp_config");
+    } catch (error) {
+      console.error("Failed to load acemcp config:", error);
+      // 返回默认配置
+      return {
+        baseUrl: '',
+        token: '',
+        batchSize: 10,
+        maxLinesPerBlob: 800,
+        requestTimeoutSecs: 60,
+        idleTimeoutMins: 10,
+      };
+    }
+  },
+
+  /**
+   * Loads the user-defined keyword lists from ~/.acemcp/keywords.toml (without the built-in lists)
+   * @returns Promise resolving to the user keyword lists (empty when the file does not exist)
+   */
+  async getAcemcpKeywordConfig(): Promise<AcemcpKeywordConfig> {
+    try {
+      return await invoke<AcemcpKeywordConfig>("get_acemcp_keyword_config");
+    } catch (error) {
+      console.error("Failed to load acemcp keyword config:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Saves the user-defined keyword lists to ~/.acemcp/keywords.toml; takes effect on the next prompt
+   * @param config - The keyword lists to save
+   * @returns Promise resolving to the saved (trimmed and deduplicated) lists
+   */
+  async saveAcemcpKeywordConfig(config: AcemcpKeywordConfig): Promise<AcemcpKeywordConfig> {
+    try {
+      return await invoke<AcemcpKeywordConfig>("save_acemcp_keyword_config", { config });
+    } catch (error) {
+      console.error("Failed to save acemcp keyword config:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Pre-indexes a project in background (non-blocking)
+   * Automatically triggered when user selects a project; progress is emitted as
+   * "acemcp-preindex-progress" events
+   */
+  async preindexProject(projectPath: string): Promise<void> {
+    try {
+      // 后台执行，不等待结果
+      invoke("preindex_project", { projectPath }).catch((error) => {
+        console.warn("Background pre-indexing failed:", error);
+      });
+    } catch (error) {
+      console.warn("Failed to start pre-indexing:", error);
+    }
+  },
+
+  /**
+   * Cancels the background pre-indexing of a project and stops its sidecar
+   * @param projectPath - The project path passed to preindexProject
+   * @returns Promise resolving to true if a running pre-index was cancelled
+   */
+  async cancelPreindex(projectPath: string): Promise<boolean> {
+    return invoke<boolean>("cancel_preindex", { projectPath });
+  },
+
+  /**
+   * Gets the last pre-indexing status of a project and its failure reason (null if never pre-indexed)
+   * Status changes are also emitted as "acemcp-preindex-status" events with the same fields
+   */
+  async getPreindexStatus(projectPath: string): Promise<PreindexStatusInfo | null> {
+    return invoke<PreindexStatusInfo | null>("get_preindex_status", { projectPath });
+  },
+
+  /**
+   * Reindexes files changed in a project (incrementally when the sidecar supports it)
+   * @param projectPath - The project root path
+   * @param changedPaths - Changed file paths, absolute or relative to the project root
+   * @returns Promise resolving to how the files were reindexed
+   */
+  async reindexChangedFiles(projectPath: string, changedPaths: string[]): Promise<ReindexMode> {
+    try {
+      return await invoke<ReindexMode>("reindex_changed_files", { projectPath, changedPaths });
+    } catch (error) {
+      console.error("Failed to reindex changed files:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Watches a project for file changes and reindexes saved files in the background
+   * Only one project is watched at a time; watching another project stops the previous watcher
+   */
+  async watchProjectChanges(projectPath: string): Promise<void> {
+    try {
+      await invoke("watch_project_changes", { projectPath });
+    } catch (error) {
+      console.error("Failed to watch project changes:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Exports the embedded acemcp sidecar to a specified path
+   * For CLI configuration
+   */
+  async exportAcemcpSidecar(targetPath: string): Promise<string> {
+    try {
+      return await invoke<string>("export_acemcp_sidecar", { targetPath });
+    } catch (error) {
+      console.error("Failed to export sidecar:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Gets the path of extracted sidecar in temp directory (if exists)
+   */
+  async getExtractedSidecarPath(): Promise<string | null> {
+    try {
+      return await invoke<string | null>("get_extracted_sidecar_path");
+    } catch (error) {
+      console.error("Failed to get extracted sidecar path:", error);
+      return null;
+    }
+  },
+
+  // Translation API methods
+
+  /**
+   * Translates text using the translation service
+   * @param text - The text to translate
+   * @param targetLang - Optional target language (defaults to auto-detection)
+   * @returns Promise resolving to translated text
+   */
+  async translateText(text: string, targetLang?: string): Promise<string> {
+    try {
+      return await invoke<string>("translate", { text, targetLang });
+    } catch (error) {
+      console.error("Failed to translate text:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Translates multiple texts in batch
+   * @param texts - Array of texts to translate
+   * @param targetLang - Optional target language
+   * @returns Promise resolving to array of translated texts
+   */
+  async translateBatch(texts: string[], targetLang?: string): Promise<string[]> {
+    try {
+      return await invoke<string[]>("translate_batch", { texts, targetLang });
+    } catch (error) {
+      console.error("Failed to batch translate texts:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Gets the current translation configuration
+   * @returns Promise resolving to translation configuration
+   */
+  async getTranslationConfig(): Promise<TranslationConfig> {
+    try {
+      return await invoke<TranslationConfig>("get_translation_config");
+    } catch (error) {
+      console.error("Failed to get translation config:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Updates the translation configuration
+   * @param config - New translation configuration
+   * @returns Promise resolving to success message
+   */
+  async updateTranslationConfig(config: TranslationConfig): Promise<string> {
+    try {
+      return await invoke<string>("update_translation_config", { config });
+    } catch (error) {
+      console.error("Failed to update translation config:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Clears the translation cache
+   * @returns Promise resolving to success message
+   */
+  async clearTranslationCache(): Promise<string> {
+    try {
+      return await invoke<string>("clear_translation_cache");
+    } catch (error) {
+      console.error("Failed to clear translation cache:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Gets translation cache statistics
+   * @returns Promise resolving to cache statistics
+   */
+  async getTranslationCacheStats(): Promise<TranslationCacheStats> {
+    try {
+      return await invoke<TranslationCacheStats>("get_translation_cache_stats");
+    } catch (error) {
+      console.error("Failed to get translation cache stats:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Detects the language of the given text
+   * @param text - The text to analyze
+   * @returns Promise resolving to detected language code
+   */
+  async detectTextLanguage(text: string): Promise<string> {
+    try {
+      return await invoke<string>("detect_text_language", { text });
+    } catch (error) {
+      console.error("Failed to detect text language:", error);
+      throw error;
+    }
+  },
 
-/**
- * Result for individual server import
- */
-export interface ImportServerResult {
-  name: string;
-  success: boolean;
-  error?: string;
-}
+  /**
+   * Initializes the translation service
+   * @param config - Optional translation configuration
+   * @returns Promise resolving to success message
+   */
+  async initTranslationService(config?: TranslationConfig): Promise<string> {
+    try {
+      return await invoke<string>("init_translation_service_command", { config });
+    } catch (error) {
+      console.error("Failed to initialize translation service:", error);
+      throw error;
+    }
+  },
+
+  // Auto-Compact Context Management API methods
 
-/**
- * API client for interacting with the Rust backend
- */
-export const api = {
   /**
-   * Lists all projects in the ~/.claude/projects directory
-   * @returns Promise resolving to an array of projects
+   * Initializes the auto-compact manager
+   * @returns Promise resolving when manager is initialized
    */
-  async listProjects(): Promise<Project[]> {
+  async initAutoCompactManager(): Promise<void> {
     try {
-      return await invoke<Project[]>("list_projects");
+      return await invoke<void>("init_auto_compact_manager");
     } catch (error) {
-      console.error("Failed to list projects:", error);
+      console.error("Failed to initialize auto-compact manager:", error);
       throw error;
     }
   },
 
   /**
-   * Retrieves sessions for a specific project (both Claude and Codex)
-   * @param projectId - The ID of the project to retrieve sessions for
-   * @param projectPath - Optional project path to filter Codex sessions (if not provided, tries to infer from Claude sessions)
-   * @returns Promise resolving to an array of sessions
+   * Registers a Claude session for auto-compact monitoring
+   * @param sessionId - The session ID to register
+   * @param projectPath - The project path
+   * @param model - The model being used
+   * @returns Promise resolving when session is registered
    */
-  async getProjectSessions(projectId: string, projectPath?: string): Promise<Session[]> {
+  async registerAutoCompactSession(sessionId: string, projectPath: string, model: string): Promise<void> {
     try {
-      // Get Claude sessions
-      const claudeSessions = await invoke<Session[]>('get_project_sessions', { projectId });
+      return await invoke<void>("register_auto_compact_session", { sessionId, projectPath, model });
+    } catch (error) {
+      console.error("Failed to register auto-compact session:", error);
+      throw error;
+    }
+  },
 
-      // Get Codex sessions and filter by project path
-      const codexSessions = await this.listCodexSessions();
+  /**
+   * Updates session token count and checks for auto-compact trigger
+   * @param sessionId - The session ID
+   * @param tokenCount - Current token count
+   * @returns Promise resolving to whether compaction was triggered
+   */
+  async updateSessionContext(sessionId: string, tokenCount: number): Promise<boolean> {
+    try {
+      return await invoke<boolean>("update_session_context", { sessionId, tokenCount });
+    } catch (error) {
+      console.error("Failed to update session context:", error);
+      throw error;
+    }
+  },
 
-      const targetPath = projectPath || claudeSessions[0]?.project_path;
+  /**
+   * Manually triggers compaction for a session
+   * @param sessionId - The session ID
+   * @param customInstructions - Optional custom compaction instructions
+   * @returns Promise resolving when compaction is complete
+   */
+  async triggerManualCompaction(sessionId: string, customInstructions?: string): Promise<void> {
+    try {
+      return await invoke<void>("trigger_manual_compaction", { sessionId, customInstructions });
+    } catch (error) {
+      console.error("Failed to trigger manual compaction:", error);
+      throw error;
+    }
+  },
 
-      // Normalize paths for comparison (handle Windows backslashes and case insensitivity)
-      const normalize = (p: string) => p ? p.replace(/\\/g, '/').replace(/\/$/, '').toLowerCase() : '';
-      const targetPathNorm = normalize(targetPath || '');
+  /**
+   * Gets the current auto-compact configuration
+   * @returns Promise resolving to the configuration
+   */
+  async getAutoCompactConfig(): Promise<AutoCompactConfig> {
+    try {
+      return await invoke<AutoCompactConfig>("get_auto_compact_config");
+    } catch (error) {
+      console.error("Failed to get auto-compact config:", error);
+      throw error;
+    }
+  },
 
-      const filteredCodexSessions: Session[] = codexSessions
-        .filter(cs => {
-          // If we don't have a target path, we can't filter, so return no Codex sessions
-          if (!targetPathNorm) return false;
+  /**
+   * Updates the auto-compact configuration
+   * @param config - The new configuration
+   * @returns Promise resolving when configuration is updated
+   */
+  async updateAutoCompactConfig(config: AutoCompactConfig): Promise<void> {
+    try {
+      return await invoke<void>("update_auto_compact_config", { config });
+    } catch (error) {
+      console.error("Failed to update auto-compact config:", error);
+      throw error;
+    }
+  },
 
-          const csPathNorm = normalize(cs.projectPath);
-          const match = csPathNorm === targetPathNorm;
+  /**
+   * Gets session context statistics
+   * @param sessionId - The session ID
+   * @returns Promise resolving to session context information
+   */
+  async getSessionContextStats(sessionId: string): Promise<SessionContext | null> {
+    try {
+      return await invoke<SessionContext | null>("get_session_context_stats", { sessionId });
+    } catch (error) {
+      console.error("Failed to get session context stats:", error);
+      throw error;
+    }
+  },
 
-          return match;
-        })
-        .map(cs => ({
-          id: cs.id,
-          project_id: projectId,
-          project_path: cs.projectPath,
-          created_at: cs.createdAt,
-          model: cs.model || 'gpt-5.1-codex-max',
-          engine: 'codex' as const,
-          // 🆕 Use actual first message from JSONL file
-          first_message: cs.firstMessage || `Codex Session`,
-          last_message_timestamp: cs.lastMessageTimestamp,
-        }));
+  /**
+   * Gets all monitored sessions
+   * @returns Promise resolving to array of session contexts
+   */
+  async getAllMonitoredSessions(): Promise<SessionContext[]> {
+    try {
+      return await invoke<SessionContext[]>("get_all_monitored_sessions");
+    } catch (error) {
+      console.error("Failed to get monitored sessions:", error);
+      throw error;
+    }
+  },
 
-      // Merge and sort by creation time
-      const allSessions = [...claudeSessions.map(s => ({ ...s, engine: 'claude' as const })), ...filteredCodexSessions];
-      allSessions.sort((a, b) => b.created_at - a.created_at);
+  /**
+   * Unregisters session from auto-compact monitoring
+   * @param sessionId - The session ID to unregister
+   * @returns Promise resolving when session is unregistered
+   */
+  async unregisterAutoCompactSession(sessionId: string): Promise<void> {
+    try {
+      return await invoke<void>("unregister_auto_compact_session", { sessionId });
+    } catch (error) {
+      console.error("Failed to unregister auto-compact session:", error);
+      throw error;
+    }
+  },
 
-      return allSessions;
+  /**
+   * Stops auto-compact monitoring
+   * @returns Promise resolving when monitoring is stopped
+   */
+  async stopAutoCompactMonitoring(): Promise<void> {
+    try {
+      return await invoke<void>("stop_auto_compact_monitoring");
     } catch (error) {
-      console.error("Failed to get project sessions:", error);
+      console.error("Failed to stop auto-compact monitoring:", error);
       throw error;
     }
   },
 
   /**
-   * Deletes a session and all its associated data
-   * @param sessionId - The session ID to delete
-   * @param projectId - The project ID this session belongs to
-   * @returns Promise resolving to success message
+   * Starts auto-compact monitoring
+   * @returns Promise resolving when monitoring is started
    */
-  async deleteSession(sessionId: string, projectId: string): Promise<string> {
+  async startAutoCompactMonitoring(): Promise<void> {
     try {
-      return await invoke<string>('delete_session', { sessionId, projectId });
+      return await invoke<void>("start_auto_compact_monitoring");
     } catch (error) {
-      console.error("Failed to delete session:", error);
+      console.error("Failed to start auto-compact monitoring:", error);
       throw error;
     }
   },
 
   /**
-   * Deletes multiple sessions in batch
-   * @param sessionIds - Array of session IDs to delete
-   * @param projectId - The project ID these sessions belong to
-   * @returns Promise resolving to success message
+   * Gets auto-compact status and statistics
+   * @returns Promise resolving to status information
    */
-  async deleteSessionsBatch(sessionIds: string[], projectId: string): Promise<string> {
+  async getAutoCompactStatus(): Promise<AutoCompactStatus> {
     try {
-      return await invoke<string>('delete_sessions_batch', { sessionIds, projectId });
+      return await invoke<AutoCompactStatus>("get_auto_compact_status");
     } catch (error) {
-      console.error("Failed to batch delete sessions:", error);
+      console.error("Failed to get auto-compact status:", error);
       throw error;
     }
   },
 
   /**
-   * Removes a project from the project list (without deleting files)
-   * @param projectId - The ID of the project to remove from list
-   * @returns Promise resolving to success message
+   * Gets active sessions information
+   * @returns Promise resolving to array of active session info
    */
-  async deleteProject(projectId: string): Promise<string> {
+  async getActiveSessions(): Promise<any[]> {
     try {
-      return await invoke<string>('delete_project', { projectId });
+      return await invoke("get_active_sessions");
     } catch (error) {
-      console.error("Failed to remove project from list:", error);
+      console.error('Failed to get active sessions:', error);
       throw error;
     }
   },
 
+  // Subagent Management & Specialization API methods
+
+
+
+
+
+
+
+
+  // Enhanced Hooks Automation API methods
+
   /**
-   * Restores a hidden project back to the project list
-   * @param projectId - The ID of the project to restore
-   * @returns Promise resolving to success message
+   * Triggers a hook event with context
+   * @param event - The hook event name
+   * @param context - The hook execution context
+   * @returns Promise resolving to hook chain execution result
+   */
+  async triggerHookEvent(event: string, context: any): Promise<any> {
+    try {
+      return await invoke<any>("trigger_hook_event", { event, context });
+    } catch (error) {
+      console.error("Failed to trigger hook event:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Tests a hook condition expression
+   * @param condition - The condition expression to test
+   * @param context - The hook context for evaluation
+   * @returns Promise resolving to whether condition is true
    */
-  async restoreProject(projectId: string): Promise<string> {
+  async testHookCondition(condition: string, context: any): Promise<boolean> {
     try {
-      return await invoke<string>('restore_project', { projectId });
+      return await invoke<boolean>("test_hook_condition", { condition, context });
     } catch (error) {
-      console.error("Failed to restore project:", error);
+      console.error("Failed to test hook condition:", error);
       throw error;
     }
   },
 
   /**
-   * Lists all hidden projects
-   * @returns Promise resolving to array of hidden project IDs
+   * Executes pre-commit code review hook with intelligent decision making
+   * @param projectPath - The project path to review
+   * @param config - Optional configuration for the review hook
+   * @returns Promise resolving to commit decision
    */
-  async listHiddenProjects(): Promise<string[]> {
+  async executePreCommitReview(
+    projectPath: string,
+    config?: import('@/types/enhanced-hooks').PreCommitCodeReviewConfig
+  ): Promise<import('@/types/enhanced-hooks').CommitDecision> {
     try {
-      return await invoke<string[]>('list_hidden_projects');
+      return await invoke<import('@/types/enhanced-hooks').CommitDecision>("execute_pre_commit_review", {
+        projectPath,
+        config
+      });
     } catch (error) {
-      console.error("Failed to list hidden projects:", error);
+      console.error("Failed to execute pre-commit review:", error);
       throw error;
     }
   },
 
+  // ==================== Checkpoint API Methods ====================
+
   /**
-   * Permanently delete a project and all its files
-   * @param projectId - The project ID to permanently delete
-   * @returns Promise resolving to success message
+  /**
+   * Tracks a batch of messages for a session for checkpointing
    */
-  async deleteProjectPermanently(projectId: string): Promise<string> {
+  async trackSessionMessages(
+    sessionId: string,
+    projectId: string,
+    projectPath: string,
+    messages: string[]
+  ): Promise<void> {
     try {
-      return await invoke<string>('delete_project_permanently', { projectId });
+      return await invoke<void>("track_session_messages", {
+        sessionId,
+        projectId,
+        projectPath,
+        messages
+      });
     } catch (error) {
-      console.error("Failed to permanently delete project:", error);
+      console.error("Failed to track session messages:", error);
       throw error;
     }
   },
 
+  // ==================== Prompt Revert System ====================
+
   /**
-   * Reads the Claude settings file
-   * @returns Promise resolving to the settings object
-  */
-  async getClaudeSettings(): Promise<ClaudeSettings> {
+   * Check and initialize Git repository
+   */
+  async checkAndInitGit(projectPath: string): Promise<boolean> {
     try {
-      // Due to #[serde(flatten)] in Rust, the result is directly the settings object
-      return await invoke<ClaudeSettings>("get_claude_settings");
+      return await invoke<boolean>("check_and_init_git", { projectPath });
     } catch (error) {
-      console.error("Failed to get Claude settings:", error);
-      throw error;
+      console.error("Failed to check/init Git:", error);
+      return false;
     }
   },
 
   /**
-   * Opens a new Claude Code session
-   * @param path - Optional path to open the session in
-   * @returns Promise resolving when the session is opened
+   * Check if a git reset operation is safe
+   * This prevents accidentally reverting to a much older version when
+   * multiple engines or user manual commits are involved
    */
-  async openNewSession(path?: string): Promise<string> {
+  async checkResetSafety(
+    projectPath: string,
+    targetCommit: string,
+    currentEngine: string
+  ): Promise<ResetSafetyInfo> {
     try {
-      return await invoke<string>("open_new_session", { path });
+      return await invoke<ResetSafetyInfo>("check_reset_safety", {
+        projectPath,
+        targetCommit,
+        currentEngine,
+      });
     } catch (error) {
-      console.error("Failed to open new session:", error);
-      throw error;
+      console.error("Failed to check reset safety:", error);
+      // Return a safe default that allows proceeding
+      return {
+        commitsToLose: 0,
+        hasOtherEngineCommits: false,
+        hasUserCommits: false,
+        commitsSummary: [],
+        safeToProceed: true,
+        warning: null,
+      };
     }
   },
 
   /**
-   * Reads the CLAUDE.md system prompt file
-   * @returns Promise resolving to the system prompt content
+   * Gets the conflicts of an interactive revert (null when none is in progress)
    */
-  async getSystemPrompt(): Promise<string> {
+  async getRevertConflict(projectPath: string): Promise<RevertConflict | null> {
     try {
-      return await invoke<string>("get_system_prompt");
+      return await invoke<RevertConflict | null>("get_revert_conflict", { projectPath });
     } catch (error) {
-      console.error("Failed to get system prompt:", error);
+      console.error("Failed to get revert conflict:", error);
       throw error;
     }
   },
 
   /**
-   * Checks if Claude Code is installed and gets its version
-   * @returns Promise resolving to the version status
+   * Writes the chosen conflict blocks, stages them and continues the revert
+   * @returns hasConflicts stays true while files remain unresolved or a later commit conflicts
    */
-  async checkClaudeVersion(): Promise<ClaudeVersionStatus> {
+  async resolveRevertConflict(
+    projectPath: string,
+    resolutions: ConflictResolution[]
+  ): Promise<RevertResult> {
     try {
-      return await invoke<ClaudeVersionStatus>("check_claude_version");
+      return await invoke<RevertResult>("resolve_revert_conflict", { projectPath, resolutions });
     } catch (error) {
-      console.error("Failed to check Claude version:", error);
+      console.error("Failed to resolve revert conflict:", error);
       throw error;
     }
   },
 
   /**
-   * Saves the CLAUDE.md system prompt file
-   * @param content - The new content for the system prompt
-   * @returns Promise resolving when the file is saved
+   * Abandons an interactive revert and returns to the HEAD from before it started
    */
-  async saveSystemPrompt(content: string): Promise<string> {
+  async abortRevert(projectPath: string): Promise<void> {
     try {
-      return await invoke<string>("save_system_prompt", { content });
+      await invoke("abort_revert", { projectPath });
     } catch (error) {
-      console.error("Failed to save system prompt:", error);
+      console.error("Failed to abort revert:", error);
       throw error;
     }
   },
 
   /**
-   * Reads the AGENTS.md system prompt file from Codex directory
-   * @returns Promise resolving to the Codex system prompt content
+   * Record a prompt being sent
    */
-  async getCodexSystemPrompt(): Promise<string> {
+  async recordPromptSent(
+    sessionId: string,
+    projectId: string,
+    projectPath: string,
+    promptText: string,
+    traceId?: string
+  ): Promise<number> {
     try {
-      return await invoke<string>("get_codex_system_prompt");
+      return await invoke<number>("record_prompt_sent", {
+        sessionId,
+        projectId,
+        projectPath,
+        promptText,
+        traceId
+      });
     } catch (error) {
-      console.error("Failed to get Codex system prompt:", error);
+      console.error("Failed to record prompt:", error);
       throw error;
     }
   },
 
   /**
-   * Saves the AGENTS.md system prompt file to Codex directory
-   * @param content - The new content for the Codex system prompt
-   * @returns Promise resolving when the file is saved
+   * Mark a prompt as completed
    */
-  async saveCodexSystemPrompt(content: string): Promise<string> {
+  async markPromptCompleted(
+    sessionId: string,
+    projectId: string,
+    projectPath: string,
+    promptIndex: number,
+    promptText?: string,
+    traceId?: string
+  ): Promise<PromptCompletionResult> {
     try {
-      return await invoke<string>("save_codex_system_prompt", { content });
+      const payload: Record<string, unknown> = {
+        sessionId,
+        projectId,
+        projectPath,
+        promptIndex
+      };
+      if (promptText !== undefined) {
+        payload.promptText = promptText;
+      }
+      if (traceId !== undefined) {
+        payload.traceId = traceId;
+      }
+      return await invoke<PromptCompletionResult>("mark_prompt_completed", {
+        ...payload
+      });
     } catch (error) {
-      console.error("Failed to save Codex system prompt:", error);
+      console.error("Failed to mark prompt completed:", error);
       throw error;
     }
   },
 
   /**
-   * Reads the GEMINI.md system prompt file from Gemini directory
-   * @returns Promise resolving to the content of GEMINI.md
+   * Revert to a specific prompt with support for different rewind modes
    */
-  async getGeminiSystemPrompt(): Promise<string> {
+  async revertToPrompt(
+    sessionId: string,
+    projectId: string,
+    projectPath: string,
+    promptIndex: number,
+    mode: RewindMode = "both",
+    onConflict: RevertConflictStrategy = "abort"
+  ): Promise<string> {
     try {
-      return await invoke<string>("get_gemini_system_prompt");
+      return await invoke<string>("revert_to_prompt", {
+        sessionId,
+        projectId,
+        projectPath,
+        promptIndex,
+        mode,
+        onConflict
+      });
     } catch (error) {
-      console.error("Failed to get Gemini system prompt:", error);
+      console.error("Failed to revert to prompt:", error);
       throw error;
     }
   },
 
   /**
-   * Saves the GEMINI.md system prompt file to Gemini directory
-   * @param content - The new content for the Gemini system prompt
-   * @returns Promise resolving when the file is saved
+   * Remove prompts in [startIndex, endIndex] from the conversation while keeping later prompts
+   * Only the conversation is edited; code changes are left untouched
    */
-  async saveGeminiSystemPrompt(content: string): Promise<string> {
+  async removePromptRange(
+    sessionId: string,
+    projectId: string,
+    startIndex: number,
+    endIndex: number
+  ): Promise<void> {
     try {
-      return await invoke<string>("save_gemini_system_prompt", { content });
+      await invoke("remove_prompt_range", {
+        sessionId,
+        projectId,
+        startIndex,
+        endIndex
+      });
     } catch (error) {
-      console.error("Failed to save Gemini system prompt:", error);
+      console.error("Failed to remove prompt range:", error);
       throw error;
     }
   },
 
   /**
-   * Saves the Claude settings file
-   * @param settings - The settings object to save
-   * @returns Promise resolving when the settings are saved
+   * Delete a single prompt (and its replies) from the middle of a conversation
+   * Later prompts are kept and code changes are left untouched
+   * @returns The text of the deleted prompt, for restoring to the input field
    */
-  async saveClaudeSettings(settings: ClaudeSettings): Promise<string> {
+  async revertSinglePrompt(
+    sessionId: string,
+    projectId: string,
+    targetIndex: number
+  ): Promise<string> {
     try {
-      return await invoke<string>("save_claude_settings", { settings });
+      return await invoke<string>("revert_single_prompt", {
+        sessionId,
+        projectId,
+        targetIndex
+      });
     } catch (error) {
-      console.error("Failed to save Claude settings:", error);
+      console.error("Failed to revert single prompt:", error);
       throw error;
     }
   },
 
   /**
-   * Updates the thinking mode by modifying MAX_THINKING_TOKENS in settings.json
-   * @param enabled - Whether to enable thinking mode
-   * @param tokens - Optional token limit (defaults to 10000)
-   * @returns Promise resolving when the settings are updated
+   * Revert only the code changes of one prompt, keeping later prompts and the conversation
+   * Fails without touching the repository when the changes conflict with later prompts
+   * @returns The number of reverted commits
    */
-  async updateThinkingMode(enabled: boolean, tokens?: number): Promise<string> {
+  async revertSinglePromptCode(
+    sessionId: string,
+    projectId: string,
+    projectPath: string,
+    promptIndex: number
+  ): Promise<number> {
     try {
-      return await invoke<string>("update_thinking_mode", { enabled, tokens });
+      return await invoke<number>("revert_single_prompt_code", {
+        sessionId,
+        projectId,
+        projectPath,
+        promptIndex
+      });
     } catch (error) {
-      console.error("Failed to update thinking mode:", error);
+      console.error("Failed to revert single prompt code:", error);
       throw error;
     }
   },
 
   /**
-   * Get Claude execution configuration
-   * @returns Promise resolving to the current execution config
+   * Get list of all prompts for a session
+   * Extracts all prompts from .jsonl (single source of truth)
    */
-  async getClaudeExecutionConfig(): Promise<ClaudeExecutionConfig> {
+  async getPromptList(
+    sessionId: string,
+    projectId: string
+  ): Promise<PromptRecord[]> {
     try {
-      return await invoke<ClaudeExecutionConfig>("get_claude_execution_config");
+      return await invoke<PromptRecord[]>("get_prompt_list", {
+        sessionId,
+        projectId
+      });
     } catch (error) {
-      console.error("Failed to get Claude execution config:", error);
-      throw error;
+      console.error("Failed to get prompt list:", error);
+      return [];
     }
   },
 
   /**
-   * Update Claude execution configuration
-   * @param config - The new execution configuration
-   * @returns Promise resolving when the config is saved
+   * Get unified prompt list with git records enriched from .git-records.json
+   * Combines .jsonl prompts (all messages) with git records (hash-based mapping)
+   * This includes both project interface prompts (with git records) and CLI prompts (without git records)
    */
-  async updateClaudeExecutionConfig(config: ClaudeExecutionConfig): Promise<void> {
+  async getUnifiedPromptList(
+    sessionId: string,
+    projectId: string
+  ): Promise<PromptRecord[]> {
     try {
-      return await invoke<void>("update_claude_execution_config", { config });
+      return await invoke<PromptRecord[]>("get_unified_prompt_list", {
+        sessionId,
+        projectId
+      });
     } catch (error) {
-      console.error("Failed to update Claude execution config:", error);
-      throw error;
+      console.error("Failed to get unified prompt list:", error);
+      return [];
     }
   },
 
   /**
-   * Finds all CLAUDE.md files in a project directory
-   * @param projectPath - The absolute path to the project
-   * @returns Promise resolving to an array of CLAUDE.md files
+   * Check rewind capabilities for a specific prompt
+   * Determines whether a prompt can be reverted fully (conversation + code) or partially (conversation only)
    */
-  async findClaudeMdFiles(projectPath: string): Promise<ClaudeMdFile[]> {
+  async checkRewindCapabilities(
+    sessionId: string,
+    projectId: string,
+    promptIndex: number
+  ): Promise<RewindCapabilities> {
     try {
-      return await invoke<ClaudeMdFile[]>("find_claude_md_files", { projectPath });
+      return await invoke<RewindCapabilities>("check_rewind_capabilities", {
+        sessionId,
+        projectId,
+        promptIndex
+      });
     } catch (error) {
-      console.error("Failed to find CLAUDE.md files:", error);
+      console.error("Failed to check rewind capabilities:", error);
       throw error;
     }
   },
 
   /**
-   * Reads a specific CLAUDE.md file
-   * @param filePath - The absolute path to the file
-   * @returns Promise resolving to the file content
+   * Re-derives prompt sources for a session: prompts with git records become "project"
+   * so legacy or converted sessions can revert code
    */
-  async readClaudeMdFile(filePath: string): Promise<string> {
+  async reclassifyPromptSources(sessionId: string, projectId: string): Promise<ReclassifyResult> {
     try {
-      return await invoke<string>("read_claude_md_file", { filePath });
+      return await invoke<ReclassifyResult>("reclassify_prompt_sources", { sessionId, projectId });
     } catch (error) {
-      console.error("Failed to read CLAUDE.md file:", error);
+      console.error("Failed to reclassify prompt sources:", error);
       throw error;
     }
   },
 
   /**
-   * Saves a specific CLAUDE.md file
-   * @param filePath - The absolute path to the file
-   * @param content - The new content for the file
-   * @returns Promise resolving when the file is saved
+   * Gets skill message markers used for prompt filtering
    */
-  async saveClaudeMdFile(filePath: string, content: string): Promise<string> {
+  async getPromptFilterConfig(): Promise<PromptFilterConfig> {
     try {
-      return await invoke<string>("save_claude_md_file", { filePath, content });
+      return await invoke<PromptFilterConfig>("get_prompt_filter_config");
     } catch (error) {
-      console.error("Failed to save CLAUDE.md file:", error);
+      console.error("Failed to get prompt filter config:", error);
       throw error;
     }
   },
 
-
-  /**
-   * Loads the JSONL history for a specific session (Claude or Codex)
-   */
-  async loadSessionHistory(sessionId: string, projectId: string, engine?: 'claude' | 'codex'): Promise<any[]> {
-    // For Codex sessions, read directly from .codex/sessions
-    if (engine === 'codex') {
-      return this.loadCodexSessionHistory(sessionId);
-    }
-    // For Claude sessions, use existing backend
-    return invoke("load_session_history", { sessionId, projectId });
-  },
-
   /**
-   * 🆕 Loads Codex session history from JSONL file
+   * Updates skill message markers used for prompt filtering
    */
-  async loadCodexSessionHistory(sessionId: string): Promise<any[]> {
+  async updatePromptFilterConfig(config: PromptFilterConfig): Promise<void> {
     try {
-      return await invoke("load_codex_session_history", { sessionId });
+      return await invoke<void>("update_prompt_filter_config", { config });
     } catch (error) {
-      console.error("Failed to load Codex session history:", error);
+      console.error("Failed to update prompt filter config:", error);
       throw error;
     }
   },
 
-  /**
-   * Executes a new interactive Claude Code session with streaming output
-   * @param planMode - Enable Plan Mode for read-only research and planning
-   * @param tabId - Unique identifier for the tab, used to filter global events
-   */
-  async executeClaudeCode(projectPath: string, prompt: string, model: string, planMode?: boolean, maxThinkingTokens?: number, tabId?: string): Promise<void> {
-    return invoke("execute_claude_code", { projectPath, prompt, model, planMode, maxThinkingTokens, tabId });
-  },
-
-  /**
-   * Continues an existing Claude Code conversation with streaming output
-   * @param planMode - Enable Plan Mode for read-only research and planning
-   * @param tabId - Unique identifier for the tab, used to filter global events
-   */
-  async continueClaudeCode(projectPath: string, prompt: string, model: string, planMode?: boolean, maxThinkingTokens?: number, tabId?: string): Promise<void> {
-    return invoke("continue_claude_code", { projectPath, prompt, model, planMode, maxThinkingTokens, tabId });
-  },
-
-  /**
-   * Resumes an existing Claude Code session by ID with streaming output
-   * @param planMode - Enable Plan Mode for read-only research and planning
-   * @param tabId - Unique identifier for the tab, used to filter global events
-   */
-  async resumeClaudeCode(projectPath: string, sessionId: string, prompt: string, model: string, planMode?: boolean, maxThinkingTokens?: number, tabId?: string): Promise<void> {
-    return invoke("resume_claude_code", { projectPath, sessionId, prompt, model, planMode, maxThinkingTokens, tabId });
-  },
-
-  /**
-   * Cancels the currently running Claude Code execution
-   * @param sessionId - Optional session ID to cancel a specific session
-   */
-  async cancelClaudeExecution(sessionId?: string): Promise<void> {
-    return invoke("cancel_claude_execution", { sessionId });
-  },
-
-  /**
-   * Lists all currently running Claude sessions
-   * @returns Promise resolving to list of running Claude sessions
-   */
-  async listRunningClaudeSessions(): Promise<any[]> {
-    return invoke("list_running_claude_sessions");
-  },
-
-  /**
-   * Gets live output from a Claude session
-   * @param sessionId - The session ID to get output for
-   * @returns Promise resolving to the current live output
-   */
-  async getClaudeSessionOutput(sessionId: string): Promise<string> {
-    return invoke("get_claude_session_output", { sessionId });
-  },
-
-  /**
-   * Lists files and directories in a given path
-   */
-  async listDirectoryContents(directoryPath: string): Promise<FileEntry[]> {
-    return invoke("list_directory_contents", { directoryPath });
-  },
-
-  /**
-   * Searches for files and directories matching a pattern
-   */
-  async searchFiles(basePath: string, query: string): Promise<FileEntry[]> {
-    return invoke("search_files", { basePath, query });
-  },
+  // ==================== Claude Extensions (Plugins, Subagents & Skills) ====================
 
   /**
-   * Gets overall usage statistics
-   * @returns Promise resolving to usage statistics
+   * List all installed plugins
    */
-  async getUsageStats(): Promise<UsageStats> {
+  async listPlugins(projectPath?: string): Promise<any[]> {
     try {
-      return await invoke<UsageStats>("get_usage_stats");
+      return await invoke<any[]>("list_plugins", { projectPath });
     } catch (error) {
-      console.error("Failed to get usage stats:", error);
-      throw error;
+      console.error("Failed to list plugins:", error);
+      return [];
     }
   },
 
-
   /**
-   * Gets usage statistics filtered by date range
-   * @param startDate - Start date (ISO format)
-   * @param endDate - End date (ISO format)
-   * @returns Promise resolving to usage statistics
+   * Open plugins directory
    */
-  async getUsageByDateRange(startDate: string, endDate: string): Promise<UsageStats> {
+  async openPluginsDirectory(projectPath?: string): Promise<string> {
     try {
-      return await invoke<UsageStats>("get_usage_by_date_range", { startDate, endDate });
+      return await invoke<string>("open_plugins_directory", { projectPath });
     } catch (error) {
-      console.error("Failed to get usage by date range:", error);
+      console.error("Failed to open plugins directory:", error);
       throw error;
     }
   },
 
   /**
-   * Gets usage statistics grouped by session
-   * @param since - Optional start date (YYYYMMDD)
-   * @param until - Optional end date (YYYYMMDD)
-   * @param order - Optional sort order ('asc' or 'desc')
-   * @returns Promise resolving to an array of session usage data
+   * List all subagents
    */
-  async getSessionStats(
-    since?: string,
-    until?: string,
-    order?: "asc" | "desc"
-  ): Promise<ProjectUsage[]> {
+  async listSubagents(projectPath?: string): Promise<any[]> {
     try {
-      return await invoke<ProjectUsage[]>("get_session_stats", {
-        since,
-        until,
-        order,
-      });
+      return await invoke<any[]>("list_subagents", { projectPath });
     } catch (error) {
-      console.error("Failed to get session stats:", error);
-      throw error;
+      console.error("Failed to list subagents:", error);
+      return [];
     }
   },
 
-
-
-
   /**
-   * Gets cache tokens for a specific session
-   * @param sessionId - The session ID to get cache tokens for
-   * @returns Promise resolving to session cache tokens
+   * List all agent skills
    */
-  async getSessionCacheTokens(sessionId: string): Promise<SessionCacheTokens> {
+  async listAgentSkills(projectPath?: string): Promise<any[]> {
     try {
-      return await invoke<SessionCacheTokens>("get_session_cache_tokens", { sessionId });
+      return await invoke<any[]>("list_agent_skills", { projectPath });
     } catch (error) {
-      console.error("Failed to get session cache tokens:", error);
-      throw error;
+      console.error("Failed to list agent skills:", error);
+      return [];
     }
   },
 
-  // ============================================================================
-  // CODEX USAGE STATISTICS
-  // ============================================================================
-
   /**
-   * Gets Codex usage statistics
-   * @param startDate - Optional start date (YYYY-MM-DD)
-   * @param endDate - Optional end date (YYYY-MM-DD)
-   * @returns Promise resolving to Codex usage statistics
+   * Read a subagent file
    */
-  async getCodexUsageStats(
-    startDate?: string,
-    endDate?: string
-  ): Promise<import('@/types/usage').CodexUsageStats> {
+  async readSubagent(filePath: string): Promise<string> {
     try {
-      return await invoke<import('@/types/usage').CodexUsageStats>("get_codex_usage_stats", {
-        startDate,
-        endDate,
-      });
+      return await invoke<string>("read_subagent", { filePath });
     } catch (error) {
-      console.error("Failed to get Codex usage stats:", error);
+      console.error("Failed to read subagent:", error);
       throw error;
     }
   },
 
-  // ============================================================================
-  // GEMINI USAGE STATISTICS
-  // ============================================================================
-
   /**
-   * Gets Gemini usage statistics
-   * @param startDate - Optional start date (YYYY-MM-DD)
-   * @param endDate - Optional end date (YYYY-MM-DD)
-   * @returns Promise resolving to Gemini usage statistics
+   * Read a skill file
    */
-  async getGeminiUsageStats(
-    startDate?: string,
-    endDate?: string
-  ): Promise<import('@/types/usage').GeminiUsageStats> {
+  async readSkill(filePath: string): Promise<string> {
     try {
-      return await invoke<import('@/types/usage').GeminiUsageStats>("get_gemini_usage_stats", {
-        startDate,
-        endDate,
-      });
+      return await invoke<string>("read_skill", { filePath });
     } catch (error) {
-      console.error("Failed to get Gemini usage stats:", error);
+      console.error("Failed to read skill:", error);
       throw error;
     }
   },
 
-  // ============================================================================
-  // MCP SERVER OPERATIONS
-  // ============================================================================
-
   /**
-   * Adds a new MCP server
+   * Open agents directory in file explorer
    */
-  async mcpAdd(
-    name: string,
-    transport: string,
-    command?: string,
-    args: string[] = [],
-    env: Record<string, string> = {},
-    url?: string,
-    scope: string = "local"
-  ): Promise<AddServerResult> {
+  async openAgentsDirectory(projectPath?: string): Promise<string> {
     try {
-      return await invoke<AddServerResult>("mcp_add", {
-        name,
-        transport,
-        command,
-        args,
-        env,
-        url,
-        scope
-      });
+      return await invoke<string>("open_agents_directory", { projectPath });
     } catch (error) {
-      console.error("Failed to add MCP server:", error);
+      console.error("Failed to open agents directory:", error);
       throw error;
     }
   },
 
   /**
-   * Lists all configured MCP servers
+   * Open skills directory in file explorer
    */
-  async mcpList(): Promise<MCPServer[]> {
+  async openSkillsDirectory(projectPath?: string): Promise<string> {
     try {
-      return await invoke<MCPServer[]>("mcp_list");
+      return await invoke<string>("open_skills_directory", { projectPath });
     } catch (error) {
-      console.error("API: Failed to list MCP servers:", error);
+      console.error("Failed to open skills directory:", error);
       throw error;
     }
   },
 
   /**
-   * Gets details for a specific MCP server
+   * Create a new subagent
+   * @param name - Agent name (alphanumeric, hyphens, underscores only)
+   * @param description - Short description of the agent
+   * @param content - Agent system prompt content
+   * @param scope - "project" or "user"
+   * @param projectPath - Required for project scope
    */
-  async mcpGet(name: string): Promise<MCPServer> {
+  async createSubagent(
+    name: string,
+    description: string,
+    content: string,
+    scope: 'project' | 'user',
+    projectPath?: string
+  ): Promise<{ name: string; path: string; scope: string; description: string; content: string }> {
     try {
-      return await invoke<MCPServer>("mcp_get", { name });
+      return await invoke("create_subagent", { name, description, content, scope, projectPath });
     } catch (error) {
-      console.error("Failed to get MCP server:", error);
+      console.error("Failed to create subagent:", error);
       throw error;
     }
   },
 
   /**
-   * Removes an MCP server
+   * Create a new Agent Skill
+   * @param name - Skill name (alphanumeric, hyphens, underscores only)
+   * @param description - Short description of what this skill does
+   * @param content - Skill instructions content
+   * @param scope - "project" or "user"
+   * @param projectPath - Required for project scope
    */
-  async mcpRemove(name: string): Promise<string> {
+  async createSkill(
+    name: string,
+    description: string,
+    content: string,
+    scope: 'project' | 'user',
+    projectPath?: string
+  ): Promise<{ name: string; path: string; scope: string; description: string; content: string }> {
     try {
-      return await invoke<string>("mcp_remove", { name });
+      return await invoke("create_skill", { name, description, content, scope, projectPath });
     } catch (error) {
-      console.error("Failed to remove MCP server:", error);
+      console.error("Failed to create skill:", error);
       throw error;
     }
   },
 
   /**
-   * Adds an MCP server from JSON configuration
+   * Lists subagent templates (built-in + locally saved; local overrides built-in with the same id)
    */
-  async mcpAddJson(name: string, jsonConfig: string, scope: string = "local"): Promise<AddServerResult> {
+  async listAgentTemplates(): Promise<ExtensionTemplate[]> {
     try {
-      return await invoke<AddServerResult>("mcp_add_json", { name, jsonConfig, scope });
+      return await invoke<ExtensionTemplate[]>("list_agent_templates");
     } catch (error) {
-      console.error("Failed to add MCP server from JSON:", error);
+      console.error("Failed to list agent templates:", error);
       throw error;
     }
   },
 
   /**
-   * Imports MCP servers from Claude Desktop
+   * Lists skill templates (built-in + locally saved; local overrides built-in with the same id)
    */
-  async mcpAddFromClaudeDesktop(scope: string = "local"): Promise<ImportResult> {
+  async listSkillTemplates(): Promise<ExtensionTemplate[]> {
     try {
-      return await invoke<ImportResult>("mcp_add_from_claude_desktop", { scope });
+      return await invoke<ExtensionTemplate[]>("list_skill_templates");
     } catch (error) {
-      console.error("Failed to import from Claude Desktop:", error);
+      console.error("Failed to list skill templates:", error);
       throw error;
     }
   },
 
   /**
-   * Starts Claude Code as an MCP server
+   * Create a subagent from a template ({{name}} placeholders are replaced with the name)
+   * @param templateId - Template id from listAgentTemplates
+   * @param name - Agent name (alphanumeric, hyphens, underscores only)
+   * @param overrides - Optional description/model replacing the template's frontmatter
+   * @param scope - "project" or "user"
+   * @param projectPath - Required for project scope
    */
-  async mcpServe(): Promise<string> {
+  async createSubagentFromTemplate(
+    templateId: string,
+    name: string,
+    overrides: TemplateOverrides | undefined,
+    scope: 'project' | 'user',
+    projectPath?: string
+  ): Promise<{ name: string; path: string; scope: string; description?: string; content: string }> {
     try {
-      return await invoke<string>("mcp_serve");
+      return await invoke("create_subagent_from_template", { templateId, name, overrides, scope, projectPath });
     } catch (error) {
-      console.error("Failed to start MCP server:", error);
+      console.error("Failed to create subagent from template:", error);
       throw error;
     }
   },
 
   /**
-   * Tests connection to an MCP server
+   * Create an Agent Skill from a template ({{name}} placeholders are replaced with the name)
+   * @param templateId - Template id from listSkillTemplates
+   * @param name - Skill name (alphanumeric, hyphens, underscores only)
+   * @param overrides - Optional description/model replacing the template's frontmatter
+   * @param scope - "project" or "user"
+   * @param projectPath - Required for project scope
    */
-  async mcpTestConnection(name: string): Promise<string> {
+  async createSkillFromTemplate(
+    templateId: string,
+    name: string,
+    overrides: TemplateOverrides | undefined,
+    scope: 'project' | 'user',
+    projectPath?: string
+  ): Promise<{ name: string; path: string; scope: string; description?: string; content: string }> {
     try {
-      return await invoke<string>("mcp_test_connection", { name });
+      return await invoke("create_skill_from_template", { templateId, name, overrides, scope, projectPath });
     } catch (error) {
-      console.error("Failed to test MCP connection:", error);
+      console.error("Failed to create skill from template:", error);
       throw error;
     }
   },
 
   /**
-   * Exports MCP server configuration from .claude.json
+   * Save an existing subagent file as a local template (overwrites a local template with the same id)
+   * @param filePath - Path of the agent .md file
+   * @param templateId - Template id (alphanumeric, hyphens, underscores only)
    */
-  async mcpExportConfig(): Promise<string> {
+  async saveAgentAsTemplate(filePath: string, templateId: string): Promise<ExtensionTemplate> {
     try {
-      return await invoke<string>("mcp_export_config");
+      return await invoke<ExtensionTemplate>("save_agent_as_template", { filePath, templateId });
     } catch (error) {
-      console.error("Failed to export MCP configuration:", error);
+      console.error("Failed to save agent as template:", error);
       throw error;
     }
   },
 
   /**
-   * Resets project-scoped server approval choices
+   * Delete a local template (built-in templates cannot be deleted; undoable)
+   * @param kind - "agent" or "skill"
+   * @param templateId - Template id from listAgentTemplates / listSkillTemplates
    */
-  async mcpResetProjectChoices(): Promise<string> {
+  async deleteExtensionTemplate(kind: 'agent' | 'skill', templateId: string): Promise<void> {
     try {
-      return await invoke<string>("mcp_reset_project_choices");
+      await invoke("delete_extension_template", { kind, templateId });
     } catch (error) {
-      console.error("Failed to reset project choices:", error);
+      console.error("Failed to delete template:", error);
       throw error;
     }
   },
 
   /**
-   * Gets the status of MCP servers
+   * Delete a custom slash command file under .claude/commands (undoable)
+   * @param path - Full file path from the command list
    */
-  async mcpGetServerStatus(): Promise<Record<string, ServerStatus>> {
+  async deleteCustomSlashCommand(path: string): Promise<void> {
     try {
-      return await invoke<Record<string, ServerStatus>>("mcp_get_server_status");
+      await invoke("delete_custom_slash_command", { path });
     } catch (error) {
-      console.error("Failed to get server status:", error);
+      console.error("Failed to delete custom slash command:", error);
       throw error;
     }
   },
 
   /**
-   * Reads .mcp.json from the current project
+   * Open a directory in system file explorer (cross-platform)
    */
-  async mcpReadProjectConfig(projectPath: string): Promise<MCPProjectConfig> {
+  async openDirectoryInExplorer(directoryPath: string): Promise<void> {
     try {
-      return await invoke<MCPProjectConfig>("mcp_read_project_config", { projectPath });
+      return await invoke<void>("open_directory_in_explorer", { directoryPath });
     } catch (error) {
-      console.error("Failed to read project MCP config:", error);
+      console.error("Failed to open directory in explorer:", error);
       throw error;
     }
   },
 
   /**
-   * Saves .mcp.json to the current project
+   * Open a file with system default application (cross-platform)
    */
-  async mcpSaveProjectConfig(projectPath: string, config: MCPProjectConfig): Promise<string> {
+  async openFileWithDefaultApp(filePath: string): Promise<void> {
     try {
-      return await invoke<string>("mcp_save_project_config", { projectPath, config });
+      return await invoke<void>("open_file_with_default_app", { filePath });
     } catch (error) {
-      console.error("Failed to save project MCP config:", error);
+      console.error("Failed to open file with default app:", error);
       throw error;
     }
   },
 
-  // ============================================================================
-  // MCP 多应用支持方法（新版）
-  // ============================================================================
+  // ==================== Git Statistics ====================
 
   /**
-   * 获取 Claude MCP 配置状态
+   * Get Git diff statistics between commits
    */
-  async mcpGetStatus(): Promise<McpStatus> {
+  async getGitDiffStats(
+    projectPath: string,
+    fromCommit: string,
+    toCommit?: string
+  ): Promise<{ linesAdded: number; linesRemoved: number; filesChanged: number }> {
     try {
-      return await invoke<McpStatus>("mcp_get_claude_status");
+      return await invoke("get_git_diff_stats", { projectPath, fromCommit, toCommit });
     } catch (error) {
-      console.error("Failed to get MCP status:", error);
+      console.error("Failed to get git diff stats:", error);
       throw error;
     }
   },
 
   /**
-   * 获取所有 MCP 服务器（从 Claude 配置）
-   * @deprecated 使用 mcpGetUnifiedServers 获取真实的多应用状态
+   * Get code changes for current session
    */
-  async mcpGetAllServers(): Promise<Record<string, MCPServerSpec>> {
+  async getSessionCodeChanges(
+    projectPath: string,
+    sessionStartCommit: string
+  ): Promise<{ linesAdded: number; linesRemoved: number; filesChanged: number }> {
     try {
-      return await invoke<Record<string, MCPServerSpec>>("mcp_get_all_servers");
+      return await invoke("get_session_code_changes", { projectPath, sessionStartCommit });
     } catch (error) {
-      console.error("Failed to get all MCP servers:", error);
+      console.error("Failed to get session code changes:", error);
       throw error;
     }
   },
 
+  // ==================== OpenAI Codex Integration ====================
+
   /**
-   * 获取所有应用的 MCP 服务器统一视图（推荐）
-   *
-   * 返回合并后的服务器列表，每个服务器的 apps 字段显示真实的启用状态
-   * @deprecated 使用 mcpGetEngineServers 代替，按引擎独立管理
+   * Executes a Codex task in non-interactive mode with streaming output
+   * @param options - Codex execution options
+   * @returns Promise resolving when execution starts (events are streamed via event listeners)
    */
-  async mcpGetUnifiedServers(): Promise<Record<string, McpServer>> {
+  async executeCodex(options: import('@/types/codex').CodexExecutionOptions): Promise<void> {
     try {
-      return await invoke<Record<string, McpServer>>("mcp_get_unified_servers");
+      return await invoke("execute_codex", { options });
     } catch (error) {
-      console.error("Failed to get unified MCP servers:", error);
-      throw error;
+      console.error("Failed to execute Codex:", error);
+      throw toCommandError(error);
     }
   },
 
-  // ============================================================================
-  // 多引擎独立隔离控制 API（新设计）
-  // ============================================================================
-
   /**
-   * 获取指定引擎的 MCP 服务器列表
-   *
-   * @param engine 引擎名称（"claude" | "codex" | "gemini"）
-   * @returns 该引擎的 MCP 服务器映射
+   * Resumes a previous Codex session
+   * @param sessionId - The session ID to resume
+   * @param options - Codex execution options (prompt, mode, etc.)
+   * @returns Promise resolving when execution starts
    */
-  async mcpGetEngineServers(
-    engine: "claude" | "codex" | "gemini"
-  ): Promise<Record<string, MCPServerSpec>> {
+  async resumeCodex(
+    sessionId: string,
+    options: Omit<import('@/types/codex').CodexExecutionOptions, 'sessionId'>
+  ): Promise<void> {
     try {
-      return await invoke<Record<string, MCPServerSpec>>("mcp_get_engine_servers", {
-        engine,
-      });
+      return await invoke("resume_codex", { sessionId, options });
     } catch (error) {
-      console.error(`Failed to get ${engine} MCP servers:`, error);
-      throw error;
+      console.error("Failed to resume Codex session:", error);
+      throw toCommandError(error);
     }
   },
 
   /**
-   * 在指定引擎中添加或更新 MCP 服务器
-   *
-   * @param engine 引擎名称（"claude" | "codex" | "gemini"）
-   * @param id 服务器 ID
-   * @param serverSpec 服务器规范
+   * Estimates the context footprint of resuming a Codex session
+   * @param sessionId - The session ID to resume
+   * @param model - Model to check against (defaults to the session's model)
+   * @param prompt - Prompt about to be sent
    */
-  async mcpUpsertEngineServer(
-    engine: "claude" | "codex" | "gemini",
-    id: string,
-    serverSpec: MCPServerSpec
-  ): Promise<string> {
+  async estimateCodexContext(
+    sessionId: string,
+    model?: string,
+    prompt?: string
+  ): Promise<import('@/types/codex').CodexContextEstimate> {
     try {
-      return await invoke<string>("mcp_upsert_engine_server", {
-        engine,
-        id,
-        serverSpec,
-      });
+      return await invoke("estimate_codex_context", { sessionId, model, prompt });
     } catch (error) {
-      console.error(`Failed to upsert ${engine} MCP server:`, error);
+      console.error("Failed to estimate Codex context:", error);
       throw error;
     }
   },
 
   /**
-   * 从指定引擎中删除 MCP 服务器
-   *
-   * @param engine 引擎名称（"claude" | "codex" | "gemini"）
-   * @param id 服务器 ID
+   * Resumes the last Codex session
+   * @param options - Codex execution options
+   * @returns Promise resolving when execution starts
    */
-  async mcpDeleteEngineServer(
-    engine: "claude" | "codex" | "gemini",
-    id: string
-  ): Promise<string> {
+  async resumeLastCodex(
+    options: Omit<import('@/types/codex').CodexExecutionOptions, 'resumeLast'>
+  ): Promise<void> {
     try {
-      return await invoke<string>("mcp_delete_engine_server", {
-        engine,
-        id,
-      });
+      return await invoke("resume_last_codex", { options });
     } catch (error) {
-      console.error(`Failed to delete ${engine} MCP server:`, error);
-      throw error;
+      console.error("Failed to resume last Codex session:", error);
+      throw toCommandError(error);
     }
   },
 
   /**
-   * 切换指定引擎中 MCP 服务器的启用状态
+   * Cancels a running Codex execution
    *
-   * @param engine 引擎名称（"claude" | "codex" | "gemini"）
-   * @param id 服务器 ID
-   * @param serverSpec 服务器规范
-   * @param enabled 启用状态
+   * The CLI is interrupted first and force killed after 5s; the result is emitted
+   * as a CancelOutcome on the `<engine>-cancelled` event
+   * @param sessionId - Optional session ID to cancel a specific session
+   * @returns Promise resolving when cancellation is complete
    */
-  async mcpToggleEngineServer(
-    engine: "claude" | "codex" | "gemini",
-    id: string,
-    serverSpec: MCPServerSpec,
-    enabled: boolean
-  ): Promise<string> {
+  async cancelCodex(sessionId?: string): Promise<void> {
     try {
-      return await invoke<string>("mcp_toggle_engine_server", {
-        engine,
-        id,
-        serverSpec,
-        enabled,
-      });
+      return await invoke("cancel_codex", { sessionId });
     } catch (error) {
-      console.error(`Failed to toggle ${engine} MCP server:`, error);
+      console.error("Failed to cancel Codex execution:", error);
       throw error;
     }
   },
 
   /**
-   * 带启用状态的 MCP 服务器条目
-   */
-  // McpServerWithStatus 类型定义在下方
-
-  /**
-   * 获取指定引擎的 MCP 服务器列表（包含禁用的服务器）
-   *
-   * @param engine 引擎名称（"claude" | "codex" | "gemini"）
-   * @returns 该引擎的 MCP 服务器列表（包含启用状态）
+   * Gets a list of Codex sessions, newest first
+   * @param includeContextMessages - Allow injected context messages as the preview text
+   * @param query - Optional pagination, project path filter and inclusive date range (YYYY-MM-DD)
+   * @returns Promise resolving to array of Codex sessions
    */
-  async mcpGetEngineServersWithStatus(
-    engine: "claude" | "codex" | "gemini"
-  ): Promise<McpServerWithStatus[]> {
+  async listCodexSessions(
+    includeContextMessages?: boolean,
+    query?: import('@/types/codex').CodexSessionQuery
+  ): Promise<import('@/types/codex').CodexSession[]> {
     try {
-      return await invoke<McpServerWithStatus[]>("mcp_get_engine_servers_with_status", {
-        engine,
+      return await invoke<import('@/types/codex').CodexSession[]>("list_codex_sessions", {
+        includeContextMessages,
+        query,
       });
     } catch (error) {
-      console.error(`Failed to get ${engine} MCP servers with status:`, error);
+      console.error("Failed to list Codex sessions:", error);
       throw error;
     }
   },
 
   /**
-   * 添加或更新 MCP 服务器（支持多应用）
+   * Deletes a Codex session
+   * @param sessionId - The session ID to delete
+   * @returns Promise resolving to success message
    */
-  async mcpUpsertServer(
-    id: string,
-    name: string,
-    serverSpec: MCPServerSpec,
-    apps: McpApps
-  ): Promise<string> {
+  async deleteCodexSession(sessionId: string): Promise<string> {
     try {
-      return await invoke<string>("mcp_upsert_server", {
-        id,
-        name,
-        serverSpec,
-        apps,
-      });
+      return await invoke<string>("delete_codex_session", { sessionId });
     } catch (error) {
-      console.error("Failed to upsert MCP server:", error);
+      console.error("Failed to delete Codex session:", error);
       throw error;
     }
   },
 
   /**
-   * 删除 MCP 服务器（从所有应用）
+   * Checks if Codex is available and properly configured
+   * @returns Promise resolving to availability status
    */
-  async mcpDeleteServer(id: string, apps: McpApps): Promise<string> {
+  async checkCodexAvailability(): Promise<{
+    available: boolean;
+    version?: string;
+    error?: string;
+    warning?: string;
+  }> {
     try {
-      return await invoke<string>("mcp_delete_server", { id, apps });
+      return await invoke("check_codex_availability");
     } catch (error) {
-      console.error("Failed to delete MCP server:", error);
-      throw error;
+      console.error("Failed to check Codex availability:", error);
+      return {
+        available: false,
+        error: error instanceof Error ? error.message : String(error)
+      };
     }
   },
 
+  // ============================================================================
+  // Codex Mode Configuration (WSL Support)
+  // ============================================================================
+
   /**
-   * 切换 MCP 服务器在指定应用的启用状态
+   * Gets Codex mode configuration
+   * @returns Promise resolving to mode configuration info
    */
-  async mcpToggleApp(
-    id: string,
-    serverSpec: MCPServerSpec,
-    app: string,
-    enabled: boolean
-  ): Promise<string> {
+  async getCodexModeConfig(): Promise<{
+    mode: 'auto' | 'native' | 'wsl';
+    wslDistro: string | null;
+    actualMode: 'native' | 'wsl';
+    nativeAvailable: boolean;
+    wslAvailable: boolean;
+    availableDistros: string[];
+    isWindows: boolean;
+  }> {
     try {
-      return await invoke<string>("mcp_toggle_app", {
-        id,
-        serverSpec,
-        app,
-        enabled,
-      });
+      return await invoke("get_codex_mode_config");
     } catch (error) {
-      console.error("Failed to toggle MCP app:", error);
+      console.error("Failed to get Codex mode config:", error);
       throw error;
     }
   },
 
   /**
-   * 从指定应用导入 MCP 服务器
+   * Sets Codex mode configuration
+   * @param mode - The mode to set: 'auto', 'native', or 'wsl'
+   * @param wslDistro - Optional WSL distro name
+   * @param customCodexPath - Optional custom Codex path
+   * @returns Promise resolving to success message
    */
-  async mcpImportFromApp(app: string): Promise<string[]> {
+  async setCodexModeConfig(
+    mode: 'auto' | 'native' | 'wsl',
+    wslDistro?: string | null,
+    customCodexPath?: string | null
+  ): Promise<string> {
     try {
-      return await invoke<string[]>("mcp_import_from_app", { app });
+      return await invoke<string>("set_codex_mode_config", {
+        mode,
+        wslDistro: wslDistro || null,
+        customCodexPath: customCodexPath || null
+      });
     } catch (error) {
-      console.error("Failed to import from app:", error);
+      console.error("Failed to set Codex mode config:", error);
       throw error;
     }
   },
 
+  // ============================================================================
+  // Gemini WSL Mode Configuration
+  // ============================================================================
+
   /**
-   * 验证命令是否在 PATH 中可用
+   * Gets Gemini WSL mode configuration
+   * @returns Promise resolving to Gemini WSL mode configuration info
    */
-  async mcpValidateCommand(cmd: string): Promise<boolean> {
+  async getGeminiWslModeConfig(): Promise<{
+    mode: 'auto' | 'native' | 'wsl';
+    wslDistro: string | null;
+    wslAvailable: boolean;
+    availableDistros: string[];
+    wslEnabled: boolean;
+    wslGeminiPath: string | null;
+    wslGeminiVersion: string | null;
+    nativeAvailable: boolean;
+    isWindows: boolean;
+  }> {
     try {
-      return await invoke<boolean>("mcp_validate_command", { cmd });
+      return await invoke("get_gemini_wsl_mode_config");
     } catch (error) {
-      console.error("Failed to validate command:", error);
+      console.error("Failed to get Gemini WSL mode config:", error);
       throw error;
     }
   },
 
   /**
-   * 读取 Claude MCP 配置文本内容
+   * Sets Gemini WSL mode configuration
+   * @param mode - The mode to set: 'auto', 'native', or 'wsl'
+   * @param wslDistro - Optional WSL distro name
+   * @returns Promise resolving when config is saved
    */
-  async mcpReadClaudeConfig(): Promise<string | null> {
+  async setGeminiWslModeConfig(
+    mode: 'auto' | 'native' | 'wsl',
+    wslDistro?: string | null
+  ): Promise<void> {
     try {
-      return await invoke<string | null>("mcp_read_claude_config");
+      await invoke("set_gemini_wsl_mode_config", {
+        mode,
+        wslDistro: wslDistro || null
+      });
     } catch (error) {
-      console.error("Failed to read Claude MCP config:", error);
+      console.error("Failed to set Gemini WSL mode config:", error);
       throw error;
     }
   },
 
+  // ============================================================================
+  // Claude WSL Mode Configuration
+  // ============================================================================
+
   /**
-   * Get the stored Claude binary path from settings
-   * @returns Promise resolving to the path if set, null otherwise
+   * Gets Claude WSL mode configuration
+   * @returns Promise resolving to Claude WSL mode configuration info
    */
-  async getClaudeBinaryPath(): Promise<string | null> {
+  async getClaudeWslModeConfig(): Promise<{
+    mode: 'auto' | 'native' | 'wsl';
+    wslDistro: string | null;
+    wslAvailable: boolean;
+    availableDistros: string[];
+    wslEnabled: boolean;
+    wslClaudePath: string | null;
+    wslClaudeVersion: string | null;
+    nativeAvailable: boolean;
+    actualMode: 'native' | 'wsl';
+    isWindows: boolean;
+  }> {
     try {
-      return await invoke<string | null>("get_claude_binary_path");
+      return await invoke("get_claude_wsl_mode_config");
     } catch (error) {
-      console.error("Failed to get Claude binary path:", error);
+      console.error("Failed to get Claude WSL mode config:", error);
       throw error;
     }
   },
 
   /**
-   * Set the Claude binary path in settings
-   * @param path - The absolute path to the Claude binary
-   * @returns Promise resolving when the path is saved
+   * Sets Claude WSL mode configuration
+   * @param mode - The mode to set: 'auto', 'native', or 'wsl'
+   * @param wslDistro - Optional WSL distro name
+   * @returns Promise resolving to success message
    */
-  async setClaudeBinaryPath(path: string): Promise<void> {
+  async setClaudeWslModeConfig(
+    mode: 'auto' | 'native' | 'wsl',
+    wslDistro?: string | null
+  ): Promise<string> {
     try {
-      return await invoke<void>("set_claude_binary_path", { path });
+      return await invoke("set_claude_wsl_mode_config", {
+        mode,
+        wslDistro: wslDistro || null
+      });
     } catch (error) {
-      console.error("Failed to set Claude binary path:", error);
+      console.error("Failed to set Claude WSL mode config:", error);
       throw error;
     }
   },
 
-
-  // Storage API methods
-
   /**
-   * Lists all tables in the SQLite database
-   * @returns Promise resolving to an array of table information
+   * Get current Codex CLI path（优先自定义，其次自动检测）
    */
-  async storageListTables(): Promise<any[]> {
+  async getCodexPath(): Promise<string> {
     try {
-      return await invoke<any[]>("storage_list_tables");
+      return await invoke<string>("get_codex_path");
     } catch (error) {
-      console.error("Failed to list tables:", error);
+      console.error("Failed to get Codex path:", error);
       throw error;
     }
   },
 
   /**
-   * Reads table data with pagination
-   * @param tableName - Name of the table to read
-   * @param page - Page number (1-indexed)
-   * @param pageSize - Number of rows per page
-   * @param searchQuery - Optional search query
-   * @returns Promise resolving to table data with pagination info
+   * Sets custom Codex CLI path
+   * @param path - Path to custom Codex CLI executable (null to clear)
+   * @returns Promise resolving to success message
    */
-  async storageReadTable(
-    tableName: string,
-    page: number,
-    pageSize: number,
-    searchQuery?: string
-  ): Promise<any> {
+  async setCodexCustomPath(path: string | null): Promise<void> {
     try {
-      return await invoke<any>("storage_read_table", {
-        tableName,
-        page,
-        pageSize,
-        searchQuery,
-      });
+      const normalizedPath = path?.trim() ?? "";
+
+      if (normalizedPath) {
+        await invoke<void>("set_custom_codex_path", { customPath: normalizedPath });
+      } else {
+        await invoke<void>("clear_custom_codex_path");
+      }
     } catch (error) {
-      console.error("Failed to read table:", error);
+      console.error("Failed to set custom Codex path:", error);
       throw error;
     }
   },
 
   /**
-   * Updates a row in a table
-   * @param tableName - Name of the table
-   * @param primaryKeyValues - Map of primary key column names to values
-   * @param updates - Map of column names to new values
-   * @returns Promise resolving when the row is updated
+   * Gets binary detection settings (source priority, blocked paths) for a tool
    */
-  async storageUpdateRow(
-    tableName: string,
-    primaryKeyValues: Record<string, any>,
-    updates: Record<string, any>
-  ): Promise<void> {
+  async getBinaryDetectionSettings(tool: BinaryTool): Promise<BinaryDetectionSettings> {
     try {
-      return await invoke<void>("storage_update_row", {
-        tableName,
-        primaryKeyValues,
-        updates,
-      });
+      return await invoke<BinaryDetectionSettings>("get_binary_detection_settings", { tool });
     } catch (error) {
-      console.error("Failed to update row:", error);
+      console.error("Failed to get binary detection settings:", error);
       throw error;
     }
   },
 
   /**
-   * Deletes a row from a table
-   * @param tableName - Name of the table
-   * @param primaryKeyValues - Map of primary key column names to values
-   * @returns Promise resolving when the row is deleted
+   * Saves binary detection settings for a tool and invalidates its detection cache
    */
-  async storageDeleteRow(
-    tableName: string,
-    primaryKeyValues: Record<string, any>
-  ): Promise<void> {
+  async saveBinaryDetectionSettings(
+    tool: BinaryTool,
+    settings: BinaryDetectionSettings
+  ): Promise<BinaryDetectionSettings> {
     try {
-      return await invoke<void>("storage_delete_row", {
-        tableName,
-        primaryKeyValues,
-      });
+      return await invoke<BinaryDetectionSettings>("save_binary_detection_settings", { tool, settings });
     } catch (error) {
-      console.error("Failed to delete row:", error);
+      console.error("Failed to save binary detection settings:", error);
       throw error;
     }
   },
 
   /**
-   * Inserts a new row into a table
-   * @param tableName - Name of the table
-   * @param values - Map of column names to values
-   * @returns Promise resolving to the last insert row ID
+   * Lists all detected binaries for a tool (cached for 10 minutes unless refresh is set)
    */
-  async storageInsertRow(
-    tableName: string,
-    values: Record<string, any>
-  ): Promise<number> {
+  async listBinaryCandidates(tool: BinaryTool, refresh = false): Promise<BinaryDetection> {
     try {
-      return await invoke<number>("storage_insert_row", {
-        tableName,
-        values,
-      });
+      return await invoke<BinaryDetection>("list_binary_candidates", { tool, refresh });
     } catch (error) {
-      console.error("Failed to insert row:", error);
+      console.error("Failed to list binary candidates:", error);
       throw error;
     }
   },
 
   /**
-   * Executes a raw SQL query
-   * @param query - SQL query string
-   * @returns Promise resolving to query result
+   * Invalidates the binary detection cache (all tools when tool is omitted)
    */
-  async storageExecuteSql(query: string): Promise<any> {
+  async invalidateBinaryCache(tool?: BinaryTool): Promise<void> {
     try {
-      return await invoke<any>("storage_execute_sql", { query });
+      await invoke<void>("invalidate_binary_cache", { tool: tool ?? null });
     } catch (error) {
-      console.error("Failed to execute SQL:", error);
+      console.error("Failed to invalidate binary cache:", error);
       throw error;
     }
   },
 
   /**
-   * Resets the entire database
-   * @returns Promise resolving when the database is reset
+   * Validates a Codex path
+   * @param path - Path to validate
+   * @returns Promise resolving to whether the path is valid
    */
-  async storageResetDatabase(): Promise<void> {
+  async validateCodexPath(path: string): Promise<boolean> {
     try {
-      return await invoke<void>("storage_reset_database");
-    } catch (error) {
-      console.error("Failed to reset database:", error);
-      throw error;
+      return await invoke<boolean>("validate_codex_path_cmd", { path: path.trim() });
+    } catch (error) {
+      console.error("Failed to validate Codex path:", error);
+      return false;
     }
   },
 
   /**
-   * Get hooks configuration for a specific scope
-   * @param scope - The configuration scope: 'user', 'project', or 'local'
-   * @param projectPath - Project path (required for project and local scopes)
-   * @returns Promise resolving to the hooks configuration
+   * Scans for all possible Codex installation paths
+   * @returns Promise resolving to array of found paths
    */
-  async getHooksConfig(scope: 'user' | 'project' | 'local', projectPath?: string): Promise<HooksConfiguration> {
+  async scanCodexPaths(): Promise<string[]> {
     try {
-      return await invoke<HooksConfiguration>("get_hooks_config", { scope, projectPath });
+      return await invoke<string[]>("scan_codex_paths");
     } catch (error) {
-      console.error("Failed to get hooks config:", error);
-      throw error;
+      console.error("Failed to scan Codex paths:", error);
+      return [];
     }
   },
 
+  // ============================================================================
+  // Codex Rewind Commands
+  // ============================================================================
+
   /**
-   * Update hooks configuration for a specific scope
-   * @param scope - The configuration scope: 'user', 'project', or 'local'
-   * @param hooks - The hooks configuration to save
-   * @param projectPath - Project path (required for project and local scopes)
-   * @returns Promise resolving to success message
+   * Records a Codex prompt being sent (called before execution)
+   * @param sessionId - The Codex session ID
+   * @param projectPath - The project path
+   * @param promptText - The prompt text
+   * @returns Promise resolving to the prompt index
    */
-  async updateHooksConfig(
-    scope: 'user' | 'project' | 'local',
-    hooks: HooksConfiguration,
-    projectPath?: string
-  ): Promise<string> {
+  async recordCodexPromptSent(
+    sessionId: string,
+    projectPath: string,
+    promptText: string,
+    traceId?: string
+  ): Promise<number> {
     try {
-      return await invoke<string>("update_hooks_config", { scope, projectPath, hooks });
+      return await invoke<number>("record_codex_prompt_sent", {
+        sessionId,
+        projectPath,
+        promptText,
+        traceId
+      });
     } catch (error) {
-      console.error("Failed to update hooks config:", error);
+      console.error("Failed to record Codex prompt sent:", error);
       throw error;
     }
   },
 
   /**
-   * Validate a hook command syntax
-   * @param command - The shell command to validate
-   * @returns Promise resolving to validation result
+   * Records a Codex prompt completion (called after AI response)
+   * @param sessionId - The Codex session ID
+   * @param projectPath - The project path
+   * @param promptIndex - The prompt index to complete
+   * @returns Promise resolving to the code snapshot status
    */
-  async validateHookCommand(command: string): Promise<{ valid: boolean; message: string }> {
+  async recordCodexPromptCompleted(
+    sessionId: string,
+    projectPath: string,
+    promptIndex: number,
+    promptText?: string,
+    traceId?: string
+  ): Promise<PromptCompletionResult> {
     try {
-      return await invoke<{ valid: boolean; message: string }>("validate_hook_command", { command });
+      const payload: Record<string, unknown> = {
+        sessionId,
+        projectPath,
+        promptIndex
+      };
+      if (promptText !== undefined) {
+        payload.promptText = promptText;
+      }
+      if (traceId !== undefined) {
+        payload.traceId = traceId;
+      }
+      return await invoke<PromptCompletionResult>("record_codex_prompt_completed", {
+        ...payload
+      });
     } catch (error) {
-      console.error("Failed to validate hook command:", error);
+      console.error("Failed to record Codex prompt completed:", error);
       throw error;
     }
   },
 
   /**
-   * Get merged hooks configuration (respecting priority)
-   * @param projectPath - The project path
-   * @returns Promise resolving to merged hooks configuration
+   * Gets Codex prompt list for a session (used by revert picker)
    */
-  async getMergedHooksConfig(projectPath: string): Promise<HooksConfiguration> {
+  async getCodexPromptList(sessionId: string): Promise<PromptRecord[]> {
     try {
-      const [userHooks, projectHooks, localHooks] = await Promise.all([
-        this.getHooksConfig('user'),
-        this.getHooksConfig('project', projectPath),
-        this.getHooksConfig('local', projectPath)
-      ]);
-
-      return HooksManager.mergeConfigs(userHooks, projectHooks, localHooks);
+      return await invoke<PromptRecord[]>("get_codex_prompt_list", { sessionId });
     } catch (error) {
-      console.error("Failed to get merged hooks config:", error);
-      throw error;
+      console.error("Failed to get Codex prompt list:", error);
+      return [];
     }
   },
 
-
   /**
-   * Set custom Claude CLI path
-   * @param customPath - Path to custom Claude CLI executable
-   * @returns Promise resolving when path is set successfully
+   * Checks rewind capabilities for a Codex prompt
+   * @param sessionId - Codex session ID
+   * @param promptIndex - Prompt index to check
    */
-  async setCustomClaudePath(customPath: string): Promise<void> {
+  async checkCodexRewindCapabilities(
+    sessionId: string,
+    promptIndex: number
+  ): Promise<RewindCapabilities> {
     try {
-      return await invoke<void>("set_custom_claude_path", { customPath });
+      return await invoke<RewindCapabilities>("check_codex_rewind_capabilities", {
+        sessionId,
+        promptIndex,
+      });
     } catch (error) {
-      console.error("Failed to set custom Claude path:", error);
-      throw error;
+      console.error("Failed to check Codex rewind capabilities:", error);
+      // Fallback to conversation-only to keep UI functional
+      return {
+        conversation: true,
+        code: false,
+        both: false,
+        warning: "无法获取 Codex 撤回能力，只能删除对话记录。",
+        source: "cli",
+      };
     }
   },
 
   /**
-   * Get current Claude CLI path (custom or auto-detected)
-   * @returns Promise resolving to current Claude CLI path
+   * Reverts a Codex session to a specific prompt
+   * @param sessionId - The Codex session ID
+   * @param projectPath - The project path
+   * @param promptIndex - The prompt index to revert to
+   * @param mode - The rewind mode (conversation_only, code_only, or both)
+   * @returns Promise resolving to the prompt text (for restoring to input)
    */
-  async getClaudePath(): Promise<string> {
+  async revertCodexToPrompt(
+    sessionId: string,
+    projectPath: string,
+    promptIndex: number,
+    mode: RewindMode = "both"
+  ): Promise<string> {
     try {
-      return await invoke<string>("get_claude_path");
+      return await invoke<string>("revert_codex_to_prompt", {
+        sessionId,
+        projectPath,
+        promptIndex,
+        mode
+      });
     } catch (error) {
-      console.error("Failed to get Claude path:", error);
+      console.error("Failed to revert Codex to prompt:", error);
       throw error;
     }
   },
 
   /**
-   * Clear custom Claude CLI path and revert to auto-detection
-   * @returns Promise resolving when custom path is cleared
+   * Revert only the code changes of one Codex prompt, keeping later prompts and the conversation
+   * Fails without touching the repository when the changes conflict with later prompts
+   * @param sessionId - The Codex session ID
+   * @param projectPath - The project path
+   * @param promptIndex - The prompt whose changes should be reverted
+   * @returns Promise resolving to the number of reverted commits
    */
-  async clearCustomClaudePath(): Promise<void> {
+  async revertCodexSinglePromptCode(
+    sessionId: string,
+    projectPath: string,
+    promptIndex: number
+  ): Promise<number> {
     try {
-      return await invoke<void>("clear_custom_claude_path");
+      return await invoke<number>("revert_codex_single_prompt_code", {
+        sessionId,
+        projectPath,
+        promptIndex
+      });
     } catch (error) {
-      console.error("Failed to clear custom Claude path:", error);
+      console.error("Failed to revert Codex single prompt code:", error);
       throw error;
     }
   },
 
-
-
-  // Clipboard API methods
+  // ============================================================================
+  // Gemini Rewind Commands
+  // ============================================================================
 
   /**
-   * Saves clipboard image data to a temporary file
-   * @param base64Data - Base64 encoded image data
-   * @param format - Optional image format
-   * @returns Promise resolving to saved image result
+   * Records a Gemini prompt being sent (called before execution)
+   * @param sessionId - The Gemini session ID
+   * @param projectPath - The project path
+   * @param promptText - The prompt text
+   * @returns Promise resolving to the prompt index
    */
-  async saveClipboardImage(base64Data: string, format?: string): Promise<SavedImageResult> {
+  async recordGeminiPromptSent(
+    sessionId: string,
+    projectPath: string,
+    promptText: string,
+    traceId?: string
+  ): Promise<number> {
     try {
-      return await invoke<SavedImageResult>("save_clipboard_image", { base64Data, format });
+      return await invoke<number>("record_gemini_prompt_sent", {
+        sessionId,
+        projectPath,
+        promptText,
+        traceId
+      });
     } catch (error) {
-      console.error("Failed to save clipboard image:", error);
+      console.error("Failed to record Gemini prompt sent:", error);
       throw error;
     }
   },
 
-  // Provider Management API methods
-
   /**
-   * Gets the list of preset provider configurations
-   * @returns Promise resolving to array of provider configurations
+   * Records a Gemini prompt completion (called after AI response)
+   * @param sessionId - The Gemini session ID
+   * @param projectPath - The project path
+   * @param promptIndex - The prompt index to complete
+   * @returns Promise resolving to the code snapshot status
    */
-  async getProviderPresets(): Promise<ProviderConfig[]> {
+  async recordGeminiPromptCompleted(
+    sessionId: string,
+    projectPath: string,
+    promptIndex: number,
+    promptText?: string,
+    traceId?: string
+  ): Promise<PromptCompletionResult> {
     try {
-      return await invoke<ProviderConfig[]>("get_provider_presets");
+      const payload: Record<string, unknown> = {
+        sessionId,
+        projectPath,
+        promptIndex
+      };
+      if (promptText !== undefined) {
+        payload.promptText = promptText;
+      }
+      if (traceId !== undefined) {
+        payload.traceId = traceId;
+      }
+      return await invoke<PromptCompletionResult>("record_gemini_prompt_completed", {
+        ...payload
+      });
     } catch (error) {
-      console.error("Failed to get provider presets:", error);
+      console.error("Failed to record Gemini prompt completed:", error);
       throw error;
     }
   },
 
   /**
-   * Gets the current provider configuration from environment variables
-   * @returns Promise resolving to current configuration
+   * Gets Gemini prompt list for a session (used by revert picker)
    */
-  async getCurrentProviderConfig(): Promise<CurrentProviderConfig> {
+  async getGeminiPromptList(sessionId: string, projectPath: string): Promise<PromptRecord[]> {
     try {
-      return await invoke<CurrentProviderConfig>("get_current_provider_config");
+      return await invoke<PromptRecord[]>("get_gemini_prompt_list", { sessionId, projectPath });
     } catch (error) {
-      console.error("Failed to get current provider config:", error);
-      throw error;
+      console.error("Failed to get Gemini prompt list:", error);
+      return [];
     }
   },
 
   /**
-   * Switches to a new provider configuration
-   * @param config - The provider configuration to switch to
-   * @returns Promise resolving to success message
+   * Checks rewind capabilities for a Gemini prompt
+   * @param sessionId - Gemini session ID
+   * @param projectPath - The project path
+   * @param promptIndex - Prompt index to check
    */
-  async switchProviderConfig(config: ProviderConfig): Promise<string> {
+  async checkGeminiRewindCapabilities(
+    sessionId: string,
+    projectPath: string,
+    promptIndex: number
+  ): Promise<RewindCapabilities> {
     try {
-      return await invoke<string>("switch_provider_config", { config });
+      return await invoke<RewindCapabilities>("check_gemini_rewind_capabilities", {
+        sessionId,
+        projectPath,
+        promptIndex,
+      });
     } catch (error) {
-      console.error("Failed to switch provider config:", error);
-      throw error;
+      console.error("Failed to check Gemini rewind capabilities:", error);
+      // Fallback to conversation-only to keep UI functional
+      return {
+        conversation: true,
+        code: false,
+        both: false,
+        warning: "无法获取 Gemini 撤回能力，只能删除对话记录。",
+        source: "project",
+      };
     }
   },
 
   /**
-   * Clears all provider-related environment variables
+   * Reverts a Gemini session to a specific prompt
+   * @param sessionId - The Gemini session ID
+   * @param projectPath - The project path
+   * @param promptIndex - The prompt index to revert to
+   * @param mode - The rewind mode (conversation_only, code_only, or both)
    * @returns Promise resolving to success message
    */
-  async clearProviderConfig(): Promise<string> {
+  async revertGeminiToPrompt(
+    sessionId: string,
+    projectPath: string,
+    promptIndex: number,
+    mode: RewindMode = "both"
+  ): Promise<string> {
     try {
-      return await invoke<string>("clear_provider_config");
+      return await invoke<string>("revert_gemini_to_prompt", {
+        sessionId,
+        projectPath,
+        promptIndex,
+        mode
+      });
     } catch (error) {
-      console.error("Failed to clear provider config:", error);
+      console.error("Failed to revert Gemini to prompt:", error);
       throw error;
     }
   },
 
+  // ============================================================================
+  // REMOTE PROVIDER PRESETS
+  // ============================================================================
+
   /**
-   * Tests connection to a provider endpoint
-   * @param baseUrl - The base URL to test
-   * @returns Promise resolving to test result message
+   * Gets the remote provider preset manifest settings
    */
-  async testProviderConnection(baseUrl: string): Promise<string> {
+  async getProviderPresetsConfig(): Promise<ProviderPresetsConfig> {
     try {
-      return await invoke<string>("test_provider_connection", { baseUrl });
+      return await invoke<ProviderPresetsConfig>("get_provider_presets_config");
     } catch (error) {
-      console.error("Failed to test provider connection:", error);
+      console.error("Failed to get provider presets config:", error);
       throw error;
     }
   },
 
   /**
-   * Adds a new provider configuration
-   * @param config - The provider configuration to add
-   * @returns Promise resolving to success message
+   * Updates the remote provider preset manifest settings
+   * @param config - Manifest URL must be https; an empty URL disables remote presets
    */
-  async addProviderConfig(config: Omit<ProviderConfig, 'id'>): Promise<string> {
-    // Generate ID from name
-    const id = config.name
-      .toLowerCase()
-      .replace(/[^a-z0-9]/g, '-')
-      .replace(/-+/g, '-')
-      .replace(/^-|-$/g, '');
-
-    const fullConfig: ProviderConfig = {
-      ...config,
-      id
-    };
-
+  async updateProviderPresetsConfig(config: ProviderPresetsConfig): Promise<ProviderPresetsConfig> {
     try {
-      return await invoke<string>("add_provider_config", { config: fullConfig });
+      return await invoke<ProviderPresetsConfig>("update_provider_presets_config", { config });
     } catch (error) {
-      console.error("Failed to add provider config:", error);
+      console.error("Failed to update provider presets config:", error);
       throw error;
     }
   },
 
   /**
-   * Updates an existing provider configuration
-   * @param config - The provider configuration to update (with id)
-   * @returns Promise resolving to success message
+   * Gets the cached state of the remote preset manifest without fetching it
    */
-  async updateProviderConfig(config: ProviderConfig): Promise<string> {
+  async getProviderPresetsStatus(): Promise<RemotePresetsStatus> {
     try {
-      return await invoke<string>("update_provider_config", { config });
+      return await invoke<RemotePresetsStatus>("get_provider_presets_status");
     } catch (error) {
-      console.error("Failed to update provider config:", error);
+      console.error("Failed to get provider presets status:", error);
       throw error;
     }
   },
 
   /**
-   * Deletes a provider configuration by ID
-   * @param id - The ID of the provider configuration to delete
-   * @returns Promise resolving to success message
+   * Fetches the remote preset manifest now, ignoring the cache lifetime
    */
-  async deleteProviderConfig(id: string): Promise<string> {
+  async refreshProviderPresets(): Promise<RemotePresetsStatus> {
     try {
-      return await invoke<string>("delete_provider_config", { id });
+      return await invoke<RemotePresetsStatus>("refresh_provider_presets");
     } catch (error) {
-      console.error("Failed to delete provider config:", error);
+      console.error("Failed to refresh provider presets:", error);
       throw error;
     }
   },
 
+  // ============================================================================
+  // CODEX PROVIDER MANAGEMENT
+  // ============================================================================
+
   /**
-   * Gets a single provider configuration by ID
-   * @param id - The ID of the provider configuration to get
-   * @returns Promise resolving to provider configuration
+   * Gets the list of Codex provider presets
+   * @returns Promise resolving to array of Codex provider configurations
    */
-  async getProviderConfig(id: string): Promise<ProviderConfig> {
+  async getCodexProviderPresets(): Promise<CodexProviderConfig[]> {
     try {
-      return await invoke<ProviderConfig>("get_provider_config", { id });
+      return await invoke<CodexProviderConfig[]>("get_codex_provider_presets");
     } catch (error) {
-      console.error("Failed to get provider config:", error);
-      throw error;
+      console.error("Failed to get Codex provider presets:", error);
+      throw toCommandError(error);
     }
   },
 
   /**
-   * Queries API Key usage/balance from the provider
-   * @param baseUrl - The base URL of the provider API
-   * @param apiKey - The API key to query usage for
-   * @returns Promise resolving to API key usage information
+   * Gets the current Codex provider configuration from ~/.codex directory
+   * @returns Promise resolving to current Codex configuration
    */
-  async queryProviderUsage(baseUrl: string, apiKey: string): Promise<ApiKeyUsage> {
+  async getCurrentCodexConfig(): Promise<CurrentCodexConfig> {
     try {
-      return await invoke<ApiKeyUsage>("query_provider_usage", { baseUrl, apiKey });
+      return await invoke<CurrentCodexConfig>("get_current_codex_config");
     } catch (error) {
-      console.error("Failed to query provider usage:", error);
-      throw error;
+      console.error("Failed to get current Codex config:", error);
+      throw toCommandError(error);
     }
   },
 
   /**
-   * Reorders provider configurations
-   * @param ids - Array of provider IDs in the desired order
+   * Switches to a Codex provider configuration
+   * Writes auth.json and config.toml to ~/.codex directory
+   * @param config - The Codex provider configuration to switch to
    * @returns Promise resolving to success message
    */
-  async reorderProviderConfigs(ids: string[]): Promise<string> {
+  async switchCodexProvider(config: CodexProviderConfig): Promise<string> {
     try {
-      return await invoke<string>("reorder_provider_configs", { ids });
+      return await invoke<string>("switch_codex_provider", { config });
     } catch (error) {
-      console.error("Failed to reorder provider configs:", error);
-      throw error;
+      console.error("Failed to switch Codex provider:", error);
+      throw toCommandError(error);
     }
   },
 
-
-  // ============================================================================
-  // ACEMCP INTEGRATION
-  // ============================================================================
-
   /**
-   * Enhances a prompt by adding project context from acemcp semantic search
-   * 🆕 v2: 支持历史上下文感知和多轮搜索
-   *
-   * @param prompt - The original prompt to enhance
-   * @param projectPath - Path to the project directory
-   * @param sessionId - 🆕 Optional session ID for history-aware search
-   * @param projectId - 🆕 Optional project ID for history-aware search
-   * @param maxContextLength - Maximum length of context to include (default: 3000)
-   * @param enableMultiRound - 🆕 Enable multi-round search for better coverage (default: true)
-   * @returns Promise resolving to enhancement result
+   * Adds a new Codex provider configuration
+   * @param config - The Codex provider configuration to add
+   * @returns Promise resolving to success message
    */
-  async enhancePromptWithContext(
-    prompt: string,
-    projectPath: string,
-    sessionId?: string,
-    projectId?: string,
-    maxContextLength?: number,
-    enableMultiRound?: boolean
-  ): Promise<{
-    originalPrompt: string;
-    enhancedPrompt: string;
-    contextCount: number;
-    acemcpUsed: boolean;
-    error?: string;
-  }> {
+  async addCodexProviderConfig(config: Omit<CodexProviderConfig, 'id'>): Promise<string> {
+    // Generate base ID from name
+    let baseId = config.name
+      .toLowerCase()
+      .replace(/[^a-z0-9]/g, '-')
+      .replace(/-+/g, '-')
+      .replace(/^-|-$/g, '');
+
+    // Check if ID conflicts with built-in presets
+    const builtInIds = codexProviderPresets.map(p => p.id);
+
+    // Get existing custom configurations to check for conflicts
+    let existingConfigs: CodexProviderConfig[] = [];
     try {
-      return await invoke("enhance_prompt_with_context", {
-        prompt,
-        projectPath,
-        sessionId,
-        projectId,
-        maxContextLength,
-        enableMultiRound,
-      });
+      existingConfigs = await this.getCodexProviderPresets();
     } catch (error) {
-      console.error("Failed to enhance prompt with context:", error);
-      throw error;
+      console.warn("Failed to load existing Codex configs:", error);
     }
-  },
+    const existingIds = existingConfigs.map(c => c.id);
+
+    // Generate unique ID by adding suffix if needed
+    let id = baseId;
+    let suffix = 1;
+    while (builtInIds.includes(id) || existingIds.includes(id)) {
+      id = `${baseId}-${suffix}`;
+      suffix++;
+    }
+
+    const fullConfig: CodexProviderConfig = {
+      ...config,
+      id,
+      createdAt: Date.now(),
+    };
 
-  /**
-   * Tests if acemcp is available and can be used
-   * @returns Promise resolving to true if acemcp is available
-   */
-  async testAcemcpAvailability(): Promise<boolean> {
     try {
-      return await invoke<boolean>("test_acemcp_availability");
+      return await invoke<string>("add_codex_provider_config", { config: fullConfig });
     } catch (error) {
-      console.error("Failed to test acemcp availability:", error);
-      return false;
+      console.error("Failed to add Codex provider config:", error);
+      throw toCommandError(error);
     }
   },
 
   /**
-   * Saves acemcp configuration to ~/.acemcp/settings.toml
+   * Updates an existing Codex provider configuration
+   * @param config - The Codex provider configuration to update (with id)
+   * @returns Promise resolving to success message
    */
-  async saveAcemcpConfig(
-    baseUrl: string,
-    token: string,
-    batchSize?: number,
-    maxLinesPerBlob?: number
-  ): Promise<void> {
+  async updateCodexProviderConfig(config: CodexProviderConfig): Promise<string> {
     try {
-      return await invoke("save_acemcp_config", {
-        baseUrl,
-        token,
-        batchSize,
-        maxLinesPerBlob,
-      });
+      return await invoke<string>("update_codex_provider_config", { config });
     } catch (error) {
-      console.error("Failed to save acemcp config:", error);
-      throw error;
+      console.error("Failed to update Codex provider config:", error);
+      throw toCommandError(error);
     }
   },
 
   /**
-   * Loads acemcp configuration from ~/.acemcp/settings.toml
+   * Deletes a Codex provider configuration by ID
+   * @param id - The ID of the Codex provider configuration to delete
+   * @returns Promise resolving to success message
    */
-  async loadAcemcpConfig(): Promise<{
-    baseUrl: string;
-    token: string;
-    batchSize?: number;
-    maxLinesPerBlob?: number;
-  }> {
+  async deleteCodexProviderConfig(id: string): Promise<string> {
     try {
-      return await invoke("load_acemcp_config");
+      return await invoke<string>("delete_codex_provider_config", { id });
     } catch (error) {
-      console.error("Failed to load acemcp config:", error);
-      // 返回默认配置
-      return {
-        baseUrl: '',
-        token: '',
-        batchSize: 10,
-        maxLinesPerBlob: 800,
-      };
+      console.error("Failed to delete Codex provider config:", error);
+      throw toCommandError(error);
     }
   },
 
   /**
-   * Pre-indexes a project in background (non-blocking)
-   * Automatically triggered when user selects a project
+   * Reorders Codex provider configurations
+   * @param ids - Array of provider IDs in the desired order
+   * @returns Promise resolving to success message
    */
-  async preindexProject(projectPath: string): Promise<void> {
+  async reorderCodexProviderConfigs(ids: string[]): Promise<string> {
     try {
-      // 后台执行，不等待结果
-      invoke("preindex_project", { projectPath }).catch((error) => {
-        console.warn("Background pre-indexing failed:", error);
-      });
+      return await invoke<string>("reorder_codex_provider_configs", { ids });
     } catch (error) {
-      console.warn("Failed to start pre-indexing:", error);
+      console.error("Failed to reorder Codex provider configs:", error);
+      throw toCommandError(error);
     }
   },
 
   /**
-   * Exports the embedded acemcp sidecar to a specified path
-   * For CLI configuration
+   * Clears Codex provider configuration (resets to official)
+   * Removes auth.json and config.toml from ~/.codex directory
+   * @returns Promise resolving to success message
    */
-  async exportAcemcpSidecar(targetPath: string): Promise<string> {
+  async clearCodexProviderConfig(): Promise<string> {
     try {
-      return await invoke<string>("export_acemcp_sidecar", { targetPath });
+      return await invoke<string>("clear_codex_provider_config");
     } catch (error) {
-      console.error("Failed to export sidecar:", error);
-      throw error;
+      console.error("Failed to clear Codex provider config:", error);
+      throw toCommandError(error);
     }
   },
 
   /**
-   * Gets the path of extracted sidecar in temp directory (if exists)
+   * Tests Codex provider connection
+   * @param baseUrl - The base URL to test
+   * @param apiKey - The API key to use for testing
+   * @param customHeaders - Extra headers required by the gateway
+   * @returns Promise resolving to test result message
    */
-  async getExtractedSidecarPath(): Promise<string | null> {
+  async testCodexProviderConnection(
+    baseUrl: string,
+    apiKey?: string,
+    customHeaders?: Record<string, string>
+  ): Promise<string> {
     try {
-      return await invoke<string | null>("get_extracted_sidecar_path");
+      return await invoke<string>("test_codex_provider_connection", { baseUrl, apiKey, customHeaders });
     } catch (error) {
-      console.error("Failed to get extracted sidecar path:", error);
-      return null;
+      console.error("Failed to test Codex provider connection:", error);
+      throw toCommandError(error);
     }
   },
 
-  // Translation API methods
-
   /**
-   * Translates text using the translation service
-   * @param text - The text to translate
-   * @param targetLang - Optional target language (defaults to auto-detection)
-   * @returns Promise resolving to translated text
+   * Updates Codex reasoning effort level in config.toml
+   * @param level - The reasoning level: 'low', 'medium', 'high', or 'xhigh'
+   * @returns Promise resolving to success message
    */
-  async translateText(text: string, targetLang?: string): Promise<string> {
+  async updateCodexReasoningLevel(level: 'low' | 'medium' | 'high' | 'xhigh'): Promise<string> {
     try {
-      return await invoke<string>("translate", { text, targetLang });
+      return await invoke<string>("update_codex_reasoning_level", { level });
     } catch (error) {
-      console.error("Failed to translate text:", error);
+      console.error("Failed to update Codex reasoning level:", error);
       throw error;
     }
   },
 
   /**
-   * Translates multiple texts in batch
-   * @param texts - Array of texts to translate
-   * @param targetLang - Optional target language
-   * @returns Promise resolving to array of translated texts
+   * Lists the named profiles defined in Codex config.toml
+   * Reads the WSL-side config.toml when Codex runs in WSL mode
    */
-  async translateBatch(texts: string[], targetLang?: string): Promise<string[]> {
+  async getCodexProfiles(): Promise<CodexProfile[]> {
     try {
-      return await invoke<string[]>("translate_batch", { texts, targetLang });
+      return await invoke<CodexProfile[]>("get_codex_profiles");
     } catch (error) {
-      console.error("Failed to batch translate texts:", error);
+      console.error("Failed to get Codex profiles:", error);
       throw error;
     }
   },
 
   /**
-   * Gets the current translation configuration
-   * @returns Promise resolving to translation configuration
+   * Gets which `-c` config overrides (reasoning effort, verbosity) the installed Codex CLI supports
+   * Derived from the cached CLI version; unknown versions report every key as supported
    */
-  async getTranslationConfig(): Promise<TranslationConfig> {
+  async getCodexCapabilities(): Promise<CodexCapabilities> {
     try {
-      return await invoke<TranslationConfig>("get_translation_config");
+      return await invoke<CodexCapabilities>("get_codex_capabilities");
     } catch (error) {
-      console.error("Failed to get translation config:", error);
+      console.error("Failed to get Codex capabilities:", error);
       throw error;
     }
   },
 
   /**
-   * Updates the translation configuration
-   * @param config - New translation configuration
-   * @returns Promise resolving to success message
+   * Creates a `[profiles.<name>]` section, keeping the rest of config.toml intact
    */
-  async updateTranslationConfig(config: TranslationConfig): Promise<string> {
+  async createCodexProfile(name: string, profile: CodexProfileInput): Promise<CodexProfile> {
     try {
-      return await invoke<string>("update_translation_config", { config });
+      return await invoke<CodexProfile>("create_codex_profile", { name, profile });
     } catch (error) {
-      console.error("Failed to update translation config:", error);
+      console.error("Failed to create Codex profile:", error);
       throw error;
     }
   },
 
   /**
-   * Clears the translation cache
-   * @returns Promise resolving to success message
+   * Updates the editable fields of an existing Codex profile
    */
-  async clearTranslationCache(): Promise<string> {
+  async updateCodexProfile(name: string, profile: CodexProfileInput): Promise<CodexProfile> {
     try {
-      return await invoke<string>("clear_translation_cache");
+      return await invoke<CodexProfile>("update_codex_profile", { name, profile });
     } catch (error) {
-      console.error("Failed to clear translation cache:", error);
+      console.error("Failed to update Codex profile:", error);
       throw error;
     }
   },
 
   /**
-   * Gets translation cache statistics
-   * @returns Promise resolving to cache statistics
+   * Deletes a Codex profile (and the top-level default if it pointed to it)
    */
-  async getTranslationCacheStats(): Promise<TranslationCacheStats> {
+  async deleteCodexProfile(name: string): Promise<void> {
     try {
-      return await invoke<TranslationCacheStats>("get_translation_cache_stats");
+      await invoke("delete_codex_profile", { name });
     } catch (error) {
-      console.error("Failed to get translation cache stats:", error);
+      console.error("Failed to delete Codex profile:", error);
       throw error;
     }
   },
 
+  // ============================================================================
+  // GEMINI PROVIDER MANAGEMENT
+  // ============================================================================
+
   /**
-   * Detects the language of the given text
-   * @param text - The text to analyze
-   * @returns Promise resolving to detected language code
+   * Gets the list of Gemini provider presets
+   * @returns Promise resolving to array of Gemini provider configurations
    */
-  async detectTextLanguage(text: string): Promise<string> {
+  async getGeminiProviderPresets(): Promise<GeminiProviderConfig[]> {
     try {
-      return await invoke<string>("detect_text_language", { text });
+      return await invoke<GeminiProviderConfig[]>("get_gemini_provider_presets");
     } catch (error) {
-      console.error("Failed to detect text language:", error);
-      throw error;
+      console.error("Failed to get Gemini provider presets:", error);
+      throw toCommandError(error);
     }
   },
 
   /**
-   * Initializes the translation service
-   * @param config - Optional translation configuration
-   * @returns Promise resolving to success message
+   * Gets the current Gemini provider configuration from ~/.gemini directory
+   * @returns Promise resolving to current Gemini configuration
    */
-  async initTranslationService(config?: TranslationConfig): Promise<string> {
+  async getCurrentGeminiProviderConfig(): Promise<CurrentGeminiProviderConfig> {
     try {
-      return await invoke<string>("init_translation_service_command", { config });
+      return await invoke<CurrentGeminiProviderConfig>("get_current_gemini_provider_config");
     } catch (error) {
-      console.error("Failed to initialize translation service:", error);
-      throw error;
+      console.error("Failed to get current Gemini provider config:", error);
+      throw toCommandError(error);
     }
   },
 
-  // Auto-Compact Context Management API methods
-
   /**
-   * Initializes the auto-compact manager
-   * @returns Promise resolving when manager is initialized
+   * Switches to a Gemini provider configuration
+   * Writes env to ~/.gemini/.env and updates settings.json
+   * @param config - The Gemini provider configuration to switch to
+   * @returns Promise resolving to success message
    */
-  async initAutoCompactManager(): Promise<void> {
+  async switchGeminiProvider(config: GeminiProviderConfig): Promise<string> {
     try {
-      return await invoke<void>("init_auto_compact_manager");
+      return await invoke<string>("switch_gemini_provider", { config });
     } catch (error) {
-      console.error("Failed to initialize auto-compact manager:", error);
-      throw error;
+      console.error("Failed to switch Gemini provider:", error);
+      throw toCommandError(error);
     }
   },
 
   /**
-   * Registers a Claude session for auto-compact monitoring
-   * @param sessionId - The session ID to register
-   * @param projectPath - The project path
-   * @param model - The model being used
-   * @returns Promise resolving when session is registered
+   * Adds a new Gemini provider configuration
+   * @param config - The Gemini provider configuration to add
+   * @returns Promise resolving to success message
    */
-  async registerAutoCompactSession(sessionId: string, projectPath: string, model: string): Promise<void> {
+  async addGeminiProviderConfig(config: Omit<GeminiProviderConfig, 'id'>): Promise<string> {
+    // Generate ID from name
+    const id = config.name
+      .toLowerCase()
+      .replace(/[^a-z0-9]/g, '-')
+      .replace(/-+/g, '-')
+      .replace(/^-|-$/g, '');
+
+    const fullConfig: GeminiProviderConfig = {
+      ...config,
+      id,
+      createdAt: Date.now(),
+    };
+
     try {
-      return await invoke<void>("register_auto_compact_session", { sessionId, projectPath, model });
+      return await invoke<string>("add_gemini_provider_config", { config: fullConfig });
     } catch (error) {
-      console.error("Failed to register auto-compact session:", error);
-      throw error;
+      console.error("Failed to add Gemini provider config:", error);
+      throw toCommandError(error);
     }
   },
 
   /**
-   * Updates session token count and checks for auto-compact trigger
-   * @param sessionId - The session ID
-   * @param tokenCount - Current token count
-   * @returns Promise resolving to whether compaction was triggered
+   * Updates an existing Gemini provider configuration
+   * @param config - The Gemini provider configuration to update (with id)
+   * @returns Promise resolving to success message
    */
-  async updateSessionContext(sessionId: string, tokenCount: number): Promise<boolean> {
+  async updateGeminiProviderConfig(config: GeminiProviderConfig): Promise<string> {
     try {
-      return await invoke<boolean>("update_session_context", { sessionId, tokenCount });
+      return await invoke<string>("update_gemini_provider_config", { config });
     } catch (error) {
-      console.error("Failed to update session context:", error);
-      throw error;
+      console.error("Failed to update Gemini provider config:", error);
+      throw toCommandError(error);
     }
   },
 
   /**
-   * Manually triggers compaction for a session
-   * @param sessionId - The session ID
-   * @param customInstructions - Optional custom compaction instructions
-   * @returns Promise resolving when compaction is complete
+   * Deletes a Gemini provider configuration by ID
+   * @param id - The ID of the Gemini provider configuration to delete
+   * @returns Promise resolving to success message
    */
-  async triggerManualCompaction(sessionId: string, customInstructions?: string): Promise<void> {
+  async deleteGeminiProviderConfig(id: string): Promise<string> {
     try {
-      return await invoke<void>("trigger_manual_compaction", { sessionId, customInstructions });
+      return await invoke<string>("delete_gemini_provider_config", { id });
     } catch (error) {
-      console.error("Failed to trigger manual compaction:", error);
-      throw error;
+      console.error("Failed to delete Gemini provider config:", error);
+      throw toCommandError(error);
     }
   },
 
   /**
-   * Gets the current auto-compact configuration
-   * @returns Promise resolving to the configuration
+   * Reorders Gemini provider configurations
+   * @param ids - Array of provider IDs in the desired order
+   * @returns Promise resolving to success message
    */
-  async getAutoCompactConfig(): Promise<AutoCompactConfig> {
+  async reorderGeminiProviderConfigs(ids: string[]): Promise<string> {
     try {
-      return await invoke<AutoCompactConfig>("get_auto_compact_config");
+      return await invoke<string>("reorder_gemini_provider_configs", { ids });
     } catch (error) {
-      console.error("Failed to get auto-compact config:", error);
-      throw error;
+      console.error("Failed to reorder Gemini provider configs:", error);
+      throw toCommandError(error);
     }
   },
 
   /**
-   * Updates the auto-compact configuration
-   * @param config - The new configuration
-   * @returns Promise resolving when configuration is updated
+   * Clears Gemini provider configuration (resets to official OAuth)
+   * Clears .env and sets auth type to oauth-personal
+   * @returns Promise resolving to success message
    */
-  async updateAutoCompactConfig(config: AutoCompactConfig): Promise<void> {
+  async clearGeminiProviderConfig(): Promise<string> {
     try {
-      return await invoke<void>("update_auto_compact_config", { config });
+      return await invoke<string>("clear_gemini_provider_config");
     } catch (error) {
-      console.error("Failed to update auto-compact config:", error);
-      throw error;
+      console.error("Failed to clear Gemini provider config:", error);
+      throw toCommandError(error);
     }
   },
 
   /**
-   * Gets session context statistics
-   * @param sessionId - The session ID
-   * @returns Promise resolving to session context information
+   * Tests Gemini provider connection, measuring latency and listing models
+   * @param config - The provider to test (API key or Vertex AI auth from its env)
+   * @returns Promise resolving to the test result; models are empty when listing isn't supported
    */
-  async getSessionContextStats(sessionId: string): Promise<SessionContext | null> {
+  async testGeminiProviderConnection(config: GeminiProviderConfig): Promise<ProviderTestResult> {
     try {
-      return await invoke<SessionContext | null>("get_session_context_stats", { sessionId });
+      return await invoke<ProviderTestResult>("test_gemini_provider_connection", { config });
     } catch (error) {
-      console.error("Failed to get session context stats:", error);
-      throw error;
+      console.error("Failed to test Gemini provider connection:", error);
+      throw toCommandError(error);
     }
   },
 
   /**
-   * Gets all monitored sessions
-   * @returns Promise resolving to array of session contexts
+   * Tests every configured Gemini provider concurrently
+   * @returns Promise resolving to test results keyed by provider id
    */
-  async getAllMonitoredSessions(): Promise<SessionContext[]> {
+  async testAllGeminiProviders(): Promise<Record<string, ProviderTestResult>> {
     try {
-      return await invoke<SessionContext[]>("get_all_monitored_sessions");
+      return await invoke<Record<string, ProviderTestResult>>("test_all_gemini_providers");
     } catch (error) {
-      console.error("Failed to get monitored sessions:", error);
-      throw error;
+      console.error("Failed to test Gemini providers:", error);
+      throw toCommandError(error);
     }
   },
 
+  // ============================================================================
+  // Session Conversion (Claude ↔ Codex)
+  // ============================================================================
+
   /**
-   * Unregisters session from auto-compact monitoring
-   * @param sessionId - The session ID to unregister
-   * @returns Promise resolving when session is unregistered
+   * Convert a session between Claude and Codex/Gemini formats
+   * @param sessionId - The source session ID
+   * @param targetEngine - The target engine ('claude' | 'codex' | 'gemini'); Gemini converts to and from Claude only
+   * @param projectId - The project ID (directory name)
+   * @param projectPath - The project path
+   * @param conversionId - Optional ID used for progress events and cancellation
+   * @param dryRun - Run the full conversion without writing the target file
+   * @param inlineBlobs - Keep full tool results in the target instead of storing large ones as blob attachments
+   * @returns Promise resolving to conversion result
    */
-  async unregisterAutoCompactSession(sessionId: string): Promise<void> {
+  async convertSession(
+    sessionId: string,
+    targetEngine: 'claude' | 'codex' | 'gemini',
+    projectId: string,
+    projectPath: string,
+    conversionId?: string,
+    dryRun?: boolean,
+    inlineBlobs?: boolean
+  ): Promise<ConversionResult> {
     try {
-      return await invoke<void>("unregister_auto_compact_session", { sessionId });
+      return await invoke<ConversionResult>("convert_session", {
+        sessionId,
+        targetEngine,
+        projectId,
+        projectPath,
+        conversionId,
+        dryRun,
+        inlineBlobs,
+      });
     } catch (error) {
-      console.error("Failed to unregister auto-compact session:", error);
+      console.error("Failed to convert session:", error);
       throw error;
     }
   },
 
   /**
-   * Stops auto-compact monitoring
-   * @returns Promise resolving when monitoring is stopped
+   * Convert several sessions, or all sessions of a project, to another engine
+   * Emits `conversion:batch-progress` after each session; a failed session does not stop the batch.
+   * Sessions already converted into the target are skipped unless options.force is set.
+   * Cancel with cancelConversion(batchId).
+   * @param sessionIds - Sessions to convert; null converts every sourceEngine session of the project
+   * @param sourceEngine - Engine to list when sessionIds is null (defaults to the other engine)
    */
-  async stopAutoCompactMonitoring(): Promise<void> {
+  async convertSessionsBatch(
+    sessionIds: string[] | null,
+    projectId: string,
+    projectPath: string,
+    targetEngine: 'claude' | 'codex',
+    sourceEngine?: 'claude' | 'codex',
+    options?: BatchConversionOptions
+  ): Promise<BatchConversionReport> {
     try {
-      return await invoke<void>("stop_auto_compact_monitoring");
+      return await invoke<BatchConversionReport>("convert_sessions_batch", {
+        sessionIds,
+        projectId,
+        projectPath,
+        sourceEngine,
+        targetEngine,
+        options,
+      });
     } catch (error) {
-      console.error("Failed to stop auto-compact monitoring:", error);
+      console.error("Failed to batch convert sessions:", error);
       throw error;
     }
   },
 
   /**
-   * Starts auto-compact monitoring
-   * @returns Promise resolving when monitoring is started
+   * Cancel a running session conversion (the partially written target file is removed)
+   * @param conversionId - The ID passed to convertSession
+   * @returns Promise resolving to whether a running conversion was found
    */
-  async startAutoCompactMonitoring(): Promise<void> {
+  async cancelConversion(conversionId: string): Promise<boolean> {
     try {
-      return await invoke<void>("start_auto_compact_monitoring");
+      return await invoke<boolean>("cancel_conversion", { conversionId });
     } catch (error) {
-      console.error("Failed to start auto-compact monitoring:", error);
+      console.error("Failed to cancel conversion:", error);
       throw error;
     }
   },
 
   /**
-   * Gets auto-compact status and statistics
-   * @returns Promise resolving to status information
+   * Import an external session file (e.g. shared by a colleague) into the app's session directories
+   * The file is validated with the engine's parser and stored under a new session ID to avoid collisions
+   * @param filePath - Path of the session .jsonl file
+   * @param engine - Engine the file belongs to ('claude' | 'codex')
+   * @param projectId - Target project ID (directory name), required for Claude
+   * @returns Promise resolving to the new session ID
    */
-  async getAutoCompactStatus(): Promise<AutoCompactStatus> {
+  async importSessionFile(filePath: string, engine: 'claude' | 'codex', projectId?: string): Promise<string> {
     try {
-      return await invoke<AutoCompactStatus>("get_auto_compact_status");
+      return await invoke<string>("import_session_file", { filePath, engine, projectId });
     } catch (error) {
-      console.error("Failed to get auto-compact status:", error);
+      console.error("Failed to import session file:", error);
       throw error;
     }
   },
 
   /**
-   * Gets active sessions information
-   * @returns Promise resolving to array of active session info
+   * Export only the reasoning blocks of a session (Claude thinking / Codex reasoning)
+   * @param engine - Engine of the session ('claude' | 'codex')
+   * @param sessionId - The session ID
+   * @param projectId - The project ID (directory name), ignored for Codex
+   * @returns Promise resolving to the reasoning blocks in session order
    */
-  async getActiveSessions(): Promise<any[]> {
+  async exportReasoning(
+    engine: 'claude' | 'codex',
+    sessionId: string,
+    projectId: string
+  ): Promise<ReasoningBlock[]> {
     try {
-      return await invoke("get_active_sessions");
+      return await invoke<ReasoningBlock[]>("export_reasoning", { engine, sessionId, projectId });
     } catch (error) {
-      console.error('Failed to get active sessions:', error);
+      console.error("Failed to export reasoning:", error);
       throw error;
     }
   },
 
-  // Subagent Management & Specialization API methods
-
-
-
-
-
-
-
-
-  // Enhanced Hooks Automation API methods
-
   /**
-   * Triggers a hook event with context
-   * @param event - The hook event name
-   * @param context - The hook execution context
-   * @returns Promise resolving to hook chain execution result
+   * Export a Claude session as a GitHub-Flavored Markdown transcript
+   * @param sessionId - The session ID
+   * @param projectId - The project ID (directory name)
+   * @param outputPath - Absolute path of the .md file to write
+   * @param includeToolCalls - Render tool calls as code blocks and tool results as collapsible sections
+   * @returns Promise resolving to the written file path
    */
-  async triggerHookEvent(event: string, context: any): Promise<any> {
+  async exportSessionToMarkdown(
+    sessionId: string,
+    projectId: string,
+    outputPath: string,
+    includeToolCalls: boolean
+  ): Promise<string> {
     try {
-      return await invoke<any>("trigger_hook_event", { event, context });
+      return await invoke<string>("export_session_to_markdown", {
+        sessionId,
+        projectId,
+        outputPath,
+        includeToolCalls,
+      });
     } catch (error) {
-      console.error("Failed to trigger hook event:", error);
+      console.error("Failed to export session to markdown:", error);
       throw error;
     }
   },
 
   /**
-   * Tests a hook condition expression
-   * @param condition - The condition expression to test
-   * @param context - The hook context for evaluation
-   * @returns Promise resolving to whether condition is true
+   * Export a Claude or Codex session as a chronological Markdown transcript
+   * (user messages quoted, tool calls with command and output, reasoning collapsed)
+   * @param sessionId - The session ID
+   * @param projectId - The project ID (directory name), ignored for Codex
+   * @param engine - Engine of the session ('claude' | 'codex')
+   * @param outputPath - Absolute path of the .md file to write; omit to get the Markdown back
+   * @returns Promise resolving to the Markdown, or the written file path when outputPath is given
    */
-  async testHookCondition(condition: string, context: any): Promise<boolean> {
+  async exportSessionMarkdown(
+    sessionId: string,
+    projectId: string,
+    engine: 'claude' | 'codex',
+    outputPath?: string
+  ): Promise<string> {
     try {
-      return await invoke<boolean>("test_hook_condition", { condition, context });
+      return await invoke<string>("export_session_markdown", {
+        sessionId,
+        projectId,
+        engine,
+        outputPath,
+      });
     } catch (error) {
-      console.error("Failed to test hook condition:", error);
+      console.error("Failed to export session markdown:", error);
       throw error;
     }
   },
 
   /**
-   * Executes pre-commit code review hook with intelligent decision making
-   * @param projectPath - The project path to review
-   * @param config - Optional configuration for the review hook
-   * @returns Promise resolving to commit decision
+   * Convert a Claude session to Codex format
+   * @param sessionId - The Claude session ID (UUID format)
+   * @param projectId - The project ID (directory name)
+   * @param projectPath - The project path
+   * @returns Promise resolving to conversion result
    */
-  async executePreCommitReview(
-    projectPath: string,
-    config?: import('@/types/enhanced-hooks').PreCommitCodeReviewConfig
-  ): Promise<import('@/types/enhanced-hooks').CommitDecision> {
+  async convertClaudeToCodex(
+    sessionId: string,
+    projectId: string,
+    projectPath: string
+  ): Promise<ConversionResult> {
     try {
-      return await invoke<import('@/types/enhanced-hooks').CommitDecision>("execute_pre_commit_review", {
+      return await invoke<ConversionResult>("convert_claude_to_codex", {
+        sessionId,
+        projectId,
         projectPath,
-        config
       });
     } catch (error) {
-      console.error("Failed to execute pre-commit review:", error);
+      console.error("Failed to convert Claude to Codex:", error);
       throw error;
     }
   },
 
-  // ==================== Checkpoint API Methods ====================
-
-  /**
   /**
-   * Tracks a batch of messages for a session for checkpointing
+   * Convert a Codex session to Claude format
+   * @param sessionId - The Codex session ID (rollout-* format)
+   * @param projectId - The project ID (directory name)
+   * @param projectPath - The project path
+   * @returns Promise resolving to conversion result
    */
-  async trackSessionMessages(
+  async convertCodexToClaude(
     sessionId: string,
     projectId: string,
-    projectPath: string,
-    messages: string[]
-  ): Promise<void> {
+    projectPath: string
+  ): Promise<ConversionResult> {
     try {
-      return await invoke<void>("track_session_messages", {
+      return await invoke<ConversionResult>("convert_codex_to_claude", {
         sessionId,
         projectId,
         projectPath,
-        messages
       });
     } catch (error) {
-      console.error("Failed to track session messages:", error);
+      console.error("Failed to convert Codex to Claude:", error);
       throw error;
     }
   },
 
-  // ==================== Prompt Revert System ====================
+  // ==================== Google Gemini CLI Integration ====================
 
   /**
-   * Check and initialize Git repository
+   * Executes a Gemini CLI session with streaming output
+   * @param options - Gemini execution options
+   * @returns Promise resolving when execution starts (events are streamed via event listeners)
    */
-  async checkAndInitGit(projectPath: string): Promise<boolean> {
+  async executeGemini(options: import('@/types/gemini').GeminiExecutionOptions): Promise<void> {
     try {
-      return await invoke<boolean>("check_and_init_git", { projectPath });
+      return await invoke("execute_gemini", { options });
     } catch (error) {
-      console.error("Failed to check/init Git:", error);
-      return false;
+      console.error("Failed to execute Gemini:", error);
+      throw toCommandError(error);
     }
   },
 
   /**
-   * Check if a git reset operation is safe
-   * This prevents accidentally reverting to a much older version when
-   * multiple engines or user manual commits are involved
+   * Cancels a running Gemini execution
+   *
+   * The CLI is interrupted first and force killed after 5s; the result is emitted
+   * as a CancelOutcome on the `<engine>-cancelled` event
+   * @param sessionId - Optional session ID to cancel (cancels all if not provided)
    */
-  async checkResetSafety(
-    projectPath: string,
-    targetCommit: string,
-    currentEngine: string
-  ): Promise<ResetSafetyInfo> {
+  async cancelGemini(sessionId?: string): Promise<void> {
     try {
-      return await invoke<ResetSafetyInfo>("check_reset_safety", {
-        projectPath,
-        targetCommit,
-        currentEngine,
-      });
+      await invoke("cancel_gemini", { sessionId });
     } catch (error) {
-      console.error("Failed to check reset safety:", error);
-      // Return a safe default that allows proceeding
-      return {
-        commitsToLose: 0,
-        hasOtherEngineCommits: false,
-        hasUserCommits: false,
-        commitsSummary: [],
-        safeToProceed: true,
-        warning: null,
-      };
+      console.error("Failed to cancel Gemini:", error);
+      throw error;
     }
   },
 
   /**
-   * Record a prompt being sent
+   * Checks if Gemini CLI is installed
+   * @returns Promise resolving to installation status
    */
-  async recordPromptSent(
-    sessionId: string,
-    projectId: string,
-    projectPath: string,
-    promptText: string
-  ): Promise<number> {
+  async checkGeminiInstalled(): Promise<import('@/types/gemini').GeminiInstallStatus> {
     try {
-      return await invoke<number>("record_prompt_sent", {
-        sessionId,
-        projectId,
-        projectPath,
-        promptText
-      });
+      return await invoke("check_gemini_installed");
     } catch (error) {
-      console.error("Failed to record prompt:", error);
-      throw error;
+      console.error("Failed to check Gemini installation:", error);
+      return {
+        installed: false,
+        error: String(error),
+      };
     }
   },
 
   /**
-   * Mark a prompt as completed
+   * Gets Gemini CLI configuration
+   * @returns Promise resolving to Gemini configuration
    */
-  async markPromptCompleted(
-    sessionId: string,
-    projectId: string,
-    projectPath: string,
-    promptIndex: number,
-    promptText?: string
-  ): Promise<void> {
+  async getGeminiConfig(): Promise<import('@/types/gemini').GeminiConfig> {
     try {
-      const payload: Record<string, unknown> = {
-        sessionId,
-        projectId,
-        projectPath,
-        promptIndex
-      };
-      if (promptText !== undefined) {
-        payload.promptText = promptText;
-      }
-      return await invoke<void>("mark_prompt_completed", {
-        ...payload
-      });
+      return await invoke("get_gemini_config");
     } catch (error) {
-      console.error("Failed to mark prompt completed:", error);
+      console.error("Failed to get Gemini config:", error);
       throw error;
     }
   },
 
   /**
-   * Revert to a specific prompt with support for different rewind modes
+   * Updates Gemini CLI configuration
+   * @param config - New configuration to apply
    */
-  async revertToPrompt(
-    sessionId: string,
-    projectId: string,
-    projectPath: string,
-    promptIndex: number,
-    mode: RewindMode = "both"
-  ): Promise<string> {
+  async updateGeminiConfig(config: import('@/types/gemini').GeminiConfig): Promise<void> {
     try {
-      return await invoke<string>("revert_to_prompt", {
-        sessionId,
-        projectId,
-        projectPath,
-        promptIndex,
-        mode
-      });
+      await invoke("update_gemini_config", { config });
     } catch (error) {
-      console.error("Failed to revert to prompt:", error);
+      console.error("Failed to update Gemini config:", error);
       throw error;
     }
   },
 
   /**
-   * Get list of all prompts for a session
-   * Extracts all prompts from .jsonl (single source of truth)
+   * Gets available Gemini models
+   * @returns Promise resolving to array of model information
    */
-  async getPromptList(
-    sessionId: string,
-    projectId: string
-  ): Promise<PromptRecord[]> {
+  async getGeminiModels(): Promise<import('@/types/gemini').GeminiModelInfo[]> {
     try {
-      return await invoke<PromptRecord[]>("get_prompt_list", {
-        sessionId,
-        projectId
-      });
+      return await invoke("get_gemini_models");
     } catch (error) {
-      console.error("Failed to get prompt list:", error);
-      return [];
+      console.error("Failed to get Gemini models:", error);
+      throw error;
     }
   },
 
+  // ============================================================================
+  // Gemini Session History
+  // ============================================================================
+
   /**
-   * Get unified prompt list with git records enriched from .git-records.json
-   * Combines .jsonl prompts (all messages) with git records (hash-based mapping)
-   * This includes both project interface prompts (with git records) and CLI prompts (without git records)
+   * Gets session logs for a project (from logs.json)
+   * @param projectPath - Project path to get session logs for
+   * @returns Promise resolving to array of session logs
    */
-  async getUnifiedPromptList(
-    sessionId: string,
-    projectId: string
-  ): Promise<PromptRecord[]> {
+  async getGeminiSessionLogs(projectPath: string): Promise<import('@/types/gemini').GeminiSessionLog[]> {
     try {
-      return await invoke<PromptRecord[]>("get_unified_prompt_list", {
-        sessionId,
-        projectId
-      });
+      return await invoke("get_gemini_session_logs", { projectPath });
     } catch (error) {
-      console.error("Failed to get unified prompt list:", error);
-      return [];
+      console.error("Failed to get Gemini session logs:", error);
+      throw error;
     }
   },
 
   /**
-   * Check rewind capabilities for a specific prompt
-   * Determines whether a prompt can be reverted fully (conversation + code) or partially (conversation only)
+   * Lists all sessions for a project (from chats/ directory)
+   * @param projectPath - Project path to list sessions for
+   * @returns Promise resolving to array of session info
    */
-  async checkRewindCapabilities(
-    sessionId: string,
-    projectId: string,
-    promptIndex: number
-  ): Promise<RewindCapabilities> {
+  async listGeminiSessions(projectPath: string): Promise<import('@/types/gemini').GeminiSessionInfo[]> {
     try {
-      return await invoke<RewindCapabilities>("check_rewind_capabilities", {
-        sessionId,
-        projectId,
-        promptIndex
-      });
+      return await invoke("list_gemini_sessions", { projectPath });
     } catch (error) {
-      console.error("Failed to check rewind capabilities:", error);
+      console.error("Failed to list Gemini sessions:", error);
       throw error;
     }
   },
 
-  // ==================== Claude Extensions (Plugins, Subagents & Skills) ====================
-
   /**
-   * List all installed plugins
+   * Gets detailed session information
+   * @param projectPath - Project path
+   * @param sessionId - Session ID to get details for
+   * @returns Promise resolving to complete session detail
    */
-  async listPlugins(projectPath?: string): Promise<any[]> {
+  async getGeminiSessionDetail(
+    projectPath: string,
+    sessionId: string
+  ): Promise<import('@/types/gemini').GeminiSessionDetail> {
     try {
-      return await invoke<any[]>("list_plugins", { projectPath });
+      return await invoke("get_gemini_session_detail", { projectPath, sessionId });
     } catch (error) {
-      console.error("Failed to list plugins:", error);
-      return [];
+      console.error("Failed to get Gemini session detail:", error);
+      throw error;
     }
   },
 
   /**
-   * Open plugins directory
+   * Delete a Gemini session
+   * @param projectPath - Project path
+   * @param sessionId - Session ID to delete
    */
-  async openPluginsDirectory(projectPath?: string): Promise<string> {
+  async deleteGeminiSession(projectPath: string, sessionId: string): Promise<void> {
     try {
-      return await invoke<string>("open_plugins_directory", { projectPath });
+      await invoke("delete_gemini_session", { projectPath, sessionId });
     } catch (error) {
-      console.error("Failed to open plugins directory:", error);
+      console.error("Failed to delete Gemini session:", error);
       throw error;
     }
   },
 
   /**
-   * List all subagents
+   * Cleans up old Gemini sessions according to the configured retention policy
+   * Sessions are moved to ~/.anycode/trash/gemini together with their git records
+   * @param dryRun - Only report what would be cleaned up
    */
-  async listSubagents(projectPath?: string): Promise<any[]> {
+  async cleanupGeminiSessions(dryRun: boolean): Promise<import('@/types/gemini').GeminiCleanupReport> {
     try {
-      return await invoke<any[]>("list_subagents", { projectPath });
+      return await invoke("cleanup_gemini_sessions", { dryRun });
     } catch (error) {
-      console.error("Failed to list subagents:", error);
-      return [];
+      console.error("Failed to clean up Gemini sessions:", error);
+      throw error;
     }
   },
 
   /**
-   * List all agent skills
+   * Lists sessions moved to the trash by earlier cleanups, newest first
    */
-  async listAgentSkills(projectPath?: string): Promise<any[]> {
+  async listGeminiTrash(): Promise<import('@/types/gemini').GeminiTrashEntry[]> {
     try {
-      return await invoke<any[]>("list_agent_skills", { projectPath });
+      return await invoke("list_gemini_trash");
     } catch (error) {
-      console.error("Failed to list agent skills:", error);
-      return [];
+      console.error("Failed to list Gemini trash:", error);
+      throw error;
     }
   },
 
   /**
-   * Read a subagent file
+   * Restores a session from the trash into ~/.gemini; never overwrites an existing session
+   * Encrypted sessions need the encrypted storage unlocked
+   * @param trashDir - Cleanup run directory of the session
+   * @param sessionId - The session to restore
    */
-  async readSubagent(filePath: string): Promise<string> {
+  async restoreGeminiTrashSession(trashDir: string, sessionId: string): Promise<import('@/types/gemini').GeminiCleanupEntry> {
     try {
-      return await invoke<string>("read_subagent", { filePath });
+      return await invoke("restore_gemini_trash_session", { trashDir, sessionId });
     } catch (error) {
-      console.error("Failed to read subagent:", error);
+      console.error("Failed to restore Gemini session from trash:", error);
       throw error;
     }
   },
 
   /**
-   * Read a skill file
+   * Gets favorite/tag/read-only marks of all Gemini sessions, keyed by session ID
    */
-  async readSkill(filePath: string): Promise<string> {
+  async getGeminiSessionMarks(): Promise<Record<string, import('@/types/gemini').GeminiSessionMark>> {
     try {
-      return await invoke<string>("read_skill", { filePath });
+      return await invoke("get_gemini_session_marks");
     } catch (error) {
-      console.error("Failed to read skill:", error);
+      console.error("Failed to get Gemini session marks:", error);
       throw error;
     }
   },
 
   /**
-   * Open agents directory in file explorer
+   * Sets the marks of a Gemini session (an empty mark removes it)
+   * @param sessionId - Gemini CLI session ID
+   * @param mark - Favorite/tags/read-only flags
    */
-  async openAgentsDirectory(projectPath?: string): Promise<string> {
+  async setGeminiSessionMark(sessionId: string, mark: import('@/types/gemini').GeminiSessionMark): Promise<void> {
     try {
-      return await invoke<string>("open_agents_directory", { projectPath });
+      await invoke("set_gemini_session_mark", { sessionId, mark });
     } catch (error) {
-      console.error("Failed to open agents directory:", error);
+      console.error("Failed to set Gemini session mark:", error);
       throw error;
     }
   },
 
   /**
-   * Open skills directory in file explorer
+   * Collects a diagnostics report (environment, WSL, CLI paths, acemcp, redacted config, session counts)
+   * The result is meant to be pasted into bug reports as JSON
    */
-  async openSkillsDirectory(projectPath?: string): Promise<string> {
+  async collectDiagnostics(): Promise<DiagnosticsReport> {
     try {
-      return await invoke<string>("open_skills_directory", { projectPath });
+      return await invoke<DiagnosticsReport>("collect_diagnostics");
     } catch (error) {
-      console.error("Failed to open skills directory:", error);
+      console.error("Failed to collect diagnostics:", error);
       throw error;
     }
   },
 
   /**
-   * Create a new subagent
-   * @param name - Agent name (alphanumeric, hyphens, underscores only)
-   * @param description - Short description of the agent
-   * @param content - Agent system prompt content
-   * @param scope - "project" or "user"
-   * @param projectPath - Required for project scope
+   * Changes the backend log level at runtime and persists it
+   * @returns The normalized level name
    */
-  async createSubagent(
-    name: string,
-    description: string,
-    content: string,
-    scope: 'project' | 'user',
-    projectPath?: string
-  ): Promise<{ name: string; path: string; scope: string; description: string; content: string }> {
+  async setLogLevel(level: LogLevel): Promise<LogLevel> {
     try {
-      return await invoke("create_subagent", { name, description, content, scope, projectPath });
+      return await invoke<LogLevel>("set_log_level", { level });
     } catch (error) {
-      console.error("Failed to create subagent:", error);
+      console.error("Failed to set log level:", error);
       throw error;
     }
   },
 
   /**
-   * Create a new Agent Skill
-   * @param name - Skill name (alphanumeric, hyphens, underscores only)
-   * @param description - Short description of what this skill does
-   * @param content - Skill instructions content
-   * @param scope - "project" or "user"
-   * @param projectPath - Required for project scope
+   * Gets the current log file path, size and level
    */
-  async createSkill(
-    name: string,
-    description: string,
-    content: string,
-    scope: 'project' | 'user',
-    projectPath?: string
-  ): Promise<{ name: string; path: string; scope: string; description: string; content: string }> {
+  async getLogFileInfo(): Promise<LogFileInfo> {
     try {
-      return await invoke("create_skill", { name, description, content, scope, projectPath });
+      return await invoke<LogFileInfo>("get_log_file_info");
     } catch (error) {
-      console.error("Failed to create skill:", error);
+      console.error("Failed to get log file info:", error);
       throw error;
     }
   },
 
   /**
-   * Open a directory in system file explorer (cross-platform)
+   * Opens the log directory in the system file manager
    */
-  async openDirectoryInExplorer(directoryPath: string): Promise<void> {
+  async openLogDirectory(): Promise<void> {
     try {
-      return await invoke<void>("open_directory_in_explorer", { directoryPath });
+      await invoke("open_log_directory");
     } catch (error) {
-      console.error("Failed to open directory in explorer:", error);
+      console.error("Failed to open log directory:", error);
       throw error;
     }
   },
 
   /**
-   * Open a file with system default application (cross-platform)
+   * Exports a zip with recent logs, the last 5 task traces, the diagnostics report and the redacted config
+   * @param targetPath - Optional output path, defaults to ~/.anycode/diagnostics/
+   * @returns Path of the exported zip
    */
-  async openFileWithDefaultApp(filePath: string): Promise<void> {
+  async exportDiagnosticBundle(targetPath?: string): Promise<string> {
     try {
-      return await invoke<void>("open_file_with_default_app", { filePath });
+      return await invoke<string>("export_diagnostic_bundle", { targetPath });
     } catch (error) {
-      console.error("Failed to open file with default app:", error);
+      console.error("Failed to export diagnostic bundle:", error);
       throw error;
     }
   },
 
-  // ==================== Git Statistics ====================
-
   /**
-   * Get Git diff statistics between commits
+   * Previews the merged effective configuration of each subsystem, with the layer each value came from
+   * @param projectPath - Optional project whose project-level settings are merged in
    */
-  async getGitDiffStats(
-    projectPath: string,
-    fromCommit: string,
-    toCommit?: string
-  ): Promise<{ linesAdded: number; linesRemoved: number; filesChanged: number }> {
+  async getEffectiveConfig(projectPath?: string): Promise<EffectiveConfig> {
     try {
-      return await invoke("get_git_diff_stats", { projectPath, fromCommit, toCommit });
+      return await invoke<EffectiveConfig>("get_effective_config", { projectPath });
     } catch (error) {
-      console.error("Failed to get git diff stats:", error);
+      console.error("Failed to get effective config:", error);
       throw error;
     }
   },
 
   /**
-   * Get code changes for current session
+   * Gets deduplicated prompt history across all engines (newest first)
    */
-  async getSessionCodeChanges(
-    projectPath: string,
-    sessionStartCommit: string
-  ): Promise<{ linesAdded: number; linesRemoved: number; filesChanged: number }> {
+  async getPromptHistory(projectPath?: string, query?: string, limit?: number): Promise<PromptHistoryEntry[]> {
     try {
-      return await invoke("get_session_code_changes", { projectPath, sessionStartCommit });
+      return await invoke<PromptHistoryEntry[]>("get_prompt_history", { projectPath, query, limit });
     } catch (error) {
-      console.error("Failed to get session code changes:", error);
+      console.error("Failed to get prompt history:", error);
       throw error;
     }
   },
 
-  // ==================== OpenAI Codex Integration ====================
-
   /**
-   * Executes a Codex task in non-interactive mode with streaming output
-   * @param options - Codex execution options
-   * @returns Promise resolving when execution starts (events are streamed via event listeners)
+   * Resends a history prompt (optionally modified) to a new or existing session
    */
-  async executeCodex(options: import('@/types/codex').CodexExecutionOptions): Promise<void> {
+  async resendPrompt(
+    entryId: string,
+    targetEngine: 'claude' | 'codex' | 'gemini',
+    targetSession?: string,
+    modifications?: PromptModifications,
+    projectPath?: string,
+    model?: string
+  ): Promise<ResendPromptResult> {
     try {
-      return await invoke("execute_codex", { options });
+      return await invoke<ResendPromptResult>("resend_prompt", {
+        entryId,
+        targetEngine,
+        targetSession,
+        modifications,
+        projectPath,
+        model,
+      });
     } catch (error) {
-      console.error("Failed to execute Codex:", error);
+      console.error("Failed to resend prompt:", error);
       throw error;
     }
   },
 
   /**
-   * Resumes a previous Codex session
-   * @param sessionId - The session ID to resume
-   * @param options - Codex execution options (prompt, mode, etc.)
-   * @returns Promise resolving when execution starts
+   * Imports prompts (JSON array or markdown checklist file, or an inline list) as a batch
    */
-  async resumeCodex(
-    sessionId: string,
-    options: Omit<import('@/types/codex').CodexExecutionOptions, 'sessionId'>
-  ): Promise<void> {
+  async importPromptBatch(
+    engine: 'claude' | 'codex' | 'gemini',
+    options: BatchOptions,
+    source: { filePath?: string; prompts?: BatchPromptInput[] }
+  ): Promise<PromptBatch> {
     try {
-      return await invoke("resume_codex", { sessionId, options });
+      return await invoke<PromptBatch>("import_prompt_batch", {
+        filePath: source.filePath,
+        prompts: source.prompts,
+        engine,
+        options,
+      });
     } catch (error) {
-      console.error("Failed to resume Codex session:", error);
+      console.error("Failed to import prompt batch:", error);
       throw error;
     }
   },
 
   /**
-   * Resumes the last Codex session
-   * @param options - Codex execution options
-   * @returns Promise resolving when execution starts
+   * Starts (or resumes) sequential execution of a batch; progress is emitted as "prompt-batch-updated"
    */
-  async resumeLastCodex(
-    options: Omit<import('@/types/codex').CodexExecutionOptions, 'resumeLast'>
-  ): Promise<void> {
+  async runPromptBatch(batchId: string, stopOnFailure: boolean): Promise<PromptBatch> {
     try {
-      return await invoke("resume_last_codex", { options });
+      return await invoke<PromptBatch>("run_prompt_batch", { batchId, stopOnFailure });
     } catch (error) {
-      console.error("Failed to resume last Codex session:", error);
+      console.error("Failed to run prompt batch:", error);
       throw error;
     }
   },
 
   /**
-   * Cancels a running Codex execution
-   * @param sessionId - Optional session ID to cancel a specific session
-   * @returns Promise resolving when cancellation is complete
+   * Pauses a running batch after the current prompt finishes
    */
-  async cancelCodex(sessionId?: string): Promise<void> {
+  async pausePromptBatch(batchId: string): Promise<boolean> {
     try {
-      return await invoke("cancel_codex", { sessionId });
+      return await invoke<boolean>("pause_prompt_batch", { batchId });
     } catch (error) {
-      console.error("Failed to cancel Codex execution:", error);
+      console.error("Failed to pause prompt batch:", error);
       throw error;
     }
   },
 
   /**
-   * Gets a list of all Codex sessions
-   * @returns Promise resolving to array of Codex sessions
+   * Cancels the remaining prompts of a batch
    */
-  async listCodexSessions(): Promise<import('@/types/codex').CodexSession[]> {
+  async cancelPromptBatch(batchId: string): Promise<boolean> {
     try {
-      return await invoke<import('@/types/codex').CodexSession[]>("list_codex_sessions");
+      return await invoke<boolean>("cancel_prompt_batch", { batchId });
     } catch (error) {
-      console.error("Failed to list Codex sessions:", error);
+      console.error("Failed to cancel prompt batch:", error);
       throw error;
     }
   },
 
   /**
-   * Deletes a Codex session
-   * @param sessionId - The session ID to delete
-   * @returns Promise resolving to success message
+   * Gets per-prompt status, duration and result summary of a batch
    */
-  async deleteCodexSession(sessionId: string): Promise<string> {
+  async getBatchStatus(batchId: string): Promise<PromptBatch> {
     try {
-      return await invoke<string>("delete_codex_session", { sessionId });
+      return await invoke<PromptBatch>("get_batch_status", { batchId });
     } catch (error) {
-      console.error("Failed to delete Codex session:", error);
+      console.error("Failed to get batch status:", error);
       throw error;
     }
   },
 
   /**
-   * Checks if Codex is available and properly configured
-   * @returns Promise resolving to availability status
+   * Lists saved prompt batches (newest first)
    */
-  async checkCodexAvailability(): Promise<{
-    available: boolean;
-    version?: string;
-    error?: string;
-  }> {
+  async listPromptBatches(): Promise<PromptBatch[]> {
     try {
-      return await invoke("check_codex_availability");
+      return await invoke<PromptBatch[]>("list_prompt_batches");
     } catch (error) {
-      console.error("Failed to check Codex availability:", error);
-      return {
-        available: false,
-        error: error instanceof Error ? error.message : String(error)
-      };
+      console.error("Failed to list prompt batches:", error);
+      throw error;
     }
   },
 
   // ============================================================================
-  // Codex Mode Configuration (WSL Support)
+  // Project Memory
   // ============================================================================
 
   /**
-   * Gets Codex mode configuration
-   * @returns Promise resolving to mode configuration info
+   * Adds a project memory entry (returns the existing entry for duplicates)
    */
-  async getCodexModeConfig(): Promise<{
-    mode: 'auto' | 'native' | 'wsl';
-    wslDistro: string | null;
-    actualMode: 'native' | 'wsl';
-    nativeAvailable: boolean;
-    wslAvailable: boolean;
-    availableDistros: string[];
-    isWindows: boolean;
-  }> {
+  async addMemoryEntry(projectPath: string, entry: MemoryEntryInput): Promise<MemoryEntry> {
     try {
-      return await invoke("get_codex_mode_config");
+      return await invoke<MemoryEntry>("add_memory_entry", { projectPath, entry });
     } catch (error) {
-      console.error("Failed to get Codex mode config:", error);
+      console.error("Failed to add memory entry:", error);
       throw error;
     }
   },
 
   /**
-   * Sets Codex mode configuration
-   * @param mode - The mode to set: 'auto', 'native', or 'wsl'
-   * @param wslDistro - Optional WSL distro name
-   * @param customCodexPath - Optional custom Codex path
-   * @returns Promise resolving to success message
+   * Lists the memory entries of a project (newest first)
    */
-  async setCodexModeConfig(
-    mode: 'auto' | 'native' | 'wsl',
-    wslDistro?: string | null,
-    customCodexPath?: string | null
-  ): Promise<string> {
+  async listMemoryEntries(projectPath: string): Promise<MemoryEntry[]> {
     try {
-      return await invoke<string>("set_codex_mode_config", {
-        mode,
-        wslDistro: wslDistro || null,
-        customCodexPath: customCodexPath || null
-      });
+      return await invoke<MemoryEntry[]>("list_memory_entries", { projectPath });
     } catch (error) {
-      console.error("Failed to set Codex mode config:", error);
+      console.error("Failed to list memory entries:", error);
       throw error;
     }
   },
 
-  // ============================================================================
-  // Gemini WSL Mode Configuration
-  // ============================================================================
-
   /**
-   * Gets Gemini WSL mode configuration
-   * @returns Promise resolving to Gemini WSL mode configuration info
+   * Deletes a project memory entry
    */
-  async getGeminiWslModeConfig(): Promise<{
-    mode: 'auto' | 'native' | 'wsl';
-    wslDistro: string | null;
-    wslAvailable: boolean;
-    availableDistros: string[];
-    wslEnabled: boolean;
-    wslGeminiPath: string | null;
-    wslGeminiVersion: string | null;
-    nativeAvailable: boolean;
-    isWindows: boolean;
-  }> {
+  async deleteMemoryEntry(projectPath: string, entryId: string): Promise<void> {
     try {
-      return await invoke("get_gemini_wsl_mode_config");
+      return await invoke("delete_memory_entry", { projectPath, entryId });
     } catch (error) {
-      console.error("Failed to get Gemini WSL mode config:", error);
+      console.error("Failed to delete memory entry:", error);
       throw error;
     }
   },
 
   /**
-   * Sets Gemini WSL mode configuration
-   * @param mode - The mode to set: 'auto', 'native', or 'wsl'
-   * @param wslDistro - Optional WSL distro name
-   * @returns Promise resolving when config is saved
+   * Compiles the project memory into injectable context text within a token budget
+   * @returns Promise resolving to the context text (empty when there are no entries)
    */
-  async setGeminiWslModeConfig(
-    mode: 'auto' | 'native' | 'wsl',
-    wslDistro?: string | null
-  ): Promise<void> {
+  async compileMemoryContext(projectPath: string, maxTokens: number): Promise<string> {
     try {
-      await invoke("set_gemini_wsl_mode_config", {
-        mode,
-        wslDistro: wslDistro || null
-      });
+      return await invoke<string>("compile_memory_context", { projectPath, maxTokens });
     } catch (error) {
-      console.error("Failed to set Gemini WSL mode config:", error);
+      console.error("Failed to compile memory context:", error);
       throw error;
     }
   },
 
-  // ============================================================================
-  // Claude WSL Mode Configuration
-  // ============================================================================
-
   /**
-   * Gets Claude WSL mode configuration
-   * @returns Promise resolving to Claude WSL mode configuration info
+   * Gets the registered data migrations and which of them have been applied
    */
-  async getClaudeWslModeConfig(): Promise<{
-    mode: 'auto' | 'native' | 'wsl';
-    wslDistro: string | null;
-    wslAvailable: boolean;
-    availableDistros: string[];
-    wslEnabled: boolean;
-    wslClaudePath: string | null;
-    wslClaudeVersion: string | null;
-    nativeAvailable: boolean;
-    actualMode: 'native' | 'wsl';
-    isWindows: boolean;
-  }> {
+  async getMigrationStatus(): Promise<MigrationStatus> {
     try {
-      return await invoke("get_claude_wsl_mode_config");
+      return await invoke<MigrationStatus>("get_migration_status");
     } catch (error) {
-      console.error("Failed to get Claude WSL mode config:", error);
+      console.error("Failed to get migration status:", error);
       throw error;
     }
   },
 
   /**
-   * Sets Claude WSL mode configuration
-   * @param mode - The mode to set: 'auto', 'native', or 'wsl'
-   * @param wslDistro - Optional WSL distro name
-   * @returns Promise resolving to success message
+   * Runs the pending data migrations in order, stopping at the first failure
+   * @param dryRun - Only list the migrations that would run
    */
-  async setClaudeWslModeConfig(
-    mode: 'auto' | 'native' | 'wsl',
-    wslDistro?: string | null
-  ): Promise<string> {
+  async runPendingMigrations(dryRun?: boolean): Promise<MigrationRunReport> {
     try {
-      return await invoke("set_claude_wsl_mode_config", {
-        mode,
-        wslDistro: wslDistro || null
-      });
+      return await invoke<MigrationRunReport>("run_pending_migrations", { dryRun });
     } catch (error) {
-      console.error("Failed to set Claude WSL mode config:", error);
+      console.error("Failed to run pending migrations:", error);
       throw error;
     }
   },
 
   /**
-   * Get current Codex CLI path（优先自定义，其次自动检测）
+   * Lists the available CLAUDE.md templates (built-in and local)
    */
-  async getCodexPath(): Promise<string> {
+  async listClaudeMdTemplates(): Promise<ClaudeMdTemplate[]> {
     try {
-      return await invoke<string>("get_codex_path");
+      return await invoke<ClaudeMdTemplate[]>("list_claude_md_templates");
     } catch (error) {
-      console.error("Failed to get Codex path:", error);
+      console.error("Failed to list CLAUDE.md templates:", error);
       throw error;
     }
   },
 
   /**
-   * Sets custom Codex CLI path
-   * @param path - Path to custom Codex CLI executable (null to clear)
-   * @returns Promise resolving to success message
+   * Initializes a project's AI workflow and returns the result of each step
    */
-  async setCodexCustomPath(path: string | null): Promise<void> {
+  async initProjectAiSetup(projectPath: string, options?: ProjectSetupOptions): Promise<ProjectSetupReport> {
     try {
-      const normalizedPath = path?.trim() ?? "";
-
-      if (normalizedPath) {
-        await invoke<void>("set_custom_codex_path", { customPath: normalizedPath });
-      } else {
-        await invoke<void>("clear_custom_codex_path");
-      }
+      return await invoke<ProjectSetupReport>("init_project_ai_setup", { projectPath, options });
     } catch (error) {
-      console.error("Failed to set custom Codex path:", error);
+      console.error("Failed to initialize project AI setup:", error);
       throw error;
     }
   },
 
   /**
-   * Validates a Codex path
-   * @param path - Path to validate
-   * @returns Promise resolving to whether the path is valid
+   * Saves the working state of a project; omitted fields keep their saved values and
+   * the backend throttles disk writes
    */
-  async validateCodexPath(path: string): Promise<boolean> {
+  async saveProjectWorkspaceState(projectPath: string, state: ProjectWorkspaceState): Promise<void> {
     try {
-      return await invoke<boolean>("validate_codex_path_cmd", { path: path.trim() });
+      await invoke("save_project_workspace_state", { projectPath, state });
     } catch (error) {
-      console.error("Failed to validate Codex path:", error);
-      return false;
+      console.error("Failed to save project workspace state:", error);
+      throw error;
     }
   },
 
   /**
-   * Scans for all possible Codex installation paths
-   * @returns Promise resolving to array of found paths
+   * Gets the saved working state of a project, or null if none was saved
    */
-  async scanCodexPaths(): Promise<string[]> {
+  async getProjectWorkspaceState(projectPath: string): Promise<RestoredWorkspaceState | null> {
     try {
-      return await invoke<string[]>("scan_codex_paths");
+      return await invoke<RestoredWorkspaceState | null>("get_project_workspace_state", { projectPath });
     } catch (error) {
-      console.error("Failed to scan Codex paths:", error);
-      return [];
+      console.error("Failed to get project workspace state:", error);
+      throw error;
     }
   },
 
-  // ============================================================================
-  // Codex Rewind Commands
-  // ============================================================================
-
   /**
-   * Records a Codex prompt being sent (called before execution)
-   * @param sessionId - The Codex session ID
-   * @param projectPath - The project path
-   * @param promptText - The prompt text
-   * @returns Promise resolving to the prompt index
+   * Gets the global protected paths settings
    */
-  async recordCodexPromptSent(
-    sessionId: string,
-    projectPath: string,
-    promptText: string
-  ): Promise<number> {
+  async getProtectedPathsSettings(): Promise<ProtectedPathsSettings> {
     try {
-      return await invoke<number>("record_codex_prompt_sent", {
-        sessionId,
-        projectPath,
-        promptText
-      });
+      return await invoke<ProtectedPathsSettings>("get_protected_paths_settings");
     } catch (error) {
-      console.error("Failed to record Codex prompt sent:", error);
+      console.error("Failed to get protected paths settings:", error);
       throw error;
     }
   },
 
   /**
-   * Records a Codex prompt completion (called after AI response)
-   * @param sessionId - The Codex session ID
-   * @param projectPath - The project path
-   * @param promptIndex - The prompt index to complete
+   * Saves the global protected paths settings (patterns are normalized and validated)
    */
-  async recordCodexPromptCompleted(
-    sessionId: string,
-    projectPath: string,
-    promptIndex: number,
-    promptText?: string
-  ): Promise<void> {
+  async saveProtectedPathsSettings(settings: ProtectedPathsSettings): Promise<void> {
     try {
-      const payload: Record<string, unknown> = {
-        sessionId,
-        projectPath,
-        promptIndex
-      };
-      if (promptText !== undefined) {
-        payload.promptText = promptText;
-      }
-      await invoke("record_codex_prompt_completed", {
-        ...payload
-      });
+      await invoke("save_protected_paths_settings", { settings });
     } catch (error) {
-      console.error("Failed to record Codex prompt completed:", error);
+      console.error("Failed to save protected paths settings:", error);
       throw error;
     }
   },
 
   /**
-   * Gets Codex prompt list for a session (used by revert picker)
+   * Gets a project's protected paths and the patterns in effect
    */
-  async getCodexPromptList(sessionId: string): Promise<PromptRecord[]> {
+  async getProtectedPaths(projectPath: string): Promise<ProtectedPathsInfo> {
     try {
-      return await invoke<PromptRecord[]>("get_codex_prompt_list", { sessionId });
+      return await invoke<ProtectedPathsInfo>("get_protected_paths", { projectPath });
     } catch (error) {
-      console.error("Failed to get Codex prompt list:", error);
-      return [];
+      console.error("Failed to get protected paths:", error);
+      throw error;
     }
   },
 
   /**
-   * Checks rewind capabilities for a Codex prompt
-   * @param sessionId - Codex session ID
-   * @param promptIndex - Prompt index to check
+   * Saves a project's protected path patterns and returns the normalized list
    */
-  async checkCodexRewindCapabilities(
-    sessionId: string,
-    promptIndex: number
-  ): Promise<RewindCapabilities> {
+  async saveProjectProtectedPaths(projectPath: string, patterns: string[]): Promise<string[]> {
     try {
-      return await invoke<RewindCapabilities>("check_codex_rewind_capabilities", {
-        sessionId,
-        promptIndex,
-      });
+      return await invoke<string[]>("save_project_protected_paths", { projectPath, patterns });
     } catch (error) {
-      console.error("Failed to check Codex rewind capabilities:", error);
-      // Fallback to conversation-only to keep UI functional
-      return {
-        conversation: true,
-        code: false,
-        both: false,
-        warning: "无法获取 Codex 撤回能力，只能删除对话记录。",
-        source: "cli",
-      };
+      console.error("Failed to save project protected paths:", error);
+      throw error;
     }
   },
 
   /**
-   * Reverts a Codex session to a specific prompt
-   * @param sessionId - The Codex session ID
-   * @param projectPath - The project path
-   * @param promptIndex - The prompt index to revert to
-   * @param mode - The rewind mode (conversation_only, code_only, or both)
-   * @returns Promise resolving to the prompt text (for restoring to input)
+   * Restores protected files changed by a prompt to their state before it was sent
    */
-  async revertCodexToPrompt(
+  async revertProtectedChanges(
+    engine: 'claude' | 'codex' | 'gemini',
     sessionId: string,
-    projectPath: string,
-    promptIndex: number,
-    mode: RewindMode = "both"
-  ): Promise<string> {
+    promptIndex: number
+  ): Promise<ProtectedRestore> {
     try {
-      return await invoke<string>("revert_codex_to_prompt", {
-        sessionId,
-        projectPath,
-        promptIndex,
-        mode
-      });
+      return await invoke<ProtectedRestore>("revert_protected_changes", { engine, sessionId, promptIndex });
     } catch (error) {
-      console.error("Failed to revert Codex to prompt:", error);
+      console.error("Failed to revert protected changes:", error);
       throw error;
     }
   },
 
-  // ============================================================================
-  // Gemini Rewind Commands
-  // ============================================================================
-
   /**
-   * Records a Gemini prompt being sent (called before execution)
-   * @param sessionId - The Gemini session ID
-   * @param projectPath - The project path
-   * @param promptText - The prompt text
-   * @returns Promise resolving to the prompt index
+   * Gets the saved Gemini context file defaults for a project
    */
-  async recordGeminiPromptSent(
-    sessionId: string,
-    projectPath: string,
-    promptText: string
-  ): Promise<number> {
+  async getGeminiContextFiles(projectPath: string): Promise<import('@/types/gemini').GeminiContextFiles> {
     try {
-      return await invoke<number>("record_gemini_prompt_sent", {
-        sessionId,
-        projectPath,
-        promptText
-      });
+      return await invoke<import('@/types/gemini').GeminiContextFiles>("get_gemini_context_files", { projectPath });
     } catch (error) {
-      console.error("Failed to record Gemini prompt sent:", error);
+      console.error("Failed to get Gemini context files:", error);
       throw error;
     }
   },
 
   /**
-   * Records a Gemini prompt completion (called after AI response)
-   * @param sessionId - The Gemini session ID
-   * @param projectPath - The project path
-   * @param promptIndex - The prompt index to complete
+   * Saves the Gemini context file defaults for a project
+   * Extra files are validated (inside the workspace, total size limit) before saving
    */
-  async recordGeminiPromptCompleted(
-    sessionId: string,
-    projectPath: string,
-    promptIndex: number,
-    promptText?: string
-  ): Promise<void> {
+  async saveGeminiContextFiles(projectPath: string, config: import('@/types/gemini').GeminiContextFiles): Promise<void> {
     try {
-      const payload: Record<string, unknown> = {
-        sessionId,
-        projectPath,
-        promptIndex
-      };
-      if (promptText !== undefined) {
-        payload.promptText = promptText;
-      }
-      await invoke("record_gemini_prompt_completed", {
-        ...payload
-      });
+      await invoke("save_gemini_context_files", { projectPath, config });
     } catch (error) {
-      console.error("Failed to record Gemini prompt completed:", error);
+      console.error("Failed to save Gemini context files:", error);
       throw error;
     }
   },
 
   /**
-   * Gets Gemini prompt list for a session (used by revert picker)
+   * Lists the context files a Gemini run would load with the given config
+   * @returns Promise resolving to absolute file paths
    */
-  async getGeminiPromptList(sessionId: string, projectPath: string): Promise<PromptRecord[]> {
+  async previewGeminiContextFiles(projectPath: string, config: import('@/types/gemini').GeminiContextFiles): Promise<string[]> {
     try {
-      return await invoke<PromptRecord[]>("get_gemini_prompt_list", { sessionId, projectPath });
+      return await invoke<string[]>("preview_gemini_context_files", { projectPath, config });
     } catch (error) {
-      console.error("Failed to get Gemini prompt list:", error);
-      return [];
+      console.error("Failed to preview Gemini context files:", error);
+      throw error;
     }
   },
 
   /**
-   * Checks rewind capabilities for a Gemini prompt
-   * @param sessionId - Gemini session ID
-   * @param projectPath - The project path
-   * @param promptIndex - Prompt index to check
+   * Extracts candidate memory entries from a session with the current provider's model
+   * Candidates are not stored; confirm them with addMemoryEntry
+   * @param projectIdOrPath - Claude project ID, or the project path for Gemini (ignored for Codex)
    */
-  async checkGeminiRewindCapabilities(
+  async extractMemoryFromSession(
+    engine: 'claude' | 'codex' | 'gemini',
     sessionId: string,
-    projectPath: string,
-    promptIndex: number
-  ): Promise<RewindCapabilities> {
+    projectIdOrPath: string
+  ): Promise<MemoryEntryInput[]> {
     try {
-      return await invoke<RewindCapabilities>("check_gemini_rewind_capabilities", {
+      return await invoke<MemoryEntryInput[]>("extract_memory_from_session", {
+        engine,
         sessionId,
-        projectPath,
-        promptIndex,
+        projectIdOrPath,
       });
     } catch (error) {
-      console.error("Failed to check Gemini rewind capabilities:", error);
-      // Fallback to conversation-only to keep UI functional
-      return {
-        conversation: true,
-        code: false,
-        both: false,
-        warning: "无法获取 Gemini 撤回能力，只能删除对话记录。",
-        source: "project",
-      };
+      console.error("Failed to extract memory from session:", error);
+      throw error;
     }
   },
 
   /**
-   * Reverts a Gemini session to a specific prompt
-   * @param sessionId - The Gemini session ID
-   * @param projectPath - The project path
-   * @param promptIndex - The prompt index to revert to
-   * @param mode - The rewind mode (conversation_only, code_only, or both)
-   * @returns Promise resolving to success message
+   * Sends an assistant reply to another engine for review
+   * @param sourceEngine - Engine of the session containing the reply
+   * @param sessionId - Session containing the reply
+   * @param messageRef - "last", "ts:<timestamp>", or the reply ID (Claude record uuid / message id, Gemini message id)
+   * @param targetEngine - Engine that performs the review
+   * @param reviewInstruction - What the reviewer should focus on
+   * @param options - Project, target session and execution options
+   * @returns Promise resolving to the started review
    */
-  async revertGeminiToPrompt(
+  async crossEngineReview(
+    sourceEngine: 'claude' | 'codex' | 'gemini',
     sessionId: string,
-    projectPath: string,
-    promptIndex: number,
-    mode: RewindMode = "both"
-  ): Promise<string> {
-    try {
-      return await invoke<string>("revert_gemini_to_prompt", {
+    messageRef: string,
+    targetEngine: 'claude' | 'codex' | 'gemini',
+    reviewInstruction: string,
+    options: CrossEngineReviewOptions
+  ): Promise<CrossEngineReviewResult> {
+    try {
+      return await invoke<CrossEngineReviewResult>("cross_engine_review", {
+        sourceEngine,
         sessionId,
-        projectPath,
-        promptIndex,
-        mode
+        messageRef,
+        targetEngine,
+        reviewInstruction,
+        options,
       });
     } catch (error) {
-      console.error("Failed to revert Gemini to prompt:", error);
+      console.error("Failed to start cross-engine review:", error);
       throw error;
     }
   },
 
-  // ============================================================================
-  // CODEX PROVIDER MANAGEMENT
-  // ============================================================================
-
   /**
-   * Gets the list of Codex provider presets
-   * @returns Promise resolving to array of Codex provider configurations
+   * Gets the cross-engine review settings
    */
-  async getCodexProviderPresets(): Promise<CodexProviderConfig[]> {
+  async getCrossEngineReviewConfig(): Promise<CrossEngineReviewConfig> {
     try {
-      return await invoke<CodexProviderConfig[]>("get_codex_provider_presets");
+      return await invoke<CrossEngineReviewConfig>("get_cross_engine_review_config");
     } catch (error) {
-      console.error("Failed to get Codex provider presets:", error);
+      console.error("Failed to get cross-engine review config:", error);
       throw error;
     }
   },
 
   /**
-   * Gets the current Codex provider configuration from ~/.codex directory
-   * @returns Promise resolving to current Codex configuration
+   * Updates the cross-engine review settings
+   * @param config - The new settings; the template must contain {content}
    */
-  async getCurrentCodexConfig(): Promise<CurrentCodexConfig> {
+  async updateCrossEngineReviewConfig(config: CrossEngineReviewConfig): Promise<void> {
     try {
-      return await invoke<CurrentCodexConfig>("get_current_codex_config");
+      await invoke("update_cross_engine_review_config", { config });
     } catch (error) {
-      console.error("Failed to get current Codex config:", error);
+      console.error("Failed to update cross-engine review config:", error);
       throw error;
     }
   },
 
   /**
-   * Switches to a Codex provider configuration
-   * Writes auth.json and config.toml to ~/.codex directory
-   * @param config - The Codex provider configuration to switch to
-   * @returns Promise resolving to success message
+   * Adds an instruction to a running Codex/Gemini task; it is sent (resuming the session)
+   * once the current turn finishes. Status changes arrive as task-interjection events.
+   * @param sessionId - Channel ID or CLI session ID of the running task
    */
-  async switchCodexProvider(config: CodexProviderConfig): Promise<string> {
+  async interjectRunningTask(
+    engine: 'codex' | 'gemini',
+    sessionId: string,
+    text: string
+  ): Promise<Interjection> {
     try {
-      return await invoke<string>("switch_codex_provider", { config });
+      return await invoke<Interjection>("interject_running_task", { engine, sessionId, text });
     } catch (error) {
-      console.error("Failed to switch Codex provider:", error);
+      console.error("Failed to interject running task:", error);
       throw error;
     }
   },
 
-  /**
-   * Adds a new Codex provider configuration
-   * @param config - The Codex provider configuration to add
-   * @returns Promise resolving to success message
-   */
-  async addCodexProviderConfig(config: Omit<CodexProviderConfig, 'id'>): Promise<string> {
-    // Generate base ID from name
-    let baseId = config.name
-      .toLowerCase()
-      .replace(/[^a-z0-9]/g, '-')
-      .replace(/-+/g, '-')
-      .replace(/^-|-$/g, '');
-
-    // Check if ID conflicts with built-in presets
-    const builtInIds = codexProviderPresets.map(p => p.id);
-
-    // Get existing custom configurations to check for conflicts
-    let existingConfigs: CodexProviderConfig[] = [];
-    try {
-      existingConfigs = await this.getCodexProviderPresets();
-    } catch (error) {
-      console.warn("Failed to load existing Codex configs:", error);
-    }
-    const existingIds = existingConfigs.map(c => c.id);
-
-    // Generate unique ID by adding suffix if needed
-    let id = baseId;
-    let suffix = 1;
-    while (builtInIds.includes(id) || existingIds.includes(id)) {
-      id = `${baseId}-${suffix}`;
-      suffix++;
-    }
-
-    const fullConfig: CodexProviderConfig = {
-      ...config,
-      id,
-      createdAt: Date.now(),
-    };
-
+  /**
+   * Lists interjections waiting for a running task to finish
+   */
+  async listInterjections(sessionId: string): Promise<Interjection[]> {
     try {
-      return await invoke<string>("add_codex_provider_config", { config: fullConfig });
+      return await invoke<Interjection[]>("list_interjections", { sessionId });
     } catch (error) {
-      console.error("Failed to add Codex provider config:", error);
+      console.error("Failed to list interjections:", error);
       throw error;
     }
   },
 
   /**
-   * Updates an existing Codex provider configuration
-   * @param config - The Codex provider configuration to update (with id)
-   * @returns Promise resolving to success message
+   * Withdraws an interjection that has not been sent yet
    */
-  async updateCodexProviderConfig(config: CodexProviderConfig): Promise<string> {
+  async cancelInterjection(interjectionId: string): Promise<boolean> {
     try {
-      return await invoke<string>("update_codex_provider_config", { config });
+      return await invoke<boolean>("cancel_interjection", { interjectionId });
     } catch (error) {
-      console.error("Failed to update Codex provider config:", error);
+      console.error("Failed to cancel interjection:", error);
       throw error;
     }
   },
 
   /**
-   * Deletes a Codex provider configuration by ID
-   * @param id - The ID of the Codex provider configuration to delete
-   * @returns Promise resolving to success message
+   * Extracts all fenced code blocks from a session's assistant replies
+   * @param projectIdOrPath - Claude project ID, or project path for Gemini (ignored for Codex)
    */
-  async deleteCodexProviderConfig(id: string): Promise<string> {
+  async extractCodeBlocks(
+    engine: 'claude' | 'codex' | 'gemini',
+    sessionId: string,
+    projectIdOrPath: string,
+    filters?: CodeBlockFilters
+  ): Promise<CodeBlock[]> {
     try {
-      return await invoke<string>("delete_codex_provider_config", { id });
+      return await invoke<CodeBlock[]>("extract_code_blocks", {
+        engine,
+        sessionId,
+        projectIdOrPath,
+        filters: filters ?? null,
+      });
     } catch (error) {
-      console.error("Failed to delete Codex provider config:", error);
+      console.error("Failed to extract code blocks:", error);
       throw error;
     }
   },
 
   /**
-   * Reorders Codex provider configurations
-   * @param ids - Array of provider IDs in the desired order
-   * @returns Promise resolving to success message
+   * Writes code blocks into a directory (blocks without a file name become snippet_N.ext)
+   * @param targetDir - Absolute directory, created when missing
+   * @param overwritePolicy - What to do when the file exists (default rename)
    */
-  async reorderCodexProviderConfigs(ids: string[]): Promise<string> {
+  async saveCodeBlocks(
+    blocks: CodeBlock[],
+    targetDir: string,
+    overwritePolicy?: CodeBlockOverwritePolicy
+  ): Promise<SaveCodeBlocksReport> {
     try {
-      return await invoke<string>("reorder_codex_provider_configs", { ids });
+      return await invoke<SaveCodeBlocksReport>("save_code_blocks", {
+        blocks,
+        targetDir,
+        overwritePolicy: overwritePolicy ?? null,
+      });
     } catch (error) {
-      console.error("Failed to reorder Codex provider configs:", error);
+      console.error("Failed to save code blocks:", error);
       throw error;
     }
   },
 
   /**
-   * Clears Codex provider configuration (resets to official)
-   * Removes auth.json and config.toml from ~/.codex directory
-   * @returns Promise resolving to success message
+   * Gets the auto-retry settings for Codex/Gemini runs
    */
-  async clearCodexProviderConfig(): Promise<string> {
+  async getExecutionRetryConfig(): Promise<ExecutionRetryConfig> {
     try {
-      return await invoke<string>("clear_codex_provider_config");
+      return await invoke<ExecutionRetryConfig>("get_execution_retry_config");
     } catch (error) {
-      console.error("Failed to clear Codex provider config:", error);
+      console.error("Failed to get execution retry config:", error);
       throw error;
     }
   },
 
   /**
-   * Tests Codex provider connection
-   * @param baseUrl - The base URL to test
-   * @param apiKey - The API key to use for testing
-   * @returns Promise resolving to test result message
+   * Updates the auto-retry settings for Codex/Gemini runs
+   * @param config - The new settings
    */
-  async testCodexProviderConnection(baseUrl: string, apiKey?: string): Promise<string> {
+  async updateExecutionRetryConfig(config: ExecutionRetryConfig): Promise<void> {
     try {
-      return await invoke<string>("test_codex_provider_connection", { baseUrl, apiKey });
+      await invoke("update_execution_retry_config", { config });
     } catch (error) {
-      console.error("Failed to test Codex provider connection:", error);
+      console.error("Failed to update execution retry config:", error);
       throw error;
     }
   },
 
   /**
-   * Updates Codex reasoning effort level in config.toml
-   * @param level - The reasoning level: 'low', 'medium', 'high', or 'xhigh'
-   * @returns Promise resolving to success message
+   * Gets the stalled-stream detection settings for Codex/Gemini runs
    */
-  async updateCodexReasoningLevel(level: 'low' | 'medium' | 'high' | 'xhigh'): Promise<string> {
+  async getStreamStallConfig(): Promise<StreamStallConfig> {
     try {
-      return await invoke<string>("update_codex_reasoning_level", { level });
+      return await invoke<StreamStallConfig>("get_stream_stall_config");
     } catch (error) {
-      console.error("Failed to update Codex reasoning level:", error);
+      console.error("Failed to get stream stall config:", error);
       throw error;
     }
   },
 
-  // ============================================================================
-  // GEMINI PROVIDER MANAGEMENT
-  // ============================================================================
-
   /**
-   * Gets the list of Gemini provider presets
-   * @returns Promise resolving to array of Gemini provider configurations
+   * Updates the stalled-stream detection settings for Codex/Gemini runs
+   * @param config - The new settings
    */
-  async getGeminiProviderPresets(): Promise<GeminiProviderConfig[]> {
+  async updateStreamStallConfig(config: StreamStallConfig): Promise<void> {
     try {
-      return await invoke<GeminiProviderConfig[]>("get_gemini_provider_presets");
+      await invoke("update_stream_stall_config", { config });
     } catch (error) {
-      console.error("Failed to get Gemini provider presets:", error);
+      console.error("Failed to update stream stall config:", error);
       throw error;
     }
   },
 
   /**
-   * Gets the current Gemini provider configuration from ~/.gemini directory
-   * @returns Promise resolving to current Gemini configuration
+   * Gets the sleep prevention settings for Codex/Gemini runs
    */
-  async getCurrentGeminiProviderConfig(): Promise<CurrentGeminiProviderConfig> {
+  async getPowerInhibitConfig(): Promise<PowerInhibitConfig> {
     try {
-      return await invoke<CurrentGeminiProviderConfig>("get_current_gemini_provider_config");
+      return await invoke<PowerInhibitConfig>("get_power_inhibit_config");
     } catch (error) {
-      console.error("Failed to get current Gemini provider config:", error);
+      console.error("Failed to get power inhibit config:", error);
       throw error;
     }
   },
 
   /**
-   * Switches to a Gemini provider configuration
-   * Writes env to ~/.gemini/.env and updates settings.json
-   * @param config - The Gemini provider configuration to switch to
-   * @returns Promise resolving to success message
+   * Updates the sleep prevention settings; applies to runs already in progress
+   * @param config - The new settings
    */
-  async switchGeminiProvider(config: GeminiProviderConfig): Promise<string> {
+  async updatePowerInhibitConfig(config: PowerInhibitConfig): Promise<void> {
     try {
-      return await invoke<string>("switch_gemini_provider", { config });
+      await invoke("update_power_inhibit_config", { config });
     } catch (error) {
-      console.error("Failed to switch Gemini provider:", error);
+      console.error("Failed to update power inhibit config:", error);
       throw error;
     }
   },
 
   /**
-   * Adds a new Gemini provider configuration
-   * @param config - The Gemini provider configuration to add
-   * @returns Promise resolving to success message
+   * Gets whether system sleep is currently inhibited and by which runs
    */
-  async addGeminiProviderConfig(config: Omit<GeminiProviderConfig, 'id'>): Promise<string> {
-    // Generate ID from name
-    const id = config.name
-      .toLowerCase()
-      .replace(/[^a-z0-9]/g, '-')
-      .replace(/-+/g, '-')
-      .replace(/^-|-$/g, '');
-
-    const fullConfig: GeminiProviderConfig = {
-      ...config,
-      id,
-      createdAt: Date.now(),
-    };
-
+  async getPowerInhibitionStatus(): Promise<PowerInhibitionStatus> {
     try {
-      return await invoke<string>("add_gemini_provider_config", { config: fullConfig });
+      return await invoke<PowerInhibitionStatus>("get_power_inhibition_status");
     } catch (error) {
-      console.error("Failed to add Gemini provider config:", error);
+      console.error("Failed to get power inhibition status:", error);
       throw error;
     }
   },
 
   /**
-   * Updates an existing Gemini provider configuration
-   * @param config - The Gemini provider configuration to update (with id)
-   * @returns Promise resolving to success message
+   * Lists running Codex/Gemini tasks with live CPU/memory of their process trees
    */
-  async updateGeminiProviderConfig(config: GeminiProviderConfig): Promise<string> {
+  async listRunningAiTasks(): Promise<RunningAiTask[]> {
     try {
-      return await invoke<string>("update_gemini_provider_config", { config });
+      return await invoke<RunningAiTask[]>("list_running_ai_tasks");
     } catch (error) {
-      console.error("Failed to update Gemini provider config:", error);
+      console.error("Failed to list running AI tasks:", error);
       throw error;
     }
   },
 
   /**
-   * Deletes a Gemini provider configuration by ID
-   * @param id - The ID of the Gemini provider configuration to delete
-   * @returns Promise resolving to success message
+   * Terminates one descendant process of a running task (and its children) without cancelling the task
+   * @param pid - A process from the task's process tree, other than the CLI process itself
    */
-  async deleteGeminiProviderConfig(id: string): Promise<string> {
+  async killTaskSubprocess(pid: number): Promise<void> {
     try {
-      return await invoke<string>("delete_gemini_provider_config", { id });
+      await invoke("kill_task_subprocess", { pid });
     } catch (error) {
-      console.error("Failed to delete Gemini provider config:", error);
+      console.error("Failed to kill task subprocess:", error);
       throw error;
     }
   },
 
   /**
-   * Reorders Gemini provider configurations
-   * @param ids - Array of provider IDs in the desired order
-   * @returns Promise resolving to success message
+   * Gets the resource warning thresholds for running tasks
    */
-  async reorderGeminiProviderConfigs(ids: string[]): Promise<string> {
+  async getTaskResourceConfig(): Promise<TaskResourceConfig> {
     try {
-      return await invoke<string>("reorder_gemini_provider_configs", { ids });
+      return await invoke<TaskResourceConfig>("get_task_resource_config");
     } catch (error) {
-      console.error("Failed to reorder Gemini provider configs:", error);
+      console.error("Failed to get task resource config:", error);
       throw error;
     }
   },
 
   /**
-   * Clears Gemini provider configuration (resets to official OAuth)
-   * Clears .env and sets auth type to oauth-personal
-   * @returns Promise resolving to success message
+   * Updates the resource warning thresholds; applies from the next sample
+   * @param config - The new thresholds
    */
-  async clearGeminiProviderConfig(): Promise<string> {
+  async updateTaskResourceConfig(config: TaskResourceConfig): Promise<void> {
     try {
-      return await invoke<string>("clear_gemini_provider_config");
+      await invoke("update_task_resource_config", { config });
     } catch (error) {
-      console.error("Failed to clear Gemini provider config:", error);
+      console.error("Failed to update task resource config:", error);
       throw error;
     }
   },
 
   /**
-   * Tests Gemini provider connection
-   * @param baseUrl - The base URL to test
-   * @param apiKey - The API key to use for testing
-   * @returns Promise resolving to test result message
+   * Gets the scheduled backup settings
    */
-  async testGeminiProviderConnection(baseUrl: string, apiKey?: string): Promise<string> {
+  async getBackupConfig(): Promise<BackupConfig> {
     try {
-      return await invoke<string>("test_gemini_provider_connection", { baseUrl, apiKey });
+      return await invoke<BackupConfig>("get_backup_config");
     } catch (error) {
-      console.error("Failed to test Gemini provider connection:", error);
+      console.error("Failed to get backup config:", error);
       throw error;
     }
   },
 
-  // ============================================================================
-  // Session Conversion (Claude ↔ Codex)
-  // ============================================================================
+  /**
+   * Updates the scheduled backup settings
+   * @param config - The new settings
+   */
+  async updateBackupConfig(config: BackupConfig): Promise<void> {
+    try {
+      await invoke("update_backup_config", { config });
+    } catch (error) {
+      console.error("Failed to update backup config:", error);
+      throw error;
+    }
+  },
 
   /**
-   * Convert a session between Claude and Codex formats
-   * @param sessionId - The source session ID
-   * @param targetEngine - The target engine ('claude' | 'codex')
-   * @param projectId - The project ID (directory name)
-   * @param projectPath - The project path
-   * @returns Promise resolving to conversion result
+   * Runs a backup now; progress is reported through `backup-progress` events
    */
-  async convertSession(
-    sessionId: string,
-    targetEngine: 'claude' | 'codex',
-    projectId: string,
-    projectPath: string
-  ): Promise<ConversionResult> {
+  async backupNow(): Promise<BackupResult> {
     try {
-      return await invoke<ConversionResult>("convert_session", {
-        sessionId,
-        targetEngine,
-        projectId,
-        projectPath,
-      });
+      return await invoke<BackupResult>("backup_now");
     } catch (error) {
-      console.error("Failed to convert session:", error);
+      console.error("Failed to run backup:", error);
       throw error;
     }
   },
 
   /**
-   * Convert a Claude session to Codex format
-   * @param sessionId - The Claude session ID (UUID format)
-   * @param projectId - The project ID (directory name)
-   * @param projectPath - The project path
-   * @returns Promise resolving to conversion result
+   * Lists the backups in the target directory, newest first
    */
-  async convertClaudeToCodex(
-    sessionId: string,
-    projectId: string,
-    projectPath: string
-  ): Promise<ConversionResult> {
+  async listBackups(): Promise<BackupInfo[]> {
     try {
-      return await invoke<ConversionResult>("convert_claude_to_codex", {
-        sessionId,
-        projectId,
-        projectPath,
-      });
+      return await invoke<BackupInfo[]>("list_backups");
     } catch (error) {
-      console.error("Failed to convert Claude to Codex:", error);
+      console.error("Failed to list backups:", error);
       throw error;
     }
   },
 
   /**
-   * Convert a Codex session to Claude format
-   * @param sessionId - The Codex session ID (rollout-* format)
-   * @param projectId - The project ID (directory name)
-   * @param projectPath - The project path
-   * @returns Promise resolving to conversion result
+   * Restores the selected categories from a backup; by default only missing files are restored
+   * @param backupId - ID of the backup to restore from
+   * @param categories - Categories to restore
+   * @param overwrite - Also replace changed files; they are moved to a safety directory first
    */
-  async convertCodexToClaude(
-    sessionId: string,
-    projectId: string,
-    projectPath: string
-  ): Promise<ConversionResult> {
+  async restoreFromBackup(
+    backupId: string,
+    categories: BackupCategory[],
+    overwrite = false
+  ): Promise<RestoreResult> {
     try {
-      return await invoke<ConversionResult>("convert_codex_to_claude", {
-        sessionId,
-        projectId,
-        projectPath,
-      });
+      return await invoke<RestoreResult>("restore_from_backup", { backupId, categories, overwrite });
     } catch (error) {
-      console.error("Failed to convert Codex to Claude:", error);
+      console.error("Failed to restore from backup:", error);
       throw error;
     }
   },
 
-  // ==================== Google Gemini CLI Integration ====================
+  /**
+   * Gets the per-provider rate limits
+   */
+  async getProviderRateLimits(): Promise<RateLimitConfig> {
+    try {
+      return await invoke<RateLimitConfig>("get_provider_rate_limits");
+    } catch (error) {
+      console.error("Failed to get provider rate limits:", error);
+      throw error;
+    }
+  },
 
   /**
-   * Executes a Gemini CLI session with streaming output
-   * @param options - Gemini execution options
-   * @returns Promise resolving when execution starts (events are streamed via event listeners)
+   * Updates the per-provider rate limits; requests already in flight keep their slots
+   * @param config - The new limits
    */
-  async executeGemini(options: import('@/types/gemini').GeminiExecutionOptions): Promise<void> {
+  async updateProviderRateLimits(config: RateLimitConfig): Promise<void> {
     try {
-      return await invoke("execute_gemini", { options });
+      await invoke("update_provider_rate_limits", { config });
     } catch (error) {
-      console.error("Failed to execute Gemini:", error);
+      console.error("Failed to update provider rate limits:", error);
       throw error;
     }
   },
 
   /**
-   * Cancels a running Gemini execution
-   * @param sessionId - Optional session ID to cancel (cancels all if not provided)
+   * Gets the queue and request counts of every active provider limiter
    */
-  async cancelGemini(sessionId?: string): Promise<void> {
+  async getRateLimiterStatus(): Promise<ProviderLimiterStatus[]> {
     try {
-      await invoke("cancel_gemini", { sessionId });
+      return await invoke<ProviderLimiterStatus[]>("get_rate_limiter_status");
     } catch (error) {
-      console.error("Failed to cancel Gemini:", error);
+      console.error("Failed to get rate limiter status:", error);
       throw error;
     }
   },
 
   /**
-   * Checks if Gemini CLI is installed
-   * @returns Promise resolving to installation status
+   * Resolves a session ID to the latest ID continuing it (the ID itself when it never changed)
+   * @param engine - The engine the session belongs to
+   * @param sessionId - A possibly outdated session ID
    */
-  async checkGeminiInstalled(): Promise<import('@/types/gemini').GeminiInstallStatus> {
+  async resolveSessionId(engine: string, sessionId: string): Promise<string> {
     try {
-      return await invoke("check_gemini_installed");
+      return await invoke<string>("resolve_session_id", { engine, sessionId });
     } catch (error) {
-      console.error("Failed to check Gemini installation:", error);
-      return {
-        installed: false,
-        error: String(error),
-      };
+      console.error("Failed to resolve session ID:", error);
+      throw error;
     }
   },
 
   /**
-   * Gets Gemini CLI configuration
-   * @returns Promise resolving to Gemini configuration
+   * Gets whether trash/archive encryption is enabled and unlocked
    */
-  async getGeminiConfig(): Promise<import('@/types/gemini').GeminiConfig> {
+  async getEncryptedStorageStatus(): Promise<EncryptedStorageStatus> {
     try {
-      return await invoke("get_gemini_config");
+      return await invoke<EncryptedStorageStatus>("get_encrypted_storage_status");
     } catch (error) {
-      console.error("Failed to get Gemini config:", error);
+      console.error("Failed to get encrypted storage status:", error);
       throw error;
     }
   },
 
   /**
-   * Updates Gemini CLI configuration
-   * @param config - New configuration to apply
+   * Enables encryption of new trash/archive files and unlocks it
+   * @param passphrase - Sets the passphrase the first time; must match the existing one afterwards
    */
-  async updateGeminiConfig(config: import('@/types/gemini').GeminiConfig): Promise<void> {
+  async enableEncryptedStorage(passphrase: string): Promise<EncryptedStorageStatus> {
     try {
-      await invoke("update_gemini_config", { config });
+      return await invoke<EncryptedStorageStatus>("enable_encrypted_storage", { passphrase });
     } catch (error) {
-      console.error("Failed to update Gemini config:", error);
+      console.error("Failed to enable encrypted storage:", error);
       throw error;
     }
   },
 
   /**
-   * Gets available Gemini models
-   * @returns Promise resolving to array of model information
+   * Stops encrypting new files; already encrypted files keep needing the passphrase
    */
-  async getGeminiModels(): Promise<import('@/types/gemini').GeminiModelInfo[]> {
+  async disableEncryptedStorage(): Promise<EncryptedStorageStatus> {
     try {
-      return await invoke("get_gemini_models");
+      return await invoke<EncryptedStorageStatus>("disable_encrypted_storage");
     } catch (error) {
-      console.error("Failed to get Gemini models:", error);
+      console.error("Failed to disable encrypted storage:", error);
       throw error;
     }
   },
 
-  // ============================================================================
-  // Gemini Session History
-  // ============================================================================
+  /**
+   * Unlocks encrypted storage; the key stays in memory until the app exits
+   * @param passphrase - The storage passphrase
+   */
+  async unlockEncryptedStorage(passphrase: string): Promise<EncryptedStorageStatus> {
+    try {
+      return await invoke<EncryptedStorageStatus>("unlock_encrypted_storage", { passphrase });
+    } catch (error) {
+      console.error("Failed to unlock encrypted storage:", error);
+      throw error;
+    }
+  },
 
   /**
-   * Gets session logs for a project (from logs.json)
-   * @param projectPath - Project path to get session logs for
-   * @returns Promise resolving to array of session logs
+   * Encrypts trash files written before encryption was enabled
    */
-  async getGeminiSessionLogs(projectPath: string): Promise<import('@/types/gemini').GeminiSessionLog[]> {
+  async migrateExistingArchivesToEncrypted(): Promise<EncryptionMigrationReport> {
     try {
-      return await invoke("get_gemini_session_logs", { projectPath });
+      return await invoke<EncryptionMigrationReport>("migrate_existing_archives_to_encrypted");
     } catch (error) {
-      console.error("Failed to get Gemini session logs:", error);
+      console.error("Failed to encrypt existing archives:", error);
       throw error;
     }
   },
 
   /**
-   * Lists all sessions for a project (from chats/ directory)
-   * @param projectPath - Project path to list sessions for
-   * @returns Promise resolving to array of session info
+   * Gets the timeline of one execution: stages with durations, final status and commits
+   * @param traceId - The trace ID passed to (or generated by) the execution
    */
-  async listGeminiSessions(projectPath: string): Promise<import('@/types/gemini').GeminiSessionInfo[]> {
+  async getTraceTimeline(traceId: string): Promise<TraceTimeline> {
     try {
-      return await invoke("list_gemini_sessions", { projectPath });
+      return await invoke<TraceTimeline>("get_trace_timeline", { traceId });
     } catch (error) {
-      console.error("Failed to list Gemini sessions:", error);
+      console.error("Failed to get trace timeline:", error);
       throw error;
     }
   },
 
+  // ============================================================================
+  // Undo Stack
+  // ============================================================================
+
   /**
-   * Gets detailed session information
-   * @param projectPath - Project path
-   * @param sessionId - Session ID to get details for
-   * @returns Promise resolving to complete session detail
+   * Lists the reversible operations that can be undone, most recent first
    */
-  async getGeminiSessionDetail(
-    projectPath: string,
-    sessionId: string
-  ): Promise<import('@/types/gemini').GeminiSessionDetail> {
+  async getUndoStack(): Promise<UndoOperation[]> {
     try {
-      return await invoke("get_gemini_session_detail", { projectPath, sessionId });
+      return await invoke<UndoOperation[]>("get_undo_stack");
     } catch (error) {
-      console.error("Failed to get Gemini session detail:", error);
+      console.error("Failed to get undo stack:", error);
       throw error;
     }
   },
 
   /**
-   * Delete a Gemini session
-   * @param projectPath - Project path
-   * @param sessionId - Session ID to delete
+   * Undoes the most recent operation and returns its description
    */
-  async deleteGeminiSession(projectPath: string, sessionId: string): Promise<void> {
+  async undoLastOperation(): Promise<string> {
     try {
-      await invoke("delete_gemini_session", { projectPath, sessionId });
+      return await invoke<string>("undo_last_operation");
     } catch (error) {
-      console.error("Failed to delete Gemini session:", error);
+      console.error("Failed to undo last operation:", error);
       throw error;
     }
   },
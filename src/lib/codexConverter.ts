@@ -19,6 +19,7 @@ import type {
   CodexRateLimits,
 } from '@/types/codex';
 import type { ClaudeStreamMessage } from '@/types/claude';
+import { readDurationMs } from '@/lib/toolTiming';
 
 
 /**
@@ -69,6 +70,27 @@ function mapCodexToolName(codexName: string): string {
   return CODEX_TOOL_NAME_MAP[lowerName] || codexName;
 }
 
+/**
+ * Checks whether a user message is a context block injected by Codex
+ * (environment_context / AGENTS.md instructions), by structure rather than substring.
+ * Must stay in sync with `is_codex_context_message` in the backend.
+ */
+function isCodexContextMessage(text: string): boolean {
+  const trimmed = text.trim();
+  const isWrapped = (tag: string) =>
+    trimmed.startsWith(`<${tag}>`) && trimmed.endsWith(`</${tag}>`);
+  if (isWrapped('environment_context') || isWrapped('user_instructions')) {
+    return true;
+  }
+
+  const newlineIndex = trimmed.indexOf('\n');
+  const header = (newlineIndex === -1 ? trimmed : trimmed.slice(0, newlineIndex)).trimEnd();
+  const body = newlineIndex === -1 ? '' : trimmed.slice(newlineIndex + 1).trim();
+  return header.startsWith('# AGENTS.md instructions for ')
+    && body.startsWith('<INSTRUCTIONS>')
+    && body.endsWith('</INSTRUCTIONS>');
+}
+
 function parseCodexRateLimitEntry(raw: any, fallbackWindowMinutes: number): CodexRateLimit | undefined {
   if (!raw || typeof raw !== 'object') {
     return undefined;
@@ -519,10 +541,7 @@ export class CodexEventConverter {
     // Filter out system environment context messages from user
     if (payload.role === 'user' && payload.content) {
       const isEnvContext = payload.content.some((c: any) =>
-        c.type === 'input_text' && c.text && (
-          c.text.includes('<environment_context>') ||
-          c.text.includes('# AGENTS.md instructions')
-        )
+        c.type === 'input_text' && c.text && isCodexContextMessage(c.text)
       );
 
       if (isEnvContext) {
@@ -600,6 +619,7 @@ export class CodexEventConverter {
             id: callId,
             name: toolName,
             input: normalizedInput,
+            duration_ms: readDurationMs(payload),
           },
         ],
       },
@@ -721,6 +741,7 @@ export class CodexEventConverter {
             id: callId,
             name: toolName,
             input: normalizedInput,
+            duration_ms: readDurationMs(payload),
           },
         ],
       },
@@ -944,6 +965,7 @@ export class CodexEventConverter {
         },
       ],
       is_error: item.status === 'failed',
+      duration_ms: readDurationMs(item),
     };
 
     return {
@@ -1055,6 +1077,9 @@ export class CodexEventConverter {
     // Extract tool name from Codex format: server.tool or just tool
     const toolName = item.server ? `mcp__${item.server}__${item.tool}` : (item.tool || item.tool_name);
 
+    // Duration is measured by the backend (item.started → item.completed)
+    const durationMs = readDurationMs(item);
+
     // Always create a complete message with both tool_use and tool_result
     {
     // Extract actual result content from nested structure
@@ -1092,6 +1117,7 @@ export class CodexEventConverter {
             tool_use_id: toolUseId,
             content: [{ type: 'text', text: resultText }],
             is_error: item.status === 'failed' || item.error !== null,
+            duration_ms: durationMs,
           }
         ]
       },
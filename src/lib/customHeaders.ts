@@ -0,0 +1,41 @@
+/**
+ * Provider custom HTTP headers helpers
+ *
+ * 表单中以每行一个 "Name: value" 的文本编辑，提交时解析为对象
+ */
+
+/** header 名允许的 token 字符集（与后端校验一致） */
+const HEADER_NAME_PATTERN = /^[!#$%&'*+\-.^_`|~0-9A-Za-z]+$/;
+
+/**
+ * 将多行 "Name: value" 文本解析为 header 对象
+ * @returns 解析结果；存在非法行时返回 error（行号从 1 开始）
+ */
+export function parseCustomHeaders(text: string): { headers?: Record<string, string>; error?: string } {
+  const headers: Record<string, string> = {};
+  const lines = text.split('\n');
+
+  for (let i = 0; i < lines.length; i++) {
+    const line = lines[i].trim();
+    if (!line) continue;
+
+    const sep = line.indexOf(':');
+    const name = sep > 0 ? line.slice(0, sep).trim() : '';
+    if (!name || !HEADER_NAME_PATTERN.test(name)) {
+      return { error: `line ${i + 1}: ${line}` };
+    }
+    headers[name] = line.slice(sep + 1).trim();
+  }
+
+  return { headers: Object.keys(headers).length > 0 ? headers : undefined };
+}
+
+/**
+ * 将 header 对象格式化为多行 "Name: value" 文本
+ */
+export function formatCustomHeaders(headers?: Record<string, string>): string {
+  if (!headers) return '';
+  return Object.entries(headers)
+    .map(([name, value]) => `${name}: ${value}`)
+    .join('\n');
+}
@@ -0,0 +1,122 @@
+/**
+ * 发送时增强开关
+ *
+ * 开启后三引擎执行命令会带上 enhancement，由后端在执行前用 acemcp 项目上下文增强提示词，
+ * 一次调用完成"增强并执行"，增强元数据可通过 getPromptEnhancementInfo 查询。
+ * 链接预抓取开关独立于增强开关，开启后由后端抓取提示词中的 URL 并附加网页内容。
+ * 文件快照默认开启：提示词中有 @ 引用文件时，后端在执行前为这些文件拍内容快照
+ */
+
+import type { EnhancementRequest } from '@/lib/api';
+
+const STORAGE_KEY = 'enhance_on_send';
+const PREFETCH_URLS_KEY = 'prefetch_urls_on_send';
+const FILE_SNAPSHOTS_DISABLED_KEY = 'file_snapshots_disabled';
+
+// 与后端 prompt_file_snapshots::extract_file_references 保持一致
+const FILE_REFERENCE_RE = /(?:^|\s)@(?:"([^"\n]+)"|([^\s"'`,;()[\]{}<>，。；、]+))/g;
+
+function readFlag(key: string): boolean {
+  try {
+    return localStorage.getItem(key) === 'true';
+  } catch {
+    return false;
+  }
+}
+
+function writeFlag(key: string, enabled: boolean): void {
+  try {
+    if (enabled) {
+      localStorage.setItem(key, 'true');
+    } else {
+      localStorage.removeItem(key);
+    }
+  } catch {
+    // Ignore localStorage errors
+  }
+}
+
+/**
+ * 是否在发送时自动增强提示词
+ */
+export function isEnhanceOnSendEnabled(): boolean {
+  return readFlag(STORAGE_KEY);
+}
+
+/**
+ * 设置是否在发送时自动增强提示词
+ */
+export function setEnhanceOnSendEnabled(enabled: boolean): void {
+  writeFlag(STORAGE_KEY, enabled);
+}
+
+/**
+ * 是否在发送时预抓取提示词中的链接
+ */
+export function isUrlPrefetchEnabled(): boolean {
+  return readFlag(PREFETCH_URLS_KEY);
+}
+
+/**
+ * 设置是否在发送时预抓取提示词中的链接
+ */
+export function setUrlPrefetchEnabled(enabled: boolean): void {
+  writeFlag(PREFETCH_URLS_KEY, enabled);
+}
+
+/**
+ * 是否在发送时为 @ 引用的文件拍内容快照（默认开启）
+ */
+export function isFileSnapshotEnabled(): boolean {
+  return !readFlag(FILE_SNAPSHOTS_DISABLED_KEY);
+}
+
+/**
+ * 设置是否在发送时为 @ 引用的文件拍内容快照
+ */
+export function setFileSnapshotEnabled(enabled: boolean): void {
+  writeFlag(FILE_SNAPSHOTS_DISABLED_KEY, !enabled);
+}
+
+/**
+ * 提取提示词中 @ 引用的文件路径（支持 @"带空格的路径"），按出现顺序去重
+ */
+export function extractFileReferences(prompt: string): string[] {
+  const references = new Set<string>();
+  for (const match of prompt.matchAll(FILE_REFERENCE_RE)) {
+    const reference = (match[1] ?? match[2]).replace(/[.:!?！？]+$/, '');
+    if (reference) {
+      references.add(reference);
+    }
+  }
+  return [...references];
+}
+
+/**
+ * 开启发送时增强、链接预抓取或有需要快照的 @ 引用时构造执行选项中的 enhancement，否则返回 undefined
+ * @param sessionId - Claude 会话 ID，用于结合对话历史生成检索查询
+ * @param projectId - Claude 项目 ID
+ * @param fileReferences - 提示词中 @ 引用的文件路径
+ */
+export function buildEnhancementRequest(
+  sessionId?: string,
+  projectId?: string,
+  fileReferences: string[] = []
+): EnhancementRequest | undefined {
+  const projectContext = isEnhanceOnSendEnabled();
+  const fetchUrls = isUrlPrefetchEnabled();
+  const fileSnapshots = isFileSnapshotEnabled() && fileReferences.length > 0;
+  if (!projectContext && !fetchUrls && !fileSnapshots) {
+    return undefined;
+  }
+  return {
+    sessionId,
+    projectId,
+    enableMultiRound: true,
+    failOpen: true,
+    projectContext,
+    fetchUrls,
+    fileSnapshots,
+    fileReferences: fileSnapshots ? fileReferences : undefined,
+  };
+}
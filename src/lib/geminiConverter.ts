@@ -1,5 +1,6 @@
 import type { ClaudeStreamMessage } from '@/types/claude';
 import type { GeminiSessionDetail } from '@/types/gemini';
+import { readDurationMs } from '@/lib/toolTiming';
 
 type GeminiUsage = {
   input_tokens: number;
@@ -115,6 +116,7 @@ export function convertGeminiSessionDetailToClaudeMessages(
           id: toolCall.id,
           name: toolCall.name,
           input: toolCall.args,
+          duration_ms: readDurationMs(toolCall),
         });
 
         // If there's a result, add it as a separate user message (tool_result)
@@ -0,0 +1,92 @@
+/**
+ * MCP 工具调用解析
+ *
+ * Claude 与 Codex 的消息都会先转换为 ClaudeStreamMessage，MCP 调用在这一层统一处理：
+ * - 从 mcp__server__tool 拆出 server 与工具名（与后端 parse_mcp_tool_name 规则相同）
+ * - 按 tool_use_id 找到 tool_result，读取 is_error
+ * - 耗时只读取后端写入的 duration_ms（见 tool_timing.rs），前端不再自行推算
+ *
+ * 解析结果以 `mcp` 字段附加在 tool_use 块上，历史加载与流式输出都经过这里
+ */
+
+import type { ClaudeStreamMessage } from '@/types/claude';
+import { readDurationMs } from '@/lib/toolTiming';
+
+const MCP_TOOL_PREFIX = 'mcp__';
+
+/**
+ * 附加在 MCP tool_use 块上的解析字段
+ */
+export interface McpCallInfo {
+  server: string;
+  tool: string;
+  /** 调用耗时，结果尚未返回时为 undefined */
+  durationMs?: number;
+  /** 结果尚未返回时为 undefined */
+  isError?: boolean;
+}
+
+interface McpToolResult {
+  isError: boolean;
+  /** 结果块上的耗时（流式执行时由后端写入） */
+  durationMs?: number;
+}
+
+/**
+ * 拆分 mcp__server__tool 形式的工具名
+ *
+ * 以第一个 `__` 分隔 server 与工具名，工具名中的 `__` 原样保留
+ */
+export function parseMcpToolName(name: string | undefined): { server: string; tool: string } | null {
+  if (!name || !name.startsWith(MCP_TOOL_PREFIX)) return null;
+
+  const rest = name.slice(MCP_TOOL_PREFIX.length);
+  const separator = rest.indexOf('__');
+  if (separator <= 0 || separator + 2 >= rest.length) return null;
+
+  return { server: rest.slice(0, separator), tool: rest.slice(separator + 2) };
+}
+
+const contentBlocks = (message: ClaudeStreamMessage): any[] =>
+  Array.isArray(message.message?.content) ? message.message!.content! : [];
+
+/**
+ * 为 MCP tool_use 块附加 server / 工具名 / 耗时 / is_error
+ *
+ * 只替换包含 MCP 调用的消息，其余消息保持原引用
+ */
+export function annotateMcpToolCalls(messages: ClaudeStreamMessage[]): ClaudeStreamMessage[] {
+  const results = new Map<string, McpToolResult>();
+  for (const message of messages) {
+    for (const block of contentBlocks(message)) {
+      if (block?.type === 'tool_result' && block.tool_use_id) {
+        results.set(block.tool_use_id, {
+          isError: block.is_error === true,
+          durationMs: readDurationMs(block),
+        });
+      }
+    }
+  }
+
+  return messages.map((message) => {
+    const content = contentBlocks(message);
+    if (!content.some((block) => block?.type === 'tool_use' && parseMcpToolName(block.name))) {
+      return message;
+    }
+
+    const annotated = content.map((block) => {
+      const parsed = block?.type === 'tool_use' ? parseMcpToolName(block.name) : null;
+      if (!parsed) return block;
+
+      const mcp: McpCallInfo = { ...parsed };
+      const result = results.get(block.id);
+      if (result) {
+        mcp.isError = result.isError;
+        mcp.durationMs = readDurationMs(block) ?? result.durationMs;
+      }
+      return { ...block, mcp };
+    });
+
+    return { ...message, message: { ...message.message, content: annotated } };
+  });
+}
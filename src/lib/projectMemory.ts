@@ -0,0 +1,34 @@
+/**
+ * 项目记忆开关
+ *
+ * 是否在执行时注入项目记忆按项目保存在 localStorage 中，
+ * 开启后三引擎执行命令会带上 useProjectMemory，由后端编译并注入记忆
+ */
+
+const STORAGE_KEY_PREFIX = 'use_project_memory:';
+
+/**
+ * 项目是否开启了执行时注入项目记忆
+ */
+export function isProjectMemoryEnabled(projectPath: string): boolean {
+  try {
+    return localStorage.getItem(STORAGE_KEY_PREFIX + projectPath) === 'true';
+  } catch {
+    return false;
+  }
+}
+
+/**
+ * 设置项目是否在执行时注入项目记忆
+ */
+export function setProjectMemoryEnabled(projectPath: string, enabled: boolean): void {
+  try {
+    if (enabled) {
+      localStorage.setItem(STORAGE_KEY_PREFIX + projectPath, 'true');
+    } else {
+      localStorage.removeItem(STORAGE_KEY_PREFIX + projectPath);
+    }
+  } catch {
+    // Ignore localStorage errors
+  }
+}
@@ -0,0 +1,89 @@
+/**
+ * Session Blob Utilities
+ * 处理会话中外置存储的工具结果：超过阈值的 tool_result 保存在会话旁的 attachments/<session>/<hash>.blob，
+ * 消息中只保留字符串占位："<预览>\n\n[anycode-blob blob://<hash> <字节数> bytes]"
+ */
+
+import { api, type BlobRef, type Session } from '@/lib/api';
+import type { ClaudeStreamMessage } from '@/types/claude';
+
+type Engine = 'claude' | 'codex' | 'gemini';
+
+const BLOB_PLACEHOLDER = /^([\s\S]*)\n\n\[anycode-blob (blob:\/\/[0-9a-f]{64}) (\d+) bytes\]$/;
+
+/**
+ * 解析外置工具结果的占位字符串，不是占位时返回 undefined
+ */
+export function parseBlobRef(value: unknown): BlobRef | undefined {
+  if (typeof value !== 'string') return undefined;
+  const match = BLOB_PLACEHOLDER.exec(value);
+  return match ? { $ref: match[2], size: Number(match[3]), preview: match[1] } : undefined;
+}
+
+/**
+ * 格式化 blob 大小
+ */
+export const formatBlobSize = (bytes: number) =>
+  bytes >= 1024 * 1024 ? `${(bytes / 1024 / 1024).toFixed(1)} MB` : `${Math.ceil(bytes / 1024)} KB`;
+
+/**
+ * 取回占位对应的工具结果全文
+ * @param projectIdOrPath - Claude 传项目 ID，Codex 忽略
+ */
+export function loadMessageBlob(
+  blobRef: BlobRef,
+  engine: Engine,
+  sessionId: string,
+  projectIdOrPath: string
+): Promise<any> {
+  return api.getMessageBlob(engine, sessionId, projectIdOrPath, blobRef.$ref);
+}
+
+/**
+ * 把消息中的所有占位替换为全文（导出时使用），取回失败的占位保持原样
+ * 没有会话信息或会话为 Gemini 时原样返回
+ */
+export async function inlineSessionBlobs(
+  messages: ClaudeStreamMessage[],
+  session?: Session
+): Promise<ClaudeStreamMessage[]> {
+  const engine = session?.engine ?? 'claude';
+  if (!session?.id || engine === 'gemini') {
+    return messages;
+  }
+  const projectIdOrPath = engine === 'claude' ? session.project_id : session.project_path;
+  const cache = new Map<string, Promise<any>>();
+
+  const resolve = async (value: any): Promise<any> => {
+    const blobRef = parseBlobRef(value);
+    if (blobRef) {
+      if (!cache.has(blobRef.$ref)) {
+        cache.set(
+          blobRef.$ref,
+          loadMessageBlob(blobRef, engine, session.id, projectIdOrPath).catch((error) => {
+            console.warn('Failed to inline session blob:', blobRef.$ref, error);
+            return value;
+          })
+        );
+      }
+      return cache.get(blobRef.$ref);
+    }
+    if (Array.isArray(value)) {
+      return Promise.all(value.map(resolve));
+    }
+    if (typeof value === 'object' && value !== null) {
+      const entries = await Promise.all(
+        Object.entries(value).map(async ([key, item]) => [key, await resolve(item)] as const)
+      );
+      return Object.fromEntries(entries);
+    }
+    return value;
+  };
+
+  const hasBlobRef = (value: any): boolean =>
+    parseBlobRef(value) !== undefined ||
+    (Array.isArray(value) ? value.some(hasBlobRef) :
+      typeof value === 'object' && value !== null && Object.values(value).some(hasBlobRef));
+
+  return Promise.all(messages.map((msg) => (hasBlobRef(msg) ? resolve(msg) : msg)));
+}
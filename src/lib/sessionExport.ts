@@ -8,6 +8,7 @@ import { writeTextFile } from '@tauri-apps/plugin-fs';
 import type { ClaudeStreamMessage } from '@/types/claude';
 import type { Session } from '@/lib/api';
 import { clipboardService } from '@/lib/clipboard';
+import { inlineSessionBlobs } from '@/lib/sessionBlobs';
 
 /**
  * 导出格式类型
@@ -249,13 +250,19 @@ export function generateExportFilename(session: Session | undefined, format: Exp
 
 /**
  * 导出会话记录（完整流程：生成内容 + 用户选择保存路径）
+ * @param options.inlineBlobs - 把外置存储的工具结果还原为全文（默认开启）
  * @returns 保存的文件路径，如果用户取消则返回 null
  */
 export async function exportSession(
   messages: ClaudeStreamMessage[],
   format: ExportFormat,
-  session?: Session
+  session?: Session,
+  options: { inlineBlobs?: boolean } = {}
 ): Promise<string | null> {
+  if (options.inlineBlobs ?? true) {
+    messages = await inlineSessionBlobs(messages, session);
+  }
+
   let content: string;
   let filters: { name: string; extensions: string[] }[];
 
@@ -6,6 +6,7 @@
  */
 
 import { FC } from 'react';
+import type { McpCallInfo } from './mcpToolCalls';
 
 /**
  * 工具渲染 Props 统一接口
@@ -26,6 +27,12 @@ export interface ToolRenderProps {
   /** 工具唯一 ID */
   toolId?: string;
 
+  /** 子代理会话文件（Task 工具，由后端按 tool_use_id 关联） */
+  agentFile?: string;
+
+  /** MCP 调用的解析字段（server / 工具名 / 耗时） */
+  mcp?: McpCallInfo;
+
   /** 可选的回调函数 */
   onLinkDetected?: (url: string) => void;
 
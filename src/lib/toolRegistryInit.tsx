@@ -543,6 +543,7 @@ export function initializeToolRegistry(): void {
         toolName: props.toolName,
         input: props.input,
         result: props.result,
+        mcp: props.mcp,
       })),
       description: 'Model Context Protocol 工具（通用）',
     },
@@ -555,6 +556,7 @@ export function initializeToolRegistry(): void {
         prompt: props.input?.prompt ?? props.result?.content?.prompt,
         result: props.result,
         subagentType: props.input?.subagent_type ?? props.result?.content?.subagent_type,
+        agentFile: props.agentFile,
       })),
       description: 'Claude Code 子代理工具',
     },
@@ -0,0 +1,23 @@
+/**
+ * 工具调用耗时的展示辅助
+ *
+ * 耗时由后端写入工具块的 `duration_ms`（历史加载时按时间戳推算，流式执行时实时计时）
+ */
+
+/** 超过该耗时的工具调用视为慢操作 */
+export const SLOW_TOOL_THRESHOLD_MS = 30_000;
+
+/** 读取工具块上的耗时字段 */
+export function readDurationMs(block: any): number | undefined {
+  const value = block?.duration_ms;
+  return typeof value === 'number' && Number.isFinite(value) && value >= 0 ? value : undefined;
+}
+
+/** 格式化耗时：850ms / 4.2s / 3m 12s */
+export function formatToolDuration(ms: number): string {
+  if (ms < 1000) return `${Math.round(ms)}ms`;
+  if (ms < 60_000) return `${(ms / 1000).toFixed(1)}s`;
+  const minutes = Math.floor(ms / 60_000);
+  const seconds = Math.round((ms % 60_000) / 1000);
+  return seconds > 0 ? `${minutes}m ${seconds}s` : `${minutes}m`;
+}
@@ -38,6 +38,7 @@ export interface ClaudeStreamMessage {
     toolId?: string;
     status?: string;
     exitCode?: number;
+    citations?: import('./gemini').GeminiGroundingCitation[];
     raw?: unknown;
   };
   // Execution engine identifier
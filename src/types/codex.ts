@@ -130,6 +130,16 @@ export type CodexItem =
  */
 export type CodexExecutionMode = 'read-only' | 'full-auto' | 'danger-full-access';
 
+/**
+ * Values accepted for `model_reasoning_effort`
+ */
+export type CodexReasoningEffort = 'minimal' | 'low' | 'medium' | 'high' | 'xhigh';
+
+/**
+ * Values accepted for `model_verbosity`
+ */
+export type CodexVerbosity = 'low' | 'medium' | 'high';
+
 /**
  * Codex execution options
  */
@@ -146,6 +156,18 @@ export interface CodexExecutionOptions {
   /** Model to use (e.g., gpt-5.1-codex-max) */
   model?: string;
 
+  /** Named profile from config.toml; its model and approval settings take precedence */
+  profile?: string;
+
+  /** Reasoning effort passed as `-c model_reasoning_effort=<value>` */
+  reasoningEffort?: CodexReasoningEffort;
+
+  /** Output verbosity passed as `-c model_verbosity=<value>` */
+  verbosity?: CodexVerbosity;
+
+  /** Refuse to run when the installed CLI does not support a requested override */
+  strictConfig?: boolean;
+
   /** Enable JSON output mode */
   json?: boolean;
 
@@ -166,12 +188,51 @@ export interface CodexExecutionOptions {
 
   /** Resume last session */
   resumeLast?: boolean;
+
+  /** Check the estimated context against the model window before resuming */
+  checkContextOverflow?: boolean;
+
+  /** Resume even if the context check predicts an overflow */
+  force?: boolean;
+
+  /** Append the compiled project memory to the prompt */
+  useProjectMemory?: boolean;
+
+  /** Trace ID of the user action that started this run */
+  traceId?: string;
+
+  /** Enhance the prompt with acemcp project context before running */
+  enhancement?: import('@/lib/api').EnhancementRequest;
+}
+
+/**
+ * Prefix of the error returned when resuming would overflow the context window
+ */
+export const CODEX_CONTEXT_OVERFLOW_ERROR_PREFIX = 'CONTEXT_OVERFLOW';
+
+/**
+ * Estimated token footprint of a Codex session
+ */
+export interface CodexContextEstimate {
+  sessionTokens: number;
+  promptTokens: number;
+  totalTokens: number;
+  model?: string;
+  contextWindow?: number;
+  /** "usage" (from token_count events) or "heuristic" */
+  source: string;
+  overflow: boolean;
 }
 
 // ============================================================================
 // Session Management
 // ============================================================================
 
+/**
+ * Source of a Codex session's firstMessage (fallback level)
+ */
+export type CodexFirstMessageSource = 'user' | 'assistant' | 'session_meta' | 'placeholder';
+
 /**
  * Codex session metadata
  */
@@ -197,13 +258,32 @@ export interface CodexSession {
   /** Session status */
   status: 'active' | 'completed' | 'failed';
 
-  /** 🆕 First user message */
+  /** 🆕 First user message (or a fallback summary, see firstMessageSource) */
   firstMessage?: string;
 
+  /** Where firstMessage came from: user input, first AI reply, session_meta, or a placeholder */
+  firstMessageSource?: CodexFirstMessageSource;
+
   /** 🆕 Last message timestamp (ISO string) */
   lastMessageTimestamp?: string;
 }
 
+/**
+ * Pagination and filters for listing Codex sessions
+ */
+export interface CodexSessionQuery {
+  /** Zero-based page index */
+  page?: number;
+  /** Sessions per page; 0 or omitted returns all matches */
+  pageSize?: number;
+  /** Only sessions whose working directory matches this path */
+  projectPathFilter?: string;
+  /** Inclusive start date (YYYY-MM-DD) */
+  dateFrom?: string;
+  /** Inclusive end date (YYYY-MM-DD) */
+  dateTo?: string;
+}
+
 // ============================================================================
 // Message Conversion (Codex → ClaudeStreamMessage)
 // ============================================================================
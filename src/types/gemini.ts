@@ -15,6 +15,18 @@ export interface GeminiConfig {
   apiKey?: string;
   googleCloudProject?: string;
   env?: Record<string, string>;
+  sessionRetention?: GeminiSessionRetention;
+  /** Read-only: fields of ~/.gemini/settings.json not managed by the app (never written back) */
+  unmanagedSettings?: Record<string, unknown>;
+}
+
+/**
+ * Gemini session retention policy (a session is kept if any rule keeps it)
+ */
+export interface GeminiSessionRetention {
+  retentionDays?: number;
+  maxSessions?: number;
+  cleanupOnStartup?: boolean;
 }
 
 /**
@@ -26,6 +38,17 @@ export interface GeminiModelInfo {
   description: string;
   contextWindow: number;
   isDefault: boolean;
+  /** Built-in tool support; absent for models the app does not know */
+  capabilities?: GeminiModelCapabilities | null;
+}
+
+/**
+ * Built-in tools and input types a Gemini model supports
+ */
+export interface GeminiModelCapabilities {
+  searchGrounding: boolean;
+  codeExecution: boolean;
+  multimodal: boolean;
 }
 
 /**
@@ -39,6 +62,27 @@ export interface GeminiExecutionOptions {
   includeDirectories?: string[];
   sessionId?: string;
   debug?: boolean;
+  /** Append the compiled project memory to the prompt */
+  useProjectMemory?: boolean;
+  /** Trace ID of the user action that started this run */
+  traceId?: string;
+  /** Enhance the prompt with acemcp project context before running */
+  enhancement?: import('@/lib/api').EnhancementRequest;
+  /** Context file control; falls back to the saved project defaults */
+  contextFiles?: GeminiContextFiles;
+  /** Google Search switch: omitted keeps the CLI default, false excludes the tool,
+   *  true is rejected for models without support */
+  enableSearchGrounding?: boolean;
+}
+
+/**
+ * Gemini context file control for a run
+ */
+export interface GeminiContextFiles {
+  /** Load the default GEMINI.md hierarchy */
+  includeDefaultContext: boolean;
+  /** Extra files inside the workspace to add as context */
+  extraContextFiles: string[];
 }
 
 /**
@@ -89,9 +133,21 @@ export interface GeminiMetadata {
   toolId?: string;
   status?: string;
   exitCode?: number;
+  /** Sources cited by a Google Search grounded tool result */
+  citations?: GeminiGroundingCitation[];
   raw?: unknown;
 }
 
+/**
+ * A source cited by a Google Search grounded answer
+ */
+export interface GeminiGroundingCitation {
+  /** Number used by the [n] markers in the answer */
+  index: number;
+  title: string;
+  url: string;
+}
+
 /**
  * Gemini stream event types
  */
@@ -268,5 +324,53 @@ export interface GeminiSessionInfo {
   sessionId: string;
   fileName: string;
   startTime: string;
+  /** Time of the last message */
+  lastUpdated: string;
+  messageCount: number;
   firstMessage?: string;
 }
+
+/**
+ * User marks on a Gemini session; marked sessions are never cleaned up
+ */
+export interface GeminiSessionMark {
+  favorite?: boolean;
+  tags?: string[];
+  readOnly?: boolean;
+}
+
+/**
+ * Session selected by the retention cleanup
+ */
+export interface GeminiCleanupEntry {
+  sessionId: string;
+  projectHash: string;
+  fileName: string;
+  /** Last activity, UTC Unix seconds */
+  lastActive: number;
+  sizeBytes: number;
+  hasGitRecords: boolean;
+}
+
+/**
+ * Result of a session cleanup run (or its preview for dry runs)
+ */
+export interface GeminiCleanupReport {
+  dryRun: boolean;
+  sessions: GeminiCleanupEntry[];
+  freedBytes: number;
+  skippedProtected: number;
+  skippedRunning: number;
+  trashDir?: string;
+  errors: string[];
+}
+
+/**
+ * A session in the trash, listed from the plaintext index of its cleanup run
+ */
+export interface GeminiTrashEntry extends GeminiCleanupEntry {
+  /** Directory of the cleanup run that moved the session */
+  trashDir: string;
+  /** Restoring an encrypted session needs the encrypted storage unlocked */
+  encrypted: boolean;
+}